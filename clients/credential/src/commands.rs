@@ -6,7 +6,11 @@ use log::*;
 use nym_bandwidth_controller::acquire::state::State;
 use nym_bin_common::completions::ArgShell;
 use nym_credential_storage::persistent_storage::PersistentStorage;
+use nym_credentials::coconut::bandwidth::default_expiration_date;
 use nym_validator_client::nyxd::traits::DkgQueryClient;
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
 
 use crate::error::Result;
 use crate::recovery_storage::RecoveryStorage;
@@ -52,13 +56,72 @@ pub(crate) struct Run {
     pub(crate) recovery_mode: bool,
 }
 
+/// Outcome of the `run` command, printed in the requested output format.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct RunOutput {
+    /// Hash of the transaction of the new deposit, if one was made.
+    pub(crate) deposit_tx_hash: Option<String>,
+
+    /// Hashes of the deposits that got converted into credentials.
+    pub(crate) obtained_credentials: Vec<String>,
+
+    /// Hashes of the deposits that couldn't be converted into credentials and have to be recovered.
+    pub(crate) pending_recovery: Vec<String>,
+
+    /// Path to the recovery data dumped for the new deposit, if it couldn't be converted.
+    pub(crate) recovery_file: Option<PathBuf>,
+}
+
+impl Display for RunOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(deposit_tx_hash) = &self.deposit_tx_hash {
+            writeln!(f, "deposit transaction: {deposit_tx_hash}")?;
+        }
+        writeln!(
+            f,
+            "obtained credentials: {}",
+            self.obtained_credentials.len()
+        )?;
+        for tx_hash in &self.obtained_credentials {
+            writeln!(f, "\t{tx_hash}")?;
+        }
+        write!(
+            f,
+            "deposits pending recovery: {}",
+            self.pending_recovery.len()
+        )?;
+        for tx_hash in &self.pending_recovery {
+            write!(f, "\n\t{tx_hash}")?;
+        }
+        if let Some(recovery_file) = &self.recovery_file {
+            write!(f, "\nrecovery data: {}", recovery_file.display())?;
+        }
+        Ok(())
+    }
+}
+
 pub(crate) async fn recover_credentials<C: DkgQueryClient + Send + Sync>(
     client: &C,
     recovery_storage: &RecoveryStorage,
     shared_storage: &PersistentStorage,
+    output: &mut RunOutput,
 ) -> Result<()> {
     for voucher in recovery_storage.unconsumed_vouchers()? {
-        let state = State::new(voucher);
+        let mut state = State::new(voucher);
+        if state.voucher.get_expiration_date().is_none() {
+            // the vouchers dumped before the introduction of the expiration date get it assigned,
+            // as otherwise they could no longer be converted into credentials
+            info!(
+                "Assigning the expiration date to the legacy deposit {}",
+                state.voucher.tx_hash()
+            );
+            state.voucher = state
+                .voucher
+                .with_expiration_date(&state.params, default_expiration_date());
+            if let Err(e) = recovery_storage.insert_voucher(&state.voucher) {
+                warn!("Could not update recovery data - {:?}", e);
+            }
+        }
         if let Err(e) =
             nym_bandwidth_controller::acquire::get_credential(&state, client, shared_storage).await
         {
@@ -66,8 +129,14 @@ pub(crate) async fn recover_credentials<C: DkgQueryClient + Send + Sync>(
                 "Could not recover deposit {} due to {:?}, try again later",
                 state.voucher.tx_hash(),
                 e
-            )
+            );
+            output
+                .pending_recovery
+                .push(state.voucher.tx_hash().to_string());
         } else {
+            output
+                .obtained_credentials
+                .push(state.voucher.tx_hash().to_string());
             info!(
                 "Converted deposit {} to a credential, removing recovery data for it",
                 state.voucher.tx_hash()
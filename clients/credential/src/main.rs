@@ -9,6 +9,7 @@ use commands::*;
 use error::Result;
 use log::*;
 use nym_bin_common::completions::fig_generate;
+use nym_bin_common::output_format::OutputFormat;
 use nym_config::{CRED_DB_FILE_NAME, DATA_DIR};
 use nym_network_defaults::{setup_env, NymNetworkDetails};
 use std::process::exit;
@@ -29,6 +30,10 @@ struct Cli {
     #[clap(short, long)]
     pub(crate) config_env_file: Option<std::path::PathBuf>,
 
+    /// Format of the results printed by the command.
+    #[clap(short, long, global = true, default_value_t = OutputFormat::default())]
+    pub(crate) output: OutputFormat,
+
     #[clap(subcommand)]
     pub(crate) command: Command,
 }
@@ -93,9 +98,12 @@ async fn main() -> Result<()> {
             block_until_coconut_is_available(&client).await?;
             info!("Starting depositing funds, don't kill the process");
 
+            let mut output = RunOutput::default();
             if !r.recovery_mode {
                 let state =
                     nym_bandwidth_controller::acquire::deposit(&client.nyxd, amount).await?;
+                let tx_hash = state.voucher.tx_hash().to_string();
+                output.deposit_tx_hash = Some(tx_hash.clone());
                 if nym_bandwidth_controller::acquire::get_credential(
                     &state,
                     &client,
@@ -105,18 +113,30 @@ async fn main() -> Result<()> {
                 .is_err()
                 {
                     warn!("Failed to obtain credential. Dumping recovery data.",);
+                    output.pending_recovery.push(tx_hash);
                     match recovery_storage.insert_voucher(&state.voucher) {
                         Ok(file_path) => {
                             warn!("Dumped recovery data to {:?}. Try using recovery mode to convert it to a credential", file_path);
+                            output.recovery_file = Some(file_path);
                         }
                         Err(e) => {
                             error!("Could not dump recovery data to file system due to {:?}, the deposit will be lost!", e)
                         }
                     }
+                } else {
+                    output.obtained_credentials.push(tx_hash);
                 }
             } else {
-                recover_credentials(&client.nyxd, &recovery_storage, &shared_storage).await?;
+                recover_credentials(
+                    &client.nyxd,
+                    &recovery_storage,
+                    &shared_storage,
+                    &mut output,
+                )
+                .await?;
             }
+
+            println!("{}", args.output.format(&output));
         }
         Command::Completions(c) => c.generate(&mut Cli::command(), bin_name),
         Command::GenerateFigSpec => fig_generate(&mut Cli::command(), bin_name),
@@ -4,6 +4,7 @@
 use crate::client::config::template::config_template;
 use nym_client_core::config::ClientCoreConfigTrait;
 use nym_config::defaults::DEFAULT_WEBSOCKET_LISTENING_PORT;
+use nym_config::paths::default_nym_directory;
 use nym_config::{NymConfig, OptionalSet};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
@@ -55,14 +56,11 @@ impl NymConfig for Config {
     }
 
     fn default_root_directory() -> PathBuf {
-        dirs::home_dir()
-            .expect("Failed to evaluate $HOME value")
-            .join(".nym")
-            .join("clients")
+        default_nym_directory("clients").expect("no data directory known for this OS")
     }
 
     fn try_default_root_directory() -> Option<PathBuf> {
-        dirs::home_dir().map(|path| path.join(".nym").join("clients"))
+        default_nym_directory("clients")
     }
 
     fn root_directory(&self) -> PathBuf {
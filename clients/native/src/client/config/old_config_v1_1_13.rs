@@ -3,6 +3,7 @@
 
 use crate::client::config::{Config, Socket};
 use nym_client_core::config::old_config_v1_1_13::OldConfigV1_1_13 as OldBaseConfigV1_1_13;
+use nym_config::paths::default_nym_directory;
 use nym_config::NymConfig;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -23,14 +24,11 @@ impl NymConfig for OldConfigV1_1_13 {
     }
 
     fn default_root_directory() -> PathBuf {
-        dirs::home_dir()
-            .expect("Failed to evaluate $HOME value")
-            .join(".nym")
-            .join("clients")
+        default_nym_directory("clients").expect("no data directory known for this OS")
     }
 
     fn try_default_root_directory() -> Option<PathBuf> {
-        dirs::home_dir().map(|path| path.join(".nym").join("clients"))
+        default_nym_directory("clients")
     }
 
     fn root_directory(&self) -> PathBuf {
@@ -37,6 +37,13 @@ nym_api_urls = [
     {{/each}}
 ]
 
+# Identity keys of the mixnodes and gateways that should never be used by this client.
+ignored_nodes = [
+    {{#each client.ignored_nodes }}
+        '{{this}}',
+    {{/each}}
+]
+
 # Path to file containing private identity key.
 private_identity_key_file = '{{ client.private_identity_key_file }}'
 
@@ -103,6 +110,36 @@ host = '{{ socket.host }}'
 
 # TODO
 
+##### proxy configuration options #####
+# Proxies through which the HTTP(S) requests of the particular classes of endpoints are routed.
+# The nym API can be reached through either HTTP(S) or SOCKS5 proxies (use the 'socks5h' scheme
+# for the '.onion' endpoints), while nyxd can only be reached through HTTP(S) ones.
+
+[proxy]
+{{#if proxy.directory }}
+# proxy used for retrieving the network topology and the list of available gateways
+directory = '{{ proxy.directory }}'
+{{/if}}
+{{#if proxy.nym_api }}
+# proxy used for all the other requests sent to the nym API
+nym_api = '{{ proxy.nym_api }}'
+{{/if}}
+{{#if proxy.nyxd }}
+# proxy used for communicating with the nyxd validators
+nyxd = '{{ proxy.nyxd }}'
+{{/if}}
+
+##### storage encryption options #####
+# The bandwidth credentials, reply surbs, reply keys and the recipients of the used sender tags
+# can be encrypted with a key derived from a passphrase. The passphrase can be either provided
+# on startup ('prompt', also read from the NYM_CLIENT_STORAGE_PASSPHRASE environment variable),
+# kept in the keychain of the operating system ('keychain') or supplied through
+# the control API of the client ('control_api').
+
+[storage_encryption]
+enabled = {{ storage_encryption.enabled }}
+passphrase_source = '{{ storage_encryption.passphrase_source }}'
+
 
 ##### debug configuration options #####
 # The following options should not be modified unless you know EXACTLY what you are doing
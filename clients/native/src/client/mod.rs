@@ -3,23 +3,26 @@
 
 use crate::client::config::Config;
 use crate::error::ClientError;
+#[cfg(feature = "websocket")]
 use crate::websocket;
 use futures::channel::mpsc;
-use log::*;
 use nym_bandwidth_controller::BandwidthController;
-use nym_client_core::client::base_client::{
-    non_wasm_helpers, BaseClientBuilder, ClientInput, ClientOutput, ClientState,
-};
+use nym_client_core::client::base_client::{non_wasm_helpers, BaseClientBuilder, ClientInput};
+#[cfg(feature = "websocket")]
+use nym_client_core::client::base_client::{ClientOutput, ClientState};
 use nym_client_core::client::inbound_messages::InputMessage;
 use nym_client_core::client::received_buffer::{
     ReceivedBufferMessage, ReceivedBufferRequestSender, ReconstructedMessagesReceiver,
 };
+use nym_client_core::client::replies::reply_storage::fs_backend;
+use nym_client_core::client::storage_passphrase::storage_passphrase;
 use nym_client_core::config::persistence::key_pathfinder::ClientKeyPathfinder;
+use nym_client_core::config::PassphraseSource;
 use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
 use nym_task::connections::TransmissionLane;
 use nym_task::TaskManager;
 use nym_validator_client::nyxd::QueryNyxdClient;
-use std::error::Error;
+use std::path::PathBuf;
 use tokio::sync::watch::error::SendError;
 
 pub use nym_client_core::client::key_manager::KeyManager;
@@ -30,6 +33,8 @@ use nym_validator_client::Client;
 
 pub mod config;
 
+type NativeBandwidthController = BandwidthController<Client<QueryNyxdClient>, PersistentStorage>;
+
 pub struct SocketClient {
     /// Client configuration options, including, among other things, packet sending rates,
     /// key filepaths, etc.
@@ -37,6 +42,16 @@ pub struct SocketClient {
 
     /// KeyManager object containing smart pointers to all relevant keys used by the client.
     key_manager: KeyManager,
+
+    /// Optional (development) file into which all the sent traffic is going to be recorded.
+    traffic_recording: Option<PathBuf>,
+
+    /// Optional (development) file with the previously recorded traffic that is going to be replayed.
+    traffic_replay: Option<PathBuf>,
+
+    /// Passphrase of the encrypted storage supplied by the library user,
+    /// overriding the source specified in the config.
+    storage_passphrase: Option<String>,
 }
 
 impl SocketClient {
@@ -47,6 +62,9 @@ impl SocketClient {
         SocketClient {
             config,
             key_manager,
+            traffic_recording: None,
+            traffic_replay: None,
+            storage_passphrase: None,
         }
     }
 
@@ -54,12 +72,29 @@ impl SocketClient {
         SocketClient {
             config,
             key_manager,
+            traffic_recording: None,
+            traffic_replay: None,
+            storage_passphrase: None,
         }
     }
 
-    async fn create_bandwidth_controller(
-        config: &Config,
-    ) -> BandwidthController<Client<QueryNyxdClient>, PersistentStorage> {
+    pub fn with_storage_passphrase(mut self, storage_passphrase: Option<String>) -> Self {
+        self.storage_passphrase = storage_passphrase;
+        self
+    }
+
+    pub fn with_traffic_recording(mut self, traffic_recording: Option<PathBuf>) -> Self {
+        self.traffic_recording = traffic_recording;
+        self
+    }
+
+    pub fn with_traffic_replay(mut self, traffic_replay: Option<PathBuf>) -> Self {
+        self.traffic_replay = traffic_replay;
+        self
+    }
+
+    #[cfg(feature = "coconut")]
+    pub(crate) fn create_validator_client(config: &Config) -> Client<QueryNyxdClient> {
         let details = nym_network_defaults::NymNetworkDetails::new_from_env();
         let mut client_config =
             nym_validator_client::Config::try_from_nym_network_details(&details)
@@ -75,18 +110,90 @@ impl SocketClient {
             .pop()
             .expect("No validator api endpoint provided");
         // overwrite env configuration with config URLs
-        client_config = client_config.with_urls(nyxd_url, api_url);
-        let client = nym_validator_client::Client::new_query(client_config)
-            .expect("Could not construct query client");
-        BandwidthController::new(
-            nym_credential_storage::initialise_persistent_storage(
+        client_config = client_config
+            .with_urls(nyxd_url, api_url)
+            .with_nym_api_proxy(config.get_base().get_nym_api_proxy())
+            .with_nyxd_proxy(config.get_base().get_nyxd_proxy());
+        nym_validator_client::Client::new_query(client_config)
+            .expect("Could not construct query client")
+    }
+
+    #[cfg(feature = "coconut")]
+    async fn create_bandwidth_controller(
+        config: &Config,
+        passphrase: Option<&str>,
+    ) -> Result<Option<NativeBandwidthController>, ClientError> {
+        // don't create bandwidth controller if credentials are disabled
+        if config.get_base().get_disabled_credentials_mode() {
+            return Ok(None);
+        }
+
+        Ok(Some(BandwidthController::new(
+            PersistentStorage::init_with_passphrase(
                 config.get_base().get_database_path(),
+                passphrase,
             )
-            .await,
-            client,
+            .await?,
+            Self::create_validator_client(config),
+        )))
+    }
+
+    #[cfg(not(feature = "coconut"))]
+    async fn create_bandwidth_controller(
+        config: &Config,
+        _passphrase: Option<&str>,
+    ) -> Result<Option<NativeBandwidthController>, ClientError> {
+        if config.get_base().get_disabled_credentials_mode() {
+            Ok(None)
+        } else {
+            Err(ClientError::CredentialsNotSupported)
+        }
+    }
+
+    async fn setup_storage(
+        config: &Config,
+        passphrase: Option<&str>,
+    ) -> Result<(Option<NativeBandwidthController>, fs_backend::Backend), ClientError> {
+        let bandwidth_controller = Self::create_bandwidth_controller(config, passphrase).await?;
+        let reply_storage_backend = non_wasm_helpers::setup_fs_reply_surb_backend(
+            Some(config.get_base().get_reply_surb_database_path()),
+            config.get_debug_settings(),
+            passphrase,
         )
+        .await?;
+        Ok((bandwidth_controller, reply_storage_backend))
+    }
+
+    /// Opens the client storages, obtaining their passphrase first if they're encrypted.
+    async fn unlock_storage(
+        &self,
+    ) -> Result<(Option<NativeBandwidthController>, fs_backend::Backend), ClientError> {
+        let base_config = self.config.get_base();
+        if self.storage_passphrase.is_some() || !base_config.get_storage_encryption_enabled() {
+            return Self::setup_storage(&self.config, self.storage_passphrase.as_deref()).await;
+        }
+
+        match base_config.get_storage_passphrase_source() {
+            #[cfg(feature = "websocket")]
+            PassphraseSource::ControlApi if self.config.get_socket_type().is_websocket() => {
+                websocket::wait_for_storage_unlock(
+                    self.config.get_listening_ip(),
+                    self.config.get_listening_port(),
+                    |passphrase| async move {
+                        Self::setup_storage(&self.config, Some(&passphrase)).await
+                    },
+                )
+                .await
+            }
+            PassphraseSource::ControlApi => Err(ClientError::StorageLocked),
+            _ => {
+                let passphrase = storage_passphrase(base_config)?;
+                Self::setup_storage(&self.config, passphrase.as_deref()).await
+            }
+        }
     }
 
+    #[cfg(feature = "websocket")]
     fn start_websocket_listener(
         config: &Config,
         client_input: ClientInput,
@@ -95,30 +202,34 @@ impl SocketClient {
         self_address: &Recipient,
         shutdown: nym_task::TaskClient,
     ) {
-        info!("Starting websocket listener...");
+        log::info!("Starting websocket listener...");
 
         let ClientInput {
             connection_command_sender,
             input_sender,
         } = client_input;
 
-        let ClientOutput {
-            received_buffer_request_sender,
-        } = client_output;
-
         let ClientState {
             shared_lane_queue_lengths,
             reply_controller_sender,
+            hibernation,
+            topology_anomalies,
+            bandwidth_forecast,
+            events,
             ..
         } = client_state;
 
         let websocket_handler = websocket::HandlerBuilder::new(
             input_sender,
             connection_command_sender,
-            received_buffer_request_sender,
+            client_output,
             self_address,
             shared_lane_queue_lengths,
             reply_controller_sender,
+            hibernation,
+            topology_anomalies,
+            bandwidth_forecast,
+            events,
         );
 
         websocket::Listener::new(config.get_listening_ip(), config.get_listening_port())
@@ -126,7 +237,8 @@ impl SocketClient {
     }
 
     /// blocking version of `start_socket` method. Will run forever (or until SIGINT is sent)
-    pub async fn run_socket_forever(self) -> Result<(), Box<dyn Error + Send + Sync>> {
+    #[cfg(feature = "websocket")]
+    pub async fn run_socket_forever(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let shutdown = self.start_socket().await?;
 
         let res = shutdown.catch_interrupt().await;
@@ -134,28 +246,26 @@ impl SocketClient {
         res
     }
 
+    #[cfg(feature = "websocket")]
     pub async fn start_socket(self) -> Result<TaskManager, ClientError> {
         if !self.config.get_socket_type().is_websocket() {
             return Err(ClientError::InvalidSocketMode);
         }
 
-        // don't create bandwidth controller if credentials are disabled
-        let bandwidth_controller = if self.config.get_base().get_disabled_credentials_mode() {
-            None
-        } else {
-            Some(Self::create_bandwidth_controller(&self.config).await)
-        };
+        let (bandwidth_controller, reply_storage_backend) = self.unlock_storage().await?;
 
-        let base_builder = BaseClientBuilder::new_from_base_config(
+        let mut base_builder = BaseClientBuilder::new_from_base_config(
             self.config.get_base(),
             self.key_manager,
             bandwidth_controller,
-            non_wasm_helpers::setup_fs_reply_surb_backend(
-                Some(self.config.get_base().get_reply_surb_database_path()),
-                self.config.get_debug_settings(),
-            )
-            .await?,
+            reply_storage_backend,
         );
+        if let Some(path) = self.traffic_recording {
+            base_builder = base_builder.with_traffic_recording(path);
+        }
+        if let Some(path) = self.traffic_replay {
+            base_builder = base_builder.with_traffic_replay(path);
+        }
 
         let self_address = base_builder.as_mix_recipient();
         let mut started_client = base_builder.start_base().await?;
@@ -172,8 +282,8 @@ impl SocketClient {
             started_client.task_manager.subscribe(),
         );
 
-        info!("Client startup finished!");
-        info!("The address of this client is: {}", self_address);
+        log::info!("Client startup finished!");
+        log::info!("The address of this client is: {}", self_address);
 
         Ok(started_client.task_manager)
     }
@@ -183,23 +293,20 @@ impl SocketClient {
             return Err(ClientError::InvalidSocketMode);
         }
 
-        // don't create bandwidth controller if credentials are disabled
-        let bandwidth_controller = if self.config.get_base().get_disabled_credentials_mode() {
-            None
-        } else {
-            Some(Self::create_bandwidth_controller(&self.config).await)
-        };
+        let (bandwidth_controller, reply_storage_backend) = self.unlock_storage().await?;
 
-        let base_client = BaseClientBuilder::new_from_base_config(
+        let mut base_client = BaseClientBuilder::new_from_base_config(
             self.config.get_base(),
             self.key_manager,
             bandwidth_controller,
-            non_wasm_helpers::setup_fs_reply_surb_backend(
-                Some(self.config.get_base().get_reply_surb_database_path()),
-                self.config.get_debug_settings(),
-            )
-            .await?,
+            reply_storage_backend,
         );
+        if let Some(path) = self.traffic_recording {
+            base_client = base_client.with_traffic_recording(path);
+        }
+        if let Some(path) = self.traffic_replay {
+            base_client = base_client.with_traffic_replay(path);
+        }
 
         let address = base_client.as_mix_recipient();
 
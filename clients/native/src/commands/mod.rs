@@ -7,19 +7,44 @@ use clap::CommandFactory;
 use clap::{Parser, Subcommand};
 use lazy_static::lazy_static;
 use log::info;
-use nym_bin_common::build_information::BinaryBuildInformation;
+use nym_bin_common::build_information::{protocols, BinaryBuildInformation, BuildInfo};
 use nym_bin_common::completions::{fig_generate, ArgShell};
 use nym_config::{NymConfig, OptionalSet};
 use std::error::Error;
 use std::net::IpAddr;
 
 pub(crate) mod init;
+#[cfg(feature = "coconut")]
+pub(crate) mod request_bandwidth;
+#[cfg(feature = "websocket")]
 pub(crate) mod run;
 pub(crate) mod upgrade;
 
+pub(crate) const BUILD_INFORMATION: BinaryBuildInformation =
+    BinaryBuildInformation::new(env!("CARGO_PKG_VERSION"))
+        .with_enabled_features(&[
+            #[cfg(feature = "websocket")]
+            "websocket",
+            #[cfg(feature = "coconut")]
+            "coconut",
+            #[cfg(feature = "tokio-console")]
+            "tokio-console",
+            #[cfg(feature = "packet-tracing")]
+            "packet-tracing",
+        ])
+        .with_protocol_versions(&[
+            (
+                protocols::SPHINX_PACKET,
+                nym_sphinx::params::CURRENT_PACKET_VERSION_NUMBER,
+            ),
+            (
+                protocols::GATEWAY_CLIENT,
+                nym_gateway_requests::PROTOCOL_VERSION,
+            ),
+        ]);
+
 lazy_static! {
-    pub static ref PRETTY_BUILD_INFORMATION: String =
-        BinaryBuildInformation::new(env!("CARGO_PKG_VERSION")).pretty_print();
+    pub static ref PRETTY_BUILD_INFORMATION: String = BUILD_INFORMATION.pretty_print();
 }
 
 // Helper for passing LONG_VERSION to clap
@@ -40,12 +65,19 @@ pub(crate) struct Cli {
 
 #[derive(Subcommand)]
 pub(crate) enum Commands {
+    /// Show build information of this binary
+    BuildInfo(BuildInfo),
     /// Initialise a Nym client. Do this first!
     Init(init::Init),
     /// Run the Nym client with provided configuration client optionally overriding set parameters
+    #[cfg(feature = "websocket")]
     Run(run::Run),
     /// Try to upgrade the client
     Upgrade(upgrade::Upgrade),
+    /// Generate a deposit request to be paid from an external wallet and obtain the bandwidth
+    /// credential once the deposit lands on chain
+    #[cfg(feature = "coconut")]
+    RequestBandwidth(request_bandwidth::RequestBandwidth),
 
     /// Generate shell completions
     Completions(ArgShell),
@@ -70,9 +102,13 @@ pub(crate) async fn execute(args: &Cli) -> Result<(), Box<dyn Error + Send + Syn
     let bin_name = "nym-native-client";
 
     match &args.command {
+        Commands::BuildInfo(m) => m.execute(&BUILD_INFORMATION),
         Commands::Init(m) => init::execute(m).await?,
+        #[cfg(feature = "websocket")]
         Commands::Run(m) => run::execute(m).await?,
         Commands::Upgrade(m) => upgrade::execute(m),
+        #[cfg(feature = "coconut")]
+        Commands::RequestBandwidth(m) => request_bandwidth::execute(m).await?,
         Commands::Completions(s) => s.generate(&mut Cli::command(), bin_name),
         Commands::GenerateFigSpec => fig_generate(&mut Cli::command(), bin_name),
     }
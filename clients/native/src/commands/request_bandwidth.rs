@@ -0,0 +1,112 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::client::config::Config;
+use crate::client::SocketClient;
+use crate::error::ClientError;
+use clap::Args;
+use log::*;
+use nym_bandwidth_controller::acquire::deposit_request::PendingDeposit;
+use nym_client_core::client::storage_passphrase::storage_passphrase;
+use nym_config::NymConfig;
+use nym_credential_storage::persistent_storage::PersistentStorage;
+use nym_network_defaults::NymNetworkDetails;
+use nym_validator_client::nyxd::Coin;
+use std::error::Error;
+use std::path::Path;
+use std::time::Duration;
+use std::{fs, io};
+
+const PENDING_DEPOSIT_FILENAME: &str = "pending_deposit.json";
+const DEPOSIT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Args, Clone)]
+pub(crate) struct RequestBandwidth {
+    /// Id of the nym-mixnet-client that is going to use the credential.
+    #[clap(long)]
+    id: String,
+
+    /// The amount of utokens the credential should hold. It is ignored if there already exists
+    /// a pending deposit request for this client.
+    #[clap(long)]
+    amount: u64,
+}
+
+fn load_pending_deposit(path: &Path) -> io::Result<Option<PendingDeposit>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read(path)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+fn store_pending_deposit(path: &Path, pending_deposit: &PendingDeposit) -> io::Result<()> {
+    fs::write(path, serde_json::to_vec(pending_deposit)?)
+}
+
+pub(crate) async fn execute(args: &RequestBandwidth) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let id = &args.id;
+
+    let config = match Config::load_from_file(id) {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!("Failed to load config for {}. Are you sure you have run `init` before? (Error was: {err})", id);
+            return Err(Box::new(ClientError::FailedToLoadConfig(id.to_string())));
+        }
+    };
+
+    let client = SocketClient::create_validator_client(&config);
+    let passphrase = storage_passphrase(config.get_base())?;
+    let storage = PersistentStorage::init_with_passphrase(
+        config.get_base().get_database_path(),
+        passphrase.as_deref(),
+    )
+    .await?;
+
+    // keep the request around until the credential is obtained, otherwise the deposit would be lost
+    let pending_deposit_path = config
+        .get_base()
+        .get_database_path()
+        .with_file_name(PENDING_DEPOSIT_FILENAME);
+    let pending_deposit = match load_pending_deposit(&pending_deposit_path)? {
+        Some(pending_deposit) => {
+            info!("Resuming the previously generated deposit request");
+            pending_deposit
+        }
+        None => {
+            let denom = NymNetworkDetails::new_from_env()
+                .chain_details
+                .mix_denom
+                .base;
+            let pending_deposit = PendingDeposit::new(
+                client.nyxd.coconut_bandwidth_contract_address().to_string(),
+                Coin::new(args.amount as u128, denom),
+            );
+            store_pending_deposit(&pending_deposit_path, &pending_deposit)?;
+            pending_deposit
+        }
+    };
+
+    println!("Open the following deposit request in your wallet or scan it as a QR code:");
+    println!("{}", pending_deposit.request().to_uri());
+
+    info!("Waiting for the deposit to land on chain. You can safely kill the process and resume later.");
+    let state = loop {
+        match pending_deposit.try_find_deposit(&client.nyxd).await {
+            Ok(Some(state)) => break state,
+            Ok(None) => debug!("The deposit hasn't landed yet"),
+            Err(err) => warn!("Failed to look up the deposit - {err}"),
+        }
+        tokio::time::sleep(DEPOSIT_POLL_INTERVAL).await;
+    };
+
+    info!(
+        "Found deposit {}. Obtaining the credential...",
+        state.voucher.tx_hash()
+    );
+    nym_bandwidth_controller::acquire::get_credential(&state, &client, &storage).await?;
+    fs::remove_file(&pending_deposit_path)?;
+
+    info!("Obtained the bandwidth credential");
+    Ok(())
+}
@@ -3,6 +3,7 @@
 
 use std::error::Error;
 use std::net::IpAddr;
+use std::path::PathBuf;
 
 use crate::commands::try_upgrade_v1_1_13_config;
 use crate::{
@@ -61,6 +62,14 @@ pub(crate) struct Run {
     /// with bandwidth credential requirement.
     #[clap(long, hide = true)]
     enabled_credentials_mode: Option<bool>,
+
+    /// Record all the packets sent to the gateway into the specified file (for development only)
+    #[clap(long, hide = true)]
+    record_traffic: Option<PathBuf>,
+
+    /// Replay the traffic previously recorded into the specified file (for development only)
+    #[clap(long, hide = true)]
+    replay_traffic: Option<PathBuf>,
 }
 
 impl From<Run> for OverrideConfig {
@@ -128,5 +137,9 @@ pub(crate) async fn execute(args: &Run) -> Result<(), Box<dyn Error + Send + Syn
         return Err(Box::new(ClientError::FailedLocalVersionCheck));
     }
 
-    SocketClient::new(config).run_socket_forever().await
+    SocketClient::new(config)
+        .with_traffic_recording(args.record_traffic.clone())
+        .with_traffic_replay(args.replay_traffic.clone())
+        .run_socket_forever()
+        .await
 }
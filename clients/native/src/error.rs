@@ -20,4 +20,13 @@ pub enum ClientError {
 
     #[error("Attempted to start the client in invalid socket mode")]
     InvalidSocketMode,
+
+    #[error("credential storage error: {0}")]
+    CredentialStorageError(#[from] nym_credential_storage::error::StorageError),
+
+    #[error("The storage passphrase is meant to be supplied through the websocket, but the client is not running in the websocket mode")]
+    StorageLocked,
+
+    #[error("The client is configured to use bandwidth credentials, but it has been built without the support for them (the `coconut` feature)")]
+    CredentialsNotSupported,
 }
@@ -3,4 +3,5 @@
 
 pub mod client;
 pub mod error;
+#[cfg(feature = "websocket")]
 pub mod websocket;
@@ -1,23 +1,38 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::websocket::sent_messages::{
+    payload_digest, PayloadDigest, Registration, SendOutcome, SentMessages, DUPLICATE_SEND_WINDOW,
+};
 use futures::channel::mpsc;
 use futures::{SinkExt, StreamExt};
 use log::*;
+use nym_client_core::client::bandwidth_forecast::{BandwidthForecast, BandwidthForecastControl};
+use nym_client_core::client::base_client::ClientOutput;
+use nym_client_core::client::delivery::{
+    DeliveryEvent, DeliveryEventReceiver, DeliveryFailure, MessageId,
+};
+use nym_client_core::client::events::{ClientEvent, ClientEventBus};
+use nym_client_core::client::hibernation::HibernationControl;
 use nym_client_core::client::replies::reply_controller::requests::ReplyControllerSender;
+use nym_client_core::client::topology_control::anomaly::{
+    TopologyAnomalyControl, TopologyAnomalyStatus,
+};
 use nym_client_core::client::{
     inbound_messages::{InputMessage, InputMessageSender},
-    received_buffer::{
-        ReceivedBufferMessage, ReceivedBufferRequestSender, ReconstructedMessagesReceiver,
-    },
+    received_buffer::{ReceivedBufferMessage, ReconstructedMessagesReceiver},
+};
+use nym_client_websocket_requests::{
+    requests::ClientRequest,
+    responses::{ClientEventInfo, DeadLetterInfo, LaneStatusInfo, ServerResponse},
 };
-use nym_client_websocket_requests::{requests::ClientRequest, responses::ServerResponse};
 use nym_sphinx::addressing::clients::Recipient;
 use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
 use nym_sphinx::receiver::ReconstructedMessage;
 use nym_task::connections::{
     ConnectionCommand, ConnectionCommandSender, ConnectionId, LaneQueueLengths, TransmissionLane,
 };
+use std::collections::HashSet;
 use std::time::Duration;
 use tokio::net::TcpStream;
 use tokio::time::Instant;
@@ -27,6 +42,17 @@ use tokio_tungstenite::{
     WebSocketStream,
 };
 
+// how often the lanes are checked for crossing the thresholds of the lane status subscription
+const LANE_STATUS_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+struct LaneStatusSubscription {
+    pause_threshold: usize,
+    resume_threshold: usize,
+
+    /// Lanes for which the client has been told they're congested.
+    congested_lanes: HashSet<ConnectionId>,
+}
+
 #[derive(Default)]
 enum ReceivedResponseType {
     #[default]
@@ -37,28 +63,43 @@ enum ReceivedResponseType {
 pub(crate) struct HandlerBuilder {
     msg_input: InputMessageSender,
     client_connection_tx: ConnectionCommandSender,
-    buffer_requester: ReceivedBufferRequestSender,
+    client_output: ClientOutput,
     self_full_address: Recipient,
     lane_queue_lengths: LaneQueueLengths,
     reply_controller_sender: ReplyControllerSender,
+    hibernation: HibernationControl,
+    topology_anomalies: TopologyAnomalyControl,
+    bandwidth_forecast: BandwidthForecastControl,
+    client_events: ClientEventBus,
+    sent_messages: SentMessages,
 }
 
 impl HandlerBuilder {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         msg_input: InputMessageSender,
         client_connection_tx: ConnectionCommandSender,
-        buffer_requester: ReceivedBufferRequestSender,
+        client_output: ClientOutput,
         self_full_address: &Recipient,
         lane_queue_lengths: LaneQueueLengths,
         reply_controller_sender: ReplyControllerSender,
+        hibernation: HibernationControl,
+        topology_anomalies: TopologyAnomalyControl,
+        bandwidth_forecast: BandwidthForecastControl,
+        client_events: ClientEventBus,
     ) -> Self {
         Self {
             msg_input,
             client_connection_tx,
-            buffer_requester,
+            client_output,
             self_full_address: *self_full_address,
             lane_queue_lengths,
             reply_controller_sender,
+            hibernation,
+            topology_anomalies,
+            bandwidth_forecast,
+            client_events,
+            sent_messages: SentMessages::new(DUPLICATE_SEND_WINDOW),
         }
     }
 
@@ -67,12 +108,19 @@ impl HandlerBuilder {
         Handler {
             msg_input: self.msg_input.clone(),
             client_connection_tx: self.client_connection_tx.clone(),
-            buffer_requester: self.buffer_requester.clone(),
+            client_output: self.client_output.clone(),
             self_full_address: self.self_full_address,
             socket: None,
             received_response_type: Default::default(),
             lane_queue_lengths: self.lane_queue_lengths.clone(),
             reply_controller_sender: self.reply_controller_sender.clone(),
+            lane_status_subscription: None,
+            hibernation: self.hibernation.clone(),
+            topology_anomalies: self.topology_anomalies.clone(),
+            bandwidth_forecast: self.bandwidth_forecast.clone(),
+            client_events: self.client_events.clone(),
+            subscribed_to_client_events: false,
+            sent_messages: self.sent_messages.clone(),
         }
     }
 }
@@ -80,18 +128,26 @@ impl HandlerBuilder {
 pub(crate) struct Handler {
     msg_input: InputMessageSender,
     client_connection_tx: ConnectionCommandSender,
-    buffer_requester: ReceivedBufferRequestSender,
+    client_output: ClientOutput,
     self_full_address: Recipient,
     socket: Option<WebSocketStream<TcpStream>>,
     received_response_type: ReceivedResponseType,
     lane_queue_lengths: LaneQueueLengths,
     reply_controller_sender: ReplyControllerSender,
+    lane_status_subscription: Option<LaneStatusSubscription>,
+    hibernation: HibernationControl,
+    topology_anomalies: TopologyAnomalyControl,
+    bandwidth_forecast: BandwidthForecastControl,
+    client_events: ClientEventBus,
+    subscribed_to_client_events: bool,
+    sent_messages: SentMessages,
 }
 
 impl Drop for Handler {
     fn drop(&mut self) {
         if self
-            .buffer_requester
+            .client_output
+            .received_buffer_request_sender
             .unbounded_send(ReceivedBufferMessage::ReceiverDisconnect)
             .is_err()
         {
@@ -143,11 +199,83 @@ impl Handler {
         })
     }
 
+    async fn get_lane_status(&self, connection_id: ConnectionId) -> Option<LaneStatusInfo> {
+        let conn_lane = TransmissionLane::ConnectionId(connection_id);
+        let reply_queue_length = self
+            .reply_controller_sender
+            .get_lane_queue_length(connection_id)
+            .await;
+
+        let Ok(guard) = self.lane_queue_lengths.lock() else {
+            error!("The lane queue length lock is poisoned!!");
+            return None;
+        };
+        let queue_length = guard.get(&conn_lane).unwrap_or_default() + reply_queue_length;
+
+        Some(LaneStatusInfo {
+            lane: connection_id,
+            queue_length,
+            estimated_drain_time: guard.estimated_drain_time(queue_length),
+        })
+    }
+
+    async fn check_lane_congestion(&mut self) -> Vec<ServerResponse> {
+        let Some(subscription) = &self.lane_status_subscription else {
+            return Vec::new();
+        };
+
+        let Ok(mut lanes) = self.lane_queue_lengths.lock().map(|guard| {
+            guard
+                .map
+                .keys()
+                .filter_map(|lane| match lane {
+                    TransmissionLane::ConnectionId(id) => Some(*id),
+                    _ => None,
+                })
+                .collect::<HashSet<_>>()
+        }) else {
+            error!("The lane queue length lock is poisoned!!");
+            return Vec::new();
+        };
+        // make sure to also check the lanes that might have been drained (and removed) in the meantime
+        lanes.extend(subscription.congested_lanes.iter().copied());
+
+        let pause_threshold = subscription.pause_threshold;
+        let resume_threshold = subscription.resume_threshold;
+
+        let mut events = Vec::new();
+        for lane in lanes {
+            let Some(status) = self.get_lane_status(lane).await else {
+                continue;
+            };
+            let Some(subscription) = &mut self.lane_status_subscription else {
+                break;
+            };
+
+            let congested = subscription.congested_lanes.contains(&lane);
+            if !congested && status.queue_length >= pause_threshold {
+                subscription.congested_lanes.insert(lane);
+                events.push(ServerResponse::LaneCongestion {
+                    congested: true,
+                    status,
+                });
+            } else if congested && status.queue_length <= resume_threshold {
+                subscription.congested_lanes.remove(&lane);
+                events.push(ServerResponse::LaneCongestion {
+                    congested: false,
+                    status,
+                });
+            }
+        }
+        events
+    }
+
     async fn handle_send(
         &mut self,
         recipient: Recipient,
         message: Vec<u8>,
         connection_id: Option<u64>,
+        message_id: Option<MessageId>,
     ) -> Option<ServerResponse> {
         info!(
             "Attempting to send {:.2} kiB message to {recipient} on connection_id {connection_id:?}",
@@ -160,7 +288,10 @@ impl Handler {
         });
 
         // the ack control is now responsible for chunking, etc.
-        let input_msg = InputMessage::new_regular(recipient, message, lane);
+        let input_msg = with_tracking(
+            InputMessage::new_regular(recipient, message, lane),
+            message_id,
+        );
         self.msg_input
             .send(input_msg)
             .await
@@ -180,6 +311,7 @@ impl Handler {
         message: Vec<u8>,
         reply_surbs: u32,
         connection_id: Option<u64>,
+        message_id: Option<MessageId>,
     ) -> Option<ServerResponse> {
         info!(
             "Attempting to anonymously send {:.2} kiB message to {recipient} on connection_id {connection_id:?} while attaching {reply_surbs} replySURBs.",
@@ -191,7 +323,10 @@ impl Handler {
             TransmissionLane::ConnectionId(id)
         });
 
-        let input_msg = InputMessage::new_anonymous(recipient, message, reply_surbs, lane);
+        let input_msg = with_tracking(
+            InputMessage::new_anonymous(recipient, message, reply_surbs, lane),
+            message_id,
+        );
         self.msg_input
             .send(input_msg)
             .await
@@ -210,6 +345,7 @@ impl Handler {
         recipient_tag: AnonymousSenderTag,
         message: Vec<u8>,
         connection_id: Option<u64>,
+        message_id: Option<MessageId>,
     ) -> Option<ServerResponse> {
         info!("Attempting to send {:.2} kiB reply message to {recipient_tag} on connection_id {connection_id:?}", message.len() as f64 / 1024.0);
 
@@ -218,7 +354,10 @@ impl Handler {
             TransmissionLane::ConnectionId(id)
         });
 
-        let input_msg = InputMessage::new_reply(recipient_tag, message, lane);
+        let input_msg = with_tracking(
+            InputMessage::new_reply(recipient_tag, message, lane),
+            message_id,
+        );
         self.msg_input
             .send(input_msg)
             .await
@@ -247,13 +386,183 @@ impl Handler {
         self.get_lane_queue_length(connection_id).await
     }
 
+    async fn handle_get_lane_status(&self, connection_id: u64) -> Option<ServerResponse> {
+        self.get_lane_status(connection_id)
+            .await
+            .map(ServerResponse::LaneStatus)
+    }
+
+    fn handle_subscribe_lane_status(
+        &mut self,
+        pause_threshold: u64,
+        resume_threshold: u64,
+    ) -> Option<ServerResponse> {
+        info!("Subscribing to the lane status with the pause threshold of {pause_threshold} packets and the resume threshold of {resume_threshold} packets");
+        self.lane_status_subscription = Some(LaneStatusSubscription {
+            pause_threshold: pause_threshold as usize,
+            resume_threshold: resume_threshold as usize,
+            congested_lanes: HashSet::new(),
+        });
+        None
+    }
+
+    fn handle_subscribe_client_events(&mut self) -> Option<ServerResponse> {
+        if !self.subscribed_to_client_events {
+            info!("Subscribing to the client events");
+            self.subscribed_to_client_events = true;
+        }
+        None
+    }
+
+    fn handle_hibernate(&self) -> ServerResponse {
+        if self.hibernation.hibernate() {
+            info!("Entering the hibernation mode - the cover traffic is going to be suspended");
+        }
+        ServerResponse::Hibernation { hibernating: true }
+    }
+
+    fn handle_wake(&self) -> ServerResponse {
+        if self.hibernation.wake() {
+            info!("Leaving the hibernation mode - restoring the full traffic shaping");
+        }
+        ServerResponse::Hibernation { hibernating: false }
+    }
+
+    fn handle_get_topology_anomalies(&self) -> ServerResponse {
+        topology_anomalies_response(self.topology_anomalies.status())
+    }
+
+    fn handle_acknowledge_topology_anomalies(&self) -> ServerResponse {
+        let acknowledged = self.topology_anomalies.acknowledge();
+        if !acknowledged.is_empty() {
+            info!(
+                "Acknowledged {} topology anomalies - the traffic is no longer paused",
+                acknowledged.len()
+            );
+        }
+        topology_anomalies_response(self.topology_anomalies.status())
+    }
+
+    /// Responds to a resubmitted tracked message the same way as to the original one, unless its
+    /// outcome is already known, in which case it's sent back straight away.
+    async fn handle_duplicate_send(
+        &self,
+        message_id: MessageId,
+        outcome: SendOutcome,
+        request: &ClientRequest,
+    ) -> Option<ServerResponse> {
+        match outcome {
+            SendOutcome::Delivered => Some(ServerResponse::Delivered { message_id }),
+            SendOutcome::Failed(reason) => {
+                Some(ServerResponse::DeliveryFailed { message_id, reason })
+            }
+            SendOutcome::Pending => match request {
+                ClientRequest::Send {
+                    connection_id: Some(connection_id),
+                    ..
+                }
+                | ClientRequest::SendAnonymous {
+                    connection_id: Some(connection_id),
+                    ..
+                }
+                | ClientRequest::Reply {
+                    connection_id: Some(connection_id),
+                    ..
+                } => self.get_lane_queue_length(*connection_id).await,
+                _ => None,
+            },
+        }
+    }
+
+    async fn handle_get_dead_letters(&self) -> ServerResponse {
+        match self.client_output.dead_letters().await {
+            Ok(dead_letters) => ServerResponse::DeadLetters(
+                dead_letters
+                    .into_iter()
+                    .map(|dead_letter| DeadLetterInfo {
+                        message_id: dead_letter.message_id,
+                        reason: dead_letter.reason.to_string(),
+                    })
+                    .collect(),
+            ),
+            Err(err) => ServerResponse::new_error(err.to_string()),
+        }
+    }
+
+    async fn handle_retry_dead_letter(&mut self, message_id: MessageId) -> Option<ServerResponse> {
+        let dead_letter = match self.client_output.take_dead_letter(message_id).await {
+            Ok(Some(dead_letter)) => dead_letter,
+            Ok(None) => {
+                return Some(ServerResponse::new_error(format!(
+                    "there is no undelivered message with id {message_id}"
+                )))
+            }
+            Err(err) => return Some(ServerResponse::new_error(err.to_string())),
+        };
+
+        info!("Attempting to resend undelivered message {message_id}");
+        self.sent_messages.resend(message_id);
+        // note: the message is going to take a completely new route through the mixnet
+        let input_msg = dead_letter.message.with_delivery_tracking(message_id);
+        self.msg_input
+            .send(input_msg)
+            .await
+            .expect("InputMessageReceiver has stopped receiving!");
+
+        None
+    }
+
+    async fn handle_discard_dead_letter(&self, message_id: MessageId) -> Option<ServerResponse> {
+        match self.client_output.take_dead_letter(message_id).await {
+            Ok(Some(_)) => None,
+            Ok(None) => Some(ServerResponse::new_error(format!(
+                "there is no undelivered message with id {message_id}"
+            ))),
+            Err(err) => Some(ServerResponse::new_error(err.to_string())),
+        }
+    }
+
     async fn handle_request(&mut self, request: ClientRequest) -> Option<ServerResponse> {
+        let (request, message_id) = match request {
+            ClientRequest::Tracked {
+                message_id,
+                request,
+            } => (*request, Some(message_id)),
+            request => (request, None),
+        };
+
+        if let Some(message_id) = message_id {
+            if let Some((connection_id, digest)) = tracked_send_fingerprint(&request) {
+                match self
+                    .sent_messages
+                    .register(message_id, connection_id, digest)
+                {
+                    Registration::New => (),
+                    Registration::Duplicate(outcome) => {
+                        info!("Message {message_id} has already been sent - not sending it again");
+                        return self
+                            .handle_duplicate_send(message_id, outcome, &request)
+                            .await;
+                    }
+                    Registration::Conflict => {
+                        warn!("Message id {message_id} is already used by a different message");
+                        return Some(ServerResponse::new_error(format!(
+                            "message id {message_id} is already used by a different message"
+                        )));
+                    }
+                }
+            }
+        }
+
         match request {
             ClientRequest::Send {
                 recipient,
                 message,
                 connection_id,
-            } => self.handle_send(recipient, message, connection_id).await,
+            } => {
+                self.handle_send(recipient, message, connection_id, message_id)
+                    .await
+            }
 
             ClientRequest::SendAnonymous {
                 recipient,
@@ -261,19 +570,54 @@ impl Handler {
                 reply_surbs,
                 connection_id,
             } => {
-                self.handle_send_anonymous(recipient, message, reply_surbs, connection_id)
-                    .await
+                self.handle_send_anonymous(
+                    recipient,
+                    message,
+                    reply_surbs,
+                    connection_id,
+                    message_id,
+                )
+                .await
             }
 
             ClientRequest::Reply {
                 message,
                 sender_tag,
                 connection_id,
-            } => self.handle_reply(sender_tag, message, connection_id).await,
+            } => {
+                self.handle_reply(sender_tag, message, connection_id, message_id)
+                    .await
+            }
 
             ClientRequest::SelfAddress => Some(self.handle_self_address()),
             ClientRequest::ClosedConnection(id) => self.handle_closed_connection(id),
             ClientRequest::GetLaneQueueLength(id) => self.handle_get_lane_queue_length(id).await,
+            ClientRequest::GetDeadLetters => Some(self.handle_get_dead_letters().await),
+            ClientRequest::RetryDeadLetter(id) => self.handle_retry_dead_letter(id).await,
+            ClientRequest::DiscardDeadLetter(id) => self.handle_discard_dead_letter(id).await,
+            ClientRequest::GetLaneStatus(id) => self.handle_get_lane_status(id).await,
+            ClientRequest::SubscribeLaneStatus {
+                pause_threshold,
+                resume_threshold,
+            } => self.handle_subscribe_lane_status(pause_threshold, resume_threshold),
+            ClientRequest::Hibernate => Some(self.handle_hibernate()),
+            ClientRequest::Wake => Some(self.handle_wake()),
+            ClientRequest::GetTopologyAnomalies => Some(self.handle_get_topology_anomalies()),
+            ClientRequest::AcknowledgeTopologyAnomalies => {
+                Some(self.handle_acknowledge_topology_anomalies())
+            }
+            ClientRequest::GetBandwidthForecast => Some(bandwidth_forecast_response(
+                self.bandwidth_forecast.forecast(),
+            )),
+            ClientRequest::SubscribeClientEvents => self.handle_subscribe_client_events(),
+            ClientRequest::UnlockStorage { .. } => Some(ServerResponse::new_error(
+                "the client storage has already been unlocked",
+            )),
+
+            // this is rejected when the request is being deserialized
+            ClientRequest::Tracked { .. } => Some(ServerResponse::new_error(
+                "tracked requests can't be nested",
+            )),
         }
     }
 
@@ -337,6 +681,74 @@ impl Handler {
             .await
     }
 
+    async fn push_websocket_delivery_event(&mut self, event: DeliveryEvent) -> Result<(), WsError> {
+        let response = match event {
+            DeliveryEvent::Delivered(message_id) => ServerResponse::Delivered { message_id },
+            DeliveryEvent::Failed(message_id, reason) => ServerResponse::DeliveryFailed {
+                message_id,
+                reason: reason.to_string(),
+            },
+            DeliveryEvent::Expired(message_id) => ServerResponse::DeliveryFailed {
+                message_id,
+                reason: DeliveryFailure::Expired.to_string(),
+            },
+        };
+
+        let msg = match self.received_response_type {
+            ReceivedResponseType::Binary => WsMessage::Binary(response.into_binary()),
+            ReceivedResponseType::Text => WsMessage::Text(response.into_text()),
+        };
+        self.send_websocket_response(msg).await
+    }
+
+    async fn push_websocket_lane_congestion_events(&mut self) -> Result<(), WsError> {
+        for event in self.check_lane_congestion().await {
+            let msg = match self.received_response_type {
+                ReceivedResponseType::Binary => WsMessage::Binary(event.into_binary()),
+                ReceivedResponseType::Text => WsMessage::Text(event.into_text()),
+            };
+            self.send_websocket_response(msg).await?;
+        }
+        Ok(())
+    }
+
+    async fn push_websocket_topology_anomalies(
+        &mut self,
+        status: TopologyAnomalyStatus,
+    ) -> Result<(), WsError> {
+        let response = topology_anomalies_response(status);
+        let msg = match self.received_response_type {
+            ReceivedResponseType::Binary => WsMessage::Binary(response.into_binary()),
+            ReceivedResponseType::Text => WsMessage::Text(response.into_text()),
+        };
+        self.send_websocket_response(msg).await
+    }
+
+    async fn push_websocket_bandwidth_warning(
+        &mut self,
+        forecast: BandwidthForecast,
+    ) -> Result<(), WsError> {
+        let response = ServerResponse::BandwidthWarning {
+            remaining: forecast.remaining,
+            consumption_rate: forecast.consumption_rate,
+            time_left: forecast.time_left,
+        };
+        let msg = match self.received_response_type {
+            ReceivedResponseType::Binary => WsMessage::Binary(response.into_binary()),
+            ReceivedResponseType::Text => WsMessage::Text(response.into_text()),
+        };
+        self.send_websocket_response(msg).await
+    }
+
+    async fn push_websocket_client_event(&mut self, event: ClientEvent) -> Result<(), WsError> {
+        let response = ServerResponse::ClientEvent(client_event_info(event));
+        let msg = match self.received_response_type {
+            ReceivedResponseType::Binary => WsMessage::Binary(response.into_binary()),
+            ReceivedResponseType::Text => WsMessage::Text(response.into_text()),
+        };
+        self.send_websocket_response(msg).await
+    }
+
     async fn send_websocket_response(&mut self, msg: WsMessage) -> Result<(), WsError> {
         match self.socket {
             // TODO: more closely investigate difference between `Sink::send` and `Sink::send_all`
@@ -357,8 +769,13 @@ impl Handler {
     async fn listen_for_requests(
         &mut self,
         mut msg_receiver: ReconstructedMessagesReceiver,
+        mut delivery_receiver: DeliveryEventReceiver,
         mut task_client: nym_task::TaskClient,
     ) {
+        let mut lane_status_check = tokio::time::interval(LANE_STATUS_CHECK_INTERVAL);
+        let mut topology_anomalies = self.topology_anomalies.listener();
+        let mut bandwidth_forecast = self.bandwidth_forecast.listener();
+        let mut client_events = self.client_events.subscribe();
         while !task_client.is_shutdown() {
             tokio::select! {
                 // we can either get a client request from the websocket
@@ -398,6 +815,52 @@ impl Handler {
                         break;
                     }
                 }
+                // or information about the delivery of a message sent with tracking enabled
+                Some(delivery_event) = delivery_receiver.next() => {
+                    self.sent_messages.record_outcome(&delivery_event);
+                    if let Err(err) = self.push_websocket_delivery_event(delivery_event).await {
+                        warn!("failed to send delivery notification back to the client - {err}, assuming the connection is dead");
+                        break;
+                    }
+                }
+                // or it's time to tell the subscribed client about the congestion of its lanes
+                _ = lane_status_check.tick(), if self.lane_status_subscription.is_some() => {
+                    if let Err(err) = self.push_websocket_lane_congestion_events().await {
+                        warn!("failed to send lane congestion notification back to the client - {err}, assuming the connection is dead");
+                        break;
+                    }
+                }
+                // or new suspicious changes of the network topology have been detected
+                status = topology_anomalies.changed() => {
+                    // acknowledgements are already responded to directly
+                    if status.anomalies.is_empty() {
+                        continue;
+                    }
+                    if let Err(err) = self.push_websocket_topology_anomalies(status).await {
+                        warn!("failed to send topology anomalies notification back to the client - {err}, assuming the connection is dead");
+                        break;
+                    }
+                }
+                // or the bandwidth is expected to run out soon
+                forecast = bandwidth_forecast.changed() => {
+                    if !forecast.running_low {
+                        continue;
+                    }
+                    if let Err(err) = self.push_websocket_bandwidth_warning(forecast).await {
+                        warn!("failed to send bandwidth warning back to the client - {err}, assuming the connection is dead");
+                        break;
+                    }
+                }
+                // or something notable has happened to the client and the connection asked to know about it
+                event = client_events.next() => {
+                    if !self.subscribed_to_client_events {
+                        continue;
+                    }
+                    if let Err(err) = self.push_websocket_client_event(event).await {
+                        warn!("failed to send client event back to the client - {err}, assuming the connection is dead");
+                        break;
+                    }
+                }
                 _ = task_client.recv() => {
                     log::trace!("Websocket handler: Received shutdown");
                 }
@@ -428,17 +891,104 @@ impl Handler {
         let (reconstructed_sender, reconstructed_receiver) = mpsc::unbounded();
 
         // tell the buffer to start sending stuff to us
-        self.buffer_requester
+        self.client_output
+            .received_buffer_request_sender
             .unbounded_send(ReceivedBufferMessage::ReceiverAnnounce(
                 reconstructed_sender,
             ))
             .expect("the buffer request failed!");
 
-        self.listen_for_requests(reconstructed_receiver, task_client)
+        // and to notify us about delivery of any tracked messages
+        let delivery_receiver = self
+            .client_output
+            .register_delivery_receiver()
+            .expect("the delivery listener registration failed!");
+
+        self.listen_for_requests(reconstructed_receiver, delivery_receiver, task_client)
             .await;
     }
 }
 
+fn topology_anomalies_response(status: TopologyAnomalyStatus) -> ServerResponse {
+    ServerResponse::TopologyAnomalies {
+        traffic_paused: status.traffic_paused,
+        anomalies: status.anomalies.iter().map(ToString::to_string).collect(),
+    }
+}
+
+fn bandwidth_forecast_response(forecast: BandwidthForecast) -> ServerResponse {
+    ServerResponse::BandwidthForecast {
+        remaining: forecast.remaining,
+        consumption_rate: forecast.consumption_rate,
+        time_left: forecast.time_left,
+        running_low: forecast.running_low,
+    }
+}
+
+fn client_event_info(event: ClientEvent) -> ClientEventInfo {
+    match event {
+        ClientEvent::GatewayConnected { gateway } => ClientEventInfo::GatewayConnected { gateway },
+        ClientEvent::GatewayDisconnected { reason } => {
+            ClientEventInfo::GatewayDisconnected { reason }
+        }
+        ClientEvent::TopologyRefreshed { mixnodes, gateways } => {
+            ClientEventInfo::TopologyRefreshed {
+                mixnodes: mixnodes as u64,
+                gateways: gateways as u64,
+            }
+        }
+        ClientEvent::CredentialSpent {
+            remaining_bandwidth,
+        } => ClientEventInfo::CredentialSpent {
+            remaining_bandwidth,
+        },
+    }
+}
+
+fn with_tracking(input_msg: InputMessage, message_id: Option<MessageId>) -> InputMessage {
+    match message_id {
+        Some(message_id) => input_msg.with_delivery_tracking(message_id),
+        None => input_msg,
+    }
+}
+
+// identifies the connection and the content of a tracked send, so that the same message id
+// could not be silently reused for something else
+fn tracked_send_fingerprint(request: &ClientRequest) -> Option<(Option<u64>, PayloadDigest)> {
+    match request {
+        ClientRequest::Send {
+            recipient,
+            message,
+            connection_id,
+        } => {
+            let mut destination = vec![0];
+            destination.extend_from_slice(&recipient.to_bytes());
+            Some((*connection_id, payload_digest(&destination, message)))
+        }
+        ClientRequest::SendAnonymous {
+            recipient,
+            message,
+            reply_surbs,
+            connection_id,
+        } => {
+            let mut destination = vec![1];
+            destination.extend_from_slice(&recipient.to_bytes());
+            destination.extend_from_slice(&reply_surbs.to_be_bytes());
+            Some((*connection_id, payload_digest(&destination, message)))
+        }
+        ClientRequest::Reply {
+            sender_tag,
+            message,
+            connection_id,
+        } => {
+            let mut destination = vec![2];
+            destination.extend_from_slice(&sender_tag.to_bytes());
+            Some((*connection_id, payload_digest(&destination, message)))
+        }
+        _ => None,
+    }
+}
+
 // I'm still not entirely sure why `send_all` requires `TryStream` rather than `Stream`, but
 // let's just play along for now
 fn prepare_reconstructed_binary(
@@ -3,6 +3,9 @@
 
 pub(crate) use handler::HandlerBuilder;
 pub(crate) use listener::Listener;
+pub(crate) use unlock::wait_for_storage_unlock;
 
 pub(crate) mod handler;
 pub(crate) mod listener;
+pub(crate) mod sent_messages;
+pub(crate) mod unlock;
@@ -0,0 +1,249 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_client_core::client::delivery::{DeliveryEvent, DeliveryFailure, MessageId};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Period during which resubmitting a tracked message with an already used id
+/// does not result in it being sent again.
+pub(crate) const DUPLICATE_SEND_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+// upper bound on the number of remembered message ids, regardless of the window
+const MAXIMUM_REMEMBERED_SENDS: usize = 10_000;
+
+/// Digest of the destination and the content of a tracked message.
+pub(crate) type PayloadDigest = [u8; 32];
+
+pub(crate) fn payload_digest(destination: &[u8], message: &[u8]) -> PayloadDigest {
+    let mut hasher = Sha256::new();
+    hasher.update((destination.len() as u64).to_be_bytes());
+    hasher.update(destination);
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// Known outcome of a tracked message that has been sent before.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SendOutcome {
+    Pending,
+    Delivered,
+    Failed(String),
+}
+
+/// Result of attempting to register a tracked message as sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Registration {
+    /// The message has not been seen before and should be sent.
+    New,
+
+    /// The very same message has already been sent on this connection,
+    /// so it must not be sent again.
+    Duplicate(SendOutcome),
+
+    /// The id is already in use by a different message or a different connection.
+    Conflict,
+}
+
+struct SentMessage {
+    connection_id: Option<u64>,
+    digest: PayloadDigest,
+    sent_at: Instant,
+    outcome: SendOutcome,
+}
+
+#[derive(Default)]
+struct SentMessagesInner {
+    messages: HashMap<MessageId, SentMessage>,
+    // ids in the order they were sent in, so that the expired ones could be cheaply removed
+    order: VecDeque<MessageId>,
+}
+
+/// Ids of the recently sent tracked messages, alongside their outcomes, used for suppressing
+/// duplicate sends from applications that retry their requests.
+///
+/// A resubmission is only treated as a duplicate if it comes with the same `connection_id`
+/// and the same destination and content as the original. Since the delivery events are only
+/// identified by the message id, reusing an id for anything else is rejected rather than being
+/// silently answered with the outcome of an unrelated message.
+///
+/// It's shared between all websocket connections so that the duplicates are also caught
+/// if the application resubmits the message after reconnecting.
+#[derive(Clone)]
+pub(crate) struct SentMessages {
+    window: Duration,
+    inner: Arc<Mutex<SentMessagesInner>>,
+}
+
+impl SentMessages {
+    pub(crate) fn new(window: Duration) -> Self {
+        SentMessages {
+            window,
+            inner: Arc::new(Mutex::new(SentMessagesInner::default())),
+        }
+    }
+
+    fn remove_expired(&self, inner: &mut SentMessagesInner, now: Instant) {
+        while let Some(oldest) = inner.order.front() {
+            let expired = match inner.messages.get(oldest) {
+                Some(message) => {
+                    now.duration_since(message.sent_at) > self.window
+                        || inner.messages.len() > MAXIMUM_REMEMBERED_SENDS
+                }
+                None => true,
+            };
+            if !expired {
+                break;
+            }
+            if let Some(id) = inner.order.pop_front() {
+                inner.messages.remove(&id);
+            }
+        }
+    }
+
+    /// Records the message as sent. If the same message has already been sent within
+    /// the window, it returns the outcome of that original send instead, in which case the message
+    /// must not be sent again.
+    pub(crate) fn register(
+        &self,
+        message_id: MessageId,
+        connection_id: Option<u64>,
+        digest: PayloadDigest,
+    ) -> Registration {
+        self.register_at(message_id, connection_id, digest, Instant::now())
+    }
+
+    fn register_at(
+        &self,
+        message_id: MessageId,
+        connection_id: Option<u64>,
+        digest: PayloadDigest,
+        now: Instant,
+    ) -> Registration {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("the sent messages lock is poisoned");
+        self.remove_expired(&mut inner, now);
+
+        if let Some(original) = inner.messages.get(&message_id) {
+            return if original.connection_id == connection_id && original.digest == digest {
+                Registration::Duplicate(original.outcome.clone())
+            } else {
+                Registration::Conflict
+            };
+        }
+        inner.messages.insert(
+            message_id,
+            SentMessage {
+                connection_id,
+                digest,
+                sent_at: now,
+                outcome: SendOutcome::Pending,
+            },
+        );
+        inner.order.push_back(message_id);
+        Registration::New
+    }
+
+    /// Marks the message as being deliberately sent again, such as when retrying a dead letter,
+    /// restarting its window.
+    pub(crate) fn resend(&self, message_id: MessageId) {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("the sent messages lock is poisoned");
+        let Some(message) = inner.messages.get_mut(&message_id) else {
+            return;
+        };
+        message.sent_at = Instant::now();
+        message.outcome = SendOutcome::Pending;
+        inner.order.retain(|id| *id != message_id);
+        inner.order.push_back(message_id);
+    }
+
+    pub(crate) fn record_outcome(&self, event: &DeliveryEvent) {
+        let outcome = match event {
+            DeliveryEvent::Delivered(_) => SendOutcome::Delivered,
+            DeliveryEvent::Failed(_, reason) => SendOutcome::Failed(reason.to_string()),
+            DeliveryEvent::Expired(_) => SendOutcome::Failed(DeliveryFailure::Expired.to_string()),
+        };
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("the sent messages lock is poisoned");
+        if let Some(message) = inner.messages.get_mut(&event.message_id()) {
+            message.outcome = outcome;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicates_are_reported_within_the_window() {
+        let start = Instant::now();
+        let digest = payload_digest(b"recipient", b"hello");
+        let sent = SentMessages::new(Duration::from_secs(60));
+        assert_eq!(sent.register_at(1, None, digest, start), Registration::New);
+        assert_eq!(
+            sent.register_at(1, None, digest, start),
+            Registration::Duplicate(SendOutcome::Pending)
+        );
+
+        sent.record_outcome(&DeliveryEvent::Delivered(1));
+        assert_eq!(
+            sent.register_at(1, None, digest, start + Duration::from_secs(30)),
+            Registration::Duplicate(SendOutcome::Delivered)
+        );
+
+        assert_eq!(
+            sent.register_at(1, None, digest, start + Duration::from_secs(61)),
+            Registration::New
+        );
+    }
+
+    #[test]
+    fn reused_ids_with_different_messages_are_rejected() {
+        let start = Instant::now();
+        let digest = payload_digest(b"recipient", b"hello");
+        let sent = SentMessages::new(Duration::from_secs(60));
+        assert_eq!(
+            sent.register_at(1, Some(1), digest, start),
+            Registration::New
+        );
+
+        let other_payload = payload_digest(b"recipient", b"goodbye");
+        assert_eq!(
+            sent.register_at(1, Some(1), other_payload, start),
+            Registration::Conflict
+        );
+        let other_recipient = payload_digest(b"another recipient", b"hello");
+        assert_eq!(
+            sent.register_at(1, Some(1), other_recipient, start),
+            Registration::Conflict
+        );
+        assert_eq!(
+            sent.register_at(1, Some(2), digest, start),
+            Registration::Conflict
+        );
+    }
+
+    #[test]
+    fn resent_messages_are_pending_again() {
+        let digest = payload_digest(b"recipient", b"hello");
+        let sent = SentMessages::new(Duration::from_secs(60));
+        assert_eq!(sent.register(1, None, digest), Registration::New);
+        sent.record_outcome(&DeliveryEvent::Expired(1));
+
+        sent.resend(1);
+        assert_eq!(
+            sent.register(1, None, digest),
+            Registration::Duplicate(SendOutcome::Pending)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::ClientError;
+use futures::{SinkExt, StreamExt};
+use log::*;
+use nym_client_websocket_requests::{requests::ClientRequest, responses::ServerResponse};
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_tungstenite::{accept_async, tungstenite::protocol::Message as WsMessage};
+
+// delay before responding to the first failed unlock attempt. it doubles with every subsequent failure
+// (up to `MAXIMUM_UNLOCK_FAILURE_DELAY`) so that the passphrase couldn't be brute-forced through the socket
+const UNLOCK_FAILURE_DELAY: Duration = Duration::from_secs(1);
+const MAXIMUM_UNLOCK_FAILURE_DELAY: Duration = Duration::from_secs(60);
+
+fn unlock_failure_delay(failed_attempts: u32) -> Duration {
+    let exponent = failed_attempts.saturating_sub(1).min(16);
+    (UNLOCK_FAILURE_DELAY * 2u32.pow(exponent)).min(MAXIMUM_UNLOCK_FAILURE_DELAY)
+}
+
+/// Serves the websocket connections until a passphrase accepted by `try_unlock` is supplied
+/// through the `UnlockStorage` request. Any other request is rejected as the client is not running yet.
+/// The connection is closed once the storage is unlocked, so the application has to reconnect
+/// after the client has started. Every failed attempt delays the response, and as the connections
+/// are served one at a time, any further attempts, by an increasing amount of time.
+pub(crate) async fn wait_for_storage_unlock<T, F, Fut>(
+    host: IpAddr,
+    port: u16,
+    mut try_unlock: F,
+) -> Result<T, ClientError>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<T, ClientError>>,
+{
+    let address = SocketAddr::new(host, port);
+    let tcp_listener = TcpListener::bind(address).await?;
+    info!("Waiting for the storage passphrase to be supplied through the websocket on {address}");
+
+    let mut failed_attempts = 0u32;
+    loop {
+        let socket = match tcp_listener.accept().await {
+            Ok((socket, remote_addr)) => {
+                debug!("Received connection from {remote_addr:?}");
+                socket
+            }
+            Err(err) => {
+                warn!("failed to get client: {err}");
+                continue;
+            }
+        };
+        let mut ws_stream = match accept_async(socket).await {
+            Ok(ws_stream) => ws_stream,
+            Err(err) => {
+                warn!("error while performing the websocket handshake - {err}");
+                continue;
+            }
+        };
+
+        while let Some(msg) = ws_stream.next().await {
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(err) => {
+                    warn!("failed to read the websocket message - {err}");
+                    break;
+                }
+            };
+            let (request, binary) = match msg {
+                WsMessage::Binary(bin_msg) => (ClientRequest::try_from_binary(&bin_msg), true),
+                WsMessage::Text(text_msg) => (ClientRequest::try_from_text(text_msg), false),
+                WsMessage::Close(_) => break,
+                _ => continue,
+            };
+
+            let mut unlocked = None;
+            let response = match request {
+                Ok(ClientRequest::UnlockStorage { passphrase }) => {
+                    match try_unlock(passphrase).await {
+                        Ok(storage) => {
+                            unlocked = Some(storage);
+                            ServerResponse::StorageUnlocked
+                        }
+                        Err(err) => {
+                            warn!("failed to unlock the client storage - {err}");
+                            failed_attempts = failed_attempts.saturating_add(1);
+                            tokio::time::sleep(unlock_failure_delay(failed_attempts)).await;
+                            ServerResponse::new_error(format!(
+                                "failed to unlock the client storage: {err}"
+                            ))
+                        }
+                    }
+                }
+                Ok(_) => ServerResponse::new_error(
+                    "the client storage is locked - its passphrase has to be supplied first",
+                ),
+                Err(err) => ServerResponse::Error(err),
+            };
+
+            let response = if binary {
+                WsMessage::Binary(response.into_binary())
+            } else {
+                WsMessage::Text(response.into_text())
+            };
+            if let Err(err) = ws_stream.send(response).await {
+                warn!("failed to send the websocket response - {err}");
+            }
+
+            if let Some(storage) = unlocked {
+                ws_stream.close(None).await.ok();
+                info!("The client storage has been unlocked");
+                return Ok(storage);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_unlock_attempts_are_increasingly_delayed() {
+        assert_eq!(unlock_failure_delay(1), UNLOCK_FAILURE_DELAY);
+        assert_eq!(unlock_failure_delay(2), UNLOCK_FAILURE_DELAY * 2);
+        assert_eq!(unlock_failure_delay(3), UNLOCK_FAILURE_DELAY * 4);
+        assert_eq!(unlock_failure_delay(u32::MAX), MAXIMUM_UNLOCK_FAILURE_DELAY);
+    }
+}
@@ -30,6 +30,45 @@ enum ClientRequestTag {
 
     /// Value tag representing [`GetLaneQueueLength`] variant of the [`ClientRequest`]
     GetLaneQueueLength = 0x05,
+
+    /// Value tag representing [`Tracked`] variant of the [`ClientRequest`]
+    Tracked = 0x06,
+
+    /// Value tag representing [`GetDeadLetters`] variant of the [`ClientRequest`]
+    GetDeadLetters = 0x07,
+
+    /// Value tag representing [`RetryDeadLetter`] variant of the [`ClientRequest`]
+    RetryDeadLetter = 0x08,
+
+    /// Value tag representing [`DiscardDeadLetter`] variant of the [`ClientRequest`]
+    DiscardDeadLetter = 0x09,
+
+    /// Value tag representing [`GetLaneStatus`] variant of the [`ClientRequest`]
+    GetLaneStatus = 0x0A,
+
+    /// Value tag representing [`SubscribeLaneStatus`] variant of the [`ClientRequest`]
+    SubscribeLaneStatus = 0x0B,
+
+    /// Value tag representing [`Hibernate`] variant of the [`ClientRequest`]
+    Hibernate = 0x0C,
+
+    /// Value tag representing [`Wake`] variant of the [`ClientRequest`]
+    Wake = 0x0D,
+
+    /// Value tag representing [`GetTopologyAnomalies`] variant of the [`ClientRequest`]
+    GetTopologyAnomalies = 0x0E,
+
+    /// Value tag representing [`AcknowledgeTopologyAnomalies`] variant of the [`ClientRequest`]
+    AcknowledgeTopologyAnomalies = 0x0F,
+
+    /// Value tag representing [`GetBandwidthForecast`] variant of the [`ClientRequest`]
+    GetBandwidthForecast = 0x10,
+
+    /// Value tag representing [`SubscribeClientEvents`] variant of the [`ClientRequest`]
+    SubscribeClientEvents = 0x11,
+
+    /// Value tag representing [`UnlockStorage`] variant of the [`ClientRequest`]
+    UnlockStorage = 0x12,
 }
 
 impl TryFrom<u8> for ClientRequestTag {
@@ -43,6 +82,21 @@ impl TryFrom<u8> for ClientRequestTag {
             _ if value == (Self::SelfAddress as u8) => Ok(Self::SelfAddress),
             _ if value == (Self::ClosedConnection as u8) => Ok(Self::ClosedConnection),
             _ if value == (Self::GetLaneQueueLength as u8) => Ok(Self::GetLaneQueueLength),
+            _ if value == (Self::Tracked as u8) => Ok(Self::Tracked),
+            _ if value == (Self::GetDeadLetters as u8) => Ok(Self::GetDeadLetters),
+            _ if value == (Self::RetryDeadLetter as u8) => Ok(Self::RetryDeadLetter),
+            _ if value == (Self::DiscardDeadLetter as u8) => Ok(Self::DiscardDeadLetter),
+            _ if value == (Self::GetLaneStatus as u8) => Ok(Self::GetLaneStatus),
+            _ if value == (Self::SubscribeLaneStatus as u8) => Ok(Self::SubscribeLaneStatus),
+            _ if value == (Self::Hibernate as u8) => Ok(Self::Hibernate),
+            _ if value == (Self::Wake as u8) => Ok(Self::Wake),
+            _ if value == (Self::GetTopologyAnomalies as u8) => Ok(Self::GetTopologyAnomalies),
+            _ if value == (Self::AcknowledgeTopologyAnomalies as u8) => {
+                Ok(Self::AcknowledgeTopologyAnomalies)
+            }
+            _ if value == (Self::GetBandwidthForecast as u8) => Ok(Self::GetBandwidthForecast),
+            _ if value == (Self::SubscribeClientEvents as u8) => Ok(Self::SubscribeClientEvents),
+            _ if value == (Self::UnlockStorage as u8) => Ok(Self::UnlockStorage),
             n => Err(error::Error::new(
                 ErrorKind::UnknownRequest,
                 format!("{n} does not correspond to any valid request tag"),
@@ -94,6 +148,67 @@ pub enum ClientRequest {
     ClosedConnection(u64),
 
     GetLaneQueueLength(u64),
+
+    /// Wraps any of the `Send`, `SendAnonymous` or `Reply` requests indicating that
+    /// either `Delivered` or `DeliveryFailed` response with the specified `message_id`
+    /// should be sent back once the outcome of the message is known.
+    ///
+    /// Resubmitting a request with an already used `message_id` shortly afterwards
+    /// does not send the message again. Instead, the client responds as it did to the original
+    /// request, or with its outcome if it's already known.
+    Tracked {
+        message_id: u64,
+        request: Box<ClientRequest>,
+    },
+
+    /// Retrieves identifiers of all tracked messages that could not have been delivered
+    /// alongside the reasons for their failures.
+    GetDeadLetters,
+
+    /// Attempts to send the tracked message that could not have been delivered again,
+    /// using the same `message_id`.
+    RetryDeadLetter(u64),
+
+    /// Removes the tracked message that could not have been delivered from the dead letter queue
+    /// without attempting to send it again.
+    DiscardDeadLetter(u64),
+
+    /// Retrieves the queue length and the estimated drain time of the lane of the specified connection.
+    GetLaneStatus(u64),
+
+    /// Requests `LaneCongestion` responses to be pushed whenever the queue of any connection lane
+    /// reaches `pause_threshold` packets and, afterwards, once it drops to `resume_threshold`.
+    SubscribeLaneStatus {
+        pause_threshold: u64,
+        resume_threshold: u64,
+    },
+
+    /// Puts the client into the low-power mode in which no cover traffic is sent, the topology
+    /// is refreshed less often and only the gateway session is being kept alive.
+    Hibernate,
+
+    /// Brings the client out of the low-power mode and restores its full traffic shaping.
+    Wake,
+
+    /// Retrieves the suspicious changes of the network topology that haven't been acknowledged yet.
+    GetTopologyAnomalies,
+
+    /// Acknowledges all of the detected topology anomalies and resumes the traffic
+    /// if it has been paused because of them.
+    AcknowledgeTopologyAnomalies,
+
+    /// Retrieves the estimate of how long the bandwidth remaining at the gateway is going to last.
+    GetBandwidthForecast,
+
+    /// Asks the client to push its lifecycle events, such as gateway (re)connections,
+    /// topology refreshes or spent credentials, over this connection.
+    SubscribeClientEvents,
+
+    /// Supplies the passphrase of the encrypted client storage. It's only accepted if the client
+    /// is configured to wait for it on startup, before any other request can be handled.
+    UnlockStorage {
+        passphrase: String,
+    },
 }
 
 // we could have been parsing it directly TryFrom<WsMessage>, but we want to retain
@@ -382,6 +497,283 @@ impl ClientRequest {
         Ok(ClientRequest::GetLaneQueueLength(connection_id))
     }
 
+    // TRACKED_REQUEST_TAG || message_id || inner_request
+    fn serialize_tracked(message_id: u64, request: ClientRequest) -> Vec<u8> {
+        std::iter::once(ClientRequestTag::Tracked as u8)
+            .chain(message_id.to_be_bytes().into_iter())
+            .chain(request.serialize().into_iter())
+            .collect()
+    }
+
+    // TRACKED_REQUEST_TAG || message_id || inner_request
+    fn deserialize_tracked(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() < 1 + size_of::<u64>() + 1 {
+            return Err(error::Error::new(
+                ErrorKind::TooShortRequest,
+                "not enough data provided to recover 'tracked'".to_string(),
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ClientRequestTag::Tracked as u8);
+
+        let message_id = u64::from_be_bytes(b[1..1 + size_of::<u64>()].try_into().unwrap());
+        let inner = &b[1 + size_of::<u64>()..];
+
+        // check it explicitly before attempting to recover the inner request
+        // so that we wouldn't recurse arbitrarily deep on malicious data
+        if inner[0] == ClientRequestTag::Tracked as u8 {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                "tracked requests can't be nested".to_string(),
+            ));
+        }
+
+        Self::new_tracked(message_id, Self::deserialize(inner)?)
+    }
+
+    // GET_DEAD_LETTERS_REQUEST_TAG
+    fn serialize_get_dead_letters() -> Vec<u8> {
+        vec![ClientRequestTag::GetDeadLetters as u8]
+    }
+
+    // GET_DEAD_LETTERS_REQUEST_TAG
+    fn deserialize_get_dead_letters(b: &[u8]) -> Result<Self, error::Error> {
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ClientRequestTag::GetDeadLetters as u8);
+
+        Ok(ClientRequest::GetDeadLetters)
+    }
+
+    // RETRY_DEAD_LETTER_REQUEST_TAG || message_id
+    // DISCARD_DEAD_LETTER_REQUEST_TAG || message_id
+    fn serialize_dead_letter_request(tag: ClientRequestTag, message_id: u64) -> Vec<u8> {
+        std::iter::once(tag as u8)
+            .chain(message_id.to_be_bytes().into_iter())
+            .collect()
+    }
+
+    // RETRY_DEAD_LETTER_REQUEST_TAG || message_id
+    // DISCARD_DEAD_LETTER_REQUEST_TAG || message_id
+    fn deserialize_dead_letter_message_id(b: &[u8]) -> Result<u64, error::Error> {
+        if b.len() != 1 + size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                "The received dead letter request has invalid length",
+            ));
+        }
+
+        Ok(u64::from_be_bytes(b[1..].try_into().unwrap()))
+    }
+
+    // GET_LANE_STATUS_REQUEST_TAG || conn_id
+    fn serialize_get_lane_status(connection_id: u64) -> Vec<u8> {
+        std::iter::once(ClientRequestTag::GetLaneStatus as u8)
+            .chain(connection_id.to_be_bytes().into_iter())
+            .collect()
+    }
+
+    // GET_LANE_STATUS_REQUEST_TAG || conn_id
+    fn deserialize_get_lane_status(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 1 + size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                "The received get lane status has invalid length",
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ClientRequestTag::GetLaneStatus as u8);
+
+        let connection_id = u64::from_be_bytes(b[1..].try_into().unwrap());
+        Ok(ClientRequest::GetLaneStatus(connection_id))
+    }
+
+    // SUBSCRIBE_LANE_STATUS_REQUEST_TAG || pause_threshold || resume_threshold
+    fn serialize_subscribe_lane_status(pause_threshold: u64, resume_threshold: u64) -> Vec<u8> {
+        std::iter::once(ClientRequestTag::SubscribeLaneStatus as u8)
+            .chain(pause_threshold.to_be_bytes().into_iter())
+            .chain(resume_threshold.to_be_bytes().into_iter())
+            .collect()
+    }
+
+    // SUBSCRIBE_LANE_STATUS_REQUEST_TAG || pause_threshold || resume_threshold
+    fn deserialize_subscribe_lane_status(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 1 + 2 * size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                "The received lane status subscription has invalid length",
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ClientRequestTag::SubscribeLaneStatus as u8);
+
+        let pause_threshold = u64::from_be_bytes(b[1..1 + size_of::<u64>()].try_into().unwrap());
+        let resume_threshold = u64::from_be_bytes(b[1 + size_of::<u64>()..].try_into().unwrap());
+
+        Self::new_lane_status_subscription(pause_threshold, resume_threshold)
+    }
+
+    // HIBERNATE_REQUEST_TAG
+    // WAKE_REQUEST_TAG
+    fn serialize_hibernation_request(tag: ClientRequestTag) -> Vec<u8> {
+        vec![tag as u8]
+    }
+
+    // HIBERNATE_REQUEST_TAG
+    // WAKE_REQUEST_TAG
+    fn deserialize_hibernation_request(b: &[u8], request: Self) -> Result<Self, error::Error> {
+        if b.len() != 1 {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                "The received hibernation request has invalid length",
+            ));
+        }
+
+        Ok(request)
+    }
+
+    // GET_TOPOLOGY_ANOMALIES_REQUEST_TAG
+    // ACKNOWLEDGE_TOPOLOGY_ANOMALIES_REQUEST_TAG
+    fn serialize_topology_anomalies_request(tag: ClientRequestTag) -> Vec<u8> {
+        vec![tag as u8]
+    }
+
+    // GET_TOPOLOGY_ANOMALIES_REQUEST_TAG
+    // ACKNOWLEDGE_TOPOLOGY_ANOMALIES_REQUEST_TAG
+    fn deserialize_topology_anomalies_request(
+        b: &[u8],
+        request: Self,
+    ) -> Result<Self, error::Error> {
+        if b.len() != 1 {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                "The received topology anomalies request has invalid length",
+            ));
+        }
+
+        Ok(request)
+    }
+
+    // GET_BANDWIDTH_FORECAST_REQUEST_TAG
+    fn serialize_get_bandwidth_forecast() -> Vec<u8> {
+        vec![ClientRequestTag::GetBandwidthForecast as u8]
+    }
+
+    // GET_BANDWIDTH_FORECAST_REQUEST_TAG
+    fn deserialize_get_bandwidth_forecast(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 1 {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                "The received bandwidth forecast request has invalid length",
+            ));
+        }
+
+        Ok(ClientRequest::GetBandwidthForecast)
+    }
+
+    // SUBSCRIBE_CLIENT_EVENTS_REQUEST_TAG
+    fn serialize_subscribe_client_events() -> Vec<u8> {
+        vec![ClientRequestTag::SubscribeClientEvents as u8]
+    }
+
+    // SUBSCRIBE_CLIENT_EVENTS_REQUEST_TAG
+    fn deserialize_subscribe_client_events(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 1 {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                "The received client events subscription has invalid length",
+            ));
+        }
+
+        Ok(ClientRequest::SubscribeClientEvents)
+    }
+
+    // UNLOCK_STORAGE_REQUEST_TAG || passphrase_len || passphrase
+    fn serialize_unlock_storage(passphrase: String) -> Vec<u8> {
+        let passphrase_len_bytes = (passphrase.len() as u64).to_be_bytes();
+        std::iter::once(ClientRequestTag::UnlockStorage as u8)
+            .chain(passphrase_len_bytes.into_iter())
+            .chain(passphrase.into_bytes().into_iter())
+            .collect()
+    }
+
+    // UNLOCK_STORAGE_REQUEST_TAG || passphrase_len || passphrase
+    fn deserialize_unlock_storage(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() < 1 + size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::TooShortRequest,
+                "not enough data provided to recover 'unlock storage'".to_string(),
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ClientRequestTag::UnlockStorage as u8);
+
+        let passphrase_len = u64::from_be_bytes(b[1..1 + size_of::<u64>()].try_into().unwrap());
+        let passphrase = &b[1 + size_of::<u64>()..];
+        if passphrase.len() as u64 != passphrase_len {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                format!(
+                    "passphrase len has inconsistent length. specified: {} got: {}",
+                    passphrase_len,
+                    passphrase.len()
+                ),
+            ));
+        }
+
+        let passphrase = String::from_utf8(passphrase.to_vec()).map_err(|_| {
+            error::Error::new(
+                ErrorKind::MalformedRequest,
+                "the passphrase is not valid utf8".to_string(),
+            )
+        })?;
+
+        Ok(ClientRequest::UnlockStorage { passphrase })
+    }
+
+    pub fn new_lane_status_subscription(
+        pause_threshold: u64,
+        resume_threshold: u64,
+    ) -> Result<Self, error::Error> {
+        if resume_threshold >= pause_threshold {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                "the resume threshold must be lower than the pause threshold".to_string(),
+            ));
+        }
+
+        Ok(ClientRequest::SubscribeLaneStatus {
+            pause_threshold,
+            resume_threshold,
+        })
+    }
+
+    pub fn new_tracked(message_id: u64, request: ClientRequest) -> Result<Self, error::Error> {
+        if !request.is_trackable() {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                "only 'send', 'sendAnonymous' and 'reply' requests can be tracked".to_string(),
+            ));
+        }
+
+        Ok(ClientRequest::Tracked {
+            message_id,
+            request: Box::new(request),
+        })
+    }
+
+    fn is_trackable(&self) -> bool {
+        matches!(
+            self,
+            ClientRequest::Send { .. }
+                | ClientRequest::SendAnonymous { .. }
+                | ClientRequest::Reply { .. }
+        )
+    }
+
     pub fn serialize(self) -> Vec<u8> {
         match self {
             ClientRequest::Send {
@@ -408,6 +800,52 @@ impl ClientRequest {
             ClientRequest::ClosedConnection(id) => Self::serialize_closed_connection(id),
 
             ClientRequest::GetLaneQueueLength(id) => Self::serialize_get_lane_queue_lengths(id),
+
+            ClientRequest::Tracked {
+                message_id,
+                request,
+            } => Self::serialize_tracked(message_id, *request),
+
+            ClientRequest::GetDeadLetters => Self::serialize_get_dead_letters(),
+
+            ClientRequest::RetryDeadLetter(message_id) => {
+                Self::serialize_dead_letter_request(ClientRequestTag::RetryDeadLetter, message_id)
+            }
+
+            ClientRequest::DiscardDeadLetter(message_id) => {
+                Self::serialize_dead_letter_request(ClientRequestTag::DiscardDeadLetter, message_id)
+            }
+
+            ClientRequest::GetLaneStatus(id) => Self::serialize_get_lane_status(id),
+
+            ClientRequest::SubscribeLaneStatus {
+                pause_threshold,
+                resume_threshold,
+            } => Self::serialize_subscribe_lane_status(pause_threshold, resume_threshold),
+
+            ClientRequest::Hibernate => {
+                Self::serialize_hibernation_request(ClientRequestTag::Hibernate)
+            }
+
+            ClientRequest::Wake => Self::serialize_hibernation_request(ClientRequestTag::Wake),
+
+            ClientRequest::GetTopologyAnomalies => {
+                Self::serialize_topology_anomalies_request(ClientRequestTag::GetTopologyAnomalies)
+            }
+
+            ClientRequest::AcknowledgeTopologyAnomalies => {
+                Self::serialize_topology_anomalies_request(
+                    ClientRequestTag::AcknowledgeTopologyAnomalies,
+                )
+            }
+
+            ClientRequest::GetBandwidthForecast => Self::serialize_get_bandwidth_forecast(),
+
+            ClientRequest::SubscribeClientEvents => Self::serialize_subscribe_client_events(),
+
+            ClientRequest::UnlockStorage { passphrase } => {
+                Self::serialize_unlock_storage(passphrase)
+            }
         }
     }
 
@@ -431,6 +869,32 @@ impl ClientRequest {
             ClientRequestTag::SelfAddress => Self::deserialize_self_address(b),
             ClientRequestTag::ClosedConnection => Self::deserialize_closed_connection(b),
             ClientRequestTag::GetLaneQueueLength => Self::deserialize_get_lane_queue_length(b),
+            ClientRequestTag::Tracked => Self::deserialize_tracked(b),
+            ClientRequestTag::GetDeadLetters => Self::deserialize_get_dead_letters(b),
+            ClientRequestTag::RetryDeadLetter => {
+                Self::deserialize_dead_letter_message_id(b).map(ClientRequest::RetryDeadLetter)
+            }
+            ClientRequestTag::DiscardDeadLetter => {
+                Self::deserialize_dead_letter_message_id(b).map(ClientRequest::DiscardDeadLetter)
+            }
+            ClientRequestTag::GetLaneStatus => Self::deserialize_get_lane_status(b),
+            ClientRequestTag::SubscribeLaneStatus => Self::deserialize_subscribe_lane_status(b),
+            ClientRequestTag::Hibernate => {
+                Self::deserialize_hibernation_request(b, ClientRequest::Hibernate)
+            }
+            ClientRequestTag::Wake => Self::deserialize_hibernation_request(b, ClientRequest::Wake),
+            ClientRequestTag::GetTopologyAnomalies => {
+                Self::deserialize_topology_anomalies_request(b, ClientRequest::GetTopologyAnomalies)
+            }
+            ClientRequestTag::AcknowledgeTopologyAnomalies => {
+                Self::deserialize_topology_anomalies_request(
+                    b,
+                    ClientRequest::AcknowledgeTopologyAnomalies,
+                )
+            }
+            ClientRequestTag::GetBandwidthForecast => Self::deserialize_get_bandwidth_forecast(b),
+            ClientRequestTag::SubscribeClientEvents => Self::deserialize_subscribe_client_events(b),
+            ClientRequestTag::UnlockStorage => Self::deserialize_unlock_storage(b),
         }
     }
 
@@ -555,6 +1019,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn tracked_request_serialization_works() {
+        let reply_request = ClientRequest::Reply {
+            sender_tag: [8u8; SENDER_TAG_SIZE].into(),
+            message: b"foomp".to_vec(),
+            connection_id: None,
+        };
+        let tracked_request = ClientRequest::new_tracked(42, reply_request).unwrap();
+
+        let bytes = tracked_request.serialize();
+        let recovered = ClientRequest::deserialize(&bytes).unwrap();
+        match recovered {
+            ClientRequest::Tracked {
+                message_id,
+                request,
+            } => {
+                assert_eq!(message_id, 42);
+                match *request {
+                    ClientRequest::Reply {
+                        sender_tag,
+                        message,
+                        connection_id,
+                    } => {
+                        assert_eq!(sender_tag, [8u8; SENDER_TAG_SIZE].into());
+                        assert_eq!(message, b"foomp".to_vec());
+                        assert!(connection_id.is_none());
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn only_message_requests_can_be_tracked() {
+        assert!(ClientRequest::new_tracked(42, ClientRequest::SelfAddress).is_err());
+
+        // nested tracking is not allowed either
+        let mut bytes = vec![ClientRequestTag::Tracked as u8];
+        bytes.extend_from_slice(&1u64.to_be_bytes());
+        bytes.push(ClientRequestTag::Tracked as u8);
+        bytes.extend_from_slice(&2u64.to_be_bytes());
+        bytes.push(ClientRequestTag::SelfAddress as u8);
+        assert!(ClientRequest::deserialize(&bytes).is_err());
+    }
+
     #[test]
     fn get_lane_queue_length_request_serialization_works() {
         let close_connection_request = ClientRequest::GetLaneQueueLength(42);
@@ -565,4 +1076,134 @@ mod tests {
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn dead_letter_requests_serialization_works() {
+        let bytes = ClientRequest::GetDeadLetters.serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::GetDeadLetters => (),
+            _ => unreachable!(),
+        }
+
+        let bytes = ClientRequest::RetryDeadLetter(42).serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::RetryDeadLetter(message_id) => assert_eq!(message_id, 42),
+            _ => unreachable!(),
+        }
+
+        let bytes = ClientRequest::DiscardDeadLetter(42).serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::DiscardDeadLetter(message_id) => assert_eq!(message_id, 42),
+            _ => unreachable!(),
+        }
+        assert!(ClientRequest::deserialize(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn lane_status_requests_serialization_works() {
+        let bytes = ClientRequest::GetLaneStatus(42).serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::GetLaneStatus(id) => assert_eq!(id, 42),
+            _ => unreachable!(),
+        }
+
+        let bytes = ClientRequest::new_lane_status_subscription(100, 10)
+            .unwrap()
+            .serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::SubscribeLaneStatus {
+                pause_threshold,
+                resume_threshold,
+            } => {
+                assert_eq!(pause_threshold, 100);
+                assert_eq!(resume_threshold, 10)
+            }
+            _ => unreachable!(),
+        }
+
+        // the thresholds have to make sense
+        assert!(ClientRequest::new_lane_status_subscription(10, 10).is_err());
+        let bytes = ClientRequest::SubscribeLaneStatus {
+            pause_threshold: 10,
+            resume_threshold: 100,
+        }
+        .serialize();
+        assert!(ClientRequest::deserialize(&bytes).is_err());
+    }
+
+    #[test]
+    fn hibernation_requests_serialization_works() {
+        let bytes = ClientRequest::Hibernate.serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::Hibernate => (),
+            _ => unreachable!(),
+        }
+
+        let mut bytes = ClientRequest::Wake.serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::Wake => (),
+            _ => unreachable!(),
+        }
+
+        bytes.push(42);
+        assert!(ClientRequest::deserialize(&bytes).is_err());
+    }
+
+    #[test]
+    fn topology_anomalies_requests_serialization_works() {
+        let bytes = ClientRequest::GetTopologyAnomalies.serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::GetTopologyAnomalies => (),
+            _ => unreachable!(),
+        }
+
+        let mut bytes = ClientRequest::AcknowledgeTopologyAnomalies.serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::AcknowledgeTopologyAnomalies => (),
+            _ => unreachable!(),
+        }
+
+        bytes.push(42);
+        assert!(ClientRequest::deserialize(&bytes).is_err());
+    }
+
+    #[test]
+    fn bandwidth_forecast_request_serialization_works() {
+        let mut bytes = ClientRequest::GetBandwidthForecast.serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::GetBandwidthForecast => (),
+            _ => unreachable!(),
+        }
+
+        bytes.push(42);
+        assert!(ClientRequest::deserialize(&bytes).is_err());
+    }
+
+    #[test]
+    fn client_events_subscription_serialization_works() {
+        let mut bytes = ClientRequest::SubscribeClientEvents.serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::SubscribeClientEvents => (),
+            _ => unreachable!(),
+        }
+
+        bytes.push(42);
+        assert!(ClientRequest::deserialize(&bytes).is_err());
+    }
+
+    #[test]
+    fn unlock_storage_request_serialization_works() {
+        let mut bytes = ClientRequest::UnlockStorage {
+            passphrase: "foomp".to_string(),
+        }
+        .serialize();
+        match ClientRequest::deserialize(&bytes).unwrap() {
+            ClientRequest::UnlockStorage { passphrase } => assert_eq!(passphrase, "foomp"),
+            _ => unreachable!(),
+        }
+
+        bytes.push(42);
+        assert!(ClientRequest::deserialize(&bytes).is_err());
+        assert!(ClientRequest::deserialize(&bytes[..5]).is_err());
+    }
 }
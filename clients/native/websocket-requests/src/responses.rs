@@ -11,6 +11,7 @@ use nym_sphinx::anonymous_replies::requests::{AnonymousSenderTag, SENDER_TAG_SIZ
 use nym_sphinx::receiver::ReconstructedMessage;
 use std::convert::TryInto;
 use std::mem::size_of;
+use std::time::Duration;
 
 #[repr(u8)]
 enum ServerResponseTag {
@@ -25,6 +26,39 @@ enum ServerResponseTag {
 
     /// Value tag representing [`LaneQueueLength`] variant of the [`ServerResponse`]
     LaneQueueLength = 0x03,
+
+    /// Value tag representing [`Delivered`] variant of the [`ServerResponse`]
+    Delivered = 0x04,
+
+    /// Value tag representing [`DeliveryFailed`] variant of the [`ServerResponse`]
+    DeliveryFailed = 0x05,
+
+    /// Value tag representing [`DeadLetters`] variant of the [`ServerResponse`]
+    DeadLetters = 0x06,
+
+    /// Value tag representing [`LaneStatus`] variant of the [`ServerResponse`]
+    LaneStatus = 0x07,
+
+    /// Value tag representing [`LaneCongestion`] variant of the [`ServerResponse`]
+    LaneCongestion = 0x08,
+
+    /// Value tag representing [`Hibernation`] variant of the [`ServerResponse`]
+    Hibernation = 0x09,
+
+    /// Value tag representing [`TopologyAnomalies`] variant of the [`ServerResponse`]
+    TopologyAnomalies = 0x0A,
+
+    /// Value tag representing [`BandwidthForecast`] variant of the [`ServerResponse`]
+    BandwidthForecast = 0x0B,
+
+    /// Value tag representing [`ClientEvent`] variant of the [`ServerResponse`]
+    ClientEvent = 0x0C,
+
+    /// Value tag representing [`StorageUnlocked`] variant of the [`ServerResponse`]
+    StorageUnlocked = 0x0D,
+
+    /// Value tag representing [`BandwidthWarning`] variant of the [`ServerResponse`]
+    BandwidthWarning = 0x0E,
 }
 
 impl TryFrom<u8> for ServerResponseTag {
@@ -36,6 +70,17 @@ impl TryFrom<u8> for ServerResponseTag {
             _ if value == (Self::Received as u8) => Ok(Self::Received),
             _ if value == (Self::SelfAddress as u8) => Ok(Self::SelfAddress),
             _ if value == (Self::LaneQueueLength as u8) => Ok(Self::LaneQueueLength),
+            _ if value == (Self::Delivered as u8) => Ok(Self::Delivered),
+            _ if value == (Self::DeliveryFailed as u8) => Ok(Self::DeliveryFailed),
+            _ if value == (Self::DeadLetters as u8) => Ok(Self::DeadLetters),
+            _ if value == (Self::LaneStatus as u8) => Ok(Self::LaneStatus),
+            _ if value == (Self::LaneCongestion as u8) => Ok(Self::LaneCongestion),
+            _ if value == (Self::Hibernation as u8) => Ok(Self::Hibernation),
+            _ if value == (Self::TopologyAnomalies as u8) => Ok(Self::TopologyAnomalies),
+            _ if value == (Self::BandwidthForecast as u8) => Ok(Self::BandwidthForecast),
+            _ if value == (Self::ClientEvent as u8) => Ok(Self::ClientEvent),
+            _ if value == (Self::StorageUnlocked as u8) => Ok(Self::StorageUnlocked),
+            _ if value == (Self::BandwidthWarning as u8) => Ok(Self::BandwidthWarning),
             n => Err(error::Error::new(
                 ErrorKind::UnknownResponse,
                 format!("{n} does not correspond to any valid response tag"),
@@ -44,11 +89,213 @@ impl TryFrom<u8> for ServerResponseTag {
     }
 }
 
+/// Tracked message that could not have been delivered and is retained by the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLetterInfo {
+    pub message_id: u64,
+    pub reason: String,
+}
+
+/// State of the transmission lane of a particular connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaneStatusInfo {
+    pub lane: u64,
+
+    /// Number of packets waiting to be sent out, including the ones waiting for reply SURBs.
+    pub queue_length: usize,
+
+    /// Rough estimate of the time required for sending out all of the queued up packets.
+    pub estimated_drain_time: Duration,
+}
+
+/// Lifecycle event of the client alongside its details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientEventInfo {
+    GatewayConnected { gateway: String },
+    GatewayDisconnected { reason: String },
+    TopologyRefreshed { mixnodes: u64, gateways: u64 },
+    CredentialSpent { remaining_bandwidth: i64 },
+}
+
+impl ClientEventInfo {
+    const GATEWAY_CONNECTED: u8 = 0x00;
+    const GATEWAY_DISCONNECTED: u8 = 0x01;
+    const TOPOLOGY_REFRESHED: u8 = 0x02;
+    const CREDENTIAL_SPENT: u8 = 0x03;
+
+    fn string_bytes(kind: u8, value: String) -> Vec<u8> {
+        std::iter::once(kind)
+            .chain((value.len() as u64).to_be_bytes().into_iter())
+            .chain(value.into_bytes().into_iter())
+            .collect()
+    }
+
+    // GATEWAY_CONNECTED || gateway_len || gateway
+    // GATEWAY_DISCONNECTED || reason_len || reason
+    // TOPOLOGY_REFRESHED || mixnodes || gateways
+    // CREDENTIAL_SPENT || remaining_bandwidth
+    fn to_bytes(self) -> Vec<u8> {
+        match self {
+            ClientEventInfo::GatewayConnected { gateway } => {
+                Self::string_bytes(Self::GATEWAY_CONNECTED, gateway)
+            }
+            ClientEventInfo::GatewayDisconnected { reason } => {
+                Self::string_bytes(Self::GATEWAY_DISCONNECTED, reason)
+            }
+            ClientEventInfo::TopologyRefreshed { mixnodes, gateways } => {
+                std::iter::once(Self::TOPOLOGY_REFRESHED)
+                    .chain(mixnodes.to_be_bytes().into_iter())
+                    .chain(gateways.to_be_bytes().into_iter())
+                    .collect()
+            }
+            ClientEventInfo::CredentialSpent {
+                remaining_bandwidth,
+            } => std::iter::once(Self::CREDENTIAL_SPENT)
+                .chain(remaining_bandwidth.to_be_bytes().into_iter())
+                .collect(),
+        }
+    }
+
+    fn try_from_bytes(b: &[u8]) -> Result<Self, error::Error> {
+        let malformed = |message: &str| {
+            error::Error::new(
+                ErrorKind::MalformedResponse,
+                format!("malformed client event: {message}"),
+            )
+        };
+        let string = |b: &[u8]| {
+            if b.len() < size_of::<u64>() {
+                return Err(malformed("too short"));
+            }
+            let len = u64::from_be_bytes(b[..size_of::<u64>()].try_into().unwrap());
+            if (b.len() - size_of::<u64>()) as u64 != len {
+                return Err(malformed("invalid length"));
+            }
+            String::from_utf8(b[size_of::<u64>()..].to_vec())
+                .map_err(|err| malformed(&err.to_string()))
+        };
+
+        let Some((&kind, fields)) = b.split_first() else {
+            return Err(malformed("too short"));
+        };
+        match kind {
+            Self::GATEWAY_CONNECTED => Ok(ClientEventInfo::GatewayConnected {
+                gateway: string(fields)?,
+            }),
+            Self::GATEWAY_DISCONNECTED => Ok(ClientEventInfo::GatewayDisconnected {
+                reason: string(fields)?,
+            }),
+            Self::TOPOLOGY_REFRESHED => {
+                if fields.len() != 2 * size_of::<u64>() {
+                    return Err(malformed("invalid length"));
+                }
+                Ok(ClientEventInfo::TopologyRefreshed {
+                    mixnodes: u64::from_be_bytes(fields[..size_of::<u64>()].try_into().unwrap()),
+                    gateways: u64::from_be_bytes(fields[size_of::<u64>()..].try_into().unwrap()),
+                })
+            }
+            Self::CREDENTIAL_SPENT => {
+                if fields.len() != size_of::<i64>() {
+                    return Err(malformed("invalid length"));
+                }
+                Ok(ClientEventInfo::CredentialSpent {
+                    remaining_bandwidth: i64::from_be_bytes(fields.try_into().unwrap()),
+                })
+            }
+            n => Err(malformed(&format!("unknown event kind {n}"))),
+        }
+    }
+}
+
+// lane || queue_length || estimated_drain_time_ms
+const LANE_STATUS_INFO_SIZE: usize = 3 * size_of::<u64>();
+
+impl LaneStatusInfo {
+    // lane || queue_length || estimated_drain_time_ms
+    fn to_bytes(self) -> Vec<u8> {
+        self.lane
+            .to_be_bytes()
+            .into_iter()
+            .chain((self.queue_length as u64).to_be_bytes().into_iter())
+            .chain(
+                (self.estimated_drain_time.as_millis() as u64)
+                    .to_be_bytes()
+                    .into_iter(),
+            )
+            .collect()
+    }
+
+    // lane || queue_length || estimated_drain_time_ms
+    fn from_bytes(b: &[u8]) -> Self {
+        debug_assert_eq!(b.len(), LANE_STATUS_INFO_SIZE);
+
+        let lane = u64::from_be_bytes(b[..size_of::<u64>()].try_into().unwrap());
+        let queue_length = u64::from_be_bytes(
+            b[size_of::<u64>()..2 * size_of::<u64>()]
+                .try_into()
+                .unwrap(),
+        );
+        let drain_time_ms = u64::from_be_bytes(b[2 * size_of::<u64>()..].try_into().unwrap());
+
+        LaneStatusInfo {
+            lane,
+            queue_length: queue_length as usize,
+            estimated_drain_time: Duration::from_millis(drain_time_ms),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ServerResponse {
     Received(ReconstructedMessage),
     SelfAddress(Box<Recipient>),
-    LaneQueueLength { lane: u64, queue_length: usize },
+    LaneQueueLength {
+        lane: u64,
+        queue_length: usize,
+    },
+    Delivered {
+        message_id: u64,
+    },
+    DeliveryFailed {
+        message_id: u64,
+        reason: String,
+    },
+    DeadLetters(Vec<DeadLetterInfo>),
+    LaneStatus(LaneStatusInfo),
+    /// Pushed to the subscribed clients once the queue of a lane crosses one of the thresholds,
+    /// so that they could stop (or resume) reading from their local sockets.
+    LaneCongestion {
+        congested: bool,
+        status: LaneStatusInfo,
+    },
+    /// Current hibernation state of the client, sent in response to `Hibernate` and `Wake` requests.
+    Hibernation {
+        hibernating: bool,
+    },
+    /// Descriptions of the suspicious topology changes that haven't been acknowledged yet.
+    /// It's also pushed to the client whenever new anomalies are detected.
+    TopologyAnomalies {
+        traffic_paused: bool,
+        anomalies: Vec<String>,
+    },
+    /// Estimate of how long the bandwidth remaining at the gateway is going to last at the current
+    /// consumption rate (in bytes per second).
+    BandwidthForecast {
+        remaining: i64,
+        consumption_rate: u64,
+        time_left: Option<Duration>,
+        running_low: bool,
+    },
+    /// Unsolicited [`BandwidthForecast`] pushed to the client once the bandwidth starts running low.
+    BandwidthWarning {
+        remaining: i64,
+        consumption_rate: u64,
+        time_left: Option<Duration>,
+    },
+    /// Lifecycle event of the client pushed to the connections that subscribed to them.
+    ClientEvent(ClientEventInfo),
+    /// Sent in response to `UnlockStorage` once the supplied passphrase has been accepted.
+    StorageUnlocked,
     Error(error::Error),
 }
 
@@ -191,6 +438,13 @@ impl ServerResponse {
 
     // LANE_QUEUE_LENGTH_RESPONSE_TAG || lane || queue_length
     fn deserialize_lane_queue_length(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 1 + size_of::<u64>() + size_of::<usize>() {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "The received lane queue length has invalid length",
+            ));
+        }
+
         // this MUST match because it was called by 'deserialize'
         debug_assert_eq!(b[0], ServerResponseTag::LaneQueueLength as u8);
 
@@ -206,6 +460,462 @@ impl ServerResponse {
         Ok(ServerResponse::LaneQueueLength { lane, queue_length })
     }
 
+    // LANE_STATUS_RESPONSE_TAG || lane || queue_length || estimated_drain_time_ms
+    fn serialize_lane_status(status: LaneStatusInfo) -> Vec<u8> {
+        std::iter::once(ServerResponseTag::LaneStatus as u8)
+            .chain(status.to_bytes().into_iter())
+            .collect()
+    }
+
+    // LANE_STATUS_RESPONSE_TAG || lane || queue_length || estimated_drain_time_ms
+    fn deserialize_lane_status(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 1 + LANE_STATUS_INFO_SIZE {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "The received lane status has invalid length",
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ServerResponseTag::LaneStatus as u8);
+
+        Ok(ServerResponse::LaneStatus(LaneStatusInfo::from_bytes(
+            &b[1..],
+        )))
+    }
+
+    // LANE_CONGESTION_RESPONSE_TAG || 1 | 0 indicating congestion || lane || queue_length || estimated_drain_time_ms
+    fn serialize_lane_congestion(congested: bool, status: LaneStatusInfo) -> Vec<u8> {
+        std::iter::once(ServerResponseTag::LaneCongestion as u8)
+            .chain(std::iter::once(congested as u8))
+            .chain(status.to_bytes().into_iter())
+            .collect()
+    }
+
+    // LANE_CONGESTION_RESPONSE_TAG || 1 | 0 indicating congestion || lane || queue_length || estimated_drain_time_ms
+    fn deserialize_lane_congestion(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 2 + LANE_STATUS_INFO_SIZE {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "The received lane congestion has invalid length",
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ServerResponseTag::LaneCongestion as u8);
+
+        let congested = match b[1] {
+            0 => false,
+            1 => true,
+            n => {
+                return Err(error::Error::new(
+                    ErrorKind::MalformedResponse,
+                    format!("invalid congestion flag {n}"),
+                ))
+            }
+        };
+
+        Ok(ServerResponse::LaneCongestion {
+            congested,
+            status: LaneStatusInfo::from_bytes(&b[2..]),
+        })
+    }
+
+    // STORAGE_UNLOCKED_RESPONSE_TAG
+    fn serialize_storage_unlocked() -> Vec<u8> {
+        vec![ServerResponseTag::StorageUnlocked as u8]
+    }
+
+    // STORAGE_UNLOCKED_RESPONSE_TAG
+    fn deserialize_storage_unlocked(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 1 {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "The received storage unlocked response has invalid length",
+            ));
+        }
+
+        Ok(ServerResponse::StorageUnlocked)
+    }
+
+    // HIBERNATION_RESPONSE_TAG || 1 | 0 indicating hibernation
+    fn serialize_hibernation(hibernating: bool) -> Vec<u8> {
+        vec![ServerResponseTag::Hibernation as u8, hibernating as u8]
+    }
+
+    // HIBERNATION_RESPONSE_TAG || 1 | 0 indicating hibernation
+    fn deserialize_hibernation(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 2 {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "The received hibernation response has invalid length",
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ServerResponseTag::Hibernation as u8);
+
+        let hibernating = match b[1] {
+            0 => false,
+            1 => true,
+            n => {
+                return Err(error::Error::new(
+                    ErrorKind::MalformedResponse,
+                    format!("invalid hibernation flag {n}"),
+                ))
+            }
+        };
+
+        Ok(ServerResponse::Hibernation { hibernating })
+    }
+
+    // TOPOLOGY_ANOMALIES_RESPONSE_TAG || 1 | 0 indicating paused traffic || num_anomalies || (anomaly_len || anomaly)*
+    fn serialize_topology_anomalies(traffic_paused: bool, anomalies: Vec<String>) -> Vec<u8> {
+        let num_anomalies_bytes = (anomalies.len() as u64).to_be_bytes();
+        [
+            ServerResponseTag::TopologyAnomalies as u8,
+            traffic_paused as u8,
+        ]
+        .into_iter()
+        .chain(num_anomalies_bytes.into_iter())
+        .chain(anomalies.into_iter().flat_map(|anomaly| {
+            let anomaly_len_bytes = (anomaly.len() as u64).to_be_bytes();
+            anomaly_len_bytes
+                .into_iter()
+                .chain(anomaly.into_bytes().into_iter())
+        }))
+        .collect()
+    }
+
+    // TOPOLOGY_ANOMALIES_RESPONSE_TAG || 1 | 0 indicating paused traffic || num_anomalies || (anomaly_len || anomaly)*
+    fn deserialize_topology_anomalies(b: &[u8]) -> Result<Self, error::Error> {
+        let too_short = || {
+            error::Error::new(
+                ErrorKind::TooShortResponse,
+                "not enough data provided to recover 'topology anomalies'".to_string(),
+            )
+        };
+
+        if b.len() < 2 + size_of::<u64>() {
+            return Err(too_short());
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ServerResponseTag::TopologyAnomalies as u8);
+
+        let traffic_paused = match b[1] {
+            0 => false,
+            1 => true,
+            n => {
+                return Err(error::Error::new(
+                    ErrorKind::MalformedResponse,
+                    format!("invalid paused traffic flag {n}"),
+                ))
+            }
+        };
+        let num_anomalies = u64::from_be_bytes(b[2..2 + size_of::<u64>()].try_into().unwrap());
+
+        // don't trust the declared number for the allocation
+        let mut anomalies = Vec::new();
+        let mut remaining = &b[2 + size_of::<u64>()..];
+        for _ in 0..num_anomalies {
+            if remaining.len() < size_of::<u64>() {
+                return Err(too_short());
+            }
+            let anomaly_len = u64::from_be_bytes(remaining[..size_of::<u64>()].try_into().unwrap());
+            remaining = &remaining[size_of::<u64>()..];
+            if (remaining.len() as u64) < anomaly_len {
+                return Err(too_short());
+            }
+
+            let (anomaly, rest) = remaining.split_at(anomaly_len as usize);
+            let anomaly = String::from_utf8(anomaly.to_vec()).map_err(|err| {
+                error::Error::new(
+                    ErrorKind::MalformedResponse,
+                    format!("malformed topology anomaly: {err}"),
+                )
+            })?;
+            anomalies.push(anomaly);
+            remaining = rest;
+        }
+
+        if !remaining.is_empty() {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "the received topology anomalies response has trailing data",
+            ));
+        }
+
+        Ok(ServerResponse::TopologyAnomalies {
+            traffic_paused,
+            anomalies,
+        })
+    }
+
+    // BANDWIDTH_FORECAST_RESPONSE_TAG || remaining || consumption_rate || 1 | 0 indicating running low || 1 | 0 indicating known time left || time_left_secs
+    fn serialize_bandwidth_forecast(
+        remaining: i64,
+        consumption_rate: u64,
+        time_left: Option<Duration>,
+        running_low: bool,
+    ) -> Vec<u8> {
+        let time_left_secs = time_left.map(|time_left| time_left.as_secs());
+        std::iter::once(ServerResponseTag::BandwidthForecast as u8)
+            .chain(remaining.to_be_bytes().into_iter())
+            .chain(consumption_rate.to_be_bytes().into_iter())
+            .chain(std::iter::once(running_low as u8))
+            .chain(std::iter::once(time_left_secs.is_some() as u8))
+            .chain(time_left_secs.unwrap_or_default().to_be_bytes().into_iter())
+            .collect()
+    }
+
+    // BANDWIDTH_WARNING_RESPONSE_TAG || remaining || consumption_rate || 1 | 0 indicating known time left || time_left_secs
+    fn serialize_bandwidth_warning(
+        remaining: i64,
+        consumption_rate: u64,
+        time_left: Option<Duration>,
+    ) -> Vec<u8> {
+        let time_left_secs = time_left.map(|time_left| time_left.as_secs());
+        std::iter::once(ServerResponseTag::BandwidthWarning as u8)
+            .chain(remaining.to_be_bytes().into_iter())
+            .chain(consumption_rate.to_be_bytes().into_iter())
+            .chain(std::iter::once(time_left_secs.is_some() as u8))
+            .chain(time_left_secs.unwrap_or_default().to_be_bytes().into_iter())
+            .collect()
+    }
+
+    // BANDWIDTH_WARNING_RESPONSE_TAG || remaining || consumption_rate || 1 | 0 indicating known time left || time_left_secs
+    fn deserialize_bandwidth_warning(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 2 + 3 * size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "The received bandwidth warning response has invalid length",
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ServerResponseTag::BandwidthWarning as u8);
+
+        let remaining = i64::from_be_bytes(b[1..9].try_into().unwrap());
+        let consumption_rate = u64::from_be_bytes(b[9..17].try_into().unwrap());
+        let time_left_known = match b[17] {
+            0 => false,
+            1 => true,
+            n => {
+                return Err(error::Error::new(
+                    ErrorKind::MalformedResponse,
+                    format!("invalid known time left flag {n}"),
+                ))
+            }
+        };
+        let time_left_secs = u64::from_be_bytes(b[18..].try_into().unwrap());
+
+        Ok(ServerResponse::BandwidthWarning {
+            remaining,
+            consumption_rate,
+            time_left: time_left_known.then_some(Duration::from_secs(time_left_secs)),
+        })
+    }
+
+    // BANDWIDTH_FORECAST_RESPONSE_TAG || remaining || consumption_rate || 1 | 0 indicating running low || 1 | 0 indicating known time left || time_left_secs
+    fn deserialize_bandwidth_forecast(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 3 + 3 * size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "The received bandwidth forecast response has invalid length",
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ServerResponseTag::BandwidthForecast as u8);
+
+        let flag = |byte: u8, name: &str| match byte {
+            0 => Ok(false),
+            1 => Ok(true),
+            n => Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                format!("invalid {name} flag {n}"),
+            )),
+        };
+
+        let remaining = i64::from_be_bytes(b[1..9].try_into().unwrap());
+        let consumption_rate = u64::from_be_bytes(b[9..17].try_into().unwrap());
+        let running_low = flag(b[17], "running low")?;
+        let time_left_known = flag(b[18], "known time left")?;
+        let time_left_secs = u64::from_be_bytes(b[19..].try_into().unwrap());
+
+        Ok(ServerResponse::BandwidthForecast {
+            remaining,
+            consumption_rate,
+            time_left: time_left_known.then_some(Duration::from_secs(time_left_secs)),
+            running_low,
+        })
+    }
+
+    // CLIENT_EVENT_RESPONSE_TAG || event_kind || event_fields
+    fn serialize_client_event(event: ClientEventInfo) -> Vec<u8> {
+        std::iter::once(ServerResponseTag::ClientEvent as u8)
+            .chain(event.to_bytes().into_iter())
+            .collect()
+    }
+
+    // CLIENT_EVENT_RESPONSE_TAG || event_kind || event_fields
+    fn deserialize_client_event(b: &[u8]) -> Result<Self, error::Error> {
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ServerResponseTag::ClientEvent as u8);
+
+        Ok(ServerResponse::ClientEvent(
+            ClientEventInfo::try_from_bytes(&b[1..])?,
+        ))
+    }
+
+    // DELIVERED_RESPONSE_TAG || message_id
+    fn serialize_delivered(message_id: u64) -> Vec<u8> {
+        std::iter::once(ServerResponseTag::Delivered as u8)
+            .chain(message_id.to_be_bytes().into_iter())
+            .collect()
+    }
+
+    // DELIVERED_RESPONSE_TAG || message_id
+    fn deserialize_delivered(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() != 1 + size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "The received delivered response has invalid length",
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ServerResponseTag::Delivered as u8);
+
+        let message_id = u64::from_be_bytes(b[1..].try_into().unwrap());
+        Ok(ServerResponse::Delivered { message_id })
+    }
+
+    // DELIVERY_FAILED_RESPONSE_TAG || message_id || reason_len || reason
+    fn serialize_delivery_failed(message_id: u64, reason: String) -> Vec<u8> {
+        let reason_len_bytes = (reason.len() as u64).to_be_bytes();
+        std::iter::once(ServerResponseTag::DeliveryFailed as u8)
+            .chain(message_id.to_be_bytes().into_iter())
+            .chain(reason_len_bytes.into_iter())
+            .chain(reason.into_bytes().into_iter())
+            .collect()
+    }
+
+    // DELIVERY_FAILED_RESPONSE_TAG || message_id || reason_len || reason
+    fn deserialize_delivery_failed(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() < 1 + 2 * size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::TooShortResponse,
+                "not enough data provided to recover 'delivery failed'".to_string(),
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ServerResponseTag::DeliveryFailed as u8);
+
+        let message_id = u64::from_be_bytes(b[1..1 + size_of::<u64>()].try_into().unwrap());
+        let reason_len = u64::from_be_bytes(
+            b[1 + size_of::<u64>()..1 + 2 * size_of::<u64>()]
+                .try_into()
+                .unwrap(),
+        );
+        let reason = &b[1 + 2 * size_of::<u64>()..];
+        if reason.len() as u64 != reason_len {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                format!(
+                    "reason has inconsistent length. specified: {} got: {}",
+                    reason_len,
+                    reason.len()
+                ),
+            ));
+        }
+
+        let reason = String::from_utf8(reason.to_vec()).map_err(|err| {
+            error::Error::new(
+                ErrorKind::MalformedResponse,
+                format!("malformed failure reason: {err}"),
+            )
+        })?;
+
+        Ok(ServerResponse::DeliveryFailed { message_id, reason })
+    }
+
+    // DEAD_LETTERS_RESPONSE_TAG || num_letters || (message_id || reason_len || reason)*
+    fn serialize_dead_letters(dead_letters: Vec<DeadLetterInfo>) -> Vec<u8> {
+        let num_letters_bytes = (dead_letters.len() as u64).to_be_bytes();
+        std::iter::once(ServerResponseTag::DeadLetters as u8)
+            .chain(num_letters_bytes.into_iter())
+            .chain(dead_letters.into_iter().flat_map(|dead_letter| {
+                let reason_len_bytes = (dead_letter.reason.len() as u64).to_be_bytes();
+                dead_letter
+                    .message_id
+                    .to_be_bytes()
+                    .into_iter()
+                    .chain(reason_len_bytes.into_iter())
+                    .chain(dead_letter.reason.into_bytes().into_iter())
+            }))
+            .collect()
+    }
+
+    // DEAD_LETTERS_RESPONSE_TAG || num_letters || (message_id || reason_len || reason)*
+    fn deserialize_dead_letters(b: &[u8]) -> Result<Self, error::Error> {
+        let too_short = || {
+            error::Error::new(
+                ErrorKind::TooShortResponse,
+                "not enough data provided to recover 'dead letters'".to_string(),
+            )
+        };
+
+        if b.len() < 1 + size_of::<u64>() {
+            return Err(too_short());
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], ServerResponseTag::DeadLetters as u8);
+
+        let num_letters = u64::from_be_bytes(b[1..1 + size_of::<u64>()].try_into().unwrap());
+
+        // don't trust the declared number for the allocation
+        let mut dead_letters = Vec::new();
+        let mut remaining = &b[1 + size_of::<u64>()..];
+        for _ in 0..num_letters {
+            if remaining.len() < 2 * size_of::<u64>() {
+                return Err(too_short());
+            }
+            let message_id = u64::from_be_bytes(remaining[..size_of::<u64>()].try_into().unwrap());
+            let reason_len = u64::from_be_bytes(
+                remaining[size_of::<u64>()..2 * size_of::<u64>()]
+                    .try_into()
+                    .unwrap(),
+            );
+            remaining = &remaining[2 * size_of::<u64>()..];
+            if (remaining.len() as u64) < reason_len {
+                return Err(too_short());
+            }
+
+            let (reason, rest) = remaining.split_at(reason_len as usize);
+            let reason = String::from_utf8(reason.to_vec()).map_err(|err| {
+                error::Error::new(
+                    ErrorKind::MalformedResponse,
+                    format!("malformed failure reason: {err}"),
+                )
+            })?;
+            dead_letters.push(DeadLetterInfo { message_id, reason });
+            remaining = rest;
+        }
+
+        if !remaining.is_empty() {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "the received dead letters response has trailing data",
+            ));
+        }
+
+        Ok(ServerResponse::DeadLetters(dead_letters))
+    }
+
     // ERROR_RESPONSE_TAG || err_code || msg_len || msg
     fn serialize_error(error: error::Error) -> Vec<u8> {
         let message_len_bytes = (error.message.len() as u64).to_be_bytes();
@@ -221,7 +931,7 @@ impl ServerResponse {
         // this MUST match because it was called by 'deserialize'
         debug_assert_eq!(b[0], ServerResponseTag::Error as u8);
 
-        if b.len() < size_of::<u8>() + size_of::<u64>() {
+        if b.len() < 2 * size_of::<u8>() + size_of::<u64>() {
             return Err(error::Error::new(
                 ErrorKind::TooShortResponse,
                 "not enough data provided to recover 'error'".to_string(),
@@ -269,6 +979,38 @@ impl ServerResponse {
             ServerResponse::LaneQueueLength { lane, queue_length } => {
                 Self::serialize_lane_queue_length(lane, queue_length)
             }
+            ServerResponse::Delivered { message_id } => Self::serialize_delivered(message_id),
+            ServerResponse::DeliveryFailed { message_id, reason } => {
+                Self::serialize_delivery_failed(message_id, reason)
+            }
+            ServerResponse::DeadLetters(dead_letters) => Self::serialize_dead_letters(dead_letters),
+            ServerResponse::LaneStatus(status) => Self::serialize_lane_status(status),
+            ServerResponse::LaneCongestion { congested, status } => {
+                Self::serialize_lane_congestion(congested, status)
+            }
+            ServerResponse::Hibernation { hibernating } => Self::serialize_hibernation(hibernating),
+            ServerResponse::TopologyAnomalies {
+                traffic_paused,
+                anomalies,
+            } => Self::serialize_topology_anomalies(traffic_paused, anomalies),
+            ServerResponse::BandwidthForecast {
+                remaining,
+                consumption_rate,
+                time_left,
+                running_low,
+            } => Self::serialize_bandwidth_forecast(
+                remaining,
+                consumption_rate,
+                time_left,
+                running_low,
+            ),
+            ServerResponse::BandwidthWarning {
+                remaining,
+                consumption_rate,
+                time_left,
+            } => Self::serialize_bandwidth_warning(remaining, consumption_rate, time_left),
+            ServerResponse::ClientEvent(event) => Self::serialize_client_event(event),
+            ServerResponse::StorageUnlocked => Self::serialize_storage_unlocked(),
             ServerResponse::Error(err) => Self::serialize_error(err),
         }
     }
@@ -300,6 +1042,17 @@ impl ServerResponse {
             ServerResponseTag::Received => Self::deserialize_received(b),
             ServerResponseTag::SelfAddress => Self::deserialize_self_address(b),
             ServerResponseTag::LaneQueueLength => Self::deserialize_lane_queue_length(b),
+            ServerResponseTag::Delivered => Self::deserialize_delivered(b),
+            ServerResponseTag::DeliveryFailed => Self::deserialize_delivery_failed(b),
+            ServerResponseTag::DeadLetters => Self::deserialize_dead_letters(b),
+            ServerResponseTag::LaneStatus => Self::deserialize_lane_status(b),
+            ServerResponseTag::LaneCongestion => Self::deserialize_lane_congestion(b),
+            ServerResponseTag::Hibernation => Self::deserialize_hibernation(b),
+            ServerResponseTag::TopologyAnomalies => Self::deserialize_topology_anomalies(b),
+            ServerResponseTag::BandwidthForecast => Self::deserialize_bandwidth_forecast(b),
+            ServerResponseTag::ClientEvent => Self::deserialize_client_event(b),
+            ServerResponseTag::StorageUnlocked => Self::deserialize_storage_unlocked(b),
+            ServerResponseTag::BandwidthWarning => Self::deserialize_bandwidth_warning(b),
             ServerResponseTag::Error => Self::deserialize_error(b),
         }
     }
@@ -387,6 +1140,227 @@ mod tests {
         }
     }
 
+    #[test]
+    fn lane_status_responses_serialization_works() {
+        let status = LaneStatusInfo {
+            lane: 13,
+            queue_length: 42,
+            estimated_drain_time: Duration::from_millis(1234),
+        };
+
+        let bytes = ServerResponse::LaneStatus(status).serialize();
+        match ServerResponse::deserialize(&bytes).unwrap() {
+            ServerResponse::LaneStatus(recovered) => assert_eq!(recovered, status),
+            _ => unreachable!(),
+        }
+
+        let bytes = ServerResponse::LaneCongestion {
+            congested: true,
+            status,
+        }
+        .serialize();
+        match ServerResponse::deserialize(&bytes).unwrap() {
+            ServerResponse::LaneCongestion {
+                congested,
+                status: recovered,
+            } => {
+                assert!(congested);
+                assert_eq!(recovered, status)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn hibernation_response_serialization_works() {
+        for state in [true, false] {
+            let bytes = ServerResponse::Hibernation { hibernating: state }.serialize();
+            match ServerResponse::deserialize(&bytes).unwrap() {
+                ServerResponse::Hibernation { hibernating } => assert_eq!(hibernating, state),
+                _ => unreachable!(),
+            }
+        }
+
+        let bytes = [ServerResponseTag::Hibernation as u8, 2];
+        assert!(ServerResponse::deserialize(&bytes).is_err());
+    }
+
+    #[test]
+    fn topology_anomalies_response_serialization_works() {
+        let anomalies = vec!["foomp".to_string(), String::new()];
+        let bytes = ServerResponse::TopologyAnomalies {
+            traffic_paused: true,
+            anomalies: anomalies.clone(),
+        }
+        .serialize();
+        match ServerResponse::deserialize(&bytes).unwrap() {
+            ServerResponse::TopologyAnomalies {
+                traffic_paused,
+                anomalies: recovered,
+            } => {
+                assert!(traffic_paused);
+                assert_eq!(recovered, anomalies)
+            }
+            _ => unreachable!(),
+        }
+
+        let bytes = ServerResponse::TopologyAnomalies {
+            traffic_paused: false,
+            anomalies: Vec::new(),
+        }
+        .serialize();
+        match ServerResponse::deserialize(&bytes).unwrap() {
+            ServerResponse::TopologyAnomalies {
+                traffic_paused,
+                anomalies,
+            } => {
+                assert!(!traffic_paused);
+                assert!(anomalies.is_empty())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn bandwidth_forecast_response_serialization_works() {
+        for time_left in [Some(Duration::from_secs(600)), None] {
+            let bytes = ServerResponse::BandwidthForecast {
+                remaining: -42,
+                consumption_rate: 1234,
+                time_left,
+                running_low: true,
+            }
+            .serialize();
+            match ServerResponse::deserialize(&bytes).unwrap() {
+                ServerResponse::BandwidthForecast {
+                    remaining,
+                    consumption_rate,
+                    time_left: recovered,
+                    running_low,
+                } => {
+                    assert_eq!(remaining, -42);
+                    assert_eq!(consumption_rate, 1234);
+                    assert_eq!(recovered, time_left);
+                    assert!(running_low)
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn bandwidth_warning_response_serialization_works() {
+        for time_left in [Some(Duration::from_secs(600)), None] {
+            let bytes = ServerResponse::BandwidthWarning {
+                remaining: -42,
+                consumption_rate: 1234,
+                time_left,
+            }
+            .serialize();
+            assert_eq!(bytes[0], ServerResponseTag::BandwidthWarning as u8);
+            match ServerResponse::deserialize(&bytes).unwrap() {
+                ServerResponse::BandwidthWarning {
+                    remaining,
+                    consumption_rate,
+                    time_left: recovered,
+                } => {
+                    assert_eq!(remaining, -42);
+                    assert_eq!(consumption_rate, 1234);
+                    assert_eq!(recovered, time_left);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn client_event_response_serialization_works() {
+        for event in [
+            ClientEventInfo::GatewayConnected {
+                gateway: "foomp".to_string(),
+            },
+            ClientEventInfo::GatewayDisconnected {
+                reason: String::new(),
+            },
+            ClientEventInfo::TopologyRefreshed {
+                mixnodes: 42,
+                gateways: 3,
+            },
+            ClientEventInfo::CredentialSpent {
+                remaining_bandwidth: -1,
+            },
+        ] {
+            let bytes = ServerResponse::ClientEvent(event.clone()).serialize();
+            match ServerResponse::deserialize(&bytes).unwrap() {
+                ServerResponse::ClientEvent(recovered) => assert_eq!(recovered, event),
+                _ => unreachable!(),
+            }
+        }
+
+        let bytes = [ServerResponseTag::ClientEvent as u8, 0xFF];
+        assert!(ServerResponse::deserialize(&bytes).is_err());
+    }
+
+    #[test]
+    fn storage_unlocked_response_serialization_works() {
+        let mut bytes = ServerResponse::StorageUnlocked.serialize();
+        match ServerResponse::deserialize(&bytes).unwrap() {
+            ServerResponse::StorageUnlocked => (),
+            _ => unreachable!(),
+        }
+
+        bytes.push(42);
+        assert!(ServerResponse::deserialize(&bytes).is_err());
+    }
+
+    #[test]
+    fn delivery_responses_serialization_works() {
+        let bytes = ServerResponse::Delivered { message_id: 42 }.serialize();
+        match ServerResponse::deserialize(&bytes).unwrap() {
+            ServerResponse::Delivered { message_id } => assert_eq!(message_id, 42),
+            _ => unreachable!(),
+        }
+
+        let bytes = ServerResponse::DeliveryFailed {
+            message_id: 42,
+            reason: "foomp".to_string(),
+        }
+        .serialize();
+        match ServerResponse::deserialize(&bytes).unwrap() {
+            ServerResponse::DeliveryFailed { message_id, reason } => {
+                assert_eq!(message_id, 42);
+                assert_eq!(reason, "foomp")
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn dead_letters_response_serialization_works() {
+        let dead_letters = vec![
+            DeadLetterInfo {
+                message_id: 42,
+                reason: "foomp".to_string(),
+            },
+            DeadLetterInfo {
+                message_id: 123,
+                reason: String::new(),
+            },
+        ];
+
+        let bytes = ServerResponse::DeadLetters(dead_letters.clone()).serialize();
+        match ServerResponse::deserialize(&bytes).unwrap() {
+            ServerResponse::DeadLetters(recovered) => assert_eq!(recovered, dead_letters),
+            _ => unreachable!(),
+        }
+
+        let bytes = ServerResponse::DeadLetters(Vec::new()).serialize();
+        match ServerResponse::deserialize(&bytes).unwrap() {
+            ServerResponse::DeadLetters(recovered) => assert!(recovered.is_empty()),
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn error_response_serialization_works() {
         let dummy_error = error::Error::new(ErrorKind::UnknownRequest, "foomp message".to_string());
@@ -398,4 +1372,81 @@ mod tests {
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn truncated_responses_are_rejected() {
+        let lane_queue_length = ServerResponse::LaneQueueLength {
+            lane: 13,
+            queue_length: 42,
+        }
+        .serialize();
+        let error = ServerResponse::new_error("foomp").serialize();
+        let delivered = ServerResponse::Delivered { message_id: 42 }.serialize();
+        let delivery_failed = ServerResponse::DeliveryFailed {
+            message_id: 42,
+            reason: "foomp".to_string(),
+        }
+        .serialize();
+        let dead_letters = ServerResponse::DeadLetters(vec![DeadLetterInfo {
+            message_id: 42,
+            reason: "foomp".to_string(),
+        }])
+        .serialize();
+        let status = LaneStatusInfo {
+            lane: 13,
+            queue_length: 42,
+            estimated_drain_time: Duration::from_millis(1234),
+        };
+        let lane_status = ServerResponse::LaneStatus(status).serialize();
+        let lane_congestion = ServerResponse::LaneCongestion {
+            congested: false,
+            status,
+        }
+        .serialize();
+        let hibernation = ServerResponse::Hibernation { hibernating: true }.serialize();
+        let topology_anomalies = ServerResponse::TopologyAnomalies {
+            traffic_paused: true,
+            anomalies: vec!["foomp".to_string()],
+        }
+        .serialize();
+
+        let bandwidth_forecast = ServerResponse::BandwidthForecast {
+            remaining: 42,
+            consumption_rate: 1,
+            time_left: None,
+            running_low: false,
+        }
+        .serialize();
+
+        let bandwidth_warning = ServerResponse::BandwidthWarning {
+            remaining: 42,
+            consumption_rate: 1,
+            time_left: None,
+        }
+        .serialize();
+
+        let client_event = ServerResponse::ClientEvent(ClientEventInfo::GatewayConnected {
+            gateway: "foomp".to_string(),
+        })
+        .serialize();
+
+        for bytes in [
+            lane_queue_length,
+            error,
+            delivered,
+            delivery_failed,
+            dead_letters,
+            lane_status,
+            lane_congestion,
+            hibernation,
+            topology_anomalies,
+            bandwidth_forecast,
+            bandwidth_warning,
+            client_event,
+        ] {
+            for len in 1..bytes.len() {
+                assert!(ServerResponse::deserialize(&bytes[..len]).is_err());
+            }
+        }
+    }
 }
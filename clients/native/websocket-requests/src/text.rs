@@ -3,7 +3,7 @@
 
 use crate::error::ErrorKind;
 use crate::requests::ClientRequest;
-use crate::responses::ServerResponse;
+use crate::responses::{ClientEventInfo, DeadLetterInfo, LaneStatusInfo, ServerResponse};
 use nym_sphinx::addressing::clients::Recipient;
 use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
 use serde::{Deserialize, Serialize};
@@ -35,6 +35,38 @@ pub(super) enum ClientRequestText {
         connection_id: Option<u64>,
     },
     SelfAddress,
+    #[serde(rename_all = "camelCase")]
+    Tracked {
+        message_id: u64,
+        request: Box<ClientRequestText>,
+    },
+    GetDeadLetters,
+    #[serde(rename_all = "camelCase")]
+    RetryDeadLetter {
+        message_id: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    DiscardDeadLetter {
+        message_id: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    GetLaneStatus {
+        connection_id: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    SubscribeLaneStatus {
+        pause_threshold: u64,
+        resume_threshold: u64,
+    },
+    Hibernate,
+    Wake,
+    GetTopologyAnomalies,
+    AcknowledgeTopologyAnomalies,
+    GetBandwidthForecast,
+    SubscribeClientEvents,
+    UnlockStorage {
+        passphrase: String,
+    },
 }
 
 impl TryFrom<String> for ClientRequestText {
@@ -101,6 +133,109 @@ impl TryInto<ClientRequest> for ClientRequestText {
                     connection_id,
                 })
             }
+            ClientRequestText::Tracked {
+                message_id,
+                request,
+            } => ClientRequest::new_tracked(message_id, (*request).try_into()?),
+            ClientRequestText::GetDeadLetters => Ok(ClientRequest::GetDeadLetters),
+            ClientRequestText::RetryDeadLetter { message_id } => {
+                Ok(ClientRequest::RetryDeadLetter(message_id))
+            }
+            ClientRequestText::DiscardDeadLetter { message_id } => {
+                Ok(ClientRequest::DiscardDeadLetter(message_id))
+            }
+            ClientRequestText::GetLaneStatus { connection_id } => {
+                Ok(ClientRequest::GetLaneStatus(connection_id))
+            }
+            ClientRequestText::SubscribeLaneStatus {
+                pause_threshold,
+                resume_threshold,
+            } => ClientRequest::new_lane_status_subscription(pause_threshold, resume_threshold),
+            ClientRequestText::Hibernate => Ok(ClientRequest::Hibernate),
+            ClientRequestText::Wake => Ok(ClientRequest::Wake),
+            ClientRequestText::GetTopologyAnomalies => Ok(ClientRequest::GetTopologyAnomalies),
+            ClientRequestText::AcknowledgeTopologyAnomalies => {
+                Ok(ClientRequest::AcknowledgeTopologyAnomalies)
+            }
+            ClientRequestText::GetBandwidthForecast => Ok(ClientRequest::GetBandwidthForecast),
+            ClientRequestText::SubscribeClientEvents => Ok(ClientRequest::SubscribeClientEvents),
+            ClientRequestText::UnlockStorage { passphrase } => {
+                Ok(ClientRequest::UnlockStorage { passphrase })
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct DeadLetterText {
+    message_id: u64,
+    reason: String,
+}
+
+impl From<DeadLetterInfo> for DeadLetterText {
+    fn from(dead_letter: DeadLetterInfo) -> Self {
+        DeadLetterText {
+            message_id: dead_letter.message_id,
+            reason: dead_letter.reason,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct LaneStatusText {
+    lane: u64,
+    queue_length: usize,
+    estimated_drain_time_ms: u64,
+}
+
+impl From<LaneStatusInfo> for LaneStatusText {
+    fn from(status: LaneStatusInfo) -> Self {
+        LaneStatusText {
+            lane: status.lane,
+            queue_length: status.queue_length,
+            estimated_drain_time_ms: status.estimated_drain_time.as_millis() as u64,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub(super) enum ClientEventText {
+    GatewayConnected {
+        gateway: String,
+    },
+    GatewayDisconnected {
+        reason: String,
+    },
+    TopologyRefreshed {
+        mixnodes: u64,
+        gateways: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    CredentialSpent {
+        remaining_bandwidth: i64,
+    },
+}
+
+impl From<ClientEventInfo> for ClientEventText {
+    fn from(event: ClientEventInfo) -> Self {
+        match event {
+            ClientEventInfo::GatewayConnected { gateway } => {
+                ClientEventText::GatewayConnected { gateway }
+            }
+            ClientEventInfo::GatewayDisconnected { reason } => {
+                ClientEventText::GatewayDisconnected { reason }
+            }
+            ClientEventInfo::TopologyRefreshed { mixnodes, gateways } => {
+                ClientEventText::TopologyRefreshed { mixnodes, gateways }
+            }
+            ClientEventInfo::CredentialSpent {
+                remaining_bandwidth,
+            } => ClientEventText::CredentialSpent {
+                remaining_bandwidth,
+            },
         }
     }
 }
@@ -123,6 +258,51 @@ pub(super) enum ServerResponseText {
         lane: u64,
         queue_length: usize,
     },
+    #[serde(rename_all = "camelCase")]
+    Delivered {
+        message_id: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    DeliveryFailed {
+        message_id: u64,
+        reason: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    DeadLetters {
+        dead_letters: Vec<DeadLetterText>,
+    },
+    LaneStatus {
+        status: LaneStatusText,
+    },
+    LaneCongestion {
+        congested: bool,
+        status: LaneStatusText,
+    },
+    Hibernation {
+        hibernating: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    TopologyAnomalies {
+        traffic_paused: bool,
+        anomalies: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    BandwidthForecast {
+        remaining: i64,
+        consumption_rate: u64,
+        time_left_secs: Option<u64>,
+        running_low: bool,
+    },
+    BandwidthWarning {
+        remaining: i64,
+        consumption_rate: u64,
+        time_left_secs: Option<u64>,
+    },
+    ClientEvent {
+        #[serde(flatten)]
+        event: ClientEventText,
+    },
+    StorageUnlocked,
     Error {
         message: String,
     },
@@ -165,6 +345,58 @@ impl From<ServerResponse> for ServerResponseText {
             ServerResponse::LaneQueueLength { lane, queue_length } => {
                 ServerResponseText::LaneQueueLength { lane, queue_length }
             }
+            ServerResponse::Delivered { message_id } => {
+                ServerResponseText::Delivered { message_id }
+            }
+            ServerResponse::DeliveryFailed { message_id, reason } => {
+                ServerResponseText::DeliveryFailed { message_id, reason }
+            }
+            ServerResponse::DeadLetters(dead_letters) => ServerResponseText::DeadLetters {
+                dead_letters: dead_letters.into_iter().map(Into::into).collect(),
+            },
+            ServerResponse::LaneStatus(status) => ServerResponseText::LaneStatus {
+                status: status.into(),
+            },
+            ServerResponse::LaneCongestion { congested, status } => {
+                ServerResponseText::LaneCongestion {
+                    congested,
+                    status: status.into(),
+                }
+            }
+            ServerResponse::Hibernation { hibernating } => {
+                ServerResponseText::Hibernation { hibernating }
+            }
+            ServerResponse::TopologyAnomalies {
+                traffic_paused,
+                anomalies,
+            } => ServerResponseText::TopologyAnomalies {
+                traffic_paused,
+                anomalies,
+            },
+            ServerResponse::BandwidthForecast {
+                remaining,
+                consumption_rate,
+                time_left,
+                running_low,
+            } => ServerResponseText::BandwidthForecast {
+                remaining,
+                consumption_rate,
+                time_left_secs: time_left.map(|time_left| time_left.as_secs()),
+                running_low,
+            },
+            ServerResponse::BandwidthWarning {
+                remaining,
+                consumption_rate,
+                time_left,
+            } => ServerResponseText::BandwidthWarning {
+                remaining,
+                consumption_rate,
+                time_left_secs: time_left.map(|time_left| time_left.as_secs()),
+            },
+            ServerResponse::ClientEvent(event) => ServerResponseText::ClientEvent {
+                event: event.into(),
+            },
+            ServerResponse::StorageUnlocked => ServerResponseText::StorageUnlocked,
             ServerResponse::Error(err) => ServerResponseText::Error {
                 message: err.to_string(),
             },
@@ -27,6 +27,11 @@ pub(crate) struct Init {
     #[clap(long)]
     provider: Recipient,
 
+    /// Comma separated list of additional socks5 providers. If specified, each new connection
+    /// is sent to one of the healthy providers out of this list and the main one.
+    #[clap(long, value_delimiter = ',')]
+    provider_pool: Option<Vec<Recipient>>,
+
     /// Specifies whether this client is going to use an anonymous sender tag for communication with the service provider.
     /// While this is going to hide its actual address information, it will make the actual communication
     /// slower and consume nearly double the bandwidth as it will require sending reply SURBs.
@@ -36,6 +41,19 @@ pub(crate) struct Init {
     #[clap(long, alias = "use_anonymous_sender_tag")]
     use_reply_surbs: Option<bool>,
 
+    /// Specifies whether the client should also accept HTTP CONNECT proxy requests.
+    #[clap(long)]
+    http_proxy: Option<bool>,
+
+    /// Port for the HTTP CONNECT proxy to listen on.
+    #[clap(long)]
+    http_port: Option<u16>,
+
+    /// Specifies whether the client should run a local DNS resolver which sends all queries
+    /// over the mixnet rather than to the system resolver.
+    #[clap(long)]
+    dns_resolver: Option<bool>,
+
     /// Id of the gateway we are going to connect to.
     #[clap(long)]
     gateway: Option<identity::PublicKey>,
@@ -87,6 +105,10 @@ impl From<Init> for OverrideConfig {
             nym_apis: init_config.nym_apis,
             port: init_config.port,
             use_anonymous_replies: init_config.use_reply_surbs,
+            http_proxy: init_config.http_proxy,
+            http_port: init_config.http_port,
+            dns_resolver: init_config.dns_resolver,
+            provider_pool: init_config.provider_pool,
             fastmode: init_config.fastmode,
             no_cover: init_config.no_cover,
             nyxd_urls: init_config.nyxd_urls,
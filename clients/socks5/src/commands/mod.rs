@@ -5,20 +5,46 @@ use clap::CommandFactory;
 use clap::{Parser, Subcommand};
 use lazy_static::lazy_static;
 use log::info;
-use nym_bin_common::build_information::BinaryBuildInformation;
+use nym_bin_common::build_information::{protocols, BinaryBuildInformation, BuildInfo};
 use nym_bin_common::completions::{fig_generate, ArgShell};
 use nym_config::{NymConfig, OptionalSet};
 use nym_socks5_client_core::config::old_config_v1_1_13::OldConfigV1_1_13;
 use nym_socks5_client_core::config::{BaseConfig, Config};
+use nym_sphinx::addressing::clients::Recipient;
 use std::error::Error;
 
 pub mod init;
 pub(crate) mod run;
 pub(crate) mod upgrade;
 
+pub(crate) const BUILD_INFORMATION: BinaryBuildInformation =
+    BinaryBuildInformation::new(env!("CARGO_PKG_VERSION"))
+        .with_enabled_features(&[
+            #[cfg(feature = "tokio-console")]
+            "tokio-console",
+            #[cfg(feature = "eth")]
+            "eth",
+            #[cfg(feature = "packet-tracing")]
+            "packet-tracing",
+        ])
+        .with_protocol_versions(&[
+            (
+                protocols::SPHINX_PACKET,
+                nym_sphinx::params::CURRENT_PACKET_VERSION_NUMBER,
+            ),
+            (
+                protocols::GATEWAY_CLIENT,
+                nym_gateway_requests::PROTOCOL_VERSION,
+            ),
+            (
+                protocols::SERVICE_PROVIDER_INTERFACE,
+                nym_service_providers_common::interface::INTERFACE_VERSION,
+            ),
+            (protocols::SOCKS5, nym_socks5_requests::INTERFACE_VERSION),
+        ]);
+
 lazy_static! {
-    pub static ref PRETTY_BUILD_INFORMATION: String =
-        BinaryBuildInformation::new(env!("CARGO_PKG_VERSION")).pretty_print();
+    pub static ref PRETTY_BUILD_INFORMATION: String = BUILD_INFORMATION.pretty_print();
 }
 
 // Helper for passing LONG_VERSION to clap
@@ -39,6 +65,9 @@ pub(crate) struct Cli {
 
 #[derive(Subcommand)]
 pub(crate) enum Commands {
+    /// Show build information of this binary
+    BuildInfo(BuildInfo),
+
     /// Initialise a Nym client. Do this first!
     Init(init::Init),
 
@@ -60,6 +89,10 @@ pub(crate) struct OverrideConfig {
     nym_apis: Option<Vec<url::Url>>,
     port: Option<u16>,
     use_anonymous_replies: Option<bool>,
+    http_proxy: Option<bool>,
+    http_port: Option<u16>,
+    dns_resolver: Option<bool>,
+    provider_pool: Option<Vec<Recipient>>,
     fastmode: bool,
     no_cover: bool,
     nyxd_urls: Option<Vec<url::Url>>,
@@ -70,6 +103,7 @@ pub(crate) async fn execute(args: &Cli) -> Result<(), Box<dyn Error + Send + Syn
     let bin_name = "nym-socks5-client";
 
     match &args.command {
+        Commands::BuildInfo(m) => m.execute(&BUILD_INFORMATION),
         Commands::Init(m) => init::execute(m).await?,
         Commands::Run(m) => run::execute(m).await?,
         Commands::Upgrade(m) => upgrade::execute(m),
@@ -85,6 +119,10 @@ pub(crate) fn override_config(config: Config, args: OverrideConfig) -> Config {
         .with_base(BaseConfig::with_disabled_cover_traffic, args.no_cover)
         .with_optional(Config::with_anonymous_replies, args.use_anonymous_replies)
         .with_optional(Config::with_port, args.port)
+        .with_optional(Config::with_http_proxy, args.http_proxy)
+        .with_optional(Config::with_http_port, args.http_port)
+        .with_optional(Config::with_dns_resolver, args.dns_resolver)
+        .with_optional(Config::with_provider_pool, args.provider_pool)
         .with_optional_custom_env_ext(
             BaseConfig::with_custom_nym_apis,
             args.nym_apis,
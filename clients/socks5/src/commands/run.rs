@@ -33,10 +33,28 @@ pub(crate) struct Run {
     #[clap(long, alias = "use_anonymous_sender_tag")]
     use_anonymous_replies: Option<bool>,
 
+    /// Specifies whether the client should also accept HTTP CONNECT proxy requests.
+    #[clap(long)]
+    http_proxy: Option<bool>,
+
+    /// Port for the HTTP CONNECT proxy to listen on.
+    #[clap(long)]
+    http_port: Option<u16>,
+
+    /// Specifies whether the client should run a local DNS resolver which sends all queries
+    /// over the mixnet rather than to the system resolver.
+    #[clap(long)]
+    dns_resolver: Option<bool>,
+
     /// Address of the socks5 provider to send messages to.
     #[clap(long)]
     provider: Option<Recipient>,
 
+    /// Comma separated list of additional socks5 providers. If specified, each new connection
+    /// is sent to one of the healthy providers out of this list and the main one.
+    #[clap(long, value_delimiter = ',')]
+    provider_pool: Option<Vec<Recipient>>,
+
     /// Id of the gateway we want to connect to. If overridden, it is user's responsibility to
     /// ensure prior registration happened
     #[clap(long)]
@@ -75,6 +93,10 @@ impl From<Run> for OverrideConfig {
             nym_apis: run_config.nym_apis,
             port: run_config.port,
             use_anonymous_replies: run_config.use_anonymous_replies,
+            http_proxy: run_config.http_proxy,
+            http_port: run_config.http_port,
+            dns_resolver: run_config.dns_resolver,
+            provider_pool: run_config.provider_pool,
             fastmode: run_config.fastmode,
             no_cover: run_config.no_cover,
             nyxd_urls: run_config.nyxd_urls,
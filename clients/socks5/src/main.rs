@@ -5,6 +5,7 @@ use std::error::Error;
 
 use clap::{crate_name, crate_version, Parser};
 use nym_bin_common::logging::{maybe_print_banner, setup_logging};
+use nym_config::paths::migrate_legacy_nym_directory;
 use nym_network_defaults::setup_env;
 
 mod commands;
@@ -12,10 +13,14 @@ pub mod error;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    #[cfg(feature = "tokio-console")]
+    nym_bin_common::logging::setup_tokio_console();
+
     setup_logging();
     maybe_print_banner(crate_name!(), crate_version!());
 
     let args = commands::Cli::parse();
     setup_env(args.config_env_file.as_ref());
+    migrate_legacy_nym_directory("socks5-clients");
     commands::execute(&args).await
 }
@@ -9,8 +9,8 @@
 use nym_client_core::config::{
     Acknowledgements as ConfigAcknowledgements, CoverTraffic as ConfigCoverTraffic,
     DebugConfig as ConfigDebug, GatewayConnection as ConfigGatewayConnection,
-    GatewayEndpointConfig, ReplySurbs as ConfigReplySurbs, Topology as ConfigTopology,
-    Traffic as ConfigTraffic,
+    GatewayEndpointConfig, Hibernation as ConfigHibernation, ReplySurbs as ConfigReplySurbs,
+    Topology as ConfigTopology, Traffic as ConfigTraffic,
 };
 use nym_sphinx::params::PacketSize;
 use serde::{Deserialize, Serialize};
@@ -94,6 +94,9 @@ impl From<Traffic> for ConfigTraffic {
                 .disable_main_poisson_packet_distribution,
             primary_packet_size: PacketSize::RegularPacket,
             secondary_packet_size: use_extended_packet_size,
+            padding_policy: Default::default(),
+            attach_message_ordering: false,
+            disclose_message_expiry: false,
         }
     }
 }
@@ -157,6 +160,27 @@ pub struct GatewayConnection {
     /// How long we're willing to wait for a response to a message sent to the gateway,
     /// before giving up on it.
     pub gateway_response_timeout_ms: u64,
+
+    /// Defines the number of the most recently received messages remembered in order to drop
+    /// the ones the gateway might redeliver after a crash or a reconnection.
+    /// Setting it to 0 disables the deduplication.
+    pub delivered_messages_dedup_window: usize,
+
+    /// Remaining bandwidth (in bytes) at the gateway below which a new credential is going to be
+    /// acquired and presented to it.
+    pub bandwidth_top_up_threshold: i64,
+
+    /// How often the remaining bandwidth at the gateway is checked.
+    pub bandwidth_check_interval_ms: u64,
+
+    /// If enabled, the client never acquires new credentials on its own and only warns about
+    /// the bandwidth running low, so that it could be topped up manually.
+    pub manual_bandwidth_top_up: bool,
+
+    /// The client starts warning about the bandwidth running low once, at the current consumption
+    /// rate, it's expected to run out within this period. With the automatic top-ups disabled,
+    /// it's about falling below `bandwidth_top_up_threshold` instead.
+    pub bandwidth_warning_period_ms: u64,
 }
 
 impl From<GatewayConnection> for ConfigGatewayConnection {
@@ -165,6 +189,15 @@ impl From<GatewayConnection> for ConfigGatewayConnection {
             gateway_response_timeout: Duration::from_millis(
                 gateway_connection.gateway_response_timeout_ms,
             ),
+            delivered_messages_dedup_window: gateway_connection.delivered_messages_dedup_window,
+            bandwidth_top_up_threshold: gateway_connection.bandwidth_top_up_threshold,
+            bandwidth_check_interval: Duration::from_millis(
+                gateway_connection.bandwidth_check_interval_ms,
+            ),
+            manual_bandwidth_top_up: gateway_connection.manual_bandwidth_top_up,
+            bandwidth_warning_period: Duration::from_millis(
+                gateway_connection.bandwidth_warning_period_ms,
+            ),
         }
     }
 }
@@ -174,6 +207,13 @@ impl From<ConfigGatewayConnection> for GatewayConnection {
         GatewayConnection {
             gateway_response_timeout_ms: gateway_connection.gateway_response_timeout.as_millis()
                 as u64,
+            delivered_messages_dedup_window: gateway_connection.delivered_messages_dedup_window,
+            bandwidth_top_up_threshold: gateway_connection.bandwidth_top_up_threshold,
+            bandwidth_check_interval_ms: gateway_connection.bandwidth_check_interval.as_millis()
+                as u64,
+            manual_bandwidth_top_up: gateway_connection.manual_bandwidth_top_up,
+            bandwidth_warning_period_ms: gateway_connection.bandwidth_warning_period.as_millis()
+                as u64,
         }
     }
 }
@@ -196,6 +236,41 @@ pub struct Acknowledgements {
     /// it is assumed it was lost and retransmission of the data packet happens.
     /// In an ideal network with 0 latency, this value would have been 0.
     pub ack_wait_addition_ms: u64,
+
+    /// Specifies whether the time waited for the acknowledgements should be adjusted based on
+    /// the observed round-trip times rather than always following the static formula.
+    pub adaptive_ack_timeouts: bool,
+
+    /// Weight given to every new round-trip sample when updating the smoothed estimate
+    /// of the acknowledgement latency.
+    pub ack_rtt_smoothing: f64,
+
+    /// Weight given to every new round-trip sample when updating the estimated variance
+    /// of the acknowledgement latency.
+    pub ack_rtt_variance_smoothing: f64,
+
+    /// Number of estimated deviations of the acknowledgement latency that are waited for
+    /// before the packet is retransmitted.
+    pub ack_rtt_variance_multiplier: f64,
+
+    /// Minimum time waited, on top of the expected delay of a packet, before it is retransmitted.
+    pub minimum_ack_wait_addition_ms: u64,
+
+    /// Maximum time waited, on top of the expected delay of a packet, before it is retransmitted.
+    pub maximum_ack_wait_addition_ms: u64,
+
+    /// Maximum number of times a data packet is going to be retransmitted before the client
+    /// gives up on it. If unspecified, the client will keep on retransmitting the packet until
+    /// it gets acknowledged.
+    pub maximum_retransmissions: Option<u32>,
+
+    /// Maximum amount of time, since it was first sent, during which a data packet might still
+    /// get retransmitted. If unspecified, there is no time limit on the retransmissions.
+    pub maximum_retransmission_period_ms: Option<u64>,
+
+    /// Maximum number of tracked messages that could not have been delivered that are retained
+    /// so that they could be inspected and possibly resent.
+    pub maximum_dead_letters: usize,
 }
 
 impl From<Acknowledgements> for ConfigAcknowledgements {
@@ -204,6 +279,21 @@ impl From<Acknowledgements> for ConfigAcknowledgements {
             average_ack_delay: Duration::from_millis(acknowledgements.average_ack_delay_ms),
             ack_wait_multiplier: acknowledgements.ack_wait_multiplier,
             ack_wait_addition: Duration::from_millis(acknowledgements.ack_wait_addition_ms),
+            adaptive_ack_timeouts: acknowledgements.adaptive_ack_timeouts,
+            ack_rtt_smoothing: acknowledgements.ack_rtt_smoothing,
+            ack_rtt_variance_smoothing: acknowledgements.ack_rtt_variance_smoothing,
+            ack_rtt_variance_multiplier: acknowledgements.ack_rtt_variance_multiplier,
+            minimum_ack_wait_addition: Duration::from_millis(
+                acknowledgements.minimum_ack_wait_addition_ms,
+            ),
+            maximum_ack_wait_addition: Duration::from_millis(
+                acknowledgements.maximum_ack_wait_addition_ms,
+            ),
+            maximum_retransmissions: acknowledgements.maximum_retransmissions,
+            maximum_retransmission_period: acknowledgements
+                .maximum_retransmission_period_ms
+                .map(Duration::from_millis),
+            maximum_dead_letters: acknowledgements.maximum_dead_letters,
         }
     }
 }
@@ -214,6 +304,19 @@ impl From<ConfigAcknowledgements> for Acknowledgements {
             average_ack_delay_ms: acknowledgements.average_ack_delay.as_millis() as u64,
             ack_wait_multiplier: acknowledgements.ack_wait_multiplier,
             ack_wait_addition_ms: acknowledgements.ack_wait_addition.as_millis() as u64,
+            adaptive_ack_timeouts: acknowledgements.adaptive_ack_timeouts,
+            ack_rtt_smoothing: acknowledgements.ack_rtt_smoothing,
+            ack_rtt_variance_smoothing: acknowledgements.ack_rtt_variance_smoothing,
+            ack_rtt_variance_multiplier: acknowledgements.ack_rtt_variance_multiplier,
+            minimum_ack_wait_addition_ms: acknowledgements.minimum_ack_wait_addition.as_millis()
+                as u64,
+            maximum_ack_wait_addition_ms: acknowledgements.maximum_ack_wait_addition.as_millis()
+                as u64,
+            maximum_retransmissions: acknowledgements.maximum_retransmissions,
+            maximum_retransmission_period_ms: acknowledgements
+                .maximum_retransmission_period
+                .map(|period| period.as_millis() as u64),
+            maximum_dead_letters: acknowledgements.maximum_dead_letters,
         }
     }
 }
@@ -229,6 +332,26 @@ pub struct Topology {
     /// path. This timeout determines waiting period until it is decided that the packet
     /// did not reach its destination.
     pub topology_resolution_timeout_ms: u64,
+
+    /// The minimum performance score (in percent) over the last 24h that the mixnodes and gateways
+    /// must have to be used by this client.
+    pub minimum_node_performance: u8,
+
+    /// Number of routes through the current topology that are precomputed in the background,
+    /// so that they would not have to be sampled whenever a packet is being sent.
+    pub route_pool_size: usize,
+
+    /// Fraction of the previously known nodes that can change their keys between two consecutive
+    /// topology refreshes before it is reported as a suspicious change of the network.
+    pub anomaly_key_change_threshold: f64,
+
+    /// Minimum number of nodes on each mix layer. A layer suddenly shrinking below it is reported
+    /// as a suspicious change of the network.
+    pub anomaly_minimum_layer_size: usize,
+
+    /// Specifies whether all the traffic should be paused upon detecting a suspicious change
+    /// of the network topology until the user acknowledges it.
+    pub pause_on_topology_anomaly: bool,
 }
 
 impl From<Topology> for ConfigTopology {
@@ -238,6 +361,11 @@ impl From<Topology> for ConfigTopology {
             topology_resolution_timeout: Duration::from_millis(
                 topology.topology_resolution_timeout_ms,
             ),
+            minimum_node_performance: topology.minimum_node_performance,
+            route_pool_size: topology.route_pool_size,
+            anomaly_key_change_threshold: topology.anomaly_key_change_threshold,
+            anomaly_minimum_layer_size: topology.anomaly_minimum_layer_size,
+            pause_on_topology_anomaly: topology.pause_on_topology_anomaly,
         }
     }
 }
@@ -247,6 +375,11 @@ impl From<ConfigTopology> for Topology {
         Topology {
             topology_refresh_rate_ms: topology.topology_refresh_rate.as_millis() as u64,
             topology_resolution_timeout_ms: topology.topology_resolution_timeout.as_millis() as u64,
+            minimum_node_performance: topology.minimum_node_performance,
+            route_pool_size: topology.route_pool_size,
+            anomaly_key_change_threshold: topology.anomaly_key_change_threshold,
+            anomaly_minimum_layer_size: topology.anomaly_minimum_layer_size,
+            pause_on_topology_anomaly: topology.pause_on_topology_anomaly,
         }
     }
 }
@@ -285,6 +418,11 @@ pub struct ReplySurbs {
     /// Defines maximum amount of time given reply key is going to be valid for.
     /// This is going to be superseded by key rotation once implemented.
     pub maximum_reply_key_age_ms: u64,
+
+    /// Defines the maximum number of remote parties the client is going to keep the reply state
+    /// (i.e. received reply surbs and pending replies) of.
+    /// Once exceeded, the state of the least recently active idle parties is evicted.
+    pub maximum_tracked_recipients: usize,
 }
 
 impl From<ReplySurbs> for ConfigReplySurbs {
@@ -304,6 +442,7 @@ impl From<ReplySurbs> for ConfigReplySurbs {
             ),
             maximum_reply_surb_age: Duration::from_millis(reply_surbs.maximum_reply_surb_age_ms),
             maximum_reply_key_age: Duration::from_millis(reply_surbs.maximum_reply_key_age_ms),
+            maximum_tracked_recipients: reply_surbs.maximum_tracked_recipients,
         }
     }
 }
@@ -325,6 +464,36 @@ impl From<ConfigReplySurbs> for ReplySurbs {
                 .as_millis() as u64,
             maximum_reply_surb_age_ms: reply_surbs.maximum_reply_surb_age.as_millis() as u64,
             maximum_reply_key_age_ms: reply_surbs.maximum_reply_key_age.as_millis() as u64,
+            maximum_tracked_recipients: reply_surbs.maximum_tracked_recipients,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Hibernation {
+    /// The rate at which the network topology is refreshed while the client is hibernating.
+    pub topology_refresh_rate_ms: u64,
+
+    /// The interval at which keepalive messages are sent to the gateway while the client
+    /// is hibernating, so that the established session would not get dropped.
+    pub keepalive_interval_ms: u64,
+}
+
+impl From<Hibernation> for ConfigHibernation {
+    fn from(hibernation: Hibernation) -> Self {
+        ConfigHibernation {
+            topology_refresh_rate: Duration::from_millis(hibernation.topology_refresh_rate_ms),
+            keepalive_interval: Duration::from_millis(hibernation.keepalive_interval_ms),
+        }
+    }
+}
+
+impl From<ConfigHibernation> for Hibernation {
+    fn from(hibernation: ConfigHibernation) -> Self {
+        Hibernation {
+            topology_refresh_rate_ms: hibernation.topology_refresh_rate.as_millis() as u64,
+            keepalive_interval_ms: hibernation.keepalive_interval.as_millis() as u64,
         }
     }
 }
@@ -350,6 +519,9 @@ pub struct Debug {
 
     /// Defines all configuration options related to reply SURBs.
     pub reply_surbs: ReplySurbs,
+
+    /// Defines all configuration options related to the low-power hibernation mode.
+    pub hibernation: Hibernation,
 }
 
 impl From<Debug> for ConfigDebug {
@@ -361,6 +533,7 @@ impl From<Debug> for ConfigDebug {
             acknowledgements: debug.acknowledgements.into(),
             topology: debug.topology.into(),
             reply_surbs: debug.reply_surbs.into(),
+            hibernation: debug.hibernation.into(),
         }
     }
 }
@@ -374,6 +547,7 @@ impl From<ConfigDebug> for Debug {
             acknowledgements: debug.acknowledgements.into(),
             topology: debug.topology.into(),
             reply_surbs: debug.reply_surbs.into(),
+            hibernation: debug.hibernation.into(),
         }
     }
 }
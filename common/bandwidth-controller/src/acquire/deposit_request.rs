@@ -0,0 +1,165 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::acquire::state::{KeyPair, State};
+use crate::error::BandwidthControllerError;
+use nym_coconut_bandwidth_contract_common::deposit::DepositData;
+use nym_coconut_bandwidth_contract_common::events::{
+    DEPOSITED_FUNDS_EVENT_TYPE, DEPOSIT_IDENTITY_KEY, DEPOSIT_INFO, DEPOSIT_VALUE,
+};
+use nym_coconut_bandwidth_contract_common::msg::ExecuteMsg;
+use nym_coconut_interface::Parameters;
+use nym_credentials::coconut::bandwidth::{
+    default_expiration_date, BandwidthVoucher, TOTAL_ATTRIBUTES,
+};
+use nym_crypto::asymmetric::{encryption, identity};
+use nym_network_defaults::VOUCHER_INFO;
+use nym_validator_client::nyxd::tx::Hash;
+use nym_validator_client::nyxd::{Coin, CosmWasmClient, NyxdClient, Query, TxResponse};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use url::Url;
+
+pub const DEPOSIT_REQUEST_URI_SCHEME: &str = "nym-deposit";
+pub const DEPOSIT_REQUEST_MEMO: &str = "CoconutBandwidth::Deposit";
+
+/// Request for an external wallet to deposit funds into the coconut bandwidth contract on behalf
+/// of the client, so that the client itself would not need to hold keys to any funded account.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DepositRequest {
+    contract_address: String,
+    amount: Coin,
+    data: DepositData,
+}
+
+impl DepositRequest {
+    pub fn amount(&self) -> &Coin {
+        &self.amount
+    }
+
+    pub fn execute_msg(&self) -> ExecuteMsg {
+        ExecuteMsg::DepositFunds {
+            data: self.data.clone(),
+        }
+    }
+
+    /// Encodes the request as an URI, to be opened by the wallet directly or rendered as a QR code,
+    /// i.e. `nym-deposit:<contract>?amount=<amount>&denom=<denom>&msg=<execute msg>&memo=<memo>`
+    pub fn to_uri(&self) -> String {
+        let msg = serde_json::to_string(&self.execute_msg())
+            .expect("deposit message serialization can't fail");
+
+        let mut uri = Url::parse(&format!(
+            "{DEPOSIT_REQUEST_URI_SCHEME}:{}",
+            self.contract_address
+        ))
+        .expect("deposit request uri is always valid");
+        uri.query_pairs_mut()
+            .append_pair("amount", &self.amount.amount.to_string())
+            .append_pair("denom", &self.amount.denom)
+            .append_pair("msg", &msg)
+            .append_pair("memo", DEPOSIT_REQUEST_MEMO);
+        uri.to_string()
+    }
+}
+
+/// Deposit request that has been handed out to the wallet alongside the keys required to turn it
+/// into a credential once the deposit lands on chain. It should be persisted until then,
+/// otherwise the deposited funds are lost.
+#[derive(Serialize, Deserialize)]
+pub struct PendingDeposit {
+    request: DepositRequest,
+    signing_keypair: KeyPair,
+    encryption_keypair: KeyPair,
+}
+
+impl PendingDeposit {
+    pub fn new(contract_address: String, amount: Coin) -> Self {
+        let mut rng = OsRng;
+        let signing_keypair = KeyPair::from(identity::KeyPair::new(&mut rng));
+        let encryption_keypair = KeyPair::from(encryption::KeyPair::new(&mut rng));
+
+        let data = DepositData::new(
+            VOUCHER_INFO.to_string(),
+            signing_keypair.public_key.clone(),
+            encryption_keypair.public_key.clone(),
+        );
+
+        PendingDeposit {
+            request: DepositRequest {
+                contract_address,
+                amount,
+                data,
+            },
+            signing_keypair,
+            encryption_keypair,
+        }
+    }
+
+    pub fn request(&self) -> &DepositRequest {
+        &self.request
+    }
+
+    // returns the value of the deposit made by the transaction, if it was made for this request
+    fn matching_deposit_value(&self, tx: &TxResponse) -> Option<String> {
+        let event_type = format!("wasm-{DEPOSITED_FUNDS_EVENT_TYPE}");
+        tx.tx_result
+            .events
+            .iter()
+            .filter(|event| event.type_str == event_type)
+            .find_map(|event| {
+                let attribute = |key: &str| {
+                    event
+                        .attributes
+                        .iter()
+                        .find(|tag| tag.key.as_ref() == key)
+                        .map(|tag| tag.value.as_ref())
+                };
+                if attribute(DEPOSIT_IDENTITY_KEY)? != self.signing_keypair.public_key
+                    || attribute(DEPOSIT_INFO)? != VOUCHER_INFO
+                {
+                    return None;
+                }
+                attribute(DEPOSIT_VALUE).map(ToString::to_string)
+            })
+    }
+
+    /// Looks for the deposit made for this request on chain and, if it has already landed,
+    /// returns the state required for obtaining the credential.
+    pub async fn try_find_deposit<C>(
+        &self,
+        client: &NyxdClient<C>,
+    ) -> Result<Option<State>, BandwidthControllerError>
+    where
+        C: CosmWasmClient + Sync,
+    {
+        let query = Query::eq(
+            format!("wasm-{DEPOSITED_FUNDS_EVENT_TYPE}.{DEPOSIT_IDENTITY_KEY}"),
+            self.signing_keypair.public_key.clone(),
+        );
+
+        for tx in client.search_tx(query).await? {
+            // note: the wallet might have deposited a different amount than what was requested,
+            // but the credential has to reflect what was actually deposited
+            let Some(voucher_value) = self.matching_deposit_value(&tx) else {
+                continue;
+            };
+
+            let params = Parameters::new(TOTAL_ATTRIBUTES)?;
+            let voucher = BandwidthVoucher::new(
+                &params,
+                voucher_value,
+                VOUCHER_INFO.to_string(),
+                default_expiration_date(),
+                Hash::from_str(&tx.hash.to_string())
+                    .map_err(|_| BandwidthControllerError::InvalidTxHash)?,
+                identity::PrivateKey::from_base58_string(&self.signing_keypair.private_key)?,
+                encryption::PrivateKey::from_base58_string(&self.encryption_keypair.private_key)?,
+            );
+            return Ok(Some(State { voucher, params }));
+        }
+
+        Ok(None)
+    }
+}
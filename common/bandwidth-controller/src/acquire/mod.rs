@@ -4,7 +4,9 @@
 use crate::error::BandwidthControllerError;
 use nym_coconut_interface::{Base58, Parameters};
 use nym_credential_storage::storage::Storage;
-use nym_credentials::coconut::bandwidth::{BandwidthVoucher, TOTAL_ATTRIBUTES};
+use nym_credentials::coconut::bandwidth::{
+    default_expiration_date, BandwidthVoucher, TOTAL_ATTRIBUTES,
+};
 use nym_credentials::coconut::utils::obtain_aggregate_signature;
 use nym_crypto::asymmetric::{encryption, identity};
 use nym_network_defaults::VOUCHER_INFO;
@@ -17,6 +19,7 @@ use rand::rngs::OsRng;
 use state::{KeyPair, State};
 use std::str::FromStr;
 
+pub mod deposit_request;
 pub mod state;
 
 pub async fn deposit<C>(client: &C, amount: Coin) -> Result<State, BandwidthControllerError>
@@ -45,6 +48,7 @@ where
         &params,
         voucher_value,
         VOUCHER_INFO.to_string(),
+        default_expiration_date(),
         Hash::from_str(&tx_hash).map_err(|_| BandwidthControllerError::InvalidTxHash)?,
         identity::PrivateKey::from_base58_string(&signing_keypair.private_key)?,
         encryption::PrivateKey::from_base58_string(&encryption_keypair.private_key)?,
@@ -82,6 +86,10 @@ pub async fn get_credential<C: DkgQueryClient + Send + Sync, St: Storage>(
             state.voucher.get_private_attributes()[1].to_bs58(),
             signature.to_bs58(),
             epoch_id.to_string(),
+            state
+                .voucher
+                .get_expiration_date()
+                .map(|expiration_date| expiration_date.to_string()),
         )
         .await?;
 
@@ -5,7 +5,9 @@ use nym_coconut_interface::Parameters;
 use nym_credentials::coconut::bandwidth::{BandwidthVoucher, TOTAL_ATTRIBUTES};
 
 use nym_crypto::asymmetric::{encryption, identity};
+use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize)]
 pub(crate) struct KeyPair {
     pub public_key: String,
     pub private_key: String,
@@ -38,4 +38,11 @@ pub enum BandwidthControllerError {
 
     #[error("Threshold not set yet")]
     NoThreshold,
+
+    #[error("The stored credential {id} worth {voucher_value} has expired at {expiration_date} and has been discarded")]
+    ExpiredCredential {
+        id: i64,
+        voucher_value: String,
+        expiration_date: u64,
+    },
 }
@@ -47,6 +47,33 @@ impl<C, St: Storage> BandwidthController<C, St> {
         C: DkgQueryClient + Sync + Send,
     {
         let bandwidth_credential = self.storage.get_next_coconut_credential().await?;
+        let expiration_date = bandwidth_credential
+            .expiration_date
+            .as_deref()
+            .map(u64::from_str)
+            .transpose()
+            .map_err(|_| StorageError::InconsistentData)?;
+
+        // there's no reliable system clock available in wasm,
+        // so over there it's up to the gateway to reject the expired credentials
+        #[cfg(not(target_arch = "wasm32"))]
+        if nym_credentials::coconut::bandwidth::has_expired(
+            expiration_date,
+            nym_credentials::coconut::bandwidth::current_unix_timestamp(),
+        ) {
+            // the credential can no longer be spent, so it's taken out of the storage,
+            // but the caller has to learn about the lost bandwidth
+            self.storage
+                .consume_coconut_credential(bandwidth_credential.id)
+                .await?;
+            return Err(BandwidthControllerError::ExpiredCredential {
+                id: bandwidth_credential.id,
+                voucher_value: bandwidth_credential.voucher_value,
+                expiration_date: expiration_date
+                    .unwrap_or(nym_credentials::coconut::bandwidth::LEGACY_CREDENTIALS_DEADLINE),
+            });
+        }
+
         let voucher_value = u64::from_str(&bandwidth_credential.voucher_value)
             .map_err(|_| StorageError::InconsistentData)?;
         let voucher_info = bandwidth_credential.voucher_info.clone();
@@ -72,6 +99,7 @@ impl<C, St: Storage> BandwidthController<C, St> {
             prepare_for_spending(
                 voucher_value,
                 voucher_info,
+                expiration_date,
                 serial_number,
                 binding_number,
                 epoch_id,
@@ -4,6 +4,29 @@
 // TODO: at a later date this crate should probably also expose `ContractBuildInformation`
 // and be used by our smart contracts
 
+#[cfg(feature = "output_format")]
+use crate::output_format::OutputFormat;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+/// Names of the wire protocols whose versions are reported alongside the build information.
+pub mod protocols {
+    /// Format of the sphinx packets exchanged between all the nodes of the mixnet.
+    pub const SPHINX_PACKET: &str = "sphinx-packet";
+
+    /// Protocol spoken between the clients and their gateways.
+    pub const GATEWAY_CLIENT: &str = "gateway-client";
+
+    /// Protocol spoken between the federated gateways.
+    pub const GATEWAY_FEDERATION: &str = "gateway-federation";
+
+    /// Interface of the service providers, such as the network requester.
+    pub const SERVICE_PROVIDER_INTERFACE: &str = "service-provider-interface";
+
+    /// Requests exchanged between the socks5 client and the network requester.
+    pub const SOCKS5: &str = "socks5";
+}
+
 #[derive(Debug)]
 pub struct BinaryBuildInformation {
     // VERGEN_BUILD_TIMESTAMP
@@ -37,6 +60,12 @@ pub struct BinaryBuildInformation {
     // VERGEN_CARGO_PROFILE
     /// Provides the cargo profile that was used for the build, for example `debug`.
     pub cargo_profile: &'static str,
+
+    /// Cargo features that were enabled for the build, for example `["coconut"]`.
+    pub enabled_features: &'static [&'static str],
+
+    /// Versions of the wire protocols spoken by the binary, for example `[("sphinx-packet", 7)]`.
+    pub protocol_versions: &'static [(&'static str, u8)],
 }
 
 impl BinaryBuildInformation {
@@ -51,6 +80,27 @@ impl BinaryBuildInformation {
             rustc_version: env!("VERGEN_RUSTC_SEMVER"),
             rustc_channel: env!("VERGEN_RUSTC_CHANNEL"),
             cargo_profile: env!("VERGEN_CARGO_PROFILE"),
+            enabled_features: &[],
+            protocol_versions: &[],
+        }
+    }
+
+    // the features have to be provided explicitly as the ones available to the build script
+    // of this crate are not the ones of the binary
+    pub const fn with_enabled_features(self, enabled_features: &'static [&'static str]) -> Self {
+        BinaryBuildInformation {
+            enabled_features,
+            ..self
+        }
+    }
+
+    pub const fn with_protocol_versions(
+        self,
+        protocol_versions: &'static [(&'static str, u8)],
+    ) -> Self {
+        BinaryBuildInformation {
+            protocol_versions,
+            ..self
         }
     }
 
@@ -64,11 +114,34 @@ impl BinaryBuildInformation {
             rustc_version: self.rustc_version.to_owned(),
             rustc_channel: self.rustc_channel.to_owned(),
             cargo_profile: self.cargo_profile.to_owned(),
+            enabled_features: self
+                .enabled_features
+                .iter()
+                .map(|feature| feature.to_string())
+                .collect(),
+            protocol_versions: self
+                .protocol_versions
+                .iter()
+                .map(|(protocol, version)| (protocol.to_string(), *version))
+                .collect(),
         }
     }
 
     pub fn pretty_print(&self) -> String {
-        format!(
+        self.to_owned().to_string()
+    }
+}
+
+impl Display for BinaryBuildInformationOwned {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let protocol_versions = self
+            .protocol_versions
+            .iter()
+            .map(|(protocol, version)| format!("{protocol} v{version}"))
+            .collect::<Vec<_>>();
+
+        write!(
+            f,
             r#"
 {:<20}{}
 {:<20}{}
@@ -78,6 +151,8 @@ impl BinaryBuildInformation {
 {:<20}{}
 {:<20}{}
 {:<20}{}
+{:<20}{}
+{:<20}{}
 "#,
             "Build Timestamp:",
             self.build_timestamp,
@@ -95,11 +170,15 @@ impl BinaryBuildInformation {
             self.rustc_channel,
             "cargo Profile:",
             self.cargo_profile,
+            "Enabled Features:",
+            self.enabled_features.join(", "),
+            "Protocol Versions:",
+            protocol_versions.join(", "),
         )
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BinaryBuildInformationOwned {
     // VERGEN_BUILD_TIMESTAMP
@@ -133,4 +212,65 @@ pub struct BinaryBuildInformationOwned {
     // VERGEN_CARGO_PROFILE
     /// Provides the cargo profile that was used for the build, for example `debug`.
     pub cargo_profile: String,
+
+    /// Cargo features that were enabled for the build, for example `["coconut"]`.
+    // older binaries did not report it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub enabled_features: Vec<String>,
+
+    /// Versions of the wire protocols spoken by the binary, for example `{"sphinx-packet": 7}`.
+    // older binaries did not report it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub protocol_versions: BTreeMap<String, u8>,
+}
+
+impl BinaryBuildInformationOwned {
+    /// Version of the given wire protocol spoken by the binary, if it was reported.
+    pub fn protocol_version(&self, protocol: &str) -> Option<u8> {
+        self.protocol_versions.get(protocol).copied()
+    }
+
+    /// Checks whether the binary speaks the given version of the wire protocol. Binaries that
+    /// did not report the version of the protocol are assumed to be compatible.
+    pub fn is_protocol_compatible(&self, protocol: &str, version: u8) -> bool {
+        match self.protocol_version(protocol) {
+            Some(reported) => reported == version,
+            None => true,
+        }
+    }
+}
+
+/// Arguments of the standard `build-info` command.
+#[cfg(feature = "output_format")]
+#[derive(clap::Args, Debug, Clone, Copy)]
+pub struct BuildInfo {
+    #[clap(short, long, default_value_t = OutputFormat::default())]
+    output: OutputFormat,
+}
+
+#[cfg(feature = "output_format")]
+impl BuildInfo {
+    pub fn execute(&self, build_information: &BinaryBuildInformation) {
+        println!("{}", self.output.format(&build_information.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_protocol_versions_are_assumed_compatible() {
+        let build_information = BinaryBuildInformation::new("1.2.3")
+            .with_protocol_versions(&[(protocols::SPHINX_PACKET, 7)])
+            .to_owned();
+
+        assert_eq!(
+            build_information.protocol_version(protocols::SPHINX_PACKET),
+            Some(7)
+        );
+        assert!(build_information.is_protocol_compatible(protocols::SPHINX_PACKET, 7));
+        assert!(!build_information.is_protocol_compatible(protocols::SPHINX_PACKET, 8));
+        assert!(build_information.is_protocol_compatible(protocols::GATEWAY_CLIENT, 1));
+    }
 }
@@ -14,7 +14,7 @@ pub fn fig_generate(command: &mut Command, name: &str) {
     )
 }
 
-#[derive(ValueEnum, Copy, Clone)]
+#[derive(ValueEnum, Debug, Copy, Clone)]
 pub enum Shell {
     Bash,
     Elvish,
@@ -23,7 +23,7 @@ pub enum Shell {
     Zsh,
 }
 
-#[derive(Args, Copy, Clone)]
+#[derive(Args, Debug, Copy, Clone)]
 pub struct ArgShell {
     #[clap(value_enum, value_name = "SHELL")]
     shell: Shell,
@@ -35,6 +35,14 @@ pub fn setup_logging() {
         .init();
 }
 
+/// Starts serving the runtime instrumentation data to the `tokio-console`, so that stalled or
+/// busy tasks could be inspected in a running binary.
+/// Note that the binary has to be built with `RUSTFLAGS="--cfg tokio_unstable"`.
+#[cfg(feature = "tokio-console")]
+pub fn setup_tokio_console() {
+    console_subscriber::init();
+}
+
 // TODO: This has to be a macro, running it as a function does not work for the file_appender for some reason
 #[cfg(feature = "tracing")]
 #[macro_export]
@@ -0,0 +1,6 @@
+-- present only if the stored reply keys, reply surbs and recipients are encrypted with a passphrase
+CREATE TABLE store_cipher
+(
+    id       INTEGER PRIMARY KEY CHECK (id = 0),
+    exported BLOB NOT NULL
+);
@@ -0,0 +1,10 @@
+-- sender tag the reply surb corresponding to given key has been sent with.
+-- it's unknown for the keys stored before the column got introduced
+ALTER TABLE reply_key ADD COLUMN sender_tag BLOB;
+
+-- tags explicitly chosen for isolating different streams sent to the same recipient
+CREATE TABLE isolated_sender_tag
+(
+    recipient BLOB NOT NULL,
+    tag       BLOB NOT NULL UNIQUE
+);
@@ -0,0 +1,256 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::client::helpers::{get_time_now, Instant};
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+// number of the most recent bandwidth samples used for estimating the consumption rate
+const TRACKED_BANDWIDTH_SAMPLES: usize = 10;
+
+/// Estimate of how long the bandwidth remaining at the gateway is going to last
+/// at the current consumption rate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BandwidthForecast {
+    /// Bandwidth (in bytes) remaining at the gateway.
+    pub remaining: i64,
+
+    /// Average consumption rate (in bytes per second) observed over the recent bandwidth checks.
+    pub consumption_rate: u64,
+
+    /// Estimated time until the remaining bandwidth falls below the top-up threshold.
+    /// Unknown if no bandwidth is being consumed.
+    pub until_top_up: Option<Duration>,
+
+    /// Estimated time until all of the remaining bandwidth is used up.
+    /// Unknown if no bandwidth is being consumed.
+    pub until_depletion: Option<Duration>,
+
+    /// Estimated time left before the bandwidth runs out or, with the automatic top-ups disabled,
+    /// before it falls below the top-up threshold, as it's not going to be topped up on its own.
+    pub time_left: Option<Duration>,
+
+    /// Indicates whether the `time_left` is within the warning period.
+    pub running_low: bool,
+}
+
+impl Display for BandwidthForecast {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.time_left {
+            Some(left) => write!(
+                f,
+                "{} bytes of bandwidth remaining, ~{} minutes left at the current rate of {} bytes/s",
+                self.remaining,
+                left.as_secs() / 60,
+                self.consumption_rate
+            ),
+            None => write!(f, "{} bytes of bandwidth remaining", self.remaining),
+        }
+    }
+}
+
+/// Tracks the bandwidth remaining at the gateway in order to estimate how long it's going to last.
+pub(crate) struct BandwidthForecaster {
+    top_up_threshold: i64,
+    automatic_top_up: bool,
+    warning_period: Duration,
+    control: BandwidthForecastControl,
+
+    /// The most recent samples of the remaining bandwidth, starting with the oldest one.
+    samples: VecDeque<(Instant, i64)>,
+}
+
+impl BandwidthForecaster {
+    pub(crate) fn new(
+        top_up_threshold: i64,
+        automatic_top_up: bool,
+        warning_period: Duration,
+        control: BandwidthForecastControl,
+    ) -> Self {
+        BandwidthForecaster {
+            top_up_threshold,
+            automatic_top_up,
+            warning_period,
+            control,
+            samples: VecDeque::with_capacity(TRACKED_BANDWIDTH_SAMPLES),
+        }
+    }
+
+    /// Records the currently remaining bandwidth and publishes the updated forecast.
+    pub(crate) fn record(&mut self, remaining: i64) -> BandwidthForecast {
+        let forecast = self.record_at(remaining, get_time_now());
+        self.control.update(forecast);
+        forecast
+    }
+
+    fn record_at(&mut self, remaining: i64, now: Instant) -> BandwidthForecast {
+        // once the bandwidth gets topped up, the earlier samples no longer say anything
+        // about the consumption
+        if matches!(self.samples.back(), Some((_, previous)) if *previous < remaining) {
+            self.samples.clear();
+        }
+        if self.samples.len() == TRACKED_BANDWIDTH_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, remaining));
+
+        let consumption_rate = match self.samples.front() {
+            Some((oldest_at, oldest)) => {
+                let elapsed = now.duration_since(*oldest_at).as_secs_f64();
+                if elapsed > 0.0 {
+                    ((oldest - remaining) as f64 / elapsed) as u64
+                } else {
+                    0
+                }
+            }
+            None => 0,
+        };
+
+        let time_until = |amount: i64| {
+            if consumption_rate == 0 {
+                None
+            } else {
+                Some(Duration::from_secs(amount.max(0) as u64 / consumption_rate))
+            }
+        };
+        let until_top_up = time_until(remaining - self.top_up_threshold);
+        let until_depletion = time_until(remaining);
+
+        let time_left = if self.automatic_top_up {
+            until_depletion
+        } else {
+            until_top_up
+        };
+
+        BandwidthForecast {
+            remaining,
+            consumption_rate,
+            until_top_up,
+            until_depletion,
+            time_left,
+            running_low: matches!(time_left, Some(left) if left <= self.warning_period),
+        }
+    }
+}
+
+/// Handle used for inspecting the latest forecast of the bandwidth remaining at the gateway.
+#[derive(Debug, Clone)]
+pub struct BandwidthForecastControl {
+    inner: Arc<watch::Sender<BandwidthForecast>>,
+}
+
+impl Default for BandwidthForecastControl {
+    fn default() -> Self {
+        BandwidthForecastControl::new()
+    }
+}
+
+impl BandwidthForecastControl {
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(BandwidthForecast::default());
+        BandwidthForecastControl {
+            inner: Arc::new(tx),
+        }
+    }
+
+    pub(crate) fn update(&self, forecast: BandwidthForecast) {
+        self.inner.send_replace(forecast);
+    }
+
+    pub fn forecast(&self) -> BandwidthForecast {
+        *self.inner.borrow()
+    }
+
+    pub fn listener(&self) -> BandwidthForecastListener {
+        BandwidthForecastListener {
+            inner: self.inner.subscribe(),
+        }
+    }
+}
+
+/// Receiving end of the [`BandwidthForecastControl`] used for getting notified about
+/// the updated forecasts.
+#[derive(Debug, Clone)]
+pub struct BandwidthForecastListener {
+    inner: watch::Receiver<BandwidthForecast>,
+}
+
+impl BandwidthForecastListener {
+    /// Waits until the forecast gets updated and returns the new value.
+    /// If the controlling end has been dropped, the forecast can no longer change and thus
+    /// the future never resolves.
+    pub async fn changed(&mut self) -> BandwidthForecast {
+        if self.inner.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+        *self.inner.borrow_and_update()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forecasts_depletion_at_the_current_rate() {
+        let start = get_time_now();
+        let mut forecaster = BandwidthForecaster::new(
+            1000,
+            true,
+            Duration::from_secs(600),
+            BandwidthForecastControl::new(),
+        );
+
+        let forecast = forecaster.record_at(100_000, start);
+        assert_eq!(forecast.consumption_rate, 0);
+        assert!(forecast.until_depletion.is_none());
+        assert!(!forecast.running_low);
+
+        let forecast = forecaster.record_at(40_000, start + Duration::from_secs(600));
+        assert_eq!(forecast.consumption_rate, 100);
+        assert_eq!(forecast.until_depletion, Some(Duration::from_secs(400)));
+        assert_eq!(forecast.until_top_up, Some(Duration::from_secs(390)));
+        assert!(forecast.running_low);
+    }
+
+    #[test]
+    fn top_ups_reset_the_consumption_history() {
+        let start = get_time_now();
+        let mut forecaster = BandwidthForecaster::new(
+            1000,
+            true,
+            Duration::from_secs(600),
+            BandwidthForecastControl::new(),
+        );
+
+        forecaster.record_at(100_000, start);
+        forecaster.record_at(40_000, start + Duration::from_secs(600));
+
+        let forecast = forecaster.record_at(1_000_000, start + Duration::from_secs(660));
+        assert_eq!(forecast.consumption_rate, 0);
+        assert!(!forecast.running_low);
+    }
+
+    #[test]
+    fn warns_about_reaching_threshold_without_automatic_top_ups() {
+        let start = get_time_now();
+        let mut forecaster = BandwidthForecaster::new(
+            50_000,
+            false,
+            Duration::from_secs(600),
+            BandwidthForecastControl::new(),
+        );
+
+        forecaster.record_at(200_000, start);
+        let forecast = forecaster.record_at(140_000, start + Duration::from_secs(600));
+        assert_eq!(forecast.until_top_up, Some(Duration::from_secs(900)));
+        assert!(!forecast.running_low);
+
+        let forecast = forecaster.record_at(100_000, start + Duration::from_secs(1000));
+        assert_eq!(forecast.until_top_up, Some(Duration::from_secs(500)));
+        assert!(forecast.running_low);
+    }
+}
@@ -2,12 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::received_buffer::ReceivedBufferMessage;
+use crate::client::bandwidth_forecast::{BandwidthForecastControl, BandwidthForecaster};
 use crate::client::cover_traffic_stream::LoopCoverTrafficStream;
+use crate::client::delivery::{DeadLetter, DeliveryEventReceiver, MessageId};
+use crate::client::events::{ClientEvent, ClientEventBus};
+use crate::client::hibernation::{HibernationControl, HibernationListener};
 use crate::client::inbound_messages::{InputMessage, InputMessageReceiver, InputMessageSender};
 use crate::client::key_manager::KeyManager;
+use crate::client::latency_stats::LatencyStatsControl;
 use crate::client::mix_traffic::{BatchMixMessageSender, MixTrafficController};
 use crate::client::real_messages_control;
-use crate::client::real_messages_control::RealMessagesController;
+use crate::client::real_messages_control::{
+    AckActionReceiver, AckActionSender, Action, RealMessagesController,
+};
 use crate::client::received_buffer::{
     ReceivedBufferRequestReceiver, ReceivedBufferRequestSender, ReceivedMessagesBufferController,
 };
@@ -16,20 +23,27 @@ use crate::client::replies::reply_controller::{ReplyControllerReceiver, ReplyCon
 use crate::client::replies::reply_storage::{
     CombinedReplyStorage, PersistentReplyStorage, ReplyStorageBackend, SentReplyKeys,
 };
+use crate::client::topology_control::anomaly::{TopologyAnomalyControl, TopologyAnomalyListener};
+use crate::client::topology_control::node_filter::NodeFilter;
 use crate::client::topology_control::nym_api_provider::NymApiTopologyProvider;
+use crate::client::topology_control::route_pool::RoutePoolReplenisher;
+use crate::client::topology_control::unreachable_hops::UnreachableHopsListener;
 use crate::client::topology_control::{
     TopologyAccessor, TopologyRefresher, TopologyRefresherConfig,
 };
-use crate::config::{Config, DebugConfig, GatewayEndpointConfig};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::traffic_recording::{self, TrafficRecorder, TrafficReplayer};
+use crate::config::{self, Config, DebugConfig, GatewayEndpointConfig};
 use crate::error::ClientCoreError;
 use crate::spawn_future;
-use futures::channel::mpsc;
+use futures::channel::{mpsc, oneshot};
 use log::{debug, info};
 use nym_bandwidth_controller::BandwidthController;
 use nym_crypto::asymmetric::{encryption, identity};
+use nym_gateway_client::embedded::EmbeddedConnection;
 use nym_gateway_client::{
     AcknowledgementReceiver, AcknowledgementSender, GatewayClient, MixnetMessageReceiver,
-    MixnetMessageSender,
+    MixnetMessageSender, UnreachableHopReceiver, UnreachableHopSender,
 };
 use nym_sphinx::acknowledgements::AckKey;
 use nym_sphinx::addressing::clients::Recipient;
@@ -38,6 +52,8 @@ use nym_sphinx::receiver::{ReconstructedMessage, SphinxMessageReceiver};
 use nym_task::connections::{ConnectionCommandReceiver, ConnectionCommandSender, LaneQueueLengths};
 use nym_task::{TaskClient, TaskManager};
 use nym_topology::provider_trait::TopologyProvider;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use tap::TapFallible;
@@ -73,6 +89,7 @@ impl ClientInput {
 #[derive(Clone)]
 pub struct ClientOutput {
     pub received_buffer_request_sender: ReceivedBufferRequestSender,
+    ack_action_sender: AckActionSender,
 }
 
 impl ClientOutput {
@@ -89,6 +106,49 @@ impl ClientOutput {
 
         Ok(reconstructed_receiver)
     }
+
+    /// Registers a listener for the outcomes of messages sent with delivery tracking enabled.
+    /// Note that only a single listener can be registered at any given time.
+    pub fn register_delivery_receiver(&mut self) -> Result<DeliveryEventReceiver, ClientCoreError> {
+        let (delivery_sender, delivery_receiver) = mpsc::unbounded();
+
+        self.ack_action_sender
+            .unbounded_send(Action::RegisterDeliveryListener(delivery_sender))
+            .map_err(|_| ClientCoreError::FailedToRegisterDeliveryListener)?;
+
+        Ok(delivery_receiver)
+    }
+
+    /// Retrieves all tracked messages that could not have been delivered and that are still
+    /// retained on the dead letter queue.
+    pub async fn dead_letters(&self) -> Result<Vec<DeadLetter>, ClientCoreError> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.ack_action_sender
+            .unbounded_send(Action::GetDeadLetters(response_tx))
+            .map_err(|_| ClientCoreError::FailedToQueryDeadLetters)?;
+
+        response_rx
+            .await
+            .map_err(|_| ClientCoreError::FailedToQueryDeadLetters)
+    }
+
+    /// Removes the message with the specified id from the dead letter queue, so that,
+    /// for example, it could be sent again.
+    pub async fn take_dead_letter(
+        &self,
+        message_id: MessageId,
+    ) -> Result<Option<DeadLetter>, ClientCoreError> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.ack_action_sender
+            .unbounded_send(Action::TakeDeadLetter(message_id, response_tx))
+            .map_err(|_| ClientCoreError::FailedToQueryDeadLetters)?;
+
+        response_rx
+            .await
+            .map_err(|_| ClientCoreError::FailedToQueryDeadLetters)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -96,6 +156,11 @@ pub struct ClientState {
     pub shared_lane_queue_lengths: LaneQueueLengths,
     pub reply_controller_sender: ReplyControllerSender,
     pub topology_accessor: TopologyAccessor,
+    pub hibernation: HibernationControl,
+    pub topology_anomalies: TopologyAnomalyControl,
+    pub bandwidth_forecast: BandwidthForecastControl,
+    pub latency_stats: LatencyStatsControl,
+    pub events: ClientEventBus,
 }
 
 pub enum ClientInputStatus {
@@ -158,11 +223,19 @@ pub struct BaseClientBuilder<'a, B, C, St: Storage> {
     debug_config: &'a DebugConfig,
     disabled_credentials: bool,
     nym_api_endpoints: Vec<Url>,
+    directory_proxy: Option<Url>,
+    ignored_nodes: Vec<String>,
     reply_storage_backend: B,
 
     custom_topology_provider: Option<Box<dyn TopologyProvider>>,
+    embedded_gateway_connection: Option<EmbeddedConnection>,
     bandwidth_controller: Option<BandwidthController<C, St>>,
     key_manager: KeyManager,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    traffic_recording: Option<PathBuf>,
+    #[cfg(not(target_arch = "wasm32"))]
+    traffic_replay: Option<PathBuf>,
 }
 
 impl<'a, B, C, St> BaseClientBuilder<'a, B, C, St>
@@ -182,10 +255,17 @@ where
             debug_config: base_config.get_debug_config(),
             disabled_credentials: base_config.get_disabled_credentials_mode(),
             nym_api_endpoints: base_config.get_nym_api_endpoints(),
+            directory_proxy: base_config.get_directory_proxy(),
+            ignored_nodes: base_config.get_ignored_nodes(),
             bandwidth_controller,
             reply_storage_backend,
             key_manager,
             custom_topology_provider: None,
+            embedded_gateway_connection: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            traffic_recording: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            traffic_replay: None,
         }
     }
 
@@ -203,10 +283,17 @@ where
             debug_config,
             disabled_credentials: credentials_toggle.is_disabled(),
             nym_api_endpoints,
+            directory_proxy: None,
+            ignored_nodes: Vec::new(),
             reply_storage_backend,
             custom_topology_provider: None,
+            embedded_gateway_connection: None,
             bandwidth_controller,
             key_manager,
+            #[cfg(not(target_arch = "wasm32"))]
+            traffic_recording: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            traffic_replay: None,
         }
     }
 
@@ -215,6 +302,33 @@ where
         self
     }
 
+    /// Never use any of the nodes with the specified identity keys in the routes of the client.
+    pub fn with_ignored_nodes(mut self, ignored_nodes: Vec<String>) -> Self {
+        self.ignored_nodes = ignored_nodes;
+        self
+    }
+
+    /// Use an in-process connection to the gateway instead of the websocket.
+    /// Only applicable if the client is running within the gateway binary itself.
+    pub fn with_embedded_gateway_connection(mut self, connection: EmbeddedConnection) -> Self {
+        self.embedded_gateway_connection = Some(connection);
+        self
+    }
+
+    /// Development option: record all the packets sent to the gateway into the specified file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_traffic_recording(mut self, path: PathBuf) -> Self {
+        self.traffic_recording = Some(path);
+        self
+    }
+
+    /// Development option: replay the traffic previously recorded into the specified file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_traffic_replay(mut self, path: PathBuf) -> Self {
+        self.traffic_replay = Some(path);
+        self
+    }
+
     pub fn as_mix_recipient(&self) -> Recipient {
         Recipient::new(
             *self.key_manager.identity_keypair().public_key(),
@@ -233,6 +347,7 @@ where
         self_address: Recipient,
         topology_accessor: TopologyAccessor,
         mix_tx: BatchMixMessageSender,
+        hibernation: HibernationListener,
         shutdown: TaskClient,
     ) {
         info!("Starting loop cover traffic stream...");
@@ -245,6 +360,7 @@ where
             topology_accessor,
             debug_config.traffic,
             debug_config.cover_traffic,
+            hibernation,
         );
 
         stream.start_with_shutdown(shutdown);
@@ -262,6 +378,10 @@ where
         reply_controller_receiver: ReplyControllerReceiver,
         lane_queue_lengths: LaneQueueLengths,
         client_connection_rx: ConnectionCommandReceiver,
+        ack_action_sender: AckActionSender,
+        ack_action_receiver: AckActionReceiver,
+        hibernation: HibernationListener,
+        latency_stats: LatencyStatsControl,
         shutdown: TaskClient,
     ) {
         info!("Starting real traffic stream...");
@@ -277,6 +397,10 @@ where
             reply_controller_receiver,
             lane_queue_lengths,
             client_connection_rx,
+            ack_action_sender,
+            ack_action_receiver,
+            hibernation,
+            latency_stats,
         )
         .start_with_shutdown(shutdown);
     }
@@ -307,6 +431,8 @@ where
         &mut self,
         mixnet_message_sender: MixnetMessageSender,
         ack_sender: AcknowledgementSender,
+        unreachable_hop_sender: UnreachableHopSender,
+        events: &ClientEventBus,
         shutdown: TaskClient,
     ) -> Result<GatewayClient<C, St>, ClientCoreError> {
         let gateway_id = self.gateway_config.gateway_id.clone();
@@ -344,6 +470,36 @@ where
         );
 
         gateway_client.set_disabled_credentials_mode(self.disabled_credentials);
+        gateway_client.with_delivered_messages_window(
+            self.debug_config
+                .gateway_connection
+                .delivered_messages_dedup_window,
+        );
+        gateway_client.with_bandwidth_top_up_threshold(
+            self.debug_config
+                .gateway_connection
+                .bandwidth_top_up_threshold,
+        );
+        gateway_client.with_automatic_bandwidth_top_up(
+            !self.debug_config.gateway_connection.manual_bandwidth_top_up,
+        );
+        gateway_client.with_unreachable_hop_sender(unreachable_hop_sender);
+        if let Some(embedded_connection) = self.embedded_gateway_connection.take() {
+            log::info!("Using the embedded connection to the gateway");
+            gateway_client.with_embedded_connection(embedded_connection);
+        }
+
+        // fail fast with a meaningful diagnostic rather than timing out later on
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if !gateway_client.is_embedded() {
+                let report = gateway_client
+                    .perform_connection_test()
+                    .await
+                    .tap_err(|err| log::error!("{err}"))?;
+                log::info!("Gateway connection test succeeded - {report}");
+            }
+        }
 
         gateway_client
             .authenticate_and_start()
@@ -351,36 +507,60 @@ where
             .tap_err(|err| {
                 log::error!("Could not authenticate and start up the gateway connection - {err}")
             })?;
+        events.emit(ClientEvent::GatewayConnected {
+            gateway: gateway_client.gateway_identity().to_base58_string(),
+        });
         Ok(gateway_client)
     }
 
     fn setup_topology_provider(
         custom_provider: Option<Box<dyn TopologyProvider>>,
         nym_api_urls: Vec<Url>,
-    ) -> Box<dyn TopologyProvider> {
+        directory_proxy: Option<Url>,
+        node_filter: NodeFilter,
+    ) -> Result<Box<dyn TopologyProvider>, ClientCoreError> {
         // if no custom provider was ... provided ..., create one using nym-api
-        custom_provider.unwrap_or_else(|| {
-            Box::new(NymApiTopologyProvider::new(
+        match custom_provider {
+            Some(provider) => Ok(provider),
+            None => Ok(Box::new(NymApiTopologyProvider::new(
                 nym_api_urls,
                 env!("CARGO_PKG_VERSION").to_string(),
-            ))
-        })
+                directory_proxy,
+                node_filter,
+            )?)),
+        }
     }
 
     // future responsible for periodically polling directory server and updating
     // the current global view of topology
+    #[allow(clippy::too_many_arguments)]
     async fn start_topology_refresher(
         topology_provider: Box<dyn TopologyProvider>,
-        refresh_rate: Duration,
+        topology_config: config::Topology,
+        hibernation_refresh_rate: Duration,
         topology_accessor: TopologyAccessor,
+        hibernation: HibernationListener,
+        topology_anomalies: TopologyAnomalyControl,
+        events: ClientEventBus,
         shutdown: TaskClient,
     ) -> Result<(), ClientCoreError> {
-        let topology_refresher_config = TopologyRefresherConfig::new(refresh_rate);
+        let topology_refresher_config = TopologyRefresherConfig::new(
+            topology_config.topology_refresh_rate,
+            hibernation_refresh_rate,
+        )
+        .with_anomaly_detection(
+            topology_config.anomaly_key_change_threshold,
+            topology_config.anomaly_minimum_layer_size,
+            topology_config.pause_on_topology_anomaly,
+        );
 
         let mut topology_refresher = TopologyRefresher::new(
             topology_refresher_config,
             topology_accessor,
             topology_provider,
+            hibernation,
+            topology_anomalies,
+            events,
         );
         // before returning, block entire runtime to refresh the current network view so that any
         // components depending on topology would see a non-empty view
@@ -400,16 +580,65 @@ where
         Ok(())
     }
 
+    // future responsible for keeping a pool of precomputed routes through the current topology
+    fn start_route_pool_replenisher(
+        topology_accessor: TopologyAccessor,
+        pool_size: usize,
+        shutdown: TaskClient,
+    ) {
+        if pool_size == 0 {
+            info!("Route precomputation is disabled");
+            return;
+        }
+        info!("Starting route pool replenisher...");
+        RoutePoolReplenisher::new(topology_accessor, pool_size).start_with_shutdown(shutdown)
+    }
+
+    // future responsible for routing the packets around the mixnodes our gateway can't reach
+    fn start_unreachable_hops_listener(
+        topology_accessor: TopologyAccessor,
+        unreachable_hops: UnreachableHopReceiver,
+        shutdown: TaskClient,
+    ) {
+        info!("Starting unreachable hops listener...");
+        UnreachableHopsListener::new(topology_accessor, unreachable_hops)
+            .start_with_shutdown(shutdown)
+    }
+
     // controller for sending sphinx packets to mixnet (either real traffic or cover traffic)
     // TODO: if we want to send control messages to gateway_client, this CAN'T take the ownership
     // over it. Perhaps GatewayClient needs to be thread-shareable or have some channel for
     // requests?
+    #[allow(clippy::too_many_arguments)]
     fn start_mix_traffic_controller(
         gateway_client: GatewayClient<C, St>,
+        bandwidth_check_interval: Duration,
+        bandwidth_forecaster: BandwidthForecaster,
+        keepalive_interval: Duration,
+        hibernation: HibernationListener,
+        topology_anomalies: TopologyAnomalyListener,
+        events: ClientEventBus,
+        #[cfg(not(target_arch = "wasm32"))] traffic_recorder: Option<TrafficRecorder>,
         shutdown: TaskClient,
     ) -> BatchMixMessageSender {
         info!("Starting mix traffic controller...");
-        let (mix_traffic_controller, mix_tx) = MixTrafficController::new(gateway_client);
+        #[allow(unused_mut)]
+        let (mut mix_traffic_controller, mix_tx) = MixTrafficController::new(
+            gateway_client,
+            bandwidth_check_interval,
+            bandwidth_forecaster,
+            keepalive_interval,
+            hibernation,
+            topology_anomalies,
+            events,
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(traffic_recorder) = traffic_recorder {
+            info!("Recording all the sent traffic");
+            mix_traffic_controller = mix_traffic_controller.with_traffic_recorder(traffic_recorder);
+        }
+
         mix_traffic_controller.start_with_shutdown(shutdown);
         mix_tx
     }
@@ -471,21 +700,63 @@ where
 
         // channels responsible for controlling ack messages
         let (ack_sender, ack_receiver) = mpsc::unbounded();
+
+        // channels announcing the mixnodes our gateway failed to forward the packets to
+        let (unreachable_hop_sender, unreachable_hop_receiver) = mpsc::unbounded();
+
+        // channels responsible for dealing with anything ack-related, such as retransmissions
+        // or reporting delivery of messages
+        let (ack_action_sender, ack_action_receiver) = mpsc::unbounded();
         let shared_topology_accessor = TopologyAccessor::new();
 
         // Shutdown notifier for signalling tasks to stop
         let task_manager = TaskManager::default();
 
+        // Control for putting the client into the low-power hibernation mode
+        let hibernation = HibernationControl::new();
+
+        // Control for inspecting and acknowledging suspicious changes of the network topology
+        let topology_anomalies = TopologyAnomalyControl::new();
+
+        // Control for inspecting how long the remaining bandwidth is expected to last
+        let bandwidth_forecast = BandwidthForecastControl::new();
+
+        // Statistics comparing the delays scheduled for the sent packets with the observed round trips
+        let latency_stats = LatencyStatsControl::new();
+
+        // Bus announcing the lifecycle events of the client, such as the gateway (re)connections
+        let events = ClientEventBus::new();
+
         // channels responsible for dealing with reply-related fun
         let (reply_controller_sender, reply_controller_receiver) =
             reply_controller::requests::new_control_channels();
 
         let self_address = self.as_mix_recipient();
 
+        // open the development recordings before anything gets started so that we'd fail early
+        #[cfg(not(target_arch = "wasm32"))]
+        let traffic_recorder = self
+            .traffic_recording
+            .take()
+            .map(TrafficRecorder::new)
+            .transpose()?;
+        #[cfg(not(target_arch = "wasm32"))]
+        let traffic_replay = self
+            .traffic_replay
+            .take()
+            .map(traffic_recording::load_recording)
+            .transpose()?;
+
         // the components are started in very specific order. Unless you know what you are doing,
         // do not change that.
         let gateway_client = self
-            .start_gateway_client(mixnet_messages_sender, ack_sender, task_manager.subscribe())
+            .start_gateway_client(
+                mixnet_messages_sender,
+                ack_sender,
+                unreachable_hop_sender,
+                &events,
+                task_manager.subscribe(),
+            )
             .await?;
 
         let reply_storage = Self::setup_persistent_reply_storage(
@@ -497,15 +768,36 @@ where
         let topology_provider = Self::setup_topology_provider(
             self.custom_topology_provider.take(),
             self.nym_api_endpoints,
-        );
+            self.directory_proxy,
+            NodeFilter::new(
+                self.debug_config.topology.minimum_node_performance,
+                self.ignored_nodes,
+            ),
+        )?;
         Self::start_topology_refresher(
             topology_provider,
-            self.debug_config.topology.topology_refresh_rate,
+            self.debug_config.topology,
+            self.debug_config.hibernation.topology_refresh_rate,
             shared_topology_accessor.clone(),
+            hibernation.listener(),
+            topology_anomalies.clone(),
+            events.clone(),
             task_manager.subscribe(),
         )
         .await?;
 
+        Self::start_route_pool_replenisher(
+            shared_topology_accessor.clone(),
+            self.debug_config.topology.route_pool_size,
+            task_manager.subscribe(),
+        );
+
+        Self::start_unreachable_hops_listener(
+            shared_topology_accessor.clone(),
+            unreachable_hop_receiver,
+            task_manager.subscribe(),
+        );
+
         Self::start_received_messages_buffer_controller(
             self.key_manager.encryption_keypair(),
             received_buffer_request_receiver,
@@ -519,8 +811,29 @@ where
         // that are to be sent to the mixnet. They are used by cover traffic stream and real
         // traffic stream.
         // The MixTrafficController then sends the actual traffic
-        let sphinx_message_sender =
-            Self::start_mix_traffic_controller(gateway_client, task_manager.subscribe());
+        let sphinx_message_sender = Self::start_mix_traffic_controller(
+            gateway_client,
+            self.debug_config
+                .gateway_connection
+                .bandwidth_check_interval,
+            BandwidthForecaster::new(
+                self.debug_config
+                    .gateway_connection
+                    .bandwidth_top_up_threshold,
+                !self.debug_config.gateway_connection.manual_bandwidth_top_up,
+                self.debug_config
+                    .gateway_connection
+                    .bandwidth_warning_period,
+                bandwidth_forecast.clone(),
+            ),
+            self.debug_config.hibernation.keepalive_interval,
+            hibernation.listener(),
+            topology_anomalies.listener(),
+            events.clone(),
+            #[cfg(not(target_arch = "wasm32"))]
+            traffic_recorder,
+            task_manager.subscribe(),
+        );
 
         // Channels that the websocket listener can use to signal downstream to the real traffic
         // controller that connections are closed.
@@ -547,6 +860,10 @@ where
             reply_controller_receiver,
             shared_lane_queue_lengths.clone(),
             client_connection_rx,
+            ack_action_sender.clone(),
+            ack_action_receiver,
+            hibernation.listener(),
+            latency_stats.clone(),
             task_manager.subscribe(),
         );
 
@@ -560,11 +877,27 @@ where
                 self.key_manager.ack_key(),
                 self_address,
                 shared_topology_accessor.clone(),
-                sphinx_message_sender,
+                sphinx_message_sender.clone(),
+                hibernation.listener(),
                 task_manager.subscribe(),
             );
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(recorded_batches) = traffic_replay {
+            info!("Starting traffic replayer...");
+            TrafficReplayer::new(
+                recorded_batches,
+                self.key_manager.ack_key(),
+                self.debug_config.acknowledgements.average_ack_delay,
+                self.debug_config.traffic.average_packet_delay,
+                sphinx_message_sender,
+                self_address,
+                shared_topology_accessor.clone(),
+            )
+            .start_with_shutdown(task_manager.subscribe());
+        }
+
         debug!("Core client startup finished!");
         debug!("The address of this client is: {self_address}");
 
@@ -578,12 +911,18 @@ where
             client_output: ClientOutputStatus::AwaitingConsumer {
                 client_output: ClientOutput {
                     received_buffer_request_sender,
+                    ack_action_sender,
                 },
             },
             client_state: ClientState {
                 shared_lane_queue_lengths,
                 reply_controller_sender,
                 topology_accessor: shared_topology_accessor,
+                hibernation,
+                topology_anomalies,
+                bandwidth_forecast,
+                latency_stats,
+                events,
             },
             task_manager,
         })
@@ -14,9 +14,10 @@ use time::OffsetDateTime;
 async fn setup_fresh_backend<P: AsRef<Path>>(
     db_path: P,
     debug_config: &DebugConfig,
+    passphrase: Option<&str>,
 ) -> Result<fs_backend::Backend, ClientCoreError> {
     info!("creating fresh surb database");
-    let mut storage_backend = match fs_backend::Backend::init(db_path).await {
+    let mut storage_backend = match fs_backend::Backend::init(db_path, passphrase).await {
         Ok(backend) => backend,
         Err(err) => {
             error!("failed to setup persistent storage backend for our reply needs: {err}");
@@ -80,9 +81,13 @@ fn archive_corrupted_database<P: AsRef<Path>>(db_path: P) -> io::Result<()> {
     fs::rename(db_path, renamed)
 }
 
+/// Loads the persistent reply surb storage or creates a fresh one if it doesn't exist (or is corrupted).
+/// If the passphrase is provided, the stored reply keys, reply surbs and recipients are going
+/// to be encrypted with it.
 pub async fn setup_fs_reply_surb_backend<P: AsRef<Path>>(
     db_path: Option<P>,
     debug_config: &DebugConfig,
+    passphrase: Option<&str>,
 ) -> Result<fs_backend::Backend, ClientCoreError> {
     if let Some(db_path) = db_path {
         // if the database file doesnt exist, initialise fresh storage, otherwise attempt to load
@@ -90,17 +95,21 @@ pub async fn setup_fs_reply_surb_backend<P: AsRef<Path>>(
         let db_path = db_path.as_ref();
         if db_path.exists() {
             info!("loading existing surb database");
-            match fs_backend::Backend::try_load(db_path).await {
+            match fs_backend::Backend::try_load(db_path, passphrase).await {
                 Ok(backend) => Ok(backend),
+                // the data is fine, we just can't read it, so make sure to not throw it away
+                Err(err) if err.is_passphrase_error() => Err(ClientCoreError::SurbStorageError {
+                    source: Box::new(err),
+                }),
                 Err(err) => {
                     error!("failed to setup persistent storage backend for our reply needs: {err}. We're going to create a fresh database instead. This behaviour might change in the future");
 
                     archive_corrupted_database(db_path)?;
-                    setup_fresh_backend(db_path, debug_config).await
+                    setup_fresh_backend(db_path, debug_config, passphrase).await
                 }
             }
         } else {
-            setup_fresh_backend(db_path, debug_config).await
+            setup_fresh_backend(db_path, debug_config, passphrase).await
         }
     } else {
         Ok(setup_inactive_backend(debug_config))
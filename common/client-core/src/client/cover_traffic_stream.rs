@@ -1,6 +1,7 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::client::hibernation::HibernationListener;
 use crate::client::mix_traffic::BatchMixMessageSender;
 use crate::client::topology_control::TopologyAccessor;
 use crate::{config, spawn_future};
@@ -63,6 +64,9 @@ where
 
     /// Optional secondary predefined packet size used for the loop cover messages.
     secondary_packet_size: Option<PacketSize>,
+
+    /// Listener for the hibernation state of the client. No cover traffic is sent while hibernating.
+    hibernation: HibernationListener,
 }
 
 impl<R> Stream for LoopCoverTrafficStream<R>
@@ -107,6 +111,7 @@ where
 // obviously when we finally make shared rng that is on 'higher' level, this should become
 // generic `R`
 impl LoopCoverTrafficStream<OsRng> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         ack_key: Arc<AckKey>,
         average_ack_delay: Duration,
@@ -115,6 +120,7 @@ impl LoopCoverTrafficStream<OsRng> {
         topology_access: TopologyAccessor,
         traffic_config: config::Traffic,
         cover_config: config::CoverTraffic,
+        hibernation: HibernationListener,
     ) -> Self {
         let rng = OsRng;
 
@@ -135,6 +141,7 @@ impl LoopCoverTrafficStream<OsRng> {
             topology_access,
             primary_packet_size: traffic_config.primary_packet_size,
             secondary_packet_size: traffic_config.secondary_packet_size,
+            hibernation,
         }
     }
 
@@ -148,6 +155,14 @@ impl LoopCoverTrafficStream<OsRng> {
         self.next_delay = next_delay;
     }
 
+    fn resample_next_delay(&mut self) {
+        let sampled = sample_poisson_duration(
+            &mut self.rng,
+            self.cover_traffic.loop_cover_traffic_average_delay,
+        );
+        self.set_next_delay(sampled);
+    }
+
     fn loop_cover_message_size(&mut self) -> PacketSize {
         let Some(secondary_packet_size) = self.secondary_packet_size else {
             return self.primary_packet_size
@@ -231,22 +246,32 @@ impl LoopCoverTrafficStream<OsRng> {
         }
 
         // we should set initial delay only when we actually start the stream
-        let sampled = sample_poisson_duration(
-            &mut self.rng,
-            self.cover_traffic.loop_cover_traffic_average_delay,
-        );
-        self.set_next_delay(sampled);
+        self.resample_next_delay();
 
         spawn_future(async move {
             debug!("Started LoopCoverTrafficStream with graceful shutdown support");
 
+            // separate handle so that it wouldn't conflict with the borrow of the stream itself
+            let mut hibernation = self.hibernation.clone();
+            let mut hibernating = hibernation.is_hibernating();
+
             while !shutdown.is_shutdown() {
                 tokio::select! {
                     biased;
                     _ = shutdown.recv() => {
                         log::trace!("LoopCoverTrafficStream: Received shutdown");
                     }
-                    next = self.next() => {
+                    new_state = hibernation.changed() => {
+                        hibernating = new_state;
+                        if hibernating {
+                            debug!("LoopCoverTrafficStream: suspending cover traffic for the hibernation");
+                        } else {
+                            debug!("LoopCoverTrafficStream: resuming cover traffic after the hibernation");
+                            // the old deadline has most likely already passed a long time ago
+                            self.resample_next_delay();
+                        }
+                    }
+                    next = self.next(), if !hibernating => {
                         if next.is_some() {
                             self.on_new_message().await;
                         } else {
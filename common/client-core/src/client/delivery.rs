@@ -0,0 +1,353 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::client::inbound_messages::InputMessage;
+use futures::channel::mpsc;
+use log::*;
+use nym_sphinx::chunking::fragment::FragmentIdentifier;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+use thiserror::Error;
+use time::OffsetDateTime;
+
+/// Identifier assigned by the API user to a message whose delivery they wish to be notified about.
+pub type MessageId = u64;
+
+pub type DeliveryEventSender = mpsc::UnboundedSender<DeliveryEvent>;
+pub type DeliveryEventReceiver = mpsc::UnboundedReceiver<DeliveryEvent>;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum DeliveryFailure {
+    #[error("failed to prepare the message for sending - {0}")]
+    PreparationFailure(String),
+
+    #[error(
+        "fragment {fragment} has not been acknowledged after {retransmissions} retransmissions"
+    )]
+    RetransmissionsExhausted {
+        fragment: FragmentIdentifier,
+        retransmissions: u32,
+    },
+
+    #[error("fragment {fragment} has not been acknowledged within {period:?}")]
+    RetransmissionPeriodExceeded {
+        fragment: FragmentIdentifier,
+        period: Duration,
+    },
+
+    #[error("there are no reply SURBs available to send the message")]
+    NoReplySurbs,
+
+    #[error("the message has expired before it could have been delivered")]
+    Expired,
+}
+
+/// Notification about the final outcome of a tracked message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryEvent {
+    /// All fragments of the message have been acknowledged by the recipient's gateway.
+    Delivered(MessageId),
+
+    /// The message could not have been delivered and no further attempts will be made.
+    Failed(MessageId, DeliveryFailure),
+
+    /// The message has not been delivered before its expiry and no further attempts will be made.
+    /// Unlike failed messages, it is not retained on the dead letter queue.
+    Expired(MessageId),
+}
+
+impl DeliveryEvent {
+    pub fn message_id(&self) -> MessageId {
+        match self {
+            DeliveryEvent::Delivered(id)
+            | DeliveryEvent::Failed(id, _)
+            | DeliveryEvent::Expired(id) => *id,
+        }
+    }
+}
+
+/// Tracked message that could not have been delivered. It is retained so that the application
+/// could decide whether to send it again (possibly after changing its gateway) or to give up on it.
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub message_id: MessageId,
+    pub message: InputMessage,
+    pub reason: DeliveryFailure,
+    pub failed_at: OffsetDateTime,
+}
+
+#[derive(Default)]
+struct TrackedMessage {
+    fragments: HashSet<FragmentIdentifier>,
+
+    // original content of the message, if known, so that it could be put on the dead letter queue
+    content: Option<InputMessage>,
+}
+
+/// Keeps track of which fragments belong to which tracked message so that the listener could
+/// get notified once all of them got acknowledged (or once any of them has been given up on).
+pub(crate) struct DeliveryTracker {
+    pending: HashMap<MessageId, TrackedMessage>,
+    owners: HashMap<FragmentIdentifier, MessageId>,
+    listener: Option<DeliveryEventSender>,
+
+    dead_letters: VecDeque<DeadLetter>,
+    maximum_dead_letters: usize,
+}
+
+impl DeliveryTracker {
+    pub(crate) fn new(maximum_dead_letters: usize) -> Self {
+        DeliveryTracker {
+            pending: HashMap::new(),
+            owners: HashMap::new(),
+            listener: None,
+            dead_letters: VecDeque::new(),
+            maximum_dead_letters,
+        }
+    }
+
+    pub(crate) fn register_listener(&mut self, listener: DeliveryEventSender) {
+        if self.listener.is_some() {
+            warn!("replacing an existing delivery listener");
+        }
+        self.listener = Some(listener)
+    }
+
+    fn notify(&mut self, event: DeliveryEvent) {
+        if let Some(listener) = &self.listener {
+            if listener.unbounded_send(event).is_err() {
+                debug!("the delivery listener has gone away");
+                self.listener = None;
+            }
+        }
+    }
+
+    /// Retains the content of the message in case it had to be moved onto the dead letter queue.
+    pub(crate) fn track_content(&mut self, message_id: MessageId, content: InputMessage) {
+        self.pending.entry(message_id).or_default().content = Some(content)
+    }
+
+    pub(crate) fn track(&mut self, message_id: MessageId, fragments: Vec<FragmentIdentifier>) {
+        if fragments.is_empty() {
+            self.pending.remove(&message_id);
+            self.notify(DeliveryEvent::Delivered(message_id));
+            return;
+        }
+
+        for fragment in &fragments {
+            self.owners.insert(*fragment, message_id);
+        }
+        self.pending
+            .entry(message_id)
+            .or_default()
+            .fragments
+            .extend(fragments);
+    }
+
+    /// Marks the fragment as acknowledged, and if it was the last outstanding fragment of its
+    /// message, emits the `Delivered` event.
+    pub(crate) fn on_acked(&mut self, fragment: FragmentIdentifier) {
+        let Some(message_id) = self.owners.remove(&fragment) else {
+            return;
+        };
+
+        let Some(tracked) = self.pending.get_mut(&message_id) else {
+            return;
+        };
+        tracked.fragments.remove(&fragment);
+        if tracked.fragments.is_empty() {
+            self.pending.remove(&message_id);
+            self.notify(DeliveryEvent::Delivered(message_id));
+        }
+    }
+
+    /// Marks the fragment as undeliverable, failing its entire message. It returns the other
+    /// still outstanding fragments of that message as there's no point in retransmitting them anymore.
+    pub(crate) fn on_failed(
+        &mut self,
+        fragment: FragmentIdentifier,
+        reason: DeliveryFailure,
+    ) -> Vec<FragmentIdentifier> {
+        match self.owners.get(&fragment) {
+            Some(message_id) => {
+                let message_id = *message_id;
+                self.fail_message(message_id, reason)
+                    .into_iter()
+                    .filter(|frag| frag != &fragment)
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Fails the entire message, moving it onto the dead letter queue (if its content is known)
+    /// and returning all of its outstanding fragments.
+    /// Expired messages are dropped instead as there's no point in ever sending them again.
+    pub(crate) fn fail_message(
+        &mut self,
+        message_id: MessageId,
+        reason: DeliveryFailure,
+    ) -> Vec<FragmentIdentifier> {
+        let tracked = self.pending.remove(&message_id).unwrap_or_default();
+        for fragment in &tracked.fragments {
+            self.owners.remove(fragment);
+        }
+
+        if reason == DeliveryFailure::Expired {
+            self.notify(DeliveryEvent::Expired(message_id));
+            return tracked.fragments.into_iter().collect();
+        }
+
+        if let Some(message) = tracked.content {
+            self.push_dead_letter(DeadLetter {
+                message_id,
+                message,
+                reason: reason.clone(),
+                failed_at: OffsetDateTime::now_utc(),
+            })
+        }
+
+        self.notify(DeliveryEvent::Failed(message_id, reason));
+        tracked.fragments.into_iter().collect()
+    }
+
+    fn push_dead_letter(&mut self, dead_letter: DeadLetter) {
+        if self.maximum_dead_letters == 0 {
+            return;
+        }
+        if self.dead_letters.len() >= self.maximum_dead_letters {
+            if let Some(oldest) = self.dead_letters.pop_front() {
+                debug!(
+                    "the dead letter queue is full - dropping message {}",
+                    oldest.message_id
+                );
+            }
+        }
+        self.dead_letters.push_back(dead_letter)
+    }
+
+    pub(crate) fn dead_letters(&self) -> Vec<DeadLetter> {
+        self.dead_letters.iter().cloned().collect()
+    }
+
+    /// Removes the message from the dead letter queue, for example so that it could be sent again.
+    pub(crate) fn take_dead_letter(&mut self, message_id: MessageId) -> Option<DeadLetter> {
+        let position = self
+            .dead_letters
+            .iter()
+            .position(|dead_letter| dead_letter.message_id == message_id)?;
+        self.dead_letters.remove(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use nym_task::connections::TransmissionLane;
+
+    fn fragment(set_id: i32, position: u8) -> FragmentIdentifier {
+        let id = set_id.to_be_bytes();
+        FragmentIdentifier::try_from_bytes([id[0], id[1], id[2], id[3], position]).unwrap()
+    }
+
+    fn tracker_with_listener() -> (DeliveryTracker, DeliveryEventReceiver) {
+        let (tx, rx) = mpsc::unbounded();
+        let mut tracker = DeliveryTracker::new(2);
+        tracker.register_listener(tx);
+        (tracker, rx)
+    }
+
+    #[tokio::test]
+    async fn message_is_delivered_once_all_fragments_are_acked() {
+        let (mut tracker, mut events) = tracker_with_listener();
+        tracker.track(42, vec![fragment(1, 0), fragment(1, 1)]);
+
+        tracker.on_acked(fragment(1, 1));
+        assert!(events.try_next().is_err());
+
+        // duplicate acks are irrelevant
+        tracker.on_acked(fragment(1, 1));
+        assert!(events.try_next().is_err());
+
+        tracker.on_acked(fragment(1, 0));
+        assert_eq!(events.next().await, Some(DeliveryEvent::Delivered(42)));
+        assert!(tracker.pending.is_empty());
+        assert!(tracker.owners.is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_fragment_fails_entire_message() {
+        let (mut tracker, mut events) = tracker_with_listener();
+        tracker.track(1, vec![fragment(1, 0), fragment(1, 1), fragment(1, 2)]);
+        tracker.track(2, vec![fragment(2, 0)]);
+
+        tracker.on_acked(fragment(1, 0));
+        let reason = DeliveryFailure::RetransmissionsExhausted {
+            fragment: fragment(1, 1),
+            retransmissions: 5,
+        };
+        let remaining = tracker.on_failed(fragment(1, 1), reason.clone());
+        assert_eq!(remaining, vec![fragment(1, 2)]);
+        assert_eq!(events.next().await, Some(DeliveryEvent::Failed(1, reason)));
+
+        // acks for a failed message do not emit anything
+        tracker.on_acked(fragment(1, 2));
+        tracker.on_acked(fragment(2, 0));
+        assert_eq!(events.next().await, Some(DeliveryEvent::Delivered(2)));
+    }
+
+    #[test]
+    fn untracked_fragments_are_ignored() {
+        let (mut tracker, mut events) = tracker_with_listener();
+        tracker.on_acked(fragment(1, 0));
+        assert!(tracker
+            .on_failed(fragment(1, 0), DeliveryFailure::NoReplySurbs)
+            .is_empty());
+        assert!(events.try_next().is_err());
+    }
+
+    #[test]
+    fn failed_messages_with_known_content_are_put_on_dead_letter_queue() {
+        let (mut tracker, _events) = tracker_with_listener();
+        let content =
+            || InputMessage::new_reply([42u8; 16].into(), vec![1, 2, 3], TransmissionLane::General);
+
+        for message_id in 1..=3 {
+            tracker.track_content(message_id, content());
+            tracker.track(message_id, vec![fragment(message_id as i32, 0)]);
+        }
+        tracker.track(4, vec![fragment(4, 0)]);
+
+        for message_id in 1..=4 {
+            tracker.on_failed(fragment(message_id, 0), DeliveryFailure::NoReplySurbs);
+        }
+
+        // the queue is bounded so the oldest entry got dropped,
+        // while the content of the last message has never been known
+        let dead_letters = tracker.dead_letters();
+        assert_eq!(dead_letters.len(), 2);
+        assert_eq!(dead_letters[0].message_id, 2);
+        assert_eq!(dead_letters[1].message_id, 3);
+        assert_eq!(dead_letters[1].reason, DeliveryFailure::NoReplySurbs);
+
+        assert_eq!(tracker.take_dead_letter(3).unwrap().message_id, 3);
+        assert!(tracker.take_dead_letter(3).is_none());
+        assert_eq!(tracker.dead_letters().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn expired_messages_are_not_put_on_dead_letter_queue() {
+        let (mut tracker, mut events) = tracker_with_listener();
+        let content =
+            InputMessage::new_reply([42u8; 16].into(), vec![1, 2, 3], TransmissionLane::General);
+        tracker.track_content(1, content);
+        tracker.track(1, vec![fragment(1, 0), fragment(1, 1)]);
+
+        let remaining = tracker.on_failed(fragment(1, 0), DeliveryFailure::Expired);
+        assert_eq!(remaining, vec![fragment(1, 1)]);
+        assert_eq!(events.next().await, Some(DeliveryEvent::Expired(1)));
+        assert!(tracker.dead_letters().is_empty());
+        assert!(tracker.owners.is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+// events are only ever useful while they're fresh, so the slow subscribers are going to
+// miss the oldest ones rather than hold up the client
+const EVENT_BUS_CAPACITY: usize = 64;
+
+/// Notable changes in the lifecycle of the client that the embedders might want to react to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientEvent {
+    /// The connection to the gateway has been established (or re-established after a failure).
+    GatewayConnected { gateway: String },
+
+    /// The connection to the gateway has been closed or the client failed to talk to its gateway.
+    GatewayDisconnected { reason: String },
+
+    /// A fresh view of the network has been obtained.
+    TopologyRefreshed { mixnodes: usize, gateways: usize },
+
+    /// A bandwidth credential has been spent to top up the bandwidth at the gateway.
+    CredentialSpent { remaining_bandwidth: i64 },
+}
+
+impl ClientEvent {
+    /// Short, stable name of the event kind.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClientEvent::GatewayConnected { .. } => "gatewayConnected",
+            ClientEvent::GatewayDisconnected { .. } => "gatewayDisconnected",
+            ClientEvent::TopologyRefreshed { .. } => "topologyRefreshed",
+            ClientEvent::CredentialSpent { .. } => "credentialSpent",
+        }
+    }
+
+    /// Human-readable details of the event.
+    pub fn details(&self) -> String {
+        match self {
+            ClientEvent::GatewayConnected { gateway } => format!("connected to gateway {gateway}"),
+            ClientEvent::GatewayDisconnected { reason } => {
+                format!("lost the connection to the gateway: {reason}")
+            }
+            ClientEvent::TopologyRefreshed { mixnodes, gateways } => {
+                format!("the network consists of {mixnodes} mixnodes and {gateways} gateways")
+            }
+            ClientEvent::CredentialSpent {
+                remaining_bandwidth,
+            } => format!("{remaining_bandwidth} bytes of bandwidth are now available"),
+        }
+    }
+}
+
+impl Display for ClientEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.name(), self.details())
+    }
+}
+
+/// Internal broadcast channel through which the client tasks announce the [`ClientEvent`]s,
+/// so that anyone interested could react to them without having to poll the client.
+#[derive(Debug, Clone)]
+pub struct ClientEventBus {
+    inner: Arc<broadcast::Sender<ClientEvent>>,
+}
+
+impl Default for ClientEventBus {
+    fn default() -> Self {
+        ClientEventBus::new()
+    }
+}
+
+impl ClientEventBus {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        ClientEventBus {
+            inner: Arc::new(tx),
+        }
+    }
+
+    /// Announces the event to all of the current subscribers.
+    /// Returns the number of subscribers that are going to receive it.
+    pub fn emit(&self, event: ClientEvent) -> usize {
+        log::debug!("client event: {event}");
+        // not having any subscribers is perfectly fine
+        self.inner.send(event).unwrap_or_default()
+    }
+
+    pub fn subscribe(&self) -> ClientEventListener {
+        ClientEventListener {
+            inner: self.inner.subscribe(),
+        }
+    }
+}
+
+/// Receiving end of the [`ClientEventBus`]. It only gets the events emitted after it was created.
+#[derive(Debug)]
+pub struct ClientEventListener {
+    inner: broadcast::Receiver<ClientEvent>,
+}
+
+impl ClientEventListener {
+    /// Waits for the next event. If the listener has fallen behind, the events it missed are
+    /// skipped. If the bus has been dropped, no more events can arrive and thus the future
+    /// never resolves.
+    pub async fn next(&mut self) -> ClientEvent {
+        loop {
+            match self.inner.recv().await {
+                Ok(event) => return event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("the client event listener fell behind and missed {skipped} events")
+                }
+                Err(broadcast::error::RecvError::Closed) => std::future::pending::<()>().await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn events_are_delivered_to_all_subscribers() {
+        let bus = ClientEventBus::new();
+        assert_eq!(
+            bus.emit(ClientEvent::GatewayDisconnected {
+                reason: "foomp".to_string()
+            }),
+            0
+        );
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        let event = ClientEvent::TopologyRefreshed {
+            mixnodes: 3,
+            gateways: 1,
+        };
+        assert_eq!(bus.emit(event.clone()), 2);
+
+        assert_eq!(first.next().await, event);
+        assert_eq!(second.next().await, event);
+    }
+
+    #[tokio::test]
+    async fn lagging_listener_skips_the_oldest_events() {
+        let bus = ClientEventBus::new();
+        let mut listener = bus.subscribe();
+        for remaining_bandwidth in 0..EVENT_BUS_CAPACITY as i64 + 1 {
+            bus.emit(ClientEvent::CredentialSpent {
+                remaining_bandwidth,
+            });
+        }
+
+        assert_eq!(
+            listener.next().await,
+            ClientEvent::CredentialSpent {
+                remaining_bandwidth: 1
+            }
+        );
+    }
+}
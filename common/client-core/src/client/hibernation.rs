@@ -0,0 +1,118 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Handle used for putting the client into (and bringing it out of) the low-power hibernation mode.
+///
+/// While hibernating, the client suspends its cover traffic, refreshes the network topology
+/// less often and only keeps the gateway connection alive so that the session could be
+/// resumed without re-authenticating. Real messages are still sent if any are pushed to the client.
+#[derive(Debug, Clone)]
+pub struct HibernationControl {
+    inner: Arc<watch::Sender<bool>>,
+}
+
+impl Default for HibernationControl {
+    fn default() -> Self {
+        HibernationControl::new()
+    }
+}
+
+impl HibernationControl {
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(false);
+        HibernationControl {
+            inner: Arc::new(tx),
+        }
+    }
+
+    /// Puts the client into the hibernation mode.
+    /// Returns whether the state has actually changed.
+    pub fn hibernate(&self) -> bool {
+        self.set_hibernating(true)
+    }
+
+    /// Restores the full traffic shaping of the client.
+    /// Returns whether the state has actually changed.
+    pub fn wake(&self) -> bool {
+        self.set_hibernating(false)
+    }
+
+    fn set_hibernating(&self, hibernating: bool) -> bool {
+        self.inner.send_if_modified(|current| {
+            if *current == hibernating {
+                false
+            } else {
+                *current = hibernating;
+                true
+            }
+        })
+    }
+
+    pub fn is_hibernating(&self) -> bool {
+        *self.inner.borrow()
+    }
+
+    pub fn listener(&self) -> HibernationListener {
+        HibernationListener {
+            inner: self.inner.subscribe(),
+        }
+    }
+}
+
+/// Receiving end of the [`HibernationControl`] used by the client tasks to adjust their behaviour.
+#[derive(Debug, Clone)]
+pub struct HibernationListener {
+    inner: watch::Receiver<bool>,
+}
+
+impl HibernationListener {
+    pub fn is_hibernating(&self) -> bool {
+        *self.inner.borrow()
+    }
+
+    /// Waits until the hibernation state changes and returns the new value.
+    /// If the controlling end has been dropped, the state can no longer change and thus
+    /// the future never resolves.
+    pub async fn changed(&mut self) -> bool {
+        if self.inner.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+        *self.inner.borrow_and_update()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_is_only_modified_on_transitions() {
+        let control = HibernationControl::new();
+        assert!(!control.is_hibernating());
+
+        assert!(!control.wake());
+        assert!(control.hibernate());
+        assert!(!control.hibernate());
+        assert!(control.is_hibernating());
+
+        assert!(control.wake());
+        assert!(!control.is_hibernating());
+    }
+
+    #[tokio::test]
+    async fn listener_is_notified_about_changes() {
+        let control = HibernationControl::new();
+        let mut listener = control.listener();
+        assert!(!listener.is_hibernating());
+
+        control.hibernate();
+        assert!(listener.changed().await);
+        assert!(listener.is_hibernating());
+
+        control.wake();
+        assert!(!listener.changed().await);
+    }
+}
@@ -1,11 +1,14 @@
+use crate::client::delivery::MessageId;
 use nym_sphinx::addressing::clients::Recipient;
 use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
 use nym_task::connections::TransmissionLane;
+use std::time::Duration;
+use time::OffsetDateTime;
 
 pub type InputMessageSender = tokio::sync::mpsc::Sender<InputMessage>;
 pub type InputMessageReceiver = tokio::sync::mpsc::Receiver<InputMessage>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum InputMessage {
     /// The simplest message variant where no additional information is attached.
     /// You're simply sending your `data` to specified `recipient` without any tagging.
@@ -41,6 +44,35 @@ pub enum InputMessage {
         data: Vec<u8>,
         lane: TransmissionLane,
     },
+
+    /// Wraps any of the other variants indicating that a `DeliveryEvent` with the specified
+    /// `message_id` should be emitted once all fragments of the message are acknowledged
+    /// or once the client gives up on trying to deliver it.
+    Tracked {
+        message: Box<InputMessage>,
+        message_id: MessageId,
+    },
+
+    /// Wraps any of the other variants indicating that the message should no longer be delivered
+    /// after `expires_at`. Its fragments are not going to be retransmitted past that point.
+    /// If the client is configured to disclose the expiry, the recipient's gateway is also going
+    /// to drop them if they are still undelivered.
+    ///
+    /// Note that the gateway of an anonymous recipient never learns the expiry of replies.
+    Expiring {
+        message: Box<InputMessage>,
+        expires_at: OffsetDateTime,
+    },
+
+    /// Wraps an `Anonymous` message indicating that it should be sent with the specified
+    /// `sender_tag` instead of the one shared by all anonymous messages sent to the same recipient.
+    /// The recipient is thus unable to link it with the anonymous messages using other tags.
+    ///
+    /// Has no effect on the other variants.
+    Isolated {
+        message: Box<InputMessage>,
+        sender_tag: AnonymousSenderTag,
+    },
 }
 
 impl InputMessage {
@@ -78,11 +110,44 @@ impl InputMessage {
         }
     }
 
+    /// Requests delivery notifications for this message.
+    pub fn with_delivery_tracking(self, message_id: MessageId) -> Self {
+        InputMessage::Tracked {
+            message: Box::new(self),
+            message_id,
+        }
+    }
+
+    /// Requests the message to be dropped if it hasn't been delivered within the provided `ttl`.
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        self.with_expiry(OffsetDateTime::now_utc() + ttl)
+    }
+
+    /// Requests the message to be dropped if it hasn't been delivered before `expires_at`.
+    pub fn with_expiry(self, expires_at: OffsetDateTime) -> Self {
+        InputMessage::Expiring {
+            message: Box::new(self),
+            expires_at,
+        }
+    }
+
+    /// Requests the anonymous message to be sent with its own `sender_tag`, so that the reply surbs
+    /// attached to it would be kept separate from those of the other messages sent to the recipient.
+    pub fn with_sender_tag(self, sender_tag: AnonymousSenderTag) -> Self {
+        InputMessage::Isolated {
+            message: Box::new(self),
+            sender_tag,
+        }
+    }
+
     pub fn lane(&self) -> &TransmissionLane {
         match self {
             InputMessage::Regular { lane, .. }
             | InputMessage::Anonymous { lane, .. }
             | InputMessage::Reply { lane, .. } => lane,
+            InputMessage::Tracked { message, .. }
+            | InputMessage::Expiring { message, .. }
+            | InputMessage::Isolated { message, .. } => message.lane(),
         }
     }
 }
@@ -0,0 +1,226 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use log::warn;
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+// number of acknowledged packets required before the latency is assessed
+const MINIMUM_SAMPLES: u64 = 20;
+
+// weight given to the new sample when updating the smoothed values
+const SMOOTHING: f64 = 0.1;
+
+// acknowledgements consistently arriving later than this on top of the scheduled delays
+// indicate a problem with the routes, such as overloaded or unreachable nodes
+const EXCESSIVE_LATENCY: Duration = Duration::from_secs(5);
+
+/// Problem with the packet latency detected by comparing the delays scheduled for the packets
+/// against the round trips actually observed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatencyAnomaly {
+    /// The acknowledgements arrive considerably later than the scheduled delays would imply.
+    ExcessiveLatency,
+
+    /// The acknowledgements arrive sooner than the scheduled delays would allow, meaning
+    /// the packets are not being delayed by the mix nodes as instructed.
+    DelaysNotHonoured,
+}
+
+impl Display for LatencyAnomaly {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LatencyAnomaly::ExcessiveLatency => write!(
+                f,
+                "the acknowledgements arrive much later than the scheduled delays would imply"
+            ),
+            LatencyAnomaly::DelaysNotHonoured => write!(
+                f,
+                "the acknowledgements arrive before the scheduled delays have elapsed"
+            ),
+        }
+    }
+}
+
+/// Comparison of the delays scheduled for the sent packets with their observed round trips.
+/// The scheduled delay of a packet is the sum of the delays sampled for each hop of its route
+/// and of the route of its acknowledgement. Retransmitted packets are not taken into account
+/// as it's impossible to tell which of the copies got acknowledged.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LatencyStats {
+    /// Number of packets sent into the network.
+    pub sent_packets: u64,
+
+    /// Sum of the delays scheduled for all of the sent packets.
+    pub total_scheduled_delay: Duration,
+
+    /// Number of sent packets that got acknowledged.
+    pub acknowledged_packets: u64,
+
+    /// Smoothed delay scheduled for the acknowledged packets.
+    pub scheduled_delay: Duration,
+
+    /// Smoothed round-trip time of the acknowledged packets.
+    pub round_trip: Duration,
+
+    /// Problem detected with the latency, if any.
+    pub anomaly: Option<LatencyAnomaly>,
+}
+
+impl LatencyStats {
+    /// Average delay scheduled for the sent packets.
+    pub fn average_scheduled_delay(&self) -> Option<Duration> {
+        if self.sent_packets == 0 {
+            None
+        } else {
+            let average = self.total_scheduled_delay.as_nanos() / self.sent_packets as u128;
+            Some(Duration::from_nanos(average as u64))
+        }
+    }
+
+    /// Smoothed difference, in seconds, between the observed round trips and the scheduled delays.
+    /// Negative if the packets arrive before their delays could have elapsed.
+    pub fn excess_latency_secs(&self) -> f64 {
+        self.round_trip.as_secs_f64() - self.scheduled_delay.as_secs_f64()
+    }
+
+    fn record_sent(&mut self, scheduled: Duration) {
+        self.sent_packets += 1;
+        self.total_scheduled_delay += scheduled;
+    }
+
+    fn record_acknowledged(&mut self, scheduled: Duration, round_trip: Duration) {
+        if self.acknowledged_packets == 0 {
+            self.scheduled_delay = scheduled;
+            self.round_trip = round_trip;
+        } else {
+            self.scheduled_delay =
+                self.scheduled_delay.mul_f64(1.0 - SMOOTHING) + scheduled.mul_f64(SMOOTHING);
+            self.round_trip =
+                self.round_trip.mul_f64(1.0 - SMOOTHING) + round_trip.mul_f64(SMOOTHING);
+        }
+        self.acknowledged_packets += 1;
+        self.anomaly = self.detect_anomaly();
+    }
+
+    fn detect_anomaly(&self) -> Option<LatencyAnomaly> {
+        if self.acknowledged_packets < MINIMUM_SAMPLES {
+            return None;
+        }
+
+        let excess = self.excess_latency_secs();
+        if excess > EXCESSIVE_LATENCY.as_secs_f64() {
+            Some(LatencyAnomaly::ExcessiveLatency)
+        } else if excess < 0.0 {
+            Some(LatencyAnomaly::DelaysNotHonoured)
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for LatencyStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} packets sent, {} acknowledged; scheduled delay: {:?}, round trip: {:?}",
+            self.sent_packets, self.acknowledged_packets, self.scheduled_delay, self.round_trip
+        )?;
+        if let Some(anomaly) = self.anomaly {
+            write!(f, " ({anomaly})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Shared handle to the [`LatencyStats`] of the client, updated as the packets get sent
+/// and acknowledged.
+#[derive(Debug, Clone)]
+pub struct LatencyStatsControl {
+    inner: Arc<watch::Sender<LatencyStats>>,
+}
+
+impl Default for LatencyStatsControl {
+    fn default() -> Self {
+        LatencyStatsControl::new()
+    }
+}
+
+impl LatencyStatsControl {
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(LatencyStats::default());
+        LatencyStatsControl {
+            inner: Arc::new(tx),
+        }
+    }
+
+    pub(crate) fn record_sent(&self, scheduled: Duration) {
+        self.inner.send_modify(|stats| stats.record_sent(scheduled));
+    }
+
+    pub(crate) fn record_acknowledged(&self, scheduled: Duration, round_trip: Duration) {
+        let mut new_anomaly = None;
+        self.inner.send_modify(|stats| {
+            let previous = stats.anomaly;
+            stats.record_acknowledged(scheduled, round_trip);
+            if stats.anomaly != previous {
+                new_anomaly = stats.anomaly;
+            }
+        });
+        if let Some(anomaly) = new_anomaly {
+            warn!("packet latency anomaly detected: {anomaly}")
+        }
+    }
+
+    pub fn stats(&self) -> LatencyStats {
+        *self.inner.borrow()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_track_of_the_scheduled_delays() {
+        let mut stats = LatencyStats::default();
+        assert!(stats.average_scheduled_delay().is_none());
+
+        stats.record_sent(Duration::from_millis(100));
+        stats.record_sent(Duration::from_millis(300));
+        assert_eq!(stats.sent_packets, 2);
+        assert_eq!(stats.total_scheduled_delay, Duration::from_millis(400));
+        assert_eq!(
+            stats.average_scheduled_delay(),
+            Some(Duration::from_millis(200))
+        );
+    }
+
+    #[test]
+    fn detects_latency_anomalies() {
+        let scheduled = Duration::from_millis(500);
+
+        let mut stats = LatencyStats::default();
+        for _ in 0..MINIMUM_SAMPLES {
+            stats.record_acknowledged(scheduled, Duration::from_millis(800));
+        }
+        assert!(stats.anomaly.is_none());
+        assert!((stats.excess_latency_secs() - 0.3).abs() < 0.001);
+
+        let mut slow = LatencyStats::default();
+        for _ in 0..MINIMUM_SAMPLES - 1 {
+            slow.record_acknowledged(scheduled, Duration::from_secs(10));
+        }
+        assert!(slow.anomaly.is_none());
+        slow.record_acknowledged(scheduled, Duration::from_secs(10));
+        assert_eq!(slow.anomaly, Some(LatencyAnomaly::ExcessiveLatency));
+
+        let mut early = LatencyStats::default();
+        for _ in 0..MINIMUM_SAMPLES {
+            early.record_acknowledged(scheduled, Duration::from_millis(100));
+        }
+        assert_eq!(early.anomaly, Some(LatencyAnomaly::DelaysNotHonoured));
+    }
+}
@@ -1,10 +1,19 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::client::bandwidth_forecast::BandwidthForecaster;
+use crate::client::events::{ClientEvent, ClientEventBus};
+use crate::client::helpers::{new_interval_stream, IntervalStream};
+use crate::client::hibernation::HibernationListener;
+use crate::client::topology_control::anomaly::TopologyAnomalyListener;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::traffic_recording::TrafficRecorder;
 use crate::spawn_future;
+use futures::StreamExt;
 use log::*;
 use nym_gateway_client::GatewayClient;
 use nym_sphinx::forwarding::packet::MixPacket;
+use std::time::Duration;
 
 use nym_credential_storage::storage::Storage;
 #[cfg(not(target_arch = "wasm32"))]
@@ -26,9 +35,35 @@ pub struct MixTrafficController<C, St: Storage> {
     gateway_client: GatewayClient<C, St>,
     mix_rx: BatchMixMessageReceiver,
 
+    /// How often the remaining bandwidth at the gateway is checked and topped up if needed.
+    bandwidth_check_interval: Duration,
+
+    /// Estimates how long the remaining bandwidth is going to last based on the periodic checks.
+    bandwidth_forecaster: BandwidthForecaster,
+
+    /// How often a keepalive message is sent to the gateway while the client is hibernating.
+    keepalive_interval: Duration,
+
+    /// Listener for the hibernation state of the client.
+    hibernation: HibernationListener,
+
+    /// Listener for the topology anomalies that might require pausing all the traffic.
+    topology_anomalies: TopologyAnomalyListener,
+
+    /// Bus for announcing the changes of the gateway connection and the spent credentials.
+    events: ClientEventBus,
+
     // TODO: this is temporary work-around.
     // in long run `gateway_client` will be moved away from `MixTrafficController` anyway.
     consecutive_gateway_failure_count: usize,
+
+    /// Whether the loss of the gateway connection has been announced and not yet followed by
+    /// the announcement of it being re-established.
+    gateway_disconnected: bool,
+
+    /// Optional (development) recorder of all the sent packets.
+    #[cfg(not(target_arch = "wasm32"))]
+    traffic_recorder: Option<TrafficRecorder>,
 }
 
 impl<C, St> MixTrafficController<C, St>
@@ -38,6 +73,12 @@ where
 {
     pub fn new(
         gateway_client: GatewayClient<C, St>,
+        bandwidth_check_interval: Duration,
+        bandwidth_forecaster: BandwidthForecaster,
+        keepalive_interval: Duration,
+        hibernation: HibernationListener,
+        topology_anomalies: TopologyAnomalyListener,
+        events: ClientEventBus,
     ) -> (MixTrafficController<C, St>, BatchMixMessageSender) {
         let (sphinx_message_sender, sphinx_message_receiver) =
             tokio::sync::mpsc::channel(MIX_MESSAGE_RECEIVER_BUFFER_SIZE);
@@ -45,15 +86,35 @@ where
             MixTrafficController {
                 gateway_client,
                 mix_rx: sphinx_message_receiver,
+                bandwidth_check_interval,
+                bandwidth_forecaster,
+                keepalive_interval,
+                hibernation,
+                topology_anomalies,
+                events,
                 consecutive_gateway_failure_count: 0,
+                gateway_disconnected: false,
+                #[cfg(not(target_arch = "wasm32"))]
+                traffic_recorder: None,
             },
             sphinx_message_sender,
         )
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_traffic_recorder(mut self, traffic_recorder: TrafficRecorder) -> Self {
+        self.traffic_recorder = Some(traffic_recorder);
+        self
+    }
+
     async fn on_messages(&mut self, mut mix_packets: Vec<MixPacket>) {
         debug_assert!(!mix_packets.is_empty());
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(recorder) = self.traffic_recorder.as_mut() {
+            recorder.record(&mix_packets)
+        }
+
         let result = if mix_packets.len() == 1 {
             let mix_packet = mix_packets.pop().unwrap();
             self.gateway_client.send_mix_packet(mix_packet).await
@@ -66,6 +127,7 @@ where
         match result {
             Err(err) => {
                 error!("Failed to send sphinx packet(s) to the gateway! - {err}");
+                self.on_gateway_disconnected(err.to_string());
                 self.consecutive_gateway_failure_count += 1;
                 if self.consecutive_gateway_failure_count == MAX_FAILURE_COUNT {
                     // todo: in the future this should initiate a 'graceful' shutdown or try
@@ -75,18 +137,132 @@ where
             }
             Ok(_) => {
                 trace!("We *might* have managed to forward sphinx packet(s) to the gateway!");
+                self.on_gateway_reachable();
                 self.consecutive_gateway_failure_count = 0;
             }
         }
     }
 
+    // the connection might get closed while the client is idle, in which case it's only going to
+    // get re-established once there's something to send
+    fn on_gateway_disconnected(&mut self, reason: String) {
+        if self.gateway_disconnected {
+            return;
+        }
+        warn!("Lost the connection to the gateway - {reason}");
+        self.gateway_disconnected = true;
+        self.events
+            .emit(ClientEvent::GatewayDisconnected { reason });
+    }
+
+    fn on_gateway_reachable(&mut self) {
+        if !self.gateway_disconnected {
+            return;
+        }
+        info!("The connection to the gateway got re-established");
+        self.gateway_disconnected = false;
+        self.events.emit(ClientEvent::GatewayConnected {
+            gateway: self.gateway_client.gateway_identity().to_base58_string(),
+        });
+    }
+
+    async fn on_bandwidth_check(&mut self) {
+        // note: the forecast is recorded regardless of the outcome of the top up, in particular,
+        // failing to spend a credential is exactly when the warning is the most relevant
+        match self.gateway_client.top_up_bandwidth_if_needed().await {
+            Ok(true) => {
+                let remaining_bandwidth = self.gateway_client.remaining_bandwidth();
+                info!("Topped up the bandwidth at the gateway. Currently available: {remaining_bandwidth} bytes");
+                self.events.emit(ClientEvent::CredentialSpent {
+                    remaining_bandwidth,
+                });
+            }
+            Ok(false) => trace!(
+                "Remaining bandwidth at the gateway: {} bytes",
+                self.gateway_client.remaining_bandwidth()
+            ),
+            Err(err) => warn!("Failed to top up the bandwidth at the gateway - {err}"),
+        }
+
+        let forecast = self
+            .bandwidth_forecaster
+            .record(self.gateway_client.remaining_bandwidth());
+        if forecast.running_low {
+            warn!("The bandwidth is running low: {forecast}");
+        } else {
+            trace!("Bandwidth forecast: {forecast}");
+        }
+    }
+
+    // the bandwidth of the clients embedded within the gateway is not metered, so there's
+    // nothing to top up
+    fn bandwidth_check_stream(&self) -> Option<IntervalStream> {
+        if self.gateway_client.is_embedded() || self.bandwidth_check_interval.is_zero() {
+            None
+        } else {
+            Some(new_interval_stream(self.bandwidth_check_interval))
+        }
+    }
+
+    async fn on_keepalive(&mut self) {
+        // the ping is going to transparently re-establish the connection if it got dropped,
+        // so that the session is resumable once the client wakes up
+        match self.gateway_client.send_ping_message().await {
+            Ok(_) => self.on_gateway_reachable(),
+            Err(err) => warn!("Failed to send the keepalive message to the gateway - {err}"),
+        }
+    }
+
+    // the connection to the embedded gateway is never going to time out
+    fn keepalive_stream(&self, hibernating: bool) -> Option<IntervalStream> {
+        if !hibernating || self.gateway_client.is_embedded() || self.keepalive_interval.is_zero() {
+            None
+        } else {
+            Some(new_interval_stream(self.keepalive_interval))
+        }
+    }
+
     pub fn start_with_shutdown(mut self, mut shutdown: nym_task::TaskClient) {
         spawn_future(async move {
             debug!("Started MixTrafficController with graceful shutdown support");
 
+            let mut bandwidth_check = self.bandwidth_check_stream();
+            let mut keepalive = self.keepalive_stream(self.hibernation.is_hibernating());
+            // while the traffic is paused, the packets keep on being taken off the channel
+            // at the usual rate, but are dropped rather than sent. Leaving them queued up would
+            // release them all in a single burst once the traffic is resumed, which is a timing signal
+            // on its own. The lost real messages are going to get retransmitted as their acks never arrive.
+            let mut traffic_paused = self.topology_anomalies.is_traffic_paused();
+            let mut connection_closed = self.gateway_client.take_connection_closed_receiver();
+
             loop {
                 tokio::select! {
+                    Some(_) = async { bandwidth_check.as_mut()?.next().await } => {
+                        self.on_bandwidth_check().await;
+                    },
+                    Some(_) = async { keepalive.as_mut()?.next().await } => {
+                        self.on_keepalive().await;
+                    },
+                    Some(reason) = async { connection_closed.as_mut()?.next().await } => {
+                        self.on_gateway_disconnected(reason);
+                    },
+                    hibernating = self.hibernation.changed() => {
+                        keepalive = self.keepalive_stream(hibernating);
+                    },
+                    status = self.topology_anomalies.changed() => {
+                        if status.traffic_paused != traffic_paused {
+                            if status.traffic_paused {
+                                warn!("Pausing all traffic due to the detected topology anomalies");
+                            } else {
+                                info!("Resuming the traffic after the topology anomalies got acknowledged");
+                            }
+                        }
+                        traffic_paused = status.traffic_paused;
+                    },
                     mix_packets = self.mix_rx.recv() => match mix_packets {
+                        Some(mix_packets) if traffic_paused => {
+                            trace!("dropping {} mix packets as the traffic is paused", mix_packets.len());
+                        },
                         Some(mix_packets) => {
                             self.on_messages(mix_packets).await;
                         },
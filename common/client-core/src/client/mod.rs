@@ -1,14 +1,23 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod bandwidth_forecast;
 pub mod base_client;
 pub mod cover_traffic_stream;
+pub mod delivery;
+pub mod events;
 pub(crate) mod helpers;
+pub mod hibernation;
 pub mod inbound_messages;
 pub mod key_manager;
+pub mod latency_stats;
 pub mod mix_traffic;
 pub mod real_messages_control;
 pub mod received_buffer;
 pub mod replies;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod storage_passphrase;
 pub mod topology_control;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod traffic_recording;
 pub(crate) mod transmission_buffer;
@@ -0,0 +1,188 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// number of acknowledgements that have to be received for routes of given length before
+// their estimate is used instead of the static ack wait formula
+const MINIMUM_RTT_SAMPLES: u32 = 5;
+
+// the time waited on top of the expected delay is doubled with every retransmission of a packet,
+// up to this many times (and the `maximum_addition`)
+const MAXIMUM_BACKOFF_EXPONENT: u32 = 6;
+
+/// Tuning parameters of the adaptive acknowledgement timeouts.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AdaptiveAckTimeouts {
+    /// Weight given to the new round-trip sample when updating the smoothed estimate.
+    smoothing: f64,
+
+    /// Weight given to the new deviation sample when updating the estimated variance.
+    variance_smoothing: f64,
+
+    /// Number of estimated deviations added on top of the smoothed estimate.
+    variance_multiplier: f64,
+
+    /// Lower bound on the time waited on top of the expected delay of a packet.
+    minimum_addition: Duration,
+
+    /// Upper bound on the time waited on top of the expected delay of a packet.
+    maximum_addition: Duration,
+}
+
+impl From<config::Acknowledgements> for AdaptiveAckTimeouts {
+    fn from(acks: config::Acknowledgements) -> Self {
+        AdaptiveAckTimeouts {
+            smoothing: acks.ack_rtt_smoothing,
+            variance_smoothing: acks.ack_rtt_variance_smoothing,
+            variance_multiplier: acks.ack_rtt_variance_multiplier,
+            minimum_addition: acks.minimum_ack_wait_addition,
+            maximum_addition: acks.maximum_ack_wait_addition,
+        }
+    }
+}
+
+// all values are expressed in seconds
+#[derive(Debug, Default, Clone, Copy)]
+struct RttEstimate {
+    smoothed: f64,
+    variance: f64,
+    samples: u32,
+}
+
+/// Estimates how long it takes, on top of the delays introduced by the mix nodes,
+/// for the acknowledgements to arrive, similarly to the TCP retransmission timer (RFC 6298).
+///
+/// Since the sphinx delays of every packet are known upfront, only the remaining latency
+/// (network transit, processing, queueing at the gateways) is being estimated.
+/// It is tracked separately for each route length as longer routes accumulate more of it.
+/// The routes of replies sent with the reply SURBs are of unknown length.
+pub(super) struct AckTimeoutEstimator {
+    config: AdaptiveAckTimeouts,
+    estimates: HashMap<Option<u8>, RttEstimate>,
+}
+
+impl AckTimeoutEstimator {
+    pub(super) fn new(config: AdaptiveAckTimeouts) -> Self {
+        AckTimeoutEstimator {
+            config,
+            estimates: HashMap::new(),
+        }
+    }
+
+    /// Records the measured round-trip time of an acknowledged packet whose mix delays
+    /// were expected to amount to `expected`.
+    pub(super) fn record(&mut self, route_length: Option<u8>, expected: Duration, rtt: Duration) {
+        let latency = rtt.as_secs_f64() - expected.as_secs_f64();
+        let estimate = self.estimates.entry(route_length).or_default();
+
+        if estimate.samples == 0 {
+            estimate.smoothed = latency;
+            estimate.variance = latency.abs() / 2.0;
+        } else {
+            let alpha = self.config.smoothing;
+            let beta = self.config.variance_smoothing;
+            estimate.variance =
+                (1.0 - beta) * estimate.variance + beta * (estimate.smoothed - latency).abs();
+            estimate.smoothed = (1.0 - alpha) * estimate.smoothed + alpha * latency;
+        }
+        estimate.samples = estimate.samples.saturating_add(1);
+    }
+
+    /// Returns the time to wait for the acknowledgement of a packet whose mix delays are
+    /// expected to amount to `expected` and that has already been retransmitted `retransmissions`
+    /// times. Similarly to TCP, the time waited on top of the expected delay is backed off
+    /// exponentially with every retransmission, so that a loss caused by congestion wouldn't
+    /// be made worse. If not enough round trips have been observed yet, `None` is returned.
+    pub(super) fn timeout(
+        &self,
+        route_length: Option<u8>,
+        expected: Duration,
+        retransmissions: u32,
+    ) -> Option<Duration> {
+        let estimate = self.estimates.get(&route_length)?;
+        if estimate.samples < MINIMUM_RTT_SAMPLES {
+            return None;
+        }
+
+        let addition = estimate.smoothed + self.config.variance_multiplier * estimate.variance;
+        let backoff = 2u32.pow(retransmissions.min(MAXIMUM_BACKOFF_EXPONENT));
+        let addition = (Duration::from_secs_f64(addition.max(0.0))
+            .max(self.config.minimum_addition)
+            * backoff)
+            .min(self.config.maximum_addition);
+
+        Some(expected + addition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimator() -> AckTimeoutEstimator {
+        AckTimeoutEstimator::new(AdaptiveAckTimeouts::from(
+            config::Acknowledgements::default(),
+        ))
+    }
+
+    #[test]
+    fn requires_enough_samples_before_adapting() {
+        let mut estimator = estimator();
+        let expected = Duration::from_millis(300);
+
+        for _ in 0..MINIMUM_RTT_SAMPLES - 1 {
+            estimator.record(Some(3), expected, Duration::from_millis(500));
+            assert!(estimator.timeout(Some(3), expected, 0).is_none());
+        }
+
+        estimator.record(Some(3), expected, Duration::from_millis(500));
+        let timeout = estimator.timeout(Some(3), expected, 0).unwrap();
+        assert!(timeout > Duration::from_millis(500));
+        assert!(timeout < Duration::from_millis(1000));
+
+        // routes of different length are estimated separately
+        assert!(estimator.timeout(Some(5), expected, 0).is_none());
+        assert!(estimator.timeout(None, expected, 0).is_none());
+    }
+
+    #[test]
+    fn adapts_to_route_latency() {
+        let mut estimator = estimator();
+        let expected = Duration::from_millis(300);
+
+        for _ in 0..20 {
+            estimator.record(Some(3), expected, Duration::from_millis(350));
+            estimator.record(None, expected, Duration::from_secs(4));
+        }
+
+        let fast = estimator.timeout(Some(3), expected, 0).unwrap();
+        let slow = estimator.timeout(None, expected, 0).unwrap();
+        assert!(fast < Duration::from_secs(1));
+        assert!(slow > Duration::from_secs(4));
+
+        let config = config::Acknowledgements::default();
+        assert!(fast >= expected + config.minimum_ack_wait_addition);
+        assert!(slow <= expected + config.maximum_ack_wait_addition);
+    }
+
+    #[test]
+    fn backs_off_with_retransmissions() {
+        let mut estimator = estimator();
+        let expected = Duration::from_millis(300);
+
+        for _ in 0..20 {
+            estimator.record(Some(3), expected, Duration::from_millis(800));
+        }
+
+        let first = estimator.timeout(Some(3), expected, 0).unwrap();
+        let second = estimator.timeout(Some(3), expected, 1).unwrap();
+        assert_eq!(second - expected, (first - expected) * 2);
+
+        let config = config::Acknowledgements::default();
+        let last = estimator.timeout(Some(3), expected, u32::MAX).unwrap();
+        assert_eq!(last, expected + config.maximum_ack_wait_addition);
+    }
+}
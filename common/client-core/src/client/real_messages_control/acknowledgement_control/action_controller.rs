@@ -1,9 +1,16 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use super::ack_timeout::{AckTimeoutEstimator, AdaptiveAckTimeouts};
 use super::PendingAcknowledgement;
+use crate::client::delivery::{
+    DeadLetter, DeliveryEventSender, DeliveryFailure, DeliveryTracker, MessageId,
+};
+use crate::client::helpers::{get_time_now, Instant};
+use crate::client::inbound_messages::InputMessage;
+use crate::client::latency_stats::LatencyStatsControl;
 use crate::client::real_messages_control::acknowledgement_control::RetransmissionRequestSender;
-use futures::channel::mpsc;
+use futures::channel::{mpsc, oneshot};
 use futures::StreamExt;
 use log::*;
 use nym_nonexhaustive_delayqueue::{Expired, NonExhaustiveDelayQueue, QueueKey};
@@ -24,6 +31,8 @@ type PendingAckEntry = (Arc<PendingAcknowledgement>, Option<QueueKey>);
 // - received an ack so we want to remove an entry
 // - start a retransmission timer for sending the packet into the network (on either first try or retransmission)
 // - update the internal sphinx delay of an expired packet
+// - keep track of which fragments belong to messages whose delivery should be reported
+// - query or take messages that could not have been delivered
 pub(crate) enum Action {
     /// Inserts new `PendingAcknowledgement`s into the 'shared' state.
     /// Initiated by `InputMessageListener`
@@ -42,6 +51,37 @@ pub(crate) enum Action {
     /// Updates the expected delay of given `PendingAcknowledgement` with the new provided `SphinxDelay`.
     /// Initiated by `RetransmissionRequestListener`
     UpdateDelay(FragmentIdentifier, SphinxDelay),
+
+    /// Associates given fragments with the `MessageId` so that its delivery could be reported
+    /// once all of them are acknowledged.
+    /// Initiated by `MessageHandler` or `ReplyController`
+    TrackDelivery(MessageId, Vec<FragmentIdentifier>),
+
+    /// Retains the content of the tracked message so that it could be put on the dead letter queue
+    /// if it could not have been delivered.
+    /// Initiated by `InputMessageListener`
+    TrackMessage(MessageId, Box<InputMessage>),
+
+    /// Reports failure of the message with given `MessageId` and stops retransmitting any
+    /// of its remaining fragments.
+    /// Initiated by `InputMessageListener` or `ReplyController`
+    FailDelivery(MessageId, DeliveryFailure),
+
+    /// Reports failure of all messages the given fragments belong to.
+    /// Initiated by `ReplyController` when it drops its stale pending replies.
+    FailFragments(Vec<FragmentIdentifier>, DeliveryFailure),
+
+    /// Sets the channel on which all delivery events are going to be sent.
+    /// Initiated by `ClientOutput`
+    RegisterDeliveryListener(DeliveryEventSender),
+
+    /// Retrieves all messages currently on the dead letter queue.
+    /// Initiated by `ClientOutput`
+    GetDeadLetters(oneshot::Sender<Vec<DeadLetter>>),
+
+    /// Removes the message with given `MessageId` from the dead letter queue and returns it.
+    /// Initiated by `ClientOutput`
+    TakeDeadLetter(MessageId, oneshot::Sender<Option<DeadLetter>>),
 }
 
 impl Action {
@@ -60,6 +100,28 @@ impl Action {
     pub(crate) fn new_update_delay(frag_id: FragmentIdentifier, delay: SphinxDelay) -> Self {
         Action::UpdateDelay(frag_id, delay)
     }
+
+    pub(crate) fn new_track_delivery(
+        message_id: MessageId,
+        fragments: Vec<FragmentIdentifier>,
+    ) -> Self {
+        Action::TrackDelivery(message_id, fragments)
+    }
+
+    pub(crate) fn new_track_message(message_id: MessageId, message: InputMessage) -> Self {
+        Action::TrackMessage(message_id, Box::new(message))
+    }
+
+    pub(crate) fn new_fail_delivery(message_id: MessageId, reason: DeliveryFailure) -> Self {
+        Action::FailDelivery(message_id, reason)
+    }
+
+    pub(crate) fn new_fail_fragments(
+        fragments: Vec<FragmentIdentifier>,
+        reason: DeliveryFailure,
+    ) -> Self {
+        Action::FailFragments(fragments, reason)
+    }
 }
 
 /// Configurable parameters of the `ActionController`
@@ -69,15 +131,46 @@ pub(super) struct Config {
 
     /// Given ack timeout in the form a * BASE_DELAY + b, it specifies the multiplier `a`
     ack_wait_multiplier: f64,
+
+    /// Maximum number of times a fragment is going to be retransmitted before giving up on it.
+    maximum_retransmissions: Option<u32>,
+
+    /// Maximum amount of time since a fragment was first sent during which it might still get retransmitted.
+    maximum_retransmission_period: Option<Duration>,
+
+    /// Maximum number of undelivered messages retained on the dead letter queue.
+    maximum_dead_letters: usize,
+
+    /// If specified, the ack timeouts are adjusted based on the observed round-trip times
+    /// rather than following the a * BASE_DELAY + b formula.
+    adaptive_timeouts: Option<AdaptiveAckTimeouts>,
 }
 
 impl Config {
-    pub(super) fn new(ack_wait_addition: Duration, ack_wait_multiplier: f64) -> Self {
+    pub(super) fn new(
+        ack_wait_addition: Duration,
+        ack_wait_multiplier: f64,
+        maximum_retransmissions: Option<u32>,
+        maximum_retransmission_period: Option<Duration>,
+        maximum_dead_letters: usize,
+    ) -> Self {
         Config {
             ack_wait_addition,
             ack_wait_multiplier,
+            maximum_retransmissions,
+            maximum_retransmission_period,
+            maximum_dead_letters,
+            adaptive_timeouts: None,
         }
     }
+
+    pub(super) fn with_adaptive_timeouts(
+        mut self,
+        adaptive_timeouts: Option<AdaptiveAckTimeouts>,
+    ) -> Self {
+        self.adaptive_timeouts = adaptive_timeouts;
+        self
+    }
 }
 
 pub(super) struct ActionController {
@@ -100,6 +193,19 @@ pub(super) struct ActionController {
 
     /// Channel for notifying `RetransmissionRequestListener` about expired acknowledgements.
     retransmission_sender: RetransmissionRequestSender,
+
+    /// Keeps track of messages whose delivery outcome should be reported.
+    delivery_tracker: DeliveryTracker,
+
+    /// Estimates the ack timeouts based on the observed round-trip times, if enabled.
+    timeout_estimator: Option<AckTimeoutEstimator>,
+
+    /// Times at which the packets, that have not been retransmitted, were sent into the network
+    /// so that their round-trip times could be measured.
+    sent_at: HashMap<FragmentIdentifier, Instant>,
+
+    /// Comparison of the scheduled delays of the packets with their observed round trips.
+    latency_stats: LatencyStatsControl,
 }
 
 impl ActionController {
@@ -107,13 +213,20 @@ impl ActionController {
         config: Config,
         retransmission_sender: RetransmissionRequestSender,
         incoming_actions: AckActionReceiver,
+        latency_stats: LatencyStatsControl,
     ) -> Self {
+        let delivery_tracker = DeliveryTracker::new(config.maximum_dead_letters);
+        let timeout_estimator = config.adaptive_timeouts.map(AckTimeoutEstimator::new);
         ActionController {
             config,
             pending_acks_data: HashMap::new(),
             pending_acks_timers: NonExhaustiveDelayQueue::new(),
             incoming_actions,
             retransmission_sender,
+            delivery_tracker,
+            timeout_estimator,
+            sent_at: HashMap::new(),
+            latency_stats,
         }
     }
 
@@ -147,9 +260,29 @@ impl ActionController {
             //     // timer TWICE for the SAME PendingAcknowledgement
             //     panic!("Tried to start an already started ack timer!")
             // }
-            let timeout = (pending_ack_data.delay * self.config.ack_wait_multiplier).to_duration()
+            let static_timeout = (pending_ack_data.delay * self.config.ack_wait_multiplier)
+                .to_duration()
                 + self.config.ack_wait_addition;
 
+            // the round-trip times of the retransmitted packets are ambiguous as we can't
+            // tell which of the copies got acknowledged (Karn's algorithm)
+            if pending_ack_data.retransmissions == 0 && !self.sent_at.contains_key(&frag_id) {
+                self.sent_at.insert(frag_id, get_time_now());
+                self.latency_stats
+                    .record_sent(pending_ack_data.delay.to_duration());
+            }
+
+            let timeout = match &self.timeout_estimator {
+                Some(estimator) => estimator
+                    .timeout(
+                        pending_ack_data.route_length,
+                        pending_ack_data.delay.to_duration(),
+                        pending_ack_data.retransmissions,
+                    )
+                    .unwrap_or(static_timeout),
+                None => static_timeout,
+            };
+
             let new_queue_key = self.pending_acks_timers.insert(frag_id, timeout);
             *queue_key = Some(new_queue_key)
         } else {
@@ -162,7 +295,30 @@ impl ActionController {
 
     fn handle_remove(&mut self, frag_id: FragmentIdentifier) {
         trace!("{} is getting removed", frag_id);
+        self.record_round_trip(frag_id);
+        self.delivery_tracker.on_acked(frag_id);
+        self.remove_pending_ack(frag_id)
+    }
 
+    fn record_round_trip(&mut self, frag_id: FragmentIdentifier) {
+        let Some(sent_at) = self.sent_at.remove(&frag_id) else {
+            return;
+        };
+        let Some((pending_ack_data, _)) = self.pending_acks_data.get(&frag_id) else {
+            return;
+        };
+
+        let scheduled = pending_ack_data.delay.to_duration();
+        let round_trip = get_time_now().duration_since(sent_at);
+        self.latency_stats
+            .record_acknowledged(scheduled, round_trip);
+        if let Some(estimator) = self.timeout_estimator.as_mut() {
+            estimator.record(pending_ack_data.route_length, scheduled, round_trip);
+        }
+    }
+
+    fn remove_pending_ack(&mut self, frag_id: FragmentIdentifier) {
+        self.sent_at.remove(&frag_id);
         match self.pending_acks_data.remove(&frag_id) {
             None => {
                 debug!(
@@ -193,6 +349,7 @@ impl ActionController {
     // (as new sphinx packet was created with new expected delivery time)
     fn handle_update_delay(&mut self, frag_id: FragmentIdentifier, delay: SphinxDelay) {
         trace!("{} is updating its delay", frag_id);
+        self.sent_at.remove(&frag_id);
         // TODO: is it possible to solve this without either locking or temporarily removing the value?
         if let Some((pending_ack_data, queue_key)) = self.pending_acks_data.remove(&frag_id) {
             // this Action is triggered by `RetransmissionRequestListener` (for 'normal' packets)
@@ -232,6 +389,38 @@ impl ActionController {
                 panic!("Ack expired before it was even scheduled!")
             }
             *queue_key = None;
+
+            if pending_ack_data.is_expired() {
+                debug!("{frag_id} has not been acknowledged before its message expired. Giving up on it");
+                self.give_up_on(frag_id, DeliveryFailure::Expired);
+                return;
+            }
+
+            if let Some(maximum) = self.config.maximum_retransmissions {
+                let retransmissions = pending_ack_data.retransmissions;
+                if retransmissions >= maximum {
+                    warn!("{frag_id} has not been acknowledged after {retransmissions} retransmissions. Giving up on it");
+                    let reason = DeliveryFailure::RetransmissionsExhausted {
+                        fragment: frag_id,
+                        retransmissions,
+                    };
+                    self.give_up_on(frag_id, reason);
+                    return;
+                }
+            }
+
+            if let Some(period) = self.config.maximum_retransmission_period {
+                if get_time_now().duration_since(pending_ack_data.created_at) >= period {
+                    warn!("{frag_id} has not been acknowledged within {period:?}. Giving up on it");
+                    let reason = DeliveryFailure::RetransmissionPeriodExceeded {
+                        fragment: frag_id,
+                        period,
+                    };
+                    self.give_up_on(frag_id, reason);
+                    return;
+                }
+            }
+
             // downgrading an arc and then upgrading vs cloning is difference of 30ns vs 15ns
             // so it's literally a NO difference while it might prevent us from unnecessarily
             // resending data (in maybe 1 in 1 million cases, but it's something)
@@ -251,12 +440,72 @@ impl ActionController {
         }
     }
 
+    // the fragment has exhausted its retransmission budget so we drop it alongside all other
+    // fragments of its message (if it was tracked) since it couldn't be reconstructed anyway
+    fn give_up_on(&mut self, frag_id: FragmentIdentifier, reason: DeliveryFailure) {
+        self.pending_acks_data.remove(&frag_id);
+        self.sent_at.remove(&frag_id);
+
+        for remaining in self.delivery_tracker.on_failed(frag_id, reason) {
+            self.remove_pending_ack(remaining)
+        }
+    }
+
+    fn handle_fail_delivery(&mut self, message_id: MessageId, reason: DeliveryFailure) {
+        for remaining in self.delivery_tracker.fail_message(message_id, reason) {
+            self.remove_pending_ack(remaining)
+        }
+    }
+
+    fn handle_fail_fragments(
+        &mut self,
+        fragments: Vec<FragmentIdentifier>,
+        reason: DeliveryFailure,
+    ) {
+        for frag_id in fragments {
+            for remaining in self.delivery_tracker.on_failed(frag_id, reason.clone()) {
+                self.remove_pending_ack(remaining)
+            }
+        }
+    }
+
     fn process_action(&mut self, action: Action) {
         match action {
             Action::InsertPending(pending_acks) => self.handle_insert(pending_acks),
             Action::RemovePending(frag_id) => self.handle_remove(frag_id),
             Action::StartTimer(frag_id) => self.handle_start_timer(frag_id),
             Action::UpdateDelay(frag_id, delay) => self.handle_update_delay(frag_id, delay),
+            Action::TrackDelivery(message_id, fragments) => {
+                self.delivery_tracker.track(message_id, fragments)
+            }
+            Action::FailDelivery(message_id, reason) => {
+                self.handle_fail_delivery(message_id, reason)
+            }
+            Action::FailFragments(fragments, reason) => {
+                self.handle_fail_fragments(fragments, reason)
+            }
+            Action::TrackMessage(message_id, message) => {
+                self.delivery_tracker.track_content(message_id, *message)
+            }
+            Action::RegisterDeliveryListener(listener) => {
+                self.delivery_tracker.register_listener(listener)
+            }
+            Action::GetDeadLetters(response_channel) => {
+                if response_channel
+                    .send(self.delivery_tracker.dead_letters())
+                    .is_err()
+                {
+                    debug!("the dead letters requester has gone away")
+                }
+            }
+            Action::TakeDeadLetter(message_id, response_channel) => {
+                if response_channel
+                    .send(self.delivery_tracker.take_dead_letter(message_id))
+                    .is_err()
+                {
+                    debug!("the dead letter requester has gone away")
+                }
+            }
         }
     }
 
@@ -1,7 +1,9 @@
 // Copyright 2021-2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::client::delivery::{DeliveryFailure, MessageId};
 use crate::client::inbound_messages::{InputMessage, InputMessageReceiver};
+use crate::client::real_messages_control::acknowledgement_control::{AckActionSender, Action};
 use crate::client::real_messages_control::message_handler::MessageHandler;
 use crate::client::replies::reply_controller::ReplyControllerSender;
 use log::*;
@@ -9,6 +11,7 @@ use nym_sphinx::addressing::clients::Recipient;
 use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
 use nym_task::connections::TransmissionLane;
 use rand::{CryptoRng, Rng};
+use time::OffsetDateTime;
 
 /// Module responsible for dealing with the received messages: splitting them, creating acknowledgements,
 /// putting everything into sphinx packets, etc.
@@ -20,6 +23,7 @@ where
     input_receiver: InputMessageReceiver,
     message_handler: MessageHandler<R>,
     reply_controller_sender: ReplyControllerSender,
+    ack_action_sender: AckActionSender,
 }
 
 impl<R> InputMessageListener<R>
@@ -33,23 +37,42 @@ where
         input_receiver: InputMessageReceiver,
         message_handler: MessageHandler<R>,
         reply_controller_sender: ReplyControllerSender,
+        ack_action_sender: AckActionSender,
     ) -> Self {
         InputMessageListener {
             input_receiver,
             message_handler,
             reply_controller_sender,
+            ack_action_sender,
         }
     }
 
+    fn report_failure(&self, message_id: Option<MessageId>, reason: DeliveryFailure) {
+        if let Some(message_id) = message_id {
+            self.ack_action_sender
+                .unbounded_send(Action::new_fail_delivery(message_id, reason))
+                .expect("action control task has died")
+        }
+    }
+
+    // retain the original message so that it could be put on the dead letter queue
+    // (and possibly resent by the user) if we fail to deliver it
+    fn track_message(&self, message_id: MessageId, message: InputMessage) {
+        self.ack_action_sender
+            .unbounded_send(Action::new_track_message(message_id, message))
+            .expect("action control task has died")
+    }
+
     async fn handle_reply(
         &mut self,
         recipient_tag: AnonymousSenderTag,
         data: Vec<u8>,
         lane: TransmissionLane,
+        message_id: Option<MessageId>,
     ) {
         // offload reply handling to the dedicated task
         self.reply_controller_sender
-            .send_reply(recipient_tag, data, lane)
+            .send_reply(recipient_tag, data, lane, message_id)
     }
 
     async fn handle_plain_message(
@@ -57,54 +80,163 @@ where
         recipient: Recipient,
         content: Vec<u8>,
         lane: TransmissionLane,
+        message_id: Option<MessageId>,
+        expires_at: Option<OffsetDateTime>,
     ) {
         if let Err(err) = self
             .message_handler
-            .try_send_plain_message(recipient, content, lane)
+            .try_send_plain_message(recipient, content, lane, message_id, expires_at)
             .await
         {
-            warn!("failed to send a plain message - {err}")
+            warn!("failed to send a plain message - {err}");
+            self.report_failure(
+                message_id,
+                DeliveryFailure::PreparationFailure(err.to_string()),
+            )
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn handle_repliable_message(
         &mut self,
         recipient: Recipient,
         content: Vec<u8>,
         reply_surbs: u32,
         lane: TransmissionLane,
+        message_id: Option<MessageId>,
+        expires_at: Option<OffsetDateTime>,
+        sender_tag: Option<AnonymousSenderTag>,
     ) {
         if let Err(err) = self
             .message_handler
-            .try_send_message_with_reply_surbs(recipient, content, reply_surbs, lane)
+            .try_send_message_with_reply_surbs(
+                recipient,
+                content,
+                reply_surbs,
+                lane,
+                message_id,
+                expires_at,
+                sender_tag,
+            )
             .await
         {
-            warn!("failed to send a repliable message - {err}")
+            warn!("failed to send a repliable message - {err}");
+            self.report_failure(
+                message_id,
+                DeliveryFailure::PreparationFailure(err.to_string()),
+            )
         }
     }
 
     async fn on_input_message(&mut self, msg: InputMessage) {
+        // the delivery tracking, the expiry and the sender tag might have been attached in any order
+        let mut message_id = None;
+        let mut expires_at = None;
+        let mut sender_tag = None;
+        let mut msg = msg;
+        let msg = loop {
+            msg = match msg {
+                InputMessage::Tracked {
+                    message,
+                    message_id: id,
+                } if message_id.is_none() => {
+                    self.track_message(id, (*message).clone());
+                    message_id = Some(id);
+                    *message
+                }
+                InputMessage::Expiring {
+                    message,
+                    expires_at: expiry,
+                } if expires_at.is_none() => {
+                    expires_at = Some(expiry);
+                    *message
+                }
+                InputMessage::Isolated {
+                    message,
+                    sender_tag: tag,
+                } if sender_tag.is_none() => {
+                    sender_tag = Some(tag);
+                    *message
+                }
+                msg => break msg,
+            }
+        };
+
+        if matches!(expires_at, Some(expires_at) if expires_at <= OffsetDateTime::now_utc()) {
+            debug!(
+                "received an input message that has already expired - it's not going to be sent"
+            );
+            self.report_failure(message_id, DeliveryFailure::Expired);
+            return;
+        }
+
         match msg {
             InputMessage::Regular {
                 recipient,
                 data,
                 lane,
-            } => self.handle_plain_message(recipient, data, lane).await,
+            } => {
+                self.handle_plain_message(recipient, data, lane, message_id, expires_at)
+                    .await
+            }
             InputMessage::Anonymous {
                 recipient,
                 data,
                 reply_surbs,
                 lane,
             } => {
-                self.handle_repliable_message(recipient, data, reply_surbs, lane)
-                    .await
+                self.handle_repliable_message(
+                    recipient,
+                    data,
+                    reply_surbs,
+                    lane,
+                    message_id,
+                    expires_at,
+                    sender_tag,
+                )
+                .await
             }
             InputMessage::Reply {
                 recipient_tag,
                 data,
                 lane,
             } => {
-                self.handle_reply(recipient_tag, data, lane).await;
+                if expires_at.is_some() {
+                    // the reply surbs have been created by the recipient itself,
+                    // so there's no way of attaching the expiry to the packets
+                    debug!("the expiry of replies is not supported - the message is going to be sent without it");
+                }
+                self.handle_reply(recipient_tag, data, lane, message_id)
+                    .await;
+            }
+            InputMessage::Tracked { .. } => {
+                warn!("received a doubly tracked input message - it's not going to be sent");
+                self.report_failure(
+                    message_id,
+                    DeliveryFailure::PreparationFailure(
+                        "nested delivery tracking is not supported".to_string(),
+                    ),
+                )
+            }
+            InputMessage::Expiring { .. } => {
+                warn!("received an input message with nested expiry - it's not going to be sent");
+                self.report_failure(
+                    message_id,
+                    DeliveryFailure::PreparationFailure(
+                        "nested message expiry is not supported".to_string(),
+                    ),
+                )
+            }
+            InputMessage::Isolated { .. } => {
+                warn!(
+                    "received an input message with nested sender tags - it's not going to be sent"
+                );
+                self.report_failure(
+                    message_id,
+                    DeliveryFailure::PreparationFailure(
+                        "nested sender tags are not supported".to_string(),
+                    ),
+                )
             }
         };
     }
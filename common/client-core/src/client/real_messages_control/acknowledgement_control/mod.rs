@@ -7,11 +7,12 @@ use self::{
     retransmission_request_listener::RetransmissionRequestListener,
     sent_notification_listener::SentNotificationListener,
 };
+use crate::client::helpers::{get_time_now, Instant};
 use crate::client::inbound_messages::InputMessageReceiver;
+use crate::client::latency_stats::LatencyStatsControl;
 use crate::client::real_messages_control::message_handler::MessageHandler;
 use crate::client::replies::reply_controller::ReplyControllerSender;
 use crate::spawn_future;
-use action_controller::AckActionReceiver;
 use futures::channel::mpsc;
 use log::*;
 use nym_gateway_client::AcknowledgementReceiver;
@@ -28,9 +29,12 @@ use std::{
     sync::{Arc, Weak},
     time::Duration,
 };
+use time::OffsetDateTime;
 
-pub(crate) use action_controller::{AckActionSender, Action};
+pub(crate) use ack_timeout::AdaptiveAckTimeouts;
+pub(crate) use action_controller::{AckActionReceiver, AckActionSender, Action};
 
+mod ack_timeout;
 mod acknowledgement_listener;
 mod action_controller;
 mod input_message_listener;
@@ -69,6 +73,11 @@ pub(crate) struct PendingAcknowledgement {
     message_chunk: Fragment,
     delay: SphinxDelay,
     destination: PacketDestination,
+    /// Number of mix hops on the route of the packet, if known.
+    route_length: Option<u8>,
+    retransmissions: u32,
+    created_at: Instant,
+    expires_at: Option<OffsetDateTime>,
 }
 
 impl PendingAcknowledgement {
@@ -77,11 +86,17 @@ impl PendingAcknowledgement {
         message_chunk: Fragment,
         delay: SphinxDelay,
         recipient: Recipient,
+        expires_at: Option<OffsetDateTime>,
+        mix_hops: u8,
     ) -> Self {
         PendingAcknowledgement {
             message_chunk,
             delay,
             destination: PacketDestination::KnownRecipient(recipient.into()),
+            route_length: Some(mix_hops),
+            retransmissions: 0,
+            created_at: get_time_now(),
+            expires_at,
         }
     }
 
@@ -98,6 +113,11 @@ impl PendingAcknowledgement {
                 recipient_tag,
                 extra_surb_request,
             },
+            // we have no idea how many hops the reply surbs of the recipient go through
+            route_length: None,
+            retransmissions: 0,
+            created_at: get_time_now(),
+            expires_at: None,
         }
     }
 
@@ -109,8 +129,15 @@ impl PendingAcknowledgement {
         self.message_chunk.clone()
     }
 
+    /// Checks whether the message this fragment belongs to is no longer worth delivering.
+    fn is_expired(&self) -> bool {
+        matches!(self.expires_at, Some(expires_at) if expires_at <= OffsetDateTime::now_utc())
+    }
+
+    // the delay is only ever updated when the fragment is being retransmitted
     fn update_delay(&mut self, new_delay: SphinxDelay) {
         self.delay = new_delay;
+        self.retransmissions += 1;
     }
 }
 
@@ -162,19 +189,49 @@ pub(super) struct Config {
     /// Given ack timeout in the form a * BASE_DELAY + b, it specifies the multiplier `a`
     ack_wait_multiplier: f64,
 
+    /// Maximum number of times a fragment is going to be retransmitted before giving up on it.
+    maximum_retransmissions: Option<u32>,
+
+    /// Maximum amount of time since a fragment was first sent during which it might still get retransmitted.
+    maximum_retransmission_period: Option<Duration>,
+
+    /// Maximum number of undelivered messages retained on the dead letter queue.
+    maximum_dead_letters: usize,
+
+    /// If specified, the ack timeouts are adjusted based on the observed round-trip times.
+    adaptive_timeouts: Option<AdaptiveAckTimeouts>,
+
     /// Predefined packet size used for the encapsulated messages.
     packet_size: PacketSize,
 }
 
 impl Config {
-    pub(super) fn new(ack_wait_addition: Duration, ack_wait_multiplier: f64) -> Self {
+    pub(super) fn new(
+        ack_wait_addition: Duration,
+        ack_wait_multiplier: f64,
+        maximum_retransmissions: Option<u32>,
+        maximum_retransmission_period: Option<Duration>,
+        maximum_dead_letters: usize,
+    ) -> Self {
         Config {
             ack_wait_addition,
             ack_wait_multiplier,
+            maximum_retransmissions,
+            maximum_retransmission_period,
+            maximum_dead_letters,
+            adaptive_timeouts: None,
             packet_size: Default::default(),
         }
     }
 
+    pub fn with_adaptive_timeouts(
+        mut self,
+        adaptive_timeouts: Option<AdaptiveAckTimeouts>,
+    ) -> Self {
+        self.adaptive_timeouts = adaptive_timeouts;
+        self
+    }
+
     pub fn with_custom_packet_size(mut self, packet_size: PacketSize) -> Self {
         self.packet_size = packet_size;
         self
@@ -202,15 +259,23 @@ where
         connectors: AcknowledgementControllerConnectors,
         message_handler: MessageHandler<R>,
         reply_controller_sender: ReplyControllerSender,
+        latency_stats: LatencyStatsControl,
     ) -> Self {
         let (retransmission_tx, retransmission_rx) = mpsc::unbounded();
 
-        let action_config =
-            action_controller::Config::new(config.ack_wait_addition, config.ack_wait_multiplier);
+        let action_config = action_controller::Config::new(
+            config.ack_wait_addition,
+            config.ack_wait_multiplier,
+            config.maximum_retransmissions,
+            config.maximum_retransmission_period,
+            config.maximum_dead_letters,
+        )
+        .with_adaptive_timeouts(config.adaptive_timeouts);
         let action_controller = ActionController::new(
             action_config,
             retransmission_tx,
             connectors.ack_action_receiver,
+            latency_stats,
         );
 
         // will listen for any acks coming from the network
@@ -225,6 +290,7 @@ where
             connectors.input_receiver,
             message_handler.clone(),
             reply_controller_sender.clone(),
+            connectors.ack_action_sender.clone(),
         );
 
         // will listen for any ack timeouts and trigger retransmission
@@ -17,6 +17,7 @@ use nym_sphinx::preparer::PreparedFragment;
 use nym_task::connections::TransmissionLane;
 use rand::{CryptoRng, Rng};
 use std::sync::{Arc, Weak};
+use time::OffsetDateTime;
 
 // responsible for packet retransmission upon fired timer
 pub(super) struct RetransmissionRequestListener<R> {
@@ -48,11 +49,12 @@ where
         &mut self,
         packet_recipient: Recipient,
         chunk_data: Fragment,
+        expires_at: Option<OffsetDateTime>,
     ) -> Result<PreparedFragment, PreparationError> {
         debug!("retransmitting normal packet...");
 
         self.message_handler
-            .try_prepare_single_chunk_for_sending(packet_recipient, chunk_data)
+            .try_prepare_single_chunk_for_sending(packet_recipient, chunk_data, expires_at)
             .await
     }
 
@@ -85,6 +87,7 @@ where
                 self.prepare_normal_retransmission_chunk(
                     **recipient,
                     timed_out_ack.message_chunk.clone(),
+                    timed_out_ack.expires_at,
                 )
                 .await
             }
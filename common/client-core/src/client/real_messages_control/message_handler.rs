@@ -1,6 +1,7 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::client::delivery::{DeliveryFailure, MessageId};
 use crate::client::real_messages_control::acknowledgement_control::PendingAcknowledgement;
 use crate::client::real_messages_control::real_traffic_stream::{
     BatchRealMessageSender, RealMessage,
@@ -15,16 +16,20 @@ use nym_sphinx::anonymous_replies::requests::{AnonymousSenderTag, RepliableMessa
 use nym_sphinx::anonymous_replies::{ReplySurb, SurbEncryptionKey};
 use nym_sphinx::chunking::fragment::{Fragment, FragmentIdentifier};
 use nym_sphinx::message::NymMessage;
-use nym_sphinx::params::{PacketSize, DEFAULT_NUM_MIX_HOPS};
-use nym_sphinx::preparer::{MessagePreparer, PreparedFragment};
+use nym_sphinx::params::{PacketSize, PaddingPolicy, DEFAULT_NUM_MIX_HOPS};
+use nym_sphinx::preparer::{MessagePreparer, PreparedFragment, SequenceScope};
 use nym_sphinx::Delay;
 use nym_task::connections::TransmissionLane;
 use nym_topology::{NymTopology, NymTopologyError};
 use rand::{CryptoRng, Rng};
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use thiserror::Error;
+use time::OffsetDateTime;
+
+// number of routes kept precomputed for each of the isolated sender tags
+const ISOLATED_ROUTE_POOL_SIZE: usize = 50;
 
 // TODO: move that error elsewhere since it seems to be contaminating different files
 #[derive(Debug, Clone, Error)]
@@ -106,6 +111,15 @@ pub(crate) struct Config {
 
     /// Optional secondary predefined packet size used for the encapsulated messages.
     secondary_packet_size: Option<PacketSize>,
+
+    /// Policy deciding which of the packet sizes supported by the network is used for given message.
+    padding_policy: PaddingPolicy,
+
+    /// Specifies whether the sent messages carry their sequence numbers and coarse timestamps.
+    attach_message_ordering: bool,
+
+    /// Specifies whether the expiry of the sent messages is revealed to the recipient's gateway.
+    disclose_message_expiry: bool,
 }
 
 impl Config {
@@ -123,6 +137,9 @@ impl Config {
             num_mix_hops: DEFAULT_NUM_MIX_HOPS,
             primary_packet_size: PacketSize::default(),
             secondary_packet_size: None,
+            padding_policy: PaddingPolicy::default(),
+            attach_message_ordering: false,
+            disclose_message_expiry: false,
         }
     }
 
@@ -144,6 +161,24 @@ impl Config {
         self.secondary_packet_size = packet_size;
         self
     }
+
+    /// Allows setting non-default policy for choosing the size of the sphinx packets sent out.
+    pub fn with_padding_policy(mut self, padding_policy: PaddingPolicy) -> Self {
+        self.padding_policy = padding_policy;
+        self
+    }
+
+    /// Allows attaching the ordering information to all sent messages.
+    pub fn with_message_ordering(mut self, attach_message_ordering: bool) -> Self {
+        self.attach_message_ordering = attach_message_ordering;
+        self
+    }
+
+    /// Allows revealing the expiry of the sent messages to the recipient's gateway.
+    pub fn with_message_expiry_disclosure(mut self, disclose_message_expiry: bool) -> Self {
+        self.disclose_message_expiry = disclose_message_expiry;
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -180,7 +215,8 @@ where
             config.average_packet_delay,
             config.average_ack_delay,
         )
-        .with_mix_hops(config.num_mix_hops);
+        .with_mix_hops(config.num_mix_hops)
+        .with_message_ordering(config.attach_message_ordering);
 
         MessageHandler {
             config,
@@ -207,6 +243,18 @@ where
         }
     }
 
+    // whether the tag is an explicitly chosen one rather than the default tag of the recipient
+    fn is_isolated(&self, sender_tag: &AnonymousSenderTag, recipient: &Recipient) -> bool {
+        self.tag_storage.try_get_existing(recipient).as_ref() != Some(sender_tag)
+    }
+
+    // the expiry is only attached to the packets if we're explicitly allowed to reveal it
+    fn gateway_visible_expiry(&self, expires_at: Option<OffsetDateTime>) -> Option<SystemTime> {
+        expires_at
+            .filter(|_| self.config.disclose_message_expiry)
+            .map(SystemTime::from)
+    }
+
     fn get_topology<'a>(
         &self,
         permit: &'a TopologyReadPermit<'a>,
@@ -220,34 +268,67 @@ where
         }
     }
 
-    fn optimal_packet_size(&self, msg: &NymMessage) -> PacketSize {
-        // if secondary packet was never set, then it's obvious we have to use the primary packet
-        let Some(secondary_packet) = self.config.secondary_packet_size else {
-            trace!("only primary packet size is available");
-            return self.config.primary_packet_size
-        };
-
-        let primary_count =
-            msg.required_packets(self.config.primary_packet_size, self.config.num_mix_hops);
-        let secondary_count = msg.required_packets(secondary_packet, self.config.num_mix_hops);
+    // view of the topology whose routes come from the pool used exclusively by the isolated tag,
+    // so that the packets of different isolated streams would never draw routes from the same source
+    fn isolated_topology(
+        &mut self,
+        topology: &NymTopology,
+        sender_tag: &AnonymousSenderTag,
+    ) -> NymTopology {
+        let isolated = topology.isolated_view(&sender_tag.to_bytes());
+        if let Err(err) = isolated.replenish_route_pool(
+            &mut self.rng,
+            DEFAULT_NUM_MIX_HOPS,
+            ISOLATED_ROUTE_POOL_SIZE,
+        ) {
+            debug!("failed to replenish the route pool of {sender_tag} - {err}");
+        }
+        isolated
+    }
 
-        trace!("This message would require: {primary_count} primary packets or {secondary_count} secondary packets...");
-        // if there would be no benefit in using the secondary packet - use the primary (duh)
-        if primary_count <= secondary_count {
-            trace!("so choosing primary for this message");
-            self.config.primary_packet_size
-        } else {
-            trace!("so choosing secondary for this message");
-            secondary_packet
+    async fn supported_packet_sizes(&self) -> Vec<PacketSize> {
+        match self.topology_access.get_read_permit().await.as_ref() {
+            Some(topology) => topology.supported_packet_sizes(),
+            // we won't be able to send anything anyway
+            None => vec![self.config.primary_packet_size],
         }
     }
 
+    fn optimal_packet_size(&self, msg: &NymMessage, supported: &[PacketSize]) -> PacketSize {
+        let packet_size = self.config.padding_policy.choose_packet_size(
+            self.config.primary_packet_size,
+            self.config.secondary_packet_size,
+            supported,
+            |packet_size| {
+                msg.required_packets(
+                    packet_size,
+                    self.config.num_mix_hops,
+                    self.config.attach_message_ordering,
+                )
+            },
+        );
+        trace!(
+            "{:?} policy chose {packet_size} packets out of {supported:?}",
+            self.config.padding_policy
+        );
+        packet_size
+    }
+
     async fn generate_reply_surbs_with_keys(
         &mut self,
         amount: usize,
+        isolation: Option<AnonymousSenderTag>,
     ) -> Result<(Vec<ReplySurb>, Vec<SurbEncryptionKey>), PreparationError> {
         let topology_permit = self.topology_access.get_read_permit().await;
         let topology = self.get_topology(&topology_permit)?;
+        let isolated_topology;
+        let topology = match isolation {
+            Some(sender_tag) => {
+                isolated_topology = self.isolated_topology(topology, &sender_tag);
+                &isolated_topology
+            }
+            None => topology,
+        };
 
         let reply_surbs = self
             .message_preparer
@@ -269,12 +350,13 @@ where
         is_extra_surb_request: bool,
     ) -> Result<(), SurbWrappedPreparationError> {
         let msg = NymMessage::new_reply(message);
-        let packet_size = self.optimal_packet_size(&msg);
+        let supported_sizes = self.supported_packet_sizes().await;
+        let packet_size = self.optimal_packet_size(&msg, &supported_sizes);
         debug!("Using {packet_size} packets for {msg}");
 
-        let mut fragment = self
-            .message_preparer
-            .pad_and_split_message(msg, packet_size);
+        let mut fragment =
+            self.message_preparer
+                .pad_and_split_message(msg, packet_size, target.into());
         if fragment.len() > 1 {
             // well, it's not a single surb message
             return Err(SurbWrappedPreparationError {
@@ -323,13 +405,18 @@ where
     }
 
     // // TODO: this will require additional argument to make it use different variant of `ReplyMessage`
-    pub(crate) fn split_reply_message(&mut self, message: Vec<u8>) -> Vec<Fragment> {
+    pub(crate) async fn split_reply_message(
+        &mut self,
+        target: AnonymousSenderTag,
+        message: Vec<u8>,
+    ) -> Vec<Fragment> {
         let msg = NymMessage::new_reply(ReplyMessage::new_data_message(message));
-        let packet_size = self.optimal_packet_size(&msg);
+        let supported_sizes = self.supported_packet_sizes().await;
+        let packet_size = self.optimal_packet_size(&msg, &supported_sizes);
         debug!("Using {packet_size} packets for {msg}");
 
         self.message_preparer
-            .pad_and_split_message(msg, packet_size)
+            .pad_and_split_message(msg, packet_size, target.into())
     }
 
     pub(crate) async fn send_retransmission_reply_chunks(
@@ -406,9 +493,11 @@ where
         recipient: Recipient,
         message: Vec<u8>,
         lane: TransmissionLane,
+        message_id: Option<MessageId>,
+        expires_at: Option<OffsetDateTime>,
     ) -> Result<(), PreparationError> {
         let message = NymMessage::new_plain(message);
-        self.try_split_and_send_non_reply_message(message, recipient, lane)
+        self.try_split_and_send_non_reply_message(message, recipient, lane, message_id, expires_at)
             .await
     }
 
@@ -417,6 +506,25 @@ where
         message: NymMessage,
         recipient: Recipient,
         lane: TransmissionLane,
+        message_id: Option<MessageId>,
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<(), PreparationError> {
+        self.try_split_and_send_isolated_non_reply_message(
+            message, recipient, lane, message_id, expires_at, None,
+        )
+        .await
+    }
+
+    // if the message is sent under an explicitly chosen (isolated) sender tag, it gets its own
+    // sequence numbers and routes, so it couldn't be linked with other streams sent to the same recipient
+    async fn try_split_and_send_isolated_non_reply_message(
+        &mut self,
+        message: NymMessage,
+        recipient: Recipient,
+        lane: TransmissionLane,
+        message_id: Option<MessageId>,
+        expires_at: Option<OffsetDateTime>,
+        isolation: Option<AnonymousSenderTag>,
     ) -> Result<(), PreparationError> {
         // TODO: I really dislike existence of this assertion, it implies code has to be re-organised
         debug_assert!(!matches!(message, NymMessage::Reply(_)));
@@ -424,12 +532,20 @@ where
         // TODO2: it's really annoying we have to get topology permit again here due to borrow-checker
         let topology_permit = self.topology_access.get_read_permit().await;
         let topology = self.get_topology(&topology_permit)?;
+        let isolated_topology;
+        let (topology, sequence_scope) = match isolation {
+            Some(sender_tag) => {
+                isolated_topology = self.isolated_topology(topology, &sender_tag);
+                (&isolated_topology, SequenceScope::SenderTag(sender_tag))
+            }
+            None => (topology, recipient.into()),
+        };
 
-        let packet_size = self.optimal_packet_size(&message);
+        let packet_size = self.optimal_packet_size(&message, &topology.supported_packet_sizes());
         debug!("Using {packet_size} packets for {message}");
-        let fragments = self
-            .message_preparer
-            .pad_and_split_message(message, packet_size);
+        let fragments =
+            self.message_preparer
+                .pad_and_split_message(message, packet_size, sequence_scope);
 
         let mut pending_acks = Vec::with_capacity(fragments.len());
         let mut real_messages = Vec::with_capacity(fragments.len());
@@ -437,22 +553,36 @@ where
             // we need to clone it because we need to keep it in memory in case we had to retransmit
             // it. And then we'd need to recreate entire ACK again.
             let chunk_clone = fragment.clone();
-            let prepared_fragment = self.message_preparer.prepare_chunk_for_sending(
+            let prepared_fragment = self.message_preparer.prepare_expiring_chunk_for_sending(
                 chunk_clone,
                 topology,
                 &self.config.ack_key,
                 &recipient,
+                self.gateway_visible_expiry(expires_at),
             )?;
 
             let real_message =
                 RealMessage::new(prepared_fragment.mix_packet, fragment.fragment_identifier());
             let delay = prepared_fragment.total_delay;
-            let pending_ack = PendingAcknowledgement::new_known(fragment, delay, recipient);
+            let pending_ack = PendingAcknowledgement::new_known(
+                fragment,
+                delay,
+                recipient,
+                expires_at,
+                self.config.num_mix_hops,
+            );
 
             real_messages.push(real_message);
             pending_acks.push(pending_ack);
         }
 
+        if let Some(message_id) = message_id {
+            let fragments = pending_acks
+                .iter()
+                .map(|ack| ack.inner_fragment_identifier())
+                .collect();
+            self.track_delivery(message_id, fragments);
+        }
         self.insert_pending_acks(pending_acks);
         self.forward_messages(real_messages, lane).await;
 
@@ -463,49 +593,70 @@ where
         &mut self,
         recipient: Recipient,
         amount: u32,
+        sender_tag: Option<AnonymousSenderTag>,
     ) -> Result<(), PreparationError> {
-        let sender_tag = self.get_or_create_sender_tag(&recipient);
-        let (reply_surbs, reply_keys) =
-            self.generate_reply_surbs_with_keys(amount as usize).await?;
+        let isolation = sender_tag.filter(|tag| self.is_isolated(tag, &recipient));
+        let sender_tag = sender_tag.unwrap_or_else(|| self.get_or_create_sender_tag(&recipient));
+        let (reply_surbs, reply_keys) = self
+            .generate_reply_surbs_with_keys(amount as usize, isolation)
+            .await?;
 
         let message = NymMessage::new_repliable(RepliableMessage::new_additional_surbs(
             sender_tag,
             reply_surbs,
         ));
 
-        self.try_split_and_send_non_reply_message(
+        self.try_split_and_send_isolated_non_reply_message(
             message,
             recipient,
             TransmissionLane::AdditionalReplySurbs,
+            None,
+            None,
+            isolation,
         )
         .await?;
 
         log::trace!("storing {} reply keys", reply_keys.len());
-        self.reply_key_storage.insert_multiple(reply_keys);
+        self.reply_key_storage
+            .insert_multiple(reply_keys, sender_tag);
 
         Ok(())
     }
 
+    /// Sends the message alongside the specified number of reply surbs. Unless an explicit `sender_tag`
+    /// is provided, the one shared by all anonymous messages sent to the recipient is used.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn try_send_message_with_reply_surbs(
         &mut self,
         recipient: Recipient,
         message: Vec<u8>,
         num_reply_surbs: u32,
         lane: TransmissionLane,
+        message_id: Option<MessageId>,
+        expires_at: Option<OffsetDateTime>,
+        sender_tag: Option<AnonymousSenderTag>,
     ) -> Result<(), SurbWrappedPreparationError> {
-        let sender_tag = self.get_or_create_sender_tag(&recipient);
+        // an explicitly chosen tag isolates the message from other streams sent to the recipient
+        let isolation = sender_tag;
+        if let Some(isolated_tag) = isolation {
+            self.tag_storage.insert_isolated(&recipient, isolated_tag);
+        }
+        let sender_tag = sender_tag.unwrap_or_else(|| self.get_or_create_sender_tag(&recipient));
         let (reply_surbs, reply_keys) = self
-            .generate_reply_surbs_with_keys(num_reply_surbs as usize)
+            .generate_reply_surbs_with_keys(num_reply_surbs as usize, isolation)
             .await?;
 
         let message =
             NymMessage::new_repliable(RepliableMessage::new_data(message, sender_tag, reply_surbs));
 
-        self.try_split_and_send_non_reply_message(message, recipient, lane)
-            .await?;
+        self.try_split_and_send_isolated_non_reply_message(
+            message, recipient, lane, message_id, expires_at, isolation,
+        )
+        .await?;
 
         log::trace!("storing {} reply keys", reply_keys.len());
-        self.reply_key_storage.insert_multiple(reply_keys);
+        self.reply_key_storage
+            .insert_multiple(reply_keys, sender_tag);
 
         Ok(())
     }
@@ -514,13 +665,20 @@ where
         &mut self,
         recipient: Recipient,
         chunk: Fragment,
+        expires_at: Option<OffsetDateTime>,
     ) -> Result<PreparedFragment, PreparationError> {
         let topology_permit = self.topology_access.get_read_permit().await;
         let topology = self.get_topology(&topology_permit)?;
 
         let prepared_fragment = self
             .message_preparer
-            .prepare_chunk_for_sending(chunk, topology, &self.config.ack_key, &recipient)
+            .prepare_expiring_chunk_for_sending(
+                chunk,
+                topology,
+                &self.config.ack_key,
+                &recipient,
+                self.gateway_visible_expiry(expires_at),
+            )
             .unwrap();
 
         Ok(prepared_fragment)
@@ -593,6 +751,30 @@ where
             .expect("action control task has died")
     }
 
+    // note: this has to be sent before the fragments are forwarded to the mix network so that
+    // we wouldn't miss any of their acks
+    pub(crate) fn track_delivery(&self, message_id: MessageId, fragments: Vec<FragmentIdentifier>) {
+        self.action_sender
+            .unbounded_send(Action::new_track_delivery(message_id, fragments))
+            .expect("action control task has died")
+    }
+
+    pub(crate) fn fail_delivery(&self, message_id: MessageId, reason: DeliveryFailure) {
+        self.action_sender
+            .unbounded_send(Action::new_fail_delivery(message_id, reason))
+            .expect("action control task has died")
+    }
+
+    pub(crate) fn fail_fragments_delivery(
+        &self,
+        fragments: Vec<FragmentIdentifier>,
+        reason: DeliveryFailure,
+    ) {
+        self.action_sender
+            .unbounded_send(Action::new_fail_fragments(fragments, reason))
+            .expect("action control task has died")
+    }
+
     // tells real message sender (with the poisson timer) to send this to the mix network
     pub(crate) async fn forward_messages(
         &self,
@@ -6,8 +6,11 @@
 // OUTPUT: MixMessage to mix traffic
 
 use self::{
-    acknowledgement_control::AcknowledgementController, real_traffic_stream::OutQueueControl,
+    acknowledgement_control::{AcknowledgementController, AdaptiveAckTimeouts},
+    real_traffic_stream::OutQueueControl,
 };
+use crate::client::hibernation::HibernationListener;
+use crate::client::latency_stats::LatencyStatsControl;
 use crate::client::real_messages_control::message_handler::MessageHandler;
 use crate::client::replies::reply_controller::{
     ReplyController, ReplyControllerReceiver, ReplyControllerSender,
@@ -32,7 +35,7 @@ use std::sync::Arc;
 
 use crate::client::replies::reply_controller;
 use crate::config;
-pub(crate) use acknowledgement_control::{AckActionSender, Action};
+pub(crate) use acknowledgement_control::{AckActionReceiver, AckActionSender, Action};
 
 pub(crate) mod acknowledgement_control;
 pub(crate) mod message_handler;
@@ -64,6 +67,15 @@ impl<'a> From<&'a Config> for acknowledgement_control::Config {
         acknowledgement_control::Config::new(
             cfg.acks.ack_wait_addition,
             cfg.acks.ack_wait_multiplier,
+            cfg.acks.maximum_retransmissions,
+            cfg.acks.maximum_retransmission_period,
+            cfg.acks.maximum_dead_letters,
+        )
+        // not every client validates its configuration upfront (e.g. the wasm one),
+        // so never run the estimator with nonsensical parameters
+        .with_adaptive_timeouts(
+            (cfg.acks.adaptive_ack_timeouts && cfg.acks.validate())
+                .then(|| AdaptiveAckTimeouts::from(cfg.acks)),
         )
         .with_custom_packet_size(cfg.traffic.primary_packet_size)
     }
@@ -97,6 +109,9 @@ impl<'a> From<&'a Config> for message_handler::Config {
         )
         .with_custom_primary_packet_size(cfg.traffic.primary_packet_size)
         .with_custom_secondary_packet_size(cfg.traffic.secondary_packet_size)
+        .with_padding_policy(cfg.traffic.padding_policy)
+        .with_message_ordering(cfg.traffic.attach_message_ordering)
+        .with_message_expiry_disclosure(cfg.traffic.disclose_message_expiry)
     }
 }
 
@@ -142,13 +157,16 @@ impl RealMessagesController<OsRng> {
         reply_controller_receiver: ReplyControllerReceiver,
         lane_queue_lengths: LaneQueueLengths,
         client_connection_rx: ConnectionCommandReceiver,
+        ack_action_tx: AckActionSender,
+        ack_action_rx: AckActionReceiver,
+        hibernation: HibernationListener,
+        latency_stats: LatencyStatsControl,
     ) -> Self {
         let rng = OsRng;
 
         // create channels for inter-task communication
         let (real_message_sender, real_message_receiver) = tokio::sync::mpsc::channel(1);
         let (sent_notifier_tx, sent_notifier_rx) = mpsc::unbounded();
-        let (ack_action_tx, ack_action_rx) = mpsc::unbounded();
         let ack_controller_connectors = AcknowledgementControllerConnectors::new(
             input_receiver,
             sent_notifier_rx,
@@ -180,6 +198,7 @@ impl RealMessagesController<OsRng> {
             ack_controller_connectors,
             message_handler.clone(),
             reply_controller_sender,
+            latency_stats,
         );
 
         let reply_control = ReplyController::new(
@@ -198,6 +217,7 @@ impl RealMessagesController<OsRng> {
             topology_access,
             lane_queue_lengths,
             client_connection_rx,
+            hibernation,
         );
 
         RealMessagesController {
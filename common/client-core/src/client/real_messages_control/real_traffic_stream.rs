@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use self::sending_delay_controller::SendingDelayController;
+use crate::client::hibernation::HibernationListener;
 use crate::client::mix_traffic::BatchMixMessageSender;
 use crate::client::real_messages_control::acknowledgement_control::SentPacketNotificationSender;
 use crate::client::topology_control::TopologyAccessor;
@@ -116,6 +117,10 @@ where
 
     /// Report queue lengths so that upstream can backoff sending data, and keep connections open.
     lane_queue_lengths: LaneQueueLengths,
+
+    /// Listener for the hibernation state of the client. While hibernating, only real messages
+    /// are sent out and no cover packets are being generated in their absence.
+    hibernation: HibernationListener,
 }
 
 #[derive(Debug)]
@@ -172,14 +177,21 @@ where
         mix_tx: BatchMixMessageSender,
         real_receiver: BatchRealMessageReceiver,
         topology_access: TopologyAccessor,
-        lane_queue_lengths: LaneQueueLengths,
+        mut lane_queue_lengths: LaneQueueLengths,
         client_connection_rx: ConnectionCommandReceiver,
+        hibernation: HibernationListener,
     ) -> Self {
+        let sending_delay_controller = SendingDelayController::default();
+        lane_queue_lengths.set_average_sending_delay(
+            config.traffic.message_sending_average_delay
+                * sending_delay_controller.current_multiplier(),
+        );
+
         OutQueueControl {
             config,
             sent_notifier,
             next_delay: None,
-            sending_delay_controller: Default::default(),
+            sending_delay_controller,
             mix_tx,
             real_receiver,
             rng,
@@ -187,6 +199,7 @@ where
             transmission_buffer: TransmissionBuffer::new(),
             client_connection_rx,
             lane_queue_lengths,
+            hibernation,
         }
     }
 
@@ -200,7 +213,7 @@ where
 
     fn loop_cover_message_size(&mut self) -> PacketSize {
         let Some(secondary_packet_size) = self.config.traffic.secondary_packet_size else {
-            return self.config.traffic.primary_packet_size
+            return self.config.traffic.primary_packet_size;
         };
 
         let use_primary = self
@@ -259,6 +272,15 @@ where
             }
         };
 
+        #[cfg(feature = "packet-tracing")]
+        log::info!(
+            target: nym_sphinx::params::packet_tracing::PACKET_TRACING_LOG_TARGET,
+            "[{}] client sending packet to {} (real: {})",
+            next_message.trace_id(),
+            next_message.next_hop(),
+            fragment_id.is_some()
+        );
+
         if let Err(err) = self.mix_tx.send(vec![next_message]).await {
             log::error!("Failed to send: {err}");
         }
@@ -296,6 +318,7 @@ where
     }
 
     fn adjust_current_average_message_sending_delay(&mut self) {
+        let previous_multiplier = self.sending_delay_controller.current_multiplier();
         let used_slots = self.mix_tx.max_capacity() - self.mix_tx.capacity();
         log::trace!(
             "used_slots: {used_slots}, current_multiplier: {}",
@@ -330,6 +353,12 @@ where
 
         // Keep track of multiplier changes, and log if necessary.
         self.sending_delay_controller.record_delay_multiplier();
+
+        // Publish the new delay so that upstream could estimate how long its queues will take
+        if self.sending_delay_controller.current_multiplier() != previous_multiplier {
+            let delay = self.current_average_message_sending_delay();
+            self.lane_queue_lengths.set_average_sending_delay(delay);
+        }
     }
 
     fn pop_next_message(&mut self) -> Option<RealMessage> {
@@ -405,6 +434,13 @@ where
                 Poll::Pending => {
                     if let Some(real_next) = self.pop_next_message() {
                         Poll::Ready(Some(StreamMessage::Real(Box::new(real_next))))
+                    } else if self.hibernation.is_hibernating() {
+                        // don't send any cover traffic while hibernating. We're going to get woken up
+                        // by the real receiver once there's something to send (or by the run loop
+                        // on the hibernation state change) and we'll then sample a fresh delay
+                        // rather than trying to catch up with all the missed cover packets
+                        self.next_delay = None;
+                        Poll::Pending
                     } else {
                         // otherwise construct a dummy one
                         Poll::Ready(Some(StreamMessage::Cover))
@@ -511,6 +547,10 @@ where
     pub(super) async fn run_with_shutdown(&mut self, mut shutdown: nym_task::TaskClient) {
         debug!("Started OutQueueControl with graceful shutdown support");
 
+        // we only need it for getting re-polled on the state change,
+        // the stream itself checks the current state on its own
+        let mut hibernation = self.hibernation.clone();
+
         #[cfg(not(target_arch = "wasm32"))]
         {
             let mut status_timer = tokio::time::interval(Duration::from_secs(5));
@@ -524,6 +564,9 @@ where
                     _ = status_timer.tick() => {
                         self.log_status(&mut shutdown);
                     }
+                    hibernating = hibernation.changed() => {
+                        log::debug!("OutQueueControl: hibernation state changed (hibernating: {hibernating})");
+                    }
                     next_message = self.next() => if let Some(next_message) = next_message {
                         self.on_message(next_message).await;
                     } else {
@@ -543,6 +586,9 @@ where
                     _ = shutdown.recv() => {
                         log::trace!("OutQueueControl: Received shutdown");
                     }
+                    hibernating = hibernation.changed() => {
+                        log::debug!("OutQueueControl: hibernation state changed (hibernating: {hibernating})");
+                    }
                     next_message = self.next() => if let Some(next_message) = next_message {
                         self.on_message(next_message).await;
                     } else {
@@ -12,15 +12,25 @@ use nym_crypto::asymmetric::encryption;
 use nym_crypto::Digest;
 use nym_gateway_client::MixnetMessageReceiver;
 use nym_sphinx::anonymous_replies::requests::{
-    RepliableMessage, RepliableMessageContent, ReplyMessage, ReplyMessageContent,
+    AnonymousSenderTag, RepliableMessage, RepliableMessageContent, ReplyMessage,
+    ReplyMessageContent,
 };
 use nym_sphinx::anonymous_replies::{encryption_key::EncryptionKeyDigest, SurbEncryptionKey};
-use nym_sphinx::message::{NymMessage, PlainMessage};
+use nym_sphinx::message::{MessageOrdering, NymMessage, PlainMessage};
 use nym_sphinx::params::ReplySurbKeyDigestAlgorithm;
-use nym_sphinx::receiver::{MessageReceiver, MessageRecoveryError, ReconstructedMessage};
-use std::collections::HashSet;
+use nym_sphinx::receiver::{
+    sort_by_ordering, MessageReceiver, MessageRecoveryError, ReconstructedMessage,
+};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+// Number of missing messages of a single sender above which we're going to warn about the gap.
+// Note that some reordering is expected as each packet is delayed independently.
+const MAXIMUM_EXPECTED_SEQUENCE_GAP: u32 = 100;
+
+// Reconstructed message alongside the ordering information attached by its sender (if any).
+type OrderedMessage = (NymMessage, Option<MessageOrdering>);
+
 // Buffer Requests to say "hey, send any reconstructed messages to this channel"
 // or to say "hey, I'm going offline, don't send anything more to me. Just buffer them instead"
 pub type ReceivedBufferRequestSender = mpsc::UnboundedSender<ReceivedBufferMessage>;
@@ -43,10 +53,32 @@ struct ReceivedMessagesBufferInner<R: MessageReceiver> {
     // but perhaps it should be changed to include timestamps of when the message was reconstructed
     // and every now and then remove ids older than X
     recently_reconstructed: HashSet<i32>,
+
+    // ordering of the most recent message received from given sender
+    latest_orderings: HashMap<AnonymousSenderTag, MessageOrdering>,
 }
 
 impl<R: MessageReceiver> ReceivedMessagesBufferInner<R> {
-    fn recover_from_fragment(&mut self, fragment_data: &[u8]) -> Option<NymMessage> {
+    fn check_for_sequence_gaps(&mut self, messages: &[ReconstructedMessage]) {
+        for message in messages {
+            let (Some(sender_tag), Some(ordering)) = (message.sender_tag, message.ordering) else {
+                continue;
+            };
+
+            if let Some(latest) = self.latest_orderings.get(&sender_tag) {
+                if !ordering.is_after(latest) {
+                    continue;
+                }
+                let gap = ordering.sequence_gap(latest);
+                if gap > MAXIMUM_EXPECTED_SEQUENCE_GAP {
+                    warn!("there are {gap} messages missing from {sender_tag} - they might have been lost or are still being delayed");
+                }
+            }
+            self.latest_orderings.insert(sender_tag, ordering);
+        }
+    }
+
+    fn recover_from_fragment(&mut self, fragment_data: &[u8]) -> Option<OrderedMessage> {
         if nym_sphinx::cover::is_cover(fragment_data) {
             trace!("The message was a loop cover message! Skipping it");
             return None;
@@ -84,14 +116,14 @@ impl<R: MessageReceiver> ReceivedMessagesBufferInner<R> {
                 ),
             },
             Ok(reconstruction_result) => match reconstruction_result {
-                Some((reconstructed_message, used_sets)) => {
+                Some((reconstructed_message, ordering, used_sets)) => {
                     for set_id in used_sets {
                         if !self.recently_reconstructed.insert(set_id) {
                             // or perhaps we should even panic at this point?
                             error!("Reconstructed another message containing already used set id!")
                         }
                     }
-                    Some(reconstructed_message)
+                    Some((reconstructed_message, ordering))
                 }
                 None => None,
             },
@@ -102,7 +134,7 @@ impl<R: MessageReceiver> ReceivedMessagesBufferInner<R> {
         &mut self,
         reply_ciphertext: &mut [u8],
         reply_key: SurbEncryptionKey,
-    ) -> Result<Option<NymMessage>, MessageRecoveryError> {
+    ) -> Result<Option<OrderedMessage>, MessageRecoveryError> {
         // note: this performs decryption IN PLACE without extra allocation
         self.message_receiver
             .recover_plaintext_from_reply(reply_ciphertext, reply_key)?;
@@ -111,7 +143,10 @@ impl<R: MessageReceiver> ReceivedMessagesBufferInner<R> {
         Ok(self.recover_from_fragment(fragment_data))
     }
 
-    fn process_received_regular_packet(&mut self, mut raw_fragment: Vec<u8>) -> Option<NymMessage> {
+    fn process_received_regular_packet(
+        &mut self,
+        mut raw_fragment: Vec<u8>,
+    ) -> Option<OrderedMessage> {
         let fragment_data = match self.message_receiver.recover_plaintext_from_regular_packet(
             self.local_encryption_keypair.private_key(),
             &mut raw_fragment,
@@ -149,6 +184,7 @@ impl<R: MessageReceiver> ReceivedMessagesBuffer<R> {
                 message_receiver: R::new(),
                 message_sender: None,
                 recently_reconstructed: HashSet::new(),
+                latest_orderings: HashMap::new(),
             })),
             reply_key_storage,
             reply_controller_sender,
@@ -195,17 +231,19 @@ impl<R: MessageReceiver> ReceivedMessagesBuffer<R> {
 
     fn handle_reconstructed_plain_messages(
         &mut self,
-        msgs: Vec<PlainMessage>,
+        msgs: Vec<(PlainMessage, Option<MessageOrdering>)>,
     ) -> Vec<ReconstructedMessage> {
-        msgs.into_iter().map(Into::into).collect()
+        msgs.into_iter()
+            .map(|(msg, ordering)| ReconstructedMessage::from(msg).with_ordering(ordering))
+            .collect()
     }
 
     fn handle_reconstructed_repliable_messages(
         &mut self,
-        msgs: Vec<RepliableMessage>,
+        msgs: Vec<(RepliableMessage, Option<MessageOrdering>)>,
     ) -> Vec<ReconstructedMessage> {
         let mut reconstructed = Vec::new();
-        for msg in msgs {
+        for (msg, ordering) in msgs {
             let (reply_surbs, from_surb_request) = match msg.content {
                 RepliableMessageContent::Data {
                     message,
@@ -217,7 +255,9 @@ impl<R: MessageReceiver> ReceivedMessagesBuffer<R> {
                         msg.sender_tag
                     );
 
-                    reconstructed.push(ReconstructedMessage::new(message, msg.sender_tag));
+                    reconstructed.push(
+                        ReconstructedMessage::new(message, msg.sender_tag).with_ordering(ordering),
+                    );
 
                     (reply_surbs, false)
                 }
@@ -248,23 +288,33 @@ impl<R: MessageReceiver> ReceivedMessagesBuffer<R> {
 
     fn handle_reconstructed_reply_messages(
         &mut self,
-        msgs: Vec<ReplyMessage>,
+        msgs: Vec<(
+            ReplyMessage,
+            Option<MessageOrdering>,
+            Option<AnonymousSenderTag>,
+        )>,
     ) -> Vec<ReconstructedMessage> {
         let mut reconstructed = Vec::new();
-        for msg in msgs {
+        for (msg, ordering, sender_tag) in msgs {
             match msg.content {
-                ReplyMessageContent::Data { message } => reconstructed.push(message.into()),
+                ReplyMessageContent::Data { message } => {
+                    reconstructed.push(ReconstructedMessage::from(message).with_ordering(ordering))
+                }
                 ReplyMessageContent::SurbRequest { recipient, amount } => {
                     debug!("received request for {amount} additional reply SURBs from {recipient}");
                     self.reply_controller_sender
-                        .send_additional_surbs_request(*recipient, amount);
+                        .send_additional_surbs_request(*recipient, amount, sender_tag);
                 }
             }
         }
         reconstructed
     }
 
-    async fn handle_reconstructed_messages(&mut self, msgs: Vec<NymMessage>) {
+    // alongside each message, the sender tag of the reply surb that completed it is provided (if known)
+    async fn handle_reconstructed_messages(
+        &mut self,
+        msgs: Vec<(OrderedMessage, Option<AnonymousSenderTag>)>,
+    ) {
         if msgs.is_empty() {
             return;
         }
@@ -273,11 +323,11 @@ impl<R: MessageReceiver> ReceivedMessagesBuffer<R> {
         let mut repliable_messages = Vec::new();
         let mut reply_messages = Vec::new();
 
-        for msg in msgs {
+        for ((msg, ordering), reply_tag) in msgs {
             match msg {
-                NymMessage::Plain(plain) => plain_messages.push(plain),
-                NymMessage::Repliable(repliable) => repliable_messages.push(repliable),
-                NymMessage::Reply(reply) => reply_messages.push(reply),
+                NymMessage::Plain(plain) => plain_messages.push((plain, ordering)),
+                NymMessage::Repliable(repliable) => repliable_messages.push((repliable, ordering)),
+                NymMessage::Reply(reply) => reply_messages.push((reply, ordering, reply_tag)),
             }
         }
 
@@ -286,8 +336,10 @@ impl<R: MessageReceiver> ReceivedMessagesBuffer<R> {
             .append(&mut self.handle_reconstructed_repliable_messages(repliable_messages));
         reconstructed_messages
             .append(&mut self.handle_reconstructed_reply_messages(reply_messages));
+        sort_by_ordering(&mut reconstructed_messages);
 
         let mut inner_guard = self.inner.lock().await;
+        inner_guard.check_for_sequence_gaps(&reconstructed_messages);
         debug!(
             "Adding {:?} new messages to the buffer!",
             reconstructed_messages.len()
@@ -307,10 +359,12 @@ impl<R: MessageReceiver> ReceivedMessagesBuffer<R> {
     }
 
     // this function doesn't really belong here...
+    // alongside the key, it returns the sender tag the corresponding reply surb has been created for
+    // (if known)
     fn get_reply_key<'a>(
         &self,
         raw_message: &'a mut [u8],
-    ) -> Option<(SurbEncryptionKey, &'a mut [u8])> {
+    ) -> Option<(SurbEncryptionKey, Option<AnonymousSenderTag>, &'a mut [u8])> {
         let reply_surb_digest_size = ReplySurbKeyDigestAlgorithm::output_size();
         if raw_message.len() < reply_surb_digest_size {
             return None;
@@ -320,9 +374,10 @@ impl<R: MessageReceiver> ReceivedMessagesBuffer<R> {
             EncryptionKeyDigest::clone_from_slice(&raw_message[..reply_surb_digest_size]);
         self.reply_key_storage
             .try_pop(possible_key_digest)
-            .map(|reply_encryption_key| {
+            .map(|reply_key| {
                 (
-                    *reply_encryption_key,
+                    *reply_key,
+                    reply_key.sender_tag,
                     &mut raw_message[reply_surb_digest_size..],
                 )
             })
@@ -345,16 +400,19 @@ impl<R: MessageReceiver> ReceivedMessagesBuffer<R> {
         for mut msg in msgs {
             // check first `HasherOutputSize` bytes if they correspond to known encryption key
             // if yes - this is a reply message
-            let completed_message =
-                if let Some((reply_key, reply_message)) = self.get_reply_key(&mut msg) {
-                    inner_guard.process_received_reply(reply_message, reply_key)?
+            let (completed_message, reply_tag) =
+                if let Some((reply_key, reply_tag, reply_message)) = self.get_reply_key(&mut msg) {
+                    (
+                        inner_guard.process_received_reply(reply_message, reply_key)?,
+                        reply_tag,
+                    )
                 } else {
-                    inner_guard.process_received_regular_packet(msg)
+                    (inner_guard.process_received_regular_packet(msg), None)
                 };
 
             if let Some(completed) = completed_message {
-                info!("received {completed}");
-                completed_messages.push(completed)
+                info!("received {}", completed.0);
+                completed_messages.push((completed, reply_tag))
             }
         }
 
@@ -1,6 +1,7 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::client::delivery::{DeliveryFailure, MessageId};
 use crate::client::real_messages_control::acknowledgement_control::PendingAcknowledgement;
 use crate::client::real_messages_control::message_handler::{MessageHandler, PreparationError};
 use crate::client::replies::reply_storage::CombinedReplyStorage;
@@ -185,6 +186,7 @@ where
         recipient_tag: AnonymousSenderTag,
         data: Vec<u8>,
         lane: TransmissionLane,
+        message_id: Option<MessageId>,
     ) {
         if !self
             .full_reply_storage
@@ -192,11 +194,23 @@ where
             .contains_surbs_for(&recipient_tag)
         {
             warn!("received reply request for {:?} but we don't have any surbs stored for that recipient!", recipient_tag);
+            if let Some(message_id) = message_id {
+                self.message_handler
+                    .fail_delivery(message_id, DeliveryFailure::NoReplySurbs)
+            }
             return;
         }
 
         trace!("handling reply to {:?}", recipient_tag);
-        let mut fragments = self.message_handler.split_reply_message(data);
+        let mut fragments = self
+            .message_handler
+            .split_reply_message(recipient_tag, data)
+            .await;
+        if let Some(message_id) = message_id {
+            let fragment_ids = fragments.iter().map(|f| f.fragment_identifier()).collect();
+            self.message_handler
+                .track_delivery(message_id, fragment_ids)
+        }
         let total_size = fragments.len();
         trace!("This reply requires {:?} SURBs", total_size);
 
@@ -479,14 +493,22 @@ where
         }
     }
 
-    async fn handle_surb_request(&mut self, recipient: Recipient, mut amount: u32) {
+    async fn handle_surb_request(
+        &mut self,
+        recipient: Recipient,
+        mut amount: u32,
+        sender_tag: Option<AnonymousSenderTag>,
+    ) {
         // 1. check whether we sent any surbs in the past to this recipient, otherwise
         // they have no business in asking for more
-        if !self
-            .full_reply_storage
-            .tags_storage_ref()
-            .exists(&recipient)
-        {
+        // (if the request came with the surb of a known tag, it must have been sent to that very recipient,
+        // as otherwise anyone holding our surb could redirect the new ones to an arbitrary address)
+        let tags_storage = self.full_reply_storage.tags_storage_ref();
+        let known_recipient = match &sender_tag {
+            Some(sender_tag) => tags_storage.is_bound(sender_tag, &recipient),
+            None => tags_storage.exists(&recipient),
+        };
+        if !known_recipient {
             warn!("{recipient} asked us for reply SURBs even though we never sent them any anonymous messages before!");
             return;
         }
@@ -512,7 +534,7 @@ where
             let to_send = min(remaining, 100);
             if let Err(err) = self
                 .message_handler
-                .try_send_additional_reply_surbs(recipient, to_send)
+                .try_send_additional_reply_surbs(recipient, to_send, sender_tag)
                 .await
             {
                 warn!("failed to send additional surbs to {recipient} - {err}");
@@ -653,7 +675,11 @@ where
                 recipient,
                 message,
                 lane,
-            } => self.handle_send_reply(recipient, message, lane).await,
+                message_id,
+            } => {
+                self.handle_send_reply(recipient, message, lane, message_id)
+                    .await
+            }
             ReplyControllerMessage::AdditionalSurbs {
                 sender_tag,
                 reply_surbs,
@@ -666,8 +692,13 @@ where
                 connection_id,
                 response_channel,
             } => self.handle_lane_queue_length(connection_id, response_channel),
-            ReplyControllerMessage::AdditionalSurbsRequest { recipient, amount } => {
-                self.handle_surb_request(*recipient, amount).await
+            ReplyControllerMessage::AdditionalSurbsRequest {
+                recipient,
+                amount,
+                sender_tag,
+            } => {
+                self.handle_surb_request(*recipient, amount, sender_tag)
+                    .await
             }
         }
     }
@@ -765,7 +796,61 @@ where
                 .reset_pending_reception(&pending_reply_target)
         }
         for to_remove in to_remove {
-            self.pending_replies.remove(&to_remove);
+            if let Some(dropped) = self.pending_replies.remove(&to_remove) {
+                self.message_handler.fail_fragments_delivery(
+                    dropped
+                        .into_items()
+                        .map(|f| f.fragment_identifier())
+                        .collect(),
+                    DeliveryFailure::NoReplySurbs,
+                )
+            }
+        }
+    }
+
+    fn has_pending_data(&self, target: &AnonymousSenderTag) -> bool {
+        let pending_replies = self
+            .pending_replies
+            .get(target)
+            .map(|pending_queue| !pending_queue.is_empty())
+            .unwrap_or_default();
+
+        let pending_retransmissions = self
+            .pending_retransmissions
+            .get(target)
+            .map(|pending_queue| !pending_queue.is_empty())
+            .unwrap_or_default();
+
+        pending_replies || pending_retransmissions
+    }
+
+    /// Makes sure we don't keep the state of more remote parties than configured by evicting
+    /// the ones we haven't been exchanging any replies with for the longest time.
+    /// The parties we still have pending data for are never evicted.
+    fn evict_idle_recipients(&mut self) {
+        // get rid of the leftover empty queues so that they wouldn't accumulate over time
+        self.pending_replies
+            .retain(|_, pending_queue| !pending_queue.is_empty());
+        self.pending_retransmissions
+            .retain(|_, pending_queue| !pending_queue.is_empty());
+
+        let maximum_tracked = self.config.reply_surbs.maximum_tracked_recipients;
+        let surbs_storage = self.full_reply_storage.surbs_storage_ref();
+        let tracked = surbs_storage.tracked_senders();
+        if tracked <= maximum_tracked {
+            return;
+        }
+
+        let to_evict = surbs_storage.least_recently_active(tracked - maximum_tracked, |target| {
+            !self.has_pending_data(target)
+        });
+        if to_evict.len() < tracked - maximum_tracked {
+            debug!("we're tracking the state of {tracked} remote parties, but only {} of them are idle and could be evicted", to_evict.len());
+        }
+
+        for target in to_evict {
+            debug!("evicting the reply state of idle {target}");
+            surbs_storage.remove(&target);
         }
     }
 
@@ -862,7 +947,8 @@ where
                     }
                 },
                 _ = stale_inspection.next() => {
-                    self.inspect_stale_entries().await
+                    self.inspect_stale_entries().await;
+                    self.evict_idle_recipients()
                 },
                 _ = invalidation_inspection.next() => {
                     self.invalidate_old_data().await
@@ -1,6 +1,7 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::client::delivery::MessageId;
 use crate::client::real_messages_control::acknowledgement_control::PendingAcknowledgement;
 use futures::channel::{mpsc, oneshot};
 use log::error;
@@ -45,12 +46,14 @@ impl ReplyControllerSender {
         recipient: AnonymousSenderTag,
         message: Vec<u8>,
         lane: TransmissionLane,
+        message_id: Option<MessageId>,
     ) {
         self.0
             .unbounded_send(ReplyControllerMessage::SendReply {
                 recipient,
                 message,
                 lane,
+                message_id,
             })
             .expect("ReplyControllerReceiver has died!")
     }
@@ -70,11 +73,17 @@ impl ReplyControllerSender {
             .expect("ReplyControllerReceiver has died!")
     }
 
-    pub(crate) fn send_additional_surbs_request(&self, recipient: Recipient, amount: u32) {
+    pub(crate) fn send_additional_surbs_request(
+        &self,
+        recipient: Recipient,
+        amount: u32,
+        sender_tag: Option<AnonymousSenderTag>,
+    ) {
         self.0
             .unbounded_send(ReplyControllerMessage::AdditionalSurbsRequest {
                 recipient: Box::new(recipient),
                 amount,
+                sender_tag,
             })
             .expect("ReplyControllerReceiver has died!")
     }
@@ -131,6 +140,7 @@ pub(crate) enum ReplyControllerMessage {
         recipient: AnonymousSenderTag,
         message: Vec<u8>,
         lane: TransmissionLane,
+        message_id: Option<MessageId>,
     },
 
     AdditionalSurbs {
@@ -150,5 +160,7 @@ pub(crate) enum ReplyControllerMessage {
     AdditionalSurbsRequest {
         recipient: Box<Recipient>,
         amount: u32,
+        // tag the surb used for sending the request has been created for, if known
+        sender_tag: Option<AnonymousSenderTag>,
     },
 }
@@ -1,6 +1,7 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use nym_store_cipher::StoreCipherError;
 use std::io;
 use std::path::PathBuf;
 use thiserror::Error;
@@ -48,6 +49,16 @@ pub enum StorageError {
         source: sqlx::error::Error,
     },
 
+    #[error("failed to encrypt or decrypt the stored data: {source}")]
+    CipherError {
+        #[source]
+        #[from]
+        source: StoreCipherError,
+    },
+
+    #[error("the stored data is encrypted, but no passphrase has been provided")]
+    MissingPassphrase,
+
     #[error("The loaded data is inconsistent - it seems that on the last shutdown the client hasn't finished the data flush. You may have to remove the entire storage manually")]
     IncompleteDataFlush,
 
@@ -62,3 +73,17 @@ pub enum StorageError {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
 }
+
+impl StorageError {
+    /// Indicates whether the stored data couldn't have been loaded because of the missing
+    /// or invalid passphrase rather than because of it being corrupted.
+    pub fn is_passphrase_error(&self) -> bool {
+        matches!(
+            self,
+            StorageError::MissingPassphrase
+                | StorageError::CipherError {
+                    source: StoreCipherError::InvalidPassphrase
+                }
+        )
+    }
+}
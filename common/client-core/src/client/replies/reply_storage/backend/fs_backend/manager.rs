@@ -118,10 +118,30 @@ impl StorageManager {
         Ok(())
     }
 
+    pub(crate) async fn get_store_cipher(&self) -> Result<Option<Vec<u8>>, sqlx::Error> {
+        sqlx::query!("SELECT exported FROM store_cipher;")
+            .fetch_optional(&self.connection_pool)
+            .await
+            .map(|r| r.map(|r| r.exported))
+    }
+
+    pub(crate) async fn insert_store_cipher(&self, exported: Vec<u8>) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO store_cipher(id, exported) VALUES (0, ?)",
+            exported
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
     pub(crate) async fn delete_all_tags(&self) -> Result<(), sqlx::Error> {
         sqlx::query!("DELETE FROM sender_tag;")
             .execute(&self.connection_pool)
             .await?;
+        sqlx::query!("DELETE FROM isolated_sender_tag;")
+            .execute(&self.connection_pool)
+            .await?;
         Ok(())
     }
 
@@ -144,6 +164,28 @@ impl StorageManager {
         Ok(())
     }
 
+    pub(crate) async fn get_isolated_tags(&self) -> Result<Vec<StoredSenderTag>, sqlx::Error> {
+        sqlx::query_as!(StoredSenderTag, "SELECT * FROM isolated_sender_tag;",)
+            .fetch_all(&self.connection_pool)
+            .await
+    }
+
+    pub(crate) async fn insert_isolated_tag(
+        &self,
+        stored_tag: StoredSenderTag,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+                INSERT INTO isolated_sender_tag(recipient, tag) VALUES (?, ?);
+            "#,
+            stored_tag.recipient,
+            stored_tag.tag
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
     pub(crate) async fn delete_all_reply_keys(&self) -> Result<(), sqlx::Error> {
         sqlx::query!("DELETE FROM reply_key;")
             .execute(&self.connection_pool)
@@ -163,11 +205,12 @@ impl StorageManager {
     ) -> Result<(), sqlx::Error> {
         sqlx::query!(
             r#"
-                INSERT INTO reply_key(key_digest, reply_key, sent_at_timestamp) VALUES (?, ?, ?);
+                INSERT INTO reply_key(key_digest, reply_key, sent_at_timestamp, sender_tag) VALUES (?, ?, ?, ?);
             "#,
             stored_reply_key.key_digest,
             stored_reply_key.reply_key,
-            stored_reply_key.sent_at_timestamp
+            stored_reply_key.sent_at_timestamp,
+            stored_reply_key.sender_tag
         )
         .execute(&self.connection_pool)
         .await?;
@@ -12,7 +12,9 @@ use crate::client::replies::reply_storage::{
 };
 use async_trait::async_trait;
 use log::{error, info, warn};
+use nym_sphinx::addressing::clients::RecipientBytes;
 use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
+use nym_store_cipher::{associated_data, StoreCipher};
 use std::fs;
 use std::path::{Path, PathBuf};
 use time::OffsetDateTime;
@@ -61,17 +63,38 @@ impl StorageManagerState {
     }
 }
 
-#[derive(Debug)]
 pub struct Backend {
     temporary_old_path: Option<PathBuf>,
     database_path: PathBuf,
     manager: StorageManagerState,
+
+    /// Cipher used for the reply keys, reply surbs and recipients of the sender tags,
+    /// if they're meant to be encrypted.
+    cipher: Option<StoreCipher>,
+
+    /// Indicates whether the data in the current database file has been encrypted.
+    /// It might not be the case if the encryption has only just been enabled.
+    stored_encrypted: bool,
+}
+
+impl std::fmt::Debug for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Backend")
+            .field("temporary_old_path", &self.temporary_old_path)
+            .field("database_path", &self.database_path)
+            .field("manager", &self.manager)
+            .field("encrypted", &self.cipher.is_some())
+            .finish()
+    }
 }
 
 impl Backend {
     const OLD_EXTENSION: &'static str = "old";
 
-    pub async fn init<P: AsRef<Path>>(database_path: P) -> Result<Self, StorageError> {
+    pub async fn init<P: AsRef<Path>>(
+        database_path: P,
+        passphrase: Option<&str>,
+    ) -> Result<Self, StorageError> {
         let owned_path: PathBuf = database_path.as_ref().into();
         if owned_path.file_name().is_none() {
             return Err(StorageError::DatabasePathWithoutFilename {
@@ -79,13 +102,20 @@ impl Backend {
             });
         }
 
+        let cipher = passphrase.map(StoreCipher::new).transpose()?;
+
         let manager = StorageManager::init(database_path, true).await?;
         manager.create_status_table().await?;
+        if let Some(cipher) = &cipher {
+            manager.insert_store_cipher(cipher.export()?).await?;
+        }
 
         let backend = Backend {
             temporary_old_path: None,
             database_path: owned_path,
             manager: StorageManagerState::Storage(manager),
+            stored_encrypted: cipher.is_some(),
+            cipher,
         };
 
         Ok(backend)
@@ -102,10 +132,15 @@ impl Backend {
                 minimum_reply_surb_storage_threshold,
                 maximum_reply_surb_storage_threshold,
             }),
+            cipher: None,
+            stored_encrypted: false,
         }
     }
 
-    pub async fn try_load<P: AsRef<Path>>(database_path: P) -> Result<Self, StorageError> {
+    pub async fn try_load<P: AsRef<Path>>(
+        database_path: P,
+        passphrase: Option<&str>,
+    ) -> Result<Self, StorageError> {
         let owned_path: PathBuf = database_path.as_ref().into();
         if owned_path.file_name().is_none() {
             return Err(StorageError::DatabasePathWithoutFilename {
@@ -115,6 +150,18 @@ impl Backend {
 
         let manager = StorageManager::init(database_path, false).await?;
 
+        let (cipher, stored_encrypted) = match (manager.get_store_cipher().await?, passphrase) {
+            (Some(exported), Some(passphrase)) => {
+                (Some(StoreCipher::import(passphrase, &exported)?), true)
+            }
+            (Some(_), None) => return Err(StorageError::MissingPassphrase),
+            (None, Some(passphrase)) => {
+                info!("the stored reply data is not encrypted yet - it will get encrypted on the next flush");
+                (Some(StoreCipher::new(passphrase)?), false)
+            }
+            (None, None) => (None, false),
+        };
+
         // the database flush wasn't fully finished and thus the data is in inconsistent state
         // (we don't really know what's properly saved or what's not)
         if manager.get_flush_status().await? {
@@ -177,9 +224,27 @@ impl Backend {
             temporary_old_path: None,
             database_path: owned_path,
             manager: StorageManagerState::Storage(manager),
+            cipher,
+            stored_encrypted,
         })
     }
 
+    /// Encrypts the value, if the encryption is enabled, binding it to the provided location
+    /// (see [`associated_data`]) so that it couldn't be moved elsewhere in the database.
+    fn encrypt_for_storage(&self, data: Vec<u8>, aad: &[u8]) -> Result<Vec<u8>, StorageError> {
+        match &self.cipher {
+            Some(cipher) => Ok(cipher.encrypt(&data, aad)?),
+            None => Ok(data),
+        }
+    }
+
+    fn decrypt_stored(&self, data: Vec<u8>, aad: &[u8]) -> Result<Vec<u8>, StorageError> {
+        match &self.cipher {
+            Some(cipher) if self.stored_encrypted => Ok(cipher.decrypt(&data, aad)?),
+            _ => Ok(data),
+        }
+    }
+
     async fn close_pool(&mut self) {
         self.manager.get_mut().connection_pool.close().await;
     }
@@ -203,6 +268,13 @@ impl Backend {
         self.manager =
             StorageManagerState::Storage(StorageManager::init(&self.database_path, true).await?);
         self.manager.get_mut().create_status_table().await?;
+        if let Some(cipher) = &self.cipher {
+            self.manager
+                .get()
+                .insert_store_cipher(cipher.export()?)
+                .await?;
+        }
+        self.stored_encrypted = self.cipher.is_some();
 
         self.temporary_old_path = Some(temp_old);
         Ok(())
@@ -238,26 +310,47 @@ impl Backend {
         Ok(self.manager.get().set_client_in_use_status(false).await?)
     }
 
-    async fn get_stored_tags(&self) -> Result<UsedSenderTags, StorageError> {
-        let stored = self.manager.get().get_tags().await?;
-
+    fn decrypt_stored_tags(
+        &self,
+        table: &str,
+        stored: Vec<StoredSenderTag>,
+    ) -> Result<Vec<(RecipientBytes, AnonymousSenderTag)>, StorageError> {
         // stop at the first instance of corruption. if even a single entry is malformed,
         // something weird has happened and we can't trust the rest of the data
-        let raw = stored
+        stored
             .into_iter()
-            .map(TryInto::try_into)
-            .collect::<Result<_, _>>()?;
+            .map(|mut stored| -> Result<_, StorageError> {
+                let aad = associated_data(table, "recipient", &[&stored.tag]);
+                stored.recipient = self.decrypt_stored(stored.recipient, &aad)?;
+                stored.try_into()
+            })
+            .collect()
+    }
 
-        Ok(UsedSenderTags::from_raw(raw))
+    async fn get_stored_tags(&self) -> Result<UsedSenderTags, StorageError> {
+        let raw = self.decrypt_stored_tags("sender_tag", self.manager.get().get_tags().await?)?;
+        let raw_isolated = self.decrypt_stored_tags(
+            "isolated_sender_tag",
+            self.manager.get().get_isolated_tags().await?,
+        )?;
+
+        Ok(UsedSenderTags::from_raw(raw, raw_isolated))
     }
 
     async fn dump_sender_tags(&self, tags: &UsedSenderTags) -> Result<(), StorageError> {
         for map_ref in tags.as_raw_iter() {
             let (recipient, tag) = map_ref.pair();
-            self.manager
-                .get()
-                .insert_tag(StoredSenderTag::new(*recipient, *tag))
-                .await?;
+            let mut stored = StoredSenderTag::new(*recipient, *tag);
+            let aad = associated_data("sender_tag", "recipient", &[&stored.tag]);
+            stored.recipient = self.encrypt_for_storage(stored.recipient, &aad)?;
+            self.manager.get().insert_tag(stored).await?;
+        }
+        for map_ref in tags.as_raw_isolated_iter() {
+            let (tag, recipient) = map_ref.pair();
+            let mut stored = StoredSenderTag::new(*recipient, *tag);
+            let aad = associated_data("isolated_sender_tag", "recipient", &[&stored.tag]);
+            stored.recipient = self.encrypt_for_storage(stored.recipient, &aad)?;
+            self.manager.get().insert_isolated_tag(stored).await?;
         }
         Ok(())
     }
@@ -269,7 +362,11 @@ impl Backend {
         // something weird has happened and we can't trust the rest of the data
         let raw = stored
             .into_iter()
-            .map(TryInto::try_into)
+            .map(|mut stored| -> Result<_, StorageError> {
+                let aad = associated_data("reply_key", "reply_key", &[&stored.key_digest]);
+                stored.reply_key = self.decrypt_stored(stored.reply_key, &aad)?;
+                stored.try_into()
+            })
             .collect::<Result<_, _>>()?;
 
         Ok(SentReplyKeys::from_raw(raw))
@@ -278,10 +375,10 @@ impl Backend {
     async fn dump_sender_reply_keys(&self, reply_keys: &SentReplyKeys) -> Result<(), StorageError> {
         for map_ref in reply_keys.as_raw_iter() {
             let (digest, key) = map_ref.pair();
-            self.manager
-                .get()
-                .insert_reply_key(StoredReplyKey::new(*digest, *key))
-                .await?;
+            let mut stored = StoredReplyKey::new(*digest, *key);
+            let aad = associated_data("reply_key", "reply_key", &[&stored.key_digest]);
+            stored.reply_key = self.encrypt_for_storage(stored.reply_key, &aad)?;
+            self.manager.get().insert_reply_key(stored).await?;
         }
         Ok(())
     }
@@ -295,13 +392,17 @@ impl Backend {
             let sender_id = sender.id;
             let (sender_tag, surbs_last_received_at_timestamp): (AnonymousSenderTag, i64) =
                 sender.try_into()?;
+            let aad = associated_data("reply_surb", "reply_surb", &[&sender_tag.to_bytes()]);
             let stored_surbs = self
                 .manager
                 .get()
                 .get_reply_surbs(sender_id)
                 .await?
                 .into_iter()
-                .map(|raw| raw.try_into())
+                .map(|mut raw| -> Result<_, StorageError> {
+                    raw.reply_surb = self.decrypt_stored(raw.reply_surb, &aad)?;
+                    raw.try_into()
+                })
                 .collect::<Result<_, _>>()?;
 
             received_surbs.push((
@@ -332,11 +433,11 @@ impl Backend {
                 ))
                 .await?;
 
+            let aad = associated_data("reply_surb", "reply_surb", &[&tag.to_bytes()]);
             for reply_surb in received_surbs.surbs_ref() {
-                self.manager
-                    .get()
-                    .insert_reply_surb(StoredReplySurb::new(sender_id, reply_surb))
-                    .await?
+                let mut stored = StoredReplySurb::new(sender_id, reply_surb);
+                stored.reply_surb = self.encrypt_for_storage(stored.reply_surb, &aad)?;
+                self.manager.get().insert_reply_surb(stored).await?
             }
         }
         Ok(())
@@ -375,7 +476,7 @@ impl ReplyStorageBackend for Backend {
         debug_config: &crate::config::DebugConfig,
         db_path: Option<PathBuf>,
     ) -> Result<Self, Self::StorageError> {
-        non_wasm_helpers::setup_fs_reply_surb_backend(db_path, debug_config)
+        non_wasm_helpers::setup_fs_reply_surb_backend(db_path, debug_config, None)
             .await
             .map_err(|err| {
                 log::error!("Failed to create storage: {err}");
@@ -61,6 +61,7 @@ pub(crate) struct StoredReplyKey {
     pub(crate) key_digest: Vec<u8>,
     pub(crate) reply_key: Vec<u8>,
     pub(crate) sent_at_timestamp: i64,
+    pub(crate) sender_tag: Option<Vec<u8>>,
 }
 
 impl StoredReplyKey {
@@ -69,6 +70,7 @@ impl StoredReplyKey {
             key_digest: key_digest.to_vec(),
             reply_key: (*reply_key).to_bytes(),
             sent_at_timestamp: reply_key.sent_at_timestamp,
+            sender_tag: reply_key.sender_tag.map(|tag| tag.to_bytes().to_vec()),
         }
     }
 }
@@ -98,10 +100,20 @@ impl TryFrom<StoredReplyKey> for (EncryptionKeyDigest, UsedReplyKey) {
             });
         };
 
-        Ok((
-            digest,
-            UsedReplyKey::new(reply_key, value.sent_at_timestamp),
-        ))
+        let mut used_key = UsedReplyKey::new(reply_key, value.sent_at_timestamp);
+        if let Some(tag) = value.sender_tag {
+            let tag_len = tag.len();
+            let Ok(sender_tag_bytes) = tag.try_into() else {
+                return Err(StorageError::CorruptedData {
+                    details: format!(
+                        "the retrieved sender tag has length of {tag_len} while {SENDER_TAG_SIZE} was expected",
+                    ),
+                });
+            };
+            used_key = used_key.with_sender_tag(AnonymousSenderTag::from_bytes(sender_tag_bytes));
+        }
+
+        Ok((digest, used_key))
     }
 }
 
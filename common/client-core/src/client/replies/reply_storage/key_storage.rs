@@ -4,6 +4,7 @@
 use dashmap::iter::Iter;
 use dashmap::DashMap;
 use nym_sphinx::anonymous_replies::encryption_key::EncryptionKeyDigest;
+use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
 use nym_sphinx::anonymous_replies::SurbEncryptionKey;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -41,10 +42,14 @@ impl SentReplyKeys {
         self.inner.data.iter()
     }
 
-    pub(crate) fn insert_multiple(&self, keys: Vec<SurbEncryptionKey>) {
+    pub(crate) fn insert_multiple(
+        &self,
+        keys: Vec<SurbEncryptionKey>,
+        sender_tag: AnonymousSenderTag,
+    ) {
         let now = OffsetDateTime::now_utc().unix_timestamp();
         for key in keys {
-            self.insert(UsedReplyKey::new(key, now))
+            self.insert(UsedReplyKey::new(key, now).with_sender_tag(sender_tag))
         }
     }
 
@@ -66,6 +71,10 @@ pub(crate) struct UsedReplyKey {
     key: SurbEncryptionKey,
     // the purpose of this field is to perform invalidation at relatively very long intervals
     pub(crate) sent_at_timestamp: i64,
+
+    // sender tag the corresponding reply surb has been sent with.
+    // it's unknown for the keys persisted by the older versions of the client
+    pub(crate) sender_tag: Option<AnonymousSenderTag>,
 }
 
 impl UsedReplyKey {
@@ -73,8 +82,14 @@ impl UsedReplyKey {
         UsedReplyKey {
             key,
             sent_at_timestamp,
+            sender_tag: None,
         }
     }
+
+    pub(crate) fn with_sender_tag(mut self, sender_tag: AnonymousSenderTag) -> Self {
+        self.sender_tag = Some(sender_tag);
+        self
+    }
 }
 
 impl Deref for UsedReplyKey {
@@ -6,6 +6,7 @@ use dashmap::DashMap;
 use log::trace;
 use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
 use nym_sphinx::anonymous_replies::ReplySurb;
+use std::cmp::max;
 use std::collections::VecDeque;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -64,6 +65,36 @@ impl ReceivedReplySurbsMap {
         self.inner.data.remove(target);
     }
 
+    pub(crate) fn tracked_senders(&self) -> usize {
+        self.inner.data.len()
+    }
+
+    /// Returns up to `amount` senders that have been inactive for the longest time, out of the ones
+    /// that are not waiting for any surbs and satisfy the provided `is_idle` predicate.
+    pub(crate) fn least_recently_active<F>(
+        &self,
+        amount: usize,
+        is_idle: F,
+    ) -> Vec<AnonymousSenderTag>
+    where
+        F: Fn(&AnonymousSenderTag) -> bool,
+    {
+        let mut candidates = self
+            .inner
+            .data
+            .iter()
+            .filter(|entry| entry.pending_reception() == 0 && is_idle(entry.key()))
+            .map(|entry| (entry.last_active_at(), *entry.key()))
+            .collect::<Vec<_>>();
+        candidates.sort_unstable_by_key(|(last_active, _)| *last_active);
+
+        candidates
+            .into_iter()
+            .take(amount)
+            .map(|(_, sender)| sender)
+            .collect()
+    }
+
     pub(crate) fn reset_surbs_last_received_at(&self, target: &AnonymousSenderTag) {
         if let Some(mut entry) = self.inner.data.get_mut(target) {
             entry.surbs_last_received_at_timestamp = OffsetDateTime::now_utc().unix_timestamp();
@@ -196,14 +227,17 @@ pub(crate) struct ReceivedReplySurbs {
 
     pending_reception: u32,
     surbs_last_received_at_timestamp: i64,
+    surbs_last_used_at_timestamp: i64,
 }
 
 impl ReceivedReplySurbs {
     fn new(initial_surbs: VecDeque<ReplySurb>) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
         ReceivedReplySurbs {
             data: initial_surbs,
             pending_reception: 0,
-            surbs_last_received_at_timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+            surbs_last_received_at_timestamp: now,
+            surbs_last_used_at_timestamp: now,
         }
     }
 
@@ -216,6 +250,7 @@ impl ReceivedReplySurbs {
             data: surbs.into(),
             pending_reception: 0,
             surbs_last_received_at_timestamp,
+            surbs_last_used_at_timestamp: surbs_last_received_at_timestamp,
         }
     }
 
@@ -228,6 +263,18 @@ impl ReceivedReplySurbs {
         self.surbs_last_received_at_timestamp
     }
 
+    /// Timestamp of the last time we either received or used any reply surbs of this sender.
+    pub(crate) fn last_active_at(&self) -> i64 {
+        max(
+            self.surbs_last_received_at_timestamp,
+            self.surbs_last_used_at_timestamp,
+        )
+    }
+
+    fn mark_used(&mut self) {
+        self.surbs_last_used_at_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    }
+
     pub(crate) fn pending_reception(&self) -> u32 {
         self.pending_reception
     }
@@ -251,11 +298,13 @@ impl ReceivedReplySurbs {
             (None, self.items_left())
         } else {
             let surbs = self.data.drain(..amount).collect();
+            self.mark_used();
             (Some(surbs), self.items_left())
         }
     }
 
     pub(crate) fn get_reply_surb(&mut self) -> (Option<ReplySurb>, usize) {
+        self.mark_used();
         (self.pop_surb(), self.items_left())
     }
 
@@ -276,3 +325,48 @@ impl ReceivedReplySurbs {
         trace!("we now have {} surbs!", self.data.len());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sender(byte: u8) -> AnonymousSenderTag {
+        AnonymousSenderTag::from_bytes([byte; 16])
+    }
+
+    fn set_last_active(map: &ReceivedReplySurbsMap, target: &AnonymousSenderTag, timestamp: i64) {
+        let mut entry = map.inner.data.get_mut(target).unwrap();
+        entry.surbs_last_received_at_timestamp = timestamp;
+        entry.surbs_last_used_at_timestamp = timestamp;
+    }
+
+    #[test]
+    fn least_recently_active_senders_are_returned_first() {
+        let map = ReceivedReplySurbsMap::new(10, 200);
+        for (byte, timestamp) in [(1, 300), (2, 100), (3, 200)] {
+            map.insert_surbs(&sender(byte), Vec::new());
+            set_last_active(&map, &sender(byte), timestamp);
+        }
+
+        assert_eq!(
+            map.least_recently_active(2, |_| true),
+            vec![sender(2), sender(3)]
+        );
+        assert_eq!(map.least_recently_active(5, |_| true).len(), 3);
+    }
+
+    #[test]
+    fn busy_senders_are_never_returned() {
+        let map = ReceivedReplySurbsMap::new(10, 200);
+        for (byte, timestamp) in [(1, 300), (2, 100), (3, 200)] {
+            map.insert_surbs(&sender(byte), Vec::new());
+            set_last_active(&map, &sender(byte), timestamp);
+        }
+        map.increment_pending_reception(&sender(3), 10);
+
+        assert_eq!(
+            map.least_recently_active(2, |target| target != &sender(2)),
+            vec![sender(1)]
+        );
+    }
+}
@@ -17,6 +17,10 @@ pub struct UsedSenderTags {
 #[derive(Debug)]
 struct UsedSenderTagsInner {
     data: DashMap<RecipientBytes, AnonymousSenderTag>,
+
+    // additional tags explicitly chosen for isolating different streams sent to the same recipient,
+    // alongside the recipient they have been used with
+    isolated: DashMap<AnonymousSenderTag, RecipientBytes>,
 }
 
 impl UsedSenderTags {
@@ -24,15 +28,23 @@ impl UsedSenderTags {
         UsedSenderTags {
             inner: Arc::new(UsedSenderTagsInner {
                 data: DashMap::new(),
+                isolated: DashMap::new(),
             }),
         }
     }
 
     #[cfg(all(not(target_arch = "wasm32"), feature = "fs-surb-storage"))]
-    pub(crate) fn from_raw(raw: Vec<(RecipientBytes, AnonymousSenderTag)>) -> UsedSenderTags {
+    pub(crate) fn from_raw(
+        raw: Vec<(RecipientBytes, AnonymousSenderTag)>,
+        raw_isolated: Vec<(RecipientBytes, AnonymousSenderTag)>,
+    ) -> UsedSenderTags {
         UsedSenderTags {
             inner: Arc::new(UsedSenderTagsInner {
                 data: raw.into_iter().collect(),
+                isolated: raw_isolated
+                    .into_iter()
+                    .map(|(recipient, tag)| (tag, recipient))
+                    .collect(),
             }),
         }
     }
@@ -42,6 +54,11 @@ impl UsedSenderTags {
         self.inner.data.iter()
     }
 
+    #[cfg(all(not(target_arch = "wasm32"), feature = "fs-surb-storage"))]
+    pub(crate) fn as_raw_isolated_iter(&self) -> Iter<'_, AnonymousSenderTag, RecipientBytes> {
+        self.inner.isolated.iter()
+    }
+
     pub(crate) fn insert_new(&self, recipient: &Recipient, tag: AnonymousSenderTag) {
         self.inner.data.insert(recipient.to_bytes(), tag);
     }
@@ -56,4 +73,56 @@ impl UsedSenderTags {
     pub(crate) fn exists(&self, recipient: &Recipient) -> bool {
         self.inner.data.contains_key(&recipient.to_bytes())
     }
+
+    /// Records the explicitly chosen tag as being used for sending messages to the recipient.
+    pub(crate) fn insert_isolated(&self, recipient: &Recipient, tag: AnonymousSenderTag) {
+        self.inner.isolated.insert(tag, recipient.to_bytes());
+    }
+
+    /// Checks whether the tag has been used for sending messages to the specified recipient.
+    pub(crate) fn is_bound(&self, tag: &AnonymousSenderTag, recipient: &Recipient) -> bool {
+        let recipient = recipient.to_bytes();
+        if let Some(isolated_recipient) = self.inner.isolated.get(tag) {
+            return *isolated_recipient == recipient;
+        }
+        self.inner
+            .data
+            .get(&recipient)
+            .map(|default_tag| default_tag.value() == tag)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nym_crypto::asymmetric::{encryption, identity};
+
+    fn recipient() -> Recipient {
+        let mut rng = rand::thread_rng();
+        Recipient::new(
+            *identity::KeyPair::new(&mut rng).public_key(),
+            *encryption::KeyPair::new(&mut rng).public_key(),
+            *identity::KeyPair::new(&mut rng).public_key(),
+        )
+    }
+
+    #[test]
+    fn tags_are_only_bound_to_their_recipients() {
+        let mut rng = rand::thread_rng();
+        let tags = UsedSenderTags::new();
+        let service = recipient();
+        let attacker = recipient();
+
+        let default_tag = AnonymousSenderTag::new_random(&mut rng);
+        let isolated_tag = AnonymousSenderTag::new_random(&mut rng);
+        tags.insert_new(&service, default_tag);
+        tags.insert_isolated(&service, isolated_tag);
+
+        assert!(tags.is_bound(&default_tag, &service));
+        assert!(tags.is_bound(&isolated_tag, &service));
+        assert!(!tags.is_bound(&default_tag, &attacker));
+        assert!(!tags.is_bound(&isolated_tag, &attacker));
+        assert!(!tags.is_bound(&AnonymousSenderTag::new_random(&mut rng), &service));
+    }
 }
@@ -0,0 +1,84 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Retrieval of the passphrase used for encrypting the client storages,
+//! based on the source specified in the config.
+
+use crate::config::{Config, PassphraseSource};
+use crate::error::ClientCoreError;
+
+/// Environment variable holding the passphrase of the encrypted client storage.
+pub const STORAGE_PASSPHRASE_ENV: &str = "NYM_CLIENT_STORAGE_PASSPHRASE";
+
+#[cfg(feature = "os-keychain")]
+const KEYCHAIN_SERVICE: &str = "nym-client-storage";
+
+/// Obtains the passphrase of the client storage, unless the encryption is disabled.
+/// The passphrases meant to be supplied through the control API have to be handled by the caller
+/// as this function does not know anything about it.
+pub fn storage_passphrase<T>(config: &Config<T>) -> Result<Option<String>, ClientCoreError> {
+    if !config.get_storage_encryption_enabled() {
+        return Ok(None);
+    }
+
+    match config.get_storage_passphrase_source() {
+        PassphraseSource::Prompt => read_passphrase().map(Some),
+        PassphraseSource::Keychain => keychain_passphrase(&config.get_id()).map(Some),
+        PassphraseSource::ControlApi => Err(ClientCoreError::StoragePassphraseFailure {
+            reason: "this client does not support supplying the passphrase through the control API"
+                .to_string(),
+        }),
+    }
+}
+
+/// Obtains the passphrase from the environment or, if it's not set there, asks for it on the terminal
+/// (without echoing it back).
+pub fn read_passphrase() -> Result<String, ClientCoreError> {
+    if let Ok(passphrase) = std::env::var(STORAGE_PASSPHRASE_ENV) {
+        if !passphrase.is_empty() {
+            return Ok(passphrase);
+        }
+    }
+
+    let passphrase = rpassword::prompt_password("Enter the passphrase of the client storage: ")?;
+    if passphrase.is_empty() {
+        return Err(ClientCoreError::StoragePassphraseFailure {
+            reason: "no passphrase has been provided".to_string(),
+        });
+    }
+    Ok(passphrase)
+}
+
+/// Retrieves the passphrase kept in the keychain of the operating system for the particular client.
+/// If there's none, a random one is generated and saved there.
+#[cfg(feature = "os-keychain")]
+fn keychain_passphrase(client_id: &str) -> Result<String, ClientCoreError> {
+    use rand::RngCore;
+
+    let keychain_err = |err: keyring::Error| ClientCoreError::StoragePassphraseFailure {
+        reason: format!("keychain failure - {err}"),
+    };
+
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, client_id).map_err(keychain_err)?;
+    match entry.get_password() {
+        Ok(passphrase) => Ok(passphrase),
+        Err(keyring::Error::NoEntry) => {
+            log::info!("generating the storage passphrase and saving it in the keychain");
+            let mut bytes = [0u8; 32];
+            rand::rngs::OsRng.fill_bytes(&mut bytes);
+            let passphrase: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+            entry.set_password(&passphrase).map_err(keychain_err)?;
+            Ok(passphrase)
+        }
+        Err(err) => Err(keychain_err(err)),
+    }
+}
+
+#[cfg(not(feature = "os-keychain"))]
+fn keychain_passphrase(_client_id: &str) -> Result<String, ClientCoreError> {
+    Err(ClientCoreError::StoragePassphraseFailure {
+        reason:
+            "the client has been built without the keychain support (the `os-keychain` feature)"
+                .to_string(),
+    })
+}
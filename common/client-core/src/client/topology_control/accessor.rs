@@ -4,6 +4,7 @@
 use nym_sphinx::addressing::clients::Recipient;
 use nym_sphinx::params::DEFAULT_NUM_MIX_HOPS;
 use nym_topology::{NymTopology, NymTopologyError};
+use std::net::SocketAddr;
 use std::ops::Deref;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -130,6 +131,15 @@ impl TopologyAccessor {
         self.inner.update(Some(new_topology)).await;
     }
 
+    /// Removes the mixnode listening on the specified address from the current topology, so that it
+    /// would be avoided until the next topology refresh. Returns whether the node got removed.
+    pub(crate) async fn exclude_mixnode(&self, mix_host: SocketAddr) -> bool {
+        match self.inner.topology.write().await.as_mut() {
+            Some(topology) => topology.remove_mixnode(mix_host),
+            None => false,
+        }
+    }
+
     pub fn release_manual_control(&self) {
         self.inner
             .controlled_manually
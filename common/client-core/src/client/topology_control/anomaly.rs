@@ -0,0 +1,433 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_crypto::asymmetric::{encryption, identity};
+use nym_mixnet_contract_common::MixId;
+use nym_sphinx::params::DEFAULT_NUM_MIX_HOPS;
+use nym_topology::{MixLayer, NymTopology};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+// number of the past topologies that are kept around for detecting inconsistent network views
+const TRACKED_TOPOLOGY_VIEWS: usize = 5;
+
+// upper bound on the number of anomalies kept around until they're acknowledged
+const MAX_UNACKNOWLEDGED_ANOMALIES: usize = 32;
+
+/// Suspicious change of the network topology observed between the topology refreshes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopologyAnomaly {
+    /// Large portion of the nodes present in the previous topology has changed their keys at once.
+    MassKeyChange { changed: usize, total: usize },
+
+    /// The number of nodes on the mix layer has suddenly dropped below the allowed minimum.
+    LayerShrinkage {
+        layer: MixLayer,
+        previous_size: usize,
+        current_size: usize,
+    },
+
+    /// Some nodes went back to the keys they've had in one of the earlier topologies,
+    /// implying the directory is serving different views of the network.
+    InconsistentViews { nodes: usize },
+}
+
+impl Display for TopologyAnomaly {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TopologyAnomaly::MassKeyChange { changed, total } => write!(
+                f,
+                "{changed} out of {total} previously known nodes have changed their keys"
+            ),
+            TopologyAnomaly::LayerShrinkage {
+                layer,
+                previous_size,
+                current_size,
+            } => write!(
+                f,
+                "mix layer {layer} has shrunk from {previous_size} to {current_size} nodes"
+            ),
+            TopologyAnomaly::InconsistentViews { nodes } => write!(
+                f,
+                "{nodes} nodes have reverted to their earlier keys - the directory might be serving inconsistent views of the network"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum NodeId {
+    Mixnode(MixId),
+    Gateway(identity::PublicKey),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NodeKeys {
+    identity: identity::PublicKey,
+    sphinx: encryption::PublicKey,
+}
+
+#[derive(Debug, Default)]
+struct TopologyView {
+    layer_sizes: HashMap<MixLayer, usize>,
+    nodes: HashMap<NodeId, NodeKeys>,
+}
+
+impl TopologyView {
+    fn new(topology: &NymTopology) -> Self {
+        let mut view = TopologyView::default();
+        for (layer, mixes) in topology.mixes() {
+            view.layer_sizes.insert(*layer, mixes.len());
+            for mix in mixes {
+                view.nodes.insert(
+                    NodeId::Mixnode(mix.mix_id),
+                    NodeKeys {
+                        identity: mix.identity_key,
+                        sphinx: mix.sphinx_key,
+                    },
+                );
+            }
+        }
+        for gateway in topology.gateways() {
+            view.nodes.insert(
+                NodeId::Gateway(gateway.identity_key),
+                NodeKeys {
+                    identity: gateway.identity_key,
+                    sphinx: gateway.sphinx_key,
+                },
+            );
+        }
+        view
+    }
+
+    fn layer_size(&self, layer: MixLayer) -> usize {
+        self.layer_sizes.get(&layer).copied().unwrap_or_default()
+    }
+}
+
+/// Compares the consecutive topologies obtained from the directory in order to detect
+/// suspicious changes in the network.
+pub(crate) struct TopologyAnomalyDetector {
+    key_change_threshold: f64,
+    minimum_layer_size: usize,
+
+    /// The most recent topology views, starting with the latest one.
+    previous_views: VecDeque<TopologyView>,
+}
+
+impl TopologyAnomalyDetector {
+    pub(crate) fn new(key_change_threshold: f64, minimum_layer_size: usize) -> Self {
+        TopologyAnomalyDetector {
+            key_change_threshold,
+            minimum_layer_size,
+            previous_views: VecDeque::with_capacity(TRACKED_TOPOLOGY_VIEWS),
+        }
+    }
+
+    fn check_key_changes(
+        &self,
+        previous: &TopologyView,
+        current: &TopologyView,
+    ) -> Vec<TopologyAnomaly> {
+        let mut total = 0;
+        let mut changed = 0;
+        for (node, keys) in &current.nodes {
+            if let Some(previous_keys) = previous.nodes.get(node) {
+                total += 1;
+                if previous_keys != keys {
+                    changed += 1;
+                }
+            }
+        }
+
+        if changed > 0 && changed as f64 / total as f64 > self.key_change_threshold {
+            vec![TopologyAnomaly::MassKeyChange { changed, total }]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn check_layer_sizes(
+        &self,
+        previous: &TopologyView,
+        current: &TopologyView,
+    ) -> Vec<TopologyAnomaly> {
+        // a network that has always been small is not suspicious, only the sudden shrinkage is
+        (1..=DEFAULT_NUM_MIX_HOPS)
+            .filter_map(|layer| {
+                let previous_size = previous.layer_size(layer);
+                let current_size = current.layer_size(layer);
+                if previous_size >= self.minimum_layer_size
+                    && current_size < self.minimum_layer_size
+                {
+                    Some(TopologyAnomaly::LayerShrinkage {
+                        layer,
+                        previous_size,
+                        current_size,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn check_view_consistency(&self, current: &TopologyView) -> Vec<TopologyAnomaly> {
+        let Some(previous) = self.previous_views.front() else {
+            return Vec::new();
+        };
+
+        let nodes = current
+            .nodes
+            .iter()
+            .filter(|(node, keys)| {
+                matches!(previous.nodes.get(*node), Some(previous_keys) if previous_keys != *keys)
+                    && self
+                        .previous_views
+                        .iter()
+                        .skip(1)
+                        .any(|older| older.nodes.get(*node) == Some(*keys))
+            })
+            .count();
+
+        if nodes > 0 {
+            vec![TopologyAnomaly::InconsistentViews { nodes }]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Compares the provided topology against the previously accepted ones and returns
+    /// all the detected anomalies.
+    pub(crate) fn check(&self, topology: &NymTopology) -> Vec<TopologyAnomaly> {
+        let current = TopologyView::new(topology);
+
+        let mut anomalies = Vec::new();
+        if let Some(previous) = self.previous_views.front() {
+            anomalies.extend(self.check_key_changes(previous, &current));
+            anomalies.extend(self.check_layer_sizes(previous, &current));
+            anomalies.extend(self.check_view_consistency(&current));
+        }
+        anomalies
+    }
+
+    /// Records the topology that got accepted, so that the subsequent ones would be compared against it.
+    pub(crate) fn accept(&mut self, topology: &NymTopology) {
+        if self.previous_views.len() == TRACKED_TOPOLOGY_VIEWS {
+            self.previous_views.pop_back();
+        }
+        self.previous_views.push_front(TopologyView::new(topology));
+    }
+}
+
+/// Topology anomalies that haven't yet been acknowledged by the user.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopologyAnomalyStatus {
+    pub anomalies: Vec<TopologyAnomaly>,
+
+    /// Indicates whether all the traffic is paused until the anomalies are acknowledged.
+    pub traffic_paused: bool,
+}
+
+/// Handle used for inspecting and acknowledging the detected topology anomalies.
+#[derive(Debug, Clone)]
+pub struct TopologyAnomalyControl {
+    inner: Arc<watch::Sender<TopologyAnomalyStatus>>,
+}
+
+impl Default for TopologyAnomalyControl {
+    fn default() -> Self {
+        TopologyAnomalyControl::new()
+    }
+}
+
+impl TopologyAnomalyControl {
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(TopologyAnomalyStatus::default());
+        TopologyAnomalyControl {
+            inner: Arc::new(tx),
+        }
+    }
+
+    pub(crate) fn report(&self, anomalies: Vec<TopologyAnomaly>, pause_traffic: bool) {
+        self.inner.send_modify(|status| {
+            status.anomalies.extend(anomalies);
+            let excess = status
+                .anomalies
+                .len()
+                .saturating_sub(MAX_UNACKNOWLEDGED_ANOMALIES);
+            status.anomalies.drain(..excess);
+            status.traffic_paused |= pause_traffic;
+        })
+    }
+
+    /// Acknowledges all of the detected anomalies and resumes the traffic if it was paused.
+    /// Returns the acknowledged anomalies.
+    pub fn acknowledge(&self) -> Vec<TopologyAnomaly> {
+        let mut acknowledged = Vec::new();
+        self.inner.send_if_modified(|status| {
+            if status.anomalies.is_empty() && !status.traffic_paused {
+                return false;
+            }
+            acknowledged = std::mem::take(&mut status.anomalies);
+            status.traffic_paused = false;
+            true
+        });
+        acknowledged
+    }
+
+    pub fn status(&self) -> TopologyAnomalyStatus {
+        self.inner.borrow().clone()
+    }
+
+    pub fn is_traffic_paused(&self) -> bool {
+        self.inner.borrow().traffic_paused
+    }
+
+    pub fn listener(&self) -> TopologyAnomalyListener {
+        TopologyAnomalyListener {
+            inner: self.inner.subscribe(),
+        }
+    }
+}
+
+/// Receiving end of the [`TopologyAnomalyControl`] used for getting notified about new anomalies
+/// and the traffic being paused or resumed.
+#[derive(Debug, Clone)]
+pub struct TopologyAnomalyListener {
+    inner: watch::Receiver<TopologyAnomalyStatus>,
+}
+
+impl TopologyAnomalyListener {
+    pub fn is_traffic_paused(&self) -> bool {
+        self.inner.borrow().traffic_paused
+    }
+
+    /// Waits until the anomaly status changes and returns the new value.
+    /// If the controlling end has been dropped, the status can no longer change and thus
+    /// the future never resolves.
+    pub async fn changed(&mut self) -> TopologyAnomalyStatus {
+        if self.inner.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+        self.inner.borrow_and_update().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nym_mixnet_contract_common::Layer;
+    use nym_topology::mix;
+
+    fn mixnode(mix_id: MixId, layer: Layer, sphinx_key_seed: u8) -> mix::Node {
+        mix::Node {
+            mix_id,
+            owner: "N/A".to_string(),
+            host: "3.3.3.3".parse().unwrap(),
+            mix_host: "3.3.3.3:1789".parse().unwrap(),
+            identity_key: identity::PublicKey::from_base58_string(
+                "3ebjp1Fb9hdcS1AR6AZihgeJiMHkB5jjJUsvqNnfQwU7",
+            )
+            .unwrap(),
+            sphinx_key: encryption::PublicKey::from_bytes(&[sphinx_key_seed; 32]).unwrap(),
+            layer,
+            version: "0.x.0".to_string(),
+            family: None,
+        }
+    }
+
+    // topology with `layer_size` nodes on each layer, with their sphinx keys derived from the seed
+    fn topology(layer_size: u32, sphinx_key_seed: u8) -> NymTopology {
+        let mut mixes = HashMap::new();
+        for (layer, layer_enum) in [(1, Layer::One), (2, Layer::Two), (3, Layer::Three)] {
+            let nodes = (0..layer_size)
+                .map(|i| mixnode(layer as u32 * 100 + i, layer_enum, sphinx_key_seed))
+                .collect();
+            mixes.insert(layer, nodes);
+        }
+        NymTopology::new(mixes, vec![])
+    }
+
+    // checks the topology and accepts it, the way it would happen after acknowledging the anomalies
+    fn check_and_accept(
+        detector: &mut TopologyAnomalyDetector,
+        topology: NymTopology,
+    ) -> Vec<TopologyAnomaly> {
+        let anomalies = detector.check(&topology);
+        detector.accept(&topology);
+        anomalies
+    }
+
+    #[test]
+    fn stable_topology_is_not_reported() {
+        let mut detector = TopologyAnomalyDetector::new(0.5, 3);
+        assert!(check_and_accept(&mut detector, topology(5, 1)).is_empty());
+        assert!(check_and_accept(&mut detector, topology(5, 1)).is_empty());
+        assert!(check_and_accept(&mut detector, topology(4, 1)).is_empty());
+    }
+
+    #[test]
+    fn detects_mass_key_change_and_layer_shrinkage() {
+        let mut detector = TopologyAnomalyDetector::new(0.5, 3);
+        detector.accept(&topology(5, 1));
+
+        let anomalies = check_and_accept(&mut detector, topology(5, 2));
+        assert_eq!(
+            anomalies,
+            vec![TopologyAnomaly::MassKeyChange {
+                changed: 15,
+                total: 15
+            }]
+        );
+
+        let anomalies = check_and_accept(&mut detector, topology(2, 2));
+        assert_eq!(anomalies.len(), 3);
+        assert!(anomalies.contains(&TopologyAnomaly::LayerShrinkage {
+            layer: 1,
+            previous_size: 5,
+            current_size: 2
+        }));
+    }
+
+    #[test]
+    fn detects_inconsistent_views() {
+        let mut detector = TopologyAnomalyDetector::new(1.0, 0);
+        detector.accept(&topology(2, 1));
+        detector.accept(&topology(2, 2));
+
+        let anomalies = detector.check(&topology(2, 1));
+        assert_eq!(
+            anomalies,
+            vec![TopologyAnomaly::InconsistentViews { nodes: 6 }]
+        );
+    }
+
+    #[test]
+    fn rejected_topology_is_not_used_for_comparison() {
+        let mut detector = TopologyAnomalyDetector::new(0.5, 3);
+        detector.accept(&topology(5, 1));
+
+        assert!(!detector.check(&topology(5, 2)).is_empty());
+        // the anomalous topology hasn't been accepted, so it's still flagged on the next refresh
+        assert!(!detector.check(&topology(5, 2)).is_empty());
+        assert!(detector.check(&topology(5, 1)).is_empty());
+    }
+
+    #[test]
+    fn acknowledging_anomalies_resumes_traffic() {
+        let control = TopologyAnomalyControl::new();
+        let anomaly = TopologyAnomaly::InconsistentViews { nodes: 1 };
+
+        control.report(vec![anomaly.clone()], false);
+        assert!(!control.is_traffic_paused());
+        control.report(vec![anomaly.clone()], true);
+        assert!(control.is_traffic_paused());
+
+        assert_eq!(control.acknowledge(), vec![anomaly.clone(), anomaly]);
+        assert_eq!(control.status(), TopologyAnomalyStatus::default());
+        assert!(control.acknowledge().is_empty());
+    }
+}
@@ -1,27 +1,60 @@
 // Copyright 2021-2023 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::client::events::{ClientEvent, ClientEventBus};
+use crate::client::hibernation::HibernationListener;
+use crate::client::topology_control::anomaly::{
+    TopologyAnomaly, TopologyAnomalyControl, TopologyAnomalyDetector, TopologyAnomalyListener,
+    TopologyAnomalyStatus,
+};
 use crate::spawn_future;
 pub(crate) use accessor::{TopologyAccessor, TopologyReadPermit};
 use futures::StreamExt;
 use log::*;
 use nym_topology::provider_trait::TopologyProvider;
-use nym_topology::NymTopologyError;
+use nym_topology::{NymTopology, NymTopologyError};
 use std::time::Duration;
 
 mod accessor;
+pub mod anomaly;
+pub(crate) mod node_filter;
 pub(crate) mod nym_api_provider;
+pub(crate) mod route_pool;
+pub(crate) mod unreachable_hops;
 
 // TODO: move it to config later
 const MAX_FAILURE_COUNT: usize = 10;
 
 pub struct TopologyRefresherConfig {
     refresh_rate: Duration,
+    hibernation_refresh_rate: Duration,
+
+    anomaly_key_change_threshold: f64,
+    anomaly_minimum_layer_size: usize,
+    pause_on_anomaly: bool,
 }
 
 impl TopologyRefresherConfig {
-    pub fn new(refresh_rate: Duration) -> Self {
-        TopologyRefresherConfig { refresh_rate }
+    pub fn new(refresh_rate: Duration, hibernation_refresh_rate: Duration) -> Self {
+        TopologyRefresherConfig {
+            refresh_rate,
+            hibernation_refresh_rate,
+            anomaly_key_change_threshold: 1.0,
+            anomaly_minimum_layer_size: 0,
+            pause_on_anomaly: false,
+        }
+    }
+
+    pub fn with_anomaly_detection(
+        mut self,
+        key_change_threshold: f64,
+        minimum_layer_size: usize,
+        pause_on_anomaly: bool,
+    ) -> Self {
+        self.anomaly_key_change_threshold = key_change_threshold;
+        self.anomaly_minimum_layer_size = minimum_layer_size;
+        self.pause_on_anomaly = pause_on_anomaly;
+        self
     }
 }
 
@@ -30,7 +63,22 @@ pub struct TopologyRefresher {
     topology_accessor: TopologyAccessor,
 
     refresh_rate: Duration,
+    hibernation_refresh_rate: Duration,
     consecutive_failure_count: usize,
+
+    hibernation: HibernationListener,
+    skipped_refreshes: u32,
+
+    anomaly_detector: TopologyAnomalyDetector,
+    anomalies: TopologyAnomalyControl,
+    anomaly_listener: TopologyAnomalyListener,
+    pause_on_anomaly: bool,
+
+    /// The latest topology held back due to the detected anomalies, alongside them.
+    /// It's only going to be used once the anomalies get acknowledged.
+    held_back_topology: Option<(NymTopology, Vec<TopologyAnomaly>)>,
+
+    events: ClientEventBus,
 }
 
 impl TopologyRefresher {
@@ -38,13 +86,115 @@ impl TopologyRefresher {
         cfg: TopologyRefresherConfig,
         topology_accessor: TopologyAccessor,
         topology_provider: Box<dyn TopologyProvider>,
+        hibernation: HibernationListener,
+        anomalies: TopologyAnomalyControl,
+        events: ClientEventBus,
     ) -> Self {
         TopologyRefresher {
             topology_provider,
             topology_accessor,
             refresh_rate: cfg.refresh_rate,
+            hibernation_refresh_rate: cfg.hibernation_refresh_rate,
             consecutive_failure_count: 0,
+            hibernation,
+            skipped_refreshes: 0,
+            anomaly_detector: TopologyAnomalyDetector::new(
+                cfg.anomaly_key_change_threshold,
+                cfg.anomaly_minimum_layer_size,
+            ),
+            anomaly_listener: anomalies.listener(),
+            anomalies,
+            pause_on_anomaly: cfg.pause_on_anomaly,
+            held_back_topology: None,
+            events,
+        }
+    }
+
+    // number of regular refresh ticks after which the topology is refreshed while hibernating
+    fn hibernation_refresh_ticks(&self) -> u32 {
+        if self.refresh_rate.is_zero() {
+            return 1;
+        }
+        let ticks = self.hibernation_refresh_rate.as_millis() / self.refresh_rate.as_millis();
+        let remainder = self.hibernation_refresh_rate.as_millis() % self.refresh_rate.as_millis();
+        let ticks = if remainder == 0 { ticks } else { ticks + 1 };
+        ticks.clamp(1, u32::MAX as u128) as u32
+    }
+
+    async fn on_refresh_tick(&mut self) {
+        if self.hibernation.is_hibernating() {
+            self.skipped_refreshes += 1;
+            if self.skipped_refreshes < self.hibernation_refresh_ticks() {
+                trace!("skipping the topology refresh as we're hibernating");
+                return;
+            }
+        }
+        self.skipped_refreshes = 0;
+        self.try_refresh().await;
+    }
+
+    async fn on_hibernation_change(&mut self, hibernating: bool) {
+        // make sure we're not going to keep on using possibly very outdated topology after waking up
+        if !hibernating && self.skipped_refreshes > 0 {
+            debug!("refreshing the topology after the hibernation");
+            self.skipped_refreshes = 0;
+            self.try_refresh().await;
+        }
+    }
+
+    // returns whether the topology can be used straight away. otherwise it's held back
+    // until the detected anomalies get acknowledged and the previous topology keeps on being used
+    fn check_for_anomalies(&mut self, topology: &NymTopology) -> bool {
+        let anomalies = self.anomaly_detector.check(topology);
+        if anomalies.is_empty() {
+            self.anomaly_detector.accept(topology);
+            self.held_back_topology = None;
+            return true;
+        }
+
+        // don't keep on reporting the very same anomalies on every refresh
+        let already_reported = matches!(
+            &self.held_back_topology,
+            Some((_, reported)) if *reported == anomalies
+        );
+        if !already_reported {
+            for anomaly in &anomalies {
+                warn!("detected suspicious change of the network topology: {anomaly}");
+            }
+            warn!("the previous topology is going to be used until the topology anomalies are acknowledged");
+            if self.pause_on_anomaly {
+                warn!(
+                    "all traffic is going to be paused until the topology anomalies are acknowledged"
+                );
+            }
+            self.anomalies
+                .report(anomalies.clone(), self.pause_on_anomaly);
         }
+        self.held_back_topology = Some((topology.clone(), anomalies));
+        false
+    }
+
+    async fn on_anomaly_status_change(&mut self, status: TopologyAnomalyStatus) {
+        // acknowledging the anomalies accepts the topology that got held back because of them
+        if !status.anomalies.is_empty() {
+            return;
+        }
+        let Some((topology, _)) = self.held_back_topology.take() else {
+            return;
+        };
+        info!("using the held back topology after its anomalies got acknowledged");
+        self.anomaly_detector.accept(&topology);
+        self.install_topology(topology).await;
+    }
+
+    async fn install_topology(&mut self, topology: NymTopology) {
+        self.events.emit(ClientEvent::TopologyRefreshed {
+            mixnodes: topology.num_mixnodes(),
+            gateways: topology.gateways().len(),
+        });
+        self.topology_accessor
+            .update_global_topology(Some(topology))
+            .await;
     }
 
     pub fn change_topology_provider(&mut self, provider: Box<dyn TopologyProvider>) {
@@ -72,13 +222,17 @@ impl TopologyRefresher {
             warn!("we're going to keep on using the old topology for this iteration");
             self.consecutive_failure_count += 1;
             return;
-        } else if new_topology.is_some() {
-            self.consecutive_failure_count = 0;
         }
 
-        self.topology_accessor
-            .update_global_topology(new_topology)
-            .await;
+        match new_topology {
+            Some(topology) => {
+                self.consecutive_failure_count = 0;
+                if self.check_for_anomalies(&topology) {
+                    self.install_topology(topology).await;
+                }
+            }
+            None => self.topology_accessor.update_global_topology(None).await,
+        }
     }
 
     pub async fn ensure_topology_is_routable(&self) -> Result<(), NymTopologyError> {
@@ -101,7 +255,13 @@ impl TopologyRefresher {
             while !shutdown.is_shutdown() {
                 tokio::select! {
                     _ = interval.next() => {
-                        self.try_refresh().await;
+                        self.on_refresh_tick().await;
+                    },
+                    hibernating = self.hibernation.changed() => {
+                        self.on_hibernation_change(hibernating).await;
+                    },
+                    status = self.anomaly_listener.changed() => {
+                        self.on_anomaly_status_change(status).await;
                     },
                     _ = shutdown.recv() => {
                         log::trace!("TopologyRefresher: Received shutdown");
@@ -0,0 +1,159 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::client::helpers::{get_time_now, Instant};
+use log::{debug, warn};
+use nym_validator_client::client::NymApiClient;
+use nym_validator_client::models::NodePerformance;
+use std::collections::HashSet;
+use std::time::Duration;
+
+// the scores are averaged over the last 24h, so there's no point in retrieving them on every
+// topology refresh unless some new mixnodes have appeared
+const MIXNODE_PERFORMANCE_REFRESH_RATE: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone)]
+struct CachedPerformance {
+    retrieved_at: Instant,
+
+    /// Identity keys of the mixnodes whose performance is below the threshold.
+    underperforming: HashSet<String>,
+}
+
+/// Determines which of the network nodes should never be used by the client,
+/// either because the user explicitly asked for it or because of their poor performance.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NodeFilter {
+    /// The minimum performance score (in percent) over the last 24h of a node for it to be used.
+    minimum_performance: u8,
+
+    /// Identity keys of the nodes the user never wants to use.
+    ignored_nodes: HashSet<String>,
+
+    /// The last retrieved performance of the mixnodes.
+    mixnode_performance: Option<CachedPerformance>,
+}
+
+impl NodeFilter {
+    pub(crate) fn new(minimum_performance: u8, ignored_nodes: Vec<String>) -> Self {
+        NodeFilter {
+            minimum_performance,
+            ignored_nodes: ignored_nodes.into_iter().collect(),
+            mixnode_performance: None,
+        }
+    }
+
+    fn is_performant(&self, performance: &NodePerformance) -> bool {
+        performance.last_24h.round_to_integer() >= self.minimum_performance
+    }
+
+    fn should_refresh_mixnode_performance(&self, bonded_set_changed: bool, now: Instant) -> bool {
+        match &self.mixnode_performance {
+            None => true,
+            Some(cached) => {
+                bonded_set_changed || cached.retrieved_at + MIXNODE_PERFORMANCE_REFRESH_RATE <= now
+            }
+        }
+    }
+
+    /// Retrieves the identities of all the mixnodes that must not be used in any of the routes.
+    /// The performance of the nodes is only retrieved again if the set of the bonded mixnodes
+    /// has changed or the previously retrieved one got stale. If it couldn't have been obtained,
+    /// the previously retrieved performance, if any, is used instead.
+    pub(crate) async fn excluded_mixnodes(
+        &mut self,
+        client: &NymApiClient,
+        bonded_set_changed: bool,
+    ) -> HashSet<String> {
+        let mut excluded = self.ignored_nodes.clone();
+        if self.minimum_performance == 0 {
+            return excluded;
+        }
+
+        let now = get_time_now();
+        if self.should_refresh_mixnode_performance(bonded_set_changed, now) {
+            match client.get_cached_active_mixnodes_detailed().await {
+                Ok(mixnodes) => {
+                    self.mixnode_performance = Some(CachedPerformance {
+                        retrieved_at: now,
+                        underperforming: mixnodes
+                            .iter()
+                            .filter(|mixnode| !self.is_performant(&mixnode.node_performance))
+                            .map(|mixnode| mixnode.identity_key().to_owned())
+                            .collect(),
+                    })
+                }
+                Err(err) => warn!("failed to get the performance of the mixnodes - {err}"),
+            }
+        }
+
+        if let Some(cached) = &self.mixnode_performance {
+            excluded.extend(cached.underperforming.iter().cloned())
+        }
+
+        debug!("{} mixnodes are going to be excluded", excluded.len());
+        excluded
+    }
+
+    /// Retrieves the identities of all the gateways that must not be chosen by the client.
+    /// If the performance of the nodes couldn't have been obtained, only the explicitly ignored
+    /// nodes are going to be excluded.
+    pub(crate) async fn excluded_gateways(&self, client: &NymApiClient) -> HashSet<String> {
+        let mut excluded = self.ignored_nodes.clone();
+        if self.minimum_performance == 0 {
+            return excluded;
+        }
+
+        match client.get_cached_gateways_detailed().await {
+            Ok(gateways) => excluded.extend(
+                gateways
+                    .iter()
+                    .filter(|gateway| !self.is_performant(&gateway.node_performance))
+                    .map(|gateway| gateway.identity().clone()),
+            ),
+            Err(err) => warn!("failed to get the performance of the gateways - {err}"),
+        }
+
+        debug!("{} gateways are going to be excluded", excluded.len());
+        excluded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nym_mixnet_contract_common::Percent;
+
+    fn performance(last_24h: u64) -> NodePerformance {
+        NodePerformance {
+            last_24h: Percent::from_percentage_value(last_24h).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn performance_threshold_is_inclusive() {
+        let filter = NodeFilter::new(80, Vec::new());
+        assert!(filter.is_performant(&performance(80)));
+        assert!(filter.is_performant(&performance(100)));
+        assert!(!filter.is_performant(&performance(79)));
+
+        assert!(NodeFilter::default().is_performant(&performance(0)));
+    }
+
+    #[test]
+    fn mixnode_performance_is_only_refreshed_when_needed() {
+        let mut filter = NodeFilter::new(80, Vec::new());
+        let now = get_time_now();
+        assert!(filter.should_refresh_mixnode_performance(false, now));
+
+        filter.mixnode_performance = Some(CachedPerformance {
+            retrieved_at: now,
+            underperforming: HashSet::new(),
+        });
+        assert!(!filter.should_refresh_mixnode_performance(false, now));
+        assert!(filter.should_refresh_mixnode_performance(true, now));
+        assert!(filter
+            .should_refresh_mixnode_performance(false, now + MIXNODE_PERFORMANCE_REFRESH_RATE));
+    }
+}
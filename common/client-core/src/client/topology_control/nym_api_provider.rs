@@ -1,34 +1,71 @@
 // Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::client::helpers::{get_time_now, Instant};
+use crate::client::topology_control::node_filter::NodeFilter;
 use async_trait::async_trait;
-use log::{error, warn};
+use log::{debug, error, warn};
+use nym_mixnet_contract_common::families::FamilyHead;
+use nym_mixnet_contract_common::{GatewayBond, IdentityKey, MixId, MixNodeBond};
 use nym_topology::provider_trait::TopologyProvider;
-use nym_topology::{nym_topology_from_detailed, NymTopology, NymTopologyError};
+use nym_topology::{nym_topology_from_bonds, NymTopology, NymTopologyError};
+use nym_validator_client::models::TopologyDiffResponse;
+use nym_validator_client::ValidatorClientError;
 use rand::prelude::SliceRandom;
 use rand::thread_rng;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 use url::Url;
 
+// the families rarely change, so they're only retrieved again once the bonded mixnodes change
+// or, to eventually pick up the changes to the existing families, every so often
+const MIXNODE_FAMILIES_REFRESH_RATE: Duration = Duration::from_secs(60 * 60);
+
 pub(crate) struct NymApiTopologyProvider {
     validator_client: nym_validator_client::client::NymApiClient,
     nym_api_urls: Vec<Url>,
 
     client_version: String,
     currently_used_api: usize,
+    node_filter: NodeFilter,
+
+    /// Version of the topology, as reported by the currently used nym API, that we have applied
+    /// to our local view of the network.
+    topology_version: Option<u64>,
+    mixnodes: HashMap<MixId, MixNodeBond>,
+    gateways: HashMap<IdentityKey, GatewayBond>,
+
+    /// Ids of the mixnodes that were bonded as of the last topology refresh.
+    bonded_mixnodes: HashSet<MixId>,
+
+    /// The last retrieved families of the mixnodes alongside the time of their retrieval.
+    mixnode_families: Option<(Instant, HashMap<IdentityKey, FamilyHead>)>,
 }
 
 impl NymApiTopologyProvider {
-    pub(crate) fn new(mut nym_api_urls: Vec<Url>, client_version: String) -> Self {
+    pub(crate) fn new(
+        mut nym_api_urls: Vec<Url>,
+        client_version: String,
+        proxy: Option<Url>,
+        node_filter: NodeFilter,
+    ) -> Result<Self, ValidatorClientError> {
         nym_api_urls.shuffle(&mut thread_rng());
 
-        NymApiTopologyProvider {
-            validator_client: nym_validator_client::client::NymApiClient::new(
+        Ok(NymApiTopologyProvider {
+            validator_client: nym_validator_client::client::NymApiClient::new_with_proxy(
                 nym_api_urls[0].clone(),
-            ),
+                proxy,
+            )?,
             nym_api_urls,
             client_version,
             currently_used_api: 0,
-        }
+            node_filter,
+            topology_version: None,
+            mixnodes: HashMap::new(),
+            gateways: HashMap::new(),
+            bonded_mixnodes: HashSet::new(),
+            mixnode_families: None,
+        })
     }
 
     fn use_next_nym_api(&mut self) {
@@ -39,7 +76,131 @@ impl NymApiTopologyProvider {
 
         self.currently_used_api = (self.currently_used_api + 1) % self.nym_api_urls.len();
         self.validator_client
-            .change_nym_api(self.nym_api_urls[self.currently_used_api].clone())
+            .change_nym_api(self.nym_api_urls[self.currently_used_api].clone());
+
+        // topology versions are specific to a particular nym API
+        self.reset_known_topology()
+    }
+
+    fn reset_known_topology(&mut self) {
+        self.topology_version = None;
+        self.mixnodes.clear();
+        self.gateways.clear();
+    }
+
+    fn apply_topology_diff(&mut self, diff: TopologyDiffResponse) {
+        if diff.full {
+            self.mixnodes.clear();
+            self.gateways.clear();
+        }
+
+        for mix_id in diff.removed_mixnodes {
+            self.mixnodes.remove(&mix_id);
+        }
+        for identity in diff.removed_gateways {
+            self.gateways.remove(&identity);
+        }
+        for mixnode in diff.updated_mixnodes {
+            self.mixnodes.insert(mixnode.mix_id, mixnode);
+        }
+        for gateway in diff.updated_gateways {
+            self.gateways.insert(gateway.identity().clone(), gateway);
+        }
+
+        self.topology_version = Some(diff.version);
+    }
+
+    async fn get_full_topology_bonds(&self) -> Option<(Vec<MixNodeBond>, Vec<GatewayBond>)> {
+        let mixnodes = match self.validator_client.get_cached_active_mixnodes().await {
+            Err(err) => {
+                error!("failed to get network mixnodes - {err}");
+                return None;
+            }
+            Ok(mixes) => mixes,
+        };
+
+        let gateways = match self.validator_client.get_cached_gateways().await {
+            Err(err) => {
+                error!("failed to get network gateways - {err}");
+                return None;
+            }
+            Ok(gateways) => gateways,
+        };
+
+        let mixnodes = mixnodes
+            .into_iter()
+            .map(|details| details.bond_information)
+            .collect();
+        Some((mixnodes, gateways))
+    }
+
+    /// Attempts to only retrieve the changes to the topology since the last time we've queried
+    /// the nym API. If that's not possible, for example because the API does not yet support
+    /// topology diffs, the entire topology is retrieved instead.
+    async fn get_topology_bonds(&mut self) -> Option<(Vec<MixNodeBond>, Vec<GatewayBond>)> {
+        match self
+            .validator_client
+            .get_cached_topology_diff(self.topology_version)
+            .await
+        {
+            Ok(diff) => {
+                debug!(
+                    "received topology diff (full: {}) {:?} -> {}",
+                    diff.full, self.topology_version, diff.version
+                );
+                self.apply_topology_diff(diff);
+                Some((
+                    self.mixnodes.values().cloned().collect(),
+                    self.gateways.values().cloned().collect(),
+                ))
+            }
+            Err(err) => {
+                warn!("failed to get topology diff - {err}. Going to retrieve the full topology instead");
+                self.reset_known_topology();
+                self.get_full_topology_bonds().await
+            }
+        }
+    }
+
+    /// Remembers the currently bonded mixnodes, returning whether they are any different
+    /// from the ones seen during the previous topology refresh.
+    fn update_bonded_mixnodes(&mut self, mixnodes: &[MixNodeBond]) -> bool {
+        let bonded_mixnodes = mixnodes
+            .iter()
+            .map(|mixnode| mixnode.mix_id)
+            .collect::<HashSet<_>>();
+        if bonded_mixnodes == self.bonded_mixnodes {
+            return false;
+        }
+        self.bonded_mixnodes = bonded_mixnodes;
+        true
+    }
+
+    /// Retrieves the families the mixnodes have declared on chain, so that routes could avoid
+    /// going through multiple nodes of the same operator.
+    /// The previously retrieved families are kept unless the set of the bonded mixnodes
+    /// has changed or they got stale.
+    async fn refresh_mixnode_families(&mut self, bonded_set_changed: bool) {
+        let now = get_time_now();
+        if let Some((retrieved_at, _)) = &self.mixnode_families {
+            if !bonded_set_changed && *retrieved_at + MIXNODE_FAMILIES_REFRESH_RATE > now {
+                return;
+            }
+        }
+
+        match self.validator_client.get_cached_mixnode_families().await {
+            Ok(families) => {
+                let families = families
+                    .into_iter()
+                    .map(|membership| (membership.identity_key, membership.family_head))
+                    .collect();
+                self.mixnode_families = Some((now, families))
+            }
+            Err(err) => {
+                // this is not critical, the topology is still usable without (up to date) family information
+                warn!("failed to get mixnode families - {err}");
+            }
+        }
     }
 
     /// Verifies whether nodes a reasonably distributed among all mix layers.
@@ -60,25 +221,43 @@ impl NymApiTopologyProvider {
         active_topology.ensure_even_layer_distribution(lower_threshold, upper_threshold)
     }
 
-    async fn get_current_compatible_topology(&mut self) -> Option<NymTopology> {
-        let mixnodes = match self.validator_client.get_cached_active_mixnodes().await {
-            Err(err) => {
-                error!("failed to get network mixnodes - {err}");
-                return None;
-            }
-            Ok(mixes) => mixes,
-        };
+    /// Removes all the mixnodes that the user explicitly doesn't want to use
+    /// or whose performance is below the configured threshold.
+    ///
+    /// Note that the gateways are deliberately left intact as otherwise it wouldn't be possible
+    /// to send packets to the clients connected to them (including ourselves).
+    async fn filter_excluded_mixnodes(
+        &mut self,
+        mixnodes: Vec<MixNodeBond>,
+        bonded_set_changed: bool,
+    ) -> Vec<MixNodeBond> {
+        let excluded = self
+            .node_filter
+            .excluded_mixnodes(&self.validator_client, bonded_set_changed)
+            .await;
+        if excluded.is_empty() {
+            return mixnodes;
+        }
 
-        let gateways = match self.validator_client.get_cached_gateways().await {
-            Err(err) => {
-                error!("failed to get network gateways - {err}");
-                return None;
-            }
-            Ok(gateways) => gateways,
-        };
+        mixnodes
+            .into_iter()
+            .filter(|mixnode| !excluded.contains(mixnode.identity()))
+            .collect()
+    }
+
+    async fn get_current_compatible_topology(&mut self) -> Option<NymTopology> {
+        let (mixnodes, gateways) = self.get_topology_bonds().await?;
+        let bonded_set_changed = self.update_bonded_mixnodes(&mixnodes);
+        let mixnodes = self
+            .filter_excluded_mixnodes(mixnodes, bonded_set_changed)
+            .await;
 
-        let topology = nym_topology_from_detailed(mixnodes, gateways)
-            .filter_system_version(&self.client_version);
+        let mut topology =
+            nym_topology_from_bonds(mixnodes, gateways).filter_system_version(&self.client_version);
+        self.refresh_mixnode_families(bonded_set_changed).await;
+        if let Some((_, families)) = &self.mixnode_families {
+            topology.set_mix_families(families);
+        }
 
         if let Err(err) = self.check_layer_distribution(&topology) {
             warn!("The current filtered active topology has extremely skewed layer distribution. It cannot be used: {err}");
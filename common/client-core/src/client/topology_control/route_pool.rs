@@ -0,0 +1,78 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::client::topology_control::TopologyAccessor;
+use crate::spawn_future;
+use futures::StreamExt;
+use log::*;
+use nym_sphinx::params::DEFAULT_NUM_MIX_HOPS;
+use rand::rngs::OsRng;
+use std::time::Duration;
+
+// TODO: move it to config later
+const REPLENISH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Keeps the pool of precomputed routes of the current topology topped up, so that the
+/// packet preparation would only have to sample the routes itself whenever the pool ran dry.
+pub struct RoutePoolReplenisher {
+    topology_accessor: TopologyAccessor,
+    pool_size: usize,
+    rng: OsRng,
+}
+
+impl RoutePoolReplenisher {
+    pub fn new(topology_accessor: TopologyAccessor, pool_size: usize) -> Self {
+        RoutePoolReplenisher {
+            topology_accessor,
+            pool_size,
+            rng: OsRng,
+        }
+    }
+
+    async fn replenish(&mut self) {
+        let permit = self.topology_accessor.get_read_permit().await;
+        let Some(topology) = permit.as_ref() else {
+            return;
+        };
+        if topology
+            .ensure_can_construct_path_through(DEFAULT_NUM_MIX_HOPS)
+            .is_err()
+        {
+            return;
+        }
+
+        match topology.replenish_route_pool(&mut self.rng, DEFAULT_NUM_MIX_HOPS, self.pool_size) {
+            Ok(0) => (),
+            Ok(added) => trace!("added {added} routes to the route pool"),
+            Err(err) => debug!("failed to replenish the route pool - {err}"),
+        }
+    }
+
+    pub fn start_with_shutdown(mut self, mut shutdown: nym_task::TaskClient) {
+        spawn_future(async move {
+            debug!("Started RoutePoolReplenisher with graceful shutdown support");
+
+            #[cfg(not(target_arch = "wasm32"))]
+            let mut interval = tokio_stream::wrappers::IntervalStream::new(tokio::time::interval(
+                REPLENISH_INTERVAL,
+            ));
+
+            #[cfg(target_arch = "wasm32")]
+            let mut interval =
+                gloo_timers::future::IntervalStream::new(REPLENISH_INTERVAL.as_millis() as u32);
+
+            while !shutdown.is_shutdown() {
+                tokio::select! {
+                    _ = interval.next() => {
+                        self.replenish().await;
+                    },
+                    _ = shutdown.recv() => {
+                        log::trace!("RoutePoolReplenisher: Received shutdown");
+                    },
+                }
+            }
+            shutdown.recv_timeout().await;
+            log::debug!("RoutePoolReplenisher: Exiting");
+        })
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::client::topology_control::TopologyAccessor;
+use crate::spawn_future;
+use futures::StreamExt;
+use log::*;
+use nym_gateway_client::UnreachableHopReceiver;
+use std::net::SocketAddr;
+
+/// Excludes the mixnodes our gateway could not forward the packets to from the current topology,
+/// so that the retransmissions and any subsequent packets would be routed through different nodes.
+/// The nodes are only excluded until the next topology refresh, by which time they might be back up.
+pub struct UnreachableHopsListener {
+    topology_accessor: TopologyAccessor,
+    unreachable_hops: UnreachableHopReceiver,
+}
+
+impl UnreachableHopsListener {
+    pub fn new(
+        topology_accessor: TopologyAccessor,
+        unreachable_hops: UnreachableHopReceiver,
+    ) -> Self {
+        UnreachableHopsListener {
+            topology_accessor,
+            unreachable_hops,
+        }
+    }
+
+    async fn exclude(&self, mix_host: SocketAddr) {
+        if self.topology_accessor.exclude_mixnode(mix_host).await {
+            info!("{mix_host} is unreachable - routing the packets around it until the next topology refresh");
+        } else {
+            debug!("{mix_host} is unreachable, but it can't be excluded from the topology");
+        }
+    }
+
+    pub fn start_with_shutdown(mut self, mut shutdown: nym_task::TaskClient) {
+        spawn_future(async move {
+            debug!("Started UnreachableHopsListener with graceful shutdown support");
+
+            while !shutdown.is_shutdown() {
+                tokio::select! {
+                    mix_host = self.unreachable_hops.next() => match mix_host {
+                        Some(mix_host) => self.exclude(mix_host).await,
+                        None => {
+                            log::trace!("UnreachableHopsListener: Stopping since channel closed");
+                            break;
+                        }
+                    },
+                    _ = shutdown.recv() => {
+                        log::trace!("UnreachableHopsListener: Received shutdown");
+                    },
+                }
+            }
+            shutdown.recv_timeout().await;
+            log::debug!("UnreachableHopsListener: Exiting");
+        })
+    }
+}
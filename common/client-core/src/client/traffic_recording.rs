@@ -0,0 +1,269 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Development tooling for recording the sequence of packets produced by a client session
+//! and replaying it later on against a (test) network, so that the performance of the traffic
+//! controller could be investigated in a reproducible manner.
+//!
+//! The recording is stored as json lines, one line per batch of packets given to the
+//! `MixTrafficController`.
+
+use crate::client::mix_traffic::BatchMixMessageSender;
+use crate::client::topology_control::TopologyAccessor;
+use crate::spawn_future;
+use log::*;
+use nym_sphinx::acknowledgements::AckKey;
+use nym_sphinx::addressing::clients::Recipient;
+use nym_sphinx::cover::generate_loop_cover_packet;
+use nym_sphinx::forwarding::packet::MixPacket;
+use nym_sphinx::params::PacketSize;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecordedPacket {
+    /// Address of the first hop of the packet.
+    pub next_hop: String,
+
+    /// Size of the sphinx packet, in bytes.
+    pub size: usize,
+
+    /// Whether the packet has been sent in the vpn mode.
+    pub vpn: bool,
+}
+
+impl<'a> From<&'a MixPacket> for RecordedPacket {
+    fn from(packet: &'a MixPacket) -> Self {
+        RecordedPacket {
+            next_hop: packet.next_hop().to_string(),
+            size: packet.sphinx_packet().len(),
+            vpn: !packet.packet_mode().is_mix(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecordedBatch {
+    /// Time, in milliseconds, since the start of the recording at which the batch was sent.
+    pub offset_ms: u64,
+
+    pub packets: Vec<RecordedPacket>,
+}
+
+/// Writes every batch of packets sent by the `MixTrafficController` into the specified file.
+pub struct TrafficRecorder {
+    started_at: Instant,
+    output: BufWriter<File>,
+}
+
+impl TrafficRecorder {
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(TrafficRecorder {
+            started_at: Instant::now(),
+            output: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    pub fn record(&mut self, packets: &[MixPacket]) {
+        let batch = RecordedBatch {
+            offset_ms: self.started_at.elapsed().as_millis() as u64,
+            packets: packets.iter().map(Into::into).collect(),
+        };
+
+        if let Err(err) = serde_json::to_writer(&mut self.output, &batch)
+            .map_err(io::Error::from)
+            .and_then(|_| self.output.write_all(b"\n"))
+        {
+            warn!("failed to record the sent packets - {err}")
+        }
+    }
+}
+
+impl Drop for TrafficRecorder {
+    fn drop(&mut self) {
+        if let Err(err) = self.output.flush() {
+            warn!("failed to flush the traffic recording - {err}")
+        }
+    }
+}
+
+/// Loads the batches previously saved by the `TrafficRecorder`.
+pub fn load_recording<P: AsRef<Path>>(path: P) -> io::Result<Vec<RecordedBatch>> {
+    BufReader::new(File::open(path)?)
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+/// Replays a recorded session by sending, with the original timing, batches of loop cover packets
+/// of the same sizes as the recorded ones.
+/// Note that the routes are sampled from the current topology, so the recorded first hops
+/// are not preserved.
+pub struct TrafficReplayer {
+    batches: Vec<RecordedBatch>,
+
+    /// Key used to encrypt and decrypt content of an ACK packet.
+    ack_key: Arc<AckKey>,
+
+    /// Average delay an acknowledgement packet is going to get delay at a single mixnode.
+    average_ack_delay: Duration,
+
+    /// Average delay a data packet is going to get delay at a single mixnode.
+    average_packet_delay: Duration,
+
+    /// Channel used for sending prepared sphinx packets to `MixTrafficController`.
+    mix_tx: BatchMixMessageSender,
+
+    /// Represents full address of this client.
+    our_full_destination: Recipient,
+
+    /// Instance of a cryptographically secure random number generator.
+    rng: OsRng,
+
+    /// Accessor to the common instance of network topology.
+    topology_access: TopologyAccessor,
+}
+
+impl TrafficReplayer {
+    pub fn new(
+        batches: Vec<RecordedBatch>,
+        ack_key: Arc<AckKey>,
+        average_ack_delay: Duration,
+        average_packet_delay: Duration,
+        mix_tx: BatchMixMessageSender,
+        our_full_destination: Recipient,
+        topology_access: TopologyAccessor,
+    ) -> Self {
+        TrafficReplayer {
+            batches,
+            ack_key,
+            average_ack_delay,
+            average_packet_delay,
+            mix_tx,
+            our_full_destination,
+            rng: OsRng,
+            topology_access,
+        }
+    }
+
+    async fn prepare_batch(&mut self, batch: &RecordedBatch) -> Option<Vec<MixPacket>> {
+        let topology_permit = self.topology_access.get_read_permit().await;
+        let topology_ref = match topology_permit.try_get_valid_topology_ref(
+            &self.our_full_destination,
+            Some(&self.our_full_destination),
+        ) {
+            Ok(topology) => topology,
+            Err(err) => {
+                warn!("can't replay the batch sent at {}ms as the current topology seem to be invalid - {err}", batch.offset_ms);
+                return None;
+            }
+        };
+
+        let mut packets = Vec::with_capacity(batch.packets.len());
+        for recorded in &batch.packets {
+            let packet_size = match PacketSize::get_type(recorded.size) {
+                Ok(packet_size) => packet_size,
+                Err(err) => {
+                    warn!("skipping recorded packet - {err}");
+                    continue;
+                }
+            };
+            match generate_loop_cover_packet(
+                &mut self.rng,
+                topology_ref,
+                &self.ack_key,
+                &self.our_full_destination,
+                self.average_ack_delay,
+                self.average_packet_delay,
+                packet_size,
+            ) {
+                Ok(packet) => packets.push(packet),
+                Err(err) => warn!("failed to generate replayed packet - {err}"),
+            }
+        }
+
+        if packets.is_empty() {
+            None
+        } else {
+            Some(packets)
+        }
+    }
+
+    async fn replay(&mut self) {
+        let started_at = Instant::now();
+        let batches = std::mem::take(&mut self.batches);
+        info!("replaying {} recorded batches of packets", batches.len());
+
+        for batch in &batches {
+            tokio::time::sleep_until(started_at + Duration::from_millis(batch.offset_ms)).await;
+            if let Some(packets) = self.prepare_batch(batch).await {
+                if self.mix_tx.send(packets).await.is_err() {
+                    warn!("failed to send replayed packets - channel closed");
+                    return;
+                }
+            }
+        }
+        info!(
+            "finished replaying the recorded traffic after {:?}",
+            started_at.elapsed()
+        );
+    }
+
+    pub fn start_with_shutdown(mut self, mut shutdown: nym_task::TaskClient) {
+        spawn_future(async move {
+            debug!("Started TrafficReplayer with graceful shutdown support");
+
+            tokio::select! {
+                biased;
+                _ = shutdown.recv() => {
+                    log::trace!("TrafficReplayer: Received shutdown");
+                }
+                _ = self.replay() => (),
+            }
+            shutdown.recv_timeout().await;
+            log::debug!("TrafficReplayer: Exiting");
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_can_be_loaded() {
+        let path = std::env::temp_dir().join(format!("traffic-recording-{}", std::process::id()));
+        let batches = vec![
+            RecordedBatch {
+                offset_ms: 0,
+                packets: vec![RecordedPacket {
+                    next_hop: "1.2.3.4:1789".to_string(),
+                    size: PacketSize::RegularPacket.size(),
+                    vpn: false,
+                }],
+            },
+            RecordedBatch {
+                offset_ms: 42,
+                packets: vec![],
+            },
+        ];
+
+        let mut content = String::new();
+        for batch in &batches {
+            content.push_str(&serde_json::to_string(batch).unwrap());
+            content.push('\n');
+        }
+        std::fs::write(&path, content).unwrap();
+
+        let loaded = load_recording(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, batches);
+    }
+}
@@ -55,6 +55,10 @@ impl<T> TransmissionBuffer<T> {
         self.buffer.remove(lane)
     }
 
+    pub(crate) fn into_items(self) -> impl Iterator<Item = T> {
+        self.buffer.into_values().flat_map(|entry| entry.items)
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub(crate) fn num_lanes(&self) -> usize {
         self.buffer.keys().count()
@@ -1,9 +1,10 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use nym_config::defaults::NymNetworkDetails;
+use nym_config::defaults::{NymNetworkDetails, REMAINING_BANDWIDTH_THRESHOLD};
 use nym_config::{NymConfig, OptionalSet, CRED_DB_FILE_NAME};
-use nym_sphinx::params::PacketSize;
+use nym_gateway_client::deduplication::DEFAULT_DELIVERED_MESSAGES_WINDOW;
+use nym_sphinx::params::{PacketSize, PaddingPolicy};
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 use std::path::PathBuf;
@@ -22,18 +23,36 @@ pub const MISSING_VALUE: &str = "MISSING VALUE";
 const DEFAULT_ACK_WAIT_MULTIPLIER: f64 = 1.5;
 
 const DEFAULT_ACK_WAIT_ADDITION: Duration = Duration::from_millis(1_500);
+const DEFAULT_ACK_RTT_SMOOTHING: f64 = 0.125;
+const DEFAULT_ACK_RTT_VARIANCE_SMOOTHING: f64 = 0.25;
+const DEFAULT_ACK_RTT_VARIANCE_MULTIPLIER: f64 = 4.0;
+const DEFAULT_MINIMUM_ACK_WAIT_ADDITION: Duration = Duration::from_millis(200);
+const DEFAULT_MAXIMUM_ACK_WAIT_ADDITION: Duration = Duration::from_secs(30);
+const DEFAULT_MAXIMUM_DEAD_LETTERS: usize = 64;
 const DEFAULT_LOOP_COVER_STREAM_AVERAGE_DELAY: Duration = Duration::from_millis(200);
 const DEFAULT_MESSAGE_STREAM_AVERAGE_DELAY: Duration = Duration::from_millis(20);
 const DEFAULT_AVERAGE_PACKET_DELAY: Duration = Duration::from_millis(50);
 const DEFAULT_TOPOLOGY_REFRESH_RATE: Duration = Duration::from_secs(5 * 60); // every 5min
 const DEFAULT_TOPOLOGY_RESOLUTION_TIMEOUT: Duration = Duration::from_millis(5_000);
+// by default do not exclude any nodes based on their performance
+const DEFAULT_MINIMUM_NODE_PERFORMANCE: u8 = 0;
+const DEFAULT_ROUTE_POOL_SIZE: usize = 1000;
+const DEFAULT_TOPOLOGY_ANOMALY_KEY_CHANGE_THRESHOLD: f64 = 0.5;
+const DEFAULT_TOPOLOGY_ANOMALY_MINIMUM_LAYER_SIZE: usize = 3;
 // Set this to a high value for now, so that we don't risk sporadic timeouts that might cause
 // bought bandwidth tokens to not have time to be spent; Once we remove the gateway from the
 // bandwidth bridging protocol, we can come back to a smaller timeout value
 const DEFAULT_GATEWAY_RESPONSE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_BANDWIDTH_TOP_UP_THRESHOLD: i64 = REMAINING_BANDWIDTH_THRESHOLD;
+const DEFAULT_BANDWIDTH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_BANDWIDTH_WARNING_PERIOD: Duration = Duration::from_secs(10 * 60);
 
 const DEFAULT_COVER_TRAFFIC_PRIMARY_SIZE_RATIO: f64 = 0.70;
 
+// hibernation related:
+const DEFAULT_HIBERNATION_TOPOLOGY_REFRESH_RATE: Duration = Duration::from_secs(30 * 60); // every 30min
+const DEFAULT_HIBERNATION_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(60);
+
 // reply-surbs related:
 
 // define when to request
@@ -57,6 +76,8 @@ const DEFAULT_MAXIMUM_REPLY_SURB_AGE: Duration = Duration::from_secs(12 * 60 * 6
 // 24 hours
 const DEFAULT_MAXIMUM_REPLY_KEY_AGE: Duration = Duration::from_secs(24 * 60 * 60);
 
+const DEFAULT_MAXIMUM_TRACKED_RECIPIENTS: usize = 1000;
+
 pub fn missing_string_value() -> String {
     MISSING_VALUE.to_string()
 }
@@ -73,6 +94,10 @@ pub struct Config<T> {
     #[serde(default)]
     logging: Logging,
     #[serde(default)]
+    proxy: Proxy,
+    #[serde(default)]
+    storage_encryption: StorageEncryption,
+    #[serde(default)]
     debug: DebugConfig,
 }
 
@@ -238,6 +263,31 @@ impl<T> Config<T> {
         self.debug.traffic.disable_main_poisson_packet_distribution = true;
     }
 
+    pub fn with_directory_proxy(mut self, proxy: Option<Url>) -> Self {
+        self.proxy.directory = proxy;
+        self
+    }
+
+    pub fn with_nym_api_proxy(mut self, proxy: Option<Url>) -> Self {
+        self.proxy.nym_api = proxy;
+        self
+    }
+
+    pub fn with_nyxd_proxy(mut self, proxy: Option<Url>) -> Self {
+        self.proxy.nyxd = proxy;
+        self
+    }
+
+    pub fn with_storage_encryption(mut self, enabled: bool) -> Self {
+        self.storage_encryption.enabled = enabled;
+        self
+    }
+
+    pub fn with_storage_passphrase_source(mut self, source: PassphraseSource) -> Self {
+        self.storage_encryption.passphrase_source = source;
+        self
+    }
+
     pub fn set_custom_version(&mut self, version: &str) {
         self.client.version = version.to_string();
     }
@@ -286,6 +336,10 @@ impl<T> Config<T> {
         self.client.nym_api_urls.clone()
     }
 
+    pub fn get_ignored_nodes(&self) -> Vec<String> {
+        self.client.ignored_nodes.clone()
+    }
+
     pub fn get_gateway_id(&self) -> String {
         self.client.gateway_endpoint.gateway_id.clone()
     }
@@ -298,6 +352,26 @@ impl<T> Config<T> {
         self.client.gateway_endpoint.gateway_listener.clone()
     }
 
+    pub fn get_directory_proxy(&self) -> Option<Url> {
+        self.proxy.directory.clone()
+    }
+
+    pub fn get_nym_api_proxy(&self) -> Option<Url> {
+        self.proxy.nym_api.clone()
+    }
+
+    pub fn get_nyxd_proxy(&self) -> Option<Url> {
+        self.proxy.nyxd.clone()
+    }
+
+    pub fn get_storage_encryption_enabled(&self) -> bool {
+        self.storage_encryption.enabled
+    }
+
+    pub fn get_storage_passphrase_source(&self) -> PassphraseSource {
+        self.storage_encryption.passphrase_source
+    }
+
     pub fn get_gateway_endpoint_config(&self) -> &GatewayEndpointConfig {
         &self.client.gateway_endpoint
     }
@@ -335,6 +409,42 @@ impl<T> Config<T> {
         self.debug.acknowledgements.ack_wait_addition
     }
 
+    pub fn get_adaptive_ack_timeouts(&self) -> bool {
+        self.debug.acknowledgements.adaptive_ack_timeouts
+    }
+
+    pub fn get_ack_rtt_smoothing(&self) -> f64 {
+        self.debug.acknowledgements.ack_rtt_smoothing
+    }
+
+    pub fn get_ack_rtt_variance_smoothing(&self) -> f64 {
+        self.debug.acknowledgements.ack_rtt_variance_smoothing
+    }
+
+    pub fn get_ack_rtt_variance_multiplier(&self) -> f64 {
+        self.debug.acknowledgements.ack_rtt_variance_multiplier
+    }
+
+    pub fn get_minimum_ack_wait_addition(&self) -> Duration {
+        self.debug.acknowledgements.minimum_ack_wait_addition
+    }
+
+    pub fn get_maximum_ack_wait_addition(&self) -> Duration {
+        self.debug.acknowledgements.maximum_ack_wait_addition
+    }
+
+    pub fn get_maximum_retransmissions(&self) -> Option<u32> {
+        self.debug.acknowledgements.maximum_retransmissions
+    }
+
+    pub fn get_maximum_retransmission_period(&self) -> Option<Duration> {
+        self.debug.acknowledgements.maximum_retransmission_period
+    }
+
+    pub fn get_maximum_dead_letters(&self) -> usize {
+        self.debug.acknowledgements.maximum_dead_letters
+    }
+
     pub fn get_loop_cover_traffic_average_delay(&self) -> Duration {
         self.debug.cover_traffic.loop_cover_traffic_average_delay
     }
@@ -347,6 +457,28 @@ impl<T> Config<T> {
         self.debug.gateway_connection.gateway_response_timeout
     }
 
+    pub fn get_delivered_messages_dedup_window(&self) -> usize {
+        self.debug
+            .gateway_connection
+            .delivered_messages_dedup_window
+    }
+
+    pub fn get_bandwidth_top_up_threshold(&self) -> i64 {
+        self.debug.gateway_connection.bandwidth_top_up_threshold
+    }
+
+    pub fn get_bandwidth_check_interval(&self) -> Duration {
+        self.debug.gateway_connection.bandwidth_check_interval
+    }
+
+    pub fn get_manual_bandwidth_top_up(&self) -> bool {
+        self.debug.gateway_connection.manual_bandwidth_top_up
+    }
+
+    pub fn get_bandwidth_warning_period(&self) -> Duration {
+        self.debug.gateway_connection.bandwidth_warning_period
+    }
+
     pub fn get_topology_refresh_rate(&self) -> Duration {
         self.debug.topology.topology_refresh_rate
     }
@@ -355,6 +487,18 @@ impl<T> Config<T> {
         self.debug.topology.topology_resolution_timeout
     }
 
+    pub fn get_hibernation_topology_refresh_rate(&self) -> Duration {
+        self.debug.hibernation.topology_refresh_rate
+    }
+
+    pub fn get_hibernation_keepalive_interval(&self) -> Duration {
+        self.debug.hibernation.keepalive_interval
+    }
+
+    pub fn get_minimum_node_performance(&self) -> u8 {
+        self.debug.topology.minimum_node_performance
+    }
+
     pub fn get_disabled_loop_cover_traffic_stream(&self) -> bool {
         self.debug.cover_traffic.disable_loop_cover_traffic_stream
     }
@@ -363,6 +507,14 @@ impl<T> Config<T> {
         self.debug.traffic.disable_main_poisson_packet_distribution
     }
 
+    pub fn get_attach_message_ordering(&self) -> bool {
+        self.debug.traffic.attach_message_ordering
+    }
+
+    pub fn get_disclose_message_expiry(&self) -> bool {
+        self.debug.traffic.disclose_message_expiry
+    }
+
     pub fn get_minimum_reply_surb_storage_threshold(&self) -> usize {
         self.debug.reply_surbs.minimum_reply_surb_storage_threshold
     }
@@ -404,6 +556,10 @@ impl<T> Config<T> {
     pub fn get_maximum_reply_key_age(&self) -> Duration {
         self.debug.reply_surbs.maximum_reply_key_age
     }
+
+    pub fn get_maximum_tracked_recipients(&self) -> usize {
+        self.debug.reply_surbs.maximum_tracked_recipients
+    }
 }
 
 impl<T: NymConfig> Default for Config<T> {
@@ -411,6 +567,8 @@ impl<T: NymConfig> Default for Config<T> {
         Config {
             client: Client::<T>::default(),
             logging: Default::default(),
+            proxy: Default::default(),
+            storage_encryption: Default::default(),
             debug: Default::default(),
         }
     }
@@ -479,6 +637,11 @@ pub struct Client<T> {
     #[serde(alias = "validator_api_urls")]
     pub nym_api_urls: Vec<Url>,
 
+    /// Identity keys of the mixnodes and gateways that should never be used in any of the routes
+    /// of this client nor be chosen as its gateway.
+    #[serde(default)]
+    pub ignored_nodes: Vec<String>,
+
     /// Path to file containing private identity key.
     pub private_identity_key_file: PathBuf,
 
@@ -545,6 +708,7 @@ impl<T: NymConfig> Default for Client<T> {
             disabled_credentials_mode: true,
             nyxd_urls,
             nym_api_urls,
+            ignored_nodes: Vec::new(),
             private_identity_key_file: Default::default(),
             public_identity_key_file: Default::default(),
             private_encryption_key_file: Default::default(),
@@ -598,6 +762,51 @@ impl<T: NymConfig> Client<T> {
 #[serde(deny_unknown_fields)]
 pub struct Logging {}
 
+/// Proxies through which the HTTP(S) requests of the particular classes of endpoints are routed,
+/// for the networks where they can't be reached directly.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Proxy {
+    /// Proxy used for retrieving the network topology and the list of available gateways.
+    /// Either HTTP(S) or SOCKS5 one. Use the `socks5h` scheme for the `.onion` endpoints.
+    pub directory: Option<Url>,
+
+    /// Proxy used for all the other requests sent to the nym API, such as the ones issuing
+    /// bandwidth credentials. Either HTTP(S) or SOCKS5 one.
+    pub nym_api: Option<Url>,
+
+    /// Proxy used for communicating with the nyxd validators. Only HTTP(S) proxies are supported.
+    pub nyxd: Option<Url>,
+}
+
+/// Encryption of the data kept in the client storages, i.e. the bandwidth credentials,
+/// the received reply surbs, the sent reply keys and the recipients of the used sender tags.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageEncryption {
+    /// Indicates whether the stored data should be encrypted with a key derived from a passphrase.
+    pub enabled: bool,
+
+    /// Specifies where the passphrase is obtained from on startup.
+    pub passphrase_source: PassphraseSource,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PassphraseSource {
+    /// The passphrase is read from the `NYM_CLIENT_STORAGE_PASSPHRASE` environment variable
+    /// or, if it's not set, asked for on the terminal.
+    #[default]
+    Prompt,
+
+    /// The passphrase is kept in the keychain of the operating system. It's generated on the first run.
+    Keychain,
+
+    /// The client waits for the passphrase to be supplied through its control API
+    /// (such as the websocket of the native client), for the headless deployments.
+    ControlApi,
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
 pub struct Traffic {
@@ -627,6 +836,25 @@ pub struct Traffic {
     /// Note that its use decreases overall anonymity.
     /// Do not set it it unless you understand the consequences of that change.
     pub secondary_packet_size: Option<PacketSize>,
+
+    /// Specifies how the packet size for each message is chosen out of the sizes supported
+    /// by the network. Note that any policy other than the default one decreases overall anonymity.
+    /// Do not change it unless you understand the consequences of that change.
+    pub padding_policy: PaddingPolicy,
+
+    /// Controls whether the sent messages carry their sequence numbers and coarse timestamps,
+    /// so that the recipients could present them in order and detect large gaps.
+    /// Note that the recipients running older versions of the software won't be able to
+    /// understand such messages.
+    pub attach_message_ordering: bool,
+
+    /// Controls whether the expiry of the messages sent with one is attached to their packets,
+    /// so that the recipient's gateway could drop them once they're no longer relevant.
+    /// Otherwise the expiry is only respected by this client, i.e. the messages are not
+    /// retransmitted past that point.
+    /// Note that it allows the recipient's gateway to link together the fragments of the message
+    /// and to roughly estimate when it was sent.
+    pub disclose_message_expiry: bool,
 }
 
 impl Traffic {
@@ -650,6 +878,9 @@ impl Default for Traffic {
             disable_main_poisson_packet_distribution: false,
             primary_packet_size: PacketSize::RegularPacket,
             secondary_packet_size: None,
+            padding_policy: PaddingPolicy::default(),
+            attach_message_ordering: false,
+            disclose_message_expiry: false,
         }
     }
 }
@@ -688,12 +919,40 @@ pub struct GatewayConnection {
     /// before giving up on it.
     #[serde(with = "humantime_serde")]
     pub gateway_response_timeout: Duration,
+
+    /// Defines the number of the most recently received messages remembered in order to drop
+    /// the ones the gateway might redeliver after a crash or a reconnection.
+    /// Setting it to 0 disables the deduplication.
+    pub delivered_messages_dedup_window: usize,
+
+    /// Remaining bandwidth (in bytes) at the gateway below which a new credential is going to be
+    /// acquired and presented to it.
+    pub bandwidth_top_up_threshold: i64,
+
+    /// How often the remaining bandwidth at the gateway is checked.
+    #[serde(with = "humantime_serde")]
+    pub bandwidth_check_interval: Duration,
+
+    /// If enabled, the client never acquires new credentials on its own and only warns about
+    /// the bandwidth running low, so that it could be topped up manually.
+    pub manual_bandwidth_top_up: bool,
+
+    /// The client starts warning about the bandwidth running low once, at the current consumption
+    /// rate, it's expected to run out within this period. With the automatic top-ups disabled,
+    /// it's about falling below `bandwidth_top_up_threshold` instead.
+    #[serde(with = "humantime_serde")]
+    pub bandwidth_warning_period: Duration,
 }
 
 impl Default for GatewayConnection {
     fn default() -> Self {
         GatewayConnection {
             gateway_response_timeout: DEFAULT_GATEWAY_RESPONSE_TIMEOUT,
+            delivered_messages_dedup_window: DEFAULT_DELIVERED_MESSAGES_WINDOW,
+            bandwidth_top_up_threshold: DEFAULT_BANDWIDTH_TOP_UP_THRESHOLD,
+            bandwidth_check_interval: DEFAULT_BANDWIDTH_CHECK_INTERVAL,
+            manual_bandwidth_top_up: false,
+            bandwidth_warning_period: DEFAULT_BANDWIDTH_WARNING_PERIOD,
         }
     }
 }
@@ -718,6 +977,65 @@ pub struct Acknowledgements {
     /// In an ideal network with 0 latency, this value would have been 0.
     #[serde(with = "humantime_serde")]
     pub ack_wait_addition: Duration,
+
+    /// Specifies whether the time waited for the acknowledgements should be adjusted based on
+    /// the observed round-trip times rather than always following the static
+    /// `ack_wait_multiplier` and `ack_wait_addition` formula. The static formula is still used
+    /// until enough acknowledgements have been received.
+    pub adaptive_ack_timeouts: bool,
+
+    /// Weight given to every new round-trip sample when updating the smoothed estimate
+    /// of the acknowledgement latency. Only applicable with the adaptive ack timeouts.
+    pub ack_rtt_smoothing: f64,
+
+    /// Weight given to every new round-trip sample when updating the estimated variance
+    /// of the acknowledgement latency. Only applicable with the adaptive ack timeouts.
+    pub ack_rtt_variance_smoothing: f64,
+
+    /// Number of estimated deviations of the acknowledgement latency that are waited for
+    /// before the packet is retransmitted. Only applicable with the adaptive ack timeouts.
+    pub ack_rtt_variance_multiplier: f64,
+
+    /// Minimum time waited, on top of the expected delay of a packet, before it is retransmitted.
+    /// Only applicable with the adaptive ack timeouts.
+    #[serde(with = "humantime_serde")]
+    pub minimum_ack_wait_addition: Duration,
+
+    /// Maximum time waited, on top of the expected delay of a packet, before it is retransmitted.
+    /// Only applicable with the adaptive ack timeouts.
+    #[serde(with = "humantime_serde")]
+    pub maximum_ack_wait_addition: Duration,
+
+    /// Maximum number of times a data packet is going to be retransmitted before the client
+    /// gives up on it and reports the failure of its message. If unspecified, the client
+    /// will keep on retransmitting the packet until it gets acknowledged.
+    pub maximum_retransmissions: Option<u32>,
+
+    /// Maximum amount of time, since it was first sent, during which a data packet might still
+    /// get retransmitted. Once it elapses, the client gives up on the packet and reports the failure
+    /// of its message. If unspecified, there is no time limit on the retransmissions.
+    #[serde(with = "humantime_serde")]
+    pub maximum_retransmission_period: Option<Duration>,
+
+    /// Maximum number of tracked messages that could not have been delivered that are retained
+    /// so that they could be inspected and possibly resent by the application.
+    /// Once the limit is reached, the oldest messages are dropped.
+    pub maximum_dead_letters: usize,
+}
+
+impl Acknowledgements {
+    pub fn validate(&self) -> bool {
+        if !self.adaptive_ack_timeouts {
+            return true;
+        }
+        let is_weight = |weight: f64| weight > 0.0 && weight <= 1.0;
+
+        is_weight(self.ack_rtt_smoothing)
+            && is_weight(self.ack_rtt_variance_smoothing)
+            && self.ack_rtt_variance_multiplier.is_finite()
+            && self.ack_rtt_variance_multiplier >= 0.0
+            && self.minimum_ack_wait_addition <= self.maximum_ack_wait_addition
+    }
 }
 
 impl Default for Acknowledgements {
@@ -726,6 +1044,15 @@ impl Default for Acknowledgements {
             average_ack_delay: DEFAULT_AVERAGE_PACKET_DELAY,
             ack_wait_multiplier: DEFAULT_ACK_WAIT_MULTIPLIER,
             ack_wait_addition: DEFAULT_ACK_WAIT_ADDITION,
+            adaptive_ack_timeouts: false,
+            ack_rtt_smoothing: DEFAULT_ACK_RTT_SMOOTHING,
+            ack_rtt_variance_smoothing: DEFAULT_ACK_RTT_VARIANCE_SMOOTHING,
+            ack_rtt_variance_multiplier: DEFAULT_ACK_RTT_VARIANCE_MULTIPLIER,
+            minimum_ack_wait_addition: DEFAULT_MINIMUM_ACK_WAIT_ADDITION,
+            maximum_ack_wait_addition: DEFAULT_MAXIMUM_ACK_WAIT_ADDITION,
+            maximum_retransmissions: None,
+            maximum_retransmission_period: None,
+            maximum_dead_letters: DEFAULT_MAXIMUM_DEAD_LETTERS,
         }
     }
 }
@@ -743,6 +1070,27 @@ pub struct Topology {
     /// did not reach its destination.
     #[serde(with = "humantime_serde")]
     pub topology_resolution_timeout: Duration,
+
+    /// The minimum performance score (in percent) over the last 24h that the mixnodes and gateways
+    /// must have to be used in the routes of this client or to be chosen as its gateway.
+    pub minimum_node_performance: u8,
+
+    /// Number of routes through the current topology that are precomputed in the background,
+    /// so that they would not have to be sampled whenever a packet is being sent.
+    /// Setting it to 0 disables the precomputation.
+    pub route_pool_size: usize,
+
+    /// Fraction of the previously known nodes that can change their keys between two consecutive
+    /// topology refreshes before it is reported as a suspicious change of the network.
+    pub anomaly_key_change_threshold: f64,
+
+    /// Minimum number of nodes on each mix layer. A layer suddenly shrinking below it is reported
+    /// as a suspicious change of the network.
+    pub anomaly_minimum_layer_size: usize,
+
+    /// Specifies whether all the traffic should be paused upon detecting a suspicious change
+    /// of the network topology until the user acknowledges it.
+    pub pause_on_topology_anomaly: bool,
 }
 
 impl Default for Topology {
@@ -750,6 +1098,11 @@ impl Default for Topology {
         Topology {
             topology_refresh_rate: DEFAULT_TOPOLOGY_REFRESH_RATE,
             topology_resolution_timeout: DEFAULT_TOPOLOGY_RESOLUTION_TIMEOUT,
+            minimum_node_performance: DEFAULT_MINIMUM_NODE_PERFORMANCE,
+            route_pool_size: DEFAULT_ROUTE_POOL_SIZE,
+            anomaly_key_change_threshold: DEFAULT_TOPOLOGY_ANOMALY_KEY_CHANGE_THRESHOLD,
+            anomaly_minimum_layer_size: DEFAULT_TOPOLOGY_ANOMALY_MINIMUM_LAYER_SIZE,
+            pause_on_topology_anomaly: false,
         }
     }
 }
@@ -792,6 +1145,11 @@ pub struct ReplySurbs {
     /// This is going to be superseded by key rotation once implemented.
     #[serde(with = "humantime_serde")]
     pub maximum_reply_key_age: Duration,
+
+    /// Defines the maximum number of remote parties the client is going to keep the reply state
+    /// (i.e. received reply surbs and pending replies) of.
+    /// Once exceeded, the state of the least recently active idle parties is evicted.
+    pub maximum_tracked_recipients: usize,
 }
 
 impl Default for ReplySurbs {
@@ -807,6 +1165,29 @@ impl Default for ReplySurbs {
             maximum_reply_surb_drop_waiting_period: DEFAULT_MAXIMUM_REPLY_SURB_DROP_WAITING_PERIOD,
             maximum_reply_surb_age: DEFAULT_MAXIMUM_REPLY_SURB_AGE,
             maximum_reply_key_age: DEFAULT_MAXIMUM_REPLY_KEY_AGE,
+            maximum_tracked_recipients: DEFAULT_MAXIMUM_TRACKED_RECIPIENTS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hibernation {
+    /// The rate at which the network topology is refreshed while the client is hibernating.
+    #[serde(with = "humantime_serde")]
+    pub topology_refresh_rate: Duration,
+
+    /// The interval at which keepalive messages are sent to the gateway while the client
+    /// is hibernating, so that the established session would not get dropped.
+    #[serde(with = "humantime_serde")]
+    pub keepalive_interval: Duration,
+}
+
+impl Default for Hibernation {
+    fn default() -> Self {
+        Hibernation {
+            topology_refresh_rate: DEFAULT_HIBERNATION_TOPOLOGY_REFRESH_RATE,
+            keepalive_interval: DEFAULT_HIBERNATION_KEEPALIVE_INTERVAL,
         }
     }
 }
@@ -831,12 +1212,15 @@ pub struct DebugConfig {
 
     /// Defines all configuration options related to reply SURBs.
     pub reply_surbs: ReplySurbs,
+
+    /// Defines all configuration options related to the low-power hibernation mode.
+    pub hibernation: Hibernation,
 }
 
 impl DebugConfig {
     pub fn validate(&self) -> bool {
         // no other sections have explicit requirements (yet)
-        self.traffic.validate()
+        self.traffic.validate() && self.acknowledgements.validate()
     }
 }
 
@@ -852,6 +1236,7 @@ impl Default for DebugConfig {
             acknowledgements: Default::default(),
             topology: Default::default(),
             reply_surbs: Default::default(),
+            hibernation: Default::default(),
         }
     }
 }
@@ -125,6 +125,9 @@ impl From<OldDebugConfigV1_1_13> for DebugConfig {
                     .disable_main_poisson_packet_distribution,
                 primary_packet_size: PacketSize::RegularPacket,
                 secondary_packet_size: value.use_extended_packet_size.map(Into::into),
+                padding_policy: Default::default(),
+                attach_message_ordering: false,
+                disclose_message_expiry: false,
             },
             cover_traffic: CoverTraffic {
                 loop_cover_traffic_average_delay: value.loop_cover_traffic_average_delay,
@@ -133,15 +136,19 @@ impl From<OldDebugConfigV1_1_13> for DebugConfig {
             },
             gateway_connection: GatewayConnection {
                 gateway_response_timeout: value.gateway_response_timeout,
+                ..GatewayConnection::default()
             },
             acknowledgements: Acknowledgements {
                 average_ack_delay: value.average_ack_delay,
                 ack_wait_multiplier: value.ack_wait_multiplier,
                 ack_wait_addition: value.ack_wait_addition,
+                ..Acknowledgements::default()
             },
             topology: Topology {
                 topology_refresh_rate: value.topology_refresh_rate,
                 topology_resolution_timeout: value.topology_resolution_timeout,
+                minimum_node_performance: Default::default(),
+                ..Topology::default()
             },
             reply_surbs: ReplySurbs {
                 minimum_reply_surb_storage_threshold: value.minimum_reply_surb_storage_threshold,
@@ -156,7 +163,9 @@ impl From<OldDebugConfigV1_1_13> for DebugConfig {
                     .maximum_reply_surb_drop_waiting_period,
                 maximum_reply_surb_age: value.maximum_reply_surb_age,
                 maximum_reply_key_age: value.maximum_reply_key_age,
+                ..ReplySurbs::default()
             },
+            hibernation: Default::default(),
         }
     }
 }
@@ -199,6 +208,7 @@ impl<T, U> From<OldConfigV1_1_13<T>> for Config<U> {
                 disabled_credentials_mode: value.client.disabled_credentials_mode,
                 nyxd_urls: value.client.nyxd_urls,
                 nym_api_urls: value.client.nym_api_urls,
+                ignored_nodes: Vec::new(),
                 private_identity_key_file: value.client.private_identity_key_file,
                 public_identity_key_file: value.client.public_identity_key_file,
                 private_encryption_key_file: value.client.private_encryption_key_file,
@@ -213,6 +223,8 @@ impl<T, U> From<OldConfigV1_1_13<T>> for Config<U> {
                 super_struct: PhantomData,
             },
             logging: value.logging,
+            proxy: Default::default(),
+            storage_encryption: Default::default(),
             debug: value.debug.into(),
         }
     }
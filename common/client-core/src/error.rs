@@ -84,6 +84,15 @@ pub enum ClientCoreError {
     #[error("failed to register receiver for reconstructed mixnet messages")]
     FailedToRegisterReceiver,
 
+    #[error("failed to register listener for message delivery events")]
+    FailedToRegisterDeliveryListener,
+
+    #[error("failed to query the dead letter queue")]
+    FailedToQueryDeadLetters,
+
+    #[error("failed to obtain the passphrase of the encrypted storage: {reason}")]
+    StoragePassphraseFailure { reason: String },
+
     #[error("Unexpected exit")]
     UnexpectedExit,
 }
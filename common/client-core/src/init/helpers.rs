@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    client::key_manager::KeyManager,
+    client::{key_manager::KeyManager, topology_control::node_filter::NodeFilter},
     config::{persistence::key_pathfinder::ClientKeyPathfinder, Config},
     error::ClientCoreError,
 };
@@ -64,17 +64,22 @@ impl GatewayWithLatency {
 async fn current_gateways<R: Rng>(
     rng: &mut R,
     nym_apis: Vec<Url>,
+    proxy: Option<Url>,
+    node_filter: &NodeFilter,
 ) -> Result<Vec<gateway::Node>, ClientCoreError> {
     let nym_api = nym_apis
         .choose(rng)
         .ok_or(ClientCoreError::ListOfNymApisIsEmpty)?;
-    let client = nym_validator_client::client::NymApiClient::new(nym_api.clone());
+    let client =
+        nym_validator_client::client::NymApiClient::new_with_proxy(nym_api.clone(), proxy)?;
 
     log::trace!("Fetching list of gateways from: {}", nym_api);
 
     let gateways = client.get_cached_gateways().await?;
+    let excluded = node_filter.excluded_gateways(&client).await;
     let valid_gateways = gateways
         .into_iter()
+        .filter(|gateway| !excluded.contains(gateway.identity()))
         .filter_map(|gateway| gateway.try_into().ok())
         .collect::<Vec<gateway::Node>>();
 
@@ -205,11 +210,13 @@ fn uniformly_random_gateway<R: Rng>(
 
 pub(super) async fn query_gateway_details(
     validator_servers: Vec<Url>,
+    proxy: Option<Url>,
     chosen_gateway_id: Option<identity::PublicKey>,
     by_latency: bool,
+    node_filter: &NodeFilter,
 ) -> Result<gateway::Node, ClientCoreError> {
     let mut rng = thread_rng();
-    let gateways = current_gateways(&mut rng, validator_servers).await?;
+    let gateways = current_gateways(&mut rng, validator_servers, proxy, node_filter).await?;
 
     // if we set an explicit gateway, use that one and nothing else
     if let Some(explicitly_chosen) = chosen_gateway_id {
@@ -16,6 +16,7 @@ use nym_crypto::asymmetric::{encryption, identity};
 use url::Url;
 
 use crate::client::key_manager::KeyManager;
+use crate::client::topology_control::node_filter::NodeFilter;
 use crate::{
     config::{
         persistence::key_pathfinder::ClientKeyPathfinder, ClientCoreConfigTrait, Config,
@@ -81,8 +82,14 @@ pub async fn register_with_gateway<St: Storage>(
     by_latency: bool,
 ) -> Result<GatewayEndpointConfig, ClientCoreError> {
     // Get the gateway details of the gateway we will use
-    let gateway =
-        helpers::query_gateway_details(nym_api_endpoints, chosen_gateway_id, by_latency).await?;
+    let gateway = helpers::query_gateway_details(
+        nym_api_endpoints,
+        None,
+        chosen_gateway_id,
+        by_latency,
+        &NodeFilter::default(),
+    )
+    .await?;
     log::debug!("Querying gateway gives: {}", gateway);
 
     let our_identity = key_manager.identity_keypair();
@@ -124,8 +131,13 @@ where
     // Else, we proceed by querying the nym-api
     let gateway = helpers::query_gateway_details(
         config.get_nym_api_endpoints(),
+        config.get_directory_proxy(),
         user_chosen_gateway_id,
         by_latency,
+        &NodeFilter::new(
+            config.get_minimum_node_performance(),
+            config.get_ignored_nodes(),
+        ),
     )
     .await?;
     log::debug!("Querying gateway gives: {}", gateway);
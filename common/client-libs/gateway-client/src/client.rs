@@ -1,31 +1,45 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::connection_test::{self, ConnectionStage, ConnectionTestReport};
+use crate::embedded::EmbeddedConnection;
 use crate::error::GatewayClientError;
 use crate::packet_router::PacketRouter;
 pub use crate::packet_router::{
     AcknowledgementReceiver, AcknowledgementSender, MixnetMessageReceiver, MixnetMessageSender,
+    UnreachableHopReceiver, UnreachableHopSender,
 };
+pub use crate::socket_state::{ConnectionClosedReceiver, ConnectionClosedSender};
 use crate::socket_state::{PartiallyDelegated, SocketState};
 use crate::{cleanup_socket_message, try_decrypt_binary_message};
+use futures::channel::mpsc;
 use futures::{SinkExt, StreamExt};
 use log::*;
 use nym_bandwidth_controller::BandwidthController;
 use nym_coconut_interface::Credential;
 use nym_crypto::asymmetric::identity;
+use nym_gateway_requests::authentication::attach_proof::{attach_proof_message, AttachNonce};
 use nym_gateway_requests::authentication::encrypted_address::EncryptedAddressBytes;
+use nym_gateway_requests::capabilities::{Capabilities, NegotiatedProtocol};
 use nym_gateway_requests::iv::IV;
 use nym_gateway_requests::registration::handshake::{client_handshake, SharedKeys};
-use nym_gateway_requests::{BinaryRequest, ClientControlRequest, ServerResponse, PROTOCOL_VERSION};
+use nym_gateway_requests::replay::{InboundFilter, OutboundSequence};
+use nym_gateway_requests::{
+    BinaryRequest, ClientControlRequest, EpochBandwidth, ServerResponse, PROTOCOL_VERSION,
+};
 use nym_network_defaults::{REMAINING_BANDWIDTH_THRESHOLD, TOKENS_TO_BURN};
 use nym_sphinx::forwarding::packet::MixPacket;
 use nym_task::TaskClient;
 use rand::rngs::OsRng;
 use std::convert::TryFrom;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tungstenite::protocol::Message;
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
 use nym_credential_storage::storage::Storage;
 #[cfg(not(target_arch = "wasm32"))]
 use nym_validator_client::nyxd::traits::DkgQueryClient;
@@ -51,10 +65,27 @@ pub struct GatewayClient<C, St: Storage> {
     local_identity: Arc<identity::KeyPair>,
     shared_key: Option<Arc<SharedKeys>>,
     connection: SocketState,
+    /// If specified, the client is running within the gateway process and the websocket is bypassed.
+    embedded_connection: Option<EmbeddedConnection>,
     packet_router: PacketRouter,
     response_timeout_duration: Duration,
     bandwidth_controller: Option<BandwidthController<C, St>>,
 
+    /// Remaining bandwidth below which more of it is going to be acquired.
+    bandwidth_top_up_threshold: i64,
+    /// Specifies whether more bandwidth should be acquired automatically once it's running low,
+    /// rather than only upon an explicit request.
+    automatic_bandwidth_top_up: bool,
+
+    /// Protocol version and capabilities negotiated with the gateway in the current session.
+    negotiated_protocol: Option<NegotiatedProtocol>,
+
+    // replay protection related variables (only used if the gateway supports it)
+    /// Sequence of binary frames sent to the gateway in the current session.
+    outbound_sequence: Option<OutboundSequence>,
+    /// Filter of binary frames received from the gateway in the current session.
+    inbound_filter: Option<Arc<Mutex<InboundFilter>>>,
+
     // reconnection related variables
     /// Specifies whether client should try to reconnect to gateway on connection failure.
     should_reconnect_on_failure: bool,
@@ -64,6 +95,12 @@ pub struct GatewayClient<C, St: Storage> {
     /// Delay between each subsequent reconnection attempt.
     reconnection_backoff: Duration,
 
+    /// Announces the connections to the gateway getting closed, so that it wouldn't go unnoticed
+    /// until the next attempt of sending something.
+    connection_closed_sender: ConnectionClosedSender,
+    /// Receiving end of the above, until it's taken by whoever is interested.
+    connection_closed_receiver: Option<ConnectionClosedReceiver>,
+
     /// Listen to shutdown messages.
     shutdown: TaskClient,
 }
@@ -86,6 +123,7 @@ where
         bandwidth_controller: Option<BandwidthController<C, St>>,
         shutdown: TaskClient,
     ) -> Self {
+        let (connection_closed_sender, connection_closed_receiver) = mpsc::unbounded();
         GatewayClient {
             authenticated: false,
             disabled_credentials_mode: true,
@@ -95,12 +133,20 @@ where
             local_identity,
             shared_key,
             connection: SocketState::NotConnected,
+            embedded_connection: None,
             packet_router: PacketRouter::new(ack_sender, mixnet_message_sender, shutdown.clone()),
             response_timeout_duration,
             bandwidth_controller,
+            bandwidth_top_up_threshold: REMAINING_BANDWIDTH_THRESHOLD,
+            automatic_bandwidth_top_up: true,
+            negotiated_protocol: None,
+            outbound_sequence: None,
+            inbound_filter: None,
             should_reconnect_on_failure: true,
             reconnection_attempts: DEFAULT_RECONNECTION_ATTEMPTS,
             reconnection_backoff: DEFAULT_RECONNECTION_BACKOFF,
+            connection_closed_sender,
+            connection_closed_receiver: Some(connection_closed_receiver),
             shutdown,
         }
     }
@@ -122,20 +168,44 @@ where
         self.reconnection_backoff = backoff
     }
 
+    pub fn with_bandwidth_top_up_threshold(&mut self, threshold: i64) {
+        self.bandwidth_top_up_threshold = threshold
+    }
+
+    pub fn with_automatic_bandwidth_top_up(&mut self, automatic_bandwidth_top_up: bool) {
+        self.automatic_bandwidth_top_up = automatic_bandwidth_top_up
+    }
+
+    pub fn with_delivered_messages_window(&mut self, window: usize) {
+        self.packet_router.with_delivered_messages_window(window)
+    }
+
+    pub fn with_unreachable_hop_sender(&mut self, unreachable_hop_sender: UnreachableHopSender) {
+        self.packet_router
+            .with_unreachable_hop_sender(unreachable_hop_sender)
+    }
+
+    pub fn with_embedded_connection(&mut self, embedded_connection: EmbeddedConnection) {
+        self.embedded_connection = Some(embedded_connection)
+    }
+
+    pub fn is_embedded(&self) -> bool {
+        self.embedded_connection.is_some()
+    }
+
     pub fn new_init(
         gateway_address: String,
         gateway_identity: identity::PublicKey,
         local_identity: Arc<identity::KeyPair>,
         response_timeout_duration: Duration,
     ) -> Self {
-        use futures::channel::mpsc;
-
         // note: this packet_router is completely invalid in normal circumstances, but "works"
         // perfectly fine here, because it's not meant to be used
         let (ack_tx, _) = mpsc::unbounded();
         let (mix_tx, _) = mpsc::unbounded();
         let shutdown = TaskClient::dummy();
         let packet_router = PacketRouter::new(ack_tx, mix_tx, shutdown.clone());
+        let (connection_closed_sender, connection_closed_receiver) = mpsc::unbounded();
 
         GatewayClient::<C, St> {
             authenticated: false,
@@ -146,16 +216,30 @@ where
             local_identity,
             shared_key: None,
             connection: SocketState::NotConnected,
+            embedded_connection: None,
             packet_router,
             response_timeout_duration,
             bandwidth_controller: None,
+            bandwidth_top_up_threshold: REMAINING_BANDWIDTH_THRESHOLD,
+            automatic_bandwidth_top_up: true,
+            negotiated_protocol: None,
+            outbound_sequence: None,
+            inbound_filter: None,
             should_reconnect_on_failure: false,
             reconnection_attempts: DEFAULT_RECONNECTION_ATTEMPTS,
             reconnection_backoff: DEFAULT_RECONNECTION_BACKOFF,
+            connection_closed_sender,
+            connection_closed_receiver: Some(connection_closed_receiver),
             shutdown,
         }
     }
 
+    /// Takes the receiver of the reasons for the connection to the gateway getting closed.
+    /// It can only be taken once.
+    pub fn take_connection_closed_receiver(&mut self) -> Option<ConnectionClosedReceiver> {
+        self.connection_closed_receiver.take()
+    }
+
     pub fn gateway_identity(&self) -> identity::PublicKey {
         self.gateway_identity
     }
@@ -164,6 +248,12 @@ where
         self.bandwidth_remaining
     }
 
+    /// Protocol version and capabilities negotiated with the gateway,
+    /// if we have already authenticated with it.
+    pub fn negotiated_protocol(&self) -> Option<NegotiatedProtocol> {
+        self.negotiated_protocol
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     async fn _close_connection(&mut self) -> Result<(), GatewayClientError> {
         match std::mem::replace(&mut self.connection, SocketState::NotConnected) {
@@ -282,7 +372,7 @@ where
         let mut timeout = wasm_timer::Delay::new(self.response_timeout_duration);
 
         loop {
-            tokio::select! {
+            let ws_msg = tokio::select! {
                 _ = self.shutdown.recv() => {
                     log::trace!("GatewayClient control response: Received shutdown");
                     log::debug!("GatewayClient control response: Exiting");
@@ -292,30 +382,55 @@ where
                     break Err(GatewayClientError::Timeout);
                 }
                 msg = conn.next() => {
-                    let ws_msg = match cleanup_socket_message(msg) {
+                    match cleanup_socket_message(msg) {
                         Err(err) => break Err(err),
                         Ok(msg) => msg
-                    };
-                    match ws_msg {
-                        Message::Binary(bin_msg) => {
-                            // if we have established the shared key already, attempt to use it for decryption
-                            // otherwise there's not much we can do apart from just routing what we have on hand
-                            if let Some(shared_keys) = &self.shared_key {
-                                if let Some(plaintext) = try_decrypt_binary_message(bin_msg, shared_keys) {
-                                    if let Err(err) = self.packet_router.route_received(vec![plaintext]) {
-                                        log::warn!("Route received failed: {err}");
-                                    }
-                                }
-                            } else if let Err(err) = self.packet_router.route_received(vec![bin_msg]) {
+                    }
+                }
+            };
+
+            match ws_msg {
+                Message::Binary(bin_msg) => {
+                    // if we have established the shared key already, attempt to use it for decryption
+                    // otherwise there's not much we can do apart from just routing what we have on hand
+                    if let Some(shared_keys) = &self.shared_key {
+                        if let Some(plaintext) = try_decrypt_binary_message(
+                            bin_msg,
+                            shared_keys,
+                            self.inbound_filter.as_deref(),
+                        ) {
+                            if let Err(err) = self.packet_router.route_received(vec![plaintext]) {
                                 log::warn!("Route received failed: {err}");
                             }
                         }
-                        Message::Text(txt_msg) => {
-                            break ServerResponse::try_from(txt_msg).map_err(|_| GatewayClientError::MalformedResponse);
+                    } else if let Err(err) = self.packet_router.route_received(vec![bin_msg]) {
+                        log::warn!("Route received failed: {err}");
+                    }
+                }
+                Message::Text(txt_msg) => {
+                    let response = match ServerResponse::try_from(txt_msg) {
+                        Ok(response) => response,
+                        Err(_) => break Err(GatewayClientError::MalformedResponse),
+                    };
+                    // pages of stored messages might get interleaved with actual responses.
+                    // all of the page's messages have already been routed, so we can ask for more
+                    if response.is_stored_messages_page() {
+                        if let Err(err) = conn
+                            .send(ClientControlRequest::AckStoredMessages.into())
+                            .await
+                        {
+                            break Err(err.into());
                         }
-                        _ => (),
+                        continue;
+                    }
+                    // so might the notifications about our packets that could not have been forwarded
+                    if let Some(address) = response.unreachable_hop() {
+                        self.packet_router.route_unreachable_hop(address);
+                        continue;
                     }
+                    break Ok(response);
                 }
+                _ => (),
             }
         }
     }
@@ -400,31 +515,60 @@ where
         }
     }
 
-    fn check_gateway_protocol(
-        &self,
+    /// Checks the protocol version and capabilities chosen by the gateway. If the gateway is older
+    /// than us, the features it doesn't support are disabled for the rest of the session, e.g. stored
+    /// messages are going to be pushed without requiring any acknowledgements.
+    fn negotiate_gateway_protocol(
+        &mut self,
         gateway_protocol: Option<u8>,
+        gateway_capabilities: Option<Capabilities>,
+        session_nonce: Option<u64>,
     ) -> Result<(), GatewayClientError> {
-        // right now there are no failure cases here, but this might change in the future
-        match gateway_protocol {
-            None => {
-                warn!("the gateway we're connected to has not specified its protocol version. It's probably running version < 1.1.X, but that's still fine for now. It will become a hard error in 1.2.0");
-                // note: in +1.2.0 we will have to return a hard error here
-                Ok(())
-            }
-            Some(v) if v != PROTOCOL_VERSION => {
-                let err = GatewayClientError::IncompatibleProtocol {
-                    gateway: Some(v),
-                    current: PROTOCOL_VERSION,
-                };
-                error!("{err}");
-                Err(err)
-            }
+        if gateway_protocol.is_none() {
+            warn!("the gateway we're connected to has not specified its protocol version. It's probably running version < 1.1.X, but that's still fine for now. It will become a hard error in 1.2.0");
+        }
 
-            Some(_) => {
-                info!("the gateway is using exactly the same protocol version as we are. We're good to continue!");
-                Ok(())
-            }
+        // the gateway is meant to downgrade to our version if it's newer, so if it didn't,
+        // we wouldn't be able to understand it
+        let incompatible = || {
+            let err = GatewayClientError::IncompatibleProtocol {
+                gateway: gateway_protocol,
+                current: PROTOCOL_VERSION,
+            };
+            error!("{err}");
+            err
+        };
+        if matches!(gateway_protocol, Some(v) if v > PROTOCOL_VERSION) {
+            return Err(incompatible());
+        }
+        let negotiated = NegotiatedProtocol::negotiate(gateway_protocol, gateway_capabilities)
+            .map_err(|_| incompatible())?;
+
+        if negotiated.replay_protection() && session_nonce.is_none() {
+            let err = GatewayClientError::MissingSessionNonce;
+            error!("{err}");
+            return Err(err);
+        }
+
+        let missing = negotiated.missing_capabilities();
+        if !missing.is_empty() {
+            warn!(
+                "the gateway is using protocol version {}. The following features are not going to be available: {missing}",
+                negotiated.protocol_version
+            );
+        } else {
+            info!("the gateway supports all of the features we do. We're good to continue!");
         }
+
+        self.negotiated_protocol = Some(negotiated);
+        Ok(())
+    }
+
+    /// Sets up (or disables) the replay protection for binary frames exchanged in the new session.
+    fn set_session_nonce(&mut self, session_nonce: Option<u64>) {
+        self.outbound_sequence = session_nonce.map(OutboundSequence::new);
+        self.inbound_filter =
+            session_nonce.map(|nonce| Arc::new(Mutex::new(InboundFilter::new(nonce))));
     }
 
     async fn register(&mut self) -> Result<(), GatewayClientError> {
@@ -450,22 +594,26 @@ where
             .map_err(GatewayClientError::RegistrationFailure),
             _ => unreachable!(),
         }?;
-        let (authentication_status, gateway_protocol) = match self.read_control_response().await? {
-            ServerResponse::Register {
-                protocol_version,
-                status,
-            } => (status, protocol_version),
-            ServerResponse::Error { message } => {
-                return Err(GatewayClientError::GatewayError(message))
-            }
-            _ => return Err(GatewayClientError::UnexpectedResponse),
-        };
+        let (authentication_status, gateway_protocol, gateway_capabilities, session_nonce) =
+            match self.read_control_response().await? {
+                ServerResponse::Register {
+                    protocol_version,
+                    capabilities,
+                    status,
+                    session_nonce,
+                } => (status, protocol_version, capabilities, session_nonce),
+                ServerResponse::Error { message } => {
+                    return Err(GatewayClientError::from_handshake_error_response(message))
+                }
+                _ => return Err(GatewayClientError::UnexpectedResponse),
+            };
 
-        self.check_gateway_protocol(gateway_protocol)?;
+        self.negotiate_gateway_protocol(gateway_protocol, gateway_capabilities, session_nonce)?;
         self.authenticated = authentication_status;
 
         if self.authenticated {
             self.shared_key = Some(Arc::new(shared_key));
+            self.set_session_nonce(session_nonce);
         }
         Ok(())
     }
@@ -503,15 +651,20 @@ where
         match self.send_websocket_message(msg).await? {
             ServerResponse::Authenticate {
                 protocol_version,
+                capabilities,
                 status,
                 bandwidth_remaining,
+                session_nonce,
             } => {
-                self.check_gateway_protocol(protocol_version)?;
+                self.negotiate_gateway_protocol(protocol_version, capabilities, session_nonce)?;
                 self.authenticated = status;
                 self.bandwidth_remaining = bandwidth_remaining;
+                self.set_session_nonce(session_nonce);
                 Ok(())
             }
-            ServerResponse::Error { message } => Err(GatewayClientError::GatewayError(message)),
+            ServerResponse::Error { message } => {
+                Err(GatewayClientError::from_handshake_error_response(message))
+            }
             _ => Err(GatewayClientError::UnexpectedResponse),
         }
     }
@@ -533,6 +686,58 @@ where
         }
     }
 
+    /// Asks the gateway we're currently connected to, to fetch our messages from our home gateway,
+    /// i.e. the one our address is anchored at, so that we wouldn't need to re-register when moving
+    /// between gateways.
+    ///
+    /// The ownership of our address is proven by signing a fresh nonce issued by the gateway
+    /// we're connected to alongside the identities of both gateways.
+    ///
+    /// # Arguments
+    ///
+    /// * `home_gateway`: identity of the gateway our address is anchored at.
+    pub async fn attach_to_home_gateway(
+        &mut self,
+        home_gateway: identity::PublicKey,
+    ) -> Result<(), GatewayClientError> {
+        if !self.authenticated {
+            return Err(GatewayClientError::NotAuthenticated);
+        }
+
+        let nonce = match self
+            .send_websocket_message(ClientControlRequest::GetAttachNonce.into())
+            .await?
+        {
+            ServerResponse::AttachNonce { nonce } => AttachNonce::try_from_base58_string(nonce)
+                .map_err(|_| GatewayClientError::UnexpectedResponse)?,
+            ServerResponse::Error { message } => {
+                return Err(GatewayClientError::GatewayError(message))
+            }
+            _ => return Err(GatewayClientError::UnexpectedResponse),
+        };
+        let signature = self
+            .local_identity
+            .private_key()
+            .sign(&attach_proof_message(
+                &nonce,
+                &self.gateway_identity,
+                &home_gateway,
+            ));
+
+        let msg =
+            ClientControlRequest::new_attach_home_gateway(home_gateway, nonce, signature).into();
+        match self.send_websocket_message(msg).await? {
+            ServerResponse::HomeGatewayAttached { status: true } => Ok(()),
+            ServerResponse::HomeGatewayAttached { status: false } => {
+                Err(GatewayClientError::HomeGatewayAttachmentFailure {
+                    home_gateway: home_gateway.to_base58_string(),
+                })
+            }
+            ServerResponse::Error { message } => Err(GatewayClientError::GatewayError(message)),
+            _ => Err(GatewayClientError::UnexpectedResponse),
+        }
+    }
+
     async fn claim_coconut_bandwidth(
         &mut self,
         credential: Credential,
@@ -554,6 +759,29 @@ where
         Ok(())
     }
 
+    /// Queries the gateway for our remaining bandwidth, alongside its split between the epochs
+    /// the credentials were issued in.
+    pub async fn get_bandwidth_balance(
+        &mut self,
+    ) -> Result<Vec<EpochBandwidth>, GatewayClientError> {
+        if !self.authenticated {
+            return Err(GatewayClientError::NotAuthenticated);
+        }
+
+        let msg = ClientControlRequest::GetBandwidthBalance.into();
+        match self.send_websocket_message(msg).await? {
+            ServerResponse::BandwidthBalance {
+                available_total,
+                epochs,
+            } => {
+                self.bandwidth_remaining = available_total;
+                Ok(epochs)
+            }
+            ServerResponse::Error { message } => Err(GatewayClientError::GatewayError(message)),
+            _ => Err(GatewayClientError::UnexpectedResponse),
+        }
+    }
+
     async fn try_claim_testnet_bandwidth(&mut self) -> Result<(), GatewayClientError> {
         let msg = ClientControlRequest::ClaimFreeTestnetBandwidth.into();
         self.bandwidth_remaining = match self.send_websocket_message(msg).await? {
@@ -602,6 +830,29 @@ where
         Ok(())
     }
 
+    /// Refreshes our remaining bandwidth and, if it fell below the threshold, acquires more of it,
+    /// unless the automatic top-ups are disabled. Returns whether the bandwidth got topped up.
+    pub async fn top_up_bandwidth_if_needed(&mut self) -> Result<bool, GatewayClientError>
+    where
+        C: DkgQueryClient,
+    {
+        self.get_bandwidth_balance().await?;
+        if self.bandwidth_remaining >= self.bandwidth_top_up_threshold {
+            return Ok(false);
+        }
+
+        if !self.automatic_bandwidth_top_up {
+            warn!(
+                "Only {} bytes of bandwidth remaining. Automatic top-ups are disabled, so more of it has to be requested manually",
+                self.bandwidth_remaining
+            );
+            return Ok(false);
+        }
+
+        self.claim_bandwidth().await?;
+        Ok(true)
+    }
+
     fn estimate_required_bandwidth(&self, packets: &[MixPacket]) -> i64 {
         packets
             .iter()
@@ -613,6 +864,9 @@ where
         &mut self,
         packets: Vec<MixPacket>,
     ) -> Result<(), GatewayClientError> {
+        if let Some(embedded_connection) = &self.embedded_connection {
+            return Self::forward_via_embedded_connection(embedded_connection, packets);
+        }
         if !self.authenticated {
             return Err(GatewayClientError::NotAuthenticated);
         }
@@ -626,14 +880,18 @@ where
             return Err(GatewayClientError::ConnectionNotEstablished);
         }
 
+        let shared_key = self
+            .shared_key
+            .as_ref()
+            .expect("no shared key present even though we're authenticated!");
         let messages: Vec<_> = packets
             .into_iter()
             .map(|mix_packet| {
-                BinaryRequest::new_forward_request(mix_packet).into_ws_message(
-                    self.shared_key
-                        .as_ref()
-                        .expect("no shared key present even though we're authenticated!"),
-                )
+                let header = self
+                    .outbound_sequence
+                    .as_mut()
+                    .map(|sequence| sequence.next_header());
+                BinaryRequest::new_forward_request(mix_packet).into_ws_message(shared_key, header)
             })
             .collect();
 
@@ -651,6 +909,18 @@ where
         }
     }
 
+    // the gateway does not meter the bandwidth of the clients running within its own process
+    fn forward_via_embedded_connection(
+        embedded_connection: &EmbeddedConnection,
+        packets: Vec<MixPacket>,
+    ) -> Result<(), GatewayClientError> {
+        if embedded_connection.forward_packets(packets) {
+            Ok(())
+        } else {
+            Err(GatewayClientError::EmbeddedConnectionClosed)
+        }
+    }
+
     async fn send_with_reconnection_on_failure(
         &mut self,
         msg: Message,
@@ -683,6 +953,9 @@ where
         &mut self,
         mix_packet: MixPacket,
     ) -> Result<(), GatewayClientError> {
+        if let Some(embedded_connection) = &self.embedded_connection {
+            return Self::forward_via_embedded_connection(embedded_connection, vec![mix_packet]);
+        }
         if !self.authenticated {
             return Err(GatewayClientError::NotAuthenticated);
         }
@@ -697,10 +970,15 @@ where
         }
         // note: into_ws_message encrypts the requests and adds a MAC on it. Perhaps it should
         // be more explicit in the naming?
+        let header = self
+            .outbound_sequence
+            .as_mut()
+            .map(|sequence| sequence.next_header());
         let msg = BinaryRequest::new_forward_request(mix_packet).into_ws_message(
             self.shared_key
                 .as_ref()
                 .expect("no shared key present even though we're authenticated!"),
+            header,
         );
         self.send_with_reconnection_on_failure(msg).await
     }
@@ -745,6 +1023,8 @@ where
                                 .as_ref()
                                 .expect("no shared key present even though we're authenticated!"),
                         ),
+                        self.inbound_filter.clone(),
+                        self.connection_closed_sender.clone(),
                         self.shutdown.clone(),
                     )
                 }
@@ -769,19 +1049,132 @@ where
         Ok(())
     }
 
+    /// Goes through all the stages of connecting to the gateway one by one, i.e. DNS resolution,
+    /// TCP connection, TLS and websocket handshakes, authentication and finally an echo test,
+    /// so that any failure could be attributed to the exact stage it happened at.
+    ///
+    /// On success the connection is left established and authenticated.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn perform_connection_test(
+        &mut self,
+    ) -> Result<ConnectionTestReport, GatewayClientError> {
+        let timeout = self.response_timeout_duration;
+        let resolved_addresses = connection_test::resolve(&self.gateway_address, timeout).await?;
+
+        let start = Instant::now();
+        connection_test::probe_tcp(&resolved_addresses, timeout).await?;
+        let tcp_connect = start.elapsed();
+
+        let start = Instant::now();
+        self.establish_connection()
+            .await
+            .map_err(connection_test::classify_websocket_error)?;
+        let websocket_handshake = start.elapsed();
+
+        let start = Instant::now();
+        self.perform_initial_authentication()
+            .await
+            .map_err(connection_test::classify_authentication_error)?;
+        let authentication = start.elapsed();
+
+        let start = Instant::now();
+        self.echo_test()
+            .await
+            .map_err(|err| connection_test::failure(ConnectionStage::Echo, err.to_string()))?;
+        let echo_round_trip = start.elapsed();
+
+        Ok(ConnectionTestReport {
+            resolved_addresses,
+            tcp_connect,
+            websocket_handshake,
+            authentication,
+            echo_round_trip,
+        })
+    }
+
+    /// Sends a websocket ping with random payload and waits for the matching pong.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn echo_test(&mut self) -> Result<(), GatewayClientError> {
+        let mut payload = [0u8; 8];
+        rand::RngCore::fill_bytes(&mut OsRng, &mut payload);
+
+        let conn = match self.connection {
+            SocketState::Available(ref mut conn) => conn,
+            _ => return Err(GatewayClientError::ConnectionInInvalidState),
+        };
+        conn.send(Message::Ping(payload.to_vec())).await?;
+
+        let timeout = tokio::time::sleep(self.response_timeout_duration);
+        tokio::pin!(timeout);
+
+        loop {
+            tokio::select! {
+                _ = self.shutdown.recv() => {
+                    log::trace!("GatewayClient echo test: Received shutdown");
+                    break Err(GatewayClientError::ConnectionClosedGatewayShutdown);
+                }
+                _ = &mut timeout => {
+                    break Err(GatewayClientError::Timeout);
+                }
+                msg = conn.next() => {
+                    match cleanup_socket_message(msg)? {
+                        Message::Pong(received) if received == payload => break Ok(()),
+                        Message::Binary(bin_msg) => {
+                            // we're already authenticated, so we might have received some mix packets
+                            if let Some(shared_keys) = &self.shared_key {
+                                if let Some(plaintext) = try_decrypt_binary_message(bin_msg, shared_keys, self.inbound_filter.as_deref()) {
+                                    if let Err(err) = self.packet_router.route_received(vec![plaintext]) {
+                                        log::warn!("Route received failed: {err}");
+                                    }
+                                }
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+
     pub async fn authenticate_and_start(&mut self) -> Result<Arc<SharedKeys>, GatewayClientError>
     where
         C: DkgQueryClient,
     {
+        if let Some(embedded_connection) = &mut self.embedded_connection {
+            // there's no websocket to authenticate over, but we still expect to have been
+            // registered with the gateway beforehand so that our keys would be consistent
+            let shared_key = self
+                .shared_key
+                .clone()
+                .ok_or(GatewayClientError::NotAuthenticated)?;
+            embedded_connection.start_listening_for_mixnet_messages(
+                self.packet_router.clone(),
+                self.shutdown.clone(),
+            );
+            self.authenticated = true;
+            return Ok(shared_key);
+        }
+
         if !self.connection.is_established() {
             self.establish_connection().await?;
         }
-        let shared_key = self.perform_initial_authentication().await?;
+        // we might have already authenticated as part of the connection test
+        let shared_key = match &self.shared_key {
+            Some(shared_key) if self.authenticated => Arc::clone(shared_key),
+            _ => self.perform_initial_authentication().await?,
+        };
 
-        if self.bandwidth_remaining < REMAINING_BANDWIDTH_THRESHOLD {
-            info!("Claiming more bandwidth for your tokens. This will use {} token(s) from your wallet. \
-            Stop the process now if you don't want that to happen.", TOKENS_TO_BURN);
-            self.claim_bandwidth().await?;
+        if self.bandwidth_remaining < self.bandwidth_top_up_threshold {
+            if self.automatic_bandwidth_top_up {
+                info!("Claiming more bandwidth for your tokens. This will use {} token(s) from your wallet. \
+                Stop the process now if you don't want that to happen.", TOKENS_TO_BURN);
+                self.claim_bandwidth().await?;
+            } else {
+                warn!(
+                    "Only {} bytes of bandwidth remaining. Automatic top-ups are disabled, so more of it has to be requested manually",
+                    self.bandwidth_remaining
+                );
+            }
         }
 
         // this call is NON-blocking
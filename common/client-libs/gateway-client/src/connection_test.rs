@@ -0,0 +1,156 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::GatewayClientError;
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tungstenite::Error as WsError;
+
+/// Stage of establishing the gateway connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStage {
+    Dns,
+    Tcp,
+    Tls,
+    WebsocketHandshake,
+    Authentication,
+    ProtocolVersion,
+    Echo,
+}
+
+impl ConnectionStage {
+    /// Suggestion on what to look at if the connection failed at this stage.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            ConnectionStage::Dns => "check whether the gateway address in your config is correct and whether your DNS resolver is reachable",
+            ConnectionStage::Tcp => "the gateway might be offline or a firewall might be blocking the connection, try a different gateway",
+            ConnectionStage::Tls => "the gateway's certificate could not be verified, check your system clock and the gateway address",
+            ConnectionStage::WebsocketHandshake => "the endpoint does not look like a gateway client listener, check the gateway address and port",
+            ConnectionStage::Authentication => "the gateway did not accept our keys, if you have recently changed gateways you might need to re-run `init`",
+            ConnectionStage::ProtocolVersion => "the gateway runs an incompatible version, upgrade your client or choose a different gateway",
+            ConnectionStage::Echo => "the gateway has accepted our connection but is not responding, try a different gateway",
+        }
+    }
+}
+
+impl Display for ConnectionStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionStage::Dns => write!(f, "DNS resolution"),
+            ConnectionStage::Tcp => write!(f, "TCP connection"),
+            ConnectionStage::Tls => write!(f, "TLS handshake"),
+            ConnectionStage::WebsocketHandshake => write!(f, "websocket handshake"),
+            ConnectionStage::Authentication => write!(f, "authentication"),
+            ConnectionStage::ProtocolVersion => write!(f, "protocol version negotiation"),
+            ConnectionStage::Echo => write!(f, "echo test"),
+        }
+    }
+}
+
+/// Timings of the individual stages of a successful connection test.
+#[derive(Debug, Clone)]
+pub struct ConnectionTestReport {
+    pub resolved_addresses: Vec<SocketAddr>,
+    pub tcp_connect: Duration,
+    pub websocket_handshake: Duration,
+    pub authentication: Duration,
+    pub echo_round_trip: Duration,
+}
+
+impl Display for ConnectionTestReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "resolved to {:?}, tcp connect: {:?}, websocket handshake: {:?}, authentication: {:?}, echo round trip: {:?}",
+            self.resolved_addresses,
+            self.tcp_connect,
+            self.websocket_handshake,
+            self.authentication,
+            self.echo_round_trip
+        )
+    }
+}
+
+pub(crate) fn failure<S: Into<String>>(stage: ConnectionStage, reason: S) -> GatewayClientError {
+    GatewayClientError::ConnectionTestFailure {
+        stage,
+        reason: reason.into(),
+    }
+}
+
+pub(crate) async fn resolve(
+    gateway_address: &str,
+    timeout: Duration,
+) -> Result<Vec<SocketAddr>, GatewayClientError> {
+    let url = url::Url::parse(gateway_address).map_err(|err| {
+        failure(
+            ConnectionStage::Dns,
+            format!("'{gateway_address}' is not a valid address: {err}"),
+        )
+    })?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| failure(ConnectionStage::Dns, "the address does not contain a host"))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| failure(ConnectionStage::Dns, "the address does not contain a port"))?;
+
+    let resolved = tokio::time::timeout(timeout, tokio::net::lookup_host((host, port)))
+        .await
+        .map_err(|_| failure(ConnectionStage::Dns, format!("resolving {host} timed out")))?
+        .map_err(|err| {
+            failure(
+                ConnectionStage::Dns,
+                format!("failed to resolve {host}: {err}"),
+            )
+        })?
+        .collect::<Vec<_>>();
+
+    if resolved.is_empty() {
+        return Err(failure(
+            ConnectionStage::Dns,
+            format!("{host} did not resolve to any address"),
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Checks whether any of the resolved addresses accepts TCP connections.
+pub(crate) async fn probe_tcp(
+    addresses: &[SocketAddr],
+    timeout: Duration,
+) -> Result<(), GatewayClientError> {
+    let mut failures = Vec::new();
+    for address in addresses {
+        match tokio::time::timeout(timeout, TcpStream::connect(address)).await {
+            Ok(Ok(_)) => return Ok(()),
+            Ok(Err(err)) => failures.push(format!("{address}: {err}")),
+            Err(_) => failures.push(format!("{address}: timed out")),
+        }
+    }
+    Err(failure(ConnectionStage::Tcp, failures.join(", ")))
+}
+
+pub(crate) fn classify_websocket_error(err: GatewayClientError) -> GatewayClientError {
+    match err {
+        GatewayClientError::NetworkError(WsError::Tls(err)) => {
+            failure(ConnectionStage::Tls, err.to_string())
+        }
+        GatewayClientError::NetworkError(WsError::Io(err)) => {
+            failure(ConnectionStage::Tcp, err.to_string())
+        }
+        other => failure(ConnectionStage::WebsocketHandshake, other.to_string()),
+    }
+}
+
+pub(crate) fn classify_authentication_error(err: GatewayClientError) -> GatewayClientError {
+    match err {
+        err @ (GatewayClientError::IncompatibleProtocol { .. }
+        | GatewayClientError::MissingSessionNonce) => {
+            failure(ConnectionStage::ProtocolVersion, err.to_string())
+        }
+        other => failure(ConnectionStage::Authentication, other.to_string()),
+    }
+}
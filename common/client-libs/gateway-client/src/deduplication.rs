@@ -0,0 +1,107 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deduplication of the messages pushed by the gateway.
+//!
+//! The gateway only removes the stored messages once the client acknowledges their delivery.
+//! If either party crashes (or the connection drops) between the delivery and the processing
+//! of that acknowledgement, the very same messages are going to be pushed again after reconnecting.
+//! Every sphinx packet is unique, so the same plaintext being received twice always implies
+//! a redelivery that should not be propagated to the application.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashSet, VecDeque};
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// Default number of the most recently delivered messages remembered for the purposes of deduplication.
+pub const DEFAULT_DELIVERED_MESSAGES_WINDOW: usize = 10_000;
+
+/// Bounded window of the most recently delivered messages.
+#[derive(Debug)]
+pub struct DeliveredMessages {
+    /// Maximum number of remembered messages. Deduplication is disabled if it's set to 0.
+    window: usize,
+
+    /// Keys of the hasher used for deriving ids of the messages.
+    // randomised so that nobody could craft colliding messages to make us drop legitimate ones
+    hasher_keys: RandomState,
+
+    ids: HashSet<u64>,
+    ids_order: VecDeque<u64>,
+}
+
+impl DeliveredMessages {
+    pub fn new(window: usize) -> Self {
+        DeliveredMessages {
+            window,
+            hasher_keys: RandomState::new(),
+            ids: HashSet::with_capacity(window),
+            ids_order: VecDeque::with_capacity(window),
+        }
+    }
+
+    fn message_id(&self, message: &[u8]) -> u64 {
+        let mut hasher = self.hasher_keys.build_hasher();
+        message.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Records the delivery of the message. Returns `false` if it has already been delivered before.
+    pub fn insert(&mut self, message: &[u8]) -> bool {
+        if self.window == 0 {
+            return true;
+        }
+
+        let id = self.message_id(message);
+        if !self.ids.insert(id) {
+            return false;
+        }
+
+        self.ids_order.push_back(id);
+        if self.ids_order.len() > self.window {
+            if let Some(oldest) = self.ids_order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+impl Default for DeliveredMessages {
+    fn default() -> Self {
+        DeliveredMessages::new(DEFAULT_DELIVERED_MESSAGES_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redelivered_messages_are_detected() {
+        let mut delivered = DeliveredMessages::new(10);
+        assert!(delivered.insert(b"foo"));
+        assert!(delivered.insert(b"bar"));
+        assert!(!delivered.insert(b"foo"));
+        assert!(!delivered.insert(b"bar"));
+    }
+
+    #[test]
+    fn only_the_most_recent_messages_are_remembered() {
+        let mut delivered = DeliveredMessages::new(2);
+        assert!(delivered.insert(b"foo"));
+        assert!(delivered.insert(b"bar"));
+        assert!(delivered.insert(b"baz"));
+
+        // "foo" got pushed out of the window
+        assert!(delivered.insert(b"foo"));
+        assert!(!delivered.insert(b"baz"));
+    }
+
+    #[test]
+    fn deduplication_can_be_disabled() {
+        let mut delivered = DeliveredMessages::new(0);
+        assert!(delivered.insert(b"foo"));
+        assert!(delivered.insert(b"foo"));
+    }
+}
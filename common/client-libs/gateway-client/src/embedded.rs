@@ -0,0 +1,86 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::packet_router::PacketRouter;
+use futures::channel::mpsc;
+use futures::StreamExt;
+use log::*;
+use nym_sphinx::forwarding::packet::MixPacket;
+use nym_task::TaskClient;
+
+pub type EmbeddedPacketSender = mpsc::UnboundedSender<MixPacket>;
+pub type EmbeddedPacketReceiver = mpsc::UnboundedReceiver<MixPacket>;
+
+pub type EmbeddedMessageSender = mpsc::UnboundedSender<Vec<Vec<u8>>>;
+pub type EmbeddedMessageReceiver = mpsc::UnboundedReceiver<Vec<Vec<u8>>>;
+
+/// In-process connection to the gateway used by clients running embedded within the gateway binary
+/// itself, such as the network requester in the colocated mode.
+/// Rather than going through the websocket, the mix packets are pushed straight into the gateway's
+/// forwarding queue and the unwrapped messages are received directly from its delivery queue.
+pub struct EmbeddedConnection {
+    packet_sender: EmbeddedPacketSender,
+    message_receiver: Option<EmbeddedMessageReceiver>,
+}
+
+impl EmbeddedConnection {
+    pub fn new(
+        packet_sender: EmbeddedPacketSender,
+        message_receiver: EmbeddedMessageReceiver,
+    ) -> Self {
+        EmbeddedConnection {
+            packet_sender,
+            message_receiver: Some(message_receiver),
+        }
+    }
+
+    pub(crate) fn forward_packets(&self, packets: Vec<MixPacket>) -> bool {
+        packets
+            .into_iter()
+            .all(|packet| self.packet_sender.unbounded_send(packet).is_ok())
+    }
+
+    /// Starts routing all messages received from the gateway to the rest of the client.
+    /// It only has an effect the first time it's called.
+    pub(crate) fn start_listening_for_mixnet_messages(
+        &mut self,
+        packet_router: PacketRouter,
+        shutdown: TaskClient,
+    ) {
+        if let Some(message_receiver) = self.message_receiver.take() {
+            let route_future = route_embedded_messages(message_receiver, packet_router, shutdown);
+
+            #[cfg(target_arch = "wasm32")]
+            wasm_bindgen_futures::spawn_local(route_future);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            tokio::spawn(route_future);
+        }
+    }
+}
+
+async fn route_embedded_messages(
+    mut message_receiver: EmbeddedMessageReceiver,
+    mut packet_router: PacketRouter,
+    mut shutdown: TaskClient,
+) {
+    while !shutdown.is_shutdown() {
+        tokio::select! {
+            biased;
+            _ = shutdown.recv() => {
+                log::trace!("Embedded gateway connection: Received shutdown");
+            }
+            messages = message_receiver.next() => match messages {
+                Some(messages) => {
+                    if let Err(err) = packet_router.route_received(messages) {
+                        warn!("Route received failed: {err}");
+                    }
+                }
+                None => {
+                    error!("The gateway has stopped delivering messages to the embedded client");
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,10 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::connection_test::ConnectionStage;
 use nym_gateway_requests::registration::handshake::error::HandshakeError;
+use nym_gateway_requests::PROTOCOL_VERSION;
 use std::io;
 use thiserror::Error;
 use tungstenite::Error as WsError;
@@ -42,6 +45,9 @@ pub enum GatewayClientError {
     #[error("Connection was abruptly closed as gateway was stopped")]
     ConnectionClosedGatewayShutdown,
 
+    #[error("The embedded connection to the gateway has been closed")]
+    EmbeddedConnectionClosed,
+
     #[error("Received response was malformed")]
     MalformedResponse,
 
@@ -69,6 +75,9 @@ pub enum GatewayClientError {
     #[error("Authentication failure")]
     AuthenticationFailure,
 
+    #[error("Gateway refused to attach to our home gateway {home_gateway}")]
+    HomeGatewayAttachmentFailure { home_gateway: String },
+
     #[error("Timed out")]
     Timeout,
 
@@ -77,9 +86,41 @@ pub enum GatewayClientError {
 
     #[error("Attempted to negotiate connection with gateway using incompatible protocol version. Ours is {current} and the gateway reports {gateway:?}")]
     IncompatibleProtocol { gateway: Option<u8>, current: u8 },
+
+    #[error("The gateway has negotiated a replay-protected protocol version, but it has not provided a session nonce")]
+    MissingSessionNonce,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Gateway connection test failed during {stage}: {reason}. Hint: {}", .stage.hint())]
+    ConnectionTestFailure {
+        stage: ConnectionStage,
+        reason: String,
+    },
 }
 
+// prefix of the error returned by the gateways that reject, rather than downgrade,
+// clients using newer protocol versions
+const LEGACY_INCOMPATIBLE_PROTOCOL_ERROR: &str =
+    "Attempted to negotiate connection with client using incompatible protocol version";
+
 impl GatewayClientError {
+    /// Converts the error returned by the gateway during the initial authentication,
+    /// recognising the protocol incompatibility reported by older gateways.
+    pub(crate) fn from_handshake_error_response(message: String) -> Self {
+        if !message.starts_with(LEGACY_INCOMPATIBLE_PROTOCOL_ERROR) {
+            return GatewayClientError::GatewayError(message);
+        }
+        let gateway = message
+            .split("Ours is ")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|version| version.parse().ok());
+        GatewayClientError::IncompatibleProtocol {
+            gateway,
+            current: PROTOCOL_VERSION,
+        }
+    }
+
     pub fn is_closed_connection(&self) -> bool {
         match self {
             GatewayClientError::NetworkError(ws_err) => match ws_err {
@@ -5,13 +5,20 @@ use crate::error::GatewayClientError;
 pub use client::GatewayClient;
 use log::warn;
 use nym_gateway_requests::registration::handshake::SharedKeys;
+use nym_gateway_requests::replay::InboundFilter;
 use nym_gateway_requests::BinaryResponse;
 pub use packet_router::{
     AcknowledgementReceiver, AcknowledgementSender, MixnetMessageReceiver, MixnetMessageSender,
+    UnreachableHopReceiver, UnreachableHopSender,
 };
+use std::sync::Mutex;
 use tungstenite::{protocol::Message, Error as WsError};
 
 pub mod client;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod connection_test;
+pub mod deduplication;
+pub mod embedded;
 pub mod error;
 pub mod packet_router;
 pub mod socket_state;
@@ -41,8 +48,18 @@ pub(crate) fn cleanup_socket_messages(
 pub(crate) fn try_decrypt_binary_message(
     bin_msg: Vec<u8>,
     shared_keys: &SharedKeys,
+    inbound_filter: Option<&Mutex<InboundFilter>>,
 ) -> Option<Vec<u8>> {
-    match BinaryResponse::try_from_encrypted_tagged_bytes(bin_msg, shared_keys) {
+    let mut inbound_filter = inbound_filter.map(|filter| {
+        filter
+            .lock()
+            .expect("the inbound replay filter mutex got poisoned!")
+    });
+    match BinaryResponse::try_from_encrypted_tagged_bytes(
+        bin_msg,
+        shared_keys,
+        inbound_filter.as_deref_mut(),
+    ) {
         Ok(bin_response) => match bin_response {
             BinaryResponse::PushedMixMessage(plaintext) => Some(plaintext),
         },
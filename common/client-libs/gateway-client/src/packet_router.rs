@@ -4,12 +4,15 @@
 // JS: I personally don't like this name very much, but could not think of anything better.
 // I will gladly take any suggestions on how to rename this.
 
+use crate::deduplication::DeliveredMessages;
 use crate::error::GatewayClientError;
 use futures::channel::mpsc;
 use log::*;
 use nym_sphinx::addressing::nodes::MAX_NODE_ADDRESS_UNPADDED_LEN;
 use nym_sphinx::params::packet_sizes::PacketSize;
 use nym_task::TaskClient;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 
 pub type MixnetMessageSender = mpsc::UnboundedSender<Vec<Vec<u8>>>;
 pub type MixnetMessageReceiver = mpsc::UnboundedReceiver<Vec<Vec<u8>>>;
@@ -17,11 +20,23 @@ pub type MixnetMessageReceiver = mpsc::UnboundedReceiver<Vec<Vec<u8>>>;
 pub type AcknowledgementSender = mpsc::UnboundedSender<Vec<Vec<u8>>>;
 pub type AcknowledgementReceiver = mpsc::UnboundedReceiver<Vec<Vec<u8>>>;
 
+/// Announces the first hops the gateway could not forward our packets to.
+pub type UnreachableHopSender = mpsc::UnboundedSender<SocketAddr>;
+pub type UnreachableHopReceiver = mpsc::UnboundedReceiver<SocketAddr>;
+
 #[derive(Clone, Debug)]
 pub struct PacketRouter {
     ack_sender: AcknowledgementSender,
     mixnet_message_sender: MixnetMessageSender,
     shutdown: TaskClient,
+
+    /// Messages delivered during the lifetime of the client, shared between all clones of the router
+    /// so that the redeliveries would be detected regardless of the connection they arrived on.
+    delivered_messages: Arc<Mutex<DeliveredMessages>>,
+
+    /// Optional channel for announcing the unreachable first hops, so that they could be avoided
+    /// when choosing the routes of the subsequent packets.
+    unreachable_hop_sender: Option<UnreachableHopSender>,
 }
 
 impl PacketRouter {
@@ -34,9 +49,36 @@ impl PacketRouter {
             ack_sender,
             mixnet_message_sender,
             shutdown,
+            delivered_messages: Arc::new(Mutex::new(DeliveredMessages::default())),
+            unreachable_hop_sender: None,
+        }
+    }
+
+    pub fn with_unreachable_hop_sender(&mut self, unreachable_hop_sender: UnreachableHopSender) {
+        self.unreachable_hop_sender = Some(unreachable_hop_sender);
+    }
+
+    pub fn route_unreachable_hop(&self, address: SocketAddr) {
+        debug!("the gateway could not forward our packet to {address}");
+        if let Some(sender) = &self.unreachable_hop_sender {
+            // nobody might be interested in the unreachable hops anymore, which is fine
+            sender.unbounded_send(address).ok();
         }
     }
 
+    /// Sets the number of the most recently delivered messages remembered for the purposes of deduplication.
+    pub fn with_delivered_messages_window(&mut self, window: usize) {
+        self.delivered_messages = Arc::new(Mutex::new(DeliveredMessages::new(window)));
+    }
+
+    fn is_redelivery(&self, message: &[u8]) -> bool {
+        !self
+            .delivered_messages
+            .lock()
+            .expect("the delivered messages mutex got poisoned!")
+            .insert(message)
+    }
+
     pub fn route_received(
         &mut self,
         unwrapped_packets: Vec<Vec<u8>>,
@@ -52,6 +94,8 @@ impl PacketRouter {
         for received_packet in unwrapped_packets {
             if received_packet.len() == PacketSize::AckPacket.plaintext_size() {
                 received_acks.push(received_packet);
+            } else if self.is_redelivery(&received_packet) {
+                debug!("dropping a message that has already been delivered before");
             } else if received_packet.len()
                 == PacketSize::RegularPacket.plaintext_size() - ack_overhead
             {
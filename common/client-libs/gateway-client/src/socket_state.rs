@@ -4,15 +4,22 @@
 use crate::error::GatewayClientError;
 use crate::packet_router::PacketRouter;
 use crate::{cleanup_socket_messages, try_decrypt_binary_message};
-use futures::channel::oneshot;
+use futures::channel::{mpsc, oneshot};
+use futures::lock::Mutex as AsyncMutex;
 use futures::stream::{SplitSink, SplitStream};
 use futures::{SinkExt, StreamExt};
 use log::*;
 use nym_gateway_requests::registration::handshake::SharedKeys;
+use nym_gateway_requests::replay::InboundFilter;
+use nym_gateway_requests::{ClientControlRequest, ServerResponse};
 use nym_task::TaskClient;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tungstenite::Message;
 
+/// Announces why the connection to the gateway has been closed.
+pub type ConnectionClosedSender = mpsc::UnboundedSender<String>;
+pub type ConnectionClosedReceiver = mpsc::UnboundedReceiver<String>;
+
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::net::TcpStream;
 #[cfg(not(target_arch = "wasm32"))]
@@ -37,18 +44,31 @@ type WsConn = JSWebsocket;
 type SplitStreamReceiver = oneshot::Receiver<Result<SplitStream<WsConn>, GatewayClientError>>;
 
 pub(crate) struct PartiallyDelegated {
-    sink_half: SplitSink<WsConn, Message>,
+    // the sink is shared with the delegated stream so that it could acknowledge received pages
+    // of stored messages without having to yield the stream back
+    sink_half: Arc<AsyncMutex<SplitSink<WsConn, Message>>>,
     delegated_stream: (SplitStreamReceiver, oneshot::Sender<()>),
 }
 
 impl PartiallyDelegated {
-    fn recover_received_plaintexts(ws_msgs: Vec<Message>, shared_key: &SharedKeys) -> Vec<Vec<u8>> {
+    /// Recovers plaintexts of the received messages alongside the number of received pages
+    /// of stored messages, all of which have to be acknowledged. Any unreachable hops announced
+    /// by the gateway are passed to the packet router straight away.
+    fn recover_received_plaintexts(
+        ws_msgs: Vec<Message>,
+        packet_router: &PacketRouter,
+        shared_key: &SharedKeys,
+        inbound_filter: Option<&Mutex<InboundFilter>>,
+    ) -> (Vec<Vec<u8>>, usize) {
         let mut plaintexts = Vec::with_capacity(ws_msgs.len());
+        let mut stored_messages_pages = 0;
         for ws_msg in ws_msgs {
             match ws_msg {
                 Message::Binary(bin_msg) => {
-                    // this function decrypts the request and checks the MAC
-                    if let Some(plaintext) = try_decrypt_binary_message(bin_msg, shared_key) {
+                    // this function decrypts the request, checks the MAC and, if applicable, rejects replayed frames
+                    if let Some(plaintext) =
+                        try_decrypt_binary_message(bin_msg, shared_key, inbound_filter)
+                    {
                         plaintexts.push(plaintext)
                     }
                 }
@@ -59,31 +79,55 @@ impl PartiallyDelegated {
 
                 // TODO: those can return the "send confirmations" - perhaps it should be somehow worked around?
                 Message::Text(text) => {
-                    trace!(
-                    "received a text message - probably a response to some previous query! - {}",
-                    text
-                );
+                    match ServerResponse::try_from(text) {
+                        Ok(response) if response.is_stored_messages_page() => {
+                            stored_messages_pages += 1
+                        }
+                        Ok(ServerResponse::UnreachableHop { address }) => {
+                            packet_router.route_unreachable_hop(address)
+                        }
+                        response => trace!(
+                            "received a text message - probably a response to some previous query! - {response:?}"
+                        ),
+                    }
                     continue;
                 }
                 _ => continue,
             }
         }
-        plaintexts
+        (plaintexts, stored_messages_pages)
+    }
+
+    async fn ack_stored_messages_pages(
+        sink: &AsyncMutex<SplitSink<WsConn, Message>>,
+        pages: usize,
+    ) -> Result<(), GatewayClientError> {
+        let mut sink = sink.lock().await;
+        for _ in 0..pages {
+            sink.send(ClientControlRequest::AckStoredMessages.into())
+                .await?;
+        }
+        Ok(())
     }
 
     fn route_socket_messages(
         ws_msgs: Vec<Message>,
         packet_router: &mut PacketRouter,
         shared_key: &SharedKeys,
-    ) -> Result<(), GatewayClientError> {
-        let plaintexts = Self::recover_received_plaintexts(ws_msgs, shared_key);
-        packet_router.route_received(plaintexts)
+        inbound_filter: Option<&Mutex<InboundFilter>>,
+    ) -> Result<usize, GatewayClientError> {
+        let (plaintexts, stored_messages_pages) =
+            Self::recover_received_plaintexts(ws_msgs, packet_router, shared_key, inbound_filter);
+        packet_router.route_received(plaintexts)?;
+        Ok(stored_messages_pages)
     }
 
     pub(crate) fn split_and_listen_for_mixnet_messages(
         conn: WsConn,
         packet_router: PacketRouter,
         shared_key: Arc<SharedKeys>,
+        inbound_filter: Option<Arc<Mutex<InboundFilter>>>,
+        connection_closed: ConnectionClosedSender,
         mut shutdown: TaskClient,
     ) -> Self {
         // when called for, it NEEDS TO yield back the stream so that we could merge it and
@@ -92,6 +136,8 @@ impl PartiallyDelegated {
         let (stream_sender, stream_receiver) = oneshot::channel();
 
         let (sink, mut stream) = conn.split();
+        let sink = Arc::new(AsyncMutex::new(sink));
+        let ack_sink = Arc::clone(&sink);
 
         let mixnet_receiver_future = async move {
             let mut notify_receiver = notify_receiver;
@@ -114,15 +160,26 @@ impl PartiallyDelegated {
                             Ok(msgs) => msgs
                         };
 
-                        if let Err(err) = Self::route_socket_messages(ws_msgs, &mut packet_router, shared_key.as_ref()) {
-                            log::warn!("Route socket messages failed: {err}");
+                        match Self::route_socket_messages(ws_msgs, &mut packet_router, shared_key.as_ref(), inbound_filter.as_deref()) {
+                            Err(err) => log::warn!("Route socket messages failed: {err}"),
+                            // only acknowledge the pages once all of their messages got routed
+                            Ok(pages) => if let Err(err) = Self::ack_stored_messages_pages(&ack_sink, pages).await {
+                                break Err(err)
+                            }
                         }
                     }
                 };
             };
 
+            // the sink has to be exclusively owned again before the halves can get reunited
+            drop(ack_sink);
+
             if match ret_err {
-                Err(err) => stream_sender.send(Err(err)),
+                Err(err) => {
+                    // nobody might be interested in the closed connections, which is fine
+                    connection_closed.unbounded_send(err.to_string()).ok();
+                    stream_sender.send(Err(err))
+                }
                 Ok(_) => {
                     shutdown.mark_as_success();
                     stream_sender.send(Ok(stream))
@@ -152,7 +209,7 @@ impl PartiallyDelegated {
         &mut self,
         msg: Message,
     ) -> Result<(), GatewayClientError> {
-        Ok(self.sink_half.send(msg).await?)
+        Ok(self.sink_half.lock().await.send(msg).await?)
     }
 
     pub(crate) async fn batch_send_without_response(
@@ -161,7 +218,12 @@ impl PartiallyDelegated {
     ) -> Result<(), GatewayClientError> {
         let stream_messages: Vec<_> = messages.into_iter().map(Ok).collect();
         let mut send_stream = futures::stream::iter(stream_messages);
-        Ok(self.sink_half.send_all(&mut send_stream).await?)
+        Ok(self
+            .sink_half
+            .lock()
+            .await
+            .send_all(&mut send_stream)
+            .await?)
     }
 
     pub(crate) async fn merge(self) -> Result<WsConn, GatewayClientError> {
@@ -192,9 +254,13 @@ impl PartiallyDelegated {
             // in receive_res
             .map_err(|_| GatewayClientError::ConnectionAbruptlyClosed)?;
         let stream = stream_results?;
+        // the delegated stream releases its handle to the sink before yielding the stream back
+        let sink_half = Arc::try_unwrap(self.sink_half)
+            .map_err(|_| GatewayClientError::ConnectionInInvalidState)?
+            .into_inner();
         // the error is thrown when trying to reunite sink and stream that did not originate
         // from the same split which is impossible to happen here
-        Ok(self.sink_half.reunite(stream).unwrap())
+        Ok(sink_half.reunite(stream).unwrap())
     }
 }
 
@@ -2,25 +2,31 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use futures::channel::mpsc;
-use futures::StreamExt;
+use futures::{stream, SinkExt, StreamExt};
 use log::*;
+use nym_retry::{ExponentialBackoff, Jitter};
+use nym_sphinx::addressing::nodes::NymNodeRoutingAddress;
+use nym_sphinx::forwarding::packet::MixPacket;
 use nym_sphinx::framing::codec::SphinxCodec;
+use nym_sphinx::framing::handshake::{HandshakeCodec, HandshakeError, HandshakeMessage};
 use nym_sphinx::framing::packet::FramedSphinxPacket;
-use nym_sphinx::params::PacketMode;
-use nym_sphinx::{addressing::nodes::NymNodeRoutingAddress, SphinxPacket};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::net::TcpStream;
-use tokio::time::sleep;
+use tokio::time::{sleep, Instant};
 use tokio_util::codec::Framed;
 
+// how long a peer that did not understand the handshake is assumed to be running the outdated
+// software before the handshake is attempted again, so that it'd be picked up once it gets upgraded
+const LEGACY_PEER_RECHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone, Copy)]
 pub struct Config {
-    initial_reconnection_backoff: Duration,
-    maximum_reconnection_backoff: Duration,
+    reconnection_backoff: ExponentialBackoff,
     initial_connection_timeout: Duration,
     maximum_connection_buffer_size: usize,
     use_legacy_version: bool,
@@ -35,41 +41,177 @@ impl Config {
         use_legacy_version: bool,
     ) -> Self {
         Config {
-            initial_reconnection_backoff,
-            maximum_reconnection_backoff,
+            reconnection_backoff: ExponentialBackoff::new(
+                initial_reconnection_backoff,
+                maximum_reconnection_backoff,
+            ),
             initial_connection_timeout,
             maximum_connection_buffer_size,
             use_legacy_version,
         }
     }
+
+    /// Randomise the reconnection backoffs so that the connections to a peer that went down
+    /// wouldn't all be re-attempted at the same time.
+    #[must_use]
+    pub fn with_reconnection_jitter(mut self, jitter: Jitter) -> Self {
+        self.reconnection_backoff = self.reconnection_backoff.with_jitter(jitter);
+        self
+    }
+
+    /// If we're trying to reconnect, determine how long we should wait.
+    fn determine_backoff(
+        &self,
+        current_attempt: u32,
+        previous_backoff: Option<Duration>,
+    ) -> Option<Duration> {
+        if current_attempt == 0 {
+            None
+        } else {
+            Some(self.reconnection_backoff.delay(
+                current_attempt,
+                previous_backoff,
+                &mut rand::thread_rng(),
+            ))
+        }
+    }
+}
+
+/// Packet handed back by the client as it could not have been sent to its next hop
+/// before its deadline.
+#[derive(Debug)]
+pub struct UndeliverablePacket {
+    pub packet: MixPacket,
+
+    /// Time at which the connection to the next hop of the packet is going to be re-attempted.
+    pub reconnection_at: Instant,
+}
+
+pub type UndeliverablePacketSender = mpsc::UnboundedSender<UndeliverablePacket>;
+pub type UndeliverablePacketReceiver = mpsc::UnboundedReceiver<UndeliverablePacket>;
+
+#[derive(Debug)]
+pub enum DeadlineSendError {
+    /// The next hop is currently down and the connection to it is not going to be re-attempted
+    /// before the deadline. The packet is handed back so that it could be re-routed instead.
+    NextHopUnavailable(UndeliverablePacket),
+
+    Io(io::Error),
 }
 
 pub trait SendWithoutResponse {
     // Without response in this context means we will not listen for anything we might get back (not
     // that we should get anything), including any possible io errors
-    fn send_without_response(
+    fn send_without_response(&mut self, packet: MixPacket) -> io::Result<()>;
+
+    /// Same as `send_without_response`, but the packet is not queued up if it couldn't possibly
+    /// reach its next hop before the provided deadline.
+    fn send_before_deadline(
         &mut self,
-        address: NymNodeRoutingAddress,
-        packet: SphinxPacket,
-        packet_mode: PacketMode,
-    ) -> io::Result<()>;
+        packet: MixPacket,
+        _deadline: Instant,
+    ) -> Result<(), DeadlineSendError> {
+        self.send_without_response(packet)
+            .map_err(DeadlineSendError::Io)
+    }
 }
 
 pub struct Client {
     conn_new: HashMap<NymNodeRoutingAddress, ConnectionSender>,
     config: Config,
+
+    /// Channel for handing back the queued packets that are not going to be sent before their
+    /// deadlines, because their next hop is down. If not set, such packets are dropped.
+    undeliverable_packets: Option<UndeliverablePacketSender>,
+}
+
+#[derive(Default)]
+struct ConnectionState {
+    current_reconnection_attempt: AtomicU32,
+
+    /// If the peer is currently down, time at which the next connection attempt is going to be made.
+    next_connection_attempt: Mutex<Option<Instant>>,
+
+    /// If the peer is running an outdated version that does not understand the handshake,
+    /// time until which the connections to it are not going to attempt it.
+    legacy_peer_until: Mutex<Option<Instant>>,
+}
+
+impl ConnectionState {
+    fn next_connection_attempt(&self) -> Option<Instant> {
+        *self
+            .next_connection_attempt
+            .lock()
+            .expect("connection state lock got poisoned")
+    }
+
+    fn set_next_connection_attempt(&self, next_attempt: Option<Instant>) {
+        *self
+            .next_connection_attempt
+            .lock()
+            .expect("connection state lock got poisoned") = next_attempt
+    }
+
+    fn is_legacy_peer(&self) -> bool {
+        let mut legacy_peer_until = self
+            .legacy_peer_until
+            .lock()
+            .expect("connection state lock got poisoned");
+        match *legacy_peer_until {
+            Some(until) if until > Instant::now() => true,
+            Some(_) => {
+                // give it another chance, it might have been upgraded in the meantime
+                *legacy_peer_until = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn mark_legacy_peer(&self) {
+        *self
+            .legacy_peer_until
+            .lock()
+            .expect("connection state lock got poisoned") =
+            Some(Instant::now() + LEGACY_PEER_RECHECK_INTERVAL)
+    }
+}
+
+/// Packet waiting to be sent to the peer alongside the time by which it has to be sent, if any.
+struct QueuedPacket {
+    packet: MixPacket,
+    deadline: Option<Instant>,
+}
+
+impl QueuedPacket {
+    fn into_framed(self, use_legacy_version: bool) -> FramedSphinxPacket {
+        #[cfg(feature = "packet-tracing")]
+        let trace_id = self.packet.trace_id();
+
+        let packet_mode = self.packet.packet_mode();
+        let framed_packet = FramedSphinxPacket::new(
+            self.packet.into_sphinx_packet(),
+            packet_mode,
+            use_legacy_version,
+        );
+
+        #[cfg(feature = "packet-tracing")]
+        let framed_packet = framed_packet.with_trace_id(trace_id);
+
+        framed_packet
+    }
 }
 
 struct ConnectionSender {
-    channel: mpsc::Sender<FramedSphinxPacket>,
-    current_reconnection_attempt: Arc<AtomicU32>,
+    channel: mpsc::Sender<QueuedPacket>,
+    state: Arc<ConnectionState>,
 }
 
 impl ConnectionSender {
-    fn new(channel: mpsc::Sender<FramedSphinxPacket>) -> Self {
+    fn new(channel: mpsc::Sender<QueuedPacket>) -> Self {
         ConnectionSender {
             channel,
-            current_reconnection_attempt: Arc::new(AtomicU32::new(0)),
+            state: Default::default(),
         }
     }
 }
@@ -79,41 +221,220 @@ impl Client {
         Client {
             conn_new: HashMap::new(),
             config,
+            undeliverable_packets: None,
         }
     }
 
-    async fn manage_connection(
+    /// Hand back the queued packets that are not going to be sent before their deadlines
+    /// on the provided channel rather than dropping them.
+    #[must_use]
+    pub fn with_undeliverable_packets_sender(mut self, sender: UndeliverablePacketSender) -> Self {
+        self.undeliverable_packets = Some(sender);
+        self
+    }
+
+    async fn try_connect(
         address: SocketAddr,
-        receiver: mpsc::Receiver<FramedSphinxPacket>,
         connection_timeout: Duration,
-        current_reconnection: &AtomicU32,
-    ) {
+        state: &ConnectionState,
+    ) -> Option<Framed<TcpStream, SphinxCodec>> {
         let connection_fut = TcpStream::connect(address);
 
-        let conn = match tokio::time::timeout(connection_timeout, connection_fut).await {
-            Ok(stream_res) => match stream_res {
-                Ok(stream) => {
-                    debug!("Managed to establish connection to {}", address);
+        match tokio::time::timeout(connection_timeout, connection_fut).await {
+            Ok(Ok(stream)) => {
+                debug!("Managed to establish connection to {}", address);
+                if let Some(conn) =
+                    Self::establish_framing(address, stream, connection_timeout, state).await
+                {
                     // if we managed to connect, reset the reconnection count (whatever it might have been)
-                    current_reconnection.store(0, Ordering::Release);
-                    Framed::new(stream, SphinxCodec)
+                    state
+                        .current_reconnection_attempt
+                        .store(0, Ordering::Release);
+                    return Some(conn);
                 }
-                Err(err) => {
-                    debug!(
-                        "failed to establish connection to {} (err: {})",
-                        address, err
-                    );
-                    return;
-                }
-            },
+            }
+            Ok(Err(err)) => {
+                debug!(
+                    "failed to establish connection to {} (err: {})",
+                    address, err
+                );
+            }
             Err(_) => {
                 debug!(
                     "failed to connect to {} within {:?}",
                     address, connection_timeout
                 );
+            }
+        }
+
+        // we failed to connect - increase reconnection attempt
+        state
+            .current_reconnection_attempt
+            .fetch_add(1, Ordering::SeqCst);
+        None
+    }
+
+    /// Performs the handshake on the newly established connection, unless the peer is known not to
+    /// support it, so that any protocol mismatch is detected before we start sending the packets.
+    async fn establish_framing(
+        address: SocketAddr,
+        stream: TcpStream,
+        handshake_timeout: Duration,
+        state: &ConnectionState,
+    ) -> Option<Framed<TcpStream, SphinxCodec>> {
+        if state.is_legacy_peer() {
+            return Some(Framed::new(stream, SphinxCodec));
+        }
+
+        match tokio::time::timeout(handshake_timeout, Self::perform_handshake(stream)).await {
+            Ok(Ok(conn)) => Some(conn),
+            Ok(Err(HandshakeError::ConnectionClosed)) => {
+                // outdated nodes close the connection as soon as they fail to parse the handshake
+                info!(
+                    "{} does not support the connection handshake - falling back to the legacy framing",
+                    address
+                );
+                state.mark_legacy_peer();
+                match tokio::time::timeout(handshake_timeout, TcpStream::connect(address)).await {
+                    Ok(Ok(stream)) => Some(Framed::new(stream, SphinxCodec)),
+                    _ => None,
+                }
+            }
+            Ok(Err(err)) => {
+                warn!("failed to complete the handshake with {}: {}", address, err);
+                None
+            }
+            Err(_) => {
+                debug!(
+                    "{} did not complete the handshake within {:?}",
+                    address, handshake_timeout
+                );
+                None
+            }
+        }
+    }
 
-                // we failed to connect - increase reconnection attempt
-                current_reconnection.fetch_add(1, Ordering::SeqCst);
+    async fn perform_handshake(
+        stream: TcpStream,
+    ) -> Result<Framed<TcpStream, SphinxCodec>, HandshakeError> {
+        let mut framed_conn = Framed::new(stream, HandshakeCodec);
+        framed_conn.send(HandshakeMessage::new_request()).await?;
+
+        let response = match framed_conn.next().await {
+            Some(Ok(response)) => response,
+            Some(Err(HandshakeError::IoError(err)))
+                if err.kind() == io::ErrorKind::ConnectionReset =>
+            {
+                return Err(HandshakeError::ConnectionClosed)
+            }
+            Some(Err(err)) => return Err(err),
+            None => return Err(HandshakeError::ConnectionClosed),
+        };
+        response.evaluate_response()?;
+
+        Ok(Framed::new(framed_conn.into_inner(), SphinxCodec))
+    }
+
+    /// Takes the packets queued up for the peer out of its channel, so that they'd be sent
+    /// at once as soon as the connection gets re-established.
+    fn coalesce_queued(
+        receiver: &mut mpsc::Receiver<QueuedPacket>,
+        pending: &mut VecDeque<QueuedPacket>,
+        maximum_pending: usize,
+    ) {
+        while pending.len() < maximum_pending {
+            match receiver.try_next() {
+                Ok(Some(packet)) => pending.push_back(packet),
+                _ => return,
+            }
+        }
+    }
+
+    /// Hands back the pending packets whose deadlines are going to pass before the connection
+    /// to the peer is re-attempted.
+    fn hand_back_expiring(
+        address: SocketAddr,
+        pending: &mut VecDeque<QueuedPacket>,
+        reconnection_at: Instant,
+        undeliverable_packets: Option<&UndeliverablePacketSender>,
+    ) {
+        let (expiring, remaining): (Vec<_>, VecDeque<_>) = pending.drain(..).partition(
+            |queued| matches!(queued.deadline, Some(deadline) if deadline < reconnection_at),
+        );
+        *pending = remaining;
+        if expiring.is_empty() {
+            return;
+        }
+
+        debug!(
+            "{address} is down until {reconnection_at:?} - {} queued packets are not going to be sent in time",
+            expiring.len()
+        );
+        let Some(sender) = undeliverable_packets else {
+            return;
+        };
+        for queued in expiring {
+            // nobody might be interested in the undeliverable packets anymore, which is fine
+            sender
+                .unbounded_send(UndeliverablePacket {
+                    packet: queued.packet,
+                    reconnection_at,
+                })
+                .ok();
+        }
+    }
+
+    async fn manage_connection(
+        address: SocketAddr,
+        mut receiver: mpsc::Receiver<QueuedPacket>,
+        config: Config,
+        state: Arc<ConnectionState>,
+        undeliverable_packets: Option<UndeliverablePacketSender>,
+    ) {
+        // packets that got taken out of the queue while the peer was down
+        let mut pending = VecDeque::new();
+        let mut previous_backoff = None;
+
+        let conn = loop {
+            // before attempting the connection, wait for what was specified, if anything
+            let reconnection_attempt = state.current_reconnection_attempt.load(Ordering::Acquire);
+            if let Some(backoff) = config.determine_backoff(reconnection_attempt, previous_backoff)
+            {
+                trace!("waiting for {:?} before attempting connection", backoff);
+                previous_backoff = Some(backoff);
+                let reconnection_at = Instant::now() + backoff;
+                state.set_next_connection_attempt(Some(reconnection_at));
+
+                // anything queued before the peer went into the backoff might not make it in time
+                Self::coalesce_queued(
+                    &mut receiver,
+                    &mut pending,
+                    config.maximum_connection_buffer_size,
+                );
+                Self::hand_back_expiring(
+                    address,
+                    &mut pending,
+                    reconnection_at,
+                    undeliverable_packets.as_ref(),
+                );
+                sleep(backoff).await;
+            }
+            state.set_next_connection_attempt(None);
+
+            if let Some(conn) =
+                Self::try_connect(address, config.initial_connection_timeout, &state).await
+            {
+                break conn;
+            }
+
+            // while the peer is down, keep the queued packets around for the next attempt rather
+            // than failing them one by one. but if there's nothing to send, don't bother retrying
+            Self::coalesce_queued(
+                &mut receiver,
+                &mut pending,
+                config.maximum_connection_buffer_size,
+            );
+            if pending.is_empty() {
                 return;
             }
         };
@@ -121,7 +442,13 @@ impl Client {
         // Take whatever the receiver channel produces and put it on the connection.
         // We could have as well used conn.send_all(receiver.map(Ok)), but considering we don't care
         // about neither receiver nor the connection, it doesn't matter which one gets consumed
-        if let Err(err) = receiver.map(Ok).forward(conn).await {
+        let use_legacy_version = config.use_legacy_version;
+        if let Err(err) = stream::iter(pending)
+            .chain(receiver)
+            .map(|queued| Ok(queued.into_framed(use_legacy_version)))
+            .forward(conn)
+            .await
+        {
             warn!("Failed to forward packets to {} - {err}", address);
         }
 
@@ -133,26 +460,18 @@ impl Client {
 
     /// If we're trying to reconnect, determine how long we should wait.
     fn determine_backoff(&self, current_attempt: u32) -> Option<Duration> {
-        if current_attempt == 0 {
-            None
-        } else {
-            let exp = 2_u32.checked_pow(current_attempt);
-            let backoff = exp
-                .and_then(|exp| self.config.initial_reconnection_backoff.checked_mul(exp))
-                .unwrap_or(self.config.maximum_reconnection_backoff);
-
-            Some(std::cmp::min(
-                backoff,
-                self.config.maximum_reconnection_backoff,
-            ))
-        }
+        self.config.determine_backoff(current_attempt, None)
     }
 
-    fn make_connection(
-        &mut self,
-        address: NymNodeRoutingAddress,
-        pending_packet: FramedSphinxPacket,
-    ) {
+    /// If the connection to the peer is currently down, returns the time at which it's going to be
+    /// re-attempted.
+    fn next_connection_attempt(&self, address: &NymNodeRoutingAddress) -> Option<Instant> {
+        self.conn_new
+            .get(address)
+            .and_then(|sender| sender.state.next_connection_attempt())
+    }
+
+    fn make_connection(&mut self, address: NymNodeRoutingAddress, pending_packet: QueuedPacket) {
         let (mut sender, receiver) = mpsc::channel(self.config.maximum_connection_buffer_size);
 
         // this CAN'T fail because we just created the channel which has a non-zero capacity
@@ -160,55 +479,33 @@ impl Client {
             sender.try_send(pending_packet).unwrap();
         }
 
-        // if we already tried to connect to `address` before, grab the current attempt count
-        let current_reconnection_attempt = if let Some(existing) = self.conn_new.get_mut(&address) {
+        // if we already tried to connect to `address` before, grab the current connection state
+        let state = if let Some(existing) = self.conn_new.get_mut(&address) {
             existing.channel = sender;
-            Arc::clone(&existing.current_reconnection_attempt)
+            Arc::clone(&existing.state)
         } else {
             let new_entry = ConnectionSender::new(sender);
-            let current_attempt = Arc::clone(&new_entry.current_reconnection_attempt);
+            let state = Arc::clone(&new_entry.state);
             self.conn_new.insert(address, new_entry);
-            current_attempt
+            state
         };
 
-        // load the actual value.
-        let reconnection_attempt = current_reconnection_attempt.load(Ordering::Acquire);
-        let backoff = self.determine_backoff(reconnection_attempt);
-
-        // copy the value before moving into another task
-        let initial_connection_timeout = self.config.initial_connection_timeout;
-
-        tokio::spawn(async move {
-            // before executing the manager, wait for what was specified, if anything
-            if let Some(backoff) = backoff {
-                trace!("waiting for {:?} before attempting connection", backoff);
-                sleep(backoff).await;
-            }
-
-            Self::manage_connection(
-                address.into(),
-                receiver,
-                initial_connection_timeout,
-                &current_reconnection_attempt,
-            )
-            .await
-        });
+        tokio::spawn(Self::manage_connection(
+            address.into(),
+            receiver,
+            self.config,
+            state,
+            self.undeliverable_packets.clone(),
+        ));
     }
-}
 
-impl SendWithoutResponse for Client {
-    fn send_without_response(
-        &mut self,
-        address: NymNodeRoutingAddress,
-        packet: SphinxPacket,
-        packet_mode: PacketMode,
-    ) -> io::Result<()> {
+    fn queue_packet(&mut self, packet: MixPacket, deadline: Option<Instant>) -> io::Result<()> {
+        let address = packet.next_hop();
         trace!("Sending packet to {:?}", address);
-        let framed_packet =
-            FramedSphinxPacket::new(packet, packet_mode, self.config.use_legacy_version);
 
+        let queued_packet = QueuedPacket { packet, deadline };
         if let Some(sender) = self.conn_new.get_mut(&address) {
-            if let Err(err) = sender.channel.try_send(framed_packet) {
+            if let Err(err) = sender.channel.try_send(queued_packet) {
                 if err.is_full() {
                     debug!("Connection to {} seems to not be able to handle all the traffic - dropping the current packet", address);
                     // it's not a 'big' error, but we did not manage to send the packet
@@ -244,7 +541,7 @@ impl SendWithoutResponse for Client {
             debug!("establishing initial connection to {}", address);
             // it's not a 'big' error, but we did not manage to send the packet, but queue the packet
             // for sending for as soon as the connection is created
-            self.make_connection(address, framed_packet);
+            self.make_connection(address, queued_packet);
             Err(io::Error::new(
                 io::ErrorKind::NotConnected,
                 "connection is in progress",
@@ -253,18 +550,79 @@ impl SendWithoutResponse for Client {
     }
 }
 
+impl SendWithoutResponse for Client {
+    fn send_without_response(&mut self, packet: MixPacket) -> io::Result<()> {
+        self.queue_packet(packet, None)
+    }
+
+    fn send_before_deadline(
+        &mut self,
+        packet: MixPacket,
+        deadline: Instant,
+    ) -> Result<(), DeadlineSendError> {
+        if let Some(reconnection_at) = self.next_connection_attempt(&packet.next_hop()) {
+            if reconnection_at > deadline {
+                trace!(
+                    "{:?} is down until {reconnection_at:?} - handing the packet back",
+                    packet.next_hop()
+                );
+                return Err(DeadlineSendError::NextHopUnavailable(UndeliverablePacket {
+                    packet,
+                    reconnection_at,
+                }));
+            }
+        }
+        self.queue_packet(packet, Some(deadline))
+            .map_err(DeadlineSendError::Io)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nym_sphinx::builder::SphinxPacketBuilder;
+    use nym_sphinx::params::packet_sizes::PacketSize;
+    use nym_sphinx::params::PacketMode;
+    use nym_sphinx::{
+        crypto, Delay, Destination, DestinationAddressBytes, Node, NodeAddressBytes,
+        DESTINATION_ADDRESS_LENGTH, IDENTIFIER_LENGTH, NODE_ADDRESS_LENGTH,
+    };
 
     fn dummy_client() -> Client {
-        Client::new(Config {
-            initial_reconnection_backoff: Duration::from_millis(10_000),
-            maximum_reconnection_backoff: Duration::from_millis(300_000),
-            initial_connection_timeout: Duration::from_millis(1_500),
-            maximum_connection_buffer_size: 128,
-            use_legacy_version: false,
-        })
+        Client::new(Config::new(
+            Duration::from_millis(10_000),
+            Duration::from_millis(300_000),
+            Duration::from_millis(1_500),
+            128,
+            false,
+        ))
+    }
+
+    fn dummy_packet(next_hop: NymNodeRoutingAddress) -> MixPacket {
+        let route = [1u8, 2, 3].map(|i| {
+            Node::new(
+                NodeAddressBytes::from_bytes([i; NODE_ADDRESS_LENGTH]),
+                crypto::keygen().1,
+            )
+        });
+        let destination = Destination::new(
+            DestinationAddressBytes::from_bytes([4u8; DESTINATION_ADDRESS_LENGTH]),
+            [5u8; IDENTIFIER_LENGTH],
+        );
+        let delays = [42, 42, 42].map(Delay::new_from_nanos);
+        let sphinx_packet = SphinxPacketBuilder::new()
+            .with_payload_size(PacketSize::default().payload_size())
+            .build_packet(b"foomp", &route, &destination, &delays)
+            .unwrap();
+        MixPacket::new(next_hop, sphinx_packet, PacketMode::default())
+    }
+
+    // address nothing is listening on, so any connection attempt to it fails straight away
+    async fn unreachable_address() -> NymNodeRoutingAddress {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        address.into()
     }
 
     #[test]
@@ -275,23 +633,95 @@ mod tests {
         assert!(client.determine_backoff(2).is_some());
         assert_eq!(
             client.determine_backoff(16).unwrap(),
-            client.config.maximum_reconnection_backoff
+            client.config.reconnection_backoff.maximum
         );
         assert_eq!(
             client.determine_backoff(32).unwrap(),
-            client.config.maximum_reconnection_backoff
+            client.config.reconnection_backoff.maximum
         );
         assert_eq!(
             client.determine_backoff(1024).unwrap(),
-            client.config.maximum_reconnection_backoff
+            client.config.reconnection_backoff.maximum
         );
         assert_eq!(
             client.determine_backoff(65536).unwrap(),
-            client.config.maximum_reconnection_backoff
+            client.config.reconnection_backoff.maximum
         );
         assert_eq!(
             client.determine_backoff(u32::MAX).unwrap(),
-            client.config.maximum_reconnection_backoff
+            client.config.reconnection_backoff.maximum
         );
     }
+
+    #[test]
+    fn next_connection_attempt_is_only_known_for_peers_in_backoff() {
+        let mut client = dummy_client();
+        let address: NymNodeRoutingAddress = "1.2.3.4:1789".parse::<SocketAddr>().unwrap().into();
+        assert!(client.next_connection_attempt(&address).is_none());
+
+        let (sender, _receiver) = mpsc::channel(1);
+        client
+            .conn_new
+            .insert(address, ConnectionSender::new(sender));
+        assert!(client.next_connection_attempt(&address).is_none());
+
+        let retry_at = Instant::now() + Duration::from_secs(10);
+        client.conn_new[&address]
+            .state
+            .set_next_connection_attempt(Some(retry_at));
+        assert_eq!(client.next_connection_attempt(&address), Some(retry_at));
+    }
+
+    #[tokio::test]
+    async fn packets_past_their_deadline_are_handed_back_while_the_peer_is_in_backoff() {
+        let (undeliverable_sender, mut undeliverable_receiver) = mpsc::unbounded();
+        let mut client = dummy_client().with_undeliverable_packets_sender(undeliverable_sender);
+        let address = unreachable_address().await;
+
+        // the packet gets queued up while the initial connection is attempted, but once it fails,
+        // the peer goes into the 10s backoff which the packet can't wait for
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let res = client.send_before_deadline(dummy_packet(address), deadline);
+        assert!(matches!(res, Err(DeadlineSendError::Io(_))));
+
+        let handed_back =
+            tokio::time::timeout(Duration::from_secs(5), undeliverable_receiver.next())
+                .await
+                .expect("the queued packet was not handed back in time")
+                .unwrap();
+        assert_eq!(handed_back.packet.next_hop(), address);
+        assert!(handed_back.reconnection_at > deadline);
+        assert_eq!(
+            client.next_connection_attempt(&address),
+            Some(handed_back.reconnection_at)
+        );
+
+        // any subsequent packet that can't wait for the reconnection is not queued up at all
+        match client.send_before_deadline(dummy_packet(address), deadline) {
+            Err(DeadlineSendError::NextHopUnavailable(undeliverable)) => {
+                assert_eq!(undeliverable.reconnection_at, handed_back.reconnection_at)
+            }
+            _ => panic!("the packet should have been handed back"),
+        }
+
+        // while the ones that can wait are kept for the next attempt
+        let later_deadline = handed_back.reconnection_at + Duration::from_secs(60);
+        assert!(client
+            .send_before_deadline(dummy_packet(address), later_deadline)
+            .is_ok());
+        assert!(undeliverable_receiver.try_next().is_err());
+    }
+
+    #[test]
+    fn legacy_peers_are_eventually_rechecked() {
+        let state = ConnectionState::default();
+        assert!(!state.is_legacy_peer());
+
+        state.mark_legacy_peer();
+        assert!(state.is_legacy_peer());
+
+        *state.legacy_peer_until.lock().unwrap() = Some(Instant::now() - Duration::from_secs(1));
+        assert!(!state.is_legacy_peer());
+        assert!(state.legacy_peer_until.lock().unwrap().is_none());
+    }
 }
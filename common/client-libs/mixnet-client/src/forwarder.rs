@@ -1,21 +1,68 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::client::{Client, Config, SendWithoutResponse};
+use crate::client::{
+    Client, Config, DeadlineSendError, SendWithoutResponse, UndeliverablePacket,
+    UndeliverablePacketReceiver,
+};
 use futures::channel::mpsc;
 use futures::StreamExt;
 use log::*;
+use nym_retry::Jitter;
+use nym_sphinx::addressing::nodes::NymNodeRoutingAddress;
 use nym_sphinx::forwarding::packet::MixPacket;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::time::Instant;
 
 pub type MixForwardingSender = mpsc::UnboundedSender<MixPacket>;
 type MixForwardingReceiver = mpsc::UnboundedReceiver<MixPacket>;
 
+/// Next hops the packets could not have been forwarded to in time, alongside the time at which
+/// the connections to them are going to be re-attempted. It's shared with whoever is sending
+/// the packets through the forwarder, so that they could choose different routes in the meantime.
+#[derive(Debug, Clone, Default)]
+pub struct UnreachableHops {
+    inner: Arc<Mutex<HashMap<NymNodeRoutingAddress, Instant>>>,
+}
+
+impl UnreachableHops {
+    fn mark_unreachable(&self, hop: NymNodeRoutingAddress, reconnection_at: Instant) {
+        self.inner
+            .lock()
+            .expect("unreachable hops lock got poisoned")
+            .insert(hop, reconnection_at);
+    }
+
+    /// If the hop is currently unreachable, returns the time at which the connection to it
+    /// is going to be re-attempted.
+    pub fn unreachable_until(&self, hop: &NymNodeRoutingAddress) -> Option<Instant> {
+        let mut inner = self
+            .inner
+            .lock()
+            .expect("unreachable hops lock got poisoned");
+        match inner.get(hop) {
+            Some(reconnection_at) if *reconnection_at > Instant::now() => Some(*reconnection_at),
+            Some(_) => {
+                inner.remove(hop);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
 /// A specialisation of client such that it forwards any received packets on the channel into the
 /// mix network immediately, i.e. will not try to listen for any responses.
 pub struct PacketForwarder {
     mixnet_client: Client,
     packet_receiver: MixForwardingReceiver,
+    undeliverable_packets: UndeliverablePacketReceiver,
+
+    /// Maximum duration a packet can wait for the connection to its next hop to be re-established.
+    maximum_forwarding_delay: Duration,
+    unreachable_hops: UnreachableHops,
     shutdown: nym_task::TaskClient,
 }
 
@@ -25,6 +72,7 @@ impl PacketForwarder {
         maximum_reconnection_backoff: Duration,
         initial_connection_timeout: Duration,
         maximum_connection_buffer_size: usize,
+        maximum_forwarding_delay: Duration,
         use_legacy_version: bool,
         shutdown: nym_task::TaskClient,
     ) -> (PacketForwarder, MixForwardingSender) {
@@ -34,20 +82,59 @@ impl PacketForwarder {
             initial_connection_timeout,
             maximum_connection_buffer_size,
             use_legacy_version,
-        );
+        )
+        .with_reconnection_jitter(Jitter::Decorrelated);
 
         let (packet_sender, packet_receiver) = mpsc::unbounded();
+        let (undeliverable_sender, undeliverable_packets) = mpsc::unbounded();
 
         (
             PacketForwarder {
-                mixnet_client: Client::new(client_config),
+                mixnet_client: Client::new(client_config)
+                    .with_undeliverable_packets_sender(undeliverable_sender),
                 packet_receiver,
+                undeliverable_packets,
+                maximum_forwarding_delay,
+                unreachable_hops: Default::default(),
                 shutdown,
             },
             packet_sender,
         )
     }
 
+    pub fn unreachable_hops(&self) -> UnreachableHops {
+        self.unreachable_hops.clone()
+    }
+
+    fn handle_undeliverable(&self, undeliverable: UndeliverablePacket) {
+        let next_hop = undeliverable.packet.next_hop();
+        // we can't re-route the packet ourselves, but we can make sure the senders would avoid the hop
+        debug!(
+            "dropping the packet to {next_hop} as it's unreachable until {:?}",
+            undeliverable.reconnection_at
+        );
+        self.unreachable_hops
+            .mark_unreachable(next_hop, undeliverable.reconnection_at);
+    }
+
+    fn forward_packet(&mut self, mix_packet: MixPacket) {
+        trace!("Going to forward packet to {:?}", mix_packet.next_hop());
+
+        // we don't care about responses, we just want to fire packets
+        // as quickly as possible
+        let deadline = Instant::now() + self.maximum_forwarding_delay;
+        match self
+            .mixnet_client
+            .send_before_deadline(mix_packet, deadline)
+        {
+            Ok(_) => (),
+            Err(DeadlineSendError::NextHopUnavailable(undeliverable)) => {
+                self.handle_undeliverable(undeliverable)
+            }
+            Err(DeadlineSendError::Io(err)) => debug!("failed to forward the packet - {err}"),
+        }
+    }
+
     pub async fn run(&mut self) {
         while !self.shutdown.is_shutdown() {
             tokio::select! {
@@ -55,21 +142,11 @@ impl PacketForwarder {
                 _ = self.shutdown.recv() => {
                     log::trace!("PacketForwarder: Received shutdown");
                 }
+                Some(undeliverable) = self.undeliverable_packets.next() => {
+                    self.handle_undeliverable(undeliverable)
+                }
                 Some(mix_packet) = self.packet_receiver.next() => {
-                     trace!("Going to forward packet to {:?}", mix_packet.next_hop());
-
-                    let next_hop = mix_packet.next_hop();
-                    let packet_mode = mix_packet.packet_mode();
-                    let sphinx_packet = mix_packet.into_sphinx_packet();
-                    // we don't care about responses, we just want to fire packets
-                    // as quickly as possible
-
-                    if let Err(err) =
-                        self.mixnet_client
-                            .send_without_response(next_hop, sphinx_packet, packet_mode)
-                    {
-                        debug!("failed to forward the packet - {err}")
-                    }
+                    self.forward_packet(mix_packet)
                 }
             }
         }
@@ -4,4 +4,7 @@
 pub mod client;
 pub mod forwarder;
 
-pub use client::{Client, Config, SendWithoutResponse};
+pub use client::{
+    Client, Config, DeadlineSendError, SendWithoutResponse, UndeliverablePacket,
+    UndeliverablePacketReceiver, UndeliverablePacketSender,
+};
@@ -3,11 +3,14 @@
 
 use crate::{nym_api, ValidatorClientError};
 use nym_api_requests::coconut::{
-    BlindSignRequestBody, BlindedSignatureResponse, VerifyCredentialBody, VerifyCredentialResponse,
+    BlindSignRequestBody, BlindedSignatureResponse, DkgStatusResponse,
+    ResharingDiagnosticsRequestBody, ResharingDiagnosticsResponse, VerifyCredentialBody,
+    VerifyCredentialResponse,
 };
 use nym_api_requests::models::{
-    GatewayCoreStatusResponse, MixnodeCoreStatusResponse, MixnodeStatusResponse,
-    RewardEstimationResponse, StakeSaturationResponse,
+    GatewayBondAnnotated, GatewayCoreStatusResponse, MixNodeBondAnnotated, MixNodeFamilyMembership,
+    MixnodeCoreStatusResponse, MixnodeStatusResponse, RewardEstimationResponse,
+    SignedNodeHeartbeat, StakeSaturationResponse, TopologyDiffResponse,
 };
 use nym_coconut_dkg_common::types::NodeIndex;
 use nym_coconut_interface::VerificationKey;
@@ -21,8 +24,6 @@ use crate::nyxd::{self, CosmWasmClient, NyxdClient, QueryNyxdClient, SigningNyxd
 #[cfg(feature = "nyxd-client")]
 use crate::signing::direct_wallet::DirectSecp256k1HdWallet;
 #[cfg(feature = "nyxd-client")]
-use nym_api_requests::models::MixNodeBondAnnotated;
-#[cfg(feature = "nyxd-client")]
 use nym_coconut_dkg_common::{types::EpochId, verification_key::ContractVKShare};
 #[cfg(feature = "nyxd-client")]
 use nym_coconut_interface::Base58;
@@ -46,6 +47,11 @@ pub struct Config {
     nyxd_url: Url,
 
     nyxd_config: nyxd::Config,
+    nym_api_proxy: Option<Url>,
+
+    /// Gas price used by the signing client. If not set, the default price in the mix
+    /// denomination of the network is used.
+    gas_price: Option<nyxd::GasPrice>,
 
     mixnode_page_limit: Option<u32>,
     gateway_page_limit: Option<u32>,
@@ -76,6 +82,8 @@ impl Config {
                 .parse()
                 .map_err(ValidatorClientError::MalformedUrlProvided)?,
             nyxd_config: nyxd::Config::try_from_nym_network_details(details)?,
+            nym_api_proxy: None,
+            gas_price: None,
             mixnode_page_limit: None,
             gateway_page_limit: None,
             mixnode_delegations_page_limit: None,
@@ -96,6 +104,24 @@ impl Config {
         self
     }
 
+    /// Routes all the requests to the nym API through the specified HTTP(S) or SOCKS5 proxy.
+    pub fn with_nym_api_proxy(mut self, proxy: Option<Url>) -> Self {
+        self.nym_api_proxy = proxy;
+        self
+    }
+
+    /// Routes all the requests to the nyxd validator through the specified HTTP proxy.
+    pub fn with_nyxd_proxy(mut self, proxy: Option<Url>) -> Self {
+        self.nyxd_config = self.nyxd_config.with_proxy(proxy);
+        self
+    }
+
+    /// Pays the fees of the transactions using the specified gas price (and thus denomination).
+    pub fn with_gas_price(mut self, gas_price: nyxd::GasPrice) -> Self {
+        self.gas_price = Some(gas_price);
+        self
+    }
+
     pub fn with_mixnode_page_limit(mut self, limit: Option<u32>) -> Config {
         self.mixnode_page_limit = limit;
         self
@@ -135,12 +161,13 @@ impl Client<SigningNyxdClient<DirectSecp256k1HdWallet>> {
         config: Config,
         mnemonic: bip39::Mnemonic,
     ) -> Result<Client<SigningNyxdClient<DirectSecp256k1HdWallet>>, ValidatorClientError> {
-        let nym_api_client = nym_api::Client::new(config.api_url.clone());
+        let nym_api_client =
+            nym_api::Client::new_with_proxy(config.api_url.clone(), config.nym_api_proxy.clone())?;
         let nyxd_client = NyxdClient::connect_with_mnemonic(
             config.nyxd_config.clone(),
             config.nyxd_url.as_str(),
             mnemonic,
-            None,
+            config.gas_price.clone(),
         )?;
 
         Ok(Client {
@@ -166,7 +193,8 @@ impl Client<SigningNyxdClient<DirectSecp256k1HdWallet>> {
 #[cfg(feature = "nyxd-client")]
 impl Client<QueryNyxdClient> {
     pub fn new_query(config: Config) -> Result<Client<QueryNyxdClient>, ValidatorClientError> {
-        let nym_api_client = nym_api::Client::new(config.api_url.clone());
+        let nym_api_client =
+            nym_api::Client::new_with_proxy(config.api_url.clone(), config.nym_api_proxy.clone())?;
         let nyxd_client =
             NyxdClient::connect(config.nyxd_config.clone(), config.nyxd_url.as_str())?;
 
@@ -687,6 +715,13 @@ impl NymApiClient {
         NymApiClient { nym_api_client }
     }
 
+    /// Creates the client with all of its requests routed through the specified HTTP(S) or SOCKS5 proxy.
+    pub fn new_with_proxy(api_url: Url, proxy: Option<Url>) -> Result<Self, ValidatorClientError> {
+        let nym_api_client = nym_api::Client::new_with_proxy(api_url, proxy)?;
+
+        Ok(NymApiClient { nym_api_client })
+    }
+
     pub fn change_nym_api(&mut self, new_endpoint: Url) {
         self.nym_api_client.change_url(new_endpoint);
     }
@@ -697,6 +732,12 @@ impl NymApiClient {
         Ok(self.nym_api_client.get_active_mixnodes().await?)
     }
 
+    pub async fn get_cached_active_mixnodes_detailed(
+        &self,
+    ) -> Result<Vec<MixNodeBondAnnotated>, ValidatorClientError> {
+        Ok(self.nym_api_client.get_active_mixnodes_detailed().await?)
+    }
+
     pub async fn get_cached_rewarded_mixnodes(
         &self,
     ) -> Result<Vec<MixNodeDetails>, ValidatorClientError> {
@@ -711,6 +752,25 @@ impl NymApiClient {
         Ok(self.nym_api_client.get_gateways().await?)
     }
 
+    pub async fn get_cached_gateways_detailed(
+        &self,
+    ) -> Result<Vec<GatewayBondAnnotated>, ValidatorClientError> {
+        Ok(self.nym_api_client.get_gateways_detailed().await?)
+    }
+
+    pub async fn get_cached_topology_diff(
+        &self,
+        since: Option<u64>,
+    ) -> Result<TopologyDiffResponse, ValidatorClientError> {
+        Ok(self.nym_api_client.get_topology_diff(since).await?)
+    }
+
+    pub async fn get_cached_mixnode_families(
+        &self,
+    ) -> Result<Vec<MixNodeFamilyMembership>, ValidatorClientError> {
+        Ok(self.nym_api_client.get_mixnode_families().await?)
+    }
+
     pub async fn get_gateway_core_status_count(
         &self,
         identity: IdentityKeyRef<'_>,
@@ -760,6 +820,13 @@ impl NymApiClient {
             .await?)
     }
 
+    pub async fn send_node_heartbeat(
+        &self,
+        heartbeat: &SignedNodeHeartbeat,
+    ) -> Result<(), ValidatorClientError> {
+        Ok(self.nym_api_client.send_node_heartbeat(heartbeat).await?)
+    }
+
     pub async fn blind_sign(
         &self,
         request_body: &BlindSignRequestBody,
@@ -776,4 +843,18 @@ impl NymApiClient {
             .verify_bandwidth_credential(request_body)
             .await?)
     }
+
+    pub async fn resharing_diagnostics(
+        &self,
+        request_body: &ResharingDiagnosticsRequestBody,
+    ) -> Result<ResharingDiagnosticsResponse, ValidatorClientError> {
+        Ok(self
+            .nym_api_client
+            .resharing_diagnostics(request_body)
+            .await?)
+    }
+
+    pub async fn get_dkg_status(&self) -> Result<DkgStatusResponse, ValidatorClientError> {
+        Ok(self.nym_api_client.get_dkg_status().await?)
+    }
 }
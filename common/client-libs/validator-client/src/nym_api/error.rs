@@ -1,4 +1,5 @@
 use nym_api_requests::models::RequestError;
+use nym_retry::Retryable;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -12,6 +13,27 @@ pub enum NymAPIError {
     #[error("Request failed with error message - {0}")]
     GenericRequestFailure(String),
 
+    #[error("Routing the requests through a proxy is not supported on this platform")]
+    ProxyNotSupported,
+
     #[error("The nym API has failed to resolve our request. It returned status code {status} and additional error message: {}", error.message())]
     ApiRequestFailure { status: u16, error: RequestError },
 }
+
+impl Retryable for NymAPIError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            NymAPIError::ReqwestClientError { source } => {
+                let transient = source.is_timeout()
+                    || matches!(source.status(), Some(status) if status.is_server_error());
+
+                #[cfg(not(target_arch = "wasm32"))]
+                let transient = transient || source.is_connect();
+
+                transient
+            }
+            NymAPIError::ApiRequestFailure { status, .. } => *status >= 500,
+            NymAPIError::GenericRequestFailure(_) | NymAPIError::ProxyNotSupported => false,
+        }
+    }
+}
@@ -4,19 +4,24 @@
 use crate::nym_api::error::NymAPIError;
 use crate::nym_api::routes::{CORE_STATUS_COUNT, SINCE_ARG};
 use nym_api_requests::coconut::{
-    BlindSignRequestBody, BlindedSignatureResponse, VerifyCredentialBody, VerifyCredentialResponse,
+    BlindSignRequestBody, BlindedSignatureResponse, DkgStatusResponse,
+    ResharingDiagnosticsRequestBody, ResharingDiagnosticsResponse, VerifyCredentialBody,
+    VerifyCredentialResponse,
 };
 use nym_api_requests::models::{
-    ComputeRewardEstParam, GatewayCoreStatusResponse, GatewayStatusReportResponse,
-    GatewayUptimeHistoryResponse, InclusionProbabilityResponse, MixNodeBondAnnotated,
+    ComputeRewardEstParam, EpochRewardsDryRunResponse, GatewayBondAnnotated,
+    GatewayCoreStatusResponse, GatewayStatusReportResponse, GatewayUptimeHistoryResponse,
+    InclusionProbabilityResponse, MixNodeBondAnnotated, MixNodeFamilyMembership,
     MixnodeCoreStatusResponse, MixnodeStatusReportResponse, MixnodeStatusResponse,
-    MixnodeUptimeHistoryResponse, RequestError, RewardEstimationResponse, StakeSaturationResponse,
-    UptimeResponse,
+    MixnodeUptimeHistoryResponse, RequestError, RewardEstimationResponse, SignedNodeHeartbeat,
+    StakeSaturationResponse, TopologyDiffResponse, UptimeResponse,
 };
 use nym_mixnet_contract_common::mixnode::MixNodeDetails;
 use nym_mixnet_contract_common::{GatewayBond, IdentityKeyRef, MixId};
+use nym_retry::{retry, ExponentialBackoff, Jitter, RetryPolicy};
 use reqwest::Response;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use url::Url;
 
 pub mod error;
@@ -27,10 +32,25 @@ type Params<'a, K, V> = &'a [(K, V)];
 
 const NO_PARAMS: Params<'_, &'_ str, &'_ str> = &[];
 
+const DEFAULT_QUERY_ATTEMPTS: u32 = 3;
+const DEFAULT_QUERY_INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+const DEFAULT_QUERY_MAXIMUM_BACKOFF: Duration = Duration::from_secs(2);
+
+fn default_retry_policy() -> RetryPolicy {
+    RetryPolicy::new(
+        ExponentialBackoff::new(DEFAULT_QUERY_INITIAL_BACKOFF, DEFAULT_QUERY_MAXIMUM_BACKOFF)
+            .with_jitter(Jitter::Decorrelated),
+    )
+    .with_max_attempts(DEFAULT_QUERY_ATTEMPTS)
+}
+
 #[derive(Clone)]
 pub struct Client {
     url: Url,
     reqwest_client: reqwest::Client,
+
+    /// Policy used for re-attempting the queries that failed due to transient errors.
+    retry_policy: RetryPolicy,
 }
 
 impl Client {
@@ -39,9 +59,30 @@ impl Client {
         Self {
             url,
             reqwest_client,
+            retry_policy: default_retry_policy(),
         }
     }
 
+    /// Creates the client with all of its requests routed through the specified HTTP(S) or SOCKS5
+    /// proxy. Use the `socks5h` scheme for the hostnames (such as the `.onion` ones) to get
+    /// resolved by the proxy itself.
+    pub fn new_with_proxy(url: Url, proxy: Option<Url>) -> Result<Self, NymAPIError> {
+        let Some(proxy) = proxy else {
+            return Ok(Self::new(url));
+        };
+        Ok(Self {
+            url,
+            reqwest_client: proxied_reqwest_client(&proxy)?,
+            retry_policy: default_retry_policy(),
+        })
+    }
+
+    #[must_use]
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     pub fn change_url(&mut self, new_url: Url) {
         self.url = new_url
     }
@@ -68,6 +109,19 @@ impl Client {
         path: PathSegments<'_>,
         params: Params<'_, K, V>,
     ) -> Result<T, NymAPIError>
+    where
+        for<'a> T: Deserialize<'a>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        retry(&self.retry_policy, || self.try_query_nym_api(path, params)).await
+    }
+
+    async fn try_query_nym_api<T, K, V>(
+        &self,
+        path: PathSegments<'_>,
+        params: Params<'_, K, V>,
+    ) -> Result<T, NymAPIError>
     where
         for<'a> T: Deserialize<'a>,
         K: AsRef<str>,
@@ -87,6 +141,22 @@ impl Client {
         path: PathSegments<'_>,
         params: Params<'_, K, V>,
     ) -> Result<T, NymAPIError>
+    where
+        for<'a> T: Deserialize<'a>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        retry(&self.retry_policy, || {
+            self.try_query_nym_api_fallible(path, params)
+        })
+        .await
+    }
+
+    async fn try_query_nym_api_fallible<T, K, V>(
+        &self,
+        path: PathSegments<'_>,
+        params: Params<'_, K, V>,
+    ) -> Result<T, NymAPIError>
     where
         for<'a> T: Deserialize<'a>,
         K: AsRef<str>,
@@ -164,6 +234,46 @@ impl Client {
             .await
     }
 
+    pub async fn get_gateways_detailed(&self) -> Result<Vec<GatewayBondAnnotated>, NymAPIError> {
+        self.query_nym_api(
+            &[
+                routes::API_VERSION,
+                routes::STATUS,
+                routes::GATEWAYS,
+                routes::DETAILED,
+            ],
+            NO_PARAMS,
+        )
+        .await
+    }
+
+    pub async fn get_mixnode_families(&self) -> Result<Vec<MixNodeFamilyMembership>, NymAPIError> {
+        self.query_nym_api(
+            &[routes::API_VERSION, routes::MIXNODES, routes::FAMILIES],
+            NO_PARAMS,
+        )
+        .await
+    }
+
+    pub async fn get_topology_diff(
+        &self,
+        since: Option<u64>,
+    ) -> Result<TopologyDiffResponse, NymAPIError> {
+        if let Some(since) = since {
+            self.query_nym_api(
+                &[routes::API_VERSION, routes::TOPOLOGY, routes::DIFF],
+                &[(SINCE_ARG, since.to_string())],
+            )
+            .await
+        } else {
+            self.query_nym_api(
+                &[routes::API_VERSION, routes::TOPOLOGY, routes::DIFF],
+                NO_PARAMS,
+            )
+            .await
+        }
+    }
+
     pub async fn get_active_mixnodes(&self) -> Result<Vec<MixNodeDetails>, NymAPIError> {
         self.query_nym_api(
             &[routes::API_VERSION, routes::MIXNODES, routes::ACTIVE],
@@ -396,6 +506,37 @@ impl Client {
         .await
     }
 
+    pub async fn send_node_heartbeat(
+        &self,
+        heartbeat: &SignedNodeHeartbeat,
+    ) -> Result<(), NymAPIError> {
+        self.post_nym_api(
+            &[
+                routes::API_VERSION,
+                routes::STATUS_ROUTES,
+                routes::HEARTBEAT,
+            ],
+            NO_PARAMS,
+            heartbeat,
+        )
+        .await
+    }
+
+    pub async fn get_epoch_rewards_dry_run(
+        &self,
+    ) -> Result<EpochRewardsDryRunResponse, NymAPIError> {
+        self.query_nym_api_fallible(
+            &[
+                routes::API_VERSION,
+                routes::STATUS_ROUTES,
+                routes::EPOCH,
+                routes::REWARDS_DRY_RUN,
+            ],
+            NO_PARAMS,
+        )
+        .await
+    }
+
     pub async fn get_mixnode_stake_saturation(
         &self,
         mix_id: MixId,
@@ -480,6 +621,48 @@ impl Client {
         )
         .await
     }
+
+    pub async fn resharing_diagnostics(
+        &self,
+        request_body: &ResharingDiagnosticsRequestBody,
+    ) -> Result<ResharingDiagnosticsResponse, NymAPIError> {
+        self.post_nym_api(
+            &[
+                routes::API_VERSION,
+                routes::COCONUT_ROUTES,
+                routes::DKG,
+                routes::DKG_RESHARING_DIAGNOSTICS,
+            ],
+            NO_PARAMS,
+            request_body,
+        )
+        .await
+    }
+
+    pub async fn get_dkg_status(&self) -> Result<DkgStatusResponse, NymAPIError> {
+        self.query_nym_api(
+            &[
+                routes::API_VERSION,
+                routes::COCONUT_ROUTES,
+                routes::DKG,
+                routes::DKG_STATUS,
+            ],
+            NO_PARAMS,
+        )
+        .await
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn proxied_reqwest_client(proxy: &Url) -> Result<reqwest::Client, NymAPIError> {
+    let proxy = reqwest::Proxy::all(proxy.as_str())?;
+    Ok(reqwest::Client::builder().proxy(proxy).build()?)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn proxied_reqwest_client(_proxy: &Url) -> Result<reqwest::Client, NymAPIError> {
+    // requests made from within the browser are subject to its own proxy settings
+    Err(NymAPIError::ProxyNotSupported)
 }
 
 // utility function that should solve the double slash problem in validator API forever.
@@ -513,6 +696,19 @@ fn create_api_url<K: AsRef<str>, V: AsRef<str>>(
 mod tests {
     use super::*;
 
+    #[test]
+    fn creating_proxied_client() {
+        let base_url: Url = "http://foomp.com".parse().unwrap();
+
+        for proxy in ["http://127.0.0.1:8080", "socks5h://127.0.0.1:9050"] {
+            let proxy = Some(proxy.parse().unwrap());
+            assert!(Client::new_with_proxy(base_url.clone(), proxy).is_ok());
+        }
+
+        let unsupported = Some("ftp://127.0.0.1:21".parse().unwrap());
+        assert!(Client::new_with_proxy(base_url, unsupported).is_err());
+    }
+
     #[test]
     fn creating_api_path() {
         let base_url: Url = "http://foomp.com".parse().unwrap();
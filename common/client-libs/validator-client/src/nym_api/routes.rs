@@ -6,6 +6,9 @@ use nym_network_defaults::NYM_API_VERSION;
 pub const API_VERSION: &str = NYM_API_VERSION;
 pub const MIXNODES: &str = "mixnodes";
 pub const GATEWAYS: &str = "gateways";
+pub const TOPOLOGY: &str = "topology";
+pub const DIFF: &str = "diff";
+pub const FAMILIES: &str = "families";
 
 pub const DETAILED: &str = "detailed";
 pub const DETAILED_UNFILTERED: &str = "detailed-unfiltered";
@@ -17,6 +20,10 @@ pub const BANDWIDTH: &str = "bandwidth";
 pub const COCONUT_BLIND_SIGN: &str = "blind-sign";
 pub const COCONUT_VERIFY_BANDWIDTH_CREDENTIAL: &str = "verify-bandwidth-credential";
 
+pub const DKG: &str = "dkg";
+pub const DKG_RESHARING_DIAGNOSTICS: &str = "resharing-diagnostics";
+pub const DKG_STATUS: &str = "status";
+
 pub const STATUS_ROUTES: &str = "status";
 pub const MIXNODE: &str = "mixnode";
 pub const GATEWAY: &str = "gateway";
@@ -29,6 +36,9 @@ pub const REPORT: &str = "report";
 pub const HISTORY: &str = "history";
 pub const REWARD_ESTIMATION: &str = "reward-estimation";
 pub const COMPUTE_REWARD_ESTIMATION: &str = "compute-reward-estimation";
+pub const EPOCH: &str = "epoch";
+pub const REWARDS_DRY_RUN: &str = "rewards-dry-run";
 pub const AVG_UPTIME: &str = "avg_uptime";
 pub const STAKE_SATURATION: &str = "stake-saturation";
 pub const INCLUSION_CHANCE: &str = "inclusion-probability";
+pub const HEARTBEAT: &str = "heartbeat";
@@ -5,6 +5,7 @@ use crate::nyxd::error::NyxdError;
 use crate::nyxd::GasPrice;
 use cosmrs::rpc::{Error as TendermintRpcError, HttpClient, HttpClientUrl};
 use std::convert::TryInto;
+use url::Url;
 
 pub mod client;
 mod helpers;
@@ -19,6 +20,16 @@ where
     Ok(HttpClient::new(endpoint)?)
 }
 
+pub fn connect_with_proxy<U>(endpoint: U, proxy: Option<&Url>) -> Result<HttpClient, NyxdError>
+where
+    U: TryInto<HttpClientUrl, Error = TendermintRpcError>,
+{
+    match proxy {
+        Some(proxy) => Ok(HttpClient::new_with_proxy(endpoint, proxy.as_str())?),
+        None => connect(endpoint),
+    }
+}
+
 pub fn connect_with_signer<S, U: Clone>(
     endpoint: U,
     signer: S,
@@ -27,5 +38,5 @@ pub fn connect_with_signer<S, U: Clone>(
 where
     U: TryInto<HttpClientUrl, Error = TendermintRpcError>,
 {
-    signing_client::Client::connect_with_signer(endpoint, signer, gas_price)
+    signing_client::Client::connect_with_signer(endpoint, signer, gas_price, None)
 }
@@ -29,6 +29,7 @@ use sha2::Digest;
 use sha2::Sha256;
 use std::convert::TryInto;
 use std::time::{Duration, SystemTime};
+use url::Url;
 
 const DEFAULT_BROADCAST_POLLING_RATE: Duration = Duration::from_secs(4);
 const DEFAULT_BROADCAST_TIMEOUT: Duration = Duration::from_secs(60);
@@ -718,6 +719,9 @@ pub struct Client<S> {
     tx_signer: TxSigner<S>,
     gas_price: GasPrice,
 
+    /// HTTP proxy all the requests are routed through. It's kept around for when the endpoint changes.
+    proxy: Option<Url>,
+
     broadcast_polling_rate: Duration,
     broadcast_timeout: Duration,
 }
@@ -727,15 +731,17 @@ impl<S> Client<S> {
         endpoint: U,
         signer: S,
         gas_price: GasPrice,
+        proxy: Option<Url>,
     ) -> Result<Self, NyxdError>
     where
         U: TryInto<HttpClientUrl, Error = TendermintRpcError>,
     {
-        let rpc_client = HttpClient::new(endpoint)?;
+        let rpc_client = super::connect_with_proxy(endpoint, proxy.as_ref())?;
         Ok(Client {
             rpc_client,
             tx_signer: TxSigner::new(signer),
             gas_price,
+            proxy,
             broadcast_polling_rate: DEFAULT_BROADCAST_POLLING_RATE,
             broadcast_timeout: DEFAULT_BROADCAST_TIMEOUT,
         })
@@ -752,7 +758,7 @@ impl<S> Client<S> {
     where
         U: TryInto<HttpClientUrl, Error = TendermintRpcError>,
     {
-        let new_rpc_client = HttpClient::new(new_endpoint)?;
+        let new_rpc_client = super::connect_with_proxy(new_endpoint, self.proxy.as_ref())?;
         self.rpc_client = new_rpc_client;
         Ok(())
     }
@@ -12,15 +12,16 @@ use crate::signing::direct_wallet::DirectSecp256k1HdWallet;
 use crate::signing::signer::OfflineSigner;
 use cosmrs::cosmwasm;
 use cosmrs::rpc::endpoint::block::Response as BlockResponse;
-use cosmrs::rpc::query::Query;
 use cosmrs::rpc::Error as TendermintRpcError;
 use cosmrs::rpc::HttpClientUrl;
+use cosmrs::tendermint::chain;
 use cosmrs::tx::Msg;
 use log::debug;
 use nym_network_defaults::{ChainDetails, NymNetworkDetails};
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 use std::time::SystemTime;
+use url::Url;
 
 pub use crate::nyxd::cosmwasm_client::client::CosmWasmClient;
 pub use crate::nyxd::cosmwasm_client::signing_client::SigningCosmWasmClient;
@@ -29,6 +30,7 @@ pub use coin::Coin;
 pub use cosmrs::bank::MsgSend;
 pub use cosmrs::rpc::endpoint::tx::Response as TxResponse;
 pub use cosmrs::rpc::endpoint::validators::Response as ValidatorResponse;
+pub use cosmrs::rpc::query::Query;
 pub use cosmrs::rpc::HttpClient as QueryNyxdClient;
 pub use cosmrs::rpc::Paging;
 pub use cosmrs::tendermint::abci::responses::{DeliverTx, Event};
@@ -67,6 +69,9 @@ pub struct Config {
     pub(crate) group_contract_address: Option<AccountId>,
     pub(crate) multisig_contract_address: Option<AccountId>,
     pub(crate) coconut_dkg_contract_address: Option<AccountId>,
+
+    /// HTTP proxy all the requests to the validator are routed through.
+    pub(crate) proxy: Option<Url>,
     // TODO: add this in later commits
     // pub(crate) gas_price: GasPrice,
 }
@@ -131,8 +136,16 @@ impl Config {
                 details.contracts.coconut_dkg_contract_address.as_ref(),
                 prefix,
             )?,
+            proxy: None,
         })
     }
+
+    /// Routes all the requests to the validator through the specified HTTP proxy.
+    /// Note that, unlike the nym API, the validators can't be reached via SOCKS proxies.
+    pub fn with_proxy(mut self, proxy: Option<Url>) -> Self {
+        self.proxy = proxy;
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -149,7 +162,7 @@ impl NyxdClient<QueryNyxdClient> {
         U: TryInto<HttpClientUrl, Error = TendermintRpcError>,
     {
         Ok(NyxdClient {
-            client: QueryNyxdClient::new(endpoint)?,
+            client: cosmwasm_client::connect_with_proxy(endpoint, config.proxy.as_ref())?,
             config,
             client_address: None,
             simulated_gas_multiplier: DEFAULT_SIMULATED_GAS_MULTIPLIER,
@@ -198,7 +211,12 @@ where
         let gas_price = gas_price.unwrap_or(GasPrice::new_with_default_price(denom)?);
 
         Ok(NyxdClient {
-            client: SigningNyxdClient::connect_with_signer(endpoint, signer, gas_price)?,
+            client: SigningNyxdClient::connect_with_signer(
+                endpoint,
+                signer,
+                gas_price,
+                config.proxy.clone(),
+            )?,
             config,
             client_address: Some(client_address),
             simulated_gas_multiplier: DEFAULT_SIMULATED_GAS_MULTIPLIER,
@@ -454,6 +472,14 @@ impl<C> NyxdClient<C> {
         self.client.get_height().await
     }
 
+    /// Obtains the id of the chain the connected validator is running on.
+    pub async fn get_chain_id(&self) -> Result<chain::Id, NyxdError>
+    where
+        C: CosmWasmClient + Sync,
+    {
+        self.client.get_chain_id().await
+    }
+
     /// Obtains the hash of a block specified by the provided height.
     ///
     /// # Arguments
@@ -5,6 +5,9 @@ use crate::nyxd::error::NyxdError;
 use crate::nyxd::{CosmWasmClient, NyxdClient};
 use async_trait::async_trait;
 use cosmrs::AccountId;
+use nym_coconut_dkg_common::complaints::{
+    DealerComplaintsResponse, DisputedDealer, PagedDisputedDealersResponse,
+};
 use nym_coconut_dkg_common::dealer::{
     ContractDealing, DealerDetailsResponse, PagedDealerResponse, PagedDealingsResponse,
 };
@@ -28,6 +31,11 @@ pub trait DkgQueryClient {
         self.query_dkg_contract(request).await
     }
 
+    async fn get_total_dealings(&self) -> Result<u32, NyxdError> {
+        let request = DkgQueryMsg::GetTotalDealings {};
+        self.query_dkg_contract(request).await
+    }
+
     async fn get_initial_dealers(&self) -> Result<Option<InitialReplacementData>, NyxdError> {
         let request = DkgQueryMsg::GetInitialDealers {};
         self.query_dkg_contract(request).await
@@ -95,6 +103,28 @@ pub trait DkgQueryClient {
         self.query_dkg_contract(request).await
     }
 
+    async fn get_dealer_complaints(
+        &self,
+        address: &AccountId,
+    ) -> Result<DealerComplaintsResponse, NyxdError> {
+        let request = DkgQueryMsg::GetDealerComplaints {
+            dealer_address: address.to_string(),
+        };
+        self.query_dkg_contract(request).await
+    }
+
+    async fn get_disputed_dealers_paged(
+        &self,
+        start_after: Option<String>,
+        page_limit: Option<u32>,
+    ) -> Result<PagedDisputedDealersResponse, NyxdError> {
+        let request = DkgQueryMsg::GetDisputedDealers {
+            start_after,
+            limit: page_limit,
+        };
+        self.query_dkg_contract(request).await
+    }
+
     async fn get_all_current_dealers(&self) -> Result<Vec<DealerDetails>, NyxdError> {
         let mut dealers = Vec::new();
         let mut start_after = None;
@@ -173,6 +203,25 @@ pub trait DkgQueryClient {
 
         Ok(shares)
     }
+
+    async fn get_all_disputed_dealers(&self) -> Result<Vec<DisputedDealer>, NyxdError> {
+        let mut dealers = Vec::new();
+        let mut start_after = None;
+        loop {
+            let mut paged_response = self
+                .get_disputed_dealers_paged(start_after.take(), None)
+                .await?;
+            dealers.append(&mut paged_response.dealers);
+
+            if let Some(start_after_res) = paged_response.start_next_after {
+                start_after = Some(start_after_res.into_string())
+            } else {
+                break;
+            }
+        }
+
+        Ok(dealers)
+    }
 }
 
 #[async_trait]
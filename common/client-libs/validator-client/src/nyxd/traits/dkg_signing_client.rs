@@ -5,6 +5,8 @@ use crate::nyxd::cosmwasm_client::types::ExecuteResult;
 use crate::nyxd::error::NyxdError;
 use crate::nyxd::{Fee, NyxdClient, SigningCosmWasmClient};
 use async_trait::async_trait;
+use cosmrs::AccountId;
+use nym_coconut_dkg_common::complaints::ComplaintReason;
 use nym_coconut_dkg_common::msg::ExecuteMsg as DkgExecuteMsg;
 use nym_coconut_dkg_common::types::EncodedBTEPublicKeyWithProof;
 use nym_coconut_dkg_common::verification_key::VerificationKeyShare;
@@ -28,6 +30,15 @@ pub trait DkgSigningClient {
         fee: Option<Fee>,
     ) -> Result<ExecuteResult, NyxdError>;
 
+    async fn submit_complaint(
+        &self,
+        dealer: &AccountId,
+        dealing_index: Option<u64>,
+        reason: ComplaintReason,
+        resharing: bool,
+        fee: Option<Fee>,
+    ) -> Result<ExecuteResult, NyxdError>;
+
     async fn submit_verification_key_share(
         &self,
         share: VerificationKeyShare,
@@ -104,6 +115,33 @@ where
             .await
     }
 
+    async fn submit_complaint(
+        &self,
+        dealer: &AccountId,
+        dealing_index: Option<u64>,
+        reason: ComplaintReason,
+        resharing: bool,
+        fee: Option<Fee>,
+    ) -> Result<ExecuteResult, NyxdError> {
+        let req = DkgExecuteMsg::SubmitComplaint {
+            dealer: dealer.to_string(),
+            dealing_index,
+            reason,
+            resharing,
+        };
+
+        self.client
+            .execute(
+                self.address(),
+                self.coconut_dkg_contract_address(),
+                &req,
+                fee.unwrap_or_default(),
+                "complaint submission",
+                vec![],
+            )
+            .await
+    }
+
     async fn submit_verification_key_share(
         &self,
         share: VerificationKeyShare,
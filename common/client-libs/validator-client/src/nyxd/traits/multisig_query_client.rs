@@ -5,12 +5,14 @@ use crate::nyxd::error::NyxdError;
 use crate::nyxd::{CosmWasmClient, NyxdClient};
 
 use cw3::{ProposalListResponse, ProposalResponse};
-use nym_multisig_contract_common::msg::QueryMsg;
+use nym_multisig_contract_common::msg::{ConfigResponse, QueryMsg};
 
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait MultisigQueryClient {
+    async fn get_multisig_config(&self) -> Result<ConfigResponse, NyxdError>;
+
     async fn get_proposal(&self, proposal_id: u64) -> Result<ProposalResponse, NyxdError>;
 
     async fn list_proposals(
@@ -42,6 +44,13 @@ pub trait MultisigQueryClient {
 
 #[async_trait]
 impl<C: CosmWasmClient + Sync + Send> MultisigQueryClient for NyxdClient<C> {
+    async fn get_multisig_config(&self) -> Result<ConfigResponse, NyxdError> {
+        let request = QueryMsg::Config {};
+        self.client
+            .query_contract_smart(self.multisig_contract_address(), &request)
+            .await
+    }
+
     async fn get_proposal(&self, proposal_id: u64) -> Result<ProposalResponse, NyxdError> {
         let request = QueryMsg::Proposal { proposal_id };
         self.client
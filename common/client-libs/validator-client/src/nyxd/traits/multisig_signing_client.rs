@@ -23,10 +23,12 @@ pub trait MultisigSigningClient {
         fee: Option<Fee>,
     ) -> Result<ExecuteResult, NyxdError>;
 
+    /// Votes on the specified proposal. The optional memo can be used to justify the vote.
     async fn vote_proposal(
         &self,
         proposal_id: u64,
         yes: bool,
+        memo: Option<String>,
         fee: Option<Fee>,
     ) -> Result<ExecuteResult, NyxdError>;
 
@@ -80,6 +82,7 @@ impl<C: SigningCosmWasmClient + Sync + Send> MultisigSigningClient for NyxdClien
         &self,
         proposal_id: u64,
         vote_yes: bool,
+        memo: Option<String>,
         fee: Option<Fee>,
     ) -> Result<ExecuteResult, NyxdError> {
         let fee = fee.unwrap_or(Fee::Auto(Some(self.simulated_gas_multiplier)));
@@ -91,7 +94,7 @@ impl<C: SigningCosmWasmClient + Sync + Send> MultisigSigningClient for NyxdClien
                 self.multisig_contract_address(),
                 &req,
                 fee,
-                "Multisig::Vote",
+                memo.unwrap_or_else(|| "Multisig::Vote".to_string()),
                 vec![],
             )
             .await
@@ -10,6 +10,9 @@ use error::CoconutInterfaceError;
 
 pub use nym_coconut::*;
 
+// number of attributes of the credentials issued before the introduction of the expiration date
+const LEGACY_N_PARAMS: u32 = 4;
+
 #[derive(Debug, Serialize, Deserialize, Getters, CopyGetters, Clone, PartialEq, Eq)]
 pub struct Credential {
     #[getset(get = "pub")]
@@ -20,6 +23,11 @@ pub struct Credential {
     voucher_info: String,
     #[getset(get = "pub")]
     epoch_id: u64,
+    /// Unix timestamp after which the credential is no longer valid.
+    /// It is not present on the credentials issued before the introduction of the attribute.
+    #[serde(default)]
+    #[getset(get_copy = "pub")]
+    expiration_date: Option<u64>,
 }
 impl Credential {
     pub fn new(
@@ -28,6 +36,7 @@ impl Credential {
         voucher_value: u64,
         voucher_info: String,
         epoch_id: u64,
+        expiration_date: Option<u64>,
     ) -> Credential {
         Credential {
             n_params,
@@ -35,6 +44,7 @@ impl Credential {
             voucher_value,
             voucher_info,
             epoch_id,
+            expiration_date,
         }
     }
 
@@ -57,13 +67,15 @@ impl Credential {
 
     pub fn verify(&self, verification_key: &VerificationKey) -> bool {
         let params = Parameters::new(self.n_params).unwrap();
-        let public_attributes = vec![
-            self.voucher_value.to_string().as_bytes(),
-            self.voucher_info.as_bytes(),
-        ]
-        .iter()
-        .map(hash_to_scalar)
-        .collect::<Vec<Attribute>>();
+        let mut public_attributes_plain =
+            vec![self.voucher_value.to_string(), self.voucher_info.clone()];
+        if let Some(expiration_date) = self.expiration_date {
+            public_attributes_plain.push(expiration_date.to_string());
+        }
+        let public_attributes = public_attributes_plain
+            .iter()
+            .map(hash_to_scalar)
+            .collect::<Vec<Attribute>>();
         nym_coconut::verify_credential(&params, verification_key, &self.theta, &public_attributes)
     }
 
@@ -76,12 +88,17 @@ impl Credential {
         let voucher_info_bytes = self.voucher_info.as_bytes();
         let voucher_info_len = voucher_info_bytes.len();
 
-        let mut bytes = Vec::with_capacity(28 + theta_bytes_len + voucher_info_len);
+        let mut bytes = Vec::with_capacity(36 + theta_bytes_len + voucher_info_len);
         bytes.extend_from_slice(&n_params_bytes);
         bytes.extend_from_slice(&(theta_bytes_len as u64).to_be_bytes());
         bytes.extend_from_slice(&theta_bytes);
         bytes.extend_from_slice(&voucher_value_bytes);
         bytes.extend_from_slice(&epoch_id_bytes);
+        // the expiration date is only present on the credentials with the additional attribute,
+        // so that the legacy ones keep their original representation
+        if let Some(expiration_date) = self.expiration_date {
+            bytes.extend_from_slice(&expiration_date.to_be_bytes());
+        }
         bytes.extend_from_slice(voucher_info_bytes);
 
         bytes
@@ -111,7 +128,22 @@ impl Credential {
         let voucher_value = u64::from_be_bytes(eight_byte);
         eight_byte.copy_from_slice(&bytes[20 + theta_len as usize..28 + theta_len as usize]);
         let epoch_id = u64::from_be_bytes(eight_byte);
-        let voucher_info = String::from_utf8(bytes[28 + theta_len as usize..].to_vec())
+
+        let mut voucher_info_start = 28 + theta_len as usize;
+        let expiration_date = if n_params > LEGACY_N_PARAMS {
+            if bytes.len() < voucher_info_start + 8 {
+                return Err(CoconutError::Deserialization(String::from(
+                    "To few bytes in credential",
+                )));
+            }
+            eight_byte.copy_from_slice(&bytes[voucher_info_start..voucher_info_start + 8]);
+            voucher_info_start += 8;
+            Some(u64::from_be_bytes(eight_byte))
+        } else {
+            None
+        };
+
+        let voucher_info = String::from_utf8(bytes[voucher_info_start..].to_vec())
             .map_err(|e| CoconutError::Deserialization(e.to_string()))?;
 
         Ok(Credential {
@@ -120,6 +152,7 @@ impl Credential {
             voucher_value,
             voucher_info,
             epoch_id,
+            expiration_date,
         })
     }
 }
@@ -175,11 +208,26 @@ mod tests {
             binding_number,
         )
         .unwrap();
-        let credential = Credential::new(4, theta, voucher_value, voucher_info, 42);
+        let credential = Credential::new(
+            4,
+            theta.clone(),
+            voucher_value,
+            voucher_info.clone(),
+            42,
+            None,
+        );
 
         let serialized_credential = credential.as_bytes();
         let deserialized_credential = Credential::from_bytes(&serialized_credential).unwrap();
 
         assert_eq!(credential, deserialized_credential);
+
+        let expiring_credential =
+            Credential::new(5, theta, voucher_value, voucher_info, 42, Some(1700000000));
+
+        let serialized_credential = expiring_credential.as_bytes();
+        let deserialized_credential = Credential::from_bytes(&serialized_credential).unwrap();
+
+        assert_eq!(expiring_credential, deserialized_credential);
     }
 }
@@ -23,6 +23,9 @@ pub struct Args {
     #[clap(long)]
     pub dealing_exchange_time_secs: Option<u64>,
 
+    #[clap(long)]
+    pub complaint_submission_time_secs: Option<u64>,
+
     #[clap(long)]
     pub verification_key_submission_time_secs: Option<u64>,
 
@@ -37,6 +40,10 @@ pub struct Args {
 
     #[clap(long)]
     pub mix_denom: Option<String>,
+
+    /// Number of dealings each dealer submits, i.e. the number of attributes the keys sign plus one
+    #[clap(long)]
+    pub total_dealings: Option<u32>,
 }
 
 pub async fn generate(args: Args) {
@@ -68,6 +75,9 @@ pub async fn generate(args: Args) {
     if let Some(dealing_exchange_time_secs) = args.dealing_exchange_time_secs {
         time_configuration.dealing_exchange_time_secs = dealing_exchange_time_secs;
     }
+    if let Some(complaint_submission_time_secs) = args.complaint_submission_time_secs {
+        time_configuration.complaint_submission_time_secs = complaint_submission_time_secs;
+    }
     if let Some(verification_key_submission_time_secs) = args.verification_key_submission_time_secs
     {
         time_configuration.verification_key_submission_time_secs =
@@ -93,6 +103,7 @@ pub async fn generate(args: Args) {
         multisig_addr: multisig_addr.to_string(),
         time_configuration: Some(time_configuration),
         mix_denom,
+        total_dealings: args.total_dealings,
     };
 
     debug!("instantiate_msg: {:?}", instantiate_msg);
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use clap::Parser;
+use nym_crypto::asymmetric::identity;
 
 #[derive(Debug, Parser)]
 pub struct Args {
@@ -11,7 +12,8 @@ pub struct Args {
 
 pub fn decode_mixnode_key(args: Args) {
     let b64_decoded = base64::decode(args.key).expect("failed to decode base64 string");
-    let b58_encoded = bs58::encode(&b64_decoded).into_string();
+    let key = identity::PublicKey::from_bytes(&b64_decoded)
+        .expect("the decoded bytes do not represent a valid identity key");
 
-    println!("{b58_encoded}")
+    println!("{key}")
 }
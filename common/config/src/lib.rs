@@ -15,16 +15,19 @@ use std::str::FromStr;
 use std::{fs, io};
 
 pub mod defaults;
+pub mod paths;
+pub mod serde_helpers;
 
 pub const CONFIG_DIR: &str = "config";
 pub const DATA_DIR: &str = "data";
+pub const DEFAULT_CONFIG_FILE_NAME: &str = "config.toml";
 pub const CRED_DB_FILE_NAME: &str = "credentials_database.db";
 
 pub trait NymConfig: Default + Serialize + DeserializeOwned {
     fn template() -> &'static str;
 
     fn config_file_name() -> String {
-        "config.toml".to_string()
+        DEFAULT_CONFIG_FILE_NAME.to_string()
     }
 
     fn default_root_directory() -> PathBuf;
@@ -100,10 +103,15 @@ pub trait NymConfig: Default + Serialize + DeserializeOwned {
     fn load_from_file(id: &str) -> io::Result<Self> {
         let file = Self::default_config_file_path(id);
         log::trace!("Loading from file: {:#?}", file);
-        let config_contents = fs::read_to_string(file)?;
-
-        toml::from_str(&config_contents)
-            .map_err(|toml_err| io::Error::new(io::ErrorKind::Other, toml_err))
+        let config_contents = fs::read_to_string(&file)?;
+
+        // note: the toml error already includes the name of the offending field
+        toml::from_str(&config_contents).map_err(|toml_err| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                format!("failed to parse config file {}: {toml_err}", file.display()),
+            )
+        })
     }
 }
 
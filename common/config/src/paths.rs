@@ -0,0 +1,305 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Platform specific locations of the nym directories:
+//! - `$XDG_DATA_HOME/nym` (or `~/.local/share/nym`) on Linux,
+//! - `~/Library/Application Support/nym` on macOS,
+//! - `%APPDATA%\nym` on Windows.
+//!
+//! The directories created in the legacy `~/.nym` location are moved to the new one with
+//! [`migrate_legacy_nym_directory`], which the binaries call once on startup. Until that happens
+//! (or if it fails), the legacy directories keep on being used. The binaries that are still
+//! running from the legacy directory should be stopped before the upgraded ones are started.
+
+use crate::DEFAULT_CONFIG_FILE_NAME;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const NYM_DIR: &str = "nym";
+const LEGACY_NYM_DIR: &str = ".nym";
+const MIGRATION_LOCK_EXTENSION: &str = "migration-lock";
+
+/// The platform specific directory containing the data of all nym binaries.
+pub fn platform_nym_directory() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(NYM_DIR))
+}
+
+/// The `~/.nym` directory used by the previous versions of the binaries.
+pub fn legacy_nym_directory() -> Option<PathBuf> {
+    dirs::home_dir().map(|dir| dir.join(LEGACY_NYM_DIR))
+}
+
+/// Returns the default directory of the particular kind of binary, such as `gateways`
+/// or `clients`. The legacy directory is returned if it's still in use, i.e. if it hasn't been
+/// migrated to the new location.
+pub fn default_nym_directory(kind: &str) -> Option<PathBuf> {
+    let legacy = legacy_nym_directory().map(|dir| dir.join(kind));
+    let Some(target) = platform_nym_directory().map(|dir| dir.join(kind)) else {
+        return legacy;
+    };
+
+    match legacy {
+        Some(legacy) if legacy.is_dir() && !target.exists() => Some(legacy),
+        _ => Some(target),
+    }
+}
+
+/// Moves the directory of the particular kind of binary from the legacy location to the new one,
+/// if it's still being used. It's meant to be called once, on startup, before any config is loaded.
+/// If the migration fails, the legacy directory keeps on being used.
+///
+/// The migration is guarded by a lock file placed next to the legacy directory, so that binaries
+/// started at the same time wouldn't attempt to move the same directory. If the lock file is left
+/// behind by an interrupted migration, it has to be removed manually.
+pub fn migrate_legacy_nym_directory(kind: &str) {
+    let (Some(legacy), Some(target)) = (
+        legacy_nym_directory().map(|dir| dir.join(kind)),
+        platform_nym_directory().map(|dir| dir.join(kind)),
+    ) else {
+        return;
+    };
+    if !legacy.is_dir() || target.exists() {
+        return;
+    }
+
+    let lock_path = legacy.with_extension(MIGRATION_LOCK_EXTENSION);
+    let _lock = match MigrationLock::acquire(&lock_path) {
+        Ok(lock) => lock,
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            log::warn!(
+                "{} is already being moved by another process. If that's not the case, remove {}. The old location is going to be used for now",
+                legacy.display(),
+                lock_path.display()
+            );
+            return;
+        }
+        Err(err) => {
+            log::warn!(
+                "failed to create the migration lock {}: {err}. The old location is going to be used for now",
+                lock_path.display()
+            );
+            return;
+        }
+    };
+
+    // another process might have completed the migration in the meantime
+    if !legacy.is_dir() || target.exists() {
+        return;
+    }
+
+    match migrate_directory(&legacy, &target) {
+        Ok(()) => log::info!("moved {} to {}", legacy.display(), target.display()),
+        Err(err) if legacy.is_dir() && !target.exists() => log::warn!(
+            "failed to move {} to {}: {err}. The old location is going to be used instead",
+            legacy.display(),
+            target.display()
+        ),
+        Err(err) => log::error!(
+            "failed to move {} to {}: {err}. The migration could not be rolled back and has to be completed manually",
+            legacy.display(),
+            target.display()
+        ),
+    }
+}
+
+/// Lock file held for the duration of the migration. It's removed once dropped.
+struct MigrationLock {
+    path: PathBuf,
+}
+
+impl MigrationLock {
+    fn acquire(path: &Path) -> io::Result<Self> {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+        // purely informational, so that it'd be possible to tell who's holding the lock
+        writeln!(file, "{}", std::process::id())?;
+        Ok(MigrationLock {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for MigrationLock {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            log::warn!(
+                "failed to remove the migration lock {}: {err}",
+                self.path.display()
+            )
+        }
+    }
+}
+
+/// Config file whose paths have to be updated once the directory is moved.
+struct ConfigUpdate {
+    /// Path of the config relative to the migrated directory.
+    relative_path: PathBuf,
+    original: String,
+    updated: String,
+}
+
+/// Moves the directory to its new location and updates the paths inside all the config files
+/// it contained. All the configs are read and updated before anything is moved. If any of them
+/// can't be written afterwards, the migration is rolled back.
+fn migrate_directory(legacy: &Path, target: &Path) -> io::Result<()> {
+    let mut updates = Vec::new();
+    collect_config_updates(legacy, legacy, target, &mut updates)?;
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(legacy, target)?;
+
+    if let Err(err) = write_configs(target, &updates, |update| &update.updated) {
+        let rollback = write_configs(target, &updates, |update| &update.original)
+            .and_then(|_| fs::rename(target, legacy));
+        return match rollback {
+            Ok(()) => Err(err),
+            Err(rollback_err) => Err(io::Error::new(
+                err.kind(),
+                format!("{err} (failed to roll back the migration: {rollback_err})"),
+            )),
+        };
+    }
+    Ok(())
+}
+
+fn collect_config_updates(
+    dir: &Path,
+    legacy: &Path,
+    target: &Path,
+    updates: &mut Vec<ConfigUpdate>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // don't follow the symlinks as they might point outside the migrated directory
+        if entry.file_type()?.is_dir() {
+            collect_config_updates(&path, legacy, target, updates)?;
+        } else if path.file_name() == Some(DEFAULT_CONFIG_FILE_NAME.as_ref()) {
+            let original = fs::read_to_string(&path)?;
+            let updated = replace_path_prefix(&original, legacy, target);
+            if updated != original {
+                // the unwrap is fine as we're only ever traversing the legacy directory
+                let relative_path = path.strip_prefix(legacy).unwrap().to_path_buf();
+                updates.push(ConfigUpdate {
+                    relative_path,
+                    original,
+                    updated,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_configs<F>(dir: &Path, updates: &[ConfigUpdate], content: F) -> io::Result<()>
+where
+    F: Fn(&ConfigUpdate) -> &str,
+{
+    for update in updates {
+        fs::write(dir.join(&update.relative_path), content(update))?;
+    }
+    Ok(())
+}
+
+/// Replaces all the paths starting with the `legacy` one, i.e. those where it's followed
+/// by a separator or where the path ends (so that `/foo/nym` would not match `/foo/nymx`).
+/// The paths have to be quoted, as they are in the config files.
+fn replace_path_prefix(content: &str, legacy: &Path, target: &Path) -> String {
+    let legacy = legacy.display().to_string();
+    let target = target.display().to_string();
+
+    let is_boundary = |c: Option<char>| matches!(c, None | Some('/' | '\\' | '\'' | '"'));
+    let is_start = |c: Option<char>| matches!(c, None | Some('\'' | '"'));
+
+    let mut updated = String::with_capacity(content.len());
+    let mut last = 0;
+    for (start, _) in content.match_indices(&legacy) {
+        let end = start + legacy.len();
+        if !is_start(content[..start].chars().next_back())
+            || !is_boundary(content[end..].chars().next())
+        {
+            continue;
+        }
+        updated.push_str(&content[last..start]);
+        updated.push_str(&target);
+        last = end;
+    }
+    updated.push_str(&content[last..]);
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_legacy_prefix_is_replaced() {
+        let content = "\
+nym_root_directory = '/home/user/.nym/gateways'
+private_identity_key_file = '/home/user/.nym/gateways/foo/data/private_identity.pem'
+something_else = '/opt/home/user/.nym/gateways'
+similar = '/home/user/.nym/gateways-old/foo'
+";
+        let updated = replace_path_prefix(
+            content,
+            Path::new("/home/user/.nym/gateways"),
+            Path::new("/home/user/.local/share/nym/gateways"),
+        );
+        assert_eq!(
+            updated,
+            "\
+nym_root_directory = '/home/user/.local/share/nym/gateways'
+private_identity_key_file = '/home/user/.local/share/nym/gateways/foo/data/private_identity.pem'
+something_else = '/opt/home/user/.nym/gateways'
+similar = '/home/user/.nym/gateways-old/foo'
+"
+        );
+    }
+
+    #[test]
+    fn legacy_directory_is_migrated_with_its_configs() {
+        let temp = tempfile::tempdir().unwrap();
+        let legacy = temp.path().join(".nym").join("gateways");
+        let target = temp.path().join("nym").join("gateways");
+
+        let config_dir = legacy.join("foo").join("config");
+        fs::create_dir_all(&config_dir).unwrap();
+        let config_path = config_dir.join(DEFAULT_CONFIG_FILE_NAME);
+        fs::write(
+            &config_path,
+            format!("id = '{}'", legacy.join("foo").display()),
+        )
+        .unwrap();
+
+        migrate_directory(&legacy, &target).unwrap();
+
+        assert!(!legacy.exists());
+        let migrated = fs::read_to_string(
+            target
+                .join("foo")
+                .join("config")
+                .join(DEFAULT_CONFIG_FILE_NAME),
+        )
+        .unwrap();
+        assert_eq!(migrated, format!("id = '{}'", target.join("foo").display()));
+    }
+
+    #[test]
+    fn migration_lock_is_exclusive() {
+        let temp = tempfile::tempdir().unwrap();
+        let lock_path = temp.path().join("gateways.migration-lock");
+
+        let lock = MigrationLock::acquire(&lock_path).unwrap();
+        let err = MigrationLock::acquire(&lock_path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+
+        drop(lock);
+        assert!(!lock_path.exists());
+        assert!(MigrationLock::acquire(&lock_path).is_ok());
+    }
+}
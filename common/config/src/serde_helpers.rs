@@ -0,0 +1,192 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers for (de)serializing human-friendly config values.
+//!
+//! Durations are expected to be used with `humantime_serde` (i.e. `"5s"`, `"250ms"`),
+//! while sizes can be used with the [`bytesize`] module defined here.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+const UNITS: &[(&str, u64)] = &[
+    ("TiB", 1 << 40),
+    ("GiB", 1 << 30),
+    ("MiB", 1 << 20),
+    ("KiB", 1 << 10),
+    ("TB", 1_000_000_000_000),
+    ("GB", 1_000_000_000),
+    ("MB", 1_000_000),
+    ("KB", 1_000),
+    ("B", 1),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ByteSizeParseError {
+    Empty,
+    InvalidNumber(String),
+    UnknownUnit(String),
+    Overflow(String),
+}
+
+impl Display for ByteSizeParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ByteSizeParseError::Empty => write!(f, "the size value is empty"),
+            ByteSizeParseError::InvalidNumber(raw) => {
+                write!(f, "'{raw}' does not start with a valid non-negative number")
+            }
+            ByteSizeParseError::UnknownUnit(unit) => write!(
+                f,
+                "'{unit}' is not a known size unit. Use one of B, KB, MB, GB, TB, KiB, MiB, GiB or TiB"
+            ),
+            ByteSizeParseError::Overflow(raw) => write!(f, "'{raw}' is too large to be represented"),
+        }
+    }
+}
+
+impl std::error::Error for ByteSizeParseError {}
+
+/// Parses size such as `"10MB"`, `"512 KiB"` or `"1.5GB"` into the number of bytes.
+/// Values without any unit are interpreted as bytes.
+pub fn parse_bytes(raw: &str) -> Result<u64, ByteSizeParseError> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(ByteSizeParseError::Empty);
+    }
+
+    let unit_start = raw
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(unit_start);
+    let unit = unit.trim();
+
+    let multiplier = if unit.is_empty() {
+        1
+    } else {
+        UNITS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(unit))
+            .map(|(_, multiplier)| *multiplier)
+            .ok_or_else(|| ByteSizeParseError::UnknownUnit(unit.to_string()))?
+    };
+
+    // try to avoid going through floats whenever possible so that we wouldn't lose any precision
+    if let Ok(value) = u64::from_str(number) {
+        return value
+            .checked_mul(multiplier)
+            .ok_or_else(|| ByteSizeParseError::Overflow(raw.to_string()));
+    }
+
+    let value =
+        f64::from_str(number).map_err(|_| ByteSizeParseError::InvalidNumber(raw.to_string()))?;
+    let bytes = value * multiplier as f64;
+    if bytes >= u64::MAX as f64 {
+        return Err(ByteSizeParseError::Overflow(raw.to_string()));
+    }
+    Ok(bytes as u64)
+}
+
+/// Formats the number of bytes using the largest unit that represents it exactly, e.g. `"1GB"`.
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes == 0 {
+        return "0B".to_string();
+    }
+    // unwrap is fine as the last unit always divides the value
+    let (unit, multiplier) = UNITS
+        .iter()
+        .find(|(_, multiplier)| bytes % multiplier == 0)
+        .unwrap();
+    format!("{}{unit}", bytes / multiplier)
+}
+
+/// (De)serializes number of bytes as a human-friendly size, such as `"10MB"`.
+/// Plain integers are accepted as well and are interpreted as the number of bytes.
+///
+/// Usage: `#[serde(with = "nym_config::serde_helpers::bytesize")]`
+pub mod bytesize {
+    use super::{format_bytes, parse_bytes};
+    use serde::de::{self, Visitor};
+    use serde::{Deserializer, Serializer};
+    use std::fmt::{self, Formatter};
+
+    pub fn serialize<S: Serializer>(bytes: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format_bytes(*bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        struct ByteSizeVisitor;
+
+        impl<'de> Visitor<'de> for ByteSizeVisitor {
+            type Value = u64;
+
+            fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a size such as \"10MB\" or a number of bytes")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(v)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                u64::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                parse_bytes(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(ByteSizeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_sizes() {
+        assert_eq!(parse_bytes("42").unwrap(), 42);
+        assert_eq!(parse_bytes("10MB").unwrap(), 10_000_000);
+        assert_eq!(parse_bytes("10 mb").unwrap(), 10_000_000);
+        assert_eq!(parse_bytes("512KiB").unwrap(), 512 * 1024);
+        assert_eq!(parse_bytes("1.5GB").unwrap(), 1_500_000_000);
+        assert_eq!(
+            parse_bytes("10XB"),
+            Err(ByteSizeParseError::UnknownUnit("XB".to_string()))
+        );
+        assert!(parse_bytes("").is_err());
+        assert!(parse_bytes("MB").is_err());
+        assert!(parse_bytes("99999999999TB").is_err());
+    }
+
+    #[test]
+    fn formatting_sizes_roundtrips() {
+        for bytes in [0, 1, 1000, 1024, 1_000_000_000, 1_500_000_000, 123_456_789] {
+            assert_eq!(parse_bytes(&format_bytes(bytes)).unwrap(), bytes)
+        }
+        assert_eq!(format_bytes(1_000_000_000), "1GB");
+        assert_eq!(format_bytes(2 * 1024 * 1024), "2MiB");
+    }
+
+    #[test]
+    fn bytesize_in_config() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Dummy {
+            #[serde(with = "bytesize")]
+            quota: u64,
+        }
+
+        let parsed: Dummy = toml::from_str("quota = '10MB'").unwrap();
+        assert_eq!(parsed.quota, 10_000_000);
+        let parsed: Dummy = toml::from_str("quota = 1234").unwrap();
+        assert_eq!(parsed.quota, 1234);
+
+        let err = toml::from_str::<Dummy>("quota = '10 potatoes'").unwrap_err();
+        assert!(err.to_string().contains("quota"));
+
+        let serialized = toml::to_string(&Dummy { quota: 2_000_000 }).unwrap();
+        assert_eq!(serialized.trim(), "quota = \"2MB\"");
+    }
+}
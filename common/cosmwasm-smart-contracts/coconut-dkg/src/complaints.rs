@@ -0,0 +1,73 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use cosmwasm_std::Addr;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Misbehaviour of a dealer that made the receivers exclude it from the key derivation.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, JsonSchema)]
+pub enum ComplaintReason {
+    MalformedBTEPublicKey,
+    InvalidBTEPublicKey,
+    MissingDealing,
+    MalformedDealing,
+    DealingVerificationError,
+    /// The dealer announced a BTE public key different from the one it used earlier in the
+    /// ceremony or, when resharing, in a prior epoch.
+    InconsistentBTEPublicKey,
+}
+
+/// Complaint raised by one of the receivers against a dealer of the current epoch.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Complaint {
+    pub complainer: Addr,
+    pub dealer: Addr,
+    /// Index of the offending dealing, if the complaint concerns a particular one.
+    pub dealing_index: Option<u64>,
+    pub reason: ComplaintReason,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DealerComplaintsResponse {
+    pub dealer: Addr,
+    pub complaints: Vec<Complaint>,
+    /// Indicates whether enough receivers (at least the threshold) have complained about the dealer
+    /// for it to be excluded by everyone.
+    pub upheld: bool,
+}
+
+/// Dealer of the current epoch that has been complained about.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DisputedDealer {
+    pub dealer: Addr,
+    pub complaints: u32,
+    /// Indicates whether enough receivers (at least the threshold) have complained about the dealer
+    /// for it to be excluded by everyone.
+    pub upheld: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PagedDisputedDealersResponse {
+    pub dealers: Vec<DisputedDealer>,
+    pub per_page: usize,
+    pub start_next_after: Option<Addr>,
+}
+
+impl PagedDisputedDealersResponse {
+    pub fn new(
+        dealers: Vec<DisputedDealer>,
+        per_page: usize,
+        start_next_after: Option<Addr>,
+    ) -> Self {
+        PagedDisputedDealersResponse {
+            dealers,
+            per_page,
+            start_next_after,
+        }
+    }
+}
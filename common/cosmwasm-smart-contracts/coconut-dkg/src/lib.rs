@@ -1,3 +1,4 @@
+pub mod complaints;
 pub mod dealer;
 pub mod event_attributes;
 pub mod msg;
@@ -1,6 +1,7 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::complaints::ComplaintReason;
 use crate::types::{ContractSafeBytes, EncodedBTEPublicKeyWithProof, EpochId, TimeConfiguration};
 use crate::verification_key::VerificationKeyShare;
 use cosmwasm_std::Addr;
@@ -13,6 +14,11 @@ pub struct InstantiateMsg {
     pub multisig_addr: String,
     pub time_configuration: Option<TimeConfiguration>,
     pub mix_denom: String,
+
+    /// Number of dealings each dealer is going to submit, derived from the number of attributes
+    /// the keys are meant to sign. If not provided, the default value is used.
+    #[serde(default)]
+    pub total_dealings: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
@@ -39,6 +45,13 @@ pub enum ExecuteMsg {
         resharing: bool,
     },
 
+    SubmitComplaint {
+        dealer: String,
+        dealing_index: Option<u64>,
+        reason: ComplaintReason,
+        resharing: bool,
+    },
+
     SurpassedThreshold {},
 
     AdvanceEpochState {},
@@ -49,6 +62,7 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     GetCurrentEpochState {},
     GetCurrentEpochThreshold {},
+    GetTotalDealings {},
     GetInitialDealers {},
     GetDealerDetails {
         dealer_address: String,
@@ -71,8 +85,19 @@ pub enum QueryMsg {
         limit: Option<u32>,
         start_after: Option<String>,
     },
+    GetDealerComplaints {
+        dealer_address: String,
+    },
+    GetDisputedDealers {
+        limit: Option<u32>,
+        start_after: Option<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// Changes the number of dealings each dealer is going to submit in the following epochs.
+    #[serde(default)]
+    pub total_dealings: Option<u32>,
+}
@@ -15,8 +15,34 @@ pub type EncodedBTEPublicKeyWithProofRef<'a> = &'a str;
 pub type NodeIndex = u64;
 pub type EpochId = u64;
 
-// 2 public attributes, 2 private attributes, 1 fixed for coconut credential
-pub const TOTAL_DEALINGS: usize = 2 + 2 + 1;
+/// Number of attributes of the bandwidth credentials: 3 public (voucher value, voucher info
+/// and expiration date) and 2 private (serial number and binding number).
+pub const BANDWIDTH_CREDENTIAL_ATTRIBUTES: u32 = 3 + 2;
+
+/// Number of dealings each dealer submits, unless the contract has been instantiated with a different value.
+pub const DEFAULT_DEALINGS: usize = dealings_for_attributes(BANDWIDTH_CREDENTIAL_ATTRIBUTES);
+
+/// Maximum number of dealings the contract is able to store for each dealer.
+pub const MAX_DEALINGS: usize = 16;
+
+/// Number of dealings each dealer has to submit for the derived keys to be able to sign
+/// credentials with the specified number of attributes.
+pub const fn dealings_for_attributes(attributes: u32) -> usize {
+    attributes as usize + 1
+}
+
+/// Number of attributes the keys derived from the specified number of dealings are able to sign,
+/// i.e. the inverse of [`dealings_for_attributes`].
+pub const fn attributes_for_dealings(dealings: usize) -> u32 {
+    dealings.saturating_sub(1) as u32
+}
+
+/// Minimum number of the initial dealers that have to leave the group for the keys to be generated
+/// from scratch rather than the existing ones being reshared amongst the new dealer set.
+pub fn replacement_threshold(threshold: u64, initial_dealers: usize) -> usize {
+    // note: ceiling in integer division can be achieved via q = (x + y - 1) / y;
+    (threshold as usize).saturating_sub((initial_dealers + 2 - 1) / 2) + 1
+}
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
 pub struct InitialReplacementData {
@@ -24,6 +50,10 @@ pub struct InitialReplacementData {
     pub initial_height: u64,
 }
 
+fn default_complaint_submission_time_secs() -> u64 {
+    60 * 5 // 5 minutes
+}
+
 #[derive(
     Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Ord, PartialOrd, JsonSchema,
 )]
@@ -31,6 +61,9 @@ pub struct TimeConfiguration {
     // The time sign-up is open for dealers to join
     pub public_key_submission_time_secs: u64,
     pub dealing_exchange_time_secs: u64,
+    // introduced after the other fields, so the already stored configurations won't have it
+    #[serde(default = "default_complaint_submission_time_secs")]
+    pub complaint_submission_time_secs: u64,
     pub verification_key_submission_time_secs: u64,
     pub verification_key_validation_time_secs: u64,
     pub verification_key_finalization_time_secs: u64,
@@ -47,17 +80,48 @@ impl FromStr for TimeConfiguration {
             .map(|t| t.parse())
             .collect::<Result<Vec<u64>, _>>()
             .map_err(|_| String::from("Could not parse string"))?;
-        if times.len() != 6 {
-            Err(String::from("Not enough time specified"))
-        } else {
-            Ok(TimeConfiguration {
+        match times.len() {
+            // configurations predating the complaint submission use its default duration
+            6 => Ok(TimeConfiguration {
                 public_key_submission_time_secs: times[0],
                 dealing_exchange_time_secs: times[1],
+                complaint_submission_time_secs: default_complaint_submission_time_secs(),
                 verification_key_submission_time_secs: times[2],
                 verification_key_validation_time_secs: times[3],
                 verification_key_finalization_time_secs: times[4],
                 in_progress_time_secs: times[5],
-            })
+            }),
+            7 => Ok(TimeConfiguration {
+                public_key_submission_time_secs: times[0],
+                dealing_exchange_time_secs: times[1],
+                complaint_submission_time_secs: times[2],
+                verification_key_submission_time_secs: times[3],
+                verification_key_validation_time_secs: times[4],
+                verification_key_finalization_time_secs: times[5],
+                in_progress_time_secs: times[6],
+            }),
+            _ => Err(String::from("Not enough time specified")),
+        }
+    }
+}
+
+impl TimeConfiguration {
+    /// How long the epoch is meant to stay in the provided state.
+    pub fn state_duration_secs(&self, state: EpochState) -> u64 {
+        match state {
+            EpochState::PublicKeySubmission { .. } => self.public_key_submission_time_secs,
+            EpochState::DealingExchange { .. } => self.dealing_exchange_time_secs,
+            EpochState::ComplaintSubmission { .. } => self.complaint_submission_time_secs,
+            EpochState::VerificationKeySubmission { .. } => {
+                self.verification_key_submission_time_secs
+            }
+            EpochState::VerificationKeyValidation { .. } => {
+                self.verification_key_validation_time_secs
+            }
+            EpochState::VerificationKeyFinalization { .. } => {
+                self.verification_key_finalization_time_secs
+            }
+            EpochState::InProgress => self.in_progress_time_secs,
         }
     }
 }
@@ -65,8 +129,9 @@ impl FromStr for TimeConfiguration {
 impl Default for TimeConfiguration {
     fn default() -> Self {
         Self {
-            public_key_submission_time_secs: 60 * 10,      // 10 minutes
-            dealing_exchange_time_secs: 60 * 5,            // 5 minutes
+            public_key_submission_time_secs: 60 * 10, // 10 minutes
+            dealing_exchange_time_secs: 60 * 5,       // 5 minutes
+            complaint_submission_time_secs: default_complaint_submission_time_secs(),
             verification_key_submission_time_secs: 60 * 5, // 5 minutes
             verification_key_validation_time_secs: 60,     // 1 minute
             verification_key_finalization_time_secs: 60,   // 1 minute
@@ -91,22 +156,7 @@ impl Epoch {
         time_configuration: TimeConfiguration,
         current_timestamp: Timestamp,
     ) -> Self {
-        let duration = match state {
-            EpochState::PublicKeySubmission { .. } => {
-                time_configuration.public_key_submission_time_secs
-            }
-            EpochState::DealingExchange { .. } => time_configuration.dealing_exchange_time_secs,
-            EpochState::VerificationKeySubmission { .. } => {
-                time_configuration.verification_key_submission_time_secs
-            }
-            EpochState::VerificationKeyValidation { .. } => {
-                time_configuration.verification_key_validation_time_secs
-            }
-            EpochState::VerificationKeyFinalization { .. } => {
-                time_configuration.verification_key_finalization_time_secs
-            }
-            EpochState::InProgress => time_configuration.in_progress_time_secs,
-        };
+        let duration = time_configuration.state_duration_secs(state);
         Epoch {
             state,
             epoch_id,
@@ -115,6 +165,13 @@ impl Epoch {
         }
     }
 
+    /// Timestamp (in seconds) at which the epoch entered its current state.
+    pub fn state_start_timestamp_secs(&self) -> u64 {
+        self.finish_timestamp
+            .seconds()
+            .saturating_sub(self.time_configuration.state_duration_secs(self.state))
+    }
+
     pub fn final_timestamp_secs(&self) -> u64 {
         let mut finish = self.finish_timestamp.seconds();
         let time_configuration = self.time_configuration;
@@ -126,6 +183,9 @@ impl Epoch {
                     time_configuration.public_key_submission_time_secs
                 }
                 EpochState::DealingExchange { .. } => time_configuration.dealing_exchange_time_secs,
+                EpochState::ComplaintSubmission { .. } => {
+                    time_configuration.complaint_submission_time_secs
+                }
                 EpochState::VerificationKeySubmission { .. } => {
                     time_configuration.verification_key_submission_time_secs
                 }
@@ -147,8 +207,8 @@ impl Epoch {
 // the epoch can be in the following states (in order):
 // 1. PublicKeySubmission -> potential dealers are submitting their BTE and ed25519 public keys to participate in dealing exchange
 // 2. DealingExchange -> the actual (off-chain) dealing exchange is happening
-// 3. ComplaintSubmission -> receivers submitting evidence of other dealers sending malformed data
-// 4. ComplaintVoting -> (if any complaints were submitted) receivers voting on the validity of the evidence provided
+// 3. ComplaintSubmission -> receivers submitting complaints against dealers that posted malformed (or no) dealings
+// 4. ComplaintVoting -> (currently implicit) a complaint is upheld once at least threshold receivers raised it
 // 5. VerificationKeySubmission -> receivers submitting their partial (and master) verification keys
 // 6. VerificationKeyMismatchSubmission -> receivers / watchers raising issue that the submitted VK are mismatched with their local derivations
 // 7. VerificationKeyMismatchVoting -> (if any complaints were submitted) receivers voting on received mismatches
@@ -160,6 +220,7 @@ impl Epoch {
 pub enum EpochState {
     PublicKeySubmission { resharing: bool },
     DealingExchange { resharing: bool },
+    ComplaintSubmission { resharing: bool },
     VerificationKeySubmission { resharing: bool },
     VerificationKeyValidation { resharing: bool },
     VerificationKeyFinalization { resharing: bool },
@@ -179,6 +240,9 @@ impl Display for EpochState {
                 write!(f, "PublicKeySubmission with resharing {resharing}")
             }
             EpochState::DealingExchange { resharing } => write!(f, "DealingExchange {resharing}"),
+            EpochState::ComplaintSubmission { resharing } => {
+                write!(f, "ComplaintSubmission with resharing {resharing}")
+            }
             EpochState::VerificationKeySubmission { resharing } => {
                 write!(f, "VerificationKeySubmission with resharing {resharing}")
             }
@@ -200,6 +264,9 @@ impl EpochState {
                 Some(EpochState::DealingExchange { resharing })
             }
             EpochState::DealingExchange { resharing } => {
+                Some(EpochState::ComplaintSubmission { resharing })
+            }
+            EpochState::ComplaintSubmission { resharing } => {
                 Some(EpochState::VerificationKeySubmission { resharing })
             }
             EpochState::VerificationKeySubmission { resharing } => {
@@ -10,6 +10,30 @@ use serde::{Deserialize, Serialize};
 
 pub type VerificationKeyShare = String;
 
+/// Reason for voting against a verification key share.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VkShareRejectionReason {
+    /// The share does not match the partial key recovered from the dealings.
+    FailedPairingCheck,
+
+    /// The share could not be decoded.
+    MalformedEncoding,
+
+    /// The node index of the share does not belong to any of the accepted dealers.
+    IndexMismatch,
+}
+
+/// Justification attached to the `No` vote on a verification key share proposal, so that the
+/// ceremony coordinators could tell honest faults apart from Byzantine behaviour.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct VkShareVoteJustification {
+    pub owner: Addr,
+    pub node_index: NodeIndex,
+    pub epoch_id: EpochId,
+    pub reason: VkShareRejectionReason,
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct ContractVKShare {
     pub share: VerificationKeyShare,
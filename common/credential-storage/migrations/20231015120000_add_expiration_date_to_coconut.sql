@@ -0,0 +1,7 @@
+/*
+ * Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+-- credentials issued before the introduction of the expiration date attribute are going to have it unset
+ALTER TABLE coconut_credentials ADD COLUMN expiration_date TEXT;
@@ -0,0 +1,11 @@
+/*
+ * Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+-- present only if the secret parts of the stored credentials are encrypted with a passphrase
+CREATE TABLE store_cipher
+(
+    id       INTEGER PRIMARY KEY CHECK (id = 0),
+    exported BLOB NOT NULL
+);
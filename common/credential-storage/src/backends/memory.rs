@@ -27,6 +27,9 @@ impl CoconutCredentialManager {
     /// * `serial_number`: Base58 representation of the serial number attribute.
     /// * `binding_number`: Base58 representation of the binding number attribute.
     /// * `signature`: Coconut credential in the form of a signature.
+    /// * `epoch_id`: The epoch when it was signed.
+    /// * `expiration_date`: Unix timestamp after which the credential is no longer valid.
+    #[allow(clippy::too_many_arguments)]
     pub async fn insert_coconut_credential(
         &self,
         voucher_value: String,
@@ -35,6 +38,7 @@ impl CoconutCredentialManager {
         binding_number: String,
         signature: String,
         epoch_id: String,
+        expiration_date: Option<String>,
     ) {
         let mut creds = self.inner.write().await;
         let id = creds.len() as i64;
@@ -47,6 +51,7 @@ impl CoconutCredentialManager {
             signature,
             epoch_id,
             consumed: false,
+            expiration_date,
         });
     }
 
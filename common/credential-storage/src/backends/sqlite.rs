@@ -27,6 +27,9 @@ impl CoconutCredentialManager {
     /// * `serial_number`: Base58 representation of the serial number attribute.
     /// * `binding_number`: Base58 representation of the binding number attribute.
     /// * `signature`: Coconut credential in the form of a signature.
+    /// * `epoch_id`: The epoch when it was signed.
+    /// * `expiration_date`: Unix timestamp after which the credential is no longer valid.
+    #[allow(clippy::too_many_arguments)]
     pub async fn insert_coconut_credential(
         &self,
         voucher_value: String,
@@ -35,10 +38,11 @@ impl CoconutCredentialManager {
         binding_number: String,
         signature: String,
         epoch_id: String,
+        expiration_date: Option<String>,
     ) -> Result<(), sqlx::Error> {
         sqlx::query!(
-            "INSERT INTO coconut_credentials(voucher_value, voucher_info, serial_number, binding_number, signature, epoch_id, consumed) VALUES (?, ?, ?, ?, ?, ?, ?)",
-            voucher_value, voucher_info, serial_number, binding_number, signature, epoch_id, false
+            "INSERT INTO coconut_credentials(voucher_value, voucher_info, serial_number, binding_number, signature, epoch_id, consumed, expiration_date) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            voucher_value, voucher_info, serial_number, binding_number, signature, epoch_id, false, expiration_date
         )
         .execute(&self.connection_pool)
         .await?;
@@ -71,4 +75,49 @@ impl CoconutCredentialManager {
         .await?;
         Ok(())
     }
+
+    /// Retrieves all of the stored credentials, including the consumed ones.
+    pub async fn get_all_coconut_credentials(&self) -> Result<Vec<CoconutCredential>, sqlx::Error> {
+        sqlx::query_as!(CoconutCredential, "SELECT * FROM coconut_credentials")
+            .fetch_all(&self.connection_pool)
+            .await
+    }
+
+    /// Retrieves the exported store cipher, if the credentials have been encrypted.
+    pub async fn get_store_cipher(&self) -> Result<Option<Vec<u8>>, sqlx::Error> {
+        sqlx::query!("SELECT exported FROM store_cipher")
+            .fetch_optional(&self.connection_pool)
+            .await
+            .map(|row| row.map(|row| row.exported))
+    }
+
+    /// Saves the exported store cipher alongside the encrypted secrets of all the existing
+    /// credentials in a single transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `exported`: The exported store cipher.
+    /// * `credentials`: The credentials with their secrets already encrypted.
+    pub async fn encrypt_coconut_credentials(
+        &self,
+        exported: Vec<u8>,
+        credentials: Vec<CoconutCredential>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.connection_pool.begin().await?;
+        for credential in credentials {
+            sqlx::query!(
+                "UPDATE coconut_credentials SET serial_number = ?, binding_number = ?, signature = ? WHERE id = ?",
+                credential.serial_number, credential.binding_number, credential.signature, credential.id
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        sqlx::query!(
+            "INSERT INTO store_cipher(id, exported) VALUES (0, ?)",
+            exported
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await
+    }
 }
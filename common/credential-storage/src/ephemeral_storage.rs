@@ -32,6 +32,7 @@ impl Storage for EphemeralStorage {
         binding_number: String,
         signature: String,
         epoch_id: String,
+        expiration_date: Option<String>,
     ) -> Result<(), StorageError> {
         self.coconut_credential_manager
             .insert_coconut_credential(
@@ -41,6 +42,7 @@ impl Storage for EphemeralStorage {
                 binding_number,
                 signature,
                 epoch_id,
+                expiration_date,
             )
             .await;
 
@@ -13,6 +13,13 @@ pub enum StorageError {
     #[error("Failed to perform database migration - {0}")]
     MigrationError(#[from] sqlx::migrate::MigrateError),
 
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Failed to encrypt or decrypt the stored credentials - {0}")]
+    CipherError(#[from] nym_store_cipher::StoreCipherError),
+
+    #[error("The stored credentials are encrypted, but no passphrase has been provided")]
+    MissingPassphrase,
+
     #[error("Inconsistent data in database")]
     InconsistentData,
 
@@ -12,4 +12,5 @@ pub struct CoconutCredential {
     pub signature: String,
     pub epoch_id: String,
     pub consumed: bool,
+    pub expiration_date: Option<String>,
 }
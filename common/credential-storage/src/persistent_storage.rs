@@ -7,7 +7,8 @@ use crate::storage::Storage;
 
 use crate::models::CoconutCredential;
 use async_trait::async_trait;
-use log::{debug, error};
+use log::{debug, error, info};
+use nym_store_cipher::{associated_data, StoreCipher};
 use sqlx::ConnectOptions;
 use std::path::Path;
 
@@ -15,6 +16,9 @@ use std::path::Path;
 #[derive(Clone)]
 pub struct PersistentStorage {
     coconut_credential_manager: CoconutCredentialManager,
+
+    /// Cipher used for the secret parts of the credentials, if they're meant to be encrypted.
+    cipher: Option<StoreCipher>,
 }
 
 impl PersistentStorage {
@@ -24,6 +28,21 @@ impl PersistentStorage {
     ///
     /// * `database_path`: path to the database.
     pub async fn init<P: AsRef<Path> + Send>(database_path: P) -> Result<Self, StorageError> {
+        Self::init_with_passphrase(database_path, None).await
+    }
+
+    /// Initialises `PersistentStorage` using the provided path, encrypting the serial numbers,
+    /// binding numbers and signatures of the credentials with a key derived from the passphrase.
+    /// Any plaintext credentials stored before the encryption got enabled are encrypted in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `database_path`: path to the database.
+    /// * `passphrase`: passphrase of the storage, if it is meant to be encrypted.
+    pub async fn init_with_passphrase<P: AsRef<Path> + Send>(
+        database_path: P,
+        passphrase: Option<&str>,
+    ) -> Result<Self, StorageError> {
         debug!(
             "Attempting to connect to database {:?}",
             database_path.as_ref().as_os_str()
@@ -48,12 +67,93 @@ impl PersistentStorage {
             return Err(err.into());
         }
 
+        let coconut_credential_manager = CoconutCredentialManager::new(connection_pool.clone());
+        let cipher = match (
+            coconut_credential_manager.get_store_cipher().await?,
+            passphrase,
+        ) {
+            (Some(exported), Some(passphrase)) => Some(StoreCipher::import(passphrase, &exported)?),
+            (Some(_), None) => return Err(StorageError::MissingPassphrase),
+            (None, Some(passphrase)) => {
+                info!("Encrypting the stored credentials");
+                let cipher = StoreCipher::new(passphrase)?;
+                let credentials = coconut_credential_manager
+                    .get_all_coconut_credentials()
+                    .await?
+                    .into_iter()
+                    .map(|credential| encrypt_secrets(&cipher, credential))
+                    .collect::<Result<_, _>>()?;
+                coconut_credential_manager
+                    .encrypt_coconut_credentials(cipher.export()?, credentials)
+                    .await?;
+                Some(cipher)
+            }
+            (None, None) => None,
+        };
+
         Ok(PersistentStorage {
-            coconut_credential_manager: CoconutCredentialManager::new(connection_pool.clone()),
+            coconut_credential_manager,
+            cipher,
         })
     }
 }
 
+/// Binds the secret kept in the given column to the credential it belongs to, identified by
+/// its unencrypted attributes (the row id is only assigned upon insertion).
+fn secret_aad(column: &str, voucher_value: &str, voucher_info: &str, epoch_id: &str) -> Vec<u8> {
+    associated_data(
+        "coconut_credentials",
+        column,
+        &[
+            voucher_value.as_bytes(),
+            voucher_info.as_bytes(),
+            epoch_id.as_bytes(),
+        ],
+    )
+}
+
+fn encrypt_secrets(
+    cipher: &StoreCipher,
+    mut credential: CoconutCredential,
+) -> Result<CoconutCredential, StorageError> {
+    let aad = |column| {
+        secret_aad(
+            column,
+            &credential.voucher_value,
+            &credential.voucher_info,
+            &credential.epoch_id,
+        )
+    };
+    let serial_number = cipher.encrypt_str(&credential.serial_number, &aad("serial_number"))?;
+    let binding_number = cipher.encrypt_str(&credential.binding_number, &aad("binding_number"))?;
+    let signature = cipher.encrypt_str(&credential.signature, &aad("signature"))?;
+    credential.serial_number = serial_number;
+    credential.binding_number = binding_number;
+    credential.signature = signature;
+    Ok(credential)
+}
+
+fn decrypt_secrets(
+    cipher: &StoreCipher,
+    mut credential: CoconutCredential,
+) -> Result<CoconutCredential, StorageError> {
+    let aad = |column| {
+        secret_aad(
+            column,
+            &credential.voucher_value,
+            &credential.voucher_info,
+            &credential.epoch_id,
+        )
+    };
+    let serial_number = cipher.decrypt_str(&credential.serial_number, &aad("serial_number"))?;
+    let binding_number = cipher.decrypt_str(&credential.binding_number, &aad("binding_number"))?;
+    let signature = cipher.decrypt_str(&credential.signature, &aad("signature"))?;
+    credential.serial_number = serial_number;
+    credential.binding_number = binding_number;
+    credential.signature = signature;
+    Ok(credential)
+}
+
 #[async_trait]
 impl Storage for PersistentStorage {
     async fn insert_coconut_credential(
@@ -64,7 +164,19 @@ impl Storage for PersistentStorage {
         binding_number: String,
         signature: String,
         epoch_id: String,
+        expiration_date: Option<String>,
     ) -> Result<(), StorageError> {
+        let (serial_number, binding_number, signature) = match &self.cipher {
+            Some(cipher) => {
+                let aad = |column| secret_aad(column, &voucher_value, &voucher_info, &epoch_id);
+                (
+                    cipher.encrypt_str(&serial_number, &aad("serial_number"))?,
+                    cipher.encrypt_str(&binding_number, &aad("binding_number"))?,
+                    cipher.encrypt_str(&signature, &aad("signature"))?,
+                )
+            }
+            None => (serial_number, binding_number, signature),
+        };
         self.coconut_credential_manager
             .insert_coconut_credential(
                 voucher_value,
@@ -73,6 +185,7 @@ impl Storage for PersistentStorage {
                 binding_number,
                 signature,
                 epoch_id,
+                expiration_date,
             )
             .await?;
 
@@ -86,7 +199,10 @@ impl Storage for PersistentStorage {
             .await?
             .ok_or(StorageError::NoCredential)?;
 
-        Ok(credential)
+        match &self.cipher {
+            Some(cipher) => decrypt_secrets(cipher, credential),
+            None => Ok(credential),
+        }
     }
 
     async fn consume_coconut_credential(&self, id: i64) -> Result<(), StorageError> {
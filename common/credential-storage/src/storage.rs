@@ -18,6 +18,8 @@ pub trait Storage: Send + Sync {
     /// * `binding_number`: Binding number of the credential.
     /// * `signature`: Coconut credential in the form of a signature.
     /// * `epoch_id`: The epoch when it was signed.
+    /// * `expiration_date`: Unix timestamp after which the credential is no longer valid.
+    #[allow(clippy::too_many_arguments)]
     async fn insert_coconut_credential(
         &self,
         voucher_value: String,
@@ -26,6 +28,7 @@ pub trait Storage: Send + Sync {
         binding_number: String,
         signature: String,
         epoch_id: String,
+        expiration_date: Option<String>,
     ) -> Result<(), StorageError>;
 
     /// Tries to retrieve one of the stored, unused credentials.
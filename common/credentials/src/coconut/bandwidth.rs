@@ -6,6 +6,7 @@
 // right now this has no double-spending protection, spender binding, etc
 // it's the simplest possible case
 
+use nym_coconut_dkg_common::types::{dealings_for_attributes, DEFAULT_DEALINGS};
 use nym_coconut_interface::{
     hash_to_scalar, prepare_blind_sign, Attribute, BlindSignRequest, Credential, Parameters,
     PrivateAttribute, PublicAttribute, Signature, VerificationKey,
@@ -13,14 +14,69 @@ use nym_coconut_interface::{
 use nym_crypto::asymmetric::{encryption, identity};
 
 use cosmrs::tx::Hash;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use super::utils::prepare_credential_for_spending;
 use crate::error::Error;
 
-pub const PUBLIC_ATTRIBUTES: u32 = 2;
+pub const PUBLIC_ATTRIBUTES: u32 = 3;
 pub const PRIVATE_ATTRIBUTES: u32 = 2;
 pub const TOTAL_ATTRIBUTES: u32 = PUBLIC_ATTRIBUTES + PRIVATE_ATTRIBUTES;
 
+// the keys derived by the DKG by default have to be able to sign all of the attributes,
+// otherwise the issued credentials would never pass the verification
+const _: () = assert!(dealings_for_attributes(TOTAL_ATTRIBUTES) == DEFAULT_DEALINGS);
+
+// credentials issued before the introduction of the expiration date do not have that attribute
+pub const LEGACY_PUBLIC_ATTRIBUTES: u32 = 2;
+pub const LEGACY_TOTAL_ATTRIBUTES: u32 = LEGACY_PUBLIC_ATTRIBUTES + PRIVATE_ATTRIBUTES;
+
+/// Validity of the newly issued credentials.
+pub const CREDENTIAL_VALIDITY: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Maximum validity of a credential accepted for issuance, leaving some leeway for clock skew.
+pub const MAX_CREDENTIAL_VALIDITY: Duration = Duration::from_secs(31 * 24 * 60 * 60);
+
+/// Granularity of the expiration dates. They are public attributes of the credential,
+/// so they are rounded down to the start of the (UTC) day in order not to make the
+/// credentials linkable to the exact time of their issuance.
+pub const EXPIRATION_DATE_GRANULARITY: Duration = Duration::from_secs(24 * 60 * 60);
+
+pub fn current_unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Expiration date of a credential issued right now.
+pub fn default_expiration_date() -> u64 {
+    round_expiration_date(current_unix_timestamp() + CREDENTIAL_VALIDITY.as_secs())
+}
+
+/// Rounds the provided timestamp down to the expiration date granularity.
+pub fn round_expiration_date(timestamp: u64) -> u64 {
+    let granularity = EXPIRATION_DATE_GRANULARITY.as_secs();
+    timestamp / granularity * granularity
+}
+
+/// Determines whether the provided expiration date has been rounded to the required granularity.
+pub fn is_rounded_expiration_date(expiration_date: u64) -> bool {
+    expiration_date % EXPIRATION_DATE_GRANULARITY.as_secs() == 0
+}
+
+/// Unix timestamp (2027-01-01T00:00:00Z) until which the legacy credentials, issued without
+/// the expiration date, are still accepted, so that the ones obtained before the introduction
+/// of the attribute could still be spent.
+pub const LEGACY_CREDENTIALS_DEADLINE: u64 = 1798761600;
+
+/// Determines whether a credential with the provided expiration date is no longer valid at `now`.
+/// The legacy credentials, issued without the expiration date, are valid until
+/// [`LEGACY_CREDENTIALS_DEADLINE`].
+pub fn has_expired(expiration_date: Option<u64>, now: u64) -> bool {
+    now >= expiration_date.unwrap_or(LEGACY_CREDENTIALS_DEADLINE)
+}
+
 pub struct BandwidthVoucher {
     // a random secret value generated by the client used for double-spending detection
     serial_number: PrivateAttribute,
@@ -34,6 +90,9 @@ pub struct BandwidthVoucher {
     voucher_info: PublicAttribute,
     // the plain text information
     voucher_info_plain: String,
+    // unix timestamp after which the credential is no longer valid,
+    // not present on the vouchers created before the introduction of the attribute
+    expiration_date: Option<u64>,
     // the hash of the deposit transaction
     tx_hash: Hash,
     // base58 encoded private key ensuring the depositer requested these attributes
@@ -49,6 +108,7 @@ impl BandwidthVoucher {
         params: &Parameters,
         voucher_value: String,
         voucher_info: String,
+        expiration_date: u64,
         tx_hash: Hash,
         signing_key: identity::PrivateKey,
         encryption_key: encryption::PrivateKey,
@@ -62,7 +122,11 @@ impl BandwidthVoucher {
         let (pedersen_commitments_openings, blind_sign_request) = prepare_blind_sign(
             params,
             &[serial_number, binding_number],
-            &[voucher_value, voucher_info],
+            &[
+                voucher_value,
+                voucher_info,
+                hash_to_scalar(expiration_date.to_string()),
+            ],
         )
         .unwrap();
         BandwidthVoucher {
@@ -72,6 +136,7 @@ impl BandwidthVoucher {
             voucher_value_plain,
             voucher_info,
             voucher_info_plain,
+            expiration_date: Some(expiration_date),
             tx_hash,
             signing_key,
             encryption_key,
@@ -80,6 +145,26 @@ impl BandwidthVoucher {
         }
     }
 
+    /// Regenerates the voucher created before the introduction of the expiration date with the
+    /// provided one, so that it could still be converted into a credential. The deposit data
+    /// and keys are retained, while the attributes get blinded again.
+    /// The voucher is returned unchanged if it already has an expiration date.
+    pub fn with_expiration_date(self, params: &Parameters, expiration_date: u64) -> Self {
+        if self.expiration_date.is_some() {
+            return self;
+        }
+
+        BandwidthVoucher::new(
+            params,
+            self.voucher_value_plain,
+            self.voucher_info_plain,
+            expiration_date,
+            self.tx_hash,
+            self.signing_key,
+            self.encryption_key,
+        )
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         let serial_number_b = self.serial_number.to_bytes();
         let binding_number_b = self.binding_number.to_bytes();
@@ -107,6 +192,11 @@ impl BandwidthVoucher {
         for commitment in self.pedersen_commitments_openings.iter() {
             ret.extend_from_slice(&commitment.to_bytes());
         }
+        // appended at the end so that the vouchers serialized before its introduction
+        // could still be recovered
+        if let Some(expiration_date) = self.expiration_date {
+            ret.extend_from_slice(&expiration_date.to_be_bytes());
+        }
 
         ret
     }
@@ -153,11 +243,17 @@ impl BandwidthVoucher {
             + voucher_info_plain_no
             + blind_sign_request_no
             + pedersen_commitments_openings_no * 32;
-        if bytes.len() != total_length {
+        let expiration_date = if bytes.len() == total_length {
+            None
+        } else if bytes.len() == total_length + 8 {
+            small_buff.copy_from_slice(&bytes[total_length..]);
+            Some(u64::from_be_bytes(small_buff))
+        } else {
             return Err(Error::BandwidthVoucherDeserializationError(format!(
-                "Expected {total_length} bytes",
+                "Expected {total_length} or {} bytes",
+                total_length + 8
             )));
-        }
+        };
 
         let utf_err = |_| {
             Err(Error::BandwidthVoucherDeserializationError(String::from(
@@ -198,6 +294,7 @@ impl BandwidthVoucher {
             voucher_value_plain,
             voucher_info,
             voucher_info_plain,
+            expiration_date,
             tx_hash,
             signing_key,
             encryption_key,
@@ -208,10 +305,15 @@ impl BandwidthVoucher {
 
     /// Check if the plain values correspond to the PublicAttributes
     pub fn verify_against_plain(values: &[PublicAttribute], plain_values: &[String]) -> bool {
-        values.len() == 2
-            && plain_values.len() == 2
-            && values[0] == hash_to_scalar(&plain_values[0])
-            && values[1] == hash_to_scalar(&plain_values[1])
+        let expected_len = values.len() == PUBLIC_ATTRIBUTES as usize
+            || values.len() == LEGACY_PUBLIC_ATTRIBUTES as usize;
+
+        expected_len
+            && values.len() == plain_values.len()
+            && values
+                .iter()
+                .zip(plain_values)
+                .all(|(value, plain)| *value == hash_to_scalar(plain))
     }
 
     pub fn tx_hash(&self) -> &Hash {
@@ -219,7 +321,11 @@ impl BandwidthVoucher {
     }
 
     pub fn get_public_attributes(&self) -> Vec<PublicAttribute> {
-        vec![self.voucher_value, self.voucher_info]
+        let mut attributes = vec![self.voucher_value, self.voucher_info];
+        if let Some(expiration_date) = self.expiration_date {
+            attributes.push(hash_to_scalar(expiration_date.to_string()));
+        }
+        attributes
     }
 
     pub fn encryption_key(&self) -> &encryption::PrivateKey {
@@ -238,11 +344,19 @@ impl BandwidthVoucher {
         self.voucher_value_plain.clone()
     }
 
+    pub fn get_expiration_date(&self) -> Option<u64> {
+        self.expiration_date
+    }
+
     pub fn get_public_attributes_plain(&self) -> Vec<String> {
-        vec![
+        let mut attributes = vec![
             self.voucher_value_plain.clone(),
             self.voucher_info_plain.clone(),
-        ]
+        ];
+        if let Some(expiration_date) = self.expiration_date {
+            attributes.push(expiration_date.to_string());
+        }
+        attributes
     }
 
     pub fn get_private_attributes(&self) -> Vec<PrivateAttribute> {
@@ -256,9 +370,11 @@ impl BandwidthVoucher {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn prepare_for_spending(
     voucher_value: u64,
     voucher_info: String,
+    expiration_date: Option<u64>,
     serial_number: PrivateAttribute,
     binding_number: PrivateAttribute,
     epoch_id: u64,
@@ -271,6 +387,7 @@ pub fn prepare_for_spending(
         &params,
         voucher_value,
         voucher_info,
+        expiration_date,
         serial_number,
         binding_number,
         epoch_id,
@@ -282,16 +399,21 @@ pub fn prepare_for_spending(
 #[cfg(test)]
 mod test {
     use super::*;
-    use nym_coconut_interface::Base58;
+    use nym_coconut_dkg_common::types::attributes_for_dealings;
+    use nym_coconut_interface::{
+        aggregate_signature_shares, aggregate_verification_keys, blind_sign, ttp_keygen, Base58,
+        SignatureShare,
+    };
     use rand::rngs::OsRng;
 
     fn voucher_fixture() -> BandwidthVoucher {
-        let params = Parameters::new(4).unwrap();
+        let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
         let mut rng = OsRng;
         BandwidthVoucher::new(
             &params,
             "1234".to_string(),
             "voucher info".to_string(),
+            1700000000,
             Hash::new([0; 32]),
             identity::PrivateKey::from_base58_string(
                 identity::KeyPair::new(&mut rng)
@@ -323,6 +445,10 @@ mod test {
             voucher.voucher_info_plain,
             deserialized_voucher.voucher_info_plain
         );
+        assert_eq!(
+            voucher.expiration_date,
+            deserialized_voucher.expiration_date
+        );
         assert_eq!(voucher.tx_hash, deserialized_voucher.tx_hash);
         assert_eq!(
             voucher.signing_key.to_string(),
@@ -380,4 +506,141 @@ mod test {
             &voucher.get_public_attributes_plain()
         ));
     }
+
+    #[test]
+    fn legacy_voucher_deserialization() {
+        let voucher = voucher_fixture();
+        let bytes = voucher.to_bytes();
+
+        // vouchers serialized before the introduction of the expiration date lacked the trailing bytes
+        let legacy_bytes = &bytes[..bytes.len() - 8];
+        let deserialized_voucher = BandwidthVoucher::try_from_bytes(legacy_bytes).unwrap();
+        assert!(deserialized_voucher.expiration_date.is_none());
+        assert_eq!(
+            deserialized_voucher.get_public_attributes().len(),
+            LEGACY_PUBLIC_ATTRIBUTES as usize
+        );
+        assert!(BandwidthVoucher::verify_against_plain(
+            &deserialized_voucher.get_public_attributes(),
+            &deserialized_voucher.get_public_attributes_plain()
+        ));
+
+        assert!(BandwidthVoucher::try_from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn legacy_voucher_reissuance() {
+        let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
+        let voucher = voucher_fixture();
+        let bytes = voucher.to_bytes();
+        let legacy_voucher = BandwidthVoucher::try_from_bytes(&bytes[..bytes.len() - 8]).unwrap();
+
+        let expiration_date = default_expiration_date();
+        let reissued = legacy_voucher.with_expiration_date(&params, expiration_date);
+        assert_eq!(reissued.expiration_date, Some(expiration_date));
+        assert_eq!(reissued.voucher_value_plain, voucher.voucher_value_plain);
+        assert_eq!(reissued.voucher_info_plain, voucher.voucher_info_plain);
+        assert_eq!(reissued.tx_hash, voucher.tx_hash);
+        assert_eq!(
+            reissued.signing_key.to_string(),
+            voucher.signing_key.to_string()
+        );
+        assert_eq!(
+            reissued.get_public_attributes().len(),
+            PUBLIC_ATTRIBUTES as usize
+        );
+        assert!(BandwidthVoucher::verify_against_plain(
+            &reissued.get_public_attributes(),
+            &reissued.get_public_attributes_plain()
+        ));
+
+        // vouchers that already have the expiration date are left intact
+        let kept = voucher.with_expiration_date(&params, expiration_date);
+        assert_eq!(kept.expiration_date, Some(1700000000));
+    }
+
+    #[test]
+    fn credential_expiration() {
+        assert!(!has_expired(Some(1000), 999));
+        assert!(has_expired(Some(1000), 1000));
+        assert!(!has_expired(None, LEGACY_CREDENTIALS_DEADLINE - 1));
+        assert!(has_expired(None, LEGACY_CREDENTIALS_DEADLINE));
+    }
+
+    #[test]
+    fn expiration_date_rounding() {
+        let day = EXPIRATION_DATE_GRANULARITY.as_secs();
+        assert_eq!(round_expiration_date(1700000000), 1699920000);
+        assert_eq!(round_expiration_date(1699920000), 1699920000);
+        assert!(is_rounded_expiration_date(1699920000));
+        assert!(!is_rounded_expiration_date(1699920000 + 1));
+        assert!(is_rounded_expiration_date(default_expiration_date()));
+        assert!(
+            default_expiration_date() + day
+                > current_unix_timestamp() + CREDENTIAL_VALIDITY.as_secs()
+        );
+    }
+
+    #[test]
+    fn issued_credential_verifies_with_default_dealings_keys() {
+        let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
+        let voucher = voucher_fixture();
+        let public_attributes = voucher.get_public_attributes();
+        let private_attributes = voucher.get_private_attributes();
+
+        // keys as derived by the DKG with the default number of dealings
+        let key_params = Parameters::new(attributes_for_dealings(DEFAULT_DEALINGS)).unwrap();
+        let key_pairs = ttp_keygen(&key_params, 2, 3).unwrap();
+        let indices: [u64; 3] = [1, 2, 3];
+
+        let shares = key_pairs
+            .iter()
+            .zip(indices)
+            .map(|(key_pair, index)| {
+                let blinded_signature = blind_sign(
+                    &params,
+                    &key_pair.secret_key(),
+                    voucher.blind_sign_request(),
+                    &public_attributes,
+                )
+                .unwrap();
+                let signature = blinded_signature
+                    .unblind(
+                        &params,
+                        &key_pair.verification_key(),
+                        &private_attributes,
+                        &public_attributes,
+                        &voucher.blind_sign_request().get_commitment_hash(),
+                        voucher.pedersen_commitments_openings(),
+                    )
+                    .unwrap();
+                SignatureShare::new(signature, index)
+            })
+            .collect::<Vec<_>>();
+
+        let verification_keys = key_pairs
+            .iter()
+            .map(|key_pair| key_pair.verification_key())
+            .collect::<Vec<_>>();
+        let verification_key =
+            aggregate_verification_keys(&verification_keys, Some(&indices[..])).unwrap();
+
+        let mut attributes = private_attributes.clone();
+        attributes.extend_from_slice(&public_attributes);
+        let signature =
+            aggregate_signature_shares(&params, &verification_key, &attributes, &shares).unwrap();
+
+        let credential = prepare_for_spending(
+            voucher.get_voucher_value().parse().unwrap(),
+            voucher.voucher_info_plain.clone(),
+            voucher.get_expiration_date(),
+            private_attributes[0],
+            private_attributes[1],
+            0,
+            &signature,
+            &verification_key,
+        )
+        .unwrap();
+        assert!(credential.verify(&verification_key));
+    }
 }
@@ -11,7 +11,7 @@ use nym_crypto::shared_key::recompute_shared_key;
 use nym_crypto::symmetric::stream_cipher;
 use nym_validator_client::client::CoconutApiClient;
 
-use crate::coconut::bandwidth::{BandwidthVoucher, PRIVATE_ATTRIBUTES, PUBLIC_ATTRIBUTES};
+use crate::coconut::bandwidth::{BandwidthVoucher, LEGACY_TOTAL_ATTRIBUTES, TOTAL_ATTRIBUTES};
 use crate::coconut::params::{NymApiCredentialEncryptionAlgorithm, NymApiCredentialHkdfAlgorithm};
 use crate::error::Error;
 
@@ -137,6 +137,7 @@ pub fn prepare_credential_for_spending(
     params: &Parameters,
     voucher_value: u64,
     voucher_info: String,
+    expiration_date: Option<u64>,
     serial_number: Attribute,
     binding_number: Attribute,
     epoch_id: u64,
@@ -151,11 +152,18 @@ pub fn prepare_credential_for_spending(
         binding_number,
     )?;
 
+    let n_params = if expiration_date.is_some() {
+        TOTAL_ATTRIBUTES
+    } else {
+        LEGACY_TOTAL_ATTRIBUTES
+    };
+
     Ok(Credential::new(
-        PUBLIC_ATTRIBUTES + PRIVATE_ATTRIBUTES,
+        n_params,
         theta,
         voucher_value,
         voucher_info,
         epoch_id,
+        expiration_date,
     ))
 }
@@ -2,9 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nym_pemstore::traits::{PemStorableKey, PemStorableKeyPair};
-use std::fmt::{self, Display, Formatter};
+use std::fmt::{self, Debug, Display, Formatter};
 use std::str::FromStr;
 use thiserror::Error;
+use zeroize::ZeroizeOnDrop;
 
 #[cfg(feature = "rand")]
 use rand::{CryptoRng, RngCore};
@@ -180,6 +181,15 @@ impl PemStorableKey for PublicKey {
 
 pub struct PrivateKey(x25519_dalek::StaticSecret);
 
+// the underlying `StaticSecret` zeroes its memory when dropped
+impl ZeroizeOnDrop for PrivateKey {}
+
+impl Debug for PrivateKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "x25519 PrivateKey(<redacted>)")
+    }
+}
+
 impl Display for PrivateKey {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.to_base58_string())
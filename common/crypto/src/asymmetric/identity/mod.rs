@@ -6,9 +6,11 @@ pub use ed25519_dalek::SignatureError;
 pub use ed25519_dalek::{Verifier, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH, SIGNATURE_LENGTH};
 use nym_pemstore::traits::{PemStorableKey, PemStorableKeyPair};
 use nym_sphinx_types::{DestinationAddressBytes, DESTINATION_ADDRESS_LENGTH};
-use std::fmt::{self, Display, Formatter};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 use thiserror::Error;
+use zeroize::ZeroizeOnDrop;
 
 #[cfg(feature = "rand")]
 use rand::{CryptoRng, RngCore};
@@ -100,6 +102,12 @@ impl PemStorableKeyPair for KeyPair {
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct PublicKey(ed25519_dalek::PublicKey);
 
+impl Hash for PublicKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_bytes().hash(state)
+    }
+}
+
 impl Display for PublicKey {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.to_base58_string())
@@ -189,9 +197,17 @@ impl PemStorableKey for PublicKey {
 }
 
 /// ed25519 EdDSA Private Key
-#[derive(Debug)]
 pub struct PrivateKey(ed25519_dalek::SecretKey);
 
+// the underlying `SecretKey` zeroes its memory when dropped
+impl ZeroizeOnDrop for PrivateKey {}
+
+impl Debug for PrivateKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "ed25519 PrivateKey(<redacted>)")
+    }
+}
+
 impl Display for PrivateKey {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.to_base58_string())
@@ -1,5 +1,147 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use nym_pemstore::traits::PemStorableKey;
+use std::io;
+use thiserror::Error;
+
 pub mod encryption;
 pub mod identity;
+
+#[derive(Debug, Error)]
+pub enum KeyDecodingError {
+    #[error("the base58 representation of the key was malformed - {source}")]
+    MalformedBase58 {
+        #[source]
+        source: bs58::decode::Error,
+    },
+
+    #[error("the PEM representation of the key was malformed - {source}")]
+    MalformedPem {
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("the recovered bytes do not represent a valid key - {message}")]
+    InvalidKeyBytes { message: String },
+}
+
+/// Uniform textual representations of all our asymmetric keys, i.e. both the identity (ed25519)
+/// and the encryption (x25519) ones, so that they could be handled in exactly the same way
+/// regardless of the underlying algorithm.
+pub trait KeyEncoding: PemStorableKey {
+    fn to_base58(&self) -> String {
+        bs58::encode(PemStorableKey::to_bytes(self)).into_string()
+    }
+
+    fn from_base58<I: AsRef<[u8]>>(val: I) -> Result<Self, KeyDecodingError> {
+        let bytes = bs58::decode(val)
+            .into_vec()
+            .map_err(|source| KeyDecodingError::MalformedBase58 { source })?;
+        <Self as PemStorableKey>::from_bytes(&bytes).map_err(|err| {
+            KeyDecodingError::InvalidKeyBytes {
+                message: err.to_string(),
+            }
+        })
+    }
+
+    fn to_pem(&self) -> String {
+        nym_pemstore::encode_key(self)
+    }
+
+    fn from_pem<I: AsRef<[u8]>>(val: I) -> Result<Self, KeyDecodingError> {
+        nym_pemstore::decode_key(val.as_ref())
+            .map_err(|source| KeyDecodingError::MalformedPem { source })
+    }
+}
+
+impl<T: PemStorableKey> KeyEncoding for T {}
+
+/// Helpers for (de)serializing keys as base58 strings rather than as raw bytes,
+/// to be used with `#[serde(with = "...")]`.
+#[cfg(feature = "serde")]
+pub mod serde_helpers {
+    pub mod bs58_key {
+        use crate::asymmetric::KeyEncoding;
+        use serde::de::Error as SerdeError;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<K: KeyEncoding, S: Serializer>(
+            key: &K,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&key.to_base58())
+        }
+
+        pub fn deserialize<'de, K: KeyEncoding, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<K, D::Error> {
+            let s = String::deserialize(deserializer)?;
+            K::from_base58(s).map_err(SerdeError::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_keys_roundtrip() {
+        let private = identity::PrivateKey::from_bytes(&[42; 32]).unwrap();
+        let public = identity::PublicKey::from(&private);
+
+        let recovered = identity::PrivateKey::from_base58(private.to_base58()).unwrap();
+        assert_eq!(private.to_bytes(), recovered.to_bytes());
+        let recovered = identity::PrivateKey::from_pem(private.to_pem()).unwrap();
+        assert_eq!(private.to_bytes(), recovered.to_bytes());
+
+        assert_eq!(public.to_base58(), public.to_base58_string());
+        assert_eq!(
+            identity::PublicKey::from_base58(public.to_base58()).unwrap(),
+            public
+        );
+        assert_eq!(
+            identity::PublicKey::from_pem(public.to_pem()).unwrap(),
+            public
+        );
+    }
+
+    #[test]
+    fn encryption_keys_roundtrip() {
+        let private = encryption::PrivateKey::from_bytes(&[42; 32]).unwrap();
+        let public = encryption::PublicKey::from(&private);
+
+        let recovered = encryption::PrivateKey::from_base58(private.to_base58()).unwrap();
+        assert_eq!(private.to_bytes(), recovered.to_bytes());
+        let recovered = encryption::PrivateKey::from_pem(private.to_pem()).unwrap();
+        assert_eq!(private.to_bytes(), recovered.to_bytes());
+
+        assert_eq!(public.to_base58(), public.to_base58_string());
+        assert_eq!(
+            encryption::PublicKey::from_base58(public.to_base58()).unwrap(),
+            public
+        );
+        assert_eq!(
+            encryption::PublicKey::from_pem(public.to_pem()).unwrap(),
+            public
+        );
+    }
+
+    #[test]
+    fn pem_of_a_different_key_type_is_rejected() {
+        let private = identity::PrivateKey::from_bytes(&[42; 32]).unwrap();
+        assert!(matches!(
+            encryption::PrivateKey::from_pem(private.to_pem()),
+            Err(KeyDecodingError::MalformedPem { .. })
+        ));
+    }
+
+    #[test]
+    fn private_keys_are_not_leaked_in_debug_output() {
+        let private = identity::PrivateKey::from_bytes(&[42; 32]).unwrap();
+        assert!(!format!("{private:?}").contains("42"));
+        let private = encryption::PrivateKey::from_bytes(&[42; 32]).unwrap();
+        assert!(!format!("{private:?}").contains("42"));
+    }
+}
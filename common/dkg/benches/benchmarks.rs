@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use bls12_381::{G1Projective, G2Affine, G2Prepared, Scalar};
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
 use ff::Field;
 use nym_dkg::bte::encryption::BabyStepGiantStepLookup;
 use nym_dkg::bte::proof_chunking::ProofOfChunking;
@@ -13,7 +13,7 @@ use nym_dkg::bte::{
     PublicKey,
 };
 use nym_dkg::interpolation::polynomial::Polynomial;
-use nym_dkg::{Dealing, NodeIndex, Share};
+use nym_dkg::{combine_shares, Dealing, NodeIndex, Share};
 use rand_core::{RngCore, SeedableRng};
 use std::collections::BTreeMap;
 
@@ -459,6 +459,28 @@ pub fn share_decryption(c: &mut Criterion) {
     });
 }
 
+pub fn share_combination_for_100_parties(c: &mut Criterion) {
+    let dummy_seed = [42u8; 32];
+    let mut rng = rand_chacha::ChaCha20Rng::from_seed(dummy_seed);
+    let threshold = 67;
+
+    let node_indices = (1..=threshold).collect::<Vec<NodeIndex>>();
+    let polynomial = Polynomial::new_random(&mut rng, threshold - 1);
+
+    c.bench_function("combining 67 shares (threshold 67)", |b| {
+        b.iter_batched(
+            || {
+                node_indices
+                    .iter()
+                    .map(|&node_index| polynomial.evaluate_at(&Scalar::from(node_index)).into())
+                    .collect::<Vec<Share>>()
+            },
+            |shares| black_box(combine_shares(shares, &node_indices).unwrap()),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
 criterion_group!(
     utils,
     precompute_default_bsgs_table,
@@ -496,6 +518,7 @@ criterion_group!(
     single_share_encryption,
     share_encryption_100,
     share_decryption,
+    share_combination_for_100_parties,
 );
 
 criterion_main!(
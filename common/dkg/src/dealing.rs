@@ -53,7 +53,6 @@ impl RecoveredVerificationKeys {
         })?;
 
         let partials = u32::from_be_bytes([b[96], b[97], b[98], b[99]]) as usize;
-        let mut recovered_partials = Vec::with_capacity(partials);
 
         if b.len() != 96 + 4 + 96 * partials {
             return Err(DkgError::new_deserialization_failure(
@@ -62,6 +61,8 @@ impl RecoveredVerificationKeys {
             ));
         }
 
+        let mut recovered_partials = Vec::with_capacity(partials);
+
         let mut i = 96 + 4;
         for _ in 0..partials {
             let partial = deserialize_g2(&b[i..i + 96]).ok_or_else(|| {
@@ -256,47 +257,23 @@ impl Dealing {
     }
 
     pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, DkgError> {
-        // can we read the length of serialized public coefficients?
-        if bytes.len() < 4 {
-            return Err(DkgError::new_deserialization_failure(
-                "Dealing",
-                "insufficient number of bytes provided",
-            ));
-        }
-
         let mut i = 0;
-        let coefficients_bytes_len =
-            u32::from_be_bytes((&bytes[i..i + 4]).try_into().unwrap()) as usize;
-        i += 4;
         let public_coefficients =
-            PublicCoefficients::try_from_bytes(&bytes[i..i + coefficients_bytes_len])?;
-        i += coefficients_bytes_len;
-
-        let ciphertexts_bytes_len =
-            u32::from_be_bytes((&bytes[i..i + 4]).try_into().unwrap()) as usize;
-        i += 4;
-        let ciphertexts = Ciphertexts::try_from_bytes(&bytes[i..i + ciphertexts_bytes_len])?;
-        i += ciphertexts_bytes_len;
-
-        let proof_of_sharing_bytes_len =
-            u32::from_be_bytes((&bytes[i..i + 4]).try_into().unwrap()) as usize;
-        i += 4;
+            PublicCoefficients::try_from_bytes(read_length_prefixed(bytes, &mut i)?)?;
+        let ciphertexts = Ciphertexts::try_from_bytes(read_length_prefixed(bytes, &mut i)?)?;
         let proof_of_sharing =
-            ProofOfSecretSharing::try_from_bytes(&bytes[i..i + proof_of_sharing_bytes_len])?;
-        i += proof_of_sharing_bytes_len;
+            ProofOfSecretSharing::try_from_bytes(read_length_prefixed(bytes, &mut i)?)?;
 
-        let proof_of_chunking_bytes_len =
-            u32::from_be_bytes((&bytes[i..i + 4]).try_into().unwrap()) as usize;
-        i += 4;
+        let proof_of_chunking_bytes = read_length_prefixed(bytes, &mut i)?;
 
-        if bytes[i..].len() != proof_of_chunking_bytes_len {
+        if i != bytes.len() {
             return Err(DkgError::new_deserialization_failure(
                 "Dealing",
                 "invalid number of bytes provided",
             ));
         }
 
-        let proof_of_chunking = ProofOfChunking::try_from_bytes(&bytes[i..])?;
+        let proof_of_chunking = ProofOfChunking::try_from_bytes(proof_of_chunking_bytes)?;
 
         Ok(Dealing {
             public_coefficients,
@@ -307,6 +284,28 @@ impl Dealing {
     }
 }
 
+/// Reads the next `u32` length-prefixed chunk of bytes, advancing the provided index past it.
+fn read_length_prefixed<'a>(bytes: &'a [u8], i: &mut usize) -> Result<&'a [u8], DkgError> {
+    if bytes[*i..].len() < 4 {
+        return Err(DkgError::new_deserialization_failure(
+            "Dealing",
+            "insufficient number of bytes provided",
+        ));
+    }
+    let len = u32::from_be_bytes((&bytes[*i..*i + 4]).try_into().unwrap()) as usize;
+    *i += 4;
+
+    if bytes[*i..].len() < len {
+        return Err(DkgError::new_deserialization_failure(
+            "Dealing",
+            "insufficient number of bytes provided",
+        ));
+    }
+    let chunk = &bytes[*i..*i + len];
+    *i += len;
+    Ok(chunk)
+}
+
 #[cfg(feature = "cw-types")]
 impl<'a> From<&'a Dealing> for nym_contracts_common::dealings::ContractSafeBytes {
     fn from(dealing: &'a Dealing) -> Self {
@@ -439,6 +438,29 @@ mod tests {
         assert_eq!(keys, recovered_keys);
     }
 
+    #[test]
+    fn malformed_dealing_bytes_are_rejected() {
+        // truncated length prefix
+        assert!(Dealing::try_from_bytes(&[0, 0, 0]).is_err());
+        // length prefix pointing past the end of the data
+        assert!(Dealing::try_from_bytes(&[0, 0, 0, 42, 1, 2, 3]).is_err());
+        assert!(Dealing::try_from_bytes(&[255, 255, 255, 255]).is_err());
+        // valid (empty) public coefficients, but nothing else
+        assert!(Dealing::try_from_bytes(&[0, 0, 0, 4, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn malformed_recovered_verification_keys_are_rejected() {
+        let mut bytes = RecoveredVerificationKeys {
+            recovered_master: Default::default(),
+            recovered_partials: vec![],
+        }
+        .to_bytes();
+        // claim an absurd number of partials
+        bytes[96..100].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(RecoveredVerificationKeys::try_from_bytes(&bytes).is_err());
+    }
+
     #[test]
     #[ignore] // expensive test
     fn recovering_partial_verification_keys() {
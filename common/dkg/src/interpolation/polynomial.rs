@@ -70,7 +70,6 @@ impl PublicCoefficients {
         }
 
         let coeffs = u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize;
-        let mut coefficients = Vec::with_capacity(coeffs);
 
         if b.len() != 4 + coeffs * 96 {
             return Err(DkgError::new_deserialization_failure(
@@ -79,6 +78,8 @@ impl PublicCoefficients {
             ));
         }
 
+        let mut coefficients = Vec::with_capacity(coeffs);
+
         let mut i = 4;
         for _ in 0..coeffs {
             let coefficient = deserialize_g2(&b[i..i + 96]).ok_or_else(|| {
@@ -0,0 +1,303 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Small facade for pushing client and node metrics into external pipelines.
+//!
+//! Values are recorded through a cheap, cloneable [`Metrics`] handle into an in-memory registry,
+//! which is periodically pushed by the [`MetricsExporter`] into the backend selected in the config,
+//! i.e. a statsd daemon or an OTLP collector. Operators that prefer pulling the data can still
+//! query the http endpoints exposed by the nodes.
+
+use crate::otlp::OtlpExporter;
+use crate::statsd::StatsdExporter;
+use log::*;
+use nym_task::TaskClient;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+
+mod otlp;
+mod statsd;
+
+pub const DEFAULT_METRICS_PREFIX: &str = "nym";
+pub const DEFAULT_METRICS_EXPORT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum MetricsError {
+    #[error("no endpoint has been specified for the {backend} metrics backend")]
+    MissingEndpoint { backend: MetricsBackend },
+
+    #[error("failed to set up the statsd socket: {0}")]
+    StatsdSocket(#[from] std::io::Error),
+
+    #[error("failed to push metrics to the OTLP collector: {0}")]
+    Otlp(#[from] reqwest::Error),
+}
+
+/// Backend the metrics are going to be pushed into.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsBackend {
+    /// Metrics are not exported at all.
+    #[default]
+    Disabled,
+
+    /// Metrics are sent as statsd datagrams over UDP, e.g. to `127.0.0.1:8125`.
+    Statsd,
+
+    /// Metrics are posted using OTLP/HTTP with JSON encoding,
+    /// e.g. to `http://127.0.0.1:4318/v1/metrics`.
+    Otlp,
+}
+
+impl Display for MetricsBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MetricsBackend::Disabled => write!(f, "disabled"),
+            MetricsBackend::Statsd => write!(f, "statsd"),
+            MetricsBackend::Otlp => write!(f, "otlp"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MetricsConfig {
+    pub backend: MetricsBackend,
+
+    /// Address of the statsd daemon or url of the OTLP collector.
+    pub endpoint: String,
+
+    /// Prefix of all metric names (statsd) or the name of the service (OTLP).
+    pub prefix: String,
+
+    /// Interval between subsequent exports.
+    pub export_interval: Duration,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            backend: MetricsBackend::Disabled,
+            endpoint: String::new(),
+            prefix: DEFAULT_METRICS_PREFIX.to_string(),
+            export_interval: DEFAULT_METRICS_EXPORT_INTERVAL,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    counters: Mutex<BTreeMap<String, u64>>,
+    gauges: Mutex<BTreeMap<String, f64>>,
+}
+
+impl Registry {
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            counters: self.counters.lock().unwrap().clone(),
+            gauges: self.gauges.lock().unwrap().clone(),
+        }
+    }
+}
+
+/// Values of all metrics at particular point in time.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Snapshot {
+    /// Cumulative values of the counters.
+    pub(crate) counters: BTreeMap<String, u64>,
+    pub(crate) gauges: BTreeMap<String, f64>,
+}
+
+/// Handle used for recording metrics. If the metrics are disabled, recording is a no-op.
+#[derive(Debug, Default, Clone)]
+pub struct Metrics {
+    registry: Option<Arc<Registry>>,
+}
+
+impl Metrics {
+    pub fn disabled() -> Self {
+        Metrics { registry: None }
+    }
+
+    fn enabled() -> Self {
+        Metrics {
+            registry: Some(Arc::new(Registry::default())),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.registry.is_some()
+    }
+
+    /// Increments the monotonic counter with the specified name.
+    pub fn increment_counter(&self, name: &str, value: u64) {
+        if let Some(registry) = &self.registry {
+            let mut counters = registry.counters.lock().unwrap();
+            let counter = counters.entry(name.to_string()).or_default();
+            *counter = counter.saturating_add(value);
+        }
+    }
+
+    /// Sets the current value of the gauge with the specified name.
+    pub fn set_gauge(&self, name: &str, value: f64) {
+        if let Some(registry) = &self.registry {
+            registry
+                .gauges
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), value);
+        }
+    }
+}
+
+enum Exporter {
+    Statsd(StatsdExporter),
+    Otlp(OtlpExporter),
+}
+
+impl Exporter {
+    async fn export(&mut self, snapshot: Snapshot) -> Result<(), MetricsError> {
+        match self {
+            Exporter::Statsd(exporter) => exporter.export(snapshot).await,
+            Exporter::Otlp(exporter) => exporter.export(snapshot).await,
+        }
+    }
+}
+
+/// Task periodically pushing the recorded metrics into the configured backend.
+pub struct MetricsExporter {
+    registry: Arc<Registry>,
+    exporter: Exporter,
+    export_interval: Duration,
+    shutdown: TaskClient,
+}
+
+impl MetricsExporter {
+    /// Creates the exporter for the configured backend alongside the handle for recording
+    /// the metrics. Returns `None` as the exporter if the metrics are disabled.
+    pub async fn new(
+        config: MetricsConfig,
+        shutdown: TaskClient,
+    ) -> Result<(Metrics, Option<Self>), MetricsError> {
+        if config.backend == MetricsBackend::Disabled {
+            return Ok((Metrics::disabled(), None));
+        }
+        if config.endpoint.is_empty() {
+            return Err(MetricsError::MissingEndpoint {
+                backend: config.backend,
+            });
+        }
+
+        let exporter = match config.backend {
+            MetricsBackend::Statsd => {
+                Exporter::Statsd(StatsdExporter::new(&config.endpoint, config.prefix).await?)
+            }
+            MetricsBackend::Otlp => {
+                Exporter::Otlp(OtlpExporter::new(config.endpoint, config.prefix))
+            }
+            MetricsBackend::Disabled => unreachable!(),
+        };
+
+        let metrics = Metrics::enabled();
+        // unwrap is fine as we've just created an enabled handle
+        let registry = Arc::clone(metrics.registry.as_ref().unwrap());
+        Ok((
+            metrics,
+            Some(MetricsExporter {
+                registry,
+                exporter,
+                export_interval: config.export_interval,
+                shutdown,
+            }),
+        ))
+    }
+
+    async fn export(&mut self) {
+        let snapshot = self.registry.snapshot();
+        if let Err(err) = self.exporter.export(snapshot).await {
+            warn!("Failed to export metrics - {err}")
+        }
+    }
+
+    pub async fn run(&mut self) {
+        let mut interval = tokio::time::interval(self.export_interval);
+        while !self.shutdown.is_shutdown() {
+            tokio::select! {
+                _ = interval.tick() => self.export().await,
+                _ = self.shutdown.recv() => {
+                    trace!("MetricsExporter: Received shutdown");
+                }
+            }
+        }
+        // push whatever we have managed to record before exiting
+        self.export().await;
+        trace!("MetricsExporter: Exiting");
+    }
+
+    pub fn start(mut self) {
+        tokio::spawn(async move { self.run().await });
+    }
+}
+
+/// Convenience function for creating the metrics handle and starting the exporter, if enabled.
+pub async fn start_metrics(
+    config: MetricsConfig,
+    shutdown: TaskClient,
+) -> Result<Metrics, MetricsError> {
+    let backend = config.backend;
+    let (metrics, exporter) = MetricsExporter::new(config, shutdown).await?;
+    if let Some(exporter) = exporter {
+        info!("Exporting metrics using the {backend} backend");
+        exporter.start();
+    }
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_metrics_are_not_recorded() {
+        let metrics = Metrics::disabled();
+        metrics.increment_counter("foo", 42);
+        metrics.set_gauge("bar", 1.0);
+        assert!(!metrics.is_enabled());
+    }
+
+    #[test]
+    fn counters_are_cumulative() {
+        let metrics = Metrics::enabled();
+        let cloned = metrics.clone();
+        metrics.increment_counter("foo", 2);
+        cloned.increment_counter("foo", 3);
+        metrics.set_gauge("bar", 1.0);
+        cloned.set_gauge("bar", 2.5);
+
+        let snapshot = metrics.registry.as_ref().unwrap().snapshot();
+        assert_eq!(snapshot.counters["foo"], 5);
+        assert_eq!(snapshot.gauges["bar"], 2.5);
+    }
+
+    #[tokio::test]
+    async fn exporter_requires_endpoint() {
+        let config = MetricsConfig {
+            backend: MetricsBackend::Otlp,
+            ..Default::default()
+        };
+        assert!(matches!(
+            MetricsExporter::new(config, TaskClient::dummy()).await,
+            Err(MetricsError::MissingEndpoint { .. })
+        ));
+
+        let (metrics, exporter) =
+            MetricsExporter::new(MetricsConfig::default(), TaskClient::dummy())
+                .await
+                .unwrap();
+        assert!(!metrics.is_enabled());
+        assert!(exporter.is_none());
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{MetricsError, Snapshot};
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// as defined by the `AggregationTemporality` enum of the OTLP protobuf definitions
+const AGGREGATION_TEMPORALITY_CUMULATIVE: u8 = 2;
+
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default()
+}
+
+pub(crate) struct OtlpExporter {
+    client: reqwest::Client,
+    endpoint: String,
+    service_name: String,
+    start_time: SystemTime,
+}
+
+impl OtlpExporter {
+    pub(crate) fn new(endpoint: String, service_name: String) -> Self {
+        OtlpExporter {
+            client: reqwest::Client::new(),
+            endpoint,
+            service_name,
+            start_time: SystemTime::now(),
+        }
+    }
+
+    /// Builds the OTLP/HTTP JSON request with counters reported as cumulative monotonic sums
+    /// and gauges as, well, gauges. Note that 64-bit integers have to be encoded as strings.
+    fn build_request(&self, snapshot: Snapshot, now: SystemTime) -> Value {
+        let start = unix_nanos(self.start_time).to_string();
+        let now = unix_nanos(now).to_string();
+
+        let counters = snapshot.counters.into_iter().map(|(name, value)| {
+            json!({
+                "name": name,
+                "sum": {
+                    "dataPoints": [{
+                        "asInt": value.to_string(),
+                        "startTimeUnixNano": start,
+                        "timeUnixNano": now,
+                    }],
+                    "aggregationTemporality": AGGREGATION_TEMPORALITY_CUMULATIVE,
+                    "isMonotonic": true,
+                }
+            })
+        });
+        let gauges = snapshot.gauges.into_iter().map(|(name, value)| {
+            json!({
+                "name": name,
+                "gauge": {
+                    "dataPoints": [{
+                        "asDouble": value,
+                        "timeUnixNano": now,
+                    }]
+                }
+            })
+        });
+
+        json!({
+            "resourceMetrics": [{
+                "resource": {
+                    "attributes": [{
+                        "key": "service.name",
+                        "value": { "stringValue": self.service_name },
+                    }]
+                },
+                "scopeMetrics": [{
+                    "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                    "metrics": counters.chain(gauges).collect::<Vec<_>>(),
+                }]
+            }]
+        })
+    }
+
+    pub(crate) async fn export(&mut self, snapshot: Snapshot) -> Result<(), MetricsError> {
+        if snapshot.counters.is_empty() && snapshot.gauges.is_empty() {
+            return Ok(());
+        }
+        let request = self.build_request(snapshot, SystemTime::now());
+        self.client
+            .post(&self.endpoint)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_contains_all_metrics() {
+        let exporter = OtlpExporter::new(
+            "http://localhost:4318/v1/metrics".to_string(),
+            "nym-mixnode".to_string(),
+        );
+        let mut snapshot = Snapshot::default();
+        snapshot.counters.insert("packets_sent".to_string(), 42);
+        snapshot.gauges.insert("active_clients".to_string(), 3.0);
+
+        let request = exporter.build_request(snapshot, SystemTime::now());
+        let resource = &request["resourceMetrics"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][0]["value"]["stringValue"],
+            "nym-mixnode"
+        );
+
+        let metrics = resource["scopeMetrics"][0]["metrics"].as_array().unwrap();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0]["name"], "packets_sent");
+        assert_eq!(metrics[0]["sum"]["dataPoints"][0]["asInt"], "42");
+        assert_eq!(metrics[0]["sum"]["isMonotonic"], true);
+        assert_eq!(metrics[1]["name"], "active_clients");
+        assert_eq!(metrics[1]["gauge"]["dataPoints"][0]["asDouble"], 3.0);
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{MetricsError, Snapshot};
+use std::collections::BTreeMap;
+use tokio::net::UdpSocket;
+
+// keep the datagrams below the typical MTU so that they wouldn't get fragmented
+const MAX_DATAGRAM_SIZE: usize = 1432;
+
+pub(crate) struct StatsdExporter {
+    socket: UdpSocket,
+    prefix: String,
+    // statsd counters are deltas, so we have to remember what we have already reported
+    reported_counters: BTreeMap<String, u64>,
+}
+
+impl StatsdExporter {
+    pub(crate) async fn new(endpoint: &str, prefix: String) -> Result<Self, MetricsError> {
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        socket.connect(endpoint).await?;
+        Ok(StatsdExporter {
+            socket,
+            prefix,
+            reported_counters: BTreeMap::new(),
+        })
+    }
+
+    fn metric_name(&self, name: &str) -> String {
+        if self.prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{name}", self.prefix)
+        }
+    }
+
+    fn encode_lines(&mut self, snapshot: Snapshot) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (name, value) in snapshot.counters {
+            let reported = self
+                .reported_counters
+                .get(&name)
+                .copied()
+                .unwrap_or_default();
+            let delta = value.saturating_sub(reported);
+            if delta > 0 {
+                lines.push(format!("{}:{delta}|c", self.metric_name(&name)));
+            }
+            self.reported_counters.insert(name, value);
+        }
+        for (name, value) in snapshot.gauges {
+            lines.push(format!("{}:{value}|g", self.metric_name(&name)));
+        }
+        lines
+    }
+
+    pub(crate) async fn export(&mut self, snapshot: Snapshot) -> Result<(), MetricsError> {
+        for datagram in pack_datagrams(self.encode_lines(snapshot)) {
+            self.socket.send(datagram.as_bytes()).await?;
+        }
+        Ok(())
+    }
+}
+
+// statsd allows multiple metrics in a single datagram as long as they're separated by newlines
+fn pack_datagrams(lines: Vec<String>) -> Vec<String> {
+    let mut datagrams = Vec::new();
+    let mut current = String::new();
+    for line in lines {
+        if !current.is_empty() && current.len() + line.len() + 1 > MAX_DATAGRAM_SIZE {
+            datagrams.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(&line);
+    }
+    if !current.is_empty() {
+        datagrams.push(current);
+    }
+    datagrams
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn counters_are_reported_as_deltas() {
+        let daemon = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = daemon.local_addr().unwrap().to_string();
+        let mut exporter = StatsdExporter::new(&endpoint, "nym".to_string())
+            .await
+            .unwrap();
+
+        let mut snapshot = Snapshot::default();
+        snapshot.counters.insert("packets".to_string(), 10);
+        snapshot.gauges.insert("clients".to_string(), 3.0);
+        assert_eq!(
+            exporter.encode_lines(snapshot.clone()),
+            vec!["nym.packets:10|c", "nym.clients:3|g"]
+        );
+
+        snapshot.counters.insert("packets".to_string(), 15);
+        exporter.export(snapshot).await.unwrap();
+
+        let mut buf = [0u8; MAX_DATAGRAM_SIZE];
+        let received = daemon.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..received], b"nym.packets:5|c\nnym.clients:3|g");
+    }
+
+    #[test]
+    fn datagrams_are_split_when_too_big() {
+        let lines: Vec<_> = (0..200).map(|i| format!("nym.metric_{i}:1|c")).collect();
+        let datagrams = pack_datagrams(lines.clone());
+        assert!(datagrams.len() > 1);
+        assert!(datagrams.iter().all(|d| d.len() <= MAX_DATAGRAM_SIZE));
+        assert_eq!(datagrams.join("\n"), lines.join("\n"));
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::listener_guard::ListenerGuardConfig;
+use futures::{SinkExt, StreamExt};
+use nym_crypto::asymmetric::identity;
+use nym_sphinx_framing::codec::BoundedSphinxCodec;
+use nym_sphinx_framing::handshake::{
+    HandshakeCodec, HandshakeError, HandshakeMessage, HandshakeStatus,
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::codec::Framed;
+
+/// Completes the handshake of the peer that has just connected to us and switches the connection
+/// to the sphinx framing. Peers running outdated software that start sending the sphinx frames
+/// straight away are let through without the handshake.
+pub async fn accept_handshake<S>(
+    conn: S,
+    identity: &identity::PublicKey,
+    guard: &ListenerGuardConfig,
+) -> Result<Framed<S, BoundedSphinxCodec>, HandshakeError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let own_identity = identity.to_bytes();
+    let mut framed_conn = Framed::new(conn, HandshakeCodec);
+
+    let request = match tokio::time::timeout(guard.frame_timeout, framed_conn.next()).await {
+        Err(_) => return Err(HandshakeError::Timeout),
+        Ok(None) => return Err(HandshakeError::ConnectionClosed),
+        Ok(Some(Err(HandshakeError::NotAHandshake))) => {
+            log::trace!("the peer did not send the handshake - assuming the legacy framing");
+            return Ok(into_sphinx_framing(framed_conn, guard.codec()));
+        }
+        Ok(Some(Err(err))) => return Err(err),
+        Ok(Some(Ok(request))) => request,
+    };
+
+    if let Err(err) = request.evaluate_request() {
+        // let the peer know why the connection is going to get closed
+        if let Some(status) = err.rejection_status() {
+            framed_conn
+                .send(HandshakeMessage::new_response(status, own_identity))
+                .await?;
+        }
+        return Err(err);
+    }
+
+    framed_conn
+        .send(HandshakeMessage::new_response(
+            HandshakeStatus::Accepted,
+            own_identity,
+        ))
+        .await?;
+    Ok(into_sphinx_framing(framed_conn, guard.codec()))
+}
+
+fn into_sphinx_framing<S>(
+    framed_conn: Framed<S, HandshakeCodec>,
+    codec: BoundedSphinxCodec,
+) -> Framed<S, BoundedSphinxCodec> {
+    // make sure to keep any bytes the peer might have already sent after the handshake
+    let parts = framed_conn.into_parts();
+    let mut sphinx_conn = Framed::new(parts.io, codec);
+    *sphinx_conn.read_buffer_mut() = parts.read_buf;
+    sphinx_conn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nym_sphinx_framing::handshake::CURRENT_PROTOCOL_VERSION;
+    use tokio::io::AsyncWriteExt;
+
+    fn test_identity() -> identity::PublicKey {
+        let private_key = identity::PrivateKey::from_bytes(&[1; 32]).unwrap();
+        (&private_key).into()
+    }
+
+    #[tokio::test]
+    async fn handshake_is_accepted() {
+        let identity = test_identity();
+        let (local, remote) = tokio::io::duplex(1024);
+        let mut peer = Framed::new(remote, HandshakeCodec);
+
+        peer.send(HandshakeMessage::new_request()).await.unwrap();
+        assert!(
+            accept_handshake(local, &identity, &ListenerGuardConfig::default())
+                .await
+                .is_ok()
+        );
+
+        let response = peer.next().await.unwrap().unwrap();
+        assert_eq!(response.evaluate_response().unwrap(), identity.to_bytes());
+    }
+
+    #[tokio::test]
+    async fn unsupported_versions_are_rejected() {
+        let identity = test_identity();
+
+        let (local, remote) = tokio::io::duplex(1024);
+        let mut peer = Framed::new(remote, HandshakeCodec);
+        peer.send(HandshakeMessage::Request {
+            protocol_version: CURRENT_PROTOCOL_VERSION + 1,
+        })
+        .await
+        .unwrap();
+        assert!(matches!(
+            accept_handshake(local, &identity, &ListenerGuardConfig::default()).await,
+            Err(HandshakeError::UnsupportedVersion { .. })
+        ));
+        assert!(matches!(
+            peer.next().await.unwrap().unwrap().evaluate_response(),
+            Err(HandshakeError::Rejected {
+                status: HandshakeStatus::UnsupportedVersion,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn legacy_peers_are_let_through() {
+        let identity = test_identity();
+        let (local, mut remote) = tokio::io::duplex(1024);
+
+        // beginning of a versioned sphinx frame header
+        remote.write_all(&[7, 1, 0]).await.unwrap();
+        let sphinx_conn = accept_handshake(local, &identity, &ListenerGuardConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(sphinx_conn.read_buffer().as_ref(), &[7, 1, 0]);
+    }
+}
@@ -1,5 +1,7 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
+pub mod handshake;
+pub mod listener_guard;
 pub mod packet_processor;
 pub mod verloc;
 
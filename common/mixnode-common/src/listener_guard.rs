@@ -0,0 +1,160 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protection of the mix listeners against peers sending oversized frames, holding connections
+//! open without sending anything or trickling partial frames in order to exhaust our resources
+//! (i.e. slow-loris attacks).
+
+use nym_sphinx_framing::codec::BoundedSphinxCodec;
+use nym_sphinx_params::PacketSize;
+use std::time::Duration;
+use tokio::time::Instant;
+
+// the largest frame consists of the versioned header and the largest extended packet
+pub const DEFAULT_MAXIMUM_FRAME_SIZE: usize = 3 + PacketSize::ExtendedPacket32.size();
+pub const DEFAULT_CONNECTION_READ_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+pub const DEFAULT_FRAME_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy)]
+pub struct ListenerGuardConfig {
+    /// Maximum size of a single frame (i.e. framing header and the sphinx packet) accepted
+    /// from the peers.
+    pub maximum_frame_size: usize,
+
+    /// Maximum duration of a connection not delivering any complete frame before it gets closed.
+    pub read_timeout: Duration,
+
+    /// Maximum duration for which a partially received frame can remain incomplete.
+    pub frame_timeout: Duration,
+}
+
+impl Default for ListenerGuardConfig {
+    fn default() -> Self {
+        ListenerGuardConfig {
+            maximum_frame_size: DEFAULT_MAXIMUM_FRAME_SIZE,
+            read_timeout: DEFAULT_CONNECTION_READ_TIMEOUT,
+            frame_timeout: DEFAULT_FRAME_TIMEOUT,
+        }
+    }
+}
+
+impl ListenerGuardConfig {
+    pub fn codec(&self) -> BoundedSphinxCodec {
+        BoundedSphinxCodec::new(self.maximum_frame_size)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionVerdict {
+    Keep,
+
+    /// The peer hasn't sent any complete frame within the read timeout.
+    Idle,
+
+    /// The peer has been holding a partial frame for longer than the frame timeout.
+    Stalled,
+}
+
+/// Keeps track of the progress of a single incoming connection.
+#[derive(Debug)]
+pub struct ConnectionGuard {
+    config: ListenerGuardConfig,
+    last_frame: Instant,
+    received_since_check: bool,
+    partial_at_last_check: bool,
+}
+
+impl ConnectionGuard {
+    pub fn new(config: ListenerGuardConfig, now: Instant) -> Self {
+        ConnectionGuard {
+            config,
+            last_frame: now,
+            received_since_check: false,
+            partial_at_last_check: false,
+        }
+    }
+
+    /// Interval at which `check` should be called.
+    pub fn check_interval(&self) -> Duration {
+        self.config.frame_timeout
+    }
+
+    pub fn on_frame(&mut self, now: Instant) {
+        self.last_frame = now;
+        self.received_since_check = true;
+    }
+
+    /// Determines whether the connection should be kept open, given whether there are any bytes
+    /// of an incomplete frame currently buffered.
+    pub fn check(&mut self, has_partial_frame: bool, now: Instant) -> ConnectionVerdict {
+        // the partial frame has been there at the previous check and nothing has been completed since
+        let stalled = has_partial_frame && self.partial_at_last_check && !self.received_since_check;
+
+        self.partial_at_last_check = has_partial_frame;
+        self.received_since_check = false;
+
+        if stalled {
+            ConnectionVerdict::Stalled
+        } else if now.saturating_duration_since(self.last_frame) >= self.config.read_timeout {
+            ConnectionVerdict::Idle
+        } else {
+            ConnectionVerdict::Keep
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> ListenerGuardConfig {
+        ListenerGuardConfig {
+            maximum_frame_size: DEFAULT_MAXIMUM_FRAME_SIZE,
+            read_timeout: Duration::from_secs(60),
+            frame_timeout: Duration::from_secs(10),
+        }
+    }
+
+    #[test]
+    fn stalled_partial_frames_are_detected() {
+        let start = Instant::now();
+        let mut guard = ConnectionGuard::new(test_config(), start);
+
+        // the partial frame might have only just arrived
+        assert_eq!(
+            guard.check(true, start + Duration::from_secs(10)),
+            ConnectionVerdict::Keep
+        );
+        assert_eq!(
+            guard.check(true, start + Duration::from_secs(20)),
+            ConnectionVerdict::Stalled
+        );
+    }
+
+    #[test]
+    fn progressing_connections_are_kept() {
+        let start = Instant::now();
+        let mut guard = ConnectionGuard::new(test_config(), start);
+
+        for i in 1..10 {
+            let now = start + Duration::from_secs(10 * i);
+            guard.on_frame(now);
+            assert_eq!(guard.check(true, now), ConnectionVerdict::Keep);
+        }
+    }
+
+    #[test]
+    fn idle_connections_are_detected() {
+        let start = Instant::now();
+        let mut guard = ConnectionGuard::new(test_config(), start);
+
+        assert_eq!(
+            guard.check(false, start + Duration::from_secs(30)),
+            ConnectionVerdict::Keep
+        );
+        assert_eq!(
+            guard.check(false, start + Duration::from_secs(60)),
+            ConnectionVerdict::Idle
+        );
+    }
+}
@@ -5,16 +5,20 @@ use crate::measure;
 use crate::packet_processor::error::MixProcessingError;
 use log::*;
 use nym_sphinx_acknowledgements::surb_ack::SurbAck;
+use nym_sphinx_addressing::expiry::decode_expiry;
 use nym_sphinx_addressing::nodes::NymNodeRoutingAddress;
 use nym_sphinx_forwarding::packet::MixPacket;
 use nym_sphinx_framing::packet::FramedSphinxPacket;
+#[cfg(feature = "packet-tracing")]
+use nym_sphinx_params::packet_tracing::TraceId;
 use nym_sphinx_params::{PacketMode, PacketSize};
 use nym_sphinx_types::{
     Delay as SphinxDelay, DestinationAddressBytes, NodeAddressBytes, Payload, PrivateKey,
-    ProcessedPacket, SphinxPacket,
+    ProcessedPacket, SURBIdentifier, SphinxPacket,
 };
 use std::convert::TryFrom;
 use std::sync::Arc;
+use std::time::SystemTime;
 #[cfg(feature = "cpucycles")]
 use tracing::instrument;
 
@@ -24,6 +28,10 @@ pub struct ProcessedFinalHop {
     pub destination: DestinationAddressBytes,
     pub forward_ack: Option<ForwardAck>,
     pub message: Vec<u8>,
+    /// Time after which the message should no longer be delivered, if the sender has specified it.
+    pub expires_at: Option<SystemTime>,
+    #[cfg(feature = "packet-tracing")]
+    pub trace_id: TraceId,
 }
 
 pub enum MixProcessingResult {
@@ -34,6 +42,24 @@ pub enum MixProcessingResult {
     FinalHop(ProcessedFinalHop),
 }
 
+#[cfg(feature = "packet-tracing")]
+impl MixProcessingResult {
+    /// Makes sure all packets resulting from the processing carry the trace id of the received packet.
+    fn with_trace_id(self, trace_id: TraceId) -> Self {
+        match self {
+            MixProcessingResult::ForwardHop(packet, delay) => {
+                MixProcessingResult::ForwardHop(packet.with_trace_id(trace_id), delay)
+            }
+            MixProcessingResult::FinalHop(mut final_hop) => {
+                final_hop.forward_ack =
+                    final_hop.forward_ack.map(|ack| ack.with_trace_id(trace_id));
+                final_hop.trace_id = trace_id;
+                MixProcessingResult::FinalHop(final_hop)
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct SphinxPacketProcessor {
     /// Private sphinx key of this node required to unwrap received sphinx packet.
@@ -150,6 +176,7 @@ impl SphinxPacketProcessor {
     fn process_final_hop(
         &self,
         destination: DestinationAddressBytes,
+        identifier: SURBIdentifier,
         payload: Payload,
         packet_size: PacketSize,
         packet_mode: PacketMode,
@@ -163,6 +190,9 @@ impl SphinxPacketProcessor {
             destination,
             forward_ack,
             message,
+            expires_at: decode_expiry(&identifier),
+            #[cfg(feature = "packet-tracing")]
+            trace_id: Default::default(),
         }))
     }
 
@@ -178,10 +208,9 @@ impl SphinxPacketProcessor {
             ProcessedPacket::ForwardHop(packet, address, delay) => {
                 self.process_forward_hop(*packet, address, delay, packet_mode)
             }
-            // right now there's no use for the surb_id included in the header - probably it should get removed from the
-            // sphinx all together?
-            ProcessedPacket::FinalHop(destination, _, payload) => {
-                self.process_final_hop(destination, payload, packet_size, packet_mode)
+            // the surb_id included in the header is (ab)used for carrying the expiry of the message
+            ProcessedPacket::FinalHop(destination, identifier, payload) => {
+                self.process_final_hop(destination, identifier, payload, packet_size, packet_mode)
             }
         }
     }
@@ -198,13 +227,21 @@ impl SphinxPacketProcessor {
         measure!({
             let packet_size = received.packet_size();
             let packet_mode = received.packet_mode();
+            #[cfg(feature = "packet-tracing")]
+            let trace_id = received.trace_id();
 
             // unwrap the sphinx packet and if possible and appropriate, cache keys
             let processed_packet = self.perform_initial_unwrapping(received)?;
 
             // for forward packets, extract next hop and set delay (but do NOT delay here)
             // for final packets, extract SURBAck
-            self.perform_final_processing(processed_packet, packet_size, packet_mode)
+            let processed =
+                self.perform_final_processing(processed_packet, packet_size, packet_mode)?;
+
+            #[cfg(feature = "packet-tracing")]
+            let processed = processed.with_trace_id(trace_id);
+
+            Ok(processed)
         })
     }
 }
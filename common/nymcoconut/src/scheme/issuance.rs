@@ -281,6 +281,15 @@ pub fn blind_sign(
         });
     }
 
+    // the key has to be able to sign all of the attributes, otherwise some of them would be
+    // silently left out of the signature and the resulting credential would fail to verify
+    if signing_secret_key.ys.len() < hs.len() {
+        return Err(CoconutError::IssuanceMaxAttributes {
+            max: signing_secret_key.ys.len(),
+            requested: hs.len(),
+        });
+    }
+
     // Verify the commitment hash
     let h = compute_hash(blind_sign_request.commitment, public_attributes);
     if !(h == blind_sign_request.commitment_hash) {
@@ -385,4 +394,27 @@ mod tests {
             lambda
         );
     }
+
+    #[test]
+    fn blind_sign_rejects_keys_with_too_few_attributes() {
+        let params = Parameters::new(5).unwrap();
+        let private_attributes = params.n_random_scalars(2);
+        let public_attributes = params.n_random_scalars(3);
+
+        let (_commitments_openings, lambda) =
+            prepare_blind_sign(&params, &private_attributes, &public_attributes).unwrap();
+
+        let small_params = Parameters::new(4).unwrap();
+        let keypair = crate::scheme::keygen::keygen(&small_params);
+        assert!(matches!(
+            blind_sign(&params, &keypair.secret_key(), &lambda, &public_attributes),
+            Err(CoconutError::IssuanceMaxAttributes {
+                max: 4,
+                requested: 5
+            })
+        ));
+
+        let keypair = crate::scheme::keygen::keygen(&params);
+        assert!(blind_sign(&params, &keypair.secret_key(), &lambda, &public_attributes).is_ok());
+    }
 }
@@ -13,7 +13,7 @@ use nym_sphinx_types::builder::SphinxPacketBuilder;
 use nym_sphinx_types::Error as SphinxError;
 use nym_sphinx_types::{
     delays::{self, Delay},
-    SphinxPacket,
+    SphinxConstraints, SphinxPacket,
 };
 use nym_topology::{NymTopology, NymTopologyError};
 use rand::{CryptoRng, RngCore};
@@ -57,6 +57,12 @@ impl SurbAck {
         let destination = recipient.as_sphinx_destination();
 
         let surb_ack_payload = prepare_identifier(rng, ack_key, marshaled_fragment_id);
+        SphinxConstraints::default().validate_packet(
+            &route,
+            &delays,
+            surb_ack_payload.len(),
+            PacketSize::AckPacket.payload_size(),
+        )?;
 
         let surb_ack_packet = SphinxPacketBuilder::new()
             .with_payload_size(PacketSize::AckPacket.payload_size())
@@ -4,6 +4,7 @@
 // of a helper/utils structure, because before it reaches the gateway
 // it's already destructed).
 
+use crate::expiry::encode_expiry;
 use crate::nodes::{NodeIdentity, NODE_IDENTITY_SIZE};
 use nym_crypto::asymmetric::{encryption, identity};
 use nym_sphinx_types::Destination;
@@ -11,6 +12,7 @@ use serde::de::{Error as SerdeError, Unexpected, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::{self, Formatter};
 use std::str::FromStr;
+use std::time::SystemTime;
 use thiserror::Error;
 
 // Not entirely sure whether this is the correct place for those, but let's see how it's going
@@ -123,6 +125,19 @@ impl Recipient {
         )
     }
 
+    /// Same as [`Recipient::as_sphinx_destination`], but with the expiry of the message
+    /// encoded in the otherwise unused "surb_id" field so that the gateway could drop the message
+    /// once it's no longer relevant.
+    pub fn as_expiring_sphinx_destination(&self, expires_at: Option<SystemTime>) -> Destination {
+        match expires_at {
+            Some(expires_at) => Destination::new(
+                self.client_identity.derive_destination_address(),
+                encode_expiry(expires_at),
+            ),
+            None => self.as_sphinx_destination(),
+        }
+    }
+
     pub fn identity(&self) -> &ClientIdentity {
         &self.client_identity
     }
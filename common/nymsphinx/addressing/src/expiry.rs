@@ -0,0 +1,95 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encoding of the message expiry inside the identifier of the sphinx destination.
+//!
+//! The nym mix network has no use for the "surb_id" included in the final hop of the packet,
+//! so the sender can use it to tell the recipient's gateway for how long the message is relevant.
+//! Identifiers that do not start with [`EXPIRY_MARKER`] (such as the all-zero default) are treated
+//! as messages that never expire.
+//!
+//! Note that the identifier is visible to the recipient's gateway in the clear, hence attaching
+//! the expiry reveals some metadata to it:
+//! - all fragments of the message carry the same expiry, so they can be linked together
+//!   (and told apart from the fragments of other messages of the same sender),
+//! - knowing the ttl commonly used by the sender, the gateway can estimate when the message
+//!   was sent and thus how long it took to traverse the mixnet.
+//!
+//! To limit the latter, the expiry is rounded up to [`EXPIRY_GRANULARITY_SECS`], which is
+//! deliberately coarser than the granularity of the timestamps attached to the messages
+//! themselves. Clients only ever attach it if explicitly configured to do so.
+
+use nym_sphinx_types::{SURBIdentifier, IDENTIFIER_LENGTH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Prefix of the identifier indicating it contains the expiry of the message.
+pub const EXPIRY_MARKER: [u8; 4] = *b"nexp";
+
+/// Granularity of the encoded expiry. The expiry is always rounded up to it, so that the message
+/// is never dropped prematurely.
+pub const EXPIRY_GRANULARITY_SECS: u64 = 300;
+
+const TIMESTAMP_LENGTH: usize = std::mem::size_of::<u64>();
+
+/// Encodes the expiry of the message into the identifier included in the sphinx destination.
+/// The expiry is rounded up to the [`EXPIRY_GRANULARITY_SECS`].
+pub fn encode_expiry(expires_at: SystemTime) -> SURBIdentifier {
+    let timestamp = expires_at
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| {
+            let secs = since_epoch.as_secs() + u64::from(since_epoch.subsec_nanos() > 0);
+            match secs % EXPIRY_GRANULARITY_SECS {
+                0 => secs,
+                remainder => secs + (EXPIRY_GRANULARITY_SECS - remainder),
+            }
+        })
+        .unwrap_or_default();
+
+    let mut identifier = [0u8; IDENTIFIER_LENGTH];
+    identifier[..EXPIRY_MARKER.len()].copy_from_slice(&EXPIRY_MARKER);
+    identifier[EXPIRY_MARKER.len()..EXPIRY_MARKER.len() + TIMESTAMP_LENGTH]
+        .copy_from_slice(&timestamp.to_be_bytes());
+    identifier
+}
+
+/// Attempts to recover the expiry of the message from the identifier of its sphinx destination.
+/// Returns `None` if the message has no expiry attached.
+pub fn decode_expiry(identifier: &SURBIdentifier) -> Option<SystemTime> {
+    if identifier[..EXPIRY_MARKER.len()] != EXPIRY_MARKER {
+        return None;
+    }
+
+    let mut timestamp = [0u8; TIMESTAMP_LENGTH];
+    timestamp
+        .copy_from_slice(&identifier[EXPIRY_MARKER.len()..EXPIRY_MARKER.len() + TIMESTAMP_LENGTH]);
+    UNIX_EPOCH.checked_add(Duration::from_secs(u64::from_be_bytes(timestamp)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expiry_is_rounded_up_to_the_granularity() {
+        let expires_at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        let decoded = decode_expiry(&encode_expiry(expires_at)).unwrap();
+        assert_eq!(decoded, UNIX_EPOCH + Duration::from_secs(1_700_000_100));
+
+        // expiries already at the granularity are kept as they are
+        let expires_at = UNIX_EPOCH + Duration::from_secs(1_700_000_100);
+        assert_eq!(
+            decode_expiry(&encode_expiry(expires_at)).unwrap(),
+            expires_at
+        );
+
+        let expires_at = UNIX_EPOCH + Duration::from_millis(1_700_000_100_001);
+        let decoded = decode_expiry(&encode_expiry(expires_at)).unwrap();
+        assert_eq!(decoded, UNIX_EPOCH + Duration::from_secs(1_700_000_400));
+    }
+
+    #[test]
+    fn default_identifier_has_no_expiry() {
+        assert!(decode_expiry(&Default::default()).is_none());
+        assert!(decode_expiry(&[42u8; IDENTIFIER_LENGTH]).is_none());
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use nym_sphinx::acknowledgements::identifier::{prepare_identifier, recover_identifier};
+use nym_sphinx::acknowledgements::surb_ack::SurbAck;
+use nym_sphinx::acknowledgements::AckKey;
+use nym_sphinx::addressing::nodes::{NymNodeRoutingAddress, MAX_NODE_ADDRESS_UNPADDED_LEN};
+use nym_sphinx::message::NymMessage;
+use nym_sphinx::params::PacketSize;
+use nym_sphinx::{
+    builder::SphinxPacketBuilder, crypto, Delay, Destination, DestinationAddressBytes, Node,
+    NodeAddressBytes, PrivateKey, SphinxPacket, DESTINATION_ADDRESS_LENGTH, IDENTIFIER_LENGTH,
+};
+use rand::rngs::OsRng;
+use std::convert::TryInto;
+use std::net::SocketAddr;
+
+const ROUTE_LENGTHS: [usize; 5] = [1, 2, 3, 4, 5];
+const MESSAGE_SIZES: [usize; 4] = [1024, 16 * 1024, 256 * 1024, 2 * 1024 * 1024];
+
+fn node_address(index: usize) -> NodeAddressBytes {
+    let socket_addr: SocketAddr = format!("10.0.0.{}:1789", index + 1).parse().unwrap();
+    NymNodeRoutingAddress::from(socket_addr).try_into().unwrap()
+}
+
+fn make_route(length: usize) -> (Vec<Node>, Vec<PrivateKey>) {
+    let mut route = Vec::with_capacity(length);
+    let mut keys = Vec::with_capacity(length);
+    for i in 0..length {
+        let (private_key, public_key) = crypto::keygen();
+        route.push(Node::new(node_address(i), public_key));
+        keys.push(private_key);
+    }
+    (route, keys)
+}
+
+fn destination() -> Destination {
+    Destination::new(
+        DestinationAddressBytes::from_bytes([42u8; DESTINATION_ADDRESS_LENGTH]),
+        [0u8; IDENTIFIER_LENGTH],
+    )
+}
+
+fn make_packet(route: &[Node], packet_size: PacketSize, payload: &[u8]) -> SphinxPacket {
+    let delays = vec![Delay::new_from_nanos(42); route.len()];
+    SphinxPacketBuilder::new()
+        .with_payload_size(packet_size.payload_size())
+        .build_packet(payload, route, &destination(), &delays)
+        .unwrap()
+}
+
+pub fn sphinx_packet_creation(c: &mut Criterion) {
+    let payload = vec![42u8; PacketSize::RegularPacket.plaintext_size()];
+
+    let mut group = c.benchmark_group("sphinx packet creation");
+    for route_length in ROUTE_LENGTHS {
+        let (route, _) = make_route(route_length);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{route_length} hops")),
+            &route,
+            |b, route| {
+                b.iter(|| black_box(make_packet(route, PacketSize::RegularPacket, &payload)))
+            },
+        );
+    }
+    group.finish();
+}
+
+pub fn sphinx_packet_processing(c: &mut Criterion) {
+    let payload = vec![42u8; PacketSize::RegularPacket.plaintext_size()];
+
+    let mut group = c.benchmark_group("sphinx packet processing");
+    for route_length in ROUTE_LENGTHS {
+        let (route, keys) = make_route(route_length);
+        let packet_bytes = make_packet(&route, PacketSize::RegularPacket, &payload).to_bytes();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{route_length} hops")),
+            &packet_bytes,
+            |b, packet_bytes| {
+                b.iter(|| {
+                    // the packet is consumed during processing, so we have to recover it every time.
+                    // this mimics what happens on the node upon receiving the bytes from the wire
+                    let packet = SphinxPacket::from_bytes(packet_bytes).unwrap();
+                    black_box(packet.process(&keys[0]).unwrap())
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+pub fn message_chunking(c: &mut Criterion) {
+    let mut rng = OsRng;
+
+    let mut group = c.benchmark_group("message chunking");
+    for message_size in MESSAGE_SIZES {
+        let plaintext_per_packet = NymMessage::new_plain(Vec::new())
+            .true_available_plaintext_per_packet(PacketSize::RegularPacket);
+
+        group.throughput(Throughput::Bytes(message_size as u64));
+        group.bench_function(BenchmarkId::from_parameter(message_size), |b| {
+            b.iter_batched(
+                || NymMessage::new_plain(vec![42u8; message_size]),
+                |message| {
+                    black_box(
+                        message
+                            .pad_to_full_packet_lengths(plaintext_per_packet)
+                            .split_into_fragments(&mut rng, plaintext_per_packet),
+                    )
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+pub fn ack_identifier_preparation(c: &mut Criterion) {
+    let mut rng = OsRng;
+    let ack_key = AckKey::new(&mut rng);
+    let fragment_id = [1, 2, 3, 4, 5];
+
+    c.bench_function("ack identifier preparation", |b| {
+        b.iter(|| black_box(prepare_identifier(&mut rng, &ack_key, fragment_id)))
+    });
+}
+
+pub fn ack_identifier_recovery(c: &mut Criterion) {
+    let mut rng = OsRng;
+    let ack_key = AckKey::new(&mut rng);
+    let fragment_id = [1, 2, 3, 4, 5];
+    let ack_content = prepare_identifier(&mut rng, &ack_key, fragment_id);
+
+    c.bench_function("ack identifier recovery", |b| {
+        b.iter(|| black_box(recover_identifier(&ack_key, &ack_content).unwrap()))
+    });
+}
+
+pub fn surb_ack_first_hop_recovery(c: &mut Criterion) {
+    let mut rng = OsRng;
+    let ack_key = AckKey::new(&mut rng);
+    let (route, _) = make_route(4);
+    let ack_content = prepare_identifier(&mut rng, &ack_key, [1, 2, 3, 4, 5]);
+    let surb_ack_packet = make_packet(&route, PacketSize::AckPacket, &ack_content);
+
+    let first_hop = NymNodeRoutingAddress::try_from(route[0].address).unwrap();
+    let surb_ack_bytes: Vec<_> = first_hop
+        .as_zero_padded_bytes(MAX_NODE_ADDRESS_UNPADDED_LEN)
+        .into_iter()
+        .chain(surb_ack_packet.to_bytes())
+        .collect();
+
+    c.bench_function("SURB-ack first hop recovery", |b| {
+        b.iter(|| black_box(SurbAck::try_recover_first_hop_packet(&surb_ack_bytes).unwrap()))
+    });
+}
+
+criterion_group!(sphinx, sphinx_packet_creation, sphinx_packet_processing);
+
+criterion_group!(chunking, message_chunking);
+
+criterion_group!(
+    acks,
+    ack_identifier_preparation,
+    ack_identifier_recovery,
+    surb_ack_first_hop_recovery
+);
+
+criterion_main!(sphinx, chunking, acks);
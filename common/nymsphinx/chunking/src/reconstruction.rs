@@ -276,6 +276,16 @@ impl MessageReconstructor {
             .entry(set_id)
             .or_insert_with(|| ReconstructionBuffer::new(set_len));
 
+        // the fragment is either malformed or malicious as it disagrees with the other fragments
+        // of the set on its size. we can't do anything sensible with it but to drop it.
+        if buf.fragments.len() != set_len as usize {
+            warn!(
+                "received fragment claiming its set (id: {set_id}) consists of {set_len} fragments while we expected {}. It is going to be dropped",
+                buf.fragments.len()
+            );
+            return None;
+        }
+
         buf.insert_fragment(fragment);
         if self.is_message_fully_received(set_id) {
             Some(self.reconstruct_message(set_id))
@@ -1848,3 +1858,144 @@ mod message_reconstruction {
         }
     }
 }
+
+#[cfg(test)]
+mod reconstruction_properties {
+    use super::*;
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashSet;
+
+    const MAX_MESSAGE_LEN: usize = 40_000;
+
+    fn split_into_raw_fragments(
+        rng: &mut StdRng,
+        message: &[u8],
+        max_plaintext_size: usize,
+    ) -> Vec<Vec<u8>> {
+        crate::split_into_sets(rng, message, max_plaintext_size)
+            .into_iter()
+            .flat_map(|fragment_set| fragment_set.into_iter())
+            .map(|fragment| fragment.into_bytes())
+            .collect()
+    }
+
+    fn number_of_sets(raw_fragments: &[Vec<u8>]) -> usize {
+        let reconstructor = MessageReconstructor::new();
+        raw_fragments
+            .iter()
+            .map(|raw| reconstructor.recover_fragment(raw.clone()).unwrap().id())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Feeds all the provided raw fragments, in order, to a fresh reconstructor and returns
+    /// all messages that got reconstructed along the way. Malformed fragments are skipped.
+    fn reconstruct(raw_fragments: Vec<Vec<u8>>) -> Vec<ReconstructedMessage> {
+        let mut reconstructor = MessageReconstructor::new();
+        raw_fragments
+            .into_iter()
+            .filter_map(|raw| {
+                let fragment = reconstructor.recover_fragment(raw).ok()?;
+                reconstructor.insert_new_fragment(fragment)
+            })
+            .collect()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn reordered_fragments_reconstruct_original_message(
+            message in prop::collection::vec(any::<u8>(), 1..MAX_MESSAGE_LEN),
+            max_plaintext_size in 64usize..1024,
+            seed in any::<u64>(),
+        ) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut fragments = split_into_raw_fragments(&mut rng, &message, max_plaintext_size);
+            let sets = number_of_sets(&fragments);
+            fragments.shuffle(&mut rng);
+
+            let reconstructed = reconstruct(fragments);
+            prop_assert_eq!(reconstructed.len(), 1);
+            prop_assert_eq!(&reconstructed[0].0, &message);
+            prop_assert_eq!(reconstructed[0].1.len(), sets);
+        }
+
+        #[test]
+        fn lost_fragments_never_result_in_partial_message(
+            message in prop::collection::vec(any::<u8>(), 1..MAX_MESSAGE_LEN),
+            max_plaintext_size in 64usize..1024,
+            loss_probability in 0.0f64..0.5,
+            seed in any::<u64>(),
+        ) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut fragments = split_into_raw_fragments(&mut rng, &message, max_plaintext_size);
+            fragments.shuffle(&mut rng);
+
+            let total = fragments.len();
+            fragments.retain(|_| !rng.gen_bool(loss_probability));
+            let lost_any = fragments.len() != total;
+
+            let reconstructed = reconstruct(fragments);
+            if lost_any {
+                prop_assert!(reconstructed.is_empty());
+            } else {
+                prop_assert_eq!(reconstructed.len(), 1);
+                prop_assert_eq!(&reconstructed[0].0, &message);
+            }
+        }
+
+        #[test]
+        fn duplicate_fragments_never_corrupt_message(
+            message in prop::collection::vec(any::<u8>(), 1..MAX_MESSAGE_LEN),
+            max_plaintext_size in 64usize..1024,
+            duplication_probability in 0.0f64..0.5,
+            seed in any::<u64>(),
+        ) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let fragments = split_into_raw_fragments(&mut rng, &message, max_plaintext_size);
+
+            let mut with_duplicates = Vec::with_capacity(fragments.len() * 2);
+            for fragment in fragments {
+                if rng.gen_bool(duplication_probability) {
+                    with_duplicates.push(fragment.clone());
+                }
+                with_duplicates.push(fragment);
+            }
+            with_duplicates.shuffle(&mut rng);
+
+            let reconstructed = reconstruct(with_duplicates);
+            prop_assert!(!reconstructed.is_empty());
+            for (reconstructed_message, _) in reconstructed {
+                prop_assert_eq!(&reconstructed_message, &message);
+            }
+        }
+
+        #[test]
+        fn arbitrary_fragment_data_never_causes_a_panic(
+            message in prop::collection::vec(any::<u8>(), 1..MAX_MESSAGE_LEN),
+            max_plaintext_size in 64usize..1024,
+            junk in prop::collection::vec(prop::collection::vec(any::<u8>(), 0..64), 0..32),
+            tampered_byte in any::<prop::sample::Index>(),
+            tampered_value in any::<u8>(),
+            seed in any::<u64>(),
+        ) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut fragments = split_into_raw_fragments(&mut rng, &message, max_plaintext_size);
+
+            // tamper with a single byte of one of the headers
+            let target = rng.gen_range(0, fragments.len());
+            let header_byte = tampered_byte.index(10.min(fragments[target].len()));
+            fragments[target][header_byte] = tampered_value;
+
+            fragments.extend(junk);
+            fragments.shuffle(&mut rng);
+
+            // we make no claims about the content here, just that nothing blows up
+            reconstruct(fragments);
+        }
+    }
+}
@@ -14,7 +14,7 @@ use nym_sphinx_params::{
     PacketEncryptionAlgorithm, PacketHkdfAlgorithm, PacketMode, DEFAULT_NUM_MIX_HOPS,
 };
 use nym_sphinx_types::builder::SphinxPacketBuilder;
-use nym_sphinx_types::{delays, Error as SphinxError};
+use nym_sphinx_types::{delays, Error as SphinxError, SphinxConstraintError, SphinxConstraints};
 use nym_topology::{NymTopology, NymTopologyError};
 use rand::{CryptoRng, RngCore};
 use std::convert::TryFrom;
@@ -30,6 +30,9 @@ pub enum CoverMessageError {
 
     #[error("Could not construct a valid sphinx packet - {0}")]
     SphinxError(#[from] SphinxError),
+
+    #[error("The cover message would violate sphinx constraints - {0}")]
+    SphinxConstraintViolation(#[from] SphinxConstraintError),
 }
 
 pub fn generate_loop_cover_surb_ack<R>(
@@ -109,6 +112,12 @@ where
         topology.random_route_to_gateway(rng, DEFAULT_NUM_MIX_HOPS, full_address.gateway())?;
     let delays = delays::generate_from_average_duration(route.len(), average_packet_delay);
     let destination = full_address.as_sphinx_destination();
+    SphinxConstraints::default().validate_packet(
+        &route,
+        &delays,
+        packet_payload.len(),
+        packet_size.payload_size(),
+    )?;
 
     // once merged, that's an easy rng injection point for sphinx packets : )
     let packet = SphinxPacketBuilder::new()
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nym_sphinx_addressing::nodes::{NymNodeRoutingAddress, NymNodeRoutingAddressError};
+#[cfg(feature = "packet-tracing")]
+use nym_sphinx_params::packet_tracing::TraceId;
 use nym_sphinx_params::{PacketMode, PacketSize};
 use nym_sphinx_types::SphinxPacket;
 use std::convert::TryFrom;
@@ -48,6 +50,8 @@ pub struct MixPacket {
     next_hop: NymNodeRoutingAddress,
     sphinx_packet: SphinxPacket,
     packet_mode: PacketMode,
+    #[cfg(feature = "packet-tracing")]
+    trace_id: TraceId,
 }
 
 impl Debug for MixPacket {
@@ -73,9 +77,22 @@ impl MixPacket {
             next_hop,
             sphinx_packet,
             packet_mode,
+            #[cfg(feature = "packet-tracing")]
+            trace_id: TraceId::new_random(),
         }
     }
 
+    #[cfg(feature = "packet-tracing")]
+    pub fn with_trace_id(mut self, trace_id: TraceId) -> Self {
+        self.trace_id = trace_id;
+        self
+    }
+
+    #[cfg(feature = "packet-tracing")]
+    pub fn trace_id(&self) -> TraceId {
+        self.trace_id
+    }
+
     pub fn next_hop(&self) -> NymNodeRoutingAddress {
         self.next_hop
     }
@@ -94,7 +111,12 @@ impl MixPacket {
 
     // the message is formatted as follows:
     // PACKET_MODE || FIRST_HOP || SPHINX_PACKET
+    // (or PACKET_MODE || FIRST_HOP || TRACE_ID || SPHINX_PACKET with the `packet-tracing` feature)
     pub fn try_from_bytes(b: &[u8]) -> Result<Self, MixPacketFormattingError> {
+        if b.is_empty() {
+            return Err(MixPacketFormattingError::TooFewBytesProvided);
+        }
+
         let packet_mode = match PacketMode::try_from(b[0]) {
             Ok(mode) => mode,
             Err(_) => return Err(MixPacketFormattingError::InvalidPacketMode),
@@ -103,6 +125,12 @@ impl MixPacket {
         let next_hop = NymNodeRoutingAddress::try_from_bytes(&b[1..])?;
         let addr_offset = next_hop.bytes_min_len();
 
+        #[cfg(feature = "packet-tracing")]
+        let trace_id = TraceId::try_from_bytes(&b[addr_offset + 1..])
+            .ok_or(MixPacketFormattingError::TooFewBytesProvided)?;
+        #[cfg(feature = "packet-tracing")]
+        let addr_offset = addr_offset + TraceId::SIZE;
+
         let sphinx_packet_data = &b[addr_offset + 1..];
         let packet_size = sphinx_packet_data.len();
         if PacketSize::get_type(packet_size).is_err() {
@@ -117,15 +145,19 @@ impl MixPacket {
                 next_hop,
                 sphinx_packet,
                 packet_mode,
+                #[cfg(feature = "packet-tracing")]
+                trace_id,
             })
         }
     }
 
     pub fn into_bytes(self) -> Vec<u8> {
-        std::iter::once(self.packet_mode as u8)
-            .chain(self.next_hop.as_bytes().into_iter())
-            .chain(self.sphinx_packet.to_bytes().into_iter())
-            .collect()
+        let packet = std::iter::once(self.packet_mode as u8).chain(self.next_hop.as_bytes());
+
+        #[cfg(feature = "packet-tracing")]
+        let packet = packet.chain(self.trace_id.to_bytes());
+
+        packet.chain(self.sphinx_packet.to_bytes()).collect()
     }
 }
 
@@ -22,6 +22,9 @@ pub enum SphinxCodecError {
     #[error("the actual sphinx packet was malformed - {0}")]
     MalformedSphinxPacket(#[from] SphinxError),
 
+    #[error("the frame of {size} bytes exceeds the maximum allowed size of {limit} bytes")]
+    FrameTooLarge { size: usize, limit: usize },
+
     #[error("encountered an IO error - {0}")]
     IoError(#[from] io::Error),
 }
@@ -38,6 +41,9 @@ impl From<SphinxCodecError> for io::Error {
             SphinxCodecError::MalformedSphinxPacket(source) => {
                 io::Error::new(io::ErrorKind::InvalidData, source)
             }
+            err @ SphinxCodecError::FrameTooLarge { .. } => {
+                io::Error::new(io::ErrorKind::InvalidData, err)
+            }
             SphinxCodecError::IoError(err) => err,
         }
     }
@@ -125,6 +131,40 @@ impl Decoder for SphinxCodec {
     }
 }
 
+/// Decoder of sphinx frames that rejects any frame larger than the specified limit as soon as its
+/// header is received, rather than buffering the whole thing.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundedSphinxCodec {
+    max_frame_size: usize,
+}
+
+impl BoundedSphinxCodec {
+    pub fn new(max_frame_size: usize) -> Self {
+        BoundedSphinxCodec { max_frame_size }
+    }
+}
+
+impl Decoder for BoundedSphinxCodec {
+    type Item = FramedSphinxPacket;
+    type Error = SphinxCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if !src.is_empty() {
+            if let Some(header) = Header::decode(src)? {
+                let frame_len = header.size() + header.packet_size.size();
+                if frame_len > self.max_frame_size {
+                    return Err(SphinxCodecError::FrameTooLarge {
+                        size: frame_len,
+                        limit: self.max_frame_size,
+                    });
+                }
+            }
+        }
+
+        SphinxCodec.decode(src)
+    }
+}
+
 #[cfg(test)]
 mod packet_encoding {
     use super::*;
@@ -218,6 +258,8 @@ mod packet_encoding {
                     packet_version: PacketVersion::Legacy,
                     packet_size,
                     packet_mode: Default::default(),
+                    #[cfg(feature = "packet-tracing")]
+                    trace_id: Default::default(),
                 };
                 let mut bytes = BytesMut::new();
                 header.encode(&mut bytes);
@@ -242,6 +284,8 @@ mod packet_encoding {
                     packet_version: PacketVersion::Versioned(123),
                     packet_size,
                     packet_mode: Default::default(),
+                    #[cfg(feature = "packet-tracing")]
+                    trace_id: Default::default(),
                 };
                 let mut bytes = BytesMut::new();
                 header.encode(&mut bytes);
@@ -262,6 +306,8 @@ mod packet_encoding {
                     packet_version: PacketVersion::Legacy,
                     packet_size: Default::default(),
                     packet_mode: Default::default(),
+                    #[cfg(feature = "packet-tracing")]
+                    trace_id: Default::default(),
                 },
                 packet: make_valid_sphinx_packet(Default::default()),
             };
@@ -309,6 +355,8 @@ mod packet_encoding {
                         packet_version: PacketVersion::Legacy,
                         packet_size: Default::default(),
                         packet_mode: Default::default(),
+                        #[cfg(feature = "packet-tracing")]
+                        trace_id: Default::default(),
                     },
                     packet: make_valid_sphinx_packet(Default::default()),
                 };
@@ -352,6 +400,28 @@ mod packet_encoding {
         }
     }
 
+    #[test]
+    fn bounded_codec_rejects_oversized_frames() {
+        let packet = FramedSphinxPacket {
+            header: Header::default(),
+            packet: make_valid_sphinx_packet(Default::default()),
+        };
+        let frame_len = packet.header.size() + packet.packet.len();
+
+        let mut bytes = BytesMut::new();
+        SphinxCodec.encode(packet, &mut bytes).unwrap();
+
+        let mut oversized = bytes.clone();
+        assert!(matches!(
+            BoundedSphinxCodec::new(frame_len - 1).decode(&mut oversized),
+            Err(SphinxCodecError::FrameTooLarge { .. })
+        ));
+        assert!(BoundedSphinxCodec::new(frame_len)
+            .decode(&mut bytes)
+            .unwrap()
+            .is_some());
+    }
+
     #[test]
     fn can_decode_two_packets_immediately() {
         let packet1 = FramedSphinxPacket {
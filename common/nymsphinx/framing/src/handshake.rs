@@ -0,0 +1,336 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Handshake exchanged when a connection to a mixnode or a gateway is opened, before any sphinx
+//! frames are sent, so that peers speaking incompatible versions of the wire protocol fail
+//! with a clear error rather than with garbled frames.
+//!
+//! Both messages have the same fixed length:
+//! `MAGIC (4) || PROTOCOL_VERSION (1) || KIND (1) || FLAG (1) || IDENTITY (32)`,
+//! where for the response the flag holds the [`HandshakeStatus`] alongside the identity of the node.
+//! The senders of sphinx packets only know the address of the next hop, so the request doesn't
+//! carry any identity and both of its fields are reserved and zeroed.
+
+use bytes::{Buf, BufMut, BytesMut};
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use thiserror::Error;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Bytes starting every handshake message. The first byte does not correspond to any valid
+/// sphinx framing header so that the connections of the peers not performing the handshake
+/// can be told apart.
+pub const HANDSHAKE_MAGIC: [u8; 4] = *b"NYMH";
+
+/// Version of the wire protocol spoken by this node.
+pub const CURRENT_PROTOCOL_VERSION: u8 = 1;
+
+pub const IDENTITY_KEY_LENGTH: usize = 32;
+
+pub const HANDSHAKE_MESSAGE_SIZE: usize = HANDSHAKE_MAGIC.len() + 3 + IDENTITY_KEY_LENGTH;
+
+const REQUEST_KIND: u8 = 0;
+const RESPONSE_KIND: u8 = 1;
+
+/// Bytes of the ed25519 identity key of a node.
+pub type NodeIdentity = [u8; IDENTITY_KEY_LENGTH];
+
+#[derive(Error, Debug)]
+pub enum HandshakeError {
+    #[error("the peer did not initiate the connection with a handshake")]
+    NotAHandshake,
+
+    #[error("received handshake message of unknown kind {0}")]
+    UnknownMessageKind(u8),
+
+    #[error("received handshake response with unknown status {0}")]
+    UnknownStatus(u8),
+
+    #[error("received unexpected handshake message")]
+    UnexpectedMessage,
+
+    #[error("the peer speaks protocol version {remote} while we speak version {local}")]
+    UnsupportedVersion { local: u8, remote: u8 },
+
+    #[error("the peer (speaking protocol version {remote_version}) has rejected our handshake: {status}")]
+    Rejected {
+        status: HandshakeStatus,
+        remote_version: u8,
+    },
+
+    #[error("the connection got closed before the handshake was completed")]
+    ConnectionClosed,
+
+    #[error("the handshake was not completed in time")]
+    Timeout,
+
+    #[error("encountered an IO error - {0}")]
+    IoError(#[from] io::Error),
+}
+
+impl HandshakeError {
+    /// Status with which a handshake request failing with this error should be answered, if any.
+    pub fn rejection_status(&self) -> Option<HandshakeStatus> {
+        match self {
+            HandshakeError::UnsupportedVersion { .. } => Some(HandshakeStatus::UnsupportedVersion),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HandshakeStatus {
+    Accepted = 0,
+    UnsupportedVersion = 1,
+}
+
+impl Display for HandshakeStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HandshakeStatus::Accepted => write!(f, "accepted"),
+            HandshakeStatus::UnsupportedVersion => write!(f, "unsupported protocol version"),
+        }
+    }
+}
+
+impl TryFrom<u8> for HandshakeStatus {
+    type Error = HandshakeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            _ if value == HandshakeStatus::Accepted as u8 => Ok(HandshakeStatus::Accepted),
+            _ if value == HandshakeStatus::UnsupportedVersion as u8 => {
+                Ok(HandshakeStatus::UnsupportedVersion)
+            }
+            unknown => Err(HandshakeError::UnknownStatus(unknown)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeMessage {
+    /// Sent by the side opening the connection.
+    Request { protocol_version: u8 },
+
+    /// Sent back by the node accepting the connection.
+    Response {
+        protocol_version: u8,
+        status: HandshakeStatus,
+        identity: NodeIdentity,
+    },
+}
+
+impl HandshakeMessage {
+    pub fn new_request() -> Self {
+        HandshakeMessage::Request {
+            protocol_version: CURRENT_PROTOCOL_VERSION,
+        }
+    }
+
+    pub fn new_response(status: HandshakeStatus, identity: NodeIdentity) -> Self {
+        HandshakeMessage::Response {
+            protocol_version: CURRENT_PROTOCOL_VERSION,
+            status,
+            identity,
+        }
+    }
+
+    /// Determines the status of the received handshake request.
+    pub fn evaluate_request(&self) -> Result<(), HandshakeError> {
+        let HandshakeMessage::Request { protocol_version } = self else {
+            return Err(HandshakeError::UnexpectedMessage);
+        };
+
+        if *protocol_version != CURRENT_PROTOCOL_VERSION {
+            return Err(HandshakeError::UnsupportedVersion {
+                local: CURRENT_PROTOCOL_VERSION,
+                remote: *protocol_version,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks whether the received handshake response allows us to proceed with the connection,
+    /// returning the identity of the node on the other end if so.
+    pub fn evaluate_response(&self) -> Result<NodeIdentity, HandshakeError> {
+        let HandshakeMessage::Response {
+            protocol_version,
+            status,
+            identity,
+        } = self
+        else {
+            return Err(HandshakeError::UnexpectedMessage);
+        };
+
+        if *status != HandshakeStatus::Accepted {
+            return Err(HandshakeError::Rejected {
+                status: *status,
+                remote_version: *protocol_version,
+            });
+        }
+        if *protocol_version != CURRENT_PROTOCOL_VERSION {
+            return Err(HandshakeError::UnsupportedVersion {
+                local: CURRENT_PROTOCOL_VERSION,
+                remote: *protocol_version,
+            });
+        }
+        Ok(*identity)
+    }
+}
+
+pub struct HandshakeCodec;
+
+impl Encoder<HandshakeMessage> for HandshakeCodec {
+    type Error = HandshakeError;
+
+    fn encode(&mut self, item: HandshakeMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.reserve(HANDSHAKE_MESSAGE_SIZE);
+        dst.put_slice(&HANDSHAKE_MAGIC);
+        match item {
+            HandshakeMessage::Request { protocol_version } => {
+                dst.put_u8(protocol_version);
+                dst.put_u8(REQUEST_KIND);
+                // reserved
+                dst.put_u8(0);
+                dst.put_slice(&NodeIdentity::default());
+            }
+            HandshakeMessage::Response {
+                protocol_version,
+                status,
+                identity,
+            } => {
+                dst.put_u8(protocol_version);
+                dst.put_u8(RESPONSE_KIND);
+                dst.put_u8(status as u8);
+                dst.put_slice(&identity);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for HandshakeCodec {
+    type Item = HandshakeMessage;
+    type Error = HandshakeError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // check whatever part of the magic we have already received so that the peers
+        // not sending the handshake are detected without waiting for the full message
+        let received_magic = src.len().min(HANDSHAKE_MAGIC.len());
+        if src[..received_magic] != HANDSHAKE_MAGIC[..received_magic] {
+            return Err(HandshakeError::NotAHandshake);
+        }
+
+        if src.len() < HANDSHAKE_MESSAGE_SIZE {
+            src.reserve(HANDSHAKE_MESSAGE_SIZE - src.len());
+            return Ok(None);
+        }
+
+        let protocol_version = src[4];
+        let kind = src[5];
+        let flag = src[6];
+        let mut identity = NodeIdentity::default();
+        identity.copy_from_slice(&src[7..HANDSHAKE_MESSAGE_SIZE]);
+
+        let message = match kind {
+            REQUEST_KIND => HandshakeMessage::Request { protocol_version },
+            RESPONSE_KIND => HandshakeMessage::Response {
+                protocol_version,
+                status: HandshakeStatus::try_from(flag)?,
+                identity,
+            },
+            unknown => return Err(HandshakeError::UnknownMessageKind(unknown)),
+        };
+
+        src.advance(HANDSHAKE_MESSAGE_SIZE);
+        Ok(Some(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::SphinxCodec;
+    use crate::packet::Header;
+
+    fn round_trip(message: HandshakeMessage) -> HandshakeMessage {
+        let mut bytes = BytesMut::new();
+        HandshakeCodec.encode(message, &mut bytes).unwrap();
+        assert_eq!(bytes.len(), HANDSHAKE_MESSAGE_SIZE);
+        let decoded = HandshakeCodec.decode(&mut bytes).unwrap().unwrap();
+        assert!(bytes.is_empty());
+        decoded
+    }
+
+    #[test]
+    fn messages_can_be_decoded_from_valid_encoded_instances() {
+        let request = HandshakeMessage::new_request();
+        assert_eq!(round_trip(request), request);
+
+        let response =
+            HandshakeMessage::new_response(HandshakeStatus::Accepted, [42; IDENTITY_KEY_LENGTH]);
+        assert_eq!(round_trip(response), response);
+    }
+
+    #[test]
+    fn partial_messages_are_buffered() {
+        let mut bytes = BytesMut::new();
+        HandshakeCodec
+            .encode(HandshakeMessage::new_request(), &mut bytes)
+            .unwrap();
+        let rest = bytes.split_off(10);
+
+        assert!(HandshakeCodec.decode(&mut bytes).unwrap().is_none());
+        bytes.unsplit(rest);
+        assert!(HandshakeCodec.decode(&mut bytes).unwrap().is_some());
+    }
+
+    #[test]
+    fn sphinx_frames_are_not_mistaken_for_handshakes() {
+        let mut bytes = BytesMut::new();
+        Header::default().encode(&mut bytes);
+        assert!(matches!(
+            HandshakeCodec.decode(&mut bytes),
+            Err(HandshakeError::NotAHandshake)
+        ));
+
+        // and an outdated node would not be able to make sense of the handshake either
+        let mut bytes = BytesMut::new();
+        HandshakeCodec
+            .encode(HandshakeMessage::new_request(), &mut bytes)
+            .unwrap();
+        assert!(SphinxCodec.decode(&mut bytes).is_err());
+    }
+
+    #[test]
+    fn requests_are_evaluated() {
+        assert!(HandshakeMessage::new_request().evaluate_request().is_ok());
+
+        let outdated = HandshakeMessage::Request {
+            protocol_version: CURRENT_PROTOCOL_VERSION + 1,
+        };
+        assert!(matches!(
+            outdated.evaluate_request(),
+            Err(HandshakeError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn responses_are_evaluated() {
+        let identity = [1; IDENTITY_KEY_LENGTH];
+
+        let accepted = HandshakeMessage::new_response(HandshakeStatus::Accepted, identity);
+        assert_eq!(accepted.evaluate_response().unwrap(), identity);
+
+        let rejected =
+            HandshakeMessage::new_response(HandshakeStatus::UnsupportedVersion, identity);
+        assert!(matches!(
+            rejected.evaluate_response(),
+            Err(HandshakeError::Rejected {
+                status: HandshakeStatus::UnsupportedVersion,
+                ..
+            })
+        ));
+    }
+}
@@ -4,6 +4,8 @@
 use crate::codec::SphinxCodecError;
 use bytes::{BufMut, BytesMut};
 use nym_sphinx_params::packet_sizes::PacketSize;
+#[cfg(feature = "packet-tracing")]
+use nym_sphinx_params::packet_tracing::TraceId;
 use nym_sphinx_params::packet_version::PacketVersion;
 use nym_sphinx_params::PacketMode;
 use nym_sphinx_types::SphinxPacket;
@@ -28,11 +30,24 @@ impl FramedSphinxPacket {
                 packet_version: PacketVersion::new(use_legacy_version),
                 packet_size,
                 packet_mode,
+                #[cfg(feature = "packet-tracing")]
+                trace_id: TraceId::new_random(),
             },
             packet,
         }
     }
 
+    #[cfg(feature = "packet-tracing")]
+    pub fn with_trace_id(mut self, trace_id: TraceId) -> Self {
+        self.header.trace_id = trace_id;
+        self
+    }
+
+    #[cfg(feature = "packet-tracing")]
+    pub fn trace_id(&self) -> TraceId {
+        self.header.trace_id
+    }
+
     pub fn packet_size(&self) -> PacketSize {
         self.header.packet_size
     }
@@ -67,6 +82,11 @@ pub struct Header {
     // Note: currently packet_mode is deprecated but is still left as a concept behind to not break
     // compatibility with existing network
     pub(crate) packet_mode: PacketMode,
+
+    /// Correlation id of the packet used for following it through the network.
+    /// It is put on the wire directly after the rest of the header.
+    #[cfg(feature = "packet-tracing")]
+    pub(crate) trace_id: TraceId,
 }
 
 impl Header {
@@ -74,11 +94,23 @@ impl Header {
     pub(crate) const VERSIONED_SIZE: usize = 3;
 
     pub(crate) fn size(&self) -> usize {
-        if self.packet_version.is_legacy() {
+        let base_size = if self.packet_version.is_legacy() {
             Self::LEGACY_SIZE
         } else {
             Self::VERSIONED_SIZE
-        }
+        };
+
+        base_size + Self::trace_id_size()
+    }
+
+    #[cfg(feature = "packet-tracing")]
+    fn trace_id_size() -> usize {
+        TraceId::SIZE
+    }
+
+    #[cfg(not(feature = "packet-tracing"))]
+    fn trace_id_size() -> usize {
+        0
     }
 
     pub(crate) fn encode(&self, dst: &mut BytesMut) {
@@ -91,6 +123,8 @@ impl Header {
 
         dst.put_u8(self.packet_size as u8);
         dst.put_u8(self.packet_mode as u8);
+        #[cfg(feature = "packet-tracing")]
+        dst.put_slice(&self.trace_id.to_bytes());
         // reserve bytes for the actual packet
         dst.reserve(self.packet_size.size());
     }
@@ -103,23 +137,43 @@ impl Header {
         }
 
         let packet_version = PacketVersion::from(src[0]);
-        if packet_version.is_legacy() {
-            Ok(Some(Header {
+        let header = if packet_version.is_legacy() {
+            Header {
                 packet_version,
                 packet_size: PacketSize::try_from(src[0])?,
                 packet_mode: PacketMode::try_from(src[1])?,
-            }))
+                #[cfg(feature = "packet-tracing")]
+                trace_id: Default::default(),
+            }
         } else if src.len() < Self::VERSIONED_SIZE {
             // we're missing that 1 byte to read the full header...
             src.reserve(Self::VERSIONED_SIZE);
-            Ok(None)
+            return Ok(None);
         } else {
-            Ok(Some(Header {
+            Header {
                 packet_version,
                 packet_size: PacketSize::try_from(src[1])?,
                 packet_mode: PacketMode::try_from(src[2])?,
-            }))
-        }
+                #[cfg(feature = "packet-tracing")]
+                trace_id: Default::default(),
+            }
+        };
+
+        #[cfg(feature = "packet-tracing")]
+        let header = {
+            let mut header = header;
+            let trace_id_offset = header.size() - TraceId::SIZE;
+            match TraceId::try_from_bytes(&src[trace_id_offset..]) {
+                Some(trace_id) => header.trace_id = trace_id,
+                None => {
+                    src.reserve(header.size());
+                    return Ok(None);
+                }
+            }
+            header
+        };
+
+        Ok(Some(header))
     }
 }
 
@@ -192,6 +246,8 @@ mod header_encoding {
                 packet_version: PacketVersion::Legacy,
                 packet_size,
                 packet_mode: Default::default(),
+                #[cfg(feature = "packet-tracing")]
+                trace_id: Default::default(),
             };
             let mut bytes = BytesMut::new();
             header.encode(&mut bytes);
@@ -213,6 +269,8 @@ mod header_encoding {
                 packet_version: PacketVersion::Versioned(123),
                 packet_size,
                 packet_mode: Default::default(),
+                #[cfg(feature = "packet-tracing")]
+                trace_id: Default::default(),
             };
             let mut bytes = BytesMut::new();
             header.encode(&mut bytes);
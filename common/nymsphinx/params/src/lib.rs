@@ -10,10 +10,14 @@ type Aes128Ctr = ctr::Ctr64BE<Aes128>;
 // Re-export for ease of use
 pub use packet_modes::PacketMode;
 pub use packet_sizes::PacketSize;
+pub use padding::PaddingPolicy;
 
 pub mod packet_modes;
 pub mod packet_sizes;
+#[cfg(feature = "packet-tracing")]
+pub mod packet_tracing;
 pub mod packet_version;
+pub mod padding;
 
 // If somebody can provide an argument why it might be reasonable to have more than 255 mix hops,
 // I will change this to [`usize`]
@@ -34,7 +38,7 @@ pub type SerializedFragmentIdentifier = [u8; FRAG_ID_LEN];
 // therefore if we receive byte `7` (or larger than that) we'll know we received a versioned packet,
 // otherwise we should treat it as legacy
 /// Increment it whenever we perform any breaking change in the wire format!
-const CURRENT_PACKET_VERSION_NUMBER: u8 = 7;
+pub const CURRENT_PACKET_VERSION_NUMBER: u8 = 7;
 
 // TODO: ask @AP about the choice of below algorithms
 
@@ -0,0 +1,60 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Correlation identifiers attached to every packet when built with the `packet-tracing` feature,
+//! so that the path of a packet could be followed through the logs of the client, its gateway
+//! and all the mixnodes on the route.
+//!
+//! Note: this completely de-anonymises all traffic and is only meant for debugging
+//! in controlled environments.
+
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(not(debug_assertions))]
+compile_error!(
+    "the `packet-tracing` feature reveals the path of every packet and can only be used in debug builds"
+);
+
+/// Log target used by all packet tracing events.
+pub const PACKET_TRACING_LOG_TARGET: &str = "packet_tracing";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceId(u64);
+
+impl TraceId {
+    pub const SIZE: usize = 8;
+
+    pub fn new_random() -> Self {
+        TraceId(rand::random())
+    }
+
+    pub fn to_bytes(self) -> [u8; Self::SIZE] {
+        self.0.to_be_bytes()
+    }
+
+    /// Attempts to recover the trace id from the beginning of the provided slice.
+    pub fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+        let id_bytes = bytes.get(..Self::SIZE)?;
+        // the unwrap is fine as we have just taken exactly `SIZE` bytes
+        Some(TraceId(u64::from_be_bytes(id_bytes.try_into().unwrap())))
+    }
+}
+
+impl Display for TraceId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace_id_bytes_roundtrip() {
+        let trace_id = TraceId::new_random();
+        let bytes = trace_id.to_bytes();
+        assert_eq!(TraceId::try_from_bytes(&bytes), Some(trace_id));
+        assert!(TraceId::try_from_bytes(&bytes[1..]).is_none());
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Policies deciding to which of the predefined packet sizes the messages get padded.
+//!
+//! Every fragment of a message is padded to the full plaintext of the sphinx packet it's put in,
+//! so the size of the packets is the only thing revealing (an approximation of) the message length.
+//! Using a single size conceals the most, while allowing multiple sizes decreases the overhead.
+
+use crate::PacketSize;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaddingPolicy {
+    /// Messages are padded to the primary packet size unless the secondary one, if specified,
+    /// would result in fewer packets.
+    #[default]
+    PrimaryWithFallback,
+
+    /// Messages are padded to whichever of the packet sizes supported by the network results
+    /// in the fewest bytes put on the wire.
+    LeastOverhead,
+
+    /// Messages are always padded to the largest packet size supported by the network.
+    Largest,
+}
+
+impl PaddingPolicy {
+    /// Chooses the size of the packets for the message, given the number of packets
+    /// it would require for each of the sizes.
+    /// The primary packet size is used if none of the other sizes is supported by the network.
+    pub fn choose_packet_size<F>(
+        &self,
+        primary: PacketSize,
+        secondary: Option<PacketSize>,
+        supported: &[PacketSize],
+        required_packets: F,
+    ) -> PacketSize
+    where
+        F: Fn(PacketSize) -> usize,
+    {
+        // ack packets are far too small to carry any actual data
+        let mut candidates = supported
+            .iter()
+            .copied()
+            .filter(|size| *size != PacketSize::AckPacket);
+
+        match self {
+            PaddingPolicy::PrimaryWithFallback => {
+                let Some(secondary) = secondary else {
+                    return primary;
+                };
+                if !supported.contains(&secondary) {
+                    return primary;
+                }
+                if required_packets(primary) <= required_packets(secondary) {
+                    primary
+                } else {
+                    secondary
+                }
+            }
+            PaddingPolicy::LeastOverhead => candidates
+                .min_by_key(|size| (required_packets(*size) * size.size(), *size))
+                .unwrap_or(primary),
+            PaddingPolicy::Largest => candidates.max().unwrap_or(primary),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_SIZES: [PacketSize; 5] = [
+        PacketSize::RegularPacket,
+        PacketSize::AckPacket,
+        PacketSize::ExtendedPacket8,
+        PacketSize::ExtendedPacket16,
+        PacketSize::ExtendedPacket32,
+    ];
+
+    fn required_packets(message_len: usize) -> impl Fn(PacketSize) -> usize {
+        move |size| (message_len + size.plaintext_size() - 1) / size.plaintext_size()
+    }
+
+    #[test]
+    fn primary_with_fallback_uses_secondary_only_if_beneficial() {
+        let policy = PaddingPolicy::PrimaryWithFallback;
+        let primary = PacketSize::RegularPacket;
+        let secondary = Some(PacketSize::ExtendedPacket32);
+
+        assert_eq!(
+            policy.choose_packet_size(primary, secondary, &ALL_SIZES, required_packets(100)),
+            primary
+        );
+        assert_eq!(
+            policy.choose_packet_size(primary, secondary, &ALL_SIZES, required_packets(100_000)),
+            PacketSize::ExtendedPacket32
+        );
+        assert_eq!(
+            policy.choose_packet_size(primary, None, &ALL_SIZES, required_packets(100_000)),
+            primary
+        );
+
+        // the network doesn't support the secondary size
+        assert_eq!(
+            policy.choose_packet_size(
+                primary,
+                secondary,
+                &[PacketSize::RegularPacket],
+                required_packets(100_000)
+            ),
+            primary
+        );
+    }
+
+    #[test]
+    fn least_overhead_minimises_bytes_on_the_wire() {
+        let policy = PaddingPolicy::LeastOverhead;
+        let primary = PacketSize::RegularPacket;
+
+        assert_eq!(
+            policy.choose_packet_size(primary, None, &ALL_SIZES, required_packets(100)),
+            PacketSize::RegularPacket
+        );
+
+        // slightly more than what fits in the 8kb packet
+        let message_len = PacketSize::ExtendedPacket8.plaintext_size() + 1;
+        assert_eq!(
+            policy.choose_packet_size(primary, None, &ALL_SIZES, required_packets(message_len)),
+            PacketSize::RegularPacket
+        );
+
+        // exactly what fits in the 16kb packet
+        let message_len = PacketSize::ExtendedPacket16.plaintext_size();
+        assert_eq!(
+            policy.choose_packet_size(primary, None, &ALL_SIZES, required_packets(message_len)),
+            PacketSize::ExtendedPacket16
+        );
+    }
+
+    #[test]
+    fn largest_respects_network_support() {
+        let policy = PaddingPolicy::Largest;
+        let primary = PacketSize::RegularPacket;
+
+        assert_eq!(
+            policy.choose_packet_size(primary, None, &ALL_SIZES, required_packets(1)),
+            PacketSize::ExtendedPacket32
+        );
+        assert_eq!(
+            policy.choose_packet_size(
+                primary,
+                None,
+                &[PacketSize::AckPacket, PacketSize::ExtendedPacket8],
+                required_packets(1)
+            ),
+            PacketSize::ExtendedPacket8
+        );
+        assert_eq!(
+            policy.choose_packet_size(primary, None, &[], required_packets(1)),
+            primary
+        );
+    }
+}
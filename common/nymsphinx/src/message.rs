@@ -14,10 +14,21 @@ use nym_sphinx_chunking::fragment::Fragment;
 use nym_sphinx_params::{PacketSize, ReplySurbKeyDigestAlgorithm};
 use rand::Rng;
 use std::fmt::{Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
 pub(crate) const ACK_OVERHEAD: usize = MAX_NODE_ADDRESS_UNPADDED_LEN + PacketSize::AckPacket.size();
 
+/// Size of the serialized [`MessageOrdering`].
+pub const MESSAGE_ORDERING_SIZE: usize = 8;
+
+/// Bit of the message type tag indicating the message is prefixed with its [`MessageOrdering`].
+const MESSAGE_ORDERING_FLAG: u8 = 0x80;
+
+/// Granularity of the timestamps attached to the messages. They are deliberately coarse so that
+/// they'd leak as little as possible about the sender while still allowing for sensible ordering.
+const MESSAGE_TIMESTAMP_GRANULARITY_SECS: u64 = 60;
+
 #[derive(Debug, Error)]
 pub enum NymMessageError {
     #[error("{received} is not a valid type tag for a NymMessage")]
@@ -31,6 +42,78 @@ pub enum NymMessageError {
 
     #[error("Received empty message for deserialization")]
     EmptyMessage,
+
+    #[error("The received message is too short to contain its ordering information")]
+    TruncatedMessageOrdering,
+}
+
+/// Sender-side ordering hints attached to a message, allowing the recipient to present
+/// the messages in the order they were sent in and to detect large gaps in the stream.
+///
+/// The sequence number is authoritative for messages of the same sender, while the coarse
+/// timestamp (which might be skewed between different machines) is only meant to order
+/// messages of independent senders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MessageOrdering {
+    /// Sequence number of the message, local to the sender and the recipient
+    /// (or the sender tag in case of replies). It's allowed to wrap around.
+    pub sequence: u32,
+
+    /// Number of minutes since the unix epoch at the time of sending the message.
+    pub timestamp: u32,
+}
+
+impl MessageOrdering {
+    pub fn new(sequence: u32) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            / MESSAGE_TIMESTAMP_GRANULARITY_SECS;
+
+        MessageOrdering {
+            sequence,
+            timestamp: timestamp as u32,
+        }
+    }
+
+    /// Indicates whether this message was sent after the `other` one, taking the wrapping
+    /// of the sequence numbers into account.
+    pub fn is_after(&self, other: &MessageOrdering) -> bool {
+        let distance = self.sequence.wrapping_sub(other.sequence);
+        distance != 0 && distance <= u32::MAX / 2
+    }
+
+    /// Number of messages that were sent between the `previous` one and this one
+    /// (excluding both of them), taking the wrapping of the sequence numbers into account.
+    /// It's zero if this message directly follows the `previous` one or if it arrived out of order.
+    pub fn sequence_gap(&self, previous: &MessageOrdering) -> u32 {
+        if self.is_after(previous) {
+            self.sequence.wrapping_sub(previous.sequence) - 1
+        } else {
+            // it's either a duplicate or a message that was sent before the `previous` one
+            0
+        }
+    }
+
+    pub fn to_bytes(self) -> [u8; MESSAGE_ORDERING_SIZE] {
+        let mut bytes = [0u8; MESSAGE_ORDERING_SIZE];
+        bytes[..4].copy_from_slice(&self.sequence.to_be_bytes());
+        bytes[4..].copy_from_slice(&self.timestamp.to_be_bytes());
+        bytes
+    }
+
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, NymMessageError> {
+        if bytes.len() < MESSAGE_ORDERING_SIZE {
+            return Err(NymMessageError::TruncatedMessageOrdering);
+        }
+
+        // the unwraps are fine as the slices have exactly 4 bytes
+        Ok(MessageOrdering {
+            sequence: u32::from_be_bytes(bytes[..4].try_into().unwrap()),
+            timestamp: u32::from_be_bytes(bytes[4..MESSAGE_ORDERING_SIZE].try_into().unwrap()),
+        })
+    }
 }
 
 #[repr(u8)]
@@ -139,39 +222,66 @@ impl NymMessage {
 
     // the message is in the format of:
     // typ || msg
+    #[cfg(test)]
     fn into_bytes(self) -> Vec<u8> {
-        let typ = self.typ();
+        self.into_bytes_with_ordering(None)
+    }
 
-        std::iter::once(typ as u8)
-            .chain(self.inner_bytes())
-            .collect()
+    // the message is in the format of:
+    // typ || msg
+    // or, if the ordering is attached (which is indicated by the flag bit of the type tag):
+    // typ | FLAG || ordering || msg
+    fn into_bytes_with_ordering(self, ordering: Option<MessageOrdering>) -> Vec<u8> {
+        let typ = self.typ() as u8;
+
+        match ordering {
+            None => std::iter::once(typ).chain(self.inner_bytes()).collect(),
+            Some(ordering) => std::iter::once(typ | MESSAGE_ORDERING_FLAG)
+                .chain(ordering.to_bytes())
+                .chain(self.inner_bytes())
+                .collect(),
+        }
     }
 
-    fn try_from_bytes(bytes: &[u8], num_mix_hops: u8) -> Result<Self, NymMessageError> {
+    fn try_from_bytes(
+        bytes: &[u8],
+        num_mix_hops: u8,
+    ) -> Result<(Self, Option<MessageOrdering>), NymMessageError> {
         if bytes.is_empty() {
             return Err(NymMessageError::EmptyMessage);
         }
 
-        let typ_tag = NymMessageType::try_from(bytes[0])?;
-        match typ_tag {
-            NymMessageType::Plain => Ok(NymMessage::Plain(bytes[1..].to_vec())),
-            NymMessageType::Repliable => Ok(NymMessage::Repliable(
-                RepliableMessage::try_from_bytes(&bytes[1..], num_mix_hops)?,
-            )),
-            NymMessageType::Reply => Ok(NymMessage::Reply(ReplyMessage::try_from_bytes(
-                &bytes[1..],
-            )?)),
-        }
+        let (ordering, content) = if bytes[0] & MESSAGE_ORDERING_FLAG != 0 {
+            let ordering = MessageOrdering::try_from_bytes(&bytes[1..])?;
+            (Some(ordering), &bytes[1 + MESSAGE_ORDERING_SIZE..])
+        } else {
+            (None, &bytes[1..])
+        };
+
+        let typ_tag = NymMessageType::try_from(bytes[0] & !MESSAGE_ORDERING_FLAG)?;
+        let message = match typ_tag {
+            NymMessageType::Plain => NymMessage::Plain(content.to_vec()),
+            NymMessageType::Repliable => {
+                NymMessage::Repliable(RepliableMessage::try_from_bytes(content, num_mix_hops)?)
+            }
+            NymMessageType::Reply => NymMessage::Reply(ReplyMessage::try_from_bytes(content)?),
+        };
+        Ok((message, ordering))
     }
 
-    fn serialized_size(&self, num_mix_hops: u8) -> usize {
+    fn serialized_size(&self, num_mix_hops: u8, with_ordering: bool) -> usize {
         let inner_size = match self {
             NymMessage::Plain(msg) => msg.len(),
             NymMessage::Repliable(msg) => msg.serialized_size(num_mix_hops),
             NymMessage::Reply(msg) => msg.serialized_size(),
         };
         let message_type_size = 1;
-        message_type_size + inner_size
+        let ordering_size = if with_ordering {
+            MESSAGE_ORDERING_SIZE
+        } else {
+            0
+        };
+        message_type_size + ordering_size + inner_size
     }
 
     /// Length of plaintext (from the **sphinx** point of view) data that is available per sphinx
@@ -198,9 +308,15 @@ impl NymMessage {
     }
 
     /// Determines the number of required packets of the provided size for the split message.
-    pub fn required_packets(&self, packet_size: PacketSize, num_mix_hops: u8) -> usize {
+    /// `with_ordering` indicates whether the message is going to have its [`MessageOrdering`] attached.
+    pub fn required_packets(
+        &self,
+        packet_size: PacketSize,
+        num_mix_hops: u8,
+        with_ordering: bool,
+    ) -> usize {
         let plaintext_per_packet = self.true_available_plaintext_per_packet(packet_size);
-        let serialized_len = self.serialized_size(num_mix_hops);
+        let serialized_len = self.serialized_size(num_mix_hops, with_ordering);
 
         let (num_fragments, _) =
             chunking::number_of_required_fragments(serialized_len, plaintext_per_packet);
@@ -217,9 +333,20 @@ impl NymMessage {
     /// Pads the message so that after it gets chunked, it will occupy exactly N sphinx packets.
     /// Produces new_message = message || 1 || 0000....
     pub fn pad_to_full_packet_lengths(self, plaintext_per_packet: usize) -> PaddedMessage {
+        self.pad_to_full_packet_lengths_with_ordering(plaintext_per_packet, None)
+    }
+
+    /// Same as [`NymMessage::pad_to_full_packet_lengths`], but additionally attaches the provided
+    /// [`MessageOrdering`] to the message. Note that it's placed before the padding, so it's
+    /// encrypted alongside the rest of the message and doesn't affect the size of the packets.
+    pub fn pad_to_full_packet_lengths_with_ordering(
+        self,
+        plaintext_per_packet: usize,
+        ordering: Option<MessageOrdering>,
+    ) -> PaddedMessage {
         let self_display = self.to_string();
 
-        let bytes = self.into_bytes();
+        let bytes = self.into_bytes_with_ordering(ordering);
 
         // 1 (chunking::MIN_PADDING_OVERHEAD) is added as there will always have to be at least a single byte of padding (1) added
         // to be able to later distinguish the actual padding from the underlying message
@@ -264,6 +391,15 @@ impl PaddedMessage {
 
     // reverse of NymMessage::pad_to_full_packet_lengths
     pub fn remove_padding(self, num_mix_hops: u8) -> Result<NymMessage, NymMessageError> {
+        self.remove_padding_with_ordering(num_mix_hops)
+            .map(|(message, _)| message)
+    }
+
+    // reverse of NymMessage::pad_to_full_packet_lengths_with_ordering
+    pub fn remove_padding_with_ordering(
+        self,
+        num_mix_hops: u8,
+    ) -> Result<(NymMessage, Option<MessageOrdering>), NymMessageError> {
         // we are looking for first occurrence of 1 in the tail and we get its index
         if let Some(padding_end) = self.0.iter().rposition(|b| *b == 1) {
             // and now we only take bytes until that point (but not including it)
@@ -288,7 +424,7 @@ mod tests {
     fn serialized_size_matches_actual_serialization() {
         // plain
         let plain = NymMessage::new_plain(vec![1, 2, 3, 4, 5]);
-        assert_eq!(plain.serialized_size(3), plain.into_bytes().len());
+        assert_eq!(plain.serialized_size(3, false), plain.into_bytes().len());
 
         // a single variant for each repliable and reply is enough as they are more thoroughly tested
         // internally
@@ -297,9 +433,61 @@ mod tests {
             [42u8; 16].into(),
             vec![],
         ));
-        assert_eq!(repliable.serialized_size(3), repliable.into_bytes().len());
+        assert_eq!(
+            repliable.serialized_size(3, false),
+            repliable.into_bytes().len()
+        );
 
         let reply = NymMessage::new_reply(ReplyMessage::new_data_message(vec![1, 2, 3, 4, 5]));
-        assert_eq!(reply.serialized_size(3), reply.into_bytes().len());
+        assert_eq!(reply.serialized_size(3, false), reply.into_bytes().len());
+
+        // with the ordering attached
+        let ordering = Some(MessageOrdering::new(42));
+        let plain = NymMessage::new_plain(vec![1, 2, 3, 4, 5]);
+        assert_eq!(
+            plain.serialized_size(3, true),
+            plain.into_bytes_with_ordering(ordering).len()
+        );
+    }
+
+    #[test]
+    fn ordering_survives_padding() {
+        let ordering = MessageOrdering {
+            sequence: 123,
+            timestamp: 456,
+        };
+
+        let padded = NymMessage::new_plain(vec![1, 2, 3, 4, 5])
+            .pad_to_full_packet_lengths_with_ordering(1000, Some(ordering));
+        assert_eq!(padded.0.len(), 1000);
+
+        let (message, recovered) = padded.remove_padding_with_ordering(3).unwrap();
+        assert_eq!(recovered, Some(ordering));
+        assert_eq!(message.into_inner_data(), vec![1, 2, 3, 4, 5]);
+
+        // and messages without the ordering are still understood
+        let padded = NymMessage::new_reply(ReplyMessage::new_data_message(vec![1, 2, 3]))
+            .pad_to_full_packet_lengths(1000);
+        let (message, recovered) = padded.remove_padding_with_ordering(3).unwrap();
+        assert!(recovered.is_none());
+        assert_eq!(message.into_inner_data(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn sequence_gaps_account_for_wrapping() {
+        let ordering = |sequence| MessageOrdering {
+            sequence,
+            timestamp: 0,
+        };
+
+        assert_eq!(ordering(11).sequence_gap(&ordering(10)), 0);
+        assert_eq!(ordering(20).sequence_gap(&ordering(10)), 9);
+        assert_eq!(ordering(1).sequence_gap(&ordering(u32::MAX)), 1);
+
+        // duplicates and out of order messages
+        assert_eq!(ordering(10).sequence_gap(&ordering(10)), 0);
+        assert_eq!(ordering(9).sequence_gap(&ordering(10)), 0);
+        assert!(!ordering(9).is_after(&ordering(10)));
+        assert!(ordering(0).is_after(&ordering(u32::MAX)));
     }
 }
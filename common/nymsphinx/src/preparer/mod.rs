@@ -1,25 +1,28 @@
 // Copyright 2021-2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::message::{NymMessage, ACK_OVERHEAD};
+use crate::message::{MessageOrdering, NymMessage, ACK_OVERHEAD};
 use crate::NymsphinxPayloadBuilder;
 use nym_crypto::asymmetric::encryption;
 use nym_crypto::Digest;
 use nym_sphinx_acknowledgements::surb_ack::SurbAck;
 use nym_sphinx_acknowledgements::AckKey;
-use nym_sphinx_addressing::clients::Recipient;
+use nym_sphinx_addressing::clients::{Recipient, RecipientBytes};
 use nym_sphinx_addressing::nodes::NymNodeRoutingAddress;
 use nym_sphinx_anonymous_replies::reply_surb::ReplySurb;
+use nym_sphinx_anonymous_replies::requests::AnonymousSenderTag;
 use nym_sphinx_chunking::fragment::{Fragment, FragmentIdentifier};
 use nym_sphinx_forwarding::packet::MixPacket;
 use nym_sphinx_params::packet_sizes::PacketSize;
 use nym_sphinx_params::{ReplySurbKeyDigestAlgorithm, DEFAULT_NUM_MIX_HOPS};
 use nym_sphinx_types::builder::SphinxPacketBuilder;
-use nym_sphinx_types::{delays, Delay};
+use nym_sphinx_types::{delays, Delay, SphinxConstraints};
 use nym_topology::{NymTopology, NymTopologyError};
 use rand::{CryptoRng, Rng};
+use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
 pub(crate) mod payload;
 
@@ -38,6 +41,54 @@ pub struct PreparedFragment {
     pub fragment_identifier: FragmentIdentifier,
 }
 
+/// Identifies the party the sequence numbers of the sent messages are scoped to.
+/// Each of them observes its own, independent, sequence, so that the numbers could not be used
+/// for linking messages sent to different recipients or under different sender tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SequenceScope {
+    /// Messages sent directly to the recipient with the specified address.
+    Recipient(RecipientBytes),
+
+    /// Replies sent with the reply SURBs received under the specified sender tag.
+    Reply(AnonymousSenderTag),
+
+    /// Repliable messages we send under the specified sender tag of our own,
+    /// used whenever the same recipient is contacted under multiple, unlinkable, tags.
+    SenderTag(AnonymousSenderTag),
+}
+
+impl From<Recipient> for SequenceScope {
+    fn from(recipient: Recipient) -> Self {
+        SequenceScope::Recipient(recipient.to_bytes())
+    }
+}
+
+impl From<AnonymousSenderTag> for SequenceScope {
+    fn from(sender_tag: AnonymousSenderTag) -> Self {
+        SequenceScope::Reply(sender_tag)
+    }
+}
+
+/// Source of the sequence numbers of the sent messages. It's shared between all clones
+/// of the [`MessagePreparer`], so that the numbers would never repeat within given scope.
+#[derive(Clone, Default)]
+struct MessageSequence {
+    next: Arc<Mutex<HashMap<SequenceScope, u32>>>,
+}
+
+impl MessageSequence {
+    fn next_ordering(&self, scope: SequenceScope) -> MessageOrdering {
+        let mut next = self
+            .next
+            .lock()
+            .expect("message sequence lock got poisoned");
+        let sequence = next.entry(scope).or_default();
+        let ordering = MessageOrdering::new(*sequence);
+        *sequence = sequence.wrapping_add(1);
+        ordering
+    }
+}
+
 /// Prepares the message that is to be sent through the mix network by attaching
 /// an optional reply-SURB, padding it to appropriate length, encrypting its content,
 /// and chunking into appropriate size [`Fragment`]s.
@@ -60,6 +111,9 @@ pub struct MessagePreparer<R> {
     /// Number of mix hops each packet ('real' message, ack, reply) is expected to take.
     /// Note that it does not include gateway hops.
     num_mix_hops: u8,
+
+    /// If set, each sent message is going to have its [`MessageOrdering`] attached.
+    message_sequence: Option<MessageSequence>,
 }
 
 impl<R> MessagePreparer<R>
@@ -78,9 +132,18 @@ where
             average_packet_delay,
             average_ack_delay,
             num_mix_hops: DEFAULT_NUM_MIX_HOPS,
+            message_sequence: None,
         }
     }
 
+    /// Makes each prepared message carry its sequence number and a coarse timestamp so that
+    /// the recipient could present the messages in order. Note that the recipients running
+    /// older versions of the software won't be able to understand such messages.
+    pub fn with_message_ordering(mut self, enabled: bool) -> Self {
+        self.message_sequence = enabled.then(MessageSequence::default);
+        self
+    }
+
     /// Allows setting non-default number of expected mix hops in the network.
     pub fn with_mix_hops(mut self, hops: u8) -> Self {
         self.num_mix_hops = hops;
@@ -190,6 +253,19 @@ where
         topology: &NymTopology,
         ack_key: &AckKey,
         packet_recipient: &Recipient,
+    ) -> Result<PreparedFragment, NymTopologyError> {
+        self.prepare_expiring_chunk_for_sending(fragment, topology, ack_key, packet_recipient, None)
+    }
+
+    /// Same as [`MessagePreparer::prepare_chunk_for_sending`], but additionally informs the
+    /// recipient's gateway about the time after which the fragment should no longer be delivered.
+    pub fn prepare_expiring_chunk_for_sending(
+        &mut self,
+        fragment: Fragment,
+        topology: &NymTopology,
+        ack_key: &AckKey,
+        packet_recipient: &Recipient,
+        expires_at: Option<SystemTime>,
     ) -> Result<PreparedFragment, NymTopologyError> {
         // each plain or repliable packet (i.e. not a reply) attaches an ephemeral public key so that the recipient
         // could perform diffie-hellman with its own keys followed by a kdf to re-derive
@@ -217,11 +293,18 @@ where
             self.num_mix_hops,
             packet_recipient.gateway(),
         )?;
-        let destination = packet_recipient.as_sphinx_destination();
+        let destination = packet_recipient.as_expiring_sphinx_destination(expires_at);
 
         // including set of delays
         let delays = delays::generate_from_average_duration(route.len(), self.average_packet_delay);
 
+        SphinxConstraints::default().validate_packet(
+            &route,
+            &delays,
+            packet_payload.len(),
+            packet_size.payload_size(),
+        )?;
+
         // create the actual sphinx packet here. With valid route and correct payload size,
         // there's absolutely no reason for this call to fail.
         let sphinx_packet = SphinxPacketBuilder::new()
@@ -260,15 +343,22 @@ where
         )
     }
 
+    /// Pads the message and splits it into [`Fragment`]s. If message ordering is enabled,
+    /// the attached sequence number is the next one within the provided scope.
     pub fn pad_and_split_message(
         &mut self,
         message: NymMessage,
         packet_size: PacketSize,
+        scope: SequenceScope,
     ) -> Vec<Fragment> {
         let plaintext_per_packet = message.available_sphinx_plaintext_per_packet(packet_size);
+        let ordering = self
+            .message_sequence
+            .as_ref()
+            .map(|sequence| sequence.next_ordering(scope));
 
         message
-            .pad_to_full_packet_lengths(plaintext_per_packet)
+            .pad_to_full_packet_lengths_with_ordering(plaintext_per_packet, ordering)
             .split_into_fragments(&mut self.rng, plaintext_per_packet)
     }
 }
@@ -281,3 +371,22 @@ where
    4. deal with fragment as before
    5. on full message reconstruction output (message, Option<reply_surb>)
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_sequences_are_independent_per_scope() {
+        let sequence = MessageSequence::default();
+        let first = SequenceScope::Reply(AnonymousSenderTag::from_bytes([1; 16]));
+        let second = SequenceScope::Reply(AnonymousSenderTag::from_bytes([2; 16]));
+
+        assert_eq!(sequence.next_ordering(first).sequence, 0);
+        assert_eq!(sequence.next_ordering(first).sequence, 1);
+        assert_eq!(sequence.next_ordering(second).sequence, 0);
+
+        // the clones keep using the same counters
+        assert_eq!(sequence.clone().next_ordering(first).sequence, 2);
+    }
+}
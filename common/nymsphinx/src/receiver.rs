@@ -1,7 +1,7 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::message::{NymMessage, NymMessageError, PaddedMessage, PlainMessage};
+use crate::message::{MessageOrdering, NymMessage, NymMessageError, PaddedMessage, PlainMessage};
 use nym_crypto::aes::cipher::{KeyIvInit, StreamCipher};
 use nym_crypto::asymmetric::encryption;
 use nym_crypto::shared_key::recompute_shared_key;
@@ -29,6 +29,9 @@ pub struct ReconstructedMessage {
     /// Optional ephemeral sender tag indicating pseudo-identity of the party who sent us the message
     /// (alongside any reply SURBs)
     pub sender_tag: Option<AnonymousSenderTag>,
+
+    /// Optional ordering hints attached by the sender of the message.
+    pub ordering: Option<MessageOrdering>,
 }
 
 impl From<ReconstructedMessage> for (Vec<u8>, Option<AnonymousSenderTag>) {
@@ -42,9 +45,16 @@ impl ReconstructedMessage {
         Self {
             message,
             sender_tag: Some(sender_tag),
+            ordering: None,
         }
     }
 
+    #[must_use]
+    pub fn with_ordering(mut self, ordering: Option<MessageOrdering>) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
     pub fn into_inner(self) -> (Vec<u8>, Option<AnonymousSenderTag>) {
         self.into()
     }
@@ -55,10 +65,23 @@ impl From<PlainMessage> for ReconstructedMessage {
         ReconstructedMessage {
             message,
             sender_tag: None,
+            ordering: None,
         }
     }
 }
 
+/// Sorts the messages according to the ordering hints attached by their senders.
+/// Messages sent within the same minute are ordered by their sequence numbers, which makes
+/// the ordering tolerant to small clock skews. Messages without any ordering information
+/// are placed first, preserving their relative order.
+pub fn sort_by_ordering(messages: &mut [ReconstructedMessage]) {
+    messages.sort_by_key(|message| {
+        message
+            .ordering
+            .map(|ordering| (ordering.timestamp, ordering.sequence))
+    })
+}
+
 #[derive(Debug, Error)]
 pub enum MessageRecoveryError {
     #[error("The received message did not contain enough bytes to recover the ephemeral public key. Got {provided}. required: {required}")]
@@ -186,10 +209,12 @@ pub trait MessageReceiver {
     fn insert_new_fragment(
         &mut self,
         fragment: Fragment,
-    ) -> Result<Option<(NymMessage, Vec<i32>)>, MessageRecoveryError> {
+    ) -> Result<Option<(NymMessage, Option<MessageOrdering>, Vec<i32>)>, MessageRecoveryError> {
         if let Some((message, used_sets)) = self.reconstructor().insert_new_fragment(fragment) {
-            match PaddedMessage::new_reconstructed(message).remove_padding(self.num_mix_hops()) {
-                Ok(message) => Ok(Some((message, used_sets))),
+            match PaddedMessage::new_reconstructed(message)
+                .remove_padding_with_ordering(self.num_mix_hops())
+            {
+                Ok((message, ordering)) => Ok(Some((message, ordering, used_sets))),
                 Err(err) => Err(MessageRecoveryError::MalformedReconstructedMessage {
                     source: err,
                     used_sets,
@@ -289,6 +314,7 @@ mod message_receiver {
                 .unwrap(),
                 layer: Layer::One,
                 version: "0.8.0-dev".to_string(),
+                family: None,
             }],
         );
 
@@ -309,6 +335,7 @@ mod message_receiver {
                 .unwrap(),
                 layer: Layer::Two,
                 version: "0.8.0-dev".to_string(),
+                family: None,
             }],
         );
 
@@ -329,6 +356,7 @@ mod message_receiver {
                 .unwrap(),
                 layer: Layer::Three,
                 version: "0.8.0-dev".to_string(),
+                family: None,
             }],
         );
 
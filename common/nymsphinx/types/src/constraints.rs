@@ -0,0 +1,292 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Validation of the packet parameters against the constraints imposed by the sphinx packet format.
+//!
+//! Violating any of them makes the packet construction fail (or even panic) without telling much
+//! about what went wrong, so the parameters should be checked before building the packet.
+
+use sphinx_packet::constants::MAX_PATH_LENGTH;
+use sphinx_packet::header::delays::Delay;
+use sphinx_packet::payload::PAYLOAD_OVERHEAD_SIZE;
+use sphinx_packet::route::Node;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum SphinxConstraintError {
+    #[error("the route does not contain any nodes")]
+    EmptyRoute,
+
+    #[error("the route consists of {length} hops while at most {maximum} are supported")]
+    RouteTooLong { length: usize, maximum: usize },
+
+    #[error("got {delays} delays for a route consisting of {hops} hops")]
+    DelaysMismatch { hops: usize, delays: usize },
+
+    #[error("delay of {delay:?} at hop {hop} exceeds the maximum of {maximum:?}")]
+    DelayTooLong {
+        hop: usize,
+        delay: Duration,
+        maximum: Duration,
+    },
+
+    #[error("payload of {payload_size} bytes is not larger than the sphinx payload overhead of {PAYLOAD_OVERHEAD_SIZE} bytes")]
+    PayloadTooSmall { payload_size: usize },
+
+    #[error("message of {message_len} bytes does not fit in a payload of {payload_size} bytes (at most {available} bytes are available)")]
+    MessageTooLong {
+        message_len: usize,
+        payload_size: usize,
+        available: usize,
+    },
+}
+
+/// Constraints each constructed sphinx packet has to satisfy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SphinxConstraints {
+    /// Maximum number of hops, including the final one, in the route of the packet.
+    maximum_route_length: usize,
+
+    /// Optional upper bound on the delay at any single hop of the route.
+    maximum_hop_delay: Option<Duration>,
+}
+
+impl Default for SphinxConstraints {
+    fn default() -> Self {
+        SphinxConstraints {
+            maximum_route_length: MAX_PATH_LENGTH,
+            maximum_hop_delay: None,
+        }
+    }
+}
+
+impl SphinxConstraints {
+    /// Allows restricting the route length further than what the sphinx header supports.
+    #[must_use]
+    pub fn with_maximum_route_length(mut self, maximum_route_length: usize) -> Self {
+        self.maximum_route_length = maximum_route_length.min(MAX_PATH_LENGTH);
+        self
+    }
+
+    /// Allows rejecting packets that would get delayed for too long at any of the hops.
+    #[must_use]
+    pub fn with_maximum_hop_delay(mut self, maximum_hop_delay: Duration) -> Self {
+        self.maximum_hop_delay = Some(maximum_hop_delay);
+        self
+    }
+
+    /// Maximum length of the message that can be put in the payload of the specified size.
+    pub fn available_plaintext(&self, payload_size: usize) -> usize {
+        payload_size.saturating_sub(PAYLOAD_OVERHEAD_SIZE)
+    }
+
+    /// Checks whether the route and its associated delays can be encoded in the sphinx header.
+    pub fn validate_route(
+        &self,
+        route: &[Node],
+        delays: &[Delay],
+    ) -> Result<(), SphinxConstraintError> {
+        if route.is_empty() {
+            return Err(SphinxConstraintError::EmptyRoute);
+        }
+        if route.len() > self.maximum_route_length {
+            return Err(SphinxConstraintError::RouteTooLong {
+                length: route.len(),
+                maximum: self.maximum_route_length,
+            });
+        }
+        if route.len() != delays.len() {
+            return Err(SphinxConstraintError::DelaysMismatch {
+                hops: route.len(),
+                delays: delays.len(),
+            });
+        }
+
+        if let Some(maximum) = self.maximum_hop_delay {
+            for (hop, delay) in delays.iter().enumerate() {
+                let delay = Duration::from_nanos(delay.to_nanos());
+                if delay > maximum {
+                    return Err(SphinxConstraintError::DelayTooLong {
+                        hop,
+                        delay,
+                        maximum,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether the message of the specified length fits in the payload of the specified size.
+    pub fn validate_payload(
+        &self,
+        message_len: usize,
+        payload_size: usize,
+    ) -> Result<(), SphinxConstraintError> {
+        if payload_size <= PAYLOAD_OVERHEAD_SIZE {
+            return Err(SphinxConstraintError::PayloadTooSmall { payload_size });
+        }
+
+        let available = self.available_plaintext(payload_size);
+        if message_len > available {
+            return Err(SphinxConstraintError::MessageTooLong {
+                message_len,
+                payload_size,
+                available,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks all parameters of the packet that is about to be constructed.
+    pub fn validate_packet(
+        &self,
+        route: &[Node],
+        delays: &[Delay],
+        message_len: usize,
+        payload_size: usize,
+    ) -> Result<(), SphinxConstraintError> {
+        self.validate_route(route, delays)?;
+        self.validate_payload(message_len, payload_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sphinx_packet::constants::{DESTINATION_ADDRESS_LENGTH, IDENTIFIER_LENGTH};
+    use sphinx_packet::crypto;
+    use sphinx_packet::packet::builder::SphinxPacketBuilder;
+    use sphinx_packet::route::{Destination, DestinationAddressBytes, NodeAddressBytes};
+    use std::panic::{self, AssertUnwindSafe};
+
+    const PAYLOAD_SIZE: usize = 1024;
+
+    fn route(length: usize) -> Vec<Node> {
+        (0..length)
+            .map(|i| {
+                let (_, pub_key) = crypto::keygen();
+                Node::new(NodeAddressBytes::from_bytes([i as u8; 32]), pub_key)
+            })
+            .collect()
+    }
+
+    fn delays(length: usize) -> Vec<Delay> {
+        (0..length).map(|_| Delay::new_from_nanos(42)).collect()
+    }
+
+    #[test]
+    fn route_length_is_bounded() {
+        let constraints = SphinxConstraints::default();
+        assert_eq!(
+            constraints.validate_route(&[], &[]),
+            Err(SphinxConstraintError::EmptyRoute)
+        );
+        assert!(constraints
+            .validate_route(&route(MAX_PATH_LENGTH), &delays(MAX_PATH_LENGTH))
+            .is_ok());
+        assert_eq!(
+            constraints.validate_route(&route(MAX_PATH_LENGTH + 1), &delays(MAX_PATH_LENGTH + 1)),
+            Err(SphinxConstraintError::RouteTooLong {
+                length: MAX_PATH_LENGTH + 1,
+                maximum: MAX_PATH_LENGTH
+            })
+        );
+
+        // the length can't be increased past what sphinx supports
+        let constraints = SphinxConstraints::default().with_maximum_route_length(100);
+        assert!(constraints
+            .validate_route(&route(MAX_PATH_LENGTH + 1), &delays(MAX_PATH_LENGTH + 1))
+            .is_err());
+    }
+
+    #[test]
+    fn each_hop_requires_a_delay() {
+        assert_eq!(
+            SphinxConstraints::default().validate_route(&route(3), &delays(2)),
+            Err(SphinxConstraintError::DelaysMismatch { hops: 3, delays: 2 })
+        );
+    }
+
+    #[test]
+    fn delays_are_bounded_if_requested() {
+        let mut delays = delays(3);
+        delays[1] = Delay::new_from_nanos(Duration::from_secs(10).as_nanos() as u64);
+
+        assert!(SphinxConstraints::default()
+            .validate_route(&route(3), &delays)
+            .is_ok());
+        assert_eq!(
+            SphinxConstraints::default()
+                .with_maximum_hop_delay(Duration::from_secs(1))
+                .validate_route(&route(3), &delays),
+            Err(SphinxConstraintError::DelayTooLong {
+                hop: 1,
+                delay: Duration::from_secs(10),
+                maximum: Duration::from_secs(1)
+            })
+        );
+    }
+
+    #[test]
+    fn message_has_to_fit_in_the_payload() {
+        let constraints = SphinxConstraints::default();
+        let available = PAYLOAD_SIZE - PAYLOAD_OVERHEAD_SIZE;
+        assert!(constraints
+            .validate_payload(available, PAYLOAD_SIZE)
+            .is_ok());
+        assert_eq!(
+            constraints.validate_payload(available + 1, PAYLOAD_SIZE),
+            Err(SphinxConstraintError::MessageTooLong {
+                message_len: available + 1,
+                payload_size: PAYLOAD_SIZE,
+                available
+            })
+        );
+        assert_eq!(
+            constraints.validate_payload(0, PAYLOAD_OVERHEAD_SIZE),
+            Err(SphinxConstraintError::PayloadTooSmall {
+                payload_size: PAYLOAD_OVERHEAD_SIZE
+            })
+        );
+    }
+
+    // makes sure whatever passes the validation can actually be turned into a packet
+    // and whatever doesn't, can't
+    #[test]
+    fn validation_is_consistent_with_packet_construction() {
+        let constraints = SphinxConstraints::default();
+        let destination = Destination::new(
+            DestinationAddressBytes::from_bytes([42u8; DESTINATION_ADDRESS_LENGTH]),
+            [0u8; IDENTIFIER_LENGTH],
+        );
+        let available = constraints.available_plaintext(PAYLOAD_SIZE);
+
+        for route_length in 0..=MAX_PATH_LENGTH + 2 {
+            for message_len in [1, available / 2, available - 1, available + 64] {
+                let route = route(route_length);
+                let delays = delays(route_length);
+                let message = vec![42u8; message_len];
+
+                let valid = constraints
+                    .validate_packet(&route, &delays, message_len, PAYLOAD_SIZE)
+                    .is_ok();
+                let constructed = panic::catch_unwind(AssertUnwindSafe(|| {
+                    SphinxPacketBuilder::new()
+                        .with_payload_size(PAYLOAD_SIZE)
+                        .build_packet(message, &route, &destination, &delays)
+                        .is_ok()
+                }))
+                .unwrap_or(false);
+
+                assert_eq!(
+                    valid, constructed,
+                    "route of {route_length} hops with message of {message_len} bytes"
+                );
+            }
+        }
+    }
+}
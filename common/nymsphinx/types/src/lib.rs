@@ -15,3 +15,7 @@ pub use sphinx_packet::{
     surb::{SURBMaterial, SURB},
     Error, ProcessedPacket, Result, SphinxPacket,
 };
+
+pub mod constraints;
+
+pub use constraints::{SphinxConstraintError, SphinxConstraints};
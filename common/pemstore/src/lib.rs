@@ -44,7 +44,18 @@ pub fn load_key<T>(path: &Path) -> io::Result<T>
 where
     T: PemStorableKey,
 {
-    let key_pem = read_pem_file(path)?;
+    let mut pem_bytes = File::open(path)?;
+    let mut buf = Vec::new();
+    pem_bytes.read_to_end(&mut buf)?;
+    decode_key(&buf)
+}
+
+/// Recovers the key from its PEM representation.
+pub fn decode_key<T>(raw: &[u8]) -> io::Result<T>
+where
+    T: PemStorableKey,
+{
+    let key_pem = pem::parse(raw).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
     if T::pem_type() != key_pem.tag {
         return Err(io::Error::new(
@@ -65,26 +76,26 @@ pub fn store_key<T>(key: &T, path: &Path) -> io::Result<()>
 where
     T: PemStorableKey,
 {
-    write_pem_file(path, key.to_bytes(), T::pem_type())
+    write_pem_file(path, encode_key(key))
 }
 
-fn read_pem_file(filepath: &Path) -> io::Result<Pem> {
-    let mut pem_bytes = File::open(filepath)?;
-    let mut buf = Vec::new();
-    pem_bytes.read_to_end(&mut buf)?;
-    pem::parse(&buf).map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+/// Produces the PEM representation of the key.
+pub fn encode_key<T>(key: &T) -> String
+where
+    T: PemStorableKey,
+{
+    let pem = Pem {
+        tag: T::pem_type().to_string(),
+        contents: key.to_bytes(),
+    };
+    pem::encode(&pem)
 }
 
-fn write_pem_file(filepath: &Path, data: Vec<u8>, tag: &str) -> io::Result<()> {
+fn write_pem_file(filepath: &Path, key: String) -> io::Result<()> {
     // ensure the whole directory structure exists
     if let Some(parent_dir) = filepath.parent() {
         std::fs::create_dir_all(parent_dir)?;
     }
-    let pem = Pem {
-        tag: tag.to_string(),
-        contents: data,
-    };
-    let key = pem::encode(&pem);
 
     let mut file = File::create(filepath)?;
     file.write_all(key.as_bytes())?;
@@ -0,0 +1,171 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use rand::Rng;
+use std::time::Duration;
+
+/// Randomisation applied to the backoff delays so that multiple parties failing at the same time
+/// wouldn't all retry in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Jitter {
+    /// Plain exponential backoff.
+    #[default]
+    None,
+
+    /// Delay chosen uniformly between zero and the exponential backoff.
+    Full,
+
+    /// Delay chosen uniformly between the initial backoff and three times the previous delay,
+    /// as described in <https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/>.
+    Decorrelated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    /// Delay before the first retry.
+    pub initial: Duration,
+
+    /// Upper bound on any delay.
+    pub maximum: Duration,
+
+    pub jitter: Jitter,
+}
+
+impl ExponentialBackoff {
+    pub fn new(initial: Duration, maximum: Duration) -> Self {
+        ExponentialBackoff {
+            initial,
+            maximum,
+            jitter: Jitter::None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Exponential delay, without any jitter, for the specified attempt, i.e. `initial * 2^attempt`
+    /// capped at `maximum`.
+    pub fn base_delay(&self, attempt: u32) -> Duration {
+        let backoff = 2_u32
+            .checked_pow(attempt)
+            .and_then(|exp| self.initial.checked_mul(exp))
+            .unwrap_or(self.maximum);
+
+        std::cmp::min(backoff, self.maximum)
+    }
+
+    /// Delay for the specified attempt with the jitter applied. Decorrelated jitter depends on
+    /// the previously used delay rather than on the attempt number.
+    pub fn delay<R: Rng + ?Sized>(
+        &self,
+        attempt: u32,
+        previous: Option<Duration>,
+        rng: &mut R,
+    ) -> Duration {
+        match self.jitter {
+            Jitter::None => self.base_delay(attempt),
+            Jitter::Full => {
+                let base = self.base_delay(attempt);
+                rng.gen_range(Duration::ZERO..=base)
+            }
+            Jitter::Decorrelated => {
+                let lower = std::cmp::min(self.initial, self.maximum);
+                let previous = previous.unwrap_or(lower).max(lower);
+                let upper = previous.saturating_mul(3).min(self.maximum);
+                rng.gen_range(lower..=upper)
+            }
+        }
+    }
+}
+
+/// Keeps track of the consecutive failures in order to determine subsequent delays.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    config: ExponentialBackoff,
+    attempt: u32,
+    previous: Option<Duration>,
+}
+
+impl Backoff {
+    pub fn new(config: ExponentialBackoff) -> Self {
+        Backoff {
+            config,
+            attempt: 0,
+            previous: None,
+        }
+    }
+
+    /// Number of delays handed out since the creation or the last reset.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    pub fn next_delay<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Duration {
+        let delay = self.config.delay(self.attempt, self.previous, rng);
+        self.attempt = self.attempt.saturating_add(1);
+        self.previous = Some(delay);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+        self.previous = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+    use rand::thread_rng;
+
+    fn config() -> ExponentialBackoff {
+        ExponentialBackoff::new(Duration::from_millis(100), Duration::from_secs(10))
+    }
+
+    #[test]
+    fn base_delay_is_capped_regardless_of_attempt() {
+        let backoff = config();
+        assert_eq!(backoff.base_delay(0), Duration::from_millis(100));
+        assert_eq!(backoff.base_delay(3), Duration::from_millis(800));
+        for attempt in [16, 32, 1024, 65536, u32::MAX] {
+            assert_eq!(backoff.base_delay(attempt), backoff.maximum);
+        }
+    }
+
+    #[test]
+    fn jittered_delays_stay_within_bounds() {
+        let mut rng = thread_rng();
+
+        let full = config().with_jitter(Jitter::Full);
+        for attempt in 0..20 {
+            assert!(full.delay(attempt, None, &mut rng) <= full.base_delay(attempt));
+        }
+
+        let mut backoff = Backoff::new(config().with_jitter(Jitter::Decorrelated));
+        let mut previous = Duration::from_millis(100);
+        for _ in 0..100 {
+            let delay = backoff.next_delay(&mut rng);
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= previous * 3);
+            assert!(delay <= Duration::from_secs(10));
+            previous = delay;
+        }
+    }
+
+    #[test]
+    fn reset_restarts_the_sequence() {
+        let mut rng = StepRng::new(0, 0);
+        let mut backoff = Backoff::new(config());
+        backoff.next_delay(&mut rng);
+        backoff.next_delay(&mut rng);
+        assert_eq!(backoff.next_delay(&mut rng), Duration::from_millis(400));
+
+        backoff.reset();
+        assert_eq!(backoff.attempt(), 0);
+        assert_eq!(backoff.next_delay(&mut rng), Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Retrying of fallible async operations with exponential backoff.
+//!
+//! The operation is re-attempted for as long as its error is classified as retryable and neither
+//! the maximum number of attempts nor the maximum elapsed time have been exhausted.
+
+use log::debug;
+use std::future::Future;
+use std::time::Duration;
+
+pub use backoff::{Backoff, ExponentialBackoff, Jitter};
+
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_timer::Instant;
+
+pub mod backoff;
+
+/// Classification of errors into the ones worth retrying and the fatal ones.
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub backoff: ExponentialBackoff,
+
+    /// Maximum number of attempts, including the initial one.
+    pub max_attempts: Option<u32>,
+
+    /// Maximum time since the initial attempt after which no more attempts are going to be made.
+    pub max_elapsed_time: Option<Duration>,
+}
+
+impl RetryPolicy {
+    pub fn new(backoff: ExponentialBackoff) -> Self {
+        RetryPolicy {
+            backoff,
+            max_attempts: None,
+            max_elapsed_time: None,
+        }
+    }
+
+    /// Policy making only a single attempt.
+    pub fn no_retries() -> Self {
+        RetryPolicy::new(ExponentialBackoff::new(Duration::ZERO, Duration::ZERO))
+            .with_max_attempts(1)
+    }
+
+    #[must_use]
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_elapsed_time(mut self, max_elapsed_time: Duration) -> Self {
+        self.max_elapsed_time = Some(max_elapsed_time);
+        self
+    }
+
+    /// Determines whether another attempt should be made after `delay`, given the number of
+    /// attempts made so far and the time elapsed since the first one.
+    fn allows_retry(&self, attempts: u32, elapsed: Duration, delay: Duration) -> bool {
+        if matches!(self.max_attempts, Some(max_attempts) if attempts >= max_attempts) {
+            return false;
+        }
+        !matches!(self.max_elapsed_time, Some(budget) if elapsed + delay > budget)
+    }
+}
+
+async fn sleep(delay: Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(delay).await;
+
+    #[cfg(target_arch = "wasm32")]
+    if let Err(err) = wasm_timer::Delay::new(delay).await {
+        log::error!("the timer has gone away while in retry backoff! - {err}");
+    }
+}
+
+/// Attempts the operation until it succeeds, fails with a non-retryable error or the policy
+/// gets exhausted, in which case the last error is returned.
+pub async fn retry<T, E, F, Fut>(policy: &RetryPolicy, operation: F) -> Result<T, E>
+where
+    E: Retryable,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_if(policy, Retryable::is_retryable, operation).await
+}
+
+/// Same as [`retry`], but with the errors classified by the provided closure.
+pub async fn retry_if<T, E, F, Fut, C>(
+    policy: &RetryPolicy,
+    is_retryable: C,
+    mut operation: F,
+) -> Result<T, E>
+where
+    C: Fn(&E) -> bool,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let start = Instant::now();
+    let mut backoff = Backoff::new(policy.backoff);
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        let err = match operation().await {
+            Ok(res) => return Ok(res),
+            Err(err) => err,
+        };
+
+        if !is_retryable(&err) {
+            return Err(err);
+        }
+
+        let delay = backoff.next_delay(&mut rand::thread_rng());
+        if !policy.allows_retry(attempts, start.elapsed(), delay) {
+            debug!("giving up after {attempts} attempts");
+            return Err(err);
+        }
+
+        debug!("attempt {attempts} has failed - retrying in {delay:?}");
+        sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum TestError {
+        Transient,
+        Fatal,
+    }
+
+    impl Retryable for TestError {
+        fn is_retryable(&self) -> bool {
+            matches!(self, TestError::Transient)
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new(ExponentialBackoff::new(
+            Duration::from_millis(100),
+            Duration::from_secs(1),
+        ))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_until_success() {
+        let attempts = Cell::new(0);
+        let res = retry(&policy().with_max_attempts(5), || async {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                Err(TestError::Transient)
+            } else {
+                Ok(attempts.get())
+            }
+        })
+        .await;
+
+        assert_eq!(res, Ok(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fatal_errors_are_not_retried() {
+        let attempts = Cell::new(0);
+        let res: Result<(), _> = retry(&policy(), || async {
+            attempts.set(attempts.get() + 1);
+            Err(TestError::Fatal)
+        })
+        .await;
+
+        assert_eq!(res, Err(TestError::Fatal));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn policy_limits_are_respected() {
+        let attempts = Cell::new(0);
+        let res: Result<(), _> = retry(&policy().with_max_attempts(4), || async {
+            attempts.set(attempts.get() + 1);
+            Err(TestError::Transient)
+        })
+        .await;
+        assert_eq!(res, Err(TestError::Transient));
+        assert_eq!(attempts.get(), 4);
+
+        // delays of 100ms, 200ms and 400ms fit within the budget, but the subsequent 800ms doesn't
+        attempts.set(0);
+        let res: Result<(), _> = retry(
+            &policy().with_max_elapsed_time(Duration::from_secs(1)),
+            || async {
+                attempts.set(attempts.get() + 1);
+                Err(TestError::Transient)
+            },
+        )
+        .await;
+        assert_eq!(res, Err(TestError::Transient));
+        assert_eq!(attempts.get(), 4);
+    }
+}
@@ -6,14 +6,18 @@ pub use nym_client_core::config::Config as BaseConfig;
 pub use nym_client_core::config::MISSING_VALUE;
 use nym_client_core::config::{ClientCoreConfigTrait, DebugConfig};
 use nym_config::defaults::DEFAULT_SOCKS5_LISTENING_PORT;
+use nym_config::paths::default_nym_directory;
 use nym_config::{NymConfig, OptionalSet};
 use nym_service_providers_common::interface::ProviderInterfaceVersion;
 use nym_socks5_requests::Socks5ProtocolVersion;
 use nym_sphinx::addressing::clients::Recipient;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
 
 pub mod old_config_v1_1_13;
 mod template;
@@ -21,6 +25,15 @@ mod template;
 const DEFAULT_CONNECTION_START_SURBS: u32 = 20;
 const DEFAULT_PER_REQUEST_SURBS: u32 = 3;
 
+const DEFAULT_HTTP_LISTENING_PORT: u16 = 1081;
+
+const DEFAULT_DNS_LISTENING_PORT: u16 = 1053;
+const DEFAULT_DOH_URL: &str = "https://cloudflare-dns.com/dns-query";
+const DEFAULT_DNS_QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_DNS_CACHE_CAPACITY: usize = 1000;
+const DEFAULT_DNS_MAX_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_DNS_NEGATIVE_CACHE_TTL: Duration = Duration::from_secs(60);
+
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
@@ -37,15 +50,17 @@ impl NymConfig for Config {
 
     fn default_root_directory() -> PathBuf {
         #[cfg(not(target_os = "android"))]
-        let base_dir = dirs::home_dir().expect("Failed to evaluate $HOME value");
+        {
+            default_nym_directory("socks5-clients").expect("no data directory known for this OS")
+        }
         #[cfg(target_os = "android")]
-        let base_dir = PathBuf::from("/tmp");
-
-        base_dir.join(".nym").join("socks5-clients")
+        {
+            PathBuf::from("/tmp").join(".nym").join("socks5-clients")
+        }
     }
 
     fn try_default_root_directory() -> Option<PathBuf> {
-        dirs::home_dir().map(|path| path.join(".nym").join("socks5-clients"))
+        default_nym_directory("socks5-clients")
     }
 
     fn root_directory(&self) -> PathBuf {
@@ -126,6 +141,26 @@ impl Config {
         self
     }
 
+    pub fn with_http_proxy(mut self, enabled: bool) -> Self {
+        self.socks5.with_http_proxy(enabled);
+        self
+    }
+
+    pub fn with_http_port(mut self, port: u16) -> Self {
+        self.socks5.with_http_port(port);
+        self
+    }
+
+    pub fn with_dns_resolver(mut self, enabled: bool) -> Self {
+        self.socks5.with_dns_resolver(enabled);
+        self
+    }
+
+    pub fn with_provider_pool(mut self, addresses: Vec<Recipient>) -> Self {
+        self.socks5.with_provider_pool(addresses);
+        self
+    }
+
     // helper methods to use `OptionalSet` trait. Those are defined due to very... ehm. 'specific' structure of this config
     // (plz, lets refactor it)
     pub fn with_optional_ext<F, T>(mut self, f: F, val: Option<T>) -> Self
@@ -171,6 +206,16 @@ pub struct Socks5 {
     /// The mix address of the provider to which all requests are going to be sent.
     provider_mix_address: String,
 
+    /// Specifies whether all connections are sent to the provider at `provider_mix_address`
+    /// or whether each new connection picks one of the healthy providers out of that one
+    /// and the ones in `provider_pool`.
+    #[serde(default)]
+    provider_selection: ProviderSelection,
+
+    /// The mix addresses of additional providers used in the `pool` provider selection mode.
+    #[serde(default)]
+    provider_pool: Vec<String>,
+
     /// The version of the 'service provider' this client is going to use in its communication with the
     /// specified socks5 provider.
     // if in doubt, use the legacy version as initially nobody will be using the updated binaries
@@ -188,6 +233,20 @@ pub struct Socks5 {
     #[serde(default)]
     send_anonymously: bool,
 
+    /// Specifies whether the anonymous connections of different local applications should be isolated
+    /// from each other, so that the service provider would be unable to tell they belong to the same client.
+    /// Requires `send_anonymously` to be enabled.
+    #[serde(default)]
+    stream_isolation: StreamIsolation,
+
+    /// Configuration of the HTTP CONNECT proxy running alongside the socks5 one.
+    #[serde(default)]
+    http: Socks5Http,
+
+    /// Configuration of the local DNS resolver that tunnels all queries over the mixnet.
+    #[serde(default)]
+    dns: Socks5Dns,
+
     #[serde(default)]
     socks5_debug: Socks5Debug,
 }
@@ -197,9 +256,14 @@ impl Socks5 {
         Socks5 {
             listening_port: DEFAULT_SOCKS5_LISTENING_PORT,
             provider_mix_address: provider_mix_address.into(),
+            provider_selection: Default::default(),
+            provider_pool: Vec::new(),
             provider_interface_version: ProviderInterfaceVersion::Legacy,
             socks5_protocol_version: Socks5ProtocolVersion::Legacy,
             send_anonymously: false,
+            stream_isolation: Default::default(),
+            http: Default::default(),
+            dns: Default::default(),
             socks5_debug: Default::default(),
         }
     }
@@ -212,6 +276,11 @@ impl Socks5 {
         self.provider_mix_address = address;
     }
 
+    pub fn with_provider_pool(&mut self, addresses: Vec<Recipient>) {
+        self.provider_selection = ProviderSelection::Pool;
+        self.provider_pool = addresses.iter().map(ToString::to_string).collect();
+    }
+
     pub fn with_provider_interface_version(&mut self, version: ProviderInterfaceVersion) {
         self.provider_interface_version = version;
     }
@@ -224,11 +293,46 @@ impl Socks5 {
         self.send_anonymously = anonymous_replies;
     }
 
+    pub fn with_stream_isolation(&mut self, stream_isolation: StreamIsolation) {
+        self.stream_isolation = stream_isolation;
+    }
+
+    pub fn with_http_proxy(&mut self, enabled: bool) {
+        self.http.enabled = enabled;
+    }
+
+    pub fn with_http_port(&mut self, port: u16) {
+        self.http.listening_port = port;
+    }
+
+    pub fn with_dns_resolver(&mut self, enabled: bool) {
+        self.dns.enabled = enabled;
+    }
+
     pub fn get_provider_mix_address(&self) -> Recipient {
         Recipient::try_from_base58_string(&self.provider_mix_address)
             .expect("malformed provider address")
     }
 
+    pub fn get_provider_selection(&self) -> ProviderSelection {
+        self.provider_selection
+    }
+
+    /// All the providers the connections can be sent to, given the provider selection mode.
+    pub fn get_providers(&self) -> Vec<Recipient> {
+        let mut providers = vec![self.get_provider_mix_address()];
+        if self.provider_selection == ProviderSelection::Pool {
+            for address in &self.provider_pool {
+                let provider =
+                    Recipient::try_from_base58_string(address).expect("malformed provider address");
+                if !providers.contains(&provider) {
+                    providers.push(provider)
+                }
+            }
+        }
+        providers
+    }
+
     pub fn get_provider_interface_version(&self) -> ProviderInterfaceVersion {
         self.provider_interface_version
     }
@@ -241,10 +345,52 @@ impl Socks5 {
         self.send_anonymously
     }
 
+    pub fn get_stream_isolation(&self) -> StreamIsolation {
+        self.stream_isolation
+    }
+
     pub fn get_listening_port(&self) -> u16 {
         self.listening_port
     }
 
+    pub fn get_http_proxy_enabled(&self) -> bool {
+        self.http.enabled
+    }
+
+    pub fn get_http_listening_address(&self) -> SocketAddr {
+        // similarly to the socks5 listener, we only ever want to listen locally
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), self.http.listening_port)
+    }
+
+    pub fn get_dns_resolver_enabled(&self) -> bool {
+        self.dns.enabled
+    }
+
+    pub fn get_dns_listening_address(&self) -> SocketAddr {
+        // similarly to the socks5 listener, we only ever want to listen locally
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), self.dns.listening_port)
+    }
+
+    pub fn get_doh_url(&self) -> Url {
+        self.dns.doh_url.clone()
+    }
+
+    pub fn get_dns_query_timeout(&self) -> Duration {
+        self.dns.query_timeout
+    }
+
+    pub fn get_dns_cache_capacity(&self) -> usize {
+        self.dns.cache_capacity
+    }
+
+    pub fn get_dns_max_cache_ttl(&self) -> Duration {
+        self.dns.max_cache_ttl
+    }
+
+    pub fn get_dns_negative_cache_ttl(&self) -> Duration {
+        self.dns.negative_cache_ttl
+    }
+
     pub fn get_connection_start_surbs(&self) -> u32 {
         self.socks5_debug.connection_start_surbs
     }
@@ -259,14 +405,107 @@ impl Default for Socks5 {
         Socks5 {
             listening_port: DEFAULT_SOCKS5_LISTENING_PORT,
             provider_mix_address: "".into(),
+            provider_selection: Default::default(),
+            provider_pool: Vec::new(),
             provider_interface_version: ProviderInterfaceVersion::Legacy,
             socks5_protocol_version: Socks5ProtocolVersion::Legacy,
             send_anonymously: false,
+            stream_isolation: Default::default(),
+            http: Default::default(),
+            dns: Default::default(),
             socks5_debug: Default::default(),
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderSelection {
+    /// All connections are sent to the single provider.
+    #[default]
+    Single,
+
+    /// Each connection is sent to one of the healthy providers out of the pool.
+    Pool,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamIsolation {
+    /// All anonymous connections share the same sender tag and thus the same pool of reply SURBs.
+    #[default]
+    Disabled,
+
+    /// Each local application, identified by the username it uses for the socks5 authentication,
+    /// gets its own sender tag and pool of reply SURBs. Connections without a username
+    /// (including the HTTP CONNECT ones) are each isolated on their own.
+    PerApplication,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Socks5Http {
+    /// Specifies whether the client should also accept HTTP CONNECT proxy requests,
+    /// for applications that do not support socks5.
+    enabled: bool,
+
+    /// The port on which the client will be listening for HTTP CONNECT requests.
+    listening_port: u16,
+}
+
+impl Default for Socks5Http {
+    fn default() -> Self {
+        Socks5Http {
+            enabled: false,
+            listening_port: DEFAULT_HTTP_LISTENING_PORT,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Socks5Dns {
+    /// Specifies whether the client should run a local DNS resolver which, rather than using the
+    /// system resolver, sends all queries as DNS-over-HTTPS requests tunneled over the mixnet.
+    enabled: bool,
+
+    /// The port on which the resolver will be listening for DNS queries.
+    listening_port: u16,
+
+    /// Address of the DNS-over-HTTPS server. Note that the network requester has to allow it.
+    doh_url: Url,
+
+    /// Maximum amount of time to wait for the DNS-over-HTTPS request to complete.
+    #[serde(with = "humantime_serde")]
+    query_timeout: Duration,
+
+    /// Maximum number of responses kept in the cache.
+    cache_capacity: usize,
+
+    /// Maximum amount of time for which a positive response is cached, regardless of its TTL.
+    #[serde(with = "humantime_serde")]
+    max_cache_ttl: Duration,
+
+    /// Maximum amount of time for which a negative response, i.e. non-existent domain or
+    /// a lack of records of the requested type, is cached.
+    #[serde(with = "humantime_serde")]
+    negative_cache_ttl: Duration,
+}
+
+impl Default for Socks5Dns {
+    fn default() -> Self {
+        Socks5Dns {
+            enabled: false,
+            listening_port: DEFAULT_DNS_LISTENING_PORT,
+            doh_url: DEFAULT_DOH_URL.parse().expect("invalid default DoH url"),
+            query_timeout: DEFAULT_DNS_QUERY_TIMEOUT,
+            cache_capacity: DEFAULT_DNS_CACHE_CAPACITY,
+            max_cache_ttl: DEFAULT_DNS_MAX_CACHE_TTL,
+            negative_cache_ttl: DEFAULT_DNS_NEGATIVE_CACHE_TTL,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Socks5Debug {
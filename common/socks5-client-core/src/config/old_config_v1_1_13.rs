@@ -3,6 +3,7 @@
 
 use crate::config::{Config, Socks5};
 use nym_client_core::config::old_config_v1_1_13::OldConfigV1_1_13 as OldBaseConfigV1_1_13;
+use nym_config::paths::default_nym_directory;
 use nym_config::NymConfig;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -24,15 +25,17 @@ impl NymConfig for OldConfigV1_1_13 {
 
     fn default_root_directory() -> PathBuf {
         #[cfg(not(target_os = "android"))]
-        let base_dir = dirs::home_dir().expect("Failed to evaluate $HOME value");
+        {
+            default_nym_directory("socks5-clients").expect("no data directory known for this OS")
+        }
         #[cfg(target_os = "android")]
-        let base_dir = PathBuf::from("/tmp");
-
-        base_dir.join(".nym").join("socks5-clients")
+        {
+            PathBuf::from("/tmp").join(".nym").join("socks5-clients")
+        }
     }
 
     fn try_default_root_directory() -> Option<PathBuf> {
-        dirs::home_dir().map(|path| path.join(".nym").join("socks5-clients"))
+        default_nym_directory("socks5-clients")
     }
 
     fn root_directory(&self) -> PathBuf {
@@ -37,6 +37,13 @@ nym_api_urls = [
     {{/each}}
 ]
 
+# Identity keys of the mixnodes and gateways that should never be used by this client.
+ignored_nodes = [
+    {{#each client.ignored_nodes }}
+        '{{this}}',
+    {{/each}}
+]
+
 # Path to file containing private identity key.
 private_identity_key_file = '{{ client.private_identity_key_file }}'
 
@@ -88,6 +95,18 @@ gateway_listener = '{{ client.gateway_endpoint.gateway_listener }}'
 # The mix address of the provider to which all requests are going to be sent.
 provider_mix_address = '{{ socks5.provider_mix_address }}'
 
+# Specifies whether all connections are sent to the above provider ('single')
+# or whether each new connection picks one of the healthy providers out of that one
+# and the ones in `provider_pool` ('pool').
+provider_selection = '{{ socks5.provider_selection }}'
+
+# The mix addresses of additional providers used in the 'pool' provider selection mode.
+provider_pool = [
+    {{#each socks5.provider_pool }}
+        '{{this}}',
+    {{/each}}
+]
+
 # The port on which the client will be listening for incoming requests
 listening_port = {{ socks5.listening_port }}
 
@@ -98,12 +117,69 @@ listening_port = {{ socks5.listening_port }}
 # Note that some service providers might not support this.
 send_anonymously = {{ socks5.send_anonymously }}
 
+# Specifies whether the anonymous connections of different local applications should be isolated
+# from each other ('per_application'), so that the service provider would be unable to tell they belong
+# to the same client, or not ('disabled'). Applications are told apart by the socks5 username they use.
+# Requires `send_anonymously` to be enabled.
+stream_isolation = '{{ socks5.stream_isolation }}'
+
+[socks5.http]
+
+# Specifies whether the client should also accept HTTP CONNECT proxy requests,
+# for applications that do not support socks5.
+enabled = {{ socks5.http.enabled }}
+
+# The port on which the client will be listening for HTTP CONNECT requests.
+listening_port = {{ socks5.http.listening_port }}
+
+[socks5.dns]
+
+# Specifies whether the client should run a local DNS resolver which, rather than using the
+# system resolver, sends all queries as DNS-over-HTTPS requests tunneled over the mixnet.
+enabled = {{ socks5.dns.enabled }}
+
+# The port on which the resolver will be listening for DNS queries.
+listening_port = {{ socks5.dns.listening_port }}
+
+# Address of the DNS-over-HTTPS server. Note that the network requester has to allow it.
+doh_url = '{{ socks5.dns.doh_url }}'
+
 ##### logging configuration options #####
 
 [logging]
 
 # TODO
 
+##### proxy configuration options #####
+# Proxies through which the HTTP(S) requests of the particular classes of endpoints are routed.
+# The nym API can be reached through either HTTP(S) or SOCKS5 proxies (use the 'socks5h' scheme
+# for the '.onion' endpoints), while nyxd can only be reached through HTTP(S) ones.
+
+[proxy]
+{{#if proxy.directory }}
+# proxy used for retrieving the network topology and the list of available gateways
+directory = '{{ proxy.directory }}'
+{{/if}}
+{{#if proxy.nym_api }}
+# proxy used for all the other requests sent to the nym API
+nym_api = '{{ proxy.nym_api }}'
+{{/if}}
+{{#if proxy.nyxd }}
+# proxy used for communicating with the nyxd validators
+nyxd = '{{ proxy.nyxd }}'
+{{/if}}
+
+##### storage encryption options #####
+# The bandwidth credentials, reply surbs, reply keys and the recipients of the used sender tags
+# can be encrypted with a key derived from a passphrase. The passphrase can be either provided
+# on startup ('prompt', also read from the NYM_CLIENT_STORAGE_PASSPHRASE environment variable),
+# kept in the keychain of the operating system ('keychain') or supplied through
+# the control API of the client ('control_api').
+
+[storage_encryption]
+enabled = {{ storage_encryption.enabled }}
+passphrase_source = '{{ storage_encryption.passphrase_source }}'
+
 
 ##### debug configuration options #####
 # The following options should not be modified unless you know EXACTLY what you are doing
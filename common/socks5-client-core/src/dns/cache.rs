@@ -0,0 +1,183 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dns::message::{summarise_response, Question, RCODE_NAME_ERROR, RCODE_NO_ERROR};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+struct CacheEntry {
+    response: Vec<u8>,
+    expires_at: Instant,
+}
+
+/// Cache of both positive and negative DNS responses, keyed by the question they answer.
+///
+/// Note that the cached responses are returned as they were received, i.e. the TTLs of the
+/// contained records are not adjusted by the time they spent in the cache.
+pub(crate) struct DnsCache {
+    capacity: usize,
+    max_ttl: Duration,
+    negative_ttl: Duration,
+    entries: HashMap<Question, CacheEntry>,
+}
+
+impl DnsCache {
+    pub(crate) fn new(capacity: usize, max_ttl: Duration, negative_ttl: Duration) -> Self {
+        DnsCache {
+            capacity,
+            max_ttl,
+            negative_ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Determines for how long the response can be cached, if at all.
+    fn cache_duration(&self, response: &[u8]) -> Option<Duration> {
+        let summary = summarise_response(response).ok()?;
+        let duration = match (summary.rcode, summary.answer_ttl) {
+            (RCODE_NO_ERROR, Some(ttl)) => Some(Duration::from_secs(ttl as u64).min(self.max_ttl)),
+            // either the domain doesn't exist or it has no records of the requested type
+            (RCODE_NO_ERROR, None) | (RCODE_NAME_ERROR, _) => Some(
+                summary
+                    .negative_ttl
+                    .map(|ttl| Duration::from_secs(ttl as u64))
+                    .unwrap_or(self.negative_ttl)
+                    .min(self.negative_ttl),
+            ),
+            // server failures, refusals, etc. are not worth remembering
+            _ => None,
+        };
+        duration.filter(|duration| !duration.is_zero())
+    }
+
+    pub(crate) fn get(&mut self, question: &Question) -> Option<Vec<u8>> {
+        self.get_at(question, Instant::now())
+    }
+
+    fn get_at(&mut self, question: &Question, now: Instant) -> Option<Vec<u8>> {
+        match self.entries.get(question) {
+            Some(entry) if entry.expires_at > now => Some(entry.response.clone()),
+            Some(_) => {
+                self.entries.remove(question);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, question: Question, response: Vec<u8>) {
+        self.insert_at(question, response, Instant::now())
+    }
+
+    fn insert_at(&mut self, question: Question, response: Vec<u8>, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        let Some(duration) = self.cache_duration(&response) else {
+            return;
+        };
+
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&question) {
+            self.entries.retain(|_, entry| entry.expires_at > now);
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&question) {
+            // still full - get rid of whatever was going to expire the soonest
+            let soonest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires_at)
+                .map(|(question, _)| question.clone());
+            if let Some(soonest) = soonest {
+                self.entries.remove(&soonest);
+            }
+        }
+
+        self.entries.insert(
+            question,
+            CacheEntry {
+                response,
+                expires_at: now + duration,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns::message::parse_question;
+    use crate::dns::message::tests::{answer, nxdomain};
+
+    fn question(response: &[u8]) -> Question {
+        parse_question(response).unwrap().0
+    }
+
+    #[test]
+    fn positive_responses_expire_after_their_ttl() {
+        let mut cache = DnsCache::new(10, Duration::from_secs(3600), Duration::from_secs(60));
+        let now = Instant::now();
+
+        let response = answer(1, "nymtech.net", &[30, 120]);
+        let question = question(&response);
+        cache.insert_at(question.clone(), response.clone(), now);
+
+        assert_eq!(
+            cache.get_at(&question, now + Duration::from_secs(29)),
+            Some(response)
+        );
+        assert_eq!(cache.get_at(&question, now + Duration::from_secs(30)), None);
+    }
+
+    #[test]
+    fn ttls_are_capped() {
+        let mut cache = DnsCache::new(10, Duration::from_secs(100), Duration::from_secs(10));
+        let now = Instant::now();
+
+        let response = answer(1, "nymtech.net", &[3600]);
+        let positive = question(&response);
+        cache.insert_at(positive.clone(), response, now);
+
+        let response = nxdomain(1, "foo.example", 900, 300);
+        let negative = question(&response);
+        cache.insert_at(negative.clone(), response, now);
+
+        let later = now + Duration::from_secs(10);
+        assert!(cache.get_at(&positive, later).is_some());
+        assert!(cache.get_at(&negative, later).is_none());
+        assert!(cache
+            .get_at(&positive, now + Duration::from_secs(100))
+            .is_none());
+    }
+
+    #[test]
+    fn uncacheable_responses_are_ignored() {
+        let mut cache = DnsCache::new(10, Duration::from_secs(100), Duration::from_secs(10));
+        let mut response = answer(1, "nymtech.net", &[60]);
+        // SERVFAIL
+        response[3] = 2;
+        let question = question(&response);
+        cache.insert(question.clone(), response);
+        assert!(cache.get(&question).is_none());
+
+        let response = answer(1, "nymtech.net", &[0]);
+        cache.insert(question.clone(), response);
+        assert!(cache.get(&question).is_none());
+    }
+
+    #[test]
+    fn entry_expiring_the_soonest_is_evicted_when_full() {
+        let mut cache = DnsCache::new(2, Duration::from_secs(3600), Duration::from_secs(60));
+        let now = Instant::now();
+
+        let first = answer(1, "first.net", &[300]);
+        let second = answer(1, "second.net", &[30]);
+        let third = answer(1, "third.net", &[300]);
+        cache.insert_at(question(&first), first.clone(), now);
+        cache.insert_at(question(&second), second.clone(), now);
+        cache.insert_at(question(&third), third.clone(), now);
+
+        assert!(cache.get_at(&question(&first), now).is_some());
+        assert!(cache.get_at(&question(&second), now).is_none());
+        assert!(cache.get_at(&question(&third), now).is_some());
+    }
+}
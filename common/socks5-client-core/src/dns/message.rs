@@ -0,0 +1,298 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Minimal parsing of DNS messages (RFC 1035). It is only concerned with what is needed for
+//! keying the cache and determining for how long the received responses can be cached.
+//! Everything else is treated as opaque bytes and forwarded as is.
+
+use thiserror::Error;
+
+pub(crate) const HEADER_LEN: usize = 12;
+
+const FLAG_RESPONSE: u8 = 0x80;
+const FLAG_RECURSION_AVAILABLE: u8 = 0x80;
+const RCODE_MASK: u8 = 0x0f;
+
+pub(crate) const RCODE_NO_ERROR: u8 = 0;
+pub(crate) const RCODE_SERVER_FAILURE: u8 = 2;
+pub(crate) const RCODE_NAME_ERROR: u8 = 3;
+
+const TYPE_SOA: u16 = 6;
+
+// protects against malicious messages with compression pointer loops
+const MAX_COMPRESSION_POINTERS: usize = 16;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DnsMessageError {
+    #[error("the DNS message got truncated")]
+    Truncated,
+
+    #[error("the DNS message contains a malformed domain name")]
+    MalformedName,
+
+    #[error("the DNS message contains {0} questions while exactly one was expected")]
+    UnsupportedQuestionCount(u16),
+}
+
+/// The question section of a DNS query, used for keying the cache.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Question {
+    /// Queried domain name, normalised to lowercase.
+    pub(crate) name: String,
+    pub(crate) qtype: u16,
+    pub(crate) qclass: u16,
+}
+
+/// Information extracted from a DNS response relevant for caching it.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ResponseSummary {
+    pub(crate) rcode: u8,
+
+    /// The lowest TTL of all records in the answer section, if there are any.
+    pub(crate) answer_ttl: Option<u32>,
+
+    /// TTL for caching the negative response as specified by the SOA record
+    /// in the authority section (RFC 2308), if present.
+    pub(crate) negative_ttl: Option<u32>,
+}
+
+fn read_u16(msg: &[u8], offset: usize) -> Result<u16, DnsMessageError> {
+    msg.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or(DnsMessageError::Truncated)
+}
+
+fn read_u32(msg: &[u8], offset: usize) -> Result<u32, DnsMessageError> {
+    msg.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(DnsMessageError::Truncated)
+}
+
+/// Reads the (possibly compressed) domain name starting at the specified offset.
+/// Returns the name alongside the offset immediately following it.
+fn read_name(msg: &[u8], offset: usize) -> Result<(String, usize), DnsMessageError> {
+    let mut labels = Vec::new();
+    let mut position = offset;
+    // offset right after the name in its original position, i.e. before following any pointers
+    let mut end = None;
+    let mut pointers = 0;
+
+    loop {
+        let len = *msg.get(position).ok_or(DnsMessageError::Truncated)?;
+        match len & 0xc0 {
+            0x00 => {
+                if len == 0 {
+                    let end = end.unwrap_or(position + 1);
+                    return Ok((labels.join("."), end));
+                }
+                let label = msg
+                    .get(position + 1..position + 1 + len as usize)
+                    .ok_or(DnsMessageError::Truncated)?;
+                labels.push(String::from_utf8_lossy(label).to_ascii_lowercase());
+                position += 1 + len as usize;
+            }
+            0xc0 => {
+                pointers += 1;
+                if pointers > MAX_COMPRESSION_POINTERS {
+                    return Err(DnsMessageError::MalformedName);
+                }
+                let pointer = read_u16(msg, position)? & 0x3fff;
+                end.get_or_insert(position + 2);
+                position = pointer as usize;
+            }
+            _ => return Err(DnsMessageError::MalformedName),
+        }
+    }
+}
+
+pub(crate) fn message_id(msg: &[u8]) -> Result<u16, DnsMessageError> {
+    read_u16(msg, 0)
+}
+
+pub(crate) fn set_message_id(msg: &mut [u8], id: u16) {
+    if msg.len() >= 2 {
+        msg[..2].copy_from_slice(&id.to_be_bytes())
+    }
+}
+
+/// Parses the single question contained in the query. Returns it alongside the offset
+/// at which the question section ends.
+pub(crate) fn parse_question(msg: &[u8]) -> Result<(Question, usize), DnsMessageError> {
+    let question_count = read_u16(msg, 4)?;
+    if question_count != 1 {
+        return Err(DnsMessageError::UnsupportedQuestionCount(question_count));
+    }
+
+    let (name, offset) = read_name(msg, HEADER_LEN)?;
+    let question = Question {
+        name,
+        qtype: read_u16(msg, offset)?,
+        qclass: read_u16(msg, offset + 2)?,
+    };
+    Ok((question, offset + 4))
+}
+
+pub(crate) fn summarise_response(msg: &[u8]) -> Result<ResponseSummary, DnsMessageError> {
+    let rcode = msg.get(3).ok_or(DnsMessageError::Truncated)? & RCODE_MASK;
+    let (_, mut offset) = parse_question(msg)?;
+    let answer_count = read_u16(msg, 6)? as u32;
+    let authority_count = read_u16(msg, 8)? as u32;
+
+    let mut answer_ttl: Option<u32> = None;
+    let mut negative_ttl = None;
+    for i in 0..answer_count + authority_count {
+        let (_, name_end) = read_name(msg, offset)?;
+        let rtype = read_u16(msg, name_end)?;
+        let ttl = read_u32(msg, name_end + 4)?;
+        let data_len = read_u16(msg, name_end + 8)? as usize;
+        let data_start = name_end + 10;
+
+        if i < answer_count {
+            answer_ttl = Some(answer_ttl.map_or(ttl, |current| current.min(ttl)));
+        } else if rtype == TYPE_SOA {
+            // SOA rdata: mname, rname, serial, refresh, retry, expire, minimum
+            let (_, rname_start) = read_name(msg, data_start)?;
+            let (_, rname_end) = read_name(msg, rname_start)?;
+            let minimum = read_u32(msg, rname_end + 16)?;
+            negative_ttl = Some(ttl.min(minimum));
+        }
+
+        offset = data_start + data_len;
+        if offset > msg.len() {
+            return Err(DnsMessageError::Truncated);
+        }
+    }
+
+    Ok(ResponseSummary {
+        rcode,
+        answer_ttl,
+        negative_ttl,
+    })
+}
+
+/// Creates a SERVFAIL response to the provided query.
+pub(crate) fn server_failure(query: &[u8], question_end: usize) -> Vec<u8> {
+    let mut response = query[..question_end].to_vec();
+    response[2] |= FLAG_RESPONSE;
+    response[3] = FLAG_RECURSION_AVAILABLE | RCODE_SERVER_FAILURE;
+    // we're only including the question
+    response[6..HEADER_LEN].fill(0);
+    response
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn encode_name(name: &str) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        for label in name.split('.') {
+            encoded.push(label.len() as u8);
+            encoded.extend_from_slice(label.as_bytes());
+        }
+        encoded.push(0);
+        encoded
+    }
+
+    pub(crate) fn query(id: u16, name: &str) -> Vec<u8> {
+        let mut msg = Vec::new();
+        msg.extend_from_slice(&id.to_be_bytes());
+        // standard query with recursion desired
+        msg.extend_from_slice(&[0x01, 0x00]);
+        msg.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+        msg.extend_from_slice(&encode_name(name));
+        // type A, class IN
+        msg.extend_from_slice(&[0, 1, 0, 1]);
+        msg
+    }
+
+    pub(crate) fn answer(id: u16, name: &str, ttls: &[u32]) -> Vec<u8> {
+        let mut msg = query(id, name);
+        msg[2] |= FLAG_RESPONSE;
+        msg[7] = ttls.len() as u8;
+        for ttl in ttls {
+            // pointer to the name in the question section
+            msg.extend_from_slice(&[0xc0, HEADER_LEN as u8]);
+            msg.extend_from_slice(&[0, 1, 0, 1]);
+            msg.extend_from_slice(&ttl.to_be_bytes());
+            msg.extend_from_slice(&[0, 4, 1, 2, 3, 4]);
+        }
+        msg
+    }
+
+    pub(crate) fn nxdomain(id: u16, name: &str, soa_ttl: u32, soa_minimum: u32) -> Vec<u8> {
+        let mut msg = query(id, name);
+        msg[2] |= FLAG_RESPONSE;
+        msg[3] = RCODE_NAME_ERROR;
+        msg[9] = 1;
+
+        let mut rdata = encode_name("ns.example");
+        rdata.extend_from_slice(&encode_name("admin.example"));
+        // serial, refresh, retry, expire
+        rdata.extend_from_slice(&[0; 16]);
+        rdata.extend_from_slice(&soa_minimum.to_be_bytes());
+
+        msg.extend_from_slice(&encode_name("example"));
+        msg.extend_from_slice(&TYPE_SOA.to_be_bytes());
+        msg.extend_from_slice(&[0, 1]);
+        msg.extend_from_slice(&soa_ttl.to_be_bytes());
+        msg.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        msg.extend_from_slice(&rdata);
+        msg
+    }
+
+    #[test]
+    fn question_is_parsed_and_normalised() {
+        let msg = query(42, "Nymtech.NET");
+        let (question, end) = parse_question(&msg).unwrap();
+        assert_eq!(question.name, "nymtech.net");
+        assert_eq!(question.qtype, 1);
+        assert_eq!(question.qclass, 1);
+        assert_eq!(end, msg.len());
+        assert_eq!(message_id(&msg).unwrap(), 42);
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        assert_eq!(parse_question(&[0; 4]), Err(DnsMessageError::Truncated));
+
+        let msg = query(42, "nymtech.net");
+        assert_eq!(
+            parse_question(&msg[..msg.len() - 3]),
+            Err(DnsMessageError::Truncated)
+        );
+
+        // a pointer pointing at itself
+        let mut looped = msg[..HEADER_LEN].to_vec();
+        looped.extend_from_slice(&[0xc0, HEADER_LEN as u8]);
+        assert_eq!(parse_question(&looped), Err(DnsMessageError::MalformedName));
+    }
+
+    #[test]
+    fn lowest_answer_ttl_is_used() {
+        let summary = summarise_response(&answer(1, "nymtech.net", &[300, 60, 120])).unwrap();
+        assert_eq!(summary.rcode, RCODE_NO_ERROR);
+        assert_eq!(summary.answer_ttl, Some(60));
+        assert_eq!(summary.negative_ttl, None);
+    }
+
+    #[test]
+    fn negative_ttl_is_derived_from_soa_record() {
+        let summary = summarise_response(&nxdomain(1, "foo.example", 900, 300)).unwrap();
+        assert_eq!(summary.rcode, RCODE_NAME_ERROR);
+        assert_eq!(summary.answer_ttl, None);
+        assert_eq!(summary.negative_ttl, Some(300));
+    }
+
+    #[test]
+    fn server_failure_only_contains_the_question() {
+        let query = query(42, "nymtech.net");
+        let (_, end) = parse_question(&query).unwrap();
+        let response = server_failure(&query, end);
+
+        let summary = summarise_response(&response).unwrap();
+        assert_eq!(summary.rcode, RCODE_SERVER_FAILURE);
+        assert_eq!(summary.answer_ttl, None);
+        assert_eq!(message_id(&response).unwrap(), 42);
+    }
+}
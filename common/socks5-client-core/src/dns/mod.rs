@@ -0,0 +1,192 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Local DNS resolver that, rather than leaking the queries to the system resolver, sends them
+//! as DNS-over-HTTPS requests through our own socks5 listener. This way they get tunneled over
+//! the mixnet and are made by the network requester on our behalf.
+//!
+//! Note that the network requester has to allow connections to the configured DoH server.
+
+use crate::dns::cache::DnsCache;
+use crate::dns::message::{message_id, parse_question, server_failure, set_message_id};
+use crate::error::Socks5ClientCoreError;
+use log::*;
+use nym_task::TaskClient;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tap::TapFallible;
+use thiserror::Error;
+use tokio::net::UdpSocket;
+use url::Url;
+
+pub use message::DnsMessageError;
+
+mod cache;
+mod message;
+
+const DNS_MESSAGE_CONTENT_TYPE: &str = "application/dns-message";
+
+// DNS over UDP messages are limited to 512 bytes unless EDNS is used, but that rarely goes
+// above 4096 bytes
+const MAX_QUERY_SIZE: usize = 4096;
+
+#[derive(Debug, Error)]
+pub enum DnsQueryError {
+    #[error("failed to send the DoH request - {0}")]
+    RequestFailure(#[from] reqwest::Error),
+
+    #[error("the DoH server responded with status {0}")]
+    UnexpectedStatus(reqwest::StatusCode),
+
+    #[error("received malformed DNS message - {0}")]
+    MalformedMessage(#[from] DnsMessageError),
+}
+
+pub struct DnsResolverConfig {
+    pub listening_address: SocketAddr,
+    pub doh_url: Url,
+    pub socks5_port: u16,
+    pub query_timeout: Duration,
+    pub cache_capacity: usize,
+    pub max_cache_ttl: Duration,
+    pub negative_cache_ttl: Duration,
+}
+
+/// Sends the queries to the DoH server and caches the answers.
+#[derive(Clone)]
+struct QueryHandler {
+    http_client: reqwest::Client,
+    doh_url: Url,
+    cache: Arc<Mutex<DnsCache>>,
+}
+
+impl QueryHandler {
+    async fn query_doh_server(&self, mut query: Vec<u8>) -> Result<Vec<u8>, DnsQueryError> {
+        // as recommended by RFC 8484, use the id of 0 to make the request more cache friendly
+        let id = message_id(&query)?;
+        set_message_id(&mut query, 0);
+
+        let response = self
+            .http_client
+            .post(self.doh_url.clone())
+            .header(reqwest::header::CONTENT_TYPE, DNS_MESSAGE_CONTENT_TYPE)
+            .header(reqwest::header::ACCEPT, DNS_MESSAGE_CONTENT_TYPE)
+            .body(query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(DnsQueryError::UnexpectedStatus(response.status()));
+        }
+
+        let mut answer = response.bytes().await?.to_vec();
+        // make sure it's something we could reasonably hand over to the requester
+        parse_question(&answer)?;
+        set_message_id(&mut answer, id);
+        Ok(answer)
+    }
+
+    /// Produces the response to the provided query, either from the cache or by asking the DoH server.
+    async fn resolve(&self, query: Vec<u8>) -> Result<Vec<u8>, DnsMessageError> {
+        let (question, question_end) = parse_question(&query)?;
+        let id = message_id(&query)?;
+
+        let cached = self.cache.lock().unwrap().get(&question);
+        if let Some(mut response) = cached {
+            trace!("answering query for {} from the cache", question.name);
+            set_message_id(&mut response, id);
+            return Ok(response);
+        }
+
+        debug!("resolving {} through the mixnet", question.name);
+        match self.query_doh_server(query.clone()).await {
+            Ok(response) => {
+                self.cache
+                    .lock()
+                    .unwrap()
+                    .insert(question, response.clone());
+                Ok(response)
+            }
+            Err(err) => {
+                warn!("failed to resolve {} - {err}", question.name);
+                Ok(server_failure(&query, question_end))
+            }
+        }
+    }
+}
+
+pub struct DnsResolver {
+    listening_address: SocketAddr,
+    handler: QueryHandler,
+    shutdown: TaskClient,
+}
+
+impl DnsResolver {
+    pub fn new(
+        config: DnsResolverConfig,
+        shutdown: TaskClient,
+    ) -> Result<Self, Socks5ClientCoreError> {
+        // 'socks5h' ensures the DoH server name itself is resolved by the network requester
+        let proxy = reqwest::Proxy::all(format!("socks5h://127.0.0.1:{}", config.socks5_port))?;
+        let http_client = reqwest::Client::builder()
+            .proxy(proxy)
+            .timeout(config.query_timeout)
+            .build()?;
+
+        Ok(DnsResolver {
+            listening_address: config.listening_address,
+            handler: QueryHandler {
+                http_client,
+                doh_url: config.doh_url,
+                cache: Arc::new(Mutex::new(DnsCache::new(
+                    config.cache_capacity,
+                    config.max_cache_ttl,
+                    config.negative_cache_ttl,
+                ))),
+            },
+            shutdown,
+        })
+    }
+
+    pub async fn run(&mut self) -> Result<(), Socks5ClientCoreError> {
+        let socket = UdpSocket::bind(self.listening_address)
+            .await
+            .tap_err(|err| log::error!("Failed to bind the DNS resolver socket: {err}"))?;
+        let socket = Arc::new(socket);
+        info!("DNS resolver is listening on {}", self.listening_address);
+
+        let mut buf = vec![0u8; MAX_QUERY_SIZE];
+        loop {
+            tokio::select! {
+                received = socket.recv_from(&mut buf) => {
+                    let (len, requester) = match received {
+                        Ok(received) => received,
+                        Err(err) => {
+                            warn!("failed to receive DNS query: {err}");
+                            continue;
+                        }
+                    };
+                    let query = buf[..len].to_vec();
+                    let handler = self.handler.clone();
+                    let socket = Arc::clone(&socket);
+
+                    tokio::spawn(async move {
+                        match handler.resolve(query).await {
+                            Ok(response) => {
+                                if let Err(err) = socket.send_to(&response, requester).await {
+                                    debug!("failed to send DNS response to {requester}: {err}")
+                                }
+                            }
+                            Err(err) => debug!("received malformed DNS query from {requester}: {err}"),
+                        }
+                    });
+                }
+                _ = self.shutdown.recv() => {
+                    log::trace!("DnsResolver: Received shutdown");
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::socks::types::SocksProxyError;
 use nym_client_core::error::ClientCoreError;
-use nym_socks5_requests::{ConnectionError, ConnectionId};
+use nym_socks5_requests::{ConnectionError, ConnectionId, QuotaExceeded};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Socks5ClientCoreError {
@@ -10,14 +10,23 @@ pub enum Socks5ClientCoreError {
     #[error("SOCKS proxy error")]
     SocksProxyError(SocksProxyError),
 
+    #[error("failed to set up the HTTP client: {0}")]
+    HttpClientError(#[from] reqwest::Error),
+
     #[error("client-core error: {0}")]
     ClientCoreError(#[from] ClientCoreError),
 
+    #[error("credential storage error: {0}")]
+    CredentialStorageError(#[from] nym_credential_storage::error::StorageError),
+
     #[error("Network requester: connection id {connection_id}: {error}")]
     NetworkRequesterError {
         connection_id: ConnectionId,
         error: String,
     },
+
+    #[error("Network requester: {0}")]
+    NetworkRequesterQuotaExceeded(QuotaExceeded),
 }
 
 impl From<ConnectionError> for Socks5ClientCoreError {
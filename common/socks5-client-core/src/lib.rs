@@ -1,7 +1,8 @@
 // Copyright 2021-2023 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::config::{Config, Socks5};
+use crate::config::{Config, Socks5, StreamIsolation};
+use crate::dns::{DnsResolver, DnsResolverConfig};
 use crate::error::Socks5ClientCoreError;
 use crate::socks::{
     authentication::{AuthenticationMethods, Authenticator, User},
@@ -20,7 +21,11 @@ use nym_client_core::client::base_client::{
     BaseClientBuilder, ClientInput, ClientOutput, ClientState,
 };
 use nym_client_core::client::key_manager::KeyManager;
+#[cfg(not(target_os = "android"))]
+use nym_client_core::client::storage_passphrase::storage_passphrase;
 use nym_client_core::config::persistence::key_pathfinder::ClientKeyPathfinder;
+#[cfg(not(target_os = "android"))]
+use nym_credential_storage::persistent_storage::PersistentStorage;
 use nym_credential_storage::storage::Storage;
 use nym_sphinx::addressing::clients::Recipient;
 use nym_task::{TaskClient, TaskManager};
@@ -29,6 +34,7 @@ use nym_validator_client::Client;
 use std::error::Error;
 
 pub mod config;
+pub mod dns;
 pub mod error;
 pub mod socks;
 
@@ -93,7 +99,10 @@ impl NymClient {
             .pop()
             .expect("No validator api endpoint provided");
         // overwrite env configuration with config URLs
-        client_config = client_config.with_urls(nyxd_url, api_url);
+        client_config = client_config
+            .with_urls(nyxd_url, api_url)
+            .with_nym_api_proxy(config.get_base().get_nym_api_proxy())
+            .with_nyxd_proxy(config.get_base().get_nyxd_proxy());
         let client = nym_validator_client::Client::new_query(client_config)
             .expect("Could not construct query client");
 
@@ -109,7 +118,17 @@ impl NymClient {
         shutdown: TaskClient,
     ) {
         info!("Starting socks5 listener...");
-        let auth_methods = vec![AuthenticationMethods::NoAuth as u8];
+        let stream_isolation =
+            socks5_config.get_stream_isolation() == StreamIsolation::PerApplication;
+        if stream_isolation && !socks5_config.get_send_anonymously() {
+            warn!("stream isolation is enabled, but it has no effect unless anonymous replies are used");
+        }
+
+        let mut auth_methods = vec![AuthenticationMethods::NoAuth as u8];
+        if stream_isolation {
+            // the credentials are only used for telling the applications apart
+            auth_methods.push(AuthenticationMethods::UserPass as u8);
+        }
         let allowed_users: Vec<User> = Vec::new();
 
         let ClientInput {
@@ -119,6 +138,7 @@ impl NymClient {
 
         let ClientOutput {
             received_buffer_request_sender,
+            ..
         } = client_output;
 
         let ClientState {
@@ -127,10 +147,15 @@ impl NymClient {
         } = client_status;
 
         let authenticator = Authenticator::new(auth_methods, allowed_users);
-        let mut sphinx_socks = SphinxSocksServer::new(
+        let authenticator = if stream_isolation {
+            authenticator.with_any_user_allowed()
+        } else {
+            authenticator
+        };
+        let sphinx_socks = SphinxSocksServer::new(
             socks5_config.get_listening_port(),
             authenticator,
-            socks5_config.get_provider_mix_address(),
+            socks5_config.get_providers(),
             self_address,
             shared_lane_queue_lengths,
             socks::client::Config::new(
@@ -142,6 +167,16 @@ impl NymClient {
             ),
             shutdown.clone(),
         );
+        let sphinx_socks = if socks5_config.get_http_proxy_enabled() {
+            sphinx_socks.with_http_listener(socks5_config.get_http_listening_address())
+        } else {
+            sphinx_socks
+        };
+        let mut sphinx_socks = if stream_isolation {
+            sphinx_socks.with_stream_isolation()
+        } else {
+            sphinx_socks
+        };
         nym_task::spawn_with_report_error(
             async move {
                 sphinx_socks
@@ -156,6 +191,27 @@ impl NymClient {
         );
     }
 
+    pub fn start_dns_resolver(
+        socks5_config: &Socks5,
+        shutdown: TaskClient,
+    ) -> Result<(), Socks5ClientCoreError> {
+        info!("Starting DNS resolver...");
+        let mut resolver = DnsResolver::new(
+            DnsResolverConfig {
+                listening_address: socks5_config.get_dns_listening_address(),
+                doh_url: socks5_config.get_doh_url(),
+                socks5_port: socks5_config.get_listening_port(),
+                query_timeout: socks5_config.get_dns_query_timeout(),
+                cache_capacity: socks5_config.get_dns_cache_capacity(),
+                max_cache_ttl: socks5_config.get_dns_max_cache_ttl(),
+                negative_cache_ttl: socks5_config.get_dns_negative_cache_ttl(),
+            },
+            shutdown.clone(),
+        )?;
+        nym_task::spawn_with_report_error(async move { resolver.run().await }, shutdown);
+        Ok(())
+    }
+
     /// blocking version of `start` method. Will run forever (or until SIGINT is sent)
     pub async fn run_forever(self) -> Result<(), Box<dyn Error + Send + Sync>> {
         let shutdown = self.start().await?;
@@ -212,6 +268,9 @@ impl NymClient {
     }
 
     pub async fn start(self) -> Result<TaskManager, Socks5ClientCoreError> {
+        #[cfg(not(target_os = "android"))]
+        let passphrase = storage_passphrase(self.config.get_base())?;
+
         #[cfg(not(target_os = "android"))]
         let base_builder = BaseClientBuilder::new_from_base_config(
             self.config.get_base(),
@@ -219,16 +278,18 @@ impl NymClient {
             Some(
                 Self::create_bandwidth_controller(
                     &self.config,
-                    nym_credential_storage::initialise_persistent_storage(
+                    PersistentStorage::init_with_passphrase(
                         self.config.get_base().get_database_path(),
+                        passphrase.as_deref(),
                     )
-                    .await,
+                    .await?,
                 )
                 .await,
             ),
             non_wasm_helpers::setup_fs_reply_surb_backend(
                 Some(self.config.get_base().get_reply_surb_database_path()),
                 self.config.get_debug_settings(),
+                passphrase.as_deref(),
             )
             .await?,
         );
@@ -262,6 +323,13 @@ impl NymClient {
             started_client.task_manager.subscribe(),
         );
 
+        if self.config.get_socks5().get_dns_resolver_enabled() {
+            Self::start_dns_resolver(
+                self.config.get_socks5(),
+                started_client.task_manager.subscribe(),
+            )?;
+        }
+
         info!("Client startup finished!");
         info!("The address of this client is: {}", self_address);
 
@@ -21,6 +21,7 @@ pub struct User {
 /// and keeps a list of users who have access if that method is enabled.
 pub(crate) struct Authenticator {
     allowed_users: Vec<User>,
+    allow_any_user: bool,
     pub(crate) auth_methods: Vec<u8>,
 }
 
@@ -28,17 +29,25 @@ impl Authenticator {
     pub(crate) fn new(auth_methods: Vec<u8>, allowed_users: Vec<User>) -> Authenticator {
         Authenticator {
             allowed_users,
+            allow_any_user: false,
             auth_methods,
         }
     }
 
+    /// Accepts any username / password pair, for when the credentials are only used
+    /// for telling the local applications apart rather than for access control.
+    pub(crate) fn with_any_user_allowed(mut self) -> Self {
+        self.allow_any_user = true;
+        self
+    }
+
     /// Check if username + password pair are valid
     pub fn is_allowed(&self, user: &User) -> bool {
         if self
             .auth_methods
             .contains(&(AuthenticationMethods::UserPass as u8))
         {
-            self.allowed_users.contains(user)
+            self.allow_any_user || self.allowed_users.contains(user)
         } else {
             false
         }
@@ -113,6 +122,21 @@ mod tests {
             assert!(authenticator.is_allowed(&admin));
         }
 
+        #[test]
+        fn any_user_passes_authentication_check_if_allowed() {
+            let auth_methods = vec![AuthenticationMethods::UserPass as u8];
+
+            let user = User {
+                username: "browser".to_string(),
+                password: "".to_string(),
+            };
+
+            let authenticator =
+                Authenticator::new(auth_methods, Vec::new()).with_any_user_allowed();
+
+            assert!(authenticator.is_allowed(&user));
+        }
+
         #[test]
         fn disallowed_user_fails_authentication_check() {
             let auth_methods = vec![AuthenticationMethods::UserPass as u8];
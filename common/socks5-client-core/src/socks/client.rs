@@ -1,22 +1,30 @@
 #![forbid(unsafe_code)]
 
 use super::authentication::{AuthenticationMethods, Authenticator, User};
+use super::exit_policy::KnownExitPolicy;
+use super::http;
+use super::isolation::IsolatedApplications;
+use super::provider_pool::ProviderPool;
 use super::request::{SocksCommand, SocksRequest};
 use super::types::{ResponseCodeV4, ResponseCodeV5, SocksProxyError};
+use super::udp;
 use super::{SocksVersion, RESERVED, SOCKS4_VERSION, SOCKS5_VERSION};
 use futures::channel::mpsc;
 use futures::task::{Context, Poll};
+use futures::StreamExt;
 use log::*;
 use nym_client_core::client::inbound_messages::{InputMessage, InputMessageSender};
 use nym_service_providers_common::interface::{ProviderInterfaceVersion, RequestVersion};
 use nym_socks5_proxy_helpers::connection_controller::{
     ConnectionReceiver, ControllerCommand, ControllerSender,
 };
+use nym_socks5_proxy_helpers::datagram::DatagramReceiver;
 use nym_socks5_proxy_helpers::proxy_runner::ProxyRunner;
 use nym_socks5_requests::{
     ConnectionId, RemoteAddress, Socks5ProtocolVersion, Socks5ProviderRequest, Socks5Request,
 };
 use nym_sphinx::addressing::clients::Recipient;
+use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
 use nym_task::connections::{LaneQueueLengths, TransmissionLane};
 use nym_task::TaskClient;
 use pin_project::pin_project;
@@ -25,6 +33,7 @@ use std::io;
 use std::net::SocketAddr;
 use std::pin::Pin;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::net::UdpSocket;
 use tokio::{self, net::TcpStream};
 
 #[pin_project(project = StateProject)]
@@ -66,6 +75,17 @@ impl StreamState {
         }
     }
 
+    /// Returns the local address that this stream is bound to.
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            StreamState::RunningProxy => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "stream is being used to run the proxy",
+            )),
+            StreamState::Available(ref stream) => stream.local_addr(),
+        }
+    }
+
     async fn shutdown(&mut self) -> io::Result<()> {
         // shutdown should only be called if proxy is not being run. If it is, there's some bug
         // somewhere
@@ -161,6 +181,14 @@ impl Config {
             provider_protocol: self.socks5_protocol_version,
         }
     }
+
+    pub(crate) fn provider_interface_version(&self) -> ProviderInterfaceVersion {
+        self.provider_interface_version
+    }
+
+    pub(crate) fn connection_start_surbs(&self) -> u32 {
+        self.connection_start_surbs
+    }
 }
 
 /// A client connecting to the Socks proxy server, because
@@ -177,9 +205,13 @@ pub(crate) struct SocksClient {
     input_sender: InputMessageSender,
     connection_id: ConnectionId,
     service_provider: Recipient,
+    provider_pool: ProviderPool,
     self_address: Recipient,
     started_proxy: bool,
     lane_queue_lengths: LaneQueueLengths,
+    exit_policy: KnownExitPolicy,
+    isolated_applications: Option<IsolatedApplications>,
+    sender_tag: Option<AnonymousSenderTag>,
     shutdown_listener: TaskClient,
 }
 
@@ -193,6 +225,7 @@ impl Drop for SocksClient {
                     connection_id: self.connection_id,
                 })
                 .unwrap();
+            self.provider_pool.connection_closed(self.connection_id);
         }
     }
 }
@@ -204,7 +237,7 @@ impl SocksClient {
         stream: TcpStream,
         authenticator: Authenticator,
         input_sender: InputMessageSender,
-        service_provider: &Recipient,
+        provider_pool: ProviderPool,
         controller_sender: ControllerSender,
         self_address: &Recipient,
         lane_queue_lengths: LaneQueueLengths,
@@ -224,14 +257,49 @@ impl SocksClient {
             socks_version: None,
             authenticator,
             input_sender,
-            service_provider: *service_provider,
+            service_provider: provider_pool.choose_provider(),
+            provider_pool,
             self_address: *self_address,
             started_proxy: false,
             lane_queue_lengths,
+            exit_policy: KnownExitPolicy::default(),
+            isolated_applications: None,
+            sender_tag: None,
             shutdown_listener,
         }
     }
 
+    /// Rejects destinations disallowed by the provided exit policy of the service provider
+    /// without sending anything to the mixnet.
+    pub(crate) fn with_exit_policy(mut self, exit_policy: KnownExitPolicy) -> Self {
+        self.exit_policy = exit_policy;
+        self
+    }
+
+    /// Sends the anonymous messages of this connection with the sender tag of the local application
+    /// that has opened it, or, if it could not be identified, with a tag of its own.
+    pub(crate) fn with_isolation(mut self, isolated_applications: IsolatedApplications) -> Self {
+        self.sender_tag = Some(isolated_applications.connection_tag());
+        self.isolated_applications = Some(isolated_applications);
+        self
+    }
+
+    fn isolate(sender_tag: Option<AnonymousSenderTag>, message: InputMessage) -> InputMessage {
+        match sender_tag {
+            Some(sender_tag) => message.with_sender_tag(sender_tag),
+            None => message,
+        }
+    }
+
+    fn check_exit_policy(&self, remote_address: &str) -> Result<(), SocksProxyError> {
+        if self.exit_policy.allows(remote_address) {
+            Ok(())
+        } else {
+            warn!("{remote_address} is not allowed by the exit policy of the service provider");
+            Err(ResponseCodeV5::RuleFailure.into())
+        }
+    }
+
     fn generate_random() -> ConnectionId {
         let mut rng = rand::rngs::OsRng;
         rng.next_u64()
@@ -250,14 +318,12 @@ impl SocksClient {
                 self.send_error_v4(response).await
             }
             SocksVersion::V5 => {
-                let response = if error_text.contains("Host") {
-                    ResponseCodeV5::HostUnreachable
-                } else if error_text.contains("Network") {
-                    ResponseCodeV5::NetworkUnreachable
-                } else if error_text.contains("ttl") {
-                    ResponseCodeV5::TtlExpired
-                } else {
-                    ResponseCodeV5::Failure
+                let response = match err {
+                    SocksProxyError::Socks5ResponseFailure(code) => code,
+                    _ if error_text.contains("Host") => ResponseCodeV5::HostUnreachable,
+                    _ if error_text.contains("Network") => ResponseCodeV5::NetworkUnreachable,
+                    _ if error_text.contains("ttl") => ResponseCodeV5::TtlExpired,
+                    _ => ResponseCodeV5::Failure,
                 };
                 self.send_error_v5(response).await
             }
@@ -329,6 +395,40 @@ impl SocksClient {
         self.handle_request().await
     }
 
+    /// Runs the client that connected to the HTTP proxy listener rather than the SOCKS one.
+    /// Only CONNECT requests are supported, after which the connection is proxied
+    /// exactly the same way as with SOCKS.
+    pub async fn run_http_connect(&mut self) -> Result<(), SocksProxyError> {
+        let head = http::read_request_head(&mut self.stream).await?;
+        let remote_address = match http::parse_connect_target(&head) {
+            Ok(remote_address) => remote_address,
+            Err(err) => {
+                self.stream
+                    .write_all(err.response())
+                    .await
+                    .map_err(|source| SocksProxyError::SocketWriteError { source })?;
+                return Err(err.into());
+            }
+        };
+
+        if let Err(err) = self.check_exit_policy(&remote_address) {
+            self.stream
+                .write_all(http::FORBIDDEN)
+                .await
+                .map_err(|source| SocksProxyError::SocketWriteError { source })?;
+            return Err(err);
+        }
+
+        trace!("Connecting to: {:?}", remote_address);
+        self.stream
+            .write_all(http::CONNECTION_ESTABLISHED)
+            .await
+            .map_err(|source| SocksProxyError::SocketWriteError { source })?;
+
+        self.start_proxy(remote_address).await;
+        Ok(())
+    }
+
     async fn send_anonymous_connect_to_mixnet(&mut self, remote_address: RemoteAddress) {
         // TODO: simplify by using `request_version`
         let req = Socks5Request::new_connect(
@@ -340,11 +440,14 @@ impl SocksClient {
         let msg =
             Socks5ProviderRequest::new_provider_data(self.config.provider_interface_version, req);
 
-        let input_message = InputMessage::new_anonymous(
-            self.service_provider,
-            msg.into_bytes(),
-            self.config.connection_start_surbs,
-            TransmissionLane::ConnectionId(self.connection_id),
+        let input_message = Self::isolate(
+            self.sender_tag,
+            InputMessage::new_anonymous(
+                self.service_provider,
+                msg.into_bytes(),
+                self.config.connection_start_surbs,
+                TransmissionLane::ConnectionId(self.connection_id),
+            ),
         );
         self.input_sender
             .send(input_message)
@@ -402,6 +505,7 @@ impl SocksClient {
         let anonymous = self.config.use_surbs_for_responses;
         let per_request_surbs = self.config.per_request_surbs;
         let request_version = self.config.request_version();
+        let sender_tag = self.sender_tag;
 
         let recipient = self.service_provider;
         let (stream, _) = ProxyRunner::new(
@@ -427,11 +531,14 @@ impl SocksClient {
             );
             let lane = TransmissionLane::ConnectionId(conn_id);
             if anonymous {
-                InputMessage::new_anonymous(
-                    recipient,
-                    provider_message.into_bytes(),
-                    per_request_surbs,
-                    lane,
+                Self::isolate(
+                    sender_tag,
+                    InputMessage::new_anonymous(
+                        recipient,
+                        provider_message.into_bytes(),
+                        per_request_surbs,
+                        lane,
+                    ),
                 )
             } else {
                 InputMessage::new_regular(recipient, provider_message.into_bytes(), lane)
@@ -443,6 +550,211 @@ impl SocksClient {
         self.stream.finish_proxy(stream)
     }
 
+    async fn send_udp_associate_to_mixnet(&mut self) {
+        let lane = TransmissionLane::ConnectionId(self.connection_id);
+        let input_message = if self.config.use_surbs_for_responses {
+            let req = Socks5Request::new_udp_associate(
+                self.config.socks5_protocol_version,
+                self.connection_id,
+                None,
+            );
+            let msg = Socks5ProviderRequest::new_provider_data(
+                self.config.provider_interface_version,
+                req,
+            );
+            Self::isolate(
+                self.sender_tag,
+                InputMessage::new_anonymous(
+                    self.service_provider,
+                    msg.into_bytes(),
+                    self.config.connection_start_surbs,
+                    lane,
+                ),
+            )
+        } else {
+            let req = Socks5Request::new_udp_associate(
+                self.config.socks5_protocol_version,
+                self.connection_id,
+                Some(self.self_address),
+            );
+            let msg = Socks5ProviderRequest::new_provider_data(
+                self.config.provider_interface_version,
+                req,
+            );
+            InputMessage::new_regular(self.service_provider, msg.into_bytes(), lane)
+        };
+
+        self.input_sender
+            .send(input_message)
+            .await
+            .expect("InputMessageReceiver has stopped receiving!");
+    }
+
+    async fn send_datagram_to_mixnet(&mut self, sequence: u64, remote_addr: String, data: Vec<u8>) {
+        let req = Socks5Request::new_send_datagram(
+            self.config.socks5_protocol_version,
+            self.connection_id,
+            sequence,
+            remote_addr,
+            data,
+        );
+        let msg =
+            Socks5ProviderRequest::new_provider_data(self.config.provider_interface_version, req);
+
+        let lane = TransmissionLane::ConnectionId(self.connection_id);
+        let input_message = if self.config.use_surbs_for_responses {
+            Self::isolate(
+                self.sender_tag,
+                InputMessage::new_anonymous(
+                    self.service_provider,
+                    msg.into_bytes(),
+                    self.config.per_request_surbs,
+                    lane,
+                ),
+            )
+        } else {
+            InputMessage::new_regular(self.service_provider, msg.into_bytes(), lane)
+        };
+
+        self.input_sender
+            .send(input_message)
+            .await
+            .expect("InputMessageReceiver has stopped receiving!");
+    }
+
+    /// Relays datagrams between the local application and the mixnet for as long as
+    /// the TCP connection the association was requested on stays open.
+    ///
+    /// Datagrams are sent in the relaxed reliability mode: each one carries its own sequence number,
+    /// there's no ordering nor buffering on either side, and duplicates or stale datagrams
+    /// are silently dropped.
+    async fn run_udp_association(
+        &mut self,
+        mut datagram_receiver: DatagramReceiver,
+    ) -> Result<(), SocksProxyError> {
+        let local_addr = self
+            .stream
+            .local_addr()
+            .map_err(|source| SocksProxyError::PeerAddrExtractionFailure { source })?;
+        let peer_addr = self
+            .stream
+            .peer_addr()
+            .map_err(|source| SocksProxyError::PeerAddrExtractionFailure { source })?;
+
+        let socket = UdpSocket::bind(SocketAddr::new(local_addr.ip(), 0))
+            .await
+            .map_err(|source| SocksProxyError::UdpSocketBindFailure { source })?;
+        let relay_addr = socket
+            .local_addr()
+            .map_err(|source| SocksProxyError::UdpSocketBindFailure { source })?;
+
+        self.acknowledge_udp_associate(relay_addr).await?;
+
+        self.started_proxy = true;
+        self.provider_pool
+            .track_connection(self.connection_id, self.service_provider);
+        self.send_udp_associate_to_mixnet().await;
+
+        let mut buf = vec![0u8; udp::MAX_DATAGRAM_SIZE];
+        let mut control_buf = [0u8; 1];
+        let mut client_addr = None;
+        let mut sequence = 0;
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = self.shutdown_listener.recv() => {
+                    log::trace!("UDP association {}: Received shutdown", self.connection_id);
+                    break;
+                }
+                read = self.stream.read(&mut control_buf) => {
+                    // nothing meaningful is ever sent over the control connection,
+                    // we only care about it being closed
+                    if matches!(read, Ok(0) | Err(_)) {
+                        break;
+                    }
+                }
+                received = socket.recv_from(&mut buf) => {
+                    let (n, sender) = match received {
+                        Ok(received) => received,
+                        Err(err) => {
+                            debug!("failed to receive a datagram: {err}");
+                            continue;
+                        }
+                    };
+
+                    // only accept datagrams from the host that requested the association
+                    // and lock onto the first port it used
+                    if sender.ip() != peer_addr.ip() || matches!(client_addr, Some(addr) if addr != sender) {
+                        trace!("dropping datagram from unexpected {sender}");
+                        continue;
+                    }
+                    client_addr = Some(sender);
+
+                    let (remote_addr, data) = match udp::parse_datagram(&buf[..n]) {
+                        Ok(parsed) => parsed,
+                        Err(err) => {
+                            debug!("dropping malformed datagram: {err}");
+                            continue;
+                        }
+                    };
+                    if !self.exit_policy.allows(&remote_addr) {
+                        debug!("dropping datagram to {remote_addr} disallowed by the exit policy");
+                        continue;
+                    }
+                    let data = data.to_vec();
+
+                    self.send_datagram_to_mixnet(sequence, remote_addr, data).await;
+                    sequence += 1;
+                }
+                datagram = datagram_receiver.next() => {
+                    let Some(datagram) = datagram else {
+                        break;
+                    };
+                    let Some(client_addr) = client_addr else {
+                        debug!("received a datagram before the client has sent anything - dropping it");
+                        continue;
+                    };
+
+                    let encoded = udp::encode_datagram(&datagram.remote_addr, &datagram.data);
+                    if let Err(err) = socket.send_to(&encoded, client_addr).await {
+                        debug!("failed to forward datagram to {client_addr}: {err}");
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Registers the connection with the controller and proxies it over the mixnet
+    /// until either side closes it.
+    async fn start_proxy(&mut self, remote_address: String) {
+        // setup for receiving from the mixnet
+        let (mix_sender, mix_receiver) = mpsc::unbounded();
+
+        self.started_proxy = true;
+        self.provider_pool
+            .track_connection(self.connection_id, self.service_provider);
+        self.controller_sender
+            .unbounded_send(ControllerCommand::Insert {
+                connection_id: self.connection_id,
+                connection_sender: mix_sender,
+            })
+            .unwrap();
+
+        info!(
+            "Starting proxy for {} (id: {})",
+            remote_address.clone(),
+            self.connection_id
+        );
+        self.run_proxy(mix_receiver, remote_address.clone()).await;
+        info!(
+            "Proxy for {} is finished (id: {})",
+            remote_address, self.connection_id
+        );
+    }
+
     /// Handles a client request.
     async fn handle_request(&mut self) -> Result<(), SocksProxyError> {
         debug!("Handling CONNECT Command");
@@ -459,40 +771,49 @@ impl SocksClient {
 
         let remote_address = request.address_string();
 
-        // setup for receiving from the mixnet
-        let (mix_sender, mix_receiver) = mpsc::unbounded();
-
         match request.command {
             // Use the Proxy to connect to the specified addr/port
             SocksCommand::Connect => {
+                self.check_exit_policy(&remote_address)?;
+
                 trace!("Connecting to: {:?}", remote_address.clone());
                 match version {
                     SocksVersion::V4 => self.acknowledge_socks4().await,
                     SocksVersion::V5 => self.acknowledge_socks5().await,
                 }
 
-                self.started_proxy = true;
+                self.start_proxy(remote_address).await;
+            }
+
+            SocksCommand::UdpAssociate => {
+                // UDP ASSOCIATE is not part of SOCKS4
+                if *version != SocksVersion::V5 {
+                    return Err(ResponseCodeV5::CommandNotSupported.into());
+                }
+
+                let (datagram_sender, datagram_receiver) = mpsc::unbounded();
                 self.controller_sender
-                    .unbounded_send(ControllerCommand::Insert {
+                    .unbounded_send(ControllerCommand::InsertAssociation {
                         connection_id: self.connection_id,
-                        connection_sender: mix_sender,
+                        datagram_sender,
                     })
                     .unwrap();
 
-                info!(
-                    "Starting proxy for {} (id: {})",
-                    remote_address.clone(),
-                    self.connection_id
-                );
-                self.run_proxy(mix_receiver, remote_address.clone()).await;
-                info!(
-                    "Proxy for {} is finished (id: {})",
-                    remote_address, self.connection_id
-                );
+                info!("Starting UDP association (id: {})", self.connection_id);
+                let res = self.run_udp_association(datagram_receiver).await;
+                if !self.started_proxy {
+                    // the association never got far enough for `Drop` to clean it up
+                    self.controller_sender
+                        .unbounded_send(ControllerCommand::Remove {
+                            connection_id: self.connection_id,
+                        })
+                        .unwrap();
+                }
+                res?;
+                info!("UDP association is finished (id: {})", self.connection_id);
             }
 
             SocksCommand::Bind => unimplemented!(), // not handled
-            SocksCommand::UdpAssociate => unimplemented!(), // not handled
         };
 
         Ok(())
@@ -518,6 +839,23 @@ impl SocksClient {
             .unwrap();
     }
 
+    /// Writes a Socks5 header back to the requesting client's TCP stream, letting it know
+    /// the address of the relay it should be sending its datagrams to.
+    async fn acknowledge_udp_associate(
+        &mut self,
+        relay_addr: SocketAddr,
+    ) -> Result<(), SocksProxyError> {
+        let response: Vec<_> = [SOCKS5_VERSION, ResponseCodeV5::Success as u8, RESERVED]
+            .into_iter()
+            .chain(udp::encode_socket_address(&relay_addr).into_iter())
+            .collect();
+
+        self.stream
+            .write_all(&response)
+            .await
+            .map_err(|source| SocksProxyError::SocketWriteError { source })
+    }
+
     /// Writes a Socks4 header back to the requesting client's TCP stream,
     async fn acknowledge_socks4(&mut self) {
         self.stream
@@ -622,6 +960,9 @@ impl SocksClient {
             // Authenticate passwords
             if self.authenticator.is_allowed(&user) {
                 debug!("Access Granted. User: {}", user.username);
+                if let Some(isolated_applications) = &self.isolated_applications {
+                    self.sender_tag = Some(isolated_applications.application_tag(&user.username));
+                }
                 let response = [1, ResponseCodeV5::Success as u8];
                 self.stream
                     .write_all(&response)
@@ -0,0 +1,47 @@
+use nym_service_providers_common::interface::ExitPolicy;
+use std::sync::{Arc, RwLock};
+
+/// Exit policy published by the service provider, if we have received it yet.
+/// It's used to reject disallowed destinations locally rather than waiting
+/// for the provider to close the connection.
+#[derive(Clone, Default)]
+pub(crate) struct KnownExitPolicy {
+    inner: Arc<RwLock<Option<ExitPolicy>>>,
+}
+
+impl KnownExitPolicy {
+    pub(crate) fn update(&self, policy: Option<ExitPolicy>) {
+        *self.inner.write().expect("exit policy lock got poisoned") = policy;
+    }
+
+    /// Checks whether the provided destination is allowed by the provider.
+    /// If we don't know the policy, we optimistically assume it is.
+    pub(crate) fn allows(&self, destination: &str) -> bool {
+        match &*self.inner.read().expect("exit policy lock got poisoned") {
+            Some(policy) => policy.allows(destination),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_policy_allows_everything() {
+        let policy = KnownExitPolicy::default();
+        assert!(policy.allows("nymtech.net:443"));
+
+        policy.update(Some(ExitPolicy {
+            open_proxy: false,
+            allowed_domains: vec!["nymtech.net".to_string()],
+            allowed_ip_networks: Vec::new(),
+        }));
+        assert!(policy.allows("nymtech.net:443"));
+        assert!(!policy.allows("edwardsnowden.com:443"));
+
+        policy.update(None);
+        assert!(policy.allows("edwardsnowden.com:443"));
+    }
+}
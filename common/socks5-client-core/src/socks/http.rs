@@ -0,0 +1,159 @@
+//! Minimal support for HTTP CONNECT proxy requests (RFC9110, section 9.3.6), i.e.
+//!
+//! CONNECT server.example.com:443 HTTP/1.1
+//! Host: server.example.com:443
+//!
+//! Once the tunnel is established, the connection is handled exactly the same way as
+//! the SOCKS5 CONNECT would have been.
+
+use super::types::SocksProxyError;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Maximum size of the request line alongside all the headers we're willing to read.
+const MAX_REQUEST_HEAD_SIZE: usize = 8 * 1024;
+
+const REQUEST_HEAD_TERMINATOR: &[u8] = b"\r\n\r\n";
+
+pub(crate) const CONNECTION_ESTABLISHED: &[u8] = b"HTTP/1.1 200 Connection established\r\n\r\n";
+
+/// Response sent when the target is not allowed by the exit policy of the service provider.
+pub(crate) const FORBIDDEN: &[u8] = b"HTTP/1.1 403 Forbidden\r\nConnection: close\r\n\r\n";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum HttpRequestError {
+    #[error("the request head exceeds {MAX_REQUEST_HEAD_SIZE} bytes")]
+    RequestTooLarge,
+
+    #[error("the request line is malformed")]
+    MalformedRequestLine,
+
+    #[error("{method} method is not supported, only CONNECT requests can be proxied")]
+    UnsupportedMethod { method: String },
+
+    #[error("'{target}' is not a valid 'host:port' target")]
+    MalformedTarget { target: String },
+}
+
+impl HttpRequestError {
+    /// Response that should be sent back to the client before closing the connection.
+    pub(crate) fn response(&self) -> &'static [u8] {
+        match self {
+            HttpRequestError::RequestTooLarge => {
+                b"HTTP/1.1 431 Request Header Fields Too Large\r\nConnection: close\r\n\r\n"
+            }
+            HttpRequestError::UnsupportedMethod { .. } => {
+                b"HTTP/1.1 405 Method Not Allowed\r\nAllow: CONNECT\r\nConnection: close\r\n\r\n"
+            }
+            HttpRequestError::MalformedRequestLine | HttpRequestError::MalformedTarget { .. } => {
+                b"HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\n"
+            }
+        }
+    }
+}
+
+/// Reads the request line alongside all the headers. Note that it's read byte by byte
+/// so that we wouldn't accidentally consume any data that was meant to be tunneled.
+pub(crate) async fn read_request_head<R>(stream: &mut R) -> Result<Vec<u8>, SocksProxyError>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut head = Vec::new();
+    while !head.ends_with(REQUEST_HEAD_TERMINATOR) {
+        if head.len() >= MAX_REQUEST_HEAD_SIZE {
+            return Err(HttpRequestError::RequestTooLarge.into());
+        }
+        let byte = stream
+            .read_u8()
+            .await
+            .map_err(|source| SocksProxyError::SocketReadError { source })?;
+        head.push(byte);
+    }
+    Ok(head)
+}
+
+/// Extracts the `host:port` target out of the CONNECT request. All the headers are ignored.
+pub(crate) fn parse_connect_target(head: &[u8]) -> Result<String, HttpRequestError> {
+    let request_line = head
+        .split(|&b| b == b'\n')
+        .next()
+        .and_then(|line| std::str::from_utf8(line).ok())
+        .ok_or(HttpRequestError::MalformedRequestLine)?
+        .trim_end_matches('\r');
+
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(target), Some(version), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(HttpRequestError::MalformedRequestLine);
+    };
+
+    if !version.starts_with("HTTP/1.") {
+        return Err(HttpRequestError::MalformedRequestLine);
+    }
+
+    if !method.eq_ignore_ascii_case("CONNECT") {
+        return Err(HttpRequestError::UnsupportedMethod {
+            method: method.to_string(),
+        });
+    }
+
+    let valid_target = target
+        .rsplit_once(':')
+        .map(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+        .unwrap_or_default();
+    if !valid_target {
+        return Err(HttpRequestError::MalformedTarget {
+            target: target.to_string(),
+        });
+    }
+
+    Ok(target.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn request_head_is_read_up_to_the_terminator() {
+        let mut stream: &[u8] =
+            b"CONNECT nymtech.net:443 HTTP/1.1\r\nHost: nymtech.net:443\r\n\r\ntunneled data";
+        let head = read_request_head(&mut stream).await.unwrap();
+
+        assert_eq!(
+            head,
+            b"CONNECT nymtech.net:443 HTTP/1.1\r\nHost: nymtech.net:443\r\n\r\n"
+        );
+        assert_eq!(stream, b"tunneled data");
+    }
+
+    #[test]
+    fn connect_target_is_extracted() {
+        let head = b"CONNECT nymtech.net:443 HTTP/1.1\r\nHost: nymtech.net:443\r\n\r\n";
+        assert_eq!(parse_connect_target(head).unwrap(), "nymtech.net:443");
+
+        let head = b"CONNECT [::1]:8080 HTTP/1.0\r\n\r\n";
+        assert_eq!(parse_connect_target(head).unwrap(), "[::1]:8080");
+    }
+
+    #[test]
+    fn invalid_requests_are_rejected() {
+        assert_eq!(
+            parse_connect_target(b"GET http://nymtech.net/ HTTP/1.1\r\n\r\n"),
+            Err(HttpRequestError::UnsupportedMethod {
+                method: "GET".to_string()
+            })
+        );
+        assert_eq!(
+            parse_connect_target(b"CONNECT nymtech.net HTTP/1.1\r\n\r\n"),
+            Err(HttpRequestError::MalformedTarget {
+                target: "nymtech.net".to_string()
+            })
+        );
+        assert_eq!(
+            parse_connect_target(b"CONNECT nymtech.net:443\r\n\r\n"),
+            Err(HttpRequestError::MalformedRequestLine)
+        );
+    }
+}
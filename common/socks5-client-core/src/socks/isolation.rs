@@ -0,0 +1,56 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
+use rand::rngs::OsRng;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Sender tags of the local applications using the proxy, so that the service provider would be
+/// unable to link the connections of different applications with each other.
+///
+/// Applications are told apart by the username they present during the socks5 authentication
+/// (similarly to Tor's `IsolateSOCKSAuth`), with all of their connections sharing the same tag
+/// and thus the same pool of reply SURBs. Connections without a username each get their own tag.
+#[derive(Clone, Default)]
+pub(crate) struct IsolatedApplications {
+    tags: Arc<Mutex<HashMap<String, AnonymousSenderTag>>>,
+}
+
+impl IsolatedApplications {
+    pub(crate) fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sender tag for a connection that could not be attributed to any particular application.
+    pub(crate) fn connection_tag(&self) -> AnonymousSenderTag {
+        AnonymousSenderTag::new_random(&mut OsRng)
+    }
+
+    /// Sender tag shared by all the connections of the specified application.
+    pub(crate) fn application_tag(&self, application: &str) -> AnonymousSenderTag {
+        let mut tags = self
+            .tags
+            .lock()
+            .expect("the isolated applications lock is poisoned");
+        *tags
+            .entry(application.to_string())
+            .or_insert_with(|| AnonymousSenderTag::new_random(&mut OsRng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connections_of_the_same_application_share_the_tag() {
+        let applications = IsolatedApplications::new();
+        let browser = applications.application_tag("browser");
+        let wallet = applications.application_tag("wallet");
+
+        assert_eq!(browser, applications.application_tag("browser"));
+        assert_ne!(browser, wallet);
+        assert_ne!(applications.connection_tag(), applications.connection_tag());
+    }
+}
@@ -8,16 +8,22 @@ use nym_client_core::client::received_buffer::{
 };
 use nym_service_providers_common::interface::{ControlResponse, ResponseContent};
 use nym_socks5_proxy_helpers::connection_controller::ControllerSender;
-use nym_socks5_requests::{Socks5ProviderResponse, Socks5Response, Socks5ResponseContent};
+use nym_socks5_requests::{
+    NetworkData, Socks5ProviderResponse, Socks5Response, Socks5ResponseContent,
+};
 use nym_sphinx::receiver::ReconstructedMessage;
 use nym_task::TaskClient;
 
 use crate::error::Socks5ClientCoreError;
+use crate::socks::exit_policy::KnownExitPolicy;
+use crate::socks::provider_pool::ProviderPool;
 
 pub(crate) struct MixnetResponseListener {
     buffer_requester: ReceivedBufferRequestSender,
     mix_response_receiver: ReconstructedMessagesReceiver,
     controller_sender: ControllerSender,
+    exit_policy: KnownExitPolicy,
+    provider_pool: ProviderPool,
     shutdown: TaskClient,
 }
 
@@ -40,6 +46,8 @@ impl MixnetResponseListener {
     pub(crate) fn new(
         buffer_requester: ReceivedBufferRequestSender,
         controller_sender: ControllerSender,
+        exit_policy: KnownExitPolicy,
+        provider_pool: ProviderPool,
         shutdown: TaskClient,
     ) -> Self {
         let (mix_response_sender, mix_response_receiver) = mpsc::unbounded();
@@ -51,6 +59,8 @@ impl MixnetResponseListener {
             buffer_requester,
             mix_response_receiver,
             controller_sender,
+            exit_policy,
+            provider_pool,
             shutdown,
         }
     }
@@ -59,12 +69,49 @@ impl MixnetResponseListener {
         &self,
         control_response: ControlResponse,
     ) -> Result<(), Socks5ClientCoreError> {
-        error!("received a control response which we don't know how to handle yet!");
-        error!("got: {:?}", control_response);
+        match control_response {
+            ControlResponse::ProviderDescriptor(descriptor) => {
+                info!(
+                    "the service provider uses interface version {} and provider version {}",
+                    descriptor.supported_versions.interface_version,
+                    descriptor.supported_versions.provider_version
+                );
+                if descriptor.exit_policy.is_none() {
+                    warn!("the service provider did not publish its exit policy");
+                }
+                self.exit_policy.update(descriptor.exit_policy);
+                Ok(())
+            }
+            // unsolicited notifications the service provider might send us at any point
+            ControlResponse::BandwidthWarning(warning) => {
+                match warning.remaining_bytes {
+                    Some(remaining) => warn!(
+                        "the service provider is running out of bandwidth for us ({remaining} bytes remaining): {}",
+                        warning.message
+                    ),
+                    None => warn!(
+                        "the service provider is running out of bandwidth for us: {}",
+                        warning.message
+                    ),
+                }
+                Ok(())
+            }
+            ControlResponse::Error(err) => {
+                error!(
+                    "the service provider has returned an error: {}",
+                    err.message
+                );
+                Ok(())
+            }
+            other => {
+                error!("received a control response which we don't know how to handle yet!");
+                error!("got: {:?}", other);
 
-        // I guess we'd need another channel here to forward those to where they need to go
+                // I guess we'd need another channel here to forward those to where they need to go
 
-        Ok(())
+                Ok(())
+            }
+        }
     }
 
     fn on_provider_data_response(
@@ -73,6 +120,8 @@ impl MixnetResponseListener {
     ) -> Result<(), Socks5ClientCoreError> {
         match provider_response.content {
             Socks5ResponseContent::ConnectionError(err_response) => {
+                self.provider_pool
+                    .record_response(err_response.connection_id);
                 error!(
                     "Network requester failed on connection id {} with error: {}",
                     err_response.connection_id, err_response.network_requester_error
@@ -80,11 +129,33 @@ impl MixnetResponseListener {
                 Err(err_response.into())
             }
             Socks5ResponseContent::NetworkData(response) => {
+                self.provider_pool.record_response(response.connection_id);
                 self.controller_sender
                     .unbounded_send(response.into())
                     .unwrap();
                 Ok(())
             }
+            Socks5ResponseContent::Datagram(response) => {
+                self.provider_pool.record_response(response.connection_id);
+                self.controller_sender
+                    .unbounded_send(response.into())
+                    .unwrap();
+                Ok(())
+            }
+            Socks5ResponseContent::QuotaExceeded(quota_exceeded) => {
+                self.provider_pool
+                    .record_response(quota_exceeded.connection_id);
+                error!("Network requester rejected the request: {quota_exceeded}");
+                // the request is never going to be served, so close the local side of the connection
+                self.controller_sender
+                    .unbounded_send(
+                        NetworkData::new_closed_empty(quota_exceeded.connection_id).into(),
+                    )
+                    .unwrap();
+                Err(Socks5ClientCoreError::NetworkRequesterQuotaExceeded(
+                    quota_exceeded,
+                ))
+            }
         }
     }
 
@@ -6,10 +6,15 @@ use self::types::SocksProxyError;
 
 pub mod authentication;
 pub(crate) mod client;
+mod exit_policy;
+mod http;
+mod isolation;
 pub(crate) mod mixnet_responses;
+mod provider_pool;
 mod request;
 pub mod server;
 pub mod types;
+mod udp;
 pub mod utils;
 
 /// Version of socks
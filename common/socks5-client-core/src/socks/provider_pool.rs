@@ -0,0 +1,271 @@
+use nym_socks5_requests::ConnectionId;
+use nym_sphinx::addressing::clients::Recipient;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Number of consecutive failed connections after which the provider is considered unhealthy.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// Amount of time after which an unhealthy provider is given another chance.
+const UNHEALTHY_PROVIDER_RETRY: Duration = Duration::from_secs(60);
+
+/// Minimum amount of time the connection has to wait for the first response before its closure
+/// counts against the provider. It prevents connections abandoned by the local applications
+/// straight away from being mistaken for the failures of the provider.
+const MINIMUM_RESPONSE_WAIT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+struct ProviderHealth {
+    address: Recipient,
+    consecutive_failures: u32,
+    last_failure: Option<Instant>,
+}
+
+impl ProviderHealth {
+    fn new(address: Recipient) -> Self {
+        ProviderHealth {
+            address,
+            consecutive_failures: 0,
+            last_failure: None,
+        }
+    }
+
+    fn is_healthy(&self, now: Instant) -> bool {
+        if self.consecutive_failures < MAX_CONSECUTIVE_FAILURES {
+            return true;
+        }
+        match self.last_failure {
+            Some(last_failure) => {
+                now.saturating_duration_since(last_failure) >= UNHEALTHY_PROVIDER_RETRY
+            }
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TrackedConnection {
+    provider: usize,
+    started_at: Instant,
+}
+
+#[derive(Debug)]
+struct ProviderPoolInner {
+    providers: Vec<ProviderHealth>,
+    next: usize,
+    connections: HashMap<ConnectionId, TrackedConnection>,
+}
+
+/// Network requesters the connections are sent to.
+///
+/// If there's only a single provider, all connections go to it. Otherwise each new connection
+/// picks the next healthy provider in a round-robin fashion. A provider becomes unhealthy
+/// once several consecutive connections sent through it got closed without it ever responding.
+#[derive(Debug, Clone)]
+pub(crate) struct ProviderPool {
+    inner: Arc<Mutex<ProviderPoolInner>>,
+}
+
+impl ProviderPool {
+    pub(crate) fn new(providers: Vec<Recipient>) -> Self {
+        assert!(
+            !providers.is_empty(),
+            "at least a single service provider must be specified"
+        );
+
+        ProviderPool {
+            inner: Arc::new(Mutex::new(ProviderPoolInner {
+                providers: providers.into_iter().map(ProviderHealth::new).collect(),
+                next: 0,
+                connections: HashMap::new(),
+            })),
+        }
+    }
+
+    pub(crate) fn is_single(&self) -> bool {
+        self.inner
+            .lock()
+            .expect("provider pool lock got poisoned")
+            .providers
+            .len()
+            == 1
+    }
+
+    /// Chooses the provider for a new connection.
+    /// If none of them is healthy, the next one is used regardless.
+    pub(crate) fn choose_provider(&self) -> Recipient {
+        self.choose_provider_at(Instant::now())
+    }
+
+    fn choose_provider_at(&self, now: Instant) -> Recipient {
+        let mut inner = self.inner.lock().expect("provider pool lock got poisoned");
+        let providers = inner.providers.len();
+
+        let chosen = (0..providers)
+            .map(|offset| (inner.next + offset) % providers)
+            .find(|index| inner.providers[*index].is_healthy(now))
+            .unwrap_or(inner.next % providers);
+
+        inner.next = (chosen + 1) % providers;
+        inner.providers[chosen].address
+    }
+
+    /// Starts tracking the connection sent through the specified provider.
+    pub(crate) fn track_connection(&self, connection_id: ConnectionId, provider: Recipient) {
+        self.track_connection_at(connection_id, provider, Instant::now())
+    }
+
+    fn track_connection_at(&self, connection_id: ConnectionId, provider: Recipient, now: Instant) {
+        let mut inner = self.inner.lock().expect("provider pool lock got poisoned");
+        // with a single provider there are no decisions to be made based on its health
+        if inner.providers.len() == 1 {
+            return;
+        }
+
+        let Some(provider) = inner
+            .providers
+            .iter()
+            .position(|health| health.address == provider)
+        else {
+            return;
+        };
+        inner.connections.insert(
+            connection_id,
+            TrackedConnection {
+                provider,
+                started_at: now,
+            },
+        );
+    }
+
+    /// Records any response received on the connection, which proves its provider is alive.
+    pub(crate) fn record_response(&self, connection_id: ConnectionId) {
+        let mut inner = self.inner.lock().expect("provider pool lock got poisoned");
+        let Some(connection) = inner.connections.remove(&connection_id) else {
+            return;
+        };
+
+        let provider = &mut inner.providers[connection.provider];
+        provider.consecutive_failures = 0;
+        provider.last_failure = None;
+    }
+
+    /// Stops tracking the connection. If the provider has never responded on it,
+    /// it counts as a failure.
+    pub(crate) fn connection_closed(&self, connection_id: ConnectionId) {
+        self.connection_closed_at(connection_id, Instant::now())
+    }
+
+    fn connection_closed_at(&self, connection_id: ConnectionId, now: Instant) {
+        let mut inner = self.inner.lock().expect("provider pool lock got poisoned");
+        let Some(connection) = inner.connections.remove(&connection_id) else {
+            return;
+        };
+        if now.saturating_duration_since(connection.started_at) < MINIMUM_RESPONSE_WAIT {
+            return;
+        }
+
+        let provider = &mut inner.providers[connection.provider];
+        provider.consecutive_failures += 1;
+        provider.last_failure = Some(now);
+        if provider.consecutive_failures == MAX_CONSECUTIVE_FAILURES {
+            log::warn!(
+                "service provider {} has not responded on {} consecutive connections. \
+                it's not going to be used for a while",
+                provider.address,
+                MAX_CONSECUTIVE_FAILURES
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROVIDERS: [&str; 3] = [
+        "3KRydEpanwjFhq5GAraVjRUF1Tno7w7oc4EwJYTGNo5J.RgZ7uMJHruBQqD5hC9Ghi3sqiTn6NycfM5qCfJz6yoM@9Byd9VAtyYMnbVAcqdoQxJnq76XEg2dbxbiF5Aa5Jj9J",
+        "AN8eLxYWFitCkMn92zim3PrPszxJZDYyFFKP7qnnAAew.8UAxL3LwQBis6WpM3GGXaqKGaVdnLCpGJWumHT6KNdTH@77TSuVU8d1oXKbPzjec2xh4i3Wj5WwUyy9Lr36sm8gZm",
+        "CytBseW6yFXUMzz4SGAKdNLGR7q3sJLLYxyBGvutNEQV.4QXYyEVc5fUDjmmi8PrHN9tdUFV4PCvSJE1278cHyvoe@4sBbL1ngf1vtNqykydQKTFh26sQCw888GpUqvPvyNB4f",
+    ];
+
+    fn providers(amount: usize) -> Vec<Recipient> {
+        PROVIDERS[..amount]
+            .iter()
+            .map(|address| Recipient::try_from_base58_string(address).unwrap())
+            .collect()
+    }
+
+    fn fail_connections(pool: &ProviderPool, provider: Recipient, now: Instant) {
+        for id in 0..MAX_CONSECUTIVE_FAILURES as u64 {
+            pool.track_connection_at(id, provider, now);
+            pool.connection_closed_at(id, now + MINIMUM_RESPONSE_WAIT);
+        }
+    }
+
+    #[test]
+    fn providers_are_rotated() {
+        let providers = providers(3);
+        let pool = ProviderPool::new(providers.clone());
+        let now = Instant::now();
+
+        for expected in providers.iter().chain(providers.iter()) {
+            assert_eq!(pool.choose_provider_at(now), *expected);
+        }
+    }
+
+    #[test]
+    fn unresponsive_providers_are_skipped_until_retry() {
+        let providers = providers(2);
+        let pool = ProviderPool::new(providers.clone());
+        let now = Instant::now();
+
+        fail_connections(&pool, providers[0], now);
+        for _ in 0..3 {
+            assert_eq!(pool.choose_provider_at(now), providers[1]);
+        }
+
+        let later = now + MINIMUM_RESPONSE_WAIT + UNHEALTHY_PROVIDER_RETRY;
+        assert_eq!(pool.choose_provider_at(later), providers[0]);
+    }
+
+    #[test]
+    fn responses_restore_provider_health() {
+        let providers = providers(2);
+        let pool = ProviderPool::new(providers.clone());
+        let now = Instant::now();
+
+        fail_connections(&pool, providers[0], now);
+        pool.track_connection_at(42, providers[0], now);
+        pool.record_response(42);
+
+        assert_eq!(pool.choose_provider_at(now), providers[0]);
+        assert_eq!(pool.choose_provider_at(now), providers[1]);
+    }
+
+    #[test]
+    fn quickly_abandoned_connections_are_not_failures() {
+        let providers = providers(2);
+        let pool = ProviderPool::new(providers.clone());
+        let now = Instant::now();
+
+        for id in 0..MAX_CONSECUTIVE_FAILURES as u64 {
+            pool.track_connection_at(id, providers[0], now);
+            pool.connection_closed_at(id, now);
+        }
+        assert_eq!(pool.choose_provider_at(now), providers[0]);
+    }
+
+    #[test]
+    fn unhealthy_pool_still_chooses_a_provider() {
+        let providers = providers(2);
+        let pool = ProviderPool::new(providers.clone());
+        let now = Instant::now();
+
+        fail_connections(&pool, providers[0], now);
+        fail_connections(&pool, providers[1], now);
+        assert_eq!(pool.choose_provider_at(now), providers[0]);
+        assert_eq!(pool.choose_provider_at(now), providers[1]);
+    }
+}
@@ -1,29 +1,40 @@
 use crate::error::Socks5ClientCoreError;
 
 use super::{
-    authentication::Authenticator, client::SocksClient, mixnet_responses::MixnetResponseListener,
+    authentication::Authenticator, client::SocksClient, exit_policy::KnownExitPolicy,
+    isolation::IsolatedApplications, mixnet_responses::MixnetResponseListener,
+    provider_pool::ProviderPool,
 };
 use crate::socks::client;
 use log::*;
 use nym_client_core::client::{
-    inbound_messages::InputMessageSender, received_buffer::ReceivedBufferRequestSender,
+    inbound_messages::{InputMessage, InputMessageSender},
+    received_buffer::ReceivedBufferRequestSender,
 };
-use nym_socks5_proxy_helpers::connection_controller::Controller;
+use nym_service_providers_common::interface::ControlRequest;
+use nym_socks5_proxy_helpers::connection_controller::{Controller, ControllerSender};
+use nym_socks5_requests::Socks5ProviderRequest;
 use nym_sphinx::addressing::clients::Recipient;
-use nym_task::connections::{ConnectionCommandSender, LaneQueueLengths};
+use nym_task::connections::{ConnectionCommandSender, LaneQueueLengths, TransmissionLane};
 use nym_task::TaskClient;
+use std::io;
 use std::net::SocketAddr;
 use tap::TapFallible;
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
 
 /// A Socks5 server that listens for connections.
+/// Optionally, it also listens for HTTP CONNECT requests which are proxied using
+/// the same connection controller.
 pub struct SphinxSocksServer {
     authenticator: Authenticator,
     listening_address: SocketAddr,
-    service_provider: Recipient,
+    http_listening_address: Option<SocketAddr>,
+    provider_pool: ProviderPool,
     self_address: Recipient,
     client_config: client::Config,
     lane_queue_lengths: LaneQueueLengths,
+    exit_policy: KnownExitPolicy,
+    isolated_applications: Option<IsolatedApplications>,
     shutdown: TaskClient,
 }
 
@@ -32,7 +43,7 @@ impl SphinxSocksServer {
     pub(crate) fn new(
         port: u16,
         authenticator: Authenticator,
-        service_provider: Recipient,
+        service_providers: Vec<Recipient>,
         self_address: Recipient,
         lane_queue_lengths: LaneQueueLengths,
         client_config: client::Config,
@@ -45,14 +56,90 @@ impl SphinxSocksServer {
         SphinxSocksServer {
             authenticator,
             listening_address: format!("{ip}:{port}").parse().unwrap(),
-            service_provider,
+            http_listening_address: None,
+            provider_pool: ProviderPool::new(service_providers),
             self_address,
             client_config,
             lane_queue_lengths,
+            exit_policy: KnownExitPolicy::default(),
+            isolated_applications: None,
             shutdown,
         }
     }
 
+    /// Additionally accept HTTP CONNECT requests on the provided address.
+    pub(crate) fn with_http_listener(mut self, http_listening_address: SocketAddr) -> Self {
+        info!("Listening for HTTP CONNECT requests on {http_listening_address}");
+        self.http_listening_address = Some(http_listening_address);
+        self
+    }
+
+    /// Isolate the anonymous connections of different local applications from each other.
+    pub(crate) fn with_stream_isolation(mut self) -> Self {
+        info!("Isolating the connections of different applications");
+        self.isolated_applications = Some(IsolatedApplications::new());
+        self
+    }
+
+    fn new_client(
+        &self,
+        stream: TcpStream,
+        input_sender: InputMessageSender,
+        controller_sender: ControllerSender,
+    ) -> SocksClient {
+        let client = SocksClient::new(
+            self.client_config,
+            stream,
+            self.authenticator.clone(),
+            input_sender,
+            self.provider_pool.clone(),
+            controller_sender,
+            &self.self_address,
+            self.lane_queue_lengths.clone(),
+            self.shutdown.clone(),
+        )
+        .with_exit_policy(self.exit_policy.clone());
+
+        match &self.isolated_applications {
+            Some(isolated_applications) => client.with_isolation(isolated_applications.clone()),
+            None => client,
+        }
+    }
+
+    /// Asks the service provider to describe itself so that we'd learn about its exit policy.
+    /// The response is handled by the `MixnetResponseListener`.
+    async fn request_provider_descriptor(&self, input_sender: &InputMessageSender) {
+        // responses don't tell us which of the providers has sent them,
+        // so we wouldn't know to which one the exit policy applies
+        if !self.provider_pool.is_single() {
+            debug!("not requesting the exit policy as multiple service providers are used");
+            return;
+        }
+
+        let provider_interface = self.client_config.provider_interface_version();
+        if !provider_interface.supports_version_negotiation() {
+            debug!("the service provider does not support publishing its exit policy");
+            return;
+        }
+
+        let request = Socks5ProviderRequest::new_control(
+            provider_interface,
+            ControlRequest::ProviderDescriptor,
+        );
+
+        // control responses can only be sent back using reply surbs
+        let input_message = InputMessage::new_anonymous(
+            self.provider_pool.choose_provider(),
+            request.into_bytes(),
+            self.client_config.connection_start_surbs(),
+            TransmissionLane::General,
+        );
+        input_sender
+            .send(input_message)
+            .await
+            .expect("InputMessageReceiver has stopped receiving!");
+    }
+
     /// Set up the listener and initiate connection handling when something
     /// connects to the server.
     pub(crate) async fn serve(
@@ -64,6 +151,14 @@ impl SphinxSocksServer {
         let listener = TcpListener::bind(self.listening_address)
             .await
             .tap_err(|err| log::error!("Failed to bind to address: {err}"))?;
+        let http_listener = match self.http_listening_address {
+            Some(address) => Some(
+                TcpListener::bind(address)
+                    .await
+                    .tap_err(|err| log::error!("Failed to bind to address: {err}"))?,
+            ),
+            None => None,
+        };
         info!("Serving Connections...");
 
         // controller for managing all active connections
@@ -80,12 +175,16 @@ impl SphinxSocksServer {
         let mut mixnet_response_listener = MixnetResponseListener::new(
             buffer_requester,
             controller_sender.clone(),
+            self.exit_policy.clone(),
+            self.provider_pool.clone(),
             self.shutdown.clone(),
         );
         tokio::spawn(async move {
             mixnet_response_listener.run().await;
         });
 
+        self.request_provider_descriptor(&input_sender).await;
+
         // TODO:, if required, there should be another task here responsible for control requests.
         // it should get `input_sender` to send actual requests into the mixnet
         // and some channel that connects it from `MixnetResponseListener` to receive
@@ -94,17 +193,8 @@ impl SphinxSocksServer {
         loop {
             tokio::select! {
                 Ok((stream, _remote)) = listener.accept() => {
-                    let mut client = SocksClient::new(
-                        self.client_config,
-                        stream,
-                        self.authenticator.clone(),
-                        input_sender.clone(),
-                        &self.service_provider,
-                        controller_sender.clone(),
-                        &self.self_address,
-                        self.lane_queue_lengths.clone(),
-                        self.shutdown.clone(),
-                    );
+                    let mut client =
+                        self.new_client(stream, input_sender.clone(), controller_sender.clone());
 
                     tokio::spawn(async move {
                         if let Err(err) = client.run().await {
@@ -118,6 +208,20 @@ impl SphinxSocksServer {
                         }
                     });
                 },
+                Ok((stream, _remote)) = accept_optional(&http_listener) => {
+                    let mut client =
+                        self.new_client(stream, input_sender.clone(), controller_sender.clone());
+
+                    tokio::spawn(async move {
+                        // any error response has already been written by the client itself
+                        if let Err(err) = client.run_http_connect().await {
+                            error!("Error! {err}");
+                            if client.shutdown().await.is_err() {
+                                warn!("Failed to shutdown TcpStream");
+                            };
+                        }
+                    });
+                },
                 _ = self.shutdown.recv() => {
                     log::trace!("SphinxSocksServer: Received shutdown");
                     log::debug!("SphinxSocksServer: Exiting");
@@ -127,3 +231,10 @@ impl SphinxSocksServer {
         }
     }
 }
+
+async fn accept_optional(listener: &Option<TcpListener>) -> io::Result<(TcpStream, SocketAddr)> {
+    match listener {
+        Some(listener) => listener.accept().await,
+        None => std::future::pending().await,
+    }
+}
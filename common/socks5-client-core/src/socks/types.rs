@@ -1,3 +1,4 @@
+use super::http::HttpRequestError;
 use nym_socks5_requests::Socks5RequestError;
 use std::string::FromUtf8Error;
 use thiserror::Error;
@@ -57,6 +58,12 @@ pub enum SocksProxyError {
         source: std::io::Error,
     },
 
+    #[error("failed to bind the UDP relay socket: {source}")]
+    UdpSocketBindFailure {
+        #[source]
+        source: std::io::Error,
+    },
+
     #[error("failed to extract ip address of the connected peer: {source}")]
     PeerAddrExtractionFailure {
         #[source]
@@ -78,6 +85,12 @@ pub enum SocksProxyError {
     #[error(transparent)]
     Socks5ResponseFailure(#[from] ResponseCodeV5),
 
+    #[error("could not handle the HTTP proxy request: {source}")]
+    HttpRequestFailure {
+        #[from]
+        source: HttpRequestError,
+    },
+
     #[error("could not complete the provider request: {source}")]
     ProviderRequestFailure {
         #[from]
@@ -0,0 +1,146 @@
+//! Encapsulation of datagrams exchanged with local applications on a SOCKS5 UDP association.
+//! As described in RFC1928 (section 7), every datagram is prefixed with the following header:
+//!
+//! +----+------+------+----------+----------+----------+
+//! |RSV | FRAG | ATYP | DST.ADDR | DST.PORT |   DATA   |
+//! +----+------+------+----------+----------+----------+
+//! | 2  |  1   |  1   | Variable |    2     | Variable |
+//! +----+------+------+----------+----------+----------+
+
+use super::types::AddrType;
+use super::utils as socks_utils;
+use std::net::SocketAddr;
+use thiserror::Error;
+
+/// Maximum size of a datagram we're willing to receive from the local application.
+pub(crate) const MAX_DATAGRAM_SIZE: usize = u16::MAX as usize;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub(crate) enum UdpHeaderError {
+    #[error("the datagram is too short to contain a valid header")]
+    TooShort,
+
+    #[error("fragmented datagrams are not supported (got fragment {fragment})")]
+    FragmentationUnsupported { fragment: u8 },
+
+    #[error("{value} is not a valid address type")]
+    UnknownAddressType { value: u8 },
+}
+
+/// Splits a datagram received from the local application into its destination
+/// (in the `host:port` form) and the actual payload.
+pub(crate) fn parse_datagram(b: &[u8]) -> Result<(String, &[u8]), UdpHeaderError> {
+    if b.len() < 4 {
+        return Err(UdpHeaderError::TooShort);
+    }
+
+    // we don't reassemble fragments, so as recommended by the RFC, anything fragmented is dropped
+    if b[2] != 0 {
+        return Err(UdpHeaderError::FragmentationUnsupported { fragment: b[2] });
+    }
+
+    let addr_type =
+        AddrType::from(b[3] as usize).ok_or(UdpHeaderError::UnknownAddressType { value: b[3] })?;
+
+    let (addr_start, addr_len) = match addr_type {
+        AddrType::V4 => (4, 4),
+        AddrType::V6 => (4, 16),
+        AddrType::Domain => (5, *b.get(4).ok_or(UdpHeaderError::TooShort)? as usize),
+    };
+    let port_start = addr_start + addr_len;
+    let data_start = port_start + 2;
+    if b.len() < data_start {
+        return Err(UdpHeaderError::TooShort);
+    }
+
+    let addr = &b[addr_start..port_start];
+    let port = u16::from_be_bytes([b[port_start], b[port_start + 1]]);
+    let host = socks_utils::pretty_print_addr(&addr_type, addr);
+    let remote_address = match addr_type {
+        // make sure the port can be unambiguously told apart from the address itself
+        AddrType::V6 => format!("[{host}]:{port}"),
+        _ => format!("{host}:{port}"),
+    };
+
+    Ok((remote_address, &b[data_start..]))
+}
+
+/// Encodes the socket address as `ATYP || ADDR || PORT`
+pub(crate) fn encode_socket_address(address: &SocketAddr) -> Vec<u8> {
+    let (addr_type, addr) = match address {
+        SocketAddr::V4(addr) => (AddrType::V4, addr.ip().octets().to_vec()),
+        SocketAddr::V6(addr) => (AddrType::V6, addr.ip().octets().to_vec()),
+    };
+
+    std::iter::once(addr_type as u8)
+        .chain(addr.into_iter())
+        .chain(address.port().to_be_bytes().into_iter())
+        .collect()
+}
+
+/// Prepends the header to a datagram received from `remote_address` so that it could be
+/// forwarded to the local application.
+pub(crate) fn encode_datagram(remote_address: &str, data: &[u8]) -> Vec<u8> {
+    let encoded_address = if let Ok(address) = remote_address.parse::<SocketAddr>() {
+        encode_socket_address(&address)
+    } else {
+        let (host, port) = remote_address
+            .rsplit_once(':')
+            .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+            .unwrap_or((remote_address, 0));
+        let host = &host.as_bytes()[..host.len().min(u8::MAX as usize)];
+
+        std::iter::once(AddrType::Domain as u8)
+            .chain(std::iter::once(host.len() as u8))
+            .chain(host.iter().copied())
+            .chain(port.to_be_bytes().into_iter())
+            .collect()
+    };
+
+    [0, 0, 0]
+        .into_iter()
+        .chain(encoded_address.into_iter())
+        .chain(data.iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn datagrams_survive_encoding() {
+        let encoded = encode_datagram("1.1.1.1:53", &[1, 2, 3]);
+        assert_eq!(encoded, vec![0, 0, 0, 1, 1, 1, 1, 1, 0, 53, 1, 2, 3],);
+        let (remote, data) = parse_datagram(&encoded).unwrap();
+        assert_eq!(remote, "1.1.1.1:53");
+        assert_eq!(data, &[1, 2, 3]);
+
+        let encoded = encode_datagram("[2606:4700:4700::1111]:443", &[4, 5]);
+        let (remote, data) = parse_datagram(&encoded).unwrap();
+        assert_eq!(remote, "[2606:4700:4700:0:0:0:0:1111]:443");
+        assert_eq!(data, &[4, 5]);
+
+        let encoded = encode_datagram("nymtech.net:123", &[]);
+        let (remote, data) = parse_datagram(&encoded).unwrap();
+        assert_eq!(remote, "nymtech.net:123");
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn malformed_headers_are_rejected() {
+        assert_eq!(parse_datagram(&[0, 0, 0]), Err(UdpHeaderError::TooShort));
+        assert_eq!(
+            parse_datagram(&[0, 0, 1, 1, 1, 1, 1, 1, 0, 53]),
+            Err(UdpHeaderError::FragmentationUnsupported { fragment: 1 })
+        );
+        assert_eq!(
+            parse_datagram(&[0, 0, 0, 2, 1, 1, 1, 1, 0, 53]),
+            Err(UdpHeaderError::UnknownAddressType { value: 2 })
+        );
+        assert_eq!(
+            parse_datagram(&[0, 0, 0, 3, 10, 102, 111, 111]),
+            Err(UdpHeaderError::TooShort)
+        );
+    }
+}
@@ -1,11 +1,14 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::datagram::{DatagramMessage, DatagramSender, ReplayWindow};
 use futures::channel::mpsc;
 use futures::StreamExt;
 use log::*;
 use nym_ordered_buffer::{OrderedMessage, OrderedMessageBuffer, ReadContiguousData};
-use nym_socks5_requests::{ConnectionId, NetworkData, SendRequest};
+use nym_socks5_requests::{
+    ConnectionId, DatagramData, NetworkData, RemoteAddress, SendDatagramRequest, SendRequest,
+};
 use nym_task::connections::{ConnectionCommand, ConnectionCommandSender};
 use nym_task::TaskClient;
 use std::collections::{HashMap, HashSet};
@@ -36,6 +39,10 @@ pub enum ControllerCommand {
         connection_id: ConnectionId,
         connection_sender: ConnectionSender,
     },
+    InsertAssociation {
+        connection_id: ConnectionId,
+        datagram_sender: DatagramSender,
+    },
     Remove {
         connection_id: ConnectionId,
     },
@@ -44,6 +51,12 @@ pub enum ControllerCommand {
         data: Vec<u8>,
         is_closed: bool,
     },
+    SendDatagram {
+        connection_id: ConnectionId,
+        sequence: u64,
+        remote_addr: RemoteAddress,
+        data: Vec<u8>,
+    },
 }
 
 impl From<NetworkData> for ControllerCommand {
@@ -66,6 +79,33 @@ impl From<SendRequest> for ControllerCommand {
     }
 }
 
+impl From<DatagramData> for ControllerCommand {
+    fn from(value: DatagramData) -> Self {
+        ControllerCommand::SendDatagram {
+            connection_id: value.connection_id,
+            sequence: value.sequence,
+            remote_addr: value.remote_addr,
+            data: value.data,
+        }
+    }
+}
+
+impl From<SendDatagramRequest> for ControllerCommand {
+    fn from(value: SendDatagramRequest) -> Self {
+        ControllerCommand::SendDatagram {
+            connection_id: value.conn_id,
+            sequence: value.sequence,
+            remote_addr: value.remote_addr,
+            data: value.data,
+        }
+    }
+}
+
+struct ActiveAssociation {
+    datagram_sender: DatagramSender,
+    replay_window: ReplayWindow,
+}
+
 struct ActiveConnection {
     is_closed: bool,
     closed_at_index: Option<u64>,
@@ -103,6 +143,9 @@ pub enum BroadcastActiveConnections {
 /// proxy.
 pub struct Controller {
     active_connections: HashMap<ConnectionId, ActiveConnection>,
+    // UDP associations do not use the ordered buffer (nor the pending messages),
+    // any datagram that arrives for an unknown association is simply dropped
+    active_associations: HashMap<ConnectionId, ActiveAssociation>,
     receiver: ControllerReceiver,
 
     // TODO: this will need to be either completely removed (from code) or periodically cleaned
@@ -131,6 +174,7 @@ impl Controller {
         (
             Controller {
                 active_connections: HashMap::new(),
+                active_associations: HashMap::new(),
                 receiver,
                 recently_closed: HashSet::new(),
                 client_connection_tx,
@@ -161,9 +205,24 @@ impl Controller {
         }
     }
 
+    fn insert_association(&mut self, conn_id: ConnectionId, datagram_sender: DatagramSender) {
+        let active_association = ActiveAssociation {
+            datagram_sender,
+            replay_window: ReplayWindow::default(),
+        };
+        if self
+            .active_associations
+            .insert(conn_id, active_association)
+            .is_some()
+        {
+            error!("Received a duplicate 'UdpAssociate'!")
+        }
+    }
+
     fn remove_connection(&mut self, conn_id: ConnectionId) {
         debug!("Removing {} from controller", conn_id);
-        if self.active_connections.remove(&conn_id).is_none() {
+        let was_association = self.active_associations.remove(&conn_id).is_some();
+        if !was_association && self.active_connections.remove(&conn_id).is_none() {
             error!(
                 "tried to remove non-existing connection with id: {:?}",
                 conn_id
@@ -239,6 +298,34 @@ impl Controller {
         }
     }
 
+    fn send_to_association(
+        &mut self,
+        conn_id: ConnectionId,
+        sequence: u64,
+        remote_addr: RemoteAddress,
+        data: Vec<u8>,
+    ) {
+        let Some(active_association) = self.active_associations.get_mut(&conn_id) else {
+            debug!(
+                "Received a datagram for unknown association {conn_id} ({} bytes were dropped)",
+                data.len()
+            );
+            return;
+        };
+
+        if !active_association.replay_window.accept(sequence) {
+            trace!("Dropping stale or duplicate datagram {sequence} on association {conn_id}");
+            return;
+        }
+
+        if let Err(err) = active_association
+            .datagram_sender
+            .unbounded_send(DatagramMessage { remote_addr, data })
+        {
+            debug!("Failed to forward datagram to association {conn_id}: {err}");
+        }
+    }
+
     pub async fn run(&mut self) {
         loop {
             tokio::select! {
@@ -249,6 +336,12 @@ impl Controller {
                     Some(ControllerCommand::Insert{connection_id, connection_sender}) => {
                         self.insert_connection(connection_id, connection_sender)
                     }
+                    Some(ControllerCommand::InsertAssociation{connection_id, datagram_sender}) => {
+                        self.insert_association(connection_id, datagram_sender)
+                    }
+                    Some(ControllerCommand::SendDatagram{connection_id, sequence, remote_addr, data}) => {
+                        self.send_to_association(connection_id, sequence, remote_addr, data)
+                    }
                     Some(ControllerCommand::Remove{ connection_id }) => self.remove_connection(connection_id),
                     None => {
                         log::trace!("SOCKS5 Controller: Stopping since channel closed");
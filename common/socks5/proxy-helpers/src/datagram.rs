@@ -0,0 +1,100 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::channel::mpsc;
+use nym_socks5_requests::RemoteAddress;
+
+/// Size of the window (in sequence numbers) below the highest seen sequence within which
+/// late datagrams are still going to be accepted.
+const REPLAY_WINDOW_SIZE: u64 = 64;
+
+/// A single datagram received from the mix network on a UDP association alongside
+/// the address of the remote it is either destined for or came from.
+#[derive(Debug)]
+pub struct DatagramMessage {
+    pub remote_addr: RemoteAddress,
+    pub data: Vec<u8>,
+}
+
+/// Channel responsible for sending datagrams that were received from mix network into particular
+/// UDP association.
+pub type DatagramSender = mpsc::UnboundedSender<DatagramMessage>;
+
+/// Receiver part of the [`DatagramSender`]
+pub type DatagramReceiver = mpsc::UnboundedReceiver<DatagramMessage>;
+
+/// Relaxed reliability filter used for UDP associations. Unlike the ordered buffer used
+/// for TCP streams it never holds any data back: datagrams are delivered as soon as they arrive,
+/// in whatever order, while duplicates (for example caused by retransmissions)
+/// and datagrams that are too old are dropped.
+#[derive(Debug, Default)]
+pub(crate) struct ReplayWindow {
+    highest: Option<u64>,
+    // bit `n` is set if `highest - n` has already been seen
+    seen: u64,
+}
+
+impl ReplayWindow {
+    pub(crate) fn accept(&mut self, sequence: u64) -> bool {
+        let Some(highest) = self.highest else {
+            self.highest = Some(sequence);
+            self.seen = 1;
+            return true;
+        };
+
+        if sequence > highest {
+            let shift = sequence - highest;
+            self.seen = if shift >= REPLAY_WINDOW_SIZE {
+                0
+            } else {
+                self.seen << shift
+            };
+            self.seen |= 1;
+            self.highest = Some(sequence);
+            return true;
+        }
+
+        let offset = highest - sequence;
+        if offset >= REPLAY_WINDOW_SIZE {
+            return false;
+        }
+
+        let mask = 1 << offset;
+        if self.seen & mask != 0 {
+            return false;
+        }
+        self.seen |= mask;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_reordered_datagrams_but_not_duplicates() {
+        let mut window = ReplayWindow::default();
+        assert!(window.accept(5));
+        assert!(window.accept(7));
+        assert!(window.accept(6));
+        assert!(window.accept(3));
+
+        assert!(!window.accept(5));
+        assert!(!window.accept(6));
+        assert!(!window.accept(7));
+    }
+
+    #[test]
+    fn drops_datagrams_outside_the_window() {
+        let mut window = ReplayWindow::default();
+        assert!(window.accept(1));
+        assert!(window.accept(1 + REPLAY_WINDOW_SIZE));
+        assert!(!window.accept(1));
+        assert!(window.accept(2));
+
+        assert!(window.accept(1000));
+        assert!(!window.accept(2 + REPLAY_WINDOW_SIZE));
+        assert!(window.accept(999));
+    }
+}
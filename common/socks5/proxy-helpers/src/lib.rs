@@ -3,4 +3,5 @@
 
 pub mod available_reader;
 pub mod connection_controller;
+pub mod datagram;
 pub mod proxy_runner;
@@ -15,6 +15,8 @@ pub type RemoteAddress = String;
 pub enum RequestFlag {
     Connect = 0,
     Send = 1,
+    UdpAssociate = 2,
+    SendDatagram = 3,
 }
 
 impl TryFrom<u8> for RequestFlag {
@@ -24,6 +26,8 @@ impl TryFrom<u8> for RequestFlag {
         match value {
             _ if value == (RequestFlag::Connect as u8) => Ok(Self::Connect),
             _ if value == (RequestFlag::Send as u8) => Ok(Self::Send),
+            _ if value == (RequestFlag::UdpAssociate as u8) => Ok(Self::UdpAssociate),
+            _ if value == (RequestFlag::SendDatagram as u8) => Ok(Self::SendDatagram),
             value => Err(RequestDeserializationError::UnknownRequestFlag { value }),
         }
     }
@@ -40,6 +44,9 @@ pub enum RequestDeserializationError {
     #[error("not enough bytes to recover the connection id")]
     ConnectionIdTooShort,
 
+    #[error("not enough bytes to recover the datagram sequence number")]
+    SequenceTooShort,
+
     #[error("no data provided")]
     NoData,
 
@@ -74,6 +81,20 @@ pub struct SendRequest {
     pub local_closed: bool,
 }
 
+#[derive(Debug, Clone)]
+pub struct UdpAssociateRequest {
+    pub conn_id: ConnectionId,
+    pub return_address: Option<Recipient>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SendDatagramRequest {
+    pub conn_id: ConnectionId,
+    pub sequence: u64,
+    pub remote_addr: RemoteAddress,
+    pub data: Vec<u8>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Socks5Request {
     pub protocol_version: Socks5ProtocolVersion,
@@ -166,6 +187,30 @@ impl Socks5Request {
             content: Socks5RequestContent::new_send(conn_id, data, local_closed),
         }
     }
+
+    pub fn new_udp_associate(
+        protocol_version: Socks5ProtocolVersion,
+        conn_id: ConnectionId,
+        return_address: Option<Recipient>,
+    ) -> Socks5Request {
+        Socks5Request {
+            protocol_version,
+            content: Socks5RequestContent::new_udp_associate(conn_id, return_address),
+        }
+    }
+
+    pub fn new_send_datagram(
+        protocol_version: Socks5ProtocolVersion,
+        conn_id: ConnectionId,
+        sequence: u64,
+        remote_addr: RemoteAddress,
+        data: Vec<u8>,
+    ) -> Socks5Request {
+        Socks5Request {
+            protocol_version,
+            content: Socks5RequestContent::new_send_datagram(conn_id, sequence, remote_addr, data),
+        }
+    }
 }
 
 /// A request from a SOCKS5 client that a Nym Socks5 service provider should
@@ -179,6 +224,14 @@ pub enum Socks5RequestContent {
 
     /// Re-use an existing TCP connection, sending more request data up it.
     Send(SendRequest),
+
+    /// Open a new UDP association identified by the `ConnectionId`.
+    /// All datagrams received on it should come back to the specified `Recipient`
+    UdpAssociate(UdpAssociateRequest),
+
+    /// Send a single datagram to the specified `RemoteAddress` over an existing UDP association.
+    /// Datagrams carry their own sequence numbers and are neither ordered nor buffered.
+    SendDatagram(SendDatagramRequest),
 }
 
 impl Socks5RequestContent {
@@ -208,6 +261,32 @@ impl Socks5RequestContent {
         })
     }
 
+    /// Construct a new Request::UdpAssociate instance
+    pub fn new_udp_associate(
+        conn_id: ConnectionId,
+        return_address: Option<Recipient>,
+    ) -> Socks5RequestContent {
+        Socks5RequestContent::UdpAssociate(UdpAssociateRequest {
+            conn_id,
+            return_address,
+        })
+    }
+
+    /// Construct a new Request::SendDatagram instance
+    pub fn new_send_datagram(
+        conn_id: ConnectionId,
+        sequence: u64,
+        remote_addr: RemoteAddress,
+        data: Vec<u8>,
+    ) -> Socks5RequestContent {
+        Socks5RequestContent::SendDatagram(SendDatagramRequest {
+            conn_id,
+            sequence,
+            remote_addr,
+            data,
+        })
+    }
+
     /// Deserialize the request type, connection id, destination address and port,
     /// and the request body from bytes.
     ///
@@ -234,43 +313,8 @@ impl Socks5RequestContent {
         let conn_id = u64::from_be_bytes([b[1], b[2], b[3], b[4], b[5], b[6], b[7], b[8]]);
         match RequestFlag::try_from(b[0])? {
             RequestFlag::Connect => {
-                let connect_request_bytes = &b[9..];
-
-                // we need to be able to read at least 2 bytes that specify address length
-                if connect_request_bytes.len() < 2 {
-                    return Err(RequestDeserializationError::AddressLengthTooShort);
-                }
-
-                let address_length =
-                    u16::from_be_bytes([connect_request_bytes[0], connect_request_bytes[1]])
-                        as usize;
-
-                if connect_request_bytes.len() < 2 + address_length {
-                    return Err(RequestDeserializationError::AddressTooShort);
-                }
-
-                let address_start = 2;
-                let address_end = address_start + address_length;
-                let address_bytes = &connect_request_bytes[address_start..address_end];
-                let remote_address = String::from_utf8_lossy(address_bytes).to_string();
-
-                // just a temporary reference to mid-slice for ease of use
-                let recipient_data_bytes = &connect_request_bytes[address_end..];
-
-                let return_address = if recipient_data_bytes.is_empty() {
-                    None
-                } else {
-                    if recipient_data_bytes.len() != Recipient::LEN {
-                        return Err(RequestDeserializationError::ReturnAddressTooShort);
-                    }
-
-                    let mut return_bytes = [0u8; Recipient::LEN];
-                    return_bytes.copy_from_slice(&recipient_data_bytes[..Recipient::LEN]);
-                    Some(
-                        Recipient::try_from_bytes(return_bytes)
-                            .map_err(RequestDeserializationError::MalformedReturnAddress)?,
-                    )
-                };
+                let (remote_address, recipient_data_bytes) = read_remote_address(&b[9..])?;
+                let return_address = read_return_address(recipient_data_bytes)?;
 
                 Ok(Socks5RequestContent::new_connect(
                     conn_id,
@@ -288,6 +332,31 @@ impl Socks5RequestContent {
                     local_closed,
                 }))
             }
+            RequestFlag::UdpAssociate => {
+                let return_address = read_return_address(&b[9..])?;
+
+                Ok(Socks5RequestContent::new_udp_associate(
+                    conn_id,
+                    return_address,
+                ))
+            }
+            RequestFlag::SendDatagram => {
+                if b.len() < 17 {
+                    return Err(RequestDeserializationError::SequenceTooShort);
+                }
+                let mut sequence_bytes = [0u8; 8];
+                sequence_bytes.copy_from_slice(&b[9..17]);
+                let sequence = u64::from_be_bytes(sequence_bytes);
+
+                let (remote_address, data) = read_remote_address(&b[17..])?;
+
+                Ok(Socks5RequestContent::new_send_datagram(
+                    conn_id,
+                    sequence,
+                    remote_address,
+                    data.to_vec(),
+                ))
+            }
         }
     }
 
@@ -317,10 +386,71 @@ impl Socks5RequestContent {
                 .chain(std::iter::once(req.local_closed as u8))
                 .chain(req.data.into_iter())
                 .collect(),
+            // associate is: ASSOCIATE_FLAG || CONN_ID || RETURN
+            Socks5RequestContent::UdpAssociate(req) => {
+                let iter = std::iter::once(RequestFlag::UdpAssociate as u8)
+                    .chain(req.conn_id.to_be_bytes().into_iter());
+
+                if let Some(return_address) = req.return_address {
+                    iter.chain(return_address.to_bytes().into_iter()).collect()
+                } else {
+                    iter.collect()
+                }
+            }
+            // datagram is: DATAGRAM_FLAG || CONN_ID || SEQUENCE || REMOTE_LEN || REMOTE || DATA
+            Socks5RequestContent::SendDatagram(req) => {
+                let remote_address_bytes = req.remote_addr.into_bytes();
+                let remote_address_bytes_len = remote_address_bytes.len() as u16;
+
+                std::iter::once(RequestFlag::SendDatagram as u8)
+                    .chain(req.conn_id.to_be_bytes().into_iter())
+                    .chain(req.sequence.to_be_bytes().into_iter())
+                    .chain(remote_address_bytes_len.to_be_bytes().into_iter())
+                    .chain(remote_address_bytes.into_iter())
+                    .chain(req.data.into_iter())
+                    .collect()
+            }
         }
     }
 }
 
+// reads `REMOTE_LEN || REMOTE` returning the address alongside any remaining bytes
+fn read_remote_address(b: &[u8]) -> Result<(RemoteAddress, &[u8]), RequestDeserializationError> {
+    // we need to be able to read at least 2 bytes that specify address length
+    if b.len() < 2 {
+        return Err(RequestDeserializationError::AddressLengthTooShort);
+    }
+
+    let address_length = u16::from_be_bytes([b[0], b[1]]) as usize;
+
+    if b.len() < 2 + address_length {
+        return Err(RequestDeserializationError::AddressTooShort);
+    }
+
+    let address_start = 2;
+    let address_end = address_start + address_length;
+    let address_bytes = &b[address_start..address_end];
+    let remote_address = String::from_utf8_lossy(address_bytes).to_string();
+
+    Ok((remote_address, &b[address_end..]))
+}
+
+fn read_return_address(b: &[u8]) -> Result<Option<Recipient>, RequestDeserializationError> {
+    if b.is_empty() {
+        return Ok(None);
+    }
+
+    if b.len() != Recipient::LEN {
+        return Err(RequestDeserializationError::ReturnAddressTooShort);
+    }
+
+    let mut return_bytes = [0u8; Recipient::LEN];
+    return_bytes.copy_from_slice(&b[..Recipient::LEN]);
+    Recipient::try_from_bytes(return_bytes)
+        .map(Some)
+        .map_err(RequestDeserializationError::MalformedReturnAddress)
+}
+
 #[cfg(test)]
 mod request_deserialization_tests {
     use super::*;
@@ -611,4 +741,78 @@ mod request_deserialization_tests {
             }
         }
     }
+
+    #[cfg(test)]
+    mod udp_associations {
+        use super::*;
+
+        #[test]
+        fn associate_request_can_be_recovered_with_and_without_return_address() {
+            let recipient = Recipient::try_from_base58_string("CytBseW6yFXUMzz4SGAKdNLGR7q3sJLLYxyBGvutNEQV.4QXYyEVc5fUDjmmi8PrHN9tdUFV4PCvSJE1278cHyvoe@4sBbL1ngf1vtNqykydQKTFh26sQCw888GpUqvPvyNB4f").unwrap();
+
+            let bytes = Socks5RequestContent::new_udp_associate(42, Some(recipient)).into_bytes();
+            match Socks5RequestContent::try_from_bytes(&bytes).unwrap() {
+                Socks5RequestContent::UdpAssociate(req) => {
+                    assert_eq!(42, req.conn_id);
+                    assert_eq!(
+                        req.return_address.unwrap().to_bytes().to_vec(),
+                        recipient.to_bytes().to_vec()
+                    );
+                }
+                _ => unreachable!(),
+            }
+
+            let bytes = Socks5RequestContent::new_udp_associate(42, None).into_bytes();
+            match Socks5RequestContent::try_from_bytes(&bytes).unwrap() {
+                Socks5RequestContent::UdpAssociate(req) => {
+                    assert_eq!(42, req.conn_id);
+                    assert!(req.return_address.is_none());
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        #[test]
+        fn datagram_request_can_be_recovered() {
+            let bytes = Socks5RequestContent::new_send_datagram(
+                42,
+                7,
+                "1.1.1.1:53".to_string(),
+                vec![1, 2, 3],
+            )
+            .into_bytes();
+
+            match Socks5RequestContent::try_from_bytes(&bytes).unwrap() {
+                Socks5RequestContent::SendDatagram(req) => {
+                    assert_eq!(42, req.conn_id);
+                    assert_eq!(7, req.sequence);
+                    assert_eq!("1.1.1.1:53", req.remote_addr);
+                    assert_eq!(vec![1, 2, 3], req.data);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        #[test]
+        fn datagram_request_without_sequence_is_rejected() {
+            let request_bytes = [
+                RequestFlag::SendDatagram as u8,
+                1,
+                2,
+                3,
+                4,
+                5,
+                6,
+                7,
+                8,
+                0,
+                0,
+            ]
+            .to_vec();
+            match Socks5RequestContent::try_from_bytes(&request_bytes).unwrap_err() {
+                RequestDeserializationError::SequenceTooShort => {}
+                _ => unreachable!(),
+            }
+        }
+    }
 }
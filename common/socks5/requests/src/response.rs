@@ -1,8 +1,9 @@
 // Copyright 2020-2023 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{ConnectionId, Socks5ProtocolVersion, Socks5RequestError};
+use crate::{ConnectionId, RemoteAddress, Socks5ProtocolVersion, Socks5RequestError};
 use nym_service_providers_common::interface::{Serializable, ServiceProviderResponse};
+use std::fmt::{Display, Formatter};
 use thiserror::Error;
 
 // don't start tags from 0 for easier backwards compatibility since `NetworkData`
@@ -13,6 +14,8 @@ use thiserror::Error;
 pub enum ResponseFlag {
     NetworkData = 1,
     ConnectionError = 2,
+    Datagram = 3,
+    QuotaExceeded = 4,
 }
 
 impl TryFrom<u8> for ResponseFlag {
@@ -22,6 +25,8 @@ impl TryFrom<u8> for ResponseFlag {
         match value {
             _ if value == (ResponseFlag::NetworkData as u8) => Ok(Self::NetworkData),
             _ if value == (ResponseFlag::ConnectionError as u8) => Ok(Self::ConnectionError),
+            _ if value == (ResponseFlag::Datagram as u8) => Ok(Self::Datagram),
+            _ if value == (ResponseFlag::QuotaExceeded as u8) => Ok(Self::QuotaExceeded),
             value => Err(ResponseDeserializationError::UnknownResponseFlag { value }),
         }
     }
@@ -32,6 +37,21 @@ pub enum ResponseDeserializationError {
     #[error("not enough bytes to recover the connection id")]
     ConnectionIdTooShort,
 
+    #[error("not enough bytes to recover the datagram sequence number")]
+    SequenceTooShort,
+
+    #[error("not enough bytes to recover the length of the address")]
+    AddressLengthTooShort,
+
+    #[error("not enough bytes to recover the address")]
+    AddressTooShort,
+
+    #[error("not enough bytes to recover the exceeded quota")]
+    QuotaTooShort,
+
+    #[error("{value} is not a valid quota kind")]
+    UnknownQuotaKind { value: u8 },
+
     #[error("{value} is not a valid response flag")]
     UnknownResponseFlag { value: u8 },
 
@@ -135,12 +155,44 @@ impl Socks5Response {
             content: Socks5ResponseContent::new_connection_error(connection_id, error_message),
         }
     }
+
+    pub fn new_datagram(
+        protocol_version: Socks5ProtocolVersion,
+        connection_id: ConnectionId,
+        sequence: u64,
+        remote_addr: RemoteAddress,
+        data: Vec<u8>,
+    ) -> Socks5Response {
+        Socks5Response {
+            protocol_version,
+            content: Socks5ResponseContent::new_datagram(
+                connection_id,
+                sequence,
+                remote_addr,
+                data,
+            ),
+        }
+    }
+
+    pub fn new_quota_exceeded(
+        protocol_version: Socks5ProtocolVersion,
+        connection_id: ConnectionId,
+        quota: QuotaKind,
+        limit: u64,
+    ) -> Socks5Response {
+        Socks5Response {
+            protocol_version,
+            content: Socks5ResponseContent::new_quota_exceeded(connection_id, quota, limit),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum Socks5ResponseContent {
     NetworkData(NetworkData),
     ConnectionError(ConnectionError),
+    Datagram(DatagramData),
+    QuotaExceeded(QuotaExceeded),
 }
 
 impl Socks5ResponseContent {
@@ -163,6 +215,28 @@ impl Socks5ResponseContent {
         Socks5ResponseContent::ConnectionError(ConnectionError::new(connection_id, error_message))
     }
 
+    pub fn new_datagram(
+        connection_id: ConnectionId,
+        sequence: u64,
+        remote_addr: RemoteAddress,
+        data: Vec<u8>,
+    ) -> Socks5ResponseContent {
+        Socks5ResponseContent::Datagram(DatagramData::new(
+            connection_id,
+            sequence,
+            remote_addr,
+            data,
+        ))
+    }
+
+    pub fn new_quota_exceeded(
+        connection_id: ConnectionId,
+        quota: QuotaKind,
+        limit: u64,
+    ) -> Socks5ResponseContent {
+        Socks5ResponseContent::QuotaExceeded(QuotaExceeded::new(connection_id, quota, limit))
+    }
+
     pub fn into_bytes(self) -> Vec<u8> {
         match self {
             Socks5ResponseContent::NetworkData(res) => {
@@ -175,6 +249,14 @@ impl Socks5ResponseContent {
                     .chain(res.into_bytes().into_iter())
                     .collect()
             }
+            Socks5ResponseContent::Datagram(res) => std::iter::once(ResponseFlag::Datagram as u8)
+                .chain(res.into_bytes().into_iter())
+                .collect(),
+            Socks5ResponseContent::QuotaExceeded(res) => {
+                std::iter::once(ResponseFlag::QuotaExceeded as u8)
+                    .chain(res.into_bytes().into_iter())
+                    .collect()
+            }
         }
     }
 
@@ -193,6 +275,12 @@ impl Socks5ResponseContent {
             ResponseFlag::ConnectionError => Ok(Socks5ResponseContent::ConnectionError(
                 ConnectionError::try_from_bytes(&b[1..])?,
             )),
+            ResponseFlag::Datagram => Ok(Socks5ResponseContent::Datagram(
+                DatagramData::try_from_bytes(&b[1..])?,
+            )),
+            ResponseFlag::QuotaExceeded => Ok(Socks5ResponseContent::QuotaExceeded(
+                QuotaExceeded::try_from_bytes(&b[1..])?,
+            )),
         }
     }
 }
@@ -318,6 +406,176 @@ impl ConnectionError {
     }
 }
 
+/// A single datagram received by the Socks5 service provider on a UDP association.
+/// Unlike [`NetworkData`] it is not part of an ordered stream, instead it carries its own
+/// sequence number and the address of the remote it came from.
+#[derive(Debug)]
+pub struct DatagramData {
+    pub connection_id: ConnectionId,
+    pub sequence: u64,
+    pub remote_addr: RemoteAddress,
+    pub data: Vec<u8>,
+}
+
+impl DatagramData {
+    pub fn new(
+        connection_id: ConnectionId,
+        sequence: u64,
+        remote_addr: RemoteAddress,
+        data: Vec<u8>,
+    ) -> Self {
+        DatagramData {
+            connection_id,
+            sequence,
+            remote_addr,
+            data,
+        }
+    }
+
+    pub fn try_from_bytes(b: &[u8]) -> Result<DatagramData, ResponseDeserializationError> {
+        if b.is_empty() {
+            return Err(ResponseDeserializationError::NoData);
+        }
+
+        if b.len() < 8 {
+            return Err(ResponseDeserializationError::ConnectionIdTooShort);
+        }
+        if b.len() < 16 {
+            return Err(ResponseDeserializationError::SequenceTooShort);
+        }
+        if b.len() < 18 {
+            return Err(ResponseDeserializationError::AddressLengthTooShort);
+        }
+
+        let mut connection_id_bytes = [0u8; 8];
+        connection_id_bytes.copy_from_slice(&b[..8]);
+        let mut sequence_bytes = [0u8; 8];
+        sequence_bytes.copy_from_slice(&b[8..16]);
+
+        let address_length = u16::from_be_bytes([b[16], b[17]]) as usize;
+        let address_end = 18 + address_length;
+        if b.len() < address_end {
+            return Err(ResponseDeserializationError::AddressTooShort);
+        }
+
+        Ok(DatagramData {
+            connection_id: u64::from_be_bytes(connection_id_bytes),
+            sequence: u64::from_be_bytes(sequence_bytes),
+            remote_addr: String::from_utf8_lossy(&b[18..address_end]).to_string(),
+            data: b[address_end..].to_vec(),
+        })
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        let remote_address_bytes = self.remote_addr.into_bytes();
+        let remote_address_bytes_len = remote_address_bytes.len() as u16;
+
+        self.connection_id
+            .to_be_bytes()
+            .into_iter()
+            .chain(self.sequence.to_be_bytes().into_iter())
+            .chain(remote_address_bytes_len.to_be_bytes().into_iter())
+            .chain(remote_address_bytes.into_iter())
+            .chain(self.data.into_iter())
+            .collect()
+    }
+}
+
+/// Usage quotas the Socks5 service provider might impose on each of its clients.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuotaKind {
+    RequestsPerMinute = 0,
+    ConcurrentConnections = 1,
+    BytesPerDay = 2,
+}
+
+impl TryFrom<u8> for QuotaKind {
+    type Error = ResponseDeserializationError;
+
+    fn try_from(value: u8) -> Result<QuotaKind, ResponseDeserializationError> {
+        match value {
+            _ if value == (QuotaKind::RequestsPerMinute as u8) => Ok(Self::RequestsPerMinute),
+            _ if value == (QuotaKind::ConcurrentConnections as u8) => {
+                Ok(Self::ConcurrentConnections)
+            }
+            _ if value == (QuotaKind::BytesPerDay as u8) => Ok(Self::BytesPerDay),
+            value => Err(ResponseDeserializationError::UnknownQuotaKind { value }),
+        }
+    }
+}
+
+impl Display for QuotaKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaKind::RequestsPerMinute => write!(f, "requests per minute"),
+            QuotaKind::ConcurrentConnections => write!(f, "concurrent connections"),
+            QuotaKind::BytesPerDay => write!(f, "bytes per day"),
+        }
+    }
+}
+
+/// Rejection of the request on the specified connection due to the client exceeding one of its quotas.
+#[derive(Debug, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    pub connection_id: ConnectionId,
+    pub quota: QuotaKind,
+    pub limit: u64,
+}
+
+impl QuotaExceeded {
+    pub fn new(connection_id: ConnectionId, quota: QuotaKind, limit: u64) -> Self {
+        QuotaExceeded {
+            connection_id,
+            quota,
+            limit,
+        }
+    }
+
+    pub fn try_from_bytes(b: &[u8]) -> Result<QuotaExceeded, ResponseDeserializationError> {
+        if b.is_empty() {
+            return Err(ResponseDeserializationError::NoData);
+        }
+
+        if b.len() < 8 {
+            return Err(ResponseDeserializationError::ConnectionIdTooShort);
+        }
+        if b.len() < 17 {
+            return Err(ResponseDeserializationError::QuotaTooShort);
+        }
+
+        let mut connection_id_bytes = [0u8; 8];
+        connection_id_bytes.copy_from_slice(&b[..8]);
+        let mut limit_bytes = [0u8; 8];
+        limit_bytes.copy_from_slice(&b[9..17]);
+
+        Ok(QuotaExceeded {
+            connection_id: u64::from_be_bytes(connection_id_bytes),
+            quota: QuotaKind::try_from(b[8])?,
+            limit: u64::from_be_bytes(limit_bytes),
+        })
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.connection_id
+            .to_be_bytes()
+            .into_iter()
+            .chain(std::iter::once(self.quota as u8))
+            .chain(self.limit.to_be_bytes().into_iter())
+            .collect()
+    }
+}
+
+impl Display for QuotaExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "connection id {}: exceeded the quota of {} {}",
+            self.connection_id, self.limit, self.quota
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -414,4 +672,70 @@ mod tests {
             ));
         }
     }
+
+    #[cfg(test)]
+    mod datagram_response_serde_tests {
+        use super::*;
+
+        #[test]
+        fn simple_serde() {
+            let response = DatagramData::new(42, 123, "1.1.1.1:53".to_string(), vec![1, 2, 3]);
+            let bytes = response.into_bytes();
+            let deserialized = DatagramData::try_from_bytes(&bytes).unwrap();
+
+            assert_eq!(42, deserialized.connection_id);
+            assert_eq!(123, deserialized.sequence);
+            assert_eq!("1.1.1.1:53", deserialized.remote_addr);
+            assert_eq!(vec![1, 2, 3], deserialized.data);
+        }
+
+        #[test]
+        fn deserialization_errors() {
+            let err = DatagramData::try_from_bytes(&[]).err().unwrap();
+            assert_eq!(err, ResponseDeserializationError::NoData);
+
+            let err = DatagramData::try_from_bytes(&[1; 12]).err().unwrap();
+            assert_eq!(err, ResponseDeserializationError::SequenceTooShort);
+
+            let err = DatagramData::try_from_bytes(&[1; 17]).err().unwrap();
+            assert_eq!(err, ResponseDeserializationError::AddressLengthTooShort);
+
+            let err = DatagramData::try_from_bytes(&[1; 20]).err().unwrap();
+            assert_eq!(err, ResponseDeserializationError::AddressTooShort);
+        }
+    }
+
+    #[cfg(test)]
+    mod quota_exceeded_response_serde_tests {
+        use super::*;
+
+        #[test]
+        fn simple_serde() {
+            let response = QuotaExceeded::new(42, QuotaKind::BytesPerDay, 1_000_000);
+            let bytes = Socks5ResponseContent::QuotaExceeded(response).into_bytes();
+
+            match Socks5ResponseContent::try_from_bytes(&bytes).unwrap() {
+                Socks5ResponseContent::QuotaExceeded(deserialized) => assert_eq!(
+                    deserialized,
+                    QuotaExceeded::new(42, QuotaKind::BytesPerDay, 1_000_000)
+                ),
+                other => panic!("unexpected response {other:?}"),
+            }
+        }
+
+        #[test]
+        fn deserialization_errors() {
+            let err = QuotaExceeded::try_from_bytes(&[]).err().unwrap();
+            assert_eq!(err, ResponseDeserializationError::NoData);
+
+            let err = QuotaExceeded::try_from_bytes(&[1; 12]).err().unwrap();
+            assert_eq!(err, ResponseDeserializationError::QuotaTooShort);
+
+            let err = QuotaExceeded::try_from_bytes(&[42; 17]).err().unwrap();
+            assert_eq!(
+                err,
+                ResponseDeserializationError::UnknownQuotaKind { value: 42 }
+            );
+        }
+    }
 }
@@ -0,0 +1,297 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encryption of the individual values kept in the client storages with a key derived from
+//! a user-supplied passphrase (AES-256-GCM, with the key derived using Argon2id).
+//!
+//! Only the salt used for the key derivation alongside a known value encrypted with the derived key
+//! (so that an invalid passphrase could be detected straight away) have to be stored next to the data.
+//! Every value is bound to the place it's stored at (see [`associated_data`]), so that the encrypted
+//! values couldn't be swapped between the rows or the columns without it being detected.
+//!
+//! Standalone values, such as the individual keys kept in files, can be encrypted with [`seal`].
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use thiserror::Error;
+pub use zeroize::Zeroizing;
+
+const MEMORY_COST: u32 = 16 * 1024;
+const ITERATIONS: u32 = 3;
+const PARALLELISM: u32 = 1;
+const KEY_LEN: usize = 32;
+
+// as per Argon2 recommendation
+const SALT_LEN: usize = 16;
+
+// AES256GCM Nonce is 96 bit long.
+const NONCE_LEN: usize = 12;
+
+// value encrypted alongside the exported cipher in order to verify the provided passphrase
+const VERIFICATION_VALUE: &[u8] = b"nym-store-cipher";
+
+#[derive(Debug, Error)]
+pub enum StoreCipherError {
+    #[error("failed to derive the encryption key: {reason}")]
+    KeyDerivationFailure { reason: String },
+
+    #[error("the provided passphrase is invalid")]
+    InvalidPassphrase,
+
+    #[error("the exported store cipher is malformed")]
+    MalformedExport,
+
+    #[error("failed to encrypt the data")]
+    EncryptionFailure,
+
+    #[error("failed to decrypt the data - it's either corrupted or has been encrypted with a different key")]
+    DecryptionFailure,
+
+    #[error("the encrypted text is not valid base58: {source}")]
+    MalformedEncoding {
+        #[from]
+        source: bs58::decode::Error,
+    },
+
+    #[error("the decrypted text is not valid utf8")]
+    MalformedText,
+}
+
+/// Builds the associated data binding an encrypted value to the column of the table it's stored in
+/// and to the row it belongs to, identified by the provided (unencrypted) values.
+pub fn associated_data(table: &str, column: &str, row: &[&[u8]]) -> Vec<u8> {
+    // everything is length-prefixed so that the boundaries between the parts are unambiguous
+    let mut aad = Vec::new();
+    for part in [table.as_bytes(), column.as_bytes()].iter().chain(row) {
+        aad.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        aad.extend_from_slice(part);
+    }
+    aad
+}
+
+/// Encrypts a standalone value with a key derived from the passphrase under a freshly generated salt.
+/// The result is `salt || nonce || ciphertext`.
+pub fn seal(passphrase: &str, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, StoreCipherError> {
+    let cipher = StoreCipher::new(passphrase)?;
+    let encrypted = cipher.encrypt(plaintext, aad)?;
+    Ok(cipher.salt.iter().copied().chain(encrypted).collect())
+}
+
+/// Decrypts the value produced by [`seal`].
+pub fn open(
+    passphrase: &str,
+    sealed: &[u8],
+    aad: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, StoreCipherError> {
+    if sealed.len() < SALT_LEN {
+        return Err(StoreCipherError::DecryptionFailure);
+    }
+    let (salt, encrypted) = sealed.split_at(SALT_LEN);
+
+    // the unwrap is fine as we have just split exactly SALT_LEN bytes
+    let cipher = StoreCipher::derive(passphrase, salt.try_into().unwrap())?;
+    cipher.decrypt(encrypted, aad).map(Zeroizing::new)
+}
+
+/// Cipher used for encrypting the values kept in a particular storage.
+#[derive(Clone)]
+pub struct StoreCipher {
+    cipher: Aes256Gcm,
+    salt: [u8; SALT_LEN],
+}
+
+impl StoreCipher {
+    /// Derives a new cipher from the provided passphrase using a freshly generated salt.
+    pub fn new(passphrase: &str) -> Result<Self, StoreCipherError> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self::derive(passphrase, salt)
+    }
+
+    /// Recovers the cipher exported with [`StoreCipher::export`] from the provided passphrase.
+    /// Fails with [`StoreCipherError::InvalidPassphrase`] if it's different from the original one.
+    pub fn import(passphrase: &str, exported: &[u8]) -> Result<Self, StoreCipherError> {
+        if exported.len() < SALT_LEN {
+            return Err(StoreCipherError::MalformedExport);
+        }
+        let (salt, verification) = exported.split_at(SALT_LEN);
+
+        // the unwrap is fine as we have just split exactly SALT_LEN bytes
+        let cipher = Self::derive(passphrase, salt.try_into().unwrap())?;
+        match cipher.decrypt(verification, &[]) {
+            Ok(value) if value == VERIFICATION_VALUE => Ok(cipher),
+            _ => Err(StoreCipherError::InvalidPassphrase),
+        }
+    }
+
+    /// Exports the information required for recovering this cipher (but not the key itself)
+    /// as `salt || nonce || encrypted verification value`.
+    pub fn export(&self) -> Result<Vec<u8>, StoreCipherError> {
+        let verification = self.encrypt(VERIFICATION_VALUE, &[])?;
+        Ok(self.salt.iter().copied().chain(verification).collect())
+    }
+
+    fn derive(passphrase: &str, salt: [u8; SALT_LEN]) -> Result<Self, StoreCipherError> {
+        // this can only fail if output length is either smaller than 4 or larger than 2^32 - 1 which is not the case here
+        let params = Params::new(MEMORY_COST, ITERATIONS, PARALLELISM, Some(KEY_LEN)).unwrap();
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        argon2
+            .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+            .map_err(|err| StoreCipherError::KeyDerivationFailure {
+                reason: err.to_string(),
+            })?;
+
+        Ok(StoreCipher {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_ref())),
+            salt,
+        })
+    }
+
+    /// Encrypts the provided data, bound to the provided associated data, under a random nonce.
+    /// The result is `nonce || ciphertext`.
+    pub fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, StoreCipherError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| StoreCipherError::EncryptionFailure)?;
+
+        Ok(nonce.into_iter().chain(ciphertext).collect())
+    }
+
+    /// Decrypts the data produced by [`StoreCipher::encrypt`] with the same associated data.
+    pub fn decrypt(&self, data: &[u8], aad: &[u8]) -> Result<Vec<u8>, StoreCipherError> {
+        if data.len() < NONCE_LEN {
+            return Err(StoreCipherError::DecryptionFailure);
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+
+        self.cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| StoreCipherError::DecryptionFailure)
+    }
+
+    /// Encrypts the provided text, returning the base58 encoding of the result,
+    /// so that it could be kept in the text columns.
+    pub fn encrypt_str(&self, plaintext: &str, aad: &[u8]) -> Result<String, StoreCipherError> {
+        self.encrypt(plaintext.as_bytes(), aad)
+            .map(|encrypted| bs58::encode(encrypted).into_string())
+    }
+
+    /// Decrypts the text produced by [`StoreCipher::encrypt_str`].
+    pub fn decrypt_str(&self, encrypted: &str, aad: &[u8]) -> Result<String, StoreCipherError> {
+        let decoded = bs58::decode(encrypted).into_vec()?;
+        String::from_utf8(self.decrypt(&decoded, aad)?).map_err(|_| StoreCipherError::MalformedText)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exported_cipher_can_only_be_imported_with_the_same_passphrase() {
+        let cipher = StoreCipher::new("foomp").unwrap();
+        let exported = cipher.export().unwrap();
+        let encrypted = cipher.encrypt(b"my secret data", b"aad").unwrap();
+
+        let imported = StoreCipher::import("foomp", &exported).unwrap();
+        assert_eq!(
+            imported.decrypt(&encrypted, b"aad").unwrap(),
+            b"my secret data"
+        );
+
+        assert!(matches!(
+            StoreCipher::import("bar", &exported),
+            Err(StoreCipherError::InvalidPassphrase)
+        ));
+        assert!(matches!(
+            StoreCipher::import("foomp", &exported[..SALT_LEN - 1]),
+            Err(StoreCipherError::MalformedExport)
+        ));
+    }
+
+    #[test]
+    fn text_roundtrip() {
+        let cipher = StoreCipher::new("foomp").unwrap();
+        let encrypted = cipher.encrypt_str("serial number", b"aad").unwrap();
+        assert_ne!(encrypted, "serial number");
+        assert_eq!(
+            cipher.decrypt_str(&encrypted, b"aad").unwrap(),
+            "serial number"
+        );
+
+        // the same value is encrypted differently each time
+        assert_ne!(
+            encrypted,
+            cipher.encrypt_str("serial number", b"aad").unwrap()
+        );
+    }
+
+    #[test]
+    fn tampered_data_is_rejected() {
+        let cipher = StoreCipher::new("foomp").unwrap();
+        let mut encrypted = cipher.encrypt(b"my secret data", b"aad").unwrap();
+        let last = encrypted.len() - 1;
+        encrypted[last] ^= 1;
+        assert!(matches!(
+            cipher.decrypt(&encrypted, b"aad"),
+            Err(StoreCipherError::DecryptionFailure)
+        ));
+
+        let other = StoreCipher::new("foomp").unwrap();
+        assert!(other
+            .decrypt(&cipher.encrypt(b"my secret data", b"aad").unwrap(), b"aad")
+            .is_err());
+    }
+
+    #[test]
+    fn values_are_bound_to_their_location() {
+        let cipher = StoreCipher::new("foomp").unwrap();
+        let first_row = associated_data("table", "column", &[b"1"]);
+        let second_row = associated_data("table", "column", &[b"2"]);
+        let other_column = associated_data("table", "other", &[b"1"]);
+
+        let encrypted = cipher.encrypt(b"my secret data", &first_row).unwrap();
+        assert!(cipher.decrypt(&encrypted, &first_row).is_ok());
+        assert!(cipher.decrypt(&encrypted, &second_row).is_err());
+        assert!(cipher.decrypt(&encrypted, &other_column).is_err());
+
+        // the boundaries between the parts matter
+        assert_ne!(
+            associated_data("table", "column", &[b"12"]),
+            associated_data("table", "column", &[b"1", b"2"])
+        );
+    }
+
+    #[test]
+    fn sealed_values_can_only_be_opened_with_the_same_passphrase() {
+        let sealed = seal("foomp", b"my secret key", b"aad").unwrap();
+        assert_eq!(
+            open("foomp", &sealed, b"aad").unwrap().as_slice(),
+            b"my secret key"
+        );
+        assert!(open("bar", &sealed, b"aad").is_err());
+        assert!(open("foomp", &sealed, b"other").is_err());
+        assert!(open("foomp", &sealed[..SALT_LEN - 1], b"aad").is_err());
+    }
+}
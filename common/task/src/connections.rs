@@ -3,6 +3,7 @@
 
 use futures::channel::mpsc;
 use std::collections::HashMap;
+use std::time::Duration;
 
 pub type ConnectionId = u64;
 
@@ -37,6 +38,7 @@ impl LaneQueueLengths {
         LaneQueueLengths(std::sync::Arc::new(std::sync::Mutex::new(
             LaneQueueLengthsInner {
                 map: HashMap::new(),
+                average_sending_delay: Duration::ZERO,
             },
         )))
     }
@@ -58,6 +60,13 @@ impl LaneQueueLengths {
         }
     }
 
+    pub fn set_average_sending_delay(&mut self, delay: Duration) {
+        match self.0.lock() {
+            Ok(mut inner) => inner.average_sending_delay = delay,
+            Err(err) => log::warn!("Failed to set average sending delay: {err}"),
+        }
+    }
+
     pub fn get(&self, lane: &TransmissionLane) -> Option<usize> {
         match self.0.lock() {
             Ok(inner) => inner.get(lane),
@@ -86,6 +95,9 @@ impl std::ops::Deref for LaneQueueLengths {
 #[derive(Debug)]
 pub struct LaneQueueLengthsInner {
     pub map: HashMap<TransmissionLane, usize>,
+
+    /// Current average delay between subsequent packets sent by the `OutQueueControl`.
+    pub average_sending_delay: Duration,
 }
 
 impl LaneQueueLengthsInner {
@@ -97,6 +109,25 @@ impl LaneQueueLengthsInner {
         self.map.values()
     }
 
+    /// Estimates how long it's going to take to send out `lane_length` packets of a single lane,
+    /// given that the lanes with queued up packets are served in turns.
+    pub fn estimated_drain_time(&self, lane_length: usize) -> Duration {
+        let total: usize = self.map.values().sum();
+        let active_lanes = self
+            .map
+            .values()
+            .filter(|length| **length > 0)
+            .count()
+            .max(1);
+
+        // the lane can't get drained any later than the whole queue
+        let packets = lane_length
+            .saturating_mul(active_lanes)
+            .min(total.max(lane_length));
+        self.average_sending_delay
+            .saturating_mul(packets.try_into().unwrap_or(u32::MAX))
+    }
+
     pub fn modify<F>(&mut self, lane: &TransmissionLane, f: F)
     where
         F: FnOnce(&mut usize),
@@ -104,3 +135,22 @@ impl LaneQueueLengthsInner {
         self.map.entry(*lane).and_modify(f);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drain_time_accounts_for_other_lanes() {
+        let mut lengths = LaneQueueLengths::new();
+        lengths.set_average_sending_delay(Duration::from_millis(10));
+        lengths.set(&TransmissionLane::ConnectionId(1), Some(10));
+        lengths.set(&TransmissionLane::ConnectionId(2), Some(100));
+
+        let inner = lengths.lock().unwrap();
+        // the short lane is competing with the other one for the whole time
+        assert_eq!(inner.estimated_drain_time(10), Duration::from_millis(200));
+        // while the long one ends up alone after the short one is done
+        assert_eq!(inner.estimated_drain_time(100), Duration::from_millis(1100));
+    }
+}
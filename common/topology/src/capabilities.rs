@@ -0,0 +1,82 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Capabilities of the nodes in the network.
+//!
+//! Nodes do not advertise them explicitly, so they are derived from the version they're running.
+//! Nodes with versions we can't parse are assumed to only be capable of the baseline features.
+
+use nym_bin_common::version_checker::parse_version;
+use nym_sphinx_params::PacketSize;
+
+/// Version of the nodes that introduced the support for the extended packet sizes.
+pub const EXTENDED_PACKET_SIZES_VERSION: &str = "1.1.0";
+
+const BASELINE_PACKET_SIZES: [PacketSize; 2] = [PacketSize::RegularPacket, PacketSize::AckPacket];
+
+const EXTENDED_PACKET_SIZES: [PacketSize; 3] = [
+    PacketSize::ExtendedPacket8,
+    PacketSize::ExtendedPacket16,
+    PacketSize::ExtendedPacket32,
+];
+
+/// Packet sizes the node running the specified version is capable of processing.
+pub fn supported_packet_sizes(node_version: &str) -> Vec<PacketSize> {
+    let mut supported = BASELINE_PACKET_SIZES.to_vec();
+
+    let supports_extended = match (
+        parse_version(node_version),
+        parse_version(EXTENDED_PACKET_SIZES_VERSION),
+    ) {
+        (Ok(version), Ok(required)) => version >= required,
+        _ => false,
+    };
+    if supports_extended {
+        supported.extend_from_slice(&EXTENDED_PACKET_SIZES)
+    }
+
+    supported
+}
+
+/// Packet sizes every single one of the nodes running the specified versions is capable of processing.
+pub fn commonly_supported_packet_sizes<'a, I>(node_versions: I) -> Vec<PacketSize>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut supported = BASELINE_PACKET_SIZES
+        .iter()
+        .chain(EXTENDED_PACKET_SIZES.iter())
+        .copied()
+        .collect::<Vec<_>>();
+
+    for version in node_versions {
+        let node_supported = supported_packet_sizes(version);
+        supported.retain(|size| node_supported.contains(size));
+    }
+
+    supported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_sizes_depend_on_node_version() {
+        assert_eq!(supported_packet_sizes("1.0.2"), BASELINE_PACKET_SIZES);
+        assert_eq!(supported_packet_sizes("foomp"), BASELINE_PACKET_SIZES);
+        assert!(supported_packet_sizes("1.1.16").contains(&PacketSize::ExtendedPacket32));
+    }
+
+    #[test]
+    fn common_support_is_limited_by_the_oldest_node() {
+        assert_eq!(
+            commonly_supported_packet_sizes(["1.1.16", "1.1.2"]).len(),
+            BASELINE_PACKET_SIZES.len() + EXTENDED_PACKET_SIZES.len()
+        );
+        assert_eq!(
+            commonly_supported_packet_sizes(["1.1.16", "1.0.2", "1.1.2"]),
+            BASELINE_PACKET_SIZES
+        );
+    }
+}
@@ -2,11 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::filter::VersionFilterable;
-use log::warn;
+use crate::route_pool::RoutePool;
+use log::{debug, warn};
+use nym_mixnet_contract_common::families::FamilyHead;
 use nym_mixnet_contract_common::mixnode::MixNodeDetails;
-use nym_mixnet_contract_common::GatewayBond;
+use nym_mixnet_contract_common::{GatewayBond, IdentityKey, MixNodeBond};
 use nym_sphinx_addressing::nodes::NodeIdentity;
-use nym_sphinx_types::Node as SphinxNode;
+use nym_sphinx_params::PacketSize;
+use nym_sphinx_types::{Node as SphinxNode, SphinxConstraintError};
 use rand::{CryptoRng, Rng};
 use std::collections::HashMap;
 use std::convert::TryInto;
@@ -14,11 +17,14 @@ use std::fmt::{self, Display, Formatter};
 use std::io;
 use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::str::FromStr;
+use std::sync::Arc;
 use thiserror::Error;
 
+pub mod capabilities;
 pub mod filter;
 pub mod gateway;
 pub mod mix;
+pub mod route_pool;
 
 #[cfg(feature = "provider-trait")]
 pub mod provider_trait;
@@ -52,6 +58,9 @@ pub enum NymTopologyError {
         total_nodes: usize,
         layer_distribution: Vec<(MixLayer, usize)>,
     },
+
+    #[error("The constructed route can't be used for a sphinx packet - {0}")]
+    SphinxConstraintViolation(#[from] SphinxConstraintError),
 }
 
 #[derive(Debug, Clone)]
@@ -98,11 +107,18 @@ pub type MixLayer = u8;
 pub struct NymTopology {
     mixes: HashMap<MixLayer, Vec<mix::Node>>,
     gateways: Vec<gateway::Node>,
+
+    // shared between the clones of the same topology, but reset whenever the mixnodes change
+    route_pool: Arc<RoutePool>,
 }
 
 impl NymTopology {
     pub fn new(mixes: HashMap<MixLayer, Vec<mix::Node>>, gateways: Vec<gateway::Node>) -> Self {
-        NymTopology { mixes, gateways }
+        NymTopology {
+            mixes,
+            gateways,
+            route_pool: Default::default(),
+        }
     }
 
     pub fn mixes(&self) -> &HashMap<MixLayer, Vec<mix::Node>> {
@@ -145,15 +161,73 @@ impl NymTopology {
         self.gateways = gateways
     }
 
-    /// Returns a vec of size of `num_mix_hops` of mixnodes, such that each subsequent node is on
-    /// next layer, starting from layer 1
-    pub fn random_mix_route<R>(
+    /// Packet sizes that can be routed through any of the nodes in the topology.
+    pub fn supported_packet_sizes(&self) -> Vec<PacketSize> {
+        let mix_versions = self
+            .mixes
+            .values()
+            .flatten()
+            .map(|node| node.version.as_str());
+        let gateway_versions = self.gateways.iter().map(|node| node.version.as_str());
+        capabilities::commonly_supported_packet_sizes(mix_versions.chain(gateway_versions))
+    }
+
+    /// Annotates the mixnodes with the families they have declared on chain,
+    /// based on the provided mapping of member identities to their family heads.
+    pub fn set_mix_families(&mut self, families: &HashMap<IdentityKey, FamilyHead>) {
+        for node in self.mixes.values_mut().flatten() {
+            node.family = families.get(&node.identity_key.to_base58_string()).cloned();
+        }
+        self.route_pool = Default::default();
+    }
+
+    /// Returns a view of this topology whose routes are taken from a pool used exclusively
+    /// by the sender with the specified isolation key. The pool is discarded together with
+    /// the pool of this topology, i.e. whenever the mixnodes change.
+    #[must_use]
+    pub fn isolated_view(&self, isolation_key: &[u8]) -> Self {
+        NymTopology {
+            mixes: self.mixes.clone(),
+            gateways: self.gateways.clone(),
+            route_pool: self.route_pool.isolated(isolation_key),
+        }
+    }
+
+    /// Number of precomputed routes that are currently available for this topology.
+    pub fn pooled_routes(&self) -> usize {
+        self.route_pool.available()
+    }
+
+    /// Samples new mix routes with the specified number of hops until the pool of precomputed
+    /// routes contains `target_size` of them. Returns the number of routes that got added.
+    pub fn replenish_route_pool<R>(
         &self,
         rng: &mut R,
         num_mix_hops: u8,
-    ) -> Result<Vec<SphinxNode>, NymTopologyError>
+        target_size: usize,
+    ) -> Result<usize, NymTopologyError>
+    where
+        R: Rng + CryptoRng + ?Sized,
+    {
+        let missing = self.route_pool.deficit(num_mix_hops, target_size);
+        if missing == 0 {
+            return Ok(0);
+        }
+
+        // sample without holding the pool lock so that the packet preparation wouldn't get blocked
+        let routes = (0..missing)
+            .map(|_| self.sample_mix_route(rng, num_mix_hops))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.route_pool.extend(num_mix_hops, routes);
+        Ok(missing)
+    }
+
+    fn random_diverse_mix_route<R>(
+        &self,
+        rng: &mut R,
+        num_mix_hops: u8,
+    ) -> Result<Vec<&mix::Node>, NymTopologyError>
     where
-        // I don't think there's a need for this RNG to be crypto-secure
         R: Rng + ?Sized,
     {
         use rand::seq::SliceRandom;
@@ -165,6 +239,7 @@ impl NymTopology {
             });
         }
         let mut route = Vec::with_capacity(num_mix_hops as usize);
+        let mut route_families = Vec::with_capacity(num_mix_hops as usize);
 
         // there is no "layer 0"
         for layer in 1..=num_mix_hops {
@@ -174,17 +249,71 @@ impl NymTopology {
                 .get(&layer)
                 .ok_or(NymTopologyError::EmptyMixLayer { layer })?;
 
+            // prefer mixes that do not belong to any family already present on the route,
+            // so that a single operator would not control multiple hops of the same packet
+            let diverse_mixes = layer_mixes
+                .iter()
+                .filter(|mix| match &mix.family {
+                    Some(family) => !route_families.contains(&family),
+                    None => true,
+                })
+                .collect::<Vec<_>>();
+
             // choose a random mix from the above list
             // this can return a 'None' only if slice is empty
-            let random_mix = layer_mixes
-                .choose(rng)
-                .ok_or(NymTopologyError::EmptyMixLayer { layer })?;
-            route.push(random_mix.into());
+            let random_mix = if diverse_mixes.is_empty() {
+                debug!(
+                    "all mixes on layer {layer} belong to families already present on the route"
+                );
+                layer_mixes.choose(rng)
+            } else {
+                diverse_mixes.choose(rng).copied()
+            }
+            .ok_or(NymTopologyError::EmptyMixLayer { layer })?;
+
+            if let Some(family) = &random_mix.family {
+                route_families.push(family);
+            }
+            route.push(random_mix);
         }
 
         Ok(route)
     }
 
+    fn sample_mix_route<R>(
+        &self,
+        rng: &mut R,
+        num_mix_hops: u8,
+    ) -> Result<Vec<SphinxNode>, NymTopologyError>
+    where
+        R: Rng + ?Sized,
+    {
+        Ok(self
+            .random_diverse_mix_route(rng, num_mix_hops)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Returns a vec of size of `num_mix_hops` of mixnodes, such that each subsequent node is on
+    /// next layer, starting from layer 1. Whenever possible, no two nodes on the route
+    /// belong to the same family.
+    /// If available, the route is taken from the pool of the precomputed routes instead.
+    pub fn random_mix_route<R>(
+        &self,
+        rng: &mut R,
+        num_mix_hops: u8,
+    ) -> Result<Vec<SphinxNode>, NymTopologyError>
+    where
+        // I don't think there's a need for this RNG to be crypto-secure
+        R: Rng + ?Sized,
+    {
+        match self.route_pool.take(num_mix_hops) {
+            Some(route) => Ok(route),
+            None => self.sample_mix_route(rng, num_mix_hops),
+        }
+    }
+
     /// Tries to create a route to the specified gateway, such that it goes through mixnode on layer 1,
     /// mixnode on layer2, .... mixnode on layer n and finally the target gateway
     pub fn random_route_to_gateway<R>(
@@ -212,6 +341,23 @@ impl NymTopology {
     /// Overwrites the existing nodes in the specified layer
     pub fn set_mixes_in_layer(&mut self, layer: u8, mixes: Vec<mix::Node>) {
         self.mixes.insert(layer, mixes);
+        self.route_pool = Default::default();
+    }
+
+    /// Removes the mixnode listening on the specified address, unless it's the last node in its layer,
+    /// so that no further routes would go through it. Returns whether the node got removed.
+    pub fn remove_mixnode(&mut self, mix_host: SocketAddr) -> bool {
+        for nodes in self.mixes.values_mut() {
+            if let Some(index) = nodes.iter().position(|node| node.mix_host == mix_host) {
+                if nodes.len() == 1 {
+                    return false;
+                }
+                nodes.remove(index);
+                self.route_pool = Default::default();
+                return true;
+            }
+        }
+        false
     }
 
     /// Checks if a mixnet path can be constructed using the specified number of hops
@@ -300,6 +446,7 @@ impl NymTopology {
         NymTopology {
             mixes: self.mixes.filter_by_version(expected_mix_version),
             gateways: self.gateways.clone(),
+            route_pool: Default::default(),
         }
     }
 }
@@ -307,12 +454,22 @@ impl NymTopology {
 pub fn nym_topology_from_detailed(
     mix_details: Vec<MixNodeDetails>,
     gateway_bonds: Vec<GatewayBond>,
+) -> NymTopology {
+    nym_topology_from_bonds(
+        mix_details
+            .into_iter()
+            .map(|details| details.bond_information)
+            .collect(),
+        gateway_bonds,
+    )
+}
+
+pub fn nym_topology_from_bonds(
+    mix_bonds: Vec<MixNodeBond>,
+    gateway_bonds: Vec<GatewayBond>,
 ) -> NymTopology {
     let mut mixes = HashMap::new();
-    for bond in mix_details
-        .into_iter()
-        .map(|details| details.bond_information)
-    {
+    for bond in mix_bonds.into_iter() {
         let layer = bond.layer as MixLayer;
         if layer == 0 || layer > 3 {
             warn!(
@@ -377,6 +534,7 @@ mod converting_mixes_to_vec {
                 .unwrap(),
                 layer: Layer::One,
                 version: "0.x.0".to_string(),
+                family: None,
             };
 
             let node2 = mix::Node {
@@ -411,3 +569,138 @@ mod converting_mixes_to_vec {
         }
     }
 }
+
+#[cfg(test)]
+mod route_diversity {
+    use super::*;
+    use nym_crypto::asymmetric::{encryption, identity};
+    use nym_mixnet_contract_common::Layer;
+
+    fn node(mix_id: u32, layer: Layer, family: Option<&str>) -> mix::Node {
+        mix::Node {
+            mix_id,
+            owner: "N/A".to_string(),
+            host: "3.3.3.3".parse().unwrap(),
+            mix_host: "3.3.3.3:1789".parse().unwrap(),
+            identity_key: identity::PublicKey::from_base58_string(
+                "3ebjp1Fb9hdcS1AR6AZihgeJiMHkB5jjJUsvqNnfQwU7",
+            )
+            .unwrap(),
+            sphinx_key: encryption::PublicKey::from_base58_string(
+                "C7cown6dYCLZpLiMFC1PaBmhvLvmJmLDJGeRTbPD45bX",
+            )
+            .unwrap(),
+            layer,
+            version: "0.x.0".to_string(),
+            family: family.map(|head| head.parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn avoids_multiple_nodes_of_the_same_family() {
+        let mut mixes = HashMap::new();
+        mixes.insert(1, vec![node(1, Layer::One, Some("family"))]);
+        mixes.insert(
+            2,
+            vec![
+                node(2, Layer::Two, Some("family")),
+                node(3, Layer::Two, None),
+            ],
+        );
+        mixes.insert(
+            3,
+            vec![
+                node(4, Layer::Three, Some("family")),
+                node(5, Layer::Three, Some("other-family")),
+            ],
+        );
+        let topology = NymTopology::new(mixes, vec![]);
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let route = topology.random_diverse_mix_route(&mut rng, 3).unwrap();
+            let ids = route.iter().map(|node| node.mix_id).collect::<Vec<_>>();
+            assert_eq!(ids, vec![1, 3, 5]);
+        }
+    }
+
+    #[test]
+    fn falls_back_to_any_node_if_there_is_no_other_choice() {
+        let mut mixes = HashMap::new();
+        mixes.insert(1, vec![node(1, Layer::One, Some("family"))]);
+        mixes.insert(2, vec![node(2, Layer::Two, Some("family"))]);
+        let topology = NymTopology::new(mixes, vec![]);
+
+        let route = topology
+            .random_diverse_mix_route(&mut rand::thread_rng(), 2)
+            .unwrap();
+        let ids = route.iter().map(|node| node.mix_id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn pooled_routes_are_used_only_once() {
+        let mut mixes = HashMap::new();
+        mixes.insert(1, vec![node(1, Layer::One, None)]);
+        mixes.insert(2, vec![node(2, Layer::Two, None)]);
+        let mut topology = NymTopology::new(mixes, vec![]);
+
+        let mut rng = rand::thread_rng();
+        assert_eq!(topology.replenish_route_pool(&mut rng, 2, 3).unwrap(), 3);
+        assert_eq!(topology.replenish_route_pool(&mut rng, 2, 3).unwrap(), 0);
+
+        // routes of a different length are never served from the pool
+        assert!(topology.random_mix_route(&mut rng, 1).is_ok());
+        assert_eq!(topology.pooled_routes(), 3);
+
+        topology.random_mix_route(&mut rng, 2).unwrap();
+        assert_eq!(topology.pooled_routes(), 2);
+        assert_eq!(topology.replenish_route_pool(&mut rng, 2, 3).unwrap(), 1);
+
+        // changing the mixnodes invalidates all the precomputed routes
+        topology.set_mixes_in_layer(2, vec![node(3, Layer::Two, None)]);
+        assert_eq!(topology.pooled_routes(), 0);
+    }
+
+    #[test]
+    fn isolated_route_pools_are_independent() {
+        let mut mixes = HashMap::new();
+        mixes.insert(1, vec![node(1, Layer::One, None)]);
+        mixes.insert(2, vec![node(2, Layer::Two, None)]);
+        let mut topology = NymTopology::new(mixes, vec![]);
+
+        let mut rng = rand::thread_rng();
+        let isolated = topology.isolated_view(b"application");
+        assert_eq!(isolated.replenish_route_pool(&mut rng, 2, 3).unwrap(), 3);
+        assert_eq!(topology.pooled_routes(), 0);
+        assert_eq!(topology.isolated_view(b"other").pooled_routes(), 0);
+
+        isolated.random_mix_route(&mut rng, 2).unwrap();
+        assert_eq!(topology.isolated_view(b"application").pooled_routes(), 2);
+
+        topology.set_mixes_in_layer(2, vec![node(3, Layer::Two, None)]);
+        assert_eq!(topology.isolated_view(b"application").pooled_routes(), 0);
+    }
+
+    #[test]
+    fn removing_mixnodes_keeps_the_layers_routable() {
+        let mut second = node(2, Layer::One, None);
+        second.mix_host = "4.4.4.4:1789".parse().unwrap();
+        let mut mixes = HashMap::new();
+        mixes.insert(1, vec![node(1, Layer::One, None), second]);
+        mixes.insert(2, vec![node(3, Layer::Two, None)]);
+        let mut topology = NymTopology::new(mixes, vec![]);
+
+        let mut rng = rand::thread_rng();
+        assert_eq!(topology.replenish_route_pool(&mut rng, 2, 3).unwrap(), 3);
+
+        assert!(topology.remove_mixnode("4.4.4.4:1789".parse().unwrap()));
+        assert_eq!(topology.num_mixnodes(), 2);
+        assert_eq!(topology.pooled_routes(), 0);
+
+        // the remaining nodes are the only ones left in their layers
+        assert!(!topology.remove_mixnode("3.3.3.3:1789".parse().unwrap()));
+        assert!(!topology.remove_mixnode("5.5.5.5:1789".parse().unwrap()));
+        assert_eq!(topology.num_mixnodes(), 2);
+    }
+}
@@ -3,6 +3,7 @@
 
 use crate::{filter, NetworkAddress};
 use nym_crypto::asymmetric::{encryption, identity};
+use nym_mixnet_contract_common::families::FamilyHead;
 pub use nym_mixnet_contract_common::Layer;
 use nym_mixnet_contract_common::{MixId, MixNodeBond};
 use nym_sphinx_addressing::nodes::NymNodeRoutingAddress;
@@ -40,6 +41,8 @@ pub struct Node {
     pub sphinx_key: encryption::PublicKey, // TODO: or nymsphinx::PublicKey? both are x25519
     pub layer: Layer,
     pub version: String,
+    /// Family, i.e. the group of nodes run by the same operator, the node has declared on chain.
+    pub family: Option<FamilyHead>,
 }
 
 impl filter::Versioned for Node {
@@ -89,6 +92,8 @@ impl<'a> TryFrom<&'a MixNodeBond> for Node {
             sphinx_key: encryption::PublicKey::from_base58_string(&bond.mix_node.sphinx_key)?,
             layer: bond.layer,
             version: bond.mix_node.version.clone(),
+            // family membership is not part of the bond and has to be set separately
+            family: None,
         })
     }
 }
@@ -0,0 +1,85 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_sphinx_types::Node as SphinxNode;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct PooledRoutes {
+    num_mix_hops: u8,
+    routes: Vec<Vec<SphinxNode>>,
+}
+
+/// Pool of mix routes precomputed for a particular topology, so that the packet preparation
+/// would not have to sample and validate the nodes for every single packet.
+/// Each route is handed out only once.
+#[derive(Default)]
+pub struct RoutePool {
+    inner: Mutex<PooledRoutes>,
+
+    // independent pools of the isolated senders, dropped alongside this pool
+    isolated: Mutex<HashMap<Vec<u8>, Arc<RoutePool>>>,
+}
+
+impl Debug for RoutePool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoutePool")
+            .field("available", &self.available())
+            .finish()
+    }
+}
+
+impl RoutePool {
+    /// Number of routes currently available in the pool.
+    pub fn available(&self) -> usize {
+        self.inner
+            .lock()
+            .expect("route pool lock got poisoned")
+            .routes
+            .len()
+    }
+
+    /// Number of routes with the specified number of hops that are missing from the pool
+    /// for it to contain `target_size` of them.
+    pub(crate) fn deficit(&self, num_mix_hops: u8, target_size: usize) -> usize {
+        let guard = self.inner.lock().expect("route pool lock got poisoned");
+        if guard.num_mix_hops != num_mix_hops {
+            target_size
+        } else {
+            target_size.saturating_sub(guard.routes.len())
+        }
+    }
+
+    /// Adds the provided routes to the pool. If they have different number of hops than the ones
+    /// already present, the old ones are dropped.
+    pub(crate) fn extend(&self, num_mix_hops: u8, routes: Vec<Vec<SphinxNode>>) {
+        let mut guard = self.inner.lock().expect("route pool lock got poisoned");
+        if guard.num_mix_hops != num_mix_hops {
+            guard.num_mix_hops = num_mix_hops;
+            guard.routes.clear();
+        }
+        guard.routes.extend(routes)
+    }
+
+    /// Pool of routes used exclusively by the sender with the specified isolation key,
+    /// so that its packets would never share the route source with anyone else.
+    pub(crate) fn isolated(&self, isolation_key: &[u8]) -> Arc<RoutePool> {
+        self.isolated
+            .lock()
+            .expect("route pool lock got poisoned")
+            .entry(isolation_key.to_vec())
+            .or_default()
+            .clone()
+    }
+
+    /// Takes a single route with the specified number of hops out of the pool, if available.
+    pub(crate) fn take(&self, num_mix_hops: u8) -> Option<Vec<SphinxNode>> {
+        let mut guard = self.inner.lock().expect("route pool lock got poisoned");
+        if guard.num_mix_hops != num_mix_hops {
+            return None;
+        }
+        guard.routes.pop()
+    }
+}
@@ -1,5 +1,6 @@
 use crate::currency::{DecCoin, RegisteredCoins};
 use crate::error::TypesError;
+use crate::helpers::BondingSignature;
 use nym_mixnet_contract_common::{
     Gateway as MixnetContractGateway, GatewayBond as MixnetContractGatewayBond,
 };
@@ -77,6 +78,14 @@ impl GatewayBond {
     }
 }
 
+/// Local addresses the client websocket and the mixnet listeners of the gateway are bound to.
+/// Only meant for the operator and never published.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GatewayListeners {
+    pub clients: String,
+    pub mix: String,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GatewayNodeDetailsResponse {
     pub identity_key: String,
@@ -86,6 +95,7 @@ pub struct GatewayNodeDetailsResponse {
     pub version: String,
     pub mix_port: u16,
     pub clients_port: u16,
+    pub listeners: GatewayListeners,
     pub data_store: String,
 }
 
@@ -104,7 +114,33 @@ impl fmt::Display for GatewayNodeDetailsResponse {
             "Mix Port: {}, Clients port: {}",
             self.mix_port, self.clients_port
         )?;
+        writeln!(f, "Clients listener bound to: {}", self.listeners.clients)?;
+        writeln!(f, "Mix listener bound to: {}", self.listeners.mix)?;
 
         writeln!(f, "Data store is at: {}", self.data_store)
     }
 }
+
+/// Exactly the information required by the wallet (and the mixnet contract) for bonding a gateway,
+/// optionally alongside the signature on the bonding message for the specified transaction.
+#[derive(Serialize, Deserialize)]
+pub struct GatewayBondingInformation {
+    pub gateway: MixnetContractGateway,
+    pub bonding_signature: Option<BondingSignature>,
+}
+
+impl fmt::Display for GatewayBondingInformation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Identity Key: {}", self.gateway.identity_key)?;
+        writeln!(f, "Sphinx Key: {}", self.gateway.sphinx_key)?;
+        writeln!(f, "Host: {}", self.gateway.host)?;
+        writeln!(f, "Version: {}", self.gateway.version)?;
+        writeln!(f, "Mix Port: {}", self.gateway.mix_port)?;
+        writeln!(f, "Clients Port: {}", self.gateway.clients_port)?;
+        writeln!(f, "Location: {}", self.gateway.location)?;
+        if let Some(bonding_signature) = &self.bonding_signature {
+            write!(f, "{bonding_signature}")?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use cosmwasm_std::{Addr, Coin};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
@@ -28,3 +29,39 @@ impl Display for ConsoleSigningOutput {
         )
     }
 }
+
+/// Details of the bonding transaction the node's signature is going to be made for.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BondingRequest {
+    /// Address of the wallet that is going to own the node.
+    pub wallet_address: Addr,
+
+    /// Address of the vesting contract, if the node is going to get bonded with the vested tokens.
+    pub proxy: Option<Addr>,
+
+    pub pledge: Coin,
+
+    /// Current signing nonce of the wallet, as stored in the mixnet contract.
+    pub signing_nonce: u32,
+}
+
+/// Signature of the node's identity key on the bonding message expected by the mixnet contract,
+/// alongside the details of the transaction it has been made for.
+#[derive(Serialize, Deserialize)]
+pub struct BondingSignature {
+    #[serde(flatten)]
+    pub request: BondingRequest,
+    pub signature: String,
+}
+
+impl Display for BondingSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Wallet Address: {}", self.request.wallet_address)?;
+        if let Some(proxy) = &self.request.proxy {
+            writeln!(f, "Vesting Contract: {proxy}")?;
+        }
+        writeln!(f, "Pledge: {}", self.request.pledge)?;
+        writeln!(f, "Signing Nonce: {}", self.request.signing_nonce)?;
+        writeln!(f, "Signature: {}", self.signature)
+    }
+}
@@ -3,6 +3,7 @@
 
 use crate::currency::{DecCoin, RegisteredCoins};
 use crate::error::TypesError;
+use crate::helpers::BondingSignature;
 use cosmwasm_std::Decimal;
 use nym_mixnet_contract_common::{
     EpochId, MixId, MixNode, MixNodeBond as MixnetContractMixNodeBond,
@@ -195,3 +196,38 @@ impl fmt::Display for MixnodeNodeDetailsResponse {
         writeln!(f, "You are bonding to wallet address: {wallet_address}\n\n")
     }
 }
+
+/// Exactly the information required by the wallet (and the mixnet contract) for bonding a mixnode,
+/// optionally alongside the signature on the bonding message for the specified transaction.
+#[derive(Serialize, Deserialize)]
+pub struct MixnodeBondingInformation {
+    pub mix_node: MixNode,
+    pub cost_params: MixnetContractMixNodeCostParams,
+    pub bonding_signature: Option<BondingSignature>,
+}
+
+impl fmt::Display for MixnodeBondingInformation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Identity Key: {}", self.mix_node.identity_key)?;
+        writeln!(f, "Sphinx Key: {}", self.mix_node.sphinx_key)?;
+        writeln!(f, "Host: {}", self.mix_node.host)?;
+        writeln!(f, "Version: {}", self.mix_node.version)?;
+        writeln!(f, "Mix Port: {}", self.mix_node.mix_port)?;
+        writeln!(f, "Verloc Port: {}", self.mix_node.verloc_port)?;
+        writeln!(f, "Http Api Port: {}", self.mix_node.http_api_port)?;
+        writeln!(
+            f,
+            "Profit Margin: {}",
+            self.cost_params.profit_margin_percent
+        )?;
+        writeln!(
+            f,
+            "Interval Operating Cost: {}",
+            self.cost_params.interval_operating_cost
+        )?;
+        if let Some(bonding_signature) = &self.bonding_signature {
+            write!(f, "{bonding_signature}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::complaints::storage::{self, COMPLAINTS, COMPLAINT_COUNTS};
+use cosmwasm_std::{Deps, Order, StdResult};
+use cw_storage_plus::Bound;
+use nym_coconut_dkg_common::complaints::{
+    DealerComplaintsResponse, DisputedDealer, PagedDisputedDealersResponse,
+};
+
+pub fn query_dealer_complaints(
+    deps: Deps<'_>,
+    dealer_address: String,
+) -> StdResult<DealerComplaintsResponse> {
+    let dealer = deps.api.addr_validate(&dealer_address)?;
+
+    let complaints = COMPLAINTS
+        .prefix(&dealer)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|res| res.map(|(_, complaint)| complaint))
+        .collect::<StdResult<Vec<_>>>()?;
+    let upheld = storage::is_upheld(deps.storage, complaints.len() as u32)?;
+
+    Ok(DealerComplaintsResponse {
+        dealer,
+        complaints,
+        upheld,
+    })
+}
+
+pub fn query_disputed_dealers_paged(
+    deps: Deps<'_>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PagedDisputedDealersResponse> {
+    let limit = limit
+        .unwrap_or(storage::DISPUTED_DEALERS_PAGE_DEFAULT_LIMIT)
+        .min(storage::DISPUTED_DEALERS_PAGE_MAX_LIMIT) as usize;
+
+    let addr = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+
+    let start = addr.as_ref().map(Bound::exclusive);
+
+    let dealers = COMPLAINT_COUNTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| {
+            res.and_then(|(dealer, complaints)| {
+                Ok(DisputedDealer {
+                    dealer,
+                    complaints,
+                    upheld: storage::is_upheld(deps.storage, complaints)?,
+                })
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let start_next_after = dealers.last().map(|disputed| disputed.dealer.clone());
+
+    Ok(PagedDisputedDealersResponse::new(
+        dealers,
+        limit,
+        start_next_after,
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::epoch_state::storage::THRESHOLD;
+    use crate::support::tests::helpers::init_contract;
+    use cosmwasm_std::{Addr, DepsMut};
+    use nym_coconut_dkg_common::complaints::{Complaint, ComplaintReason};
+
+    fn fill_complaints(deps: DepsMut<'_>, dealer: &str, complainers: usize) {
+        let dealer = Addr::unchecked(dealer);
+        for n in 0..complainers {
+            let complainer = Addr::unchecked(format!("complainer{n}"));
+            let complaint = Complaint {
+                complainer: complainer.clone(),
+                dealer: dealer.clone(),
+                dealing_index: None,
+                reason: ComplaintReason::MissingDealing,
+            };
+            COMPLAINTS
+                .save(deps.storage, (&dealer, &complainer), &complaint)
+                .unwrap();
+        }
+        COMPLAINT_COUNTS
+            .save(deps.storage, &dealer, &(complainers as u32))
+            .unwrap();
+    }
+
+    #[test]
+    fn complaints_are_upheld_at_threshold() {
+        let mut deps = init_contract();
+        fill_complaints(deps.as_mut(), "dealer1", 2);
+        fill_complaints(deps.as_mut(), "dealer2", 3);
+
+        // the threshold is not known until the dealings are exchanged
+        let response = query_dealer_complaints(deps.as_ref(), "dealer2".to_string()).unwrap();
+        assert_eq!(response.complaints.len(), 3);
+        assert!(!response.upheld);
+
+        THRESHOLD.save(deps.as_mut().storage, &3).unwrap();
+        let response = query_dealer_complaints(deps.as_ref(), "dealer1".to_string()).unwrap();
+        assert_eq!(response.complaints.len(), 2);
+        assert!(!response.upheld);
+        let response = query_dealer_complaints(deps.as_ref(), "dealer2".to_string()).unwrap();
+        assert!(response.upheld);
+
+        let response = query_dealer_complaints(deps.as_ref(), "dealer3".to_string()).unwrap();
+        assert!(response.complaints.is_empty());
+        assert!(!response.upheld);
+
+        let page = query_disputed_dealers_paged(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            page.dealers,
+            vec![
+                DisputedDealer {
+                    dealer: Addr::unchecked("dealer1"),
+                    complaints: 2,
+                    upheld: false,
+                },
+                DisputedDealer {
+                    dealer: Addr::unchecked("dealer2"),
+                    complaints: 3,
+                    upheld: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn disputed_dealers_pagination_works() {
+        let mut deps = init_contract();
+        for n in 0..5 {
+            fill_complaints(deps.as_mut(), &format!("dealer{n}"), 1);
+        }
+
+        let page1 = query_disputed_dealers_paged(deps.as_ref(), None, Some(3)).unwrap();
+        assert_eq!(page1.dealers.len(), 3);
+
+        let start_after = page1.start_next_after.unwrap();
+        let page2 =
+            query_disputed_dealers_paged(deps.as_ref(), Some(start_after.to_string()), Some(3))
+                .unwrap();
+        assert_eq!(page2.dealers.len(), 2);
+        assert_eq!(page2.dealers[0].dealer, Addr::unchecked("dealer3"));
+
+        let crazy_limit = 1000 * storage::DISPUTED_DEALERS_PAGE_MAX_LIMIT;
+        let page = query_disputed_dealers_paged(deps.as_ref(), None, Some(crazy_limit)).unwrap();
+        assert_eq!(
+            page.per_page,
+            storage::DISPUTED_DEALERS_PAGE_MAX_LIMIT as usize
+        );
+    }
+}
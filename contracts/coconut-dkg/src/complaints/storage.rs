@@ -0,0 +1,43 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::epoch_state::storage::THRESHOLD;
+use cosmwasm_std::{Addr, Order, StdResult, Storage};
+use cw_storage_plus::Map;
+use nym_coconut_dkg_common::complaints::Complaint;
+
+pub(crate) const DISPUTED_DEALERS_PAGE_MAX_LIMIT: u32 = 75;
+pub(crate) const DISPUTED_DEALERS_PAGE_DEFAULT_LIMIT: u32 = 50;
+
+// (dealer, complainer)
+type ComplaintKey<'a> = (&'a Addr, &'a Addr);
+
+pub(crate) const COMPLAINTS: Map<'_, ComplaintKey<'_>, Complaint> = Map::new("cmpl");
+
+// number of complaints raised against given dealer
+pub(crate) const COMPLAINT_COUNTS: Map<'_, &Addr, u32> = Map::new("cmplc");
+
+/// Checks whether enough receivers have complained about a dealer for it to be excluded by everyone.
+pub(crate) fn is_upheld(storage: &dyn Storage, complaints: u32) -> StdResult<bool> {
+    Ok(match THRESHOLD.may_load(storage)? {
+        Some(threshold) => complaints as u64 >= threshold,
+        None => false,
+    })
+}
+
+pub(crate) fn remove_all_complaints(storage: &mut dyn Storage) -> StdResult<()> {
+    let complaint_keys = COMPLAINTS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (dealer, complainer) in complaint_keys {
+        COMPLAINTS.remove(storage, (&dealer, &complainer));
+    }
+
+    let disputed_dealers = COMPLAINT_COUNTS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for dealer in disputed_dealers {
+        COMPLAINT_COUNTS.remove(storage, &dealer);
+    }
+    Ok(())
+}
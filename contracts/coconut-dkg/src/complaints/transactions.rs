@@ -0,0 +1,192 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::complaints::storage::{COMPLAINTS, COMPLAINT_COUNTS};
+use crate::dealers::storage as dealers_storage;
+use crate::epoch_state::utils::check_epoch_state;
+use crate::error::ContractError;
+use crate::state::total_dealings;
+use cosmwasm_std::{DepsMut, MessageInfo, Response};
+use nym_coconut_dkg_common::complaints::{Complaint, ComplaintReason};
+use nym_coconut_dkg_common::types::EpochState;
+
+pub fn try_submit_complaint(
+    deps: DepsMut<'_>,
+    info: MessageInfo,
+    dealer: String,
+    dealing_index: Option<u64>,
+    reason: ComplaintReason,
+    resharing: bool,
+) -> Result<Response, ContractError> {
+    check_epoch_state(deps.storage, EpochState::ComplaintSubmission { resharing })?;
+
+    // only the receivers of the dealings can complain about them
+    if dealers_storage::current_dealers()
+        .may_load(deps.storage, &info.sender)?
+        .is_none()
+    {
+        return Err(ContractError::NotADealer);
+    }
+
+    let dealer = deps.api.addr_validate(&dealer)?;
+    if dealers_storage::current_dealers()
+        .may_load(deps.storage, &dealer)?
+        .is_none()
+    {
+        return Err(ContractError::UnknownDealer {
+            dealer: dealer.to_string(),
+        });
+    }
+    if dealer == info.sender {
+        return Err(ContractError::SelfComplaint);
+    }
+    if let Some(index) = dealing_index {
+        if index as usize >= total_dealings(deps.storage)? {
+            return Err(ContractError::InvalidDealingIndex { index });
+        }
+    }
+    if COMPLAINTS.has(deps.storage, (&dealer, &info.sender)) {
+        return Err(ContractError::AlreadyComplained {
+            dealer: dealer.to_string(),
+        });
+    }
+
+    let complaint = Complaint {
+        complainer: info.sender.clone(),
+        dealer: dealer.clone(),
+        dealing_index,
+        reason,
+    };
+    COMPLAINTS.save(deps.storage, (&dealer, &info.sender), &complaint)?;
+    COMPLAINT_COUNTS.update::<_, ContractError>(deps.storage, &dealer, |count| {
+        Ok(count.unwrap_or_default() + 1)
+    })?;
+
+    Ok(Response::default())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::epoch_state::storage::CURRENT_EPOCH;
+    use crate::support::tests::fixtures::dealer_details_fixture;
+    use crate::support::tests::helpers;
+    use cosmwasm_std::testing::mock_info;
+    use nym_coconut_dkg_common::types::DEFAULT_DEALINGS;
+
+    fn complain(
+        deps: DepsMut<'_>,
+        complainer: &str,
+        dealer: &str,
+        dealing_index: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        try_submit_complaint(
+            deps,
+            mock_info(complainer, &[]),
+            dealer.to_string(),
+            dealing_index,
+            ComplaintReason::DealingVerificationError,
+            false,
+        )
+    }
+
+    #[test]
+    fn invalid_complaints() {
+        let mut deps = helpers::init_contract();
+
+        let ret = complain(deps.as_mut(), "owner1", "owner2", Some(0)).unwrap_err();
+        assert_eq!(
+            ret,
+            ContractError::IncorrectEpochState {
+                current_state: EpochState::default().to_string(),
+                expected_state: EpochState::ComplaintSubmission { resharing: false }.to_string()
+            }
+        );
+
+        CURRENT_EPOCH
+            .update::<_, ContractError>(deps.as_mut().storage, |mut epoch| {
+                epoch.state = EpochState::ComplaintSubmission { resharing: false };
+                Ok(epoch)
+            })
+            .unwrap();
+
+        let ret = complain(deps.as_mut(), "owner1", "owner2", Some(0)).unwrap_err();
+        assert_eq!(ret, ContractError::NotADealer);
+
+        for index in 1..=3 {
+            let details = dealer_details_fixture(index);
+            dealers_storage::current_dealers()
+                .save(deps.as_mut().storage, &details.address, &details)
+                .unwrap();
+        }
+
+        let ret = complain(deps.as_mut(), "owner1", "owner4", Some(0)).unwrap_err();
+        assert_eq!(
+            ret,
+            ContractError::UnknownDealer {
+                dealer: "owner4".to_string()
+            }
+        );
+
+        let ret = complain(deps.as_mut(), "owner1", "owner1", Some(0)).unwrap_err();
+        assert_eq!(ret, ContractError::SelfComplaint);
+
+        let ret = complain(
+            deps.as_mut(),
+            "owner1",
+            "owner2",
+            Some(DEFAULT_DEALINGS as u64),
+        )
+        .unwrap_err();
+        assert_eq!(
+            ret,
+            ContractError::InvalidDealingIndex {
+                index: DEFAULT_DEALINGS as u64
+            }
+        );
+
+        complain(deps.as_mut(), "owner1", "owner2", Some(0)).unwrap();
+        let ret = complain(deps.as_mut(), "owner1", "owner2", None).unwrap_err();
+        assert_eq!(
+            ret,
+            ContractError::AlreadyComplained {
+                dealer: "owner2".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn complaints_are_counted() {
+        let mut deps = helpers::init_contract();
+        CURRENT_EPOCH
+            .update::<_, ContractError>(deps.as_mut().storage, |mut epoch| {
+                epoch.state = EpochState::ComplaintSubmission { resharing: false };
+                Ok(epoch)
+            })
+            .unwrap();
+        for index in 1..=3 {
+            let details = dealer_details_fixture(index);
+            dealers_storage::current_dealers()
+                .save(deps.as_mut().storage, &details.address, &details)
+                .unwrap();
+        }
+
+        complain(deps.as_mut(), "owner1", "owner3", Some(1)).unwrap();
+        complain(deps.as_mut(), "owner2", "owner3", None).unwrap();
+        complain(deps.as_mut(), "owner3", "owner1", Some(4)).unwrap();
+
+        let owner1 = dealer_details_fixture(1).address;
+        let owner3 = dealer_details_fixture(3).address;
+        assert_eq!(COMPLAINT_COUNTS.load(&deps.storage, &owner3).unwrap(), 2);
+        assert_eq!(COMPLAINT_COUNTS.load(&deps.storage, &owner1).unwrap(), 1);
+        assert_eq!(
+            COMPLAINTS.load(&deps.storage, (&owner1, &owner3)).unwrap(),
+            Complaint {
+                complainer: owner3,
+                dealer: owner1,
+                dealing_index: Some(4),
+                reason: ComplaintReason::DealingVerificationError,
+            }
+        );
+    }
+}
@@ -1,19 +1,22 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::complaints::queries::{query_dealer_complaints, query_disputed_dealers_paged};
+use crate::complaints::transactions::try_submit_complaint;
 use crate::dealers::queries::{
     query_current_dealers_paged, query_dealer_details, query_past_dealers_paged,
 };
 use crate::dealers::transactions::try_add_dealer;
-use crate::dealings::queries::query_dealings_paged;
+use crate::dealings::queries::{query_dealings_paged, query_total_dealings};
 use crate::dealings::transactions::try_commit_dealings;
 use crate::epoch_state::queries::{
     query_current_epoch, query_current_epoch_threshold, query_initial_dealers,
 };
 use crate::epoch_state::storage::CURRENT_EPOCH;
 use crate::epoch_state::transactions::{advance_epoch_state, try_surpassed_threshold};
+use crate::epoch_state::utils::check_epoch_state;
 use crate::error::ContractError;
-use crate::state::{State, MULTISIG, STATE};
+use crate::state::{validate_total_dealings, State, MULTISIG, STATE};
 use crate::verification_key_shares::queries::query_vk_shares_paged;
 use crate::verification_key_shares::transactions::try_commit_verification_key_share;
 use crate::verification_key_shares::transactions::try_verify_verification_key_share;
@@ -22,7 +25,7 @@ use cosmwasm_std::{
 };
 use cw4::Cw4Contract;
 use nym_coconut_dkg_common::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use nym_coconut_dkg_common::types::{Epoch, EpochState};
+use nym_coconut_dkg_common::types::{Epoch, EpochState, DEFAULT_DEALINGS};
 
 /// Instantiate the contract.
 ///
@@ -45,10 +48,14 @@ pub fn instantiate(
         }
     })?);
 
+    let total_dealings =
+        validate_total_dealings(msg.total_dealings.unwrap_or(DEFAULT_DEALINGS as u32))?;
+
     let state = State {
         group_addr,
         multisig_addr,
         mix_denom: msg.mix_denom,
+        total_dealings,
     };
     STATE.save(deps.storage, &state)?;
 
@@ -89,6 +96,12 @@ pub fn execute(
         ExecuteMsg::VerifyVerificationKeyShare { owner, resharing } => {
             try_verify_verification_key_share(deps, info, owner, resharing)
         }
+        ExecuteMsg::SubmitComplaint {
+            dealer,
+            dealing_index,
+            reason,
+            resharing,
+        } => try_submit_complaint(deps, info, dealer, dealing_index, reason, resharing),
         ExecuteMsg::SurpassedThreshold {} => try_surpassed_threshold(deps, env),
         ExecuteMsg::AdvanceEpochState {} => advance_epoch_state(deps, env),
     }
@@ -101,6 +114,7 @@ pub fn query(deps: Deps<'_>, _env: Env, msg: QueryMsg) -> Result<QueryResponse,
         QueryMsg::GetCurrentEpochThreshold {} => {
             to_binary(&query_current_epoch_threshold(deps.storage)?)?
         }
+        QueryMsg::GetTotalDealings {} => to_binary(&query_total_dealings(deps.storage)?)?,
         QueryMsg::GetInitialDealers {} => to_binary(&query_initial_dealers(deps.storage)?)?,
         QueryMsg::GetDealerDetails { dealer_address } => {
             to_binary(&query_dealer_details(deps, dealer_address)?)?
@@ -121,13 +135,32 @@ pub fn query(deps: Deps<'_>, _env: Env, msg: QueryMsg) -> Result<QueryResponse,
             limit,
             start_after,
         } => to_binary(&query_vk_shares_paged(deps, epoch_id, start_after, limit)?)?,
+        QueryMsg::GetDealerComplaints { dealer_address } => {
+            to_binary(&query_dealer_complaints(deps, dealer_address)?)?
+        }
+        QueryMsg::GetDisputedDealers { limit, start_after } => {
+            to_binary(&query_disputed_dealers_paged(deps, start_after, limit)?)?
+        }
     };
 
     Ok(response)
 }
 
 #[entry_point]
-pub fn migrate(_deps: DepsMut<'_>, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut<'_>, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    if let Some(total_dealings) = msg.total_dealings {
+        // the keys derived in the previous rounds are bound to the old number of dealings,
+        // so it can only be changed before the dealing exchange of a fresh round starts
+        check_epoch_state(
+            deps.storage,
+            EpochState::PublicKeySubmission { resharing: false },
+        )?;
+        let total_dealings = validate_total_dealings(total_dealings)?;
+        STATE.update::<_, ContractError>(deps.storage, |mut state| {
+            state.total_dealings = total_dealings;
+            Ok(state)
+        })?;
+    }
     Ok(Default::default())
 }
 
@@ -141,7 +174,7 @@ mod tests {
     use cw4::Member;
     use cw_multi_test::{App, AppBuilder, AppResponse, ContractWrapper, Executor};
     use nym_coconut_dkg_common::msg::ExecuteMsg::RegisterDealer;
-    use nym_coconut_dkg_common::types::NodeIndex;
+    use nym_coconut_dkg_common::types::{NodeIndex, MAX_DEALINGS};
     use nym_group_contract_common::msg::InstantiateMsg as GroupInstantiateMsg;
 
     fn instantiate_with_group(app: &mut App, members: &[Addr]) -> Addr {
@@ -178,6 +211,7 @@ mod tests {
             multisig_addr: MULTISIG_CONTRACT.to_string(),
             time_configuration: None,
             mix_denom: TEST_MIX_DENOM.to_string(),
+            total_dealings: None,
         };
         app.instantiate_contract(
             coconut_dkg_code_id,
@@ -213,6 +247,7 @@ mod tests {
             multisig_addr: "multisig_addr".to_string(),
             time_configuration: None,
             mix_denom: "nym".to_string(),
+            total_dealings: None,
         };
         let info = mock_info("creator", &[]);
 
@@ -220,6 +255,62 @@ mod tests {
         assert!(res.is_ok())
     }
 
+    #[test]
+    fn total_dealings_are_validated() {
+        let env = mock_env();
+        let info = mock_info("creator", &[]);
+        let msg = |total_dealings| InstantiateMsg {
+            group_addr: "group_addr".to_string(),
+            multisig_addr: "multisig_addr".to_string(),
+            time_configuration: None,
+            mix_denom: "nym".to_string(),
+            total_dealings,
+        };
+
+        for invalid in [0, MAX_DEALINGS as u32 + 1] {
+            let mut deps = mock_dependencies();
+            let res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg(Some(invalid)));
+            assert_eq!(
+                res.unwrap_err(),
+                ContractError::InvalidTotalDealings {
+                    total_dealings: invalid,
+                    max: MAX_DEALINGS
+                }
+            );
+        }
+
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), env.clone(), info, msg(Some(3))).unwrap();
+        assert_eq!(query_total_dealings(deps.as_ref().storage).unwrap(), 3);
+
+        // the number of dealings can be changed before the next round starts
+        migrate(
+            deps.as_mut(),
+            env.clone(),
+            MigrateMsg {
+                total_dealings: Some(7),
+            },
+        )
+        .unwrap();
+        assert_eq!(query_total_dealings(deps.as_ref().storage).unwrap(), 7);
+
+        CURRENT_EPOCH
+            .update::<_, ContractError>(deps.as_mut().storage, |mut epoch| {
+                epoch.state = EpochState::DealingExchange { resharing: false };
+                Ok(epoch)
+            })
+            .unwrap();
+        assert!(migrate(
+            deps.as_mut(),
+            env,
+            MigrateMsg {
+                total_dealings: Some(5),
+            },
+        )
+        .is_err());
+        assert_eq!(query_total_dealings(deps.as_ref().storage).unwrap(), 7);
+    }
+
     #[test]
     fn execute_add_dealer() {
         let init_funds = coins(100, TEST_MIX_DENOM);
@@ -3,10 +3,14 @@
 
 use crate::dealings::storage;
 use crate::dealings::storage::DEALINGS_BYTES;
-use cosmwasm_std::{Deps, Order, StdResult};
+use crate::state::total_dealings;
+use cosmwasm_std::{Deps, Order, StdResult, Storage};
 use cw_storage_plus::Bound;
 use nym_coconut_dkg_common::dealer::{ContractDealing, PagedDealingsResponse};
-use nym_coconut_dkg_common::types::TOTAL_DEALINGS;
+
+pub fn query_total_dealings(storage: &dyn Storage) -> StdResult<u32> {
+    total_dealings(storage).map(|total| total as u32)
+}
 
 pub fn query_dealings_paged(
     deps: Deps<'_>,
@@ -19,7 +23,7 @@ pub fn query_dealings_paged(
         .min(storage::DEALINGS_PAGE_MAX_LIMIT) as usize;
 
     let idx = idx as usize;
-    if idx >= TOTAL_DEALINGS {
+    if idx >= total_dealings(deps.storage)? {
         return Ok(PagedDealingsResponse::new(vec![], limit, None));
     }
 
@@ -51,12 +55,13 @@ pub(crate) mod tests {
     use crate::support::tests::fixtures::dealing_bytes_fixture;
     use crate::support::tests::helpers::init_contract;
     use cosmwasm_std::{Addr, DepsMut};
+    use nym_coconut_dkg_common::types::DEFAULT_DEALINGS;
 
     fn fill_dealings(deps: DepsMut<'_>, size: usize) {
         for n in 0..size {
             let dealing_share = dealing_bytes_fixture();
             let sender = Addr::unchecked(format!("owner{}", n));
-            for idx in 0..TOTAL_DEALINGS {
+            for idx in 0..DEFAULT_DEALINGS {
                 DEALINGS_BYTES[idx]
                     .save(deps.storage, &sender, &dealing_share)
                     .unwrap();
@@ -69,7 +74,7 @@ pub(crate) mod tests {
         let mut deps = init_contract();
         fill_dealings(deps.as_mut(), 1000);
 
-        for idx in TOTAL_DEALINGS as u64..100 * TOTAL_DEALINGS as u64 {
+        for idx in DEFAULT_DEALINGS as u64..100 * DEFAULT_DEALINGS as u64 {
             let page1 = query_dealings_paged(deps.as_ref(), idx, None, None).unwrap();
             assert_eq!(0, page1.dealings.len() as u32);
         }
@@ -78,7 +83,7 @@ pub(crate) mod tests {
     #[test]
     fn dealings_empty_on_init() {
         let deps = init_contract();
-        for idx in 0..TOTAL_DEALINGS as u64 {
+        for idx in 0..DEFAULT_DEALINGS as u64 {
             let response = query_dealings_paged(deps.as_ref(), idx, None, Option::from(2)).unwrap();
             assert_eq!(0, response.dealings.len());
         }
@@ -90,7 +95,7 @@ pub(crate) mod tests {
         let limit = 2;
         fill_dealings(deps.as_mut(), 1000);
 
-        for idx in 0..TOTAL_DEALINGS as u64 {
+        for idx in 0..DEFAULT_DEALINGS as u64 {
             let page1 =
                 query_dealings_paged(deps.as_ref(), idx, None, Option::from(limit)).unwrap();
             assert_eq!(limit, page1.dealings.len() as u32);
@@ -102,7 +107,7 @@ pub(crate) mod tests {
         let mut deps = init_contract();
         fill_dealings(deps.as_mut(), 1000);
 
-        for idx in 0..TOTAL_DEALINGS as u64 {
+        for idx in 0..DEFAULT_DEALINGS as u64 {
             // query without explicitly setting a limit
             let page1 = query_dealings_paged(deps.as_ref(), idx, None, None).unwrap();
 
@@ -117,7 +122,7 @@ pub(crate) mod tests {
 
         // query with a crazily high limit in an attempt to use too many resources
         let crazy_limit = 1000 * DEALINGS_PAGE_MAX_LIMIT;
-        for idx in 0..TOTAL_DEALINGS as u64 {
+        for idx in 0..DEFAULT_DEALINGS as u64 {
             let page1 =
                 query_dealings_paged(deps.as_ref(), idx, None, Option::from(crazy_limit)).unwrap();
 
@@ -135,7 +140,7 @@ pub(crate) mod tests {
 
         let per_page = 2;
 
-        for idx in 0..TOTAL_DEALINGS as u64 {
+        for idx in 0..DEFAULT_DEALINGS as u64 {
             let page1 =
                 query_dealings_paged(deps.as_ref(), idx, None, Option::from(per_page)).unwrap();
 
@@ -146,7 +151,7 @@ pub(crate) mod tests {
         // save another
         fill_dealings(deps.as_mut(), 2);
 
-        for idx in 0..TOTAL_DEALINGS as u64 {
+        for idx in 0..DEFAULT_DEALINGS as u64 {
             // page1 should have 2 results on it
             let page1 =
                 query_dealings_paged(deps.as_ref(), idx, None, Option::from(per_page)).unwrap();
@@ -155,7 +160,7 @@ pub(crate) mod tests {
 
         fill_dealings(deps.as_mut(), 3);
 
-        for idx in 0..TOTAL_DEALINGS as u64 {
+        for idx in 0..DEFAULT_DEALINGS as u64 {
             // page1 still has 2 results
             let page1 =
                 query_dealings_paged(deps.as_ref(), idx, None, Option::from(per_page)).unwrap();
@@ -176,7 +181,7 @@ pub(crate) mod tests {
 
         fill_dealings(deps.as_mut(), 4);
 
-        for idx in 0..TOTAL_DEALINGS as u64 {
+        for idx in 0..DEFAULT_DEALINGS as u64 {
             let page1 =
                 query_dealings_paged(deps.as_ref(), idx, None, Option::from(per_page)).unwrap();
             let start_after = page1.start_next_after.unwrap();
@@ -3,7 +3,7 @@
 
 use cosmwasm_std::Addr;
 use cw_storage_plus::Map;
-use nym_coconut_dkg_common::types::{ContractSafeBytes, TOTAL_DEALINGS};
+use nym_coconut_dkg_common::types::{ContractSafeBytes, MAX_DEALINGS};
 
 pub(crate) const DEALINGS_PAGE_MAX_LIMIT: u32 = 2;
 pub(crate) const DEALINGS_PAGE_DEFAULT_LIMIT: u32 = 1;
@@ -22,12 +22,23 @@ type DealingKey<'a> = &'a Addr;
 // I didn't have to do it here as I'm storing relatively little data and after just base58-encoding
 // my bytes, I was fine with the json overhead.
 
-// if TOTAL_DEALINGS is modified to anything other then current value (5), this part will also need
-// to be modified
-pub(crate) const DEALINGS_BYTES: [Map<'_, DealingKey<'_>, ContractSafeBytes>; TOTAL_DEALINGS] = [
+// only the first `total_dealings` (as set in the contract state) of those are in use.
+// if MAX_DEALINGS is modified, this part will also need to be modified
+pub(crate) const DEALINGS_BYTES: [Map<'_, DealingKey<'_>, ContractSafeBytes>; MAX_DEALINGS] = [
     Map::new("dbyt1"),
     Map::new("dbyt2"),
     Map::new("dbyt3"),
     Map::new("dbyt4"),
     Map::new("dbyt5"),
+    Map::new("dbyt6"),
+    Map::new("dbyt7"),
+    Map::new("dbyt8"),
+    Map::new("dbyt9"),
+    Map::new("dbyt10"),
+    Map::new("dbyt11"),
+    Map::new("dbyt12"),
+    Map::new("dbyt13"),
+    Map::new("dbyt14"),
+    Map::new("dbyt15"),
+    Map::new("dbyt16"),
 ];
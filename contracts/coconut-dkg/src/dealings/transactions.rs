@@ -6,6 +6,7 @@ use crate::dealings::storage::DEALINGS_BYTES;
 use crate::epoch_state::storage::INITIAL_REPLACEMENT_DATA;
 use crate::epoch_state::utils::check_epoch_state;
 use crate::error::ContractError;
+use crate::state::total_dealings;
 use cosmwasm_std::{DepsMut, MessageInfo, Response};
 use nym_coconut_dkg_common::types::{ContractSafeBytes, EpochState};
 
@@ -34,7 +35,8 @@ pub fn try_commit_dealings(
 
     // check if this dealer has already committed to all dealings
     // (we don't want to allow overwriting anything)
-    for dealings in DEALINGS_BYTES {
+    let total_dealings = total_dealings(deps.storage)?;
+    for dealings in DEALINGS_BYTES.iter().take(total_dealings) {
         if !dealings.has(deps.storage, &info.sender) {
             dealings.save(deps.storage, &info.sender, &dealing_bytes)?;
             return Ok(Response::default());
@@ -57,7 +59,9 @@ pub(crate) mod tests {
     use cosmwasm_std::testing::{mock_env, mock_info};
     use cosmwasm_std::Addr;
     use nym_coconut_dkg_common::dealer::DealerDetails;
-    use nym_coconut_dkg_common::types::{InitialReplacementData, TimeConfiguration};
+    use nym_coconut_dkg_common::types::{
+        InitialReplacementData, TimeConfiguration, DEFAULT_DEALINGS,
+    };
 
     #[test]
     fn invalid_commit_dealing() {
@@ -125,7 +129,7 @@ pub(crate) mod tests {
             })
             .unwrap();
 
-        for dealings in DEALINGS_BYTES {
+        for dealings in DEALINGS_BYTES.iter().take(DEFAULT_DEALINGS) {
             assert!(!dealings.has(deps.as_mut().storage, &owner));
             let ret = try_commit_dealings(deps.as_mut(), info.clone(), dealing_bytes.clone(), true);
             assert!(ret.is_ok());
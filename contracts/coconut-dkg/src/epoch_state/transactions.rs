@@ -1,6 +1,7 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::complaints::storage::remove_all_complaints;
 use crate::dealers::storage::{current_dealers, past_dealers};
 use crate::dealings::storage::DEALINGS_BYTES;
 use crate::epoch_state::storage::{CURRENT_EPOCH, INITIAL_REPLACEMENT_DATA, THRESHOLD};
@@ -9,10 +10,13 @@ use crate::error::ContractError;
 use crate::state::STATE;
 use crate::verification_key_shares::storage::verified_dealers;
 use cosmwasm_std::{Addr, Deps, DepsMut, Env, Order, Response, Storage};
-use nym_coconut_dkg_common::types::{Epoch, EpochState, InitialReplacementData};
+use nym_coconut_dkg_common::types::{
+    replacement_threshold, Epoch, EpochState, InitialReplacementData,
+};
 
 fn reset_epoch_state(storage: &mut dyn Storage) -> Result<(), ContractError> {
     THRESHOLD.remove(storage);
+    remove_all_complaints(storage)?;
     let dealers: Vec<_> = current_dealers()
         .keys(storage, None, None, Order::Ascending)
         .collect::<Result<_, _>>()?;
@@ -66,18 +70,17 @@ fn dealers_eq_members(deps: &DepsMut<'_>) -> Result<bool, ContractError> {
 }
 
 fn replacement_threshold_surpassed(deps: &DepsMut<'_>) -> Result<bool, ContractError> {
-    let threshold = THRESHOLD.load(deps.storage)? as usize;
+    let threshold = THRESHOLD.load(deps.storage)?;
     let initial_dealers = verified_dealers(deps.storage)?;
     if initial_dealers.is_empty() {
         // possibly failed DKG, just reset and start again
         return Ok(true);
     }
     let initial_dealer_count = initial_dealers.len();
-    let replacement_threshold = threshold - (initial_dealers.len() + 2 - 1) / 2 + 1;
     let removed_dealer_count =
         initial_dealer_count - dealers_still_active(&deps.as_ref(), initial_dealers.into_iter())?;
 
-    Ok(removed_dealer_count >= replacement_threshold)
+    Ok(removed_dealer_count >= replacement_threshold(threshold, initial_dealer_count))
 }
 
 pub(crate) fn advance_epoch_state(deps: DepsMut<'_>, env: Env) -> Result<Response, ContractError> {
@@ -464,6 +467,29 @@ pub(crate) mod tests {
                 EarlyEpochStateAdvancement(2)
             );
 
+            env.block.time = env.block.time.plus_seconds(3);
+            advance_epoch_state(deps.as_mut(), env.clone()).unwrap();
+            let epoch = CURRENT_EPOCH.load(deps.as_mut().storage).unwrap();
+            assert_eq!(
+                epoch.state,
+                EpochState::ComplaintSubmission { resharing: false }
+            );
+            assert_eq!(
+                epoch.finish_timestamp,
+                env.block
+                    .time
+                    .plus_seconds(epoch.time_configuration.complaint_submission_time_secs)
+            );
+
+            env.block.time = env
+                .block
+                .time
+                .plus_seconds(epoch.time_configuration.complaint_submission_time_secs - 2);
+            assert_eq!(
+                advance_epoch_state(deps.as_mut(), env.clone()).unwrap_err(),
+                EarlyEpochStateAdvancement(2)
+            );
+
             env.block.time = env.block.time.plus_seconds(3);
             advance_epoch_state(deps.as_mut(), env.clone()).unwrap();
             let epoch = CURRENT_EPOCH.load(deps.as_mut().storage).unwrap();
@@ -622,6 +648,7 @@ pub(crate) mod tests {
             for times in [
                 epoch.time_configuration.public_key_submission_time_secs,
                 epoch.time_configuration.dealing_exchange_time_secs,
+                epoch.time_configuration.complaint_submission_time_secs,
                 epoch.time_configuration.verification_key_submission_time_secs,
                 epoch.time_configuration.verification_key_validation_time_secs,
                 epoch.time_configuration.verification_key_finalization_time_secs,
@@ -729,6 +756,7 @@ pub(crate) mod tests {
             for times in [
                 time_configuration.public_key_submission_time_secs,
                 time_configuration.dealing_exchange_time_secs,
+                time_configuration.complaint_submission_time_secs,
                 time_configuration.verification_key_submission_time_secs,
                 time_configuration.verification_key_validation_time_secs,
                 time_configuration.verification_key_finalization_time_secs,
@@ -48,4 +48,19 @@ pub enum ContractError {
 
     #[error("No verification key committed for owner {owner}")]
     NoCommitForOwner { owner: String },
+
+    #[error("{dealer} is not a dealer for the current epoch")]
+    UnknownDealer { dealer: String },
+
+    #[error("A dealer can't complain about itself")]
+    SelfComplaint,
+
+    #[error("This sender has already complained about {dealer}")]
+    AlreadyComplained { dealer: String },
+
+    #[error("Dealing index {index} is out of range")]
+    InvalidDealingIndex { index: u64 },
+
+    #[error("The number of dealings has to be between 1 and {max}, got {total_dealings}")]
+    InvalidTotalDealings { total_dealings: u32, max: usize },
 }
@@ -1,10 +1,12 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use cosmwasm_std::Addr;
+use crate::error::ContractError;
+use cosmwasm_std::{Addr, StdResult, Storage};
 use cw4::Cw4Contract;
 use cw_controllers::Admin;
 use cw_storage_plus::Item;
+use nym_coconut_dkg_common::types::{DEFAULT_DEALINGS, MAX_DEALINGS};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -17,4 +19,28 @@ pub struct State {
     pub mix_denom: String,
     pub multisig_addr: Addr,
     pub group_addr: Cw4Contract,
+
+    /// Number of dealings each dealer submits. The states stored before it was introduced
+    /// use the default value.
+    #[serde(default = "default_total_dealings")]
+    pub total_dealings: u32,
+}
+
+fn default_total_dealings() -> u32 {
+    DEFAULT_DEALINGS as u32
+}
+
+pub(crate) fn total_dealings(storage: &dyn Storage) -> StdResult<usize> {
+    Ok(STATE.load(storage)?.total_dealings as usize)
+}
+
+pub(crate) fn validate_total_dealings(total_dealings: u32) -> Result<u32, ContractError> {
+    if total_dealings == 0 || total_dealings as usize > MAX_DEALINGS {
+        Err(ContractError::InvalidTotalDealings {
+            total_dealings,
+            max: MAX_DEALINGS,
+        })
+    } else {
+        Ok(total_dealings)
+    }
 }
@@ -87,6 +87,7 @@ pub fn init_contract() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier<Empty>>
         multisig_addr: String::from(MULTISIG_CONTRACT),
         time_configuration: None,
         mix_denom: TEST_MIX_DENOM.to_string(),
+        total_dealings: None,
     };
     let env = mock_env();
     let info = mock_info(ADMIN_ADDRESS, &[]);
@@ -117,6 +117,11 @@ mod tests {
             .time
             .plus_seconds(TimeConfiguration::default().dealing_exchange_time_secs);
         advance_epoch_state(deps.as_mut(), env.clone()).unwrap();
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(TimeConfiguration::default().complaint_submission_time_secs);
+        advance_epoch_state(deps.as_mut(), env.clone()).unwrap();
         let dealer = Addr::unchecked("requester");
         let announce_address = String::from("localhost");
         let dealer_details = DealerDetails {
@@ -185,6 +190,11 @@ mod tests {
             .time
             .plus_seconds(TimeConfiguration::default().dealing_exchange_time_secs);
         advance_epoch_state(deps.as_mut(), env.clone()).unwrap();
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(TimeConfiguration::default().complaint_submission_time_secs);
+        advance_epoch_state(deps.as_mut(), env.clone()).unwrap();
         let ret = try_commit_verification_key_share(
             deps.as_mut(),
             env.clone(),
@@ -262,6 +272,11 @@ mod tests {
             .time
             .plus_seconds(TimeConfiguration::default().dealing_exchange_time_secs);
         advance_epoch_state(deps.as_mut(), env.clone()).unwrap();
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(TimeConfiguration::default().complaint_submission_time_secs);
+        advance_epoch_state(deps.as_mut(), env.clone()).unwrap();
         env.block.time = env
             .block
             .time
@@ -308,6 +323,11 @@ mod tests {
             .time
             .plus_seconds(TimeConfiguration::default().dealing_exchange_time_secs);
         advance_epoch_state(deps.as_mut(), env.clone()).unwrap();
+        env.block.time = env
+            .block
+            .time
+            .plus_seconds(TimeConfiguration::default().complaint_submission_time_secs);
+        advance_epoch_state(deps.as_mut(), env.clone()).unwrap();
 
         let dealer_details = DealerDetails {
             address: owner.clone(),
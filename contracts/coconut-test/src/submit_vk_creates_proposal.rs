@@ -73,6 +73,7 @@ fn dkg_proposal() {
         multisig_addr: multisig_contract_addr.to_string(),
         time_configuration: None,
         mix_denom: TEST_COIN_DENOM.to_string(),
+        total_dealings: None,
     };
     let coconut_dkg_contract_addr = app
         .instantiate_contract(
@@ -19,7 +19,9 @@ use cw_utils::{maybe_addr, Expiration, ThresholdResponse};
 
 use crate::state::{Config, CONFIG};
 use nym_multisig_contract_common::error::ContractError;
-use nym_multisig_contract_common::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use nym_multisig_contract_common::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw3-flex-multisig";
@@ -294,9 +296,19 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::ListVoters { start_after, limit } => {
             to_binary(&list_voters(deps, start_after, limit)?)
         }
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
     }
 }
 
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        group_addr: cfg.group_addr.addr(),
+        coconut_bandwidth_addr: cfg.coconut_bandwidth_addr,
+        coconut_dkg_addr: cfg.coconut_dkg_addr,
+    })
+}
+
 fn query_threshold(deps: Deps) -> StdResult<ThresholdResponse> {
     let cfg = CONFIG.load(deps.storage)?;
     let total_weight = cfg.group_addr.total_weight(&deps.querier)?;
@@ -749,6 +761,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_config_query_works() {
+        let mut app = mock_app(&[]);
+        let (flex_addr, group_addr) =
+            setup_test_case_fixed(&mut app, 1, Duration::Time(2000000), vec![], false);
+
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&flex_addr, &QueryMsg::Config {})
+            .unwrap();
+        assert_eq!(
+            config,
+            ConfigResponse {
+                group_addr,
+                coconut_bandwidth_addr: Addr::unchecked(TEST_COCONUT_BANDWIDTH_CONTRACT_ADDRESS),
+                coconut_dkg_addr: Addr::unchecked(TEST_COCONUT_DKG_CONTRACT_ADDRESS),
+            }
+        );
+    }
+
     #[test]
     fn test_propose_works() {
         let init_funds = coins(10, "BTC");
@@ -8,6 +8,7 @@ use crate::mix_node::econ_stats::retrieve_mixnode_econ_stats;
 use crate::mix_node::models::{
     EconomicDynamicsStats, NodeDescription, NodeStats, PrettyDetailedMixNodeBond, SummedDelegations,
 };
+use crate::mix_node::signed::{SignedResponse, SignedResponseError};
 use crate::state::ExplorerApiStateContext;
 use nym_mixnet_contract_common::{Delegation, MixId};
 use reqwest::Error as ReqwestError;
@@ -17,6 +18,7 @@ use rocket::{Route, State};
 use rocket_okapi::okapi::openapi3::OpenApi;
 use rocket_okapi::openapi_get_routes_spec;
 use rocket_okapi::settings::OpenApiSettings;
+use thiserror::Error;
 
 pub fn mix_node_make_default_routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
     openapi_get_routes_spec![
@@ -29,11 +31,28 @@ pub fn mix_node_make_default_routes(settings: &OpenApiSettings) -> (Vec<Route>,
     ]
 }
 
-async fn get_mix_node_description(host: &str, port: u16) -> Result<NodeDescription, ReqwestError> {
-    reqwest::get(format!("http://{host}:{port}/description"))
+#[derive(Debug, Error)]
+enum DescriptionError {
+    #[error(transparent)]
+    Request(#[from] ReqwestError),
+
+    #[error("could not verify the description: {0}")]
+    Verification(#[from] SignedResponseError),
+}
+
+// the description has to be signed by the bonded identity key, so that nobody else could
+// pretend to be serving it on behalf of the node
+async fn get_mix_node_description(
+    host: &str,
+    port: u16,
+    identity_key: &str,
+) -> Result<NodeDescription, DescriptionError> {
+    let signed = reqwest::get(format!("http://{host}:{port}/description/signed"))
         .await?
-        .json::<NodeDescription>()
-        .await
+        .error_for_status()?
+        .json::<SignedResponse>()
+        .await?;
+    Ok(signed.verify(identity_key)?)
 }
 
 async fn get_mix_node_stats(host: &str, port: u16) -> Result<NodeStats, ReqwestError> {
@@ -91,6 +110,7 @@ pub(crate) async fn get_description(
                     match get_mix_node_description(
                         &bond.mix_node().host,
                         bond.mix_node().http_api_port,
+                        &bond.mix_node().identity_key,
                     )
                     .await
                     {
@@ -2,3 +2,4 @@ pub(crate) mod delegations;
 pub(crate) mod econ_stats;
 pub(crate) mod http;
 pub(crate) mod models;
+pub(crate) mod signed;
@@ -0,0 +1,143 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_crypto::asymmetric::identity;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Maximum age of a signed response before it's considered stale and is rejected
+/// in order to prevent replaying old responses.
+const MAX_SIGNED_RESPONSE_AGE: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Error)]
+pub(crate) enum SignedResponseError {
+    #[error("the response has been signed by {signer} rather than by the bonded {expected}")]
+    UnexpectedSigner { signer: String, expected: String },
+
+    #[error("the bonded identity key is malformed: {0}")]
+    MalformedIdentityKey(identity::Ed25519RecoveryError),
+
+    #[error("the signature is malformed: {0}")]
+    MalformedSignature(identity::Ed25519RecoveryError),
+
+    #[error("the signature is invalid")]
+    InvalidSignature,
+
+    #[error("the response has been signed {age:?} ago and is too stale to be accepted")]
+    StaleResponse { age: Duration },
+
+    #[error("the signed data is malformed: {0}")]
+    MalformedData(#[from] serde_json::Error),
+}
+
+#[derive(Deserialize)]
+struct SignedData<T> {
+    identity_key: String,
+    timestamp: u64,
+    payload: T,
+}
+
+/// Response of a node signed with its identity key.
+#[derive(Deserialize)]
+pub(crate) struct SignedResponse {
+    data: String,
+    signature: String,
+}
+
+impl SignedResponse {
+    /// Verifies the response has been recently signed by the node bonded with the provided
+    /// identity key and, if so, returns the payload.
+    pub(crate) fn verify<T: DeserializeOwned>(
+        self,
+        bonded_identity: &str,
+    ) -> Result<T, SignedResponseError> {
+        let identity_key = identity::PublicKey::from_base58_string(bonded_identity)
+            .map_err(SignedResponseError::MalformedIdentityKey)?;
+        let signature = identity::Signature::from_base58_string(&self.signature)
+            .map_err(SignedResponseError::MalformedSignature)?;
+        identity_key
+            .verify(self.data.as_bytes(), &signature)
+            .map_err(|_| SignedResponseError::InvalidSignature)?;
+
+        let data: SignedData<T> = serde_json::from_str(&self.data)?;
+        if data.identity_key != bonded_identity {
+            return Err(SignedResponseError::UnexpectedSigner {
+                signer: data.identity_key,
+                expected: bonded_identity.to_string(),
+            });
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let age = now.saturating_sub(Duration::from_secs(data.timestamp));
+        if age > MAX_SIGNED_RESPONSE_AGE {
+            return Err(SignedResponseError::StaleResponse { age });
+        }
+
+        Ok(data.payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sign(keypair: &identity::KeyPair, signer: &str, timestamp: u64) -> SignedResponse {
+        let data = json!({
+            "identity_key": signer,
+            "timestamp": timestamp,
+            "payload": "hello",
+        })
+        .to_string();
+        let signature = keypair
+            .private_key()
+            .sign(data.as_bytes())
+            .to_base58_string();
+        SignedResponse { data, signature }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[test]
+    fn accepts_responses_signed_by_bonded_key() {
+        let keypair = identity::KeyPair::new(&mut rand_07::thread_rng());
+        let identity = keypair.public_key().to_base58_string();
+
+        let payload: String = sign(&keypair, &identity, now()).verify(&identity).unwrap();
+        assert_eq!(payload, "hello");
+    }
+
+    #[test]
+    fn rejects_spoofed_responses() {
+        let keypair = identity::KeyPair::new(&mut rand_07::thread_rng());
+        let other = identity::KeyPair::new(&mut rand_07::thread_rng());
+        let identity = keypair.public_key().to_base58_string();
+
+        // signed by a different key, claiming to be the bonded node
+        let spoofed = sign(&other, &identity, now());
+        assert!(matches!(
+            spoofed.verify::<String>(&identity),
+            Err(SignedResponseError::InvalidSignature)
+        ));
+
+        // validly signed, but by somebody else
+        let other_identity = other.public_key().to_base58_string();
+        let foreign = sign(&other, &other_identity, now());
+        assert!(foreign.verify::<String>(&identity).is_err());
+
+        let stale = sign(&keypair, &identity, now() - 3600);
+        assert!(matches!(
+            stale.verify::<String>(&identity),
+            Err(SignedResponseError::StaleResponse { .. })
+        ));
+    }
+}
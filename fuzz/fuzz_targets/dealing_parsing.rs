@@ -0,0 +1,18 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nym_contracts_common::dealings::ContractSafeBytes;
+use nym_dkg::{Dealing, RecoveredVerificationKeys};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Dealing::try_from_bytes(data);
+    let _ = RecoveredVerificationKeys::try_from_bytes(data);
+
+    // dealings retrieved from the contract go through the json + base58 representation first
+    if let Ok(contract_bytes) = serde_json::from_slice::<ContractSafeBytes>(data) {
+        let _ = Dealing::try_from(&contract_bytes);
+    }
+});
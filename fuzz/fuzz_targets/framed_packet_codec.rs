@@ -0,0 +1,17 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+#![no_main]
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use nym_sphinx_framing::codec::SphinxCodec;
+use tokio_util::codec::Decoder;
+
+fuzz_target!(|data: &[u8]| {
+    let mut codec = SphinxCodec;
+    let mut src = BytesMut::from(data);
+
+    // keep decoding until we either run out of data or hit an error, just like a real connection would
+    while let Ok(Some(_)) = codec.decode(&mut src) {}
+});
@@ -0,0 +1,23 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nym_sphinx_addressing::clients::Recipient;
+use nym_sphinx_addressing::nodes::NymNodeRoutingAddress;
+use std::str::FromStr;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(raw) = std::str::from_utf8(data) {
+        let _ = Recipient::from_str(raw);
+    }
+
+    if data.len() >= Recipient::LEN {
+        let mut recipient_bytes = [0u8; Recipient::LEN];
+        recipient_bytes.copy_from_slice(&data[..Recipient::LEN]);
+        let _ = Recipient::try_from_bytes(recipient_bytes);
+    }
+
+    let _ = NymNodeRoutingAddress::try_from_bytes(data);
+});
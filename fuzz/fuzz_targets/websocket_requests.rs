@@ -0,0 +1,34 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nym_client_websocket_requests::requests::ClientRequest;
+use nym_client_websocket_requests::responses::ServerResponse;
+use nym_gateway_requests::generic_array::typenum::Unsigned;
+use nym_gateway_requests::registration::handshake::{SharedKeySize, SharedKeys};
+use nym_gateway_requests::{BinaryRequest, BinaryResponse, ClientControlRequest};
+use nym_sphinx_forwarding::packet::MixPacket;
+
+fuzz_target!(|data: &[u8]| {
+    // native client websocket
+    let _ = ClientRequest::try_from_binary(data);
+    let _ = ServerResponse::deserialize(data);
+    if let Ok(raw) = std::str::from_utf8(data) {
+        let _ = ClientRequest::try_from_text(raw.to_string());
+    }
+
+    // gateway websocket
+    if let Ok(raw) = std::str::from_utf8(data) {
+        let _ = ClientControlRequest::try_from(raw.to_string());
+    }
+    let _ = MixPacket::try_from_bytes(data);
+
+    // make sure we actually get past the mac verification so that the inner parsing is exercised
+    let shared_keys = SharedKeys::try_from_bytes(&vec![42u8; SharedKeySize::to_usize()]).unwrap();
+    let encrypted = shared_keys.encrypt_and_tag(data, None);
+    let _ = BinaryRequest::try_from_encrypted_tagged_bytes(encrypted.clone(), &shared_keys, None);
+    let _ = BinaryResponse::try_from_encrypted_tagged_bytes(encrypted, &shared_keys, None);
+    let _ = BinaryRequest::try_from_encrypted_tagged_bytes(data.to_vec(), &shared_keys, None);
+});
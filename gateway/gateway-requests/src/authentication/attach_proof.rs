@@ -0,0 +1,113 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_crypto::asymmetric::identity;
+use rand::{CryptoRng, RngCore};
+use std::convert::TryInto;
+use thiserror::Error;
+
+pub const ATTACH_NONCE_LENGTH: usize = 32;
+
+// the nonce starts with the unix timestamp of when it was issued
+const TIMESTAMP_LENGTH: usize = 8;
+
+// domain separator making sure the attach proofs can't be reused in any other context
+const ATTACH_PROOF_DOMAIN: &[u8] = b"nym-gateway-attach-home-gateway";
+
+/// Fresh nonce issued by the gateway a client wants to attach to its home gateway through.
+/// The client proves the ownership of its address by signing it alongside the identities of both
+/// gateways, so that the proof could not be replayed by, or to, any other gateway.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct AttachNonce([u8; ATTACH_NONCE_LENGTH]);
+
+#[derive(Debug, Error)]
+pub enum AttachNonceConversionError {
+    #[error("Failed to decode the attach nonce - {0}")]
+    DecodeError(#[from] bs58::decode::Error),
+    #[error("The decoded attach nonce has invalid length")]
+    StringOfInvalidLengthError,
+}
+
+impl AttachNonce {
+    /// Creates a new nonce issued at the specified unix timestamp.
+    pub fn new<R: RngCore + CryptoRng>(rng: &mut R, issued_at: u64) -> Self {
+        let mut nonce = [0u8; ATTACH_NONCE_LENGTH];
+        nonce[..TIMESTAMP_LENGTH].copy_from_slice(&issued_at.to_be_bytes());
+        rng.fill_bytes(&mut nonce[TIMESTAMP_LENGTH..]);
+        AttachNonce(nonce)
+    }
+
+    /// Unix timestamp of when the nonce has been issued.
+    pub fn issued_at(&self) -> u64 {
+        // the unwrap is fine as the slice has exactly `TIMESTAMP_LENGTH` bytes
+        u64::from_be_bytes(self.0[..TIMESTAMP_LENGTH].try_into().unwrap())
+    }
+
+    pub fn try_from_base58_string<S: Into<String>>(
+        val: S,
+    ) -> Result<Self, AttachNonceConversionError> {
+        let decoded = bs58::decode(val.into()).into_vec()?;
+        let nonce = decoded
+            .try_into()
+            .map_err(|_| AttachNonceConversionError::StringOfInvalidLengthError)?;
+        Ok(AttachNonce(nonce))
+    }
+
+    pub fn to_base58_string(self) -> String {
+        bs58::encode(self.0).into_string()
+    }
+}
+
+/// Constructs the message the client has to sign with its identity key in order to get attached
+/// to its `home_gateway` through the `attaching_gateway` that has issued the `nonce`.
+pub fn attach_proof_message(
+    nonce: &AttachNonce,
+    attaching_gateway: &identity::PublicKey,
+    home_gateway: &identity::PublicKey,
+) -> Vec<u8> {
+    ATTACH_PROOF_DOMAIN
+        .iter()
+        .chain(nonce.0.iter())
+        .chain(attaching_gateway.to_bytes().iter())
+        .chain(home_gateway.to_bytes().iter())
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nonce_survives_encoding() {
+        let nonce = AttachNonce::new(&mut rand::thread_rng(), 1_690_000_000);
+        let recovered = AttachNonce::try_from_base58_string(nonce.to_base58_string()).unwrap();
+        assert_eq!(recovered, nonce);
+        assert_eq!(recovered.issued_at(), 1_690_000_000);
+    }
+
+    #[test]
+    fn proof_is_bound_to_both_gateways() {
+        let mut rng = rand::thread_rng();
+        let client = identity::KeyPair::new(&mut rng);
+        let attaching = identity::KeyPair::new(&mut rng);
+        let home = identity::KeyPair::new(&mut rng);
+        let nonce = AttachNonce::new(&mut rng, 1_690_000_000);
+
+        let signature = client.private_key().sign(&attach_proof_message(
+            &nonce,
+            attaching.public_key(),
+            home.public_key(),
+        ));
+
+        let verify = |attaching, home| {
+            client
+                .public_key()
+                .verify(&attach_proof_message(&nonce, attaching, home), &signature)
+                .is_ok()
+        };
+        assert!(verify(attaching.public_key(), home.public_key()));
+        assert!(!verify(home.public_key(), attaching.public_key()));
+        assert!(!verify(attaching.public_key(), attaching.public_key()));
+    }
+}
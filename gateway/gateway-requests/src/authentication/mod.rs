@@ -1,4 +1,5 @@
 // Copyright 2020 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod attach_proof;
 pub mod encrypted_address;
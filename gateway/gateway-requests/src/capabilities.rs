@@ -0,0 +1,238 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Negotiation of the protocol version and of the optional protocol features used within a session.
+//!
+//! During the handshake the client advertises the newest protocol version it understands alongside
+//! the [`Capabilities`] it's willing to use. The gateway responds with the newest version supported
+//! by both sides and the subset of the capabilities it's going to use for the rest of the session.
+//! Peers that do not advertise any capabilities are assumed to support everything implied by
+//! their protocol version.
+
+use crate::{
+    INITIAL_PROTOCOL_VERSION, MINIMUM_SUPPORTED_PROTOCOL_VERSION,
+    PAGINATED_BACKLOG_PROTOCOL_VERSION, PROTOCOL_VERSION, REPLAY_PROTECTED_PROTOCOL_VERSION,
+    UNREACHABLE_HOP_NOTIFICATIONS_PROTOCOL_VERSION,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum NegotiationError {
+    #[error(
+        "the remote uses protocol version {remote}, but the oldest version we support is {minimum}"
+    )]
+    UnsupportedVersion { remote: u8, minimum: u8 },
+}
+
+/// Set of optional protocol features.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    /// Binary frames are prefixed with the session nonce and a sequence number.
+    pub const REPLAY_PROTECTION: Capabilities = Capabilities(1);
+
+    /// Stored messages are pushed in pages, each of which has to be acknowledged by the client.
+    /// Without it, the whole backlog is pushed at once without any acknowledgements.
+    pub const PAGINATED_BACKLOG: Capabilities = Capabilities(1 << 1);
+
+    /// Packets whose first hop is currently unreachable are answered with
+    /// [`ServerResponse::UnreachableHop`](crate::ServerResponse::UnreachableHop) rather than being forwarded.
+    pub const UNREACHABLE_HOP_NOTIFICATIONS: Capabilities = Capabilities(1 << 2);
+
+    const KNOWN: [(Capabilities, &'static str, u8); 3] = [
+        (
+            Self::REPLAY_PROTECTION,
+            "replay protection",
+            REPLAY_PROTECTED_PROTOCOL_VERSION,
+        ),
+        (
+            Self::PAGINATED_BACKLOG,
+            "paginated backlog",
+            PAGINATED_BACKLOG_PROTOCOL_VERSION,
+        ),
+        (
+            Self::UNREACHABLE_HOP_NOTIFICATIONS,
+            "unreachable hop notifications",
+            UNREACHABLE_HOP_NOTIFICATIONS_PROTOCOL_VERSION,
+        ),
+    ];
+
+    pub const fn empty() -> Self {
+        Capabilities(0)
+    }
+
+    /// All the capabilities supported by this implementation.
+    pub fn supported() -> Self {
+        Self::implied_by_version(PROTOCOL_VERSION)
+    }
+
+    /// Capabilities that are available in the specified protocol version.
+    pub fn implied_by_version(protocol_version: u8) -> Self {
+        Self::KNOWN
+            .iter()
+            .filter(|(_, _, introduced_in)| protocol_version >= *introduced_in)
+            .fold(Self::empty(), |acc, (capability, _, _)| {
+                acc.union(*capability)
+            })
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub const fn contains(&self, other: Capabilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[must_use]
+    pub const fn union(self, other: Capabilities) -> Self {
+        Capabilities(self.0 | other.0)
+    }
+
+    #[must_use]
+    pub const fn intersection(self, other: Capabilities) -> Self {
+        Capabilities(self.0 & other.0)
+    }
+
+    /// Capabilities present in `self`, but not in `other`.
+    #[must_use]
+    pub const fn difference(self, other: Capabilities) -> Self {
+        Capabilities(self.0 & !other.0)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl Display for Capabilities {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let names = Self::KNOWN
+            .iter()
+            .filter(|(capability, _, _)| self.contains(*capability))
+            .map(|(_, name, _)| *name)
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", names.join(", "))
+        }
+    }
+}
+
+/// Protocol version and capabilities agreed on by both sides of the connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegotiatedProtocol {
+    pub protocol_version: u8,
+    pub capabilities: Capabilities,
+}
+
+impl NegotiatedProtocol {
+    /// Negotiates the protocol to use based on what the remote has advertised
+    /// and on what's supported locally.
+    pub fn negotiate(
+        remote_version: Option<u8>,
+        remote_capabilities: Option<Capabilities>,
+    ) -> Result<Self, NegotiationError> {
+        // peers that do not specify the version are assumed to be using the initial one
+        let remote_version = remote_version.unwrap_or(INITIAL_PROTOCOL_VERSION);
+        if remote_version < MINIMUM_SUPPORTED_PROTOCOL_VERSION {
+            return Err(NegotiationError::UnsupportedVersion {
+                remote: remote_version,
+                minimum: MINIMUM_SUPPORTED_PROTOCOL_VERSION,
+            });
+        }
+
+        let protocol_version = remote_version.min(PROTOCOL_VERSION);
+        let remote_capabilities =
+            remote_capabilities.unwrap_or_else(|| Capabilities::implied_by_version(remote_version));
+
+        Ok(NegotiatedProtocol {
+            protocol_version,
+            capabilities: remote_capabilities
+                .intersection(Capabilities::implied_by_version(protocol_version)),
+        })
+    }
+
+    /// Supported capabilities that are not going to be used within this session.
+    pub fn missing_capabilities(&self) -> Capabilities {
+        Capabilities::supported().difference(self.capabilities)
+    }
+
+    pub fn replay_protection(&self) -> bool {
+        self.capabilities.contains(Capabilities::REPLAY_PROTECTION)
+    }
+
+    pub fn paginated_backlog(&self) -> bool {
+        self.capabilities.contains(Capabilities::PAGINATED_BACKLOG)
+    }
+
+    pub fn unreachable_hop_notifications(&self) -> bool {
+        self.capabilities
+            .contains(Capabilities::UNREACHABLE_HOP_NOTIFICATIONS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities_are_implied_by_version() {
+        assert!(Capabilities::implied_by_version(INITIAL_PROTOCOL_VERSION).is_empty());
+        assert_eq!(
+            Capabilities::implied_by_version(REPLAY_PROTECTED_PROTOCOL_VERSION),
+            Capabilities::REPLAY_PROTECTION
+        );
+        assert!(Capabilities::supported().contains(Capabilities::PAGINATED_BACKLOG));
+    }
+
+    #[test]
+    fn negotiation_picks_the_older_version() {
+        let older =
+            NegotiatedProtocol::negotiate(Some(REPLAY_PROTECTED_PROTOCOL_VERSION), None).unwrap();
+        assert_eq!(older.protocol_version, REPLAY_PROTECTED_PROTOCOL_VERSION);
+        assert!(older.replay_protection());
+        assert!(!older.paginated_backlog());
+        assert_eq!(
+            older.missing_capabilities(),
+            Capabilities::PAGINATED_BACKLOG.union(Capabilities::UNREACHABLE_HOP_NOTIFICATIONS)
+        );
+
+        // a newer remote gets downgraded rather than rejected
+        let newer = NegotiatedProtocol::negotiate(Some(PROTOCOL_VERSION + 1), None).unwrap();
+        assert_eq!(newer.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(newer.capabilities, Capabilities::supported());
+
+        let legacy = NegotiatedProtocol::negotiate(None, None).unwrap();
+        assert_eq!(legacy.protocol_version, INITIAL_PROTOCOL_VERSION);
+        assert!(legacy.capabilities.is_empty());
+    }
+
+    #[test]
+    fn negotiation_respects_advertised_capabilities() {
+        let negotiated = NegotiatedProtocol::negotiate(
+            Some(PROTOCOL_VERSION),
+            Some(Capabilities::REPLAY_PROTECTION),
+        )
+        .unwrap();
+        assert!(negotiated.replay_protection());
+        assert!(!negotiated.paginated_backlog());
+
+        // unknown capabilities are ignored
+        let negotiated =
+            NegotiatedProtocol::negotiate(Some(PROTOCOL_VERSION), Some(Capabilities(u32::MAX)))
+                .unwrap();
+        assert_eq!(negotiated.capabilities, Capabilities::supported());
+    }
+
+    #[test]
+    fn capabilities_serialize_as_plain_bits() {
+        let serialized = serde_json::to_string(&Capabilities::supported()).unwrap();
+        assert_eq!(serialized, Capabilities::supported().bits().to_string());
+    }
+}
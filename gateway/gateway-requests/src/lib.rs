@@ -8,13 +8,39 @@ use nym_sphinx::params::GatewayIntegrityHmacAlgorithm;
 pub use types::*;
 
 pub mod authentication;
+pub mod capabilities;
 pub mod iv;
 pub mod registration;
+pub mod replay;
 pub mod types;
 
 /// Defines the current version of the communication protocol between gateway and clients.
 /// It has to be incremented for any breaking change.
-pub const PROTOCOL_VERSION: u8 = 1;
+pub const PROTOCOL_VERSION: u8 = 5;
+
+/// The first version of the protocol, used by all clients and gateways that do not specify
+/// their version explicitly.
+pub const INITIAL_PROTOCOL_VERSION: u8 = 1;
+
+/// The oldest version of the protocol that we're still willing to talk to.
+/// Peers using anything older are rejected with an explicit error.
+pub const MINIMUM_SUPPORTED_PROTOCOL_VERSION: u8 = INITIAL_PROTOCOL_VERSION;
+
+/// The first version of the protocol in which binary frames are prefixed with a session nonce
+/// and a sequence number in order to protect against replay attacks.
+pub const REPLAY_PROTECTED_PROTOCOL_VERSION: u8 = 2;
+
+/// The first version of the protocol in which messages stored while the client was offline are
+/// pushed to it in pages, each of which has to be acknowledged before the next one is sent.
+pub const PAGINATED_BACKLOG_PROTOCOL_VERSION: u8 = 3;
+
+/// The first version of the protocol in which both sides of the registration handshake contribute
+/// fresh nonces and sign the whole handshake transcript rather than just the ephemeral keys.
+pub const TRANSCRIPT_BOUND_HANDSHAKE_PROTOCOL_VERSION: u8 = 4;
+
+/// The first version of the protocol in which the gateway lets the client know that the first hop
+/// of its packet is unreachable, rather than silently dropping the packet.
+pub const UNREACHABLE_HOP_NOTIFICATIONS_PROTOCOL_VERSION: u8 = 5;
 
 pub type GatewayMac = HmacOutput<GatewayIntegrityHmacAlgorithm>;
 
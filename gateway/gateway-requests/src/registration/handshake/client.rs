@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::registration::handshake::shared_key::SharedKeys;
-use crate::registration::handshake::state::State;
+use crate::registration::handshake::state::{Role, State};
 use crate::registration::handshake::{error::HandshakeError, WsItem};
 use futures::future::BoxFuture;
 use futures::task::{Context, Poll};
@@ -28,7 +28,7 @@ impl<'a> ClientHandshake<'a> {
     where
         S: Stream<Item = WsItem> + Sink<WsMessage> + Unpin + Send + 'a,
     {
-        let mut state = State::new(rng, ws_stream, identity, Some(gateway_pubkey));
+        let mut state = State::new(rng, ws_stream, identity, Some(gateway_pubkey), Role::Client);
 
         ClientHandshake {
             handshake_future: Box::pin(async move {
@@ -55,15 +55,22 @@ impl<'a> ClientHandshake<'a> {
                 state.send_handshake_data(init_message).await?;
 
                 // <- g^y || AES(k, sig(gate_priv, (g^y || g^x))
+                // or g^y || AES(k, sig(gate_priv, "gateway" || T)) if the gateway supports it
                 let mid_res = state.receive_handshake_message().await?;
+                let parameters_res =
+                    state.set_remote_parameters(mid_res.protocol_version, mid_res.nonce);
+                check_processing_error(parameters_res, &mut state).await?;
                 let (remote_ephemeral_key, remote_key_material) =
-                    check_processing_error(Self::parse_mid_response(mid_res), &mut state).await?;
+                    check_processing_error(Self::parse_mid_response(mid_res.data), &mut state)
+                        .await?;
 
                 // hkdf::<blake3>::(g^xy)
                 state.derive_shared_key(&remote_ephemeral_key);
                 let verification_res =
                     state.verify_remote_key_material(&remote_key_material, &remote_ephemeral_key);
                 check_processing_error(verification_res, &mut state).await?;
+                let versions_res = state.verify_remote_versions(mid_res.versions_mac.as_deref());
+                check_processing_error(versions_res, &mut state).await?;
 
                 // AES(k, sig(client_priv, (g^y || g^x))
                 let material = state.prepare_key_material_sig(&remote_ephemeral_key);
@@ -73,8 +80,11 @@ impl<'a> ClientHandshake<'a> {
 
                 // <- Ok
                 let finalization = state.receive_handshake_message().await?;
-                check_processing_error(Self::parse_finalization_response(finalization), &mut state)
-                    .await?;
+                check_processing_error(
+                    Self::parse_finalization_response(finalization.data),
+                    &mut state,
+                )
+                .await?;
                 Ok(state.finalize_handshake())
             }),
         }
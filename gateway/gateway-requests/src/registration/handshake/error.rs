@@ -25,4 +25,8 @@ pub enum HandshakeError {
     MalformedRequest,
     #[error("sent request was malformed")]
     HandshakeFailure,
+    #[error("the remote has not provided a valid handshake nonce")]
+    InvalidNonce,
+    #[error("the advertised protocol versions have been tampered with - refusing to downgrade the handshake")]
+    DowngradeDetected,
 }
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::registration::handshake::shared_key::SharedKeys;
-use crate::registration::handshake::state::State;
+use crate::registration::handshake::state::{Role, State};
 use crate::registration::handshake::{error::HandshakeError, WsItem};
 use futures::future::BoxFuture;
 use futures::task::{Context, Poll};
@@ -21,12 +21,14 @@ impl<'a> GatewayHandshake<'a> {
         rng: &mut (impl RngCore + CryptoRng),
         ws_stream: &'a mut S,
         identity: &'a nym_crypto::asymmetric::identity::KeyPair,
+        client_protocol_version: Option<u8>,
+        client_nonce: Option<Vec<u8>>,
         received_init_payload: Vec<u8>,
     ) -> Self
     where
         S: Stream<Item = WsItem> + Sink<WsMessage> + Unpin + Send + 'a,
     {
-        let mut state = State::new(rng, ws_stream, identity, None);
+        let mut state = State::new(rng, ws_stream, identity, None, Role::Gateway);
         GatewayHandshake {
             handshake_future: Box::pin(async move {
                 // If any step along the way failed (that are non-network related),
@@ -55,6 +57,9 @@ impl<'a> GatewayHandshake<'a> {
                 )
                 .await?;
                 state.update_remote_identity(remote_identity);
+                let parameters_res =
+                    state.set_remote_parameters(client_protocol_version, client_nonce);
+                check_processing_error(parameters_res, &mut state).await?;
 
                 // hkdf::<blake3>::(g^xy)
                 state.derive_shared_key(&remote_ephemeral_key);
@@ -73,9 +78,12 @@ impl<'a> GatewayHandshake<'a> {
 
                 // <- AES(k, sig(client_priv, g^x || g^y))
                 let remote_key_material = state.receive_handshake_message().await?;
-                let verification_res =
-                    state.verify_remote_key_material(&remote_key_material, &remote_ephemeral_key);
+                let verification_res = state
+                    .verify_remote_key_material(&remote_key_material.data, &remote_ephemeral_key);
                 check_processing_error(verification_res, &mut state).await?;
+                let versions_res =
+                    state.verify_remote_versions(remote_key_material.versions_mac.as_deref());
+                check_processing_error(versions_res, &mut state).await?;
                 let finalizer = Self::prepare_finalization_response();
 
                 // -> Ok
@@ -6,6 +6,7 @@ use self::error::HandshakeError;
 #[cfg(not(target_arch = "wasm32"))]
 use self::gateway::GatewayHandshake;
 pub use self::shared_key::{SharedKeySize, SharedKeys};
+use crate::TRANSCRIPT_BOUND_HANDSHAKE_PROTOCOL_VERSION;
 use futures::{Sink, Stream};
 use nym_crypto::asymmetric::identity;
 use rand::{CryptoRng, RngCore};
@@ -20,6 +21,33 @@ mod gateway;
 pub mod shared_key;
 mod state;
 
+/// Length of the fresh nonces contributed by both parties of the transcript-bound handshake.
+pub const HANDSHAKE_NONCE_LEN: usize = 32;
+
+/// Variant of the registration handshake used for establishing the shared keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeVersion {
+    /// Both parties only sign their ephemeral keys.
+    Legacy,
+
+    /// Both parties contribute fresh nonces and sign the whole handshake transcript,
+    /// including their identities and advertised protocol versions, under a domain separation label.
+    /// The derived keys are bound to the transcript as well.
+    TranscriptBound,
+}
+
+impl HandshakeVersion {
+    /// Determines the handshake variant to use based on the protocol version of the remote.
+    pub fn negotiate(remote_protocol_version: Option<u8>) -> Self {
+        match remote_protocol_version {
+            Some(version) if version >= TRANSCRIPT_BOUND_HANDSHAKE_PROTOCOL_VERSION => {
+                HandshakeVersion::TranscriptBound
+            }
+            _ => HandshakeVersion::Legacy,
+        }
+    }
+}
+
 // Note: the handshake is built on top of WebSocket, but in principle it shouldn't be too difficult
 // to remove that restriction, by just changing Sink<WsMessage> and Stream<Item = WsMessage> into
 // AsyncWrite and AsyncRead and slightly adjusting the implementation. But right now
@@ -42,17 +70,27 @@ pub async fn gateway_handshake<'a, S>(
     rng: &mut (impl RngCore + CryptoRng),
     ws_stream: &'a mut S,
     identity: &'a identity::KeyPair,
+    client_protocol_version: Option<u8>,
+    client_nonce: Option<Vec<u8>>,
     received_init_payload: Vec<u8>,
 ) -> Result<SharedKeys, HandshakeError>
 where
     S: Stream<Item = WsItem> + Sink<WsMessage> + Unpin + Send + 'a,
 {
-    GatewayHandshake::new(rng, ws_stream, identity, received_init_payload).await
+    GatewayHandshake::new(
+        rng,
+        ws_stream,
+        identity,
+        client_protocol_version,
+        client_nonce,
+        received_init_payload,
+    )
+    .await
 }
 
 /*
 
-Messages exchanged:
+Messages exchanged (legacy):
 
 CLIENT -> GATEWAY:
 CLIENT_ID_KEY || G^x
@@ -66,4 +104,21 @@ AES(k, SIG(PRIV_C, G^x || G^y))
 GATEWAY -> CLIENT
 DONE(status)
 
+Messages exchanged (transcript-bound), where the versions and nonces are attached to the envelopes:
+
+CLIENT -> GATEWAY:
+CLIENT_ID_KEY || G^x, [V_C, N_C]
+
+GATEWAY -> CLIENT
+G^y || AES(k, SIG(PRIV_G, "gateway" || T)), [V_G, N_G]
+
+CLIENT -> GATEWAY
+AES(k, SIG(PRIV_C, "client" || T))
+
+GATEWAY -> CLIENT
+DONE(status)
+
+with T = LABEL || V_C || V_G || CLIENT_ID_KEY || GATEWAY_ID_KEY || G^x || G^y || N_C || N_G
+and k = HKDF(salt = T, G^xy)
+
 */
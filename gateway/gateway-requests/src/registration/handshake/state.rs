@@ -3,21 +3,63 @@
 
 use crate::registration::handshake::error::HandshakeError;
 use crate::registration::handshake::shared_key::{SharedKeySize, SharedKeys};
-use crate::registration::handshake::WsItem;
-use crate::types;
+use crate::registration::handshake::{HandshakeVersion, WsItem, HANDSHAKE_NONCE_LEN};
+use crate::{types, PROTOCOL_VERSION};
 use futures::{Sink, SinkExt, Stream, StreamExt};
 use log::*;
 use nym_crypto::{
     asymmetric::{encryption, identity},
     generic_array::typenum::Unsigned,
     hkdf,
+    hmac::{compute_keyed_hmac, verify_tag},
     symmetric::stream_cipher,
 };
-use nym_sphinx::params::{GatewayEncryptionAlgorithm, GatewaySharedKeyHkdfAlgorithm};
+use nym_sphinx::params::{
+    GatewayEncryptionAlgorithm, GatewayIntegrityHmacAlgorithm, GatewaySharedKeyHkdfAlgorithm,
+};
 use rand::{CryptoRng, RngCore};
 use std::convert::{TryFrom, TryInto};
 use tungstenite::Message as WsMessage;
 
+/// Domain separation label of the transcript-bound handshake, so that the signatures produced
+/// during the handshake couldn't be reused in any other protocol.
+const TRANSCRIPT_LABEL: &[u8] = b"NYM_GATEWAY_REGISTRATION_HANDSHAKE";
+
+/// Domain separation label of the MAC over the advertised protocol versions attached
+/// when falling back to the legacy handshake.
+const VERSIONS_LABEL: &[u8] = b"NYM_GATEWAY_HANDSHAKE_VERSIONS";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Role {
+    Client,
+    Gateway,
+}
+
+impl Role {
+    // distinct labels prevent reflecting the signature of one side back to it
+    fn signature_label(&self) -> &'static [u8] {
+        match self {
+            Role::Client => b"client",
+            Role::Gateway => b"gateway",
+        }
+    }
+
+    fn remote(&self) -> Role {
+        match self {
+            Role::Client => Role::Gateway,
+            Role::Gateway => Role::Client,
+        }
+    }
+}
+
+/// Handshake payload received from the remote.
+pub(crate) struct ReceivedPayload {
+    pub(crate) protocol_version: Option<u8>,
+    pub(crate) nonce: Option<Vec<u8>>,
+    pub(crate) versions_mac: Option<Vec<u8>>,
+    pub(crate) data: Vec<u8>,
+}
+
 /// Handshake state.
 pub(crate) struct State<'a, S> {
     /// The underlying WebSocket stream.
@@ -36,6 +78,21 @@ pub(crate) struct State<'a, S> {
     /// The known or received public identity key of the remote.
     /// Ideally it would always be known before the handshake was initiated.
     remote_pubkey: Option<identity::PublicKey>,
+
+    /// Whether we're the client or the gateway in this handshake.
+    role: Role,
+
+    /// Version of the handshake, as determined based on the protocol version of the remote.
+    version: HandshakeVersion,
+
+    /// Fresh nonce contributed by us to the handshake transcript.
+    local_nonce: [u8; HANDSHAKE_NONCE_LEN],
+
+    /// Nonce contributed by the remote to the handshake transcript.
+    remote_nonce: Option<[u8; HANDSHAKE_NONCE_LEN]>,
+
+    /// Protocol version advertised by the remote.
+    remote_protocol_version: Option<u8>,
 }
 
 impl<'a, S> State<'a, S> {
@@ -44,14 +101,182 @@ impl<'a, S> State<'a, S> {
         ws_stream: &'a mut S,
         identity: &'a identity::KeyPair,
         remote_pubkey: Option<identity::PublicKey>,
+        role: Role,
     ) -> Self {
         let ephemeral_keypair = encryption::KeyPair::new(rng);
+        let mut local_nonce = [0u8; HANDSHAKE_NONCE_LEN];
+        rng.fill_bytes(&mut local_nonce);
         State {
             ws_stream,
             ephemeral_keypair,
             identity,
             remote_pubkey,
             derived_shared_keys: None,
+            role,
+            version: HandshakeVersion::Legacy,
+            local_nonce,
+            remote_nonce: None,
+            remote_protocol_version: None,
+        }
+    }
+
+    /// Sets the handshake version based on the protocol version and nonce advertised by the remote.
+    /// If the remote claims to support the transcript-bound handshake, it must provide a valid nonce.
+    pub(crate) fn set_remote_parameters(
+        &mut self,
+        remote_protocol_version: Option<u8>,
+        remote_nonce: Option<Vec<u8>>,
+    ) -> Result<(), HandshakeError> {
+        self.remote_protocol_version = remote_protocol_version;
+        self.version = HandshakeVersion::negotiate(remote_protocol_version);
+        if self.version == HandshakeVersion::TranscriptBound {
+            let nonce = remote_nonce
+                .and_then(|nonce| nonce.try_into().ok())
+                .ok_or(HandshakeError::InvalidNonce)?;
+            self.remote_nonce = Some(nonce);
+        }
+        Ok(())
+    }
+
+    // the client always advertises its nonce, as it doesn't know yet which version the gateway
+    // is going to choose, while the gateway only does so if it's actually going to use it
+    fn nonce_to_send(&self) -> Option<Vec<u8>> {
+        if self.role == Role::Client || self.version == HandshakeVersion::TranscriptBound {
+            Some(self.local_nonce.to_vec())
+        } else {
+            None
+        }
+    }
+
+    // VERSIONS_LABEL || SIGNER_LABEL || CLIENT_VERSION || GATEWAY_VERSION
+    // as seen by the signer, with the version not advertised by the remote encoded as 0
+    fn versions_message(&self, signer: Role) -> Vec<u8> {
+        let remote_version = self.remote_protocol_version.unwrap_or_default();
+        let versions = match self.role {
+            Role::Client => [PROTOCOL_VERSION, remote_version],
+            Role::Gateway => [remote_version, PROTOCOL_VERSION],
+        };
+
+        VERSIONS_LABEL
+            .iter()
+            .chain(signer.signature_label())
+            .chain(versions.iter())
+            .copied()
+            .collect()
+    }
+
+    // the transcript-bound handshake already covers the versions, while the legacy peers
+    // wouldn't understand the MAC, so it's only attached if we had to fall back to the legacy handshake
+    fn versions_mac_to_send(&self) -> Option<Vec<u8>> {
+        if self.version != HandshakeVersion::Legacy {
+            return None;
+        }
+        let shared_keys = self.derived_shared_keys.as_ref()?;
+        let mac = compute_keyed_hmac::<GatewayIntegrityHmacAlgorithm>(
+            shared_keys.mac_key(),
+            &self.versions_message(self.role),
+        );
+        Some(mac.into_bytes().to_vec())
+    }
+
+    /// Makes sure the protocol versions haven't been tampered with in order to force both parties
+    /// into the legacy handshake. Only the peers that support the transcript-bound handshake
+    /// attach the MAC, so if it's present, the remote must have seen exactly the same versions as we did.
+    /// Must be called after the shared key was derived locally.
+    pub(crate) fn verify_remote_versions(
+        &self,
+        remote_versions_mac: Option<&[u8]>,
+    ) -> Result<(), HandshakeError> {
+        if self.version != HandshakeVersion::Legacy {
+            return Ok(());
+        }
+        let Some(remote_versions_mac) = remote_versions_mac else {
+            // a genuine legacy peer
+            return Ok(());
+        };
+        let shared_keys = self
+            .derived_shared_keys
+            .as_ref()
+            .expect("shared key was not derived!");
+
+        let expected = compute_keyed_hmac::<GatewayIntegrityHmacAlgorithm>(
+            shared_keys.mac_key(),
+            &self.versions_message(self.role.remote()),
+        );
+        if verify_tag::<GatewayIntegrityHmacAlgorithm>(remote_versions_mac, expected) {
+            Ok(())
+        } else {
+            Err(HandshakeError::DowngradeDetected)
+        }
+    }
+
+    // LABEL || CLIENT_VERSION || GATEWAY_VERSION || CLIENT_ID_PUBKEY || GATEWAY_ID_PUBKEY
+    //  || G^x || G^y || CLIENT_NONCE || GATEWAY_NONCE
+    // where x is the client's and y is the gateway's ephemeral key
+    fn transcript(&self, remote_ephemeral_key: &encryption::PublicKey) -> Vec<u8> {
+        let local_id = self.identity.public_key().to_bytes();
+        let remote_id = self
+            .remote_pubkey
+            .as_ref()
+            .expect("remote identity is not known")
+            .to_bytes();
+        let local_ephemeral = self.ephemeral_keypair.public_key().to_bytes();
+        let remote_ephemeral = remote_ephemeral_key.to_bytes();
+        let remote_nonce = self.remote_nonce.expect("remote nonce is not known");
+        let remote_version = self
+            .remote_protocol_version
+            .expect("remote protocol version is not known");
+
+        let (versions, ids, ephemerals, nonces) = match self.role {
+            Role::Client => (
+                [PROTOCOL_VERSION, remote_version],
+                [local_id, remote_id],
+                [local_ephemeral, remote_ephemeral],
+                [self.local_nonce, remote_nonce],
+            ),
+            Role::Gateway => (
+                [remote_version, PROTOCOL_VERSION],
+                [remote_id, local_id],
+                [remote_ephemeral, local_ephemeral],
+                [remote_nonce, self.local_nonce],
+            ),
+        };
+
+        TRANSCRIPT_LABEL
+            .iter()
+            .chain(versions.iter())
+            .chain(ids.iter().flatten())
+            .chain(ephemerals.iter().flatten())
+            .chain(nonces.iter().flatten())
+            .copied()
+            .collect()
+    }
+
+    // the message signed by the specified party to prove the possession of its identity key
+    fn signed_message(
+        &self,
+        signer: Role,
+        remote_ephemeral_key: &encryption::PublicKey,
+    ) -> Vec<u8> {
+        let local_ephemeral = self.ephemeral_keypair.public_key().to_bytes();
+        let remote_ephemeral = remote_ephemeral_key.to_bytes();
+        match self.version {
+            // G^x || G^y, where x is the signer's and y is the other party's ephemeral key
+            HandshakeVersion::Legacy => {
+                let (first, second) = if signer == self.role {
+                    (local_ephemeral, remote_ephemeral)
+                } else {
+                    (remote_ephemeral, local_ephemeral)
+                };
+                first.iter().chain(second.iter()).copied().collect()
+            }
+            // SIGNER_LABEL || TRANSCRIPT
+            HandshakeVersion::TranscriptBound => signer
+                .signature_label()
+                .iter()
+                .copied()
+                .chain(self.transcript(remote_ephemeral_key))
+                .collect(),
         }
     }
 
@@ -107,9 +332,15 @@ impl<'a, S> State<'a, S> {
             .private_key()
             .diffie_hellman(remote_ephemeral_key);
 
+        // bind the derived keys to the whole handshake transcript
+        let salt = match self.version {
+            HandshakeVersion::Legacy => None,
+            HandshakeVersion::TranscriptBound => Some(self.transcript(remote_ephemeral_key)),
+        };
+
         // there is no reason for this to fail as our okm is expected to be only 16 bytes
         let okm = hkdf::extract_then_expand::<GatewaySharedKeyHkdfAlgorithm>(
-            None,
+            salt.as_deref(),
             &dh_result,
             None,
             SharedKeySize::to_usize(),
@@ -122,20 +353,13 @@ impl<'a, S> State<'a, S> {
         self.derived_shared_keys = Some(derived_shared_key)
     }
 
-    // produces AES(k, SIG(ID_PRIV, G^x || G^y),
-    // assuming x is local and y is remote
+    // produces AES(k, SIG(ID_PRIV, G^x || G^y) in the legacy handshake, assuming x is local
+    // and y is remote, or AES(k, SIG(ID_PRIV, LOCAL_LABEL || TRANSCRIPT)) in the transcript-bound one
     pub(crate) fn prepare_key_material_sig(
         &self,
         remote_ephemeral_key: &encryption::PublicKey,
     ) -> Vec<u8> {
-        let message: Vec<_> = self
-            .ephemeral_keypair
-            .public_key()
-            .to_bytes()
-            .iter()
-            .cloned()
-            .chain(remote_ephemeral_key.to_bytes().iter().cloned())
-            .collect();
+        let message = self.signed_message(self.role, remote_ephemeral_key);
 
         let signature = self.identity.private_key().sign(&message);
         let zero_iv = stream_cipher::zero_iv::<GatewayEncryptionAlgorithm>();
@@ -174,19 +398,8 @@ impl<'a, S> State<'a, S> {
         let signature = identity::Signature::from_bytes(&decrypted_signature)
             .map_err(|_| HandshakeError::InvalidSignature)?;
 
-        // g^y || g^x, if y is remote and x is local
-        let signed_payload: Vec<_> = remote_ephemeral_key
-            .to_bytes()
-            .iter()
-            .cloned()
-            .chain(
-                self.ephemeral_keypair
-                    .public_key()
-                    .to_bytes()
-                    .iter()
-                    .cloned(),
-            )
-            .collect();
+        // g^y || g^x, if y is remote and x is local (or REMOTE_LABEL || TRANSCRIPT)
+        let signed_payload = self.signed_message(self.role.remote(), remote_ephemeral_key);
 
         self.remote_pubkey
             .as_ref()
@@ -200,7 +413,9 @@ impl<'a, S> State<'a, S> {
         self.remote_pubkey = Some(remote_pubkey)
     }
 
-    pub(crate) async fn receive_handshake_message(&mut self) -> Result<Vec<u8>, HandshakeError>
+    pub(crate) async fn receive_handshake_message(
+        &mut self,
+    ) -> Result<ReceivedPayload, HandshakeError>
     where
         S: Stream<Item = WsItem> + Unpin,
     {
@@ -210,10 +425,7 @@ impl<'a, S> State<'a, S> {
                     match msg {
                         WsMessage::Text(ws_msg) => match types::RegistrationHandshake::try_from(ws_msg) {
                             Ok(reg_handshake_msg) => return match reg_handshake_msg {
-                                // hehe, that's a bit disgusting that the type system requires we explicitly ignore the
-                                // protocol_version field that we actually never attach at this point
-                                // yet another reason for the overdue refactor
-                                types::RegistrationHandshake::HandshakePayload {  data, .. } => Ok(data),
+                                types::RegistrationHandshake::HandshakePayload { protocol_version, nonce, versions_mac, data, .. } => Ok(ReceivedPayload { protocol_version, nonce, versions_mac, data }),
                                 types::RegistrationHandshake::HandshakeError { message } => Err(HandshakeError::RemoteError(message)),
                             },
                             Err(_) => error!("Received a non-handshake message during the registration handshake! It's getting dropped."),
@@ -251,7 +463,11 @@ impl<'a, S> State<'a, S> {
     where
         S: Sink<WsMessage> + Unpin,
     {
-        let handshake_message = types::RegistrationHandshake::new_payload(payload);
+        let handshake_message = types::RegistrationHandshake::new_payload(
+            payload,
+            self.nonce_to_send(),
+            self.versions_mac_to_send(),
+        );
         self.ws_stream
             .send(WsMessage::Text(handshake_message.try_into().unwrap()))
             .await
@@ -264,3 +480,176 @@ impl<'a, S> State<'a, S> {
         self.derived_shared_keys.unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Party {
+        identity: identity::KeyPair,
+        stream: (),
+    }
+
+    impl Party {
+        fn new() -> Self {
+            Party {
+                identity: identity::KeyPair::new(&mut rand::rngs::OsRng),
+                stream: (),
+            }
+        }
+    }
+
+    fn states<'a>(
+        client: &'a mut Party,
+        gateway: &'a mut Party,
+        version: Option<u8>,
+    ) -> (State<'a, ()>, State<'a, ()>) {
+        let mut rng = rand::rngs::OsRng;
+        let gateway_identity = *gateway.identity.public_key();
+        let client_identity = *client.identity.public_key();
+
+        let mut client_state = State::new(
+            &mut rng,
+            &mut client.stream,
+            &client.identity,
+            Some(gateway_identity),
+            Role::Client,
+        );
+        let mut gateway_state = State::new(
+            &mut rng,
+            &mut gateway.stream,
+            &gateway.identity,
+            None,
+            Role::Gateway,
+        );
+        gateway_state.update_remote_identity(client_identity);
+        gateway_state
+            .set_remote_parameters(version, client_state.nonce_to_send())
+            .unwrap();
+        client_state
+            .set_remote_parameters(version, gateway_state.nonce_to_send())
+            .unwrap();
+
+        let client_ephemeral = *client_state.ephemeral_keypair.public_key();
+        let gateway_ephemeral = *gateway_state.ephemeral_keypair.public_key();
+        client_state.derive_shared_key(&gateway_ephemeral);
+        gateway_state.derive_shared_key(&client_ephemeral);
+        (client_state, gateway_state)
+    }
+
+    #[test]
+    fn both_handshake_versions_establish_the_same_keys() {
+        for version in [None, Some(PROTOCOL_VERSION)] {
+            let mut client = Party::new();
+            let mut gateway = Party::new();
+            let (client_state, gateway_state) = states(&mut client, &mut gateway, version);
+            let client_ephemeral = *client_state.ephemeral_keypair.public_key();
+            let gateway_ephemeral = *gateway_state.ephemeral_keypair.public_key();
+
+            let gateway_material = gateway_state.prepare_key_material_sig(&client_ephemeral);
+            client_state
+                .verify_remote_key_material(&gateway_material, &gateway_ephemeral)
+                .unwrap();
+            let client_material = client_state.prepare_key_material_sig(&gateway_ephemeral);
+            gateway_state
+                .verify_remote_key_material(&client_material, &client_ephemeral)
+                .unwrap();
+
+            assert_eq!(
+                client_state.finalize_handshake().to_bytes(),
+                gateway_state.finalize_handshake().to_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn transcript_bound_signatures_cannot_be_replayed_or_reflected() {
+        let mut client = Party::new();
+        let mut gateway = Party::new();
+        let (client_state, gateway_state) =
+            states(&mut client, &mut gateway, Some(PROTOCOL_VERSION));
+        let client_ephemeral = *client_state.ephemeral_keypair.public_key();
+        let gateway_ephemeral = *gateway_state.ephemeral_keypair.public_key();
+
+        // the gateway's own signature can't be reflected back at it as the client's one
+        let gateway_material = gateway_state.prepare_key_material_sig(&client_ephemeral);
+        assert!(gateway_state
+            .verify_remote_key_material(&gateway_material, &client_ephemeral)
+            .is_err());
+
+        // a signature from one session is useless in another one with a fresh gateway nonce
+        let client_material = client_state.prepare_key_material_sig(&gateway_ephemeral);
+        let mut other_stream = ();
+        let mut other_gateway_state = State::new(
+            &mut rand::rngs::OsRng,
+            &mut other_stream,
+            &gateway.identity,
+            None,
+            Role::Gateway,
+        );
+        other_gateway_state.update_remote_identity(*client.identity.public_key());
+        other_gateway_state
+            .set_remote_parameters(Some(PROTOCOL_VERSION), client_state.nonce_to_send())
+            .unwrap();
+        other_gateway_state.ephemeral_keypair = gateway_state.ephemeral_keypair;
+        other_gateway_state.derive_shared_key(&client_ephemeral);
+        assert!(other_gateway_state
+            .verify_remote_key_material(&client_material, &client_ephemeral)
+            .is_err());
+    }
+
+    #[test]
+    fn transcript_bound_handshake_requires_a_nonce() {
+        let mut stream = ();
+        let identity = identity::KeyPair::new(&mut rand::rngs::OsRng);
+        let mut state = State::new(
+            &mut rand::rngs::OsRng,
+            &mut stream,
+            &identity,
+            None,
+            Role::Gateway,
+        );
+        assert!(matches!(
+            state.set_remote_parameters(Some(PROTOCOL_VERSION), None),
+            Err(HandshakeError::InvalidNonce)
+        ));
+        assert!(matches!(
+            state.set_remote_parameters(Some(PROTOCOL_VERSION), Some(vec![1, 2, 3])),
+            Err(HandshakeError::InvalidNonce)
+        ));
+        assert!(state.set_remote_parameters(Some(3), None).is_ok());
+    }
+
+    #[test]
+    fn stripped_protocol_versions_are_detected() {
+        // both parties support the transcript-bound handshake, but neither saw the version
+        // of the other one, so they both fell back to the legacy handshake
+        let mut client = Party::new();
+        let mut gateway = Party::new();
+        let (client_state, gateway_state) = states(&mut client, &mut gateway, None);
+
+        let gateway_mac = gateway_state.versions_mac_to_send();
+        let client_mac = client_state.versions_mac_to_send();
+        assert!(matches!(
+            client_state.verify_remote_versions(gateway_mac.as_deref()),
+            Err(HandshakeError::DowngradeDetected)
+        ));
+        assert!(matches!(
+            gateway_state.verify_remote_versions(client_mac.as_deref()),
+            Err(HandshakeError::DowngradeDetected)
+        ));
+
+        // genuine legacy peers don't attach the mac at all
+        assert!(client_state.verify_remote_versions(None).is_ok());
+    }
+
+    #[test]
+    fn versions_mac_is_only_attached_to_legacy_handshakes() {
+        let mut client = Party::new();
+        let mut gateway = Party::new();
+        let (client_state, gateway_state) =
+            states(&mut client, &mut gateway, Some(PROTOCOL_VERSION));
+        assert!(client_state.versions_mac_to_send().is_none());
+        assert!(gateway_state.versions_mac_to_send().is_none());
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replay protection for the binary frames exchanged on the shared-key encrypted channel
+//! between a client and its gateway.
+//!
+//! Each authenticated session has a random session nonce chosen by the gateway. Every binary frame
+//! sent within the session (in either direction) has a [`FrameHeader`] prepended to its plaintext
+//! that contains that nonce alongside a monotonically increasing sequence number.
+//! The receiving side rejects frames belonging to a different session as well as frames whose
+//! sequence number has either already been seen or is too old to be tracked by its [`ReplayWindow`].
+
+use rand::{CryptoRng, RngCore};
+use std::convert::TryInto;
+use thiserror::Error;
+
+/// Number of most recent sequence numbers tracked by the [`ReplayWindow`].
+pub const REPLAY_WINDOW_SIZE: u64 = 128;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReplayError {
+    #[error("the frame is too short to contain a valid header")]
+    TooShortFrame,
+
+    #[error("the frame belongs to a different session")]
+    SessionMismatch,
+
+    #[error("the frame with sequence number {0} has already been received")]
+    Replayed(u64),
+
+    #[error("the frame with sequence number {sequence} is too old. The current window starts at {window_start}")]
+    TooOld { sequence: u64, window_start: u64 },
+}
+
+/// Generates a fresh random session nonce for a newly authenticated connection.
+pub fn new_session_nonce<R: RngCore + CryptoRng>(rng: &mut R) -> u64 {
+    rng.next_u64()
+}
+
+/// Header prepended to plaintext of every binary frame on a replay-protected channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub session_nonce: u64,
+    pub sequence: u64,
+}
+
+impl FrameHeader {
+    pub const LEN: usize = 16;
+
+    pub fn to_bytes(self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..8].copy_from_slice(&self.session_nonce.to_be_bytes());
+        bytes[8..].copy_from_slice(&self.sequence.to_be_bytes());
+        bytes
+    }
+
+    /// Attempts to split the provided plaintext into the frame header and the actual payload.
+    pub fn split_frame(frame: &[u8]) -> Result<(Self, &[u8]), ReplayError> {
+        if frame.len() < Self::LEN {
+            return Err(ReplayError::TooShortFrame);
+        }
+
+        // the unwraps are fine as we have just checked the length of the frame
+        let header = FrameHeader {
+            session_nonce: u64::from_be_bytes(frame[..8].try_into().unwrap()),
+            sequence: u64::from_be_bytes(frame[8..Self::LEN].try_into().unwrap()),
+        };
+        Ok((header, &frame[Self::LEN..]))
+    }
+
+    /// Prepends the encoded header to the provided payload.
+    pub fn prepend_to(self, payload: &[u8]) -> Vec<u8> {
+        self.to_bytes()
+            .iter()
+            .chain(payload.iter())
+            .copied()
+            .collect()
+    }
+}
+
+/// Sliding window keeping track of the recently received sequence numbers.
+#[derive(Debug, Default, Clone)]
+pub struct ReplayWindow {
+    /// The highest sequence number received so far, if any.
+    highest: Option<u64>,
+
+    /// Bitmap of received sequence numbers. Bit `i` is set if `highest - i` has been received.
+    seen: u128,
+}
+
+impl ReplayWindow {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Checks whether a frame with the provided sequence number would be accepted by the window
+    /// without modifying its state.
+    pub fn check(&self, sequence: u64) -> Result<(), ReplayError> {
+        let highest = match self.highest {
+            None => return Ok(()),
+            Some(highest) => highest,
+        };
+
+        if sequence > highest {
+            return Ok(());
+        }
+
+        let offset = highest - sequence;
+        if offset >= REPLAY_WINDOW_SIZE {
+            return Err(ReplayError::TooOld {
+                sequence,
+                window_start: highest + 1 - REPLAY_WINDOW_SIZE,
+            });
+        }
+
+        if self.seen & (1u128 << offset) != 0 {
+            Err(ReplayError::Replayed(sequence))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Marks the provided sequence number as received.
+    /// Note that it should only be called after the frame has been fully authenticated.
+    pub fn mark(&mut self, sequence: u64) {
+        match self.highest {
+            None => {
+                self.highest = Some(sequence);
+                self.seen = 1;
+            }
+            Some(highest) if sequence > highest => {
+                let shift = sequence - highest;
+                self.seen = if shift >= REPLAY_WINDOW_SIZE {
+                    0
+                } else {
+                    self.seen << shift
+                };
+                self.seen |= 1;
+                self.highest = Some(sequence);
+            }
+            Some(highest) => {
+                let offset = highest - sequence;
+                if offset < REPLAY_WINDOW_SIZE {
+                    self.seen |= 1u128 << offset;
+                }
+            }
+        }
+    }
+
+    pub fn check_and_mark(&mut self, sequence: u64) -> Result<(), ReplayError> {
+        self.check(sequence)?;
+        self.mark(sequence);
+        Ok(())
+    }
+}
+
+/// Produces headers for the frames sent on a replay-protected channel.
+#[derive(Debug, Clone)]
+pub struct OutboundSequence {
+    session_nonce: u64,
+    next: u64,
+}
+
+impl OutboundSequence {
+    pub fn new(session_nonce: u64) -> Self {
+        OutboundSequence {
+            session_nonce,
+            next: 0,
+        }
+    }
+
+    pub fn next_header(&mut self) -> FrameHeader {
+        let header = FrameHeader {
+            session_nonce: self.session_nonce,
+            sequence: self.next,
+        };
+        self.next += 1;
+        header
+    }
+}
+
+/// Validates headers of the frames received on a replay-protected channel.
+#[derive(Debug, Clone)]
+pub struct InboundFilter {
+    session_nonce: u64,
+    window: ReplayWindow,
+}
+
+impl InboundFilter {
+    pub fn new(session_nonce: u64) -> Self {
+        InboundFilter {
+            session_nonce,
+            window: ReplayWindow::new(),
+        }
+    }
+
+    pub fn accept(&mut self, header: FrameHeader) -> Result<(), ReplayError> {
+        if header.session_nonce != self.session_nonce {
+            return Err(ReplayError::SessionMismatch);
+        }
+        self.window.check_and_mark(header.sequence)
+    }
+
+    /// Strips and validates the header of the provided frame returning the remaining payload.
+    pub fn open_frame(&mut self, frame: &[u8]) -> Result<Vec<u8>, ReplayError> {
+        let (header, payload) = FrameHeader::split_frame(frame)?;
+        self.accept(header)?;
+        Ok(payload.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_accepts_fresh_sequence_numbers() {
+        let mut window = ReplayWindow::new();
+        for i in 0..1000 {
+            assert!(window.check_and_mark(i).is_ok());
+        }
+    }
+
+    #[test]
+    fn window_rejects_duplicates() {
+        let mut window = ReplayWindow::new();
+        window.check_and_mark(5).unwrap();
+        window.check_and_mark(3).unwrap();
+        assert_eq!(window.check_and_mark(5), Err(ReplayError::Replayed(5)));
+        assert_eq!(window.check_and_mark(3), Err(ReplayError::Replayed(3)));
+        assert!(window.check_and_mark(4).is_ok());
+    }
+
+    #[test]
+    fn window_rejects_sequence_numbers_outside_the_window() {
+        let mut window = ReplayWindow::new();
+        window.check_and_mark(0).unwrap();
+        window.check_and_mark(REPLAY_WINDOW_SIZE + 10).unwrap();
+
+        assert_eq!(
+            window.check(10),
+            Err(ReplayError::TooOld {
+                sequence: 10,
+                window_start: 11
+            })
+        );
+        assert!(window.check(11).is_ok());
+    }
+
+    #[test]
+    fn window_forgets_everything_after_large_jump() {
+        let mut window = ReplayWindow::new();
+        window.check_and_mark(1).unwrap();
+        window.check_and_mark(2 * REPLAY_WINDOW_SIZE).unwrap();
+        assert!(window.check(REPLAY_WINDOW_SIZE + 1).is_ok());
+        assert!(window.check(2 * REPLAY_WINDOW_SIZE).is_err());
+    }
+
+    #[test]
+    fn inbound_filter_rejects_frames_from_other_sessions() {
+        let mut outbound = OutboundSequence::new(42);
+        let mut other_outbound = OutboundSequence::new(43);
+        let mut filter = InboundFilter::new(42);
+
+        let frame = outbound.next_header().prepend_to(&[1, 2, 3]);
+        let foreign_frame = other_outbound.next_header().prepend_to(&[1, 2, 3]);
+
+        assert_eq!(
+            filter.open_frame(&foreign_frame),
+            Err(ReplayError::SessionMismatch)
+        );
+        assert_eq!(filter.open_frame(&frame).unwrap(), vec![1, 2, 3]);
+        assert_eq!(filter.open_frame(&frame), Err(ReplayError::Replayed(0)));
+    }
+
+    #[test]
+    fn too_short_frames_are_rejected() {
+        let mut filter = InboundFilter::new(42);
+        assert_eq!(
+            filter.open_frame(&[0; FrameHeader::LEN - 1]),
+            Err(ReplayError::TooShortFrame)
+        );
+    }
+}
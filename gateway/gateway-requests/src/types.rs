@@ -1,11 +1,15 @@
 // Copyright 2020 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::authentication::attach_proof::AttachNonce;
 use crate::authentication::encrypted_address::EncryptedAddressBytes;
+use crate::capabilities::Capabilities;
 use crate::iv::IV;
 use crate::registration::handshake::SharedKeys;
+use crate::replay::{FrameHeader, InboundFilter, ReplayError};
 use crate::{GatewayMacSize, PROTOCOL_VERSION};
 use nym_coconut_interface::Credential;
+use nym_crypto::asymmetric::identity;
 use nym_crypto::generic_array::typenum::Unsigned;
 use nym_crypto::hmac::recompute_keyed_hmac_and_verify_tag;
 use nym_crypto::symmetric::stream_cipher;
@@ -18,6 +22,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     convert::{TryFrom, TryInto},
     fmt::{self, Error, Formatter},
+    net::SocketAddr,
 };
 use tungstenite::protocol::Message;
 
@@ -27,6 +32,17 @@ pub enum RegistrationHandshake {
     HandshakePayload {
         #[serde(default)]
         protocol_version: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        capabilities: Option<Capabilities>,
+        /// Fresh nonce contributed to the handshake transcript by the sender.
+        /// Ignored by the peers using the legacy handshake.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        nonce: Option<Vec<u8>>,
+        /// MAC over the protocol versions advertised by both parties, as seen by the sender.
+        /// Attached by the peers supporting the transcript-bound handshake whenever they fall back
+        /// to the legacy one, so that a downgrade could be detected. Ignored by the legacy peers.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        versions_mac: Option<Vec<u8>>,
         data: Vec<u8>,
     },
     HandshakeError {
@@ -35,9 +51,16 @@ pub enum RegistrationHandshake {
 }
 
 impl RegistrationHandshake {
-    pub fn new_payload(data: Vec<u8>) -> Self {
+    pub fn new_payload(
+        data: Vec<u8>,
+        nonce: Option<Vec<u8>>,
+        versions_mac: Option<Vec<u8>>,
+    ) -> Self {
         RegistrationHandshake::HandshakePayload {
             protocol_version: Some(PROTOCOL_VERSION),
+            capabilities: Some(Capabilities::supported()),
+            nonce,
+            versions_mac,
             data,
         }
     }
@@ -75,6 +98,7 @@ pub enum GatewayRequestsError {
     MalformedEncryption,
     InvalidPacketMode,
     InvalidMixPacket(MixPacketFormattingError),
+    ReplayedFrame(ReplayError),
 }
 
 impl fmt::Display for GatewayRequestsError {
@@ -95,7 +119,8 @@ impl fmt::Display for GatewayRequestsError {
             MalformedSphinxPacket => write!(f, "received sphinx packet was malformed"),
             MalformedEncryption => write!(f, "the received encrypted data was malformed"),
             InvalidPacketMode => write!(f, "provided packet mode is invalid"),
-            InvalidMixPacket(err) => write!(f, "provided mix packet was malformed - {err}")
+            InvalidMixPacket(err) => write!(f, "provided mix packet was malformed - {err}"),
+            ReplayedFrame(err) => write!(f, "the received frame failed the replay check - {err}"),
         }
     }
 }
@@ -114,6 +139,12 @@ impl From<MixPacketFormattingError> for GatewayRequestsError {
     }
 }
 
+impl From<ReplayError> for GatewayRequestsError {
+    fn from(err: ReplayError) -> Self {
+        GatewayRequestsError::ReplayedFrame(err)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum ClientControlRequest {
@@ -122,6 +153,9 @@ pub enum ClientControlRequest {
     Authenticate {
         #[serde(default)]
         protocol_version: Option<u8>,
+        /// Optional protocol features the client is willing to use.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        capabilities: Option<Capabilities>,
         address: String,
         enc_address: String,
         iv: String,
@@ -130,6 +164,12 @@ pub enum ClientControlRequest {
     RegisterHandshakeInitRequest {
         #[serde(default)]
         protocol_version: Option<u8>,
+        /// Optional protocol features the client is willing to use.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        capabilities: Option<Capabilities>,
+        /// Fresh nonce of the client, used by the transcript-bound handshake.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        nonce: Option<Vec<u8>>,
         data: Vec<u8>,
     },
     BandwidthCredential {
@@ -137,6 +177,20 @@ pub enum ClientControlRequest {
         iv: Vec<u8>,
     },
     ClaimFreeTestnetBandwidth,
+    /// Request for a fresh nonce that has to be signed in order to attach to the home gateway.
+    GetAttachNonce,
+    /// Request for the gateway to fetch messages on behalf of the client from its home gateway,
+    /// i.e. the one it has originally registered with and that is included in its address.
+    /// The `signature` is made with the identity key of the client on the `attach_proof_message`.
+    AttachHomeGateway {
+        home_gateway: String,
+        nonce: String,
+        signature: String,
+    },
+    GetBandwidthBalance,
+    /// Acknowledgement of the last received page of stored messages, allowing the gateway
+    /// to remove them and push the next page.
+    AckStoredMessages,
 }
 
 impl ClientControlRequest {
@@ -147,12 +201,27 @@ impl ClientControlRequest {
     ) -> Self {
         ClientControlRequest::Authenticate {
             protocol_version: Some(PROTOCOL_VERSION),
+            capabilities: Some(Capabilities::supported()),
             address: address.as_base58_string(),
             enc_address: enc_address.to_base58_string(),
             iv: iv.to_base58_string(),
         }
     }
 
+    /// Creates a request to attach to the specified home gateway. The `signature` has to be made
+    /// on the `attach_proof_message` constructed for the `nonce` issued by the gateway we're connected to.
+    pub fn new_attach_home_gateway(
+        home_gateway: identity::PublicKey,
+        nonce: AttachNonce,
+        signature: identity::Signature,
+    ) -> Self {
+        ClientControlRequest::AttachHomeGateway {
+            home_gateway: home_gateway.to_base58_string(),
+            nonce: nonce.to_base58_string(),
+            signature: signature.to_base58_string(),
+        }
+    }
+
     pub fn new_enc_coconut_bandwidth_credential(
         credential: &Credential,
         shared_key: &SharedKeys,
@@ -203,19 +272,42 @@ impl TryInto<String> for ClientControlRequest {
     }
 }
 
+/// Unspent bandwidth bought with credentials issued in particular epoch.
+/// It expires once the epoch is no longer considered valid.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochBandwidth {
+    pub epoch_id: u64,
+    pub remaining: i64,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum ServerResponse {
     Authenticate {
         #[serde(default)]
         protocol_version: Option<u8>,
+        /// Optional protocol features that are going to be used within this session.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        capabilities: Option<Capabilities>,
         status: bool,
         bandwidth_remaining: i64,
+        /// Nonce of this session that has to be included in all binary frames.
+        /// Only present if the negotiated protocol supports replay protection.
+        #[serde(default)]
+        session_nonce: Option<u64>,
     },
     Register {
         #[serde(default)]
         protocol_version: Option<u8>,
+        /// Optional protocol features that are going to be used within this session.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        capabilities: Option<Capabilities>,
         status: bool,
+        /// Nonce of this session that has to be included in all binary frames.
+        /// Only present if the negotiated protocol supports replay protection.
+        #[serde(default)]
+        session_nonce: Option<u64>,
     },
     Bandwidth {
         available_total: i64,
@@ -223,6 +315,31 @@ pub enum ServerResponse {
     Send {
         remaining_bandwidth: i64,
     },
+    AttachNonce {
+        nonce: String,
+    },
+    HomeGatewayAttached {
+        status: bool,
+    },
+    BandwidthBalance {
+        available_total: i64,
+        /// Part of the available bandwidth that is tied to particular epochs.
+        epochs: Vec<EpochBandwidth>,
+    },
+    /// Marks the end of a page of stored messages pushed to the client, which has to be
+    /// acknowledged with [`ClientControlRequest::AckStoredMessages`].
+    StoredMessagesPage {
+        /// Number of messages included in the page.
+        count: usize,
+        /// Indicates whether there are more stored messages to be pushed after this page.
+        more: bool,
+    },
+    /// The first hop of the sent packet is currently unreachable, so rather than being forwarded,
+    /// the packet has been dropped. The client should route its subsequent packets through
+    /// a different node.
+    UnreachableHop {
+        address: SocketAddr,
+    },
     Error {
         message: String,
     },
@@ -239,6 +356,25 @@ impl ServerResponse {
         matches!(self, ServerResponse::Error { .. })
     }
 
+    pub fn is_stored_messages_page(&self) -> bool {
+        matches!(self, ServerResponse::StoredMessagesPage { .. })
+    }
+
+    pub fn unreachable_hop(&self) -> Option<SocketAddr> {
+        match self {
+            ServerResponse::UnreachableHop { address } => Some(*address),
+            _ => None,
+        }
+    }
+
+    pub fn session_nonce(&self) -> Option<u64> {
+        match self {
+            ServerResponse::Authenticate { session_nonce, .. } => *session_nonce,
+            ServerResponse::Register { session_nonce, .. } => *session_nonce,
+            _ => None,
+        }
+    }
+
     pub fn implies_successful_authentication(&self) -> bool {
         match self {
             ServerResponse::Authenticate { status, .. } => *status,
@@ -269,6 +405,26 @@ pub enum BinaryRequest {
     ForwardSphinx(MixPacket),
 }
 
+/// Removes the replay protection header from the decrypted frame (if the channel is protected)
+/// and checks it against the provided filter.
+fn open_frame(
+    plaintext: Vec<u8>,
+    inbound_filter: Option<&mut InboundFilter>,
+) -> Result<Vec<u8>, GatewayRequestsError> {
+    match inbound_filter {
+        Some(filter) => Ok(filter.open_frame(&plaintext)?),
+        None => Ok(plaintext),
+    }
+}
+
+/// Prepends the replay protection header to the frame (if the channel is protected).
+fn seal_frame(payload: Vec<u8>, header: Option<FrameHeader>) -> Vec<u8> {
+    match header {
+        Some(header) => header.prepend_to(&payload),
+        None => payload,
+    }
+}
+
 // Right now the only valid `BinaryRequest` is a request to forward a sphinx packet.
 // It is encrypted using the derived shared key between client and the gateway. Thanks to
 // randomness inside the sphinx packet themselves (even via the same route), the 0s IV can be used here.
@@ -278,19 +434,25 @@ impl BinaryRequest {
     pub fn try_from_encrypted_tagged_bytes(
         raw_req: Vec<u8>,
         shared_keys: &SharedKeys,
+        inbound_filter: Option<&mut InboundFilter>,
     ) -> Result<Self, GatewayRequestsError> {
-        let message_bytes = &shared_keys.decrypt_tagged(&raw_req, None)?;
+        let plaintext = shared_keys.decrypt_tagged(&raw_req, None)?;
+        let message_bytes = open_frame(plaintext, inbound_filter)?;
 
         // right now there's only a single option possible which significantly simplifies the logic
         // if we decided to allow for more 'binary' messages, the API wouldn't need to change.
-        let mix_packet = MixPacket::try_from_bytes(message_bytes)?;
+        let mix_packet = MixPacket::try_from_bytes(&message_bytes)?;
         Ok(BinaryRequest::ForwardSphinx(mix_packet))
     }
 
-    pub fn into_encrypted_tagged_bytes(self, shared_key: &SharedKeys) -> Vec<u8> {
+    pub fn into_encrypted_tagged_bytes(
+        self,
+        shared_key: &SharedKeys,
+        header: Option<FrameHeader>,
+    ) -> Vec<u8> {
         match self {
             BinaryRequest::ForwardSphinx(mix_packet) => {
-                let forwarding_data = mix_packet.into_bytes();
+                let forwarding_data = seal_frame(mix_packet.into_bytes(), header);
 
                 // TODO: it could be theoretically slightly more efficient if the data wasn't taken
                 // by reference because then it makes a copy for encryption rather than do it in place
@@ -304,8 +466,8 @@ impl BinaryRequest {
         BinaryRequest::ForwardSphinx(mix_packet)
     }
 
-    pub fn into_ws_message(self, shared_key: &SharedKeys) -> Message {
-        Message::Binary(self.into_encrypted_tagged_bytes(shared_key))
+    pub fn into_ws_message(self, shared_key: &SharedKeys, header: Option<FrameHeader>) -> Message {
+        Message::Binary(self.into_encrypted_tagged_bytes(shared_key, header))
     }
 }
 
@@ -318,6 +480,7 @@ impl BinaryResponse {
     pub fn try_from_encrypted_tagged_bytes(
         raw_req: Vec<u8>,
         shared_keys: &SharedKeys,
+        inbound_filter: Option<&mut InboundFilter>,
     ) -> Result<Self, GatewayRequestsError> {
         let mac_size = GatewayMacSize::to_usize();
         if raw_req.len() < mac_size {
@@ -342,14 +505,21 @@ impl BinaryResponse {
             message_bytes,
         );
 
-        Ok(BinaryResponse::PushedMixMessage(plaintext))
+        let message = open_frame(plaintext, inbound_filter)?;
+        Ok(BinaryResponse::PushedMixMessage(message))
     }
 
-    pub fn into_encrypted_tagged_bytes(self, shared_key: &SharedKeys) -> Vec<u8> {
+    pub fn into_encrypted_tagged_bytes(
+        self,
+        shared_key: &SharedKeys,
+        header: Option<FrameHeader>,
+    ) -> Vec<u8> {
         match self {
             // TODO: it could be theoretically slightly more efficient if the data wasn't taken
             // by reference because then it makes a copy for encryption rather than do it in place
-            BinaryResponse::PushedMixMessage(message) => shared_key.encrypt_and_tag(&message, None),
+            BinaryResponse::PushedMixMessage(message) => {
+                shared_key.encrypt_and_tag(&seal_frame(message, header), None)
+            }
         }
     }
 
@@ -357,8 +527,8 @@ impl BinaryResponse {
         BinaryResponse::PushedMixMessage(msg)
     }
 
-    pub fn into_ws_message(self, shared_key: &SharedKeys) -> Message {
-        Message::Binary(self.into_encrypted_tagged_bytes(shared_key))
+    pub fn into_ws_message(self, shared_key: &SharedKeys, header: Option<FrameHeader>) -> Message {
+        Message::Binary(self.into_encrypted_tagged_bytes(shared_key, header))
     }
 }
 
@@ -371,6 +541,9 @@ mod tests {
         let handshake_data = vec![1, 2, 3, 4, 5, 6];
         let handshake_payload_with_protocol = RegistrationHandshake::HandshakePayload {
             protocol_version: Some(42),
+            capabilities: Some(Capabilities::REPLAY_PROTECTION),
+            nonce: Some(vec![7, 8, 9]),
+            versions_mac: None,
             data: handshake_data.clone(),
         };
         let serialized = serde_json::to_string(&handshake_payload_with_protocol).unwrap();
@@ -379,9 +552,13 @@ mod tests {
         match deserialized {
             ClientControlRequest::RegisterHandshakeInitRequest {
                 protocol_version,
+                capabilities,
+                nonce,
                 data,
             } => {
                 assert_eq!(protocol_version, Some(42));
+                assert_eq!(capabilities, Some(Capabilities::REPLAY_PROTECTION));
+                assert_eq!(nonce, Some(vec![7, 8, 9]));
                 assert_eq!(data, handshake_data)
             }
             _ => unreachable!("this branch shouldn't have been reached!"),
@@ -389,6 +566,9 @@ mod tests {
 
         let handshake_payload_without_protocol = RegistrationHandshake::HandshakePayload {
             protocol_version: None,
+            capabilities: None,
+            nonce: None,
+            versions_mac: None,
             data: handshake_data.clone(),
         };
         let serialized = serde_json::to_string(&handshake_payload_without_protocol).unwrap();
@@ -397,12 +577,66 @@ mod tests {
         match deserialized {
             ClientControlRequest::RegisterHandshakeInitRequest {
                 protocol_version,
+                capabilities,
+                nonce,
                 data,
             } => {
                 assert!(protocol_version.is_none());
+                assert!(capabilities.is_none());
+                assert!(nonce.is_none());
                 assert_eq!(data, handshake_data)
             }
             _ => unreachable!("this branch shouldn't have been reached!"),
         }
     }
+
+    #[test]
+    fn attach_home_gateway_request_survives_serialization() {
+        let mut rng = rand::thread_rng();
+        let home_gateway = identity::KeyPair::new(&mut rng);
+        let request = ClientControlRequest::new_attach_home_gateway(
+            *home_gateway.public_key(),
+            AttachNonce::new(&mut rng, 1_690_000_000),
+            home_gateway.private_key().sign(b"foomp"),
+        );
+        let serialized: String = request.try_into().unwrap();
+        match ClientControlRequest::try_from(serialized).unwrap() {
+            ClientControlRequest::AttachHomeGateway {
+                home_gateway: gateway,
+                ..
+            } => {
+                assert_eq!(gateway, home_gateway.public_key().to_base58_string())
+            }
+            _ => unreachable!("this branch shouldn't have been reached!"),
+        }
+    }
+
+    #[test]
+    fn stored_messages_page_survives_serialization() {
+        let page = ServerResponse::StoredMessagesPage {
+            count: 42,
+            more: true,
+        };
+        let Message::Text(serialized) = page.into() else {
+            unreachable!("responses are always sent as text")
+        };
+        match ServerResponse::try_from(serialized).unwrap() {
+            ServerResponse::StoredMessagesPage { count, more } => {
+                assert_eq!(count, 42);
+                assert!(more)
+            }
+            _ => unreachable!("this branch shouldn't have been reached!"),
+        }
+    }
+
+    #[test]
+    fn unreachable_hop_survives_serialization() {
+        let address: SocketAddr = "1.2.3.4:1789".parse().unwrap();
+        let response = ServerResponse::UnreachableHop { address };
+        let Message::Text(serialized) = response.into() else {
+            unreachable!("responses are always sent as text")
+        };
+        let deserialized = ServerResponse::try_from(serialized).unwrap();
+        assert_eq!(deserialized.unreachable_hop(), Some(address));
+    }
 }
@@ -0,0 +1,16 @@
+/*
+ * Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+ALTER TABLE message_store ADD COLUMN content_size INTEGER NOT NULL DEFAULT 0;
+UPDATE message_store SET content_size = length(content);
+
+-- used for finding the oldest messages of particular clients when choosing the ones to evict
+CREATE INDEX message_store_client_id_index ON message_store (client_address_bs58, id);
+
+CREATE TABLE inbox_activity
+(
+    client_address_bs58 TEXT    NOT NULL PRIMARY KEY UNIQUE,
+    last_active         INTEGER NOT NULL
+);
@@ -0,0 +1,12 @@
+/*
+ * Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+CREATE TABLE epoch_bandwidth
+(
+    client_address_bs58 TEXT    NOT NULL,
+    epoch_id            INTEGER NOT NULL,
+    remaining           INTEGER NOT NULL,
+    PRIMARY KEY (client_address_bs58, epoch_id)
+);
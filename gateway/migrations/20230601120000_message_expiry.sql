@@ -0,0 +1,7 @@
+/*
+ * Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+-- unix timestamp (in seconds) after which the message should no longer be delivered
+ALTER TABLE message_store ADD COLUMN expires_at INTEGER;
@@ -0,0 +1,32 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::commands::{ensure_config_version_compatibility, OverrideConfig};
+use crate::support::config::build_config;
+use clap::Args;
+use nym_bin_common::output_format::OutputFormat;
+use std::error::Error;
+
+#[derive(Args, Clone)]
+pub struct BondInfo {
+    /// The id of the gateway you want to show the bonding information of
+    #[clap(long)]
+    id: String,
+
+    /// Physical location of the gateway, such as the city or the country, announced to the clients
+    #[clap(long, default_value = "")]
+    location: String,
+
+    #[clap(short, long, default_value_t = OutputFormat::default())]
+    output: OutputFormat,
+}
+
+pub async fn execute(args: BondInfo) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let config = build_config(args.id.clone(), OverrideConfig::default())?;
+    ensure_config_version_compatibility(&config)?;
+
+    crate::node::create_gateway(config)
+        .await
+        .print_bonding_information(args.location, None, args.output);
+    Ok(())
+}
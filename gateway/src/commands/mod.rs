@@ -5,6 +5,7 @@ use crate::error::GatewayError;
 use crate::{config::Config, Cli};
 use clap::CommandFactory;
 use clap::Subcommand;
+use nym_bin_common::build_information::BuildInfo;
 use nym_bin_common::completions::{fig_generate, ArgShell};
 use nym_bin_common::version_checker;
 use nym_config::OptionalSet;
@@ -15,14 +16,26 @@ use std::error::Error;
 use std::net::IpAddr;
 use std::path::PathBuf;
 
+pub(crate) mod bond_info;
 pub(crate) mod init;
 pub(crate) mod node_details;
+pub(crate) mod prepare_bond;
 pub(crate) mod run;
 pub(crate) mod sign;
 pub(crate) mod upgrade;
 
 #[derive(Subcommand)]
 pub(crate) enum Commands {
+    /// Show the information required for bonding this gateway
+    BondInfo(bond_info::BondInfo),
+
+    /// Show the information required for bonding this gateway alongside the signature
+    /// on the bonding message for the specified wallet
+    PrepareBond(prepare_bond::PrepareBond),
+
+    /// Show build information of this binary
+    BuildInfo(BuildInfo),
+
     /// Initialise the gateway
     Init(init::Init),
 
@@ -66,6 +79,9 @@ pub(crate) async fn execute(args: Cli) -> Result<(), Box<dyn Error + Send + Sync
     let bin_name = "nym-gateway";
 
     match args.command {
+        Commands::BondInfo(m) => bond_info::execute(m).await?,
+        Commands::PrepareBond(m) => prepare_bond::execute(m).await?,
+        Commands::BuildInfo(m) => m.execute(&crate::BUILD_INFORMATION),
         Commands::Init(m) => init::execute(m).await?,
         Commands::NodeDetails(m) => node_details::execute(m).await?,
         Commands::Run(m) => run::execute(m).await?,
@@ -0,0 +1,81 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::commands::{
+    ensure_config_version_compatibility, ensure_correct_bech32_prefix, OverrideConfig,
+};
+use crate::error::GatewayError;
+use crate::support::config::build_config;
+use clap::Args;
+use nym_bin_common::output_format::OutputFormat;
+use nym_mixnet_contract_common::{Addr, Coin};
+use nym_network_defaults::NymNetworkDetails;
+use nym_types::helpers::BondingRequest;
+use nym_validator_client::nyxd;
+use std::error::Error;
+
+#[derive(Args, Clone)]
+pub struct PrepareBond {
+    /// The id of the gateway you want to bond
+    #[clap(long)]
+    id: String,
+
+    /// Address of the wallet that is going to own the gateway.
+    /// If not provided, the address from the config file is used
+    #[clap(long)]
+    wallet_address: Option<nyxd::AccountId>,
+
+    /// Amount to pledge in the base denomination (so it would be 'unym', rather than 'nym')
+    #[clap(long)]
+    amount: u128,
+
+    /// Current signing nonce of the wallet, as stored in the mixnet contract
+    #[clap(long)]
+    signing_nonce: u32,
+
+    /// Indicates whether the gateway is going to get bonded via a vesting account
+    #[clap(long)]
+    with_vesting_account: bool,
+
+    /// Physical location of the gateway, such as the city or the country, announced to the clients
+    #[clap(long, default_value = "")]
+    location: String,
+
+    #[clap(short, long, default_value_t = OutputFormat::default())]
+    output: OutputFormat,
+}
+
+pub async fn execute(args: PrepareBond) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let config = build_config(args.id.clone(), OverrideConfig::default())?;
+    ensure_config_version_compatibility(&config)?;
+
+    let wallet_address = args
+        .wallet_address
+        .or_else(|| config.get_wallet_address())
+        .ok_or(GatewayError::UnknownWalletAddress)?;
+
+    // perform extra validation to ensure we have correct prefix
+    ensure_correct_bech32_prefix(&wallet_address)?;
+
+    let network_details = NymNetworkDetails::new_from_env();
+    let proxy = if args.with_vesting_account {
+        let vesting_contract = network_details
+            .contracts
+            .vesting_contract_address
+            .ok_or(GatewayError::UnknownVestingContract)?;
+        Some(Addr::unchecked(vesting_contract))
+    } else {
+        None
+    };
+    let bonding_request = BondingRequest {
+        wallet_address: Addr::unchecked(wallet_address.as_ref()),
+        proxy,
+        pledge: Coin::new(args.amount, network_details.chain_details.mix_denom.base),
+        signing_nonce: args.signing_nonce,
+    };
+
+    crate::node::create_gateway(config)
+        .await
+        .print_bonding_information(args.location, Some(bonding_request), args.output);
+    Ok(())
+}
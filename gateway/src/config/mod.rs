@@ -2,15 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::template::config_template;
-use nym_config::defaults::{DEFAULT_CLIENT_LISTENING_PORT, DEFAULT_MIX_LISTENING_PORT};
+use nym_config::defaults::{
+    DEFAULT_CLIENT_LISTENING_PORT, DEFAULT_HTTP_API_LISTENING_PORT, DEFAULT_MIX_LISTENING_PORT,
+};
+use nym_config::paths::default_nym_directory;
 use nym_config::NymConfig;
+use nym_crypto::asymmetric::identity;
+use nym_mixnode_common::listener_guard::{
+    ListenerGuardConfig, DEFAULT_CONNECTION_READ_TIMEOUT, DEFAULT_FRAME_TIMEOUT,
+    DEFAULT_MAXIMUM_FRAME_SIZE,
+};
 use nym_network_defaults::mainnet::{NYM_API, NYXD_URL, STATISTICS_SERVICE_DOMAIN_ADDRESS};
 use nym_validator_client::nyxd;
-use serde::{Deserialize, Serialize};
-use std::net::IpAddr;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Display, Formatter};
+use std::net::{AddrParseError, IpAddr, SocketAddr};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
+use thiserror::Error;
 use url::Url;
 
 pub mod persistence;
@@ -18,6 +29,8 @@ mod template;
 
 pub(crate) const MISSING_VALUE: &str = "MISSING VALUE";
 
+const DEFAULT_FEDERATION_PORT: u16 = 9002;
+
 // 'DEBUG'
 // where applicable, the below are defined in milliseconds
 const DEFAULT_PRESENCE_SENDING_DELAY: Duration = Duration::from_millis(10_000);
@@ -25,9 +38,21 @@ const DEFAULT_PACKET_FORWARDING_INITIAL_BACKOFF: Duration = Duration::from_milli
 const DEFAULT_PACKET_FORWARDING_MAXIMUM_BACKOFF: Duration = Duration::from_millis(300_000);
 const DEFAULT_INITIAL_CONNECTION_TIMEOUT: Duration = Duration::from_millis(1_500);
 const DEFAULT_MAXIMUM_CONNECTION_BUFFER_SIZE: usize = 128;
+const DEFAULT_MAXIMUM_FORWARDING_DELAY: Duration = Duration::from_millis(30_000);
+// large enough for a full page of stored messages returned over a federation link
+const DEFAULT_MAXIMUM_WEBSOCKET_FRAME_SIZE: usize = 4 * 1024 * 1024;
+const DEFAULT_MAXIMUM_WEBSOCKET_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
 
 const DEFAULT_STORED_MESSAGE_FILENAME_LENGTH: u16 = 16;
 const DEFAULT_MESSAGE_RETRIEVAL_LIMIT: i64 = 100;
+const DEFAULT_MESSAGE_STORE_QUOTA: u64 = 16 * 1024 * 1024 * 1024;
+const DEFAULT_MESSAGE_STORE_PER_CLIENT_GUARANTEE: u64 = 16 * 1024 * 1024;
+const DEFAULT_MESSAGE_STORE_WARNING_THRESHOLD: f64 = 0.8;
+const DEFAULT_EXPIRED_MESSAGES_PURGE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+const DEFAULT_BANDWIDTH_RECONCILIATION_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_BANDWIDTH_EPOCH_VALIDITY: u64 = 2;
+const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
 
 pub fn missing_string_value() -> String {
     MISSING_VALUE.to_string()
@@ -37,6 +62,10 @@ fn bind_all_address() -> IpAddr {
     "0.0.0.0".parse().unwrap()
 }
 
+fn loopback_address() -> IpAddr {
+    "127.0.0.1".parse().unwrap()
+}
+
 fn default_mix_port() -> u16 {
     DEFAULT_MIX_LISTENING_PORT
 }
@@ -45,6 +74,14 @@ fn default_clients_port() -> u16 {
     DEFAULT_CLIENT_LISTENING_PORT
 }
 
+fn default_http_api_port() -> u16 {
+    DEFAULT_HTTP_API_LISTENING_PORT
+}
+
+fn default_federation_port() -> u16 {
+    DEFAULT_FEDERATION_PORT
+}
+
 #[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Config {
     gateway: Gateway,
@@ -52,6 +89,14 @@ pub struct Config {
     #[serde(default)]
     logging: Logging,
     #[serde(default)]
+    federation: Federation,
+    #[serde(default)]
+    access_control: AccessControl,
+    #[serde(default)]
+    network_requester: NetworkRequester,
+    #[serde(default)]
+    listeners: Listeners,
+    #[serde(default)]
     debug: Debug,
 }
 
@@ -61,14 +106,11 @@ impl NymConfig for Config {
     }
 
     fn default_root_directory() -> PathBuf {
-        dirs::home_dir()
-            .expect("Failed to evaluate $HOME value")
-            .join(".nym")
-            .join("gateways")
+        default_nym_directory("gateways").expect("no data directory known for this OS")
     }
 
     fn try_default_root_directory() -> Option<PathBuf> {
-        dirs::home_dir().map(|path| path.join(".nym").join("gateways"))
+        default_nym_directory("gateways")
     }
 
     fn root_directory(&self) -> PathBuf {
@@ -259,6 +301,28 @@ impl Config {
         self.gateway.clients_port
     }
 
+    pub fn get_clients_listening_address(&self) -> IpAddr {
+        self.listeners
+            .clients
+            .bind_address
+            .unwrap_or(self.gateway.listening_address)
+    }
+
+    pub fn get_mix_listening_address(&self) -> IpAddr {
+        self.listeners
+            .mix
+            .bind_address
+            .unwrap_or(self.gateway.listening_address)
+    }
+
+    pub fn get_http_api_address(&self) -> IpAddr {
+        self.gateway.http_api_address
+    }
+
+    pub fn get_http_api_port(&self) -> u16 {
+        self.gateway.http_api_port
+    }
+
     pub fn get_persistent_store_path(&self) -> PathBuf {
         self.gateway.persistent_storage.clone()
     }
@@ -279,6 +343,26 @@ impl Config {
         self.debug.maximum_connection_buffer_size
     }
 
+    pub fn get_maximum_forwarding_delay(&self) -> Duration {
+        self.debug.maximum_forwarding_delay
+    }
+
+    pub fn get_listener_guard_config(&self) -> ListenerGuardConfig {
+        ListenerGuardConfig {
+            maximum_frame_size: self.debug.maximum_incoming_frame_size,
+            read_timeout: self.debug.incoming_connection_read_timeout,
+            frame_timeout: self.debug.incoming_frame_timeout,
+        }
+    }
+
+    pub fn get_maximum_websocket_frame_size(&self) -> usize {
+        self.debug.maximum_websocket_frame_size
+    }
+
+    pub fn get_maximum_websocket_message_size(&self) -> usize {
+        self.debug.maximum_websocket_message_size
+    }
+
     pub fn get_use_legacy_sphinx_framing(&self) -> bool {
         self.debug.use_legacy_framed_packet_version
     }
@@ -287,6 +371,90 @@ impl Config {
         self.debug.message_retrieval_limit
     }
 
+    pub fn get_message_store_quota(&self) -> u64 {
+        self.debug.message_store_quota
+    }
+
+    pub fn get_message_store_per_client_guarantee(&self) -> u64 {
+        self.debug.message_store_per_client_guarantee
+    }
+
+    pub fn get_message_store_warning_threshold(&self) -> f64 {
+        self.debug.message_store_warning_threshold
+    }
+
+    pub fn get_message_store_eviction_policy(&self) -> EvictionPolicy {
+        self.debug.message_store_eviction_policy
+    }
+
+    pub fn get_federation_enabled(&self) -> bool {
+        self.federation.enabled
+    }
+
+    pub fn get_federation_port(&self) -> u16 {
+        self.federation.port
+    }
+
+    pub fn get_federation_peers(&self) -> Vec<FederationPeer> {
+        self.federation.peers.clone()
+    }
+
+    pub fn get_registered_clients_only(&self) -> bool {
+        self.access_control.registered_clients_only
+    }
+
+    pub fn get_allowed_clients(&self) -> Vec<identity::PublicKey> {
+        parse_client_identities(&self.access_control.allowed_clients)
+    }
+
+    pub fn get_denied_clients(&self) -> Vec<identity::PublicKey> {
+        parse_client_identities(&self.access_control.denied_clients)
+    }
+
+    pub fn get_network_requester_enabled(&self) -> bool {
+        self.network_requester.enabled
+    }
+
+    pub fn get_network_requester_id(&self) -> &str {
+        &self.network_requester.id
+    }
+
+    pub fn get_network_requester_open_proxy(&self) -> bool {
+        self.network_requester.open_proxy
+    }
+
+    pub fn get_expired_messages_purge_interval(&self) -> Duration {
+        self.debug.expired_messages_purge_interval
+    }
+
+    pub fn get_bandwidth_reconciliation_interval(&self) -> Duration {
+        self.debug.bandwidth_reconciliation_interval
+    }
+
+    pub fn get_heartbeat_interval(&self) -> Duration {
+        self.debug.heartbeat_interval
+    }
+
+    pub fn get_bandwidth_epoch_validity(&self) -> u64 {
+        self.debug.bandwidth_epoch_validity
+    }
+
+    pub fn get_log_level(&self) -> Option<log::LevelFilter> {
+        if self.logging.level.is_empty() {
+            return None;
+        }
+        match self.logging.level.parse() {
+            Ok(level) => Some(level),
+            Err(_) => {
+                log::warn!(
+                    "'{}' is not a valid log level - it's going to be ignored",
+                    self.logging.level
+                );
+                None
+            }
+        }
+    }
+
     pub fn get_version(&self) -> &str {
         &self.gateway.version
     }
@@ -331,6 +499,17 @@ pub struct Gateway {
     #[serde(default = "default_clients_port")]
     clients_port: u16,
 
+    /// Address to which the HTTP API of the gateway binds. It only serves the operator,
+    /// hence by default it's not reachable from the outside.
+    /// (default: 127.0.0.1)
+    #[serde(default = "loopback_address")]
+    http_api_address: IpAddr,
+
+    /// Port used for the HTTP API of the gateway, such as the operator overview.
+    /// (default: 8000)
+    #[serde(default = "default_http_api_port")]
+    http_api_port: u16,
+
     /// Path to file containing private identity key.
     private_identity_key_file: PathBuf,
 
@@ -405,6 +584,8 @@ impl Default for Gateway {
             announce_address: "127.0.0.1".to_string(),
             mix_port: DEFAULT_MIX_LISTENING_PORT,
             clients_port: DEFAULT_CLIENT_LISTENING_PORT,
+            http_api_address: loopback_address(),
+            http_api_port: DEFAULT_HTTP_API_LISTENING_PORT,
             private_identity_key_file: Default::default(),
             public_identity_key_file: Default::default(),
             private_sphinx_key_file: Default::default(),
@@ -424,7 +605,174 @@ impl Default for Gateway {
 
 #[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
-struct Logging {}
+struct Logging {
+    /// Maximum level of messages that are going to get logged, for example `info` or `debug`.
+    /// If left empty, the level set via the `RUST_LOG` environment variable is used.
+    /// Note that it can't be used to increase the verbosity beyond the one set at startup.
+    #[serde(default)]
+    level: String,
+}
+
+#[derive(Debug, Error)]
+pub enum FederationPeerParseError {
+    #[error("federation peer has to be specified as 'identity@host:port'")]
+    MissingSeparator,
+
+    #[error("federation peer identity is malformed - {0}")]
+    MalformedIdentity(#[from] identity::Ed25519RecoveryError),
+
+    #[error("federation peer address is malformed - {0}")]
+    MalformedAddress(#[from] AddrParseError),
+}
+
+/// Another gateway with which this gateway is willing to exchange messages of the clients
+/// anchored at either of them. Represented as `identity@host:port` in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FederationPeer {
+    pub identity: identity::PublicKey,
+    pub address: SocketAddr,
+}
+
+impl FromStr for FederationPeer {
+    type Err = FederationPeerParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (identity, address) = s
+            .split_once('@')
+            .ok_or(FederationPeerParseError::MissingSeparator)?;
+
+        Ok(FederationPeer {
+            identity: identity::PublicKey::from_base58_string(identity)?,
+            address: address.parse()?,
+        })
+    }
+}
+
+impl Display for FederationPeer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.identity, self.address)
+    }
+}
+
+impl Serialize for FederationPeer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for FederationPeer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(D::Error::custom)
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+struct Federation {
+    /// Specifies whether this gateway is going to exchange messages with its federation peers,
+    /// i.e. serve clients registered with the peers and let the peers serve its own clients.
+    enabled: bool,
+
+    /// Port used for listening for connections from the federation peers.
+    #[serde(default = "default_federation_port")]
+    port: u16,
+
+    /// Gateways with which the federation links are allowed to be established.
+    peers: Vec<FederationPeer>,
+}
+
+impl Default for Federation {
+    fn default() -> Self {
+        Federation {
+            enabled: false,
+            port: DEFAULT_FEDERATION_PORT,
+            peers: Vec::new(),
+        }
+    }
+}
+
+fn parse_client_identities(raw: &[String]) -> Vec<identity::PublicKey> {
+    raw.iter()
+        .filter_map(
+            |identity| match identity::PublicKey::from_base58_string(identity) {
+                Ok(identity) => Some(identity),
+                Err(err) => {
+                    log::warn!("'{identity}' is not a valid client identity ({err}) - it's going to be ignored");
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+struct AccessControl {
+    /// Specifies whether only the clients that have already registered with this gateway are allowed
+    /// to connect, i.e. no new registrations are accepted unless the client is explicitly allowed.
+    registered_clients_only: bool,
+
+    /// Identity keys of the clients allowed to connect. If any are specified, all other clients
+    /// are rejected.
+    allowed_clients: Vec<String>,
+
+    /// Identity keys of the clients that are not allowed to connect.
+    denied_clients: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+struct NetworkRequester {
+    /// Specifies whether the network requester is going to run embedded within the gateway process,
+    /// exchanging messages with it directly rather than through the websocket.
+    /// It requires the gateway to have been built with the `embedded-network-requester` feature.
+    enabled: bool,
+
+    /// Id of the network requester to run. It must have been initialised with this gateway beforehand.
+    id: String,
+
+    /// Specifies whether the embedded network requester should run in 'open-proxy' mode.
+    open_proxy: bool,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+struct Listeners {
+    /// Listener accepting the websocket connections of the clients.
+    clients: Listener,
+
+    /// Listener accepting the sphinx packets coming from the mixnet.
+    mix: Listener,
+}
+
+/// Settings of a single listener, which can be bound to a different interface than the other one,
+/// for example when the clients and the mixnet are reached through separate networks.
+/// Both listeners are still announced under `gateway.announce_address`, as that's the only host
+/// the gateway bond carries.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+struct Listener {
+    /// Address to which the listener binds. If not set, `gateway.listening_address` is used.
+    bind_address: Option<IpAddr>,
+}
+
+/// Policy used for choosing which stored messages to evict once the message store quota is hit.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionPolicy {
+    /// Evict the oldest messages first, regardless of their recipient.
+    OldestFirst,
+
+    /// Evict messages of the clients that have not come online for the longest time first.
+    LeastRecentlyActive,
+}
+
+impl Default for EvictionPolicy {
+    fn default() -> Self {
+        EvictionPolicy::OldestFirst
+    }
+}
 
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
@@ -446,6 +794,33 @@ struct Debug {
     /// Maximum number of packets that can be stored waiting to get sent to a particular connection.
     maximum_connection_buffer_size: usize,
 
+    /// Maximum duration a client packet can wait for the connection to its first hop to be
+    /// re-established. Packets that would wait longer are dropped and the clients sending
+    /// through the unreachable hop are told to choose a different route.
+    #[serde(with = "humantime_serde")]
+    maximum_forwarding_delay: Duration,
+
+    /// Maximum size, in bytes, of a single frame an incoming mix connection can send.
+    /// Connections sending bigger frames are immediately closed.
+    maximum_incoming_frame_size: usize,
+
+    /// Duration after which an incoming mix connection that hasn't sent any complete frame is closed.
+    #[serde(with = "humantime_serde")]
+    incoming_connection_read_timeout: Duration,
+
+    /// Maximum duration a partially received frame can remain incomplete before the connection
+    /// is closed as stalled.
+    #[serde(with = "humantime_serde")]
+    incoming_frame_timeout: Duration,
+
+    /// Maximum size, in bytes, of a single websocket frame an incoming client or federation
+    /// connection can send. Connections sending bigger frames are immediately closed.
+    maximum_websocket_frame_size: usize,
+
+    /// Maximum size, in bytes, of a single (possibly fragmented) websocket message an incoming
+    /// client or federation connection can send. Connections sending bigger messages are immediately closed.
+    maximum_websocket_message_size: usize,
+
     /// Delay between each subsequent presence data being sent.
     #[serde(with = "humantime_serde")]
     presence_sending_delay: Duration,
@@ -456,6 +831,40 @@ struct Debug {
     /// Number of messages from offline client that can be pulled at once from the storage.
     message_retrieval_limit: i64,
 
+    /// Maximum number of bytes of messages that can be stored for all offline clients combined.
+    /// Setting it to 0 disables the quota. Human-friendly sizes, such as "2GB", are accepted.
+    #[serde(with = "nym_config::serde_helpers::bytesize")]
+    message_store_quota: u64,
+
+    /// Number of bytes of messages each offline client is guaranteed to be able to store,
+    /// i.e. its messages are never evicted for as long as its inbox stays below this size.
+    #[serde(with = "nym_config::serde_helpers::bytesize")]
+    message_store_per_client_guarantee: u64,
+
+    /// Fraction of the message store quota after which warnings are going to be emitted.
+    message_store_warning_threshold: f64,
+
+    /// Policy used for choosing which messages to evict once the message store quota is hit.
+    message_store_eviction_policy: EvictionPolicy,
+
+    /// Interval between subsequent removals of stored messages whose expiry, as specified
+    /// by their senders, has already passed.
+    #[serde(with = "humantime_serde")]
+    expired_messages_purge_interval: Duration,
+
+    /// Interval between subsequent runs of the job expiring bandwidth bought in no longer valid epochs.
+    #[serde(with = "humantime_serde")]
+    bandwidth_reconciliation_interval: Duration,
+
+    /// Number of coconut epochs, including the one the credential was issued in, for which
+    /// the bandwidth bought with that credential remains valid.
+    bandwidth_epoch_validity: u64,
+
+    /// Delay between subsequent signed heartbeats sent to the nym-api to announce the gateway is alive.
+    /// Setting it to zero disables the heartbeats.
+    #[serde(with = "humantime_serde")]
+    heartbeat_interval: Duration,
+
     /// Specifies whether the mixnode should be using the legacy framing for the sphinx packets.
     // it's set to true by default. The reason for that decision is to preserve compatibility with the
     // existing nodes whilst everyone else is upgrading and getting the code for handling the new field.
@@ -471,10 +880,101 @@ impl Default for Debug {
             initial_connection_timeout: DEFAULT_INITIAL_CONNECTION_TIMEOUT,
             presence_sending_delay: DEFAULT_PRESENCE_SENDING_DELAY,
             maximum_connection_buffer_size: DEFAULT_MAXIMUM_CONNECTION_BUFFER_SIZE,
+            maximum_forwarding_delay: DEFAULT_MAXIMUM_FORWARDING_DELAY,
+            maximum_incoming_frame_size: DEFAULT_MAXIMUM_FRAME_SIZE,
+            incoming_connection_read_timeout: DEFAULT_CONNECTION_READ_TIMEOUT,
+            incoming_frame_timeout: DEFAULT_FRAME_TIMEOUT,
+            maximum_websocket_frame_size: DEFAULT_MAXIMUM_WEBSOCKET_FRAME_SIZE,
+            maximum_websocket_message_size: DEFAULT_MAXIMUM_WEBSOCKET_MESSAGE_SIZE,
             stored_messages_filename_length: DEFAULT_STORED_MESSAGE_FILENAME_LENGTH,
             message_retrieval_limit: DEFAULT_MESSAGE_RETRIEVAL_LIMIT,
+            message_store_quota: DEFAULT_MESSAGE_STORE_QUOTA,
+            message_store_per_client_guarantee: DEFAULT_MESSAGE_STORE_PER_CLIENT_GUARANTEE,
+            message_store_warning_threshold: DEFAULT_MESSAGE_STORE_WARNING_THRESHOLD,
+            message_store_eviction_policy: EvictionPolicy::default(),
+            expired_messages_purge_interval: DEFAULT_EXPIRED_MESSAGES_PURGE_INTERVAL,
+            bandwidth_reconciliation_interval: DEFAULT_BANDWIDTH_RECONCILIATION_INTERVAL,
+            bandwidth_epoch_validity: DEFAULT_BANDWIDTH_EPOCH_VALIDITY,
+            heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL,
             // TODO: remember to change it in one of future releases!!
             use_legacy_framed_packet_version: true,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn federation_peer_can_be_parsed_from_its_string_representation() {
+        let identity = identity::KeyPair::new(&mut rand::thread_rng());
+        let raw = format!("{}@127.0.0.1:9002", identity.public_key());
+
+        let peer: FederationPeer = raw.parse().unwrap();
+        assert_eq!(&peer.identity, identity.public_key());
+        assert_eq!(peer.address, "127.0.0.1:9002".parse().unwrap());
+        assert_eq!(peer.to_string(), raw);
+
+        assert!("127.0.0.1:9002".parse::<FederationPeer>().is_err());
+        assert!(format!("{}@foo", identity.public_key())
+            .parse::<FederationPeer>()
+            .is_err());
+    }
+
+    #[test]
+    fn listeners_fall_back_to_the_gateway_listening_address() {
+        let mut config = Config::default().with_listening_address("10.0.0.1".parse().unwrap());
+        assert_eq!(
+            config.get_clients_listening_address(),
+            "10.0.0.1".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            config.get_mix_listening_address(),
+            "10.0.0.1".parse::<IpAddr>().unwrap()
+        );
+
+        config.listeners.clients.bind_address = Some("192.168.0.1".parse().unwrap());
+        assert_eq!(
+            config.get_clients_listening_address(),
+            "192.168.0.1".parse::<IpAddr>().unwrap()
+        );
+
+        // the other listener is unaffected
+        assert_eq!(
+            config.get_mix_listening_address(),
+            "10.0.0.1".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn http_api_is_not_exposed_by_default() {
+        let config = Config::default().with_listening_address("10.0.0.1".parse().unwrap());
+        assert!(config.get_http_api_address().is_loopback());
+    }
+
+    #[test]
+    fn log_level_is_only_set_if_valid() {
+        let mut config = Config::default();
+        assert_eq!(config.get_log_level(), None);
+
+        config.logging.level = "debug".to_string();
+        assert_eq!(config.get_log_level(), Some(log::LevelFilter::Debug));
+
+        config.logging.level = "foomp".to_string();
+        assert_eq!(config.get_log_level(), None);
+    }
+
+    #[test]
+    fn malformed_client_identities_are_ignored() {
+        let identity = identity::KeyPair::new(&mut rand::thread_rng());
+        let mut config = Config::default();
+        config.access_control.denied_clients = vec![
+            identity.public_key().to_base58_string(),
+            "foomp".to_string(),
+        ];
+
+        assert_eq!(config.get_denied_clients(), vec![*identity.public_key()]);
+        assert!(config.get_allowed_clients().is_empty());
+    }
+}
@@ -53,6 +53,15 @@ mix_port = {{ gateway.mix_port }}
 # (default: 9000)
 clients_port = {{ gateway.clients_port }}
 
+# Address to which the HTTP API of the gateway binds. It only serves the operator,
+# hence by default it's not reachable from the outside.
+# (default: 127.0.0.1)
+http_api_address = '{{ gateway.http_api_address }}'
+
+# Port used for the HTTP API of the gateway, such as the operator overview.
+# (default: 8000)
+http_api_port = {{ gateway.http_api_port }}
+
 # Wheather gateway collects and sends anonymized statistics
 enabled_statistics = {{ gateway.enabled_statistics }}
 
@@ -92,7 +101,90 @@ persistent_storage = '{{ gateway.persistent_storage }}'
 
 [logging]
 
-# TODO
+# Maximum level of messages that are going to get logged, for example 'info' or 'debug'.
+# If left empty, the level set via the `RUST_LOG` environment variable is used.
+# Note that it can't be used to increase the verbosity beyond the one set at startup.
+# The value can be changed without restarting the node by sending it the SIGHUP signal.
+level = '{{ logging.level }}'
+
+##### federation configuration options #####
+
+[federation]
+
+# Specifies whether this gateway is going to exchange messages with its federation peers,
+# i.e. serve clients registered with the peers and let the peers serve its own clients.
+enabled = {{ federation.enabled }}
+
+# Port used for listening for connections from the federation peers.
+# (default: 9002)
+port = {{ federation.port }}
+
+# Gateways with which the federation links are allowed to be established,
+# specified as 'identity@host:port'.
+peers = [
+    {{#each federation.peers }}
+        '{{this}}',
+    {{/each}}
+]
+
+##### client access control configuration options #####
+
+[access_control]
+
+# All of the values below can be changed without restarting the node by sending it the SIGHUP signal.
+
+# Specifies whether only the clients that have already registered with this gateway are allowed
+# to connect, i.e. no new registrations are accepted unless the client is explicitly allowed.
+registered_clients_only = {{ access_control.registered_clients_only }}
+
+# Identity keys of the clients allowed to connect. If any are specified, all other clients are rejected.
+allowed_clients = [
+    {{#each access_control.allowed_clients }}
+        '{{this}}',
+    {{/each}}
+]
+
+# Identity keys of the clients that are not allowed to connect.
+denied_clients = [
+    {{#each access_control.denied_clients }}
+        '{{this}}',
+    {{/each}}
+]
+
+##### embedded network requester configuration options #####
+
+[network_requester]
+
+# Specifies whether the network requester is going to run embedded within the gateway process,
+# exchanging messages with it directly rather than through the websocket.
+# It requires the gateway to have been built with the `embedded-network-requester` feature.
+enabled = {{ network_requester.enabled }}
+
+# Id of the network requester to run. It must have been initialised with this gateway beforehand.
+id = '{{ network_requester.id }}'
+
+# Specifies whether the embedded network requester should run in 'open-proxy' mode.
+open_proxy = {{ network_requester.open_proxy }}
+
+##### listeners configuration options #####
+
+# The client websocket and the mixnet listeners can be bound to different interfaces.
+# Unless overridden below, both of them bind to `gateway.listening_address`.
+# Both are still announced under `gateway.announce_address`, on `gateway.clients_port` and `gateway.mix_port`.
+
+[listeners.clients]
+
+# Address to which the client websocket listener binds.
+{{#if listeners.clients.bind_address }}
+bind_address = '{{ listeners.clients.bind_address }}'
+{{/if}}
+
+[listeners.mix]
+
+# Address to which the mixnet listener binds.
+{{#if listeners.mix.bind_address }}
+bind_address = '{{ listeners.mix.bind_address }}'
+{{/if}}
 
 "#
 }
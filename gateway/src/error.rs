@@ -44,4 +44,37 @@ pub(crate) enum GatewayError {
         expected_prefix: String,
         actual_prefix: String,
     },
+
+    #[error("the wallet address of the gateway is unknown - either provide it explicitly or set it in the config file")]
+    UnknownWalletAddress,
+
+    #[error("the address of the vesting contract is not known for this network")]
+    UnknownVestingContract,
+
+    #[error("the embedded network requester is enabled, but this binary has been built without the 'embedded-network-requester' feature")]
+    EmbeddedNetworkRequesterUnavailable,
+
+    #[cfg(feature = "embedded-network-requester")]
+    #[error("failed to load config file of the network requester {id}. Have you run `init` for it? detailed message: {source}")]
+    NetworkRequesterConfigLoadFailure {
+        id: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[cfg(feature = "embedded-network-requester")]
+    #[error("failed to load keys of the network requester {id}: {source}")]
+    NetworkRequesterKeysLoadFailure {
+        id: String,
+        #[source]
+        source: nym_client_core::error::ClientCoreError,
+    },
+
+    #[cfg(feature = "embedded-network-requester")]
+    #[error("the network requester {id} is registered with gateway {registered_gateway} rather than with this gateway ({gateway})")]
+    NetworkRequesterGatewayMismatch {
+        id: String,
+        registered_gateway: String,
+        gateway: String,
+    },
 }
@@ -1,13 +1,17 @@
 // Copyright 2020-2023 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+#[macro_use]
+extern crate rocket;
+
 use clap::{crate_name, crate_version, Parser};
 use colored::Colorize;
 use lazy_static::lazy_static;
 use log::error;
-use nym_bin_common::build_information::BinaryBuildInformation;
+use nym_bin_common::build_information::{protocols, BinaryBuildInformation};
 use nym_bin_common::logging::{maybe_print_banner, setup_logging};
 use nym_bin_common::output_format::OutputFormat;
+use nym_config::paths::migrate_legacy_nym_directory;
 use nym_network_defaults::setup_env;
 use std::error::Error;
 
@@ -17,9 +21,33 @@ pub(crate) mod error;
 mod node;
 pub(crate) mod support;
 
+pub(crate) const BUILD_INFORMATION: BinaryBuildInformation =
+    BinaryBuildInformation::new(env!("CARGO_PKG_VERSION"))
+        .with_enabled_features(&[
+            #[cfg(feature = "tokio-console")]
+            "tokio-console",
+            #[cfg(feature = "embedded-network-requester")]
+            "embedded-network-requester",
+            #[cfg(feature = "packet-tracing")]
+            "packet-tracing",
+        ])
+        .with_protocol_versions(&[
+            (
+                protocols::SPHINX_PACKET,
+                nym_sphinx::params::CURRENT_PACKET_VERSION_NUMBER,
+            ),
+            (
+                protocols::GATEWAY_CLIENT,
+                nym_gateway_requests::PROTOCOL_VERSION,
+            ),
+            (
+                protocols::GATEWAY_FEDERATION,
+                node::federation::FEDERATION_PROTOCOL_VERSION,
+            ),
+        ]);
+
 lazy_static! {
-    pub static ref PRETTY_BUILD_INFORMATION: String =
-        BinaryBuildInformation::new(env!("CARGO_PKG_VERSION")).pretty_print();
+    pub static ref PRETTY_BUILD_INFORMATION: String = BUILD_INFORMATION.pretty_print();
 }
 
 // Helper for passing LONG_VERSION to clap
@@ -40,11 +68,15 @@ struct Cli {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    #[cfg(feature = "tokio-console")]
+    nym_bin_common::logging::setup_tokio_console();
+
     setup_logging();
     maybe_print_banner(crate_name!(), crate_version!());
 
     let args = Cli::parse();
     setup_env(args.config_env_file.as_ref());
+    migrate_legacy_nym_directory("gateways");
 
     commands::execute(args).await.map_err(|err| {
         if atty::is(atty::Stream::Stdout) {
@@ -0,0 +1,103 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::Config;
+use nym_sphinx::DestinationAddressBytes;
+use std::collections::HashSet;
+
+/// Rules deciding which clients are allowed to use the gateway.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ClientAccessPolicy {
+    registered_clients_only: bool,
+    allowed_clients: HashSet<DestinationAddressBytes>,
+    denied_clients: HashSet<DestinationAddressBytes>,
+}
+
+impl<'a> From<&'a Config> for ClientAccessPolicy {
+    fn from(config: &'a Config) -> Self {
+        ClientAccessPolicy {
+            registered_clients_only: config.get_registered_clients_only(),
+            allowed_clients: config
+                .get_allowed_clients()
+                .iter()
+                .map(|identity| identity.derive_destination_address())
+                .collect(),
+            denied_clients: config
+                .get_denied_clients()
+                .iter()
+                .map(|identity| identity.derive_destination_address())
+                .collect(),
+        }
+    }
+}
+
+impl ClientAccessPolicy {
+    /// Checks whether the client is allowed to keep using the gateway it has already registered with.
+    pub(crate) fn is_allowed(&self, client: &DestinationAddressBytes) -> bool {
+        if self.denied_clients.contains(client) {
+            return false;
+        }
+        self.allowed_clients.is_empty() || self.allowed_clients.contains(client)
+    }
+
+    /// Checks whether the client is allowed to register with the gateway.
+    pub(crate) fn is_allowed_to_register(&self, client: &DestinationAddressBytes) -> bool {
+        if !self.is_allowed(client) {
+            return false;
+        }
+        !self.registered_clients_only || self.allowed_clients.contains(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nym_crypto::asymmetric::identity;
+
+    fn random_address() -> DestinationAddressBytes {
+        identity::KeyPair::new(&mut rand::thread_rng())
+            .public_key()
+            .derive_destination_address()
+    }
+
+    #[test]
+    fn everyone_is_allowed_by_default() {
+        let policy = ClientAccessPolicy::default();
+        let client = random_address();
+        assert!(policy.is_allowed(&client));
+        assert!(policy.is_allowed_to_register(&client));
+    }
+
+    #[test]
+    fn denied_clients_are_always_rejected() {
+        let client = random_address();
+        let policy = ClientAccessPolicy {
+            allowed_clients: [client].into_iter().collect(),
+            denied_clients: [client].into_iter().collect(),
+            ..Default::default()
+        };
+        assert!(!policy.is_allowed(&client));
+        assert!(!policy.is_allowed_to_register(&client));
+        assert!(!policy.is_allowed(&random_address()));
+    }
+
+    #[test]
+    fn only_allowed_clients_can_register_in_registered_only_mode() {
+        let allowed = random_address();
+        let other = random_address();
+        let policy = ClientAccessPolicy {
+            registered_clients_only: true,
+            ..Default::default()
+        };
+        assert!(policy.is_allowed(&other));
+        assert!(!policy.is_allowed_to_register(&other));
+
+        let policy = ClientAccessPolicy {
+            registered_clients_only: true,
+            allowed_clients: [allowed].into_iter().collect(),
+            ..Default::default()
+        };
+        assert!(policy.is_allowed_to_register(&allowed));
+        assert!(!policy.is_allowed(&other));
+    }
+}
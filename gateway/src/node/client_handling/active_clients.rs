@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::node::client_handling::websocket::message_receiver::MixMessageSender;
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
 use nym_sphinx::DestinationAddressBytes;
 use std::sync::Arc;
@@ -56,8 +57,61 @@ impl ActiveClientsStore {
         self.0.insert(client, handle);
     }
 
+    /// Inserts new client handle into the store unless the client is already connected, either
+    /// directly or through a federation link. The check and the insertion happen atomically so that
+    /// two concurrent connections of the same client could not both end up draining its stored messages.
+    ///
+    /// Returns whether the handle got inserted.
+    ///
+    /// # Arguments
+    ///
+    /// * `client`: address of the client for which to insert the handle.
+    /// * `handle`: the sender channel for all mix packets to be pushed back onto the websocket
+    pub(crate) fn try_insert(
+        &self,
+        client: DestinationAddressBytes,
+        handle: MixMessageSender,
+    ) -> bool {
+        match self.0.entry(client) {
+            Entry::Occupied(mut entry) => {
+                // stale entries can be replaced
+                if !entry.get().is_closed() {
+                    return false;
+                }
+                entry.insert(handle);
+                true
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(handle);
+                true
+            }
+        }
+    }
+
     /// Get number of active clients in store
     pub(crate) fn size(&self) -> usize {
         self.0.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc;
+
+    #[test]
+    fn only_one_connection_can_claim_the_client() {
+        let store = ActiveClientsStore::new();
+        let client = DestinationAddressBytes::from_bytes([42; 32]);
+
+        let (first, first_receiver) = mpsc::unbounded();
+        let (second, _second_receiver) = mpsc::unbounded();
+        assert!(store.try_insert(client, first));
+        assert!(!store.try_insert(client, second.clone()));
+
+        // once the first connection is gone, its stale handle can be replaced
+        drop(first_receiver);
+        assert!(store.try_insert(client, second));
+        assert!(store.get(client).is_some());
+    }
+}
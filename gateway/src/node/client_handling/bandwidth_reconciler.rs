@@ -0,0 +1,107 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::client_handling::websocket::connection_handler::coconut::CoconutVerifier;
+use crate::node::storage::Storage;
+use log::*;
+use nym_task::TaskClient;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Returns id of the oldest epoch whose bandwidth is still valid.
+///
+/// # Arguments
+///
+/// * `current_epoch`: id of the current coconut epoch.
+/// * `epoch_validity`: number of epochs, including the current one, for which the bandwidth stays valid.
+fn first_valid_epoch(current_epoch: u64, epoch_validity: u64) -> u64 {
+    current_epoch.saturating_sub(epoch_validity.saturating_sub(1))
+}
+
+/// Periodically expires unspent bandwidth bought with credentials issued in epochs that are
+/// no longer valid and makes sure the per-epoch balances agree with the total available bandwidth.
+pub(crate) struct BandwidthReconciler<St> {
+    storage: St,
+    coconut_verifier: Arc<CoconutVerifier>,
+    epoch_validity: u64,
+    reconciliation_interval: Duration,
+}
+
+impl<St> BandwidthReconciler<St>
+where
+    St: Storage + 'static,
+{
+    pub(crate) fn new(
+        storage: St,
+        coconut_verifier: Arc<CoconutVerifier>,
+        epoch_validity: u64,
+        reconciliation_interval: Duration,
+    ) -> Self {
+        BandwidthReconciler {
+            storage,
+            coconut_verifier,
+            epoch_validity,
+            reconciliation_interval,
+        }
+    }
+
+    async fn reconcile(&self) {
+        let current_epoch = match self.coconut_verifier.current_epoch_id().await {
+            Ok(epoch_id) => epoch_id,
+            Err(err) => {
+                warn!("Failed to query the current coconut epoch - {err}. Bandwidth won't be reconciled this time");
+                return;
+            }
+        };
+
+        let first_valid_epoch = first_valid_epoch(current_epoch, self.epoch_validity);
+        match self.storage.expire_epoch_bandwidth(first_valid_epoch).await {
+            Ok(0) => trace!("No bandwidth has expired"),
+            Ok(expired) => {
+                info!(
+                    "Expired {expired} bytes of bandwidth bought before epoch {first_valid_epoch}"
+                )
+            }
+            Err(err) => error!("Failed to expire bandwidth - {err}"),
+        }
+
+        match self.storage.reconcile_epoch_bandwidth().await {
+            Ok(0) => (),
+            Ok(corrected) => debug!("Corrected epoch bandwidth of {corrected} clients"),
+            Err(err) => error!("Failed to reconcile epoch bandwidth - {err}"),
+        }
+    }
+
+    async fn run(&mut self, mut shutdown: TaskClient) {
+        let mut interval = tokio::time::interval(self.reconciliation_interval);
+
+        while !shutdown.is_shutdown() {
+            tokio::select! {
+                biased;
+                _ = shutdown.recv() => {
+                    log::trace!("BandwidthReconciler: received shutdown");
+                }
+                _ = interval.tick() => self.reconcile().await,
+            }
+        }
+    }
+
+    pub(crate) fn start(mut self, shutdown: TaskClient) -> JoinHandle<()> {
+        tokio::spawn(async move { self.run(shutdown).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_valid_epoch_accounts_for_the_current_one() {
+        assert_eq!(first_valid_epoch(10, 1), 10);
+        assert_eq!(first_valid_epoch(10, 2), 9);
+        assert_eq!(first_valid_epoch(1, 5), 0);
+        // validity of 0 is treated as if only the current epoch was valid
+        assert_eq!(first_valid_epoch(10, 0), 10);
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::storage::Storage;
+use log::*;
+use nym_task::TaskClient;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Periodically removes messages of offline clients whose senders are no longer interested
+/// in them being delivered, so that they would not take up space in the message store.
+pub(crate) struct ExpiredMessagesPurger<St> {
+    storage: St,
+    purge_interval: Duration,
+}
+
+impl<St> ExpiredMessagesPurger<St>
+where
+    St: Storage + 'static,
+{
+    pub(crate) fn new(storage: St, purge_interval: Duration) -> Self {
+        ExpiredMessagesPurger {
+            storage,
+            purge_interval,
+        }
+    }
+
+    async fn purge(&self) {
+        match self.storage.remove_expired_messages().await {
+            Ok(0) => trace!("No stored messages have expired"),
+            Ok(removed) => debug!("Removed {removed} expired messages from the message store"),
+            Err(err) => error!("Failed to remove expired messages - {err}"),
+        }
+    }
+
+    async fn run(&mut self, mut shutdown: TaskClient) {
+        let mut interval = tokio::time::interval(self.purge_interval);
+
+        while !shutdown.is_shutdown() {
+            tokio::select! {
+                biased;
+                _ = shutdown.recv() => {
+                    log::trace!("ExpiredMessagesPurger: received shutdown");
+                }
+                _ = interval.tick() => self.purge().await,
+            }
+        }
+    }
+
+    pub(crate) fn start(mut self, shutdown: TaskClient) -> JoinHandle<()> {
+        tokio::spawn(async move { self.run(shutdown).await })
+    }
+}
@@ -1,8 +1,11 @@
 // Copyright 2020 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+pub(crate) mod access_control;
 pub(crate) mod active_clients;
 mod bandwidth;
+pub(crate) mod bandwidth_reconciler;
+pub(crate) mod expired_messages_purger;
 pub(crate) mod websocket;
 
 pub(crate) const FREE_TESTNET_BANDWIDTH_VALUE: i64 = 64 * 1024 * 1024 * 1024; // 64GB
@@ -3,23 +3,30 @@
 
 use crate::node::client_handling::websocket::connection_handler::{ClientDetails, FreshHandler};
 use crate::node::client_handling::websocket::message_receiver::MixMessageReceiver;
+use crate::node::federation::FederationError;
+use crate::node::reload::ReloadableConfig;
 use crate::node::storage::error::StorageError;
 use crate::node::storage::Storage;
 use futures::StreamExt;
 use log::*;
+use nym_gateway_requests::authentication::attach_proof::AttachNonce;
 use nym_gateway_requests::iv::IVConversionError;
-use nym_gateway_requests::types::{BinaryRequest, ServerResponse};
+use nym_gateway_requests::replay::{InboundFilter, OutboundSequence};
+use nym_gateway_requests::types::{BinaryRequest, EpochBandwidth, ServerResponse};
 use nym_gateway_requests::{ClientControlRequest, GatewayRequestsError};
 use nym_sphinx::forwarding::packet::MixPacket;
+use rand::rngs::OsRng;
 use rand::{CryptoRng, Rng};
 use std::convert::TryFrom;
 use std::process;
 use thiserror::Error;
 use tokio::io::{AsyncRead, AsyncWrite};
-use tokio_tungstenite::tungstenite::protocol::Message;
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::{protocol::Message, Error as WsError};
 
 use crate::node::client_handling::bandwidth::Bandwidth;
 use crate::node::client_handling::FREE_TESTNET_BANDWIDTH_VALUE;
+use nym_credentials::coconut::bandwidth::{current_unix_timestamp, has_expired};
 use nym_gateway_requests::iv::IV;
 use nym_task::TaskClient;
 
@@ -49,6 +56,9 @@ pub(crate) enum RequestHandlingError {
     #[error("This gateway is only accepting coconut credentials for bandwidth")]
     OnlyCoconutCredentials,
 
+    #[error("Provided bandwidth credential has expired")]
+    ExpiredBandwidthCredential,
+
     #[error("Nyxd Error - {0}")]
     NyxdError(#[from] nym_validator_client::nyxd::error::NyxdError),
 
@@ -66,6 +76,15 @@ pub(crate) enum RequestHandlingError {
 
     #[error("Credential error - {0}")]
     CredentialError(#[from] nym_credentials::error::Error),
+
+    #[error("This gateway does not support federation")]
+    FederationDisabled,
+
+    #[error("Federation error - {0}")]
+    FederationError(#[from] FederationError),
+
+    #[error("Connection error - {0}")]
+    ConnectionError(#[from] WsError),
 }
 
 impl RequestHandlingError {
@@ -93,6 +112,24 @@ pub(crate) struct AuthenticatedHandler<R, S, St> {
     inner: FreshHandler<R, S, St>,
     client: ClientDetails,
     mix_receiver: MixMessageReceiver,
+
+    /// Sequence of frames pushed to the client, if the session is replay-protected.
+    outbound_sequence: Option<OutboundSequence>,
+
+    /// Filter of frames received from the client, if the session is replay-protected.
+    inbound_filter: Option<InboundFilter>,
+
+    /// Identity of the home gateway forwarding messages of this client to us, if any.
+    home_gateway: Option<String>,
+
+    /// Nonce issued to the client for proving it owns its address to its home gateway.
+    attach_nonce: Option<AttachNonce>,
+
+    /// Runtime configuration used for checking whether the client is still allowed to be connected.
+    settings: watch::Receiver<ReloadableConfig>,
+
+    /// Ids of the stored messages pushed to the client that are awaiting its acknowledgement.
+    pending_stored_messages: Option<Vec<i64>>,
 }
 
 // explicitly remove handle from the global store upon being dropped
@@ -100,7 +137,14 @@ impl<R, S, St> Drop for AuthenticatedHandler<R, S, St> {
     fn drop(&mut self) {
         self.inner
             .active_clients_store
-            .disconnect(self.client.address)
+            .disconnect(self.client.address);
+
+        // and let the home gateway know it should go back to storing the messages
+        if let (Some(federation_client), Some(home_gateway)) =
+            (&self.inner.federation_client, &self.home_gateway)
+        {
+            federation_client.detach(home_gateway, self.client.address)
+        }
     }
 }
 
@@ -119,15 +163,23 @@ where
     /// * `fresh`: fresh, unauthenticated, connection handler.
     /// * `client`: details (i.e. address and shared keys) of the registered client
     /// * `mix_receiver`: channel used for receiving messages from the mixnet destined for this client.
+    /// * `outbound_sequence`: sequence of frames already pushed to the client, if the session is replay-protected.
     pub(crate) fn upgrade(
         fresh: FreshHandler<R, S, St>,
         client: ClientDetails,
         mix_receiver: MixMessageReceiver,
+        outbound_sequence: Option<OutboundSequence>,
     ) -> Self {
         AuthenticatedHandler {
+            settings: fresh.settings.clone(),
             inner: fresh,
             client,
             mix_receiver,
+            outbound_sequence,
+            inbound_filter: client.session_nonce.map(InboundFilter::new),
+            home_gateway: None,
+            attach_nonce: None,
+            pending_stored_messages: None,
         }
     }
 
@@ -138,6 +190,62 @@ where
             .disconnect(self.client.address)
     }
 
+    /// Pushes the next page of messages stored while the client was offline, followed by the page
+    /// marker that the client has to acknowledge before the messages are removed and another page is sent.
+    async fn push_stored_messages_page(&mut self) -> Result<(), RequestHandlingError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        // all previously pushed messages have already been removed, so start from the beginning
+        let (messages, start_next_after) = self
+            .inner
+            .storage
+            .retrieve_messages(self.client.address, None)
+            .await?;
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let (messages, ids): (Vec<_>, Vec<_>) = messages
+            .into_iter()
+            .map(|msg| (msg.content, msg.id))
+            .unzip();
+        let count = messages.len();
+
+        self.inner
+            .push_packets_to_client(
+                self.client.shared_keys,
+                self.outbound_sequence.as_mut(),
+                messages,
+            )
+            .await?;
+        self.inner
+            .send_websocket_message(
+                ServerResponse::StoredMessagesPage {
+                    count,
+                    more: start_next_after.is_some(),
+                }
+                .into(),
+            )
+            .await?;
+        self.pending_stored_messages = Some(ids);
+
+        Ok(())
+    }
+
+    /// Removes the acknowledged page of stored messages and pushes the next one, if any.
+    async fn handle_ack_stored_messages(&mut self) -> Result<(), RequestHandlingError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let ids = self
+            .pending_stored_messages
+            .take()
+            .ok_or(RequestHandlingError::IllegalRequest)?;
+        self.inner.storage.remove_messages(ids).await?;
+        self.push_stored_messages_page().await
+    }
+
     /// Checks the amount of bandwidth available for the connected client.
     async fn get_available_bandwidth(&self) -> Result<i64, RequestHandlingError> {
         let bandwidth = self
@@ -162,6 +270,25 @@ where
         Ok(())
     }
 
+    /// Increases the amount of available bandwidth of the connected client by the specified value
+    /// bought with a credential issued in the specified epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch_id`: id of the epoch the credential was issued in.
+    /// * `amount`: amount to increase the available bandwidth by.
+    async fn increase_epoch_bandwidth(
+        &self,
+        epoch_id: u64,
+        amount: i64,
+    ) -> Result<(), RequestHandlingError> {
+        self.inner
+            .storage
+            .increase_epoch_bandwidth(self.client.address, epoch_id, amount)
+            .await?;
+        Ok(())
+    }
+
     /// Decreases the amount of available bandwidth of the connected client by the specified value.
     ///
     /// # Arguments
@@ -181,6 +308,14 @@ where
     ///
     /// * `mix_packet`: packet received from the client that should get forwarded into the network.
     fn forward_packet(&self, mix_packet: MixPacket) {
+        #[cfg(feature = "packet-tracing")]
+        log::info!(
+            target: nym_sphinx::params::packet_tracing::PACKET_TRACING_LOG_TARGET,
+            "[{}] gateway forwarding client packet to {}",
+            mix_packet.trace_id(),
+            mix_packet.next_hop()
+        );
+
         if let Err(err) = self.inner.outbound_mix_sender.unbounded_send(mix_packet) {
             error!("We failed to forward requested mix packet - {err}. Presumably our mix forwarder has crashed. We cannot continue.");
             process::exit(1);
@@ -206,6 +341,10 @@ where
             iv,
         )?;
 
+        if has_expired(credential.expiration_date(), current_unix_timestamp()) {
+            return Err(RequestHandlingError::ExpiredBandwidthCredential);
+        }
+
         // Get the latest coconut signers and their VK
         let credential_api_clients = self
             .inner
@@ -238,6 +377,7 @@ where
             .release_funds(current_api_clients, &credential)
             .await?;
 
+        let epoch_id = *credential.epoch_id();
         let bandwidth = Bandwidth::from(credential);
         let bandwidth_value = bandwidth.value();
 
@@ -251,12 +391,35 @@ where
             ));
         }
 
-        self.increase_bandwidth(bandwidth_value as i64).await?;
+        self.increase_epoch_bandwidth(epoch_id, bandwidth_value as i64)
+            .await?;
         let available_total = self.get_available_bandwidth().await?;
 
         Ok(ServerResponse::Bandwidth { available_total })
     }
 
+    /// Retrieves the remaining bandwidth of the client alongside its split between the epochs
+    /// in which the credentials were issued.
+    async fn handle_get_bandwidth_balance(&self) -> Result<ServerResponse, RequestHandlingError> {
+        let available_total = self.get_available_bandwidth().await?;
+        let epochs = self
+            .inner
+            .storage
+            .get_epoch_bandwidth(self.client.address)
+            .await?
+            .into_iter()
+            .map(|bucket| EpochBandwidth {
+                epoch_id: bucket.epoch_id as u64,
+                remaining: bucket.remaining,
+            })
+            .collect();
+
+        Ok(ServerResponse::BandwidthBalance {
+            available_total,
+            epochs,
+        })
+    }
+
     async fn handle_claim_testnet_bandwidth(
         &mut self,
     ) -> Result<ServerResponse, RequestHandlingError> {
@@ -271,10 +434,71 @@ where
         Ok(ServerResponse::Bandwidth { available_total })
     }
 
+    /// Issues a fresh nonce the client has to sign in order to attach to its home gateway.
+    /// Any previously issued nonce is invalidated.
+    fn handle_get_attach_nonce(&mut self) -> Result<ServerResponse, RequestHandlingError> {
+        if self.home_gateway.is_some() {
+            return Err(RequestHandlingError::IllegalRequest);
+        }
+        if self.inner.federation_client.is_none() {
+            return Err(RequestHandlingError::FederationDisabled);
+        }
+
+        let nonce = AttachNonce::new(&mut OsRng, current_unix_timestamp());
+        self.attach_nonce = Some(nonce);
+        Ok(ServerResponse::AttachNonce {
+            nonce: nonce.to_base58_string(),
+        })
+    }
+
+    /// Tries to handle the request to attach to the home gateway of the client, so that it would
+    /// forward all messages of the client to us.
+    ///
+    /// # Arguments
+    ///
+    /// * `home_gateway`: identity of the gateway the client has originally registered with.
+    /// * `nonce`: nonce we have previously issued to the client.
+    /// * `signature`: signature of the client on the attach proof constructed for the nonce.
+    async fn handle_attach_home_gateway(
+        &mut self,
+        home_gateway: String,
+        nonce: String,
+        signature: String,
+    ) -> Result<ServerResponse, RequestHandlingError> {
+        if self.home_gateway.is_some() {
+            return Err(RequestHandlingError::IllegalRequest);
+        }
+        let federation_client = self
+            .inner
+            .federation_client
+            .as_ref()
+            .ok_or(RequestHandlingError::FederationDisabled)?;
+
+        // each nonce can only be used once
+        let issued_nonce = self
+            .attach_nonce
+            .take()
+            .ok_or(RequestHandlingError::IllegalRequest)?;
+        if AttachNonce::try_from_base58_string(nonce).ok() != Some(issued_nonce) {
+            return Ok(ServerResponse::HomeGatewayAttached { status: false });
+        }
+
+        let status = federation_client
+            .attach(&home_gateway, self.client.address, issued_nonce, signature)
+            .await?;
+        if status {
+            self.home_gateway = Some(home_gateway);
+        }
+
+        Ok(ServerResponse::HomeGatewayAttached { status })
+    }
+
     /// Tries to handle request to forward sphinx packet into the network. The request can only succeed
     /// if the client has enough available bandwidth.
     ///
     /// Upon forwarding, client's bandwidth is decreased by the size of the forwarded packet.
+    /// If the first hop of the packet is currently unreachable and the client knows how to handle it,
+    /// the packet is rejected, free of charge, so that the client could re-route it.
     ///
     /// # Arguments
     ///
@@ -283,6 +507,19 @@ where
         &self,
         mix_packet: MixPacket,
     ) -> Result<ServerResponse, RequestHandlingError> {
+        if self.client.unreachable_hop_notifications {
+            let next_hop = mix_packet.next_hop();
+            if let Some(reconnection_at) = self.inner.unreachable_hops.unreachable_until(&next_hop)
+            {
+                // there's no point in accepting the packet only for it to get stuck waiting for
+                // the reconnection. let the client send it through a different route instead
+                trace!("{next_hop} is unreachable until {reconnection_at:?} - not forwarding the packet");
+                return Ok(ServerResponse::UnreachableHop {
+                    address: next_hop.into(),
+                });
+            }
+        }
+
         let consumed_bandwidth = mix_packet.sphinx_packet().len() as i64;
 
         let available_bandwidth = self.get_available_bandwidth().await?;
@@ -295,6 +532,9 @@ where
 
         self.consume_bandwidth(consumed_bandwidth).await?;
         self.forward_packet(mix_packet);
+        self.inner
+            .operator_metrics
+            .record_bandwidth_served(consumed_bandwidth as u64);
 
         Ok(ServerResponse::Send {
             remaining_bandwidth: available_bandwidth - consumed_bandwidth,
@@ -306,10 +546,17 @@ where
     /// # Arguments
     ///
     /// * `bin_msg`: raw message to handle.
-    async fn handle_binary(&self, bin_msg: Vec<u8>) -> Message {
-        // this function decrypts the request and checks the MAC
-        match BinaryRequest::try_from_encrypted_tagged_bytes(bin_msg, &self.client.shared_keys) {
-            Err(e) => RequestHandlingError::InvalidBinaryRequest(e).into_error_message(),
+    async fn handle_binary(&mut self, bin_msg: Vec<u8>) -> Message {
+        // this function decrypts the request, checks the MAC and, if applicable, rejects replayed frames
+        match BinaryRequest::try_from_encrypted_tagged_bytes(
+            bin_msg,
+            &self.client.shared_keys,
+            self.inbound_filter.as_mut(),
+        ) {
+            Err(e) => {
+                self.inner.operator_metrics.record_invalid_request();
+                RequestHandlingError::InvalidBinaryRequest(e).into_error_message()
+            }
             Ok(request) => match request {
                 // currently only a single type exists
                 BinaryRequest::ForwardSphinx(mix_packet) => self
@@ -322,26 +569,61 @@ where
 
     /// Attempts to handle a text data frame websocket message.
     ///
-    /// Currently the bandwidth credential request is the only one we can receive after authentication.
+    /// After authentication we can only receive bandwidth requests, requests to attach to the home gateway
+    /// and acknowledgements of stored messages, the last of which do not warrant any response.
     ///
     /// # Arguments
     ///
     /// * `raw_request`: raw message to handle.
-    async fn handle_text(&mut self, raw_request: String) -> Message {
-        match ClientControlRequest::try_from(raw_request) {
-            Err(e) => RequestHandlingError::InvalidTextRequest(e).into_error_message(),
+    async fn handle_text(&mut self, raw_request: String) -> Option<Message>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let response = match ClientControlRequest::try_from(raw_request) {
+            Err(e) => {
+                self.inner.operator_metrics.record_invalid_request();
+                RequestHandlingError::InvalidTextRequest(e).into_error_message()
+            }
             Ok(request) => match request {
-                ClientControlRequest::BandwidthCredential { enc_credential, iv } => self
-                    .handle_bandwidth(enc_credential, iv)
-                    .await
-                    .into_ws_message(),
+                ClientControlRequest::BandwidthCredential { enc_credential, iv } => {
+                    let result = self.handle_bandwidth(enc_credential, iv).await;
+                    self.inner
+                        .operator_metrics
+                        .record_credential_redemption(result.is_ok());
+                    result.into_ws_message()
+                }
                 ClientControlRequest::ClaimFreeTestnetBandwidth => self
                     .handle_claim_testnet_bandwidth()
                     .await
                     .into_ws_message(),
-                _ => RequestHandlingError::IllegalRequest.into_error_message(),
+                ClientControlRequest::GetAttachNonce => {
+                    self.handle_get_attach_nonce().into_ws_message()
+                }
+                ClientControlRequest::AttachHomeGateway {
+                    home_gateway,
+                    nonce,
+                    signature,
+                } => self
+                    .handle_attach_home_gateway(home_gateway, nonce, signature)
+                    .await
+                    .into_ws_message(),
+                ClientControlRequest::GetBandwidthBalance => {
+                    self.handle_get_bandwidth_balance().await.into_ws_message()
+                }
+                ClientControlRequest::AckStoredMessages => {
+                    return self
+                        .handle_ack_stored_messages()
+                        .await
+                        .err()
+                        .map(RequestHandlingError::into_error_message)
+                }
+                _ => {
+                    self.inner.operator_metrics.record_invalid_request();
+                    RequestHandlingError::IllegalRequest.into_error_message()
+                }
             },
-        }
+        };
+        Some(response)
     }
 
     /// Attempts to handle websocket message received from the connected client.
@@ -349,13 +631,16 @@ where
     /// # Arguments
     ///
     /// * `raw_request`: raw received websocket message.
-    async fn handle_request(&mut self, raw_request: Message) -> Option<Message> {
+    async fn handle_request(&mut self, raw_request: Message) -> Option<Message>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
         // apparently tungstenite auto-handles ping/pong/close messages so for now let's ignore
         // them and let's test that claim. If that's not the case, just copy code from
         // desktop nym-client websocket as I've manually handled everything there
         match raw_request {
             Message::Binary(bin_msg) => Some(self.handle_binary(bin_msg).await),
-            Message::Text(text_msg) => Some(self.handle_text(text_msg).await),
+            Message::Text(text_msg) => self.handle_text(text_msg).await,
             _ => None,
         }
     }
@@ -370,6 +655,14 @@ where
     {
         trace!("Started listening for ALL incoming requests...");
 
+        if self.client.paginated_backlog {
+            if let Err(err) = self.push_stored_messages_page().await {
+                debug!("Failed to push stored messages to the client - {err}");
+                self.disconnect();
+                return;
+            }
+        }
+
         while !shutdown.is_shutdown() {
             tokio::select! {
                 _ = shutdown.recv() => {
@@ -381,6 +674,7 @@ where
                         Some(Ok(socket_msg)) => socket_msg,
                         Some(Err(err)) => {
                             error!("failed to obtain message from websocket stream! stopping connection handler: {err}");
+                            self.inner.operator_metrics.record_connection_error();
                             break;
                         }
                     };
@@ -394,14 +688,30 @@ where
                             warn!(
                                 "Failed to send message over websocket: {err}. Assuming the connection is dead.",
                             );
+                            self.inner.operator_metrics.record_connection_error();
                             break;
                         }
                     }
                 },
+                Ok(_) = self.settings.changed() => {
+                    let allowed = self
+                        .settings
+                        .borrow()
+                        .client_access_policy
+                        .is_allowed(&self.client.address);
+                    if !allowed {
+                        info!(
+                            "{} is no longer allowed to use this gateway - disconnecting it",
+                            self.client.address.as_base58_string()
+                        );
+                        break;
+                    }
+                }
                 mix_messages = self.mix_receiver.next() => {
                     let mix_messages = mix_messages.expect("sender was unexpectedly closed! this shouldn't have ever happened!");
-                    if let Err(err) = self.inner.push_packets_to_client(self.client.shared_keys, mix_messages).await {
+                    if let Err(err) = self.inner.push_packets_to_client(self.client.shared_keys, self.outbound_sequence.as_mut(), mix_messages).await {
                         warn!("failed to send the unwrapped sphinx packets back to the client - {err}, assuming the connection is dead");
+                        self.inner.operator_metrics.record_connection_error();
                         break;
                     }
                 }
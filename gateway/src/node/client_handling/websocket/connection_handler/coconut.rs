@@ -38,6 +38,10 @@ impl CoconutVerifier {
         }
     }
 
+    pub async fn current_epoch_id(&self) -> Result<u64, RequestHandlingError> {
+        Ok(self.nyxd_client.nyxd.get_current_epoch().await?.epoch_id)
+    }
+
     pub async fn all_current_coconut_api_clients(
         &self,
     ) -> Result<Vec<CoconutApiClient>, RequestHandlingError> {
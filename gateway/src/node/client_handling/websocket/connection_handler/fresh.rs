@@ -6,26 +6,34 @@ use crate::node::client_handling::websocket::connection_handler::coconut::Coconu
 use crate::node::client_handling::websocket::connection_handler::{
     AuthenticatedHandler, ClientDetails, InitialAuthResult, SocketStream,
 };
+use crate::node::federation::FederationClient;
+use crate::node::reload::ReloadableConfig;
+use crate::node::statistics::operator::OperatorMetrics;
 use crate::node::storage::error::StorageError;
 use crate::node::storage::Storage;
-use futures::{channel::mpsc, SinkExt, StreamExt};
+use crate::node::websocket_guard::WebSocketGuard;
+use futures::{channel::mpsc, SinkExt};
 use log::*;
 use nym_crypto::asymmetric::identity;
 use nym_gateway_requests::authentication::encrypted_address::{
     EncryptedAddressBytes, EncryptedAddressConversionError,
 };
+use nym_gateway_requests::capabilities::{Capabilities, NegotiatedProtocol, NegotiationError};
 use nym_gateway_requests::iv::{IVConversionError, IV};
 use nym_gateway_requests::registration::handshake::error::HandshakeError;
 use nym_gateway_requests::registration::handshake::{gateway_handshake, SharedKeys};
+use nym_gateway_requests::replay::{new_session_nonce, OutboundSequence};
 use nym_gateway_requests::types::{ClientControlRequest, ServerResponse};
 use nym_gateway_requests::{BinaryResponse, PROTOCOL_VERSION};
-use nym_mixnet_client::forwarder::MixForwardingSender;
+use nym_mixnet_client::forwarder::{MixForwardingSender, UnreachableHops};
 use nym_sphinx::DestinationAddressBytes;
 use rand::{CryptoRng, Rng};
 use std::convert::TryFrom;
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::watch;
+use tokio::time::Instant;
 use tokio_tungstenite::tungstenite::{protocol::Message, Error as WsError};
 
 #[derive(Debug, Error)]
@@ -46,6 +54,9 @@ pub(crate) enum InitialAuthenticationError {
     #[error("There is already an open connection to this client")]
     DuplicateConnection,
 
+    #[error("This client is not allowed to use this gateway")]
+    AccessDenied,
+
     #[error("Provided authentication IV is malformed - {0}")]
     MalformedIV(#[from] IVConversionError),
 
@@ -55,8 +66,12 @@ pub(crate) enum InitialAuthenticationError {
     #[error("Experienced connection error - {0}")]
     ConnectionError(#[from] WsError),
 
-    #[error("Attempted to negotiate connection with client using incompatible protocol version. Ours is {current} and the client reports {client:?}")]
-    IncompatibleProtocol { client: Option<u8>, current: u8 },
+    #[error("Attempted to negotiate connection with client using incompatible protocol version. Ours is {current} and the client reports {client:?}: {source}")]
+    IncompatibleProtocol {
+        client: Option<u8>,
+        current: u8,
+        source: NegotiationError,
+    },
 }
 
 impl InitialAuthenticationError {
@@ -75,6 +90,14 @@ pub(crate) struct FreshHandler<R, S, St> {
     pub(crate) socket_connection: SocketStream<S>,
     pub(crate) storage: St,
     pub(crate) coconut_verifier: Arc<CoconutVerifier>,
+    pub(crate) federation_client: Option<FederationClient>,
+    pub(crate) unreachable_hops: UnreachableHops,
+    pub(crate) settings: watch::Receiver<ReloadableConfig>,
+    pub(crate) websocket_guard: WebSocketGuard,
+    pub(crate) operator_metrics: OperatorMetrics,
+
+    /// Time at which the last websocket message has been received from the client.
+    last_message: Instant,
 }
 
 impl<R, S, St> FreshHandler<R, S, St>
@@ -96,6 +119,11 @@ where
         storage: St,
         active_clients_store: ActiveClientsStore,
         coconut_verifier: Arc<CoconutVerifier>,
+        federation_client: Option<FederationClient>,
+        unreachable_hops: UnreachableHops,
+        settings: watch::Receiver<ReloadableConfig>,
+        websocket_guard: WebSocketGuard,
+        operator_metrics: OperatorMetrics,
     ) -> Self {
         FreshHandler {
             rng,
@@ -106,6 +134,12 @@ where
             local_identity,
             storage,
             coconut_verifier,
+            federation_client,
+            unreachable_hops,
+            settings,
+            websocket_guard,
+            operator_metrics,
+            last_message: Instant::now(),
         }
     }
 
@@ -120,7 +154,8 @@ where
                 SocketStream::RawTcp(conn) => {
                     // TODO: perhaps in the future, rather than panic here (and uncleanly shut tcp stream)
                     // return a result with an error?
-                    let ws_stream = tokio_tungstenite::accept_async(conn).await?;
+                    let ws_stream = self.websocket_guard.accept(conn).await?;
+                    self.last_message = Instant::now();
                     SocketStream::UpgradedWebSocket(ws_stream)
                 }
                 other => other,
@@ -133,9 +168,13 @@ where
     ///
     /// # Arguments
     ///
+    /// * `client_protocol_version`: protocol version advertised by the client, determining the handshake variant.
+    /// * `client_nonce`: fresh nonce of the client, required by the transcript-bound handshake.
     /// * `init_msg`: a client handshake init message which should contain its identity public key as well as an ephemeral key.
     async fn perform_registration_handshake(
         &mut self,
+        client_protocol_version: Option<u8>,
+        client_nonce: Option<Vec<u8>>,
         init_msg: Vec<u8>,
     ) -> Result<SharedKeys, HandshakeError>
     where
@@ -148,6 +187,8 @@ where
                     &mut self.rng,
                     ws_stream,
                     self.local_identity.as_ref(),
+                    client_protocol_version,
+                    client_nonce,
                     init_msg,
                 )
                 .await
@@ -157,14 +198,23 @@ where
     }
 
     /// Attempts to read websocket message from the associated socket.
+    /// It fails if the client hasn't sent anything within the read timeout.
     pub(crate) async fn read_websocket_message(&mut self) -> Option<Result<Message, WsError>>
     where
         S: AsyncRead + AsyncWrite + Unpin,
     {
-        match self.socket_connection {
-            SocketStream::UpgradedWebSocket(ref mut ws_stream) => ws_stream.next().await,
+        let msg = match self.socket_connection {
+            SocketStream::UpgradedWebSocket(ref mut ws_stream) => {
+                self.websocket_guard
+                    .next_message(ws_stream, self.last_message)
+                    .await
+            }
             _ => panic!("impossible state - websocket handshake was somehow reverted"),
+        };
+        if let Some(Ok(_)) = msg {
+            self.last_message = Instant::now();
         }
+        msg
     }
 
     /// Attempts to write a single websocket message on the available socket.
@@ -191,10 +241,12 @@ where
     /// # Arguments
     ///
     /// * `shared_keys`: keys derived between the client and gateway.
+    /// * `outbound_sequence`: sequence used for replay protection of the frames, if supported by the client.
     /// * `packets`: unwrapped packets that are to be pushed back to the client.
     pub(crate) async fn push_packets_to_client(
         &mut self,
         shared_keys: SharedKeys,
+        mut outbound_sequence: Option<&mut OutboundSequence>,
         packets: Vec<Vec<u8>>,
     ) -> Result<(), WsError>
     where
@@ -205,8 +257,11 @@ where
         let messages: Vec<Result<Message, WsError>> = packets
             .into_iter()
             .map(|received_message| {
+                let header = outbound_sequence
+                    .as_mut()
+                    .map(|sequence| sequence.next_header());
                 Ok(BinaryResponse::new_pushed_mix_message(received_message)
-                    .into_ws_message(&shared_keys))
+                    .into_ws_message(&shared_keys, header))
             })
             .collect();
         let mut send_stream = futures::stream::iter(messages);
@@ -246,10 +301,12 @@ where
     ///
     /// * `client_address`: address of the client that is going to receive the messages.
     /// * `shared_keys`: shared keys derived between the client and the gateway used to encrypt and tag the messages.
+    /// * `outbound_sequence`: sequence used for replay protection of the frames, if supported by the client.
     async fn push_stored_messages_to_client(
         &mut self,
         client_address: DestinationAddressBytes,
         shared_keys: SharedKeys,
+        mut outbound_sequence: Option<&mut OutboundSequence>,
     ) -> Result<(), InitialAuthenticationError>
     where
         S: AsyncRead + AsyncWrite + Unpin,
@@ -268,7 +325,10 @@ where
                 .unzip();
 
             // push them to the client
-            if let Err(err) = self.push_packets_to_client(shared_keys, messages).await {
+            if let Err(err) = self
+                .push_packets_to_client(shared_keys, outbound_sequence.as_deref_mut(), messages)
+                .await
+            {
                 warn!("We failed to send stored messages to fresh client - {err}",);
                 return Err(InitialAuthenticationError::ConnectionError(err));
             } else {
@@ -329,30 +389,50 @@ where
         }
     }
 
-    fn check_client_protocol(
+    /// Negotiates the protocol version and capabilities to use for the rest of the session
+    /// based on what the client has advertised. Clients using newer protocol versions are
+    /// downgraded to ours rather than rejected.
+    fn negotiate_client_protocol(
         &self,
         client_protocol: Option<u8>,
-    ) -> Result<(), InitialAuthenticationError> {
-        // right now there are no failure cases here, but this might change in the future
-        match client_protocol {
-            None => {
-                warn!("the client we're connected to has not specified its protocol version. It's probably running version < 1.1.X, but that's still fine for now. It will become a hard error in 1.2.0");
-                // note: in +1.2.0 we will have to return a hard error here
-                Ok(())
-            }
-            Some(v) if v != PROTOCOL_VERSION => {
+        client_capabilities: Option<Capabilities>,
+    ) -> Result<NegotiatedProtocol, InitialAuthenticationError> {
+        if client_protocol.is_none() {
+            warn!("the client we're connected to has not specified its protocol version. It's probably running version < 1.1.X, but that's still fine for now. It will become a hard error in 1.2.0");
+        }
+
+        let negotiated = NegotiatedProtocol::negotiate(client_protocol, client_capabilities)
+            .map_err(|source| {
                 let err = InitialAuthenticationError::IncompatibleProtocol {
-                    client: Some(v),
+                    client: client_protocol,
                     current: PROTOCOL_VERSION,
+                    source,
                 };
                 error!("{err}");
-                Err(err)
-            }
+                err
+            })?;
+
+        if !negotiated.missing_capabilities().is_empty() {
+            warn!(
+                "the client is using protocol version {}. The following features are not going to be available: {}",
+                negotiated.protocol_version,
+                negotiated.missing_capabilities()
+            );
+        } else {
+            debug!(
+                "negotiated protocol version {} with capabilities: {}",
+                negotiated.protocol_version, negotiated.capabilities
+            );
+        }
+        Ok(negotiated)
+    }
 
-            Some(_) => {
-                info!("the client is using exactly the same protocol version as we are. We're good to continue!");
-                Ok(())
-            }
+    /// Generates a fresh session nonce if the negotiated protocol supports replay protection.
+    fn maybe_new_session_nonce(&mut self, negotiated: NegotiatedProtocol) -> Option<u64> {
+        if negotiated.replay_protection() {
+            Some(new_session_nonce(&mut self.rng))
+        } else {
+            None
         }
     }
 
@@ -360,7 +440,8 @@ where
     /// a fresh IV, attempts to authenticate the client by checking whether the ciphertext matches
     /// the expected value if encrypted with the shared key.
     ///
-    /// Finally, upon completion, all previously stored messages are pushed back to the client.
+    /// Note that all previously stored messages are only pushed to the client after it receives
+    /// the authentication response.
     ///
     /// # Arguments
     ///
@@ -372,26 +453,14 @@ where
         client_address: DestinationAddressBytes,
         encrypted_address: EncryptedAddressBytes,
         iv: IV,
-    ) -> Result<Option<SharedKeys>, InitialAuthenticationError>
-    where
-        S: AsyncRead + AsyncWrite + Unpin,
-    {
+    ) -> Result<Option<SharedKeys>, InitialAuthenticationError> {
         debug!(
             "Processing authenticate client request for: {}",
             client_address.as_base58_string()
         );
 
-        let shared_keys = self
-            .verify_stored_shared_key(client_address, encrypted_address, iv)
-            .await?;
-
-        if let Some(shared_keys) = shared_keys {
-            self.push_stored_messages_to_client(client_address, shared_keys)
-                .await?;
-            Ok(Some(shared_keys))
-        } else {
-            Ok(None)
-        }
+        self.verify_stored_shared_key(client_address, encrypted_address, iv)
+            .await
     }
 
     /// Tries to handle the received authentication request by checking correctness of the received data.
@@ -404,6 +473,7 @@ where
     async fn handle_authenticate(
         &mut self,
         client_protocol_version: Option<u8>,
+        client_capabilities: Option<Capabilities>,
         address: String,
         enc_address: String,
         iv: String,
@@ -411,13 +481,27 @@ where
     where
         S: AsyncRead + AsyncWrite + Unpin,
     {
-        self.check_client_protocol(client_protocol_version)?;
+        let negotiated =
+            self.negotiate_client_protocol(client_protocol_version, client_capabilities)?;
 
         let address = DestinationAddressBytes::try_from_base58_string(address)
             .map_err(|err| InitialAuthenticationError::MalformedClientAddress(err.to_string()))?;
         let encrypted_address = EncryptedAddressBytes::try_from_base58_string(enc_address)?;
         let iv = IV::try_from_base58_string(iv)?;
 
+        if !self
+            .settings
+            .borrow()
+            .client_access_policy
+            .is_allowed(&address)
+        {
+            debug!(
+                "Rejecting authentication of {} due to the access policy",
+                address.as_base58_string()
+            );
+            return Err(InitialAuthenticationError::AccessDenied);
+        }
+
         if self.active_clients_store.get(address).is_some() {
             return Err(InitialAuthenticationError::DuplicateConnection);
         }
@@ -431,15 +515,29 @@ where
             .get_available_bandwidth(address)
             .await?
             .unwrap_or(0);
-        let client_details =
-            shared_keys.map(|shared_keys| ClientDetails::new(address, shared_keys));
+        let session_nonce = if status {
+            self.maybe_new_session_nonce(negotiated)
+        } else {
+            None
+        };
+        let client_details = shared_keys.map(|shared_keys| {
+            ClientDetails::new(
+                address,
+                shared_keys,
+                session_nonce,
+                negotiated.paginated_backlog(),
+                negotiated.unreachable_hop_notifications(),
+            )
+        });
 
         Ok(InitialAuthResult::new(
             client_details,
             ServerResponse::Authenticate {
-                protocol_version: Some(PROTOCOL_VERSION),
+                protocol_version: Some(negotiated.protocol_version),
+                capabilities: Some(negotiated.capabilities),
                 status,
                 bandwidth_remaining,
+                session_nonce,
             },
         ))
     }
@@ -447,7 +545,8 @@ where
     /// Attempts to finalize registration of the client by storing the derived shared keys in the
     /// persistent store as well as creating entry for its bandwidth allocation.
     ///
-    /// Finally, upon completion, all previously stored messages are pushed back to the client.
+    /// Note that all previously stored messages are only pushed to the client after it receives
+    /// the registration response.
     ///
     /// # Arguments
     ///
@@ -455,10 +554,7 @@ where
     async fn register_client(
         &mut self,
         client: ClientDetails,
-    ) -> Result<bool, InitialAuthenticationError>
-    where
-        S: AsyncRead + AsyncWrite + Unpin,
-    {
+    ) -> Result<bool, InitialAuthenticationError> {
         debug!(
             "Processing register client request for: {}",
             client.address.as_base58_string()
@@ -478,9 +574,6 @@ where
             self.storage.create_bandwidth_entry(client.address).await?;
         }
 
-        self.push_stored_messages_to_client(client.address, client.shared_keys)
-            .await?;
-
         Ok(true)
     }
 
@@ -493,30 +586,57 @@ where
     async fn handle_register(
         &mut self,
         client_protocol_version: Option<u8>,
+        client_capabilities: Option<Capabilities>,
+        client_nonce: Option<Vec<u8>>,
         init_data: Vec<u8>,
     ) -> Result<InitialAuthResult, InitialAuthenticationError>
     where
         S: AsyncRead + AsyncWrite + Unpin + Send,
     {
-        self.check_client_protocol(client_protocol_version)?;
+        let negotiated =
+            self.negotiate_client_protocol(client_protocol_version, client_capabilities)?;
 
         let remote_identity = Self::extract_remote_identity_from_register_init(&init_data)?;
         let remote_address = remote_identity.derive_destination_address();
 
+        if !self
+            .settings
+            .borrow()
+            .client_access_policy
+            .is_allowed_to_register(&remote_address)
+        {
+            debug!(
+                "Rejecting registration of {} due to the access policy",
+                remote_address.as_base58_string()
+            );
+            return Err(InitialAuthenticationError::AccessDenied);
+        }
+
         if self.active_clients_store.get(remote_address).is_some() {
             return Err(InitialAuthenticationError::DuplicateConnection);
         }
 
-        let shared_keys = self.perform_registration_handshake(init_data).await?;
-        let client_details = ClientDetails::new(remote_address, shared_keys);
+        let shared_keys = self
+            .perform_registration_handshake(client_protocol_version, client_nonce, init_data)
+            .await?;
+        let session_nonce = self.maybe_new_session_nonce(negotiated);
+        let client_details = ClientDetails::new(
+            remote_address,
+            shared_keys,
+            session_nonce,
+            negotiated.paginated_backlog(),
+            negotiated.unreachable_hop_notifications(),
+        );
 
         let status = self.register_client(client_details).await?;
 
         Ok(InitialAuthResult::new(
             Some(client_details),
             ServerResponse::Register {
-                protocol_version: Some(PROTOCOL_VERSION),
+                protocol_version: Some(negotiated.protocol_version),
+                capabilities: Some(negotiated.capabilities),
                 status,
+                session_nonce,
             },
         ))
     }
@@ -538,17 +658,29 @@ where
             match request {
                 ClientControlRequest::Authenticate {
                     protocol_version,
+                    capabilities,
                     address,
                     enc_address,
                     iv,
                 } => {
-                    self.handle_authenticate(protocol_version, address, enc_address, iv)
-                        .await
+                    self.handle_authenticate(
+                        protocol_version,
+                        capabilities,
+                        address,
+                        enc_address,
+                        iv,
+                    )
+                    .await
                 }
                 ClientControlRequest::RegisterHandshakeInitRequest {
                     protocol_version,
+                    capabilities,
+                    nonce,
                     data,
-                } => self.handle_register(protocol_version, data).await,
+                } => {
+                    self.handle_register(protocol_version, capabilities, nonce, data)
+                        .await
+                }
                 // won't accept anything else (like bandwidth) without prior authentication
                 _ => Err(InitialAuthenticationError::InvalidRequest),
             }
@@ -606,12 +738,50 @@ where
                             }
 
                             return if let Some(client_details) = auth_result.client_details {
-                                self.active_clients_store
-                                    .insert(client_details.address, mix_sender);
+                                // claim the client before touching its stored messages, so that
+                                // a concurrent federation attach couldn't drain them as well.
+                                // any messages received in the meantime are buffered in the channel
+                                if !self
+                                    .active_clients_store
+                                    .try_insert(client_details.address, mix_sender)
+                                {
+                                    debug!(
+                                        "{} has connected concurrently through another connection",
+                                        client_details.address
+                                    );
+                                    return None;
+                                }
+
+                                let mut outbound_sequence =
+                                    client_details.session_nonce.map(OutboundSequence::new);
+
+                                // only push the stored messages once the client knows whether
+                                // (and how) the frames are going to be protected against replays.
+                                // clients supporting it are going to receive them in pages instead
+                                // once the connection is upgraded
+                                if !client_details.paginated_backlog {
+                                    if let Err(err) = self
+                                        .push_stored_messages_to_client(
+                                            client_details.address,
+                                            client_details.shared_keys,
+                                            outbound_sequence.as_mut(),
+                                        )
+                                        .await
+                                    {
+                                        debug!(
+                                            "Failed to push stored messages to the client - {err}"
+                                        );
+                                        self.active_clients_store
+                                            .disconnect(client_details.address);
+                                        return None;
+                                    }
+                                }
+
                                 Some(AuthenticatedHandler::upgrade(
                                     self,
                                     client_details,
                                     mix_receiver,
+                                    outbound_sequence,
                                 ))
                             } else {
                                 None
@@ -40,13 +40,31 @@ impl<S> SocketStream<S> {
 pub(crate) struct ClientDetails {
     pub(crate) address: DestinationAddressBytes,
     pub(crate) shared_keys: SharedKeys,
+    /// Nonce of the current session used for replay protection of binary frames.
+    /// It's only set if the negotiated protocol version supports it.
+    pub(crate) session_nonce: Option<u64>,
+    /// Indicates whether the stored messages should be pushed in acknowledged pages rather than
+    /// all at once. It's only set if the negotiated protocol version supports it.
+    pub(crate) paginated_backlog: bool,
+    /// Indicates whether the client should be told about the unreachable first hops of its packets.
+    /// It's only set if the negotiated protocol version supports it.
+    pub(crate) unreachable_hop_notifications: bool,
 }
 
 impl ClientDetails {
-    pub(crate) fn new(address: DestinationAddressBytes, shared_keys: SharedKeys) -> Self {
+    pub(crate) fn new(
+        address: DestinationAddressBytes,
+        shared_keys: SharedKeys,
+        session_nonce: Option<u64>,
+        paginated_backlog: bool,
+        unreachable_hop_notifications: bool,
+    ) -> Self {
         ClientDetails {
             address,
             shared_keys,
+            session_nonce,
+            paginated_backlog,
+            unreachable_hop_notifications,
         }
     }
 }
@@ -4,14 +4,19 @@
 use crate::node::client_handling::active_clients::ActiveClientsStore;
 use crate::node::client_handling::websocket::connection_handler::coconut::CoconutVerifier;
 use crate::node::client_handling::websocket::connection_handler::FreshHandler;
+use crate::node::federation::FederationClient;
+use crate::node::reload::ReloadableConfig;
+use crate::node::statistics::operator::OperatorMetrics;
 use crate::node::storage::Storage;
+use crate::node::websocket_guard::WebSocketGuard;
 use log::*;
 use nym_crypto::asymmetric::identity;
-use nym_mixnet_client::forwarder::MixForwardingSender;
+use nym_mixnet_client::forwarder::{MixForwardingSender, UnreachableHops};
 use rand::rngs::OsRng;
 use std::net::SocketAddr;
 use std::process;
 use std::sync::Arc;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
 
 pub(crate) struct Listener {
@@ -19,20 +24,36 @@ pub(crate) struct Listener {
     local_identity: Arc<identity::KeyPair>,
     only_coconut_credentials: bool,
     pub(crate) coconut_verifier: Arc<CoconutVerifier>,
+    federation_client: Option<FederationClient>,
+    unreachable_hops: UnreachableHops,
+    settings: watch::Receiver<ReloadableConfig>,
+    websocket_guard: WebSocketGuard,
+    operator_metrics: OperatorMetrics,
 }
 
 impl Listener {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         address: SocketAddr,
         local_identity: Arc<identity::KeyPair>,
         only_coconut_credentials: bool,
         coconut_verifier: Arc<CoconutVerifier>,
+        federation_client: Option<FederationClient>,
+        unreachable_hops: UnreachableHops,
+        settings: watch::Receiver<ReloadableConfig>,
+        websocket_guard: WebSocketGuard,
+        operator_metrics: OperatorMetrics,
     ) -> Self {
         Listener {
             address,
             local_identity,
             only_coconut_credentials,
             coconut_verifier,
+            federation_client,
+            unreachable_hops,
+            settings,
+            websocket_guard,
+            operator_metrics,
         }
     }
 
@@ -77,6 +98,11 @@ impl Listener {
                                 storage.clone(),
                                 active_clients_store.clone(),
                                 Arc::clone(&self.coconut_verifier),
+                                self.federation_client.clone(),
+                                self.unreachable_hops.clone(),
+                                self.settings.clone(),
+                                self.websocket_guard.clone(),
+                                self.operator_metrics.clone(),
                             );
                             let shutdown = shutdown.clone();
                             tokio::spawn(async move { handle.start_handling(shutdown).await });
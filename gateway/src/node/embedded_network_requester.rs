@@ -0,0 +1,146 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::GatewayError;
+use crate::node::client_handling::active_clients::ActiveClientsStore;
+use crate::node::client_handling::websocket::message_receiver::MixMessageSender;
+use crate::node::storage::error::StorageError;
+use crate::node::storage::Storage;
+use futures::channel::mpsc;
+use log::*;
+use nym_config::NymConfig;
+use nym_crypto::asymmetric::identity;
+use nym_gateway_client::embedded::EmbeddedConnection;
+use nym_mixnet_client::forwarder::MixForwardingSender;
+use nym_network_requester::config::Config as NetworkRequesterConfig;
+use nym_network_requester::core::NRServiceProviderBuilder;
+use nym_sphinx::DestinationAddressBytes;
+
+/// Network requester running within the gateway process.
+///
+/// Its messages are delivered through an in-process queue rather than the websocket. Whenever the
+/// queue is not available, e.g. before the network requester has started, they are kept in the
+/// gateway's message store, same as for any other offline client, and are pushed to it on startup.
+pub(crate) struct EmbeddedNetworkRequester<St> {
+    config: NetworkRequesterConfig,
+    open_proxy: bool,
+    address: DestinationAddressBytes,
+    forwarding_channel: MixForwardingSender,
+    active_clients_store: ActiveClientsStore,
+    storage: St,
+}
+
+impl<St> EmbeddedNetworkRequester<St>
+where
+    St: Storage + Clone + 'static,
+{
+    pub(crate) fn new(
+        id: &str,
+        open_proxy: bool,
+        gateway_identity: &identity::PublicKey,
+        forwarding_channel: MixForwardingSender,
+        active_clients_store: ActiveClientsStore,
+        storage: St,
+    ) -> Result<Self, GatewayError> {
+        let mut config = NetworkRequesterConfig::load_from_file(id).map_err(|source| {
+            GatewayError::NetworkRequesterConfigLoadFailure {
+                id: id.to_string(),
+                source,
+            }
+        })?;
+        if config.get_base_mut().set_empty_fields_to_defaults() {
+            warn!(
+                "Some of the core config options of the network requester were left unset. \
+                The default values are going to get used instead."
+            );
+        }
+
+        let registered_gateway = config.get_base().get_gateway_id();
+        if registered_gateway != gateway_identity.to_base58_string() {
+            return Err(GatewayError::NetworkRequesterGatewayMismatch {
+                id: id.to_string(),
+                registered_gateway,
+                gateway: gateway_identity.to_base58_string(),
+            });
+        }
+
+        let address = nym_client_core::init::get_client_address_from_stored_keys(config.get_base())
+            .map_err(|source| GatewayError::NetworkRequesterKeysLoadFailure {
+                id: id.to_string(),
+                source,
+            })?;
+
+        Ok(EmbeddedNetworkRequester {
+            config,
+            open_proxy,
+            address: address.identity().derive_destination_address(),
+            forwarding_channel,
+            active_clients_store,
+            storage,
+        })
+    }
+
+    async fn push_stored_messages(
+        storage: &St,
+        address: DestinationAddressBytes,
+        message_sender: &MixMessageSender,
+    ) -> Result<(), StorageError> {
+        let mut start_next_after = None;
+        loop {
+            let (messages, new_start_next_after) =
+                storage.retrieve_messages(address, start_next_after).await?;
+
+            let (messages, ids): (Vec<_>, Vec<_>) = messages
+                .into_iter()
+                .map(|msg| (msg.content, msg.id))
+                .unzip();
+
+            if messages.is_empty() || message_sender.unbounded_send(messages).is_err() {
+                return Ok(());
+            }
+            storage.remove_messages(ids).await?;
+
+            if new_start_next_after.is_none() {
+                return Ok(());
+            }
+            start_next_after = new_start_next_after
+        }
+    }
+
+    pub(crate) fn start(self) {
+        info!("Starting the embedded network requester...");
+
+        let (message_sender, message_receiver) = mpsc::unbounded();
+        let connection = EmbeddedConnection::new(self.forwarding_channel, message_receiver);
+
+        // register the queue before draining the store so that no message could fall in between
+        self.active_clients_store
+            .insert(self.address, message_sender.clone());
+
+        let storage = self.storage;
+        let address = self.address;
+        tokio::spawn(async move {
+            if let Err(err) = Self::push_stored_messages(&storage, address, &message_sender).await {
+                warn!("Failed to push stored messages to the embedded network requester - {err}");
+            }
+        });
+
+        let active_clients_store = self.active_clients_store;
+        let open_proxy = self.open_proxy;
+        let config = self.config;
+        tokio::spawn(async move {
+            // the gateway is not going to be sending any statistics on its behalf
+            let result = NRServiceProviderBuilder::new(config, open_proxy, false, None)
+                .await
+                .with_embedded_gateway_connection(connection)
+                .run_service_provider()
+                .await;
+            if let Err(err) = result {
+                error!("The embedded network requester has failed - {err}");
+            } else {
+                warn!("The embedded network requester has stopped");
+            }
+            active_clients_store.disconnect(address);
+        });
+    }
+}
@@ -0,0 +1,325 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::FederationPeer;
+use crate::node::client_handling::active_clients::ActiveClientsStore;
+use crate::node::federation::handshake::initiate_handshake;
+use crate::node::federation::messages::{ClientPackets, FederationMessage, LinkFrame, PacketsKind};
+use crate::node::federation::session::LinkSession;
+use crate::node::federation::FederationError;
+use futures::channel::{mpsc, oneshot};
+use futures::{SinkExt, StreamExt};
+use log::*;
+use nym_crypto::asymmetric::identity;
+use nym_gateway_requests::authentication::attach_proof::AttachNonce;
+use nym_sphinx::DestinationAddressBytes;
+use nym_task::TaskClient;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::protocol::Message;
+use tokio_tungstenite::WebSocketStream;
+
+/// Interval at which the link is pinged so that our peer wouldn't close it as idle
+/// whilst there's nothing to send over it.
+const LINK_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(60);
+
+type LinkCommandSender = mpsc::UnboundedSender<LinkCommand>;
+type LinkCommandReceiver = mpsc::UnboundedReceiver<LinkCommand>;
+
+enum LinkCommand {
+    Attach {
+        client: DestinationAddressBytes,
+        nonce: AttachNonce,
+        signature: String,
+        response: oneshot::Sender<bool>,
+    },
+    Detach {
+        client: DestinationAddressBytes,
+    },
+}
+
+struct LinksState {
+    // keyed by the base58 identity of the peer
+    links: HashMap<String, LinkCommandSender>,
+    shutdown: TaskClient,
+}
+
+struct FederationClientInner {
+    local_identity: Arc<identity::KeyPair>,
+    peers: Vec<FederationPeer>,
+    connection_timeout: Duration,
+    active_clients_store: ActiveClientsStore,
+    state: Mutex<LinksState>,
+}
+
+/// Establishes and maintains federation links with the home gateways of the clients connected to us.
+#[derive(Clone)]
+pub(crate) struct FederationClient {
+    inner: Arc<FederationClientInner>,
+}
+
+impl FederationClient {
+    pub(crate) fn new(
+        local_identity: Arc<identity::KeyPair>,
+        peers: Vec<FederationPeer>,
+        connection_timeout: Duration,
+        active_clients_store: ActiveClientsStore,
+        shutdown: TaskClient,
+    ) -> Self {
+        FederationClient {
+            inner: Arc::new(FederationClientInner {
+                local_identity,
+                peers,
+                connection_timeout,
+                active_clients_store,
+                state: Mutex::new(LinksState {
+                    links: HashMap::new(),
+                    shutdown,
+                }),
+            }),
+        }
+    }
+
+    fn existing_link(&self, peer: &identity::PublicKey) -> Option<LinkCommandSender> {
+        let state = self.inner.state.lock().unwrap();
+        state
+            .links
+            .get(&peer.to_base58_string())
+            .filter(|link| !link.is_closed())
+            .cloned()
+    }
+
+    async fn establish_link(
+        &self,
+        peer: FederationPeer,
+    ) -> Result<LinkCommandSender, FederationError> {
+        debug!("Establishing federation link with {peer}");
+        let conn = tokio::time::timeout(
+            self.inner.connection_timeout,
+            TcpStream::connect(peer.address),
+        )
+        .await
+        .map_err(|_| FederationError::ConnectionTimeout)??;
+
+        let (mut ws_stream, _) =
+            tokio_tungstenite::client_async(format!("ws://{}", peer.address), conn).await?;
+        let session =
+            initiate_handshake(&mut ws_stream, &self.inner.local_identity, &peer.identity).await?;
+        info!("Established federation link with {peer}");
+
+        let (command_sender, command_receiver) = mpsc::unbounded();
+        let link = OutboundLink {
+            remote: peer.identity,
+            ws_stream,
+            session,
+            command_receiver,
+            active_clients_store: self.inner.active_clients_store.clone(),
+            pending_attachments: HashMap::new(),
+        };
+
+        let mut state = self.inner.state.lock().unwrap();
+        let shutdown = state.shutdown.clone();
+        tokio::spawn(link.run(shutdown));
+        state
+            .links
+            .insert(peer.identity.to_base58_string(), command_sender.clone());
+        Ok(command_sender)
+    }
+
+    /// Asks the home gateway of the client to forward all of its messages to us.
+    ///
+    /// # Arguments
+    ///
+    /// * `home_gateway`: base58 encoded identity of the client's home gateway.
+    /// * `client`: address of the client.
+    /// * `nonce`: nonce we have issued to the client.
+    /// * `signature`: signature of the client on the attach proof constructed for the nonce.
+    pub(crate) async fn attach(
+        &self,
+        home_gateway: &str,
+        client: DestinationAddressBytes,
+        nonce: AttachNonce,
+        signature: String,
+    ) -> Result<bool, FederationError> {
+        let home_gateway = identity::PublicKey::from_base58_string(home_gateway)?;
+        let peer = self
+            .inner
+            .peers
+            .iter()
+            .find(|peer| peer.identity == home_gateway)
+            .copied()
+            .ok_or_else(|| FederationError::UnknownPeer(home_gateway.to_base58_string()))?;
+
+        let link = match self.existing_link(&peer.identity) {
+            Some(link) => link,
+            None => self.establish_link(peer).await?,
+        };
+
+        let (response_sender, response_receiver) = oneshot::channel();
+        link.unbounded_send(LinkCommand::Attach {
+            client,
+            nonce,
+            signature,
+            response: response_sender,
+        })
+        .map_err(|_| FederationError::LinkClosed)?;
+
+        response_receiver
+            .await
+            .map_err(|_| FederationError::LinkClosed)
+    }
+
+    /// Informs the home gateway the client is no longer connected to us.
+    pub(crate) fn detach(&self, home_gateway: &str, client: DestinationAddressBytes) {
+        let state = self.inner.state.lock().unwrap();
+        if let Some(link) = state.links.get(home_gateway) {
+            // if the link is already gone, the home gateway has detached the client on its own
+            let _ = link.unbounded_send(LinkCommand::Detach { client });
+        }
+    }
+}
+
+/// Link established with a home gateway of some of our clients through which we receive their messages.
+struct OutboundLink {
+    remote: identity::PublicKey,
+    ws_stream: WebSocketStream<TcpStream>,
+    session: LinkSession,
+    command_receiver: LinkCommandReceiver,
+    active_clients_store: ActiveClientsStore,
+    pending_attachments: HashMap<DestinationAddressBytes, oneshot::Sender<bool>>,
+}
+
+impl OutboundLink {
+    async fn send_frame<F: Into<LinkFrame>>(&mut self, frame: F) -> Result<(), FederationError> {
+        let sealed = self.session.seal(frame.into());
+        self.ws_stream.send(Message::Binary(sealed)).await?;
+        Ok(())
+    }
+
+    async fn handle_command(&mut self, command: LinkCommand) -> Result<(), FederationError> {
+        let msg = match command {
+            LinkCommand::Attach {
+                client,
+                nonce,
+                signature,
+                response,
+            } => {
+                self.pending_attachments.insert(client, response);
+                FederationMessage::Attach {
+                    client: client.as_base58_string(),
+                    nonce: nonce.to_base58_string(),
+                    signature,
+                }
+            }
+            LinkCommand::Detach { client } => FederationMessage::Detach {
+                client: client.as_base58_string(),
+            },
+        };
+        self.send_frame(msg).await
+    }
+
+    fn handle_control(&mut self, msg: FederationMessage) {
+        match msg {
+            FederationMessage::AttachResponse { client, status } => {
+                let Ok(client) = DestinationAddressBytes::try_from_base58_string(client) else {
+                    warn!("{} has sent us malformed client address", self.remote);
+                    return;
+                };
+                if let Some(response) = self.pending_attachments.remove(&client) {
+                    // the client might have disconnected in the meantime
+                    let _ = response.send(status);
+                }
+            }
+            FederationMessage::Error { message } => {
+                warn!("{} has reported federation error - {message}", self.remote)
+            }
+            _ => warn!(
+                "received unexpected federation message from {}",
+                self.remote
+            ),
+        }
+    }
+
+    /// Pushes the forwarded packets to the client or, if it's no longer connected to us, sends
+    /// them back to its home gateway.
+    async fn handle_packets(&mut self, packets: ClientPackets) -> Result<(), FederationError> {
+        if packets.kind != PacketsKind::Forwarded {
+            warn!("{} has returned packets to us", self.remote);
+            return Ok(());
+        }
+
+        let client = packets.client;
+        let undelivered = match self.active_clients_store.get(client) {
+            Some(mix_sender) => match mix_sender.unbounded_send(packets.packets) {
+                Ok(_) => return Ok(()),
+                Err(err) => err.into_inner(),
+            },
+            None => packets.packets,
+        };
+
+        debug!(
+            "{client} is no longer connected - returning its packets to {}",
+            self.remote
+        );
+        let returned = ClientPackets::new(PacketsKind::Returned, client, undelivered);
+        self.send_frame(returned).await
+    }
+
+    /// Handles the sealed frame received over the link. Any frame that fails the authentication
+    /// is fatal to the link, as the remaining ones could no longer be trusted either.
+    async fn handle_sealed(&mut self, sealed: Vec<u8>) -> Result<(), FederationError> {
+        match self.session.open(&sealed)? {
+            LinkFrame::Control(msg) => {
+                self.handle_control(msg);
+                Ok(())
+            }
+            LinkFrame::Packets(packets) => self.handle_packets(packets).await,
+        }
+    }
+
+    async fn run(mut self, mut shutdown: TaskClient) {
+        shutdown.mark_as_success();
+
+        let mut keepalive = tokio::time::interval_at(
+            Instant::now() + LINK_KEEPALIVE_INTERVAL,
+            LINK_KEEPALIVE_INTERVAL,
+        );
+        while !shutdown.is_shutdown() {
+            let res = tokio::select! {
+                biased;
+                _ = shutdown.recv() => {
+                    log::trace!("federation::OutboundLink: received shutdown");
+                    Ok(())
+                }
+                command = self.command_receiver.next() => match command {
+                    Some(command) => self.handle_command(command).await,
+                    None => break,
+                },
+                _ = keepalive.tick() => self
+                    .ws_stream
+                    .send(Message::Ping(Vec::new()))
+                    .await
+                    .map_err(Into::into),
+                msg = self.ws_stream.next() => match msg {
+                    Some(Ok(Message::Binary(sealed))) => self.handle_sealed(sealed).await,
+                    // only the handshake is not protected by the session
+                    Some(Ok(Message::Text(_))) => Err(FederationError::UnexpectedMessage),
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => Ok(()),
+                    Some(Err(err)) => Err(err.into()),
+                },
+            };
+
+            if let Err(err) = res {
+                warn!("federation link with {} has failed - {err}", self.remote);
+                break;
+            }
+        }
+
+        // dropping the pending attachments is going to notify the awaiting clients about the failure
+        info!("Closed federation link with {}", self.remote);
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::FederationPeer;
+use crate::node::federation::messages::{
+    challenge_message, handshake_transcript, FederationMessage, HandshakeEphemerals,
+    CHALLENGE_LENGTH, FEDERATION_PROTOCOL_VERSION,
+};
+use crate::node::federation::session::{LinkRole, LinkSession};
+use crate::node::federation::{next_control_message, FederationError};
+use futures::SinkExt;
+use nym_crypto::asymmetric::{encryption, identity};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::WebSocketStream;
+
+fn new_challenge() -> [u8; CHALLENGE_LENGTH] {
+    let mut challenge = [0u8; CHALLENGE_LENGTH];
+    OsRng.fill_bytes(&mut challenge);
+    challenge
+}
+
+fn decode_challenge(raw: String) -> Result<Vec<u8>, FederationError> {
+    let challenge = bs58::decode(raw)
+        .into_vec()
+        .map_err(|_| FederationError::MalformedChallenge)?;
+    if challenge.len() != CHALLENGE_LENGTH {
+        return Err(FederationError::MalformedChallenge);
+    }
+    Ok(challenge)
+}
+
+fn decode_ephemeral_key(raw: String) -> Result<encryption::PublicKey, FederationError> {
+    encryption::PublicKey::from_base58_string(raw)
+        .map_err(|_| FederationError::MalformedEphemeralKey)
+}
+
+/// Proves our identity to the `verifier` by signing the challenge it has sent us
+/// alongside the ephemeral keys of the handshake.
+fn prove_identity(
+    local_identity: &identity::KeyPair,
+    challenge: &[u8],
+    verifier: &identity::PublicKey,
+    ephemerals: HandshakeEphemerals<'_>,
+) -> String {
+    local_identity
+        .private_key()
+        .sign(&challenge_message(challenge, verifier, ephemerals))
+        .to_base58_string()
+}
+
+/// Checks whether the `prover` has correctly signed the challenge we have sent it
+/// alongside the ephemeral keys of the handshake.
+fn verify_identity(
+    prover: &identity::PublicKey,
+    challenge: &[u8],
+    local_identity: &identity::KeyPair,
+    ephemerals: HandshakeEphemerals<'_>,
+    signature: String,
+) -> Result<(), FederationError> {
+    let signature = identity::Signature::from_base58_string(signature)?;
+    prover
+        .verify(
+            &challenge_message(challenge, local_identity.public_key(), ephemerals),
+            &signature,
+        )
+        .map_err(|_| FederationError::InvalidPeerSignature)
+}
+
+/// Performs the handshake with the gateway we have connected to, making sure it's the gateway
+/// with the `remote_identity` and proving our own identity to it.
+///
+/// Returns the session protecting all further frames sent over the link.
+pub(crate) async fn initiate_handshake<S>(
+    ws_stream: &mut WebSocketStream<S>,
+    local_identity: &identity::KeyPair,
+    remote_identity: &identity::PublicKey,
+) -> Result<LinkSession, FederationError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let challenge = new_challenge();
+    let ephemeral_keypair = encryption::KeyPair::new(&mut OsRng);
+    let hello = FederationMessage::Hello {
+        protocol_version: FEDERATION_PROTOCOL_VERSION,
+        identity: local_identity.public_key().to_base58_string(),
+        challenge: bs58::encode(challenge).into_string(),
+        ephemeral_key: ephemeral_keypair.public_key().to_base58_string(),
+    };
+    ws_stream.send(hello.into()).await?;
+
+    match next_control_message(ws_stream).await? {
+        FederationMessage::HelloResponse {
+            identity,
+            challenge: remote_challenge,
+            ephemeral_key,
+            signature,
+        } => {
+            let identity = identity::PublicKey::from_base58_string(identity)?;
+            if &identity != remote_identity {
+                return Err(FederationError::UnknownPeer(identity.to_base58_string()));
+            }
+            let remote_ephemeral_key = decode_ephemeral_key(ephemeral_key)?;
+            let ephemerals = HandshakeEphemerals {
+                initiator: ephemeral_keypair.public_key(),
+                responder: &remote_ephemeral_key,
+            };
+            verify_identity(
+                remote_identity,
+                &challenge,
+                local_identity,
+                ephemerals,
+                signature,
+            )?;
+
+            let remote_challenge = decode_challenge(remote_challenge)?;
+            let proof = FederationMessage::Proof {
+                signature: prove_identity(
+                    local_identity,
+                    &remote_challenge,
+                    remote_identity,
+                    ephemerals,
+                ),
+            };
+            ws_stream.send(proof.into()).await?;
+
+            let transcript = handshake_transcript(
+                local_identity.public_key(),
+                remote_identity,
+                &challenge,
+                &remote_challenge,
+                ephemerals,
+            );
+            Ok(LinkSession::derive(
+                LinkRole::Initiator,
+                &ephemeral_keypair,
+                &remote_ephemeral_key,
+                &transcript,
+            ))
+        }
+        FederationMessage::Error { message } => Err(FederationError::RemoteError(message)),
+        _ => Err(FederationError::UnexpectedMessage),
+    }
+}
+
+/// Performs the handshake with the gateway that has connected to us, making sure it's one of
+/// our federation `peers` and proving our own identity to it.
+///
+/// Returns the identity of the connected peer alongside the session protecting all further
+/// frames sent over the link.
+pub(crate) async fn accept_handshake<S>(
+    ws_stream: &mut WebSocketStream<S>,
+    local_identity: &identity::KeyPair,
+    peers: &[FederationPeer],
+) -> Result<(identity::PublicKey, LinkSession), FederationError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (remote_identity, remote_challenge, remote_ephemeral_key) =
+        match next_control_message(ws_stream).await? {
+            FederationMessage::Hello {
+                protocol_version,
+                identity,
+                challenge,
+                ephemeral_key,
+            } => {
+                if protocol_version != FEDERATION_PROTOCOL_VERSION {
+                    return Err(FederationError::IncompatibleProtocol {
+                        remote: protocol_version,
+                        current: FEDERATION_PROTOCOL_VERSION,
+                    });
+                }
+                let identity = identity::PublicKey::from_base58_string(identity)?;
+                if !peers.iter().any(|peer| peer.identity == identity) {
+                    return Err(FederationError::UnknownPeer(identity.to_base58_string()));
+                }
+                (
+                    identity,
+                    decode_challenge(challenge)?,
+                    decode_ephemeral_key(ephemeral_key)?,
+                )
+            }
+            _ => return Err(FederationError::UnexpectedMessage),
+        };
+
+    let challenge = new_challenge();
+    let ephemeral_keypair = encryption::KeyPair::new(&mut OsRng);
+    let ephemerals = HandshakeEphemerals {
+        initiator: &remote_ephemeral_key,
+        responder: ephemeral_keypair.public_key(),
+    };
+    let response = FederationMessage::HelloResponse {
+        identity: local_identity.public_key().to_base58_string(),
+        challenge: bs58::encode(challenge).into_string(),
+        ephemeral_key: ephemeral_keypair.public_key().to_base58_string(),
+        signature: prove_identity(
+            local_identity,
+            &remote_challenge,
+            &remote_identity,
+            ephemerals,
+        ),
+    };
+    ws_stream.send(response.into()).await?;
+
+    match next_control_message(ws_stream).await? {
+        FederationMessage::Proof { signature } => {
+            verify_identity(
+                &remote_identity,
+                &challenge,
+                local_identity,
+                ephemerals,
+                signature,
+            )?;
+
+            let transcript = handshake_transcript(
+                &remote_identity,
+                local_identity.public_key(),
+                &remote_challenge,
+                &challenge,
+                ephemerals,
+            );
+            let session = LinkSession::derive(
+                LinkRole::Responder,
+                &ephemeral_keypair,
+                &remote_ephemeral_key,
+                &transcript,
+            );
+            Ok((remote_identity, session))
+        }
+        FederationMessage::Error { message } => Err(FederationError::RemoteError(message)),
+        _ => Err(FederationError::UnexpectedMessage),
+    }
+}
@@ -0,0 +1,576 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::FederationPeer;
+use crate::node::client_handling::active_clients::ActiveClientsStore;
+use crate::node::client_handling::websocket::message_receiver::{
+    MixMessageReceiver, MixMessageSender,
+};
+use crate::node::federation::handshake::accept_handshake;
+use crate::node::federation::messages::{ClientPackets, FederationMessage, LinkFrame, PacketsKind};
+use crate::node::federation::session::LinkSession;
+use crate::node::federation::FederationError;
+use crate::node::storage::Storage;
+use crate::node::websocket_guard::WebSocketGuard;
+use futures::channel::mpsc;
+use futures::stream::SelectAll;
+use futures::{SinkExt, StreamExt};
+use log::*;
+use nym_credentials::coconut::bandwidth::current_unix_timestamp;
+use nym_crypto::asymmetric::identity;
+use nym_gateway_requests::authentication::attach_proof::{attach_proof_message, AttachNonce};
+use nym_sphinx::DestinationAddressBytes;
+use nym_task::TaskClient;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::protocol::Message;
+use tokio_tungstenite::WebSocketStream;
+
+/// Maximum age of the attach nonce issued by our peer for it to still be accepted.
+const ATTACH_NONCE_VALIDITY: Duration = Duration::from_secs(5 * 60);
+
+/// Nonces of all the attach proofs accepted within the last `ATTACH_NONCE_VALIDITY`,
+/// so that none of them could be replayed.
+#[derive(Clone, Default)]
+struct UsedAttachNonces(Arc<Mutex<HashMap<AttachNonce, u64>>>);
+
+impl UsedAttachNonces {
+    /// Marks the nonce as used, unless it has already been used or is not fresh.
+    ///
+    /// Returns whether the nonce was accepted.
+    fn try_use(&self, nonce: AttachNonce, now: u64) -> bool {
+        let validity = ATTACH_NONCE_VALIDITY.as_secs();
+        let issued_at = nonce.issued_at();
+        if issued_at > now + validity || issued_at + validity < now {
+            return false;
+        }
+
+        let mut used = self.0.lock().unwrap();
+        used.retain(|_, issued_at| *issued_at + validity >= now);
+        used.insert(nonce, issued_at).is_none()
+    }
+}
+
+/// Checks whether the client has signed the attach proof for the nonce issued by the gateway
+/// on the other side of the link.
+fn verify_attach_proof(
+    client: DestinationAddressBytes,
+    nonce: &AttachNonce,
+    signature: &str,
+    attaching_gateway: &identity::PublicKey,
+    home_gateway: &identity::PublicKey,
+) -> bool {
+    // the address of the client is its identity key
+    let (Ok(client_identity), Ok(signature)) = (
+        identity::PublicKey::from_bytes(client.as_bytes_ref()),
+        identity::Signature::from_base58_string(signature),
+    ) else {
+        return false;
+    };
+    client_identity
+        .verify(
+            &attach_proof_message(nonce, attaching_gateway, home_gateway),
+            &signature,
+        )
+        .is_ok()
+}
+
+/// Listens for federation links established by our peers on behalf of the clients anchored at
+/// this gateway.
+pub(crate) struct Listener<St> {
+    address: SocketAddr,
+    local_identity: Arc<identity::KeyPair>,
+    peers: Arc<Vec<FederationPeer>>,
+    storage: St,
+    active_clients_store: ActiveClientsStore,
+    used_attach_nonces: UsedAttachNonces,
+    websocket_guard: WebSocketGuard,
+}
+
+impl<St> Listener<St>
+where
+    St: Storage + Clone + 'static,
+{
+    pub(crate) fn new(
+        address: SocketAddr,
+        local_identity: Arc<identity::KeyPair>,
+        peers: Vec<FederationPeer>,
+        storage: St,
+        active_clients_store: ActiveClientsStore,
+        websocket_guard: WebSocketGuard,
+    ) -> Self {
+        Listener {
+            address,
+            local_identity,
+            peers: Arc::new(peers),
+            storage,
+            active_clients_store,
+            used_attach_nonces: UsedAttachNonces::default(),
+            websocket_guard,
+        }
+    }
+
+    async fn run(&mut self, mut shutdown: TaskClient) {
+        info!("Starting federation listener at {}", self.address);
+        let tcp_listener = match tokio::net::TcpListener::bind(self.address).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                error!("Failed to bind the federation listener to {} - {err}. Are you sure nothing else is running on the specified port and your user has sufficient permission to bind to the requested address?", self.address);
+                process::exit(1);
+            }
+        };
+
+        while !shutdown.is_shutdown() {
+            tokio::select! {
+                biased;
+                _ = shutdown.recv() => {
+                    log::trace!("federation::Listener: received shutdown");
+                }
+                connection = tcp_listener.accept() => {
+                    match connection {
+                        Ok((socket, remote_addr)) => {
+                            trace!("received a federation connection from {remote_addr}");
+                            let local_identity = Arc::clone(&self.local_identity);
+                            let peers = Arc::clone(&self.peers);
+                            let storage = self.storage.clone();
+                            let active_clients_store = self.active_clients_store.clone();
+                            let used_attach_nonces = self.used_attach_nonces.clone();
+                            let websocket_guard = self.websocket_guard.clone();
+                            let shutdown = shutdown.clone();
+                            tokio::spawn(async move {
+                                handle_connection(
+                                    socket,
+                                    remote_addr,
+                                    local_identity,
+                                    peers,
+                                    storage,
+                                    active_clients_store,
+                                    used_attach_nonces,
+                                    websocket_guard,
+                                    shutdown,
+                                )
+                                .await
+                            });
+                        }
+                        Err(err) => warn!("failed to accept federation connection: {err}"),
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn start(mut self, shutdown: TaskClient) -> JoinHandle<()> {
+        tokio::spawn(async move { self.run(shutdown).await })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_connection<St: Storage>(
+    socket: TcpStream,
+    remote_addr: SocketAddr,
+    local_identity: Arc<identity::KeyPair>,
+    peers: Arc<Vec<FederationPeer>>,
+    storage: St,
+    active_clients_store: ActiveClientsStore,
+    used_attach_nonces: UsedAttachNonces,
+    websocket_guard: WebSocketGuard,
+    mut shutdown: TaskClient,
+) {
+    shutdown.mark_as_success();
+
+    let mut ws_stream = match websocket_guard.accept(socket).await {
+        Ok(ws_stream) => ws_stream,
+        Err(err) => {
+            debug!("failed to complete websocket handshake with {remote_addr} - {err}");
+            return;
+        }
+    };
+
+    let handshake = websocket_guard
+        .complete_handshake(accept_handshake(&mut ws_stream, &local_identity, &peers))
+        .await
+        .unwrap_or(Err(FederationError::HandshakeTimeout));
+    let (remote, session) = match handshake {
+        Ok(established) => established,
+        Err(err) => {
+            warn!("failed to establish federation link with {remote_addr} - {err}");
+            let error = FederationMessage::new_error(err.to_string());
+            if let Err(err) = ws_stream.send(error.into()).await {
+                debug!("failed to send federation handshake error - {err}")
+            }
+            return;
+        }
+    };
+
+    info!("Established federation link with {remote} ({remote_addr})");
+    InboundLink {
+        remote,
+        local_identity,
+        ws_stream,
+        session,
+        storage,
+        active_clients_store,
+        used_attach_nonces,
+        websocket_guard,
+        attached: HashMap::new(),
+    }
+    .run(shutdown)
+    .await
+}
+
+/// Link established by a federation peer through which we forward messages to the clients
+/// currently connected to that peer.
+struct InboundLink<St> {
+    remote: identity::PublicKey,
+    local_identity: Arc<identity::KeyPair>,
+    ws_stream: WebSocketStream<TcpStream>,
+    session: LinkSession,
+    storage: St,
+    active_clients_store: ActiveClientsStore,
+    used_attach_nonces: UsedAttachNonces,
+    websocket_guard: WebSocketGuard,
+
+    /// Handles of all clients attached through this link.
+    attached: HashMap<DestinationAddressBytes, MixMessageSender>,
+}
+
+impl<St: Storage> InboundLink<St> {
+    async fn send_frame<F: Into<LinkFrame>>(&mut self, frame: F) -> Result<(), FederationError> {
+        let sealed = self.session.seal(frame.into());
+        self.ws_stream.send(Message::Binary(sealed)).await?;
+        Ok(())
+    }
+
+    /// Checks whether the client is registered with us and has signed a fresh attach proof
+    /// for the peer on the other side of the link.
+    async fn verify_client(
+        &self,
+        client: DestinationAddressBytes,
+        nonce: String,
+        signature: String,
+    ) -> Result<bool, FederationError> {
+        let Ok(nonce) = AttachNonce::try_from_base58_string(nonce) else {
+            return Ok(false);
+        };
+        if self.storage.get_shared_keys(client).await?.is_none() {
+            return Ok(false);
+        }
+        if !verify_attach_proof(
+            client,
+            &nonce,
+            &signature,
+            &self.remote,
+            self.local_identity.public_key(),
+        ) {
+            return Ok(false);
+        }
+
+        // only consume the nonce once the proof is known to be valid
+        Ok(self
+            .used_attach_nonces
+            .try_use(nonce, current_unix_timestamp()))
+    }
+
+    /// Forwards all messages stored for the client whilst it was offline over the link.
+    async fn push_stored_messages(
+        &mut self,
+        client: DestinationAddressBytes,
+    ) -> Result<(), FederationError> {
+        let mut start_next_after = None;
+        loop {
+            let (messages, new_start_next_after) = self
+                .storage
+                .retrieve_messages(client, start_next_after)
+                .await?;
+
+            let (messages, ids): (Vec<_>, Vec<_>) = messages
+                .into_iter()
+                .map(|msg| (msg.content, msg.id))
+                .unzip();
+
+            if messages.is_empty() {
+                return Ok(());
+            }
+            let packets = ClientPackets::new(PacketsKind::Forwarded, client, messages);
+            self.send_frame(packets).await?;
+            self.storage.remove_messages(ids).await?;
+
+            if new_start_next_after.is_none() {
+                return Ok(());
+            }
+            start_next_after = new_start_next_after
+        }
+    }
+
+    /// Attempts to attach the client to the link, so that all of its messages would get forwarded
+    /// to the remote gateway rather than getting stored.
+    ///
+    /// Returns the receiver of client messages if the request was successful.
+    async fn handle_attach(
+        &mut self,
+        client: String,
+        nonce: String,
+        signature: String,
+    ) -> Result<Option<(DestinationAddressBytes, MixMessageReceiver)>, FederationError> {
+        let address = DestinationAddressBytes::try_from_base58_string(client.clone())
+            .map_err(|_| FederationError::UnexpectedMessage)?;
+
+        // claim the client before draining its stored messages, so that neither a direct connection
+        // nor another link could drain them concurrently. if the client is already connected to us,
+        // either directly or through another link, the attachment is refused.
+        // any messages received in the meantime are buffered in the channel
+        let (mix_sender, mut mix_receiver) = mpsc::unbounded();
+        let status = self.verify_client(address, nonce, signature).await?
+            && self
+                .active_clients_store
+                .try_insert(address, mix_sender.clone());
+
+        let response = FederationMessage::AttachResponse { client, status };
+        if let Err(err) = self.send_frame(response).await {
+            if status {
+                self.active_clients_store.disconnect(address);
+            }
+            return Err(err);
+        }
+        if !status {
+            return Ok(None);
+        }
+
+        debug!(
+            "Attaching {address} to the federation link with {}",
+            self.remote
+        );
+        self.attached.insert(address, mix_sender);
+        if let Err(err) = self.push_stored_messages(address).await {
+            // make sure nothing received in the meantime gets lost
+            self.handle_detach(address);
+            while let Ok(Some(packets)) = mix_receiver.try_next() {
+                self.store_packets(address, packets).await
+            }
+            return Err(err);
+        }
+
+        Ok(Some((address, mix_receiver)))
+    }
+
+    fn handle_detach(&mut self, client: DestinationAddressBytes) {
+        if let Some(mix_sender) = self.attached.remove(&client) {
+            debug!(
+                "Detaching {client} from the federation link with {}",
+                self.remote
+            );
+            self.active_clients_store.disconnect(client);
+            // any messages still buffered in the channel are going to be sent to the remote
+            // which will return them to us as the client is gone
+            mix_sender.close_channel();
+        }
+    }
+
+    /// Stores packets that the remote gateway has failed to deliver to the client.
+    async fn store_packets(&self, client: DestinationAddressBytes, packets: Vec<Vec<u8>>) {
+        for packet in packets {
+            // note: the expiry of the packets is not known at this point anymore
+            if let Err(err) = self.storage.store_message(client, packet, None).await {
+                error!("Failed to store client data - {err}")
+            }
+        }
+    }
+
+    async fn handle_packets(&mut self, packets: ClientPackets) {
+        if packets.kind == PacketsKind::Returned {
+            self.handle_detach(packets.client);
+            self.store_packets(packets.client, packets.packets).await
+        } else {
+            warn!("{} has sent us packets to forward", self.remote)
+        }
+    }
+
+    async fn run(mut self, mut shutdown: TaskClient) {
+        let mut forwarded = SelectAll::new();
+        // our peer keeps the link alive with pings even if there's nothing to send
+        let mut last_message = Instant::now();
+
+        while !shutdown.is_shutdown() {
+            tokio::select! {
+                biased;
+                _ = shutdown.recv() => {
+                    log::trace!("federation::InboundLink: received shutdown");
+                }
+                msg = self.websocket_guard.next_message(&mut self.ws_stream, last_message) => {
+                    let msg = match msg {
+                        None => break,
+                        Some(Ok(msg)) => {
+                            last_message = Instant::now();
+                            msg
+                        }
+                        Some(Err(err)) => {
+                            warn!("federation link with {} got corrupted - {err}", self.remote);
+                            break;
+                        }
+                    };
+
+                    let frame = match msg {
+                        Message::Binary(sealed) => self.session.open(&sealed),
+                        // only the handshake is not protected by the session
+                        Message::Text(_) => Err(FederationError::UnexpectedMessage),
+                        Message::Close(_) => break,
+                        _ => continue,
+                    };
+                    // any frame failing the authentication is fatal to the link,
+                    // as the remaining ones could no longer be trusted either
+                    let frame = match frame {
+                        Ok(frame) => frame,
+                        Err(err) => {
+                            warn!("received invalid frame over the federation link with {} - {err}", self.remote);
+                            break;
+                        }
+                    };
+
+                    match frame {
+                        LinkFrame::Control(msg) => match msg {
+                            FederationMessage::Attach { client, nonce, signature } => {
+                                match self.handle_attach(client, nonce, signature).await {
+                                    Ok(Some((address, mix_receiver))) => {
+                                        forwarded.push(mix_receiver.map(move |packets| (address, packets)))
+                                    }
+                                    Ok(None) => (),
+                                    Err(err) => {
+                                        warn!("failed to attach client to the federation link with {} - {err}", self.remote);
+                                        break;
+                                    }
+                                }
+                            }
+                            FederationMessage::Detach { client } => {
+                                if let Ok(client) = DestinationAddressBytes::try_from_base58_string(client) {
+                                    self.handle_detach(client)
+                                }
+                            }
+                            FederationMessage::Error { message } => {
+                                warn!("{} has reported federation error - {message}", self.remote)
+                            }
+                            _ => warn!("received unexpected federation message from {}", self.remote),
+                        },
+                        LinkFrame::Packets(packets) => self.handle_packets(packets).await,
+                    }
+                }
+                Some((client, packets)) = forwarded.next(), if !forwarded.is_empty() => {
+                    let frame = ClientPackets::new(PacketsKind::Forwarded, client, packets.clone());
+                    if let Err(err) = self.send_frame(frame).await {
+                        warn!("failed to forward packets to {} - {err}", self.remote);
+                        self.store_packets(client, packets).await;
+                        break;
+                    }
+                }
+            }
+        }
+
+        // the link is gone - make sure all further messages of the attached clients get stored
+        let attached: Vec<_> = self.attached.keys().copied().collect();
+        for client in attached {
+            self.handle_detach(client)
+        }
+        while let Some((client, packets)) = forwarded.next().await {
+            self.store_packets(client, packets).await
+        }
+        info!("Closed federation link with {}", self.remote);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_690_000_000;
+
+    #[test]
+    fn attach_nonces_cannot_be_replayed() {
+        let mut rng = rand::thread_rng();
+        let used_nonces = UsedAttachNonces::default();
+
+        let nonce = AttachNonce::new(&mut rng, NOW);
+        assert!(used_nonces.try_use(nonce, NOW + 1));
+        assert!(!used_nonces.try_use(nonce, NOW + 2));
+
+        // and neither can the stale ones that are no longer remembered
+        let validity = ATTACH_NONCE_VALIDITY.as_secs();
+        assert!(!used_nonces.try_use(nonce, NOW + validity + 1));
+        let stale = AttachNonce::new(&mut rng, NOW);
+        assert!(!used_nonces.try_use(stale, NOW + validity + 1));
+
+        // nor the ones issued in the future
+        let future = AttachNonce::new(&mut rng, NOW + validity + 1);
+        assert!(!used_nonces.try_use(future, NOW));
+    }
+
+    #[test]
+    fn used_attach_nonces_are_eventually_forgotten() {
+        let used_nonces = UsedAttachNonces::default();
+        let nonce = AttachNonce::new(&mut rand::thread_rng(), NOW);
+        assert!(used_nonces.try_use(nonce, NOW));
+
+        let later = AttachNonce::new(
+            &mut rand::thread_rng(),
+            NOW + 2 * ATTACH_NONCE_VALIDITY.as_secs(),
+        );
+        assert!(used_nonces.try_use(later, later.issued_at()));
+        assert_eq!(used_nonces.0.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn attach_proof_is_only_valid_for_the_issuing_gateway() {
+        let mut rng = rand::thread_rng();
+        let client = identity::KeyPair::new(&mut rng);
+        let attaching = identity::KeyPair::new(&mut rng);
+        let other = identity::KeyPair::new(&mut rng);
+        let home = identity::KeyPair::new(&mut rng);
+        let address = client.public_key().derive_destination_address();
+        let nonce = AttachNonce::new(&mut rng, NOW);
+
+        let signature = client
+            .private_key()
+            .sign(&attach_proof_message(
+                &nonce,
+                attaching.public_key(),
+                home.public_key(),
+            ))
+            .to_base58_string();
+
+        assert!(verify_attach_proof(
+            address,
+            &nonce,
+            &signature,
+            attaching.public_key(),
+            home.public_key()
+        ));
+        // replayed by another gateway
+        assert!(!verify_attach_proof(
+            address,
+            &nonce,
+            &signature,
+            other.public_key(),
+            home.public_key()
+        ));
+        // replayed with a different nonce
+        assert!(!verify_attach_proof(
+            address,
+            &AttachNonce::new(&mut rng, NOW),
+            &signature,
+            attaching.public_key(),
+            home.public_key()
+        ));
+        // signed by someone else
+        assert!(!verify_attach_proof(
+            other.public_key().derive_destination_address(),
+            &nonce,
+            &signature,
+            attaching.public_key(),
+            home.public_key()
+        ));
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::federation::FederationError;
+use nym_crypto::asymmetric::{encryption, identity};
+use nym_sphinx::{DestinationAddressBytes, DESTINATION_ADDRESS_LENGTH};
+use serde::{Deserialize, Serialize};
+use std::convert::{TryFrom, TryInto};
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+pub(crate) const FEDERATION_PROTOCOL_VERSION: u8 = 2;
+
+/// Length of the challenge each side of the link has to sign during the handshake.
+pub(crate) const CHALLENGE_LENGTH: usize = 32;
+
+// domain separator making sure the handshake signatures can't be reused in any other context
+const CHALLENGE_DOMAIN: &[u8] = b"nym-gateway-federation-challenge";
+
+// domain separator of the transcript the session keys are bound to
+const TRANSCRIPT_DOMAIN: &[u8] = b"nym-gateway-federation-transcript";
+
+/// Ephemeral keys contributed to the handshake by both sides of the link.
+#[derive(Clone, Copy)]
+pub(crate) struct HandshakeEphemerals<'a> {
+    pub(crate) initiator: &'a encryption::PublicKey,
+    pub(crate) responder: &'a encryption::PublicKey,
+}
+
+impl HandshakeEphemerals<'_> {
+    fn to_bytes(self) -> Vec<u8> {
+        self.initiator
+            .to_bytes()
+            .iter()
+            .chain(self.responder.to_bytes().iter())
+            .copied()
+            .collect()
+    }
+}
+
+/// Constructs the message that has to be signed by a gateway in order to prove its identity
+/// to the `verifier` that has sent it the specified `challenge`. The message also includes
+/// the ephemeral keys of both sides, so that the session keys derived from them could
+/// not have been substituted by anyone relaying the handshake.
+pub(crate) fn challenge_message(
+    challenge: &[u8],
+    verifier: &identity::PublicKey,
+    ephemerals: HandshakeEphemerals<'_>,
+) -> Vec<u8> {
+    CHALLENGE_DOMAIN
+        .iter()
+        .chain(challenge.iter())
+        .chain(verifier.to_bytes().iter())
+        .chain(ephemerals.to_bytes().iter())
+        .copied()
+        .collect()
+}
+
+/// Constructs the transcript of the whole handshake that the session keys are bound to.
+pub(crate) fn handshake_transcript(
+    initiator: &identity::PublicKey,
+    responder: &identity::PublicKey,
+    initiator_challenge: &[u8],
+    responder_challenge: &[u8],
+    ephemerals: HandshakeEphemerals<'_>,
+) -> Vec<u8> {
+    TRANSCRIPT_DOMAIN
+        .iter()
+        .chain(initiator.to_bytes().iter())
+        .chain(responder.to_bytes().iter())
+        .chain(initiator_challenge.iter())
+        .chain(responder_challenge.iter())
+        .chain(ephemerals.to_bytes().iter())
+        .copied()
+        .collect()
+}
+
+/// Control messages exchanged between the gateways over the federation link.
+/// Apart from the handshake messages (and the errors reported during it), they are only ever sent
+/// as [`LinkFrame`]s protected by the session established by the handshake.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum FederationMessage {
+    /// Sent by the gateway initiating the link.
+    Hello {
+        protocol_version: u8,
+        identity: String,
+        challenge: String,
+        ephemeral_key: String,
+    },
+
+    /// Sent by the gateway accepting the link. Includes its signature on the initiator's challenge.
+    HelloResponse {
+        identity: String,
+        challenge: String,
+        ephemeral_key: String,
+        signature: String,
+    },
+
+    /// Final message of the handshake with the initiator's signature on the responder's challenge.
+    Proof {
+        signature: String,
+    },
+
+    /// Request to start forwarding messages of the client to the requesting gateway.
+    /// The `signature` is made by the client on the attach proof constructed for the `nonce`
+    /// issued to it by the requesting gateway.
+    Attach {
+        client: String,
+        nonce: String,
+        signature: String,
+    },
+
+    AttachResponse {
+        client: String,
+        status: bool,
+    },
+
+    /// Indication that the client is no longer connected to the requesting gateway.
+    Detach {
+        client: String,
+    },
+
+    Error {
+        message: String,
+    },
+}
+
+impl FederationMessage {
+    pub(crate) fn new_error<S: Into<String>>(message: S) -> Self {
+        FederationMessage::Error {
+            message: message.into(),
+        }
+    }
+}
+
+impl From<FederationMessage> for Message {
+    fn from(msg: FederationMessage) -> Self {
+        // it should be safe to call `unwrap` here as the message is generated by us
+        let str_msg = serde_json::to_string(&msg).unwrap();
+        Message::Text(str_msg)
+    }
+}
+
+impl TryFrom<String> for FederationMessage {
+    type Error = serde_json::Error;
+
+    fn try_from(msg: String) -> Result<Self, Self::Error> {
+        serde_json::from_str(&msg)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub(crate) enum PacketsKind {
+    /// Packets received by the home gateway that are forwarded to the gateway the client is attached to.
+    Forwarded = 0,
+
+    /// Packets that could not have been delivered to the client and are sent back to the home gateway.
+    Returned = 1,
+}
+
+/// Unwrapped sphinx packets destined for particular client sent over the federation link.
+///
+/// Encoded as `kind || client_address || (u64 length || packet)*`
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ClientPackets {
+    pub(crate) kind: PacketsKind,
+    pub(crate) client: DestinationAddressBytes,
+    pub(crate) packets: Vec<Vec<u8>>,
+}
+
+impl ClientPackets {
+    pub(crate) fn new(
+        kind: PacketsKind,
+        client: DestinationAddressBytes,
+        packets: Vec<Vec<u8>>,
+    ) -> Self {
+        ClientPackets {
+            kind,
+            client,
+            packets,
+        }
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        let mut bytes = vec![self.kind as u8];
+        bytes.extend_from_slice(self.client.as_bytes_ref());
+        for packet in self.packets {
+            bytes.extend_from_slice(&(packet.len() as u64).to_be_bytes());
+            bytes.extend_from_slice(&packet);
+        }
+        bytes
+    }
+
+    pub(crate) fn try_from_bytes(bytes: &[u8]) -> Result<Self, FederationError> {
+        if bytes.len() < 1 + DESTINATION_ADDRESS_LENGTH {
+            return Err(FederationError::MalformedPackets);
+        }
+
+        let kind = match bytes[0] {
+            0 => PacketsKind::Forwarded,
+            1 => PacketsKind::Returned,
+            _ => return Err(FederationError::MalformedPackets),
+        };
+        // the unwrap is fine as we have just checked the length
+        let client = DestinationAddressBytes::from_bytes(
+            bytes[1..1 + DESTINATION_ADDRESS_LENGTH].try_into().unwrap(),
+        );
+
+        let mut packets = Vec::new();
+        let mut remaining = &bytes[1 + DESTINATION_ADDRESS_LENGTH..];
+        while !remaining.is_empty() {
+            if remaining.len() < 8 {
+                return Err(FederationError::MalformedPackets);
+            }
+            let len = u64::from_be_bytes(remaining[..8].try_into().unwrap());
+            remaining = &remaining[8..];
+            if (remaining.len() as u64) < len {
+                return Err(FederationError::MalformedPackets);
+            }
+            let (packet, rest) = remaining.split_at(len as usize);
+            packets.push(packet.to_vec());
+            remaining = rest;
+        }
+
+        Ok(ClientPackets {
+            kind,
+            client,
+            packets,
+        })
+    }
+}
+
+/// Frame exchanged over the federation link once the handshake has completed.
+///
+/// Encoded as `kind || payload`
+#[derive(Debug)]
+pub(crate) enum LinkFrame {
+    Control(FederationMessage),
+    Packets(ClientPackets),
+}
+
+impl LinkFrame {
+    const CONTROL: u8 = 0;
+    const PACKETS: u8 = 1;
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        match self {
+            LinkFrame::Control(msg) => {
+                // it should be safe to call `unwrap` here as the message is generated by us
+                let payload = serde_json::to_vec(&msg).unwrap();
+                std::iter::once(Self::CONTROL).chain(payload).collect()
+            }
+            LinkFrame::Packets(packets) => std::iter::once(Self::PACKETS)
+                .chain(packets.into_bytes())
+                .collect(),
+        }
+    }
+
+    pub(crate) fn try_from_bytes(bytes: &[u8]) -> Result<Self, FederationError> {
+        match bytes.split_first() {
+            Some((&Self::CONTROL, payload)) => {
+                Ok(LinkFrame::Control(serde_json::from_slice(payload)?))
+            }
+            Some((&Self::PACKETS, payload)) => {
+                Ok(LinkFrame::Packets(ClientPackets::try_from_bytes(payload)?))
+            }
+            _ => Err(FederationError::UnexpectedMessage),
+        }
+    }
+}
+
+impl From<FederationMessage> for LinkFrame {
+    fn from(msg: FederationMessage) -> Self {
+        LinkFrame::Control(msg)
+    }
+}
+
+impl From<ClientPackets> for LinkFrame {
+    fn from(packets: ClientPackets) -> Self {
+        LinkFrame::Packets(packets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_packets_survive_encoding() {
+        let packets = ClientPackets::new(
+            PacketsKind::Returned,
+            DestinationAddressBytes::from_bytes([42; DESTINATION_ADDRESS_LENGTH]),
+            vec![vec![1, 2, 3], vec![], vec![4; 100]],
+        );
+        let bytes = packets.into_bytes();
+        let recovered = ClientPackets::try_from_bytes(&bytes).unwrap();
+
+        assert_eq!(recovered.kind, PacketsKind::Returned);
+        assert_eq!(recovered.packets, vec![vec![1, 2, 3], vec![], vec![4; 100]]);
+
+        // truncated packet
+        assert!(ClientPackets::try_from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        // unknown kind
+        let mut bad_kind = bytes;
+        bad_kind[0] = 42;
+        assert!(ClientPackets::try_from_bytes(&bad_kind).is_err());
+    }
+
+    #[test]
+    fn link_frames_survive_encoding() {
+        let frame = LinkFrame::from(FederationMessage::Detach {
+            client: "foomp".to_string(),
+        });
+        match LinkFrame::try_from_bytes(&frame.into_bytes()).unwrap() {
+            LinkFrame::Control(FederationMessage::Detach { client }) => assert_eq!(client, "foomp"),
+            _ => panic!("unexpected frame"),
+        }
+
+        let frame = LinkFrame::from(ClientPackets::new(
+            PacketsKind::Forwarded,
+            DestinationAddressBytes::from_bytes([42; DESTINATION_ADDRESS_LENGTH]),
+            vec![vec![1, 2, 3]],
+        ));
+        match LinkFrame::try_from_bytes(&frame.into_bytes()).unwrap() {
+            LinkFrame::Packets(packets) => assert_eq!(packets.packets, vec![vec![1, 2, 3]]),
+            _ => panic!("unexpected frame"),
+        }
+
+        assert!(LinkFrame::try_from_bytes(&[]).is_err());
+        assert!(LinkFrame::try_from_bytes(&[42, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn challenge_signature_is_bound_to_the_verifier_and_ephemeral_keys() {
+        let mut rng = rand::thread_rng();
+        let prover = identity::KeyPair::new(&mut rng);
+        let verifier = identity::KeyPair::new(&mut rng);
+        let other = identity::KeyPair::new(&mut rng);
+        let initiator_ephemeral = encryption::KeyPair::new(&mut rng);
+        let responder_ephemeral = encryption::KeyPair::new(&mut rng);
+        let other_ephemeral = encryption::KeyPair::new(&mut rng);
+        let challenge = [1u8; CHALLENGE_LENGTH];
+
+        let ephemerals = HandshakeEphemerals {
+            initiator: initiator_ephemeral.public_key(),
+            responder: responder_ephemeral.public_key(),
+        };
+        let signature = prover.private_key().sign(&challenge_message(
+            &challenge,
+            verifier.public_key(),
+            ephemerals,
+        ));
+
+        assert!(prover
+            .public_key()
+            .verify(
+                &challenge_message(&challenge, verifier.public_key(), ephemerals),
+                &signature
+            )
+            .is_ok());
+        assert!(prover
+            .public_key()
+            .verify(
+                &challenge_message(&challenge, other.public_key(), ephemerals),
+                &signature
+            )
+            .is_err());
+
+        // the ephemeral key substituted by a relay
+        let substituted = HandshakeEphemerals {
+            initiator: other_ephemeral.public_key(),
+            responder: responder_ephemeral.public_key(),
+        };
+        assert!(prover
+            .public_key()
+            .verify(
+                &challenge_message(&challenge, verifier.public_key(), substituted),
+                &signature
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Gateway federation allows a client to keep using the address anchored at its home gateway
+//! whilst being connected to a different one. The gateway the client is currently connected to
+//! establishes an authenticated link with the home gateway which then forwards all messages
+//! destined for that client over it (rather than storing them) until the client detaches.
+//! Apart from the handshake, every frame sent over the link is encrypted and authenticated
+//! with the keys of the session established by it.
+
+use crate::node::federation::messages::FederationMessage;
+use crate::node::storage::error::StorageError;
+use futures::StreamExt;
+use nym_crypto::asymmetric::identity::Ed25519RecoveryError;
+use std::convert::TryFrom;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::tungstenite::{protocol::Message, Error as WsError};
+use tokio_tungstenite::WebSocketStream;
+
+pub(crate) use client::FederationClient;
+pub(crate) use listener::Listener;
+pub(crate) use messages::FEDERATION_PROTOCOL_VERSION;
+
+mod client;
+mod handshake;
+mod listener;
+mod messages;
+mod session;
+
+#[derive(Debug, Error)]
+pub(crate) enum FederationError {
+    #[error("Internal gateway storage error")]
+    StorageError(#[from] StorageError),
+
+    #[error("Failed to connect to the federation peer - {0}")]
+    ConnectionFailure(#[from] std::io::Error),
+
+    #[error("Timed out while attempting to connect to the federation peer")]
+    ConnectionTimeout,
+
+    #[error("Experienced federation link error - {0}")]
+    LinkError(#[from] WsError),
+
+    #[error("Timed out while performing the federation handshake")]
+    HandshakeTimeout,
+
+    #[error("The federation link got closed")]
+    LinkClosed,
+
+    #[error("{0} is not one of our federation peers")]
+    UnknownPeer(String),
+
+    #[error("Attempted to establish federation link using incompatible protocol version. Ours is {current} and the peer reports {remote}")]
+    IncompatibleProtocol { remote: u8, current: u8 },
+
+    #[error("Received malformed key material - {0}")]
+    MalformedKeyMaterial(#[from] Ed25519RecoveryError),
+
+    #[error("Received malformed handshake challenge")]
+    MalformedChallenge,
+
+    #[error("Received malformed handshake ephemeral key")]
+    MalformedEphemeralKey,
+
+    #[error("The federation peer failed to prove its identity")]
+    InvalidPeerSignature,
+
+    #[error("Received federation frame that failed the authentication")]
+    UnauthenticatedFrame,
+
+    #[error("Received malformed federation message - {0}")]
+    MalformedMessage(#[from] serde_json::Error),
+
+    #[error("Received malformed forwarded packets")]
+    MalformedPackets,
+
+    #[error("Received unexpected federation message")]
+    UnexpectedMessage,
+
+    #[error("The federation peer has returned an error - {0}")]
+    RemoteError(String),
+}
+
+/// Reads the next control message from the federation link, skipping over any ping/pong frames.
+async fn next_control_message<S>(
+    ws_stream: &mut WebSocketStream<S>,
+) -> Result<FederationMessage, FederationError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        match ws_stream.next().await {
+            None => return Err(FederationError::LinkClosed),
+            Some(Err(err)) => return Err(err.into()),
+            Some(Ok(Message::Text(text))) => return Ok(FederationMessage::try_from(text)?),
+            Some(Ok(Message::Close(_))) => return Err(FederationError::LinkClosed),
+            Some(Ok(Message::Binary(_))) => return Err(FederationError::UnexpectedMessage),
+            Some(Ok(_)) => continue,
+        }
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protection of the frames exchanged over an established federation link.
+//!
+//! Both sides contribute an ephemeral x25519 key to the handshake and sign it alongside the
+//! challenges, so that the derived session keys are only known to the authenticated peers.
+//! Every subsequent frame is encrypted and tagged with the key of its direction. The tag also
+//! covers the sequence number of the frame, which is implicit as the link is ordered, so any
+//! injected, altered, replayed, reordered or dropped frame is detected by the receiver.
+
+use crate::node::federation::messages::LinkFrame;
+use crate::node::federation::FederationError;
+use nym_crypto::asymmetric::encryption;
+use nym_crypto::generic_array::typenum::Unsigned;
+use nym_crypto::hkdf;
+use nym_crypto::hmac::{compute_keyed_hmac, recompute_keyed_hmac_and_verify_tag};
+use nym_crypto::symmetric::stream_cipher::{self, IV};
+use nym_gateway_requests::registration::handshake::{SharedKeySize, SharedKeys};
+use nym_gateway_requests::GatewayMacSize;
+use nym_sphinx::params::{
+    GatewayEncryptionAlgorithm, GatewayIntegrityHmacAlgorithm, GatewaySharedKeyHkdfAlgorithm,
+};
+
+// domain separator of the session keys derivation
+const SESSION_KEYS_LABEL: &[u8] = b"nym-gateway-federation-session-keys";
+
+/// Side of the federation link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LinkRole {
+    /// The gateway that has established the link, i.e. the one the client is connected to.
+    Initiator,
+
+    /// The gateway that has accepted the link, i.e. the home gateway of the client.
+    Responder,
+}
+
+/// Keys and the sequence number of frames sent in one direction of the link.
+struct DirectionState {
+    keys: SharedKeys,
+    sequence: u64,
+}
+
+impl DirectionState {
+    // the sequence number is put in front of the iv, leaving the trailing 8 bytes
+    // for the block counter of the cipher, so that no keystream is ever reused
+    fn iv(&self) -> IV<GatewayEncryptionAlgorithm> {
+        let mut iv = stream_cipher::zero_iv::<GatewayEncryptionAlgorithm>();
+        iv[..8].copy_from_slice(&self.sequence.to_be_bytes());
+        iv
+    }
+
+    fn tagged_data(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.sequence
+            .to_be_bytes()
+            .iter()
+            .chain(ciphertext.iter())
+            .copied()
+            .collect()
+    }
+}
+
+/// Session established over the federation link once the handshake has completed.
+pub(crate) struct LinkSession {
+    sending: DirectionState,
+    receiving: DirectionState,
+}
+
+impl LinkSession {
+    /// Derives the session keys from the ephemeral keys exchanged during the handshake.
+    /// The `transcript` has to be identical on both sides of the link.
+    pub(crate) fn derive(
+        role: LinkRole,
+        local_ephemeral: &encryption::KeyPair,
+        remote_ephemeral: &encryption::PublicKey,
+        transcript: &[u8],
+    ) -> Self {
+        let dh_result = local_ephemeral
+            .private_key()
+            .diffie_hellman(remote_ephemeral);
+
+        let key_size = SharedKeySize::to_usize();
+        // there is no reason for this to fail as our okm is only twice the size of the shared keys
+        let okm = hkdf::extract_then_expand::<GatewaySharedKeyHkdfAlgorithm>(
+            Some(transcript),
+            &dh_result,
+            Some(SESSION_KEYS_LABEL),
+            2 * key_size,
+        )
+        .expect("somehow too long okm was provided");
+
+        let initiator_keys = SharedKeys::try_from_bytes(&okm[..key_size])
+            .expect("okm was expanded to incorrect length!");
+        let responder_keys = SharedKeys::try_from_bytes(&okm[key_size..])
+            .expect("okm was expanded to incorrect length!");
+
+        let (sending_keys, receiving_keys) = match role {
+            LinkRole::Initiator => (initiator_keys, responder_keys),
+            LinkRole::Responder => (responder_keys, initiator_keys),
+        };
+
+        LinkSession {
+            sending: DirectionState {
+                keys: sending_keys,
+                sequence: 0,
+            },
+            receiving: DirectionState {
+                keys: receiving_keys,
+                sequence: 0,
+            },
+        }
+    }
+
+    /// Encrypts and tags the frame to be sent over the link.
+    ///
+    /// Encoded as `tag || ciphertext`
+    pub(crate) fn seal(&mut self, frame: LinkFrame) -> Vec<u8> {
+        let state = &mut self.sending;
+        let ciphertext = stream_cipher::encrypt::<GatewayEncryptionAlgorithm>(
+            state.keys.encryption_key(),
+            &state.iv(),
+            &frame.into_bytes(),
+        );
+        let tag = compute_keyed_hmac::<GatewayIntegrityHmacAlgorithm>(
+            state.keys.mac_key(),
+            &state.tagged_data(&ciphertext),
+        );
+        state.sequence += 1;
+
+        tag.into_bytes().into_iter().chain(ciphertext).collect()
+    }
+
+    /// Verifies and decrypts the frame received over the link. It has to be the very next frame
+    /// sent by the remote.
+    pub(crate) fn open(&mut self, sealed: &[u8]) -> Result<LinkFrame, FederationError> {
+        let mac_size = GatewayMacSize::to_usize();
+        if sealed.len() < mac_size {
+            return Err(FederationError::UnauthenticatedFrame);
+        }
+
+        let state = &mut self.receiving;
+        let (tag, ciphertext) = sealed.split_at(mac_size);
+        if !recompute_keyed_hmac_and_verify_tag::<GatewayIntegrityHmacAlgorithm>(
+            state.keys.mac_key(),
+            &state.tagged_data(ciphertext),
+            tag,
+        ) {
+            return Err(FederationError::UnauthenticatedFrame);
+        }
+
+        let plaintext = stream_cipher::decrypt::<GatewayEncryptionAlgorithm>(
+            state.keys.encryption_key(),
+            &state.iv(),
+            ciphertext,
+        );
+        state.sequence += 1;
+
+        LinkFrame::try_from_bytes(&plaintext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::federation::messages::FederationMessage;
+
+    fn session_pair() -> (LinkSession, LinkSession) {
+        let mut rng = rand::thread_rng();
+        let initiator_ephemeral = encryption::KeyPair::new(&mut rng);
+        let responder_ephemeral = encryption::KeyPair::new(&mut rng);
+        let transcript = b"transcript";
+
+        let initiator = LinkSession::derive(
+            LinkRole::Initiator,
+            &initiator_ephemeral,
+            responder_ephemeral.public_key(),
+            transcript,
+        );
+        let responder = LinkSession::derive(
+            LinkRole::Responder,
+            &responder_ephemeral,
+            initiator_ephemeral.public_key(),
+            transcript,
+        );
+        (initiator, responder)
+    }
+
+    fn detach_frame(client: &str) -> LinkFrame {
+        LinkFrame::Control(FederationMessage::Detach {
+            client: client.to_string(),
+        })
+    }
+
+    fn detached_client(frame: LinkFrame) -> String {
+        match frame {
+            LinkFrame::Control(FederationMessage::Detach { client }) => client,
+            _ => panic!("unexpected frame"),
+        }
+    }
+
+    #[test]
+    fn frames_are_delivered_in_both_directions() {
+        let (mut initiator, mut responder) = session_pair();
+
+        for i in 0..3 {
+            let sealed = initiator.seal(detach_frame(&format!("foo{i}")));
+            let opened = responder.open(&sealed).unwrap();
+            assert_eq!(detached_client(opened), format!("foo{i}"));
+
+            let sealed = responder.seal(detach_frame(&format!("bar{i}")));
+            let opened = initiator.open(&sealed).unwrap();
+            assert_eq!(detached_client(opened), format!("bar{i}"));
+        }
+    }
+
+    #[test]
+    fn altered_frames_are_rejected() {
+        let (mut initiator, mut responder) = session_pair();
+
+        let mut sealed = initiator.seal(detach_frame("foo"));
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(responder.open(&sealed).is_err());
+    }
+
+    #[test]
+    fn replayed_reordered_and_dropped_frames_are_rejected() {
+        let (mut initiator, mut responder) = session_pair();
+
+        let first = initiator.seal(detach_frame("foo"));
+        let second = initiator.seal(detach_frame("bar"));
+        let third = initiator.seal(detach_frame("baz"));
+
+        // the frames can't be reordered (nor can the first one be dropped)
+        assert!(responder.open(&second).is_err());
+
+        let (mut initiator, mut responder) = session_pair();
+        let first_again = initiator.seal(detach_frame("foo"));
+        responder.open(&first_again).unwrap();
+        // nor replayed
+        assert!(responder.open(&first_again).is_err());
+
+        // and the frames of one session are meaningless in another one
+        let (_, mut other_responder) = session_pair();
+        assert!(other_responder.open(&first).is_err());
+        assert!(other_responder.open(&third).is_err());
+    }
+
+    #[test]
+    fn frames_are_not_reflected() {
+        let (mut initiator, _) = session_pair();
+
+        // the frame sent by us can't be echoed back as if it came from the remote
+        let sealed = initiator.seal(detach_frame("foo"));
+        assert!(initiator.open(&sealed).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::client_handling::active_clients::ActiveClientsStore;
+use log::*;
+use nym_crypto::asymmetric::identity;
+use nym_task::TaskClient;
+use nym_validator_client::models::{
+    HeartbeatNodeType, NodeHeartbeat, NodeHeartbeatStats, SignedNodeHeartbeat,
+};
+use nym_validator_client::NymApiClient;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Periodically announces to all nym-apis that the gateway is alive, so that it would get
+/// promptly excluded from the network if it goes down.
+pub(crate) struct HeartbeatSender {
+    identity_keypair: Arc<identity::KeyPair>,
+    active_clients_store: ActiveClientsStore,
+    nym_api_urls: Vec<Url>,
+    interval: Duration,
+    shutdown: TaskClient,
+}
+
+impl HeartbeatSender {
+    pub(crate) fn new(
+        identity_keypair: Arc<identity::KeyPair>,
+        active_clients_store: ActiveClientsStore,
+        nym_api_urls: Vec<Url>,
+        interval: Duration,
+        shutdown: TaskClient,
+    ) -> Self {
+        HeartbeatSender {
+            identity_keypair,
+            active_clients_store,
+            nym_api_urls,
+            interval,
+            shutdown,
+        }
+    }
+
+    fn signed_heartbeat(&self) -> SignedNodeHeartbeat {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let heartbeat = NodeHeartbeat {
+            identity_key: self.identity_keypair.public_key().to_base58_string(),
+            node_type: HeartbeatNodeType::Gateway,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp,
+            // the gateway does not keep track of its packet counts
+            stats: NodeHeartbeatStats {
+                connected_clients: self.active_clients_store.size() as u64,
+                ..Default::default()
+            },
+        };
+        let signature = self
+            .identity_keypair
+            .private_key()
+            .sign(&heartbeat.signable_bytes())
+            .to_base58_string();
+
+        SignedNodeHeartbeat {
+            heartbeat,
+            signature,
+        }
+    }
+
+    async fn send_heartbeat(&self) {
+        if self.nym_api_urls.is_empty() {
+            warn!("there are no nym-api endpoints to send the heartbeat to");
+            return;
+        }
+
+        // every nym-api tracks the liveness on its own, so all of them have to hear from us
+        let heartbeat = self.signed_heartbeat();
+        for nym_api in &self.nym_api_urls {
+            let client = NymApiClient::new(nym_api.clone());
+            match client.send_node_heartbeat(&heartbeat).await {
+                Ok(_) => debug!("sent heartbeat to {nym_api}"),
+                Err(err) => warn!("failed to send heartbeat to {nym_api}: {err}"),
+            }
+        }
+    }
+
+    pub(crate) async fn run(&mut self) {
+        let mut interval = tokio::time::interval(self.interval);
+        while !self.shutdown.is_shutdown() {
+            tokio::select! {
+                _ = interval.tick() => self.send_heartbeat().await,
+                _ = self.shutdown.recv() => {
+                    trace!("HeartbeatSender: Received shutdown");
+                }
+            }
+        }
+    }
+}
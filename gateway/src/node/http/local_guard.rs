@@ -0,0 +1,37 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+use std::net::SocketAddr;
+
+#[derive(Debug)]
+pub struct NonLocalRequestError;
+
+/// Request guard that only allows requests coming from a local address.
+///
+/// Note that it looks at the address of the socket peer rather than at `client_ip`, as the latter
+/// would also accept whatever a client put into the forwarding headers.
+pub(crate) struct LocalRequest;
+
+fn is_local_address(remote: Option<SocketAddr>) -> bool {
+    matches!(remote, Some(address) if address.ip().is_loopback())
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LocalRequest {
+    type Error = NonLocalRequestError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        if is_local_address(request.remote()) {
+            Outcome::Success(LocalRequest)
+        } else {
+            warn!(
+                "Received a request from {:?} for a local-only route",
+                request.remote()
+            );
+            Outcome::Failure((Status::Unauthorized, NonLocalRequestError))
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::client_handling::active_clients::ActiveClientsStore;
+use crate::node::http::local_guard::LocalRequest;
+use crate::node::statistics::operator::{
+    CredentialRedemptions, ErrorCounters, OperatorMetrics, RejectedFrames,
+};
+use crate::node::storage::quota::MessageStoreUsage;
+use crate::node::storage::Storage;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+
+/// Everything the operator overview is assembled from.
+pub(crate) struct OverviewState {
+    active_clients_store: ActiveClientsStore,
+    storage: Box<dyn Storage>,
+    metrics: OperatorMetrics,
+}
+
+impl OverviewState {
+    pub(crate) fn new<St: Storage + 'static>(
+        active_clients_store: ActiveClientsStore,
+        storage: St,
+        metrics: OperatorMetrics,
+    ) -> Self {
+        OverviewState {
+            active_clients_store,
+            storage: Box::new(storage),
+            metrics,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct MessageStoreOverview {
+    stored_messages: u64,
+    stored_bytes: u64,
+    evicted_messages: u64,
+    evicted_bytes: u64,
+    rejected_messages: u64,
+    expired_messages: u64,
+}
+
+impl From<MessageStoreUsage> for MessageStoreOverview {
+    fn from(usage: MessageStoreUsage) -> Self {
+        MessageStoreOverview {
+            stored_messages: usage.stored_messages,
+            stored_bytes: usage.stored_bytes,
+            evicted_messages: usage.evicted_messages,
+            evicted_bytes: usage.evicted_bytes,
+            rejected_messages: usage.rejected_messages,
+            expired_messages: usage.expired_messages,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct OperatorOverviewResponse {
+    /// Number of the clients currently connected to the gateway.
+    active_sessions: usize,
+
+    /// Messages stored for the offline clients.
+    message_store: MessageStoreOverview,
+
+    /// Number of bytes the clients have sent through the gateway since the UTC midnight.
+    bandwidth_served_today: u64,
+
+    credential_redemptions: CredentialRedemptions,
+    errors: ErrorCounters,
+    rejected_frames: RejectedFrames,
+}
+
+/// Returns the aggregated state of the gateway for the dashboard tooling.
+/// Only available from the local machine.
+#[get("/operator/overview")]
+pub(crate) async fn operator_overview(
+    _local: LocalRequest,
+    state: &State<OverviewState>,
+) -> Json<OperatorOverviewResponse> {
+    Json(OperatorOverviewResponse {
+        active_sessions: state.active_clients_store.size(),
+        message_store: state.storage.message_store_usage().await.into(),
+        bandwidth_served_today: state.metrics.bandwidth_served_today(),
+        credential_redemptions: state.metrics.credential_redemptions(),
+        errors: state.metrics.errors(),
+        rejected_frames: state.metrics.rejected_frames(),
+    })
+}
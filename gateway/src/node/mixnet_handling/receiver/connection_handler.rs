@@ -4,21 +4,26 @@
 use crate::node::client_handling::active_clients::ActiveClientsStore;
 use crate::node::client_handling::websocket::message_receiver::MixMessageSender;
 use crate::node::mixnet_handling::receiver::packet_processing::PacketProcessor;
+use crate::node::statistics::operator::OperatorMetrics;
 use crate::node::storage::error::StorageError;
 use crate::node::storage::Storage;
 use futures::StreamExt;
 use log::*;
+use nym_crypto::asymmetric::identity;
 use nym_mixnet_client::forwarder::MixForwardingSender;
+use nym_mixnode_common::handshake::accept_handshake;
+use nym_mixnode_common::listener_guard::{ConnectionGuard, ConnectionVerdict, ListenerGuardConfig};
 use nym_mixnode_common::packet_processor::processor::ProcessedFinalHop;
 use nym_sphinx::forwarding::packet::MixPacket;
-use nym_sphinx::framing::codec::SphinxCodec;
+use nym_sphinx::framing::codec::SphinxCodecError;
 use nym_sphinx::framing::packet::FramedSphinxPacket;
 use nym_sphinx::DestinationAddressBytes;
 use nym_task::TaskClient;
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::time::SystemTime;
 use tokio::net::TcpStream;
-use tokio_util::codec::Framed;
+use tokio::time::Instant;
 
 pub(crate) struct ConnectionHandler<St: Storage> {
     packet_processor: PacketProcessor,
@@ -31,6 +36,9 @@ pub(crate) struct ConnectionHandler<St: Storage> {
     active_clients_store: ActiveClientsStore,
     storage: St,
     ack_sender: MixForwardingSender,
+    guard: ListenerGuardConfig,
+    identity: identity::PublicKey,
+    metrics: OperatorMetrics,
 }
 
 impl<St: Storage + Clone> Clone for ConnectionHandler<St> {
@@ -49,6 +57,9 @@ impl<St: Storage + Clone> Clone for ConnectionHandler<St> {
             active_clients_store: self.active_clients_store.clone(),
             storage: self.storage.clone(),
             ack_sender: self.ack_sender.clone(),
+            guard: self.guard,
+            identity: self.identity,
+            metrics: self.metrics.clone(),
         }
     }
 }
@@ -59,6 +70,9 @@ impl<St: Storage> ConnectionHandler<St> {
         storage: St,
         ack_sender: MixForwardingSender,
         active_clients_store: ActiveClientsStore,
+        guard: ListenerGuardConfig,
+        identity: identity::PublicKey,
+        metrics: OperatorMetrics,
     ) -> Self {
         ConnectionHandler {
             packet_processor,
@@ -66,6 +80,9 @@ impl<St: Storage> ConnectionHandler<St> {
             storage,
             active_clients_store,
             ack_sender,
+            guard,
+            identity,
+            metrics,
         }
     }
 
@@ -110,13 +127,16 @@ impl<St: Storage> ConnectionHandler<St> {
         &self,
         client_address: DestinationAddressBytes,
         message: Vec<u8>,
+        expires_at: Option<SystemTime>,
     ) -> Result<(), StorageError> {
         debug!(
             "Storing received message for {} on the disk...",
             client_address
         );
 
-        self.storage.store_message(client_address, message).await
+        self.storage
+            .store_message(client_address, message, expires_at)
+            .await
     }
 
     fn forward_ack(&self, forward_ack: Option<MixPacket>, client_address: DestinationAddressBytes) {
@@ -133,14 +153,29 @@ impl<St: Storage> ConnectionHandler<St> {
 
     async fn handle_processed_packet(&mut self, processed_final_hop: ProcessedFinalHop) {
         let client_address = processed_final_hop.destination;
+
+        #[cfg(feature = "packet-tracing")]
+        log::info!(
+            target: nym_sphinx::params::packet_tracing::PACKET_TRACING_LOG_TARGET,
+            "[{}] gateway received final hop packet for {client_address}",
+            processed_final_hop.trace_id
+        );
         let message = processed_final_hop.message;
         let forward_ack = processed_final_hop.forward_ack;
+        let expires_at = processed_final_hop.expires_at;
+
+        // the sender is no longer interested in the message, so don't bother delivering it.
+        // note that we also don't send the ack back so that the sender would learn of it
+        if matches!(expires_at, Some(expires_at) if expires_at <= SystemTime::now()) {
+            debug!("Dropping an expired message for {client_address}");
+            return;
+        }
 
         // we failed to push message directly to the client - it's probably offline.
         // we should store it on the disk instead.
         match self.try_push_message_to_client(client_address, message) {
             Err(unsent_plaintext) => match self
-                .store_processed_packet_payload(client_address, unsent_plaintext)
+                .store_processed_packet_payload(client_address, unsent_plaintext, expires_at)
                 .await
             {
                 Err(err) => error!("Failed to store client data - {err}"),
@@ -182,16 +217,45 @@ impl<St: Storage> ConnectionHandler<St> {
     ) {
         debug!("Starting connection handler for {:?}", remote);
         shutdown.mark_as_success();
-        let mut framed_conn = Framed::new(conn, SphinxCodec);
+        let mut framed_conn = match accept_handshake(conn, &self.identity, &self.guard).await {
+            Ok(framed_conn) => framed_conn,
+            Err(err) => {
+                warn!("Failed to complete the handshake with {remote}: {err}. Closing the socket");
+                return;
+            }
+        };
+        let mut connection_guard = ConnectionGuard::new(self.guard, Instant::now());
+        let mut guard_check = tokio::time::interval_at(
+            Instant::now() + connection_guard.check_interval(),
+            connection_guard.check_interval(),
+        );
         while !shutdown.is_shutdown() {
             tokio::select! {
                 biased;
                 _ = shutdown.recv() => {
                     log::trace!("ConnectionHandler: received shutdown");
                 }
+                _ = guard_check.tick() => {
+                    let has_partial_frame = !framed_conn.read_buffer().is_empty();
+                    match connection_guard.check(has_partial_frame, Instant::now()) {
+                        ConnectionVerdict::Keep => (),
+                        ConnectionVerdict::Idle => {
+                            debug!("{remote} hasn't sent any packets in {:?} - closing the connection", self.guard.read_timeout);
+                            self.metrics.record_closed_connection(false);
+                            break;
+                        }
+                        ConnectionVerdict::Stalled => {
+                            warn!("{remote} hasn't completed its partial frame in {:?} - closing the connection", self.guard.frame_timeout);
+                            self.metrics.record_closed_connection(true);
+                            break;
+                        }
+                    }
+                }
                 framed_sphinx_packet = framed_conn.next() => {
                     match framed_sphinx_packet {
                         Some(Ok(framed_sphinx_packet)) => {
+                            connection_guard.on_frame(Instant::now());
+
                             // TODO: benchmark spawning tokio task with full processing vs just processing it
                             // synchronously under higher load in single and multi-threaded situation.
 
@@ -201,6 +265,10 @@ impl<St: Storage> ConnectionHandler<St> {
                             self.handle_received_packet(framed_sphinx_packet).await;
                         }
                         Some(Err(err)) => {
+                            self.metrics.record_rejected_frame(matches!(
+                                err,
+                                SphinxCodecError::FrameTooLarge { .. }
+                            ));
                             error!(
                                 "The socket connection got corrupted with error: {err}. Closing the socket",
                             );
@@ -6,29 +6,52 @@ use crate::config::persistence::pathfinder::GatewayPathfinder;
 use crate::config::Config;
 use crate::error::GatewayError;
 use crate::node::client_handling::active_clients::ActiveClientsStore;
+use crate::node::client_handling::bandwidth_reconciler::BandwidthReconciler;
+use crate::node::client_handling::expired_messages_purger::ExpiredMessagesPurger;
 use crate::node::client_handling::websocket;
 use crate::node::client_handling::websocket::connection_handler::coconut::CoconutVerifier;
+use crate::node::federation::FederationClient;
+use crate::node::heartbeat::HeartbeatSender;
+use crate::node::http::build_information::build_information;
+use crate::node::http::overview::{operator_overview, OverviewState};
 use crate::node::mixnet_handling::receiver::connection_handler::ConnectionHandler;
+use crate::node::reload::{ConfigReloader, ReloadableConfig};
 use crate::node::statistics::collector::GatewayStatisticsCollector;
+use crate::node::statistics::operator::OperatorMetrics;
+use crate::node::storage::quota::MessageStoreQuota;
 use crate::node::storage::Storage;
+use crate::node::websocket_guard::WebSocketGuard;
 use log::*;
 use nym_bin_common::output_format::OutputFormat;
 use nym_crypto::asymmetric::{encryption, identity};
-use nym_mixnet_client::forwarder::{MixForwardingSender, PacketForwarder};
+use nym_mixnet_client::forwarder::{MixForwardingSender, PacketForwarder, UnreachableHops};
+use nym_mixnet_contract_common::{
+    construct_gateway_bonding_sign_payload, Gateway as ContractGateway,
+};
 use nym_network_defaults::NymNetworkDetails;
 use nym_statistics_common::collector::StatisticsSender;
 use nym_task::{TaskClient, TaskManager};
+use nym_types::gateway::{GatewayBondingInformation, GatewayListeners};
+use nym_types::helpers::{BondingRequest, BondingSignature};
 use nym_validator_client::Client;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use std::error::Error;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use tokio::sync::watch;
 
 pub(crate) mod client_handling;
+#[cfg(feature = "embedded-network-requester")]
+pub(crate) mod embedded_network_requester;
+pub(crate) mod federation;
+pub(crate) mod heartbeat;
+pub(crate) mod http;
 pub(crate) mod mixnet_handling;
+pub(crate) mod reload;
 pub(crate) mod statistics;
 pub(crate) mod storage;
+pub(crate) mod websocket_guard;
 
 /// Wire up and create Gateway instance
 pub(crate) async fn create_gateway(config: Config) -> Gateway<PersistentStorage> {
@@ -39,7 +62,13 @@ pub(crate) async fn create_gateway(config: Config) -> Gateway<PersistentStorage>
 async fn initialise_storage(config: &Config) -> PersistentStorage {
     let path = config.get_persistent_store_path();
     let retrieval_limit = config.get_message_retrieval_limit();
-    match PersistentStorage::init(path, retrieval_limit).await {
+    let quota = MessageStoreQuota {
+        max_total_bytes: config.get_message_store_quota(),
+        per_client_guaranteed_bytes: config.get_message_store_per_client_guarantee(),
+        warning_threshold: config.get_message_store_warning_threshold(),
+        eviction_policy: config.get_message_store_eviction_policy(),
+    };
+    match PersistentStorage::init(path, retrieval_limit, quota).await {
         Err(err) => panic!("failed to initialise gateway storage - {err}"),
         Ok(storage) => storage,
     }
@@ -106,6 +135,21 @@ where
         sphinx_keypair
     }
 
+    fn listeners(&self) -> GatewayListeners {
+        GatewayListeners {
+            clients: SocketAddr::new(
+                self.config.get_clients_listening_address(),
+                self.config.get_clients_port(),
+            )
+            .to_string(),
+            mix: SocketAddr::new(
+                self.config.get_mix_listening_address(),
+                self.config.get_mix_port(),
+            )
+            .to_string(),
+        }
+    }
+
     pub(crate) fn print_node_details(&self, output: OutputFormat) {
         let node_details = nym_types::gateway::GatewayNodeDetailsResponse {
             identity_key: self.identity_keypair.public_key().to_base58_string(),
@@ -115,6 +159,7 @@ where
             version: self.config.get_version().to_string(),
             mix_port: self.config.get_mix_port(),
             clients_port: self.config.get_clients_port(),
+            listeners: self.listeners(),
             data_store: self
                 .config
                 .get_persistent_store_path()
@@ -126,10 +171,68 @@ where
         println!("{}", output.format(&node_details));
     }
 
+    /// Prints the information required for bonding this node. If the details of the bonding
+    /// transaction are provided, it also includes the signature on the bonding message
+    /// the mixnet contract expects.
+    pub(crate) fn print_bonding_information(
+        &self,
+        location: String,
+        bonding_request: Option<BondingRequest>,
+        output: OutputFormat,
+    ) {
+        let gateway = ContractGateway {
+            host: self.config.get_announce_address(),
+            mix_port: self.config.get_mix_port(),
+            clients_port: self.config.get_clients_port(),
+            location,
+            sphinx_key: self.sphinx_keypair.public_key().to_base58_string(),
+            identity_key: self.identity_keypair.public_key().to_base58_string(),
+            version: self.config.get_version().to_string(),
+        };
+        let bonding_signature = bonding_request.map(|request| {
+            let payload = construct_gateway_bonding_sign_payload(
+                request.signing_nonce,
+                request.wallet_address.clone(),
+                request.proxy.clone(),
+                request.pledge.clone(),
+                gateway.clone(),
+            );
+            // the payload consists of our own well-formed types, so its serialization can't fail
+            let plaintext = payload
+                .to_plaintext()
+                .expect("failed to serialize the bonding payload");
+            BondingSignature {
+                request,
+                signature: self
+                    .identity_keypair
+                    .private_key()
+                    .sign(&plaintext)
+                    .to_base58_string(),
+            }
+        });
+
+        let bonding_information = GatewayBondingInformation {
+            gateway,
+            bonding_signature,
+        };
+
+        println!("{}", output.format(&bonding_information));
+    }
+
+    fn websocket_guard(&self, operator_metrics: OperatorMetrics) -> WebSocketGuard {
+        WebSocketGuard::new(
+            self.config.get_listener_guard_config(),
+            self.config.get_maximum_websocket_frame_size(),
+            self.config.get_maximum_websocket_message_size(),
+            operator_metrics,
+        )
+    }
+
     fn start_mix_socket_listener(
         &self,
         ack_sender: MixForwardingSender,
         active_clients_store: ActiveClientsStore,
+        operator_metrics: OperatorMetrics,
         shutdown: TaskClient,
     ) {
         info!("Starting mix socket listener...");
@@ -142,27 +245,37 @@ where
             self.storage.clone(),
             ack_sender,
             active_clients_store,
+            self.config.get_listener_guard_config(),
+            *self.identity_keypair.public_key(),
+            operator_metrics,
         );
 
         let listening_address = SocketAddr::new(
-            self.config.get_listening_address(),
+            self.config.get_mix_listening_address(),
             self.config.get_mix_port(),
         );
 
         mixnet_handling::Listener::new(listening_address, shutdown).start(connection_handler);
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn start_client_websocket_listener(
         &self,
         forwarding_channel: MixForwardingSender,
+        unreachable_hops: UnreachableHops,
         active_clients_store: ActiveClientsStore,
         shutdown: TaskClient,
         coconut_verifier: Arc<CoconutVerifier>,
+        federation_client: Option<FederationClient>,
+        settings: watch::Receiver<ReloadableConfig>,
+        operator_metrics: OperatorMetrics,
     ) {
         info!("Starting client [web]socket listener...");
 
+        let websocket_guard = self.websocket_guard(operator_metrics.clone());
+
         let listening_address = SocketAddr::new(
-            self.config.get_listening_address(),
+            self.config.get_clients_listening_address(),
             self.config.get_clients_port(),
         );
 
@@ -171,6 +284,11 @@ where
             Arc::clone(&self.identity_keypair),
             self.config.get_only_coconut_credentials(),
             coconut_verifier,
+            federation_client,
+            unreachable_hops,
+            settings,
+            websocket_guard,
+            operator_metrics,
         )
         .start(
             forwarding_channel,
@@ -180,7 +298,138 @@ where
         );
     }
 
-    fn start_packet_forwarder(&self, shutdown: TaskClient) -> MixForwardingSender {
+    fn start_http_api(
+        &self,
+        active_clients_store: ActiveClientsStore,
+        operator_metrics: OperatorMetrics,
+        mut shutdown: TaskClient,
+    ) {
+        info!(
+            "Starting HTTP API on http://{}:{}",
+            self.config.get_http_api_address(),
+            self.config.get_http_api_port()
+        );
+
+        let mut config = rocket::config::Config::release_default();
+        config.address = self.config.get_http_api_address();
+        config.port = self.config.get_http_api_port();
+
+        let overview_state =
+            OverviewState::new(active_clients_store, self.storage.clone(), operator_metrics);
+
+        tokio::spawn(async move {
+            let rocket = match rocket::build()
+                .configure(config)
+                .mount("/", routes![operator_overview, build_information])
+                .register("/", catchers![http::not_found])
+                .manage(overview_state)
+                .ignite()
+                .await
+            {
+                Ok(rocket) => rocket,
+                Err(err) => {
+                    // dropping the shutdown listener here brings the whole gateway down
+                    error!("Failed to start the HTTP API: {err}");
+                    return;
+                }
+            };
+
+            // rocket has its own shutdown handling, so just forward our signal to it
+            let rocket_shutdown = rocket.shutdown();
+            tokio::spawn(async move {
+                shutdown.recv().await;
+                rocket_shutdown.notify();
+            });
+
+            if let Err(err) = rocket.launch().await {
+                error!("The HTTP API has failed: {err}");
+            }
+        });
+    }
+
+    fn start_federation_listener(
+        &self,
+        active_clients_store: ActiveClientsStore,
+        operator_metrics: OperatorMetrics,
+        shutdown: TaskClient,
+    ) {
+        info!("Starting federation listener...");
+
+        let listening_address = SocketAddr::new(
+            self.config.get_listening_address(),
+            self.config.get_federation_port(),
+        );
+
+        federation::Listener::new(
+            listening_address,
+            Arc::clone(&self.identity_keypair),
+            self.config.get_federation_peers(),
+            self.storage.clone(),
+            active_clients_store,
+            self.websocket_guard(operator_metrics),
+        )
+        .start(shutdown);
+    }
+
+    fn start_config_reloader(&self, shutdown: TaskClient) -> ConfigReloader {
+        info!("Starting config reloader...");
+        let config_reloader = ConfigReloader::new(&self.config);
+        tokio::spawn(config_reloader.clone().run(shutdown));
+        config_reloader
+    }
+
+    fn start_bandwidth_reconciler(
+        &self,
+        coconut_verifier: Arc<CoconutVerifier>,
+        shutdown: TaskClient,
+    ) {
+        info!("Starting bandwidth reconciler...");
+
+        BandwidthReconciler::new(
+            self.storage.clone(),
+            coconut_verifier,
+            self.config.get_bandwidth_epoch_validity(),
+            self.config.get_bandwidth_reconciliation_interval(),
+        )
+        .start(shutdown);
+    }
+
+    fn start_expired_messages_purger(&self, shutdown: TaskClient) {
+        info!("Starting expired messages purger...");
+
+        ExpiredMessagesPurger::new(
+            self.storage.clone(),
+            self.config.get_expired_messages_purge_interval(),
+        )
+        .start(shutdown);
+    }
+
+    fn start_heartbeat_sender(
+        &self,
+        active_clients_store: ActiveClientsStore,
+        shutdown: TaskClient,
+    ) {
+        let heartbeat_interval = self.config.get_heartbeat_interval();
+        if heartbeat_interval.is_zero() {
+            info!("Heartbeats are disabled");
+            return;
+        }
+
+        info!("Starting the heartbeat sender...");
+        let mut heartbeat_sender = HeartbeatSender::new(
+            Arc::clone(&self.identity_keypair),
+            active_clients_store,
+            self.config.get_nym_api_endpoints(),
+            heartbeat_interval,
+            shutdown,
+        );
+        tokio::spawn(async move { heartbeat_sender.run().await });
+    }
+
+    fn start_packet_forwarder(
+        &self,
+        shutdown: TaskClient,
+    ) -> (MixForwardingSender, UnreachableHops) {
         info!("Starting mix packet forwarder...");
 
         let (mut packet_forwarder, packet_sender) = PacketForwarder::new(
@@ -188,12 +437,14 @@ where
             self.config.get_packet_forwarding_maximum_backoff(),
             self.config.get_initial_connection_timeout(),
             self.config.get_maximum_connection_buffer_size(),
+            self.config.get_maximum_forwarding_delay(),
             self.config.get_use_legacy_sphinx_framing(),
             shutdown,
         );
+        let unreachable_hops = packet_forwarder.unreachable_hops();
 
         tokio::spawn(async move { packet_forwarder.run().await });
-        packet_sender
+        (packet_sender, unreachable_hops)
     }
 
     async fn wait_for_interrupt(
@@ -274,33 +525,63 @@ where
         Ok(())
     }
 
+    #[cfg(feature = "embedded-network-requester")]
+    fn start_embedded_network_requester(
+        &self,
+        forwarding_channel: MixForwardingSender,
+        active_clients_store: ActiveClientsStore,
+    ) -> Result<(), GatewayError> {
+        embedded_network_requester::EmbeddedNetworkRequester::new(
+            self.config.get_network_requester_id(),
+            self.config.get_network_requester_open_proxy(),
+            self.identity_keypair.public_key(),
+            forwarding_channel,
+            active_clients_store,
+            self.storage.clone(),
+        )?
+        .start();
+        Ok(())
+    }
+
+    #[cfg(not(feature = "embedded-network-requester"))]
+    fn start_embedded_network_requester(
+        &self,
+        _forwarding_channel: MixForwardingSender,
+        _active_clients_store: ActiveClientsStore,
+    ) -> Result<(), GatewayError> {
+        Err(GatewayError::EmbeddedNetworkRequesterUnavailable)
+    }
+
     pub async fn run(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
         info!("Starting nym gateway!");
 
         self.ensure_no_duplicate_host_exists().await?;
 
         let shutdown = TaskManager::new(10);
+        let config_reloader = self.start_config_reloader(shutdown.subscribe());
 
         let coconut_verifier = {
             let nyxd_client = self.random_nyxd_client();
-            CoconutVerifier::new(nyxd_client)
+            Arc::new(CoconutVerifier::new(nyxd_client))
         };
 
-        let mix_forwarding_channel = self.start_packet_forwarder(shutdown.subscribe());
+        let (mix_forwarding_channel, unreachable_hops) =
+            self.start_packet_forwarder(shutdown.subscribe());
 
         let active_clients_store = ActiveClientsStore::new();
+        let operator_metrics = OperatorMetrics::new();
         self.start_mix_socket_listener(
             mix_forwarding_channel.clone(),
             active_clients_store.clone(),
+            operator_metrics.clone(),
             shutdown.subscribe(),
         );
 
         if self.config.get_enabled_statistics() {
-            let statistics_service_url = self.config.get_statistics_service_url();
             let stats_collector = GatewayStatisticsCollector::new(
                 self.identity_keypair.public_key().to_base58_string(),
                 active_clients_store.clone(),
-                statistics_service_url,
+                config_reloader.subscribe(),
             );
             let mut stats_sender = StatisticsSender::new(stats_collector);
             tokio::spawn(async move {
@@ -308,13 +589,51 @@ where
             });
         }
 
+        let federation_client = if self.config.get_federation_enabled() {
+            self.start_federation_listener(
+                active_clients_store.clone(),
+                operator_metrics.clone(),
+                shutdown.subscribe(),
+            );
+            Some(FederationClient::new(
+                Arc::clone(&self.identity_keypair),
+                self.config.get_federation_peers(),
+                self.config.get_initial_connection_timeout(),
+                active_clients_store.clone(),
+                shutdown.subscribe(),
+            ))
+        } else {
+            None
+        };
+
+        if self.config.get_network_requester_enabled() {
+            self.start_embedded_network_requester(
+                mix_forwarding_channel.clone(),
+                active_clients_store.clone(),
+            )?;
+        }
+
+        self.start_http_api(
+            active_clients_store.clone(),
+            operator_metrics.clone(),
+            shutdown.subscribe(),
+        );
+        self.start_heartbeat_sender(active_clients_store.clone(), shutdown.subscribe());
+
         self.start_client_websocket_listener(
             mix_forwarding_channel,
+            unreachable_hops,
             active_clients_store,
             shutdown.subscribe(),
-            Arc::new(coconut_verifier),
+            Arc::clone(&coconut_verifier),
+            federation_client,
+            config_reloader.subscribe(),
+            operator_metrics,
         );
 
+        self.start_bandwidth_reconciler(coconut_verifier, shutdown.subscribe());
+        self.start_expired_messages_purger(shutdown.subscribe());
+
         info!("Finished nym gateway startup procedure - it should now be able to receive mix and client traffic!");
 
         self.wait_for_interrupt(shutdown).await
@@ -4,7 +4,7 @@
 use async_trait::async_trait;
 use sqlx::types::chrono::{DateTime, Utc};
 use std::time::Duration;
-use url::Url;
+use tokio::sync::watch;
 
 use nym_statistics_common::{
     api::build_and_send_statistics_request, collector::StatisticsCollector, error::StatsError,
@@ -12,23 +12,24 @@ use nym_statistics_common::{
 };
 
 use crate::node::client_handling::active_clients::ActiveClientsStore;
+use crate::node::reload::ReloadableConfig;
 
 pub(crate) struct GatewayStatisticsCollector {
     gateway_id: String,
     active_clients_store: ActiveClientsStore,
-    statistics_service_url: Url,
+    settings: watch::Receiver<ReloadableConfig>,
 }
 
 impl GatewayStatisticsCollector {
     pub fn new(
         gateway_id: String,
         active_clients_store: ActiveClientsStore,
-        statistics_service_url: Url,
+        settings: watch::Receiver<ReloadableConfig>,
     ) -> Self {
         GatewayStatisticsCollector {
             gateway_id,
             active_clients_store,
-            statistics_service_url,
+            settings,
         }
     }
 }
@@ -53,8 +54,9 @@ impl StatisticsCollector for GatewayStatisticsCollector {
     }
 
     async fn send_stats_message(&self, stats_message: StatsMessage) -> Result<(), StatsError> {
-        build_and_send_statistics_request(stats_message, self.statistics_service_url.to_string())
-            .await
+        // the url might have been changed by a config reload
+        let statistics_service_url = self.settings.borrow().statistics_service_url.to_string();
+        build_and_send_statistics_request(stats_message, statistics_service_url).await
     }
 
     async fn reset_stats(&mut self) {}
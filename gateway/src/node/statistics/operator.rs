@@ -0,0 +1,205 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+fn current_day() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / SECONDS_PER_DAY
+}
+
+/// Counter that resets at the UTC midnight.
+#[derive(Debug, Default)]
+struct DailyCounter {
+    day: u64,
+    value: u64,
+}
+
+impl DailyCounter {
+    fn add(&mut self, day: u64, value: u64) {
+        if self.day != day {
+            self.day = day;
+            self.value = 0;
+        }
+        self.value += value;
+    }
+
+    fn get(&self, day: u64) -> u64 {
+        if self.day == day {
+            self.value
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct OperatorMetricsInner {
+    bandwidth_served_today: Mutex<DailyCounter>,
+    successful_redemptions: AtomicU64,
+    failed_redemptions: AtomicU64,
+    invalid_requests: AtomicU64,
+    connection_errors: AtomicU64,
+    oversized_frames: AtomicU64,
+    malformed_frames: AtomicU64,
+    idle_connections: AtomicU64,
+    stalled_connections: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub(crate) struct CredentialRedemptions {
+    pub(crate) successful: u64,
+    pub(crate) failed: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub(crate) struct ErrorCounters {
+    /// Requests of the clients that were malformed or otherwise couldn't have been handled.
+    pub(crate) invalid_requests: u64,
+
+    /// Failures of reading from or writing to the client websockets.
+    pub(crate) connection_errors: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub(crate) struct RejectedFrames {
+    /// Frames received from the mix, client or federation connections that exceeded
+    /// the maximum frame (or websocket message) size.
+    pub(crate) oversized: u64,
+
+    /// Frames received from the mix, client or federation connections that couldn't have been decoded.
+    pub(crate) malformed: u64,
+
+    /// Connections closed for not sending any frames within the read timeout.
+    pub(crate) idle_connections: u64,
+
+    /// Connections closed for not completing a partially sent frame (or the websocket handshake)
+    /// within the frame timeout.
+    pub(crate) stalled_connections: u64,
+}
+
+/// Counters of the client traffic handled by the gateway since its startup,
+/// exposed to the operator via the HTTP API.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OperatorMetrics {
+    inner: Arc<OperatorMetricsInner>,
+}
+
+impl OperatorMetrics {
+    pub(crate) fn new() -> Self {
+        Default::default()
+    }
+
+    pub(crate) fn record_bandwidth_served(&self, bytes: u64) {
+        self.inner
+            .bandwidth_served_today
+            .lock()
+            .expect("bandwidth counter lock got poisoned")
+            .add(current_day(), bytes)
+    }
+
+    pub(crate) fn record_credential_redemption(&self, successful: bool) {
+        let counter = if successful {
+            &self.inner.successful_redemptions
+        } else {
+            &self.inner.failed_redemptions
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_invalid_request(&self) {
+        self.inner.invalid_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_connection_error(&self) {
+        self.inner.connection_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_rejected_frame(&self, oversized: bool) {
+        let counter = if oversized {
+            &self.inner.oversized_frames
+        } else {
+            &self.inner.malformed_frames
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_closed_connection(&self, stalled: bool) {
+        let counter = if stalled {
+            &self.inner.stalled_connections
+        } else {
+            &self.inner.idle_connections
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of bytes the clients have sent through this gateway since the UTC midnight.
+    pub(crate) fn bandwidth_served_today(&self) -> u64 {
+        self.inner
+            .bandwidth_served_today
+            .lock()
+            .expect("bandwidth counter lock got poisoned")
+            .get(current_day())
+    }
+
+    pub(crate) fn credential_redemptions(&self) -> CredentialRedemptions {
+        CredentialRedemptions {
+            successful: self.inner.successful_redemptions.load(Ordering::Relaxed),
+            failed: self.inner.failed_redemptions.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn errors(&self) -> ErrorCounters {
+        ErrorCounters {
+            invalid_requests: self.inner.invalid_requests.load(Ordering::Relaxed),
+            connection_errors: self.inner.connection_errors.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn rejected_frames(&self) -> RejectedFrames {
+        RejectedFrames {
+            oversized: self.inner.oversized_frames.load(Ordering::Relaxed),
+            malformed: self.inner.malformed_frames.load(Ordering::Relaxed),
+            idle_connections: self.inner.idle_connections.load(Ordering::Relaxed),
+            stalled_connections: self.inner.stalled_connections.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_counter_resets_on_a_new_day() {
+        let mut counter = DailyCounter::default();
+        counter.add(1, 100);
+        counter.add(1, 50);
+        assert_eq!(counter.get(1), 150);
+        assert_eq!(counter.get(2), 0);
+
+        counter.add(2, 10);
+        assert_eq!(counter.get(2), 10);
+        assert_eq!(counter.get(1), 0);
+    }
+
+    #[test]
+    fn redemptions_are_counted_separately() {
+        let metrics = OperatorMetrics::new();
+        metrics.record_credential_redemption(true);
+        metrics.record_credential_redemption(true);
+        metrics.record_credential_redemption(false);
+
+        let redemptions = metrics.credential_redemptions();
+        assert_eq!(redemptions.successful, 2);
+        assert_eq!(redemptions.failed, 1);
+    }
+}
@@ -1,7 +1,37 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::node::storage::models::PersistedBandwidth;
+use crate::node::storage::models::{PersistedBandwidth, PersistedEpochBandwidth};
+
+/// Determines the new balances of the epoch buckets after consuming the specified amount of
+/// bandwidth, starting with the oldest epoch. Only the changed buckets are returned alongside
+/// their new balances.
+///
+/// Any amount exceeding the total of the buckets is assumed to have been taken from the
+/// bandwidth not tied to any epoch, such as the free testnet bandwidth.
+///
+/// # Arguments
+///
+/// * `buckets`: available epoch buckets ordered from the oldest epoch.
+/// * `amount`: amount of bandwidth consumed.
+pub(crate) fn drain_epoch_buckets(
+    buckets: &[PersistedEpochBandwidth],
+    mut amount: i64,
+) -> Vec<(i64, i64)> {
+    let mut drained = Vec::new();
+    for bucket in buckets {
+        if amount <= 0 {
+            break;
+        }
+        let taken = amount.min(bucket.remaining);
+        if taken <= 0 {
+            continue;
+        }
+        amount -= taken;
+        drained.push((bucket.epoch_id, bucket.remaining - taken));
+    }
+    drained
+}
 
 #[derive(Clone)]
 pub(crate) struct BandwidthManager {
@@ -103,4 +133,187 @@ impl BandwidthManager {
         .await?;
         Ok(())
     }
+
+    /// Increases bandwidth of the particular client bought with a credential issued in the
+    /// specified epoch. The total available bandwidth is increased accordingly.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_address_bs58`: base58-encoded address of the client.
+    /// * `epoch_id`: id of the epoch the credential was issued in.
+    /// * `amount`: amount of available bandwidth to be added to the client.
+    pub(crate) async fn increase_epoch_bandwidth(
+        &self,
+        client_address_bs58: &str,
+        epoch_id: i64,
+        amount: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+                INSERT INTO epoch_bandwidth(client_address_bs58, epoch_id, remaining) VALUES (?, ?, ?)
+                ON CONFLICT(client_address_bs58, epoch_id) DO UPDATE SET remaining = remaining + excluded.remaining
+            "#,
+            client_address_bs58,
+            epoch_id,
+            amount
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        self.increase_available_bandwidth(client_address_bs58, amount)
+            .await
+    }
+
+    /// Retrieves all non-empty epoch buckets of the particular client, ordered from the oldest epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_address_bs58`: base58-encoded address of the client.
+    pub(crate) async fn get_epoch_bandwidth(
+        &self,
+        client_address_bs58: &str,
+    ) -> Result<Vec<PersistedEpochBandwidth>, sqlx::Error> {
+        sqlx::query_as!(
+            PersistedEpochBandwidth,
+            r#"
+                SELECT * FROM epoch_bandwidth
+                WHERE client_address_bs58 = ? AND remaining > 0
+                ORDER BY epoch_id ASC
+            "#,
+            client_address_bs58
+        )
+        .fetch_all(&self.connection_pool)
+        .await
+    }
+
+    /// Consumes the specified amount from the epoch buckets of the particular client,
+    /// starting with the oldest epoch. Note that the total available bandwidth is not affected.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_address_bs58`: base58-encoded address of the client.
+    /// * `amount`: amount of bandwidth consumed.
+    pub(crate) async fn drain_epoch_bandwidth(
+        &self,
+        client_address_bs58: &str,
+        amount: i64,
+    ) -> Result<(), sqlx::Error> {
+        let buckets = self.get_epoch_bandwidth(client_address_bs58).await?;
+        for (epoch_id, remaining) in drain_epoch_buckets(&buckets, amount) {
+            sqlx::query!(
+                "UPDATE epoch_bandwidth SET remaining = ? WHERE client_address_bs58 = ? AND epoch_id = ?",
+                remaining,
+                client_address_bs58,
+                epoch_id
+            )
+            .execute(&self.connection_pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Removes all epoch buckets from before the specified epoch and decreases the total
+    /// available bandwidth of the affected clients by their unspent balances.
+    ///
+    /// Returns the total amount of bandwidth that has expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `first_valid_epoch`: id of the oldest epoch whose bandwidth is still valid.
+    pub(crate) async fn expire_epoch_bandwidth(
+        &self,
+        first_valid_epoch: i64,
+    ) -> Result<i64, sqlx::Error> {
+        let expired = sqlx::query!(
+            r#"
+                SELECT client_address_bs58 as "client_address_bs58!", SUM(remaining) as "expired!: i64"
+                FROM epoch_bandwidth
+                WHERE epoch_id < ? AND remaining > 0
+                GROUP BY client_address_bs58
+            "#,
+            first_valid_epoch
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        let mut total_expired = 0;
+        for client in expired {
+            sqlx::query!(
+                r#"
+                    UPDATE available_bandwidth
+                    SET available = MAX(available - ?, 0)
+                    WHERE client_address_bs58 = ?
+                "#,
+                client.expired,
+                client.client_address_bs58
+            )
+            .execute(&self.connection_pool)
+            .await?;
+            total_expired += client.expired;
+        }
+
+        sqlx::query!(
+            "DELETE FROM epoch_bandwidth WHERE epoch_id < ? OR remaining <= 0",
+            first_valid_epoch
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(total_expired)
+    }
+
+    /// Makes sure the epoch buckets of every client do not exceed its total available bandwidth,
+    /// which might happen if bandwidth got consumed whilst the buckets were being updated.
+    /// The excess is removed from the oldest buckets.
+    ///
+    /// Returns the number of clients whose buckets had to be corrected.
+    pub(crate) async fn trim_epoch_bandwidth(&self) -> Result<usize, sqlx::Error> {
+        let exceeding = sqlx::query!(
+            r#"
+                SELECT e.client_address_bs58 as "client_address_bs58!", SUM(e.remaining) - a.available as "excess!: i64"
+                FROM epoch_bandwidth e
+                JOIN available_bandwidth a ON a.client_address_bs58 = e.client_address_bs58
+                GROUP BY e.client_address_bs58
+                HAVING SUM(e.remaining) > a.available
+            "#
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        for client in &exceeding {
+            self.drain_epoch_bandwidth(&client.client_address_bs58, client.excess)
+                .await?;
+        }
+        Ok(exceeding.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(epoch_id: i64, remaining: i64) -> PersistedEpochBandwidth {
+        PersistedEpochBandwidth {
+            client_address_bs58: "client".to_string(),
+            epoch_id,
+            remaining,
+        }
+    }
+
+    #[test]
+    fn epoch_buckets_are_drained_starting_with_the_oldest() {
+        let buckets = vec![bucket(1, 100), bucket(2, 50), bucket(4, 200)];
+
+        assert_eq!(drain_epoch_buckets(&buckets, 0), vec![]);
+        assert_eq!(drain_epoch_buckets(&buckets, 30), vec![(1, 70)]);
+        assert_eq!(drain_epoch_buckets(&buckets, 100), vec![(1, 0)]);
+        assert_eq!(
+            drain_epoch_buckets(&buckets, 170),
+            vec![(1, 0), (2, 0), (4, 180)]
+        );
+        // anything above the buckets' total comes from the bandwidth not tied to any epoch
+        assert_eq!(
+            drain_epoch_buckets(&buckets, 1000),
+            vec![(1, 0), (2, 0), (4, 0)]
+        );
+    }
 }
@@ -10,4 +10,7 @@ pub(crate) enum StorageError {
 
     #[error("Failed to perform database migration - {0}")]
     MigrationError(#[from] sqlx::migrate::MigrateError),
+
+    #[error("The message store quota has been exceeded and the message for {client_address_bs58} could not be stored")]
+    MessageStoreQuotaExceeded { client_address_bs58: String },
 }
@@ -1,7 +1,16 @@
 // Copyright 2020 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::node::storage::error::StorageError;
 use crate::node::storage::models::StoredMessage;
+use crate::node::storage::quota::{
+    ClientInbox, MessageStoreQuota, MessageStoreState, MessageStoreUsage,
+};
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex as AsyncMutex;
 
 #[derive(Clone)]
 pub(crate) struct InboxManager {
@@ -10,6 +19,13 @@ pub(crate) struct InboxManager {
     /// It is used to prevent out of memory errors in the case of client receiving a lot of data while
     /// offline and then loading it all at once when he comes back online.
     retrieval_limit: i64,
+
+    /// Limits imposed on the total size of the stored messages.
+    quota: MessageStoreQuota,
+
+    /// Current usage of the store alongside the per client totals. The lock is never held
+    /// across any database operation, the space for new messages is reserved up front instead.
+    state: Arc<Mutex<MessageStoreState>>,
 }
 
 impl InboxManager {
@@ -18,35 +34,271 @@ impl InboxManager {
     /// # Arguments
     ///
     /// * `connection_pool`: database connection pool to use.
-    pub(crate) fn new(connection_pool: sqlx::SqlitePool, retrieval_limit: i64) -> Self {
-        InboxManager {
+    /// * `retrieval_limit`: maximum number of messages that can be obtained per operation.
+    /// * `quota`: limits imposed on the total size of the stored messages.
+    pub(crate) async fn new(
+        connection_pool: sqlx::SqlitePool,
+        retrieval_limit: i64,
+        quota: MessageStoreQuota,
+    ) -> Result<Self, sqlx::Error> {
+        let stored = sqlx::query!(
+            r#"
+                SELECT m.client_address_bs58 as "client_address_bs58!",
+                    COUNT(*) as "messages!: i64",
+                    SUM(m.content_size) as "bytes!: i64",
+                    MIN(m.id) as "oldest_id!: i64",
+                    COALESCE(MAX(a.last_active), 0) as "last_active!: i64"
+                FROM message_store m
+                LEFT JOIN inbox_activity a ON m.client_address_bs58 = a.client_address_bs58
+                GROUP BY m.client_address_bs58;
+            "#
+        )
+        .fetch_all(&connection_pool)
+        .await?;
+
+        let mut messages = 0;
+        let mut bytes = 0;
+        let mut inboxes = HashMap::with_capacity(stored.len());
+        for inbox in stored {
+            messages += inbox.messages as u64;
+            bytes += inbox.bytes as u64;
+
+            let mut client_inbox = ClientInbox::new(inbox.last_active);
+            client_inbox.bytes = inbox.bytes as u64;
+            client_inbox.oldest_id = Some(inbox.oldest_id);
+            inboxes.insert(inbox.client_address_bs58, client_inbox);
+        }
+
+        let mut usage = MessageStoreUsage::new(messages, bytes);
+        usage.check_warning_level(&quota);
+
+        Ok(InboxManager {
             connection_pool,
             retrieval_limit,
-        }
+            quota,
+            state: Arc::new(Mutex::new(MessageStoreState::new(usage, inboxes))),
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, MessageStoreState> {
+        self.state
+            .lock()
+            .expect("message store state lock got poisoned")
+    }
+
+    /// Returns the current usage of the message store alongside the eviction metrics.
+    pub(crate) fn usage(&self) -> MessageStoreUsage {
+        self.state().usage
     }
 
     /// Inserts new message to the storage for an offline client for future retrieval.
+    /// If the message doesn't fit within the quota, previously stored messages are evicted
+    /// according to the configured policy. Messages of clients whose inboxes are below
+    /// the guaranteed size are never evicted.
     ///
     /// # Arguments
     ///
     /// * `client_address_bs58`: base58-encoded address of the client
     /// * `content`: raw content of the message to store.
+    /// * `expires_at`: optional time after which the message should no longer be delivered.
     pub(crate) async fn insert_message(
         &self,
         client_address_bs58: &str,
         content: Vec<u8>,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), StorageError> {
+        let insert_lock = self.inbox_insert_lock(client_address_bs58).await?;
+        let res = {
+            let _guard = insert_lock.lock().await;
+            self.insert_within_quota(client_address_bs58, content, expires_at)
+                .await
+        };
+        drop(insert_lock);
+
+        // in case nothing got stored after all
+        self.state().prune_inbox(client_address_bs58);
+        res
+    }
+
+    async fn inbox_insert_lock(
+        &self,
+        client_address_bs58: &str,
+    ) -> Result<Arc<AsyncMutex<()>>, sqlx::Error> {
+        if let Some(inbox) = self.state().inboxes.get(client_address_bs58) {
+            return Ok(Arc::clone(&inbox.insert_lock));
+        }
+
+        let last_active = sqlx::query!(
+            "SELECT last_active FROM inbox_activity WHERE client_address_bs58 = ?",
+            client_address_bs58
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?
+        .map(|row| row.last_active)
+        .unwrap_or_default();
+
+        let mut state = self.state();
+        let inbox = state
+            .inboxes
+            .entry(client_address_bs58.to_owned())
+            .or_insert_with(|| ClientInbox::new(last_active));
+        Ok(Arc::clone(&inbox.insert_lock))
+    }
+
+    async fn insert_within_quota(
+        &self,
+        client_address_bs58: &str,
+        content: Vec<u8>,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), StorageError> {
+        let size = content.len() as u64;
+
+        loop {
+            let candidate = {
+                let mut state = self.state();
+                if state.usage.fits(&self.quota, size) {
+                    // reserve the space before the message is actually stored
+                    // so that no concurrent insert could take it
+                    state.usage.record_stored(size);
+                    break;
+                }
+
+                let candidate = if size > self.quota.max_total_bytes {
+                    None
+                } else {
+                    state.eviction_candidate(&self.quota).map(ToOwned::to_owned)
+                };
+                if candidate.is_none() {
+                    state.usage.record_rejected();
+                }
+                candidate
+            };
+
+            match candidate {
+                Some(candidate) => self.evict_oldest_message(&candidate).await?,
+                None => {
+                    warn!(
+                        "The message store quota has been hit and there's nothing left to evict - rejecting message for {client_address_bs58}"
+                    );
+                    return Err(StorageError::MessageStoreQuotaExceeded {
+                        client_address_bs58: client_address_bs58.to_owned(),
+                    });
+                }
+            }
+        }
+
+        let content_size = size as i64;
+        let expires_at = expires_at.map(unix_timestamp);
+        let res = sqlx::query!(
+            "INSERT INTO message_store(client_address_bs58, content, content_size, expires_at) VALUES (?, ?, ?, ?)",
+            client_address_bs58,
+            content,
+            content_size,
+            expires_at,
+        )
+        .execute(&self.connection_pool)
+        .await;
+
+        let mut state = self.state();
+        match res {
+            Ok(res) => {
+                state.add_to_inbox(client_address_bs58, res.last_insert_rowid(), size);
+                state.usage.check_warning_level(&self.quota);
+                Ok(())
+            }
+            Err(err) => {
+                // release the reserved space
+                state.usage.record_removed(size);
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Evicts the oldest message of the client. Its inbox is looked up using the
+    /// `(client_address_bs58, id)` index, so the cost doesn't depend on the size of the store.
+    async fn evict_oldest_message(&self, client_address_bs58: &str) -> Result<(), sqlx::Error> {
+        // get the one after as well, as it's going to become the oldest message of the client
+        let oldest = sqlx::query!(
+            r#"
+                SELECT id as "id!", content_size FROM message_store
+                WHERE client_address_bs58 = ?
+                ORDER BY id ASC
+                LIMIT 2;
+            "#,
+            client_address_bs58
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        let Some(evicted) = oldest.first() else {
+            // the messages have been removed in the meantime, so the inbox is empty
+            let mut state = self.state();
+            if let Some(inbox) = state.inboxes.get_mut(client_address_bs58) {
+                inbox.bytes = 0;
+                inbox.oldest_id = None;
+            }
+            state.prune_inbox(client_address_bs58);
+            return Ok(());
+        };
+
+        if !self.delete_message(evicted.id).await? {
+            // somebody else has removed it in the meantime and has also accounted for it
+            return Ok(());
+        }
+
+        let size = evicted.content_size as u64;
+        debug!(
+            "Evicted message {} ({size} bytes) of {client_address_bs58} to free up space in the message store",
+            evicted.id
+        );
+
+        let mut state = self.state();
+        state.usage.record_evicted(size);
+        if state.remove_from_inbox(client_address_bs58, size) {
+            state.update_oldest(client_address_bs58, oldest.get(1).map(|next| next.id));
+        }
+        Ok(())
+    }
+
+    async fn oldest_message_id(
+        &self,
+        client_address_bs58: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let oldest = sqlx::query!(
+            r#"SELECT MIN(id) as "id: i64" FROM message_store WHERE client_address_bs58 = ?"#,
+            client_address_bs58
+        )
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(oldest.id)
+    }
+
+    /// Marks the client as active, i.e. makes its messages the least likely to get evicted
+    /// under the `LeastRecentlyActive` policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_address_bs58`: base58-encoded address of the client
+    pub(crate) async fn mark_client_active(
+        &self,
+        client_address_bs58: &str,
     ) -> Result<(), sqlx::Error> {
+        let now = unix_timestamp(SystemTime::now());
+
         sqlx::query!(
-            "INSERT INTO message_store(client_address_bs58, content) VALUES (?, ?)",
+            "INSERT OR REPLACE INTO inbox_activity(client_address_bs58, last_active) VALUES (?, ?)",
             client_address_bs58,
-            content,
+            now,
         )
         .execute(&self.connection_pool)
         .await?;
+
+        self.state().mark_active(client_address_bs58, now);
         Ok(())
     }
 
     /// Retrieves messages stored for the particular client specified by the provided address.
+    /// Messages that have already expired are never returned.
     ///
     /// It also respects the specified retrieval limit. If there are more messages stored than allowed
     /// by the limit, it returns id of the last message retrieved to indicate start of the next query.
@@ -66,17 +318,19 @@ impl InboxManager {
         // get 1 additional message to check whether there will be more to grab
         // next time
         let limit = self.retrieval_limit + 1;
+        let now = unix_timestamp(SystemTime::now());
         let mut res = if let Some(start_after) = start_after {
             sqlx::query_as!(
                 StoredMessage,
                 r#"
-                    SELECT * FROM message_store 
-                    WHERE client_address_bs58 = ? AND id > ?
+                    SELECT id, client_address_bs58, content FROM message_store
+                    WHERE client_address_bs58 = ? AND id > ? AND (expires_at IS NULL OR expires_at > ?)
                     ORDER BY id ASC
                     LIMIT ?;
                 "#,
                 client_address_bs58,
                 start_after,
+                now,
                 limit
             )
             .fetch_all(&self.connection_pool)
@@ -85,12 +339,13 @@ impl InboxManager {
             sqlx::query_as!(
                 StoredMessage,
                 r#"
-                    SELECT * FROM message_store 
-                    WHERE client_address_bs58 = ?
+                    SELECT id, client_address_bs58, content FROM message_store
+                    WHERE client_address_bs58 = ? AND (expires_at IS NULL OR expires_at > ?)
                     ORDER BY id ASC
                     LIMIT ?;
                 "#,
                 client_address_bs58,
+                now,
                 limit
             )
             .fetch_all(&self.connection_pool)
@@ -114,9 +369,104 @@ impl InboxManager {
     ///
     /// * `id`: id of the message to remove
     pub(crate) async fn remove_message(&self, id: i64) -> Result<(), sqlx::Error> {
-        sqlx::query!("DELETE FROM message_store WHERE id = ?", id)
-            .execute(&self.connection_pool)
-            .await?;
+        let Some(message) = sqlx::query!(
+            "SELECT client_address_bs58, content_size FROM message_store WHERE id = ?",
+            id
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?
+        else {
+            return Ok(());
+        };
+
+        if !self.delete_message(id).await? {
+            return Ok(());
+        }
+
+        let client_address_bs58 = message.client_address_bs58;
+        let size = message.content_size as u64;
+        let refresh_oldest = {
+            let mut state = self.state();
+            state.usage.record_removed(size);
+            state.usage.check_warning_level(&self.quota);
+            let was_oldest = state.is_oldest(&client_address_bs58, id);
+            state.remove_from_inbox(&client_address_bs58, size) && was_oldest
+        };
+
+        if refresh_oldest {
+            let oldest = self.oldest_message_id(&client_address_bs58).await?;
+            self.state().update_oldest(&client_address_bs58, oldest);
+        }
         Ok(())
     }
+
+    /// Removes all messages whose expiry has already passed.
+    /// Returns the number of removed messages.
+    pub(crate) async fn remove_expired_messages(&self) -> Result<u64, sqlx::Error> {
+        let now = unix_timestamp(SystemTime::now());
+
+        let mut tx = self.connection_pool.begin().await?;
+        let expired = sqlx::query!(
+            r#"
+                SELECT client_address_bs58 as "client_address_bs58!", COUNT(*) as "messages!: i64", SUM(content_size) as "bytes!: i64"
+                FROM message_store
+                WHERE expires_at IS NOT NULL AND expires_at <= ?
+                GROUP BY client_address_bs58;
+            "#,
+            now
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        sqlx::query!(
+            "DELETE FROM message_store WHERE expires_at IS NOT NULL AND expires_at <= ?",
+            now
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+
+        let mut expired_messages = 0;
+        let mut affected_inboxes = Vec::new();
+        {
+            let mut state = self.state();
+            for inbox in expired {
+                expired_messages += inbox.messages as u64;
+                state
+                    .usage
+                    .record_expired(inbox.messages as u64, inbox.bytes as u64);
+                if state.remove_from_inbox(&inbox.client_address_bs58, inbox.bytes as u64) {
+                    affected_inboxes.push(inbox.client_address_bs58);
+                }
+            }
+            state.usage.check_warning_level(&self.quota);
+        }
+
+        // we don't know which messages got removed, so refresh the oldest ones of all affected inboxes
+        for client_address_bs58 in affected_inboxes {
+            let oldest = self.oldest_message_id(&client_address_bs58).await?;
+            self.state().update_oldest(&client_address_bs58, oldest);
+        }
+
+        Ok(expired_messages)
+    }
+
+    /// Deletes message with the specified id. Returns whether it was still there.
+    async fn delete_message(&self, id: i64) -> Result<bool, sqlx::Error> {
+        let deleted = sqlx::query!("DELETE FROM message_store WHERE id = ?", id)
+            .execute(&self.connection_pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+}
+
+fn unix_timestamp(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
 }
@@ -4,19 +4,22 @@
 use crate::node::storage::bandwidth::BandwidthManager;
 use crate::node::storage::error::StorageError;
 use crate::node::storage::inboxes::InboxManager;
-use crate::node::storage::models::{PersistedSharedKeys, StoredMessage};
+use crate::node::storage::models::{PersistedEpochBandwidth, PersistedSharedKeys, StoredMessage};
+use crate::node::storage::quota::{MessageStoreQuota, MessageStoreUsage};
 use crate::node::storage::shared_keys::SharedKeysManager;
 use async_trait::async_trait;
-use log::{debug, error};
+use log::{debug, error, info};
 use nym_gateway_requests::registration::handshake::SharedKeys;
 use nym_sphinx::DestinationAddressBytes;
 use sqlx::ConnectOptions;
 use std::path::Path;
+use std::time::SystemTime;
 
 mod bandwidth;
 pub(crate) mod error;
 mod inboxes;
 mod models;
+pub(crate) mod quota;
 mod shared_keys;
 
 #[async_trait]
@@ -63,10 +66,12 @@ pub(crate) trait Storage: Send + Sync {
     ///
     /// * `client_address`: address of the client
     /// * `message`: raw message to store.
+    /// * `expires_at`: optional time after which the message should no longer be delivered.
     async fn store_message(
         &self,
         client_address: DestinationAddressBytes,
         message: Vec<u8>,
+        expires_at: Option<SystemTime>,
     ) -> Result<(), StorageError>;
 
     /// Retrieves messages stored for the particular client specified by the provided address.
@@ -91,6 +96,13 @@ pub(crate) trait Storage: Send + Sync {
     /// * `ids`: ids of the messages to remove
     async fn remove_messages(&self, ids: Vec<i64>) -> Result<(), StorageError>;
 
+    /// Removes all stored messages whose expiry has already passed.
+    /// Returns the number of removed messages.
+    async fn remove_expired_messages(&self) -> Result<u64, StorageError>;
+
+    /// Returns the current usage of the message store alongside the eviction metrics.
+    async fn message_store_usage(&self) -> MessageStoreUsage;
+
     /// Creates a new bandwidth entry for the particular client.
     ///
     /// # Arguments
@@ -134,6 +146,44 @@ pub(crate) trait Storage: Send + Sync {
         client_address: DestinationAddressBytes,
         amount: i64,
     ) -> Result<(), StorageError>;
+
+    /// Increases available bandwidth of the particular client by the amount bought with
+    /// a credential issued in the specified epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_address`: address of the client
+    /// * `epoch_id`: id of the epoch the credential was issued in.
+    /// * `amount`: amount of available bandwidth to be added to the client.
+    async fn increase_epoch_bandwidth(
+        &self,
+        client_address: DestinationAddressBytes,
+        epoch_id: u64,
+        amount: i64,
+    ) -> Result<(), StorageError>;
+
+    /// Retrieves the unspent bandwidth of the particular client bought in each epoch,
+    /// ordered from the oldest epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_address`: address of the client
+    async fn get_epoch_bandwidth(
+        &self,
+        client_address: DestinationAddressBytes,
+    ) -> Result<Vec<PersistedEpochBandwidth>, StorageError>;
+
+    /// Expires all unspent bandwidth bought in epochs preceding the specified one.
+    /// Returns the total amount of bandwidth that has expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `first_valid_epoch`: id of the oldest epoch whose bandwidth is still valid.
+    async fn expire_epoch_bandwidth(&self, first_valid_epoch: u64) -> Result<i64, StorageError>;
+
+    /// Reconciles the per-epoch bandwidth with the total available bandwidth of all clients.
+    /// Returns the number of clients whose per-epoch bandwidth had to be corrected.
+    async fn reconcile_epoch_bandwidth(&self) -> Result<usize, StorageError>;
 }
 
 // note that clone here is fine as upon cloning the same underlying pool will be used
@@ -151,9 +201,11 @@ impl PersistentStorage {
     ///
     /// * `database_path`: path to the database.
     /// * `message_retrieval_limit`: maximum number of stored client messages that can be retrieved at once.
+    /// * `message_store_quota`: limits imposed on the total size of stored client messages.
     pub async fn init<P: AsRef<Path> + Send>(
         database_path: P,
         message_retrieval_limit: i64,
+        message_store_quota: MessageStoreQuota,
     ) -> Result<Self, StorageError> {
         debug!(
             "Attempting to connect to database {:?}",
@@ -183,10 +235,23 @@ impl PersistentStorage {
             return Err(err.into());
         }
 
+        let inbox_manager = InboxManager::new(
+            connection_pool.clone(),
+            message_retrieval_limit,
+            message_store_quota,
+        )
+        .await?;
+
+        let usage = inbox_manager.usage();
+        info!(
+            "The message store currently contains {} messages ({} bytes)",
+            usage.stored_messages, usage.stored_bytes
+        );
+
         // the cloning here are cheap as connection pool is stored behind an Arc
         Ok(PersistentStorage {
             shared_key_manager: SharedKeysManager::new(connection_pool.clone()),
-            inbox_manager: InboxManager::new(connection_pool.clone(), message_retrieval_limit),
+            inbox_manager,
             bandwidth_manager: BandwidthManager::new(connection_pool),
         })
     }
@@ -235,11 +300,11 @@ impl Storage for PersistentStorage {
         &self,
         client_address: DestinationAddressBytes,
         message: Vec<u8>,
+        expires_at: Option<SystemTime>,
     ) -> Result<(), StorageError> {
         self.inbox_manager
-            .insert_message(&client_address.as_base58_string(), message)
-            .await?;
-        Ok(())
+            .insert_message(&client_address.as_base58_string(), message, expires_at)
+            .await
     }
 
     async fn retrieve_messages(
@@ -247,9 +312,18 @@ impl Storage for PersistentStorage {
         client_address: DestinationAddressBytes,
         start_after: Option<i64>,
     ) -> Result<(Vec<StoredMessage>, Option<i64>), StorageError> {
+        let client_address_bs58 = client_address.as_base58_string();
+
+        // beginning of the retrieval means the client has just come back online
+        if start_after.is_none() {
+            self.inbox_manager
+                .mark_client_active(&client_address_bs58)
+                .await?;
+        }
+
         let messages = self
             .inbox_manager
-            .get_messages(&client_address.as_base58_string(), start_after)
+            .get_messages(&client_address_bs58, start_after)
             .await?;
         Ok(messages)
     }
@@ -261,6 +335,14 @@ impl Storage for PersistentStorage {
         Ok(())
     }
 
+    async fn remove_expired_messages(&self) -> Result<u64, StorageError> {
+        Ok(self.inbox_manager.remove_expired_messages().await?)
+    }
+
+    async fn message_store_usage(&self) -> MessageStoreUsage {
+        self.inbox_manager.usage()
+    }
+
     async fn create_bandwidth_entry(
         &self,
         client_address: DestinationAddressBytes,
@@ -299,11 +381,51 @@ impl Storage for PersistentStorage {
         client_address: DestinationAddressBytes,
         amount: i64,
     ) -> Result<(), StorageError> {
+        let client_address_bs58 = client_address.as_base58_string();
+        self.bandwidth_manager
+            .decrease_available_bandwidth(&client_address_bs58, amount)
+            .await?;
         self.bandwidth_manager
-            .decrease_available_bandwidth(&client_address.as_base58_string(), amount)
+            .drain_epoch_bandwidth(&client_address_bs58, amount)
             .await?;
         Ok(())
     }
+
+    async fn increase_epoch_bandwidth(
+        &self,
+        client_address: DestinationAddressBytes,
+        epoch_id: u64,
+        amount: i64,
+    ) -> Result<(), StorageError> {
+        self.bandwidth_manager
+            .increase_epoch_bandwidth(&client_address.as_base58_string(), epoch_id as i64, amount)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_epoch_bandwidth(
+        &self,
+        client_address: DestinationAddressBytes,
+    ) -> Result<Vec<PersistedEpochBandwidth>, StorageError> {
+        let buckets = self
+            .bandwidth_manager
+            .get_epoch_bandwidth(&client_address.as_base58_string())
+            .await?;
+        Ok(buckets)
+    }
+
+    async fn expire_epoch_bandwidth(&self, first_valid_epoch: u64) -> Result<i64, StorageError> {
+        let expired = self
+            .bandwidth_manager
+            .expire_epoch_bandwidth(first_valid_epoch as i64)
+            .await?;
+        Ok(expired)
+    }
+
+    async fn reconcile_epoch_bandwidth(&self) -> Result<usize, StorageError> {
+        let corrected = self.bandwidth_manager.trim_epoch_bandwidth().await?;
+        Ok(corrected)
+    }
 }
 
 /// In-memory implementation of `Storage`. The intention is primarily in testing environments.
@@ -348,6 +470,7 @@ impl Storage for InMemStorage {
         &self,
         _client_address: DestinationAddressBytes,
         _message: Vec<u8>,
+        _expires_at: Option<SystemTime>,
     ) -> Result<(), StorageError> {
         todo!()
     }
@@ -364,6 +487,14 @@ impl Storage for InMemStorage {
         todo!()
     }
 
+    async fn remove_expired_messages(&self) -> Result<u64, StorageError> {
+        todo!()
+    }
+
+    async fn message_store_usage(&self) -> MessageStoreUsage {
+        todo!()
+    }
+
     async fn create_bandwidth_entry(
         &self,
         _client_address: DestinationAddressBytes,
@@ -393,4 +524,28 @@ impl Storage for InMemStorage {
     ) -> Result<(), StorageError> {
         todo!()
     }
+
+    async fn increase_epoch_bandwidth(
+        &self,
+        _client_address: DestinationAddressBytes,
+        _epoch_id: u64,
+        _amount: i64,
+    ) -> Result<(), StorageError> {
+        todo!()
+    }
+
+    async fn get_epoch_bandwidth(
+        &self,
+        _client_address: DestinationAddressBytes,
+    ) -> Result<Vec<PersistedEpochBandwidth>, StorageError> {
+        todo!()
+    }
+
+    async fn expire_epoch_bandwidth(&self, _first_valid_epoch: u64) -> Result<i64, StorageError> {
+        todo!()
+    }
+
+    async fn reconcile_epoch_bandwidth(&self) -> Result<usize, StorageError> {
+        todo!()
+    }
 }
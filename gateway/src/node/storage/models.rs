@@ -18,3 +18,11 @@ pub(crate) struct PersistedBandwidth {
     pub(crate) client_address_bs58: String,
     pub(crate) available: i64,
 }
+
+/// Bandwidth bought with credentials issued in particular epoch that is still available to the client.
+pub(crate) struct PersistedEpochBandwidth {
+    #[allow(dead_code)]
+    pub(crate) client_address_bs58: String,
+    pub(crate) epoch_id: i64,
+    pub(crate) remaining: i64,
+}
@@ -0,0 +1,358 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::EvictionPolicy;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Limits imposed on the total amount of data stored for offline clients.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MessageStoreQuota {
+    /// Maximum number of bytes of message content that can be stored across all clients.
+    /// Setting it to 0 disables the quota.
+    pub(crate) max_total_bytes: u64,
+
+    /// Number of bytes every client is guaranteed to be able to store, i.e. its messages
+    /// are never going to get evicted for as long as its inbox stays below this size.
+    pub(crate) per_client_guaranteed_bytes: u64,
+
+    /// Fraction of the quota after which the operator is warned about the imminent evictions.
+    pub(crate) warning_threshold: f64,
+
+    /// Policy used for choosing messages to evict once the quota is hit.
+    pub(crate) eviction_policy: EvictionPolicy,
+}
+
+impl MessageStoreQuota {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.max_total_bytes > 0
+    }
+
+    fn warning_level(&self) -> u64 {
+        (self.max_total_bytes as f64 * self.warning_threshold) as u64
+    }
+}
+
+/// Current usage of the message store alongside the eviction metrics.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct MessageStoreUsage {
+    pub(crate) stored_messages: u64,
+    pub(crate) stored_bytes: u64,
+    pub(crate) evicted_messages: u64,
+    pub(crate) evicted_bytes: u64,
+    pub(crate) rejected_messages: u64,
+    pub(crate) expired_messages: u64,
+
+    above_warning_level: bool,
+}
+
+impl MessageStoreUsage {
+    pub(crate) fn new(stored_messages: u64, stored_bytes: u64) -> Self {
+        MessageStoreUsage {
+            stored_messages,
+            stored_bytes,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn fits(&self, quota: &MessageStoreQuota, size: u64) -> bool {
+        !quota.is_enabled() || self.stored_bytes + size <= quota.max_total_bytes
+    }
+
+    pub(crate) fn record_stored(&mut self, size: u64) {
+        self.stored_messages += 1;
+        self.stored_bytes += size;
+    }
+
+    pub(crate) fn record_removed(&mut self, size: u64) {
+        self.stored_messages = self.stored_messages.saturating_sub(1);
+        self.stored_bytes = self.stored_bytes.saturating_sub(size);
+    }
+
+    pub(crate) fn record_evicted(&mut self, size: u64) {
+        self.record_removed(size);
+        self.evicted_messages += 1;
+        self.evicted_bytes += size;
+    }
+
+    pub(crate) fn record_expired(&mut self, messages: u64, bytes: u64) {
+        self.stored_messages = self.stored_messages.saturating_sub(messages);
+        self.stored_bytes = self.stored_bytes.saturating_sub(bytes);
+        self.expired_messages += messages;
+    }
+
+    pub(crate) fn record_rejected(&mut self) {
+        self.rejected_messages += 1;
+    }
+
+    /// Warns the operator whenever the usage goes above the warning level of the quota.
+    /// Each crossing is only reported once.
+    pub(crate) fn check_warning_level(&mut self, quota: &MessageStoreQuota) {
+        if !quota.is_enabled() {
+            return;
+        }
+
+        let above = self.stored_bytes >= quota.warning_level();
+        if above && !self.above_warning_level {
+            warn!(
+                "The message store is using {} out of {} allowed bytes ({} messages). Messages of offline clients are going to get evicted once the quota is hit. So far {} messages ({} bytes) have been evicted and {} have been rejected",
+                self.stored_bytes,
+                quota.max_total_bytes,
+                self.stored_messages,
+                self.evicted_messages,
+                self.evicted_bytes,
+                self.rejected_messages
+            );
+        } else if !above && self.above_warning_level {
+            info!(
+                "The message store usage went back below the warning level ({} out of {} allowed bytes)",
+                self.stored_bytes, quota.max_total_bytes
+            );
+        }
+        self.above_warning_level = above;
+    }
+}
+
+/// Messages stored for a single client. The totals are kept in memory so that the eviction
+/// candidates could be chosen without having to scan the whole message store.
+#[derive(Debug)]
+pub(crate) struct ClientInbox {
+    /// Total size of the stored messages.
+    pub(crate) bytes: u64,
+
+    /// Id of the oldest stored message, if known.
+    pub(crate) oldest_id: Option<i64>,
+
+    /// Unix timestamp of the last time the client has retrieved its messages.
+    pub(crate) last_active: i64,
+
+    /// Serialises the inserts into the inbox, so that a single flooding sender
+    /// would only ever contend with itself.
+    pub(crate) insert_lock: Arc<Mutex<()>>,
+}
+
+impl ClientInbox {
+    pub(crate) fn new(last_active: i64) -> Self {
+        ClientInbox {
+            bytes: 0,
+            oldest_id: None,
+            last_active,
+            insert_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    fn is_unused(&self) -> bool {
+        self.bytes == 0 && Arc::strong_count(&self.insert_lock) == 1
+    }
+}
+
+/// Usage of the message store alongside the inboxes of the clients with any stored messages.
+#[derive(Debug, Default)]
+pub(crate) struct MessageStoreState {
+    pub(crate) usage: MessageStoreUsage,
+    pub(crate) inboxes: HashMap<String, ClientInbox>,
+}
+
+impl MessageStoreState {
+    pub(crate) fn new(usage: MessageStoreUsage, inboxes: HashMap<String, ClientInbox>) -> Self {
+        MessageStoreState { usage, inboxes }
+    }
+
+    /// Chooses the client whose oldest message should get evicted next according to the policy,
+    /// amongst the clients whose inboxes exceed the guaranteed size.
+    pub(crate) fn eviction_candidate(&self, quota: &MessageStoreQuota) -> Option<&str> {
+        let exceeding = self
+            .inboxes
+            .iter()
+            .filter(|(_, inbox)| inbox.bytes > quota.per_client_guaranteed_bytes);
+
+        let candidate = match quota.eviction_policy {
+            EvictionPolicy::OldestFirst => {
+                exceeding.min_by_key(|(_, inbox)| inbox.oldest_id.unwrap_or(i64::MAX))
+            }
+            EvictionPolicy::LeastRecentlyActive => exceeding
+                .min_by_key(|(_, inbox)| (inbox.last_active, inbox.oldest_id.unwrap_or(i64::MAX))),
+        };
+        candidate.map(|(client_address_bs58, _)| client_address_bs58.as_str())
+    }
+
+    /// Adds the just stored message to the inbox of the client.
+    /// The usage of the store is meant to have been updated beforehand.
+    pub(crate) fn add_to_inbox(&mut self, client_address_bs58: &str, id: i64, size: u64) {
+        if let Some(inbox) = self.inboxes.get_mut(client_address_bs58) {
+            if inbox.bytes == 0 {
+                inbox.oldest_id = Some(id);
+            }
+            inbox.bytes += size;
+        }
+    }
+
+    /// Removes the message of given size from the inbox of the client. The usage of the store
+    /// is meant to be updated separately. Returns whether the inbox still contains any messages.
+    pub(crate) fn remove_from_inbox(&mut self, client_address_bs58: &str, size: u64) -> bool {
+        let Some(inbox) = self.inboxes.get_mut(client_address_bs58) else {
+            return false;
+        };
+        inbox.bytes = inbox.bytes.saturating_sub(size);
+        if inbox.bytes == 0 {
+            inbox.oldest_id = None;
+        }
+        let non_empty = inbox.bytes > 0;
+        self.prune_inbox(client_address_bs58);
+        non_empty
+    }
+
+    pub(crate) fn is_oldest(&self, client_address_bs58: &str, id: i64) -> bool {
+        matches!(self.inboxes.get(client_address_bs58), Some(inbox) if inbox.oldest_id == Some(id))
+    }
+
+    pub(crate) fn update_oldest(&mut self, client_address_bs58: &str, oldest_id: Option<i64>) {
+        if let Some(inbox) = self.inboxes.get_mut(client_address_bs58) {
+            if inbox.bytes > 0 {
+                inbox.oldest_id = oldest_id;
+            }
+        }
+    }
+
+    pub(crate) fn mark_active(&mut self, client_address_bs58: &str, last_active: i64) {
+        if let Some(inbox) = self.inboxes.get_mut(client_address_bs58) {
+            inbox.last_active = last_active;
+        }
+    }
+
+    /// Forgets the inbox of the client if it's empty and nobody is inserting into it.
+    pub(crate) fn prune_inbox(&mut self, client_address_bs58: &str) {
+        if matches!(self.inboxes.get(client_address_bs58), Some(inbox) if inbox.is_unused()) {
+            self.inboxes.remove(client_address_bs58);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quota() -> MessageStoreQuota {
+        MessageStoreQuota {
+            max_total_bytes: 1000,
+            per_client_guaranteed_bytes: 100,
+            warning_threshold: 0.8,
+            eviction_policy: EvictionPolicy::OldestFirst,
+        }
+    }
+
+    #[test]
+    fn disabled_quota_fits_everything() {
+        let quota = MessageStoreQuota {
+            max_total_bytes: 0,
+            ..quota()
+        };
+        let usage = MessageStoreUsage::new(1, u64::MAX / 2);
+        assert!(usage.fits(&quota, 1000));
+    }
+
+    #[test]
+    fn usage_respects_quota() {
+        let quota = quota();
+        let mut usage = MessageStoreUsage::new(0, 0);
+        assert!(usage.fits(&quota, 1000));
+        usage.record_stored(900);
+        assert!(usage.fits(&quota, 100));
+        assert!(!usage.fits(&quota, 101));
+
+        usage.record_evicted(500);
+        assert!(usage.fits(&quota, 600));
+        assert_eq!(usage.stored_messages, 0);
+        assert_eq!(usage.evicted_messages, 1);
+        assert_eq!(usage.evicted_bytes, 500);
+    }
+
+    #[test]
+    fn expired_messages_free_up_space() {
+        let quota = quota();
+        let mut usage = MessageStoreUsage::new(0, 0);
+        usage.record_stored(600);
+        usage.record_stored(400);
+        assert!(!usage.fits(&quota, 1));
+
+        usage.record_expired(2, 1000);
+        assert!(usage.fits(&quota, 1000));
+        assert_eq!(usage.stored_messages, 0);
+        assert_eq!(usage.expired_messages, 2);
+        assert_eq!(usage.evicted_messages, 0);
+    }
+
+    #[test]
+    fn warning_level_is_tracked() {
+        let quota = quota();
+        let mut usage = MessageStoreUsage::new(0, 0);
+        usage.record_stored(799);
+        usage.check_warning_level(&quota);
+        assert!(!usage.above_warning_level);
+
+        usage.record_stored(1);
+        usage.check_warning_level(&quota);
+        assert!(usage.above_warning_level);
+
+        usage.record_removed(100);
+        usage.check_warning_level(&quota);
+        assert!(!usage.above_warning_level);
+    }
+
+    fn state_with_inboxes(inboxes: &[(&str, u64, i64, i64)]) -> MessageStoreState {
+        let inboxes = inboxes
+            .iter()
+            .map(|(client, bytes, oldest_id, last_active)| {
+                let mut inbox = ClientInbox::new(*last_active);
+                inbox.bytes = *bytes;
+                inbox.oldest_id = Some(*oldest_id);
+                (client.to_string(), inbox)
+            })
+            .collect();
+        MessageStoreState::new(MessageStoreUsage::new(0, 0), inboxes)
+    }
+
+    #[test]
+    fn eviction_candidates_respect_the_policy_and_the_guarantee() {
+        // (client, bytes, oldest message, last active)
+        let state = state_with_inboxes(&[
+            ("small", 50, 1, 0),
+            ("old", 200, 2, 20),
+            ("inactive", 200, 3, 10),
+        ]);
+
+        let oldest_first = quota();
+        assert_eq!(state.eviction_candidate(&oldest_first), Some("old"));
+
+        let least_recently_active = MessageStoreQuota {
+            eviction_policy: EvictionPolicy::LeastRecentlyActive,
+            ..quota()
+        };
+        assert_eq!(
+            state.eviction_candidate(&least_recently_active),
+            Some("inactive")
+        );
+
+        let state = state_with_inboxes(&[("small", 50, 1, 0), ("other", 100, 2, 0)]);
+        assert_eq!(state.eviction_candidate(&oldest_first), None);
+    }
+
+    #[test]
+    fn empty_inboxes_are_pruned() {
+        let mut state = state_with_inboxes(&[("client", 200, 1, 0)]);
+
+        assert!(state.remove_from_inbox("client", 100));
+        assert_eq!(state.inboxes["client"].bytes, 100);
+
+        // nothing is pruned while there's an insert in progress
+        let insert_lock = state.inboxes["client"].insert_lock.clone();
+        assert!(!state.remove_from_inbox("client", 100));
+        assert!(state.inboxes.contains_key("client"));
+
+        drop(insert_lock);
+        state.prune_inbox("client");
+        assert!(state.inboxes.is_empty());
+    }
+}
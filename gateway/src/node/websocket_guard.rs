@@ -0,0 +1,198 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protection of the websocket listeners, i.e. the client and the federation ones, against peers
+//! sending oversized messages or holding connections open without sending anything.
+//! The timeouts are shared with the mix listener and the rejections are counted
+//! in the same operator metrics.
+
+use crate::node::statistics::operator::OperatorMetrics;
+use futures::StreamExt;
+use nym_mixnode_common::listener_guard::ListenerGuardConfig;
+use std::future::Future;
+use std::io;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::error::CapacityError;
+use tokio_tungstenite::tungstenite::protocol::{Message, WebSocketConfig};
+use tokio_tungstenite::tungstenite::Error as WsError;
+use tokio_tungstenite::WebSocketStream;
+
+#[derive(Debug, Clone)]
+pub(crate) struct WebSocketGuard {
+    listener_guard: ListenerGuardConfig,
+    websocket_config: WebSocketConfig,
+    metrics: OperatorMetrics,
+}
+
+impl WebSocketGuard {
+    pub(crate) fn new(
+        listener_guard: ListenerGuardConfig,
+        maximum_frame_size: usize,
+        maximum_message_size: usize,
+        metrics: OperatorMetrics,
+    ) -> Self {
+        WebSocketGuard {
+            listener_guard,
+            websocket_config: WebSocketConfig {
+                max_frame_size: Some(maximum_frame_size),
+                max_message_size: Some(maximum_message_size),
+                ..Default::default()
+            },
+            metrics,
+        }
+    }
+
+    fn timed_out(what: &str) -> WsError {
+        WsError::Io(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("{what} has timed out"),
+        ))
+    }
+
+    /// Performs the websocket handshake with the size limits applied. The handshake has to be
+    /// completed within the frame timeout, otherwise the connection is considered stalled.
+    pub(crate) async fn accept<S>(&self, conn: S) -> Result<WebSocketStream<S>, WsError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let handshake =
+            tokio_tungstenite::accept_async_with_config(conn, Some(self.websocket_config));
+        match tokio::time::timeout(self.listener_guard.frame_timeout, handshake).await {
+            Ok(res) => res.map_err(|err| self.inspect_error(err)),
+            Err(_) => {
+                self.metrics.record_closed_connection(true);
+                Err(Self::timed_out("websocket handshake"))
+            }
+        }
+    }
+
+    /// Runs the handshake of the protocol spoken over the established websocket. Just like
+    /// the websocket handshake, it has to be completed within the frame timeout.
+    ///
+    /// Returns `None` if it has timed out.
+    pub(crate) async fn complete_handshake<F: Future>(&self, handshake: F) -> Option<F::Output> {
+        let res = tokio::time::timeout(self.listener_guard.frame_timeout, handshake)
+            .await
+            .ok();
+        if res.is_none() {
+            self.metrics.record_closed_connection(true);
+        }
+        res
+    }
+
+    /// Reads the next message from the websocket. If no message has been received since
+    /// `last_message` within the read timeout, the connection is considered idle.
+    pub(crate) async fn next_message<S>(
+        &self,
+        ws_stream: &mut WebSocketStream<S>,
+        last_message: Instant,
+    ) -> Option<Result<Message, WsError>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let deadline = last_message + self.listener_guard.read_timeout;
+        match tokio::time::timeout_at(deadline, ws_stream.next()).await {
+            Ok(Some(Err(err))) => Some(Err(self.inspect_error(err))),
+            Ok(msg) => msg,
+            Err(_) => {
+                self.metrics.record_closed_connection(false);
+                Some(Err(Self::timed_out("websocket read")))
+            }
+        }
+    }
+
+    // counts the frames rejected for violating the limits or the protocol
+    fn inspect_error(&self, err: WsError) -> WsError {
+        match &err {
+            WsError::Capacity(CapacityError::MessageTooLong { .. }) => {
+                self.metrics.record_rejected_frame(true)
+            }
+            WsError::Capacity(_) | WsError::Protocol(_) | WsError::Utf8 => {
+                self.metrics.record_rejected_frame(false)
+            }
+            _ => (),
+        }
+        err
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::SinkExt;
+    use std::time::Duration;
+    use tokio::net::{TcpListener, TcpStream};
+
+    fn test_guard(metrics: OperatorMetrics) -> WebSocketGuard {
+        let listener_guard = ListenerGuardConfig {
+            read_timeout: Duration::from_millis(100),
+            frame_timeout: Duration::from_millis(100),
+            ..Default::default()
+        };
+        WebSocketGuard::new(listener_guard, 1024, 1024, metrics)
+    }
+
+    async fn connected_pair(
+        guard: &WebSocketGuard,
+    ) -> (WebSocketStream<TcpStream>, WebSocketStream<TcpStream>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = tokio::spawn(async move {
+            let conn = TcpStream::connect(address).await.unwrap();
+            tokio_tungstenite::client_async(format!("ws://{address}"), conn)
+                .await
+                .unwrap()
+                .0
+        });
+
+        let (conn, _) = listener.accept().await.unwrap();
+        let server = guard.accept(conn).await.unwrap();
+        (server, client.await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn oversized_messages_are_rejected() {
+        let metrics = OperatorMetrics::new();
+        let guard = test_guard(metrics.clone());
+        let (mut server, mut client) = connected_pair(&guard).await;
+
+        client.send(Message::Binary(vec![42; 512])).await.unwrap();
+        client.send(Message::Binary(vec![42; 2048])).await.unwrap();
+
+        let msg = guard.next_message(&mut server, Instant::now()).await;
+        assert!(matches!(msg, Some(Ok(Message::Binary(_)))));
+        let msg = guard.next_message(&mut server, Instant::now()).await;
+        assert!(matches!(msg, Some(Err(WsError::Capacity(_)))));
+        assert_eq!(metrics.rejected_frames().oversized, 1);
+    }
+
+    #[tokio::test]
+    async fn idle_connections_are_closed() {
+        let metrics = OperatorMetrics::new();
+        let guard = test_guard(metrics.clone());
+        let (mut server, _client) = connected_pair(&guard).await;
+
+        let msg = guard.next_message(&mut server, Instant::now()).await;
+        assert!(matches!(msg, Some(Err(WsError::Io(_)))));
+        assert_eq!(metrics.rejected_frames().idle_connections, 1);
+    }
+
+    #[tokio::test]
+    async fn stalled_handshakes_are_closed() {
+        let metrics = OperatorMetrics::new();
+        let guard = test_guard(metrics.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        // the peer never sends the websocket upgrade request
+        let _conn = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (conn, _) = listener.accept().await.unwrap();
+        assert!(guard.accept(conn).await.is_err());
+
+        let never = futures::future::pending::<()>();
+        assert!(guard.complete_handshake(never).await.is_none());
+        assert_eq!(metrics.rejected_frames().stalled_connections, 2);
+    }
+}
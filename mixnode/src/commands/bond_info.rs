@@ -0,0 +1,50 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::commands::CostParamsArgs;
+use crate::config::Config;
+use crate::node::MixNode;
+use clap::Args;
+use nym_bin_common::output_format::OutputFormat;
+use nym_config::defaults::NymNetworkDetails;
+use nym_config::NymConfig;
+
+use super::version_check;
+
+#[derive(Args)]
+pub(crate) struct BondInfo {
+    /// The id of the mixnode you want to show the bonding information of
+    #[clap(long)]
+    id: String,
+
+    #[clap(flatten)]
+    cost_params: CostParamsArgs,
+
+    #[clap(short, long, default_value_t = OutputFormat::default())]
+    output: OutputFormat,
+}
+
+pub(crate) fn execute(args: &BondInfo) {
+    let config = match Config::load_from_file(&args.id) {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!(
+                "Failed to load config for {}. Are you sure you have run `init` before? (Error was: {err})",
+                args.id,
+            );
+            return;
+        }
+    };
+
+    if !version_check(&config) {
+        error!("Failed the local version check");
+        return;
+    }
+
+    let network_details = NymNetworkDetails::new_from_env();
+    let cost_params = args
+        .cost_params
+        .cost_params_or_exit(&network_details.chain_details.mix_denom.base);
+
+    MixNode::new(config).print_bonding_information(cost_params, None, args.output)
+}
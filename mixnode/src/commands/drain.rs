@@ -0,0 +1,67 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::Config;
+use clap::Args;
+use nym_config::NymConfig;
+use std::net::{IpAddr, Ipv4Addr};
+
+#[derive(Args)]
+pub(crate) struct Drain {
+    /// The id of the mixnode you want to drain
+    #[clap(long)]
+    id: String,
+}
+
+fn drain_endpoint(config: &Config) -> String {
+    // the drain route only accepts requests coming from the local machine
+    let host = match config.get_listening_address() {
+        address if address.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        address => address,
+    };
+    let host = match host {
+        IpAddr::V4(ip) => ip.to_string(),
+        IpAddr::V6(ip) => format!("[{ip}]"),
+    };
+
+    format!("http://{host}:{}/drain", config.get_http_api_port())
+}
+
+pub(crate) async fn execute(args: &Drain) {
+    let config = match Config::load_from_file(&args.id) {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!(
+                "Failed to load config for {}. Are you sure you have run `init` before? (Error was: {})",
+                args.id,
+                err,
+            );
+            return;
+        }
+    };
+
+    let endpoint = drain_endpoint(&config);
+    let response = match reqwest::Client::new().post(&endpoint).send().await {
+        Ok(response) => response,
+        Err(err) => {
+            error!("Failed to send the drain request to {endpoint}. Is your mixnode running? (Error was: {err})");
+            return;
+        }
+    };
+
+    if !response.status().is_success() {
+        error!(
+            "The mixnode has rejected the drain request with status {}",
+            response.status()
+        );
+        return;
+    }
+
+    match response.text().await {
+        Ok(presence) => {
+            println!("{presence}");
+            println!("The mixnode will stop accepting new packets after announcing its unavailability and will exit once its delay queue is empty");
+        }
+        Err(err) => error!("Failed to read the drain response - {err}"),
+    }
+}
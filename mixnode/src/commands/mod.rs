@@ -3,29 +3,48 @@
 
 use crate::{config::Config, Cli};
 use clap::CommandFactory;
-use clap::Subcommand;
+use clap::{Args, Subcommand};
 use colored::Colorize;
+use nym_bin_common::build_information::BuildInfo;
 use nym_bin_common::completions::{fig_generate, ArgShell};
 use nym_bin_common::version_checker;
 use nym_config::defaults::var_names::{BECH32_PREFIX, NYM_API};
 use nym_config::OptionalSet;
 use nym_crypto::bech32_address_validation;
+use nym_mixnet_contract_common::{Coin, MixNodeCostParams, Percent};
 use nym_validator_client::nyxd;
 use std::net::IpAddr;
 use std::process;
 
+mod bond_info;
 mod describe;
+mod drain;
 mod init;
 mod node_details;
+mod prepare_bond;
 mod run;
 mod sign;
 mod upgrade;
 
 #[derive(Subcommand)]
 pub(crate) enum Commands {
+    /// Show the information required for bonding this mixnode
+    BondInfo(bond_info::BondInfo),
+
+    /// Show the information required for bonding this mixnode alongside the signature
+    /// on the bonding message for the specified wallet
+    PrepareBond(prepare_bond::PrepareBond),
+
+    /// Show build information of this binary
+    BuildInfo(BuildInfo),
+
     /// Describe your mixnode and tell people why they should delegate state to you
     Describe(describe::Describe),
 
+    /// Gracefully drain a running mixnode: announce its unavailability, stop accepting new packets,
+    /// forward everything left in its delay queue and exit
+    Drain(drain::Drain),
+
     /// Initialise the mixnode
     Init(init::Init),
 
@@ -64,7 +83,11 @@ pub(crate) async fn execute(args: Cli) {
     let bin_name = "nym-mixnode";
 
     match args.command {
+        Commands::BondInfo(m) => bond_info::execute(&m),
+        Commands::PrepareBond(m) => prepare_bond::execute(&m),
+        Commands::BuildInfo(m) => m.execute(&crate::BUILD_INFORMATION),
         Commands::Describe(m) => describe::execute(m),
+        Commands::Drain(m) => drain::execute(&m).await,
         Commands::Init(m) => init::execute(&m),
         Commands::Run(m) => run::execute(&m).await,
         Commands::Sign(m) => sign::execute(&m),
@@ -109,6 +132,39 @@ fn override_config(mut config: Config, args: OverrideConfig) -> Config {
         )
 }
 
+const DEFAULT_PROFIT_MARGIN_PERCENT: u8 = 10;
+const DEFAULT_INTERVAL_OPERATING_COST: u128 = 40_000_000;
+
+#[derive(Args)]
+pub(crate) struct CostParamsArgs {
+    /// Profit margin of the mixnode, in percent
+    #[clap(long, default_value_t = DEFAULT_PROFIT_MARGIN_PERCENT)]
+    profit_margin_percent: u8,
+
+    /// Operating cost of the mixnode per interval in the base denomination
+    /// (so it would be 'unym', rather than 'nym')
+    #[clap(long, default_value_t = DEFAULT_INTERVAL_OPERATING_COST)]
+    interval_operating_cost: u128,
+}
+
+impl CostParamsArgs {
+    /// Converts the arguments into the cost parameters of the mixnode, or exits if they're invalid.
+    pub(crate) fn cost_params_or_exit(&self, denom: &str) -> MixNodeCostParams {
+        let profit_margin_percent =
+            match Percent::from_percentage_value(self.profit_margin_percent as u64) {
+                Ok(percent) => percent,
+                Err(err) => {
+                    error!("Error: invalid profit margin - {err}");
+                    process::exit(1);
+                }
+            };
+        MixNodeCostParams {
+            profit_margin_percent,
+            interval_operating_cost: Coin::new(self.interval_operating_cost, denom),
+        }
+    }
+}
+
 /// Ensures that a given bech32 address is valid, or exits
 pub(crate) fn validate_bech32_address_or_exit(address: &str) {
     let prefix = std::env::var(BECH32_PREFIX).expect("bech32 prefix not set");
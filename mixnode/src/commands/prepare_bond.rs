@@ -0,0 +1,97 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::commands::{validate_bech32_address_or_exit, CostParamsArgs};
+use crate::config::Config;
+use crate::node::MixNode;
+use clap::Args;
+use nym_bin_common::output_format::OutputFormat;
+use nym_config::defaults::NymNetworkDetails;
+use nym_config::NymConfig;
+use nym_mixnet_contract_common::{Addr, Coin};
+use nym_types::helpers::BondingRequest;
+use nym_validator_client::nyxd;
+
+use super::version_check;
+
+#[derive(Args)]
+pub(crate) struct PrepareBond {
+    /// The id of the mixnode you want to bond
+    #[clap(long)]
+    id: String,
+
+    /// Address of the wallet that is going to own the mixnode.
+    /// If not provided, the address from the config file is used
+    #[clap(long)]
+    wallet_address: Option<nyxd::AccountId>,
+
+    /// Amount to pledge in the base denomination (so it would be 'unym', rather than 'nym')
+    #[clap(long)]
+    amount: u128,
+
+    /// Current signing nonce of the wallet, as stored in the mixnet contract
+    #[clap(long)]
+    signing_nonce: u32,
+
+    /// Indicates whether the mixnode is going to get bonded via a vesting account
+    #[clap(long)]
+    with_vesting_account: bool,
+
+    #[clap(flatten)]
+    cost_params: CostParamsArgs,
+
+    #[clap(short, long, default_value_t = OutputFormat::default())]
+    output: OutputFormat,
+}
+
+pub(crate) fn execute(args: &PrepareBond) {
+    let config = match Config::load_from_file(&args.id) {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!(
+                "Failed to load config for {}. Are you sure you have run `init` before? (Error was: {err})",
+                args.id,
+            );
+            return;
+        }
+    };
+
+    if !version_check(&config) {
+        error!("Failed the local version check");
+        return;
+    }
+
+    let Some(wallet_address) = args
+        .wallet_address
+        .clone()
+        .or_else(|| config.get_wallet_address())
+    else {
+        error!("The wallet address is unknown - either provide it with `--wallet-address` or set it in the config file");
+        return;
+    };
+
+    // perform extra validation to ensure we have correct prefix
+    validate_bech32_address_or_exit(wallet_address.as_ref());
+
+    let network_details = NymNetworkDetails::new_from_env();
+    let denom = &network_details.chain_details.mix_denom.base;
+    let proxy = if args.with_vesting_account {
+        let Some(vesting_contract) = network_details.contracts.vesting_contract_address else {
+            error!("The address of the vesting contract is not known for this network");
+            return;
+        };
+        Some(Addr::unchecked(vesting_contract))
+    } else {
+        None
+    };
+
+    let bonding_request = BondingRequest {
+        wallet_address: Addr::unchecked(wallet_address.as_ref()),
+        proxy,
+        pledge: Coin::new(args.amount, denom),
+        signing_nonce: args.signing_nonce,
+    };
+    let cost_params = args.cost_params.cost_params_or_exit(denom);
+
+    MixNode::new(config).print_bonding_information(cost_params, Some(bonding_request), args.output)
+}
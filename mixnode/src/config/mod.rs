@@ -6,7 +6,15 @@ use nym_config::defaults::mainnet::NYM_API;
 use nym_config::defaults::{
     DEFAULT_HTTP_API_LISTENING_PORT, DEFAULT_MIX_LISTENING_PORT, DEFAULT_VERLOC_LISTENING_PORT,
 };
+use nym_config::paths::default_nym_directory;
 use nym_config::NymConfig;
+use nym_metrics::{
+    MetricsBackend, MetricsConfig, DEFAULT_METRICS_EXPORT_INTERVAL, DEFAULT_METRICS_PREFIX,
+};
+use nym_mixnode_common::listener_guard::{
+    ListenerGuardConfig, DEFAULT_CONNECTION_READ_TIMEOUT, DEFAULT_FRAME_TIMEOUT,
+    DEFAULT_MAXIMUM_FRAME_SIZE,
+};
 use nym_validator_client::nyxd;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::net::{IpAddr, SocketAddr};
@@ -36,6 +44,19 @@ const DEFAULT_PACKET_FORWARDING_INITIAL_BACKOFF: Duration = Duration::from_milli
 const DEFAULT_PACKET_FORWARDING_MAXIMUM_BACKOFF: Duration = Duration::from_millis(300_000);
 const DEFAULT_INITIAL_CONNECTION_TIMEOUT: Duration = Duration::from_millis(1_500);
 const DEFAULT_MAXIMUM_CONNECTION_BUFFER_SIZE: usize = 128;
+const DEFAULT_MAXIMUM_FORWARDING_DELAY: Duration = Duration::from_millis(30_000);
+const DEFAULT_DRAIN_ANNOUNCEMENT_PERIOD: Duration = Duration::from_secs(60);
+const DEFAULT_MAXIMUM_CONNECTION_PACKET_BURST: u32 = 20_000;
+const DEFAULT_MAXIMUM_CONNECTION_PACKET_RATE: u32 = 10_000;
+const DEFAULT_SOURCE_BAN_THRESHOLD: u32 = 100_000;
+const DEFAULT_SOURCE_BAN_DURATION: Duration = Duration::from_secs(10 * 60);
+const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+// 'DELAY QUEUE'
+const DEFAULT_MAXIMUM_PERSISTED_PACKETS: usize = 100_000;
+const DEFAULT_MAXIMUM_RESTORED_PACKET_LATENESS: Duration = Duration::from_secs(30);
+const DEFAULT_OVERDUE_RESTORED_PACKETS_SPREAD: Duration = Duration::from_secs(5);
+const DELAY_QUEUE_SNAPSHOT_FILE: &str = "delay_queue.snapshot";
 
 pub fn missing_string_value<T: From<String>>() -> T {
     MISSING_VALUE.to_string().into()
@@ -83,6 +104,10 @@ pub struct Config {
     #[serde(default)]
     logging: Logging,
     #[serde(default)]
+    metrics: Metrics,
+    #[serde(default)]
+    delay_queue: DelayQueue,
+    #[serde(default)]
     debug: Debug,
 }
 
@@ -92,14 +117,11 @@ impl NymConfig for Config {
     }
 
     fn default_root_directory() -> PathBuf {
-        dirs::home_dir()
-            .expect("Failed to evaluate $HOME value")
-            .join(".nym")
-            .join("mixnodes")
+        default_nym_directory("mixnodes").expect("no data directory known for this OS")
     }
 
     fn try_default_root_directory() -> Option<PathBuf> {
-        dirs::home_dir().map(|path| path.join(".nym").join("mixnodes"))
+        default_nym_directory("mixnodes")
     }
 
     fn root_directory(&self) -> PathBuf {
@@ -274,10 +296,91 @@ impl Config {
         self.debug.maximum_connection_buffer_size
     }
 
+    pub fn get_maximum_forwarding_delay(&self) -> Duration {
+        self.debug.maximum_forwarding_delay
+    }
+
+    pub fn get_drain_announcement_period(&self) -> Duration {
+        self.debug.drain_announcement_period
+    }
+
+    pub fn get_maximum_connection_packet_burst(&self) -> u32 {
+        self.debug.maximum_connection_packet_burst
+    }
+
+    pub fn get_maximum_connection_packet_rate(&self) -> u32 {
+        self.debug.maximum_connection_packet_rate
+    }
+
+    pub fn get_source_ban_threshold(&self) -> u32 {
+        self.debug.source_ban_threshold
+    }
+
+    pub fn get_source_ban_duration(&self) -> Duration {
+        self.debug.source_ban_duration
+    }
+
+    pub fn get_heartbeat_interval(&self) -> Duration {
+        self.debug.heartbeat_interval
+    }
+
+    pub fn get_listener_guard_config(&self) -> ListenerGuardConfig {
+        ListenerGuardConfig {
+            maximum_frame_size: self.debug.maximum_incoming_frame_size,
+            read_timeout: self.debug.incoming_connection_read_timeout,
+            frame_timeout: self.debug.incoming_frame_timeout,
+        }
+    }
+
     pub fn get_use_legacy_sphinx_framing(&self) -> bool {
         self.debug.use_legacy_framed_packet_version
     }
 
+    pub fn get_log_level(&self) -> Option<log::LevelFilter> {
+        if self.logging.level.is_empty() {
+            return None;
+        }
+        match self.logging.level.parse() {
+            Ok(level) => Some(level),
+            Err(_) => {
+                log::warn!(
+                    "'{}' is not a valid log level - it's going to be ignored",
+                    self.logging.level
+                );
+                None
+            }
+        }
+    }
+
+    pub fn get_metrics_config(&self) -> MetricsConfig {
+        MetricsConfig {
+            backend: self.metrics.backend,
+            endpoint: self.metrics.endpoint.clone(),
+            prefix: self.metrics.prefix.clone(),
+            export_interval: self.metrics.export_interval,
+        }
+    }
+
+    pub fn get_persist_delay_queue(&self) -> bool {
+        self.delay_queue.persist_on_shutdown
+    }
+
+    pub fn get_maximum_persisted_packets(&self) -> usize {
+        self.delay_queue.maximum_persisted_packets
+    }
+
+    pub fn get_maximum_restored_packet_lateness(&self) -> Duration {
+        self.delay_queue.maximum_restored_packet_lateness
+    }
+
+    pub fn get_overdue_restored_packets_spread(&self) -> Duration {
+        self.delay_queue.overdue_restored_packets_spread
+    }
+
+    pub fn get_delay_queue_snapshot_file(&self) -> PathBuf {
+        self.data_directory().join(DELAY_QUEUE_SNAPSHOT_FILE)
+    }
+
     pub fn get_version(&self) -> &str {
         &self.mixnode.version
     }
@@ -415,7 +518,75 @@ impl Default for MixNode {
 
 #[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
-struct Logging {}
+struct Logging {
+    /// Maximum level of messages that are going to get logged, for example `info` or `debug`.
+    /// If left empty, the level set via the `RUST_LOG` environment variable is used.
+    /// Note that it can't be used to increase the verbosity beyond the one set at startup.
+    #[serde(default)]
+    level: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct Metrics {
+    /// Backend the metrics are going to be pushed into, i.e. `disabled`, `statsd` or `otlp`.
+    backend: MetricsBackend,
+
+    /// Address of the statsd daemon, e.g. `127.0.0.1:8125`, or url of the OTLP collector,
+    /// e.g. `http://127.0.0.1:4318/v1/metrics`.
+    endpoint: String,
+
+    /// Prefix of the metric names (statsd) or the name of the service (OTLP).
+    prefix: String,
+
+    /// Interval between subsequent exports of the metrics.
+    #[serde(with = "humantime_serde")]
+    export_interval: Duration,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics {
+            backend: MetricsBackend::Disabled,
+            endpoint: String::new(),
+            prefix: format!("{DEFAULT_METRICS_PREFIX}.mixnode"),
+            export_interval: DEFAULT_METRICS_EXPORT_INTERVAL,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+struct DelayQueue {
+    /// Specifies whether the packets still waiting in the delay queue when the node is shutting
+    /// down should be saved to disk and restored on its next start rather than getting dropped.
+    persist_on_shutdown: bool,
+
+    /// Maximum number of packets that can be saved to disk. If there are more of them queued,
+    /// the ones that were meant to be forwarded the latest are dropped.
+    maximum_persisted_packets: usize,
+
+    /// Maximum duration by which a restored packet can miss its forwarding time. Packets that
+    /// are late by more than that are dropped, as their senders have most likely given up on them.
+    #[serde(with = "humantime_serde")]
+    maximum_restored_packet_lateness: Duration,
+
+    /// Interval over which the restored packets whose forwarding time has already passed are
+    /// randomly spread, rather than all of them being forwarded at once.
+    #[serde(with = "humantime_serde")]
+    overdue_restored_packets_spread: Duration,
+}
+
+impl Default for DelayQueue {
+    fn default() -> Self {
+        DelayQueue {
+            persist_on_shutdown: false,
+            maximum_persisted_packets: DEFAULT_MAXIMUM_PERSISTED_PACKETS,
+            maximum_restored_packet_lateness: DEFAULT_MAXIMUM_RESTORED_PACKET_LATENESS,
+            overdue_restored_packets_spread: DEFAULT_OVERDUE_RESTORED_PACKETS_SPREAD,
+        }
+    }
+}
 
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
@@ -424,22 +595,27 @@ struct Verloc {
     packets_per_node: usize,
 
     /// Specifies maximum amount of time to wait for the connection to get established.
+    #[serde(with = "humantime_serde")]
     connection_timeout: Duration,
 
     /// Specifies maximum amount of time to wait for the reply packet to arrive before abandoning the test.
+    #[serde(with = "humantime_serde")]
     packet_timeout: Duration,
 
     /// Specifies delay between subsequent test packets being sent (after receiving a reply).
+    #[serde(with = "humantime_serde")]
     delay_between_packets: Duration,
 
     /// Specifies number of nodes being tested at once.
     tested_nodes_batch_size: usize,
 
     /// Specifies delay between subsequent test runs.
+    #[serde(with = "humantime_serde")]
     testing_interval: Duration,
 
     /// Specifies delay between attempting to run the measurement again if the previous run failed
     /// due to being unable to get the list of nodes.
+    #[serde(with = "humantime_serde")]
     retry_timeout: Duration,
 }
 
@@ -485,6 +661,51 @@ struct Debug {
     /// Maximum number of packets that can be stored waiting to get sent to a particular connection.
     maximum_connection_buffer_size: usize,
 
+    /// Maximum duration a delayed packet can wait for the connection to its next hop to be
+    /// re-established. Packets that would wait longer are dropped straight away, so that
+    /// their senders could retransmit them through different routes sooner.
+    #[serde(with = "humantime_serde")]
+    maximum_forwarding_delay: Duration,
+
+    /// Duration for which the node keeps accepting new packets after a drain has been requested,
+    /// while announcing its imminent unavailability, before it starts forwarding its delay queue.
+    #[serde(with = "humantime_serde")]
+    drain_announcement_period: Duration,
+
+    /// Maximum number of sphinx packets a single source can send in a burst, over all of its
+    /// incoming connections, before they start getting dropped.
+    maximum_connection_packet_burst: u32,
+
+    /// Sustained number of sphinx packets per second a single source can send, over all of its
+    /// incoming connections.
+    maximum_connection_packet_rate: u32,
+
+    /// Number of packets of a single source dropped due to rate limiting within a 10s window,
+    /// after which it gets temporarily banned and all of its connections are closed.
+    source_ban_threshold: u32,
+
+    /// Duration for which a banned source is not allowed to connect to the node.
+    #[serde(with = "humantime_serde")]
+    source_ban_duration: Duration,
+
+    /// Maximum size, in bytes, of a single frame an incoming connection can send. Connections
+    /// sending bigger frames are immediately closed.
+    maximum_incoming_frame_size: usize,
+
+    /// Duration after which an incoming connection that hasn't sent any complete frame is closed.
+    #[serde(with = "humantime_serde")]
+    incoming_connection_read_timeout: Duration,
+
+    /// Maximum duration a partially received frame can remain incomplete before the connection
+    /// is closed as stalled.
+    #[serde(with = "humantime_serde")]
+    incoming_frame_timeout: Duration,
+
+    /// Delay between subsequent signed heartbeats sent to the nym-api to announce the node is alive.
+    /// Setting it to zero disables the heartbeats.
+    #[serde(with = "humantime_serde")]
+    heartbeat_interval: Duration,
+
     /// Specifies whether the mixnode should be using the legacy framing for the sphinx packets.
     // it's set to true by default. The reason for that decision is to preserve compatibility with the
     // existing nodes whilst everyone else is upgrading and getting the code for handling the new field.
@@ -501,6 +722,16 @@ impl Default for Debug {
             packet_forwarding_maximum_backoff: DEFAULT_PACKET_FORWARDING_MAXIMUM_BACKOFF,
             initial_connection_timeout: DEFAULT_INITIAL_CONNECTION_TIMEOUT,
             maximum_connection_buffer_size: DEFAULT_MAXIMUM_CONNECTION_BUFFER_SIZE,
+            maximum_forwarding_delay: DEFAULT_MAXIMUM_FORWARDING_DELAY,
+            drain_announcement_period: DEFAULT_DRAIN_ANNOUNCEMENT_PERIOD,
+            maximum_connection_packet_burst: DEFAULT_MAXIMUM_CONNECTION_PACKET_BURST,
+            maximum_connection_packet_rate: DEFAULT_MAXIMUM_CONNECTION_PACKET_RATE,
+            source_ban_threshold: DEFAULT_SOURCE_BAN_THRESHOLD,
+            source_ban_duration: DEFAULT_SOURCE_BAN_DURATION,
+            maximum_incoming_frame_size: DEFAULT_MAXIMUM_FRAME_SIZE,
+            incoming_connection_read_timeout: DEFAULT_CONNECTION_READ_TIMEOUT,
+            incoming_frame_timeout: DEFAULT_FRAME_TIMEOUT,
+            heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL,
             // TODO: remember to change it in one of future releases!!
             use_legacy_framed_packet_version: true,
         }
@@ -73,7 +73,48 @@ nym_root_directory = '{{ mixnode.nym_root_directory }}'
 
 [logging]
 
-# TODO
+# Maximum level of messages that are going to get logged, for example 'info' or 'debug'.
+# If left empty, the level set via the `RUST_LOG` environment variable is used.
+# Note that it can't be used to increase the verbosity beyond the one set at startup.
+# The value can be changed without restarting the node by sending it the SIGHUP signal.
+level = '{{ logging.level }}'
+
+##### metrics configuration options #####
+
+[metrics]
+
+# Backend the metrics are going to be pushed into, i.e. 'disabled', 'statsd' or 'otlp'.
+backend = '{{ metrics.backend }}'
+
+# Address of the statsd daemon, e.g. '127.0.0.1:8125', or url of the OTLP collector,
+# e.g. 'http://127.0.0.1:4318/v1/metrics'.
+endpoint = '{{ metrics.endpoint }}'
+
+# Prefix of the metric names (statsd) or the name of the service (OTLP).
+prefix = '{{ metrics.prefix }}'
+
+# Interval between subsequent exports of the metrics.
+export_interval = '{{ metrics.export_interval }}'
+
+##### delay queue configuration options #####
+
+[delay_queue]
+
+# Specifies whether the packets still waiting in the delay queue when the node is shutting down
+# should be saved to disk and restored on its next start rather than getting dropped.
+persist_on_shutdown = {{ delay_queue.persist_on_shutdown }}
+
+# Maximum number of packets that can be saved to disk. If there are more of them queued,
+# the ones that were meant to be forwarded the latest are dropped.
+maximum_persisted_packets = {{ delay_queue.maximum_persisted_packets }}
+
+# Maximum duration by which a restored packet can miss its forwarding time. Packets that
+# are late by more than that are dropped, as their senders have most likely given up on them.
+maximum_restored_packet_lateness = '{{ delay_queue.maximum_restored_packet_lateness }}'
+
+# Interval over which the restored packets whose forwarding time has already passed are
+# randomly spread, rather than all of them being forwarded at once.
+overdue_restored_packets_spread = '{{ delay_queue.overdue_restored_packets_spread }}'
 
 "#
 }
@@ -7,11 +7,12 @@ extern crate rocket;
 use ::nym_config::defaults::setup_env;
 use clap::{crate_name, crate_version, Parser};
 use lazy_static::lazy_static;
-use nym_bin_common::build_information::BinaryBuildInformation;
+use nym_bin_common::build_information::{protocols, BinaryBuildInformation};
 #[allow(unused_imports)]
 use nym_bin_common::logging::{maybe_print_banner, setup_logging};
 #[cfg(feature = "cpucycles")]
 use nym_bin_common::setup_tracing;
+use nym_config::paths::migrate_legacy_nym_directory;
 #[cfg(feature = "cpucycles")]
 use nym_mixnode_common::measure;
 #[cfg(feature = "cpucycles")]
@@ -20,9 +21,21 @@ mod commands;
 mod config;
 mod node;
 
+pub(crate) const BUILD_INFORMATION: BinaryBuildInformation =
+    BinaryBuildInformation::new(env!("CARGO_PKG_VERSION"))
+        .with_enabled_features(&[
+            #[cfg(feature = "packet-tracing")]
+            "packet-tracing",
+            #[cfg(feature = "cpucycles")]
+            "cpucycles",
+        ])
+        .with_protocol_versions(&[(
+            protocols::SPHINX_PACKET,
+            nym_sphinx::params::CURRENT_PACKET_VERSION_NUMBER,
+        )]);
+
 lazy_static! {
-    pub static ref PRETTY_BUILD_INFORMATION: String =
-        BinaryBuildInformation::new(env!("CARGO_PKG_VERSION")).pretty_print();
+    pub static ref PRETTY_BUILD_INFORMATION: String = BUILD_INFORMATION.pretty_print();
 }
 
 // Helper for passing LONG_VERSION to clap
@@ -61,6 +74,7 @@ async fn main() {
 
     let args = Cli::parse();
     setup_env(args.config_env_file.as_ref());
+    migrate_legacy_nym_directory("mixnodes");
     commands::execute(args).await;
 
     cfg_if::cfg_if! {
@@ -0,0 +1,267 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Persistence of the packets waiting in the delay queue across restarts of the node,
+//! so that scheduled maintenance doesn't drop every single one of them.
+//!
+//! The snapshot consists of a sequence of entries, each of which is formatted as follows:
+//! FORWARD_AT (8 bytes, unix timestamp in milliseconds) || PACKET_LEN (4 bytes) || MIX_PACKET
+
+use nym_sphinx::forwarding::packet::MixPacket;
+use rand::Rng;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const FORWARD_AT_LEN: usize = 8;
+const PACKET_LEN_LEN: usize = 4;
+
+/// Packet alongside the (wall clock) time at which it should get forwarded.
+pub(crate) type PersistedPacket = (MixPacket, SystemTime);
+
+pub(crate) struct DelayQueueSnapshot {
+    path: PathBuf,
+    maximum_packets: usize,
+
+    /// Maximum duration by which a restored packet can miss its forwarding time
+    /// before it's considered stale and dropped.
+    maximum_lateness: Duration,
+
+    /// Interval over which the restored packets that are already overdue are spread.
+    overdue_spread: Duration,
+}
+
+impl DelayQueueSnapshot {
+    pub(crate) fn new(
+        path: PathBuf,
+        maximum_packets: usize,
+        maximum_lateness: Duration,
+        overdue_spread: Duration,
+    ) -> Self {
+        DelayQueueSnapshot {
+            path,
+            maximum_packets,
+            maximum_lateness,
+            overdue_spread,
+        }
+    }
+
+    /// Determines the remaining delay of a restored packet that was meant to be forwarded at
+    /// `forward_at`. Rather than forwarding all the packets that became overdue while the node was
+    /// offline in a single burst, they get random delays within the overdue spread.
+    /// Returns `None` if the packet is late by more than the maximum lateness and should be dropped.
+    pub(crate) fn restored_delay<R: Rng + ?Sized>(
+        &self,
+        forward_at: SystemTime,
+        now: SystemTime,
+        rng: &mut R,
+    ) -> Option<Duration> {
+        match forward_at.duration_since(now) {
+            Ok(delay) => Some(delay),
+            Err(lateness) if lateness.duration() > self.maximum_lateness => None,
+            Err(_) => {
+                let spread = self.overdue_spread.as_millis() as u64;
+                if spread == 0 {
+                    Some(Duration::ZERO)
+                } else {
+                    Some(Duration::from_millis(rng.gen_range(0, spread)))
+                }
+            }
+        }
+    }
+
+    /// Saves the packets to disk, keeping at most `maximum_packets` of them that are meant to be
+    /// forwarded the earliest. Returns the number of the saved packets.
+    pub(crate) fn save(&self, mut packets: Vec<PersistedPacket>) -> io::Result<usize> {
+        packets.sort_by_key(|(_, forward_at)| *forward_at);
+        packets.truncate(self.maximum_packets);
+        let saved = packets.len();
+
+        // write to a temporary file first so that we'd never end up with a partial snapshot
+        let tmp_path = self.path.with_extension("tmp");
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        {
+            let mut file = File::create(&tmp_path)?;
+            file.write_all(&encode(packets))?;
+            file.sync_all()?;
+        }
+        fs::rename(tmp_path, &self.path)?;
+
+        // make sure the rename itself is persisted
+        #[cfg(target_family = "unix")]
+        if let Some(parent) = self.path.parent() {
+            File::open(parent)?.sync_all()?;
+        }
+        Ok(saved)
+    }
+
+    /// Loads the previously saved packets, if any. The snapshot is removed afterwards,
+    /// so that the same packets would never get forwarded twice.
+    pub(crate) fn load(&self) -> io::Result<Vec<PersistedPacket>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read(&self.path)?;
+        fs::remove_file(&self.path)?;
+        decode(&content)
+    }
+}
+
+fn encode(packets: Vec<PersistedPacket>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for (packet, forward_at) in packets {
+        let forward_at = forward_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let packet = packet.into_bytes();
+
+        bytes.extend_from_slice(&forward_at.to_be_bytes());
+        bytes.extend_from_slice(&(packet.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&packet);
+    }
+    bytes
+}
+
+fn malformed(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("malformed delay queue snapshot: {reason}"),
+    )
+}
+
+fn decode(mut bytes: &[u8]) -> io::Result<Vec<PersistedPacket>> {
+    let mut packets = Vec::new();
+    while !bytes.is_empty() {
+        if bytes.len() < FORWARD_AT_LEN + PACKET_LEN_LEN {
+            return Err(malformed("truncated entry header"));
+        }
+        let (forward_at, rest) = bytes.split_at(FORWARD_AT_LEN);
+        let (packet_len, rest) = rest.split_at(PACKET_LEN_LEN);
+
+        // the unwraps are fine as the slices have exactly the expected lengths
+        let forward_at = u64::from_be_bytes(forward_at.try_into().unwrap());
+        let packet_len = u32::from_be_bytes(packet_len.try_into().unwrap()) as usize;
+        if rest.len() < packet_len {
+            return Err(malformed("truncated packet"));
+        }
+        let (packet, rest) = rest.split_at(packet_len);
+
+        let packet =
+            MixPacket::try_from_bytes(packet).map_err(|err| malformed(&err.to_string()))?;
+        packets.push((packet, UNIX_EPOCH + Duration::from_millis(forward_at)));
+        bytes = rest;
+    }
+    Ok(packets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::packet_delayforwarder::tests::make_valid_sphinx_packet;
+    use nym_sphinx::addressing::nodes::NymNodeRoutingAddress;
+    use nym_sphinx_params::packet_sizes::PacketSize;
+    use nym_sphinx_params::PacketMode;
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+    fn mix_packet(port: u16) -> MixPacket {
+        let next_hop = NymNodeRoutingAddress::from(SocketAddr::new(
+            IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)),
+            port,
+        ));
+        MixPacket::new(
+            next_hop,
+            make_valid_sphinx_packet(PacketSize::default()),
+            PacketMode::default(),
+        )
+    }
+
+    fn test_snapshot(path: PathBuf, maximum_packets: usize) -> DelayQueueSnapshot {
+        DelayQueueSnapshot::new(
+            path,
+            maximum_packets,
+            Duration::from_secs(30),
+            Duration::from_secs(5),
+        )
+    }
+
+    #[test]
+    fn overdue_packets_are_spread_out_or_dropped() {
+        let snapshot = test_snapshot(PathBuf::from("unused"), 10);
+        let mut rng = rand::thread_rng();
+        let now = SystemTime::now();
+
+        // packets that are still on time keep their remaining delay
+        let on_time = now + Duration::from_secs(2);
+        assert_eq!(
+            snapshot.restored_delay(on_time, now, &mut rng),
+            Some(Duration::from_secs(2))
+        );
+
+        // overdue ones get forwarded within the spread
+        for _ in 0..100 {
+            let overdue = now - Duration::from_secs(10);
+            let delay = snapshot.restored_delay(overdue, now, &mut rng).unwrap();
+            assert!(delay < Duration::from_secs(5));
+        }
+
+        // and the stale ones are dropped
+        let stale = now - Duration::from_secs(31);
+        assert!(snapshot.restored_delay(stale, now, &mut rng).is_none());
+    }
+
+    #[test]
+    fn packets_survive_encoding() {
+        let forward_at = UNIX_EPOCH + Duration::from_millis(1_686_000_000_000);
+        let packets = vec![(mix_packet(1), forward_at), (mix_packet(2), forward_at)];
+
+        let decoded = decode(&encode(packets)).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].0.next_hop(), mix_packet(1).next_hop());
+        assert_eq!(decoded[1].0.next_hop(), mix_packet(2).next_hop());
+        assert!(decoded.iter().all(|(_, at)| *at == forward_at));
+    }
+
+    #[test]
+    fn truncated_snapshot_is_rejected() {
+        let forward_at = UNIX_EPOCH + Duration::from_millis(1_686_000_000_000);
+        let encoded = encode(vec![(mix_packet(1), forward_at)]);
+
+        assert!(decode(&encoded[..encoded.len() - 1]).is_err());
+        assert!(decode(&encoded[..FORWARD_AT_LEN]).is_err());
+    }
+
+    #[test]
+    fn only_the_earliest_packets_are_saved() {
+        let path = std::env::temp_dir().join(format!(
+            "nym-mixnode-delay-queue-{}.snapshot",
+            std::process::id()
+        ));
+        let snapshot = test_snapshot(path.clone(), 2);
+
+        let now = SystemTime::now();
+        let packets = vec![
+            (mix_packet(3), now + Duration::from_secs(3)),
+            (mix_packet(1), now + Duration::from_secs(1)),
+            (mix_packet(2), now + Duration::from_secs(2)),
+        ];
+        assert_eq!(snapshot.save(packets).unwrap(), 2);
+
+        let loaded = snapshot.load().unwrap();
+        let next_hops = loaded
+            .iter()
+            .map(|(packet, _)| packet.next_hop())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            next_hops,
+            vec![mix_packet(1).next_hop(), mix_packet(2).next_hop()]
+        );
+
+        // the snapshot is gone after it has been loaded
+        assert!(!path.exists());
+        assert!(snapshot.load().unwrap().is_empty());
+    }
+}
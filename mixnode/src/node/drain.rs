@@ -0,0 +1,102 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Describes the availability of the mixnode with regards to the graceful drain procedure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DrainState {
+    /// The node is operating normally.
+    Active,
+
+    /// The node has announced its imminent unavailability, but it is still accepting new packets.
+    Announced,
+
+    /// The node is no longer accepting new packets and is forwarding everything left in its delay queue.
+    Draining,
+
+    /// All delayed packets have been forwarded and the node is about to exit.
+    Drained,
+}
+
+impl DrainState {
+    /// Specifies whether the node is still willing to receive new packets.
+    pub(crate) fn is_accepting_packets(&self) -> bool {
+        matches!(self, DrainState::Active | DrainState::Announced)
+    }
+}
+
+/// Shared handle to the drain state of the node, used by all tasks that need to react to it.
+#[derive(Clone)]
+pub(crate) struct DrainController {
+    state_sender: Arc<watch::Sender<DrainState>>,
+    state_receiver: watch::Receiver<DrainState>,
+}
+
+impl DrainController {
+    pub(crate) fn new() -> Self {
+        let (state_sender, state_receiver) = watch::channel(DrainState::Active);
+        DrainController {
+            state_sender: Arc::new(state_sender),
+            state_receiver,
+        }
+    }
+
+    pub(crate) fn state(&self) -> DrainState {
+        *self.state_receiver.borrow()
+    }
+
+    /// Moves the node into the specified state, unless it has already progressed further.
+    fn advance(&self, new_state: DrainState) -> bool {
+        self.state_sender.send_if_modified(|state| {
+            if *state < new_state {
+                *state = new_state;
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    /// Requests the drain procedure to begin. Returns `false` if it has already been requested before.
+    pub(crate) fn request_drain(&self) -> bool {
+        self.advance(DrainState::Announced)
+    }
+
+    pub(crate) fn mark_drained(&self) {
+        self.advance(DrainState::Drained);
+    }
+
+    /// Waits until the node reaches (or goes past) the specified state.
+    pub(crate) async fn wait_for(&self, target: DrainState) {
+        let mut receiver = self.state_receiver.clone();
+        while *receiver.borrow_and_update() < target {
+            if receiver.changed().await.is_err() {
+                // this can't really happen since we're holding the sender ourselves,
+                // but if it somehow did, the state would never change again
+                futures::future::pending::<()>().await
+            }
+        }
+    }
+
+    /// Drives the drain procedure once it has been requested. The returned future resolves
+    /// once all delayed packets have been forwarded and it is safe for the node to exit.
+    pub(crate) async fn run(self, announcement_period: Duration) {
+        self.wait_for(DrainState::Announced).await;
+        info!(
+            "Drain has been requested. Announcing imminent unavailability for {}s before we stop accepting new packets",
+            announcement_period.as_secs()
+        );
+        tokio::time::sleep(announcement_period).await;
+
+        info!("No longer accepting new packets. Forwarding everything left in the delay queue...");
+        self.advance(DrainState::Draining);
+
+        self.wait_for(DrainState::Drained).await;
+        info!("All delayed packets have been forwarded");
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::node_statistics::SharedNodeStats;
+use log::{debug, trace, warn};
+use nym_crypto::asymmetric::identity;
+use nym_task::TaskClient;
+use nym_validator_client::models::{
+    HeartbeatNodeType, NodeHeartbeat, NodeHeartbeatStats, SignedNodeHeartbeat,
+};
+use nym_validator_client::NymApiClient;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Periodically announces to all nym-apis that the mixnode is alive, so that it would get
+/// promptly excluded from the network if it goes down.
+pub(crate) struct HeartbeatSender {
+    identity_keypair: Arc<identity::KeyPair>,
+    node_stats: SharedNodeStats,
+    nym_api_urls: Vec<Url>,
+    interval: Duration,
+
+    // packet totals reported in the previous heartbeat
+    previous_totals: NodeHeartbeatStats,
+    shutdown: TaskClient,
+}
+
+impl HeartbeatSender {
+    pub(crate) fn new(
+        identity_keypair: Arc<identity::KeyPair>,
+        node_stats: SharedNodeStats,
+        nym_api_urls: Vec<Url>,
+        interval: Duration,
+        shutdown: TaskClient,
+    ) -> Self {
+        HeartbeatSender {
+            identity_keypair,
+            node_stats,
+            nym_api_urls,
+            interval,
+            previous_totals: NodeHeartbeatStats::default(),
+            shutdown,
+        }
+    }
+
+    async fn current_stats(&mut self) -> NodeHeartbeatStats {
+        let stats = self.node_stats.clone_data().await.simplify();
+        let totals = NodeHeartbeatStats {
+            packets_received: stats.packets_received_since_startup(),
+            packets_sent: stats.packets_sent_since_startup(),
+            packets_dropped: stats.packets_explicitly_dropped_since_startup(),
+            connected_clients: 0,
+        };
+
+        let since_previous = NodeHeartbeatStats {
+            packets_received: totals
+                .packets_received
+                .saturating_sub(self.previous_totals.packets_received),
+            packets_sent: totals
+                .packets_sent
+                .saturating_sub(self.previous_totals.packets_sent),
+            packets_dropped: totals
+                .packets_dropped
+                .saturating_sub(self.previous_totals.packets_dropped),
+            connected_clients: 0,
+        };
+        self.previous_totals = totals;
+        since_previous
+    }
+
+    async fn send_heartbeat(&mut self) {
+        if self.nym_api_urls.is_empty() {
+            warn!("there are no nym-api endpoints to send the heartbeat to");
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let heartbeat = NodeHeartbeat {
+            identity_key: self.identity_keypair.public_key().to_base58_string(),
+            node_type: HeartbeatNodeType::Mixnode,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp,
+            stats: self.current_stats().await,
+        };
+        let signature = self
+            .identity_keypair
+            .private_key()
+            .sign(&heartbeat.signable_bytes())
+            .to_base58_string();
+
+        let signed = SignedNodeHeartbeat {
+            heartbeat,
+            signature,
+        };
+
+        // every nym-api tracks the liveness on its own, so all of them have to hear from us
+        for nym_api in &self.nym_api_urls {
+            let client = NymApiClient::new(nym_api.clone());
+            match client.send_node_heartbeat(&signed).await {
+                Ok(_) => debug!("sent heartbeat to {nym_api}"),
+                Err(err) => warn!("failed to send heartbeat to {nym_api}: {err}"),
+            }
+        }
+    }
+
+    pub(crate) async fn run(&mut self) {
+        let mut interval = tokio::time::interval(self.interval);
+        while !self.shutdown.is_shutdown() {
+            tokio::select! {
+                _ = interval.tick() => self.send_heartbeat().await,
+                _ = self.shutdown.recv() => {
+                    trace!("HeartbeatSender: Received shutdown");
+                }
+            }
+        }
+    }
+}
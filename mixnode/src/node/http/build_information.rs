@@ -0,0 +1,12 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_bin_common::build_information::BinaryBuildInformationOwned;
+use rocket::serde::json::Json;
+
+/// Provides the build information of this binary, including the versions of the wire protocols
+/// it speaks, so that incompatible nodes could be recognised.
+#[get("/build-information")]
+pub(crate) fn build_information() -> Json<BinaryBuildInformationOwned> {
+    Json(crate::BUILD_INFORMATION.to_owned())
+}
@@ -1,9 +1,21 @@
+use crate::node::http::signed::SignedResponse;
 use crate::node::node_description::NodeDescription;
+use nym_crypto::asymmetric::identity;
 use rocket::serde::json::Json;
 use rocket::State;
+use std::sync::Arc;
 
 /// Returns a description of the node and why someone might want to delegate stake to it.
 #[get("/description")]
 pub(crate) fn description(description: &State<NodeDescription>) -> Json<NodeDescription> {
     Json(description.inner().clone())
 }
+
+/// Returns the description of the node signed with its identity key.
+#[get("/description/signed")]
+pub(crate) fn signed_description(
+    description: &State<NodeDescription>,
+    identity_keypair: &State<Arc<identity::KeyPair>>,
+) -> Json<SignedResponse> {
+    Json(SignedResponse::new(identity_keypair, description.inner()))
+}
@@ -0,0 +1,54 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::drain::{DrainController, DrainState};
+use crate::node::http::local_guard::LocalRequest;
+use crate::node::http::signed::SignedResponse;
+use nym_crypto::asymmetric::identity;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+use std::sync::Arc;
+
+#[derive(Serialize)]
+pub(crate) struct PresenceResponse {
+    state: DrainState,
+    accepting_packets: bool,
+}
+
+impl From<DrainState> for PresenceResponse {
+    fn from(state: DrainState) -> Self {
+        PresenceResponse {
+            state,
+            accepting_packets: state.is_accepting_packets(),
+        }
+    }
+}
+
+/// Returns the presence flag of the node, i.e. whether it's about to become unavailable.
+#[get("/presence")]
+pub(crate) fn presence(drain: &State<DrainController>) -> Json<PresenceResponse> {
+    Json(drain.state().into())
+}
+
+/// Returns the presence flag of the node signed with its identity key.
+#[get("/presence/signed")]
+pub(crate) fn signed_presence(
+    drain: &State<DrainController>,
+    identity_keypair: &State<Arc<identity::KeyPair>>,
+) -> Json<SignedResponse> {
+    let presence: PresenceResponse = drain.state().into();
+    Json(SignedResponse::new(identity_keypair, &presence))
+}
+
+/// Begins the graceful drain procedure of the node. Only available from the local machine.
+#[post("/drain")]
+pub(crate) fn drain(
+    _local: LocalRequest,
+    controller: &State<DrainController>,
+) -> Json<PresenceResponse> {
+    if controller.request_drain() {
+        info!("Received a drain request");
+    }
+    Json(controller.state().into())
+}
@@ -1,5 +1,10 @@
+pub(crate) mod build_information;
 pub(crate) mod description;
+pub(crate) mod drain;
 pub(crate) mod hardware;
+pub(crate) mod local_guard;
+pub(crate) mod reload;
+pub(crate) mod signed;
 pub(crate) mod stats;
 pub(crate) mod verloc;
 
@@ -0,0 +1,32 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::http::local_guard::LocalRequest;
+use crate::node::reload::ConfigReloader;
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub(crate) struct ReloadResponse {
+    changed: bool,
+}
+
+/// Re-reads the config file and applies all values that can be changed without restarting the node.
+/// Only available from the local machine.
+#[post("/reload")]
+pub(crate) fn reload(
+    _local: LocalRequest,
+    reloader: &State<ConfigReloader>,
+) -> Result<Json<ReloadResponse>, Custom<String>> {
+    info!("Received a reload request");
+    match reloader.reload() {
+        Ok(changed) => Ok(Json(ReloadResponse { changed })),
+        Err(err) => {
+            error!("Failed to reload the configuration - {err}");
+            Err(Custom(Status::InternalServerError, err.to_string()))
+        }
+    }
+}
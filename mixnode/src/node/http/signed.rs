@@ -0,0 +1,71 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_crypto::asymmetric::identity;
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize)]
+struct SignedData<'a, T> {
+    identity_key: String,
+    timestamp: u64,
+    payload: &'a T,
+}
+
+/// Response signed with the identity key of the node, so that anyone querying it (such as the
+/// explorer) could verify it indeed originates from the node bonded with that key.
+#[derive(Serialize)]
+pub(crate) struct SignedResponse {
+    /// Json-encoded signed data, i.e. the identity key of the node, unix timestamp of
+    /// the signing and the actual payload.
+    data: String,
+
+    /// Base58-encoded ed25519 signature on the bytes of `data`.
+    signature: String,
+}
+
+impl SignedResponse {
+    pub(crate) fn new<T: Serialize>(identity_keypair: &identity::KeyPair, payload: &T) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+
+        let data = serde_json::to_string(&SignedData {
+            identity_key: identity_keypair.public_key().to_base58_string(),
+            timestamp,
+            payload,
+        })
+        .expect("signed response serialization can't fail");
+        let signature = identity_keypair
+            .private_key()
+            .sign(data.as_bytes())
+            .to_base58_string();
+
+        SignedResponse { data, signature }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_verifies_against_node_identity() {
+        let keypair = identity::KeyPair::new(&mut rand::thread_rng());
+        let response = SignedResponse::new(&keypair, &"hello");
+
+        let signature = identity::Signature::from_base58_string(&response.signature).unwrap();
+        assert!(keypair
+            .public_key()
+            .verify(response.data.as_bytes(), &signature)
+            .is_ok());
+
+        let data: serde_json::Value = serde_json::from_str(&response.data).unwrap();
+        assert_eq!(
+            data["identity_key"],
+            keypair.public_key().to_base58_string()
+        );
+        assert_eq!(data["payload"], "hello");
+    }
+}
@@ -1,21 +1,26 @@
 // Copyright 2020 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::node::drain::{DrainController, DrainState};
 use crate::node::listener::connection_handler::packet_processing::{
     MixProcessingResult, PacketProcessor,
 };
+use crate::node::listener::rate_limiting::{PacketVerdict, SourceRateLimiter};
 use crate::node::packet_delayforwarder::PacketDelayForwardSender;
 use crate::node::TaskClient;
 use futures::StreamExt;
+use nym_crypto::asymmetric::identity;
+use nym_metrics::Metrics;
+use nym_mixnode_common::handshake::accept_handshake;
+use nym_mixnode_common::listener_guard::{ConnectionGuard, ConnectionVerdict, ListenerGuardConfig};
 use nym_mixnode_common::measure;
 use nym_sphinx::forwarding::packet::MixPacket;
-use nym_sphinx::framing::codec::SphinxCodec;
+use nym_sphinx::framing::codec::SphinxCodecError;
 use nym_sphinx::framing::packet::FramedSphinxPacket;
 use nym_sphinx::Delay as SphinxDelay;
 use std::net::SocketAddr;
 use tokio::net::TcpStream;
 use tokio::time::Instant;
-use tokio_util::codec::Framed;
 #[cfg(feature = "cpucycles")]
 use tracing::{error, info, instrument};
 
@@ -25,16 +30,28 @@ pub(crate) mod packet_processing;
 pub(crate) struct ConnectionHandler {
     packet_processor: PacketProcessor,
     delay_forwarding_channel: PacketDelayForwardSender,
+    rate_limiter: SourceRateLimiter,
+    guard: ListenerGuardConfig,
+    identity: identity::PublicKey,
+    metrics: Metrics,
 }
 
 impl ConnectionHandler {
     pub(crate) fn new(
         packet_processor: PacketProcessor,
         delay_forwarding_channel: PacketDelayForwardSender,
+        rate_limiter: SourceRateLimiter,
+        guard: ListenerGuardConfig,
+        identity: identity::PublicKey,
+        metrics: Metrics,
     ) -> Self {
         ConnectionHandler {
             packet_processor,
             delay_forwarding_channel,
+            rate_limiter,
+            guard,
+            identity,
+            metrics,
         }
     }
 
@@ -61,6 +78,13 @@ impl ConnectionHandler {
         // question: can it also be per connection vs global?
         //
 
+        #[cfg(feature = "packet-tracing")]
+        log::info!(
+            target: nym_sphinx::params::packet_tracing::PACKET_TRACING_LOG_TARGET,
+            "[{}] mixnode received packet",
+            framed_sphinx_packet.trace_id()
+        );
+
         // all processing such, key caching, etc. was done.
         // however, if it was a forward hop, we still need to delay it
         measure!({
@@ -82,20 +106,84 @@ impl ConnectionHandler {
         self,
         conn: TcpStream,
         remote: SocketAddr,
+        drain: DrainController,
         mut shutdown: TaskClient,
     ) {
         debug!("Starting connection handler for {:?}", remote);
         shutdown.mark_as_success();
-        let mut framed_conn = Framed::new(conn, SphinxCodec);
+        let mut framed_conn = match accept_handshake(conn, &self.identity, &self.guard).await {
+            Ok(framed_conn) => framed_conn,
+            Err(err) => {
+                self.metrics.increment_counter("rejected_handshakes", 1);
+                warn!("Failed to complete the handshake with {remote}: {err}. Closing the socket");
+                return;
+            }
+        };
+        let Some(rate_limiter) = self
+            .rate_limiter
+            .register_connection(remote.ip(), Instant::now())
+        else {
+            debug!("{remote} got banned during the handshake - closing the connection");
+            return;
+        };
+        let mut connection_guard = ConnectionGuard::new(self.guard, Instant::now());
+        let mut guard_check = tokio::time::interval_at(
+            Instant::now() + connection_guard.check_interval(),
+            connection_guard.check_interval(),
+        );
         while !shutdown.is_shutdown() {
             tokio::select! {
                 biased;
                 _ = shutdown.recv() => {
                     log::trace!("ConnectionHandler: received shutdown");
                 }
+                _ = drain.wait_for(DrainState::Draining) => {
+                    log::trace!("ConnectionHandler: the node is draining");
+                    break;
+                }
+                _ = rate_limiter.banned() => {
+                    debug!("{remote} has been banned - closing the connection");
+                    break;
+                }
+                _ = guard_check.tick() => {
+                    let has_partial_frame = !framed_conn.read_buffer().is_empty();
+                    match connection_guard.check(has_partial_frame, Instant::now()) {
+                        ConnectionVerdict::Keep => (),
+                        ConnectionVerdict::Idle => {
+                            debug!("{remote} hasn't sent any packets in {:?} - closing the connection", self.guard.read_timeout);
+                            self.metrics.increment_counter("connections_closed_idle", 1);
+                            break;
+                        }
+                        ConnectionVerdict::Stalled => {
+                            warn!("{remote} hasn't completed its partial frame in {:?} - closing the connection", self.guard.frame_timeout);
+                            self.metrics.increment_counter("connections_closed_stalled", 1);
+                            break;
+                        }
+                    }
+                }
                 framed_sphinx_packet = framed_conn.next() => {
                     match framed_sphinx_packet {
                         Some(Ok(framed_sphinx_packet)) => {
+                            connection_guard.on_frame(Instant::now());
+
+                            // drop the excess packets before doing any processing so that a single
+                            // noisy peer wouldn't be able to flood our delay queue
+                            match rate_limiter.on_packet(Instant::now()) {
+                                PacketVerdict::Accept => (),
+                                PacketVerdict::Drop => {
+                                    log::trace!("Dropping packet from {remote} due to rate limiting");
+                                    continue;
+                                }
+                                PacketVerdict::Ban => {
+                                    warn!(
+                                        "{} has been exceeding its rate limit - banning it for {:?}",
+                                        remote.ip(),
+                                        self.rate_limiter.ban_duration()
+                                    );
+                                    break;
+                                }
+                            }
+
                             // TODO: benchmark spawning tokio task with full processing vs just processing it
                             // synchronously (without delaying inside of course,
                             // delay is moved to a global DelayQueue)
@@ -107,6 +195,11 @@ impl ConnectionHandler {
                             self.handle_received_packet(framed_sphinx_packet);
                         }
                         Some(Err(err)) => {
+                            if matches!(err, SphinxCodecError::FrameTooLarge { .. }) {
+                                self.metrics.increment_counter("rejected_frames_oversized", 1);
+                            } else {
+                                self.metrics.increment_counter("rejected_frames_malformed", 1);
+                            }
                             error!(
                                 "The socket connection got corrupted with error: {err}. Closing the socket",
                             );
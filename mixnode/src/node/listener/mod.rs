@@ -1,26 +1,42 @@
 // Copyright 2020 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::node::drain::{DrainController, DrainState};
 use crate::node::listener::connection_handler::ConnectionHandler;
+use crate::node::listener::rate_limiting::{SourceRateLimiter, SOURCES_PRUNING_INTERVAL};
 use std::net::SocketAddr;
 use std::process;
 use tokio::net::TcpListener;
 use tokio::task::JoinHandle;
+use tokio::time::Instant;
 #[cfg(feature = "cpucycles")]
 use tracing::error;
 
 use super::TaskClient;
 
 pub(crate) mod connection_handler;
+pub(crate) mod rate_limiting;
 
 pub(crate) struct Listener {
     address: SocketAddr,
+    drain: DrainController,
+    rate_limiter: SourceRateLimiter,
     shutdown: TaskClient,
 }
 
 impl Listener {
-    pub(crate) fn new(address: SocketAddr, shutdown: TaskClient) -> Self {
-        Listener { address, shutdown }
+    pub(crate) fn new(
+        address: SocketAddr,
+        drain: DrainController,
+        rate_limiter: SourceRateLimiter,
+        shutdown: TaskClient,
+    ) -> Self {
+        Listener {
+            address,
+            drain,
+            rate_limiter,
+            shutdown,
+        }
     }
 
     async fn run(&mut self, connection_handler: ConnectionHandler) {
@@ -33,17 +49,28 @@ impl Listener {
             }
         };
 
+        let mut pruning_interval = tokio::time::interval(SOURCES_PRUNING_INTERVAL);
         while !self.shutdown.is_shutdown() {
             tokio::select! {
                 biased;
                 _ = self.shutdown.recv() => {
                     log::trace!("Listener: Received shutdown");
                 }
+                _ = self.drain.wait_for(DrainState::Draining) => {
+                    info!("The node is draining - no longer accepting new connections");
+                    break;
+                }
+                _ = pruning_interval.tick() => {
+                    self.rate_limiter.prune_stale_sources(Instant::now());
+                }
                 connection = listener.accept() => {
                     match connection {
+                        Ok((_socket, remote_addr)) if self.rate_limiter.is_banned(remote_addr.ip(), Instant::now()) => {
+                            debug!("Rejecting connection from banned source {remote_addr}");
+                        }
                         Ok((socket, remote_addr)) => {
                             let handler = connection_handler.clone();
-                            tokio::spawn(handler.handle_connection(socket, remote_addr, self.shutdown.clone()));
+                            tokio::spawn(handler.handle_connection(socket, remote_addr, self.drain.clone(), self.shutdown.clone()));
                         }
                         Err(err) => warn!("Failed to accept incoming connection - {err}"),
                     }
@@ -0,0 +1,387 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::{CancellationToken, WaitForCancellationFuture};
+
+/// Period over which the dropped packets are counted towards the ban threshold.
+const VIOLATION_WINDOW: Duration = Duration::from_secs(10);
+
+/// Interval between subsequent removals of the sources that no longer have to be tracked.
+pub(crate) const SOURCES_PRUNING_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RateLimitingConfig {
+    /// Maximum number of packets a single source can send in a burst.
+    pub(crate) packet_burst: u32,
+
+    /// Sustained number of packets per second a single source can send.
+    pub(crate) packet_rate: u32,
+
+    /// Number of packets dropped within the violation window after which the source gets banned.
+    pub(crate) ban_threshold: u32,
+
+    /// Duration for which the misbehaving source is not allowed to connect.
+    pub(crate) ban_duration: Duration,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_rate: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, refill_rate: u32, now: Instant) -> Self {
+        TokenBucket {
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            refill_rate: refill_rate as f64,
+            last_refill: now,
+        }
+    }
+
+    fn is_full(&self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens + elapsed.as_secs_f64() * self.refill_rate >= self.capacity
+    }
+
+    fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.refill_rate).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum PacketVerdict {
+    Accept,
+    Drop,
+    Ban,
+}
+
+/// Rate limiting state shared by all the connections from the same source.
+#[derive(Debug)]
+struct SourceState {
+    bucket: TokenBucket,
+    violations: u32,
+    window_start: Instant,
+    connections: usize,
+    banned_until: Option<Instant>,
+
+    /// Cancelled once the source gets banned, so that all of its connections would get closed.
+    ban: CancellationToken,
+}
+
+impl SourceState {
+    fn new(config: &RateLimitingConfig, now: Instant) -> Self {
+        SourceState {
+            bucket: TokenBucket::new(config.packet_burst, config.packet_rate, now),
+            violations: 0,
+            window_start: now,
+            connections: 0,
+            banned_until: None,
+            ban: CancellationToken::new(),
+        }
+    }
+
+    fn is_banned(&self, now: Instant) -> bool {
+        matches!(self.banned_until, Some(banned_until) if banned_until > now)
+    }
+
+    // the state can only be forgotten if doing so wouldn't give the source any extra allowance
+    fn is_stale(&self, now: Instant) -> bool {
+        self.connections == 0
+            && !self.is_banned(now)
+            && self.bucket.is_full(now)
+            && now.saturating_duration_since(self.window_start) > VIOLATION_WINDOW
+    }
+}
+
+/// Rate limiter of the incoming connections. All the connections coming from the same
+/// source share the same limits, so that opening more of them doesn't give it any extra allowance.
+#[derive(Debug, Clone)]
+pub(crate) struct SourceRateLimiter {
+    config: RateLimitingConfig,
+    sources: Arc<Mutex<HashMap<IpAddr, SourceState>>>,
+}
+
+impl SourceRateLimiter {
+    pub(crate) fn new(config: RateLimitingConfig) -> Self {
+        SourceRateLimiter {
+            config,
+            sources: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn sources(&self) -> MutexGuard<'_, HashMap<IpAddr, SourceState>> {
+        self.sources
+            .lock()
+            .expect("rate limiting sources lock got poisoned")
+    }
+
+    pub(crate) fn ban_duration(&self) -> Duration {
+        self.config.ban_duration
+    }
+
+    pub(crate) fn is_banned(&self, source: IpAddr, now: Instant) -> bool {
+        matches!(self.sources().get(&source), Some(state) if state.is_banned(now))
+    }
+
+    /// Gets rid of all the sources we no longer have to track, i.e. the ones without any
+    /// connections whose allowance has been fully restored. Meant to be called periodically
+    /// rather than on every new connection so that a flood of them wouldn't be any costlier.
+    pub(crate) fn prune_stale_sources(&self, now: Instant) {
+        self.sources().retain(|_, state| !state.is_stale(now))
+    }
+
+    /// Registers new connection from the source. Returns `None` if the source is currently banned.
+    pub(crate) fn register_connection(
+        &self,
+        source: IpAddr,
+        now: Instant,
+    ) -> Option<SourceConnection> {
+        let mut guard = self.sources();
+        let state = guard
+            .entry(source)
+            .or_insert_with(|| SourceState::new(&self.config, now));
+        if state.is_banned(now) {
+            return None;
+        }
+        if state.ban.is_cancelled() {
+            // the previous ban has expired
+            state.ban = CancellationToken::new();
+        }
+        state.connections += 1;
+
+        Some(SourceConnection {
+            limiter: self.clone(),
+            source,
+            ban: state.ban.clone(),
+        })
+    }
+
+    fn unregister_connection(&self, source: IpAddr) {
+        if let Some(state) = self.sources().get_mut(&source) {
+            state.connections = state.connections.saturating_sub(1);
+        }
+    }
+
+    fn on_packet(&self, source: IpAddr, now: Instant) -> PacketVerdict {
+        let mut guard = self.sources();
+        let Some(state) = guard.get_mut(&source) else {
+            // this can't happen as the state is kept for as long as there are any connections
+            return PacketVerdict::Drop;
+        };
+        if state.is_banned(now) {
+            // the connection is about to get closed anyway
+            return PacketVerdict::Drop;
+        }
+        if state.bucket.try_take(now) {
+            return PacketVerdict::Accept;
+        }
+
+        if now.saturating_duration_since(state.window_start) > VIOLATION_WINDOW {
+            state.window_start = now;
+            state.violations = 0;
+        }
+        state.violations += 1;
+
+        if state.violations >= self.config.ban_threshold {
+            state.violations = 0;
+            state.banned_until = Some(now + self.config.ban_duration);
+            state.ban.cancel();
+            PacketVerdict::Ban
+        } else {
+            PacketVerdict::Drop
+        }
+    }
+}
+
+/// Connection registered with the rate limiter. It's unregistered once dropped.
+#[derive(Debug)]
+pub(crate) struct SourceConnection {
+    limiter: SourceRateLimiter,
+    source: IpAddr,
+    ban: CancellationToken,
+}
+
+impl SourceConnection {
+    pub(crate) fn on_packet(&self, now: Instant) -> PacketVerdict {
+        self.limiter.on_packet(self.source, now)
+    }
+
+    /// Resolves once the source gets banned, be it due to this or any other of its connections.
+    pub(crate) fn banned(&self) -> WaitForCancellationFuture<'_> {
+        self.ban.cancelled()
+    }
+}
+
+impl Drop for SourceConnection {
+    fn drop(&mut self) {
+        self.limiter.unregister_connection(self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> RateLimitingConfig {
+        RateLimitingConfig {
+            packet_burst: 10,
+            packet_rate: 5,
+            ban_threshold: 20,
+            ban_duration: Duration::from_secs(60),
+        }
+    }
+
+    fn test_source() -> IpAddr {
+        "1.2.3.4".parse().unwrap()
+    }
+
+    #[test]
+    fn allows_bursts_up_to_the_limit() {
+        let now = Instant::now();
+        let limiter = SourceRateLimiter::new(test_config());
+        let conn = limiter.register_connection(test_source(), now).unwrap();
+        for _ in 0..10 {
+            assert_eq!(conn.on_packet(now), PacketVerdict::Accept);
+        }
+        assert_eq!(conn.on_packet(now), PacketVerdict::Drop);
+    }
+
+    #[test]
+    fn refills_at_the_sustained_rate() {
+        let now = Instant::now();
+        let limiter = SourceRateLimiter::new(test_config());
+        let conn = limiter.register_connection(test_source(), now).unwrap();
+        for _ in 0..10 {
+            conn.on_packet(now);
+        }
+
+        let later = now + Duration::from_secs(1);
+        for _ in 0..5 {
+            assert_eq!(conn.on_packet(later), PacketVerdict::Accept);
+        }
+        assert_eq!(conn.on_packet(later), PacketVerdict::Drop);
+
+        // the bucket never goes above its capacity
+        let much_later = later + Duration::from_secs(3600);
+        for _ in 0..10 {
+            assert_eq!(conn.on_packet(much_later), PacketVerdict::Accept);
+        }
+        assert_eq!(conn.on_packet(much_later), PacketVerdict::Drop);
+    }
+
+    #[test]
+    fn connections_from_the_same_source_share_the_limit() {
+        let now = Instant::now();
+        let limiter = SourceRateLimiter::new(test_config());
+        let first = limiter.register_connection(test_source(), now).unwrap();
+        let second = limiter.register_connection(test_source(), now).unwrap();
+        for _ in 0..5 {
+            assert_eq!(first.on_packet(now), PacketVerdict::Accept);
+            assert_eq!(second.on_packet(now), PacketVerdict::Accept);
+        }
+        assert_eq!(first.on_packet(now), PacketVerdict::Drop);
+        assert_eq!(second.on_packet(now), PacketVerdict::Drop);
+
+        // reconnecting doesn't reset the limit either
+        drop(first);
+        drop(second);
+        let third = limiter.register_connection(test_source(), now).unwrap();
+        assert_eq!(third.on_packet(now), PacketVerdict::Drop);
+
+        // while other sources are unaffected
+        let other = limiter
+            .register_connection("5.6.7.8".parse().unwrap(), now)
+            .unwrap();
+        assert_eq!(other.on_packet(now), PacketVerdict::Accept);
+    }
+
+    #[test]
+    fn bans_egregious_violators() {
+        let now = Instant::now();
+        let limiter = SourceRateLimiter::new(test_config());
+        let conn = limiter.register_connection(test_source(), now).unwrap();
+        for _ in 0..10 {
+            conn.on_packet(now);
+        }
+        for _ in 0..19 {
+            assert_eq!(conn.on_packet(now), PacketVerdict::Drop);
+        }
+        assert_eq!(conn.on_packet(now), PacketVerdict::Ban);
+    }
+
+    #[test]
+    fn violations_are_forgotten_after_the_window() {
+        let now = Instant::now();
+        let limiter = SourceRateLimiter::new(test_config());
+        let conn = limiter.register_connection(test_source(), now).unwrap();
+        for _ in 0..29 {
+            conn.on_packet(now);
+        }
+
+        // refill happens at the same time, so exhaust the bucket again first
+        let later = now + VIOLATION_WINDOW + Duration::from_secs(1);
+        for _ in 0..10 {
+            conn.on_packet(later);
+        }
+        assert_eq!(conn.on_packet(later), PacketVerdict::Drop);
+    }
+
+    #[tokio::test]
+    async fn ban_closes_all_connections_of_the_source() {
+        let now = Instant::now();
+        let limiter = SourceRateLimiter::new(test_config());
+        let offender = limiter.register_connection(test_source(), now).unwrap();
+        let other = limiter.register_connection(test_source(), now).unwrap();
+        for _ in 0..29 {
+            offender.on_packet(now);
+        }
+        assert_eq!(offender.on_packet(now), PacketVerdict::Ban);
+
+        tokio::time::timeout(Duration::from_secs(1), other.banned())
+            .await
+            .expect("the other connection was not cancelled");
+        assert!(limiter
+            .register_connection(test_source(), now + Duration::from_secs(1))
+            .is_none());
+    }
+
+    #[test]
+    fn bans_expire() {
+        let now = Instant::now();
+        let limiter = SourceRateLimiter::new(test_config());
+        let conn = limiter.register_connection(test_source(), now).unwrap();
+        for _ in 0..30 {
+            conn.on_packet(now);
+        }
+        drop(conn);
+
+        assert!(limiter.is_banned(test_source(), now + Duration::from_secs(59)));
+        assert!(!limiter.is_banned(test_source(), now + Duration::from_secs(61)));
+
+        limiter.prune_stale_sources(now + Duration::from_secs(61));
+        assert!(limiter.sources().is_empty());
+
+        let conn = limiter
+            .register_connection(test_source(), now + Duration::from_secs(61))
+            .unwrap();
+        assert!(!conn.ban.is_cancelled());
+    }
+}
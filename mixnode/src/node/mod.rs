@@ -3,25 +3,39 @@
 
 use crate::config::persistence::pathfinder::MixNodePathfinder;
 use crate::config::Config;
+use crate::node::delay_queue_snapshot::DelayQueueSnapshot;
+use crate::node::drain::DrainController;
+use crate::node::heartbeat::HeartbeatSender;
 use crate::node::http::{
-    description::description,
+    build_information::build_information,
+    description::{description, signed_description},
+    drain::{drain as drainRoute, presence, signed_presence},
     hardware::hardware,
     not_found,
+    reload::reload as reloadRoute,
     stats::stats,
     verloc::{verloc as verlocRoute, VerlocState},
 };
 use crate::node::listener::connection_handler::packet_processing::PacketProcessor;
 use crate::node::listener::connection_handler::ConnectionHandler;
+use crate::node::listener::rate_limiting::{RateLimitingConfig, SourceRateLimiter};
 use crate::node::listener::Listener;
 use crate::node::node_description::NodeDescription;
 use crate::node::node_statistics::SharedNodeStats;
 use crate::node::packet_delayforwarder::{DelayForwarder, PacketDelayForwardSender};
+use crate::node::reload::ConfigReloader;
 use nym_bin_common::output_format::OutputFormat;
 use nym_bin_common::version_checker::parse_version;
 use nym_config::NymConfig;
 use nym_crypto::asymmetric::{encryption, identity};
+use nym_metrics::Metrics;
+use nym_mixnet_contract_common::{
+    construct_mixnode_bonding_sign_payload, MixNode as ContractMixNode, MixNodeCostParams,
+};
 use nym_mixnode_common::verloc::{self, AtomicVerlocResult, VerlocMeasurer};
 use nym_task::{TaskClient, TaskManager};
+use nym_types::helpers::{BondingRequest, BondingSignature};
+use nym_types::mixnode::MixnodeBondingInformation;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use std::net::SocketAddr;
@@ -30,11 +44,15 @@ use std::sync::Arc;
 #[cfg(feature = "cpucycles")]
 use tracing::{error, info, warn};
 
+mod delay_queue_snapshot;
+mod drain;
+mod heartbeat;
 mod http;
 mod listener;
 pub(crate) mod node_description;
 mod node_statistics;
 mod packet_delayforwarder;
+mod reload;
 
 // the MixNode will live for whole duration of this program
 pub struct MixNode {
@@ -100,10 +118,62 @@ impl MixNode {
         println!("{}", output.format(&node_details));
     }
 
+    /// Prints the information required for bonding this node. If the details of the bonding
+    /// transaction are provided, it also includes the signature on the bonding message
+    /// the mixnet contract expects.
+    pub(crate) fn print_bonding_information(
+        &self,
+        cost_params: MixNodeCostParams,
+        bonding_request: Option<BondingRequest>,
+        output: OutputFormat,
+    ) {
+        let mix_node = ContractMixNode {
+            host: self.config.get_announce_address(),
+            mix_port: self.config.get_mix_port(),
+            verloc_port: self.config.get_verloc_port(),
+            http_api_port: self.config.get_http_api_port(),
+            sphinx_key: self.sphinx_keypair.public_key().to_base58_string(),
+            identity_key: self.identity_keypair.public_key().to_base58_string(),
+            version: self.config.get_version().to_string(),
+        };
+        let bonding_signature = bonding_request.map(|request| {
+            let payload = construct_mixnode_bonding_sign_payload(
+                request.signing_nonce,
+                request.wallet_address.clone(),
+                request.proxy.clone(),
+                request.pledge.clone(),
+                mix_node.clone(),
+                cost_params.clone(),
+            );
+            // the payload consists of our own well-formed types, so its serialization can't fail
+            let plaintext = payload
+                .to_plaintext()
+                .expect("failed to serialize the bonding payload");
+            BondingSignature {
+                request,
+                signature: self
+                    .identity_keypair
+                    .private_key()
+                    .sign(&plaintext)
+                    .to_base58_string(),
+            }
+        });
+
+        let bonding_information = MixnodeBondingInformation {
+            mix_node,
+            cost_params,
+            bonding_signature,
+        };
+
+        println!("{}", output.format(&bonding_information));
+    }
+
     fn start_http_api(
         &self,
         atomic_verloc_result: AtomicVerlocResult,
         node_stats_pointer: SharedNodeStats,
+        drain_controller: DrainController,
+        config_reloader: ConfigReloader,
     ) {
         info!("Starting HTTP API on http://localhost:8000");
 
@@ -115,30 +185,64 @@ impl MixNode {
 
         let verloc_state = VerlocState::new(atomic_verloc_result);
         let descriptor = self.descriptor.clone();
+        let identity_keypair = Arc::clone(&self.identity_keypair);
 
         tokio::spawn(async move {
             rocket::build()
                 .configure(config)
-                .mount("/", routes![verlocRoute, description, stats, hardware])
+                .mount(
+                    "/",
+                    routes![
+                        verlocRoute,
+                        description,
+                        signed_description,
+                        stats,
+                        hardware,
+                        presence,
+                        signed_presence,
+                        drainRoute,
+                        reloadRoute,
+                        build_information
+                    ],
+                )
                 .register("/", catchers![not_found])
                 .manage(verloc_state)
                 .manage(descriptor)
+                .manage(identity_keypair)
                 .manage(node_stats_pointer)
+                .manage(drain_controller)
+                .manage(config_reloader)
                 .launch()
                 .await
         });
     }
 
+    fn start_config_reloader(&self, shutdown: TaskClient) -> ConfigReloader {
+        info!("Starting config reloader...");
+        let config_reloader = ConfigReloader::new(&self.config);
+        tokio::spawn(config_reloader.clone().run(shutdown));
+        config_reloader
+    }
+
+    async fn start_metrics_exporter(&self, shutdown: TaskClient) -> Metrics {
+        match nym_metrics::start_metrics(self.config.get_metrics_config(), shutdown).await {
+            Ok(metrics) => metrics,
+            Err(err) => {
+                error!("Failed to start the metrics exporter - {err}. The metrics are not going to be exported");
+                Metrics::disabled()
+            }
+        }
+    }
+
     fn start_node_stats_controller(
         &self,
+        config_reloader: &ConfigReloader,
+        metrics: Metrics,
         shutdown: TaskClient,
     ) -> (SharedNodeStats, node_statistics::UpdateSender) {
         info!("Starting node stats controller...");
-        let controller = node_statistics::Controller::new(
-            self.config.get_node_stats_logging_delay(),
-            self.config.get_node_stats_updating_delay(),
-            shutdown,
-        );
+        let controller =
+            node_statistics::Controller::new(config_reloader.subscribe(), metrics, shutdown);
         let node_stats_pointer = controller.get_node_stats_data_pointer();
         let update_sender = controller.start();
 
@@ -149,6 +253,8 @@ impl MixNode {
         &self,
         node_stats_update_sender: node_statistics::UpdateSender,
         delay_forwarding_channel: PacketDelayForwardSender,
+        metrics: Metrics,
+        drain_controller: DrainController,
         shutdown: TaskClient,
     ) {
         info!("Starting socket listener...");
@@ -156,19 +262,35 @@ impl MixNode {
         let packet_processor =
             PacketProcessor::new(self.sphinx_keypair.private_key(), node_stats_update_sender);
 
-        let connection_handler = ConnectionHandler::new(packet_processor, delay_forwarding_channel);
+        let rate_limiter = SourceRateLimiter::new(RateLimitingConfig {
+            packet_burst: self.config.get_maximum_connection_packet_burst(),
+            packet_rate: self.config.get_maximum_connection_packet_rate(),
+            ban_threshold: self.config.get_source_ban_threshold(),
+            ban_duration: self.config.get_source_ban_duration(),
+        });
+
+        let connection_handler = ConnectionHandler::new(
+            packet_processor,
+            delay_forwarding_channel,
+            rate_limiter.clone(),
+            self.config.get_listener_guard_config(),
+            *self.identity_keypair.public_key(),
+            metrics,
+        );
 
         let listening_address = SocketAddr::new(
             self.config.get_listening_address(),
             self.config.get_mix_port(),
         );
 
-        Listener::new(listening_address, shutdown).start(connection_handler);
+        Listener::new(listening_address, drain_controller, rate_limiter, shutdown)
+            .start(connection_handler);
     }
 
     fn start_packet_delay_forwarder(
         &mut self,
         node_stats_update_sender: node_statistics::UpdateSender,
+        drain_controller: DrainController,
         shutdown: TaskClient,
     ) -> PacketDelayForwardSender {
         info!("Starting packet delay-forwarder...");
@@ -184,8 +306,18 @@ impl MixNode {
         let mut packet_forwarder = DelayForwarder::new(
             nym_mixnet_client::Client::new(client_config),
             node_stats_update_sender,
+            self.config.get_maximum_forwarding_delay(),
+            drain_controller,
             shutdown,
         );
+        if self.config.get_persist_delay_queue() {
+            packet_forwarder = packet_forwarder.with_snapshot(DelayQueueSnapshot::new(
+                self.config.get_delay_queue_snapshot_file(),
+                self.config.get_maximum_persisted_packets(),
+                self.config.get_maximum_restored_packet_lateness(),
+                self.config.get_overdue_restored_packets_spread(),
+            ));
+        }
 
         let packet_sender = packet_forwarder.sender();
 
@@ -233,6 +365,24 @@ impl MixNode {
         atomic_verloc_results
     }
 
+    fn start_heartbeat_sender(&self, node_stats_pointer: SharedNodeStats, shutdown: TaskClient) {
+        let heartbeat_interval = self.config.get_heartbeat_interval();
+        if heartbeat_interval.is_zero() {
+            info!("Heartbeats are disabled");
+            return;
+        }
+
+        info!("Starting the heartbeat sender...");
+        let mut heartbeat_sender = HeartbeatSender::new(
+            Arc::clone(&self.identity_keypair),
+            node_stats_pointer,
+            self.config.get_nym_api_endpoints(),
+            heartbeat_interval,
+            shutdown,
+        );
+        tokio::spawn(async move { heartbeat_sender.run().await });
+    }
+
     fn random_api_client(&self) -> nym_validator_client::NymApiClient {
         let endpoints = self.config.get_nym_api_endpoints();
         let nym_api = endpoints
@@ -266,9 +416,19 @@ impl MixNode {
             .map(|node| node.bond_information.mix_node.identity_key.clone())
     }
 
-    async fn wait_for_interrupt(&self, shutdown: TaskManager) {
-        let _res = shutdown.catch_interrupt().await;
+    async fn wait_for_interrupt_or_drain(
+        &self,
+        mut shutdown: TaskManager,
+        drain_controller: DrainController,
+    ) {
+        tokio::select! {
+            _ = nym_task::wait_for_signal_and_error(&mut shutdown) => (),
+            _ = drain_controller.run(self.config.get_drain_announcement_period()) => (),
+        }
+
         log::info!("Stopping nym mixnode");
+        shutdown.signal_shutdown().ok();
+        shutdown.wait_for_shutdown().await;
     }
 
     pub async fn run(&mut self) {
@@ -287,24 +447,42 @@ impl MixNode {
         }
 
         let shutdown = TaskManager::default();
+        let drain_controller = DrainController::new();
+        let config_reloader = self.start_config_reloader(shutdown.subscribe());
+        let metrics = self.start_metrics_exporter(shutdown.subscribe()).await;
 
-        let (node_stats_pointer, node_stats_update_sender) =
-            self.start_node_stats_controller(shutdown.subscribe());
-        let delay_forwarding_channel = self
-            .start_packet_delay_forwarder(node_stats_update_sender.clone(), shutdown.subscribe());
+        let (node_stats_pointer, node_stats_update_sender) = self.start_node_stats_controller(
+            &config_reloader,
+            metrics.clone(),
+            shutdown.subscribe(),
+        );
+        let delay_forwarding_channel = self.start_packet_delay_forwarder(
+            node_stats_update_sender.clone(),
+            drain_controller.clone(),
+            shutdown.subscribe(),
+        );
         self.start_socket_listener(
             node_stats_update_sender,
             delay_forwarding_channel,
+            metrics,
+            drain_controller.clone(),
             shutdown.subscribe(),
         );
         let atomic_verloc_results = self.start_verloc_measurements(shutdown.subscribe());
+        self.start_heartbeat_sender(node_stats_pointer.clone(), shutdown.subscribe());
 
         // Rocket handles shutdown on it's own, but its shutdown handling should be incorporated
         // with that of the rest of the tasks.
         // Currently it's runtime is forcefully terminated once the mixnode exits.
-        self.start_http_api(atomic_verloc_results, node_stats_pointer);
+        self.start_http_api(
+            atomic_verloc_results,
+            node_stats_pointer,
+            drain_controller.clone(),
+            config_reloader,
+        );
 
         info!("Finished nym mixnode startup procedure - it should now be able to receive mix traffic!");
-        self.wait_for_interrupt(shutdown).await
+        self.wait_for_interrupt_or_drain(shutdown, drain_controller)
+            .await
     }
 }
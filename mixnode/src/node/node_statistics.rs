@@ -1,14 +1,16 @@
 use futures::channel::mpsc;
 use futures::lock::Mutex;
 use futures::StreamExt;
+use nym_metrics::Metrics;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::ops::DerefMut;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
-use tokio::sync::{RwLock, RwLockReadGuard};
+use std::time::SystemTime;
+use tokio::sync::{watch, RwLock, RwLockReadGuard};
 
+use super::reload::ReloadableConfig;
 use super::TaskClient;
 
 // convenience aliases
@@ -150,6 +152,20 @@ pub(crate) struct NodeStatsSimple {
     packets_explicitly_dropped_since_last_update: u64,
 }
 
+impl NodeStatsSimple {
+    pub(crate) fn packets_received_since_startup(&self) -> u64 {
+        self.packets_received_since_startup
+    }
+
+    pub(crate) fn packets_sent_since_startup(&self) -> u64 {
+        self.packets_sent_since_startup
+    }
+
+    pub(crate) fn packets_explicitly_dropped_since_startup(&self) -> u64 {
+        self.packets_explicitly_dropped_since_startup
+    }
+}
+
 pub(crate) enum PacketEvent {
     Sent(String),
     Received,
@@ -290,23 +306,26 @@ impl UpdateSender {
 // Worker that periodically updates the shared node stats from the current packet data buffer that
 // the `UpdateHandler` updates.
 struct StatsUpdater {
-    updating_delay: Duration,
+    settings: watch::Receiver<ReloadableConfig>,
     current_packet_data: CurrentPacketData,
     current_stats: SharedNodeStats,
+    metrics: Metrics,
     shutdown: TaskClient,
 }
 
 impl StatsUpdater {
     fn new(
-        updating_delay: Duration,
+        settings: watch::Receiver<ReloadableConfig>,
         current_packet_data: CurrentPacketData,
         current_stats: SharedNodeStats,
+        metrics: Metrics,
         shutdown: TaskClient,
     ) -> Self {
         StatsUpdater {
-            updating_delay,
+            settings,
             current_packet_data,
             current_stats,
+            metrics,
             shutdown,
         }
     }
@@ -314,13 +333,22 @@ impl StatsUpdater {
     async fn update_stats(&self) {
         // grab new data since last update
         let (received, sent, dropped) = self.current_packet_data.acquire_and_reset().await;
+
+        self.metrics.increment_counter("packets_received", received);
+        self.metrics
+            .increment_counter("packets_sent", sent.values().sum());
+        self.metrics
+            .increment_counter("packets_dropped", dropped.values().sum());
+
         self.current_stats.update(received, sent, dropped).await;
     }
 
     async fn run(&mut self) {
         while !self.shutdown.is_shutdown() {
+            // the delay might have been changed by a config reload
+            let updating_delay = self.settings.borrow().node_stats_updating_delay;
             tokio::select! {
-                _ = tokio::time::sleep(self.updating_delay) => self.update_stats().await,
+                _ = tokio::time::sleep(updating_delay) => self.update_stats().await,
                 _ = self.shutdown.recv() => {
                     log::trace!("StatsUpdater: Received shutdown");
                 }
@@ -333,15 +361,19 @@ impl StatsUpdater {
 // TODO: question: should this data still be logged to the console or should we perhaps remove it
 // since we have the http endpoint now?
 struct PacketStatsConsoleLogger {
-    logging_delay: Duration,
+    settings: watch::Receiver<ReloadableConfig>,
     stats: SharedNodeStats,
     shutdown: TaskClient,
 }
 
 impl PacketStatsConsoleLogger {
-    fn new(logging_delay: Duration, stats: SharedNodeStats, shutdown: TaskClient) -> Self {
+    fn new(
+        settings: watch::Receiver<ReloadableConfig>,
+        stats: SharedNodeStats,
+        shutdown: TaskClient,
+    ) -> Self {
         PacketStatsConsoleLogger {
-            logging_delay,
+            settings,
             stats,
             shutdown,
         }
@@ -418,8 +450,10 @@ impl PacketStatsConsoleLogger {
     async fn run(&mut self) {
         log::trace!("Starting PacketStatsConsoleLogger");
         while !self.shutdown.is_shutdown() {
+            // the delay might have been changed by a config reload
+            let logging_delay = self.settings.borrow().node_stats_logging_delay;
             tokio::select! {
-                _ = tokio::time::sleep(self.logging_delay) => self.log_running_stats().await,
+                _ = tokio::time::sleep(logging_delay) => self.log_running_stats().await,
                 _ = self.shutdown.recv() => {
                     log::trace!("PacketStatsConsoleLogger: Received shutdown");
                 }
@@ -449,8 +483,8 @@ pub struct Controller {
 
 impl Controller {
     pub(crate) fn new(
-        logging_delay: Duration,
-        stats_updating_delay: Duration,
+        settings: watch::Receiver<ReloadableConfig>,
+        metrics: Metrics,
         shutdown: TaskClient,
     ) -> Self {
         let (sender, receiver) = mpsc::unbounded();
@@ -465,14 +499,15 @@ impl Controller {
             ),
             update_sender: UpdateSender::new(sender),
             console_logger: PacketStatsConsoleLogger::new(
-                logging_delay,
+                settings.clone(),
                 shared_node_stats.clone(),
                 shutdown.clone(),
             ),
             stats_updater: StatsUpdater::new(
-                stats_updating_delay,
+                settings,
                 shared_packet_data,
                 shared_node_stats.clone(),
+                metrics,
                 shutdown,
             ),
             node_stats: shared_node_stats,
@@ -504,14 +539,18 @@ impl Controller {
 mod tests {
     use super::*;
     use nym_task::TaskManager;
+    use std::time::Duration;
 
     #[tokio::test]
     async fn node_stats_reported_are_received() {
-        let logging_delay = Duration::from_millis(20);
-        let stats_updating_delay = Duration::from_millis(10);
+        let (_settings_sender, settings) = watch::channel(ReloadableConfig {
+            log_level: None,
+            node_stats_logging_delay: Duration::from_millis(20),
+            node_stats_updating_delay: Duration::from_millis(10),
+        });
         let shutdown = TaskManager::default();
         let node_stats_controller =
-            Controller::new(logging_delay, stats_updating_delay, shutdown.subscribe());
+            Controller::new(settings, Metrics::disabled(), shutdown.subscribe());
 
         let node_stats_pointer = node_stats_controller.get_node_stats_data_pointer();
         let update_sender = node_stats_controller.start();
@@ -1,12 +1,17 @@
 // Copyright 2020 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::node::delay_queue_snapshot::DelayQueueSnapshot;
+use crate::node::drain::{DrainController, DrainState};
 use crate::node::node_statistics::UpdateSender;
 use futures::channel::mpsc;
 use futures::StreamExt;
-use nym_nonexhaustive_delayqueue::{Expired, NonExhaustiveDelayQueue};
+use nym_mixnet_client::DeadlineSendError;
+use nym_nonexhaustive_delayqueue::{Expired, NonExhaustiveDelayQueue, QueueKey};
 use nym_sphinx::forwarding::packet::MixPacket;
+use std::collections::HashSet;
 use std::io;
+use std::time::{Duration, SystemTime};
 use tokio::time::Instant;
 
 use super::TaskClient;
@@ -28,7 +33,18 @@ where
     packet_sender: PacketDelayForwardSender,
     packet_receiver: PacketDelayForwardReceiver,
     node_stats_update_sender: UpdateSender,
+    drain: DrainController,
     shutdown: TaskClient,
+
+    /// Maximum duration a packet can wait for the connection to its next hop to be re-established.
+    maximum_forwarding_delay: Duration,
+
+    /// If set, packets still waiting in the delay queue upon shutdown are persisted
+    /// and restored on the next start.
+    snapshot: Option<DelayQueueSnapshot>,
+
+    /// Keys of the packets currently in the delay queue. Only tracked if the snapshot is set.
+    queued_keys: HashSet<QueueKey>,
 }
 
 impl<C> DelayForwarder<C>
@@ -38,6 +54,8 @@ where
     pub(crate) fn new(
         client: C,
         node_stats_update_sender: UpdateSender,
+        maximum_forwarding_delay: Duration,
+        drain: DrainController,
         shutdown: TaskClient,
     ) -> DelayForwarder<C> {
         let (packet_sender, packet_receiver) = mpsc::unbounded();
@@ -48,42 +66,69 @@ where
             packet_sender,
             packet_receiver,
             node_stats_update_sender,
+            drain,
             shutdown,
+            maximum_forwarding_delay,
+            snapshot: None,
+            queued_keys: HashSet::new(),
         }
     }
 
+    pub(crate) fn with_snapshot(mut self, snapshot: DelayQueueSnapshot) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
     pub(crate) fn sender(&self) -> PacketDelayForwardSender {
         self.packet_sender.clone()
     }
 
     fn forward_packet(&mut self, packet: MixPacket) {
         let next_hop = packet.next_hop();
-        let packet_mode = packet.packet_mode();
-        let sphinx_packet = packet.into_sphinx_packet();
-
-        if let Err(err) =
-            self.mixnet_client
-                .send_without_response(next_hop, sphinx_packet, packet_mode)
-        {
-            if err.kind() == io::ErrorKind::WouldBlock {
+
+        #[cfg(feature = "packet-tracing")]
+        log::info!(
+            target: nym_sphinx::params::packet_tracing::PACKET_TRACING_LOG_TARGET,
+            "[{}] mixnode forwarding packet to {next_hop}",
+            packet.trace_id()
+        );
+
+        let deadline = Instant::now() + self.maximum_forwarding_delay;
+        match self.mixnet_client.send_before_deadline(packet, deadline) {
+            Ok(_) => self
+                .node_stats_update_sender
+                .report_sent(next_hop.to_string()),
+            Err(DeadlineSendError::NextHopUnavailable(undeliverable)) => {
+                // we can't re-route the packet ourselves, but rather than letting it go stale
+                // in the queue, drop it so that its sender would retransmit it sooner
+                log::debug!(
+                    "{next_hop} is unreachable until {:?} - dropping the packet",
+                    undeliverable.reconnection_at
+                );
+                self.node_stats_update_sender
+                    .report_dropped(next_hop.to_string())
+            }
+            Err(DeadlineSendError::Io(err)) if err.kind() == io::ErrorKind::WouldBlock => {
                 // we only know for sure if we dropped a packet if our sending queue was full
                 // in any other case the connection might still be re-established (or created for the first time)
                 // and the packet might get sent, but we won't know about it
                 self.node_stats_update_sender
                     .report_dropped(next_hop.to_string())
-            } else if err.kind() == io::ErrorKind::NotConnected {
+            }
+            Err(DeadlineSendError::Io(err)) if err.kind() == io::ErrorKind::NotConnected => {
                 // let's give the benefit of the doubt and assume we manage to establish connection
                 self.node_stats_update_sender
                     .report_sent(next_hop.to_string());
             }
-        } else {
-            self.node_stats_update_sender
-                .report_sent(next_hop.to_string());
+            Err(DeadlineSendError::Io(_)) => (),
         }
     }
 
     /// Upon packet being finished getting delayed, forward it to the mixnet.
     fn handle_done_delaying(&mut self, packet: Expired<MixPacket>) {
+        if self.snapshot.is_some() {
+            self.queued_keys.remove(&packet.key());
+        }
         let delayed_packet = packet.into_inner();
         self.forward_packet(delayed_packet)
     }
@@ -97,24 +142,114 @@ where
             if instant.checked_duration_since(Instant::now()).is_none() {
                 self.forward_packet(new_packet.0)
             } else {
-                self.delay_queue.insert_at(new_packet.0, instant);
+                let key = self.delay_queue.insert_at(new_packet.0, instant);
+                if self.snapshot.is_some() {
+                    self.queued_keys.insert(key);
+                }
             }
         } else {
             self.forward_packet(new_packet.0)
         }
     }
 
+    /// Once we're draining, the listener no longer accepts new packets, so as soon as the delay
+    /// queue becomes empty, there's nothing left for us to forward.
+    fn check_drain_completion(&self, draining: bool) {
+        if draining && self.delay_queue.is_empty() {
+            self.drain.mark_drained()
+        }
+    }
+
+    /// Puts the packets persisted during the previous shutdown back into the delay queue.
+    fn restore_snapshot(&mut self) {
+        let Some(snapshot) = &self.snapshot else {
+            return;
+        };
+        let packets = match snapshot.load() {
+            Ok(packets) => packets,
+            Err(err) => {
+                log::warn!("Failed to restore the delay queue snapshot: {err}");
+                return;
+            }
+        };
+        if packets.is_empty() {
+            return;
+        }
+
+        log::info!(
+            "Restoring {} delayed packets from the snapshot",
+            packets.len()
+        );
+        let now = SystemTime::now();
+        let mut rng = rand::thread_rng();
+        let restored = packets
+            .into_iter()
+            .map(|(packet, forward_at)| {
+                let delay = snapshot.restored_delay(forward_at, now, &mut rng);
+                (packet, delay)
+            })
+            .collect::<Vec<_>>();
+
+        for (packet, delay) in restored {
+            match delay {
+                Some(delay) => self.handle_new_packet((packet, Some(Instant::now() + delay))),
+                None => {
+                    // the packet has been stuck with us for way too long, it's better off dropped
+                    self.node_stats_update_sender
+                        .report_dropped(packet.next_hop().to_string())
+                }
+            }
+        }
+    }
+
+    /// Persists all the packets still waiting in the delay queue.
+    fn persist_snapshot(&mut self) {
+        let Some(snapshot) = &self.snapshot else {
+            return;
+        };
+
+        let now = Instant::now();
+        let wall_now = SystemTime::now();
+        let packets = self
+            .queued_keys
+            .drain()
+            .map(|key| {
+                let expired = self.delay_queue.remove(&key);
+                let forward_at = wall_now + expired.deadline().saturating_duration_since(now);
+                (expired.into_inner(), forward_at)
+            })
+            .collect::<Vec<_>>();
+
+        let queued = packets.len();
+        match snapshot.save(packets) {
+            Ok(saved) => {
+                log::info!("Persisted {saved} out of {queued} delayed packets");
+            }
+            Err(err) => log::error!("Failed to persist the delay queue snapshot: {err}"),
+        }
+    }
+
     pub(crate) async fn run(&mut self) {
         log::trace!("Starting DelayForwarder");
+        self.restore_snapshot();
+        let drain = self.drain.clone();
+        let mut draining = false;
         loop {
             tokio::select! {
                 delayed = self.delay_queue.next() => {
                     self.handle_done_delaying(delayed.unwrap());
+                    self.check_drain_completion(draining);
                 }
                 new_packet = self.packet_receiver.next() => {
                     // this one is impossible to ever panic - the object itself contains a sender
                     // and hence it can't happen that ALL senders are dropped
-                    self.handle_new_packet(new_packet.unwrap())
+                    self.handle_new_packet(new_packet.unwrap());
+                    self.check_drain_completion(draining);
+                }
+                _ = drain.wait_for(DrainState::Draining), if !draining => {
+                    log::debug!("DelayForwarder: draining {} remaining packets", self.delay_queue.len());
+                    draining = true;
+                    self.check_drain_completion(draining);
                 }
                 _ = self.shutdown.recv() => {
                     log::trace!("DelayForwarder: Received shutdown");
@@ -122,12 +257,13 @@ where
                 }
             }
         }
+        self.persist_snapshot();
         log::trace!("DelayForwarder: Exiting");
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
@@ -151,21 +287,19 @@ mod tests {
     }
 
     impl nym_mixnet_client::SendWithoutResponse for TestClient {
-        fn send_without_response(
-            &mut self,
-            address: NymNodeRoutingAddress,
-            packet: SphinxPacket,
-            packet_mode: PacketMode,
-        ) -> io::Result<()> {
-            self.packets_sent
-                .lock()
-                .unwrap()
-                .push((address, packet, packet_mode));
+        fn send_without_response(&mut self, packet: MixPacket) -> io::Result<()> {
+            let address = packet.next_hop();
+            let packet_mode = packet.packet_mode();
+            self.packets_sent.lock().unwrap().push((
+                address,
+                packet.into_sphinx_packet(),
+                packet_mode,
+            ));
             Ok(())
         }
     }
 
-    fn make_valid_sphinx_packet(size: PacketSize) -> SphinxPacket {
+    pub(crate) fn make_valid_sphinx_packet(size: PacketSize) -> SphinxPacket {
         let (_, node1_pk) = crypto::keygen();
         let node1 = Node::new(
             NodeAddressBytes::from_bytes([5u8; NODE_ADDRESS_LENGTH]),
@@ -206,8 +340,13 @@ mod tests {
         let client = TestClient::default();
         let client_packets_sent = client.packets_sent.clone();
         let shutdown = TaskManager::default();
-        let mut delay_forwarder =
-            DelayForwarder::new(client, node_stats_update_sender, shutdown.subscribe());
+        let mut delay_forwarder = DelayForwarder::new(
+            client,
+            node_stats_update_sender,
+            Duration::from_secs(30),
+            DrainController::new(),
+            shutdown.subscribe(),
+        );
         let packet_sender = delay_forwarder.sender();
 
         // Spawn the worker, listening on packet_sender channel
@@ -240,4 +379,48 @@ mod tests {
             vec![next_hop]
         );
     }
+
+    #[tokio::test]
+    async fn delayed_packets_are_forwarded_before_drain_completes() {
+        let (stats_sender, _stats_receiver) = mpsc::unbounded();
+        let node_stats_update_sender = UpdateSender::new(stats_sender);
+        let client = TestClient::default();
+        let client_packets_sent = client.packets_sent.clone();
+        let shutdown = TaskManager::default();
+        let drain = DrainController::new();
+        let mut delay_forwarder = DelayForwarder::new(
+            client,
+            node_stats_update_sender,
+            Duration::from_secs(30),
+            drain.clone(),
+            shutdown.subscribe(),
+        );
+        let packet_sender = delay_forwarder.sender();
+
+        tokio::spawn(async move { delay_forwarder.run().await });
+
+        let next_hop =
+            NymNodeRoutingAddress::from(SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), 42));
+        let mix_packet = MixPacket::new(
+            next_hop,
+            make_valid_sphinx_packet(PacketSize::default()),
+            PacketMode::default(),
+        );
+        let forward_instant = Some(Instant::now() + Duration::from_millis(50));
+        packet_sender
+            .unbounded_send((mix_packet, forward_instant))
+            .unwrap();
+
+        // Give the the worker a chance to put the packet in its delay queue
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert!(drain.request_drain());
+        assert!(!drain.request_drain());
+        tokio::time::timeout(Duration::from_secs(1), drain.clone().run(Duration::ZERO))
+            .await
+            .expect("the drain did not complete in time");
+
+        assert_eq!(drain.state(), DrainState::Drained);
+        assert_eq!(client_packets_sent.lock().unwrap().len(), 1);
+    }
 }
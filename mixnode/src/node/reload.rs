@@ -0,0 +1,112 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::Config;
+use log::LevelFilter;
+use nym_config::NymConfig;
+use nym_task::TaskClient;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Subset of the node configuration that can be safely changed without restarting the node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ReloadableConfig {
+    pub(crate) log_level: Option<LevelFilter>,
+    pub(crate) node_stats_logging_delay: Duration,
+    pub(crate) node_stats_updating_delay: Duration,
+}
+
+impl<'a> From<&'a Config> for ReloadableConfig {
+    fn from(config: &'a Config) -> Self {
+        ReloadableConfig {
+            log_level: config.get_log_level(),
+            node_stats_logging_delay: config.get_node_stats_logging_delay(),
+            node_stats_updating_delay: config.get_node_stats_updating_delay(),
+        }
+    }
+}
+
+/// Re-reads the config file on request and propagates the values that can be changed at runtime
+/// to all the interested tasks.
+#[derive(Clone)]
+pub(crate) struct ConfigReloader {
+    id: String,
+
+    /// Log level the logger has been set up with, used if the config doesn't specify one.
+    startup_log_level: LevelFilter,
+    sender: Arc<watch::Sender<ReloadableConfig>>,
+}
+
+impl ConfigReloader {
+    pub(crate) fn new(config: &Config) -> Self {
+        let current = ReloadableConfig::from(config);
+        let reloader = ConfigReloader {
+            id: config.get_id(),
+            startup_log_level: log::max_level(),
+            sender: Arc::new(watch::channel(current.clone()).0),
+        };
+        reloader.apply_log_level(current.log_level);
+        reloader
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<ReloadableConfig> {
+        self.sender.subscribe()
+    }
+
+    fn apply_log_level(&self, level: Option<LevelFilter>) {
+        log::set_max_level(level.unwrap_or(self.startup_log_level))
+    }
+
+    /// Re-reads the config file and applies all values that can be changed without restarting
+    /// the node. Returns whether any of them has changed.
+    pub(crate) fn reload(&self) -> io::Result<bool> {
+        let config = Config::load_from_file(&self.id)?;
+        let reloaded = ReloadableConfig::from(&config);
+
+        let changed = self.sender.send_if_modified(|current| {
+            if *current != reloaded {
+                *current = reloaded.clone();
+                true
+            } else {
+                false
+            }
+        });
+
+        if changed {
+            self.apply_log_level(reloaded.log_level);
+            info!("Applied the reloaded configuration: {reloaded:?}");
+        } else {
+            info!("The reloaded configuration did not change any runtime values");
+        }
+        Ok(changed)
+    }
+
+    #[cfg(unix)]
+    pub(crate) async fn run(self, mut shutdown: TaskClient) {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sighup = signal(SignalKind::hangup()).expect("Failed to setup SIGHUP channel");
+
+        while !shutdown.is_shutdown() {
+            tokio::select! {
+                biased;
+                _ = shutdown.recv() => {
+                    log::trace!("ConfigReloader: received shutdown");
+                }
+                _ = sighup.recv() => {
+                    info!("Received SIGHUP - reloading the configuration");
+                    if let Err(err) = self.reload() {
+                        error!("Failed to reload the configuration - {err}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) async fn run(self, mut shutdown: TaskClient) {
+        shutdown.recv().await;
+        log::trace!("ConfigReloader: received shutdown");
+    }
+}
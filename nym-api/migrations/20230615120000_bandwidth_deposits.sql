@@ -0,0 +1,22 @@
+/*
+ * Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+CREATE TABLE bandwidth_deposit
+(
+    id             INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    tx_hash        VARCHAR NOT NULL UNIQUE,
+    height         INTEGER NOT NULL,
+    deposit_value  VARCHAR NOT NULL,
+    deposit_info   VARCHAR NOT NULL,
+    identity_key   VARCHAR NOT NULL,
+    encryption_key VARCHAR NOT NULL
+);
+
+-- there's only ever a single row in this table
+CREATE TABLE deposit_indexer_state
+(
+    id                  INTEGER NOT NULL PRIMARY KEY CHECK (id = 0),
+    last_indexed_height INTEGER NOT NULL
+);
@@ -3,19 +3,34 @@
 
 use cosmrs::AccountId;
 use getset::{CopyGetters, Getters};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use nym_coconut_interface::{
     error::CoconutInterfaceError, Attribute, Base58, BlindSignRequest, Credential, VerificationKey,
 };
 
-#[derive(Serialize, Deserialize, Getters, CopyGetters)]
+/// Schema of the serialized [`Credential`], whose `theta` is base58 encoded.
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct CredentialSchema {
+    n_params: u32,
+    theta: String,
+    voucher_value: u64,
+    voucher_info: String,
+    epoch_id: u64,
+    expiration_date: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Getters, CopyGetters)]
 pub struct VerifyCredentialBody {
     #[getset(get = "pub")]
+    #[schemars(with = "CredentialSchema")]
     credential: Credential,
     #[getset(get = "pub")]
     proposal_id: u64,
     #[getset(get = "pub")]
+    #[schemars(with = "String")]
     gateway_cosmos_addr: AccountId,
 }
 
@@ -33,7 +48,7 @@ impl VerifyCredentialBody {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct VerifyCredentialResponse {
     pub verification_result: bool,
 }
@@ -47,9 +62,10 @@ impl VerifyCredentialResponse {
 }
 
 //  All strings are base58 encoded representations of structs
-#[derive(Clone, Serialize, Deserialize, Debug, Getters, CopyGetters)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, Getters, CopyGetters)]
 pub struct BlindSignRequestBody {
     #[getset(get = "pub")]
+    #[schemars(with = "String")]
     blind_sign_request: BlindSignRequest,
     #[getset(get = "pub")]
     tx_hash: String,
@@ -92,7 +108,7 @@ impl BlindSignRequestBody {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct BlindedSignatureResponse {
     pub remote_key: [u8; 32],
     pub encrypted_signature: Vec<u8>,
@@ -135,8 +151,9 @@ impl BlindedSignatureResponse {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct VerificationKeyResponse {
+    #[schemars(with = "String")]
     pub key: VerificationKey,
 }
 
@@ -146,8 +163,9 @@ impl VerificationKeyResponse {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct CosmosAddressResponse {
+    #[schemars(with = "String")]
     pub addr: AccountId,
 }
 
@@ -156,3 +174,126 @@ impl CosmosAddressResponse {
         CosmosAddressResponse { addr }
     }
 }
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct ResharingDiagnosticsRequestBody {
+    /// Addresses of the members of the hypothetical new signer group.
+    pub new_dealers: Vec<String>,
+}
+
+impl ResharingDiagnosticsRequestBody {
+    pub fn new(new_dealers: Vec<String>) -> ResharingDiagnosticsRequestBody {
+        ResharingDiagnosticsRequestBody { new_dealers }
+    }
+}
+
+/// The way the coconut keys are going to be handled once the signer group changes.
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DealerSetTransition {
+    /// The dealer set remains the same, so the current keys remain in use.
+    Unchanged,
+
+    /// The current keys are going to be reshared amongst the new dealers.
+    Resharing,
+
+    /// Too many of the current key holders are leaving, so the new dealers are going to
+    /// generate completely new keys.
+    FreshKeygen,
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct ResharingDiagnosticsResponse {
+    pub transition: DealerSetTransition,
+    pub epoch_id: u64,
+    pub threshold: u64,
+
+    /// Dealers holding shares of the current keys.
+    pub current_dealers: Vec<String>,
+    pub retained_dealers: Vec<String>,
+    pub removed_dealers: Vec<String>,
+    pub added_dealers: Vec<String>,
+
+    /// Current key holders that are expected to submit their dealings during resharing.
+    pub required_holders: Vec<String>,
+
+    /// Minimum number of the above that have to be online for the keys to be recovered.
+    pub minimum_online_holders: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct DkgStatusResponse {
+    /// Id of the DKG epoch the signer is currently taking part in, if it's known yet.
+    pub epoch_id: Option<u64>,
+
+    /// Id of the ceremony the signer's DKG logs are attributed to.
+    pub ceremony_id: Option<String>,
+
+    /// If set, the signer has stopped issuing credentials as its DKG state is inconsistent.
+    #[serde(default)]
+    pub issuance_suspension: Option<IssuanceSuspension>,
+
+    /// Progress the signer has made in the DKG, if it's taking part in it.
+    #[serde(default)]
+    pub progress: Option<DkgProgress>,
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+pub struct DkgProgress {
+    /// Phase of the DKG epoch the signer has last observed, if any.
+    pub phase: Option<String>,
+
+    /// Index assigned to the signer when it registered as a dealer.
+    pub node_index: Option<u64>,
+
+    pub threshold: Option<u64>,
+
+    /// Dealers the signer has excluded from the key derivation.
+    pub bad_dealers: Vec<BadDealer>,
+
+    /// Id of the proposal for verifying the verification key share of the signer.
+    pub proposal_id: Option<u64>,
+
+    /// Whether the signer has derived its coconut keypair.
+    pub coconut_keypair_derived: bool,
+
+    /// Number of dealings the signer has submitted in the current epoch.
+    #[serde(default)]
+    pub submitted_dealings: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+pub struct BadDealer {
+    pub address: String,
+
+    /// Reason the dealer has been excluded for, such as `MissingDealing`.
+    pub reason: String,
+}
+
+/// Machine-readable reason for the signer refusing to issue credentials.
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssuanceSuspensionReason {
+    /// The coconut keypair has not been derived or could not be loaded.
+    KeypairMissing,
+
+    /// The signer is not registered as a dealer or its registered DKG key differs from the local one.
+    DkgRegistrationMismatch,
+
+    /// The signer could not decrypt the shares dealt to it.
+    UndecryptableDealings,
+
+    /// The verification key share recorded on chain is missing, unverified
+    /// or does not correspond to the local keypair.
+    VerificationKeyMismatch,
+}
+
+/// Body of the `503 Service Unavailable` responses returned by the issuance endpoints
+/// while the issuance is suspended.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+pub struct IssuanceSuspension {
+    pub reason: IssuanceSuspensionReason,
+
+    /// Human-readable description of the problem.
+    pub details: String,
+}
@@ -1,6 +1,10 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+//! Types of the requests and responses of the nym API endpoints, so that its Rust consumers
+//! wouldn't have to define their own JSON types. All of them implement `serde` traits alongside
+//! the `schemars` `JsonSchema`.
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -7,7 +7,7 @@ use nym_mixnet_contract_common::mixnode::MixNodeDetails;
 use nym_mixnet_contract_common::reward_params::{Performance, RewardingParams};
 use nym_mixnet_contract_common::rewarding::RewardEstimate;
 use nym_mixnet_contract_common::{
-    GatewayBond, IdentityKey, Interval, MixId, MixNode, Percent, RewardedSetNodeStatus,
+    GatewayBond, IdentityKey, Interval, MixId, MixNode, MixNodeBond, Percent, RewardedSetNodeStatus,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -175,6 +175,29 @@ pub struct RewardEstimationResponse {
     pub as_at: i64,
 }
 
+/// Reward the node would receive if the epoch ended now, alongside all the inputs it's derived from.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MixnodeRewardBreakdown {
+    pub mix_id: MixId,
+    pub identity_key: IdentityKey,
+    pub rewarded_set_status: RewardedSetNodeStatus,
+
+    /// The uptime factor that would be submitted to the mixnet contract.
+    pub performance: Performance,
+    pub stake_saturation: StakeSaturation,
+    pub uncapped_stake_saturation: StakeSaturation,
+    pub profit_margin_percent: Percent,
+    pub estimation: RewardEstimate,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EpochRewardsDryRunResponse {
+    pub epoch: Interval,
+    pub reward_params: RewardingParams,
+    pub as_at: i64,
+    pub nodes: Vec<MixnodeRewardBreakdown>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UptimeResponse {
     pub mix_id: MixId,
@@ -271,7 +294,7 @@ impl fmt::Display for InclusionProbabilityResponse {
     }
 }
 
-#[derive(Clone, Serialize, schemars::JsonSchema)]
+#[derive(Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct AllInclusionProbabilitiesResponse {
     pub inclusion_probabilities: Vec<InclusionProbability>,
     pub samples: u64,
@@ -281,7 +304,7 @@ pub struct AllInclusionProbabilitiesResponse {
     pub as_at: i64,
 }
 
-#[derive(Clone, Serialize, schemars::JsonSchema)]
+#[derive(Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct InclusionProbability {
     pub mix_id: MixId,
     pub in_active: f64,
@@ -337,3 +360,144 @@ pub struct CirculatingSupplyResponse {
     pub vesting_tokens: Coin,
     pub circulating_supply: Coin,
 }
+
+/// Membership of a mixnode in a family declared on chain.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MixNodeFamilyMembership {
+    /// Identity key of the member mixnode.
+    pub identity_key: IdentityKey,
+
+    /// Head of the family the mixnode belongs to.
+    pub family_head: FamilyHead,
+}
+
+/// Changes to the active topology, i.e. the active set mixnodes and the gateways,
+/// since the requested version.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TopologyDiffResponse {
+    /// Version of the topology obtained after applying this diff.
+    pub version: u64,
+
+    /// Indicates whether the diff is relative to an empty topology, i.e. whether the recipient
+    /// should discard its current view of the network before applying it.
+    /// It happens whenever the requested version is unknown or too old to be diffed against.
+    pub full: bool,
+
+    /// Mixnodes that either got added to the active set or whose bond information has changed.
+    pub updated_mixnodes: Vec<MixNodeBond>,
+
+    /// Mixnodes that are no longer part of the active set.
+    pub removed_mixnodes: Vec<MixId>,
+
+    /// Gateways that either got bonded or whose bond information has changed.
+    pub updated_gateways: Vec<GatewayBond>,
+
+    /// Gateways that are no longer available.
+    pub removed_gateways: Vec<IdentityKey>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HeartbeatNodeType {
+    Mixnode,
+    Gateway,
+}
+
+impl HeartbeatNodeType {
+    fn as_byte(&self) -> u8 {
+        match self {
+            HeartbeatNodeType::Mixnode => 0,
+            HeartbeatNodeType::Gateway => 1,
+        }
+    }
+}
+
+impl fmt::Display for HeartbeatNodeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeartbeatNodeType::Mixnode => write!(f, "mixnode"),
+            HeartbeatNodeType::Gateway => write!(f, "gateway"),
+        }
+    }
+}
+
+/// Traffic statistics of a node reported alongside its heartbeat.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct NodeHeartbeatStats {
+    /// Number of packets received since the previous heartbeat.
+    pub packets_received: u64,
+
+    /// Number of packets sent since the previous heartbeat.
+    pub packets_sent: u64,
+
+    /// Number of packets dropped since the previous heartbeat.
+    pub packets_dropped: u64,
+
+    /// Number of clients currently connected to the node. Always 0 for mixnodes.
+    pub connected_clients: u64,
+}
+
+/// Periodic liveness report of a bonded node.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct NodeHeartbeat {
+    /// Identity key of the node sending the heartbeat. The heartbeat must be signed with it.
+    pub identity_key: IdentityKey,
+
+    pub node_type: HeartbeatNodeType,
+
+    /// Version of the binary the node is running.
+    pub version: String,
+
+    /// Unix timestamp (in seconds) of the moment the heartbeat was created.
+    pub timestamp: i64,
+
+    pub stats: NodeHeartbeatStats,
+}
+
+// domain separator making sure the heartbeat signatures can't be reused in any other context
+const HEARTBEAT_SIGNATURE_DOMAIN: &[u8] = b"nym-node-heartbeat";
+
+impl NodeHeartbeat {
+    /// Deterministic representation of the heartbeat the signature is created over.
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let mut bytes = HEARTBEAT_SIGNATURE_DOMAIN.to_vec();
+        for field in [self.identity_key.as_bytes(), self.version.as_bytes()] {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes.push(self.node_type.as_byte());
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes.extend_from_slice(&self.stats.packets_received.to_be_bytes());
+        bytes.extend_from_slice(&self.stats.packets_sent.to_be_bytes());
+        bytes.extend_from_slice(&self.stats.packets_dropped.to_be_bytes());
+        bytes.extend_from_slice(&self.stats.connected_clients.to_be_bytes());
+        bytes
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct SignedNodeHeartbeat {
+    pub heartbeat: NodeHeartbeat,
+
+    /// Base58-encoded ed25519 signature of the heartbeat's signable bytes.
+    pub signature: String,
+}
+
+/// Last heartbeat received from a node alongside its liveness as determined by the nym-api.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct NodeLivenessResponse {
+    pub identity_key: IdentityKey,
+
+    pub node_type: HeartbeatNodeType,
+
+    /// Version reported in the last heartbeat.
+    pub version: String,
+
+    /// Unix timestamp (in seconds) of the last accepted heartbeat.
+    pub last_heartbeat: i64,
+
+    pub stats: NodeHeartbeatStats,
+
+    /// Indicates whether the node has sent a heartbeat within the allowed timeout.
+    pub alive: bool,
+}
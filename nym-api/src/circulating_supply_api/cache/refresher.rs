@@ -44,6 +44,7 @@ impl CirculatingSupplyCacheRefresher {
                         ret = self.refresh() => {
                             if let Err(err) = ret {
                                 error!("Failed to refresh circulating supply cache - {err}");
+                                self.nyxd_client.try_failover().await;
                             } else {
                                 // relaxed memory ordering is fine here. worst case scenario network monitor
                                 // will just have to wait for an additional backoff to see the change.
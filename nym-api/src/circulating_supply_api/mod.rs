@@ -5,8 +5,9 @@ use nym_task::TaskManager;
 use okapi::openapi3::OpenApi;
 use rocket::Route;
 use rocket_okapi::{openapi_get_routes_spec, settings::OpenApiSettings};
+use std::sync::Arc;
 
-use crate::support::{config::Config, nyxd};
+use crate::support::{config::Config, nyxd, supervisor::TaskSupervisor};
 
 use self::cache::refresher::CirculatingSupplyCacheRefresher;
 
@@ -27,6 +28,7 @@ pub(crate) fn start_cache_refresh(
     config: &Config,
     nyxd_client: nyxd::Client,
     circulating_supply_cache: &cache::CirculatingSupplyCache,
+    supervisor: &TaskSupervisor,
     shutdown: &TaskManager,
 ) {
     if config.get_circulating_supply_enabled() {
@@ -35,7 +37,17 @@ pub(crate) fn start_cache_refresh(
             circulating_supply_cache.to_owned(),
             config.get_circulating_supply_caching_interval(),
         );
-        let shutdown_listener = shutdown.subscribe();
-        tokio::spawn(async move { refresher.run(shutdown_listener).await });
+        let refresher = Arc::new(refresher);
+        supervisor.spawn(
+            "circulating-supply-cache-refresher",
+            shutdown,
+            move |shutdown| {
+                let refresher = Arc::clone(&refresher);
+                async move {
+                    refresher.run(shutdown).await;
+                    Ok(())
+                }
+            },
+        );
     }
 }
@@ -0,0 +1,133 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Suspension of the credential issuance whenever the DKG state of the signer is inconsistent
+//! (e.g. its keys are missing or do not match the ones recorded on chain), as any credential
+//! issued in that state would have been rejected by the verifiers anyway.
+//! The issuance is automatically resumed once the health checks pass again.
+
+use crate::coconut::dkg::client::DkgClient;
+use crate::coconut::keystore::CoconutKeyStore;
+use crate::coconut::self_check::run_checks;
+use crate::nyxd;
+use crate::support::config::Config;
+use crate::support::supervisor::TaskSupervisor;
+use nym_api_requests::coconut::IssuanceSuspension;
+use nym_pemstore::KeyPairPath;
+use nym_task::{TaskClient, TaskManager};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time::interval;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct IssuanceCircuitBreaker {
+    suspension: Arc<RwLock<Option<IssuanceSuspension>>>,
+}
+
+impl IssuanceCircuitBreaker {
+    /// Returns the reason for the issuance being suspended, if it currently is.
+    pub(crate) fn suspension(&self) -> Option<IssuanceSuspension> {
+        self.suspension
+            .read()
+            .expect("issuance suspension lock got poisoned")
+            .clone()
+    }
+
+    pub(crate) fn update(&self, suspension: Option<IssuanceSuspension>) {
+        let mut current = self
+            .suspension
+            .write()
+            .expect("issuance suspension lock got poisoned");
+        match (&*current, &suspension) {
+            (None, Some(new)) => error!(
+                "Suspending the credential issuance as the DKG state is inconsistent: {}",
+                new.details
+            ),
+            (Some(_), None) => {
+                info!("The DKG state is consistent again, resuming the credential issuance")
+            }
+            _ => (),
+        }
+        *current = suspension;
+    }
+}
+
+pub(crate) struct IssuanceHealthMonitor {
+    dkg_client: DkgClient,
+    decryption_key_path: PathBuf,
+    public_key_with_proof_path: PathBuf,
+    coconut_keystore: CoconutKeyStore,
+    circuit_breaker: IssuanceCircuitBreaker,
+    check_interval: Duration,
+}
+
+impl IssuanceHealthMonitor {
+    pub(crate) fn new(
+        config: &Config,
+        nyxd_client: nyxd::Client,
+        coconut_keystore: CoconutKeyStore,
+        circuit_breaker: IssuanceCircuitBreaker,
+    ) -> Self {
+        IssuanceHealthMonitor {
+            dkg_client: DkgClient::new(nyxd_client),
+            decryption_key_path: config.decryption_key_path(),
+            public_key_with_proof_path: config.public_key_with_proof_path(),
+            coconut_keystore,
+            circuit_breaker,
+            check_interval: config.get_issuance_health_check_interval(),
+        }
+    }
+
+    async fn check_health(&self) {
+        let dkg_keypair_path = KeyPairPath::new(
+            self.decryption_key_path.clone(),
+            self.public_key_with_proof_path.clone(),
+        );
+        match run_checks(&self.dkg_client, &dkg_keypair_path, &self.coconut_keystore).await {
+            Ok(report) => self.circuit_breaker.update(report.issuance_suspension()),
+            Err(err) => {
+                // we couldn't determine the state one way or the other, so leave it as it was
+                warn!("Could not verify the consistency of the DKG state: {err}");
+                if err.is_chain_error() {
+                    self.dkg_client.try_failover().await;
+                }
+            }
+        }
+    }
+
+    pub(crate) async fn run(&self, mut shutdown: TaskClient) {
+        let mut interval = interval(self.check_interval);
+        while !shutdown.is_shutdown() {
+            tokio::select! {
+                _ = interval.tick() => self.check_health().await,
+                _ = shutdown.recv() => {
+                    trace!("IssuanceHealthMonitor: Received shutdown");
+                }
+            }
+        }
+    }
+
+    pub(crate) fn start(
+        config: &Config,
+        nyxd_client: nyxd::Client,
+        coconut_keystore: CoconutKeyStore,
+        circuit_breaker: IssuanceCircuitBreaker,
+        supervisor: &TaskSupervisor,
+        shutdown: &TaskManager,
+    ) {
+        let health_monitor = Arc::new(IssuanceHealthMonitor::new(
+            config,
+            nyxd_client,
+            coconut_keystore,
+            circuit_breaker,
+        ));
+        supervisor.spawn("issuance-health-monitor", shutdown, move |shutdown| {
+            let health_monitor = Arc::clone(&health_monitor);
+            async move {
+                health_monitor.run(shutdown).await;
+                Ok(())
+            }
+        });
+    }
+}
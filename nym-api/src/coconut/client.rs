@@ -5,7 +5,10 @@ use crate::coconut::error::Result;
 use cw3::ProposalResponse;
 use cw4::MemberResponse;
 use nym_coconut_bandwidth_contract_common::spend_credential::SpendCredentialResponse;
-use nym_coconut_dkg_common::dealer::{ContractDealing, DealerDetails, DealerDetailsResponse};
+use nym_coconut_dkg_common::complaints::{
+    ComplaintReason, DealerComplaintsResponse, DisputedDealer,
+};
+use nym_coconut_dkg_common::dealer::{DealerDetails, DealerDetailsResponse, PagedDealingsResponse};
 use nym_coconut_dkg_common::types::{
     EncodedBTEPublicKeyWithProof, Epoch, EpochId, InitialReplacementData,
 };
@@ -17,8 +20,15 @@ use nym_validator_client::nyxd::{AccountId, Fee, TxResponse};
 
 #[async_trait]
 pub trait Client {
+    /// Switches to the next available chain endpoint, if there is any.
+    async fn try_failover(&self) {}
+
     async fn address(&self) -> AccountId;
     async fn get_tx(&self, tx_hash: &str) -> Result<TxResponse>;
+    async fn get_current_block_height(&self) -> Result<u64>;
+    /// Searches for the transactions that deposited funds into the bandwidth contract
+    /// within the specified (inclusive) range of blocks.
+    async fn search_deposits(&self, from_height: u64, to_height: u64) -> Result<Vec<TxResponse>>;
     async fn get_proposal(&self, proposal_id: u64) -> Result<ProposalResponse>;
     async fn list_proposals(&self) -> Result<Vec<ProposalResponse>>;
     async fn get_spent_credential(
@@ -28,13 +38,25 @@ pub trait Client {
     async fn get_current_epoch(&self) -> Result<Epoch>;
     async fn group_member(&self, addr: String) -> Result<MemberResponse>;
     async fn get_current_epoch_threshold(&self) -> Result<Option<Threshold>>;
+    async fn get_total_dealings(&self) -> Result<usize>;
     async fn get_initial_dealers(&self) -> Result<Option<InitialReplacementData>>;
     async fn get_self_registered_dealer_details(&self) -> Result<DealerDetailsResponse>;
     async fn get_current_dealers(&self) -> Result<Vec<DealerDetails>>;
-    async fn get_dealings(&self, idx: usize) -> Result<Vec<ContractDealing>>;
+    async fn get_dealings_paged(
+        &self,
+        idx: usize,
+        start_after: Option<String>,
+    ) -> Result<PagedDealingsResponse>;
     async fn get_verification_key_shares(&self, epoch_id: EpochId) -> Result<Vec<ContractVKShare>>;
-    async fn vote_proposal(&self, proposal_id: u64, vote_yes: bool, fee: Option<Fee>)
-        -> Result<()>;
+    async fn get_dealer_complaints(&self, dealer: &AccountId) -> Result<DealerComplaintsResponse>;
+    async fn get_disputed_dealers(&self) -> Result<Vec<DisputedDealer>>;
+    async fn vote_proposal(
+        &self,
+        proposal_id: u64,
+        vote_yes: bool,
+        memo: Option<String>,
+        fee: Option<Fee>,
+    ) -> Result<()>;
     async fn execute_proposal(&self, proposal_id: u64) -> Result<()>;
     async fn advance_epoch_state(&self) -> Result<()>;
     async fn register_dealer(
@@ -48,6 +70,13 @@ pub trait Client {
         dealing_bytes: ContractSafeBytes,
         resharing: bool,
     ) -> Result<ExecuteResult>;
+    async fn submit_complaint(
+        &self,
+        dealer: &AccountId,
+        dealing_index: Option<u64>,
+        reason: ComplaintReason,
+        resharing: bool,
+    ) -> Result<ExecuteResult>;
     async fn submit_verification_key_share(
         &self,
         share: VerificationKeyShare,
@@ -1,23 +1,53 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::support::storage::models::BandwidthDeposit;
 use nym_api_requests::coconut::BlindSignRequestBody;
 use nym_coconut_bandwidth_contract_common::events::{
     DEPOSITED_FUNDS_EVENT_TYPE, DEPOSIT_ENCRYPTION_KEY, DEPOSIT_IDENTITY_KEY, DEPOSIT_INFO,
     DEPOSIT_VALUE,
 };
-use nym_credentials::coconut::bandwidth::BandwidthVoucher;
+use nym_credentials::coconut::bandwidth::{
+    current_unix_timestamp, has_expired, is_rounded_expiration_date, BandwidthVoucher,
+    MAX_CREDENTIAL_VALIDITY,
+};
 use nym_crypto::asymmetric::encryption;
 use nym_crypto::asymmetric::identity::{self, Signature};
-use nym_validator_client::nyxd::TxResponse;
+use nym_validator_client::nyxd::{Event, TxResponse};
 
 use super::error::{CoconutError, Result};
 
-pub async fn extract_encryption_key(
-    blind_sign_request_body: &BlindSignRequestBody,
-    tx: TxResponse,
-) -> Result<encryption::PublicKey> {
-    let blind_sign_request = blind_sign_request_body.blind_sign_request();
+fn find_deposit_event(tx: &TxResponse) -> Option<&Event> {
+    tx.tx_result
+        .events
+        .iter()
+        .find(|event| event.type_str == format!("wasm-{}", DEPOSITED_FUNDS_EVENT_TYPE))
+}
+
+fn find_attribute<'a>(event: &'a Event, key: &str) -> Option<&'a str> {
+    event
+        .attributes
+        .iter()
+        .find(|tag| tag.key.as_ref() == key)
+        .map(|tag| tag.value.as_ref())
+}
+
+/// Extracts the bandwidth deposit made in the provided transaction, if there was any.
+pub(crate) fn parse_bandwidth_deposit(tx: &TxResponse) -> Option<BandwidthDeposit> {
+    let event = find_deposit_event(tx)?;
+
+    Some(BandwidthDeposit {
+        tx_hash: tx.hash.to_string(),
+        height: tx.height.value() as i64,
+        deposit_value: find_attribute(event, DEPOSIT_VALUE)?.to_string(),
+        deposit_info: find_attribute(event, DEPOSIT_INFO)?.to_string(),
+        identity_key: find_attribute(event, DEPOSIT_IDENTITY_KEY)?.to_string(),
+        encryption_key: find_attribute(event, DEPOSIT_ENCRYPTION_KEY)?.to_string(),
+    })
+}
+
+// checks of the request that do not depend on the deposit itself
+fn verify_request(blind_sign_request_body: &BlindSignRequestBody) -> Result<Signature> {
     let public_attributes = blind_sign_request_body.public_attributes();
     let public_attributes_plain = blind_sign_request_body.public_attributes_plain();
 
@@ -25,27 +55,53 @@ pub async fn extract_encryption_key(
         return Err(CoconutError::InconsistentPublicAttributes);
     }
 
-    let tx_hash_str = blind_sign_request_body.tx_hash();
-    let mut message = blind_sign_request.to_bytes();
-    message.extend_from_slice(tx_hash_str.as_bytes());
+    let now = current_unix_timestamp();
+    match public_attributes_plain.get(2) {
+        Some(expiration_date) => verify_expiration_date(expiration_date, now)?,
+        // legacy requests, without the expiration date, are only accepted for as long as
+        // the resulting credentials would still be accepted by the gateways
+        None if !has_expired(None, now) => {}
+        None => return Err(CoconutError::MissingExpirationDate),
+    }
 
-    let signature = Signature::from_base58_string(blind_sign_request_body.signature())?;
+    Ok(Signature::from_base58_string(
+        blind_sign_request_body.signature(),
+    )?)
+}
 
-    let attributes: &Vec<_> = tx
-        .tx_result
-        .events
-        .iter()
-        .find(|event| event.type_str == format!("wasm-{}", DEPOSITED_FUNDS_EVENT_TYPE))
-        .ok_or(CoconutError::DepositEventNotFound)?
-        .attributes
-        .as_ref();
+fn verify_expiration_date(expiration_date: &str, now: u64) -> Result<()> {
+    let invalid = || CoconutError::InvalidExpirationDate {
+        expiration_date: expiration_date.to_string(),
+    };
 
-    let deposit_value = attributes
-        .iter()
-        .find(|tag| tag.key.as_ref() == DEPOSIT_VALUE)
-        .ok_or(CoconutError::DepositValueNotFound)?
-        .value
-        .as_ref();
+    let expiration_date = expiration_date.parse::<u64>().map_err(|_| invalid())?;
+    // the expiration date is a public attribute, so anything more precise than the agreed upon
+    // granularity could be used for linking the credential to its issuance
+    if !is_rounded_expiration_date(expiration_date)
+        || expiration_date <= now
+        || expiration_date > now + MAX_CREDENTIAL_VALIDITY.as_secs()
+    {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+fn verify_deposit<'a, F>(
+    blind_sign_request_body: &BlindSignRequestBody,
+    signature: &Signature,
+    deposit_attribute: F,
+) -> Result<encryption::PublicKey>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    let public_attributes_plain = blind_sign_request_body.public_attributes_plain();
+
+    let tx_hash_str = blind_sign_request_body.tx_hash();
+    let mut message = blind_sign_request_body.blind_sign_request().to_bytes();
+    message.extend_from_slice(tx_hash_str.as_bytes());
+
+    let deposit_value =
+        deposit_attribute(DEPOSIT_VALUE).ok_or(CoconutError::DepositValueNotFound)?;
     let deposit_value_plain = public_attributes_plain.get(0).cloned().unwrap_or_default();
     if deposit_value != deposit_value_plain {
         return Err(CoconutError::DifferentPublicAttributes(
@@ -54,12 +110,7 @@ pub async fn extract_encryption_key(
         ));
     }
 
-    let deposit_info = attributes
-        .iter()
-        .find(|tag| tag.key.as_ref() == DEPOSIT_INFO)
-        .ok_or(CoconutError::DepositInfoNotFound)?
-        .value
-        .as_ref();
+    let deposit_info = deposit_attribute(DEPOSIT_INFO).ok_or(CoconutError::DepositInfoNotFound)?;
     let deposit_info_plain = public_attributes_plain.get(1).cloned().unwrap_or_default();
     if deposit_info != deposit_info_plain {
         return Err(CoconutError::DifferentPublicAttributes(
@@ -69,34 +120,54 @@ pub async fn extract_encryption_key(
     }
 
     let verification_key = identity::PublicKey::from_base58_string(
-        attributes
-            .iter()
-            .find(|tag| tag.key.as_ref() == DEPOSIT_IDENTITY_KEY)
-            .ok_or(CoconutError::DepositVerifKeyNotFound)?
-            .value
-            .as_ref(),
+        deposit_attribute(DEPOSIT_IDENTITY_KEY).ok_or(CoconutError::DepositVerifKeyNotFound)?,
     )?;
 
     let encryption_key = encryption::PublicKey::from_base58_string(
-        attributes
-            .iter()
-            .find(|tag| tag.key.as_ref() == DEPOSIT_ENCRYPTION_KEY)
-            .ok_or(CoconutError::DepositEncrKeyNotFound)?
-            .value
-            .as_ref(),
+        deposit_attribute(DEPOSIT_ENCRYPTION_KEY).ok_or(CoconutError::DepositEncrKeyNotFound)?,
     )?;
 
-    verification_key.verify(&message, &signature)?;
+    verification_key.verify(&message, signature)?;
 
     Ok(encryption_key)
 }
 
+pub async fn extract_encryption_key(
+    blind_sign_request_body: &BlindSignRequestBody,
+    tx: TxResponse,
+) -> Result<encryption::PublicKey> {
+    let signature = verify_request(blind_sign_request_body)?;
+    let event = find_deposit_event(&tx).ok_or(CoconutError::DepositEventNotFound)?;
+
+    verify_deposit(blind_sign_request_body, &signature, |key| {
+        find_attribute(event, key)
+    })
+}
+
+/// Verifies the request against the deposit that has already been indexed,
+/// without having to query the chain for the deposit transaction.
+pub(crate) fn verify_indexed_deposit(
+    blind_sign_request_body: &BlindSignRequestBody,
+    deposit: &BandwidthDeposit,
+) -> Result<encryption::PublicKey> {
+    let signature = verify_request(blind_sign_request_body)?;
+
+    verify_deposit(blind_sign_request_body, &signature, |key| match key {
+        DEPOSIT_VALUE => Some(deposit.deposit_value.as_str()),
+        DEPOSIT_INFO => Some(deposit.deposit_info.as_str()),
+        DEPOSIT_IDENTITY_KEY => Some(deposit.identity_key.as_str()),
+        DEPOSIT_ENCRYPTION_KEY => Some(deposit.encryption_key.as_str()),
+        _ => None,
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::coconut::tests::tx_entry_fixture;
     use nym_coconut::{prepare_blind_sign, BlindSignRequest, Parameters};
     use nym_config::defaults::VOUCHER_INFO;
+    use nym_credentials::coconut::bandwidth::{default_expiration_date, TOTAL_ATTRIBUTES};
     use nym_validator_client::nyxd::tx::Hash;
     use nym_validator_client::nyxd::{Event, Tag};
     use rand_07::rngs::OsRng;
@@ -108,12 +179,13 @@ mod test {
             Hash::from_str("6B27412050B823E58BB38447D7870BBC8CBE3C51C905BEA89D459ACCDA80A00E")
                 .unwrap();
         let mut tx_entry = tx_entry_fixture(&tx_hash.to_string());
-        let params = Parameters::new(4).unwrap();
+        let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
         let mut rng = OsRng;
         let voucher = BandwidthVoucher::new(
             &params,
             "1234".to_string(),
             VOUCHER_INFO.to_string(),
+            default_expiration_date(),
             tx_hash,
             identity::PrivateKey::from_base58_string(
                 identity::KeyPair::new(&mut rng)
@@ -159,7 +231,7 @@ mod test {
             String::from("Invalid signature"),
             &voucher.get_public_attributes(),
             voucher.get_public_attributes_plain(),
-            4,
+            TOTAL_ATTRIBUTES,
         );
         let err = extract_encryption_key(&req, tx_entry.clone())
             .await
@@ -178,7 +250,7 @@ mod test {
             signature.clone(),
             &voucher.get_public_attributes(),
             voucher.get_public_attributes_plain(),
-            4,
+            TOTAL_ATTRIBUTES,
         );
 
         tx_entry.tx_result.events.push(Event {
@@ -402,7 +474,7 @@ mod test {
             "gSFgpma5GAVMcsmZwKieqGNHNd3dPzcfa8eT2Qn2LoBccSeyiJdphREbNrkuh5XWxMe2hUsranaYzLro48L9Qhd".to_string(),
             &voucher.get_public_attributes(),
             voucher.get_public_attributes_plain(),
-            4,
+            TOTAL_ATTRIBUTES,
         );
         tx_entry.tx_result.events.get_mut(0).unwrap().attributes = vec![
             Tag {
@@ -431,4 +503,31 @@ mod test {
             .unwrap();
         assert_eq!(encryption_key.to_base58_string(), expected_encryption_key);
     }
+
+    #[test]
+    fn expiration_date_must_be_within_validity_window() {
+        // 2023-11-14T22:13:20Z
+        let now = 1700000000;
+        // 2023-11-15T00:00:00Z
+        let next_day = 1700006400;
+        // 2023-12-15T00:00:00Z
+        let last_valid_day = 1702598400;
+
+        assert!(verify_expiration_date(&next_day.to_string(), now).is_ok());
+        assert!(verify_expiration_date(&last_valid_day.to_string(), now).is_ok());
+
+        for invalid in [
+            now.to_string(),
+            (now - 1).to_string(),
+            (now + 3600).to_string(),
+            (next_day - 86400).to_string(),
+            (last_valid_day + 86400).to_string(),
+            "foomp".to_string(),
+        ] {
+            assert!(matches!(
+                verify_expiration_date(&invalid, now),
+                Err(CoconutError::InvalidExpirationDate { .. })
+            ));
+        }
+    }
 }
@@ -0,0 +1,118 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Indexing of the bandwidth deposits made on chain, so that the blind sign requests could be
+//! validated with a local lookup rather than by querying the chain for every single deposit
+//! transaction. Deposits that haven't been indexed yet (or were made before the indexer has
+//! ever been started) are still going to be verified against the chain directly.
+
+use crate::coconut::client::Client as LocalClient;
+use crate::coconut::deposit::parse_bandwidth_deposit;
+use crate::coconut::error::Result;
+use crate::support::config::Config;
+use crate::support::storage::NymApiStorage;
+use crate::support::supervisor::TaskSupervisor;
+use nym_task::{TaskClient, TaskManager};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::interval;
+
+/// Maximum number of blocks searched for deposits in a single query.
+const MAX_BLOCKS_PER_QUERY: u64 = 1000;
+
+pub(crate) struct DepositIndexer<C> {
+    client: C,
+    storage: NymApiStorage,
+    polling_rate: Duration,
+}
+
+impl<C> DepositIndexer<C>
+where
+    C: LocalClient + Send + Sync + 'static,
+{
+    pub(crate) fn new(config: &Config, client: C, storage: NymApiStorage) -> Self {
+        DepositIndexer {
+            client,
+            storage,
+            polling_rate: config.get_deposit_indexer_polling_rate(),
+        }
+    }
+
+    /// Indexes all the deposits made since the last indexed block.
+    pub(crate) async fn index_new_deposits(&self) -> Result<()> {
+        let current_height = self.client.get_current_block_height().await?;
+
+        let Some(last_indexed_height) = self.storage.get_last_indexed_deposit_height().await?
+        else {
+            info!("starting to index bandwidth deposits from block {current_height}");
+            self.storage
+                .set_last_indexed_deposit_height(current_height as i64)
+                .await?;
+            return Ok(());
+        };
+
+        let mut from_height = last_indexed_height as u64 + 1;
+        while from_height <= current_height {
+            let to_height = (from_height + MAX_BLOCKS_PER_QUERY - 1).min(current_height);
+
+            let deposits = self.client.search_deposits(from_height, to_height).await?;
+            debug!(
+                "found {} bandwidth deposit(s) in blocks {from_height}-{to_height}",
+                deposits.len()
+            );
+            for tx in deposits {
+                match parse_bandwidth_deposit(&tx) {
+                    Some(deposit) => self.storage.insert_bandwidth_deposit(&deposit).await?,
+                    None => warn!(
+                        "transaction {} does not contain a complete bandwidth deposit",
+                        tx.hash
+                    ),
+                }
+            }
+
+            // only move forward once the whole range has been stored
+            self.storage
+                .set_last_indexed_deposit_height(to_height as i64)
+                .await?;
+            from_height = to_height + 1;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn run(&self, mut shutdown: TaskClient) {
+        let mut interval = interval(self.polling_rate);
+        while !shutdown.is_shutdown() {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(err) = self.index_new_deposits().await {
+                        warn!("Could not index new bandwidth deposits: {err}");
+                        if err.is_chain_error() {
+                            self.client.try_failover().await;
+                        }
+                    }
+                }
+                _ = shutdown.recv() => {
+                    trace!("DepositIndexer: Received shutdown");
+                }
+            }
+        }
+    }
+
+    pub(crate) fn start(
+        config: &Config,
+        client: C,
+        storage: NymApiStorage,
+        supervisor: &TaskSupervisor,
+        shutdown: &TaskManager,
+    ) {
+        let deposit_indexer = Arc::new(DepositIndexer::new(config, client, storage));
+        supervisor.spawn("deposit-indexer", shutdown, move |shutdown| {
+            let deposit_indexer = Arc::clone(&deposit_indexer);
+            async move {
+                deposit_indexer.run(shutdown).await;
+                Ok(())
+            }
+        });
+    }
+}
@@ -5,25 +5,90 @@ use crate::coconut::client::Client;
 use crate::coconut::error::CoconutError;
 use cw3::ProposalResponse;
 use cw4::MemberResponse;
-use nym_coconut_dkg_common::dealer::{ContractDealing, DealerDetails, DealerDetailsResponse};
+use nym_coconut_dkg_common::complaints::{
+    ComplaintReason, DealerComplaintsResponse, DisputedDealer,
+};
+use nym_coconut_dkg_common::dealer::{
+    ContractDealing, DealerDetails, DealerDetailsResponse, PagedDealingsResponse,
+};
 use nym_coconut_dkg_common::types::{
     EncodedBTEPublicKeyWithProof, Epoch, EpochId, InitialReplacementData, NodeIndex,
 };
-use nym_coconut_dkg_common::verification_key::{ContractVKShare, VerificationKeyShare};
+use nym_coconut_dkg_common::verification_key::{
+    ContractVKShare, VerificationKeyShare, VkShareVoteJustification,
+};
 use nym_contracts_common::dealings::ContractSafeBytes;
 use nym_dkg::Threshold;
+use nym_retry::{retry, ExponentialBackoff, Jitter, RetryPolicy};
 use nym_validator_client::nyxd::cosmwasm_client::logs::{find_attribute, NODE_INDEX};
 use nym_validator_client::nyxd::cosmwasm_client::types::ExecuteResult;
 use nym_validator_client::nyxd::AccountId;
+use std::time::Duration;
 
 pub(crate) struct DkgClient {
     inner: Box<dyn Client + Send + Sync>,
 }
 
+/// Retrieves the dealings of a particular index one page at a time.
+/// The resumption token only advances after a page has been successfully retrieved, so a failed
+/// query can be retried without having to download all the previous pages again.
+pub(crate) struct DealingsPager<'a> {
+    client: &'a DkgClient,
+    idx: usize,
+    start_after: Option<String>,
+    finished: bool,
+}
+
+impl<'a> DealingsPager<'a> {
+    /// Address of the dealer whose dealing was the last one to be retrieved.
+    pub(crate) fn resumption_token(&self) -> Option<&str> {
+        self.start_after.as_deref()
+    }
+
+    /// Retrieves the next page of dealings or `None` if all of them have already been retrieved.
+    pub(crate) async fn next_page(&mut self) -> Result<Option<Vec<ContractDealing>>, CoconutError> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let page = retry(&DkgClient::retry_policy(), || self.try_get_next_page()).await?;
+
+        match page.start_next_after {
+            Some(start_next_after) if !page.dealings.is_empty() => {
+                self.start_after = Some(start_next_after.into_string())
+            }
+            _ => self.finished = true,
+        }
+
+        if page.dealings.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(page.dealings))
+        }
+    }
+
+    async fn try_get_next_page(&self) -> Result<PagedDealingsResponse, CoconutError> {
+        self.client
+            .inner
+            .get_dealings_paged(self.idx, self.start_after.clone())
+            .await
+    }
+}
+
 impl DkgClient {
     // Some queries simply don't work the first time
     // Until we determine why that is, retry the query a few more times
-    const RETRIES: usize = 3;
+    const ATTEMPTS: u32 = 4;
+    const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+    const MAXIMUM_BACKOFF: Duration = Duration::from_secs(2);
+
+    fn retry_policy() -> RetryPolicy {
+        RetryPolicy::new(
+            ExponentialBackoff::new(Self::INITIAL_BACKOFF, Self::MAXIMUM_BACKOFF)
+                .with_jitter(Jitter::Decorrelated),
+        )
+        .with_max_attempts(Self::ATTEMPTS)
+    }
 
     pub(crate) fn new<C>(nyxd_client: C) -> Self
     where
@@ -34,19 +99,16 @@ impl DkgClient {
         }
     }
 
+    pub(crate) async fn try_failover(&self) {
+        self.inner.try_failover().await
+    }
+
     pub(crate) async fn get_address(&self) -> AccountId {
         self.inner.address().await
     }
 
     pub(crate) async fn get_current_epoch(&self) -> Result<Epoch, CoconutError> {
-        let mut ret = self.inner.get_current_epoch().await;
-        for _ in 0..Self::RETRIES {
-            if ret.is_ok() {
-                return ret;
-            }
-            ret = self.inner.get_current_epoch().await;
-        }
-        ret
+        retry(&Self::retry_policy(), || self.inner.get_current_epoch()).await
     }
 
     pub(crate) async fn group_member(&self) -> Result<MemberResponse, CoconutError> {
@@ -61,6 +123,11 @@ impl DkgClient {
         self.inner.get_current_epoch_threshold().await
     }
 
+    /// Number of dealings each dealer submits, as set in the DKG contract.
+    pub(crate) async fn get_total_dealings(&self) -> Result<usize, CoconutError> {
+        retry(&Self::retry_policy(), || self.inner.get_total_dealings()).await
+    }
+
     pub(crate) async fn get_initial_dealers(
         &self,
     ) -> Result<Option<InitialReplacementData>, CoconutError> {
@@ -77,18 +144,15 @@ impl DkgClient {
         self.inner.get_current_dealers().await
     }
 
-    pub(crate) async fn get_dealings(
-        &self,
-        idx: usize,
-    ) -> Result<Vec<ContractDealing>, CoconutError> {
-        let mut ret = self.inner.get_dealings(idx).await;
-        for _ in 0..Self::RETRIES {
-            if ret.is_ok() {
-                return ret;
-            }
-            ret = self.inner.get_dealings(idx).await;
+    /// Returns a pager over the dealings posted for the given index, starting after the dealings
+    /// of the `start_after` dealer, if specified.
+    pub(crate) fn dealings_pager(&self, idx: usize, start_after: Option<String>) -> DealingsPager {
+        DealingsPager {
+            client: self,
+            idx,
+            start_after,
+            finished: false,
         }
-        ret
     }
 
     pub(crate) async fn get_verification_key_shares(
@@ -98,6 +162,30 @@ impl DkgClient {
         self.inner.get_verification_key_shares(epoch_id).await
     }
 
+    pub(crate) async fn get_dealer_complaints(
+        &self,
+        dealer: &AccountId,
+    ) -> Result<DealerComplaintsResponse, CoconutError> {
+        retry(&Self::retry_policy(), || {
+            self.inner.get_dealer_complaints(dealer)
+        })
+        .await
+    }
+
+    pub(crate) async fn get_disputed_dealers(&self) -> Result<Vec<DisputedDealer>, CoconutError> {
+        retry(&Self::retry_policy(), || self.inner.get_disputed_dealers()).await
+    }
+
+    pub(crate) async fn get_proposal(
+        &self,
+        proposal_id: u64,
+    ) -> Result<ProposalResponse, CoconutError> {
+        retry(&Self::retry_policy(), || {
+            self.inner.get_proposal(proposal_id)
+        })
+        .await
+    }
+
     pub(crate) async fn list_proposals(&self) -> Result<Vec<ProposalResponse>, CoconutError> {
         self.inner.list_proposals().await
     }
@@ -138,33 +226,49 @@ impl DkgClient {
         Ok(())
     }
 
+    pub(crate) async fn submit_complaint(
+        &self,
+        dealer: &AccountId,
+        dealing_index: Option<u64>,
+        reason: ComplaintReason,
+        resharing: bool,
+    ) -> Result<(), CoconutError> {
+        self.inner
+            .submit_complaint(dealer, dealing_index, reason, resharing)
+            .await?;
+        Ok(())
+    }
+
     pub(crate) async fn submit_verification_key_share(
         &self,
         share: VerificationKeyShare,
         resharing: bool,
     ) -> Result<ExecuteResult, CoconutError> {
-        let mut ret = self
-            .inner
-            .submit_verification_key_share(share.clone(), resharing)
-            .await;
-        for _ in 0..Self::RETRIES {
-            if let Ok(res) = ret {
-                return Ok(res);
-            }
-            ret = self
-                .inner
+        retry(&Self::retry_policy(), || {
+            self.inner
                 .submit_verification_key_share(share.clone(), resharing)
-                .await;
-        }
-        ret
+        })
+        .await
     }
 
-    pub(crate) async fn vote_verification_key_share(
+    pub(crate) async fn accept_verification_key_share(
         &self,
         proposal_id: u64,
-        vote_yes: bool,
     ) -> Result<(), CoconutError> {
-        self.inner.vote_proposal(proposal_id, vote_yes, None).await
+        self.inner
+            .vote_proposal(proposal_id, true, None, None)
+            .await
+    }
+
+    pub(crate) async fn reject_verification_key_share(
+        &self,
+        proposal_id: u64,
+        justification: &VkShareVoteJustification,
+    ) -> Result<(), CoconutError> {
+        let memo = serde_json::to_string(justification)?;
+        self.inner
+            .vote_proposal(proposal_id, false, Some(memo), None)
+            .await
     }
 
     pub(crate) async fn execute_verification_key_share(
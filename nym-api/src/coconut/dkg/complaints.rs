@@ -1,13 +1,297 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use serde::{Deserialize, Serialize};
-
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-pub(crate) enum ComplaintReason {
-    MalformedBTEPublicKey,
-    InvalidBTEPublicKey,
-    MissingDealing,
-    MalformedDealing,
-    DealingVerificationError,
+use crate::coconut::dkg::client::DkgClient;
+use crate::coconut::dkg::state::{ConsistentState, State};
+use crate::coconut::dkg::verification_key::dealer_faults;
+use crate::coconut::error::CoconutError;
+use crate::coconut::log_context::{ctx_debug, ctx_info, ctx_warn};
+use cosmwasm_std::Addr;
+use nym_validator_client::nyxd::error::NyxdError;
+use nym_validator_client::nyxd::AccountId;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+pub(crate) use nym_coconut_dkg_common::complaints::ComplaintReason;
+
+/// Misbehaviour of a dealer found while going through the dealings posted to the contract.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct DealerFault {
+    pub(crate) dealer: Addr,
+    pub(crate) dealing_index: Option<u64>,
+    pub(crate) reason: ComplaintReason,
+}
+
+fn dealer_account(dealer: &Addr) -> Result<AccountId, CoconutError> {
+    AccountId::from_str(dealer.as_str())
+        .map_err(|_| NyxdError::MalformedAccountAddress(dealer.to_string()).into())
+}
+
+pub(crate) async fn complaint_submission(
+    dkg_client: &DkgClient,
+    state: &State,
+    resharing: bool,
+) -> Result<(), CoconutError> {
+    if state.submitted_complaints().await {
+        ctx_debug!("Complaints were submitted previously, nothing to do");
+        return Ok(());
+    }
+
+    // the dealings have been encrypted to the keys announced during the dealing exchange,
+    // so make sure nobody has swapped them since
+    let dealers = dkg_client.get_current_dealers().await?;
+    let inconsistent_dealers = state.cross_check_dealers(dealers).await;
+
+    let threshold = state.threshold().await?;
+    let faults = dealer_faults(dkg_client, state, threshold, resharing).await?;
+
+    // a single complaint can be raised against each dealer, so only report the first problem found
+    let mut complaints: BTreeMap<_, _> = state
+        .bad_dealers()
+        .await
+        .into_iter()
+        .map(|(dealer, reason)| (dealer, (None, reason)))
+        .collect();
+    for dealer in inconsistent_dealers {
+        complaints
+            .entry(dealer)
+            .or_insert((None, ComplaintReason::InconsistentBTEPublicKey));
+    }
+    for fault in faults {
+        complaints
+            .entry(fault.dealer)
+            .or_insert((fault.dealing_index, fault.reason));
+    }
+
+    let self_address = dkg_client.get_address().await;
+    let mut all_submitted = true;
+    for (dealer, (dealing_index, reason)) in complaints {
+        if dealer.as_str() == self_address.as_ref() {
+            continue;
+        }
+        let account = dealer_account(&dealer)?;
+        let existing = dkg_client.get_dealer_complaints(&account).await?;
+        if existing
+            .complaints
+            .iter()
+            .any(|complaint| complaint.complainer.as_str() == self_address.as_ref())
+        {
+            ctx_debug!("Already complained about dealer {dealer}");
+            continue;
+        }
+
+        ctx_info!("DKG: Complaining about dealer {dealer} (dealing {dealing_index:?}): {reason:?}");
+        if let Err(err) = dkg_client
+            .submit_complaint(&account, dealing_index, reason, resharing)
+            .await
+        {
+            ctx_warn!("Could not submit the complaint about dealer {dealer}: {err}");
+            all_submitted = false;
+        }
+    }
+
+    if all_submitted {
+        state.set_submitted_complaints().await;
+        ctx_info!("DKG: Submitted complaints");
+    }
+    Ok(())
+}
+
+/// Excludes the dealers that at least threshold receivers have complained about, even if their
+/// dealings seemed fine locally, so that every receiver derives its keys from the same dealers.
+pub(crate) async fn apply_upheld_complaints(
+    dkg_client: &DkgClient,
+    state: &State,
+) -> Result<(), CoconutError> {
+    let current_dealers = state.current_dealers_by_addr().await;
+    for disputed in dkg_client.get_disputed_dealers().await? {
+        if !disputed.upheld || !current_dealers.contains_key(&disputed.dealer) {
+            continue;
+        }
+
+        let account = dealer_account(&disputed.dealer)?;
+        let complaints = dkg_client.get_dealer_complaints(&account).await?;
+        if let Some(complaint) = complaints.complaints.into_iter().next() {
+            ctx_warn!(
+                "Dealer {} has been excluded by {} complaints",
+                disputed.dealer,
+                disputed.complaints
+            );
+            state
+                .mark_bad_dealer(&disputed.dealer, complaint.reason)
+                .await;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::coconut::dkg::dealing::dealing_exchange;
+    use crate::coconut::dkg::public_key::public_key_submission;
+    use crate::coconut::dkg::state::PersistentState;
+    use crate::coconut::dummy_client::DummyClient;
+    use crate::coconut::KeyPair;
+    use nym_coconut_dkg_common::complaints::Complaint;
+    use nym_coconut_dkg_common::dealer::DealerDetails;
+    use nym_coconut_dkg_common::types::DEFAULT_DEALINGS;
+    use nym_contracts_common::dealings::ContractSafeBytes;
+    use nym_dkg::bte::keys::KeyPair as DkgKeyPair;
+    use nym_dkg::bte::setup;
+    use rand::rngs::OsRng;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::{Arc, RwLock};
+    use url::Url;
+
+    const TEST_VALIDATORS_ADDRESS: [&str; 3] = [
+        "n1aq9kakfgwqcufr23lsv644apavcntrsqsk4yus",
+        "n1s9l3xr4g0rglvk4yctktmck3h4eq0gp6z2e20v",
+        "n19kl4py32vsk297dm93ezem992cdyzdy4zuc2x6",
+    ];
+
+    async fn prepare_dealers(
+        clients_and_states: &mut Vec<(DkgClient, State)>,
+        dealings_db: &Arc<RwLock<HashMap<String, Vec<ContractSafeBytes>>>>,
+        complaints_db: &Arc<RwLock<HashMap<String, Vec<Complaint>>>>,
+    ) {
+        let dealer_details_db = Arc::new(RwLock::new(HashMap::new()));
+        let threshold_db = Arc::new(RwLock::new(Some(2)));
+        for addr in TEST_VALIDATORS_ADDRESS {
+            let dkg_client = DkgClient::new(
+                DummyClient::new(AccountId::from_str(addr).unwrap())
+                    .with_dealer_details(&dealer_details_db)
+                    .with_threshold(&threshold_db)
+                    .with_dealings(dealings_db)
+                    .with_complaints(complaints_db),
+            );
+            let keypair = DkgKeyPair::new(&setup(), OsRng);
+            let state = State::new(
+                PathBuf::default(),
+                PersistentState::default(),
+                Url::parse("localhost:8000").unwrap(),
+                keypair,
+                KeyPair::new(),
+            );
+            clients_and_states.push((dkg_client, state));
+        }
+        for (dkg_client, state) in clients_and_states.iter() {
+            public_key_submission(dkg_client, state, false)
+                .await
+                .unwrap();
+        }
+        for (dkg_client, state) in clients_and_states.iter() {
+            dealing_exchange(dkg_client, state, OsRng, false)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // expensive test
+    async fn complaints_about_missing_dealings_are_upheld() {
+        let dealings_db = Arc::new(RwLock::new(HashMap::new()));
+        let complaints_db = Arc::new(RwLock::new(HashMap::new()));
+        let mut clients_and_states = vec![];
+        prepare_dealers(&mut clients_and_states, &dealings_db, &complaints_db).await;
+
+        // the last dealer didn't post all of its dealings
+        let bad_dealer = TEST_VALIDATORS_ADDRESS[2];
+        dealings_db
+            .write()
+            .unwrap()
+            .get_mut(bad_dealer)
+            .unwrap()
+            .pop();
+
+        for (dkg_client, state) in clients_and_states.iter() {
+            complaint_submission(dkg_client, state, false)
+                .await
+                .unwrap();
+            assert!(state.submitted_complaints().await);
+        }
+
+        let complaints = complaints_db.read().unwrap().clone();
+        assert_eq!(complaints.len(), 1);
+        let bad_dealer_complaints = complaints.get(bad_dealer).unwrap();
+        assert_eq!(bad_dealer_complaints.len(), 2);
+        for complaint in bad_dealer_complaints {
+            assert_eq!(complaint.reason, ComplaintReason::MissingDealing);
+            assert_eq!(complaint.dealing_index, Some(DEFAULT_DEALINGS as u64 - 1));
+        }
+
+        // complaining again doesn't duplicate anything
+        let (dkg_client, state) = &clients_and_states[0];
+        state.reset_submitted_complaints().await;
+        complaint_submission(dkg_client, state, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            complaints_db.read().unwrap().get(bad_dealer).unwrap().len(),
+            2
+        );
+
+        // the bad dealer is excluded by everyone, including itself
+        for (dkg_client, state) in clients_and_states.iter() {
+            apply_upheld_complaints(dkg_client, state).await.unwrap();
+            assert!(!state
+                .current_dealers_by_addr()
+                .await
+                .contains_key(&Addr::unchecked(bad_dealer)));
+        }
+    }
+
+    #[tokio::test]
+    async fn complaints_below_threshold_are_not_upheld() {
+        let complaints_db = Arc::new(RwLock::new(HashMap::new()));
+        let threshold_db = Arc::new(RwLock::new(Some(2)));
+        let dealer = Addr::unchecked(TEST_VALIDATORS_ADDRESS[1]);
+        let dkg_client = DkgClient::new(
+            DummyClient::new(AccountId::from_str(TEST_VALIDATORS_ADDRESS[0]).unwrap())
+                .with_threshold(&threshold_db)
+                .with_complaints(&complaints_db),
+        );
+        let state = State::new(
+            PathBuf::default(),
+            PersistentState::default(),
+            Url::parse("localhost:8000").unwrap(),
+            DkgKeyPair::new(&setup(), OsRng),
+            KeyPair::new(),
+        );
+        let keypair = DkgKeyPair::new(&setup(), OsRng);
+        let details = DealerDetails {
+            address: dealer.clone(),
+            bte_public_key_with_proof: bs58::encode(&keypair.public_key().to_bytes()).into_string(),
+            announce_address: String::new(),
+            assigned_index: 2,
+        };
+        state.set_dealers(vec![details], false).await;
+
+        dkg_client
+            .submit_complaint(
+                &dealer_account(&dealer).unwrap(),
+                Some(0),
+                ComplaintReason::DealingVerificationError,
+                false,
+            )
+            .await
+            .unwrap();
+        apply_upheld_complaints(&dkg_client, &state).await.unwrap();
+        assert!(state.current_dealers_by_addr().await.contains_key(&dealer));
+
+        complaints_db
+            .write()
+            .unwrap()
+            .get_mut(dealer.as_str())
+            .unwrap()
+            .push(Complaint {
+                complainer: Addr::unchecked(TEST_VALIDATORS_ADDRESS[2]),
+                dealer: dealer.clone(),
+                dealing_index: None,
+                reason: ComplaintReason::MissingDealing,
+            });
+        apply_upheld_complaints(&dkg_client, &state).await.unwrap();
+        assert!(!state.current_dealers_by_addr().await.contains_key(&dealer));
+    }
 }
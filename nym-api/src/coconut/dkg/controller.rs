@@ -2,25 +2,34 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::coconut::dkg::client::DkgClient;
+use crate::coconut::dkg::deadline::round_has_stalled;
+use crate::coconut::dkg::epoch_transition::epoch_transition;
 use crate::coconut::dkg::state::{ConsistentState, PersistentState, State};
 use crate::coconut::dkg::verification_key::{
     verification_key_finalization, verification_key_validation,
 };
 use crate::coconut::dkg::{
-    dealing::dealing_exchange, public_key::public_key_submission,
+    complaints::complaint_submission, dealing::dealing_exchange, public_key::public_key_submission,
     verification_key::verification_key_submission,
 };
+use crate::coconut::error::CoconutError;
 use crate::coconut::keypair::KeyPair as CoconutKeyPair;
+use crate::coconut::keystore::CoconutKeyStore;
+use crate::coconut::log_context::{
+    ctx_debug, ctx_info, ctx_warn, Ceremony, DkgLogContext, LogContext,
+};
 use crate::nyxd;
 use crate::support::config::Config;
+use crate::support::supervisor::TaskSupervisor;
 use anyhow::Result;
-use nym_coconut_dkg_common::types::EpochState;
+use nym_coconut_dkg_common::types::{Epoch, EpochId, EpochState};
 use nym_dkg::bte::keys::KeyPair as DkgKeyPair;
 use nym_task::{TaskClient, TaskManager};
 use rand::rngs::OsRng;
 use rand::{CryptoRng, RngCore};
-use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
+use tokio::sync::Mutex;
 use tokio::time::interval;
 
 pub(crate) fn init_keypair(config: &Config) -> Result<()> {
@@ -39,11 +48,15 @@ pub(crate) fn init_keypair(config: &Config) -> Result<()> {
 
 pub(crate) struct DkgController<R> {
     dkg_client: DkgClient,
-    secret_key_path: PathBuf,
-    verification_key_path: PathBuf,
+    coconut_keystore: CoconutKeyStore,
     state: State,
+    dkg_context: DkgLogContext,
     rng: R,
     polling_rate: Duration,
+    submission_deadline: Duration,
+    // verdict on whether the round has stalled, which can't change for the remainder of the state
+    // it's been reached in, so that the dealings wouldn't have to be retrieved on every poll
+    stall_verdict: Option<(EpochId, EpochState, bool)>,
 }
 
 impl<R: RngCore + CryptoRng + Clone> DkgController<R> {
@@ -51,27 +64,51 @@ impl<R: RngCore + CryptoRng + Clone> DkgController<R> {
         config: &Config,
         nyxd_client: nyxd::Client,
         coconut_keypair: CoconutKeyPair,
+        coconut_keystore: CoconutKeyStore,
+        dkg_context: DkgLogContext,
         rng: R,
     ) -> Result<Self> {
         let dkg_keypair = nym_pemstore::load_keypair(&nym_pemstore::KeyPairPath::new(
             config.decryption_key_path(),
             config.public_key_with_proof_path(),
         ))?;
-        if let Ok(coconut_keypair_value) =
-            nym_pemstore::load_keypair(&nym_pemstore::KeyPairPath::new(
-                config.secret_key_path(),
-                config.verification_key_path(),
-            ))
-        {
-            coconut_keypair.set(Some(coconut_keypair_value)).await;
+        match coconut_keystore.load() {
+            Ok(coconut_keypair_value) => coconut_keypair.set(Some(coconut_keypair_value)).await,
+            // missing keys are going to be derived anew, but the ones we failed to decrypt
+            // must not get overwritten
+            Err(
+                err @ (CoconutError::SecretKeyDecryptionError
+                | CoconutError::MissingSecretKeyPassphrase),
+            ) => return Err(err.into()),
+            Err(_) => (),
         }
-        let persistent_state =
-            PersistentState::load_from_file(config.persistent_state_path()).unwrap_or_default();
+        let persistent_state_path = config.persistent_state_path();
+        let persistent_state = match PersistentState::load_from_file(persistent_state_path.clone())
+        {
+            Ok(Some(persistent_state)) => {
+                info!(
+                    "Restored the DKG state from {}",
+                    persistent_state_path.display()
+                );
+                persistent_state
+            }
+            Ok(None) => PersistentState::default(),
+            Err(err) => {
+                // keep the unreadable snapshot around for inspection rather than overwriting it
+                let corrupted_path = persistent_state_path.with_extension("corrupted");
+                error!(
+                    "Could not restore the DKG state from {}: {err}. Starting with a fresh state - the unreadable one is moved to {}",
+                    persistent_state_path.display(),
+                    corrupted_path.display()
+                );
+                std::fs::rename(&persistent_state_path, corrupted_path).ok();
+                PersistentState::default()
+            }
+        };
 
         Ok(DkgController {
             dkg_client: DkgClient::new(nyxd_client),
-            secret_key_path: config.secret_key_path(),
-            verification_key_path: config.verification_key_path(),
+            coconut_keystore,
             state: State::new(
                 config.persistent_state_path(),
                 persistent_state,
@@ -79,112 +116,174 @@ impl<R: RngCore + CryptoRng + Clone> DkgController<R> {
                 dkg_keypair,
                 coconut_keypair,
             ),
+            dkg_context,
             rng,
             polling_rate: config.get_dkg_contract_polling_rate(),
+            submission_deadline: config.get_dkg_submission_deadline(),
+            stall_verdict: None,
         })
     }
 
     async fn dump_persistent_state(&self) {
         if !self.state.coconut_keypair_is_some().await {
             // Delete the files just in case the process is killed before the new keys are generated
-            std::fs::remove_file(&self.secret_key_path).ok();
-            std::fs::remove_file(&self.verification_key_path).ok();
+            self.coconut_keystore.remove();
         }
-        let persistent_state = PersistentState::from(&self.state);
+        let persistent_state = PersistentState::from_state(&self.state).await;
         if let Err(err) = persistent_state.save_to_file(self.state.persistent_state_path()) {
-            warn!("Could not backup the state for this iteration: {err}");
+            ctx_warn!("Could not backup the state for this iteration: {err}");
+        }
+    }
+
+    async fn round_has_stalled(&mut self, epoch: &Epoch) -> bool {
+        if let Some((epoch_id, state, stalled)) = self.stall_verdict {
+            if epoch_id == epoch.epoch_id && state == epoch.state {
+                return stalled;
+            }
+        }
+        let Ok(current_timestamp) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) else {
+            return false;
+        };
+        match round_has_stalled(
+            &self.dkg_client,
+            &self.state,
+            epoch,
+            self.submission_deadline,
+            current_timestamp.as_secs(),
+        )
+        .await
+        {
+            Ok(Some(stalled)) => {
+                self.stall_verdict = Some((epoch.epoch_id, epoch.state, stalled));
+                stalled
+            }
+            Ok(None) => false,
+            Err(err) => {
+                ctx_warn!("Could not check whether the DKG round has stalled: {err}");
+                if err.is_chain_error() {
+                    self.dkg_client.try_failover().await;
+                }
+                false
+            }
         }
     }
 
+    async fn abort_round(&mut self, epoch_id: EpochId) {
+        ctx_warn!("DKG: Aborting the round and waiting for the next epoch");
+        self.state.reset_persistent(true).await;
+        // make sure the aborted round isn't re-entered after a restart
+        self.state.set_aborted_epoch(epoch_id).await;
+        self.dump_persistent_state().await;
+    }
+
     pub(crate) async fn handle_epoch_state(&mut self) {
         match self.dkg_client.get_current_epoch().await {
-            Err(err) => warn!("Could not get current epoch state {err}"),
+            Err(err) => {
+                ctx_warn!("Could not get current epoch state {err}");
+                self.dkg_client.try_failover().await;
+            }
             Ok(epoch) => {
-                if self
-                    .dkg_client
-                    .group_member()
+                // everything logged from now on is attributed to the ceremony of this epoch
+                let context = LogContext::new(Some(epoch.epoch_id), Ceremony::Dkg);
+                self.dkg_context.set(context.clone());
+                context.scope(self.handle_epoch(epoch)).await
+            }
+        }
+    }
+
+    async fn handle_epoch(&mut self, epoch: Epoch) {
+        self.state.set_epoch_state(epoch.state).await;
+        if self
+            .dkg_client
+            .group_member()
+            .await
+            .map(|resp| resp.weight.is_none())
+            .unwrap_or(true)
+        {
+            ctx_debug!("Not a member of the group, DKG won't be run");
+            return;
+        }
+        match epoch_transition(
+            &self.dkg_client,
+            &self.state,
+            &self.coconut_keystore,
+            &epoch,
+        )
+        .await
+        {
+            // make sure the archived keys don't get overwritten by the previous epoch's state on restart
+            Ok(true) => self.dump_persistent_state().await,
+            Ok(false) => (),
+            Err(err) => {
+                ctx_warn!("Could not rotate the keys of the previous epoch: {err}");
+                if err.is_chain_error() {
+                    self.dkg_client.try_failover().await;
+                }
+                // don't make any progress using the state of the previous epoch
+                return;
+            }
+        }
+        if self.state.aborted_epoch().await == Some(epoch.epoch_id) {
+            ctx_debug!("The DKG round of this epoch was aborted. Awaiting for the next epoch.");
+        } else if let Err(err) = self.state.is_consistent(epoch.state).await {
+            ctx_debug!("Epoch state is corrupted - {err}. Awaiting for a DKG restart.");
+        } else if self.round_has_stalled(&epoch).await {
+            self.abort_round(epoch.epoch_id).await;
+        } else {
+            let ret = match epoch.state {
+                EpochState::PublicKeySubmission { resharing } => {
+                    public_key_submission(&self.dkg_client, &self.state, resharing).await
+                }
+                EpochState::DealingExchange { resharing } => {
+                    dealing_exchange(&self.dkg_client, &self.state, self.rng.clone(), resharing)
+                        .await
+                }
+                EpochState::ComplaintSubmission { resharing } => {
+                    complaint_submission(&self.dkg_client, &self.state, resharing).await
+                }
+                EpochState::VerificationKeySubmission { resharing } => {
+                    verification_key_submission(
+                        &self.dkg_client,
+                        &self.state,
+                        &self.coconut_keystore,
+                        resharing,
+                    )
                     .await
-                    .map(|resp| resp.weight.is_none())
-                    .unwrap_or(true)
-                {
-                    debug!("Not a member of the group, DKG won't be run");
-                    return;
                 }
-                if let Err(err) = self.state.is_consistent(epoch.state).await {
-                    debug!("Epoch state is corrupted - {err}. Awaiting for a DKG restart.");
-                } else {
-                    let ret = match epoch.state {
-                        EpochState::PublicKeySubmission { resharing } => {
-                            public_key_submission(&self.dkg_client, &mut self.state, resharing)
-                                .await
-                        }
-                        EpochState::DealingExchange { resharing } => {
-                            dealing_exchange(
-                                &self.dkg_client,
-                                &mut self.state,
-                                self.rng.clone(),
-                                resharing,
-                            )
-                            .await
-                        }
-                        EpochState::VerificationKeySubmission { resharing } => {
-                            let keypair_path = nym_pemstore::KeyPairPath::new(
-                                self.secret_key_path.clone(),
-                                self.verification_key_path.clone(),
-                            );
-                            verification_key_submission(
-                                &self.dkg_client,
-                                &mut self.state,
-                                &keypair_path,
-                                resharing,
-                            )
-                            .await
-                        }
-                        EpochState::VerificationKeyValidation { resharing } => {
-                            verification_key_validation(
-                                &self.dkg_client,
-                                &mut self.state,
-                                resharing,
-                            )
-                            .await
-                        }
-                        EpochState::VerificationKeyFinalization { resharing } => {
-                            verification_key_finalization(
-                                &self.dkg_client,
-                                &mut self.state,
-                                resharing,
-                            )
-                            .await
-                        }
-                        // Just wait, in case we need to redo dkg at some point
-                        EpochState::InProgress => {
-                            self.state.set_was_in_progress();
-                            // We're dumping state here so that we don't do it uselessly during the
-                            // long InProgress state
-                            self.dump_persistent_state().await;
-                            Ok(())
-                        }
-                    };
-                    if let Err(err) = ret {
-                        warn!("Could not handle this iteration for the epoch state: {err}");
-                    } else if epoch.state != EpochState::InProgress {
-                        self.dump_persistent_state().await;
-                    }
+                EpochState::VerificationKeyValidation { resharing } => {
+                    verification_key_validation(&self.dkg_client, &self.state, resharing).await
+                }
+                EpochState::VerificationKeyFinalization { resharing } => {
+                    verification_key_finalization(&self.dkg_client, &self.state, resharing).await
+                }
+                // Just wait, in case we need to redo dkg at some point
+                EpochState::InProgress => {
+                    self.state.set_was_in_progress().await;
+                    // We're dumping state here so that we don't do it uselessly during the
+                    // long InProgress state
+                    self.dump_persistent_state().await;
+                    Ok(())
                 }
-                if let Ok(current_timestamp) =
-                    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)
-                {
-                    if current_timestamp.as_secs() >= epoch.finish_timestamp.seconds() {
-                        // We try advancing the epoch state, on a best-effort basis
-                        info!("DKG: Trying to advance the epoch");
-                        self.dkg_client.advance_epoch_state().await.ok();
-                    }
+            };
+            if let Err(err) = ret {
+                ctx_warn!("Could not handle this iteration for the epoch state: {err}");
+                if err.is_chain_error() {
+                    self.dkg_client.try_failover().await;
                 }
+            } else if epoch.state != EpochState::InProgress {
+                self.dump_persistent_state().await;
+            }
+        }
+        if let Ok(current_timestamp) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            if current_timestamp.as_secs() >= epoch.finish_timestamp.seconds() {
+                // We try advancing the epoch state, on a best-effort basis
+                ctx_info!("DKG: Trying to advance the epoch");
+                self.dkg_client.advance_epoch_state().await.ok();
             }
         }
     }
 
-    pub(crate) async fn run(mut self, mut shutdown: TaskClient) {
+    pub(crate) async fn run(&mut self, mut shutdown: TaskClient) {
         let mut interval = interval(self.polling_rate);
         while !shutdown.is_shutdown() {
             tokio::select! {
@@ -194,6 +293,8 @@ impl<R: RngCore + CryptoRng + Clone> DkgController<R> {
                 }
             }
         }
+        // make sure whatever progress has been made survives the restart
+        self.dump_persistent_state().await;
     }
 
     // TODO: can we make it non-async? it seems we'd have to modify `coconut_keypair.set(coconut_keypair_value)` in new
@@ -202,15 +303,35 @@ impl<R: RngCore + CryptoRng + Clone> DkgController<R> {
         config: &Config,
         nyxd_client: nyxd::Client,
         coconut_keypair: CoconutKeyPair,
+        coconut_keystore: CoconutKeyStore,
+        dkg_context: DkgLogContext,
         rng: R,
+        supervisor: &TaskSupervisor,
         shutdown: &TaskManager,
-    ) -> Result<()>
+    ) -> Result<State>
     where
         R: Sync + Send + 'static,
     {
-        let shutdown_listener = shutdown.subscribe();
-        let dkg_controller = DkgController::new(config, nyxd_client, coconut_keypair, rng).await?;
-        tokio::spawn(async move { dkg_controller.run(shutdown_listener).await });
-        Ok(())
+        let dkg_controller = DkgController::new(
+            config,
+            nyxd_client,
+            coconut_keypair,
+            coconut_keystore,
+            dkg_context,
+            rng,
+        )
+        .await?;
+        let state = dkg_controller.state.clone();
+        // the restarted controller carries on with the same state, which is also shared with the
+        // credential issuance
+        let dkg_controller = Arc::new(Mutex::new(dkg_controller));
+        supervisor.spawn("dkg-controller", shutdown, move |shutdown| {
+            let dkg_controller = Arc::clone(&dkg_controller);
+            async move {
+                dkg_controller.lock().await.run(shutdown).await;
+                Ok(())
+            }
+        });
+        Ok(state)
     }
 }
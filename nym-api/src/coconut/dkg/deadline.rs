@@ -0,0 +1,235 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::coconut::dkg::client::DkgClient;
+use crate::coconut::dkg::state::{ConsistentState, State};
+use crate::coconut::error::CoconutError;
+use crate::coconut::log_context::ctx_warn;
+use cosmwasm_std::Addr;
+use nym_coconut_dkg_common::types::{Epoch, EpochState};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// What the current epoch state relies on having been submitted earlier in the round.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Submissions {
+    Dealings,
+    VerificationKeyShares,
+}
+
+/// Returns what has to have been submitted for the epoch to be able to progress from its current
+/// state alongside the timestamp (in seconds) at which it was due, i.e. when the state it was
+/// supposed to be submitted in has ended.
+fn due_submissions(epoch: &Epoch) -> Option<(Submissions, u64)> {
+    let state_start = epoch.state_start_timestamp_secs();
+    let time_configuration = epoch.time_configuration;
+    match epoch.state {
+        EpochState::ComplaintSubmission { .. } => Some((Submissions::Dealings, state_start)),
+        EpochState::VerificationKeySubmission { .. } => Some((
+            Submissions::Dealings,
+            state_start.saturating_sub(time_configuration.complaint_submission_time_secs),
+        )),
+        EpochState::VerificationKeyValidation { .. } => {
+            Some((Submissions::VerificationKeyShares, state_start))
+        }
+        EpochState::VerificationKeyFinalization { .. } => Some((
+            Submissions::VerificationKeyShares,
+            state_start.saturating_sub(time_configuration.verification_key_validation_time_secs),
+        )),
+        _ => None,
+    }
+}
+
+/// Number of dealers that have posted all of their dealings.
+async fn complete_dealers(dkg_client: &DkgClient) -> Result<usize, CoconutError> {
+    let total_dealings = dkg_client.get_total_dealings().await?;
+    let mut dealings_per_dealer: HashMap<Addr, usize> = HashMap::new();
+    for idx in 0..total_dealings {
+        let mut pager = dkg_client.dealings_pager(idx, None);
+        while let Some(page) = pager.next_page().await? {
+            for contract_dealing in page {
+                *dealings_per_dealer
+                    .entry(contract_dealing.dealer)
+                    .or_default() += 1;
+            }
+        }
+    }
+    Ok(dealings_per_dealer
+        .values()
+        .filter(|&&dealings| dealings == total_dealings)
+        .count())
+}
+
+/// Checks whether, `deadline` after the dealings or the verification key shares were due, fewer
+/// than threshold participants have submitted them, in which case the round can no longer complete.
+/// Returns `None` if there's nothing to check (yet). Otherwise, since nothing else can be submitted
+/// past the due date, the verdict holds for the remainder of the current epoch state.
+pub(crate) async fn round_has_stalled(
+    dkg_client: &DkgClient,
+    state: &State,
+    epoch: &Epoch,
+    deadline: Duration,
+    current_timestamp_secs: u64,
+) -> Result<Option<bool>, CoconutError> {
+    let Some((submissions, due)) = due_submissions(epoch) else {
+        return Ok(None);
+    };
+    if current_timestamp_secs < due.saturating_add(deadline.as_secs()) {
+        return Ok(None);
+    }
+
+    let threshold = match state.threshold().await {
+        Ok(threshold) => threshold,
+        Err(err) => {
+            ctx_warn!("The DKG round can't be completed: {err}");
+            return Ok(Some(true));
+        }
+    };
+    let submitted = match submissions {
+        Submissions::Dealings => complete_dealers(dkg_client).await?,
+        Submissions::VerificationKeyShares => dkg_client
+            .get_verification_key_shares(epoch.epoch_id)
+            .await?
+            .len(),
+    };
+    if (submitted as u64) < threshold {
+        ctx_warn!(
+            "Only {submitted} participants submitted their {submissions:?} by the deadline, while {threshold} are required"
+        );
+        return Ok(Some(true));
+    }
+    Ok(Some(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coconut::dkg::state::PersistentState;
+    use crate::coconut::dummy_client::DummyClient;
+    use crate::coconut::KeyPair;
+    use nym_coconut_dkg_common::dealer::DealerDetails;
+    use nym_coconut_dkg_common::types::{TimeConfiguration, Timestamp};
+    use nym_coconut_dkg_common::verification_key::ContractVKShare;
+    use nym_dkg::bte::keys::KeyPair as DkgKeyPair;
+    use nym_dkg::bte::setup;
+    use nym_validator_client::nyxd::AccountId;
+    use rand::rngs::OsRng;
+    use std::path::PathBuf;
+    use std::str::FromStr;
+    use std::sync::{Arc, RwLock};
+    use url::Url;
+
+    const TEST_VALIDATORS_ADDRESS: [&str; 2] = [
+        "n1aq9kakfgwqcufr23lsv644apavcntrsqsk4yus",
+        "n1s9l3xr4g0rglvk4yctktmck3h4eq0gp6z2e20v",
+    ];
+    const DEADLINE: Duration = Duration::from_secs(60);
+
+    async fn prepare_state() -> State {
+        let state = State::new(
+            PathBuf::default(),
+            PersistentState::default(),
+            Url::parse("localhost:8000").unwrap(),
+            DkgKeyPair::new(&setup(), OsRng),
+            KeyPair::new(),
+        );
+        let dealers = TEST_VALIDATORS_ADDRESS
+            .iter()
+            .enumerate()
+            .map(|(idx, addr)| {
+                let keypair = DkgKeyPair::new(&setup(), OsRng);
+                DealerDetails {
+                    address: Addr::unchecked(*addr),
+                    bte_public_key_with_proof: bs58::encode(&keypair.public_key().to_bytes())
+                        .into_string(),
+                    announce_address: String::new(),
+                    assigned_index: idx as u64 + 1,
+                }
+            })
+            .collect();
+        state.set_dealers(dealers, false).await;
+        state.set_threshold(Some(2)).await;
+        state
+    }
+
+    fn vk_share(owner: &str) -> ContractVKShare {
+        ContractVKShare {
+            share: String::new(),
+            announce_address: String::new(),
+            node_index: 1,
+            owner: Addr::unchecked(owner),
+            epoch_id: 0,
+            verified: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn round_stalls_without_threshold_verification_key_shares() {
+        let verification_shares = Arc::new(RwLock::new(HashMap::new()));
+        let dkg_client = DkgClient::new(
+            DummyClient::new(AccountId::from_str(TEST_VALIDATORS_ADDRESS[0]).unwrap())
+                .with_verification_share(&verification_shares),
+        );
+        let state = prepare_state().await;
+        verification_shares.write().unwrap().insert(
+            TEST_VALIDATORS_ADDRESS[0].to_string(),
+            vk_share(TEST_VALIDATORS_ADDRESS[0]),
+        );
+
+        let time_configuration = TimeConfiguration::default();
+        let epoch = Epoch::new(
+            EpochState::VerificationKeyFinalization { resharing: false },
+            0,
+            time_configuration,
+            Timestamp::from_seconds(1000),
+        );
+        let due = 1000 - time_configuration.verification_key_validation_time_secs;
+
+        // the shares were due before the validation started, so the deadline counts from then on
+        let before_deadline = due + DEADLINE.as_secs() - 1;
+        assert_eq!(
+            round_has_stalled(&dkg_client, &state, &epoch, DEADLINE, before_deadline)
+                .await
+                .unwrap(),
+            None
+        );
+        let after_deadline = due + DEADLINE.as_secs();
+        assert_eq!(
+            round_has_stalled(&dkg_client, &state, &epoch, DEADLINE, after_deadline)
+                .await
+                .unwrap(),
+            Some(true)
+        );
+
+        verification_shares.write().unwrap().insert(
+            TEST_VALIDATORS_ADDRESS[1].to_string(),
+            vk_share(TEST_VALIDATORS_ADDRESS[1]),
+        );
+        assert_eq!(
+            round_has_stalled(&dkg_client, &state, &epoch, DEADLINE, after_deadline)
+                .await
+                .unwrap(),
+            Some(false)
+        );
+    }
+
+    #[tokio::test]
+    async fn round_doesnt_stall_before_submissions_are_due() {
+        let dkg_client = DkgClient::new(DummyClient::new(
+            AccountId::from_str(TEST_VALIDATORS_ADDRESS[0]).unwrap(),
+        ));
+        let state = prepare_state().await;
+        let epoch = Epoch::new(
+            EpochState::DealingExchange { resharing: false },
+            0,
+            TimeConfiguration::default(),
+            Timestamp::from_seconds(1000),
+        );
+        assert_eq!(
+            round_has_stalled(&dkg_client, &state, &epoch, DEADLINE, u64::MAX)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -2,39 +2,71 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::coconut::dkg::client::DkgClient;
-use crate::coconut::dkg::state::{ConsistentState, State};
+use crate::coconut::dkg::state::{ConsistentState, PersistentState, State};
 use crate::coconut::error::CoconutError;
-use log::debug;
-use nym_coconut_dkg_common::types::TOTAL_DEALINGS;
+use crate::coconut::log_context::{ctx_debug, ctx_info, ctx_warn};
 use nym_contracts_common::dealings::ContractSafeBytes;
 use nym_dkg::bte::setup;
 use nym_dkg::Dealing;
 use rand::RngCore;
 use std::collections::VecDeque;
 
+/// Number of dealings of the given dealer that have already made it to the chain.
+/// The contract fills the dealing indices in order, so the first missing one ends the count.
+async fn submitted_dealings_on_chain(
+    dkg_client: &DkgClient,
+    dealer: &str,
+    total_dealings: usize,
+) -> Result<usize, CoconutError> {
+    for idx in 0..total_dealings {
+        let mut found = false;
+        let mut pager = dkg_client.dealings_pager(idx, None);
+        while let Some(page) = pager.next_page().await? {
+            if page.iter().any(|dealing| dealing.dealer.as_str() == dealer) {
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            return Ok(idx);
+        }
+    }
+    Ok(total_dealings)
+}
+
+// generating the dealings for a large set of receivers takes a while, so the progress is saved
+// after each one of them rather than at the end of the DKG iteration
+async fn save_dealing_progress(state: &State) {
+    let persistent_state = PersistentState::from_state(state).await;
+    if let Err(err) = persistent_state.save_to_file(state.persistent_state_path()) {
+        ctx_warn!("Could not save the progress of the dealing exchange: {err}");
+    }
+}
+
 pub(crate) async fn dealing_exchange(
     dkg_client: &DkgClient,
-    state: &mut State,
+    state: &State,
     rng: impl RngCore + Clone,
     resharing: bool,
 ) -> Result<(), CoconutError> {
-    if state.receiver_index().is_some() {
-        debug!("Receiver index was set previously, nothing to do");
+    if state.receiver_index().await.is_some() {
+        ctx_debug!("Receiver index was set previously, nothing to do");
         return Ok(());
     }
 
     let dealers = dkg_client.get_current_dealers().await?;
     let threshold = dkg_client.get_current_epoch_threshold().await?;
+    let total_dealings = dkg_client.get_total_dealings().await?;
     let initial_dealers = dkg_client
         .get_initial_dealers()
         .await?
         .map(|d| d.initial_dealers)
         .unwrap_or_default();
     let own_address = dkg_client.get_address().await.as_ref().to_string();
-    state.set_dealers(dealers);
-    state.set_threshold(threshold);
-    let receivers = state.current_dealers_by_idx();
-    let dealer_index = state.node_index_value()?;
+    state.set_dealers(dealers, resharing).await;
+    state.set_threshold(threshold).await;
+    let receivers = state.current_dealers_by_idx().await;
+    let dealer_index = state.node_index_value().await?;
     let receiver_index = receivers
         .keys()
         .position(|node_index| *node_index == dealer_index);
@@ -43,16 +75,16 @@ pub(crate) async fn dealing_exchange(
         // Double check that we are in resharing mode
         if resharing {
             let (x, mut scalars) = sk.into_raw();
-            if scalars.len() + 1 != TOTAL_DEALINGS {
+            if scalars.len() + 1 != total_dealings {
                 return Err(CoconutError::CorruptedCoconutKeyPair);
             }
             // We can now erase the keypair from memory
-            debug!("Removing coconut keypair from memory");
+            ctx_debug!("Removing coconut keypair from memory");
             state.set_coconut_keypair(None).await;
             scalars.push(x);
             scalars
         } else {
-            log::warn!("Coconut key hasn't been reset in memory. The state might be corrupt");
+            ctx_warn!("Coconut key hasn't been reset in memory. The state might be corrupt");
             vec![]
         }
     } else {
@@ -61,30 +93,63 @@ pub(crate) async fn dealing_exchange(
     let mut prior_resharing_secrets = VecDeque::from(prior_resharing_secrets);
     if !resharing || initial_dealers.iter().any(|d| *d == own_address) {
         let params = setup();
-        for _ in 0..TOTAL_DEALINGS {
-            debug!(
-                "Submitting dealing for indexes {:?} with resharing: {}",
-                receivers.keys().collect::<Vec<_>>(),
-                prior_resharing_secrets.front().is_some()
-            );
-            let (dealing, _) = Dealing::create(
-                rng.clone(),
-                &params,
-                dealer_index,
-                state.threshold()?,
-                &receivers,
-                prior_resharing_secrets.pop_front(),
+        let threshold = state.threshold().await?;
+
+        let submitted =
+            submitted_dealings_on_chain(dkg_client, &own_address, total_dealings).await?;
+        if submitted > state.submitted_dealings().await {
+            // the pending dealing went through before we got to record it
+            state.set_pending_dealing(None).await;
+        }
+        state.set_submitted_dealings(submitted).await;
+        if submitted > 0 {
+            ctx_info!(
+                "DKG: Resuming dealing exchange, {submitted}/{total_dealings} dealings have already been submitted"
             );
-            dkg_client
-                .submit_dealing(ContractSafeBytes::from(&dealing), resharing)
-                .await?;
+        }
+        // the secrets of the dealings that have already been submitted are not needed anymore
+        prior_resharing_secrets.drain(..submitted.min(prior_resharing_secrets.len()));
+
+        for idx in submitted..total_dealings {
+            let dealing_bytes = match state.pending_dealing().await {
+                Some(dealing_bytes) => {
+                    ctx_debug!("Reusing the dealing generated before the restart");
+                    prior_resharing_secrets.pop_front();
+                    dealing_bytes
+                }
+                None => {
+                    ctx_debug!(
+                        "Generating dealing for indexes {:?} with resharing: {}",
+                        receivers.keys().collect::<Vec<_>>(),
+                        prior_resharing_secrets.front().is_some()
+                    );
+                    let (dealing, _) = Dealing::create(
+                        rng.clone(),
+                        &params,
+                        dealer_index,
+                        threshold,
+                        &receivers,
+                        prior_resharing_secrets.pop_front(),
+                    );
+                    let dealing_bytes = ContractSafeBytes::from(&dealing);
+                    state.set_pending_dealing(Some(dealing_bytes.clone())).await;
+                    save_dealing_progress(state).await;
+                    dealing_bytes
+                }
+            };
+
+            dkg_client.submit_dealing(dealing_bytes, resharing).await?;
+            state.set_pending_dealing(None).await;
+            state.set_submitted_dealings(idx + 1).await;
+            save_dealing_progress(state).await;
+            ctx_info!("DKG: Submitted dealing {}/{total_dealings}", idx + 1);
         }
     } else {
-        debug!("Nothing to do, waiting for initial dealers to submit dealings");
+        ctx_debug!("Nothing to do, waiting for initial dealers to submit dealings");
     }
 
-    info!("DKG: Finished dealing exchange");
-    state.set_receiver_index(receiver_index);
+    ctx_info!("DKG: Finished dealing exchange");
+    state.set_receiver_index(receiver_index).await;
 
     Ok(())
 }
@@ -93,13 +158,12 @@ pub(crate) async fn dealing_exchange(
 pub(crate) mod tests {
     use super::*;
     use crate::coconut::dkg::complaints::ComplaintReason;
-    use crate::coconut::dkg::state::PersistentState;
-    use crate::coconut::tests::DummyClient;
+    use crate::coconut::dummy_client::DummyClient;
     use crate::coconut::KeyPair;
     use cosmwasm_std::Addr;
     use nym_coconut::{ttp_keygen, Parameters};
     use nym_coconut_dkg_common::dealer::DealerDetails;
-    use nym_coconut_dkg_common::types::InitialReplacementData;
+    use nym_coconut_dkg_common::types::{InitialReplacementData, DEFAULT_DEALINGS};
     use nym_dkg::bte::keys::KeyPair as DkgKeyPair;
     use nym_dkg::bte::{Params, PublicKeyWithProof};
     use nym_validator_client::nyxd::AccountId;
@@ -157,38 +221,42 @@ pub(crate) mod tests {
                 .with_threshold(&threshold_db),
         );
         let params = setup();
-        let mut state = State::new(
+        let state = State::new(
             PathBuf::default(),
             PersistentState::default(),
             Url::parse("localhost:8000").unwrap(),
             DkgKeyPair::new(&params, OsRng),
             KeyPair::new(),
         );
-        state.set_node_index(Some(self_index));
+        state.set_node_index(Some(self_index)).await;
         let keypairs = insert_dealers(&params, &dealer_details_db);
 
-        dealing_exchange(&dkg_client, &mut state, OsRng, false)
+        dealing_exchange(&dkg_client, &state, OsRng, false)
             .await
             .unwrap();
 
         assert_eq!(
-            state.current_dealers_by_idx().values().collect::<Vec<_>>(),
+            state
+                .current_dealers_by_idx()
+                .await
+                .values()
+                .collect::<Vec<_>>(),
             keypairs
                 .iter()
                 .map(|k| k.public_key().public_key())
                 .collect::<Vec<_>>()
         );
-        assert_eq!(state.threshold().unwrap(), 2);
-        assert_eq!(state.receiver_index().unwrap(), 1);
+        assert_eq!(state.threshold().await.unwrap(), 2);
+        assert_eq!(state.receiver_index().await.unwrap(), 1);
         let dealings = dealings_db
             .read()
             .unwrap()
             .get(TEST_VALIDATORS_ADDRESS[0])
             .unwrap()
             .clone();
-        assert_eq!(dealings.len(), TOTAL_DEALINGS);
+        assert_eq!(dealings.len(), DEFAULT_DEALINGS);
 
-        dealing_exchange(&dkg_client, &mut state, OsRng, false)
+        dealing_exchange(&dkg_client, &state, OsRng, false)
             .await
             .unwrap();
         let new_dealings = dealings_db
@@ -200,6 +268,96 @@ pub(crate) mod tests {
         assert_eq!(dealings, new_dealings);
     }
 
+    #[tokio::test]
+    #[ignore] // expensive test
+    async fn exchange_dealing_follows_the_contract_total() {
+        let total_dealings = 3;
+        let dealer_details_db = Arc::new(RwLock::new(HashMap::new()));
+        let dealings_db = Arc::new(RwLock::new(HashMap::new()));
+        let threshold_db = Arc::new(RwLock::new(Some(2)));
+        let dkg_client = DkgClient::new(
+            DummyClient::new(AccountId::from_str(TEST_VALIDATORS_ADDRESS[0]).unwrap())
+                .with_dealer_details(&dealer_details_db)
+                .with_dealings(&dealings_db)
+                .with_threshold(&threshold_db)
+                .with_total_dealings(total_dealings),
+        );
+        let params = setup();
+        let state = State::new(
+            PathBuf::default(),
+            PersistentState::default(),
+            Url::parse("localhost:8000").unwrap(),
+            DkgKeyPair::new(&params, OsRng),
+            KeyPair::new(),
+        );
+        state.set_node_index(Some(2)).await;
+        insert_dealers(&params, &dealer_details_db);
+
+        dealing_exchange(&dkg_client, &state, OsRng, false)
+            .await
+            .unwrap();
+
+        let dealings = dealings_db
+            .read()
+            .unwrap()
+            .get(TEST_VALIDATORS_ADDRESS[0])
+            .unwrap()
+            .clone();
+        assert_eq!(dealings.len(), total_dealings);
+        assert_eq!(state.submitted_dealings().await, total_dealings);
+    }
+
+    #[tokio::test]
+    #[ignore] // expensive test
+    async fn resumed_dealing_exchange_does_not_redo_dealings() {
+        let self_index = 2;
+        let dealer_details_db = Arc::new(RwLock::new(HashMap::new()));
+        let dealings_db = Arc::new(RwLock::new(HashMap::new()));
+        let threshold_db = Arc::new(RwLock::new(Some(2)));
+        let dkg_client = DkgClient::new(
+            DummyClient::new(AccountId::from_str(TEST_VALIDATORS_ADDRESS[0]).unwrap())
+                .with_dealer_details(&dealer_details_db)
+                .with_dealings(&dealings_db)
+                .with_threshold(&threshold_db),
+        );
+        let params = setup();
+        let state = State::new(
+            PathBuf::default(),
+            PersistentState::default(),
+            Url::parse("localhost:8000").unwrap(),
+            DkgKeyPair::new(&params, OsRng),
+            KeyPair::new(),
+        );
+        state.set_node_index(Some(self_index)).await;
+        insert_dealers(&params, &dealer_details_db);
+
+        // the process got restarted after the first dealing was submitted and the second one generated
+        let submitted = ContractSafeBytes(vec![1]);
+        let pending = ContractSafeBytes(vec![2]);
+        dealings_db.write().unwrap().insert(
+            TEST_VALIDATORS_ADDRESS[0].to_string(),
+            vec![submitted.clone()],
+        );
+        state.set_submitted_dealings(1).await;
+        state.set_pending_dealing(Some(pending.clone())).await;
+
+        dealing_exchange(&dkg_client, &state, OsRng, false)
+            .await
+            .unwrap();
+
+        let dealings = dealings_db
+            .read()
+            .unwrap()
+            .get(TEST_VALIDATORS_ADDRESS[0])
+            .unwrap()
+            .clone();
+        assert_eq!(dealings.len(), DEFAULT_DEALINGS);
+        assert_eq!(dealings[0], submitted);
+        assert_eq!(dealings[1], pending);
+        assert_eq!(state.submitted_dealings().await, DEFAULT_DEALINGS);
+        assert!(state.pending_dealing().await.is_none());
+    }
+
     #[tokio::test]
     #[ignore] // expensive test
     async fn invalid_bte_proof_dealing_posted() {
@@ -214,14 +372,14 @@ pub(crate) mod tests {
                 .with_threshold(&threshold_db),
         );
         let params = setup();
-        let mut state = State::new(
+        let state = State::new(
             PathBuf::default(),
             PersistentState::default(),
             Url::parse("localhost:8000").unwrap(),
             DkgKeyPair::new(&params, OsRng),
             KeyPair::new(),
         );
-        state.set_node_index(Some(self_index));
+        state.set_node_index(Some(self_index)).await;
         insert_dealers(&params, &dealer_details_db);
 
         dealer_details_db
@@ -247,12 +405,13 @@ pub(crate) mod tests {
                 details.0.bte_public_key_with_proof = bs58::encode(&bytes).into_string();
             });
 
-        dealing_exchange(&dkg_client, &mut state, OsRng, false)
+        dealing_exchange(&dkg_client, &state, OsRng, false)
             .await
             .unwrap();
         assert_eq!(
             *state
                 .all_dealers()
+                .await
                 .get(&Addr::unchecked(TEST_VALIDATORS_ADDRESS[1]))
                 .unwrap()
                 .as_ref()
@@ -286,40 +445,44 @@ pub(crate) mod tests {
         let coconut_keypair = KeyPair::new();
         coconut_keypair.set(Some(keys.pop().unwrap())).await;
 
-        let mut state = State::new(
+        let state = State::new(
             PathBuf::default(),
             PersistentState::default(),
             Url::parse("localhost:8000").unwrap(),
             DkgKeyPair::new(&params, OsRng),
             coconut_keypair.clone(),
         );
-        state.set_node_index(Some(self_index));
+        state.set_node_index(Some(self_index)).await;
         let keypairs = insert_dealers(&params, &dealer_details_db);
 
-        dealing_exchange(&dkg_client, &mut state, OsRng, true)
+        dealing_exchange(&dkg_client, &state, OsRng, true)
             .await
             .unwrap();
 
         assert_eq!(
-            state.current_dealers_by_idx().values().collect::<Vec<_>>(),
+            state
+                .current_dealers_by_idx()
+                .await
+                .values()
+                .collect::<Vec<_>>(),
             keypairs
                 .iter()
                 .map(|k| k.public_key().public_key())
                 .collect::<Vec<_>>()
         );
-        assert_eq!(state.threshold().unwrap(), 3);
-        assert_eq!(state.receiver_index().unwrap(), 1);
+        assert_eq!(state.threshold().await.unwrap(), 3);
+        assert_eq!(state.receiver_index().await.unwrap(), 1);
         let addr = dkg_client.get_address().await;
         assert!(dealings_db.read().unwrap().get(addr.as_ref()).is_none());
 
-        let mut state = State::new(
+        let state = State::new(
             PathBuf::default(),
             PersistentState::default(),
             Url::parse("localhost:8000").unwrap(),
             DkgKeyPair::new(&params, OsRng),
             coconut_keypair,
         );
-        state.set_node_index(Some(self_index));
+        state.set_node_index(Some(self_index)).await;
         // Use a client that is in the initial dealers set
         let dkg_client = DkgClient::new(
             DummyClient::new(AccountId::from_str(TEST_VALIDATORS_ADDRESS[0]).unwrap())
@@ -329,7 +492,7 @@ pub(crate) mod tests {
                 .with_initial_dealers_db(&initial_dealers_db),
         );
 
-        dealing_exchange(&dkg_client, &mut state, OsRng, true)
+        dealing_exchange(&dkg_client, &state, OsRng, true)
             .await
             .unwrap();
 
@@ -339,6 +502,66 @@ pub(crate) mod tests {
             .get(TEST_VALIDATORS_ADDRESS[0])
             .unwrap()
             .clone();
-        assert_eq!(dealings.len(), TOTAL_DEALINGS);
+        assert_eq!(dealings.len(), DEFAULT_DEALINGS);
+    }
+
+    #[tokio::test]
+    #[ignore] // expensive test
+    async fn resharing_with_swapped_bte_key() {
+        let self_index = 2;
+        let dealer_details_db = Arc::new(RwLock::new(HashMap::new()));
+        let threshold_db = Arc::new(RwLock::new(Some(3)));
+        let dkg_client = DkgClient::new(
+            DummyClient::new(
+                AccountId::from_str("n1vxkywf9g4cg0k2dehanzwzz64jw782qm0kuynf").unwrap(),
+            )
+            .with_dealer_details(&dealer_details_db)
+            .with_threshold(&threshold_db),
+        );
+        let params = setup();
+        let state = State::new(
+            PathBuf::default(),
+            PersistentState::default(),
+            Url::parse("localhost:8000").unwrap(),
+            DkgKeyPair::new(&params, OsRng),
+            KeyPair::new(),
+        );
+        state.set_node_index(Some(self_index)).await;
+        insert_dealers(&params, &dealer_details_db);
+
+        dealing_exchange(&dkg_client, &state, OsRng, true)
+            .await
+            .unwrap();
+
+        // move on to the next epoch, with one of the dealers announcing a brand new key
+        state.reset_persistent(false).await;
+        state.set_node_index(Some(self_index)).await;
+        dealer_details_db
+            .write()
+            .unwrap()
+            .entry(TEST_VALIDATORS_ADDRESS[1].to_string())
+            .and_modify(|details| {
+                let keypair = DkgKeyPair::new(&params, OsRng);
+                details.0.bte_public_key_with_proof =
+                    bs58::encode(&keypair.public_key().to_bytes()).into_string();
+            });
+
+        dealing_exchange(&dkg_client, &state, OsRng, true)
+            .await
+            .unwrap();
+
+        let dealers = state.all_dealers().await;
+        assert_eq!(
+            *dealers
+                .get(&Addr::unchecked(TEST_VALIDATORS_ADDRESS[1]))
+                .unwrap()
+                .as_ref()
+                .unwrap_err(),
+            ComplaintReason::InconsistentBTEPublicKey
+        );
+        assert!(dealers
+            .get(&Addr::unchecked(TEST_VALIDATORS_ADDRESS[2]))
+            .unwrap()
+            .is_ok());
     }
 }
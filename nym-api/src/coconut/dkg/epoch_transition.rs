@@ -0,0 +1,196 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::coconut::dkg::client::DkgClient;
+use crate::coconut::dkg::public_key::must_reset_coconut_keypair;
+use crate::coconut::dkg::state::State;
+use crate::coconut::error::CoconutError;
+use crate::coconut::keystore::CoconutKeyStore;
+use crate::coconut::log_context::{ctx_debug, ctx_info};
+use nym_coconut_dkg_common::types::{Epoch, EpochState};
+
+fn is_resharing(epoch_state: EpochState) -> bool {
+    match epoch_state {
+        EpochState::PublicKeySubmission { resharing }
+        | EpochState::DealingExchange { resharing }
+        | EpochState::ComplaintSubmission { resharing }
+        | EpochState::VerificationKeySubmission { resharing }
+        | EpochState::VerificationKeyValidation { resharing }
+        | EpochState::VerificationKeyFinalization { resharing } => resharing,
+        EpochState::InProgress => false,
+    }
+}
+
+/// Rotates the keys once the contract moves on to a new epoch: the keys derived in the previous
+/// epoch are archived, and the progress made in it is discarded so that the DKG state machine
+/// starts over, eventually deriving and submitting a new verification key share.
+///
+/// Returns whether the epoch has changed since the last time it's been observed.
+pub(crate) async fn epoch_transition(
+    dkg_client: &DkgClient,
+    state: &State,
+    coconut_keystore: &CoconutKeyStore,
+    epoch: &Epoch,
+) -> Result<bool, CoconutError> {
+    let Some(previous_epoch_id) = state.epoch_id().await else {
+        // the state has been created before the epochs were tracked (or the DKG hasn't been run yet),
+        // so whatever progress has been made is assumed to relate to the current epoch
+        state.set_epoch_id(epoch.epoch_id).await;
+        return Ok(false);
+    };
+    if previous_epoch_id == epoch.epoch_id {
+        return Ok(false);
+    }
+
+    ctx_info!(
+        "DKG: The epoch has advanced from {} to {}, rotating the coconut keys",
+        previous_epoch_id,
+        epoch.epoch_id
+    );
+    if state.coconut_keypair_is_some().await {
+        coconut_keystore.archive(previous_epoch_id)?;
+    }
+    let reset_coconut_keypair =
+        must_reset_coconut_keypair(dkg_client, is_resharing(epoch.state)).await?;
+    ctx_debug!(
+        "Resetting state, with coconut keypair reset: {}",
+        reset_coconut_keypair
+    );
+    state.reset_persistent(reset_coconut_keypair).await;
+    state.set_epoch_id(epoch.epoch_id).await;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coconut::dkg::state::PersistentState;
+    use crate::coconut::dummy_client::DummyClient;
+    use crate::coconut::KeyPair;
+    use cosmwasm_std::Addr;
+    use nym_coconut::{ttp_keygen, Parameters};
+    use nym_coconut_dkg_common::types::InitialReplacementData;
+    use nym_dkg::bte::keys::KeyPair as DkgKeyPair;
+    use nym_validator_client::nyxd::AccountId;
+    use rand::rngs::OsRng;
+    use std::path::PathBuf;
+    use std::str::FromStr;
+    use std::sync::{Arc, RwLock};
+    use url::Url;
+
+    const TEST_VALIDATOR_ADDRESS: &str = "n19lc9u84cz0yz3fww5283nucc9yvr8gsjmgeul0";
+
+    fn epoch(epoch_id: u64, state: EpochState) -> Epoch {
+        Epoch {
+            state,
+            epoch_id,
+            ..Epoch::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn keys_are_rotated_when_the_epoch_advances() {
+        let data_directory = tempfile::tempdir().unwrap();
+        let dkg_client = DkgClient::new(DummyClient::new(
+            AccountId::from_str(TEST_VALIDATOR_ADDRESS).unwrap(),
+        ));
+        let keystore = CoconutKeyStore::new_with_paths(
+            data_directory.path().join("coconut.pem"),
+            data_directory.path().join("coconut_vk.pem"),
+            None,
+        );
+        let state = State::new(
+            PathBuf::default(),
+            PersistentState::default(),
+            Url::parse("localhost:8000").unwrap(),
+            DkgKeyPair::new(&nym_dkg::bte::setup(), OsRng),
+            KeyPair::new(),
+        );
+
+        // the first observed epoch is adopted as is
+        assert!(!epoch_transition(
+            &dkg_client,
+            &state,
+            &keystore,
+            &epoch(1, EpochState::InProgress)
+        )
+        .await
+        .unwrap());
+        assert_eq!(state.epoch_id().await, Some(1));
+
+        let keypair = ttp_keygen(&Parameters::new(4).unwrap(), 1, 1)
+            .unwrap()
+            .remove(0);
+        keystore.store(&keypair).unwrap();
+        state.set_coconut_keypair(Some(keypair)).await;
+        state.set_node_index(Some(3)).await;
+        state.set_was_in_progress().await;
+
+        assert!(!epoch_transition(
+            &dkg_client,
+            &state,
+            &keystore,
+            &epoch(1, EpochState::InProgress)
+        )
+        .await
+        .unwrap());
+        assert_eq!(state.node_index().await, Some(3));
+
+        let next_epoch = epoch(2, EpochState::PublicKeySubmission { resharing: false });
+        assert!(
+            epoch_transition(&dkg_client, &state, &keystore, &next_epoch)
+                .await
+                .unwrap()
+        );
+        assert_eq!(state.epoch_id().await, Some(2));
+        assert_eq!(state.node_index().await, None);
+        assert!(!state.was_in_progress().await);
+        assert!(!state.coconut_keypair_is_some().await);
+        assert!(data_directory.path().join("coconut.epoch_1.pem").exists());
+        assert!(data_directory
+            .path()
+            .join("coconut_vk.epoch_1.pem")
+            .exists());
+    }
+
+    #[tokio::test]
+    async fn initial_dealers_keep_their_keys_when_resharing() {
+        let data_directory = tempfile::tempdir().unwrap();
+        let initial_dealers = Arc::new(RwLock::new(Some(InitialReplacementData {
+            initial_dealers: vec![Addr::unchecked(TEST_VALIDATOR_ADDRESS)],
+            initial_height: 1,
+        })));
+        let dkg_client = DkgClient::new(
+            DummyClient::new(AccountId::from_str(TEST_VALIDATOR_ADDRESS).unwrap())
+                .with_initial_dealers_db(&initial_dealers),
+        );
+        let keystore = CoconutKeyStore::new_with_paths(
+            data_directory.path().join("coconut.pem"),
+            data_directory.path().join("coconut_vk.pem"),
+            None,
+        );
+        let state = State::new(
+            PathBuf::default(),
+            PersistentState::default(),
+            Url::parse("localhost:8000").unwrap(),
+            DkgKeyPair::new(&nym_dkg::bte::setup(), OsRng),
+            KeyPair::new(),
+        );
+        state.set_epoch_id(1).await;
+        let keypair = ttp_keygen(&Parameters::new(4).unwrap(), 1, 1)
+            .unwrap()
+            .remove(0);
+        keystore.store(&keypair).unwrap();
+        state.set_coconut_keypair(Some(keypair)).await;
+
+        let next_epoch = epoch(2, EpochState::PublicKeySubmission { resharing: true });
+        assert!(
+            epoch_transition(&dkg_client, &state, &keystore, &next_epoch)
+                .await
+                .unwrap()
+        );
+        assert!(state.coconut_keypair_is_some().await);
+        assert!(data_directory.path().join("coconut.epoch_1.pem").exists());
+    }
+}
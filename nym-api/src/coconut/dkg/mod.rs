@@ -4,7 +4,11 @@
 pub(crate) mod client;
 pub(crate) mod complaints;
 pub(crate) mod controller;
+pub(crate) mod deadline;
 pub(crate) mod dealing;
+pub(crate) mod epoch_transition;
 pub(crate) mod public_key;
+pub(crate) mod resharing;
+pub(crate) mod simulation;
 pub(crate) mod state;
 pub(crate) mod verification_key;
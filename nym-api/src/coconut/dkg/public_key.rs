@@ -4,30 +4,24 @@
 use crate::coconut::dkg::client::DkgClient;
 use crate::coconut::dkg::state::State;
 use crate::coconut::error::CoconutError;
-use log::debug;
+use crate::coconut::log_context::{ctx_debug, ctx_info};
 use nym_coconut_dkg_common::dealer::DealerType;
 
 pub(crate) async fn public_key_submission(
     dkg_client: &DkgClient,
-    state: &mut State,
+    state: &State,
     resharing: bool,
 ) -> Result<(), CoconutError> {
-    if state.was_in_progress() {
-        let own_address = dkg_client.get_address().await.as_ref().to_string();
-        let is_initial_dealer = dkg_client
-            .get_initial_dealers()
-            .await?
-            .map(|data| data.initial_dealers.iter().any(|d| *d == own_address))
-            .unwrap_or(false);
-        let reset_coconut_keypair = !resharing || !is_initial_dealer;
-        debug!(
+    if state.was_in_progress().await {
+        let reset_coconut_keypair = must_reset_coconut_keypair(dkg_client, resharing).await?;
+        ctx_debug!(
             "Resetting state, with coconut keypair reset: {}",
             reset_coconut_keypair
         );
         state.reset_persistent(reset_coconut_keypair).await;
     }
-    if state.node_index().is_some() {
-        debug!("Node index was set previously, nothing to do");
+    if state.node_index().await.is_some() {
+        ctx_debug!("Node index was set previously, nothing to do");
         return Ok(());
     }
 
@@ -36,30 +30,48 @@ pub(crate) async fn public_key_submission(
     let index = if let Some(details) = dealer_details.details {
         if dealer_details.dealer_type == DealerType::Past {
             // If it was a dealer in a previous epoch, re-register it for this epoch
-            debug!("Registering for the current DKG round, with keys from a previous epoch");
+            ctx_debug!("Registering for the current DKG round, with keys from a previous epoch");
             dkg_client
                 .register_dealer(bte_key, state.announce_address().to_string(), resharing)
                 .await?;
         }
         details.assigned_index
     } else {
-        debug!("Registering for the first time to be a dealer");
+        ctx_debug!("Registering for the first time to be a dealer");
         // First time registration
         dkg_client
             .register_dealer(bte_key, state.announce_address().to_string(), resharing)
             .await?
     };
-    state.set_node_index(Some(index));
-    info!("DKG: Using node index {}", index);
+    state.set_node_index(Some(index)).await;
+    ctx_info!("DKG: Using node index {}", index);
 
     Ok(())
 }
 
+/// When resharing, the initial dealers have to hold on to their current keys, as they're
+/// the ones the new dealings are derived from. Everyone else starts from scratch.
+pub(crate) async fn must_reset_coconut_keypair(
+    dkg_client: &DkgClient,
+    resharing: bool,
+) -> Result<bool, CoconutError> {
+    if !resharing {
+        return Ok(true);
+    }
+    let own_address = dkg_client.get_address().await.as_ref().to_string();
+    let is_initial_dealer = dkg_client
+        .get_initial_dealers()
+        .await?
+        .map(|data| data.initial_dealers.iter().any(|d| *d == own_address))
+        .unwrap_or(false);
+    Ok(!is_initial_dealer)
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
     use crate::coconut::dkg::state::PersistentState;
-    use crate::coconut::tests::DummyClient;
+    use crate::coconut::dummy_client::DummyClient;
     use crate::coconut::KeyPair;
     use nym_dkg::bte::keys::KeyPair as DkgKeyPair;
     use nym_validator_client::nyxd::AccountId;
@@ -76,7 +88,7 @@ pub(crate) mod tests {
         let dkg_client = DkgClient::new(DummyClient::new(
             AccountId::from_str(TEST_VALIDATOR_ADDRESS).unwrap(),
         ));
-        let mut state = State::new(
+        let state = State::new(
             PathBuf::default(),
             PersistentState::default(),
             Url::parse("localhost:8000").unwrap(),
@@ -90,7 +102,7 @@ pub(crate) mod tests {
             .unwrap()
             .details
             .is_none());
-        public_key_submission(&dkg_client, &mut state, false)
+        public_key_submission(&dkg_client, &state, false)
             .await
             .unwrap();
         let client_idx = dkg_client
@@ -100,13 +112,13 @@ pub(crate) mod tests {
             .details
             .unwrap()
             .assigned_index;
-        assert_eq!(state.node_index().unwrap(), client_idx);
+        assert_eq!(state.node_index().await.unwrap(), client_idx);
 
         // keeps the same index from chain, not calling register_dealer again
-        state.set_node_index(None);
-        public_key_submission(&dkg_client, &mut state, false)
+        state.set_node_index(None).await;
+        public_key_submission(&dkg_client, &state, false)
             .await
             .unwrap();
-        assert_eq!(state.node_index().unwrap(), client_idx);
+        assert_eq!(state.node_index().await.unwrap(), client_idx);
     }
 }
@@ -0,0 +1,162 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::coconut::client::Client;
+use crate::coconut::error::{CoconutError, Result};
+use nym_api_requests::coconut::{DealerSetTransition, ResharingDiagnosticsResponse};
+use nym_coconut_dkg_common::types::{replacement_threshold, EpochId, EpochState};
+use nym_dkg::Threshold;
+use std::collections::BTreeSet;
+
+/// Predicts how the DKG contract is going to react to the signer group changing to `new_dealers`,
+/// following the same rules as `advance_epoch_state` of the contract.
+pub(crate) fn predict_dealer_set_transition(
+    epoch_id: EpochId,
+    threshold: Threshold,
+    verified_dealers: BTreeSet<String>,
+    initial_dealers: Option<BTreeSet<String>>,
+    new_dealers: BTreeSet<String>,
+) -> ResharingDiagnosticsResponse {
+    let retained_dealers: Vec<_> = verified_dealers
+        .intersection(&new_dealers)
+        .cloned()
+        .collect();
+    let removed_dealers: Vec<_> = verified_dealers.difference(&new_dealers).cloned().collect();
+    let added_dealers: Vec<_> = new_dealers.difference(&verified_dealers).cloned().collect();
+
+    let transition = if removed_dealers.is_empty() && added_dealers.is_empty() {
+        DealerSetTransition::Unchanged
+    } else if verified_dealers.is_empty()
+        || removed_dealers.len() >= replacement_threshold(threshold, verified_dealers.len())
+    {
+        DealerSetTransition::FreshKeygen
+    } else {
+        DealerSetTransition::Resharing
+    };
+
+    let (required_holders, minimum_online_holders) = if transition == DealerSetTransition::Resharing
+    {
+        // if we're already in the middle of resharing, the original holders remain the same
+        let holders = initial_dealers.unwrap_or_else(|| verified_dealers.clone());
+        let required = holders.intersection(&new_dealers).cloned().collect();
+        (required, threshold)
+    } else {
+        (Vec::new(), 0)
+    };
+
+    ResharingDiagnosticsResponse {
+        transition,
+        epoch_id,
+        threshold,
+        current_dealers: verified_dealers.into_iter().collect(),
+        retained_dealers,
+        removed_dealers,
+        added_dealers,
+        required_holders,
+        minimum_online_holders,
+    }
+}
+
+pub(crate) async fn resharing_diagnostics(
+    client: &(dyn Client + Send + Sync),
+    new_dealers: Vec<String>,
+) -> Result<ResharingDiagnosticsResponse> {
+    let epoch = client.get_current_epoch().await?;
+    if epoch.state != EpochState::InProgress {
+        return Err(CoconutError::DkgNotFinished {
+            state: epoch.state.to_string(),
+        });
+    }
+    let threshold = client.get_current_epoch_threshold().await?.ok_or_else(|| {
+        CoconutError::DkgNotFinished {
+            state: epoch.state.to_string(),
+        }
+    })?;
+
+    let verified_dealers = client
+        .get_verification_key_shares(epoch.epoch_id)
+        .await?
+        .into_iter()
+        .filter(|share| share.verified)
+        .map(|share| share.owner.to_string())
+        .collect();
+    let initial_dealers = client.get_initial_dealers().await?.map(|data| {
+        data.initial_dealers
+            .into_iter()
+            .map(|addr| addr.to_string())
+            .collect()
+    });
+
+    Ok(predict_dealer_set_transition(
+        epoch.epoch_id,
+        threshold,
+        verified_dealers,
+        initial_dealers,
+        new_dealers.into_iter().collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dealers(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn unchanged_dealer_set() {
+        let current = dealers(&["a", "b", "c", "d"]);
+        let prediction = predict_dealer_set_transition(1, 3, current.clone(), None, current);
+        assert_eq!(prediction.transition, DealerSetTransition::Unchanged);
+        assert!(prediction.required_holders.is_empty());
+    }
+
+    #[test]
+    fn small_changes_trigger_resharing() {
+        let current = dealers(&["a", "b", "c", "d"]);
+        let new = dealers(&["a", "b", "c", "e"]);
+        let prediction = predict_dealer_set_transition(1, 3, current, None, new);
+
+        assert_eq!(prediction.transition, DealerSetTransition::Resharing);
+        assert_eq!(prediction.removed_dealers, vec!["d".to_string()]);
+        assert_eq!(prediction.added_dealers, vec!["e".to_string()]);
+        assert_eq!(
+            prediction.required_holders,
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+        assert_eq!(prediction.minimum_online_holders, 3);
+
+        // additions alone also require resharing
+        let current = dealers(&["a", "b", "c"]);
+        let new = dealers(&["a", "b", "c", "d"]);
+        let prediction = predict_dealer_set_transition(1, 2, current, None, new);
+        assert_eq!(prediction.transition, DealerSetTransition::Resharing);
+    }
+
+    #[test]
+    fn large_changes_trigger_fresh_keygen() {
+        // replacement threshold is 3 - 2 + 1 = 2
+        let current = dealers(&["a", "b", "c", "d"]);
+        let new = dealers(&["a", "b", "e", "f"]);
+        let prediction = predict_dealer_set_transition(1, 3, current, None, new);
+
+        assert_eq!(prediction.transition, DealerSetTransition::FreshKeygen);
+        assert!(prediction.required_holders.is_empty());
+        assert_eq!(prediction.minimum_online_holders, 0);
+    }
+
+    #[test]
+    fn ongoing_resharing_keeps_original_holders() {
+        let current = dealers(&["a", "b", "c", "e"]);
+        let initial = dealers(&["a", "b", "c", "d"]);
+        let new = dealers(&["a", "b", "c", "e", "f"]);
+        let prediction = predict_dealer_set_transition(2, 3, current, Some(initial), new);
+
+        assert_eq!(prediction.transition, DealerSetTransition::Resharing);
+        assert_eq!(
+            prediction.required_holders,
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+    }
+}
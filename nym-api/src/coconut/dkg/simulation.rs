@@ -0,0 +1,497 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Dry run of the DKG ceremony against an in-memory contract, shared with a handful of simulated
+//! dealers. It lets the operators make sure their DKG keys, system clock and configuration are fine
+//! before taking part in the real ceremony, without submitting anything on chain.
+
+use crate::coconut::dkg::client::DkgClient;
+use crate::coconut::dkg::dealing::dealing_exchange;
+use crate::coconut::dkg::public_key::public_key_submission;
+use crate::coconut::dkg::state::{PersistentState, State};
+use crate::coconut::dkg::verification_key::{
+    verification_key_finalization, verification_key_submission, verification_key_validation,
+};
+use crate::coconut::dummy_client::DummyClient;
+use crate::coconut::error::CoconutError;
+use crate::coconut::keypair::KeyPair as CoconutKeyPair;
+use crate::coconut::keystore::CoconutKeyStore;
+use crate::coconut::self_check::{check_verification_key, CheckOutcome};
+use crate::nyxd;
+use crate::support::config::Config;
+use cw3::{ProposalResponse, Status};
+use nym_coconut::{Base58, Parameters, VerificationKey};
+use nym_coconut_dkg_common::dealer::DealerDetails;
+use nym_coconut_dkg_common::types::dealings_for_attributes;
+use nym_coconut_dkg_common::verification_key::ContractVKShare;
+use nym_contracts_common::dealings::ContractSafeBytes;
+use nym_credentials::coconut::bandwidth::{PRIVATE_ATTRIBUTES, PUBLIC_ATTRIBUTES};
+use nym_dkg::bte::keys::KeyPair as DkgKeyPair;
+use nym_dkg::bte::setup;
+use nym_dkg::Threshold;
+use nym_pemstore::KeyPairPath;
+use nym_validator_client::nyxd::AccountId;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use url::{Host, Url};
+
+/// Number of the simulated dealers taking part in the ceremony alongside us.
+const SIMULATED_DEALERS: u8 = 3;
+
+/// Maximum difference between the system clock and the time of the latest block.
+/// Note that the latest block is itself a few seconds old.
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SimulationStep {
+    Configuration,
+    SystemClock,
+    DkgKeys,
+    Registration,
+    DealingExchange,
+    KeyDerivation,
+    VerificationKeyPairing,
+    Finalization,
+}
+
+impl SimulationStep {
+    // steps that actually run the ceremony, as opposed to the preliminary checks
+    const CEREMONY: [SimulationStep; 5] = [
+        SimulationStep::Registration,
+        SimulationStep::DealingExchange,
+        SimulationStep::KeyDerivation,
+        SimulationStep::VerificationKeyPairing,
+        SimulationStep::Finalization,
+    ];
+}
+
+impl Display for SimulationStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationStep::Configuration => write!(f, "configuration"),
+            SimulationStep::SystemClock => write!(f, "system clock"),
+            SimulationStep::DkgKeys => write!(f, "dkg keys"),
+            SimulationStep::Registration => write!(f, "registration"),
+            SimulationStep::DealingExchange => write!(f, "dealing exchange"),
+            SimulationStep::KeyDerivation => write!(f, "key derivation"),
+            SimulationStep::VerificationKeyPairing => write!(f, "verification key pairing"),
+            SimulationStep::Finalization => write!(f, "finalization"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SimulationReport {
+    steps: Vec<(SimulationStep, CheckOutcome)>,
+}
+
+impl SimulationReport {
+    fn add(&mut self, step: SimulationStep, outcome: CheckOutcome) {
+        self.steps.push((step, outcome))
+    }
+
+    /// The simulation succeeds if none of the steps has failed. Skipped steps, such as the clock
+    /// check when the chain couldn't be reached, are not considered to be failures.
+    pub(crate) fn succeeded(&self) -> bool {
+        !self.steps.is_empty() && !self.steps.iter().any(|(_, outcome)| outcome.is_failed())
+    }
+}
+
+impl Display for SimulationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (step, outcome) in &self.steps {
+            writeln!(f, "{step}: {outcome}")?;
+        }
+        if self.succeeded() {
+            write!(f, "the DKG simulation has succeeded")
+        } else {
+            write!(f, "the DKG simulation has FAILED")
+        }
+    }
+}
+
+fn is_loopback(address: &Url) -> bool {
+    match address.host() {
+        Some(Host::Domain(domain)) => domain == "localhost",
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+fn check_configuration(config: &Config) -> CheckOutcome {
+    if !config.get_coconut_signer_enabled() {
+        return CheckOutcome::Failed("the coconut signer is not enabled".to_string());
+    }
+
+    let announce_address = config.get_announce_address();
+    if announce_address.host().is_none() {
+        CheckOutcome::Failed(format!(
+            "the announce address {announce_address} does not specify the host"
+        ))
+    } else if is_loopback(&announce_address) {
+        CheckOutcome::Failed(format!(
+            "the announce address {announce_address} is not reachable by the other signers"
+        ))
+    } else {
+        CheckOutcome::Passed(format!("announcing {announce_address}"))
+    }
+}
+
+fn check_clock_skew(local_timestamp: i64, block_timestamp: i64) -> CheckOutcome {
+    let skew = local_timestamp - block_timestamp;
+    let details = format!("the system clock is {skew}s off the time of the latest block");
+    if skew.unsigned_abs() > MAX_CLOCK_SKEW.as_secs() {
+        CheckOutcome::Failed(details)
+    } else {
+        CheckOutcome::Passed(details)
+    }
+}
+
+async fn check_system_clock(config: &Config) -> CheckOutcome {
+    let block_time = match nyxd::Client::new_query(config)
+        .current_block_timestamp()
+        .await
+    {
+        Ok(block_time) => block_time,
+        Err(err) => {
+            return CheckOutcome::Skipped(format!(
+                "could not obtain the time of the latest block - {err}"
+            ))
+        }
+    };
+    let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) else {
+        return CheckOutcome::Failed("the system clock is set before the unix epoch".to_string());
+    };
+    check_clock_skew(now.as_secs() as i64, block_time.unix_timestamp())
+}
+
+fn check_dkg_keys(dkg_keypair: &DkgKeyPair) -> CheckOutcome {
+    if dkg_keypair.public_key().verify() {
+        CheckOutcome::Passed("the proof of the DKG public key is valid".to_string())
+    } else {
+        CheckOutcome::Failed("the proof of the DKG public key is invalid".to_string())
+    }
+}
+
+/// In-memory replacement of the DKG and multisig contracts, shared by all the participants.
+#[derive(Default)]
+struct SimulatedContract {
+    dealer_details: Arc<RwLock<HashMap<String, (DealerDetails, bool)>>>,
+    dealings: Arc<RwLock<HashMap<String, Vec<ContractSafeBytes>>>>,
+    proposals: Arc<RwLock<HashMap<u64, ProposalResponse>>>,
+    verification_shares: Arc<RwLock<HashMap<String, ContractVKShare>>>,
+    threshold: Arc<RwLock<Option<Threshold>>>,
+}
+
+impl SimulatedContract {
+    fn new(participants: u64) -> Self {
+        let contract = SimulatedContract::default();
+        // the same threshold the contract is going to use
+        *contract.threshold.write().unwrap() = Some((2 * participants + 3 - 1) / 3);
+        contract
+    }
+
+    fn client(&self, address: AccountId) -> DkgClient {
+        DkgClient::new(
+            DummyClient::new(address)
+                .with_dealer_details(&self.dealer_details)
+                .with_dealings(&self.dealings)
+                .with_proposal_db(&self.proposals)
+                .with_verification_share(&self.verification_shares)
+                .with_threshold(&self.threshold)
+                .with_total_dealings(dealings_for_attributes(
+                    PUBLIC_ATTRIBUTES + PRIVATE_ATTRIBUTES,
+                )),
+        )
+    }
+}
+
+struct Participant {
+    dkg_client: DkgClient,
+    state: State,
+    coconut_keystore: CoconutKeyStore,
+}
+
+impl Participant {
+    fn new(
+        contract: &SimulatedContract,
+        id: u8,
+        announce_address: Url,
+        dkg_keypair: DkgKeyPair,
+        data_directory: &std::path::Path,
+    ) -> Self {
+        // the address is never used for signing anything, so it doesn't need a private key
+        let address = AccountId::new("n", &[id; 20]).expect("the simulated address is valid");
+        Participant {
+            dkg_client: contract.client(address),
+            state: State::new(
+                data_directory.join(format!("dkg_state_{id}.json")),
+                PersistentState::default(),
+                announce_address,
+                dkg_keypair,
+                CoconutKeyPair::new(),
+            ),
+            coconut_keystore: CoconutKeyStore::new_with_paths(
+                data_directory.join(format!("coconut_secret_key_{id}.pem")),
+                data_directory.join(format!("coconut_verification_key_{id}.pem")),
+                None,
+            ),
+        }
+    }
+
+    async fn proposal_status(&self) -> Result<Option<Status>, CoconutError> {
+        match self.state.progress_report().await.proposal_id {
+            Some(proposal_id) => Ok(Some(
+                self.dkg_client.get_proposal(proposal_id).await?.status,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    async fn submitted_share(&self) -> Result<Option<ContractVKShare>, CoconutError> {
+        let address = self.dkg_client.get_address().await.to_string();
+        let epoch_id = self.dkg_client.get_current_epoch().await?.epoch_id;
+        Ok(self
+            .dkg_client
+            .get_verification_key_shares(epoch_id)
+            .await?
+            .into_iter()
+            .find(|share| share.owner.as_str() == address))
+    }
+}
+
+/// Runs a single step of the ceremony for all the participants, the first of which is us,
+/// and checks how it went from our point of view.
+async fn run_ceremony_step(
+    step: SimulationStep,
+    participants: &[Participant],
+) -> Result<CheckOutcome, CoconutError> {
+    let ours = &participants[0];
+    match step {
+        SimulationStep::Registration => {
+            for p in participants {
+                public_key_submission(&p.dkg_client, &p.state, false).await?;
+            }
+            match ours.state.node_index().await {
+                Some(node_index) => Ok(CheckOutcome::Passed(format!(
+                    "registered with node index {node_index}"
+                ))),
+                None => Ok(CheckOutcome::Failed(
+                    "no node index has been assigned".to_string(),
+                )),
+            }
+        }
+        SimulationStep::DealingExchange => {
+            for p in participants {
+                dealing_exchange(&p.dkg_client, &p.state, OsRng, false).await?;
+            }
+            Ok(CheckOutcome::Passed(format!(
+                "submitted {} dealings for {} receivers",
+                ours.state.submitted_dealings().await,
+                participants.len()
+            )))
+        }
+        SimulationStep::KeyDerivation => {
+            for p in participants {
+                verification_key_submission(&p.dkg_client, &p.state, &p.coconut_keystore, false)
+                    .await?;
+            }
+            let bad_dealers = ours.state.bad_dealers().await;
+            if bad_dealers.is_empty() {
+                Ok(CheckOutcome::Passed(format!(
+                    "derived the coconut keypair from the dealings of all {} dealers",
+                    participants.len()
+                )))
+            } else {
+                Ok(CheckOutcome::Failed(format!(
+                    "the dealings of {bad_dealers:?} had to be excluded"
+                )))
+            }
+        }
+        SimulationStep::VerificationKeyPairing => {
+            for p in participants {
+                verification_key_validation(&p.dkg_client, &p.state, false).await?;
+            }
+            if ours.proposal_status().await? != Some(Status::Passed) {
+                return Ok(CheckOutcome::Failed(
+                    "the other dealers have rejected our verification key share".to_string(),
+                ));
+            }
+
+            let params = Parameters::new(PUBLIC_ATTRIBUTES + PRIVATE_ATTRIBUTES)?;
+            let keypair = ours.coconut_keystore.load()?;
+            let Some(share) = ours.submitted_share().await? else {
+                return Ok(CheckOutcome::Failed(
+                    "our verification key share has not been submitted".to_string(),
+                ));
+            };
+            match VerificationKey::try_from_bs58(&share.share) {
+                Ok(share) => Ok(check_verification_key(&params, &keypair, &share)),
+                Err(err) => Ok(CheckOutcome::Failed(format!(
+                    "our verification key share is malformed - {err}"
+                ))),
+            }
+        }
+        SimulationStep::Finalization => {
+            for p in participants {
+                verification_key_finalization(&p.dkg_client, &p.state, false).await?;
+            }
+            if ours.proposal_status().await? == Some(Status::Executed) {
+                Ok(CheckOutcome::Passed(
+                    "our verification key share has been finalized".to_string(),
+                ))
+            } else {
+                Ok(CheckOutcome::Failed(
+                    "our verification key share has not been finalized".to_string(),
+                ))
+            }
+        }
+        _ => unreachable!("{step} is not a step of the ceremony"),
+    }
+}
+
+async fn run_ceremony(
+    report: &mut SimulationReport,
+    announce_address: Url,
+    dkg_keypair: DkgKeyPair,
+    data_directory: &std::path::Path,
+) -> Result<(), CoconutError> {
+    let params = setup();
+    let contract = SimulatedContract::new(SIMULATED_DEALERS as u64 + 1);
+    let mut participants = vec![Participant::new(
+        &contract,
+        0,
+        announce_address,
+        dkg_keypair,
+        data_directory,
+    )];
+    for id in 1..=SIMULATED_DEALERS {
+        let announce_address = Url::parse(&format!("http://simulated-dealer-{id}.nym:8080"))
+            .expect("the simulated announce address is valid");
+        participants.push(Participant::new(
+            &contract,
+            id,
+            announce_address,
+            DkgKeyPair::new(&params, OsRng),
+            data_directory,
+        ));
+    }
+
+    let total_dealings = dealings_for_attributes(PUBLIC_ATTRIBUTES + PRIVATE_ATTRIBUTES);
+    let mut failed_step = None;
+    for step in SimulationStep::CEREMONY {
+        if let Some(failed_step) = failed_step {
+            report.add(
+                step,
+                CheckOutcome::Skipped(format!("the {failed_step} step has failed")),
+            );
+            continue;
+        }
+
+        info!("Simulating the {step} step of the DKG ({total_dealings} dealings per dealer)");
+        let outcome = run_ceremony_step(step, &participants)
+            .await
+            .unwrap_or_else(|err| CheckOutcome::Failed(err.to_string()));
+        if outcome.is_failed() {
+            failed_step = Some(step);
+        }
+        report.add(step, outcome);
+    }
+    Ok(())
+}
+
+/// Runs the preliminary checks followed by the simulated ceremony. The failure of an individual
+/// step is recorded in the report, while errors are only returned if the simulation couldn't be set up.
+pub(crate) async fn simulate(config: &Config) -> Result<SimulationReport, CoconutError> {
+    let mut report = SimulationReport::default();
+    report.add(SimulationStep::Configuration, check_configuration(config));
+    report.add(
+        SimulationStep::SystemClock,
+        check_system_clock(config).await,
+    );
+
+    let dkg_keypair_path = KeyPairPath::new(
+        config.decryption_key_path(),
+        config.public_key_with_proof_path(),
+    );
+    let dkg_keypair = match nym_pemstore::load_keypair::<DkgKeyPair>(&dkg_keypair_path) {
+        Ok(dkg_keypair) => dkg_keypair,
+        Err(err) => {
+            report.add(
+                SimulationStep::DkgKeys,
+                CheckOutcome::Failed(format!("could not load the DKG keypair - {err}")),
+            );
+            for step in SimulationStep::CEREMONY {
+                report.add(
+                    step,
+                    CheckOutcome::Skipped("the DKG keypair is not available".to_string()),
+                );
+            }
+            return Ok(report);
+        }
+    };
+    report.add(SimulationStep::DkgKeys, check_dkg_keys(&dkg_keypair));
+
+    // the simulated state and keys are written to a scratch directory, as not to touch the real ones
+    let data_directory =
+        std::env::temp_dir().join(format!("nym-api-dkg-simulation-{}", OsRng.next_u64()));
+    std::fs::create_dir_all(&data_directory)?;
+    let res = run_ceremony(
+        &mut report,
+        config.get_announce_address(),
+        dkg_keypair,
+        &data_directory,
+    )
+    .await;
+    std::fs::remove_dir_all(&data_directory).ok();
+    res?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_skew_is_tolerated_within_bounds() {
+        assert!(check_clock_skew(1000, 1000).is_passed());
+        assert!(check_clock_skew(1005, 1000).is_passed());
+        assert!(check_clock_skew(940, 1000).is_passed());
+        assert!(check_clock_skew(1061, 1000).is_failed());
+        assert!(check_clock_skew(900, 1000).is_failed());
+    }
+
+    #[test]
+    fn loopback_announce_addresses_are_detected() {
+        assert!(is_loopback(&Url::parse("http://localhost:8080").unwrap()));
+        assert!(is_loopback(&Url::parse("http://127.0.0.1:8080").unwrap()));
+        assert!(is_loopback(&Url::parse("http://[::1]:8080").unwrap()));
+        assert!(!is_loopback(
+            &Url::parse("https://nym-api.example.com").unwrap()
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore] // expensive test
+    async fn simulated_ceremony_succeeds_with_valid_keys() {
+        let data_directory = tempfile::tempdir().unwrap();
+        let mut report = SimulationReport::default();
+        run_ceremony(
+            &mut report,
+            Url::parse("https://nym-api.example.com").unwrap(),
+            DkgKeyPair::new(&setup(), OsRng),
+            data_directory.path(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.steps.len(), SimulationStep::CEREMONY.len());
+        assert!(report.succeeded(), "{report}");
+    }
+}
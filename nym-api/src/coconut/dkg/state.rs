@@ -2,19 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::coconut::dkg::complaints::ComplaintReason;
+use crate::coconut::dkg::verification_key::VerifiedDealings;
 use crate::coconut::error::CoconutError;
 use crate::coconut::keypair::KeyPair as CoconutKeyPair;
+use crate::coconut::log_context::{ctx_debug, ctx_warn};
 use cosmwasm_std::Addr;
-use log::debug;
+use nym_api_requests::coconut::{BadDealer, DkgProgress};
 use nym_coconut::SecretKey;
 use nym_coconut_dkg_common::dealer::DealerDetails;
-use nym_coconut_dkg_common::types::EpochState;
+use nym_coconut_dkg_common::types::{EpochId, EpochState};
+use nym_contracts_common::dealings::ContractSafeBytes;
 use nym_dkg::bte::{keys::KeyPair as DkgKeyPair, PublicKey, PublicKeyWithProof};
 use nym_dkg::{NodeIndex, RecoveredVerificationKeys, Threshold};
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
 use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{RwLock, RwLockReadGuard};
 use url::Url;
 
 fn bte_pk_serialize<S: Serializer>(
@@ -42,6 +49,12 @@ pub(crate) struct DkgParticipant {
     pub(crate) assigned_index: NodeIndex,
 }
 
+impl DkgParticipant {
+    fn has_same_key(&self, other: &DkgParticipant) -> bool {
+        self.bte_public_key_with_proof.public_key() == other.bte_public_key_with_proof.public_key()
+    }
+}
+
 impl TryFrom<DealerDetails> for DkgParticipant {
     type Error = ComplaintReason;
 
@@ -66,26 +79,27 @@ impl TryFrom<DealerDetails> for DkgParticipant {
 
 #[async_trait]
 pub(crate) trait ConsistentState {
-    fn node_index_value(&self) -> Result<NodeIndex, CoconutError>;
-    fn receiver_index_value(&self) -> Result<usize, CoconutError>;
-    fn threshold(&self) -> Result<Threshold, CoconutError>;
+    async fn node_index_value(&self) -> Result<NodeIndex, CoconutError>;
+    async fn receiver_index_value(&self) -> Result<usize, CoconutError>;
+    async fn threshold(&self) -> Result<Threshold, CoconutError>;
     async fn coconut_keypair_is_some(&self) -> Result<(), CoconutError>;
-    fn proposal_id_value(&self) -> Result<u64, CoconutError>;
+    async fn proposal_id_value(&self) -> Result<u64, CoconutError>;
     async fn is_consistent(&self, epoch_state: EpochState) -> Result<(), CoconutError> {
         match epoch_state {
             EpochState::PublicKeySubmission { .. } => {}
             EpochState::DealingExchange { .. } => {
-                self.node_index_value()?;
+                self.node_index_value().await?;
             }
-            EpochState::VerificationKeySubmission { .. } => {
-                self.receiver_index_value()?;
-                self.threshold()?;
+            EpochState::ComplaintSubmission { .. }
+            | EpochState::VerificationKeySubmission { .. } => {
+                self.receiver_index_value().await?;
+                self.threshold().await?;
             }
             EpochState::VerificationKeyValidation { .. } => {
                 self.coconut_keypair_is_some().await?;
             }
             EpochState::VerificationKeyFinalization { .. } => {
-                self.proposal_id_value()?;
+                self.proposal_id_value().await?;
             }
             EpochState::InProgress => {}
         }
@@ -95,23 +109,32 @@ pub(crate) trait ConsistentState {
 
 #[async_trait]
 impl ConsistentState for State {
-    fn node_index_value(&self) -> Result<NodeIndex, CoconutError> {
-        self.node_index.ok_or(CoconutError::UnrecoverableState {
-            reason: String::from("Node index should have been set"),
-        })
+    async fn node_index_value(&self) -> Result<NodeIndex, CoconutError> {
+        self.node_index()
+            .await
+            .ok_or(CoconutError::UnrecoverableState {
+                reason: String::from("Node index should have been set"),
+            })
     }
 
-    fn receiver_index_value(&self) -> Result<usize, CoconutError> {
-        self.receiver_index.ok_or(CoconutError::UnrecoverableState {
-            reason: String::from("Receiver index should have been set"),
-        })
+    async fn receiver_index_value(&self) -> Result<usize, CoconutError> {
+        self.receiver_index()
+            .await
+            .ok_or(CoconutError::UnrecoverableState {
+                reason: String::from("Receiver index should have been set"),
+            })
     }
 
-    fn threshold(&self) -> Result<Threshold, CoconutError> {
-        let threshold = self.threshold.ok_or(CoconutError::UnrecoverableState {
-            reason: String::from("Threshold should have been set"),
-        })?;
-        if self.current_dealers_by_idx().len() < threshold as usize {
+    async fn threshold(&self) -> Result<Threshold, CoconutError> {
+        let threshold =
+            self.progress
+                .read()
+                .await
+                .threshold
+                .ok_or(CoconutError::UnrecoverableState {
+                    reason: String::from("Threshold should have been set"),
+                })?;
+        if self.current_dealers_by_idx().await.len() < threshold as usize {
             Err(CoconutError::UnrecoverableState {
                 reason: String::from(
                     "Not enough good dealers in the signer set to achieve threshold",
@@ -132,10 +155,14 @@ impl ConsistentState for State {
         }
     }
 
-    fn proposal_id_value(&self) -> Result<u64, CoconutError> {
-        self.proposal_id.ok_or(CoconutError::UnrecoverableState {
-            reason: String::from("Proposal id should have been set"),
-        })
+    async fn proposal_id_value(&self) -> Result<u64, CoconutError> {
+        self.progress
+            .read()
+            .await
+            .proposal_id
+            .ok_or(CoconutError::UnrecoverableState {
+                reason: String::from("Proposal id should have been set"),
+            })
     }
 }
 
@@ -162,60 +189,124 @@ where
 
 #[derive(Default, Deserialize, Serialize)]
 pub(crate) struct PersistentState {
+    #[serde(default)]
+    epoch_id: Option<EpochId>,
     node_index: Option<NodeIndex>,
     dealers: BTreeMap<Addr, Result<DkgParticipant, ComplaintReason>>,
+    #[serde(default)]
+    past_dealers: BTreeMap<Addr, DkgParticipant>,
     receiver_index: Option<usize>,
     threshold: Option<Threshold>,
     #[serde(serialize_with = "vks_serialize")]
     #[serde(deserialize_with = "vks_deserialize")]
     recovered_vks: Vec<RecoveredVerificationKeys>,
     proposal_id: Option<u64>,
+    #[serde(default)]
+    submitted_complaints: bool,
     voted_vks: bool,
     executed_proposal: bool,
     was_in_progress: bool,
+    #[serde(default)]
+    submitted_dealings: usize,
+    #[serde(default)]
+    pending_dealing: Option<ContractSafeBytes>,
+    #[serde(default)]
+    aborted_epoch: Option<EpochId>,
 }
 
-impl From<&State> for PersistentState {
-    fn from(s: &State) -> Self {
+impl PersistentState {
+    pub async fn from_state(state: &State) -> Self {
+        let progress = *state.progress.read().await;
         PersistentState {
-            node_index: s.node_index,
-            dealers: s.dealers.clone(),
-            receiver_index: s.receiver_index,
-            threshold: s.threshold,
-            recovered_vks: s.recovered_vks.clone(),
-            proposal_id: s.proposal_id,
-            voted_vks: s.voted_vks,
-            executed_proposal: s.executed_proposal,
-            was_in_progress: s.was_in_progress,
+            epoch_id: progress.epoch_id,
+            node_index: progress.node_index,
+            dealers: state.dealers.read().await.clone(),
+            past_dealers: state.past_dealers.read().await.clone(),
+            receiver_index: progress.receiver_index,
+            threshold: progress.threshold,
+            recovered_vks: state.recovered_vks.read().await.clone(),
+            proposal_id: progress.proposal_id,
+            submitted_complaints: progress.submitted_complaints,
+            voted_vks: progress.voted_vks,
+            executed_proposal: progress.executed_proposal,
+            was_in_progress: progress.was_in_progress,
+            submitted_dealings: progress.submitted_dealings,
+            pending_dealing: state.pending_dealing.read().await.clone(),
+            aborted_epoch: progress.aborted_epoch,
         }
     }
-}
 
-impl PersistentState {
+    /// Replaces the snapshot atomically, so that if the process dies mid-write,
+    /// the previous snapshot is left intact rather than a truncated one.
     pub fn save_to_file(&self, path: PathBuf) -> Result<(), CoconutError> {
-        std::fs::write(path, serde_json::to_string(self)?)?;
+        let temp_path = path.with_extension("tmp");
+        {
+            let mut file = File::create(&temp_path)?;
+            file.write_all(serde_json::to_string(self)?.as_bytes())?;
+            file.sync_all()?;
+        }
+        std::fs::rename(temp_path, &path)?;
+
+        // make sure the rename itself is persisted
+        #[cfg(target_family = "unix")]
+        if let Some(parent) = path.parent() {
+            File::open(parent)?.sync_all()?;
+        }
         Ok(())
     }
 
-    pub fn load_from_file(path: PathBuf) -> Result<Self, CoconutError> {
-        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    /// Loads the snapshot of the state, if one has ever been saved.
+    pub fn load_from_file(path: PathBuf) -> Result<Option<Self>, CoconutError> {
+        match std::fs::read_to_string(path) {
+            Ok(snapshot) => Ok(Some(serde_json::from_str(&snapshot)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
     }
 }
 
-pub(crate) struct State {
-    persistent_state_path: PathBuf,
-    announce_address: Url,
-    dkg_keypair: DkgKeyPair,
-    coconut_keypair: CoconutKeyPair,
+// values describing how far along we are in the current DKG epoch
+#[derive(Clone, Copy, Default)]
+struct Progress {
+    // epoch the rest of the progress relates to
+    epoch_id: Option<EpochId>,
     node_index: Option<NodeIndex>,
-    dealers: BTreeMap<Addr, Result<DkgParticipant, ComplaintReason>>,
     receiver_index: Option<usize>,
     threshold: Option<Threshold>,
-    recovered_vks: Vec<RecoveredVerificationKeys>,
     proposal_id: Option<u64>,
+    submitted_complaints: bool,
     voted_vks: bool,
     executed_proposal: bool,
     was_in_progress: bool,
+    submitted_dealings: usize,
+    // epoch whose round got aborted, so that it's not attempted again until the next one starts
+    aborted_epoch: Option<EpochId>,
+}
+
+/// State of the DKG protocol.
+///
+/// Each section of the state is behind its own lock, so that it could be shared (and cheaply cloned)
+/// between the DKG task and anything else that needs to look at it, such as the credential signer,
+/// without anyone having to hold on to the entire state for the duration of the DKG iteration.
+#[derive(Clone)]
+pub(crate) struct State {
+    persistent_state_path: PathBuf,
+    announce_address: Url,
+    dkg_keypair: Arc<DkgKeyPair>,
+    coconut_keypair: CoconutKeyPair,
+    dealers: Arc<RwLock<BTreeMap<Addr, Result<DkgParticipant, ComplaintReason>>>>,
+    // dealers of the prior epochs, used for making sure the keys aren't swapped when resharing
+    past_dealers: Arc<RwLock<BTreeMap<Addr, DkgParticipant>>>,
+    recovered_vks: Arc<RwLock<Vec<RecoveredVerificationKeys>>>,
+    progress: Arc<RwLock<Progress>>,
+    // dealing that has been generated, but not yet submitted, so that it wouldn't have to be
+    // generated again if the process got restarted in the meantime
+    pending_dealing: Arc<RwLock<Option<ContractSafeBytes>>>,
+    // phase of the epoch as last observed by the DKG task, only kept for reporting purposes
+    epoch_state: Arc<RwLock<Option<EpochState>>>,
+    // dealings verified while looking for the complaints to submit, so that they wouldn't have
+    // to be verified all over again when deriving the keys
+    verified_dealings: Arc<RwLock<Option<VerifiedDealings>>>,
 }
 
 impl State {
@@ -226,36 +317,59 @@ impl State {
         dkg_keypair: DkgKeyPair,
         coconut_keypair: CoconutKeyPair,
     ) -> Self {
-        State {
-            persistent_state_path,
-            announce_address,
-            dkg_keypair,
-            coconut_keypair,
+        let progress = Progress {
+            epoch_id: persistent_state.epoch_id,
             node_index: persistent_state.node_index,
-            dealers: persistent_state.dealers,
             receiver_index: persistent_state.receiver_index,
             threshold: persistent_state.threshold,
-            recovered_vks: persistent_state.recovered_vks,
             proposal_id: persistent_state.proposal_id,
+            submitted_complaints: persistent_state.submitted_complaints,
             voted_vks: persistent_state.voted_vks,
             executed_proposal: persistent_state.executed_proposal,
             was_in_progress: persistent_state.was_in_progress,
+            submitted_dealings: persistent_state.submitted_dealings,
+            aborted_epoch: persistent_state.aborted_epoch,
+        };
+
+        State {
+            persistent_state_path,
+            announce_address,
+            dkg_keypair: Arc::new(dkg_keypair),
+            coconut_keypair,
+            dealers: Arc::new(RwLock::new(persistent_state.dealers)),
+            past_dealers: Arc::new(RwLock::new(persistent_state.past_dealers)),
+            recovered_vks: Arc::new(RwLock::new(persistent_state.recovered_vks)),
+            progress: Arc::new(RwLock::new(progress)),
+            pending_dealing: Arc::new(RwLock::new(persistent_state.pending_dealing)),
+            epoch_state: Arc::new(RwLock::new(None)),
+            verified_dealings: Arc::new(RwLock::new(None)),
         }
     }
 
-    pub async fn reset_persistent(&mut self, reset_coconut_keypair: bool) {
+    pub async fn reset_persistent(&self, reset_coconut_keypair: bool) {
         if reset_coconut_keypair {
             self.coconut_keypair.set(None).await;
         }
-        self.node_index = Default::default();
-        self.dealers = Default::default();
-        self.receiver_index = Default::default();
-        self.threshold = Default::default();
-        self.recovered_vks = Default::default();
-        self.proposal_id = Default::default();
-        self.voted_vks = Default::default();
-        self.executed_proposal = Default::default();
-        self.was_in_progress = Default::default();
+        let dealers = std::mem::take(&mut *self.dealers.write().await);
+        self.past_dealers.write().await.extend(
+            dealers
+                .into_iter()
+                .filter_map(|(addr, dealer)| dealer.ok().map(|participant| (addr, participant))),
+        );
+        *self.recovered_vks.write().await = Default::default();
+        // the epoch doesn't change just because the progress made in it got discarded
+        let Progress {
+            epoch_id,
+            aborted_epoch,
+            ..
+        } = *self.progress.read().await;
+        *self.progress.write().await = Progress {
+            epoch_id,
+            aborted_epoch,
+            ..Default::default()
+        };
+        *self.pending_dealing.write().await = None;
+        *self.verified_dealings.write().await = None;
     }
 
     pub fn persistent_state_path(&self) -> PathBuf {
@@ -282,16 +396,30 @@ impl State {
             .map(|kp| kp.secret_key())
     }
 
-    pub fn node_index(&self) -> Option<NodeIndex> {
-        self.node_index
+    pub async fn epoch_id(&self) -> Option<EpochId> {
+        self.progress.read().await.epoch_id
+    }
+
+    pub async fn node_index(&self) -> Option<NodeIndex> {
+        self.progress.read().await.node_index
     }
 
-    pub fn receiver_index(&self) -> Option<usize> {
-        self.receiver_index
+    pub async fn receiver_index(&self) -> Option<usize> {
+        self.progress.read().await.receiver_index
     }
 
-    pub fn current_dealers_by_addr(&self) -> BTreeMap<Addr, NodeIndex> {
+    pub async fn submitted_dealings(&self) -> usize {
+        self.progress.read().await.submitted_dealings
+    }
+
+    pub async fn pending_dealing(&self) -> Option<ContractSafeBytes> {
+        self.pending_dealing.read().await.clone()
+    }
+
+    pub async fn current_dealers_by_addr(&self) -> BTreeMap<Addr, NodeIndex> {
         self.dealers
+            .read()
+            .await
             .iter()
             .filter_map(|(addr, dealer)| {
                 dealer
@@ -302,8 +430,10 @@ impl State {
             .collect()
     }
 
-    pub fn current_dealers_by_idx(&self) -> BTreeMap<NodeIndex, PublicKey> {
+    pub async fn current_dealers_by_idx(&self) -> BTreeMap<NodeIndex, PublicKey> {
         self.dealers
+            .read()
+            .await
             .iter()
             .filter_map(|(_, dealer)| {
                 dealer.as_ref().ok().map(|participant| {
@@ -316,85 +446,362 @@ impl State {
             .collect()
     }
 
-    pub fn recovered_vks(&self) -> &Vec<RecoveredVerificationKeys> {
-        &self.recovered_vks
+    /// Dealers that have been marked as bad, along with the reason.
+    pub async fn bad_dealers(&self) -> Vec<(Addr, ComplaintReason)> {
+        self.dealers
+            .read()
+            .await
+            .iter()
+            .filter_map(|(addr, dealer)| {
+                dealer
+                    .as_ref()
+                    .err()
+                    .map(|reason| (addr.clone(), reason.clone()))
+            })
+            .collect()
+    }
+
+    /// Snapshot of the progress made in the current epoch, as reported by the status endpoint.
+    pub async fn progress_report(&self) -> DkgProgress {
+        let progress = *self.progress.read().await;
+        DkgProgress {
+            phase: self.epoch_state.read().await.map(|state| state.to_string()),
+            node_index: progress.node_index,
+            threshold: progress.threshold,
+            bad_dealers: self
+                .bad_dealers()
+                .await
+                .into_iter()
+                .map(|(address, reason)| BadDealer {
+                    address: address.into_string(),
+                    reason: format!("{reason:?}"),
+                })
+                .collect(),
+            proposal_id: progress.proposal_id,
+            coconut_keypair_derived: self.coconut_keypair_is_some().await,
+            submitted_dealings: progress.submitted_dealings as u64,
+        }
+    }
+
+    pub async fn recovered_vks(&self) -> RwLockReadGuard<'_, Vec<RecoveredVerificationKeys>> {
+        self.recovered_vks.read().await
     }
 
-    pub fn voted_vks(&self) -> bool {
-        self.voted_vks
+    pub async fn submitted_complaints(&self) -> bool {
+        self.progress.read().await.submitted_complaints
     }
 
-    pub fn executed_proposal(&self) -> bool {
-        self.executed_proposal
+    pub async fn voted_vks(&self) -> bool {
+        self.progress.read().await.voted_vks
     }
 
-    pub fn was_in_progress(&self) -> bool {
-        self.was_in_progress
+    pub async fn executed_proposal(&self) -> bool {
+        self.progress.read().await.executed_proposal
     }
 
-    pub fn set_recovered_vks(&mut self, recovered_vks: Vec<RecoveredVerificationKeys>) {
-        self.recovered_vks = recovered_vks;
+    pub async fn was_in_progress(&self) -> bool {
+        self.progress.read().await.was_in_progress
+    }
+
+    pub async fn set_epoch_state(&self, epoch_state: EpochState) {
+        *self.epoch_state.write().await = Some(epoch_state);
+    }
+
+    pub async fn set_recovered_vks(&self, recovered_vks: Vec<RecoveredVerificationKeys>) {
+        *self.recovered_vks.write().await = recovered_vks;
     }
 
     pub async fn set_coconut_keypair(
-        &mut self,
+        &self,
         coconut_keypair: Option<nym_coconut_interface::KeyPair>,
     ) {
         self.coconut_keypair.set(coconut_keypair).await
     }
 
-    pub fn set_node_index(&mut self, node_index: Option<NodeIndex>) {
-        self.node_index = node_index;
+    pub async fn set_epoch_id(&self, epoch_id: EpochId) {
+        self.progress.write().await.epoch_id = Some(epoch_id);
+    }
+
+    pub async fn set_node_index(&self, node_index: Option<NodeIndex>) {
+        self.progress.write().await.node_index = node_index;
+    }
+
+    pub async fn set_dealers(&self, dealers: Vec<DealerDetails>, resharing: bool) {
+        let past_dealers = self.past_dealers.read().await;
+        *self.dealers.write().await = BTreeMap::from_iter(dealers.into_iter().map(|details| {
+            let addr = details.address.clone();
+            let participant = DkgParticipant::try_from(details).and_then(|participant| {
+                // when resharing, the dealers must keep on using the keys the prior secrets
+                // have been shared with
+                match past_dealers.get(&addr) {
+                    Some(past) if resharing && !past.has_same_key(&participant) => {
+                        ctx_warn!(
+                            "Dealer {} announced a BTE public key different from the one of the prior epoch",
+                            addr
+                        );
+                        Err(ComplaintReason::InconsistentBTEPublicKey)
+                    }
+                    _ => Ok(participant),
+                }
+            });
+            (addr, participant)
+        }))
+    }
+
+    /// Makes sure the dealers are still announcing the same keys and indices as they did when
+    /// the dealings were exchanged, returning the ones that don't.
+    /// Note that it's a purely local observation, as other receivers might have seen
+    /// the announcements at different times, so the dealers are not marked as bad here. Instead,
+    /// they should be complained about and only get excluded once the complaints are upheld.
+    pub async fn cross_check_dealers(&self, dealers: Vec<DealerDetails>) -> Vec<Addr> {
+        let announced: BTreeMap<_, _> = dealers
+            .into_iter()
+            .map(|details| (details.address.clone(), DkgParticipant::try_from(details)))
+            .collect();
+
+        let mut inconsistent = Vec::new();
+        for (addr, index) in self.current_dealers_by_addr().await {
+            let consistent = match (announced.get(&addr), self.dealers.read().await.get(&addr)) {
+                (Some(Ok(announced)), Some(Ok(recorded))) => {
+                    announced.assigned_index == index && recorded.has_same_key(announced)
+                }
+                _ => false,
+            };
+            if !consistent {
+                ctx_warn!(
+                    "Dealer {} changed its announced details during the DKG ceremony",
+                    addr
+                );
+                inconsistent.push(addr);
+            }
+        }
+        inconsistent
     }
 
-    pub fn set_dealers(&mut self, dealers: Vec<DealerDetails>) {
-        self.dealers = BTreeMap::from_iter(
-            dealers
-                .into_iter()
-                .map(|details| (details.address.clone(), DkgParticipant::try_from(details))),
-        )
+    /// Caches the outcome of verifying the dealings of the current epoch.
+    pub async fn cache_verified_dealings(&self, verified: VerifiedDealings) {
+        *self.verified_dealings.write().await = Some(verified);
+    }
+
+    /// Takes the cached outcome of verifying the dealings, as long as it's from the provided epoch.
+    pub async fn take_verified_dealings(
+        &self,
+        epoch_id: Option<EpochId>,
+    ) -> Option<VerifiedDealings> {
+        let mut verified_dealings = self.verified_dealings.write().await;
+        match verified_dealings.as_ref() {
+            Some(verified) if verified.epoch_id == epoch_id => verified_dealings.take(),
+            _ => None,
+        }
     }
 
-    pub fn mark_bad_dealer(&mut self, dealer_addr: &Addr, reason: ComplaintReason) {
+    pub async fn mark_bad_dealer(&self, dealer_addr: &Addr, reason: ComplaintReason) {
         if let Some((_, value)) = self
             .dealers
+            .write()
+            .await
             .iter_mut()
             .find(|(addr, _)| *addr == dealer_addr)
         {
-            debug!(
+            ctx_debug!(
                 "Dealer {} misbehaved: {:?}. It will be marked locally as bad dealer and ignored",
-                dealer_addr, reason
+                dealer_addr,
+                reason
             );
             *value = Err(reason);
         }
     }
 
-    pub fn set_receiver_index(&mut self, receiver_index: Option<usize>) {
-        self.receiver_index = receiver_index;
+    pub async fn set_receiver_index(&self, receiver_index: Option<usize>) {
+        self.progress.write().await.receiver_index = receiver_index;
     }
 
-    pub fn set_threshold(&mut self, threshold: Option<Threshold>) {
-        self.threshold = threshold;
+    pub async fn set_threshold(&self, threshold: Option<Threshold>) {
+        self.progress.write().await.threshold = threshold;
     }
 
-    pub fn set_proposal_id(&mut self, proposal_id: u64) {
-        self.proposal_id = Some(proposal_id);
+    pub async fn set_submitted_dealings(&self, submitted_dealings: usize) {
+        self.progress.write().await.submitted_dealings = submitted_dealings;
     }
 
-    pub fn set_voted_vks(&mut self) {
-        self.voted_vks = true;
+    pub async fn set_pending_dealing(&self, dealing: Option<ContractSafeBytes>) {
+        *self.pending_dealing.write().await = dealing;
     }
 
-    pub fn set_executed_proposal(&mut self) {
-        self.executed_proposal = true;
+    pub async fn set_proposal_id(&self, proposal_id: u64) {
+        self.progress.write().await.proposal_id = Some(proposal_id);
     }
 
-    pub fn set_was_in_progress(&mut self) {
-        self.was_in_progress = true;
+    pub async fn set_submitted_complaints(&self) {
+        self.progress.write().await.submitted_complaints = true;
     }
 
     #[cfg(test)]
-    pub fn all_dealers(&self) -> &BTreeMap<Addr, Result<DkgParticipant, ComplaintReason>> {
-        &self.dealers
+    pub async fn reset_submitted_complaints(&self) {
+        self.progress.write().await.submitted_complaints = false;
+    }
+
+    pub async fn set_voted_vks(&self) {
+        self.progress.write().await.voted_vks = true;
+    }
+
+    pub async fn set_executed_proposal(&self) {
+        self.progress.write().await.executed_proposal = true;
+    }
+
+    pub async fn aborted_epoch(&self) -> Option<EpochId> {
+        self.progress.read().await.aborted_epoch
+    }
+
+    pub async fn set_aborted_epoch(&self, epoch_id: EpochId) {
+        self.progress.write().await.aborted_epoch = Some(epoch_id);
+    }
+
+    pub async fn set_was_in_progress(&self) {
+        self.progress.write().await.was_in_progress = true;
+    }
+
+    #[cfg(test)]
+    pub async fn all_dealers(
+        &self,
+    ) -> RwLockReadGuard<'_, BTreeMap<Addr, Result<DkgParticipant, ComplaintReason>>> {
+        self.dealers.read().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn persistent_state_survives_restarts() {
+        let data_directory = tempfile::tempdir().unwrap();
+        let path = data_directory.path().join("dkg_state.json");
+        assert!(PersistentState::load_from_file(path.clone())
+            .unwrap()
+            .is_none());
+
+        let persistent_state = PersistentState {
+            epoch_id: Some(7),
+            node_index: Some(3),
+            receiver_index: Some(2),
+            threshold: Some(2),
+            proposal_id: Some(42),
+            voted_vks: true,
+            submitted_dealings: 1,
+            pending_dealing: Some(ContractSafeBytes(vec![1, 2, 3])),
+            aborted_epoch: Some(6),
+            ..Default::default()
+        };
+        persistent_state.save_to_file(path.clone()).unwrap();
+
+        let restored = PersistentState::load_from_file(path.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.epoch_id, Some(7));
+        assert_eq!(restored.node_index, Some(3));
+        assert_eq!(restored.receiver_index, Some(2));
+        assert_eq!(restored.threshold, Some(2));
+        assert_eq!(restored.proposal_id, Some(42));
+        assert!(restored.voted_vks);
+        assert_eq!(restored.submitted_dealings, 1);
+        assert_eq!(
+            restored.pending_dealing,
+            Some(ContractSafeBytes(vec![1, 2, 3]))
+        );
+        assert_eq!(restored.aborted_epoch, Some(6));
+        assert!(!path.with_extension("tmp").exists());
+
+        std::fs::write(&path, "{\"node_index\":").unwrap();
+        assert!(PersistentState::load_from_file(path).is_err());
+    }
+
+    #[tokio::test]
+    async fn progress_report_reflects_the_state() {
+        let state = State::new(
+            PathBuf::default(),
+            PersistentState::default(),
+            Url::parse("localhost:8000").unwrap(),
+            DkgKeyPair::new(&nym_dkg::bte::setup(), OsRng),
+            CoconutKeyPair::new(),
+        );
+        let report = state.progress_report().await;
+        assert_eq!(report.phase, None);
+        assert!(report.bad_dealers.is_empty());
+        assert!(!report.coconut_keypair_derived);
+
+        let dealer = Addr::unchecked("n1aq9kakfgwqcufr23lsv644apavcntrsqsk4yus");
+        let dealer_keypair = DkgKeyPair::new(&nym_dkg::bte::setup(), OsRng);
+        let details = DealerDetails {
+            address: dealer.clone(),
+            bte_public_key_with_proof: bs58::encode(&dealer_keypair.public_key().to_bytes())
+                .into_string(),
+            announce_address: String::new(),
+            assigned_index: 1,
+        };
+        state
+            .set_epoch_state(EpochState::DealingExchange { resharing: false })
+            .await;
+        state.set_node_index(Some(2)).await;
+        state.set_threshold(Some(1)).await;
+        state.set_submitted_dealings(2).await;
+        state.set_dealers(vec![details], false).await;
+        state
+            .mark_bad_dealer(&dealer, ComplaintReason::MissingDealing)
+            .await;
+
+        let report = state.progress_report().await;
+        assert_eq!(
+            report.phase,
+            Some(EpochState::DealingExchange { resharing: false }.to_string())
+        );
+        assert_eq!(report.node_index, Some(2));
+        assert_eq!(report.threshold, Some(1));
+        assert_eq!(report.submitted_dealings, 2);
+        assert_eq!(
+            report.bad_dealers,
+            vec![BadDealer {
+                address: dealer.into_string(),
+                reason: "MissingDealing".to_string(),
+            }]
+        );
+        assert_eq!(report.proposal_id, None);
+    }
+
+    #[tokio::test]
+    async fn inconsistent_dealers_are_not_excluded_locally() {
+        let state = State::new(
+            PathBuf::default(),
+            PersistentState::default(),
+            Url::parse("localhost:8000").unwrap(),
+            DkgKeyPair::new(&nym_dkg::bte::setup(), OsRng),
+            CoconutKeyPair::new(),
+        );
+        let dealer = Addr::unchecked("n1aq9kakfgwqcufr23lsv644apavcntrsqsk4yus");
+        let details = |keypair: &DkgKeyPair| DealerDetails {
+            address: dealer.clone(),
+            bte_public_key_with_proof: bs58::encode(&keypair.public_key().to_bytes()).into_string(),
+            announce_address: String::new(),
+            assigned_index: 1,
+        };
+        let original_keypair = DkgKeyPair::new(&nym_dkg::bte::setup(), OsRng);
+        let swapped_keypair = DkgKeyPair::new(&nym_dkg::bte::setup(), OsRng);
+        state
+            .set_dealers(vec![details(&original_keypair)], false)
+            .await;
+
+        assert!(state
+            .cross_check_dealers(vec![details(&original_keypair)])
+            .await
+            .is_empty());
+        assert_eq!(
+            state
+                .cross_check_dealers(vec![details(&swapped_keypair)])
+                .await,
+            vec![dealer.clone()]
+        );
+        assert!(state.bad_dealers().await.is_empty());
+        assert!(state.current_dealers_by_addr().await.contains_key(&dealer));
     }
 }
@@ -2,37 +2,97 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::coconut::dkg::client::DkgClient;
-use crate::coconut::dkg::complaints::ComplaintReason;
+use crate::coconut::dkg::complaints::{apply_upheld_complaints, ComplaintReason, DealerFault};
 use crate::coconut::dkg::state::{ConsistentState, State};
 use crate::coconut::error::CoconutError;
 use crate::coconut::helpers::accepted_vote_err;
+use crate::coconut::keystore::CoconutKeyStore;
+use crate::coconut::log_context::{ctx_debug, ctx_info, ctx_warn};
 use cosmwasm_std::Addr;
 use cw3::{ProposalResponse, Status};
-use log::debug;
 use nym_coconut::tests::helpers::transpose_matrix;
 use nym_coconut::{check_vk_pairing, Base58, KeyPair, Parameters, SecretKey, VerificationKey};
 use nym_coconut_dkg_common::event_attributes::DKG_PROPOSAL_ID;
-use nym_coconut_dkg_common::types::{NodeIndex, TOTAL_DEALINGS};
-use nym_coconut_dkg_common::verification_key::owner_from_cosmos_msgs;
+use nym_coconut_dkg_common::types::{attributes_for_dealings, EpochId, NodeIndex};
+use nym_coconut_dkg_common::verification_key::{
+    owner_from_cosmos_msgs, VkShareRejectionReason, VkShareVoteJustification,
+};
 use nym_coconut_interface::KeyPair as CoconutKeyPair;
-use nym_credentials::coconut::bandwidth::{PRIVATE_ATTRIBUTES, PUBLIC_ATTRIBUTES};
 use nym_dkg::bte::{decrypt_share, setup};
 use nym_dkg::error::DkgError;
 use nym_dkg::{combine_shares, try_recover_verification_keys, Dealing, Threshold};
-use nym_pemstore::KeyPairPath;
 use nym_validator_client::nyxd::cosmwasm_client::logs::find_attribute;
 use std::collections::BTreeMap;
 
+/// Outcome of going through the dealings posted in the contract during the particular epoch.
+pub(crate) struct VerifiedDealings {
+    pub(crate) epoch_id: Option<EpochId>,
+    dealings_maps: Vec<BTreeMap<NodeIndex, (Addr, Dealing)>>,
+    faults: Vec<DealerFault>,
+}
+
 // Filter the dealers based on what dealing they posted (or not) in the contract
 async fn deterministic_filter_dealers(
     dkg_client: &DkgClient,
-    state: &mut State,
+    state: &State,
     threshold: Threshold,
     resharing: bool,
 ) -> Result<Vec<BTreeMap<NodeIndex, (Addr, Dealing)>>, CoconutError> {
+    let verified = verify_dealings(dkg_client, state, threshold, resharing).await?;
+    for fault in verified.faults {
+        state.mark_bad_dealer(&fault.dealer, fault.reason).await;
+    }
+
+    Ok(verified.dealings_maps)
+}
+
+// Reuse the dealings verified during the complaint phase, if available, as the verification
+// is rather expensive and the dealings can no longer change by then
+async fn verify_dealings(
+    dkg_client: &DkgClient,
+    state: &State,
+    threshold: Threshold,
+    resharing: bool,
+) -> Result<VerifiedDealings, CoconutError> {
+    let epoch_id = state.epoch_id().await;
+    if let Some(verified) = state.take_verified_dealings(epoch_id).await {
+        return Ok(verified);
+    }
+
+    let (dealings_maps, faults) =
+        find_dealer_faults(dkg_client, state, threshold, resharing).await?;
+    Ok(VerifiedDealings {
+        epoch_id,
+        dealings_maps,
+        faults,
+    })
+}
+
+// Find the faults of the dealers without marking them locally, keeping the verified dealings
+// around for deriving the keys later on
+pub(crate) async fn dealer_faults(
+    dkg_client: &DkgClient,
+    state: &State,
+    threshold: Threshold,
+    resharing: bool,
+) -> Result<Vec<DealerFault>, CoconutError> {
+    let verified = verify_dealings(dkg_client, state, threshold, resharing).await?;
+    let faults = verified.faults.clone();
+    state.cache_verified_dealings(verified).await;
+    Ok(faults)
+}
+
+// Go through the dealings posted in the contract, without marking the misbehaving dealers locally
+async fn find_dealer_faults(
+    dkg_client: &DkgClient,
+    state: &State,
+    threshold: Threshold,
+    resharing: bool,
+) -> Result<(Vec<BTreeMap<NodeIndex, (Addr, Dealing)>>, Vec<DealerFault>), CoconutError> {
     let mut dealings_maps = vec![];
-    let initial_dealers_by_addr = state.current_dealers_by_addr();
-    let initial_receivers = state.current_dealers_by_idx();
+    let mut faults = vec![];
+    let initial_dealers_by_addr = state.current_dealers_by_addr().await;
+    let initial_receivers = state.current_dealers_by_idx().await;
     let initial_resharing_dealers = if resharing {
         dkg_client
             .get_initial_dealers()
@@ -44,66 +104,86 @@ async fn deterministic_filter_dealers(
     };
 
     let params = setup();
+    let total_dealings = dkg_client.get_total_dealings().await?;
+
+    for idx in 0..total_dealings {
+        let mut dealings_map = BTreeMap::new();
+        let mut pager = dkg_client.dealings_pager(idx, None);
+        loop {
+            // verify the dealings as they arrive rather than waiting for all of them to be retrieved
+            let page = match pager.next_page().await {
+                Ok(Some(page)) => page,
+                Ok(None) => break,
+                Err(err) => {
+                    ctx_warn!(
+                        "Failed to retrieve dealings of index {idx} after {:?}",
+                        pager.resumption_token()
+                    );
+                    return Err(err);
+                }
+            };
 
-    for idx in 0..TOTAL_DEALINGS {
-        let dealings = dkg_client.get_dealings(idx).await?;
-        let dealings_map =
-            BTreeMap::from_iter(dealings.into_iter().filter_map(|contract_dealing| {
+            for contract_dealing in page {
                 match Dealing::try_from(&contract_dealing.dealing) {
                     Ok(dealing) => {
                         if dealing
                             .verify(&params, threshold, &initial_receivers, None)
                             .is_err()
                         {
-                            state.mark_bad_dealer(
-                                &contract_dealing.dealer,
-                                ComplaintReason::DealingVerificationError,
-                            );
-                            None
+                            faults.push(DealerFault {
+                                dealer: contract_dealing.dealer,
+                                dealing_index: Some(idx as u64),
+                                reason: ComplaintReason::DealingVerificationError,
+                            });
                         } else if let Some(idx) =
                             initial_dealers_by_addr.get(&contract_dealing.dealer)
                         {
-                            Some((*idx, (contract_dealing.dealer, dealing)))
-                        } else {
-                            None
+                            dealings_map.insert(*idx, (contract_dealing.dealer, dealing));
                         }
                     }
                     Err(_) => {
-                        state.mark_bad_dealer(
-                            &contract_dealing.dealer,
-                            ComplaintReason::MalformedDealing,
-                        );
-                        None
+                        faults.push(DealerFault {
+                            dealer: contract_dealing.dealer,
+                            dealing_index: Some(idx as u64),
+                            reason: ComplaintReason::MalformedDealing,
+                        });
                     }
                 }
-            }));
+            }
+        }
         dealings_maps.push(dealings_map);
     }
 
     for (addr, _) in initial_dealers_by_addr.iter() {
         // in resharing mode, we don't commit dealings from dealers outside the initial set
         if !resharing || initial_resharing_dealers.contains(addr) {
-            for dealings_map in dealings_maps.iter() {
+            for (idx, dealings_map) in dealings_maps.iter().enumerate() {
                 if !dealings_map.iter().any(|(_, (address, _))| address == addr) {
-                    state.mark_bad_dealer(addr, ComplaintReason::MissingDealing);
+                    faults.push(DealerFault {
+                        dealer: addr.clone(),
+                        dealing_index: Some(idx as u64),
+                        reason: ComplaintReason::MissingDealing,
+                    });
                     break;
                 }
             }
         }
     }
 
-    Ok(dealings_maps)
+    Ok((dealings_maps, faults))
 }
 
-fn derive_partial_keypair(
-    state: &mut State,
+async fn derive_partial_keypair(
+    state: &State,
     threshold: Threshold,
     dealings_maps: Vec<BTreeMap<NodeIndex, (Addr, Dealing)>>,
 ) -> Result<KeyPair, CoconutError> {
-    let filtered_receivers_by_idx = state.current_dealers_by_idx();
-    let filtered_dealers_by_addr = state.current_dealers_by_addr();
+    let filtered_receivers_by_idx = state.current_dealers_by_idx().await;
+    let filtered_dealers_by_addr = state.current_dealers_by_addr().await;
     let dk = state.dkg_keypair().private_key();
-    let node_index_value = state.receiver_index_value()?;
+    let node_index_value = state.receiver_index_value().await?;
+    // every dealing contributes a single scalar of the key, the first one being `x`
+    let params = Parameters::new(attributes_for_dealings(dealings_maps.len()))?;
     let mut scalars = vec![];
     let mut recovered_vks = vec![];
     for dealings_map in dealings_maps.into_iter() {
@@ -117,7 +197,7 @@ fn derive_partial_keypair(
                 }
             })
             .unzip();
-        debug!(
+        ctx_debug!(
             "Recovering verification keys from dealings of dealers {:?} with receivers {:?}",
             filtered_dealers,
             filtered_receivers_by_idx.keys().collect::<Vec<_>>()
@@ -129,18 +209,17 @@ fn derive_partial_keypair(
         )?;
         recovered_vks.push(recovered);
 
-        debug!("Decrypting shares");
+        ctx_debug!("Decrypting shares");
         let shares = filtered_dealings
             .iter()
             .map(|dealing| decrypt_share(dk, node_index_value, &dealing.ciphertexts, None))
             .collect::<Result<_, _>>()?;
-        debug!("Combining shares into one secret");
+        ctx_debug!("Combining shares into one secret");
         let scalar = combine_shares(shares, &filtered_dealers)?;
         scalars.push(scalar);
     }
-    state.set_recovered_vks(recovered_vks);
+    state.set_recovered_vks(recovered_vks).await;
 
-    let params = Parameters::new(PUBLIC_ATTRIBUTES + PRIVATE_ATTRIBUTES)?;
     let x = scalars.pop().ok_or(CoconutError::DkgError(
         DkgError::NotEnoughDealingsAvailable {
             available: 0,
@@ -155,26 +234,31 @@ fn derive_partial_keypair(
 
 pub(crate) async fn verification_key_submission(
     dkg_client: &DkgClient,
-    state: &mut State,
-    keypair_path: &KeyPairPath,
+    state: &State,
+    coconut_keystore: &CoconutKeyStore,
     resharing: bool,
 ) -> Result<(), CoconutError> {
     if state.coconut_keypair_is_some().await {
-        debug!("Coconut keypair was set previously, nothing to do");
+        ctx_debug!("Coconut keypair was set previously, nothing to do");
         return Ok(());
     }
 
-    let threshold = state.threshold()?;
+    // only the deterministic checks of the dealings, alongside the upheld complaints, are used for
+    // excluding the dealers, so that all receivers derive their keys from the same set of dealings
+    let threshold = state.threshold().await?;
     let dealings_maps =
         deterministic_filter_dealers(dkg_client, state, threshold, resharing).await?;
-    debug!(
+    // regardless of what we have seen locally, exclude the dealers the other receivers
+    // have successfully complained about
+    apply_upheld_complaints(dkg_client, state).await?;
+    ctx_debug!(
         "Filtered dealers to {:?}",
         dealings_maps[0].keys().collect::<Vec<_>>()
     );
-    let coconut_keypair = derive_partial_keypair(state, threshold, dealings_maps)?;
-    debug!("Derived own coconut keypair");
+    let coconut_keypair = derive_partial_keypair(state, threshold, dealings_maps).await?;
+    ctx_debug!("Derived own coconut keypair");
     let vk_share = coconut_keypair.verification_key().to_bs58();
-    nym_pemstore::store_keypair(&coconut_keypair, keypair_path)?;
+    coconut_keystore.store(&coconut_keypair)?;
     let res = dkg_client
         .submit_verification_key_share(vk_share, resharing)
         .await?;
@@ -187,13 +271,13 @@ pub(crate) async fn verification_key_submission(
         .map_err(|_| CoconutError::ProposalIdError {
             reason: String::from("proposal id could not be parsed to u64"),
         })?;
-    debug!(
+    ctx_debug!(
         "Submitted own verification key share, proposal id {} is attached to it",
         proposal_id
     );
-    state.set_proposal_id(proposal_id);
+    state.set_proposal_id(proposal_id).await;
     state.set_coconut_keypair(Some(coconut_keypair)).await;
-    info!("DKG: Submitted own verification key");
+    ctx_info!("DKG: Submitted own verification key");
 
     Ok(())
 }
@@ -209,11 +293,11 @@ fn validate_proposal(proposal: &ProposalResponse) -> Option<(Addr, u64)> {
 
 pub(crate) async fn verification_key_validation(
     dkg_client: &DkgClient,
-    state: &mut State,
+    state: &State,
     _resharing: bool,
 ) -> Result<(), CoconutError> {
-    if state.voted_vks() {
-        debug!("Already voted on the verification keys, nothing to do");
+    if state.voted_vks().await {
+        ctx_debug!("Already voted on the verification keys, nothing to do");
         return Ok(());
     }
 
@@ -226,74 +310,79 @@ pub(crate) async fn verification_key_validation(
             .iter()
             .filter_map(validate_proposal),
     );
-    let filtered_receivers_by_idx: Vec<_> =
-        state.current_dealers_by_idx().keys().copied().collect();
+    let filtered_receivers_by_idx: Vec<_> = state
+        .current_dealers_by_idx()
+        .await
+        .keys()
+        .copied()
+        .collect();
     let recovered_partials: Vec<_> = state
         .recovered_vks()
+        .await
         .iter()
         .map(|recovered_vk| recovered_vk.recovered_partials.clone())
         .collect();
+    let params = Parameters::new(attributes_for_dealings(recovered_partials.len()))?;
     let recovered_partials = transpose_matrix(recovered_partials);
-    let params = Parameters::new(PUBLIC_ATTRIBUTES + PRIVATE_ATTRIBUTES)?;
     for contract_share in vk_shares {
         if let Some(proposal_id) = proposal_ids.get(&contract_share.owner).copied() {
-            match VerificationKey::try_from_bs58(contract_share.share) {
-                Ok(vk) => {
-                    if let Some(idx) = filtered_receivers_by_idx
-                        .iter()
-                        .position(|node_index| contract_share.node_index == *node_index)
-                    {
-                        let ret = if !check_vk_pairing(&params, &recovered_partials[idx], &vk) {
-                            debug!(
-                                "Voting NO to proposal {} because of failed VK pairing",
-                                proposal_id
-                            );
-                            dkg_client
-                                .vote_verification_key_share(proposal_id, false)
-                                .await
-                        } else {
-                            debug!("Voting YES to proposal {}", proposal_id);
-                            dkg_client
-                                .vote_verification_key_share(proposal_id, true)
-                                .await
-                        };
-                        accepted_vote_err(ret)?;
-                    }
-                }
-                Err(_) => {
-                    debug!(
-                        "Voting NO to proposal {} because of failed base 58 deserialization",
-                        proposal_id
-                    );
-                    let ret = dkg_client
-                        .vote_verification_key_share(proposal_id, false)
-                        .await;
-                    accepted_vote_err(ret)?;
-                }
-            }
+            let rejection_reason = match VerificationKey::try_from_bs58(&contract_share.share) {
+                Ok(vk) => match filtered_receivers_by_idx
+                    .iter()
+                    .position(|node_index| contract_share.node_index == *node_index)
+                {
+                    Some(idx) if check_vk_pairing(&params, &recovered_partials[idx], &vk) => None,
+                    Some(_) => Some(VkShareRejectionReason::FailedPairingCheck),
+                    None => Some(VkShareRejectionReason::IndexMismatch),
+                },
+                Err(_) => Some(VkShareRejectionReason::MalformedEncoding),
+            };
+
+            let ret = if let Some(reason) = rejection_reason {
+                ctx_debug!("Voting NO to proposal {proposal_id} because of {reason:?}");
+                let justification = VkShareVoteJustification {
+                    owner: contract_share.owner,
+                    node_index: contract_share.node_index,
+                    epoch_id,
+                    reason,
+                };
+                dkg_client
+                    .reject_verification_key_share(proposal_id, &justification)
+                    .await
+            } else {
+                ctx_debug!("Voting YES to proposal {}", proposal_id);
+                dkg_client.accept_verification_key_share(proposal_id).await
+            };
+            accepted_vote_err(ret)?;
         }
     }
-    state.set_voted_vks();
-    info!("DKG: Validated the other verification keys");
+    state.set_voted_vks().await;
+    ctx_info!("DKG: Validated the other verification keys");
     Ok(())
 }
 
 pub(crate) async fn verification_key_finalization(
     dkg_client: &DkgClient,
-    state: &mut State,
+    state: &State,
     _resharing: bool,
 ) -> Result<(), CoconutError> {
-    if state.executed_proposal() {
-        debug!("Already executed the proposal, nothing to do");
+    if state.executed_proposal().await {
+        ctx_debug!("Already executed the proposal, nothing to do");
         return Ok(());
     }
 
-    let proposal_id = state.proposal_id_value()?;
-    dkg_client
-        .execute_verification_key_share(proposal_id)
-        .await?;
-    state.set_executed_proposal();
-    info!("DKG: Finalized own verification key on chain");
+    let proposal_id = state.proposal_id_value().await?;
+    // anyone can execute a passed proposal, so another validator might have already done it for us
+    if dkg_client.get_proposal(proposal_id).await?.status == Status::Executed {
+        ctx_debug!("The proposal {proposal_id} has already been executed");
+    } else if let Err(err) = dkg_client.execute_verification_key_share(proposal_id).await {
+        if dkg_client.get_proposal(proposal_id).await?.status != Status::Executed {
+            return Err(err);
+        }
+        ctx_debug!("The proposal {proposal_id} got executed by someone else in the meantime");
+    }
+    state.set_executed_proposal().await;
+    ctx_info!("DKG: Finalized own verification key on chain");
 
     Ok(())
 }
@@ -304,11 +393,11 @@ pub(crate) mod tests {
     use crate::coconut::dkg::dealing::dealing_exchange;
     use crate::coconut::dkg::public_key::public_key_submission;
     use crate::coconut::dkg::state::PersistentState;
-    use crate::coconut::tests::DummyClient;
+    use crate::coconut::dummy_client::DummyClient;
     use crate::coconut::KeyPair;
     use nym_coconut::aggregate_verification_keys;
     use nym_coconut_dkg_common::dealer::DealerDetails;
-    use nym_coconut_dkg_common::types::InitialReplacementData;
+    use nym_coconut_dkg_common::types::{InitialReplacementData, DEFAULT_DEALINGS};
     use nym_coconut_dkg_common::verification_key::ContractVKShare;
     use nym_contracts_common::dealings::ContractSafeBytes;
     use nym_dkg::bte::keys::KeyPair as DkgKeyPair;
@@ -375,7 +464,7 @@ pub(crate) mod tests {
             );
             clients_and_states.push((dkg_client, state));
         }
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             public_key_submission(dkg_client, state, false)
                 .await
                 .unwrap();
@@ -386,8 +475,8 @@ pub(crate) mod tests {
     async fn prepare_clients_and_states_with_dealing(
         db: &MockContractDb,
     ) -> Vec<(DkgClient, State)> {
-        let mut clients_and_states = prepare_clients_and_states(db).await;
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        let clients_and_states = prepare_clients_and_states(db).await;
+        for (dkg_client, state) in clients_and_states.iter() {
             dealing_exchange(dkg_client, state, OsRng, false)
                 .await
                 .unwrap();
@@ -398,13 +487,17 @@ pub(crate) mod tests {
     async fn prepare_clients_and_states_with_submission(
         db: &MockContractDb,
     ) -> Vec<(DkgClient, State)> {
-        let mut clients_and_states = prepare_clients_and_states_with_dealing(db).await;
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        let clients_and_states = prepare_clients_and_states_with_dealing(db).await;
+        for (dkg_client, state) in clients_and_states.iter() {
             let random_file: usize = OsRng.gen();
             let private_key_path = temp_dir().join(format!("private{}.pem", random_file));
             let public_key_path = temp_dir().join(format!("public{}.pem", random_file));
-            let keypair_path = KeyPairPath::new(private_key_path.clone(), public_key_path.clone());
-            verification_key_submission(dkg_client, state, &keypair_path, false)
+            let keystore = CoconutKeyStore::new_with_paths(
+                private_key_path.clone(),
+                public_key_path.clone(),
+                None,
+            );
+            verification_key_submission(dkg_client, state, &keystore, false)
                 .await
                 .unwrap();
             std::fs::remove_file(private_key_path).unwrap();
@@ -416,8 +509,8 @@ pub(crate) mod tests {
     async fn prepare_clients_and_states_with_validation(
         db: &MockContractDb,
     ) -> Vec<(DkgClient, State)> {
-        let mut clients_and_states = prepare_clients_and_states_with_submission(db).await;
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        let clients_and_states = prepare_clients_and_states_with_submission(db).await;
+        for (dkg_client, state) in clients_and_states.iter() {
             verification_key_validation(dkg_client, state, false)
                 .await
                 .unwrap();
@@ -428,8 +521,8 @@ pub(crate) mod tests {
     async fn prepare_clients_and_states_with_finalization(
         db: &MockContractDb,
     ) -> Vec<(DkgClient, State)> {
-        let mut clients_and_states = prepare_clients_and_states_with_validation(db).await;
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        let clients_and_states = prepare_clients_and_states_with_validation(db).await;
+        for (dkg_client, state) in clients_and_states.iter() {
             verification_key_finalization(dkg_client, state, false)
                 .await
                 .unwrap();
@@ -441,12 +534,12 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn check_dealers_filter_all_good() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        let clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
+        for (dkg_client, state) in clients_and_states.iter() {
             let filtered = deterministic_filter_dealers(dkg_client, state, 2, false)
                 .await
                 .unwrap();
-            assert_eq!(filtered.len(), TOTAL_DEALINGS);
+            assert_eq!(filtered.len(), DEFAULT_DEALINGS);
             for mapping in filtered.iter() {
                 assert_eq!(mapping.len(), 4);
             }
@@ -457,7 +550,7 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn check_dealers_filter_one_bad_dealing() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
+        let clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
 
         // corrupt just one dealing
         db.dealings_db
@@ -470,13 +563,13 @@ pub(crate) mod tests {
                 dealings.push(last);
             });
 
-        for (dkg_client, state) in clients_and_states.iter_mut().skip(1) {
+        for (dkg_client, state) in clients_and_states.iter().skip(1) {
             let filtered = deterministic_filter_dealers(dkg_client, state, 2, false)
                 .await
                 .unwrap();
-            assert_eq!(filtered.len(), TOTAL_DEALINGS);
-            let corrupted_status = state
-                .all_dealers()
+            assert_eq!(filtered.len(), DEFAULT_DEALINGS);
+            let dealers = state.all_dealers().await;
+            let corrupted_status = dealers
                 .get(&Addr::unchecked(TEST_VALIDATORS_ADDRESS[0]))
                 .unwrap()
                 .as_ref()
@@ -489,16 +582,16 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn check_dealers_resharing_filter_one_missing_dealing() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states(&db).await;
+        let clients_and_states = prepare_clients_and_states(&db).await;
 
         // add all but the first dealing
-        for (dkg_client, state) in clients_and_states.iter_mut().skip(1) {
+        for (dkg_client, state) in clients_and_states.iter().skip(1) {
             dealing_exchange(dkg_client, state, OsRng, true)
                 .await
                 .unwrap();
         }
 
-        for (dkg_client, state) in clients_and_states.iter_mut().skip(1) {
+        for (dkg_client, state) in clients_and_states.iter().skip(1) {
             *db.initial_dealers_db.write().unwrap() = Some(InitialReplacementData {
                 initial_dealers: vec![Addr::unchecked(TEST_VALIDATORS_ADDRESS[0])],
                 initial_height: 1,
@@ -506,9 +599,9 @@ pub(crate) mod tests {
             let filtered = deterministic_filter_dealers(dkg_client, state, 2, true)
                 .await
                 .unwrap();
-            assert_eq!(filtered.len(), TOTAL_DEALINGS);
-            let corrupted_status = state
-                .all_dealers()
+            assert_eq!(filtered.len(), DEFAULT_DEALINGS);
+            let dealers = state.all_dealers().await;
+            let corrupted_status = dealers
                 .get(&Addr::unchecked(TEST_VALIDATORS_ADDRESS[0]))
                 .unwrap()
                 .as_ref()
@@ -522,16 +615,16 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn check_dealers_resharing_filter_one_noninitial_missing_dealing() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states(&db).await;
+        let clients_and_states = prepare_clients_and_states(&db).await;
 
         // add all but the first dealing
-        for (dkg_client, state) in clients_and_states.iter_mut().skip(1) {
+        for (dkg_client, state) in clients_and_states.iter().skip(1) {
             dealing_exchange(dkg_client, state, OsRng, true)
                 .await
                 .unwrap();
         }
 
-        for (dkg_client, state) in clients_and_states.iter_mut().skip(1) {
+        for (dkg_client, state) in clients_and_states.iter().skip(1) {
             *db.initial_dealers_db.write().unwrap() = Some(InitialReplacementData {
                 initial_dealers: vec![],
                 initial_height: 1,
@@ -539,9 +632,10 @@ pub(crate) mod tests {
             let filtered = deterministic_filter_dealers(dkg_client, state, 2, true)
                 .await
                 .unwrap();
-            assert_eq!(filtered.len(), TOTAL_DEALINGS);
+            assert_eq!(filtered.len(), DEFAULT_DEALINGS);
             assert!(state
                 .all_dealers()
+                .await
                 .get(&Addr::unchecked(TEST_VALIDATORS_ADDRESS[0]))
                 .unwrap()
                 .as_ref()
@@ -553,7 +647,7 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn check_dealers_filter_all_bad_dealings() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
+        let clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
 
         // corrupt all dealings of one address
         db.dealings_db
@@ -566,16 +660,16 @@ pub(crate) mod tests {
                 });
             });
 
-        for (dkg_client, state) in clients_and_states.iter_mut().skip(1) {
+        for (dkg_client, state) in clients_and_states.iter().skip(1) {
             let filtered = deterministic_filter_dealers(dkg_client, state, 2, false)
                 .await
                 .unwrap();
-            assert_eq!(filtered.len(), TOTAL_DEALINGS);
+            assert_eq!(filtered.len(), DEFAULT_DEALINGS);
             for mapping in filtered.iter() {
                 assert_eq!(mapping.len(), 3);
             }
-            let corrupted_status = state
-                .all_dealers()
+            let dealers = state.all_dealers().await;
+            let corrupted_status = dealers
                 .get(&Addr::unchecked(TEST_VALIDATORS_ADDRESS[0]))
                 .unwrap()
                 .as_ref()
@@ -588,7 +682,7 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn check_dealers_filter_malformed_dealing() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
+        let clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
 
         // corrupt just one dealing
         db.dealings_db
@@ -601,7 +695,7 @@ pub(crate) mod tests {
                 dealings.push(last);
             });
 
-        for (dkg_client, state) in clients_and_states.iter_mut().skip(1) {
+        for (dkg_client, state) in clients_and_states.iter().skip(1) {
             deterministic_filter_dealers(dkg_client, state, 2, false)
                 .await
                 .unwrap();
@@ -610,9 +704,9 @@ pub(crate) mod tests {
             let filtered = deterministic_filter_dealers(dkg_client, state, 2, false)
                 .await
                 .unwrap();
-            assert_eq!(filtered.len(), TOTAL_DEALINGS);
-            let corrupted_status = state
-                .all_dealers()
+            assert_eq!(filtered.len(), DEFAULT_DEALINGS);
+            let dealers = state.all_dealers().await;
+            let corrupted_status = dealers
                 .get(&Addr::unchecked(TEST_VALIDATORS_ADDRESS[0]))
                 .unwrap()
                 .as_ref()
@@ -625,7 +719,7 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn check_dealers_filter_dealing_verification_error() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
+        let clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
 
         // corrupt just one dealing
         db.dealings_db
@@ -643,7 +737,7 @@ pub(crate) mod tests {
                 dealings.push(last);
             });
 
-        for (dkg_client, state) in clients_and_states.iter_mut().skip(1) {
+        for (dkg_client, state) in clients_and_states.iter().skip(1) {
             deterministic_filter_dealers(dkg_client, state, 2, false)
                 .await
                 .unwrap();
@@ -652,9 +746,9 @@ pub(crate) mod tests {
             let filtered = deterministic_filter_dealers(dkg_client, state, 2, false)
                 .await
                 .unwrap();
-            assert_eq!(filtered.len(), TOTAL_DEALINGS);
-            let corrupted_status = state
-                .all_dealers()
+            assert_eq!(filtered.len(), DEFAULT_DEALINGS);
+            let dealers = state.all_dealers().await;
+            let corrupted_status = dealers
                 .get(&Addr::unchecked(TEST_VALIDATORS_ADDRESS[0]))
                 .unwrap()
                 .as_ref()
@@ -667,12 +761,12 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn partial_keypair_derivation() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        let clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
+        for (dkg_client, state) in clients_and_states.iter() {
             let filtered = deterministic_filter_dealers(dkg_client, state, 2, false)
                 .await
                 .unwrap();
-            assert!(derive_partial_keypair(state, 2, filtered).is_ok());
+            assert!(derive_partial_keypair(state, 2, filtered).await.is_ok());
         }
     }
 
@@ -680,7 +774,7 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn partial_keypair_derivation_with_threshold() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
+        let clients_and_states = prepare_clients_and_states_with_dealing(&db).await;
 
         // corrupt just one dealing
         db.dealings_db
@@ -693,11 +787,11 @@ pub(crate) mod tests {
                 dealings.push(last);
             });
 
-        for (dkg_client, state) in clients_and_states.iter_mut().skip(1) {
+        for (dkg_client, state) in clients_and_states.iter().skip(1) {
             let filtered = deterministic_filter_dealers(dkg_client, state, 2, false)
                 .await
                 .unwrap();
-            assert!(derive_partial_keypair(state, 2, filtered).is_ok());
+            assert!(derive_partial_keypair(state, 2, filtered).await.is_ok());
         }
     }
 
@@ -705,14 +799,14 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn submit_verification_key() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_submission(&db).await;
+        let clients_and_states = prepare_clients_and_states_with_submission(&db).await;
 
-        for (_, state) in clients_and_states.iter_mut() {
+        for (_, state) in clients_and_states.iter() {
             assert!(db
                 .proposal_db
                 .read()
                 .unwrap()
-                .contains_key(&state.proposal_id_value().unwrap()));
+                .contains_key(&state.proposal_id_value().await.unwrap()));
             assert!(state.coconut_keypair_is_some().await);
         }
     }
@@ -721,13 +815,13 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn validate_verification_key() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_validation(&db).await;
-        for (_, state) in clients_and_states.iter_mut() {
+        let clients_and_states = prepare_clients_and_states_with_validation(&db).await;
+        for (_, state) in clients_and_states.iter() {
             let proposal = db
                 .proposal_db
                 .read()
                 .unwrap()
-                .get(&state.proposal_id_value().unwrap())
+                .get(&state.proposal_id_value().await.unwrap())
                 .unwrap()
                 .clone();
             assert_eq!(proposal.status, Status::Passed);
@@ -738,7 +832,7 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn validate_verification_key_malformed_share() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_submission(&db).await;
+        let clients_and_states = prepare_clients_and_states_with_submission(&db).await;
 
         db.verification_share_db
             .write()
@@ -746,7 +840,7 @@ pub(crate) mod tests {
             .entry(TEST_VALIDATORS_ADDRESS[0].to_string())
             .and_modify(|share| share.share.push('x'));
 
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             verification_key_validation(dkg_client, state, false)
                 .await
                 .unwrap();
@@ -757,7 +851,7 @@ pub(crate) mod tests {
                 .proposal_db
                 .read()
                 .unwrap()
-                .get(&state.proposal_id_value().unwrap())
+                .get(&state.proposal_id_value().await.unwrap())
                 .unwrap()
                 .clone();
             if idx == 0 {
@@ -772,7 +866,7 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn validate_verification_key_unpaired_share() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_submission(&db).await;
+        let clients_and_states = prepare_clients_and_states_with_submission(&db).await;
 
         let second_share = db
             .verification_share_db
@@ -788,7 +882,7 @@ pub(crate) mod tests {
             .entry(TEST_VALIDATORS_ADDRESS[0].to_string())
             .and_modify(|share| share.share = second_share);
 
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             verification_key_validation(dkg_client, state, false)
                 .await
                 .unwrap();
@@ -799,7 +893,7 @@ pub(crate) mod tests {
                 .proposal_db
                 .read()
                 .unwrap()
-                .get(&state.proposal_id_value().unwrap())
+                .get(&state.proposal_id_value().await.unwrap())
                 .unwrap()
                 .clone();
             if idx == 0 {
@@ -821,20 +915,109 @@ pub(crate) mod tests {
                 .proposal_db
                 .read()
                 .unwrap()
-                .get(&state.proposal_id_value().unwrap())
+                .get(&state.proposal_id_value().await.unwrap())
                 .unwrap()
                 .clone();
             assert_eq!(proposal.status, Status::Executed);
         }
     }
 
+    fn insert_proposal(db: &MockContractDb, proposal_id: u64, status: Status) {
+        let proposal = ProposalResponse {
+            id: proposal_id,
+            title: String::new(),
+            description: String::new(),
+            msgs: vec![],
+            status,
+            expires: cw_utils::Expiration::Never {},
+            threshold: cw_utils::ThresholdResponse::AbsolutePercentage {
+                percentage: cosmwasm_std::Decimal::from_ratio(2u32, 3u32),
+                total_weight: 100,
+            },
+        };
+        db.proposal_db
+            .write()
+            .unwrap()
+            .insert(proposal_id, proposal);
+    }
+
+    async fn clients_and_states_with_proposal(
+        db: &MockContractDb,
+        proposal_id: u64,
+    ) -> Vec<(DkgClient, State)> {
+        let mut clients_and_states = vec![];
+        for addr in TEST_VALIDATORS_ADDRESS {
+            let dkg_client = DkgClient::new(
+                DummyClient::new(AccountId::from_str(addr).unwrap())
+                    .with_proposal_db(&db.proposal_db),
+            );
+            let state = State::new(
+                PathBuf::default(),
+                PersistentState::default(),
+                Url::parse("localhost:8000").unwrap(),
+                DkgKeyPair::new(&setup(), OsRng),
+                KeyPair::new(),
+            );
+            state.set_proposal_id(proposal_id).await;
+            clients_and_states.push((dkg_client, state));
+        }
+        clients_and_states
+    }
+
+    #[tokio::test]
+    async fn finalize_proposal_executed_by_someone_else() {
+        let db = MockContractDb::new();
+        insert_proposal(&db, 42, Status::Executed);
+        let clients_and_states = clients_and_states_with_proposal(&db, 42).await;
+        let (dkg_client, state) = &clients_and_states[0];
+
+        verification_key_finalization(dkg_client, state, false)
+            .await
+            .unwrap();
+        assert!(state.executed_proposal().await);
+    }
+
+    #[tokio::test]
+    async fn finalize_proposal_executed_concurrently() {
+        let db = MockContractDb::new();
+        insert_proposal(&db, 42, Status::Passed);
+        let clients_and_states = clients_and_states_with_proposal(&db, 42).await;
+
+        let results =
+            futures::future::join_all(clients_and_states.iter().map(|(dkg_client, state)| {
+                verification_key_finalization(dkg_client, state, false)
+            }))
+            .await;
+        assert!(results.iter().all(Result::is_ok));
+        for (_, state) in clients_and_states.iter() {
+            assert!(state.executed_proposal().await);
+        }
+        assert_eq!(
+            db.proposal_db.read().unwrap().get(&42).unwrap().status,
+            Status::Executed
+        );
+    }
+
+    #[tokio::test]
+    async fn finalize_proposal_that_has_not_passed() {
+        let db = MockContractDb::new();
+        insert_proposal(&db, 42, Status::Open);
+        let clients_and_states = clients_and_states_with_proposal(&db, 42).await;
+        let (dkg_client, state) = &clients_and_states[0];
+
+        assert!(verification_key_finalization(dkg_client, state, false)
+            .await
+            .is_err());
+        assert!(!state.executed_proposal().await);
+    }
+
     #[tokio::test]
     #[ignore] // expensive test
     async fn reshare_preserves_keys() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_finalization(&db).await;
-        for (_, state) in clients_and_states.iter_mut() {
-            state.set_was_in_progress();
+        let clients_and_states = prepare_clients_and_states_with_finalization(&db).await;
+        for (_, state) in clients_and_states.iter() {
+            state.set_was_in_progress().await;
         }
         let params = Parameters::new(4).unwrap();
 
@@ -846,7 +1029,7 @@ pub(crate) mod tests {
                 .await
                 .unwrap()
                 .verification_key(&params);
-            let index = state.node_index().unwrap();
+            let index = state.node_index().await.unwrap();
             vks.push(vk);
             indices.push(index);
         }
@@ -889,35 +1072,39 @@ pub(crate) mod tests {
         });
         *clients_and_states.first_mut().unwrap() = (new_dkg_client, state);
 
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             public_key_submission(dkg_client, state, true)
                 .await
                 .unwrap();
         }
 
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             dealing_exchange(dkg_client, state, OsRng, true)
                 .await
                 .unwrap();
         }
 
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             let random_file: usize = OsRng.gen();
             let private_key_path = temp_dir().join(format!("private{}.pem", random_file));
             let public_key_path = temp_dir().join(format!("public{}.pem", random_file));
-            let keypair_path = KeyPairPath::new(private_key_path.clone(), public_key_path.clone());
-            verification_key_submission(dkg_client, state, &keypair_path, true)
+            let keystore = CoconutKeyStore::new_with_paths(
+                private_key_path.clone(),
+                public_key_path.clone(),
+                None,
+            );
+            verification_key_submission(dkg_client, state, &keystore, true)
                 .await
                 .unwrap();
             std::fs::remove_file(private_key_path).unwrap();
             std::fs::remove_file(public_key_path).unwrap();
         }
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             verification_key_validation(dkg_client, state, true)
                 .await
                 .unwrap();
         }
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             verification_key_finalization(dkg_client, state, true)
                 .await
                 .unwrap();
@@ -937,7 +1124,7 @@ pub(crate) mod tests {
                 .await
                 .unwrap()
                 .verification_key(&params);
-            let index = state.node_index().unwrap();
+            let index = state.node_index().await.unwrap();
             vks.push(vk);
             indices.push(index);
         }
@@ -949,9 +1136,9 @@ pub(crate) mod tests {
     #[ignore] // expensive test
     async fn reshare_after_reset() {
         let db = MockContractDb::new();
-        let mut clients_and_states = prepare_clients_and_states_with_finalization(&db).await;
-        for (_, state) in clients_and_states.iter_mut() {
-            state.set_was_in_progress();
+        let clients_and_states = prepare_clients_and_states_with_finalization(&db).await;
+        for (_, state) in clients_and_states.iter() {
+            state.set_was_in_progress().await;
         }
 
         let new_dkg_client = DkgClient::new(
@@ -1005,33 +1192,37 @@ pub(crate) mod tests {
         clients_and_states.push((new_dkg_client2, state2));
 
         // DKG in reset mode
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             public_key_submission(dkg_client, state, false)
                 .await
                 .unwrap();
         }
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             dealing_exchange(dkg_client, state, OsRng, false)
                 .await
                 .unwrap();
         }
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             let random_file: usize = OsRng.gen();
             let private_key_path = temp_dir().join(format!("private{}.pem", random_file));
             let public_key_path = temp_dir().join(format!("public{}.pem", random_file));
-            let keypair_path = KeyPairPath::new(private_key_path.clone(), public_key_path.clone());
-            verification_key_submission(dkg_client, state, &keypair_path, false)
+            let keystore = CoconutKeyStore::new_with_paths(
+                private_key_path.clone(),
+                public_key_path.clone(),
+                None,
+            );
+            verification_key_submission(dkg_client, state, &keystore, false)
                 .await
                 .unwrap();
             std::fs::remove_file(private_key_path).unwrap();
             std::fs::remove_file(public_key_path).unwrap();
         }
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             verification_key_validation(dkg_client, state, false)
                 .await
                 .unwrap();
         }
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             verification_key_finalization(dkg_client, state, false)
                 .await
                 .unwrap();
@@ -1042,8 +1233,8 @@ pub(crate) mod tests {
             .unwrap()
             .values()
             .all(|proposal| { proposal.status == Status::Executed }));
-        for (_, state) in clients_and_states.iter_mut() {
-            state.set_was_in_progress();
+        for (_, state) in clients_and_states.iter() {
+            state.set_was_in_progress().await;
         }
 
         // DKG in reshare mode
@@ -1057,7 +1248,7 @@ pub(crate) mod tests {
                 .await
                 .unwrap()
                 .verification_key(&params);
-            let index = state.node_index().unwrap();
+            let index = state.node_index().await.unwrap();
             vks.push(vk);
             indices.push(index);
         }
@@ -1079,36 +1270,40 @@ pub(crate) mod tests {
         });
         *clients_and_states.last_mut().unwrap() = (initial_client2, initial_state2);
 
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             public_key_submission(dkg_client, state, true)
                 .await
                 .unwrap();
         }
 
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             dealing_exchange(dkg_client, state, OsRng, true)
                 .await
                 .unwrap();
         }
 
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             let random_file: usize = OsRng.gen();
             let private_key_path = temp_dir().join(format!("private{}.pem", random_file));
             let public_key_path = temp_dir().join(format!("public{}.pem", random_file));
-            let keypair_path = KeyPairPath::new(private_key_path.clone(), public_key_path.clone());
-            verification_key_submission(dkg_client, state, &keypair_path, true)
+            let keystore = CoconutKeyStore::new_with_paths(
+                private_key_path.clone(),
+                public_key_path.clone(),
+                None,
+            );
+            verification_key_submission(dkg_client, state, &keystore, true)
                 .await
                 .unwrap();
             std::fs::remove_file(private_key_path).unwrap();
             std::fs::remove_file(public_key_path).unwrap();
         }
 
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             verification_key_validation(dkg_client, state, true)
                 .await
                 .unwrap();
         }
-        for (dkg_client, state) in clients_and_states.iter_mut() {
+        for (dkg_client, state) in clients_and_states.iter() {
             verification_key_finalization(dkg_client, state, true)
                 .await
                 .unwrap();
@@ -1128,7 +1323,7 @@ pub(crate) mod tests {
                 .await
                 .unwrap()
                 .verification_key(&params);
-            let index = state.node_index().unwrap();
+            let index = state.node_index().await.unwrap();
             vks.push(vk);
             indices.push(index);
         }
@@ -0,0 +1,614 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! In-memory stand-in for the chain and the contracts the coconut signer interacts with.
+//! Besides the tests, it backs the DKG simulation, which lets the operators rehearse the ceremony.
+
+use crate::coconut::error::{CoconutError, Result};
+use async_trait::async_trait;
+use cosmwasm_std::{to_binary, Addr, CosmosMsg, Decimal, Timestamp, WasmMsg};
+use cw3::ProposalResponse;
+use cw4::MemberResponse;
+use nym_coconut_bandwidth_contract_common::events::DEPOSITED_FUNDS_EVENT_TYPE;
+use nym_coconut_bandwidth_contract_common::spend_credential::SpendCredentialResponse;
+use nym_coconut_dkg_common::complaints::{
+    Complaint, ComplaintReason, DealerComplaintsResponse, DisputedDealer,
+};
+use nym_coconut_dkg_common::dealer::{
+    ContractDealing, DealerDetails, DealerDetailsResponse, DealerType, PagedDealingsResponse,
+};
+use nym_coconut_dkg_common::event_attributes::{DKG_PROPOSAL_ID, NODE_INDEX};
+use nym_coconut_dkg_common::types::{
+    EncodedBTEPublicKeyWithProof, Epoch, EpochId, EpochState, InitialReplacementData,
+    DEFAULT_DEALINGS,
+};
+use nym_coconut_dkg_common::verification_key::{ContractVKShare, VerificationKeyShare};
+use nym_contracts_common::dealings::ContractSafeBytes;
+use nym_dkg::Threshold;
+use nym_validator_client::nyxd::cosmwasm_client::logs::Log;
+use nym_validator_client::nyxd::cosmwasm_client::types::ExecuteResult;
+use nym_validator_client::nyxd::{tx::Hash, AccountId, Fee, TxResponse};
+use rand_07::rngs::OsRng;
+use rand_07::Rng;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// same as the default of the contract, so that the paging is exercised in the tests
+const DUMMY_DEALINGS_PAGE_LIMIT: usize = 1;
+
+#[derive(Clone, Debug)]
+pub(crate) struct DummyClient {
+    validator_address: AccountId,
+    tx_db: Arc<RwLock<HashMap<String, TxResponse>>>,
+    proposal_db: Arc<RwLock<HashMap<u64, ProposalResponse>>>,
+    spent_credential_db: Arc<RwLock<HashMap<String, SpendCredentialResponse>>>,
+
+    epoch: Arc<RwLock<Epoch>>,
+    total_dealings: usize,
+    dealer_details: Arc<RwLock<HashMap<String, (DealerDetails, bool)>>>,
+    threshold: Arc<RwLock<Option<Threshold>>>,
+    dealings: Arc<RwLock<HashMap<String, Vec<ContractSafeBytes>>>>,
+    verification_share: Arc<RwLock<HashMap<String, ContractVKShare>>>,
+    group_db: Arc<RwLock<HashMap<String, MemberResponse>>>,
+    initial_dealers_db: Arc<RwLock<Option<InitialReplacementData>>>,
+    complaints: Arc<RwLock<HashMap<String, Vec<Complaint>>>>,
+}
+
+impl DummyClient {
+    pub fn new(validator_address: AccountId) -> Self {
+        Self {
+            validator_address,
+            tx_db: Arc::new(RwLock::new(HashMap::new())),
+            proposal_db: Arc::new(RwLock::new(HashMap::new())),
+            spent_credential_db: Arc::new(RwLock::new(HashMap::new())),
+            epoch: Arc::new(RwLock::new(Epoch::default())),
+            total_dealings: DEFAULT_DEALINGS,
+            dealer_details: Arc::new(RwLock::new(HashMap::new())),
+            threshold: Arc::new(RwLock::new(None)),
+            dealings: Arc::new(RwLock::new(HashMap::new())),
+            verification_share: Arc::new(RwLock::new(HashMap::new())),
+            group_db: Arc::new(RwLock::new(HashMap::new())),
+            initial_dealers_db: Arc::new(RwLock::new(None)),
+            complaints: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_tx_db(mut self, tx_db: &Arc<RwLock<HashMap<String, TxResponse>>>) -> Self {
+        self.tx_db = Arc::clone(tx_db);
+        self
+    }
+
+    pub fn with_proposal_db(
+        mut self,
+        proposal_db: &Arc<RwLock<HashMap<u64, ProposalResponse>>>,
+    ) -> Self {
+        self.proposal_db = Arc::clone(proposal_db);
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_spent_credential_db(
+        mut self,
+        spent_credential_db: &Arc<RwLock<HashMap<String, SpendCredentialResponse>>>,
+    ) -> Self {
+        self.spent_credential_db = Arc::clone(spent_credential_db);
+        self
+    }
+
+    pub fn _with_epoch(mut self, epoch: &Arc<RwLock<Epoch>>) -> Self {
+        self.epoch = Arc::clone(epoch);
+        self
+    }
+
+    pub fn with_total_dealings(mut self, total_dealings: usize) -> Self {
+        self.total_dealings = total_dealings;
+        self
+    }
+
+    pub fn with_dealer_details(
+        mut self,
+        dealer_details: &Arc<RwLock<HashMap<String, (DealerDetails, bool)>>>,
+    ) -> Self {
+        self.dealer_details = Arc::clone(dealer_details);
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: &Arc<RwLock<Option<Threshold>>>) -> Self {
+        self.threshold = Arc::clone(threshold);
+        self
+    }
+
+    pub fn with_dealings(
+        mut self,
+        dealings: &Arc<RwLock<HashMap<String, Vec<ContractSafeBytes>>>>,
+    ) -> Self {
+        self.dealings = Arc::clone(dealings);
+        self
+    }
+
+    pub fn with_verification_share(
+        mut self,
+        verification_share: &Arc<RwLock<HashMap<String, ContractVKShare>>>,
+    ) -> Self {
+        self.verification_share = Arc::clone(verification_share);
+        self
+    }
+
+    pub fn _with_group_db(
+        mut self,
+        group_db: &Arc<RwLock<HashMap<String, MemberResponse>>>,
+    ) -> Self {
+        self.group_db = Arc::clone(group_db);
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_initial_dealers_db(
+        mut self,
+        initial_dealers: &Arc<RwLock<Option<InitialReplacementData>>>,
+    ) -> Self {
+        self.initial_dealers_db = Arc::clone(initial_dealers);
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_complaints(
+        mut self,
+        complaints: &Arc<RwLock<HashMap<String, Vec<Complaint>>>>,
+    ) -> Self {
+        self.complaints = Arc::clone(complaints);
+        self
+    }
+
+    fn is_upheld(&self, complaints: usize) -> bool {
+        match *self.threshold.read().unwrap() {
+            Some(threshold) => complaints as u64 >= threshold,
+            None => false,
+        }
+    }
+}
+
+#[async_trait]
+impl super::client::Client for DummyClient {
+    async fn address(&self) -> AccountId {
+        self.validator_address.clone()
+    }
+
+    async fn get_tx(&self, tx_hash: &str) -> Result<TxResponse> {
+        self.tx_db
+            .read()
+            .unwrap()
+            .get(tx_hash)
+            .cloned()
+            .ok_or(CoconutError::TxHashParseError)
+    }
+
+    async fn get_current_block_height(&self) -> Result<u64> {
+        Ok(self
+            .tx_db
+            .read()
+            .unwrap()
+            .values()
+            .map(|tx| tx.height.value())
+            .max()
+            .unwrap_or_default())
+    }
+
+    async fn search_deposits(&self, from_height: u64, to_height: u64) -> Result<Vec<TxResponse>> {
+        Ok(self
+            .tx_db
+            .read()
+            .unwrap()
+            .values()
+            .filter(|tx| (from_height..=to_height).contains(&tx.height.value()))
+            .filter(|tx| {
+                tx.tx_result
+                    .events
+                    .iter()
+                    .any(|event| event.type_str == format!("wasm-{}", DEPOSITED_FUNDS_EVENT_TYPE))
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_proposal(&self, proposal_id: u64) -> Result<ProposalResponse> {
+        self.proposal_db
+            .read()
+            .unwrap()
+            .get(&proposal_id)
+            .cloned()
+            .ok_or(CoconutError::IncorrectProposal {
+                reason: String::from("proposal not found"),
+            })
+    }
+
+    async fn list_proposals(&self) -> Result<Vec<ProposalResponse>> {
+        Ok(self.proposal_db.read().unwrap().values().cloned().collect())
+    }
+
+    async fn get_spent_credential(
+        &self,
+        blinded_serial_number: String,
+    ) -> Result<SpendCredentialResponse> {
+        self.spent_credential_db
+            .read()
+            .unwrap()
+            .get(&blinded_serial_number)
+            .cloned()
+            .ok_or(CoconutError::InvalidCredentialStatus {
+                status: String::from("spent credential not found"),
+            })
+    }
+
+    async fn get_current_epoch(&self) -> Result<Epoch> {
+        Ok(*self.epoch.read().unwrap())
+    }
+
+    async fn group_member(&self, addr: String) -> Result<MemberResponse> {
+        Ok(self
+            .group_db
+            .read()
+            .unwrap()
+            .get(&addr)
+            .cloned()
+            .unwrap_or(MemberResponse { weight: None }))
+    }
+
+    async fn get_current_epoch_threshold(&self) -> Result<Option<Threshold>> {
+        Ok(*self.threshold.read().unwrap())
+    }
+
+    async fn get_total_dealings(&self) -> Result<usize> {
+        Ok(self.total_dealings)
+    }
+
+    async fn get_initial_dealers(&self) -> Result<Option<InitialReplacementData>> {
+        Ok(self.initial_dealers_db.read().unwrap().clone())
+    }
+
+    async fn get_self_registered_dealer_details(&self) -> Result<DealerDetailsResponse> {
+        let (details, dealer_type) = if let Some((details, current)) = self
+            .dealer_details
+            .read()
+            .unwrap()
+            .get(self.validator_address.as_ref())
+            .cloned()
+        {
+            let dealer_type = if current {
+                DealerType::Current
+            } else {
+                DealerType::Past
+            };
+            (Some(details), dealer_type)
+        } else {
+            (None, DealerType::Unknown)
+        };
+        Ok(DealerDetailsResponse {
+            details,
+            dealer_type,
+        })
+    }
+
+    async fn get_current_dealers(&self) -> Result<Vec<DealerDetails>> {
+        Ok(self
+            .dealer_details
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .filter_map(|(d, current)| if current { Some(d) } else { None })
+            .collect())
+    }
+
+    async fn get_dealings_paged(
+        &self,
+        idx: usize,
+        start_after: Option<String>,
+    ) -> Result<PagedDealingsResponse> {
+        let guard = self.dealings.read().unwrap();
+        let mut dealers: Vec<_> = guard
+            .keys()
+            .filter(|dealer| match &start_after {
+                Some(start) => *dealer > start,
+                None => true,
+            })
+            .collect();
+        dealers.sort();
+
+        // as in the contract, the dealers that haven't submitted the dealing of this index yet are skipped
+        let dealings: Vec<_> = dealers
+            .into_iter()
+            .filter_map(|dealer| {
+                guard[dealer].get(idx).map(|dealing| ContractDealing {
+                    dealing: dealing.clone(),
+                    dealer: Addr::unchecked(dealer),
+                })
+            })
+            .take(DUMMY_DEALINGS_PAGE_LIMIT)
+            .collect();
+        let start_next_after = dealings.last().map(|dealing| dealing.dealer.clone());
+
+        Ok(PagedDealingsResponse::new(
+            dealings,
+            DUMMY_DEALINGS_PAGE_LIMIT,
+            start_next_after,
+        ))
+    }
+
+    async fn get_verification_key_shares(
+        &self,
+        _epoch_id: EpochId,
+    ) -> Result<Vec<ContractVKShare>> {
+        Ok(self
+            .verification_share
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    async fn vote_proposal(
+        &self,
+        proposal_id: u64,
+        vote_yes: bool,
+        _memo: Option<String>,
+        _fee: Option<Fee>,
+    ) -> Result<()> {
+        if let Some(proposal) = self.proposal_db.write().unwrap().get_mut(&proposal_id) {
+            // for now, just suppose that every vote is honest
+            if !vote_yes {
+                proposal.status = cw3::Status::Rejected;
+            } else if vote_yes && proposal.status == cw3::Status::Open {
+                proposal.status = cw3::Status::Passed;
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute_proposal(&self, proposal_id: u64) -> Result<()> {
+        // just like the multisig contract, only allow executing each passed proposal once
+        match self.proposal_db.write().unwrap().get_mut(&proposal_id) {
+            Some(prop) if prop.status == cw3::Status::Passed => {
+                prop.status = cw3::Status::Executed;
+                Ok(())
+            }
+            _ => Err(CoconutError::IncorrectProposal {
+                reason: nym_multisig_contract_common::error::ContractError::WrongExecuteStatus {}
+                    .to_string(),
+            }),
+        }
+    }
+
+    async fn advance_epoch_state(&self) -> Result<()> {
+        // just like the contract, move onto the next state of the ceremony (fixing the threshold
+        // once the dealings start getting exchanged), or extend the epoch if it's already in progress
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut epoch = self.epoch.write().unwrap();
+        let state = match epoch.state.next() {
+            Some(state) => {
+                if let EpochState::DealingExchange { .. } = state {
+                    let dealers = self
+                        .dealer_details
+                        .read()
+                        .unwrap()
+                        .values()
+                        .filter(|(_, active)| *active)
+                        .count() as u64;
+                    *self.threshold.write().unwrap() = Some((2 * dealers + 3 - 1) / 3);
+                }
+                state
+            }
+            None => epoch.state,
+        };
+        *epoch = Epoch::new(
+            state,
+            epoch.epoch_id,
+            epoch.time_configuration,
+            Timestamp::from_seconds(now),
+        );
+        Ok(())
+    }
+
+    async fn register_dealer(
+        &self,
+        bte_public_key_with_proof: EncodedBTEPublicKeyWithProof,
+        announce_address: String,
+        _resharing: bool,
+    ) -> Result<ExecuteResult> {
+        let mut dealer_details = self.dealer_details.write().unwrap();
+        let assigned_index = if let Some((details, active)) =
+            dealer_details.get_mut(self.validator_address.as_ref())
+        {
+            *active = true;
+            details.assigned_index
+        } else {
+            // let assigned_index = OsRng.gen();
+            let assigned_index = dealer_details
+                .values()
+                .map(|(d, _)| d.assigned_index)
+                .max()
+                .unwrap_or(0)
+                + 1;
+            dealer_details.insert(
+                self.validator_address.to_string(),
+                (
+                    DealerDetails {
+                        address: Addr::unchecked(self.validator_address.to_string()),
+                        bte_public_key_with_proof,
+                        announce_address,
+                        assigned_index,
+                    },
+                    true,
+                ),
+            );
+            assigned_index
+        };
+        Ok(ExecuteResult {
+            logs: vec![Log {
+                msg_index: 0,
+                events: vec![cosmwasm_std::Event::new("wasm")
+                    .add_attribute(NODE_INDEX, assigned_index.to_string())],
+            }],
+            data: Default::default(),
+            transaction_hash: Hash::new([0; 32]),
+            gas_info: Default::default(),
+        })
+    }
+
+    async fn submit_dealing(
+        &self,
+        dealing_bytes: ContractSafeBytes,
+        _resharing: bool,
+    ) -> Result<ExecuteResult> {
+        self.dealings
+            .write()
+            .unwrap()
+            .entry(self.validator_address.to_string())
+            .and_modify(|v| {
+                if v.len() < self.total_dealings {
+                    v.push(dealing_bytes.clone())
+                }
+            })
+            .or_insert_with(|| vec![dealing_bytes]);
+
+        Ok(ExecuteResult {
+            logs: vec![],
+            data: Default::default(),
+            transaction_hash: Hash::new([0; 32]),
+            gas_info: Default::default(),
+        })
+    }
+
+    async fn get_dealer_complaints(&self, dealer: &AccountId) -> Result<DealerComplaintsResponse> {
+        let complaints = self
+            .complaints
+            .read()
+            .unwrap()
+            .get(dealer.as_ref())
+            .cloned()
+            .unwrap_or_default();
+        Ok(DealerComplaintsResponse {
+            dealer: Addr::unchecked(dealer.as_ref()),
+            upheld: self.is_upheld(complaints.len()),
+            complaints,
+        })
+    }
+
+    async fn get_disputed_dealers(&self) -> Result<Vec<DisputedDealer>> {
+        Ok(self
+            .complaints
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(dealer, complaints)| DisputedDealer {
+                dealer: Addr::unchecked(dealer),
+                complaints: complaints.len() as u32,
+                upheld: self.is_upheld(complaints.len()),
+            })
+            .collect())
+    }
+
+    async fn submit_complaint(
+        &self,
+        dealer: &AccountId,
+        dealing_index: Option<u64>,
+        reason: ComplaintReason,
+        _resharing: bool,
+    ) -> Result<ExecuteResult> {
+        let complaint = Complaint {
+            complainer: Addr::unchecked(self.validator_address.as_ref()),
+            dealer: Addr::unchecked(dealer.as_ref()),
+            dealing_index,
+            reason,
+        };
+        let mut complaints = self.complaints.write().unwrap();
+        let dealer_complaints = complaints.entry(dealer.to_string()).or_default();
+        if dealer_complaints
+            .iter()
+            .any(|c| c.complainer == complaint.complainer)
+        {
+            // Just throw some error, not really the correct one
+            return Err(CoconutError::DepositEncrKeyNotFound);
+        }
+        dealer_complaints.push(complaint);
+
+        Ok(ExecuteResult {
+            logs: vec![],
+            data: Default::default(),
+            transaction_hash: Hash::new([0; 32]),
+            gas_info: Default::default(),
+        })
+    }
+
+    async fn submit_verification_key_share(
+        &self,
+        share: VerificationKeyShare,
+        resharing: bool,
+    ) -> Result<ExecuteResult> {
+        let (dealer_details, active) = self
+            .dealer_details
+            .read()
+            .unwrap()
+            .get(self.validator_address.as_ref())
+            .unwrap()
+            .clone();
+        if !active {
+            // Just throw some error, not really the correct one
+            return Err(CoconutError::DepositEncrKeyNotFound);
+        }
+        self.verification_share.write().unwrap().insert(
+            self.validator_address.to_string(),
+            ContractVKShare {
+                share,
+                announce_address: dealer_details.announce_address.clone(),
+                node_index: dealer_details.assigned_index,
+                owner: Addr::unchecked(self.validator_address.to_string()),
+                epoch_id: 0,
+                verified: false,
+            },
+        );
+        let proposal_id = OsRng.gen();
+        let verify_vk_share_req =
+            nym_coconut_dkg_common::msg::ExecuteMsg::VerifyVerificationKeyShare {
+                owner: Addr::unchecked(self.validator_address.as_ref()),
+                resharing,
+            };
+        let verify_vk_share_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::new(),
+            msg: to_binary(&verify_vk_share_req).unwrap(),
+            funds: vec![],
+        });
+        let proposal = ProposalResponse {
+            id: proposal_id,
+            title: String::new(),
+            description: String::new(),
+            msgs: vec![verify_vk_share_msg],
+            status: cw3::Status::Open,
+            expires: cw_utils::Expiration::Never {},
+            threshold: cw_utils::ThresholdResponse::AbsolutePercentage {
+                percentage: Decimal::from_ratio(2u32, 3u32),
+                total_weight: 100,
+            },
+        };
+        self.proposal_db
+            .write()
+            .unwrap()
+            .insert(proposal_id, proposal);
+        Ok(ExecuteResult {
+            logs: vec![Log {
+                msg_index: 0,
+                events: vec![cosmwasm_std::Event::new("wasm")
+                    .add_attribute(DKG_PROPOSAL_ID, proposal_id.to_string())],
+            }],
+            data: Default::default(),
+            transaction_hash: Hash::new([0; 32]),
+            gas_info: Default::default(),
+        })
+    }
+}
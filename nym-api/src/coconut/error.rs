@@ -7,11 +7,13 @@ use rocket::{response, Request, Response};
 use std::io::Cursor;
 use thiserror::Error;
 
+use nym_api_requests::coconut::IssuanceSuspension;
 use nym_crypto::asymmetric::{
     encryption::KeyRecoveryError,
     identity::{Ed25519RecoveryError, SignatureError},
 };
 use nym_dkg::error::DkgError;
+use nym_retry::Retryable;
 use nym_validator_client::nyxd::error::NyxdError;
 
 use crate::node_status_api::models::NymApiStorageError;
@@ -65,6 +67,12 @@ pub enum CoconutError {
     #[error("Inconsistent public attributes")]
     InconsistentPublicAttributes,
 
+    #[error("The credential request does not specify the expiration date")]
+    MissingExpirationDate,
+
+    #[error("The requested credential expiration date ({expiration_date}) is invalid")]
+    InvalidExpirationDate { expiration_date: String },
+
     #[error(
         "Public attributes in request differ from the ones in deposit - Expected {0}, got {1}"
     )]
@@ -94,18 +102,65 @@ pub enum CoconutError {
     #[error("Unrecoverable state: {reason}")]
     UnrecoverableState { reason: String },
 
-    #[error("DKG has not finished yet in order to derive the coconut key")]
-    KeyPairNotDerivedYet,
+    #[error("DKG is still in progress (current state: {state}), so the dealer set changes can't be evaluated yet")]
+    DkgNotFinished { state: String },
 
     #[error("The coconut keypair is corrupted")]
     CorruptedCoconutKeyPair,
 
+    #[error("Could not encrypt the coconut secret key: {reason}")]
+    SecretKeyEncryptionError { reason: String },
+
+    #[error("Could not decrypt the coconut secret key - is the passphrase correct?")]
+    SecretKeyDecryptionError,
+
+    #[error("The coconut secret key is encrypted, but no passphrase has been provided (either set `encrypt_secret_key` in the config or provide it through {})", crate::coconut::keystore::COCONUT_KEY_PASSPHRASE_ENV)]
+    MissingSecretKeyPassphrase,
+
     #[error("There was a problem with the proposal id: {reason}")]
     ProposalIdError { reason: String },
+
+    #[error("The credential issuance is suspended: {}", .0.details)]
+    IssuanceSuspended(IssuanceSuspension),
+}
+
+impl CoconutError {
+    /// Specifies whether the error originated from interacting with the chain,
+    /// in which case it might be worth switching to a different nyxd endpoint.
+    pub(crate) fn is_chain_error(&self) -> bool {
+        matches!(
+            self,
+            CoconutError::NyxdError(_) | CoconutError::ValidatorClientError(_)
+        )
+    }
+}
+
+impl Retryable for CoconutError {
+    // failures of the chain queries and transactions might be transient,
+    // everything else is not going to go away on its own
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            CoconutError::NyxdError(_)
+                | CoconutError::ValidatorClientError(_)
+                | CoconutError::IOError(_)
+        )
+    }
 }
 
 impl<'r, 'o: 'r> Responder<'r, 'o> for CoconutError {
     fn respond_to(self, _: &'r Request<'_>) -> response::Result<'o> {
+        // the suspension reason is meant to be machine-readable, so it's returned as json
+        if let CoconutError::IssuanceSuspended(suspension) = self {
+            let body =
+                serde_json::to_string(&suspension).map_err(|_| Status::InternalServerError)?;
+            return Response::build()
+                .header(ContentType::JSON)
+                .sized_body(body.len(), Cursor::new(body))
+                .status(Status::ServiceUnavailable)
+                .ok();
+        }
+
         let err_msg = self.to_string();
         Response::build()
             .header(ContentType::Plain)
@@ -0,0 +1,312 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Storage of the coconut keypair derived at the end of the DKG. The partial secret key can be
+//! optionally encrypted at rest with a passphrase (using the same scheme as the client storages,
+//! i.e. `nym-store-cipher`), which has to be provided on startup either through the environment
+//! or interactively.
+
+use crate::coconut::error::CoconutError;
+use crate::support::config::Config;
+use nym_coconut::{KeyPair, SecretKey, VerificationKey};
+use nym_coconut_dkg_common::types::EpochId;
+use nym_pemstore::traits::PemStorableKey;
+use nym_store_cipher::Zeroizing;
+use std::convert::Infallible;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Environment variable holding the passphrase of the encrypted coconut secret key.
+pub(crate) const COCONUT_KEY_PASSPHRASE_ENV: &str = "NYM_API_COCONUT_KEY_PASSPHRASE";
+
+// associated data binding the encrypted value to its purpose
+const SECRET_KEY_AAD: &[u8] = b"nym-api/coconut-secret-key";
+
+type Passphrase = Arc<Zeroizing<String>>;
+
+lazy_static::lazy_static! {
+    // the passphrase is only ever asked for once per process, regardless of how many keystores get created
+    static ref PASSPHRASE: Mutex<Option<Passphrase>> = Mutex::new(None);
+}
+
+/// Passphrase-encrypted coconut secret key, as sealed by `nym-store-cipher`.
+struct EncryptedSecretKey(Vec<u8>);
+
+impl PemStorableKey for EncryptedSecretKey {
+    type Error = Infallible;
+
+    fn pem_type() -> &'static str {
+        "ENCRYPTED COCONUT SECRET KEY"
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.clone()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(EncryptedSecretKey(bytes.to_vec()))
+    }
+}
+
+fn encrypt_secret_key(
+    secret_key: &SecretKey,
+    passphrase: &str,
+) -> Result<EncryptedSecretKey, CoconutError> {
+    let plaintext = Zeroizing::new(secret_key.to_bytes());
+    nym_store_cipher::seal(passphrase, &plaintext, SECRET_KEY_AAD)
+        .map(EncryptedSecretKey)
+        .map_err(|err| CoconutError::SecretKeyEncryptionError {
+            reason: err.to_string(),
+        })
+}
+
+fn decrypt_secret_key(
+    encrypted: &EncryptedSecretKey,
+    passphrase: &str,
+) -> Result<SecretKey, CoconutError> {
+    let plaintext = nym_store_cipher::open(passphrase, &encrypted.0, SECRET_KEY_AAD)
+        .map_err(|_| CoconutError::SecretKeyDecryptionError)?;
+    SecretKey::from_bytes(&plaintext).map_err(|_| CoconutError::CorruptedCoconutKeyPair)
+}
+
+/// Obtains the passphrase from the environment or, if it's not set there, asks for it on the terminal
+/// (without echoing it back). It's only done once, subsequent calls return the same passphrase.
+fn read_passphrase() -> Result<Passphrase, CoconutError> {
+    let mut cached = PASSPHRASE.lock().expect("passphrase lock got poisoned");
+    if let Some(passphrase) = cached.as_ref() {
+        return Ok(Arc::clone(passphrase));
+    }
+
+    let passphrase = match std::env::var(COCONUT_KEY_PASSPHRASE_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => Zeroizing::new(passphrase),
+        _ => Zeroizing::new(rpassword::prompt_password(
+            "Enter the passphrase of the coconut secret key: ",
+        )?),
+    };
+    if passphrase.is_empty() {
+        return Err(CoconutError::MissingSecretKeyPassphrase);
+    }
+
+    let passphrase = Arc::new(passphrase);
+    *cached = Some(Arc::clone(&passphrase));
+    Ok(passphrase)
+}
+
+/// Stores the key so that a crash midway through never leaves a partially written file behind:
+/// it's written to a temporary file first, which then replaces the existing one.
+fn store_key_atomically<T: PemStorableKey>(key: &T, path: &Path) -> io::Result<()> {
+    let temp_path = path.with_extension("tmp");
+    let pem = Zeroizing::new(nym_pemstore::encode_key(key));
+    {
+        let mut file = File::create(&temp_path)?;
+        #[cfg(target_family = "unix")]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(pem.as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&temp_path, path)?;
+
+    // make sure the rename itself is persisted
+    #[cfg(target_family = "unix")]
+    if let Some(parent) = path.parent() {
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Path under which the key derived in the given epoch is kept once it's been rotated,
+/// e.g. `coconut.pem` becomes `coconut.epoch_3.pem`.
+fn archived_path(path: &Path, epoch_id: EpochId) -> PathBuf {
+    match path.extension() {
+        Some(extension) => {
+            path.with_extension(format!("epoch_{epoch_id}.{}", extension.to_string_lossy()))
+        }
+        None => path.with_extension(format!("epoch_{epoch_id}")),
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct CoconutKeyStore {
+    secret_key_path: PathBuf,
+    verification_key_path: PathBuf,
+    passphrase: Option<Passphrase>,
+}
+
+impl CoconutKeyStore {
+    /// Creates the keystore for the paths specified in the config. If the secret key is meant
+    /// to be encrypted, the passphrase is obtained straight away.
+    pub(crate) fn new(config: &Config) -> Result<Self, CoconutError> {
+        let passphrase = if config.get_encrypt_coconut_secret_key() {
+            Some(read_passphrase()?)
+        } else {
+            None
+        };
+        Ok(CoconutKeyStore {
+            secret_key_path: config.secret_key_path(),
+            verification_key_path: config.verification_key_path(),
+            passphrase,
+        })
+    }
+
+    pub(crate) fn new_with_paths(
+        secret_key_path: PathBuf,
+        verification_key_path: PathBuf,
+        passphrase: Option<&str>,
+    ) -> Self {
+        CoconutKeyStore {
+            secret_key_path,
+            verification_key_path,
+            passphrase: passphrase
+                .map(|passphrase| Arc::new(Zeroizing::new(passphrase.to_string()))),
+        }
+    }
+
+    pub(crate) fn store(&self, keypair: &KeyPair) -> Result<(), CoconutError> {
+        nym_pemstore::store_key(&keypair.verification_key(), &self.verification_key_path)?;
+        match &self.passphrase {
+            Some(passphrase) => {
+                let encrypted = encrypt_secret_key(&keypair.secret_key(), passphrase)?;
+                store_key_atomically(&encrypted, &self.secret_key_path)?;
+            }
+            None => nym_pemstore::store_key(&keypair.secret_key(), &self.secret_key_path)?,
+        }
+        Ok(())
+    }
+
+    pub(crate) fn load(&self) -> Result<KeyPair, CoconutError> {
+        let verification_key =
+            nym_pemstore::load_key::<VerificationKey>(&self.verification_key_path)?;
+        let encrypted = nym_pemstore::load_key::<EncryptedSecretKey>(&self.secret_key_path);
+
+        let secret_key = match (&self.passphrase, encrypted) {
+            (Some(passphrase), Ok(encrypted)) => decrypt_secret_key(&encrypted, passphrase)?,
+            (None, Ok(_)) => return Err(CoconutError::MissingSecretKeyPassphrase),
+            (passphrase, Err(_)) => {
+                let secret_key = nym_pemstore::load_key::<SecretKey>(&self.secret_key_path)?;
+                if let Some(passphrase) = passphrase {
+                    // the key has been stored before the encryption got enabled
+                    info!("Encrypting the previously stored coconut secret key");
+                    let encrypted = encrypt_secret_key(&secret_key, passphrase)?;
+                    store_key_atomically(&encrypted, &self.secret_key_path)?;
+                }
+                secret_key
+            }
+        };
+        Ok(KeyPair::from_keys(secret_key, verification_key))
+    }
+
+    /// Keeps a copy of the currently stored keys, derived in the given epoch, so that they
+    /// survive being replaced by the keys of the next epoch. The secret key is copied as is,
+    /// i.e. it remains encrypted if it has been stored encrypted.
+    pub(crate) fn archive(&self, epoch_id: EpochId) -> Result<(), CoconutError> {
+        for path in [&self.secret_key_path, &self.verification_key_path] {
+            match fs::copy(path, archived_path(path, epoch_id)) {
+                Ok(_) => (),
+                // nothing has been derived in that epoch
+                Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn remove(&self) {
+        fs::remove_file(&self.secret_key_path).ok();
+        fs::remove_file(&self.verification_key_path).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nym_coconut::{ttp_keygen, Parameters};
+
+    fn keypair() -> KeyPair {
+        let params = Parameters::new(4).unwrap();
+        ttp_keygen(&params, 1, 1).unwrap().remove(0)
+    }
+
+    fn keystore(dir: &std::path::Path, passphrase: Option<&str>) -> CoconutKeyStore {
+        CoconutKeyStore::new_with_paths(dir.join("secret.pem"), dir.join("vk.pem"), passphrase)
+    }
+
+    #[test]
+    fn encrypted_secret_key_roundtrip() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let keypair = keypair();
+        let keystore = keystore(dir, Some("foomp"));
+        keystore.store(&keypair).unwrap();
+
+        // the secret key is not stored in plaintext
+        assert!(nym_pemstore::load_key::<SecretKey>(&dir.join("secret.pem")).is_err());
+
+        let loaded = keystore.load().unwrap();
+        assert_eq!(
+            loaded.secret_key().to_bytes(),
+            keypair.secret_key().to_bytes()
+        );
+
+        assert!(matches!(
+            self::keystore(dir, Some("bar")).load(),
+            Err(CoconutError::SecretKeyDecryptionError)
+        ));
+        assert!(matches!(
+            self::keystore(dir, None).load(),
+            Err(CoconutError::MissingSecretKeyPassphrase)
+        ));
+    }
+
+    #[test]
+    fn plaintext_secret_key_gets_encrypted_once_passphrase_is_set() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let keypair = keypair();
+        keystore(dir, None).store(&keypair).unwrap();
+
+        let loaded = keystore(dir, Some("foomp")).load().unwrap();
+        assert_eq!(
+            loaded.secret_key().to_bytes(),
+            keypair.secret_key().to_bytes()
+        );
+        assert!(nym_pemstore::load_key::<SecretKey>(&dir.join("secret.pem")).is_err());
+        assert!(keystore(dir, Some("foomp")).load().is_ok());
+
+        // the migrated key has replaced the plaintext one without leaving anything behind
+        assert!(!dir.join("secret.tmp").exists());
+    }
+
+    #[test]
+    fn archived_keys_outlive_the_current_ones() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let keypair = keypair();
+        let keystore = keystore(dir, Some("foomp"));
+
+        // nothing to archive yet
+        keystore.archive(1).unwrap();
+        assert!(!dir.join("secret.epoch_1.pem").exists());
+
+        keystore.store(&keypair).unwrap();
+        keystore.archive(2).unwrap();
+        keystore.remove();
+        assert!(keystore.load().is_err());
+
+        let archived = CoconutKeyStore::new_with_paths(
+            dir.join("secret.epoch_2.pem"),
+            dir.join("vk.epoch_2.pem"),
+            Some("foomp"),
+        )
+        .load()
+        .unwrap();
+        assert_eq!(
+            archived.secret_key().to_bytes(),
+            keypair.secret_key().to_bytes()
+        );
+    }
+}
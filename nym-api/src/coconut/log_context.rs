@@ -0,0 +1,163 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hierarchical log context of the coconut module.
+//!
+//! Everything logged on behalf of a particular ceremony (the DKG, the issuance of a credential or
+//! the verification of one) is logged under the target of the form
+//! `nym_api::coconut::epoch_<epoch id>::<ceremony id>`, so that the logs spanning multiple epochs
+//! could be filtered per epoch or per ceremony using the standard `RUST_LOG` directives, e.g.
+//! `RUST_LOG=nym_api::coconut::epoch_3::issuance=debug`.
+
+use nym_coconut_dkg_common::types::EpochId;
+use std::fmt::{self, Display, Formatter};
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+
+const BASE_TARGET: &str = "nym_api::coconut";
+
+tokio::task_local! {
+    static LOG_CONTEXT: LogContext;
+}
+
+/// Logs the message under the target of the current log context.
+macro_rules! ctx_log {
+    ($lvl:expr, $($arg:tt)+) => {
+        ::log::log!(
+            target: $crate::coconut::log_context::current_target().as_str(),
+            $lvl,
+            $($arg)+
+        )
+    };
+}
+
+macro_rules! ctx_debug {
+    ($($arg:tt)+) => { $crate::coconut::log_context::ctx_log!(::log::Level::Debug, $($arg)+) };
+}
+
+macro_rules! ctx_info {
+    ($($arg:tt)+) => { $crate::coconut::log_context::ctx_log!(::log::Level::Info, $($arg)+) };
+}
+
+macro_rules! ctx_warn {
+    ($($arg:tt)+) => { $crate::coconut::log_context::ctx_log!(::log::Level::Warn, $($arg)+) };
+}
+
+pub(crate) use {ctx_debug, ctx_info, ctx_log, ctx_warn};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Ceremony {
+    Dkg,
+    Issuance { tx_hash: String },
+    Verification { proposal_id: u64 },
+}
+
+impl Display for Ceremony {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Ceremony::Dkg => write!(f, "dkg"),
+            Ceremony::Issuance { tx_hash } => write!(f, "issuance::{tx_hash}"),
+            Ceremony::Verification { proposal_id } => write!(f, "verification::{proposal_id}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LogContext {
+    epoch_id: Option<EpochId>,
+    ceremony: Ceremony,
+}
+
+impl LogContext {
+    pub(crate) fn new(epoch_id: Option<EpochId>, ceremony: Ceremony) -> Self {
+        LogContext { epoch_id, ceremony }
+    }
+
+    pub(crate) fn epoch_id(&self) -> Option<EpochId> {
+        self.epoch_id
+    }
+
+    pub(crate) fn ceremony_id(&self) -> String {
+        self.ceremony.to_string()
+    }
+
+    pub(crate) fn target(&self) -> String {
+        match self.epoch_id {
+            Some(epoch_id) => format!("{BASE_TARGET}::epoch_{epoch_id}::{}", self.ceremony),
+            None => format!("{BASE_TARGET}::epoch_unknown::{}", self.ceremony),
+        }
+    }
+
+    /// Drives the future to completion with everything it logs attributed to this context.
+    pub(crate) async fn scope<F: Future>(self, future: F) -> F::Output {
+        LOG_CONTEXT.scope(self, future).await
+    }
+}
+
+/// Target of the logs produced within the current context.
+pub(crate) fn current_target() -> String {
+    LOG_CONTEXT
+        .try_with(LogContext::target)
+        .unwrap_or_else(|_| BASE_TARGET.to_string())
+}
+
+/// Context of the DKG ceremony this signer is currently taking part in. It's shared with
+/// the credential issuance, so that the issued credentials could be attributed to the epoch
+/// of the keys they have been signed with.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DkgLogContext {
+    inner: Arc<RwLock<Option<LogContext>>>,
+}
+
+impl DkgLogContext {
+    pub(crate) fn get(&self) -> Option<LogContext> {
+        self.inner
+            .read()
+            .expect("dkg log context lock got poisoned")
+            .clone()
+    }
+
+    pub(crate) fn epoch_id(&self) -> Option<EpochId> {
+        self.get().and_then(|context| context.epoch_id())
+    }
+
+    pub(crate) fn set(&self, context: LogContext) {
+        *self
+            .inner
+            .write()
+            .expect("dkg log context lock got poisoned") = Some(context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_are_nested_under_the_epoch() {
+        let context = LogContext::new(Some(3), Ceremony::Dkg);
+        assert_eq!(context.target(), "nym_api::coconut::epoch_3::dkg");
+
+        let context = LogContext::new(
+            None,
+            Ceremony::Issuance {
+                tx_hash: "6B2741".to_string(),
+            },
+        );
+        assert_eq!(
+            context.target(),
+            "nym_api::coconut::epoch_unknown::issuance::6B2741"
+        );
+    }
+
+    #[tokio::test]
+    async fn current_target_follows_the_scope() {
+        assert_eq!(current_target(), BASE_TARGET);
+
+        let context = LogContext::new(Some(1), Ceremony::Verification { proposal_id: 42 });
+        let target = context.scope(async { current_target() }).await;
+        assert_eq!(target, "nym_api::coconut::epoch_1::verification::42");
+
+        assert_eq!(current_target(), BASE_TARGET);
+    }
+}
@@ -1,16 +1,21 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use self::circuit_breaker::IssuanceCircuitBreaker;
 use self::comm::APICommunicationChannel;
 use crate::coconut::client::Client as LocalClient;
-use crate::coconut::deposit::extract_encryption_key;
+use crate::coconut::deposit::{extract_encryption_key, verify_indexed_deposit};
+use crate::coconut::dkg::state::State as DkgState;
 use crate::coconut::error::{CoconutError, Result};
 use crate::coconut::helpers::accepted_vote_err;
+use crate::coconut::log_context::{ctx_debug, Ceremony, DkgLogContext, LogContext};
 use crate::support::storage::NymApiStorage;
 use getset::{CopyGetters, Getters};
 use keypair::KeyPair;
 use nym_api_requests::coconut::{
-    BlindSignRequestBody, BlindedSignatureResponse, VerifyCredentialBody, VerifyCredentialResponse,
+    BlindSignRequestBody, BlindedSignatureResponse, DkgStatusResponse, IssuanceSuspension,
+    IssuanceSuspensionReason, ResharingDiagnosticsRequestBody, ResharingDiagnosticsResponse,
+    VerifyCredentialBody, VerifyCredentialResponse,
 };
 use nym_coconut_bandwidth_contract_common::spend_credential::{
     funds_from_cosmos_msgs, SpendCredentialStatus,
@@ -21,28 +26,36 @@ use nym_coconut_interface::{
     Attribute, BlindSignRequest, BlindedSignature, Parameters, VerificationKey,
 };
 use nym_config::defaults::NYM_API_VERSION;
+use nym_credentials::coconut::bandwidth::{current_unix_timestamp, has_expired};
 use nym_credentials::coconut::params::{
     NymApiCredentialEncryptionAlgorithm, NymApiCredentialHkdfAlgorithm,
 };
 use nym_crypto::asymmetric::encryption;
 use nym_crypto::shared_key::new_ephemeral_shared_key;
 use nym_crypto::symmetric::stream_cipher;
-use nym_validator_client::nym_api::routes::{BANDWIDTH, COCONUT_ROUTES};
+use nym_validator_client::nym_api::routes::{BANDWIDTH, COCONUT_ROUTES, DKG};
+use nym_validator_client::nyxd::tx::Hash;
 use nym_validator_client::nyxd::{Coin, Fee};
 use rand_07::rngs::OsRng;
 use rocket::fairing::AdHoc;
 use rocket::serde::json::Json;
 use rocket::State as RocketState;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OnceCell};
 
+pub(crate) mod circuit_breaker;
 pub(crate) mod client;
 pub(crate) mod comm;
 mod deposit;
+pub(crate) mod deposit_indexer;
 pub(crate) mod dkg;
+pub(crate) mod dummy_client;
 pub(crate) mod error;
 pub(crate) mod helpers;
 pub(crate) mod keypair;
+pub(crate) mod keystore;
+pub(crate) mod log_context;
+pub(crate) mod self_check;
 #[cfg(test)]
 pub(crate) mod tests;
 
@@ -52,6 +65,10 @@ pub struct State {
     key_pair: KeyPair,
     comm_channel: Arc<dyn APICommunicationChannel + Send + Sync>,
     storage: NymApiStorage,
+    dkg_context: DkgLogContext,
+    circuit_breaker: IssuanceCircuitBreaker,
+    // only available once the DKG task has been started
+    dkg_state: OnceCell<DkgState>,
     rng: Arc<Mutex<OsRng>>,
 }
 
@@ -62,6 +79,8 @@ impl State {
         key_pair: KeyPair,
         comm_channel: D,
         storage: NymApiStorage,
+        dkg_context: DkgLogContext,
+        circuit_breaker: IssuanceCircuitBreaker,
     ) -> Self
     where
         C: LocalClient + Send + Sync + 'static,
@@ -76,10 +95,23 @@ impl State {
             key_pair,
             comm_channel,
             storage,
+            dkg_context,
+            circuit_breaker,
+            dkg_state: OnceCell::new(),
             rng,
         }
     }
 
+    pub(crate) fn set_dkg_state(&self, dkg_state: DkgState) {
+        if self.dkg_state.set(dkg_state).is_err() {
+            warn!("The DKG state has already been set");
+        }
+    }
+
+    pub(crate) fn storage(&self) -> &NymApiStorage {
+        &self.storage
+    }
+
     pub async fn signed_before(&self, tx_hash: &str) -> Result<Option<BlindedSignatureResponse>> {
         let ret = self.storage.get_blinded_signature_response(tx_hash).await?;
         if let Some(blinded_signature_reponse) = ret {
@@ -172,18 +204,34 @@ impl InternalSignRequest {
         key_pair: KeyPair,
         comm_channel: D,
         storage: NymApiStorage,
+        dkg_context: DkgLogContext,
+        circuit_breaker: IssuanceCircuitBreaker,
     ) -> AdHoc
     where
         C: LocalClient + Send + Sync + 'static,
         D: APICommunicationChannel + Send + Sync + 'static,
     {
-        let state = State::new(client, mix_denom, key_pair, comm_channel, storage);
+        let state = State::new(
+            client,
+            mix_denom,
+            key_pair,
+            comm_channel,
+            storage,
+            dkg_context,
+            circuit_breaker,
+        );
         AdHoc::on_ignite("Internal Sign Request Stage", |rocket| async {
-            rocket.manage(state).mount(
-                // this format! is so ugly...
-                format!("/{}/{}/{}", NYM_API_VERSION, COCONUT_ROUTES, BANDWIDTH),
-                routes![post_blind_sign, verify_bandwidth_credential],
-            )
+            rocket
+                .manage(state)
+                .mount(
+                    // this format! is so ugly...
+                    format!("/{}/{}/{}", NYM_API_VERSION, COCONUT_ROUTES, BANDWIDTH),
+                    routes![post_blind_sign, verify_bandwidth_credential],
+                )
+                .mount(
+                    format!("/{}/{}/{}", NYM_API_VERSION, COCONUT_ROUTES, DKG),
+                    routes![post_resharing_diagnostics, get_dkg_status],
+                )
         })
     }
 }
@@ -204,18 +252,49 @@ pub async fn post_blind_sign(
     blind_sign_request_body: Json<BlindSignRequestBody>,
     state: &RocketState<State>,
 ) -> Result<Json<BlindedSignatureResponse>> {
-    debug!("{:?}", blind_sign_request_body);
+    let context = LogContext::new(
+        state.dkg_context.epoch_id(),
+        Ceremony::Issuance {
+            tx_hash: blind_sign_request_body.tx_hash().to_owned(),
+        },
+    );
+    context
+        .scope(handle_blind_sign(&blind_sign_request_body, state))
+        .await
+        .map(Json)
+}
+
+async fn handle_blind_sign(
+    blind_sign_request_body: &BlindSignRequestBody,
+    state: &State,
+) -> Result<BlindedSignatureResponse> {
+    ctx_debug!("{:?}", blind_sign_request_body);
+    if let Some(suspension) = state.circuit_breaker.suspension() {
+        return Err(CoconutError::IssuanceSuspended(suspension));
+    }
     if let Some(response) = state
         .signed_before(blind_sign_request_body.tx_hash())
         .await?
     {
-        return Ok(Json(response));
+        return Ok(response);
     }
-    let tx = state
-        .client
-        .get_tx(blind_sign_request_body.tx_hash())
-        .await?;
-    let encryption_key = extract_encryption_key(&blind_sign_request_body, tx).await?;
+    // deposits are indexed under the canonical form of their hashes
+    let tx_hash = blind_sign_request_body
+        .tx_hash()
+        .parse::<Hash>()
+        .map_err(|_| CoconutError::TxHashParseError)?
+        .to_string();
+    let encryption_key = match state.storage.get_bandwidth_deposit(&tx_hash).await? {
+        Some(deposit) => verify_indexed_deposit(blind_sign_request_body, &deposit)?,
+        None => {
+            // the deposit might have not been indexed yet
+            let tx = state
+                .client
+                .get_tx(blind_sign_request_body.tx_hash())
+                .await?;
+            extract_encryption_key(blind_sign_request_body, tx).await?
+        }
+    };
     let internal_request = InternalSignRequest::new(
         *blind_sign_request_body.total_params(),
         blind_sign_request_body.public_attributes(),
@@ -224,7 +303,10 @@ pub async fn post_blind_sign(
     let blinded_signature = if let Some(keypair) = state.key_pair.get().await.as_ref() {
         blind_sign(internal_request, keypair)?
     } else {
-        return Err(CoconutError::KeyPairNotDerivedYet);
+        return Err(CoconutError::IssuanceSuspended(IssuanceSuspension {
+            reason: IssuanceSuspensionReason::KeypairMissing,
+            details: "DKG has not finished yet in order to derive the coconut key".to_string(),
+        }));
     };
 
     let response = state
@@ -235,7 +317,7 @@ pub async fn post_blind_sign(
         )
         .await?;
 
-    Ok(Json(response))
+    Ok(response)
 }
 
 #[post("/verify-bandwidth-credential", data = "<verify_credential_body>")]
@@ -243,6 +325,25 @@ pub async fn verify_bandwidth_credential(
     verify_credential_body: Json<VerifyCredentialBody>,
     state: &RocketState<State>,
 ) -> Result<Json<VerifyCredentialResponse>> {
+    let context = LogContext::new(
+        Some(*verify_credential_body.credential().epoch_id()),
+        Ceremony::Verification {
+            proposal_id: *verify_credential_body.proposal_id(),
+        },
+    );
+    context
+        .scope(handle_credential_verification(
+            &verify_credential_body,
+            state,
+        ))
+        .await
+        .map(Json)
+}
+
+async fn handle_credential_verification(
+    verify_credential_body: &VerifyCredentialBody,
+    state: &State,
+) -> Result<VerifyCredentialResponse> {
     let proposal_id = *verify_credential_body.proposal_id();
     let proposal = state.client.get_proposal(proposal_id).await?;
     // Proposal description is the blinded serial number
@@ -280,6 +381,12 @@ pub async fn verify_bandwidth_credential(
         .credential()
         .verify(&verification_key);
 
+    // the credential must not be spent past its expiration date
+    vote_yes &= !has_expired(
+        verify_credential_body.credential().expiration_date(),
+        current_unix_timestamp(),
+    );
+
     vote_yes &= Coin::from(proposed_release_funds)
         == Coin::new(
             verify_credential_body.credential().voucher_value() as u128,
@@ -292,6 +399,7 @@ pub async fn verify_bandwidth_credential(
         .vote_proposal(
             proposal_id,
             vote_yes,
+            None,
             Some(Fee::new_payer_granter_auto(
                 None,
                 None,
@@ -301,5 +409,40 @@ pub async fn verify_bandwidth_credential(
         .await;
     accepted_vote_err(ret)?;
 
-    Ok(Json(VerifyCredentialResponse::new(vote_yes)))
+    ctx_debug!("voted {vote_yes} on the proposal {proposal_id}");
+
+    Ok(VerifyCredentialResponse::new(vote_yes))
+}
+
+/// Reports whether changing the signer group to the provided dealers would result in the current
+/// coconut keys being reshared or in completely new ones being generated, alongside the current
+/// key holders that would need to be online for the resharing to succeed.
+#[post("/resharing-diagnostics", data = "<request_body>")]
+pub async fn post_resharing_diagnostics(
+    request_body: Json<ResharingDiagnosticsRequestBody>,
+    state: &RocketState<State>,
+) -> Result<Json<ResharingDiagnosticsResponse>> {
+    let response = dkg::resharing::resharing_diagnostics(
+        state.client.as_ref(),
+        request_body.into_inner().new_dealers,
+    )
+    .await?;
+    Ok(Json(response))
+}
+
+/// Reports the DKG epoch this signer is currently taking part in alongside the id of its ceremony,
+/// i.e. the values its coconut logs are attributed to, and the progress it has made in it.
+#[get("/status")]
+pub async fn get_dkg_status(state: &RocketState<State>) -> Json<DkgStatusResponse> {
+    let context = state.dkg_context.get();
+    let progress = match state.dkg_state.get() {
+        Some(dkg_state) => Some(dkg_state.progress_report().await),
+        None => None,
+    };
+    Json(DkgStatusResponse {
+        epoch_id: context.as_ref().and_then(|context| context.epoch_id()),
+        ceremony_id: context.map(|context| context.ceremony_id()),
+        issuance_suspension: state.circuit_breaker.suspension(),
+        progress,
+    })
 }
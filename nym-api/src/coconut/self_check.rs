@@ -0,0 +1,416 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of the locally stored coconut signer keys against the state of the DKG contract,
+//! so that the operator can find out whether the nym-api is ready to issue credentials
+//! without having to wait for the first failed issuance.
+
+use crate::coconut::dkg::client::DkgClient;
+use crate::coconut::error::CoconutError;
+use crate::coconut::keystore::CoconutKeyStore;
+use crate::nyxd;
+use crate::support::config::Config;
+use nym_api_requests::coconut::{IssuanceSuspension, IssuanceSuspensionReason};
+use nym_coconut::{check_vk_pairing, Base58, Parameters, VerificationKey};
+use nym_coconut_dkg_common::types::{attributes_for_dealings, NodeIndex};
+use nym_coconut_interface::KeyPair as CoconutKeyPair;
+use nym_dkg::bte::decrypt_share;
+use nym_dkg::bte::keys::KeyPair as DkgKeyPair;
+use nym_dkg::Dealing;
+use nym_pemstore::KeyPairPath;
+use std::fmt::{self, Display, Formatter};
+
+/// Maximum number of dealings addressed to us that are going to be decrypted.
+const DEALINGS_SAMPLE_SIZE: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SelfCheck {
+    DkgKeyRegistration,
+    DealingsDecryption,
+    CoconutKeypair,
+    VerificationKeyShare,
+}
+
+impl SelfCheck {
+    /// The reason for suspending the issuance if this check has failed.
+    fn suspension_reason(&self) -> IssuanceSuspensionReason {
+        match self {
+            SelfCheck::DkgKeyRegistration => IssuanceSuspensionReason::DkgRegistrationMismatch,
+            SelfCheck::DealingsDecryption => IssuanceSuspensionReason::UndecryptableDealings,
+            SelfCheck::CoconutKeypair => IssuanceSuspensionReason::KeypairMissing,
+            SelfCheck::VerificationKeyShare => IssuanceSuspensionReason::VerificationKeyMismatch,
+        }
+    }
+}
+
+impl Display for SelfCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SelfCheck::DkgKeyRegistration => write!(f, "dkg key registration"),
+            SelfCheck::DealingsDecryption => write!(f, "dealings decryption"),
+            SelfCheck::CoconutKeypair => write!(f, "coconut keypair"),
+            SelfCheck::VerificationKeyShare => write!(f, "verification key share"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CheckOutcome {
+    Passed(String),
+    Failed(String),
+    Skipped(String),
+}
+
+impl CheckOutcome {
+    pub(crate) fn is_passed(&self) -> bool {
+        matches!(self, CheckOutcome::Passed(_))
+    }
+
+    pub(crate) fn is_failed(&self) -> bool {
+        matches!(self, CheckOutcome::Failed(_))
+    }
+}
+
+impl Display for CheckOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckOutcome::Passed(details) => write!(f, "PASSED - {details}"),
+            CheckOutcome::Failed(reason) => write!(f, "FAILED - {reason}"),
+            CheckOutcome::Skipped(reason) => write!(f, "SKIPPED - {reason}"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SelfCheckReport {
+    checks: Vec<(SelfCheck, CheckOutcome)>,
+}
+
+impl SelfCheckReport {
+    fn add(&mut self, check: SelfCheck, outcome: CheckOutcome) {
+        self.checks.push((check, outcome))
+    }
+
+    /// Determines whether the issuance has to be suspended, i.e. whether any of the checks has
+    /// explicitly failed. Skipped checks do not suspend the issuance as they only indicate
+    /// there was nothing to verify.
+    pub(crate) fn issuance_suspension(&self) -> Option<IssuanceSuspension> {
+        self.checks
+            .iter()
+            .find(|(_, outcome)| outcome.is_failed())
+            .map(|(check, outcome)| IssuanceSuspension {
+                reason: check.suspension_reason(),
+                details: format!("{check}: {outcome}"),
+            })
+    }
+
+    /// The signer is only ready for issuance if every single check has passed.
+    pub(crate) fn ready_for_issuance(&self) -> bool {
+        !self.checks.is_empty() && self.checks.iter().all(|(_, outcome)| outcome.is_passed())
+    }
+}
+
+impl Display for SelfCheckReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (name, outcome) in &self.checks {
+            writeln!(f, "{name}: {outcome}")?;
+        }
+        if self.ready_for_issuance() {
+            write!(f, "the coconut signer is ready for credential issuance")
+        } else {
+            write!(f, "the coconut signer is NOT ready for credential issuance")
+        }
+    }
+}
+
+/// Checks whether the verification key share recorded on chain corresponds to our secret key.
+pub(crate) fn check_verification_key(
+    params: &Parameters,
+    keypair: &CoconutKeyPair,
+    chain_share: &VerificationKey,
+) -> CheckOutcome {
+    let local_vk = keypair.verification_key();
+    if keypair.secret_key().verification_key(params) != local_vk {
+        return CheckOutcome::Failed(
+            "the stored verification key was not derived from the stored secret key".to_string(),
+        );
+    }
+
+    let dkg_values = local_vk
+        .beta_g2()
+        .iter()
+        .chain(std::iter::once(local_vk.alpha()))
+        .copied()
+        .collect::<Vec<_>>();
+    if check_vk_pairing(params, &dkg_values, chain_share) {
+        CheckOutcome::Passed("the on-chain share matches the stored keypair".to_string())
+    } else {
+        CheckOutcome::Failed(
+            "the on-chain share does not match the stored keypair (pairing check failed)"
+                .to_string(),
+        )
+    }
+}
+
+async fn check_dkg_key_registration(
+    dkg_client: &DkgClient,
+    dkg_keypair: &DkgKeyPair,
+) -> Result<(CheckOutcome, Option<NodeIndex>), CoconutError> {
+    let dealer_details = dkg_client.get_self_registered_dealer_details().await?;
+    let Some(details) = dealer_details.details else {
+        return Ok((
+            CheckOutcome::Failed("we are not registered as a dealer".to_string()),
+            None,
+        ));
+    };
+
+    let local_key = bs58::encode(&dkg_keypair.public_key().to_bytes()).into_string();
+    let outcome = if local_key == details.bte_public_key_with_proof {
+        CheckOutcome::Passed(format!(
+            "registered with node index {} as {:?} dealer",
+            details.assigned_index, dealer_details.dealer_type
+        ))
+    } else {
+        CheckOutcome::Failed(
+            "the registered DKG public key does not match the stored one".to_string(),
+        )
+    };
+    Ok((outcome, Some(details.assigned_index)))
+}
+
+async fn check_verification_key_share(
+    dkg_client: &DkgClient,
+    params: &Parameters,
+    keypair: &CoconutKeyPair,
+) -> Result<CheckOutcome, CoconutError> {
+    let epoch_id = dkg_client.get_current_epoch().await?.epoch_id;
+    let own_address = dkg_client.get_address().await.to_string();
+    let shares = dkg_client.get_verification_key_shares(epoch_id).await?;
+
+    let Some(share) = shares
+        .into_iter()
+        .find(|share| share.owner.as_str() == own_address)
+    else {
+        return Ok(CheckOutcome::Failed(format!(
+            "no verification key share has been submitted in epoch {epoch_id}"
+        )));
+    };
+    if !share.verified {
+        return Ok(CheckOutcome::Failed(format!(
+            "the verification key share submitted in epoch {epoch_id} hasn't been verified yet"
+        )));
+    }
+
+    match VerificationKey::try_from_bs58(&share.share) {
+        Ok(chain_share) => Ok(check_verification_key(params, keypair, &chain_share)),
+        Err(err) => Ok(CheckOutcome::Failed(format!(
+            "the on-chain share is malformed - {err}"
+        ))),
+    }
+}
+
+async fn check_dealings_decryption(
+    dkg_client: &DkgClient,
+    dkg_keypair: &DkgKeyPair,
+    node_index: NodeIndex,
+) -> Result<CheckOutcome, CoconutError> {
+    let mut receivers = dkg_client
+        .get_current_dealers()
+        .await?
+        .into_iter()
+        .map(|dealer| dealer.assigned_index)
+        .collect::<Vec<_>>();
+    receivers.sort_unstable();
+    let Some(receiver_index) = receivers.iter().position(|index| *index == node_index) else {
+        return Ok(CheckOutcome::Failed(format!(
+            "node index {node_index} is not among the current receivers"
+        )));
+    };
+
+    let dealings = dkg_client
+        .dealings_pager(0, None)
+        .next_page()
+        .await?
+        .unwrap_or_default();
+    if dealings.is_empty() {
+        return Ok(CheckOutcome::Skipped(
+            "no dealings have been submitted yet".to_string(),
+        ));
+    }
+
+    let mut decrypted = 0;
+    for contract_dealing in dealings.iter().take(DEALINGS_SAMPLE_SIZE) {
+        let dealing = match Dealing::try_from(&contract_dealing.dealing) {
+            Ok(dealing) => dealing,
+            Err(err) => {
+                warn!(
+                    "the dealing of {} is malformed - {err}",
+                    contract_dealing.dealer
+                );
+                continue;
+            }
+        };
+        match decrypt_share(
+            dkg_keypair.private_key(),
+            receiver_index,
+            &dealing.ciphertexts,
+            None,
+        ) {
+            Ok(_) => decrypted += 1,
+            Err(err) => {
+                return Ok(CheckOutcome::Failed(format!(
+                    "could not decrypt the share dealt by {} - {err}",
+                    contract_dealing.dealer
+                )))
+            }
+        }
+    }
+
+    if decrypted == 0 {
+        return Ok(CheckOutcome::Skipped(
+            "none of the sampled dealings could be parsed".to_string(),
+        ));
+    }
+    Ok(CheckOutcome::Passed(format!(
+        "decrypted {decrypted} sampled dealing(s)"
+    )))
+}
+
+/// Runs all the checks of the coconut signer keys. The failure of an individual check is recorded
+/// in the report, while errors are only returned if the chain couldn't be queried.
+pub(crate) async fn self_check(config: &Config) -> Result<SelfCheckReport, CoconutError> {
+    let dkg_client = DkgClient::new(nyxd::Client::new_signing(config));
+    let dkg_keypair_path = KeyPairPath::new(
+        config.decryption_key_path(),
+        config.public_key_with_proof_path(),
+    );
+    let coconut_keystore = CoconutKeyStore::new(config)?;
+    run_checks(&dkg_client, &dkg_keypair_path, &coconut_keystore).await
+}
+
+pub(crate) async fn run_checks(
+    dkg_client: &DkgClient,
+    dkg_keypair_path: &KeyPairPath,
+    coconut_keystore: &CoconutKeyStore,
+) -> Result<SelfCheckReport, CoconutError> {
+    let mut report = SelfCheckReport::default();
+    // the keys are derived from as many dealings as the contract demands, which is what
+    // determines the number of attributes they are able to sign
+    let total_dealings = dkg_client.get_total_dealings().await?;
+    let params = Parameters::new(attributes_for_dealings(total_dealings))?;
+
+    let dkg_keypair = nym_pemstore::load_keypair::<DkgKeyPair>(dkg_keypair_path);
+    let coconut_keypair = coconut_keystore.load();
+
+    match &dkg_keypair {
+        Ok(dkg_keypair) => {
+            let (outcome, node_index) = check_dkg_key_registration(dkg_client, dkg_keypair).await?;
+            report.add(SelfCheck::DkgKeyRegistration, outcome);
+            let outcome = match node_index {
+                Some(node_index) => {
+                    check_dealings_decryption(dkg_client, dkg_keypair, node_index).await?
+                }
+                None => CheckOutcome::Skipped("we are not registered as a dealer".to_string()),
+            };
+            report.add(SelfCheck::DealingsDecryption, outcome);
+        }
+        Err(err) => {
+            let reason = format!("could not load the DKG keypair - {err}");
+            report.add(
+                SelfCheck::DkgKeyRegistration,
+                CheckOutcome::Failed(reason.clone()),
+            );
+            report.add(SelfCheck::DealingsDecryption, CheckOutcome::Skipped(reason));
+        }
+    }
+
+    match &coconut_keypair {
+        Ok(keypair) => {
+            report.add(
+                SelfCheck::CoconutKeypair,
+                CheckOutcome::Passed("loaded the stored keypair".to_string()),
+            );
+            let outcome = check_verification_key_share(dkg_client, &params, keypair).await?;
+            report.add(SelfCheck::VerificationKeyShare, outcome);
+        }
+        Err(err) => {
+            let reason = format!("could not load the coconut keypair - {err}");
+            report.add(
+                SelfCheck::CoconutKeypair,
+                CheckOutcome::Failed(reason.clone()),
+            );
+            report.add(
+                SelfCheck::VerificationKeyShare,
+                CheckOutcome::Skipped(reason),
+            );
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nym_coconut::ttp_keygen;
+    use nym_credentials::coconut::bandwidth::TOTAL_ATTRIBUTES;
+
+    #[test]
+    fn verification_key_must_match_the_on_chain_share() {
+        let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
+        let mut keypairs = ttp_keygen(&params, 2, 2).unwrap();
+        let ours = keypairs.remove(0);
+        let theirs = keypairs.remove(0);
+
+        assert!(check_verification_key(&params, &ours, &ours.verification_key()).is_passed());
+        assert!(!check_verification_key(&params, &ours, &theirs.verification_key()).is_passed());
+
+        // the stored verification key doesn't correspond to the stored secret key
+        let mismatched = CoconutKeyPair::from_keys(ours.secret_key(), theirs.verification_key());
+        assert!(
+            !check_verification_key(&params, &mismatched, &theirs.verification_key()).is_passed()
+        );
+    }
+
+    #[test]
+    fn signer_is_only_ready_if_all_checks_passed() {
+        let mut report = SelfCheckReport::default();
+        assert!(!report.ready_for_issuance());
+
+        report.add(
+            SelfCheck::CoconutKeypair,
+            CheckOutcome::Passed("ok".to_string()),
+        );
+        assert!(report.ready_for_issuance());
+
+        report.add(
+            SelfCheck::DealingsDecryption,
+            CheckOutcome::Skipped("nothing to do".to_string()),
+        );
+        assert!(!report.ready_for_issuance());
+    }
+
+    #[test]
+    fn only_failed_checks_suspend_the_issuance() {
+        let mut report = SelfCheckReport::default();
+        report.add(
+            SelfCheck::CoconutKeypair,
+            CheckOutcome::Passed("ok".to_string()),
+        );
+        report.add(
+            SelfCheck::DealingsDecryption,
+            CheckOutcome::Skipped("nothing to do".to_string()),
+        );
+        assert!(report.issuance_suspension().is_none());
+
+        report.add(
+            SelfCheck::VerificationKeyShare,
+            CheckOutcome::Failed("pairing check failed".to_string()),
+        );
+        let suspension = report.issuance_suspension().unwrap();
+        assert_eq!(
+            suspension.reason,
+            IssuanceSuspensionReason::VerificationKeyMismatch
+        );
+    }
+}
@@ -5,7 +5,8 @@ use super::InternalSignRequest;
 use crate::coconut::error::{CoconutError, Result};
 use cosmwasm_std::{to_binary, Addr, CosmosMsg, Decimal, WasmMsg};
 use nym_api_requests::coconut::{
-    BlindSignRequestBody, BlindedSignatureResponse, VerifyCredentialBody, VerifyCredentialResponse,
+    BlindSignRequestBody, BlindedSignatureResponse, IssuanceSuspension, IssuanceSuspensionReason,
+    VerifyCredentialBody, VerifyCredentialResponse,
 };
 use nym_coconut::tests::helpers::theta_from_keys_and_attributes;
 use nym_coconut::{prepare_blind_sign, ttp_keygen, Base58, BlindedSignature, Parameters};
@@ -18,7 +19,9 @@ use nym_coconut_bandwidth_contract_common::spend_credential::{
 };
 use nym_coconut_interface::{hash_to_scalar, Credential, VerificationKey};
 use nym_config::defaults::VOUCHER_INFO;
-use nym_credentials::coconut::bandwidth::BandwidthVoucher;
+use nym_credentials::coconut::bandwidth::{
+    default_expiration_date, BandwidthVoucher, TOTAL_ATTRIBUTES,
+};
 use nym_credentials::coconut::params::{
     NymApiCredentialEncryptionAlgorithm, NymApiCredentialHkdfAlgorithm,
 };
@@ -28,28 +31,20 @@ use nym_validator_client::nym_api::routes::{
     API_VERSION, BANDWIDTH, COCONUT_BLIND_SIGN, COCONUT_ROUTES, COCONUT_VERIFY_BANDWIDTH_CREDENTIAL,
 };
 use nym_validator_client::nyxd::Coin;
-use nym_validator_client::nyxd::{tx::Hash, AccountId, DeliverTx, Event, Fee, Tag, TxResponse};
+use nym_validator_client::nyxd::{tx::Hash, AccountId, DeliverTx, Event, Tag, TxResponse};
 
+use crate::coconut::circuit_breaker::IssuanceCircuitBreaker;
+use crate::coconut::deposit_indexer::DepositIndexer;
+use crate::coconut::dummy_client::DummyClient;
+use crate::coconut::log_context::DkgLogContext;
 use crate::coconut::State;
+use crate::support::config::Config;
 use crate::support::storage::NymApiStorage;
 use async_trait::async_trait;
 use cw3::ProposalResponse;
-use cw4::MemberResponse;
-use nym_coconut_dkg_common::dealer::{
-    ContractDealing, DealerDetails, DealerDetailsResponse, DealerType,
-};
-use nym_coconut_dkg_common::event_attributes::{DKG_PROPOSAL_ID, NODE_INDEX};
-use nym_coconut_dkg_common::types::{
-    EncodedBTEPublicKeyWithProof, Epoch, EpochId, InitialReplacementData, TOTAL_DEALINGS,
-};
-use nym_coconut_dkg_common::verification_key::{ContractVKShare, VerificationKeyShare};
-use nym_contracts_common::dealings::ContractSafeBytes;
+use nym_coconut_dkg_common::types::EpochId;
 use nym_crypto::asymmetric::{encryption, identity};
-use nym_dkg::Threshold;
-use nym_validator_client::nyxd::cosmwasm_client::logs::Log;
-use nym_validator_client::nyxd::cosmwasm_client::types::ExecuteResult;
 use rand_07::rngs::OsRng;
-use rand_07::Rng;
 use rocket::http::Status;
 use rocket::local::asynchronous::Client;
 use std::collections::HashMap;
@@ -59,408 +54,6 @@ use std::sync::{Arc, RwLock};
 const TEST_COIN_DENOM: &str = "unym";
 const TEST_REWARDING_VALIDATOR_ADDRESS: &str = "n19lc9u84cz0yz3fww5283nucc9yvr8gsjmgeul0";
 
-#[derive(Clone, Debug)]
-pub(crate) struct DummyClient {
-    validator_address: AccountId,
-    tx_db: Arc<RwLock<HashMap<String, TxResponse>>>,
-    proposal_db: Arc<RwLock<HashMap<u64, ProposalResponse>>>,
-    spent_credential_db: Arc<RwLock<HashMap<String, SpendCredentialResponse>>>,
-
-    epoch: Arc<RwLock<Epoch>>,
-    dealer_details: Arc<RwLock<HashMap<String, (DealerDetails, bool)>>>,
-    threshold: Arc<RwLock<Option<Threshold>>>,
-    dealings: Arc<RwLock<HashMap<String, Vec<ContractSafeBytes>>>>,
-    verification_share: Arc<RwLock<HashMap<String, ContractVKShare>>>,
-    group_db: Arc<RwLock<HashMap<String, MemberResponse>>>,
-    initial_dealers_db: Arc<RwLock<Option<InitialReplacementData>>>,
-}
-
-impl DummyClient {
-    pub fn new(validator_address: AccountId) -> Self {
-        Self {
-            validator_address,
-            tx_db: Arc::new(RwLock::new(HashMap::new())),
-            proposal_db: Arc::new(RwLock::new(HashMap::new())),
-            spent_credential_db: Arc::new(RwLock::new(HashMap::new())),
-            epoch: Arc::new(RwLock::new(Epoch::default())),
-            dealer_details: Arc::new(RwLock::new(HashMap::new())),
-            threshold: Arc::new(RwLock::new(None)),
-            dealings: Arc::new(RwLock::new(HashMap::new())),
-            verification_share: Arc::new(RwLock::new(HashMap::new())),
-            group_db: Arc::new(RwLock::new(HashMap::new())),
-            initial_dealers_db: Arc::new(RwLock::new(None)),
-        }
-    }
-
-    pub fn with_tx_db(mut self, tx_db: &Arc<RwLock<HashMap<String, TxResponse>>>) -> Self {
-        self.tx_db = Arc::clone(tx_db);
-        self
-    }
-
-    pub fn with_proposal_db(
-        mut self,
-        proposal_db: &Arc<RwLock<HashMap<u64, ProposalResponse>>>,
-    ) -> Self {
-        self.proposal_db = Arc::clone(proposal_db);
-        self
-    }
-
-    pub fn with_spent_credential_db(
-        mut self,
-        spent_credential_db: &Arc<RwLock<HashMap<String, SpendCredentialResponse>>>,
-    ) -> Self {
-        self.spent_credential_db = Arc::clone(spent_credential_db);
-        self
-    }
-
-    pub fn _with_epoch(mut self, epoch: &Arc<RwLock<Epoch>>) -> Self {
-        self.epoch = Arc::clone(epoch);
-        self
-    }
-
-    pub fn with_dealer_details(
-        mut self,
-        dealer_details: &Arc<RwLock<HashMap<String, (DealerDetails, bool)>>>,
-    ) -> Self {
-        self.dealer_details = Arc::clone(dealer_details);
-        self
-    }
-
-    pub fn with_threshold(mut self, threshold: &Arc<RwLock<Option<Threshold>>>) -> Self {
-        self.threshold = Arc::clone(threshold);
-        self
-    }
-
-    pub fn with_dealings(
-        mut self,
-        dealings: &Arc<RwLock<HashMap<String, Vec<ContractSafeBytes>>>>,
-    ) -> Self {
-        self.dealings = Arc::clone(dealings);
-        self
-    }
-
-    pub fn with_verification_share(
-        mut self,
-        verification_share: &Arc<RwLock<HashMap<String, ContractVKShare>>>,
-    ) -> Self {
-        self.verification_share = Arc::clone(verification_share);
-        self
-    }
-
-    pub fn _with_group_db(
-        mut self,
-        group_db: &Arc<RwLock<HashMap<String, MemberResponse>>>,
-    ) -> Self {
-        self.group_db = Arc::clone(group_db);
-        self
-    }
-
-    pub fn with_initial_dealers_db(
-        mut self,
-        initial_dealers: &Arc<RwLock<Option<InitialReplacementData>>>,
-    ) -> Self {
-        self.initial_dealers_db = Arc::clone(initial_dealers);
-        self
-    }
-}
-
-#[async_trait]
-impl super::client::Client for DummyClient {
-    async fn address(&self) -> AccountId {
-        self.validator_address.clone()
-    }
-
-    async fn get_tx(&self, tx_hash: &str) -> Result<TxResponse> {
-        self.tx_db
-            .read()
-            .unwrap()
-            .get(tx_hash)
-            .cloned()
-            .ok_or(CoconutError::TxHashParseError)
-    }
-
-    async fn get_proposal(&self, proposal_id: u64) -> Result<ProposalResponse> {
-        self.proposal_db
-            .read()
-            .unwrap()
-            .get(&proposal_id)
-            .cloned()
-            .ok_or(CoconutError::IncorrectProposal {
-                reason: String::from("proposal not found"),
-            })
-    }
-
-    async fn list_proposals(&self) -> Result<Vec<ProposalResponse>> {
-        Ok(self.proposal_db.read().unwrap().values().cloned().collect())
-    }
-
-    async fn get_spent_credential(
-        &self,
-        blinded_serial_number: String,
-    ) -> Result<SpendCredentialResponse> {
-        self.spent_credential_db
-            .read()
-            .unwrap()
-            .get(&blinded_serial_number)
-            .cloned()
-            .ok_or(CoconutError::InvalidCredentialStatus {
-                status: String::from("spent credential not found"),
-            })
-    }
-
-    async fn get_current_epoch(&self) -> Result<Epoch> {
-        Ok(*self.epoch.read().unwrap())
-    }
-
-    async fn group_member(&self, addr: String) -> Result<MemberResponse> {
-        Ok(self
-            .group_db
-            .read()
-            .unwrap()
-            .get(&addr)
-            .cloned()
-            .unwrap_or(MemberResponse { weight: None }))
-    }
-
-    async fn get_current_epoch_threshold(&self) -> Result<Option<Threshold>> {
-        Ok(*self.threshold.read().unwrap())
-    }
-
-    async fn get_initial_dealers(&self) -> Result<Option<InitialReplacementData>> {
-        Ok(self.initial_dealers_db.read().unwrap().clone())
-    }
-
-    async fn get_self_registered_dealer_details(&self) -> Result<DealerDetailsResponse> {
-        let (details, dealer_type) = if let Some((details, current)) = self
-            .dealer_details
-            .read()
-            .unwrap()
-            .get(self.validator_address.as_ref())
-            .cloned()
-        {
-            let dealer_type = if current {
-                DealerType::Current
-            } else {
-                DealerType::Past
-            };
-            (Some(details), dealer_type)
-        } else {
-            (None, DealerType::Unknown)
-        };
-        Ok(DealerDetailsResponse {
-            details,
-            dealer_type,
-        })
-    }
-
-    async fn get_current_dealers(&self) -> Result<Vec<DealerDetails>> {
-        Ok(self
-            .dealer_details
-            .read()
-            .unwrap()
-            .values()
-            .cloned()
-            .filter_map(|(d, current)| if current { Some(d) } else { None })
-            .collect())
-    }
-
-    async fn get_dealings(&self, idx: usize) -> Result<Vec<ContractDealing>> {
-        Ok(self
-            .dealings
-            .read()
-            .unwrap()
-            .iter()
-            .map(|(dealer, dealings)| ContractDealing {
-                dealing: dealings.get(idx).unwrap().clone(),
-                dealer: Addr::unchecked(dealer),
-            })
-            .collect())
-    }
-
-    async fn get_verification_key_shares(
-        &self,
-        _epoch_id: EpochId,
-    ) -> Result<Vec<ContractVKShare>> {
-        Ok(self
-            .verification_share
-            .read()
-            .unwrap()
-            .values()
-            .cloned()
-            .collect())
-    }
-
-    async fn vote_proposal(
-        &self,
-        proposal_id: u64,
-        vote_yes: bool,
-        _fee: Option<Fee>,
-    ) -> Result<()> {
-        if let Some(proposal) = self.proposal_db.write().unwrap().get_mut(&proposal_id) {
-            // for now, just suppose that every vote is honest
-            if !vote_yes {
-                proposal.status = cw3::Status::Rejected;
-            } else if vote_yes && proposal.status == cw3::Status::Open {
-                proposal.status = cw3::Status::Passed;
-            }
-        }
-        Ok(())
-    }
-
-    async fn execute_proposal(&self, proposal_id: u64) -> Result<()> {
-        self.proposal_db
-            .write()
-            .unwrap()
-            .entry(proposal_id)
-            .and_modify(|prop| {
-                if prop.status == cw3::Status::Passed {
-                    prop.status = cw3::Status::Executed
-                }
-            });
-        Ok(())
-    }
-
-    async fn advance_epoch_state(&self) -> Result<()> {
-        todo!()
-    }
-
-    async fn register_dealer(
-        &self,
-        bte_public_key_with_proof: EncodedBTEPublicKeyWithProof,
-        announce_address: String,
-        _resharing: bool,
-    ) -> Result<ExecuteResult> {
-        let mut dealer_details = self.dealer_details.write().unwrap();
-        let assigned_index = if let Some((details, active)) =
-            dealer_details.get_mut(self.validator_address.as_ref())
-        {
-            *active = true;
-            details.assigned_index
-        } else {
-            // let assigned_index = OsRng.gen();
-            let assigned_index = dealer_details
-                .values()
-                .map(|(d, _)| d.assigned_index)
-                .max()
-                .unwrap_or(0)
-                + 1;
-            dealer_details.insert(
-                self.validator_address.to_string(),
-                (
-                    DealerDetails {
-                        address: Addr::unchecked(self.validator_address.to_string()),
-                        bte_public_key_with_proof,
-                        announce_address,
-                        assigned_index,
-                    },
-                    true,
-                ),
-            );
-            assigned_index
-        };
-        Ok(ExecuteResult {
-            logs: vec![Log {
-                msg_index: 0,
-                events: vec![cosmwasm_std::Event::new("wasm")
-                    .add_attribute(NODE_INDEX, assigned_index.to_string())],
-            }],
-            data: Default::default(),
-            transaction_hash: Hash::new([0; 32]),
-            gas_info: Default::default(),
-        })
-    }
-
-    async fn submit_dealing(
-        &self,
-        dealing_bytes: ContractSafeBytes,
-        _resharing: bool,
-    ) -> Result<ExecuteResult> {
-        self.dealings
-            .write()
-            .unwrap()
-            .entry(self.validator_address.to_string())
-            .and_modify(|v| {
-                if v.len() < TOTAL_DEALINGS {
-                    v.push(dealing_bytes.clone())
-                }
-            })
-            .or_insert_with(|| vec![dealing_bytes]);
-
-        Ok(ExecuteResult {
-            logs: vec![],
-            data: Default::default(),
-            transaction_hash: Hash::new([0; 32]),
-            gas_info: Default::default(),
-        })
-    }
-
-    async fn submit_verification_key_share(
-        &self,
-        share: VerificationKeyShare,
-        resharing: bool,
-    ) -> Result<ExecuteResult> {
-        let (dealer_details, active) = self
-            .dealer_details
-            .read()
-            .unwrap()
-            .get(self.validator_address.as_ref())
-            .unwrap()
-            .clone();
-        if !active {
-            // Just throw some error, not really the correct one
-            return Err(CoconutError::DepositEncrKeyNotFound);
-        }
-        self.verification_share.write().unwrap().insert(
-            self.validator_address.to_string(),
-            ContractVKShare {
-                share,
-                announce_address: dealer_details.announce_address.clone(),
-                node_index: dealer_details.assigned_index,
-                owner: Addr::unchecked(self.validator_address.to_string()),
-                epoch_id: 0,
-                verified: false,
-            },
-        );
-        let proposal_id = OsRng.gen();
-        let verify_vk_share_req =
-            nym_coconut_dkg_common::msg::ExecuteMsg::VerifyVerificationKeyShare {
-                owner: Addr::unchecked(self.validator_address.as_ref()),
-                resharing,
-            };
-        let verify_vk_share_msg = CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: String::new(),
-            msg: to_binary(&verify_vk_share_req).unwrap(),
-            funds: vec![],
-        });
-        let proposal = ProposalResponse {
-            id: proposal_id,
-            title: String::new(),
-            description: String::new(),
-            msgs: vec![verify_vk_share_msg],
-            status: cw3::Status::Open,
-            expires: cw_utils::Expiration::Never {},
-            threshold: cw_utils::ThresholdResponse::AbsolutePercentage {
-                percentage: Decimal::from_ratio(2u32, 3u32),
-                total_weight: 100,
-            },
-        };
-        self.proposal_db
-            .write()
-            .unwrap()
-            .insert(proposal_id, proposal);
-        Ok(ExecuteResult {
-            logs: vec![Log {
-                msg_index: 0,
-                events: vec![cosmwasm_std::Event::new("wasm")
-                    .add_attribute(DKG_PROPOSAL_ID, proposal_id.to_string())],
-            }],
-            data: Default::default(),
-            transaction_hash: Hash::new([0; 32]),
-            gas_info: Default::default(),
-        })
-    }
-}
-
 #[derive(Clone, Debug)]
 pub struct DummyCommunicationChannel {
     aggregated_verification_key: VerificationKey,
@@ -510,12 +103,13 @@ async fn signed_before() {
         "2DHbEZ6pzToGpsAXJrqJi7Wj1pAXeT18283q2YEEyNH5gTymwRozWBdja6SMAVt1dyYmUnM4ZNhsJ4wxZyGh4Z6J",
     );
 
-    let params = Parameters::new(4).unwrap();
+    let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
     let mut rng = OsRng;
     let voucher = BandwidthVoucher::new(
         &params,
         "1234".to_string(),
         VOUCHER_INFO.to_string(),
+        default_expiration_date(),
         tx_hash,
         identity::PrivateKey::from_base58_string(
             identity::KeyPair::new(&mut rng)
@@ -557,6 +151,8 @@ async fn signed_before() {
         staged_key_pair,
         comm_channel,
         storage.clone(),
+        DkgLogContext::default(),
+        IssuanceCircuitBreaker::default(),
     ));
     let client = Client::tracked(rocket)
         .await
@@ -568,7 +164,7 @@ async fn signed_before() {
         signature.clone(),
         &voucher.get_public_attributes(),
         voucher.get_public_attributes_plain(),
-        4,
+        TOTAL_ATTRIBUTES,
     );
 
     let encrypted_signature = vec![1, 2, 3, 4];
@@ -624,6 +220,8 @@ async fn state_functions() {
         staged_key_pair,
         comm_channel,
         storage.clone(),
+        DkgLogContext::default(),
+        IssuanceCircuitBreaker::default(),
     );
 
     let tx_hash = String::from("6B27412050B823E58BB38447D7870BBC8CBE3C51C905BEA89D459ACCDA80A00E");
@@ -725,7 +323,7 @@ async fn blind_sign_correct() {
     let tx_hash =
         Hash::from_str("7C41AF8266D91DE55E1C8F4712E6A952A165ED3D8C27C7B00428CBD0DE00A52B").unwrap();
 
-    let params = Parameters::new(4).unwrap();
+    let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
     let mut rng = OsRng;
     let identity_keypair = identity::KeyPair::new(&mut rng);
     let encryption_keypair = encryption::KeyPair::new(&mut rng);
@@ -733,6 +331,7 @@ async fn blind_sign_correct() {
         &params,
         "1234".to_string(),
         VOUCHER_INFO.to_string(),
+        default_expiration_date(),
         tx_hash,
         identity::PrivateKey::from_base58_string(identity_keypair.private_key().to_base58_string())
             .unwrap(),
@@ -793,6 +392,8 @@ async fn blind_sign_correct() {
         staged_key_pair,
         comm_channel,
         storage.clone(),
+        DkgLogContext::default(),
+        IssuanceCircuitBreaker::default(),
     ));
     let client = Client::tracked(rocket)
         .await
@@ -806,7 +407,7 @@ async fn blind_sign_correct() {
             .to_base58_string(),
         &voucher.get_public_attributes(),
         voucher.get_public_attributes_plain(),
-        4,
+        TOTAL_ATTRIBUTES,
     );
 
     let response = client
@@ -825,6 +426,216 @@ async fn blind_sign_correct() {
     assert!(blinded_signature_response.is_ok());
 }
 
+#[tokio::test]
+async fn blind_sign_with_indexed_deposit() {
+    let tx_hash =
+        Hash::from_str("7C41AF8266D91DE55E1C8F4712E6A952A165ED3D8C27C7B00428CBD0DE00A52B").unwrap();
+
+    let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
+    let mut rng = OsRng;
+    let identity_keypair = identity::KeyPair::new(&mut rng);
+    let encryption_keypair = encryption::KeyPair::new(&mut rng);
+    let voucher = BandwidthVoucher::new(
+        &params,
+        "1234".to_string(),
+        VOUCHER_INFO.to_string(),
+        default_expiration_date(),
+        tx_hash,
+        identity::PrivateKey::from_base58_string(identity_keypair.private_key().to_base58_string())
+            .unwrap(),
+        encryption::PrivateKey::from_bytes(&encryption_keypair.private_key().to_bytes()).unwrap(),
+    );
+
+    let key_pair = ttp_keygen(&params, 1, 1).unwrap().remove(0);
+    let mut db_dir = std::env::temp_dir();
+    db_dir.push(&key_pair.verification_key().to_bs58()[..8]);
+    let storage = NymApiStorage::init(db_dir).await.unwrap();
+    let tx_db = Arc::new(RwLock::new(HashMap::new()));
+    let indexer = DepositIndexer::new(
+        &Config::new(),
+        DummyClient::new(AccountId::from_str(TEST_REWARDING_VALIDATOR_ADDRESS).unwrap())
+            .with_tx_db(&tx_db),
+        storage.clone(),
+    );
+
+    // the first run only marks the point from which the deposits are going to be indexed
+    indexer.index_new_deposits().await.unwrap();
+    assert_eq!(
+        storage.get_last_indexed_deposit_height().await.unwrap(),
+        Some(0)
+    );
+
+    let mut tx_entry = tx_entry_fixture(&tx_hash.to_string());
+    tx_entry.height = 42u32.into();
+    tx_entry.tx_result.events.push(Event {
+        type_str: format!("wasm-{}", DEPOSITED_FUNDS_EVENT_TYPE),
+        attributes: vec![
+            Tag {
+                key: DEPOSIT_VALUE.parse().unwrap(),
+                value: "1234".parse().unwrap(),
+            },
+            Tag {
+                key: DEPOSIT_INFO.parse().unwrap(),
+                value: VOUCHER_INFO.parse().unwrap(),
+            },
+            Tag {
+                key: DEPOSIT_IDENTITY_KEY.parse().unwrap(),
+                value: identity_keypair
+                    .public_key()
+                    .to_base58_string()
+                    .parse()
+                    .unwrap(),
+            },
+            Tag {
+                key: DEPOSIT_ENCRYPTION_KEY.parse().unwrap(),
+                value: encryption_keypair
+                    .public_key()
+                    .to_base58_string()
+                    .parse()
+                    .unwrap(),
+            },
+        ],
+    });
+    tx_db.write().unwrap().insert(tx_hash.to_string(), tx_entry);
+
+    indexer.index_new_deposits().await.unwrap();
+    assert_eq!(
+        storage.get_last_indexed_deposit_height().await.unwrap(),
+        Some(42)
+    );
+    let deposit = storage
+        .get_bandwidth_deposit(&tx_hash.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(deposit.height, 42);
+    assert_eq!(deposit.deposit_value, "1234");
+
+    // the signer is not aware of the deposit transaction itself anymore,
+    // so the request can only be validated against the index
+    let nyxd_client =
+        DummyClient::new(AccountId::from_str(TEST_REWARDING_VALIDATOR_ADDRESS).unwrap());
+    let comm_channel = DummyCommunicationChannel::new(key_pair.verification_key());
+    let staged_key_pair = crate::coconut::KeyPair::new();
+    staged_key_pair.set(Some(key_pair)).await;
+
+    let rocket = rocket::build().attach(InternalSignRequest::stage(
+        nyxd_client,
+        TEST_COIN_DENOM.to_string(),
+        staged_key_pair,
+        comm_channel,
+        storage.clone(),
+        DkgLogContext::default(),
+        IssuanceCircuitBreaker::default(),
+    ));
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let request_body = BlindSignRequestBody::new(
+        voucher.blind_sign_request(),
+        tx_hash.to_string(),
+        voucher
+            .sign(voucher.blind_sign_request())
+            .to_base58_string(),
+        &voucher.get_public_attributes(),
+        voucher.get_public_attributes_plain(),
+        TOTAL_ATTRIBUTES,
+    );
+
+    let response = client
+        .post(format!(
+            "/{}/{}/{}/{}",
+            API_VERSION, COCONUT_ROUTES, BANDWIDTH, COCONUT_BLIND_SIGN
+        ))
+        .json(&request_body)
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+}
+
+#[tokio::test]
+async fn blind_sign_while_suspended() {
+    let tx_hash =
+        Hash::from_str("0E5B8C47BB8F8B8A9D0B4F1E7B77D5DC1CA76C9A0A8D2B7E8F16C5A3E2D1B0A9").unwrap();
+
+    let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
+    let mut rng = OsRng;
+    let voucher = BandwidthVoucher::new(
+        &params,
+        "1234".to_string(),
+        VOUCHER_INFO.to_string(),
+        default_expiration_date(),
+        tx_hash,
+        identity::PrivateKey::from_base58_string(
+            identity::KeyPair::new(&mut rng)
+                .private_key()
+                .to_base58_string(),
+        )
+        .unwrap(),
+        encryption::PrivateKey::from_bytes(
+            &encryption::KeyPair::new(&mut rng).private_key().to_bytes(),
+        )
+        .unwrap(),
+    );
+
+    let key_pair = ttp_keygen(&params, 1, 1).unwrap().remove(0);
+    let mut db_dir = std::env::temp_dir();
+    db_dir.push(&key_pair.verification_key().to_bs58()[..8]);
+    let storage = NymApiStorage::init(db_dir).await.unwrap();
+    let nyxd_client =
+        DummyClient::new(AccountId::from_str(TEST_REWARDING_VALIDATOR_ADDRESS).unwrap());
+    let comm_channel = DummyCommunicationChannel::new(key_pair.verification_key());
+    let staged_key_pair = crate::coconut::KeyPair::new();
+    staged_key_pair.set(Some(key_pair)).await;
+
+    let circuit_breaker = IssuanceCircuitBreaker::default();
+    let suspension = IssuanceSuspension {
+        reason: IssuanceSuspensionReason::VerificationKeyMismatch,
+        details: "the on-chain share does not match the stored keypair".to_string(),
+    };
+    circuit_breaker.update(Some(suspension.clone()));
+
+    let rocket = rocket::build().attach(InternalSignRequest::stage(
+        nyxd_client,
+        TEST_COIN_DENOM.to_string(),
+        staged_key_pair,
+        comm_channel,
+        storage,
+        DkgLogContext::default(),
+        circuit_breaker.clone(),
+    ));
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let request_body = BlindSignRequestBody::new(
+        voucher.blind_sign_request(),
+        tx_hash.to_string(),
+        voucher
+            .sign(voucher.blind_sign_request())
+            .to_base58_string(),
+        &voucher.get_public_attributes(),
+        voucher.get_public_attributes_plain(),
+        TOTAL_ATTRIBUTES,
+    );
+    let route = format!(
+        "/{}/{}/{}/{}",
+        API_VERSION, COCONUT_ROUTES, BANDWIDTH, COCONUT_BLIND_SIGN
+    );
+
+    let response = client.post(&route).json(&request_body).dispatch().await;
+    assert_eq!(response.status(), Status::ServiceUnavailable);
+    let returned_suspension =
+        serde_json::from_str::<IssuanceSuspension>(&response.into_string().await.unwrap()).unwrap();
+    assert_eq!(returned_suspension, suspension);
+
+    // once the state is healthy again, the requests are no longer rejected because of the suspension
+    circuit_breaker.update(None);
+    let response = client.post(&route).json(&request_body).dispatch().await;
+    assert_ne!(response.status(), Status::ServiceUnavailable);
+}
+
 #[tokio::test]
 async fn verification_of_bandwidth_credential() {
     // Setup variables
@@ -835,13 +646,15 @@ async fn verification_of_bandwidth_credential() {
         .with_proposal_db(&proposal_db)
         .with_spent_credential_db(&spent_credential_db);
     let mut db_dir = std::env::temp_dir();
-    let params = Parameters::new(4).unwrap();
+    let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
     let mut key_pairs = ttp_keygen(&params, 1, 1).unwrap();
     let voucher_value = 1234u64;
     let voucher_info = "voucher info";
+    let expiration_date = default_expiration_date();
     let public_attributes = vec![
         hash_to_scalar(voucher_value.to_string()),
         hash_to_scalar(voucher_info),
+        hash_to_scalar(expiration_date.to_string()),
     ];
     let indices: Vec<u64> = key_pairs
         .iter()
@@ -862,13 +675,22 @@ async fn verification_of_bandwidth_credential() {
         staged_key_pair,
         comm_channel.clone(),
         storage1.clone(),
+        DkgLogContext::default(),
+        IssuanceCircuitBreaker::default(),
     ));
 
     let client = Client::tracked(rocket)
         .await
         .expect("valid rocket instance");
 
-    let credential = Credential::new(4, theta.clone(), voucher_value, voucher_info.to_string(), 0);
+    let credential = Credential::new(
+        TOTAL_ATTRIBUTES,
+        theta.clone(),
+        voucher_value,
+        voucher_info.to_string(),
+        0,
+        Some(expiration_date),
+    );
     let proposal_id = 42;
     // The address is not used, so we can use a duplicate
     let gateway_cosmos_addr = validator_address.clone();
@@ -1015,11 +837,12 @@ async fn verification_of_bandwidth_credential() {
         SpendCredentialResponse::new(Some(spent_credential.clone())),
     );
     let bad_credential = Credential::new(
-        4,
+        TOTAL_ATTRIBUTES,
         theta.clone(),
         voucher_value,
         String::from("bad voucher info"),
         0,
+        Some(expiration_date),
     );
     let bad_req =
         VerifyCredentialBody::new(bad_credential, proposal_id, gateway_cosmos_addr.clone());
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::epoch_operations::RewardedSetUpdater;
+use crate::support::storage::NymApiStorage;
 use cosmwasm_std::{Decimal, Fraction};
 use nym_mixnet_contract_common::reward_params::Performance;
 use nym_mixnet_contract_common::{ExecuteMsg, Interval, MixId};
@@ -36,24 +37,31 @@ pub(super) fn stake_to_f64(stake: Decimal) -> f64 {
     }
 }
 
+/// Performance of the mixnode that is going to be used for rewarding it at the end of the provided epoch.
+pub(crate) async fn epoch_performance(
+    storage: &NymApiStorage,
+    interval: &Interval,
+    mix_id: MixId,
+) -> Performance {
+    storage
+        .get_average_mixnode_uptime_in_the_last_24hrs(
+            mix_id,
+            interval.current_epoch_end_unix_timestamp(),
+        )
+        .await
+        .unwrap_or_default()
+        .into()
+}
+
 impl RewardedSetUpdater {
     pub(crate) async fn load_performance(
         &self,
         interval: &Interval,
         mix_id: MixId,
     ) -> MixnodeWithPerformance {
-        let uptime = self
-            .storage
-            .get_average_mixnode_uptime_in_the_last_24hrs(
-                mix_id,
-                interval.current_epoch_end_unix_timestamp(),
-            )
-            .await
-            .unwrap_or_default();
-
         MixnodeWithPerformance {
             mix_id,
-            performance: uptime.into(),
+            performance: epoch_performance(&self.storage, interval, mix_id).await,
         }
     }
 
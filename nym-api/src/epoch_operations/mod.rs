@@ -16,12 +16,15 @@ use crate::node_status_api::ONE_DAY;
 use crate::nym_contract_cache::cache::NymContractCache;
 use crate::support::nyxd::Client;
 use crate::support::storage::NymApiStorage;
+use crate::support::supervisor::{TaskFailure, TaskSupervisor};
 use error::RewardingError;
-pub(crate) use helpers::MixnodeWithPerformance;
+pub(crate) use helpers::{epoch_performance, MixnodeWithPerformance};
 use nym_mixnet_contract_common::{CurrentIntervalResponse, Interval};
 use nym_task::{TaskClient, TaskManager};
 use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Mutex;
 use tokio::time::sleep;
 
 pub(crate) mod error;
@@ -238,6 +241,8 @@ impl RewardedSetUpdater {
     }
 
     pub(crate) async fn run(&mut self, mut shutdown: TaskClient) -> Result<(), RewardingError> {
+        // the permission might have been revoked since the last time the updater has been started
+        ensure_rewarding_permission(&self.nyxd_client).await?;
         self.nym_contract_cache.wait_for_initial_values().await;
 
         while !shutdown.is_shutdown() {
@@ -263,15 +268,27 @@ impl RewardedSetUpdater {
         nyxd_client: Client,
         nym_contract_cache: &NymContractCache,
         storage: &NymApiStorage,
+        supervisor: &TaskSupervisor,
         shutdown: &TaskManager,
     ) {
-        let mut rewarded_set_updater = RewardedSetUpdater::new(
+        let rewarded_set_updater = Arc::new(Mutex::new(RewardedSetUpdater::new(
             nyxd_client,
             nym_contract_cache.to_owned(),
             storage.to_owned(),
-        );
-        let shutdown_listener = shutdown.subscribe();
-        tokio::spawn(async move { rewarded_set_updater.run(shutdown_listener).await });
+        )));
+        supervisor.spawn("rewarded-set-updater", shutdown, move |shutdown| {
+            let rewarded_set_updater = Arc::clone(&rewarded_set_updater);
+            async move {
+                match rewarded_set_updater.lock().await.run(shutdown).await {
+                    Ok(()) => Ok(()),
+                    // no amount of restarts is going to change the configured account
+                    Err(err @ RewardingError::Unauthorised { .. }) => {
+                        Err(TaskFailure::Unrecoverable(err.into()))
+                    }
+                    Err(err) => Err(TaskFailure::Recoverable(err.into())),
+                }
+            }
+        });
     }
 }
 
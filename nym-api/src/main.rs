@@ -11,14 +11,21 @@ use crate::support::cli::CliArgs;
 use crate::support::config::Config;
 use crate::support::storage;
 use crate::support::storage::NymApiStorage;
+use crate::support::supervisor::TaskSupervisor;
 use ::nym_config::defaults::setup_env;
 use anyhow::Result;
 use circulating_supply_api::cache::CirculatingSupplyCache;
 use clap::Parser;
+use coconut::circuit_breaker::{IssuanceCircuitBreaker, IssuanceHealthMonitor};
+use coconut::deposit_indexer::DepositIndexer;
 use coconut::dkg::controller::DkgController;
+use coconut::keystore::CoconutKeyStore;
+use coconut::log_context::DkgLogContext;
 use log::info;
+use node_status_api::heartbeats::{HeartbeatMonitor, HeartbeatStore};
 use node_status_api::NodeStatusCache;
 use nym_bin_common::logging::setup_logging;
+use nym_config::paths::migrate_legacy_nym_directory;
 use nym_config::NymConfig;
 use nym_contract_cache::cache::NymContractCache;
 use nym_sphinx::receiver::SphinxMessageReceiver;
@@ -44,14 +51,15 @@ struct ShutdownHandles {
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     println!("Starting nym api...");
 
-    cfg_if::cfg_if! {if #[cfg(feature = "console-subscriber")] {
+    cfg_if::cfg_if! {if #[cfg(feature = "tokio-console")] {
         // instrument tokio console subscriber needs RUSTFLAGS="--cfg tokio_unstable" at build time
-        console_subscriber::init();
+        nym_bin_common::logging::setup_tokio_console();
     }}
 
     setup_logging();
     let args = cli::CliArgs::parse();
     setup_env(args.config_env_file.as_ref());
+    migrate_legacy_nym_directory("nym-api");
     run_nym_api(args).await
 }
 
@@ -60,10 +68,30 @@ async fn start_nym_api_tasks(
 ) -> Result<ShutdownHandles, Box<dyn Error + Send + Sync>> {
     let system_version = clap::crate_version!();
 
-    let nyxd_client = nyxd::Client::new(&config);
+    // transactions (such as DKG submissions) are time-sensitive and so they use a dedicated client,
+    // separate from the one used for the heavy query traffic of the cache refreshers
+    let nyxd_client = nyxd::Client::new_signing(&config);
+    let nyxd_query_client = nyxd::Client::new_query(&config);
+
+    if let Some(profile) = config.get_network_profile() {
+        for client in [&nyxd_client, &nyxd_query_client] {
+            let chain_id = client.chain_id().await?;
+            if chain_id != profile.chain_id {
+                return Err(format!(
+                    "the nyxd validator is running the '{chain_id}' chain while the selected network profile expects '{}'",
+                    profile.chain_id
+                )
+                .into());
+            }
+        }
+    }
+
     let mix_denom = nyxd_client.chain_details().await.mix_denom.base;
 
     let coconut_keypair = coconut::keypair::KeyPair::new();
+    let dkg_context = DkgLogContext::default();
+    let circuit_breaker = IssuanceCircuitBreaker::default();
+    let supervisor = TaskSupervisor::new();
 
     // let's build our rocket!
     let rocket = http::setup_rocket(
@@ -71,6 +99,9 @@ async fn start_nym_api_tasks(
         mix_denom,
         nyxd_client.clone(),
         coconut_keypair.clone(),
+        dkg_context.clone(),
+        circuit_breaker.clone(),
+        supervisor.clone(),
     )
     .await?;
 
@@ -86,13 +117,15 @@ async fn start_nym_api_tasks(
     let nym_contract_cache_state = rocket.state::<NymContractCache>().unwrap();
     let node_status_cache_state = rocket.state::<NodeStatusCache>().unwrap();
     let circulating_supply_cache_state = rocket.state::<CirculatingSupplyCache>().unwrap();
+    let heartbeat_store = rocket.state::<HeartbeatStore>().unwrap();
     let maybe_storage = rocket.state::<NymApiStorage>();
 
     // start all the caches first
     let nym_contract_cache_listener = nym_contract_cache::start_refresher(
         &config,
         nym_contract_cache_state,
-        nyxd_client.clone(),
+        nyxd_query_client.clone(),
+        &supervisor,
         &shutdown,
     );
     node_status_api::start_cache_refresh(
@@ -101,25 +134,57 @@ async fn start_nym_api_tasks(
         node_status_cache_state,
         maybe_storage,
         nym_contract_cache_listener,
+        &supervisor,
         &shutdown,
     );
     circulating_supply_api::start_cache_refresh(
         &config,
-        nyxd_client.clone(),
+        nyxd_query_client.clone(),
         circulating_supply_cache_state,
+        &supervisor,
+        &shutdown,
+    );
+    HeartbeatMonitor::start(
+        &config,
+        heartbeat_store,
+        nym_contract_cache_state,
+        &supervisor,
         &shutdown,
     );
 
     // start dkg task
     if config.get_coconut_signer_enabled() {
-        DkgController::start(
+        let coconut_keystore = CoconutKeyStore::new(&config)?;
+        let dkg_state = DkgController::start(
             &config,
             nyxd_client.clone(),
             coconut_keypair,
+            coconut_keystore.clone(),
+            dkg_context,
             OsRng,
+            &supervisor,
             &shutdown,
         )
         .await?;
+
+        IssuanceHealthMonitor::start(
+            &config,
+            nyxd_client.clone(),
+            coconut_keystore,
+            circuit_breaker,
+            &supervisor,
+            &shutdown,
+        );
+
+        let coconut_state = rocket.state::<coconut::State>().unwrap();
+        coconut_state.set_dkg_state(dkg_state);
+        DepositIndexer::start(
+            &config,
+            nyxd_client.clone(),
+            coconut_state.storage().clone(),
+            &supervisor,
+            &shutdown,
+        );
     }
 
     // and then only start the uptime updater (and the monitor itself, duh)
@@ -134,16 +199,23 @@ async fn start_nym_api_tasks(
             storage,
             nyxd_client.clone(),
             system_version,
+            &supervisor,
             &shutdown,
         )
         .await;
 
-        HistoricalUptimeUpdater::start(storage, &shutdown);
+        HistoricalUptimeUpdater::start(storage, &supervisor, &shutdown);
 
         // start 'rewarding' if its enabled
         if config.get_rewarding_enabled() {
             epoch_operations::ensure_rewarding_permission(&nyxd_client).await?;
-            RewardedSetUpdater::start(nyxd_client, nym_contract_cache_state, storage, &shutdown);
+            RewardedSetUpdater::start(
+                nyxd_client,
+                nym_contract_cache_state,
+                storage,
+                &supervisor,
+                &shutdown,
+            );
         }
     }
 
@@ -158,7 +230,13 @@ async fn start_nym_api_tasks(
 
 async fn run_nym_api(cli_args: CliArgs) -> Result<(), Box<dyn Error + Send + Sync>> {
     let save_to_file = cli_args.save_config;
-    let config = cli::build_config(cli_args)?;
+    let command = cli_args.command;
+    let config = cli::build_config(cli_args).await?;
+
+    // if we just wanted to run a one-off command, exit afterwards, don't start any tasks
+    if let Some(command) = command {
+        return Ok(cli::execute(command, &config).await?);
+    }
 
     // if we just wanted to write data to the config, exit, don't start any tasks
     if save_to_file {
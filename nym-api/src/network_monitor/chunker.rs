@@ -59,9 +59,11 @@ impl Chunker {
     ) -> Vec<MixPacket> {
         let ack_key: AckKey = AckKey::new(&mut self.rng);
 
-        let split_message = self
-            .message_preparer
-            .pad_and_split_message(NymMessage::new_plain(message), self.packet_size);
+        let split_message = self.message_preparer.pad_and_split_message(
+            NymMessage::new_plain(message),
+            self.packet_size,
+            packet_sender.into(),
+        );
 
         let mut mix_packets = Vec::with_capacity(split_message.len());
         for message_chunk in split_message {
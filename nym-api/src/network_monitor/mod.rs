@@ -16,6 +16,7 @@ use crate::nym_contract_cache::cache::NymContractCache;
 use crate::storage::NymApiStorage;
 use crate::support::config::Config;
 use crate::support::nyxd;
+use crate::support::supervisor::TaskSupervisor;
 use futures::channel::mpsc;
 use nym_bandwidth_controller::BandwidthController;
 use nym_credential_storage::persistent_storage::PersistentStorage;
@@ -23,6 +24,7 @@ use nym_crypto::asymmetric::{encryption, identity};
 use nym_sphinx::receiver::MessageReceiver;
 use nym_task::TaskManager;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 
 pub(crate) mod chunker;
 pub(crate) mod gateways_reader;
@@ -150,13 +152,27 @@ impl<R: MessageReceiver + Send + 'static> NetworkMonitorRunnables<R> {
     // TODO: note, that is not exactly doing what we want, because when
     // `ReceivedProcessor` is constructed, it already spawns a future
     // this needs to be refactored!
-    pub(crate) fn spawn_tasks(self, shutdown: &TaskManager) {
-        let mut packet_receiver = self.packet_receiver;
-        let mut monitor = self.monitor;
-        let shutdown_listener = shutdown.subscribe();
-        tokio::spawn(async move { packet_receiver.run(shutdown_listener).await });
-        let shutdown_listener = shutdown.subscribe();
-        tokio::spawn(async move { monitor.run(shutdown_listener).await });
+    pub(crate) fn spawn_tasks(self, supervisor: &TaskSupervisor, shutdown: &TaskManager) {
+        let packet_receiver = Arc::new(Mutex::new(self.packet_receiver));
+        let monitor = Arc::new(Mutex::new(self.monitor));
+        supervisor.spawn(
+            "network-monitor-packet-receiver",
+            shutdown,
+            move |shutdown| {
+                let packet_receiver = Arc::clone(&packet_receiver);
+                async move {
+                    packet_receiver.lock().await.run(shutdown).await;
+                    Ok(())
+                }
+            },
+        );
+        supervisor.spawn("network-monitor", shutdown, move |shutdown| {
+            let monitor = Arc::clone(&monitor);
+            async move {
+                monitor.lock().await.run(shutdown).await;
+                Ok(())
+            }
+        });
     }
 }
 
@@ -224,6 +240,7 @@ pub(crate) async fn start<R: MessageReceiver + Send + 'static>(
     storage: &NymApiStorage,
     nyxd_client: nyxd::Client,
     system_version: &str,
+    supervisor: &TaskSupervisor,
     shutdown: &TaskManager,
 ) {
     let monitor_builder = network_monitor::setup(
@@ -235,5 +252,5 @@ pub(crate) async fn start<R: MessageReceiver + Send + 'static>(
     );
     info!("Starting network monitor...");
     let runnables: NetworkMonitorRunnables<R> = monitor_builder.build().await;
-    runnables.spawn_tasks(shutdown);
+    runnables.spawn_tasks(supervisor, shutdown);
 }
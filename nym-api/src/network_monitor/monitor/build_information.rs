@@ -0,0 +1,129 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Build information reported by the mixnodes through their HTTP APIs. It's used to exclude
+//! the nodes speaking an incompatible version of the sphinx packet format from the tests,
+//! as all packets sent through them would have been lost anyway.
+
+use futures::future::join_all;
+use log::{debug, trace};
+use nym_bin_common::build_information::{protocols, BinaryBuildInformationOwned};
+use nym_mixnet_contract_common::{MixId, MixNodeBond};
+use nym_sphinx::params::CURRENT_PACKET_VERSION_NUMBER;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+
+const BUILD_INFORMATION_ROUTE: &str = "build-information";
+
+/// How often the build information of each node is going to get re-fetched.
+const BUILD_INFORMATION_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const BUILD_INFORMATION_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct CachedBuildInformation {
+    fetched_at: Instant,
+
+    // `None` if the node did not respond or is running a version that does not expose the endpoint
+    build_information: Option<BinaryBuildInformationOwned>,
+}
+
+impl CachedBuildInformation {
+    fn is_stale(&self) -> bool {
+        self.fetched_at.elapsed() > BUILD_INFORMATION_REFRESH_INTERVAL
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct NodesBuildInformation {
+    client: reqwest::Client,
+    cache: Arc<RwLock<HashMap<MixId, CachedBuildInformation>>>,
+}
+
+impl NodesBuildInformation {
+    async fn fetch(
+        client: &reqwest::Client,
+        mixnode: &MixNodeBond,
+    ) -> Option<BinaryBuildInformationOwned> {
+        let url = format!(
+            "http://{}:{}/{BUILD_INFORMATION_ROUTE}",
+            mixnode.mix_node.host, mixnode.mix_node.http_api_port
+        );
+
+        let response = client
+            .get(&url)
+            .timeout(BUILD_INFORMATION_REQUEST_TIMEOUT)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) => response.json().await.ok(),
+            Err(err) => {
+                trace!("failed to obtain build information from {url}: {err}");
+                None
+            }
+        }
+    }
+
+    /// Fetches the build information of all the nodes for which it's either missing or stale
+    /// and removes the entries of the nodes that are no longer bonded.
+    pub(crate) async fn refresh(&self, mixnodes: &[MixNodeBond]) {
+        let to_fetch = {
+            let cache = self.cache.read().await;
+            mixnodes
+                .iter()
+                .filter(|node| match cache.get(&node.mix_id) {
+                    Some(cached) => cached.is_stale(),
+                    None => true,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        if !to_fetch.is_empty() {
+            debug!(
+                "obtaining build information of {} mixnodes...",
+                to_fetch.len()
+            );
+        }
+
+        let fetched = join_all(to_fetch.iter().map(|node| Self::fetch(&self.client, node))).await;
+
+        let mut cache = self.cache.write().await;
+        for (node, build_information) in to_fetch.into_iter().zip(fetched) {
+            cache.insert(
+                node.mix_id,
+                CachedBuildInformation {
+                    fetched_at: Instant::now(),
+                    build_information,
+                },
+            );
+        }
+        let bonded = mixnodes
+            .iter()
+            .map(|node| node.mix_id)
+            .collect::<HashSet<_>>();
+        cache.retain(|mix_id, _| bonded.contains(mix_id));
+    }
+
+    /// Returns the build versions of all the nodes that reported speaking an incompatible
+    /// version of the sphinx packet format. Nodes that didn't report it are assumed to be compatible.
+    pub(crate) async fn incompatible_mixnodes(&self) -> HashMap<MixId, String> {
+        self.cache
+            .read()
+            .await
+            .iter()
+            .filter_map(|(mix_id, cached)| {
+                let build_information = cached.build_information.as_ref()?;
+                if build_information
+                    .is_protocol_compatible(protocols::SPHINX_PACKET, CURRENT_PACKET_VERSION_NUMBER)
+                {
+                    None
+                } else {
+                    Some((*mix_id, build_information.build_version.clone()))
+                }
+            })
+            .collect()
+    }
+}
@@ -16,6 +16,7 @@ use std::collections::{HashMap, HashSet};
 use std::process;
 use tokio::time::{sleep, Duration, Instant};
 
+pub(crate) mod build_information;
 pub(crate) mod gateway_clients_cache;
 pub(crate) mod gateways_pinger;
 pub(crate) mod preparer;
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::network_monitor::chunker::Chunker;
+use crate::network_monitor::monitor::build_information::NodesBuildInformation;
 use crate::network_monitor::monitor::sender::GatewayPackets;
 use crate::network_monitor::test_packet::{NodeType, TestPacket};
 use crate::network_monitor::test_route::TestRoute;
@@ -25,7 +26,6 @@ type Id = String;
 type Owner = Addr;
 
 #[derive(Clone)]
-#[allow(dead_code)]
 pub(crate) enum InvalidNode {
     Outdated(Id, Owner, NodeType, Version),
     Malformed(Id, Owner, NodeType),
@@ -136,6 +136,9 @@ pub(crate) struct PacketPreparer {
     chunker: Option<Chunker>,
     validator_cache: NymContractCache,
 
+    /// Build information reported by the mixnodes, used to recognise the incompatible ones.
+    nodes_build_information: NodesBuildInformation,
+
     /// Number of test packets sent to each node
     per_node_test_packets: usize,
 
@@ -159,6 +162,7 @@ impl PacketPreparer {
             system_version: system_version.to_owned(),
             chunker: None,
             validator_cache,
+            nodes_build_information: NodesBuildInformation::default(),
             per_node_test_packets,
             self_public_identity,
             self_public_encryption,
@@ -384,14 +388,24 @@ impl PacketPreparer {
         )
     }
 
-    fn filter_outdated_and_malformed_mixnodes(
+    async fn filter_outdated_and_malformed_mixnodes(
         &self,
         nodes: Vec<MixNodeBond>,
     ) -> (Vec<mix::Node>, Vec<InvalidNode>) {
+        self.nodes_build_information.refresh(&nodes).await;
+        let mut incompatible = self.nodes_build_information.incompatible_mixnodes().await;
+
         let mut parsed_nodes = Vec::new();
         let mut invalid_nodes = Vec::new();
         for mixnode in nodes {
-            if let Ok(parsed_node) = (&mixnode).try_into() {
+            if let Some(version) = incompatible.remove(&mixnode.mix_id) {
+                invalid_nodes.push(InvalidNode::Outdated(
+                    mixnode.mix_node.identity_key,
+                    mixnode.owner,
+                    NodeType::Mixnode(mixnode.mix_id),
+                    version,
+                ));
+            } else if let Ok(parsed_node) = (&mixnode).try_into() {
                 parsed_nodes.push(parsed_node)
             } else {
                 invalid_nodes.push(InvalidNode::Malformed(
@@ -435,7 +449,8 @@ impl PacketPreparer {
         // any reward during the current rewarding interval
         let (mixnodes, gateways) = self.all_mixnodes_and_gateways().await;
 
-        let (mixnodes, invalid_mixnodes) = self.filter_outdated_and_malformed_mixnodes(mixnodes);
+        let (mixnodes, invalid_mixnodes) =
+            self.filter_outdated_and_malformed_mixnodes(mixnodes).await;
         let (gateways, invalid_gateways) = self.filter_outdated_and_malformed_gateways(gateways);
 
         let tested_mixnodes = mixnodes.iter().map(|node| node.into()).collect::<Vec<_>>();
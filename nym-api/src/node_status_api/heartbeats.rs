@@ -0,0 +1,365 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node_status_api::models::ErrorResponse;
+use crate::nym_contract_cache::cache::NymContractCache;
+use crate::support::config::Config;
+use crate::support::supervisor::TaskSupervisor;
+use nym_api_requests::models::{
+    HeartbeatNodeType, NodeHeartbeat, NodeLivenessResponse, SignedNodeHeartbeat,
+};
+use nym_crypto::asymmetric::identity;
+use nym_mixnet_contract_common::{IdentityKey, MixId};
+use nym_task::{TaskClient, TaskManager};
+use rocket::http::Status;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use time::OffsetDateTime;
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::interval;
+
+/// Maximum difference between the timestamp of a heartbeat and the local clock.
+pub(crate) const MAXIMUM_HEARTBEAT_CLOCK_SKEW: Duration = Duration::from_secs(2 * 60);
+
+#[derive(Debug, Error)]
+pub(crate) enum HeartbeatError {
+    #[error("the identity key of the heartbeat is malformed: {0}")]
+    MalformedIdentity(identity::Ed25519RecoveryError),
+
+    #[error("the signature of the heartbeat is malformed: {0}")]
+    MalformedSignature(identity::Ed25519RecoveryError),
+
+    #[error("the signature of the heartbeat is invalid")]
+    InvalidSignature,
+
+    #[error("{identity} is not a bonded {node_type}")]
+    NotBonded {
+        identity: IdentityKey,
+        node_type: HeartbeatNodeType,
+    },
+
+    #[error("the heartbeat timestamp ({timestamp}) is too far from the current time ({now})")]
+    ClockSkew { timestamp: i64, now: i64 },
+
+    #[error("the heartbeat is not newer than the last one received from the node (at {last})")]
+    Replayed { last: i64 },
+}
+
+impl From<HeartbeatError> for ErrorResponse {
+    fn from(err: HeartbeatError) -> Self {
+        let status = match err {
+            HeartbeatError::InvalidSignature => Status::Unauthorized,
+            HeartbeatError::NotBonded { .. } => Status::Forbidden,
+            HeartbeatError::Replayed { .. } => Status::Conflict,
+            _ => Status::BadRequest,
+        };
+        ErrorResponse::new(err.to_string(), status)
+    }
+}
+
+/// Makes sure the heartbeat has been signed by the identity key it claims to come from.
+pub(crate) fn verify_heartbeat_signature(
+    signed: &SignedNodeHeartbeat,
+) -> Result<(), HeartbeatError> {
+    let identity = identity::PublicKey::from_base58_string(&signed.heartbeat.identity_key)
+        .map_err(HeartbeatError::MalformedIdentity)?;
+    let signature = identity::Signature::from_base58_string(&signed.signature)
+        .map_err(HeartbeatError::MalformedSignature)?;
+
+    identity
+        .verify(&signed.heartbeat.signable_bytes(), &signature)
+        .map_err(|_| HeartbeatError::InvalidSignature)
+}
+
+/// Makes sure the heartbeat comes from a node that's currently bonded as the claimed node type.
+pub(crate) async fn ensure_bonded(
+    heartbeat: &NodeHeartbeat,
+    nym_contract_cache: &NymContractCache,
+) -> Result<(), HeartbeatError> {
+    let bonded = match heartbeat.node_type {
+        HeartbeatNodeType::Mixnode => nym_contract_cache
+            .mixnodes_all()
+            .await
+            .iter()
+            .any(|mix| mix.bond_information.identity() == heartbeat.identity_key),
+        HeartbeatNodeType::Gateway => nym_contract_cache
+            .gateways_all()
+            .await
+            .iter()
+            .any(|gateway| gateway.identity() == &heartbeat.identity_key),
+    };
+
+    if bonded {
+        Ok(())
+    } else {
+        Err(HeartbeatError::NotBonded {
+            identity: heartbeat.identity_key.clone(),
+            node_type: heartbeat.node_type,
+        })
+    }
+}
+
+/// Most recent heartbeats received from the bonded nodes.
+#[derive(Clone)]
+pub(crate) struct HeartbeatStore {
+    timeout: Duration,
+    heartbeats: Arc<RwLock<HashMap<IdentityKey, NodeHeartbeat>>>,
+}
+
+impl HeartbeatStore {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        HeartbeatStore {
+            timeout,
+            heartbeats: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn is_alive(&self, heartbeat: &NodeHeartbeat, now: i64) -> bool {
+        now - heartbeat.timestamp <= self.timeout.as_secs() as i64
+    }
+
+    /// Records an already verified heartbeat, as long as it's recent and newer than the previous one.
+    pub(crate) async fn accept(
+        &self,
+        heartbeat: NodeHeartbeat,
+        now: i64,
+    ) -> Result<(), HeartbeatError> {
+        if (heartbeat.timestamp - now).unsigned_abs() > MAXIMUM_HEARTBEAT_CLOCK_SKEW.as_secs() {
+            return Err(HeartbeatError::ClockSkew {
+                timestamp: heartbeat.timestamp,
+                now,
+            });
+        }
+
+        let mut heartbeats = self.heartbeats.write().await;
+        if let Some(last) = heartbeats.get(&heartbeat.identity_key) {
+            if heartbeat.timestamp <= last.timestamp {
+                return Err(HeartbeatError::Replayed {
+                    last: last.timestamp,
+                });
+            }
+        }
+        heartbeats.insert(heartbeat.identity_key.clone(), heartbeat);
+        Ok(())
+    }
+
+    pub(crate) async fn liveness(&self, now: i64) -> Vec<NodeLivenessResponse> {
+        self.heartbeats
+            .read()
+            .await
+            .values()
+            .map(|heartbeat| NodeLivenessResponse {
+                identity_key: heartbeat.identity_key.clone(),
+                node_type: heartbeat.node_type,
+                version: heartbeat.version.clone(),
+                last_heartbeat: heartbeat.timestamp,
+                stats: heartbeat.stats,
+                alive: self.is_alive(heartbeat, now),
+            })
+            .collect()
+    }
+
+    /// Nodes of the given type that used to send heartbeats, but stopped doing so.
+    /// Nodes that have never sent one are not included.
+    pub(crate) async fn stale_nodes(
+        &self,
+        node_type: HeartbeatNodeType,
+        now: i64,
+    ) -> HashSet<IdentityKey> {
+        self.heartbeats
+            .read()
+            .await
+            .values()
+            .filter(|heartbeat| heartbeat.node_type == node_type)
+            .filter(|heartbeat| !self.is_alive(heartbeat, now))
+            .map(|heartbeat| heartbeat.identity_key.clone())
+            .collect()
+    }
+}
+
+/// Excludes the nodes that stopped sending heartbeats from the served topology, without having
+/// to wait for the network monitor to notice their failures, and restores them once they resume.
+pub(crate) struct HeartbeatMonitor {
+    store: HeartbeatStore,
+    nym_contract_cache: NymContractCache,
+    check_interval: Duration,
+
+    // nodes blacklisted by this monitor. They're kept in a blacklist separate from the one of
+    // the nodes with low reliability, so the monitor can't lift the latter (and vice versa)
+    deactivated_mixnodes: HashSet<MixId>,
+    deactivated_gateways: HashSet<IdentityKey>,
+}
+
+impl HeartbeatMonitor {
+    pub(crate) fn new(
+        config: &Config,
+        store: HeartbeatStore,
+        nym_contract_cache: NymContractCache,
+    ) -> Self {
+        HeartbeatMonitor {
+            store,
+            nym_contract_cache,
+            // check a few times per timeout so that the nodes are deactivated reasonably promptly
+            check_interval: config.get_heartbeat_timeout() / 4,
+            deactivated_mixnodes: HashSet::new(),
+            deactivated_gateways: HashSet::new(),
+        }
+    }
+
+    async fn update_mixnodes(&mut self, now: i64) {
+        let stale = self
+            .store
+            .stale_nodes(HeartbeatNodeType::Mixnode, now)
+            .await;
+        let stale: HashSet<MixId> = self
+            .nym_contract_cache
+            .mixnodes_all()
+            .await
+            .into_iter()
+            .filter(|mix| stale.contains(mix.bond_information.identity()))
+            .map(|mix| mix.mix_id())
+            .collect();
+
+        for mix_id in stale.difference(&self.deactivated_mixnodes) {
+            info!("mixnode {mix_id} stopped sending heartbeats - deactivating it");
+        }
+        for mix_id in self.deactivated_mixnodes.difference(&stale) {
+            info!("mixnode {mix_id} resumed sending heartbeats - reactivating it");
+        }
+
+        self.nym_contract_cache
+            .set_mixnodes_heartbeat_blacklist(stale.clone())
+            .await;
+        self.deactivated_mixnodes = stale;
+    }
+
+    async fn update_gateways(&mut self, now: i64) {
+        let stale = self
+            .store
+            .stale_nodes(HeartbeatNodeType::Gateway, now)
+            .await;
+
+        for identity in stale.difference(&self.deactivated_gateways) {
+            info!("gateway {identity} stopped sending heartbeats - deactivating it");
+        }
+        for identity in self.deactivated_gateways.difference(&stale) {
+            info!("gateway {identity} resumed sending heartbeats - reactivating it");
+        }
+
+        self.nym_contract_cache
+            .set_gateways_heartbeat_blacklist(stale.clone())
+            .await;
+        self.deactivated_gateways = stale;
+    }
+
+    pub(crate) async fn run(&mut self, mut shutdown: TaskClient) {
+        self.nym_contract_cache.wait_for_initial_values().await;
+
+        let mut interval = interval(self.check_interval);
+        while !shutdown.is_shutdown() {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let now = OffsetDateTime::now_utc().unix_timestamp();
+                    self.update_mixnodes(now).await;
+                    self.update_gateways(now).await;
+                }
+                _ = shutdown.recv() => {
+                    trace!("HeartbeatMonitor: Received shutdown");
+                }
+            }
+        }
+    }
+
+    pub(crate) fn start(
+        config: &Config,
+        store: &HeartbeatStore,
+        nym_contract_cache: &NymContractCache,
+        supervisor: &TaskSupervisor,
+        shutdown: &TaskManager,
+    ) {
+        let monitor = Arc::new(Mutex::new(HeartbeatMonitor::new(
+            config,
+            store.clone(),
+            nym_contract_cache.clone(),
+        )));
+        supervisor.spawn("heartbeat-monitor", shutdown, move |shutdown| {
+            let monitor = Arc::clone(&monitor);
+            async move {
+                monitor.lock().await.run(shutdown).await;
+                Ok(())
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nym_api_requests::models::NodeHeartbeatStats;
+
+    fn signed_heartbeat(keys: &identity::KeyPair, timestamp: i64) -> SignedNodeHeartbeat {
+        let heartbeat = NodeHeartbeat {
+            identity_key: keys.public_key().to_base58_string(),
+            node_type: HeartbeatNodeType::Mixnode,
+            version: "1.1.20".to_string(),
+            timestamp,
+            stats: NodeHeartbeatStats::default(),
+        };
+        let signature = keys
+            .private_key()
+            .sign(&heartbeat.signable_bytes())
+            .to_base58_string();
+        SignedNodeHeartbeat {
+            heartbeat,
+            signature,
+        }
+    }
+
+    #[test]
+    fn heartbeats_must_be_signed_by_the_node() {
+        let mut rng = rand_07::thread_rng();
+        let keys = identity::KeyPair::new(&mut rng);
+        let mut signed = signed_heartbeat(&keys, 1000);
+        assert!(verify_heartbeat_signature(&signed).is_ok());
+
+        signed.heartbeat.stats.packets_sent = 42;
+        assert!(matches!(
+            verify_heartbeat_signature(&signed),
+            Err(HeartbeatError::InvalidSignature)
+        ));
+    }
+
+    #[tokio::test]
+    async fn stale_and_replayed_heartbeats() {
+        let mut rng = rand_07::thread_rng();
+        let keys = identity::KeyPair::new(&mut rng);
+        let identity = keys.public_key().to_base58_string();
+        let store = HeartbeatStore::new(Duration::from_secs(300));
+
+        let heartbeat = signed_heartbeat(&keys, 1000).heartbeat;
+        assert!(matches!(
+            store.accept(heartbeat.clone(), 2000).await,
+            Err(HeartbeatError::ClockSkew { .. })
+        ));
+        store.accept(heartbeat.clone(), 1010).await.unwrap();
+        assert!(matches!(
+            store.accept(heartbeat, 1020).await,
+            Err(HeartbeatError::Replayed { last: 1000 })
+        ));
+
+        assert!(store
+            .stale_nodes(HeartbeatNodeType::Mixnode, 1200)
+            .await
+            .is_empty());
+        assert!(store
+            .stale_nodes(HeartbeatNodeType::Mixnode, 1301)
+            .await
+            .contains(&identity));
+        assert!(store
+            .stale_nodes(HeartbeatNodeType::Gateway, 1301)
+            .await
+            .is_empty());
+    }
+}
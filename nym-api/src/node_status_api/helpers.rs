@@ -1,22 +1,24 @@
 // Copyright 2021-2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::epoch_operations::epoch_performance;
 use crate::node_status_api::models::ErrorResponse;
 use crate::storage::NymApiStorage;
 use crate::support::caching::Cache;
 use crate::{NodeStatusCache, NymContractCache};
 use cosmwasm_std::Decimal;
 use nym_api_requests::models::{
-    AllInclusionProbabilitiesResponse, ComputeRewardEstParam, GatewayBondAnnotated,
-    GatewayCoreStatusResponse, GatewayStatusReportResponse, GatewayUptimeHistoryResponse,
-    GatewayUptimeResponse, InclusionProbabilityResponse, MixNodeBondAnnotated,
-    MixnodeCoreStatusResponse, MixnodeStatusReportResponse, MixnodeStatusResponse,
-    MixnodeUptimeHistoryResponse, RewardEstimationResponse, StakeSaturationResponse,
-    UptimeResponse,
+    AllInclusionProbabilitiesResponse, ComputeRewardEstParam, EpochRewardsDryRunResponse,
+    GatewayBondAnnotated, GatewayCoreStatusResponse, GatewayStatusReportResponse,
+    GatewayUptimeHistoryResponse, GatewayUptimeResponse, InclusionProbabilityResponse,
+    MixNodeBondAnnotated, MixnodeCoreStatusResponse, MixnodeRewardBreakdown,
+    MixnodeStatusReportResponse, MixnodeStatusResponse, MixnodeUptimeHistoryResponse,
+    RewardEstimationResponse, StakeSaturationResponse, UptimeResponse,
 };
 use nym_mixnet_contract_common::{MixId, RewardedSetNodeStatus};
 use rocket::http::Status;
 use rocket::State;
+use std::collections::HashSet;
 
 use super::reward_estimate::compute_reward_estimate;
 
@@ -278,6 +280,81 @@ pub(crate) async fn _compute_mixnode_reward_estimation(
     }
 }
 
+pub(crate) async fn _get_epoch_rewards_dry_run(
+    cache: &NodeStatusCache,
+    validator_cache: &NymContractCache,
+    storage: &NymApiStorage,
+) -> Result<EpochRewardsDryRunResponse, ErrorResponse> {
+    let reward_params = validator_cache.interval_reward_params().await;
+    let as_at = reward_params.timestamp();
+    let reward_params = reward_params
+        .into_inner()
+        .ok_or_else(|| ErrorResponse::new("server error", Status::InternalServerError))?;
+    let current_interval = validator_cache
+        .current_interval()
+        .await
+        .into_inner()
+        .ok_or_else(|| ErrorResponse::new("server error", Status::InternalServerError))?;
+
+    let no_data = || ErrorResponse::new("no data available", Status::ServiceUnavailable);
+    let mut rewarded_set = cache
+        .rewarded_set_annotated()
+        .await
+        .ok_or_else(no_data)?
+        .into_inner();
+    let active_set = cache
+        .active_set_annotated()
+        .await
+        .ok_or_else(no_data)?
+        .iter()
+        .map(|node| node.mix_id())
+        .collect::<HashSet<_>>();
+
+    // that's the order in which the rewarding messages are sent
+    rewarded_set.sort_by_key(|node| node.mix_id());
+
+    let mut nodes = Vec::with_capacity(rewarded_set.len());
+    for node in rewarded_set {
+        let mix_id = node.mix_id();
+        let performance = epoch_performance(storage, &current_interval, mix_id).await;
+        let rewarded_set_status = if active_set.contains(&mix_id) {
+            RewardedSetNodeStatus::Active
+        } else {
+            RewardedSetNodeStatus::Standby
+        };
+
+        let estimation = compute_reward_estimate(
+            &node.mixnode_details,
+            performance,
+            Some(rewarded_set_status),
+            reward_params,
+            current_interval,
+        );
+
+        nodes.push(MixnodeRewardBreakdown {
+            mix_id,
+            identity_key: node.identity_key().to_owned(),
+            rewarded_set_status,
+            performance,
+            stake_saturation: node.stake_saturation,
+            uncapped_stake_saturation: node.uncapped_stake_saturation,
+            profit_margin_percent: node
+                .mixnode_details
+                .rewarding_details
+                .cost_params
+                .profit_margin_percent,
+            estimation,
+        })
+    }
+
+    Ok(EpochRewardsDryRunResponse {
+        epoch: current_interval,
+        reward_params,
+        as_at,
+        nodes,
+    })
+}
+
 pub(crate) async fn _get_mixnode_stake_saturation(
     cache: &NodeStatusCache,
     validator_cache: &NymContractCache,
@@ -1,6 +1,7 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::support::http::client_address::client_address;
 use rocket::http::Status;
 use rocket::request::{FromRequest, Outcome};
 use rocket::Request;
@@ -10,7 +11,9 @@ use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 #[derive(Debug)]
 pub struct NonLocalRequestError;
 
-/// Request guard that only allows requests coming from a local address
+/// Request guard that only allows requests coming from a local address.
+/// Both the socket peer and the client reported by a trusted reverse proxy have to be local,
+/// so a proxy running on another machine could never forward a local-only request.
 pub(crate) struct LocalRequest;
 
 fn is_local_address(ip: Option<IpAddr>) -> bool {
@@ -29,12 +32,13 @@ impl<'r> FromRequest<'r> for LocalRequest {
     type Error = NonLocalRequestError;
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        if is_local_address(request.client_ip()) {
+        let peer_address = request.remote().map(|remote| remote.ip());
+        let client_address = client_address(request);
+        if is_local_address(peer_address) && is_local_address(client_address) {
             Outcome::Success(LocalRequest)
         } else {
             warn!(
-                "Received a request from {:?} for a local-only route",
-                request.client_ip()
+                "Received a request from {client_address:?} (through {peer_address:?}) for a local-only route"
             );
             Outcome::Failure((Status::Unauthorized, NonLocalRequestError))
         }
@@ -6,15 +6,18 @@ use nym_task::TaskManager;
 use okapi::openapi3::OpenApi;
 use rocket::Route;
 use rocket_okapi::{openapi_get_routes_spec, settings::OpenApiSettings};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Mutex;
 
 use crate::{
     nym_contract_cache::cache::NymContractCache,
-    support::{self, config::Config, storage},
+    support::{self, config::Config, storage, supervisor::TaskSupervisor},
 };
 
 use self::cache::refresher::NodeStatusCacheRefresher;
 pub(crate) mod cache;
+pub(crate) mod heartbeats;
 pub(crate) mod helpers;
 pub(crate) mod local_guard;
 pub(crate) mod models;
@@ -42,6 +45,7 @@ pub(crate) fn node_status_routes(
             routes::get_mixnode_status,
             routes::get_mixnode_reward_estimation,
             routes::compute_mixnode_reward_estimation,
+            routes::get_epoch_rewards_dry_run,
             routes::get_mixnode_stake_saturation,
             routes::get_mixnode_inclusion_probability,
             routes::get_mixnode_avg_uptime,
@@ -53,6 +57,8 @@ pub(crate) fn node_status_routes(
             routes::get_active_set_detailed,
             routes::get_gateways_detailed,
             routes::get_gateways_detailed_unfiltered,
+            routes::submit_heartbeat,
+            routes::get_heartbeats,
         ]
     } else {
         // in the minimal variant we would not have access to endpoints relying on existence
@@ -65,6 +71,8 @@ pub(crate) fn node_status_routes(
             routes::get_mixnodes_detailed,
             routes::get_rewarded_set_detailed,
             routes::get_active_set_detailed,
+            routes::submit_heartbeat,
+            routes::get_heartbeats,
         ]
     }
 }
@@ -79,15 +87,22 @@ pub(crate) fn start_cache_refresh(
     node_status_cache_state: &NodeStatusCache,
     storage: Option<&storage::NymApiStorage>,
     nym_contract_cache_listener: tokio::sync::watch::Receiver<support::caching::CacheNotification>,
+    supervisor: &TaskSupervisor,
     shutdown: &TaskManager,
 ) {
-    let mut nym_api_cache_refresher = NodeStatusCacheRefresher::new(
+    let nym_api_cache_refresher = NodeStatusCacheRefresher::new(
         node_status_cache_state.to_owned(),
         config.get_node_status_caching_interval(),
         nym_contract_cache_state.to_owned(),
         nym_contract_cache_listener,
         storage.cloned(),
     );
-    let shutdown_listener = shutdown.subscribe();
-    tokio::spawn(async move { nym_api_cache_refresher.run(shutdown_listener).await });
+    let nym_api_cache_refresher = Arc::new(Mutex::new(nym_api_cache_refresher));
+    supervisor.spawn("node-status-cache-refresher", shutdown, move |shutdown| {
+        let nym_api_cache_refresher = Arc::clone(&nym_api_cache_refresher);
+        async move {
+            nym_api_cache_refresher.lock().await.run(shutdown).await;
+            Ok(())
+        }
+    });
 }
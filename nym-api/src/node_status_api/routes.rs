@@ -3,10 +3,13 @@
 
 use super::helpers::_get_gateways_detailed;
 use super::NodeStatusCache;
+use crate::node_status_api::heartbeats::{
+    ensure_bonded, verify_heartbeat_signature, HeartbeatStore,
+};
 use crate::node_status_api::helpers::{
     _compute_mixnode_reward_estimation, _gateway_core_status_count, _gateway_report,
-    _gateway_uptime_history, _get_active_set_detailed, _get_gateway_avg_uptime,
-    _get_gateways_detailed_unfiltered, _get_mixnode_avg_uptime,
+    _gateway_uptime_history, _get_active_set_detailed, _get_epoch_rewards_dry_run,
+    _get_gateway_avg_uptime, _get_gateways_detailed_unfiltered, _get_mixnode_avg_uptime,
     _get_mixnode_inclusion_probabilities, _get_mixnode_inclusion_probability,
     _get_mixnode_reward_estimation, _get_mixnode_stake_saturation, _get_mixnode_status,
     _get_mixnodes_detailed, _get_mixnodes_detailed_unfiltered, _get_rewarded_set_detailed,
@@ -16,17 +19,18 @@ use crate::node_status_api::models::ErrorResponse;
 use crate::storage::NymApiStorage;
 use crate::NymContractCache;
 use nym_api_requests::models::{
-    AllInclusionProbabilitiesResponse, ComputeRewardEstParam, GatewayBondAnnotated,
-    GatewayCoreStatusResponse, GatewayStatusReportResponse, GatewayUptimeHistoryResponse,
-    GatewayUptimeResponse, InclusionProbabilityResponse, MixNodeBondAnnotated,
-    MixnodeCoreStatusResponse, MixnodeStatusReportResponse, MixnodeStatusResponse,
-    MixnodeUptimeHistoryResponse, RewardEstimationResponse, StakeSaturationResponse,
-    UptimeResponse,
+    AllInclusionProbabilitiesResponse, ComputeRewardEstParam, EpochRewardsDryRunResponse,
+    GatewayBondAnnotated, GatewayCoreStatusResponse, GatewayStatusReportResponse,
+    GatewayUptimeHistoryResponse, GatewayUptimeResponse, InclusionProbabilityResponse,
+    MixNodeBondAnnotated, MixnodeCoreStatusResponse, MixnodeStatusReportResponse,
+    MixnodeStatusResponse, MixnodeUptimeHistoryResponse, NodeLivenessResponse,
+    RewardEstimationResponse, SignedNodeHeartbeat, StakeSaturationResponse, UptimeResponse,
 };
 use nym_mixnet_contract_common::MixId;
 use rocket::serde::json::Json;
 use rocket::State;
 use rocket_okapi::openapi;
+use time::OffsetDateTime;
 
 #[openapi(tag = "status")]
 #[get("/gateway/<identity>/report")]
@@ -131,6 +135,20 @@ pub(crate) async fn compute_mixnode_reward_estimation(
     ))
 }
 
+/// Reward breakdown of every node in the current rewarded set that would be submitted
+/// to the mixnet contract if the epoch ended now.
+#[openapi(tag = "status")]
+#[get("/epoch/rewards-dry-run")]
+pub(crate) async fn get_epoch_rewards_dry_run(
+    cache: &State<NodeStatusCache>,
+    validator_cache: &State<NymContractCache>,
+    storage: &State<NymApiStorage>,
+) -> Result<Json<EpochRewardsDryRunResponse>, ErrorResponse> {
+    Ok(Json(
+        _get_epoch_rewards_dry_run(cache, validator_cache, storage).await?,
+    ))
+}
+
 #[openapi(tag = "status")]
 #[get("/mixnode/<mix_id>/stake-saturation")]
 pub(crate) async fn get_mixnode_stake_saturation(
@@ -227,3 +245,31 @@ pub async fn get_gateways_detailed_unfiltered(
 ) -> Json<Vec<GatewayBondAnnotated>> {
     Json(_get_gateways_detailed_unfiltered(cache).await)
 }
+
+/// Accepts a signed heartbeat of a bonded mixnode or gateway.
+/// Nodes that stop sending them get excluded from the served topology.
+#[openapi(tag = "status")]
+#[post("/heartbeat", data = "<heartbeat>")]
+pub(crate) async fn submit_heartbeat(
+    heartbeat: Json<SignedNodeHeartbeat>,
+    store: &State<HeartbeatStore>,
+    contract_cache: &State<NymContractCache>,
+) -> Result<Json<()>, ErrorResponse> {
+    let signed = heartbeat.into_inner();
+    verify_heartbeat_signature(&signed)?;
+    ensure_bonded(&signed.heartbeat, contract_cache).await?;
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    store.accept(signed.heartbeat, now).await?;
+    Ok(Json(()))
+}
+
+/// Last heartbeats of all the nodes that have been sending them, alongside their liveness.
+#[openapi(tag = "status")]
+#[get("/heartbeats")]
+pub(crate) async fn get_heartbeats(
+    store: &State<HeartbeatStore>,
+) -> Json<Vec<NodeLivenessResponse>> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    Json(store.liveness(now).await)
+}
@@ -6,8 +6,10 @@ use crate::node_status_api::models::{
 };
 use crate::node_status_api::ONE_DAY;
 use crate::storage::NymApiStorage;
+use crate::support::supervisor::TaskSupervisor;
 use log::error;
 use nym_task::{TaskClient, TaskManager};
+use std::sync::Arc;
 use std::time::Duration;
 use time::{OffsetDateTime, PrimitiveDateTime, Time};
 use tokio::time::{interval, sleep};
@@ -118,9 +120,18 @@ impl HistoricalUptimeUpdater {
         }
     }
 
-    pub(crate) fn start(storage: &NymApiStorage, shutdown: &TaskManager) {
-        let uptime_updater = HistoricalUptimeUpdater::new(storage.to_owned());
-        let shutdown_listener = shutdown.subscribe();
-        tokio::spawn(async move { uptime_updater.run(shutdown_listener).await });
+    pub(crate) fn start(
+        storage: &NymApiStorage,
+        supervisor: &TaskSupervisor,
+        shutdown: &TaskManager,
+    ) {
+        let uptime_updater = Arc::new(HistoricalUptimeUpdater::new(storage.to_owned()));
+        supervisor.spawn("historical-uptime-updater", shutdown, move |shutdown| {
+            let uptime_updater = Arc::clone(&uptime_updater);
+            async move {
+                uptime_updater.run(shutdown).await;
+                Ok(())
+            }
+        });
     }
 }
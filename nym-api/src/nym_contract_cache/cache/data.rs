@@ -1,6 +1,7 @@
 // Copyright 2022-2023 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::nym_contract_cache::cache::topology_history::TopologyHistory;
 use crate::support::caching::Cache;
 use nym_mixnet_contract_common::{
     families::FamilyHead, GatewayBond, IdentityKey, Interval, MixId, MixNodeDetails,
@@ -12,9 +13,15 @@ pub(crate) struct ValidatorCacheData {
     pub(crate) mixnodes: Cache<Vec<MixNodeDetails>>,
     pub(crate) gateways: Cache<Vec<GatewayBond>>,
 
+    // nodes blacklisted due to their low reliability
     pub(crate) mixnodes_blacklist: Cache<HashSet<MixId>>,
     pub(crate) gateways_blacklist: Cache<HashSet<IdentityKey>>,
 
+    // nodes blacklisted due to them no longer sending heartbeats. kept separately, so that
+    // neither kind of blacklisting could lift the other one
+    pub(crate) mixnodes_heartbeat_blacklist: HashSet<MixId>,
+    pub(crate) gateways_heartbeat_blacklist: HashSet<IdentityKey>,
+
     pub(crate) rewarded_set: Cache<Vec<MixNodeDetails>>,
     pub(crate) active_set: Cache<Vec<MixNodeDetails>>,
 
@@ -22,6 +29,8 @@ pub(crate) struct ValidatorCacheData {
     pub(crate) current_interval: Cache<Option<Interval>>,
 
     pub(crate) mix_to_family: Cache<Vec<(IdentityKey, FamilyHead)>>,
+
+    pub(crate) topology_history: TopologyHistory,
 }
 
 impl ValidatorCacheData {
@@ -33,9 +42,48 @@ impl ValidatorCacheData {
             active_set: Cache::default(),
             mixnodes_blacklist: Cache::default(),
             gateways_blacklist: Cache::default(),
+            mixnodes_heartbeat_blacklist: HashSet::new(),
+            gateways_heartbeat_blacklist: HashSet::new(),
             current_interval: Cache::default(),
             current_reward_params: Cache::default(),
             mix_to_family: Cache::default(),
+            topology_history: TopologyHistory::default(),
         }
     }
+
+    /// All blacklisted mixnodes, regardless of the reason.
+    pub(crate) fn combined_mixnodes_blacklist(&self) -> Cache<HashSet<MixId>> {
+        let mut blacklist = self.mixnodes_blacklist.clone();
+        blacklist
+            .value
+            .extend(self.mixnodes_heartbeat_blacklist.iter().copied());
+        blacklist
+    }
+
+    /// All blacklisted gateways, regardless of the reason.
+    pub(crate) fn combined_gateways_blacklist(&self) -> Cache<HashSet<IdentityKey>> {
+        let mut blacklist = self.gateways_blacklist.clone();
+        blacklist
+            .value
+            .extend(self.gateways_heartbeat_blacklist.iter().cloned());
+        blacklist
+    }
+
+    /// Records the current active topology, as served to the clients, in the topology history.
+    pub(crate) fn record_topology(&mut self) {
+        let active_set = self
+            .active_set
+            .iter()
+            .map(|mix| mix.bond_information.clone())
+            .collect();
+        let blacklist = self.combined_gateways_blacklist();
+        let gateways = self
+            .gateways
+            .iter()
+            .filter(|gateway| !blacklist.contains(gateway.identity()))
+            .cloned()
+            .collect();
+
+        self.topology_history.record(active_set, gateways)
+    }
 }
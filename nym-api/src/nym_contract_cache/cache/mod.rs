@@ -1,11 +1,12 @@
 use crate::support::caching::Cache;
 use data::ValidatorCacheData;
-use nym_api_requests::models::MixnodeStatus;
+use nym_api_requests::models::{MixnodeStatus, TopologyDiffResponse};
 use nym_mixnet_contract_common::{
     families::FamilyHead, GatewayBond, IdentityKey, Interval, MixId, MixNodeBond, MixNodeDetails,
     RewardingParams,
 };
 use rocket::fairing::AdHoc;
+use snapshot::ContractCacheSnapshot;
 use std::{
     collections::HashSet,
     sync::{
@@ -19,6 +20,8 @@ use tokio::time;
 
 mod data;
 pub(crate) mod refresher;
+mod snapshot;
+mod topology_history;
 
 #[derive(Clone)]
 pub struct NymContractCache {
@@ -59,7 +62,8 @@ impl NymContractCache {
                 cache.active_set.update(active_set);
                 cache.current_reward_params.update(Some(rewarding_params));
                 cache.current_interval.update(Some(current_interval));
-                cache.mix_to_family.update(mix_to_family)
+                cache.mix_to_family.update(mix_to_family);
+                cache.record_topology();
             }
             Err(err) => {
                 error!("{err}");
@@ -67,9 +71,32 @@ impl NymContractCache {
         }
     }
 
+    /// Takes a snapshot of the chain data currently held in the cache.
+    pub(crate) async fn snapshot(&self) -> Option<ContractCacheSnapshot> {
+        match time::timeout(Duration::from_millis(100), self.inner.read()).await {
+            Ok(cache) => Some(ContractCacheSnapshot::new(&cache)),
+            Err(err) => {
+                error!("{err}");
+                None
+            }
+        }
+    }
+
+    /// Populates the cache with previously snapshotted chain data.
+    pub(crate) async fn restore_snapshot(&self, snapshot: ContractCacheSnapshot) {
+        match time::timeout(Duration::from_millis(100), self.inner.write()).await {
+            Ok(mut cache) => snapshot.restore(&mut cache),
+            Err(err) => {
+                error!("{err}");
+            }
+        }
+    }
+
+    /// All blacklisted mixnodes, either due to their low reliability or due to them
+    /// no longer sending heartbeats.
     pub async fn mixnodes_blacklist(&self) -> Cache<HashSet<MixId>> {
         match time::timeout(Duration::from_millis(100), self.inner.read()).await {
-            Ok(cache) => cache.mixnodes_blacklist.clone(),
+            Ok(cache) => cache.combined_mixnodes_blacklist(),
             Err(err) => {
                 error!("{err}");
                 Cache::new(HashSet::new())
@@ -77,9 +104,11 @@ impl NymContractCache {
         }
     }
 
+    /// All blacklisted gateways, either due to their low reliability or due to them
+    /// no longer sending heartbeats.
     pub async fn gateways_blacklist(&self) -> Cache<HashSet<IdentityKey>> {
         match time::timeout(Duration::from_millis(100), self.inner.read()).await {
-            Ok(cache) => cache.gateways_blacklist.clone(),
+            Ok(cache) => cache.combined_gateways_blacklist(),
             Err(err) => {
                 error!("{err}");
                 Cache::new(HashSet::new())
@@ -87,22 +116,19 @@ impl NymContractCache {
         }
     }
 
+    /// Updates the blacklist of the mixnodes with low reliability.
     pub async fn update_mixnodes_blacklist(&self, add: HashSet<MixId>, remove: HashSet<MixId>) {
-        let blacklist = self.mixnodes_blacklist().await;
-        let mut blacklist = blacklist
-            .value
-            .union(&add)
-            .cloned()
-            .collect::<HashSet<MixId>>();
-        let to_remove = blacklist
-            .intersection(&remove)
-            .cloned()
-            .collect::<HashSet<MixId>>();
-        for key in to_remove {
-            blacklist.remove(&key);
-        }
         match time::timeout(Duration::from_millis(100), self.inner.write()).await {
             Ok(mut cache) => {
+                let mut blacklist = cache
+                    .mixnodes_blacklist
+                    .value
+                    .union(&add)
+                    .cloned()
+                    .collect::<HashSet<MixId>>();
+                for key in &remove {
+                    blacklist.remove(key);
+                }
                 cache.mixnodes_blacklist.update(blacklist);
             }
             Err(err) => {
@@ -111,27 +137,25 @@ impl NymContractCache {
         }
     }
 
+    /// Updates the blacklist of the gateways with low reliability.
     pub async fn update_gateways_blacklist(
         &self,
         add: HashSet<IdentityKey>,
         remove: HashSet<IdentityKey>,
     ) {
-        let blacklist = self.gateways_blacklist().await;
-        let mut blacklist = blacklist
-            .value
-            .union(&add)
-            .cloned()
-            .collect::<HashSet<IdentityKey>>();
-        let to_remove = blacklist
-            .intersection(&remove)
-            .cloned()
-            .collect::<HashSet<IdentityKey>>();
-        for key in to_remove {
-            blacklist.remove(&key);
-        }
         match time::timeout(Duration::from_millis(100), self.inner.write()).await {
             Ok(mut cache) => {
+                let mut blacklist = cache
+                    .gateways_blacklist
+                    .value
+                    .union(&add)
+                    .cloned()
+                    .collect::<HashSet<IdentityKey>>();
+                for key in &remove {
+                    blacklist.remove(key);
+                }
                 cache.gateways_blacklist.update(blacklist);
+                cache.record_topology();
             }
             Err(err) => {
                 error!("Failed to update gateways blacklist: {err}");
@@ -139,6 +163,29 @@ impl NymContractCache {
         }
     }
 
+    /// Replaces the set of mixnodes blacklisted due to them no longer sending heartbeats.
+    pub(crate) async fn set_mixnodes_heartbeat_blacklist(&self, blacklist: HashSet<MixId>) {
+        match time::timeout(Duration::from_millis(100), self.inner.write()).await {
+            Ok(mut cache) => cache.mixnodes_heartbeat_blacklist = blacklist,
+            Err(err) => {
+                error!("Failed to update mixnodes heartbeat blacklist: {err}");
+            }
+        }
+    }
+
+    /// Replaces the set of gateways blacklisted due to them no longer sending heartbeats.
+    pub(crate) async fn set_gateways_heartbeat_blacklist(&self, blacklist: HashSet<IdentityKey>) {
+        match time::timeout(Duration::from_millis(100), self.inner.write()).await {
+            Ok(mut cache) => {
+                cache.gateways_heartbeat_blacklist = blacklist;
+                cache.record_topology();
+            }
+            Err(err) => {
+                error!("Failed to update gateways heartbeat blacklist: {err}");
+            }
+        }
+    }
+
     pub async fn mixnodes_filtered(&self) -> Vec<MixNodeDetails> {
         let mixnodes = self.mixnodes_all().await;
         if mixnodes.is_empty() {
@@ -230,6 +277,16 @@ impl NymContractCache {
         }
     }
 
+    pub async fn topology_diff(&self, since: Option<u64>) -> Option<TopologyDiffResponse> {
+        match time::timeout(Duration::from_millis(100), self.inner.read()).await {
+            Ok(cache) => cache.topology_history.diff_since(since),
+            Err(err) => {
+                error!("{err}");
+                None
+            }
+        }
+    }
+
     pub async fn mix_to_family(&self) -> Cache<Vec<(IdentityKey, FamilyHead)>> {
         match time::timeout(Duration::from_millis(100), self.inner.read()).await {
             Ok(cache) => cache.mix_to_family.clone(),
@@ -302,3 +359,37 @@ impl NymContractCache {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reliability_and_heartbeat_blacklists_are_independent() {
+        let cache = NymContractCache::new();
+
+        cache
+            .update_mixnodes_blacklist(HashSet::from([1, 2]), HashSet::new())
+            .await;
+        cache
+            .set_mixnodes_heartbeat_blacklist(HashSet::from([2, 3]))
+            .await;
+        assert_eq!(
+            cache.mixnodes_blacklist().await.value,
+            HashSet::from([1, 2, 3])
+        );
+
+        // the reliable nodes that stopped sending heartbeats stay blacklisted
+        cache
+            .update_mixnodes_blacklist(HashSet::new(), HashSet::from([2, 3]))
+            .await;
+        assert_eq!(
+            cache.mixnodes_blacklist().await.value,
+            HashSet::from([1, 2, 3])
+        );
+
+        // and the unreliable ones that resumed sending heartbeats as well
+        cache.set_mixnodes_heartbeat_blacklist(HashSet::new()).await;
+        assert_eq!(cache.mixnodes_blacklist().await.value, HashSet::from([1]));
+    }
+}
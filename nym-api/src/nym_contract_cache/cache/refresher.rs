@@ -1,9 +1,11 @@
+use super::snapshot::ContractCacheSnapshot;
 use super::NymContractCache;
 use crate::nyxd::Client;
 use crate::support::caching::CacheNotification;
 use anyhow::Result;
 use nym_mixnet_contract_common::{MixId, MixNodeDetails, RewardedSetNodeStatus};
 use nym_task::TaskClient;
+use std::path::PathBuf;
 use std::{collections::HashMap, sync::atomic::Ordering, time::Duration};
 use tokio::sync::watch;
 use tokio::time;
@@ -13,6 +15,9 @@ pub struct NymContractCacheRefresher {
     cache: NymContractCache,
     caching_interval: Duration,
 
+    snapshot_path: PathBuf,
+    snapshot_interval: Duration,
+
     // Notify listeners that the cache has been updated
     update_notifier: watch::Sender<CacheNotification>,
 }
@@ -21,6 +26,8 @@ impl NymContractCacheRefresher {
     pub(crate) fn new(
         nyxd_client: Client,
         caching_interval: Duration,
+        snapshot_path: PathBuf,
+        snapshot_interval: Duration,
         cache: NymContractCache,
     ) -> Self {
         let (tx, _) = watch::channel(CacheNotification::Start);
@@ -28,6 +35,8 @@ impl NymContractCacheRefresher {
             nyxd_client,
             cache,
             caching_interval,
+            snapshot_path,
+            snapshot_interval,
             update_notifier: tx,
         }
     }
@@ -75,6 +84,48 @@ impl NymContractCacheRefresher {
         Ok(())
     }
 
+    /// Populates the cache with the data from the last snapshot, if any, so that it could be served
+    /// while the first refresh is still in progress.
+    async fn restore_snapshot(&self) {
+        let snapshot = match ContractCacheSnapshot::load(&self.snapshot_path) {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => {
+                debug!("There's no validator cache snapshot to restore");
+                return;
+            }
+            Err(err) => {
+                warn!(
+                    "Failed to load validator cache snapshot from {} - {err}",
+                    self.snapshot_path.display()
+                );
+                return;
+            }
+        };
+
+        info!("Restoring validator cache from the snapshot");
+        self.cache.restore_snapshot(snapshot).await;
+
+        if let Err(err) = self.update_notifier.send(CacheNotification::Updated) {
+            warn!("Failed to notify validator cache restoration: {err}");
+        }
+    }
+
+    async fn store_snapshot(&self) {
+        // don't overwrite a potentially valid snapshot with empty data
+        if !self.cache.initialised() {
+            return;
+        }
+        let Some(snapshot) = self.cache.snapshot().await else {
+            return;
+        };
+        if let Err(err) = snapshot.save(&self.snapshot_path) {
+            warn!(
+                "Failed to store validator cache snapshot in {} - {err}",
+                self.snapshot_path.display()
+            );
+        }
+    }
+
     async fn get_rewarded_set_map(&self) -> HashMap<MixId, RewardedSetNodeStatus> {
         self.nyxd_client
             .get_rewarded_set_mixnodes()
@@ -103,9 +154,18 @@ impl NymContractCacheRefresher {
     }
 
     pub(crate) async fn run(&self, mut shutdown: TaskClient) {
+        self.restore_snapshot().await;
+
         let mut interval = time::interval(self.caching_interval);
+        let mut snapshot_interval = time::interval(self.snapshot_interval);
+        // the first tick completes immediately, and there's nothing to store yet anyway
+        snapshot_interval.tick().await;
+
         while !shutdown.is_shutdown() {
             tokio::select! {
+                _ = snapshot_interval.tick() => {
+                    self.store_snapshot().await
+                }
                 _ = interval.tick() => {
                     tokio::select! {
                         biased;
@@ -115,6 +175,7 @@ impl NymContractCacheRefresher {
                         ret = self.refresh() => {
                             if let Err(err) = ret {
                                 error!("Failed to refresh validator cache - {err}");
+                                self.nyxd_client.try_failover().await;
                             } else {
                                 // relaxed memory ordering is fine here. worst case scenario network monitor
                                 // will just have to wait for an additional backoff to see the change.
@@ -129,5 +190,8 @@ impl NymContractCacheRefresher {
                 }
             }
         }
+
+        // make sure the next startup uses the most recent data
+        self.store_snapshot().await
     }
 }
@@ -0,0 +1,102 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::nym_contract_cache::cache::data::ValidatorCacheData;
+use crate::support::caching::Cache;
+use nym_mixnet_contract_common::{
+    families::FamilyHead, GatewayBond, IdentityKey, Interval, MixNodeDetails, RewardingParams,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// On-disk copy of the chain data held by the contract cache, so that a restarted nym-api
+/// could serve requests straight away rather than only after the first refresh completes.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ContractCacheSnapshot {
+    mixnodes: Cache<Vec<MixNodeDetails>>,
+    gateways: Cache<Vec<GatewayBond>>,
+
+    rewarded_set: Cache<Vec<MixNodeDetails>>,
+    active_set: Cache<Vec<MixNodeDetails>>,
+
+    current_reward_params: Cache<Option<RewardingParams>>,
+    current_interval: Cache<Option<Interval>>,
+
+    mix_to_family: Cache<Vec<(IdentityKey, FamilyHead)>>,
+}
+
+impl ContractCacheSnapshot {
+    pub(crate) fn new(data: &ValidatorCacheData) -> Self {
+        ContractCacheSnapshot {
+            mixnodes: data.mixnodes.clone(),
+            gateways: data.gateways.clone(),
+            rewarded_set: data.rewarded_set.clone(),
+            active_set: data.active_set.clone(),
+            current_reward_params: data.current_reward_params.clone(),
+            current_interval: data.current_interval.clone(),
+            mix_to_family: data.mix_to_family.clone(),
+        }
+    }
+
+    /// Overwrites the chain data in the cache with the snapshotted values.
+    /// Note that the original timestamps are preserved so that the data is not mistaken for fresh.
+    pub(crate) fn restore(self, data: &mut ValidatorCacheData) {
+        data.mixnodes = self.mixnodes;
+        data.gateways = self.gateways;
+        data.rewarded_set = self.rewarded_set;
+        data.active_set = self.active_set;
+        data.current_reward_params = self.current_reward_params;
+        data.current_interval = self.current_interval;
+        data.mix_to_family = self.mix_to_family;
+        data.record_topology();
+    }
+
+    pub(crate) fn load<P: AsRef<Path>>(path: P) -> io::Result<Option<Self>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read(path)?;
+        Ok(Some(serde_json::from_slice(&content)?))
+    }
+
+    pub(crate) fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // write to a temporary file first so that we would never leave a partially written snapshot
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(self)?)?;
+        fs::rename(tmp_path, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_survives_roundtrip_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.json");
+        assert!(ContractCacheSnapshot::load(&path).unwrap().is_none());
+
+        let mut data = ValidatorCacheData::new();
+        data.mix_to_family
+            .update(vec![("member".to_string(), FamilyHead::new("head"))]);
+        let timestamp = data.mix_to_family.timestamp();
+        ContractCacheSnapshot::new(&data).save(&path).unwrap();
+
+        let mut restored = ValidatorCacheData::new();
+        ContractCacheSnapshot::load(&path)
+            .unwrap()
+            .unwrap()
+            .restore(&mut restored);
+        assert_eq!(restored.mix_to_family.value, data.mix_to_family.value);
+        assert_eq!(restored.mix_to_family.timestamp(), timestamp);
+    }
+}
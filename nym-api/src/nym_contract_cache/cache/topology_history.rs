@@ -0,0 +1,233 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_api_requests::models::TopologyDiffResponse;
+use nym_mixnet_contract_common::{GatewayBond, IdentityKey, MixId, MixNodeBond};
+use std::collections::{HashMap, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum number of past topologies kept around for computing diffs.
+const MAX_SNAPSHOTS: usize = 16;
+
+struct TopologySnapshot {
+    version: u64,
+    mixnodes: HashMap<MixId, MixNodeBond>,
+    gateways: HashMap<IdentityKey, GatewayBond>,
+}
+
+impl TopologySnapshot {
+    fn is_equivalent(
+        &self,
+        mixnodes: &HashMap<MixId, MixNodeBond>,
+        gateways: &HashMap<IdentityKey, GatewayBond>,
+    ) -> bool {
+        &self.mixnodes == mixnodes && &self.gateways == gateways
+    }
+}
+
+/// Recent versions of the active topology, i.e. the active set mixnodes alongside the available
+/// gateways, used for serving incremental updates to the clients.
+#[derive(Default)]
+pub(crate) struct TopologyHistory {
+    snapshots: VecDeque<TopologySnapshot>,
+}
+
+fn current_version() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn diff_map<K, V>(old: &HashMap<K, V>, new: &HashMap<K, V>) -> (Vec<V>, Vec<K>)
+where
+    K: Clone + Eq + std::hash::Hash,
+    V: Clone + PartialEq,
+{
+    let updated = new
+        .iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(_, value)| value.clone())
+        .collect();
+    let removed = old
+        .keys()
+        .filter(|key| !new.contains_key(*key))
+        .cloned()
+        .collect();
+    (updated, removed)
+}
+
+impl TopologyHistory {
+    /// Records the provided topology as the newest version, unless it's identical to the current one.
+    pub(crate) fn record(&mut self, active_set: Vec<MixNodeBond>, gateways: Vec<GatewayBond>) {
+        let mixnodes = active_set
+            .into_iter()
+            .map(|bond| (bond.mix_id, bond))
+            .collect::<HashMap<_, _>>();
+        let gateways = gateways
+            .into_iter()
+            .map(|bond| (bond.identity().clone(), bond))
+            .collect::<HashMap<_, _>>();
+
+        let mut version = current_version();
+        if let Some(latest) = self.snapshots.back() {
+            if latest.is_equivalent(&mixnodes, &gateways) {
+                return;
+            }
+            // make sure versions are strictly increasing even if the clock misbehaves
+            version = version.max(latest.version + 1);
+        }
+
+        if self.snapshots.len() == MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(TopologySnapshot {
+            version,
+            mixnodes,
+            gateways,
+        });
+    }
+
+    /// Computes changes to the topology since the provided version. If the version is not known,
+    /// the returned diff contains the entire current topology.
+    /// Returns `None` if no topology has been recorded yet.
+    pub(crate) fn diff_since(&self, since: Option<u64>) -> Option<TopologyDiffResponse> {
+        let latest = self.snapshots.back()?;
+        let base = since.and_then(|since| {
+            self.snapshots
+                .iter()
+                .find(|snapshot| snapshot.version == since)
+        });
+
+        let empty_mixnodes = HashMap::new();
+        let empty_gateways = HashMap::new();
+        let (base_mixnodes, base_gateways) = match base {
+            Some(base) => (&base.mixnodes, &base.gateways),
+            None => (&empty_mixnodes, &empty_gateways),
+        };
+
+        let (updated_mixnodes, removed_mixnodes) = diff_map(base_mixnodes, &latest.mixnodes);
+        let (updated_gateways, removed_gateways) = diff_map(base_gateways, &latest.gateways);
+
+        Some(TopologyDiffResponse {
+            version: latest.version,
+            full: base.is_none(),
+            updated_mixnodes,
+            removed_mixnodes,
+            updated_gateways,
+            removed_gateways,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nym_mixnet_contract_common::{Addr, Coin, Gateway, Layer, MixNode};
+
+    fn mixnode(mix_id: MixId, host: &str) -> MixNodeBond {
+        MixNodeBond {
+            mix_id,
+            owner: Addr::unchecked("owner"),
+            original_pledge: Coin::new(100, "unym"),
+            layer: Layer::One,
+            mix_node: MixNode {
+                host: host.to_string(),
+                mix_port: 1789,
+                verloc_port: 1790,
+                http_api_port: 8000,
+                sphinx_key: "sphinx".to_string(),
+                identity_key: format!("mix{mix_id}"),
+                version: "1.1.16".to_string(),
+            },
+            proxy: None,
+            bonding_height: 1,
+            is_unbonding: false,
+        }
+    }
+
+    fn gateway(identity: &str) -> GatewayBond {
+        GatewayBond {
+            pledge_amount: Coin::new(100, "unym"),
+            owner: Addr::unchecked("owner"),
+            block_height: 1,
+            gateway: Gateway {
+                host: "1.1.1.1".to_string(),
+                mix_port: 1789,
+                clients_port: 9000,
+                location: "earth".to_string(),
+                sphinx_key: "sphinx".to_string(),
+                identity_key: identity.to_string(),
+                version: "1.1.16".to_string(),
+            },
+            proxy: None,
+        }
+    }
+
+    #[test]
+    fn empty_history_has_no_diff() {
+        assert!(TopologyHistory::default().diff_since(None).is_none())
+    }
+
+    #[test]
+    fn unknown_version_results_in_full_diff() {
+        let mut history = TopologyHistory::default();
+        history.record(vec![mixnode(1, "1.1.1.1")], vec![gateway("gw1")]);
+
+        let diff = history.diff_since(Some(42)).unwrap();
+        assert!(diff.full);
+        assert_eq!(diff.updated_mixnodes.len(), 1);
+        assert_eq!(diff.updated_gateways.len(), 1);
+        assert!(diff.removed_mixnodes.is_empty());
+        assert!(diff.removed_gateways.is_empty());
+    }
+
+    #[test]
+    fn diff_contains_only_changes() {
+        let mut history = TopologyHistory::default();
+        history.record(
+            vec![mixnode(1, "1.1.1.1"), mixnode(2, "2.2.2.2")],
+            vec![gateway("gw1"), gateway("gw2")],
+        );
+        let initial_version = history.diff_since(None).unwrap().version;
+
+        history.record(
+            vec![mixnode(1, "1.1.1.1"), mixnode(3, "3.3.3.3")],
+            vec![gateway("gw1")],
+        );
+
+        let diff = history.diff_since(Some(initial_version)).unwrap();
+        assert!(!diff.full);
+        assert!(diff.version > initial_version);
+        assert_eq!(diff.updated_mixnodes, vec![mixnode(3, "3.3.3.3")]);
+        assert_eq!(diff.removed_mixnodes, vec![2]);
+        assert!(diff.updated_gateways.is_empty());
+        assert_eq!(diff.removed_gateways, vec!["gw2".to_string()]);
+    }
+
+    #[test]
+    fn identical_topologies_are_not_recorded() {
+        let mut history = TopologyHistory::default();
+        history.record(vec![mixnode(1, "1.1.1.1")], vec![gateway("gw1")]);
+        let version = history.diff_since(None).unwrap().version;
+
+        history.record(vec![mixnode(1, "1.1.1.1")], vec![gateway("gw1")]);
+        let diff = history.diff_since(Some(version)).unwrap();
+        assert_eq!(diff.version, version);
+        assert!(diff.updated_mixnodes.is_empty());
+        assert!(diff.updated_gateways.is_empty());
+    }
+
+    #[test]
+    fn old_versions_are_forgotten() {
+        let mut history = TopologyHistory::default();
+        history.record(vec![mixnode(0, "1.1.1.1")], vec![]);
+        let first_version = history.diff_since(None).unwrap().version;
+
+        for i in 1..=MAX_SNAPSHOTS as MixId {
+            history.record(vec![mixnode(i, "1.1.1.1")], vec![]);
+        }
+
+        assert!(history.diff_since(Some(first_version)).unwrap().full);
+    }
+}
@@ -3,12 +3,14 @@
 
 use crate::nym_contract_cache::cache::NymContractCache;
 use crate::support::config::Config;
+use crate::support::supervisor::TaskSupervisor;
 use crate::support::{self, nyxd};
 use nym_task::TaskManager;
 use okapi::openapi3::OpenApi;
 use rocket::Route;
 use rocket_okapi::openapi_get_routes_spec;
 use rocket_okapi::settings::OpenApiSettings;
+use std::sync::Arc;
 
 use self::cache::refresher::NymContractCacheRefresher;
 
@@ -20,6 +22,8 @@ pub(crate) fn nym_contract_cache_routes(settings: &OpenApiSettings) -> (Vec<Rout
         settings: routes::get_mixnodes,
         routes::get_mixnodes_detailed,
         routes::get_gateways,
+        routes::get_topology_diff,
+        routes::get_mixnode_families,
         routes::get_active_set,
         routes::get_active_set_detailed,
         routes::get_rewarded_set,
@@ -35,16 +39,25 @@ pub(crate) fn start_refresher(
     config: &Config,
     nym_contract_cache_state: &NymContractCache,
     nyxd_client: nyxd::Client,
+    supervisor: &TaskSupervisor,
     shutdown: &TaskManager,
 ) -> tokio::sync::watch::Receiver<support::caching::CacheNotification> {
     let nym_contract_cache_refresher = NymContractCacheRefresher::new(
         nyxd_client,
         config.get_topology_caching_interval(),
+        config.get_topology_snapshot_path(),
+        config.get_topology_snapshot_interval(),
         nym_contract_cache_state.to_owned(),
     );
     let nym_contract_cache_listener = nym_contract_cache_refresher.subscribe();
-    let shutdown_listener = shutdown.subscribe();
-    tokio::spawn(async move { nym_contract_cache_refresher.run(shutdown_listener).await });
+    let nym_contract_cache_refresher = Arc::new(nym_contract_cache_refresher);
+    supervisor.spawn("nym-contract-cache-refresher", shutdown, move |shutdown| {
+        let nym_contract_cache_refresher = Arc::clone(&nym_contract_cache_refresher);
+        async move {
+            nym_contract_cache_refresher.run(shutdown).await;
+            Ok(())
+        }
+    });
 
     nym_contract_cache_listener
 }
@@ -4,16 +4,19 @@
 use crate::{
     node_status_api::{
         helpers::{_get_active_set_detailed, _get_mixnodes_detailed, _get_rewarded_set_detailed},
+        models::ErrorResponse,
         NodeStatusCache,
     },
     nym_contract_cache::cache::NymContractCache,
 };
-use nym_api_requests::models::MixNodeBondAnnotated;
+use nym_api_requests::models::{
+    MixNodeBondAnnotated, MixNodeFamilyMembership, TopologyDiffResponse,
+};
 use nym_mixnet_contract_common::{
     mixnode::MixNodeDetails, reward_params::RewardingParams, GatewayBond, Interval, MixId,
 };
 
-use rocket::{serde::json::Json, State};
+use rocket::{http::Status, serde::json::Json, State};
 use rocket_okapi::openapi;
 use std::collections::HashSet;
 
@@ -44,6 +47,42 @@ pub async fn get_gateways(cache: &State<NymContractCache>) -> Json<Vec<GatewayBo
     Json(cache.gateways_filtered().await)
 }
 
+/// Returns changes to the active topology (active set mixnodes and gateways) since the specified
+/// version. If the version is not provided or is no longer known, the entire topology is returned.
+#[openapi(tag = "contract-cache")]
+#[get("/topology/diff?<since>")]
+pub async fn get_topology_diff(
+    cache: &State<NymContractCache>,
+    since: Option<u64>,
+) -> Result<Json<TopologyDiffResponse>, ErrorResponse> {
+    cache.topology_diff(since).await.map(Json).ok_or_else(|| {
+        ErrorResponse::new(
+            "the topology is not available yet",
+            Status::ServiceUnavailable,
+        )
+    })
+}
+
+/// Returns family membership, as declared on chain, of all mixnodes that belong to any family.
+#[openapi(tag = "contract-cache")]
+#[get("/mixnodes/families")]
+pub async fn get_mixnode_families(
+    cache: &State<NymContractCache>,
+) -> Json<Vec<MixNodeFamilyMembership>> {
+    Json(
+        cache
+            .mix_to_family()
+            .await
+            .value
+            .into_iter()
+            .map(|(identity_key, family_head)| MixNodeFamilyMembership {
+                identity_key,
+                family_head,
+            })
+            .collect(),
+    )
+}
+
 #[openapi(tag = "contract-cache")]
 #[get("/mixnodes/rewarded")]
 pub async fn get_rewarded_set(cache: &State<NymContractCache>) -> Json<Vec<MixNodeDetails>> {
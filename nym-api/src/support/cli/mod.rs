@@ -4,16 +4,30 @@
 use super::config::Config;
 use ::nym_config::defaults::var_names::{MIXNET_CONTRACT_ADDRESS, VESTING_CONTRACT_ADDRESS};
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use lazy_static::lazy_static;
-use nym_bin_common::build_information::BinaryBuildInformation;
+use nym_bin_common::build_information::{protocols, BinaryBuildInformation, BuildInfo};
 use nym_config::{NymConfig, OptionalSet};
 use nym_validator_client::nyxd;
 use std::fs;
 
+pub(crate) const BUILD_INFORMATION: BinaryBuildInformation =
+    BinaryBuildInformation::new(env!("CARGO_PKG_VERSION"))
+        .with_enabled_features(&[
+            #[cfg(feature = "no-reward")]
+            "no-reward",
+            #[cfg(feature = "tokio-console")]
+            "tokio-console",
+            #[cfg(feature = "generate-ts")]
+            "generate-ts",
+        ])
+        .with_protocol_versions(&[(
+            protocols::SPHINX_PACKET,
+            nym_sphinx::params::CURRENT_PACKET_VERSION_NUMBER,
+        )]);
+
 lazy_static! {
-    pub static ref PRETTY_BUILD_INFORMATION: String =
-        BinaryBuildInformation::new(env!("CARGO_PKG_VERSION")).pretty_print();
+    pub static ref PRETTY_BUILD_INFORMATION: String = BUILD_INFORMATION.pretty_print();
 }
 
 // Helper for passing LONG_VERSION to clap
@@ -58,6 +72,20 @@ pub(crate) struct CliArgs {
     #[clap(long)]
     pub(crate) nyxd_validator: Option<url::Url>,
 
+    /// Comma separated list of additional nyxd endpoints used for submitting transactions
+    /// in case the main one becomes unavailable
+    #[clap(long, value_delimiter = ',')]
+    pub(crate) nyxd_failover_validators: Option<Vec<url::Url>>,
+
+    /// Comma separated list of nyxd endpoints used exclusively for chain queries
+    #[clap(long, value_delimiter = ',')]
+    pub(crate) nyxd_query_validators: Option<Vec<url::Url>>,
+
+    /// Name of the network profile, defined in the config file, to run against.
+    /// Its contract addresses take precedence over `--mixnet-contract` and `--vesting-contract`
+    #[clap(long, conflicts_with_all = &["mixnet_contract", "vesting_contract"])]
+    pub(crate) network: Option<String>,
+
     /// Address of the mixnet contract managing the network
     #[clap(long)]
     pub(crate) mixnet_contract: Option<nyxd::AccountId>,
@@ -72,6 +100,18 @@ pub(crate) struct CliArgs {
     #[clap(long)]
     pub(crate) mnemonic: Option<bip39::Mnemonic>,
 
+    /// Denomination in which the transaction fees are paid, if different from the network's mix denomination
+    #[clap(long)]
+    pub(crate) fee_denom: Option<String>,
+
+    /// Amount of the fee denomination paid per unit of gas
+    #[clap(long)]
+    pub(crate) gas_price_amount: Option<f64>,
+
+    /// Multiplier applied to the simulated gas usage of transactions in order to determine their gas limit
+    #[clap(long)]
+    pub(crate) fee_adjustment: Option<f32>,
+
     /// Specifies whether a config file based on provided arguments should be saved to a file
     #[clap(short = 'w', long)]
     pub(crate) save_config: bool,
@@ -104,9 +144,77 @@ pub(crate) struct CliArgs {
         hide = true
     )]
     pub(crate) enable_coconut: Option<bool>,
+
+    /// Specifies whether the coconut secret key is stored encrypted with a passphrase
+    #[clap(long, hide = true)]
+    pub(crate) encrypt_coconut_key: Option<bool>,
+
+    /// Socket address on which the HTTP API is going to listen
+    #[clap(long)]
+    pub(crate) bind_address: Option<std::net::SocketAddr>,
+
+    /// Comma separated list of addresses of the reverse proxies whose `X-Forwarded-For` headers are trusted
+    #[clap(long, value_delimiter = ',')]
+    pub(crate) trusted_proxies: Option<Vec<std::net::IpAddr>>,
+
+    /// Specifies whether the per-endpoint performance metrics are exposed under `/v1/status/api-performance`
+    #[clap(long)]
+    pub(crate) expose_api_performance: Option<bool>,
+
+    #[clap(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+#[derive(Subcommand, Clone, Copy)]
+pub(crate) enum Command {
+    /// Show build information of this binary
+    BuildInfo(BuildInfo),
+
+    /// Commands related to the coconut signer authority
+    #[clap(subcommand)]
+    Coconut(CoconutCommand),
+}
+
+#[derive(Subcommand, Clone, Copy)]
+pub(crate) enum CoconutCommand {
+    /// Verifies the stored coconut and DKG keys against the state of the DKG contract
+    /// and reports whether the signer is ready for credential issuance
+    SelfCheck,
+
+    /// Commands related to the distributed key generation
+    #[clap(subcommand)]
+    Dkg(DkgCommand),
+}
+
+#[derive(Subcommand, Clone, Copy)]
+pub(crate) enum DkgCommand {
+    /// Runs the whole DKG ceremony against a simulated contract, without submitting anything
+    /// on chain, to verify the DKG keys, system clock and configuration ahead of the real one
+    Simulate,
 }
 
-pub(crate) fn build_config(args: CliArgs) -> Result<Config> {
+pub(crate) async fn execute(command: Command, config: &Config) -> Result<()> {
+    match command {
+        Command::BuildInfo(m) => m.execute(&BUILD_INFORMATION),
+        Command::Coconut(CoconutCommand::SelfCheck) => {
+            let report = crate::coconut::self_check::self_check(config).await?;
+            println!("{report}");
+            if !report.ready_for_issuance() {
+                anyhow::bail!("the coconut signer self-check has failed")
+            }
+        }
+        Command::Coconut(CoconutCommand::Dkg(DkgCommand::Simulate)) => {
+            let report = crate::coconut::dkg::simulation::simulate(config).await?;
+            println!("{report}");
+            if !report.succeeded() {
+                anyhow::bail!("the DKG simulation has failed")
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) async fn build_config(args: CliArgs) -> Result<Config> {
     let id = args.id.clone();
 
     // try to load config from the file, if it doesn't exist, use default values
@@ -132,6 +240,18 @@ pub(crate) fn build_config(args: CliArgs) -> Result<Config> {
             .expect("Could not create config directory");
         fs::create_dir_all(Config::default_data_directory(&id))
             .expect("Could not create data directory");
+    }
+
+    // make sure we never mix up the keys and caches of different networks
+    config.validate_network_profile()?;
+
+    // all of the contract addresses (including the mixnet contract the data directories are bound to)
+    // have to be known before anything gets written to the data directories or the clients get created.
+    // the discovered addresses must belong to the chain of the profile as much as the explicit ones
+    let config = crate::support::nyxd::discover_contracts(config).await?;
+    config.validate_network_profile()?;
+
+    if !already_initialized {
         crate::coconut::dkg::controller::init_keypair(&config)?;
     }
 
@@ -142,6 +262,14 @@ pub(crate) fn override_config(config: Config, args: CliArgs) -> Config {
     config
         .with_id(&args.id)
         .with_optional(Config::with_custom_nyxd_validator, args.nyxd_validator)
+        .with_optional(
+            Config::with_custom_signing_failover_validators,
+            args.nyxd_failover_validators,
+        )
+        .with_optional(
+            Config::with_custom_query_validators,
+            args.nyxd_query_validators,
+        )
         .with_optional_env(
             Config::with_custom_mixnet_contract,
             args.mixnet_contract,
@@ -152,7 +280,11 @@ pub(crate) fn override_config(config: Config, args: CliArgs) -> Config {
             args.vesting_contract,
             VESTING_CONTRACT_ADDRESS,
         )
+        .with_optional(Config::with_network_profile, args.network)
         .with_optional(Config::with_mnemonic, args.mnemonic)
+        .with_optional(Config::with_fee_denom, args.fee_denom)
+        .with_optional(Config::with_gas_price_amount, args.gas_price_amount)
+        .with_optional(Config::with_fee_adjustment, args.fee_adjustment)
         .with_optional(
             Config::with_minimum_interval_monitor_threshold,
             args.monitor_threshold,
@@ -173,4 +305,14 @@ pub(crate) fn override_config(config: Config, args: CliArgs) -> Config {
         )
         .with_optional(Config::with_announce_address, args.announce_address)
         .with_optional(Config::with_coconut_signer_enabled, args.enable_coconut)
+        .with_optional(
+            Config::with_encrypt_coconut_secret_key,
+            args.encrypt_coconut_key,
+        )
+        .with_optional(Config::with_http_bind_address, args.bind_address)
+        .with_optional(Config::with_trusted_proxies, args.trusted_proxies)
+        .with_optional(
+            Config::with_expose_api_performance,
+            args.expose_api_performance,
+        )
 }
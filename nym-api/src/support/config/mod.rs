@@ -1,21 +1,31 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use self::network_profile::ensure_directory_belongs_to_network;
 use self::template::config_template;
+pub use network_profile::{DiscoveredContracts, NetworkProfile, NetworkProfileError};
 use nym_config::defaults::mainnet::{MIXNET_CONTRACT_ADDRESS, VESTING_CONTRACT_ADDRESS};
-use nym_config::defaults::DEFAULT_NYM_API_PORT;
+use nym_config::defaults::{DEFAULT_NYM_API_PORT, GAS_PRICE_AMOUNT};
+use nym_config::paths::default_nym_directory;
 use nym_config::NymConfig;
 use nym_validator_client::nyxd;
+use nym_validator_client::nyxd::fee::DEFAULT_SIMULATED_GAS_MULTIPLIER;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use url::Url;
 
+mod network_profile;
 mod template;
 
 pub const DEFAULT_LOCAL_VALIDATOR: &str = "http://localhost:26657";
 
 pub const DEFAULT_DKG_CONTRACT_POLLING_RATE: Duration = Duration::from_secs(10);
+pub const DEFAULT_DKG_SUBMISSION_DEADLINE: Duration = Duration::from_secs(5 * 60);
+pub const DEFAULT_DEPOSIT_INDEXER_POLLING_RATE: Duration = Duration::from_secs(30);
+const DEFAULT_ISSUANCE_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
 
 const DEFAULT_GATEWAY_SENDING_RATE: usize = 200;
 const DEFAULT_MAX_CONCURRENT_GATEWAY_CLIENTS: usize = 50;
@@ -35,17 +45,22 @@ const DEFAULT_ROUTE_TEST_PACKETS: usize = 1000;
 const DEFAULT_PER_NODE_TEST_PACKETS: usize = 3;
 
 const DEFAULT_TOPOLOGY_CACHE_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_TOPOLOGY_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5 * 60);
 const DEFAULT_NODE_STATUS_CACHE_INTERVAL: Duration = Duration::from_secs(120);
+const DEFAULT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 const DEFAULT_CIRCULATING_SUPPLY_CACHE_INTERVAL: Duration = Duration::from_secs(3600);
 const DEFAULT_MONITOR_THRESHOLD: u8 = 60;
 const DEFAULT_MIN_MIXNODE_RELIABILITY: u8 = 50;
 const DEFAULT_MIN_GATEWAY_RELIABILITY: u8 = 20;
 
-#[derive(Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Config {
     #[serde(default)]
     base: Base,
 
+    #[serde(default)]
+    fees: Fees,
+
     #[serde(default)]
     network_monitor: NetworkMonitor,
 
@@ -63,6 +78,19 @@ pub struct Config {
 
     #[serde(default)]
     coconut_signer: CoconutSigner,
+
+    #[serde(default)]
+    http: Http,
+
+    /// Named networks (such as mainnet or a testnet) this nym-api can be run against,
+    /// one of which can be selected with `base.network_profile`.
+    #[serde(default)]
+    network_profiles: BTreeMap<String, NetworkProfile>,
+
+    /// Contract addresses of the selected network profile that have been discovered on chain.
+    /// They're never saved, so that they would be discovered again on every startup.
+    #[serde(skip)]
+    discovered_contracts: Option<DiscoveredContracts>,
 }
 
 impl NymConfig for Config {
@@ -71,14 +99,11 @@ impl NymConfig for Config {
     }
 
     fn default_root_directory() -> PathBuf {
-        dirs::home_dir()
-            .expect("Failed to evaluate $HOME value")
-            .join(".nym")
-            .join("nym-api")
+        default_nym_directory("nym-api").expect("no data directory known for this OS")
     }
 
     fn try_default_root_directory() -> Option<PathBuf> {
-        dirs::home_dir().map(|path| path.join(".nym").join("nym-api"))
+        default_nym_directory("nym-api")
     }
 
     fn root_directory(&self) -> PathBuf {
@@ -102,11 +127,24 @@ pub struct Base {
 
     local_validator: Url,
 
+    /// Additional validators used for submitting transactions (such as DKG dealings)
+    /// in case `local_validator` becomes unavailable.
+    signing_failover_validators: Vec<Url>,
+
+    /// Validators used exclusively for chain queries, such as refreshing the caches,
+    /// so that heavy read traffic doesn't compete with time-sensitive transactions.
+    /// If empty, `local_validator` and its failovers are used instead.
+    query_validators: Vec<Url>,
+
     /// Address announced to the directory server for the clients to connect to.
     // It is useful, say, in NAT scenarios or wanting to more easily update actual IP address
     // later on by using name resolvable with a DNS query, such as `nymtech.net`.
     announce_address: Url,
 
+    /// Name of the network profile used by this nym-api. If set, the chain details and
+    /// contract addresses of the profile take precedence over the ones specified here.
+    network_profile: Option<String>,
+
     /// Address of the validator contract managing the network
     mixnet_contract_address: nyxd::AccountId,
 
@@ -130,7 +168,10 @@ impl Default for Base {
         Base {
             id: String::default(),
             local_validator: default_validator,
+            signing_failover_validators: Vec::new(),
+            query_validators: Vec::new(),
             announce_address: default_announce_address,
+            network_profile: None,
             mixnet_contract_address: MIXNET_CONTRACT_ADDRESS.parse().unwrap(),
             vesting_contract_address: VESTING_CONTRACT_ADDRESS.parse().unwrap(),
             mnemonic: bip39::Mnemonic::generate(24).unwrap(),
@@ -138,6 +179,32 @@ impl Default for Base {
     }
 }
 
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Fees {
+    /// Denomination in which the fees of all the transactions (such as DKG dealings
+    /// or credential proposal votes) are paid.
+    /// If not set, the base mix denomination of the network is used.
+    denom: Option<String>,
+
+    /// Amount of the fee denomination paid per unit of gas.
+    gas_price_amount: f64,
+
+    /// Multiplier applied to the simulated gas usage of a transaction in order to determine
+    /// its gas limit.
+    fee_adjustment: f32,
+}
+
+impl Default for Fees {
+    fn default() -> Self {
+        Fees {
+            denom: None,
+            gas_price_amount: GAS_PRICE_AMOUNT,
+            fee_adjustment: DEFAULT_SIMULATED_GAS_MULTIPLIER,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default)]
 pub struct NetworkMonitor {
@@ -238,6 +305,11 @@ pub struct NodeStatusAPI {
 
     #[serde(with = "humantime_serde")]
     caching_interval: Duration,
+
+    /// Specifies the duration after which a node that stopped sending heartbeats is considered
+    /// inactive and is excluded from the served topology.
+    #[serde(with = "humantime_serde")]
+    heartbeat_timeout: Duration,
 }
 
 impl NodeStatusAPI {
@@ -253,6 +325,7 @@ impl Default for NodeStatusAPI {
         NodeStatusAPI {
             database_path: Default::default(),
             caching_interval: DEFAULT_NODE_STATUS_CACHE_INTERVAL,
+            heartbeat_timeout: DEFAULT_HEARTBEAT_TIMEOUT,
         }
     }
 }
@@ -262,12 +335,30 @@ impl Default for NodeStatusAPI {
 pub struct TopologyCacher {
     #[serde(with = "humantime_serde")]
     caching_interval: Duration,
+
+    /// Path to the file containing the snapshot of the cached chain data, used for serving
+    /// requests straight after startup, before the first refresh completes.
+    snapshot_path: PathBuf,
+
+    /// Specifies the interval at which the snapshot of the cached chain data is stored on disk.
+    #[serde(with = "humantime_serde")]
+    snapshot_interval: Duration,
+}
+
+impl TopologyCacher {
+    pub const SNAPSHOT_FILE: &'static str = "topology_snapshot.json";
+
+    fn default_snapshot_path(id: &str) -> PathBuf {
+        Config::default_data_directory(id).join(Self::SNAPSHOT_FILE)
+    }
 }
 
 impl Default for TopologyCacher {
     fn default() -> Self {
         TopologyCacher {
             caching_interval: DEFAULT_TOPOLOGY_CACHE_INTERVAL,
+            snapshot_path: Default::default(),
+            snapshot_interval: DEFAULT_TOPOLOGY_SNAPSHOT_INTERVAL,
         }
     }
 }
@@ -326,6 +417,11 @@ pub struct CoconutSigner {
     /// Path to the coconut secret key.
     secret_key_path: PathBuf,
 
+    /// Specifies whether the coconut secret key is stored encrypted with a passphrase.
+    /// The passphrase is read from the `NYM_API_COCONUT_KEY_PASSPHRASE` environment variable
+    /// or, if it's not set, asked for on startup.
+    encrypt_secret_key: bool,
+
     /// Path to the dkg dealer decryption key.
     decryption_key_path: PathBuf,
 
@@ -333,7 +429,22 @@ pub struct CoconutSigner {
     public_key_with_proof_path: PathBuf,
 
     /// Duration of the interval for polling the dkg contract.
+    #[serde(with = "humantime_serde")]
     dkg_contract_polling_rate: Duration,
+
+    /// How long, after the dealings or the verification key shares were due, to wait for at least
+    /// threshold of them to appear on chain before aborting the DKG round and waiting for the next epoch.
+    #[serde(with = "humantime_serde")]
+    dkg_submission_deadline: Duration,
+
+    /// Duration of the interval for polling the chain for new bandwidth deposits.
+    #[serde(with = "humantime_serde")]
+    deposit_indexer_polling_rate: Duration,
+
+    /// Duration of the interval for verifying the consistency of the DKG state. If it's found
+    /// to be inconsistent, the credential issuance is suspended until it's resolved.
+    #[serde(with = "humantime_serde")]
+    issuance_health_check_interval: Duration,
 }
 
 impl CoconutSigner {
@@ -371,13 +482,51 @@ impl Default for CoconutSigner {
             dkg_persistent_state_path: Default::default(),
             verification_key_path: Default::default(),
             secret_key_path: Default::default(),
+            encrypt_secret_key: false,
             decryption_key_path: Default::default(),
             public_key_with_proof_path: Default::default(),
             dkg_contract_polling_rate: DEFAULT_DKG_CONTRACT_POLLING_RATE,
+            dkg_submission_deadline: DEFAULT_DKG_SUBMISSION_DEADLINE,
+            deposit_indexer_polling_rate: DEFAULT_DEPOSIT_INDEXER_POLLING_RATE,
+            issuance_health_check_interval: DEFAULT_ISSUANCE_HEALTH_CHECK_INTERVAL,
         }
     }
 }
 
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct Http {
+    /// Socket address on which the HTTP API is going to listen.
+    /// If not set, the `ROCKET_ADDRESS` and `ROCKET_PORT` environment variables
+    /// (or rocket's defaults) are used instead.
+    bind_address: Option<SocketAddr>,
+
+    /// Addresses of the reverse proxies placed in front of this API. Only the `X-Forwarded-For`
+    /// headers set by those are trusted when determining the address of the client.
+    /// Note that if the proxy is running on the same machine, its address must be specified here,
+    /// otherwise all requests would appear to be coming from the localhost.
+    trusted_proxies: Vec<IpAddr>,
+
+    /// Path to the PEM-encoded certificate chain used for terminating TLS connections.
+    /// Must be specified alongside `tls_private_key_path`.
+    tls_certificate_path: Option<PathBuf>,
+
+    /// Path to the PEM-encoded private key of the TLS certificate.
+    tls_private_key_path: Option<PathBuf>,
+
+    /// Directory from which the responses to the ACME HTTP-01 challenges are served
+    /// (under `/.well-known/acme-challenge`).
+    /// The API does not issue or renew the certificates by itself. That has to be done by an external
+    /// ACME client, such as `certbot certonly --webroot`, writing its challenges into this directory
+    /// and the resulting certificate to `tls_certificate_path` and `tls_private_key_path`.
+    /// Note that renewed certificates are only picked up on restart.
+    acme_challenge_directory: Option<PathBuf>,
+
+    /// Specifies whether the per-endpoint request counts, error rates and latency histograms
+    /// are exposed under `/v1/status/api-performance`.
+    expose_api_performance: bool,
+}
+
 impl Config {
     pub fn new() -> Self {
         Config::default()
@@ -386,6 +535,7 @@ impl Config {
     pub fn with_id(mut self, id: &str) -> Self {
         self.base.id = id.to_string();
         self.node_status_api.database_path = NodeStatusAPI::default_database_path(id);
+        self.topology_cacher.snapshot_path = TopologyCacher::default_snapshot_path(id);
         self.network_monitor.credentials_database_path =
             NetworkMonitor::default_credentials_database_path(id);
         self.coconut_signer.dkg_persistent_state_path =
@@ -420,11 +570,26 @@ impl Config {
         self
     }
 
+    pub fn with_encrypt_coconut_secret_key(mut self, encrypt_secret_key: bool) -> Self {
+        self.coconut_signer.encrypt_secret_key = encrypt_secret_key;
+        self
+    }
+
     pub fn with_custom_nyxd_validator(mut self, validator: Url) -> Self {
         self.base.local_validator = validator;
         self
     }
 
+    pub fn with_custom_signing_failover_validators(mut self, validators: Vec<Url>) -> Self {
+        self.base.signing_failover_validators = validators;
+        self
+    }
+
+    pub fn with_custom_query_validators(mut self, validators: Vec<Url>) -> Self {
+        self.base.query_validators = validators;
+        self
+    }
+
     pub fn with_announce_address(mut self, announce_address: Url) -> Self {
         self.base.announce_address = announce_address;
         self
@@ -440,11 +605,31 @@ impl Config {
         self
     }
 
+    pub fn with_network_profile(mut self, network_profile: String) -> Self {
+        self.base.network_profile = Some(network_profile);
+        self
+    }
+
     pub fn with_mnemonic(mut self, mnemonic: bip39::Mnemonic) -> Self {
         self.base.mnemonic = mnemonic;
         self
     }
 
+    pub fn with_fee_denom(mut self, denom: String) -> Self {
+        self.fees.denom = Some(denom);
+        self
+    }
+
+    pub fn with_gas_price_amount(mut self, gas_price_amount: f64) -> Self {
+        self.fees.gas_price_amount = gas_price_amount;
+        self
+    }
+
+    pub fn with_fee_adjustment(mut self, fee_adjustment: f32) -> Self {
+        self.fees.fee_adjustment = fee_adjustment;
+        self
+    }
+
     pub fn with_minimum_interval_monitor_threshold(mut self, threshold: u8) -> Self {
         self.rewarding.minimum_interval_monitor_threshold = threshold;
         self
@@ -460,6 +645,21 @@ impl Config {
         self
     }
 
+    pub fn with_http_bind_address(mut self, bind_address: SocketAddr) -> Self {
+        self.http.bind_address = Some(bind_address);
+        self
+    }
+
+    pub fn with_trusted_proxies(mut self, trusted_proxies: Vec<IpAddr>) -> Self {
+        self.http.trusted_proxies = trusted_proxies;
+        self
+    }
+
+    pub fn with_expose_api_performance(mut self, expose_api_performance: bool) -> Self {
+        self.http.expose_api_performance = expose_api_performance;
+        self
+    }
+
     pub fn get_id(&self) -> String {
         self.base.id.clone()
     }
@@ -484,26 +684,123 @@ impl Config {
         self.rewarding.enabled
     }
 
-    pub fn get_nyxd_url(&self) -> Url {
-        self.base.local_validator.clone()
+    /// Returns the ordered list of validators used for submitting transactions,
+    /// starting with the `local_validator`.
+    pub fn get_nyxd_signing_urls(&self) -> Vec<Url> {
+        std::iter::once(self.base.local_validator.clone())
+            .chain(self.base.signing_failover_validators.iter().cloned())
+            .collect()
+    }
+
+    /// Returns the ordered list of validators used for chain queries.
+    pub fn get_nyxd_query_urls(&self) -> Vec<Url> {
+        if self.base.query_validators.is_empty() {
+            self.get_nyxd_signing_urls()
+        } else {
+            self.base.query_validators.clone()
+        }
     }
 
     pub fn get_announce_address(&self) -> Url {
         self.base.announce_address.clone()
     }
 
-    pub fn get_mixnet_contract_address(&self) -> nyxd::AccountId {
-        self.base.mixnet_contract_address.clone()
+    /// Returns the network profile selected with `base.network_profile`, if any,
+    /// completed with the contract addresses discovered on chain.
+    pub fn get_network_profile(&self) -> Option<NetworkProfile> {
+        let profile = self
+            .base
+            .network_profile
+            .as_ref()
+            .and_then(|name| self.network_profiles.get(name))
+            .cloned()?;
+        match &self.discovered_contracts {
+            Some(discovered) => Some(profile.with_discovered_contracts(discovered.clone())),
+            None => Some(profile),
+        }
+    }
+
+    /// Completes the selected network profile with the contract addresses discovered on chain.
+    pub fn with_discovered_contracts(mut self, discovered: DiscoveredContracts) -> Self {
+        self.discovered_contracts = Some(discovered);
+        self
+    }
+
+    /// Makes sure the selected network profile, if any, is defined and internally consistent
+    /// and that none of the directories holding network-specific data has been used
+    /// with a different network before.
+    pub fn validate_network_profile(&self) -> Result<(), NetworkProfileError> {
+        if let Some(name) = &self.base.network_profile {
+            let profile = self
+                .get_network_profile()
+                .ok_or_else(|| NetworkProfileError::UndefinedProfile { name: name.clone() })?;
+            profile.validate(name)?;
+        }
+
+        // if the mixnet contract is yet to be discovered, the directories are checked once that happens
+        if let Some(mixnet_contract_address) = self.get_mixnet_contract_address() {
+            for directory in self.network_data_directories() {
+                ensure_directory_belongs_to_network(&directory, mixnet_contract_address.as_ref())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// All the directories holding the network-specific data, i.e. the keys, the DKG state and the caches,
+    /// including the ones configured outside of the data directory.
+    fn network_data_directories(&self) -> BTreeSet<PathBuf> {
+        let files = [
+            self.get_credentials_database_path(),
+            self.get_topology_snapshot_path(),
+            self.get_node_status_api_database_path(),
+            self.persistent_state_path(),
+            self.verification_key_path(),
+            self.secret_key_path(),
+            self.decryption_key_path(),
+            self.public_key_with_proof_path(),
+        ];
+
+        files
+            .iter()
+            .filter_map(|file| file.parent())
+            .filter(|directory| !directory.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .chain(std::iter::once(self.data_directory()))
+            .collect()
+    }
+
+    /// Returns the address of the mixnet contract. It's only unknown if the selected network profile
+    /// discovers it and that hasn't happened yet.
+    pub fn get_mixnet_contract_address(&self) -> Option<nyxd::AccountId> {
+        match self.get_network_profile() {
+            Some(profile) => profile.mixnet_contract_address,
+            None => Some(self.base.mixnet_contract_address.clone()),
+        }
     }
 
     pub fn get_vesting_contract_address(&self) -> nyxd::AccountId {
-        self.base.vesting_contract_address.clone()
+        match self.get_network_profile() {
+            Some(profile) => profile.vesting_contract_address,
+            None => self.base.vesting_contract_address.clone(),
+        }
     }
 
     pub fn get_mnemonic(&self) -> bip39::Mnemonic {
         self.base.mnemonic.clone()
     }
 
+    pub fn get_fee_denom(&self) -> Option<String> {
+        self.fees.denom.clone()
+    }
+
+    pub fn get_gas_price_amount(&self) -> f64 {
+        self.fees.gas_price_amount
+    }
+
+    pub fn get_fee_adjustment(&self) -> f32 {
+        self.fees.fee_adjustment
+    }
+
     pub fn get_network_monitor_run_interval(&self) -> Duration {
         self.network_monitor.run_interval
     }
@@ -552,10 +849,27 @@ impl Config {
         self.topology_cacher.caching_interval
     }
 
+    pub fn get_topology_snapshot_path(&self) -> PathBuf {
+        // configs created before snapshots got introduced do not specify the path
+        if self.topology_cacher.snapshot_path.as_os_str().is_empty() {
+            TopologyCacher::default_snapshot_path(&self.base.id)
+        } else {
+            self.topology_cacher.snapshot_path.clone()
+        }
+    }
+
+    pub fn get_topology_snapshot_interval(&self) -> Duration {
+        self.topology_cacher.snapshot_interval
+    }
+
     pub fn get_node_status_caching_interval(&self) -> Duration {
         self.node_status_api.caching_interval
     }
 
+    pub fn get_heartbeat_timeout(&self) -> Duration {
+        self.node_status_api.heartbeat_timeout
+    }
+
     pub fn get_circulating_supply_caching_interval(&self) -> Duration {
         self.circulating_supply_cacher.caching_interval
     }
@@ -580,6 +894,10 @@ impl Config {
         self.coconut_signer.secret_key_path.clone()
     }
 
+    pub fn get_encrypt_coconut_secret_key(&self) -> bool {
+        self.coconut_signer.encrypt_secret_key
+    }
+
     pub fn decryption_key_path(&self) -> PathBuf {
         self.coconut_signer.decryption_key_path.clone()
     }
@@ -592,9 +910,45 @@ impl Config {
         self.coconut_signer.dkg_contract_polling_rate
     }
 
+    pub fn get_dkg_submission_deadline(&self) -> Duration {
+        self.coconut_signer.dkg_submission_deadline
+    }
+
+    pub fn get_issuance_health_check_interval(&self) -> Duration {
+        self.coconut_signer.issuance_health_check_interval
+    }
+
+    pub fn get_deposit_indexer_polling_rate(&self) -> Duration {
+        self.coconut_signer.deposit_indexer_polling_rate
+    }
+
     // TODO: Remove if still unused
     #[allow(dead_code)]
     pub fn get_minimum_interval_monitor_threshold(&self) -> u8 {
         self.rewarding.minimum_interval_monitor_threshold
     }
+
+    pub fn get_http_bind_address(&self) -> Option<SocketAddr> {
+        self.http.bind_address
+    }
+
+    pub fn get_trusted_proxies(&self) -> Vec<IpAddr> {
+        self.http.trusted_proxies.clone()
+    }
+
+    pub fn get_tls_certificate_path(&self) -> Option<PathBuf> {
+        self.http.tls_certificate_path.clone()
+    }
+
+    pub fn get_tls_private_key_path(&self) -> Option<PathBuf> {
+        self.http.tls_private_key_path.clone()
+    }
+
+    pub fn get_acme_challenge_directory(&self) -> Option<PathBuf> {
+        self.http.acme_challenge_directory.clone()
+    }
+
+    pub fn get_expose_api_performance(&self) -> bool {
+        self.http.expose_api_performance
+    }
 }
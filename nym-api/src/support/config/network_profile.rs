@@ -0,0 +1,436 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_config::defaults::{NymNetworkDetails, ValidatorDetails};
+use nym_validator_client::nyxd;
+use nym_validator_client::nyxd::error::NyxdError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NetworkProfileError {
+    #[error("the network profile '{name}' is not defined in the config")]
+    UndefinedProfile { name: String },
+
+    #[error("the {contract} contract address ({address}) of the '{profile}' network profile does not use the '{prefix}' account prefix")]
+    MismatchedContractAddress {
+        profile: String,
+        contract: &'static str,
+        address: String,
+        prefix: String,
+    },
+
+    #[error("the '{profile}' network profile discovers its contracts, but doesn't specify the multisig contract to discover them from")]
+    MissingContractRegistry { profile: String },
+
+    #[error(
+        "the '{profile}' network profile neither specifies its mixnet contract nor discovers it"
+    )]
+    MissingMixnetContract { profile: String },
+
+    #[error("failed to query the {contract} contract ({address}) in order to discover the contract addresses: {source}")]
+    ContractQueryFailure {
+        contract: &'static str,
+        address: String,
+        #[source]
+        source: NyxdError,
+    },
+
+    #[error("the multisig contract ({address}) does not expose the addresses of the coconut contracts. Either migrate it to the version supporting the `config` query or set the 'coconut_bandwidth_contract_address', 'coconut_dkg_contract_address' and 'group_contract_address' of the network profile explicitly")]
+    UnsupportedContractRegistry { address: String },
+
+    #[error("the vesting contract ({address}) does not record the address of the mixnet contract. Set the 'mixnet_contract_address' of the network profile explicitly")]
+    MissingMixnetContractRecord { address: String },
+
+    #[error("the discovered address of the {contract} contract ({address}) is malformed")]
+    MalformedDiscoveredAddress {
+        contract: &'static str,
+        address: String,
+    },
+
+    #[error("the directory {} contains data of the network with the '{found}' mixnet contract while the '{expected}' one is configured. Use a separate nym-api id for every network", path.display())]
+    MismatchedDataDirectory {
+        path: PathBuf,
+        expected: String,
+        found: String,
+    },
+
+    #[error("the directory {} already contains some data, but it doesn't record the network it belongs to. If it's the data of the network with the '{expected}' mixnet contract, write that address to {}", path.display(), marker.display())]
+    UnmarkedDataDirectory {
+        path: PathBuf,
+        marker: PathBuf,
+        expected: String,
+    },
+
+    #[error("failed to access the network marker at {}: {source}", path.display())]
+    NetworkMarkerFailure {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Details of a particular network (such as mainnet or a testnet) the nym-api can be run against.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq, Serialize)]
+pub struct NetworkProfile {
+    /// Id of the chain. The nyxd validators are verified to be running it on startup.
+    pub chain_id: String,
+
+    /// Prefix of all the account addresses on the chain, such as `n`.
+    pub bech32_account_prefix: String,
+
+    /// Base denomination of the mixnet token, such as `unym`.
+    pub mix_denom: String,
+
+    /// Base denomination of the staking token, such as `unyx`.
+    pub stake_denom: String,
+
+    /// Address of the mixnet contract managing the network.
+    /// Required unless the contracts are discovered, in which case it's looked up in the vesting contract.
+    #[serde(default)]
+    pub mixnet_contract_address: Option<nyxd::AccountId>,
+
+    /// Address of the vesting contract holding locked tokens.
+    pub vesting_contract_address: nyxd::AccountId,
+
+    /// Address of the coconut bandwidth contract. Required if the coconut signer is enabled.
+    #[serde(default)]
+    pub coconut_bandwidth_contract_address: Option<nyxd::AccountId>,
+
+    /// Address of the DKG contract. Required if the coconut signer is enabled.
+    #[serde(default)]
+    pub coconut_dkg_contract_address: Option<nyxd::AccountId>,
+
+    /// Address of the cw4 group contract of the signers. Required if the coconut signer is enabled.
+    #[serde(default)]
+    pub group_contract_address: Option<nyxd::AccountId>,
+
+    /// Address of the multisig contract of the signers. Required if the coconut signer is enabled.
+    #[serde(default)]
+    pub multisig_contract_address: Option<nyxd::AccountId>,
+
+    /// Specifies whether the contract addresses that are not set explicitly should be looked up on chain.
+    /// The coconut bandwidth, DKG and group contracts are looked up in the multisig contract,
+    /// which keeps track of all of them, while the mixnet contract is looked up in the vesting contract.
+    #[serde(default)]
+    pub discover_contracts: bool,
+}
+
+/// Contract addresses discovered on chain through the multisig and the vesting contracts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredContracts {
+    /// Only looked up if it hasn't been set explicitly.
+    pub mixnet_contract_address: Option<nyxd::AccountId>,
+    pub coconut_bandwidth_contract_address: nyxd::AccountId,
+    pub coconut_dkg_contract_address: nyxd::AccountId,
+    pub group_contract_address: nyxd::AccountId,
+}
+
+/// Name of the file put in every directory holding network-specific data
+/// to record the network (identified by its mixnet contract) the data belongs to.
+pub const NETWORK_MARKER_FILE: &str = "network_mixnet_contract";
+
+/// Makes sure the directory (containing the keys, the DKG state or the caches) has not been used
+/// with a different network before. The network is recorded in the directory the first time
+/// it's used, but only if it's still empty, as otherwise there's no telling which network
+/// the existing data belongs to.
+pub fn ensure_directory_belongs_to_network(
+    directory: &Path,
+    mixnet_contract_address: &str,
+) -> Result<(), NetworkProfileError> {
+    let path = directory.join(NETWORK_MARKER_FILE);
+    let marker_failure = |source| NetworkProfileError::NetworkMarkerFailure {
+        path: path.clone(),
+        source,
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(found) => {
+            let found = found.trim();
+            if found != mixnet_contract_address {
+                return Err(NetworkProfileError::MismatchedDataDirectory {
+                    path: directory.to_path_buf(),
+                    expected: mixnet_contract_address.to_string(),
+                    found: found.to_string(),
+                });
+            }
+            Ok(())
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            fs::create_dir_all(directory).map_err(marker_failure)?;
+            let is_empty = fs::read_dir(directory)
+                .map_err(marker_failure)?
+                .next()
+                .is_none();
+            if !is_empty {
+                return Err(NetworkProfileError::UnmarkedDataDirectory {
+                    path: directory.to_path_buf(),
+                    marker: path,
+                    expected: mixnet_contract_address.to_string(),
+                });
+            }
+            fs::write(&path, mixnet_contract_address).map_err(marker_failure)
+        }
+        Err(err) => Err(marker_failure(err)),
+    }
+}
+
+impl NetworkProfile {
+    fn contracts(&self) -> impl Iterator<Item = (&'static str, &nyxd::AccountId)> {
+        [
+            ("mixnet", self.mixnet_contract_address.as_ref()),
+            ("vesting", Some(&self.vesting_contract_address)),
+            (
+                "coconut bandwidth",
+                self.coconut_bandwidth_contract_address.as_ref(),
+            ),
+            ("coconut dkg", self.coconut_dkg_contract_address.as_ref()),
+            ("group", self.group_contract_address.as_ref()),
+            ("multisig", self.multisig_contract_address.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(contract, address)| address.map(|address| (contract, address)))
+    }
+
+    /// Makes sure all of the contract addresses belong to the chain of the profile.
+    pub fn validate(&self, name: &str) -> Result<(), NetworkProfileError> {
+        if self.discover_contracts && self.multisig_contract_address.is_none() {
+            return Err(NetworkProfileError::MissingContractRegistry {
+                profile: name.to_string(),
+            });
+        }
+        if !self.discover_contracts && self.mixnet_contract_address.is_none() {
+            return Err(NetworkProfileError::MissingMixnetContract {
+                profile: name.to_string(),
+            });
+        }
+        for (contract, address) in self.contracts() {
+            if address.prefix() != self.bech32_account_prefix {
+                return Err(NetworkProfileError::MismatchedContractAddress {
+                    profile: name.to_string(),
+                    contract,
+                    address: address.to_string(),
+                    prefix: self.bech32_account_prefix.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills in the contract addresses that haven't been set explicitly with the discovered ones.
+    /// The explicitly set addresses always take precedence, so that custom deployments could
+    /// override any of them.
+    pub fn with_discovered_contracts(mut self, discovered: DiscoveredContracts) -> Self {
+        if let Some(mixnet_contract_address) = discovered.mixnet_contract_address {
+            self.mixnet_contract_address
+                .get_or_insert(mixnet_contract_address);
+        }
+        self.coconut_bandwidth_contract_address
+            .get_or_insert(discovered.coconut_bandwidth_contract_address);
+        self.coconut_dkg_contract_address
+            .get_or_insert(discovered.coconut_dkg_contract_address);
+        self.group_contract_address
+            .get_or_insert(discovered.group_contract_address);
+        self
+    }
+
+    pub fn network_details(&self, endpoint: ValidatorDetails) -> NymNetworkDetails {
+        NymNetworkDetails::new_empty()
+            .with_bech32_account_prefix(&self.bech32_account_prefix)
+            .with_base_mix_denom(&self.mix_denom)
+            .with_base_stake_denom(&self.stake_denom)
+            .with_validator_endpoint(endpoint)
+            .with_mixnet_contract(
+                self.mixnet_contract_address
+                    .as_ref()
+                    .map(ToString::to_string),
+            )
+            .with_vesting_contract(Some(self.vesting_contract_address.to_string()))
+            .with_coconut_bandwidth_contract(
+                self.coconut_bandwidth_contract_address
+                    .as_ref()
+                    .map(ToString::to_string),
+            )
+            .with_coconut_dkg_contract(
+                self.coconut_dkg_contract_address
+                    .as_ref()
+                    .map(ToString::to_string),
+            )
+            .with_group_contract(
+                self.group_contract_address
+                    .as_ref()
+                    .map(ToString::to_string),
+            )
+            .with_multisig_contract(
+                self.multisig_contract_address
+                    .as_ref()
+                    .map(ToString::to_string),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(chain_id: &str) -> NetworkProfile {
+        NetworkProfile {
+            chain_id: chain_id.to_string(),
+            bech32_account_prefix: "n".to_string(),
+            mix_denom: "unym".to_string(),
+            stake_denom: "unyx".to_string(),
+            mixnet_contract_address: Some(
+                "n17srjznxl9dvzdkpwpw24gg668wc73val88a6m5ajg6ankwvz9wtst0cznr"
+                    .parse()
+                    .unwrap(),
+            ),
+            vesting_contract_address:
+                "n1nc5tatafv6eyq7llkr2gv50ff9e22mnf70qgjlv737ktmt4eswrq73f2nw"
+                    .parse()
+                    .unwrap(),
+            coconut_bandwidth_contract_address: None,
+            coconut_dkg_contract_address: None,
+            group_contract_address: None,
+            multisig_contract_address: None,
+            discover_contracts: false,
+        }
+    }
+
+    fn account(address: &str) -> nyxd::AccountId {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn contract_addresses_must_belong_to_the_chain() {
+        let mut profile = profile("nyx");
+        assert!(profile.validate("mainnet").is_ok());
+
+        profile.group_contract_address = Some(
+            "nymt1k8re7jwz6rnnwrktnejdwkwnncte7ek7gt29gvnl3sdrg9mtnqkstujtpg"
+                .parse()
+                .unwrap(),
+        );
+        assert!(matches!(
+            profile.validate("mainnet"),
+            Err(NetworkProfileError::MismatchedContractAddress {
+                contract: "group",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn explicit_contract_addresses_override_discovered_ones() {
+        let mut profile = profile("nyx");
+        profile.discover_contracts = true;
+        assert!(matches!(
+            profile.validate("mainnet"),
+            Err(NetworkProfileError::MissingContractRegistry { .. })
+        ));
+
+        profile.multisig_contract_address = Some(account(
+            "n10yjnqzs0nxzpesqmzfv006q9fwgu833xme5kuu60tuan72n77j8qe0wsqa",
+        ));
+        let custom_dkg = account("n10k05c0n0920r2rkjv8tspvk0sywm8h0f3sklv6rhpzwkclxqx9aqdypenx");
+        profile.coconut_dkg_contract_address = Some(custom_dkg.clone());
+        assert!(profile.validate("mainnet").is_ok());
+
+        let discovered = DiscoveredContracts {
+            mixnet_contract_address: None,
+            coconut_bandwidth_contract_address: account(
+                "n193epp3de2rf3yp8x4r5ns049kh79xu4ch7p85er6n5z65qzmc0ts93mqyz",
+            ),
+            coconut_dkg_contract_address: account(
+                "n1537ttp49rt9e8t9ea7qx7d0jjyc4frjeutav6kx0vgewxcpwylcq2ahfag",
+            ),
+            group_contract_address: account(
+                "n14kfkljldvv06vls9c04q89feq53pe82x4urpddct4hcst2txlvgsxeprfd",
+            ),
+        };
+        let profile = profile.with_discovered_contracts(discovered.clone());
+        assert_eq!(
+            profile.coconut_bandwidth_contract_address,
+            Some(discovered.coconut_bandwidth_contract_address)
+        );
+        assert_eq!(profile.coconut_dkg_contract_address, Some(custom_dkg));
+        assert_eq!(
+            profile.group_contract_address,
+            Some(discovered.group_contract_address)
+        );
+    }
+
+    #[test]
+    fn mixnet_contract_must_be_specified_unless_discovered() {
+        let mut profile = profile("nyx");
+        let mixnet_contract = profile.mixnet_contract_address.take().unwrap();
+        assert!(matches!(
+            profile.validate("mainnet"),
+            Err(NetworkProfileError::MissingMixnetContract { .. })
+        ));
+
+        profile.discover_contracts = true;
+        profile.multisig_contract_address = Some(account(
+            "n10yjnqzs0nxzpesqmzfv006q9fwgu833xme5kuu60tuan72n77j8qe0wsqa",
+        ));
+        assert!(profile.validate("mainnet").is_ok());
+
+        let discovered = DiscoveredContracts {
+            mixnet_contract_address: Some(mixnet_contract.clone()),
+            coconut_bandwidth_contract_address: account(
+                "n193epp3de2rf3yp8x4r5ns049kh79xu4ch7p85er6n5z65qzmc0ts93mqyz",
+            ),
+            coconut_dkg_contract_address: account(
+                "n1537ttp49rt9e8t9ea7qx7d0jjyc4frjeutav6kx0vgewxcpwylcq2ahfag",
+            ),
+            group_contract_address: account(
+                "n14kfkljldvv06vls9c04q89feq53pe82x4urpddct4hcst2txlvgsxeprfd",
+            ),
+        };
+        let profile = profile.with_discovered_contracts(discovered);
+        assert_eq!(profile.mixnet_contract_address, Some(mixnet_contract));
+    }
+
+    #[test]
+    fn data_directory_cannot_be_shared_between_networks() {
+        let data_directory = tempfile::tempdir().unwrap();
+        let mainnet = "n17srjznxl9dvzdkpwpw24gg668wc73val88a6m5ajg6ankwvz9wtst0cznr";
+        let testnet = "n14hj2tavq8fpesdwxxcu44rty3hh90vhujrvcmstl4zr3txmfvw9sjyr9ll";
+
+        ensure_directory_belongs_to_network(data_directory.path(), mainnet).unwrap();
+        ensure_directory_belongs_to_network(data_directory.path(), mainnet).unwrap();
+
+        assert!(matches!(
+            ensure_directory_belongs_to_network(data_directory.path(), testnet),
+            Err(NetworkProfileError::MismatchedDataDirectory { .. })
+        ));
+    }
+
+    #[test]
+    fn unmarked_non_empty_directory_is_not_adopted() {
+        let data_directory = tempfile::tempdir().unwrap();
+        let mainnet = "n17srjznxl9dvzdkpwpw24gg668wc73val88a6m5ajg6ankwvz9wtst0cznr";
+        fs::write(data_directory.path().join("private_key.pem"), "key").unwrap();
+
+        assert!(matches!(
+            ensure_directory_belongs_to_network(data_directory.path(), mainnet),
+            Err(NetworkProfileError::UnmarkedDataDirectory { .. })
+        ));
+        assert!(!data_directory.path().join(NETWORK_MARKER_FILE).exists());
+    }
+
+    #[test]
+    fn missing_directory_is_adopted() {
+        let parent = tempfile::tempdir().unwrap();
+        let data_directory = parent.path().join("data");
+        let mainnet = "n17srjznxl9dvzdkpwpw24gg668wc73val88a6m5ajg6ankwvz9wtst0cznr";
+
+        ensure_directory_belongs_to_network(&data_directory, mainnet).unwrap();
+        assert_eq!(
+            fs::read_to_string(data_directory.join(NETWORK_MARKER_FILE)).unwrap(),
+            mainnet
+        );
+    }
+}
@@ -16,11 +16,32 @@ id = '{{ base.id }}'
 # Validator server to which the API will be getting information about the network.
 local_validator = '{{ base.local_validator }}'
 
+# Additional validators used for submitting transactions in case `local_validator` becomes unavailable.
+signing_failover_validators = [
+    {{#each base.signing_failover_validators }}
+        '{{this}}',
+    {{/each}}
+]
+
+# Validators used exclusively for chain queries, such as refreshing the caches.
+# If empty, `local_validator` and its failovers are used instead.
+query_validators = [
+    {{#each base.query_validators }}
+        '{{this}}',
+    {{/each}}
+]
+
 # Address announced to the directory server for the clients to connect to.
 # It is useful, say, in NAT scenarios or wanting to more easily update actual IP address
 # later on by using name resolvable with a DNS query, such as `nymtech.net`.
 announce_address = '{{ base.announce_address }}'
 
+# Name of the network profile (defined in the `network_profiles` section) used by this nym-api.
+# If set, the chain details and contract addresses of the profile take precedence over the ones below.
+{{#if base.network_profile }}
+network_profile = '{{ base.network_profile }}'
+{{/if}}
+
 # Address of the validator contract managing the network.
 mixnet_contract_address = '{{ base.mixnet_contract_address }}'
 
@@ -30,6 +51,22 @@ vesting_contract_address = '{{ base.vesting_contract_address }}'
 # Mnemonic used for rewarding and validator interaction
 mnemonic = '{{ base.mnemonic }}'
 
+##### transaction fees config options #####
+
+[fees]
+
+# Denomination in which the fees of all the transactions are paid.
+# If not set, the base mix denomination of the network is used.
+{{#if fees.denom }}
+denom = '{{ fees.denom }}'
+{{/if}}
+
+# Amount of the fee denomination paid per unit of gas.
+gas_price_amount = {{ fees.gas_price_amount }}
+
+# Multiplier applied to the simulated gas usage of a transaction in order to determine its gas limit.
+fee_adjustment = {{ fees.fee_adjustment }}
+
 ##### network monitor config options #####
 
 [network_monitor]
@@ -84,6 +121,21 @@ per_node_test_packets = {{ network_monitor.per_node_test_packets }}
 # Path to the database file containing uptime statuses for all mixnodes and gateways.
 database_path = '{{ node_status_api.database_path }}'
 
+# Specifies the duration after which a node that stopped sending heartbeats is considered
+# inactive and is excluded from the served topology.
+heartbeat_timeout = '{{ node_status_api.heartbeat_timeout }}'
+
+##### topology cacher config options #####
+
+[topology_cacher]
+
+# Path to the file containing the snapshot of the cached chain data, used for serving
+# requests straight after startup, before the first refresh completes.
+snapshot_path = '{{ topology_cacher.snapshot_path }}'
+
+# Specifies the interval at which the snapshot of the cached chain data is stored on disk.
+snapshot_interval = '{{ topology_cacher.snapshot_interval }}'
+
 ##### rewarding config options #####
 
 [rewarding]
@@ -107,11 +159,85 @@ verification_key_path = '{{ coconut_signer.verification_key_path }}'
 # Path to the coconut verification key
 secret_key_path = '{{ coconut_signer.secret_key_path }}'
 
+# Specifies whether the coconut secret key is stored encrypted with a passphrase.
+# The passphrase is read from the `NYM_API_COCONUT_KEY_PASSPHRASE` environment variable
+# or, if it's not set, asked for on startup.
+encrypt_secret_key = {{ coconut_signer.encrypt_secret_key }}
+
 # Path to the dkg dealer decryption key
 decryption_key_path = '{{ coconut_signer.decryption_key_path }}'
 
 # Path to the dkg dealer public key with proof
 public_key_with_proof_path = '{{ coconut_signer.public_key_with_proof_path }}'
 
+##### http api config options #####
+
+[http]
+
+# Socket address on which the HTTP API is going to listen.
+# If not set, the `ROCKET_ADDRESS` and `ROCKET_PORT` environment variables are used instead.
+{{#if http.bind_address }}
+bind_address = '{{ http.bind_address }}'
+{{/if}}
+
+# Addresses of the reverse proxies placed in front of this API. Only the `X-Forwarded-For`
+# headers set by those are trusted when determining the address of the client.
+trusted_proxies = [
+    {{#each http.trusted_proxies }}
+        '{{this}}',
+    {{/each}}
+]
+
+# Paths to the PEM-encoded certificate chain and its private key used for terminating
+# TLS connections. TLS is only enabled if both of them are specified.
+{{#if http.tls_certificate_path }}
+tls_certificate_path = '{{ http.tls_certificate_path }}'
+{{/if}}
+{{#if http.tls_private_key_path }}
+tls_private_key_path = '{{ http.tls_private_key_path }}'
+{{/if}}
+
+# Directory from which the responses to the ACME HTTP-01 challenges are served.
+# The certificates are not issued or renewed by the API itself. Use an external ACME client,
+# such as `certbot certonly --webroot`, pointed at this directory and restart the API
+# after every renewal.
+{{#if http.acme_challenge_directory }}
+acme_challenge_directory = '{{ http.acme_challenge_directory }}'
+{{/if}}
+
+# Specifies whether the per-endpoint request counts, error rates and latency histograms
+# are exposed under `/v1/status/api-performance`.
+expose_api_performance = {{ http.expose_api_performance }}
+
+##### network profiles #####
+
+# Each profile defines the chain id, account prefix, denominations and contract addresses
+# of a particular network, such as mainnet or a testnet.
+{{#each network_profiles }}
+[network_profiles.{{@key}}]
+chain_id = '{{ this.chain_id }}'
+bech32_account_prefix = '{{ this.bech32_account_prefix }}'
+mix_denom = '{{ this.mix_denom }}'
+stake_denom = '{{ this.stake_denom }}'
+{{#if this.mixnet_contract_address }}
+mixnet_contract_address = '{{ this.mixnet_contract_address }}'
+{{/if}}
+vesting_contract_address = '{{ this.vesting_contract_address }}'
+{{#if this.coconut_bandwidth_contract_address }}
+coconut_bandwidth_contract_address = '{{ this.coconut_bandwidth_contract_address }}'
+{{/if}}
+{{#if this.coconut_dkg_contract_address }}
+coconut_dkg_contract_address = '{{ this.coconut_dkg_contract_address }}'
+{{/if}}
+{{#if this.group_contract_address }}
+group_contract_address = '{{ this.group_contract_address }}'
+{{/if}}
+{{#if this.multisig_contract_address }}
+multisig_contract_address = '{{ this.multisig_contract_address }}'
+{{/if}}
+discover_contracts = {{ this.discover_contracts }}
+
+{{/each}}
+
 "#
 }
@@ -0,0 +1,13 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::support::cli::BUILD_INFORMATION;
+use nym_bin_common::build_information::BinaryBuildInformationOwned;
+use rocket::serde::json::Json;
+
+/// Provides the build information of this nym API, including the versions of the wire protocols
+/// it speaks.
+#[get("/build-information")]
+pub(crate) fn build_information() -> Json<BinaryBuildInformationOwned> {
+    Json(BUILD_INFORMATION.to_owned())
+}
@@ -0,0 +1,131 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use rocket::Request;
+use std::net::IpAddr;
+
+const FORWARDED_FOR_HEADER: &str = "X-Forwarded-For";
+
+/// Addresses of the reverse proxies whose `X-Forwarded-For` headers are trusted.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TrustedProxies(Vec<IpAddr>);
+
+impl TrustedProxies {
+    pub(crate) fn new(proxies: Vec<IpAddr>) -> Self {
+        TrustedProxies(proxies)
+    }
+
+    fn is_trusted(&self, address: &IpAddr) -> bool {
+        self.0.contains(address)
+    }
+}
+
+/// Determines the address of the client, taking into account the `X-Forwarded-For` header,
+/// as long as the request came through one of the trusted proxies.
+///
+/// The header is processed right to left (i.e. starting with the address appended by the proxy
+/// closest to us) and the first address that does not belong to a trusted proxy is returned.
+/// Anything further to the left could have been set by the client itself and thus can't be relied on.
+///
+/// If a trusted proxy did not attach the header or any of the entries we'd have to rely on is malformed,
+/// the address of the client can't be determined and `None` is returned, rather than falling back
+/// to the address of one of the proxies.
+fn resolve_client_address(
+    peer: IpAddr,
+    forwarded_for: Option<&str>,
+    trusted: &TrustedProxies,
+) -> Option<IpAddr> {
+    if !trusted.is_trusted(&peer) {
+        return Some(peer);
+    }
+
+    let mut client = None;
+    for hop in forwarded_for?.rsplit(',') {
+        let address = hop.trim().parse::<IpAddr>().ok()?;
+        client = Some(address);
+        if !trusted.is_trusted(&address) {
+            break;
+        }
+    }
+    client
+}
+
+/// Returns the address of the client that has sent the request, even if it went through
+/// a reverse proxy. If the request came through a trusted proxy that has not reported
+/// a valid client address, `None` is returned.
+pub(crate) fn client_address(request: &Request<'_>) -> Option<IpAddr> {
+    let peer = request.remote()?.ip();
+    let forwarded_for = request.headers().get_one(FORWARDED_FOR_HEADER);
+    match request.rocket().state::<TrustedProxies>() {
+        Some(trusted) => resolve_client_address(peer, forwarded_for, trusted),
+        None => Some(peer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(raw: &str) -> IpAddr {
+        raw.parse().unwrap()
+    }
+
+    #[test]
+    fn header_is_ignored_for_untrusted_peers() {
+        let trusted = TrustedProxies::new(vec![ip("10.0.0.1")]);
+        assert_eq!(
+            resolve_client_address(ip("1.2.3.4"), Some("5.6.7.8"), &trusted),
+            Some(ip("1.2.3.4"))
+        );
+        assert_eq!(
+            resolve_client_address(ip("1.2.3.4"), Some("5.6.7.8"), &TrustedProxies::default()),
+            Some(ip("1.2.3.4"))
+        );
+    }
+
+    #[test]
+    fn spoofed_addresses_are_skipped() {
+        let trusted = TrustedProxies::new(vec![ip("127.0.0.1"), ip("10.0.0.1")]);
+
+        // the client claimed to be the localhost, but the proxy appended its real address
+        assert_eq!(
+            resolve_client_address(
+                ip("127.0.0.1"),
+                Some("127.0.0.1, 5.6.7.8, 10.0.0.1"),
+                &trusted
+            ),
+            Some(ip("5.6.7.8"))
+        );
+        assert_eq!(
+            resolve_client_address(ip("127.0.0.1"), Some("5.6.7.8"), &trusted),
+            Some(ip("5.6.7.8"))
+        );
+    }
+
+    #[test]
+    fn unknown_client_address_is_not_replaced_by_the_proxy() {
+        let trusted = TrustedProxies::new(vec![ip("127.0.0.1"), ip("10.0.0.1")]);
+        assert_eq!(
+            resolve_client_address(ip("127.0.0.1"), Some("foomp, 10.0.0.1"), &trusted),
+            None
+        );
+        assert_eq!(
+            resolve_client_address(ip("127.0.0.1"), Some("5.6.7.8, foomp"), &trusted),
+            None
+        );
+        assert_eq!(
+            resolve_client_address(ip("127.0.0.1"), Some(""), &trusted),
+            None
+        );
+        assert_eq!(
+            resolve_client_address(ip("127.0.0.1"), None, &trusted),
+            None
+        );
+
+        // the malformed entries further to the left are never looked at
+        assert_eq!(
+            resolve_client_address(ip("127.0.0.1"), Some("foomp, 5.6.7.8"), &trusted),
+            Some(ip("5.6.7.8"))
+        );
+    }
+}
@@ -2,29 +2,43 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::circulating_supply_api::cache::CirculatingSupplyCache;
+use crate::coconut::circuit_breaker::IssuanceCircuitBreaker;
+use crate::coconut::log_context::DkgLogContext;
 use crate::coconut::{self, comm::QueryCommunicationChannel, InternalSignRequest};
+use crate::node_status_api::heartbeats::HeartbeatStore;
 use crate::node_status_api::{self, NodeStatusCache};
 use crate::nym_contract_cache::cache::NymContractCache;
 use crate::support::config::Config;
+use crate::support::http::client_address::TrustedProxies;
+use crate::support::http::request_metrics::RequestMetrics;
+use crate::support::supervisor::{self, TaskSupervisor};
 use crate::support::{nyxd, storage};
 use crate::{circulating_supply_api, nym_contract_cache};
 use anyhow::Result;
+use rocket::figment::Figment;
+use rocket::fs::FileServer;
 use rocket::http::Method;
 use rocket::{Ignite, Rocket};
 use rocket_cors::{AllowedHeaders, AllowedOrigins, Cors};
 use rocket_okapi::mount_endpoints_and_merged_docs;
 use rocket_okapi::swagger_ui::make_swagger_ui;
 
+pub(crate) mod build_information;
+pub(crate) mod client_address;
 pub(crate) mod openapi;
+pub(crate) mod request_metrics;
 
 pub(crate) async fn setup_rocket(
     config: &Config,
     mix_denom: String,
     _nyxd_client: nyxd::Client,
     coconut_keypair: coconut::keypair::KeyPair,
+    dkg_context: DkgLogContext,
+    circuit_breaker: IssuanceCircuitBreaker,
+    supervisor: TaskSupervisor,
 ) -> anyhow::Result<Rocket<Ignite>> {
     let openapi_settings = rocket_okapi::settings::OpenApiSettings::default();
-    let mut rocket = rocket::build();
+    let mut rocket = rocket::custom(setup_figment(config)?);
 
     mount_endpoints_and_merged_docs! {
         rocket,
@@ -36,13 +50,42 @@ pub(crate) async fn setup_rocket(
         "/status" => node_status_api::node_status_routes(&openapi_settings, config.get_network_monitor_enabled()),
     }
 
+    let request_metrics = RequestMetrics::new();
     let rocket = rocket
         .mount("/swagger", make_swagger_ui(&openapi::get_docs()))
+        .mount(
+            "/v1",
+            routes![build_information::build_information, supervisor::health],
+        )
+        .manage(TrustedProxies::new(config.get_trusted_proxies()))
+        .manage(supervisor)
+        .manage(request_metrics.clone())
+        .manage(HeartbeatStore::new(config.get_heartbeat_timeout()))
+        .attach(request_metrics)
         .attach(setup_cors()?)
         .attach(NymContractCache::stage())
         .attach(NodeStatusCache::stage())
         .attach(CirculatingSupplyCache::stage(mix_denom.clone()));
 
+    let rocket = if config.get_expose_api_performance() {
+        rocket.mount("/v1/status", routes![request_metrics::api_performance])
+    } else {
+        rocket
+    };
+
+    let rocket = match config.get_acme_challenge_directory() {
+        Some(directory) => {
+            if !directory.is_dir() {
+                anyhow::bail!(
+                    "the ACME challenge directory {} does not exist",
+                    directory.display()
+                )
+            }
+            rocket.mount("/.well-known/acme-challenge", FileServer::from(directory))
+        }
+        None => rocket,
+    };
+
     // This is not a very nice approach. A lazy value would be more suitable, but that's still
     // a nightly feature: https://github.com/rust-lang/rust/issues/74465
     let storage = if config.get_coconut_signer_enabled() || config.get_network_monitor_enabled() {
@@ -59,6 +102,8 @@ pub(crate) async fn setup_rocket(
             coconut_keypair,
             comm_channel,
             storage.clone().unwrap(),
+            dkg_context,
+            circuit_breaker,
         ))
     } else {
         rocket
@@ -74,6 +119,32 @@ pub(crate) async fn setup_rocket(
     Ok(rocket.ignite().await?)
 }
 
+/// Applies the bind and TLS options from our config on top of the default rocket configuration
+/// (i.e. the one coming from `Rocket.toml` and the `ROCKET_` environment variables).
+fn setup_figment(config: &Config) -> Result<Figment> {
+    let figment = rocket::Config::figment()
+        // the client addresses are resolved based on the trusted proxies instead
+        .merge(("ip_header", false));
+
+    let figment = match config.get_http_bind_address() {
+        Some(bind_address) => figment
+            .merge(("address", bind_address.ip()))
+            .merge(("port", bind_address.port())),
+        None => figment,
+    };
+
+    match (
+        config.get_tls_certificate_path(),
+        config.get_tls_private_key_path(),
+    ) {
+        (Some(certs), Some(key)) => Ok(figment.merge(("tls.certs", certs)).merge(("tls.key", key))),
+        (None, None) => Ok(figment),
+        _ => anyhow::bail!(
+            "both the TLS certificate and its private key must be specified in order to enable TLS"
+        ),
+    }
+}
+
 fn setup_cors() -> Result<Cors> {
     let allowed_origins = AllowedOrigins::all();
 
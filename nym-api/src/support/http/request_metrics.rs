@@ -0,0 +1,205 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-endpoint request counts, error rates and latency histograms of the HTTP API,
+//! used for finding the slow paths (e.g. cache misses) of the API.
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::serde::json::Json;
+use rocket::{Data, Request, Response, State};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Upper bounds (in milliseconds) of the latency histogram buckets.
+/// Anything slower than the last one ends up in the overflow bucket.
+const LATENCY_BUCKETS_MS: [u64; 11] = [1, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+const UNMATCHED_ENDPOINT: &str = "unmatched";
+
+/// Time at which rocket started handling the request.
+struct RequestStart(Instant);
+
+#[derive(Debug, Default)]
+struct EndpointMetrics {
+    requests: u64,
+    client_errors: u64,
+    server_errors: u64,
+    total_latency: Duration,
+    // one extra bucket for everything slower than the last bound
+    latency_buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+impl EndpointMetrics {
+    fn record(&mut self, status_code: u16, latency: Duration) {
+        self.requests += 1;
+        match status_code {
+            400..=499 => self.client_errors += 1,
+            500..=599 => self.server_errors += 1,
+            _ => (),
+        }
+        self.total_latency += latency;
+
+        let latency_ms = latency.as_millis();
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| latency_ms <= *bound as u128)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.latency_buckets[bucket] += 1;
+    }
+
+    fn summary(&self, endpoint: &str) -> EndpointPerformance {
+        let latency_histogram = self
+            .latency_buckets
+            .iter()
+            .enumerate()
+            .map(|(i, count)| LatencyBucket {
+                le_ms: LATENCY_BUCKETS_MS.get(i).copied(),
+                count: *count,
+            })
+            .collect();
+
+        EndpointPerformance {
+            endpoint: endpoint.to_owned(),
+            requests: self.requests,
+            client_errors: self.client_errors,
+            server_errors: self.server_errors,
+            error_rate: (self.client_errors + self.server_errors) as f64
+                / self.requests.max(1) as f64,
+            average_latency_ms: self.total_latency.as_secs_f64() * 1000.
+                / self.requests.max(1) as f64,
+            latency_histogram,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct LatencyBucket {
+    /// Upper bound of the bucket. `None` for the bucket containing all requests
+    /// slower than the largest bound.
+    le_ms: Option<u64>,
+    count: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct EndpointPerformance {
+    endpoint: String,
+    requests: u64,
+    client_errors: u64,
+    server_errors: u64,
+    error_rate: f64,
+    average_latency_ms: f64,
+    latency_histogram: Vec<LatencyBucket>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ApiPerformance {
+    /// Time (in seconds) over which the metrics have been collected.
+    uptime: u64,
+
+    /// Performance of all the endpoints that have been queried, starting with the slowest one.
+    endpoints: Vec<EndpointPerformance>,
+}
+
+/// Fairing recording the metrics of every request handled by the API.
+#[derive(Debug, Clone)]
+pub(crate) struct RequestMetrics {
+    started_at: Instant,
+    endpoints: Arc<Mutex<HashMap<String, EndpointMetrics>>>,
+}
+
+impl RequestMetrics {
+    pub(crate) fn new() -> Self {
+        RequestMetrics {
+            started_at: Instant::now(),
+            endpoints: Default::default(),
+        }
+    }
+
+    fn record(&self, endpoint: String, status_code: u16, latency: Duration) {
+        self.endpoints
+            .lock()
+            .expect("request metrics lock got poisoned")
+            .entry(endpoint)
+            .or_default()
+            .record(status_code, latency)
+    }
+
+    pub(crate) fn performance(&self) -> ApiPerformance {
+        let mut endpoints = self
+            .endpoints
+            .lock()
+            .expect("request metrics lock got poisoned")
+            .iter()
+            .map(|(endpoint, metrics)| metrics.summary(endpoint))
+            .collect::<Vec<_>>();
+        endpoints.sort_by(|a, b| b.average_latency_ms.total_cmp(&a.average_latency_ms));
+
+        ApiPerformance {
+            uptime: self.started_at.elapsed().as_secs(),
+            endpoints,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for RequestMetrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request metrics",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let latency = request
+            .local_cache(|| RequestStart(Instant::now()))
+            .0
+            .elapsed();
+
+        // use the route templates rather than the actual paths, so that the requests
+        // for different nodes would be counted towards the same endpoint
+        let endpoint = match request.route() {
+            Some(route) => format!("{} {}", request.method(), route.uri),
+            None => UNMATCHED_ENDPOINT.to_string(),
+        };
+        self.record(endpoint, response.status().code, latency)
+    }
+}
+
+/// Provides the request counts, error rates and latency histograms of all the endpoints
+/// of this API.
+#[get("/api-performance")]
+pub(crate) fn api_performance(metrics: &State<RequestMetrics>) -> Json<ApiPerformance> {
+    Json(metrics.performance())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_put_into_correct_buckets() {
+        let mut metrics = EndpointMetrics::default();
+        metrics.record(200, Duration::from_micros(500));
+        metrics.record(200, Duration::from_millis(1));
+        metrics.record(404, Duration::from_millis(30));
+        metrics.record(500, Duration::from_secs(10));
+
+        assert_eq!(metrics.latency_buckets[0], 2);
+        assert_eq!(metrics.latency_buckets[4], 1);
+        assert_eq!(metrics.latency_buckets[LATENCY_BUCKETS_MS.len()], 1);
+
+        let summary = metrics.summary("GET /foo");
+        assert_eq!(summary.requests, 4);
+        assert_eq!(summary.client_errors, 1);
+        assert_eq!(summary.server_errors, 1);
+        assert_eq!(summary.error_rate, 0.5);
+        assert_eq!(summary.latency_histogram.last().unwrap().le_ms, None);
+    }
+}
@@ -7,3 +7,4 @@ pub(crate) mod config;
 pub(crate) mod http;
 pub(crate) mod nyxd;
 pub(crate) mod storage;
+pub(crate) mod supervisor;
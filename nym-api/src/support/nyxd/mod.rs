@@ -3,20 +3,26 @@
 
 use crate::coconut::error::CoconutError;
 use crate::epoch_operations::MixnodeWithPerformance;
-use crate::support::config::Config;
+use crate::support::config::{Config, DiscoveredContracts, NetworkProfileError};
 use anyhow::Result;
 use async_trait::async_trait;
 use cw3::ProposalResponse;
 use cw4::MemberResponse;
+use nym_coconut_bandwidth_contract_common::events::DEPOSITED_FUNDS_EVENT_TYPE;
 use nym_coconut_bandwidth_contract_common::spend_credential::SpendCredentialResponse;
+use nym_coconut_dkg_common::complaints::{
+    ComplaintReason, DealerComplaintsResponse, DisputedDealer,
+};
 use nym_coconut_dkg_common::msg::QueryMsg as DkgQueryMsg;
 use nym_coconut_dkg_common::types::InitialReplacementData;
 use nym_coconut_dkg_common::{
-    dealer::{ContractDealing, DealerDetails, DealerDetailsResponse},
+    dealer::{DealerDetails, DealerDetailsResponse, PagedDealingsResponse},
     types::{EncodedBTEPublicKeyWithProof, Epoch, EpochId},
     verification_key::{ContractVKShare, VerificationKeyShare},
 };
-use nym_config::defaults::{ChainDetails, NymNetworkDetails, DEFAULT_NYM_API_PORT};
+use nym_config::defaults::{
+    ChainDetails, NymNetworkDetails, ValidatorDetails, DEFAULT_NYM_API_PORT,
+};
 use nym_contracts_common::dealings::ContractSafeBytes;
 use nym_mixnet_contract_common::families::{Family, FamilyHead};
 use nym_mixnet_contract_common::mixnode::MixNodeDetails;
@@ -37,47 +43,154 @@ use nym_validator_client::nyxd::{
 };
 use nym_validator_client::nyxd::{
     hash::{Hash, SHA256_HASH_SIZE},
-    AccountId, Coin, DirectSigningNyxdClient, TendermintTime, VestingQueryClient,
+    AccountId, Coin, DirectSigningNyxdClient, GasPrice, Query, TendermintTime, VestingQueryClient,
 };
 use nym_validator_client::ValidatorClientError;
 use nym_vesting_contract_common::AccountVestingCoins;
 use serde::Deserialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use url::Url;
 
 pub(crate) struct Client(
     pub(crate) Arc<RwLock<nym_validator_client::Client<DirectSigningNyxdClient>>>,
+    Arc<NyxdEndpoints>,
 );
 
 impl Clone for Client {
     fn clone(&self) -> Self {
-        Client(Arc::clone(&self.0))
+        Client(Arc::clone(&self.0), Arc::clone(&self.1))
+    }
+}
+
+/// Completes the selected network profile with the contract addresses discovered through its
+/// multisig contract, if the profile is set up to do so.
+pub(crate) async fn discover_contracts(config: Config) -> Result<Config, NetworkProfileError> {
+    match config.get_network_profile() {
+        Some(profile) if profile.discover_contracts => {
+            let discovered = Client::new_query(&config)
+                .discover_contracts(profile.mixnet_contract_address.is_none())
+                .await?;
+            if let Some(mixnet_contract_address) = &discovered.mixnet_contract_address {
+                info!("Discovered the mixnet contract ({mixnet_contract_address})");
+            }
+            info!(
+                "Discovered the coconut bandwidth ({}), coconut dkg ({}) and group ({}) contracts",
+                discovered.coconut_bandwidth_contract_address,
+                discovered.coconut_dkg_contract_address,
+                discovered.group_contract_address
+            );
+            Ok(config.with_discovered_contracts(discovered))
+        }
+        _ => Ok(config),
     }
 }
 
+// keys under which the multisig contract stores its config and the vesting contract
+// stores the address of the mixnet contract
+const MULTISIG_CONFIG_STORAGE_KEY: &[u8] = b"config";
+const VESTING_MIXNET_CONTRACT_STORAGE_KEY: &[u8] = b"mix";
+
+fn discovered_account(
+    contract: &'static str,
+    address: cosmwasm_std::Addr,
+) -> Result<AccountId, NetworkProfileError> {
+    address
+        .as_str()
+        .parse()
+        .map_err(|_| NetworkProfileError::MalformedDiscoveredAddress {
+            contract,
+            address: address.into_string(),
+        })
+}
+
+/// Ordered list of nyxd endpoints the client can fail over between.
+struct NyxdEndpoints {
+    urls: Vec<Url>,
+    current: AtomicUsize,
+}
+
 impl Client {
-    pub(crate) fn new(config: &Config) -> Self {
+    /// Creates a client dedicated to submitting transactions, such as DKG dealings or rewarding.
+    pub(crate) fn new_signing(config: &Config) -> Self {
+        Self::new(config, config.get_nyxd_signing_urls())
+    }
+
+    /// Creates a client dedicated to chain queries, such as the ones refreshing the caches.
+    pub(crate) fn new_query(config: &Config) -> Self {
+        Self::new(config, config.get_nyxd_query_urls())
+    }
+
+    fn new(config: &Config, nyxd_urls: Vec<Url>) -> Self {
         // the api address is irrelevant here as **WE ARE THE API**
         // and we won't be talking on the socket here.
-        let api_url = format!("http://localhost:{}", DEFAULT_NYM_API_PORT)
+        let api_url: Url = format!("http://localhost:{}", DEFAULT_NYM_API_PORT)
             .parse()
             .unwrap();
-        let nyxd_url = config.get_nyxd_url();
+        let nyxd_url = nyxd_urls
+            .first()
+            .cloned()
+            .expect("no nyxd endpoints have been specified");
+
+        let details = match config.get_network_profile() {
+            Some(profile) => profile.network_details(ValidatorDetails::new(
+                nyxd_url.to_string(),
+                Some(api_url.to_string()),
+            )),
+            None => NymNetworkDetails::new_from_env()
+                .with_mixnet_contract(
+                    config
+                        .get_mixnet_contract_address()
+                        .map(|address| address.to_string()),
+                )
+                .with_vesting_contract(Some(config.get_vesting_contract_address().as_ref())),
+        };
 
-        let details = NymNetworkDetails::new_from_env()
-            .with_mixnet_contract(Some(config.get_mixnet_contract_address().as_ref()))
-            .with_vesting_contract(Some(config.get_vesting_contract_address().as_ref()));
+        let fee_denom = config
+            .get_fee_denom()
+            .unwrap_or_else(|| details.chain_details.mix_denom.base.clone());
+        let gas_price: GasPrice = format!("{}{fee_denom}", config.get_gas_price_amount())
+            .parse()
+            .expect("the configured gas price is malformed");
 
         let client_config = nym_validator_client::Config::try_from_nym_network_details(&details)
             .expect("failed to construct valid validator client config with the provided network")
-            .with_urls(nyxd_url, api_url);
+            .with_urls(nyxd_url, api_url)
+            .with_gas_price(gas_price);
 
         let mnemonic = config.get_mnemonic();
 
-        let inner = nym_validator_client::Client::new_signing(client_config, mnemonic)
+        let mut inner = nym_validator_client::Client::new_signing(client_config, mnemonic)
             .expect("Failed to connect to nyxd!");
+        inner.set_nyxd_simulated_gas_multiplier(config.get_fee_adjustment());
 
-        Client(Arc::new(RwLock::new(inner)))
+        let endpoints = NyxdEndpoints {
+            urls: nyxd_urls,
+            current: AtomicUsize::new(0),
+        };
+
+        Client(Arc::new(RwLock::new(inner)), Arc::new(endpoints))
+    }
+
+    /// Switches the client to the next configured nyxd endpoint, if there's more than one.
+    /// It should be called whenever the current endpoint appears to be unavailable.
+    pub(crate) async fn try_failover(&self) {
+        let endpoints = &self.1;
+        if endpoints.urls.len() < 2 {
+            return;
+        }
+
+        let mut inner = self.0.write().await;
+        let next = (endpoints.current.load(Ordering::SeqCst) + 1) % endpoints.urls.len();
+        let next_url = endpoints.urls[next].clone();
+        match inner.change_nyxd(next_url.clone()) {
+            Ok(_) => {
+                warn!("Switched nyxd endpoint to {next_url}");
+                endpoints.current.store(next, Ordering::SeqCst);
+            }
+            Err(err) => error!("Failed to switch nyxd endpoint to {next_url} - {err}"),
+        }
     }
 
     pub(crate) async fn client_address(&self) -> AccountId {
@@ -88,6 +201,80 @@ impl Client {
         self.0.read().await.nyxd.current_chain_details().clone()
     }
 
+    pub(crate) async fn chain_id(&self) -> Result<String, NyxdError> {
+        Ok(self.0.read().await.nyxd.get_chain_id().await?.to_string())
+    }
+
+    /// Looks up the addresses of the coconut contracts the multisig contract has been set up with
+    /// and, if requested, the address of the mixnet contract the vesting contract works with.
+    pub(crate) async fn discover_contracts(
+        &self,
+        discover_mixnet: bool,
+    ) -> Result<DiscoveredContracts, NetworkProfileError> {
+        let guard = self.0.read().await;
+
+        let multisig_contract = guard.nyxd.multisig_contract_address().clone();
+        let registry = match guard.nyxd.get_multisig_config().await {
+            Ok(registry) => registry,
+            Err(err) => {
+                // the contracts deployed before the `config` query got introduced
+                // still keep the very same addresses in their storage
+                debug!("failed to query the multisig config: {err}. Reading it from the contract storage instead");
+                let raw = guard
+                    .nyxd
+                    .query_contract_raw(&multisig_contract, MULTISIG_CONFIG_STORAGE_KEY.to_vec())
+                    .await
+                    .map_err(|source| NetworkProfileError::ContractQueryFailure {
+                        contract: "multisig",
+                        address: multisig_contract.to_string(),
+                        source,
+                    })?;
+                serde_json::from_slice(&raw).map_err(|_| {
+                    NetworkProfileError::UnsupportedContractRegistry {
+                        address: multisig_contract.to_string(),
+                    }
+                })?
+            }
+        };
+
+        let mixnet_contract_address = if discover_mixnet {
+            let vesting_contract = guard.nyxd.vesting_contract_address().clone();
+            let raw = guard
+                .nyxd
+                .query_contract_raw(
+                    &vesting_contract,
+                    VESTING_MIXNET_CONTRACT_STORAGE_KEY.to_vec(),
+                )
+                .await
+                .map_err(|source| NetworkProfileError::ContractQueryFailure {
+                    contract: "vesting",
+                    address: vesting_contract.to_string(),
+                    source,
+                })?;
+            let address: cosmwasm_std::Addr = serde_json::from_slice(&raw).map_err(|_| {
+                NetworkProfileError::MissingMixnetContractRecord {
+                    address: vesting_contract.to_string(),
+                }
+            })?;
+            Some(discovered_account("mixnet", address)?)
+        } else {
+            None
+        };
+
+        Ok(DiscoveredContracts {
+            mixnet_contract_address,
+            coconut_bandwidth_contract_address: discovered_account(
+                "coconut bandwidth",
+                registry.coconut_bandwidth_addr,
+            )?,
+            coconut_dkg_contract_address: discovered_account(
+                "coconut dkg",
+                registry.coconut_dkg_addr,
+            )?,
+            group_contract_address: discovered_account("group", registry.group_addr)?,
+        })
+    }
+
     pub(crate) async fn get_rewarding_validator_address(
         &self,
     ) -> Result<AccountId, ValidatorClientError> {
@@ -112,8 +299,7 @@ impl Client {
             .map_err(|_| NyxdError::MalformedAccountAddress(cosmwasm_addr).into())
     }
 
-    // a helper function for the future to obtain the current block timestamp
-    #[allow(dead_code)]
+    /// Obtains the timestamp of the latest block.
     pub(crate) async fn current_block_timestamp(
         &self,
     ) -> Result<TendermintTime, ValidatorClientError> {
@@ -309,6 +495,10 @@ impl Client {
 
 #[async_trait]
 impl crate::coconut::client::Client for Client {
+    async fn try_failover(&self) {
+        Client::try_failover(self).await
+    }
+
     async fn address(&self) -> AccountId {
         self.client_address().await
     }
@@ -323,6 +513,33 @@ impl crate::coconut::client::Client for Client {
         Ok(self.0.read().await.nyxd.get_tx(tx_hash).await?)
     }
 
+    async fn get_current_block_height(&self) -> crate::coconut::error::Result<u64> {
+        Ok(self
+            .0
+            .read()
+            .await
+            .nyxd
+            .get_current_block_height()
+            .await?
+            .value())
+    }
+
+    async fn search_deposits(
+        &self,
+        from_height: u64,
+        to_height: u64,
+    ) -> crate::coconut::error::Result<Vec<nym_validator_client::nyxd::TxResponse>> {
+        let guard = self.0.read().await;
+        let query = Query::eq(
+            format!("wasm-{DEPOSITED_FUNDS_EVENT_TYPE}._contract_address"),
+            guard.nyxd.coconut_bandwidth_contract_address().to_string(),
+        )
+        .and_gte("tx.height", from_height)
+        .and_lte("tx.height", to_height);
+
+        Ok(guard.nyxd.search_tx(query).await?)
+    }
+
     async fn get_proposal(
         &self,
         proposal_id: u64,
@@ -367,6 +584,10 @@ impl crate::coconut::client::Client for Client {
             .await?)
     }
 
+    async fn get_total_dealings(&self) -> crate::coconut::error::Result<usize> {
+        Ok(self.0.read().await.nyxd.get_total_dealings().await? as usize)
+    }
+
     async fn get_initial_dealers(
         &self,
     ) -> crate::coconut::error::Result<Option<InitialReplacementData>> {
@@ -390,11 +611,17 @@ impl crate::coconut::client::Client for Client {
         Ok(self.0.read().await.get_all_current_dealers().await?)
     }
 
-    async fn get_dealings(
+    async fn get_dealings_paged(
         &self,
         idx: usize,
-    ) -> crate::coconut::error::Result<Vec<ContractDealing>> {
-        Ok(self.0.read().await.get_all_epoch_dealings(idx).await?)
+        start_after: Option<String>,
+    ) -> crate::coconut::error::Result<PagedDealingsResponse> {
+        Ok(self
+            .0
+            .read()
+            .await
+            .get_dealings_paged(idx, start_after, None)
+            .await?)
     }
 
     async fn get_verification_key_shares(
@@ -409,17 +636,46 @@ impl crate::coconut::client::Client for Client {
             .await?)
     }
 
+    async fn get_dealer_complaints(
+        &self,
+        dealer: &AccountId,
+    ) -> crate::coconut::error::Result<DealerComplaintsResponse> {
+        Ok(self
+            .0
+            .read()
+            .await
+            .nyxd
+            .get_dealer_complaints(dealer)
+            .await?)
+    }
+
+    async fn get_disputed_dealers(&self) -> crate::coconut::error::Result<Vec<DisputedDealer>> {
+        Ok(self.0.read().await.get_all_disputed_dealers().await?)
+    }
+
     async fn vote_proposal(
         &self,
         proposal_id: u64,
         vote_yes: bool,
+        memo: Option<String>,
         fee: Option<Fee>,
     ) -> Result<(), CoconutError> {
-        self.0
-            .read()
-            .await
+        let inner = self.0.read().await;
+
+        // make sure the fee grants are also using the configured fee adjustment
+        let fee = fee.map(|fee| match fee {
+            Fee::PayerGranterAuto(mut auto_feegrant) => {
+                if auto_feegrant.gas_adjustment.is_none() {
+                    auto_feegrant.gas_adjustment = Some(inner.nyxd.gas_adjustment());
+                }
+                Fee::PayerGranterAuto(auto_feegrant)
+            }
+            fee => fee,
+        });
+
+        inner
             .nyxd
-            .vote_proposal(proposal_id, vote_yes, fee)
+            .vote_proposal(proposal_id, vote_yes, memo, fee)
             .await?;
         Ok(())
     }
@@ -473,6 +729,22 @@ impl crate::coconut::client::Client for Client {
             .await?)
     }
 
+    async fn submit_complaint(
+        &self,
+        dealer: &AccountId,
+        dealing_index: Option<u64>,
+        reason: ComplaintReason,
+        resharing: bool,
+    ) -> crate::coconut::error::Result<ExecuteResult> {
+        Ok(self
+            .0
+            .write()
+            .await
+            .nyxd
+            .submit_complaint(dealer, dealing_index, reason, resharing, None)
+            .await?)
+    }
+
     async fn submit_verification_key_share(
         &self,
         share: VerificationKeyShare,
@@ -4,7 +4,7 @@ use crate::network_monitor::monitor::summary_producer::{GatewayResult, MixnodeRe
 use crate::node_status_api::models::{HistoricalUptime, Uptime};
 use crate::node_status_api::utils::{ActiveGatewayStatuses, ActiveMixnodeStatuses};
 use crate::support::storage::models::{
-    ActiveGateway, ActiveMixnode, NodeStatus, RewardingReport, TestingRoute,
+    ActiveGateway, ActiveMixnode, BandwidthDeposit, NodeStatus, RewardingReport, TestingRoute,
 };
 use nym_mixnet_contract_common::{EpochId, IdentityKey, MixId};
 use std::convert::TryFrom;
@@ -1013,4 +1013,81 @@ impl StorageManager {
 
         Ok(blinded_signature_response)
     }
+
+    /// Inserts the bandwidth deposit found on chain. Deposits that have already been indexed are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `deposit`: the deposit to insert.
+    pub(crate) async fn insert_bandwidth_deposit(
+        &self,
+        deposit: &BandwidthDeposit,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+                INSERT OR IGNORE INTO bandwidth_deposit
+                (tx_hash, height, deposit_value, deposit_info, identity_key, encryption_key)
+                VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            deposit.tx_hash,
+            deposit.height,
+            deposit.deposit_value,
+            deposit.deposit_info,
+            deposit.identity_key,
+            deposit.encryption_key,
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Tries to obtain the indexed bandwidth deposit made in the given transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash`: transaction hash of the deposit.
+    pub(crate) async fn get_bandwidth_deposit(
+        &self,
+        tx_hash: &str,
+    ) -> Result<Option<BandwidthDeposit>, sqlx::Error> {
+        sqlx::query_as!(
+            BandwidthDeposit,
+            r#"
+                SELECT tx_hash, height, deposit_value, deposit_info, identity_key, encryption_key
+                FROM bandwidth_deposit
+                WHERE tx_hash = ?
+            "#,
+            tx_hash
+        )
+        .fetch_optional(&self.connection_pool)
+        .await
+    }
+
+    /// Gets the height of the last block that has been fully processed by the deposit indexer.
+    pub(crate) async fn get_last_indexed_deposit_height(&self) -> Result<Option<i64>, sqlx::Error> {
+        let height = sqlx::query!("SELECT last_indexed_height FROM deposit_indexer_state")
+            .fetch_optional(&self.connection_pool)
+            .await?
+            .map(|row| row.last_indexed_height);
+
+        Ok(height)
+    }
+
+    /// Sets the height of the last block that has been fully processed by the deposit indexer.
+    ///
+    /// # Arguments
+    ///
+    /// * `height`: height of the last processed block.
+    pub(crate) async fn set_last_indexed_deposit_height(
+        &self,
+        height: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT OR REPLACE INTO deposit_indexer_state(id, last_indexed_height) VALUES (0, ?)",
+            height
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
 }
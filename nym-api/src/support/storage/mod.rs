@@ -9,7 +9,7 @@ use crate::node_status_api::models::{
 };
 use crate::node_status_api::{ONE_DAY, ONE_HOUR};
 use crate::storage::manager::StorageManager;
-use crate::storage::models::{NodeStatus, TestingRoute};
+use crate::storage::models::{BandwidthDeposit, NodeStatus, TestingRoute};
 use nym_mixnet_contract_common::MixId;
 use rocket::fairing::AdHoc;
 use sqlx::ConnectOptions;
@@ -737,4 +737,43 @@ impl NymApiStorage {
             .await
             .map_err(|err| err.into())
     }
+
+    pub(crate) async fn insert_bandwidth_deposit(
+        &self,
+        deposit: &BandwidthDeposit,
+    ) -> Result<(), NymApiStorageError> {
+        self.manager
+            .insert_bandwidth_deposit(deposit)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    pub(crate) async fn get_bandwidth_deposit(
+        &self,
+        tx_hash: &str,
+    ) -> Result<Option<BandwidthDeposit>, NymApiStorageError> {
+        self.manager
+            .get_bandwidth_deposit(tx_hash)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    pub(crate) async fn get_last_indexed_deposit_height(
+        &self,
+    ) -> Result<Option<i64>, NymApiStorageError> {
+        self.manager
+            .get_last_indexed_deposit_height()
+            .await
+            .map_err(|err| err.into())
+    }
+
+    pub(crate) async fn set_last_indexed_deposit_height(
+        &self,
+        height: i64,
+    ) -> Result<(), NymApiStorageError> {
+        self.manager
+            .set_last_indexed_deposit_height(height)
+            .await
+            .map_err(|err| err.into())
+    }
 }
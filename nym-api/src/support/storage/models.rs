@@ -41,6 +41,17 @@ pub(crate) struct TestingRoute {
     pub(crate) monitor_run_db_id: i64,
 }
 
+/// Bandwidth deposit found on chain by the deposit indexer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BandwidthDeposit {
+    pub(crate) tx_hash: String,
+    pub(crate) height: i64,
+    pub(crate) deposit_value: String,
+    pub(crate) deposit_info: String,
+    pub(crate) identity_key: String,
+    pub(crate) encryption_key: String,
+}
+
 // for now let's leave it here to have a data model to use with existing database tables
 #[allow(unused)]
 pub(crate) struct RewardingReport {
@@ -0,0 +1,273 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Supervision of the long-running background tasks, such as the cache refreshers or the DKG
+//! controller. A task that crashes (or exits while the API is still running) gets restarted
+//! with an exponential backoff, and the status of all the tasks is exposed on the health endpoint.
+//! Only a task failing in a way that no restart could ever fix (i.e. due to an invalid
+//! configuration) brings the whole process down.
+
+use nym_task::{TaskClient, TaskManager};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+// a task that has been running for at least that long before crashing is restarted straight away,
+// i.e. the backoff only grows if it keeps on crashing shortly after being restarted
+const BACKOFF_RESET_THRESHOLD: Duration = Duration::from_secs(10 * 60);
+
+/// Reason for a supervised task having stopped on its own.
+#[derive(Debug)]
+pub(crate) enum TaskFailure {
+    /// The task might work once it's restarted, e.g. once the validator is reachable again.
+    Recoverable(anyhow::Error),
+
+    /// The task cannot possibly run with the current configuration.
+    Unrecoverable(anyhow::Error),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TaskStatus {
+    Running,
+    /// The task has crashed and is waiting to be restarted.
+    Restarting,
+    /// The task has been stopped as the API is shutting down.
+    Stopped,
+    /// The task has failed in an unrecoverable way.
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct TaskHealth {
+    name: &'static str,
+    status: TaskStatus,
+    restarts: u32,
+    last_failure: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ApiHealth {
+    /// Time (in seconds) since the tasks have been started.
+    uptime: u64,
+
+    tasks: Vec<TaskHealth>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct TaskSupervisor {
+    started_at: Instant,
+    initial_backoff: Duration,
+    tasks: Arc<Mutex<BTreeMap<&'static str, TaskHealth>>>,
+}
+
+impl TaskSupervisor {
+    pub(crate) fn new() -> Self {
+        TaskSupervisor {
+            started_at: Instant::now(),
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            tasks: Default::default(),
+        }
+    }
+
+    fn update<F: FnOnce(&mut TaskHealth)>(&self, name: &'static str, f: F) {
+        let mut tasks = self
+            .tasks
+            .lock()
+            .expect("task supervisor lock got poisoned");
+        let health = tasks.entry(name).or_insert_with(|| TaskHealth {
+            name,
+            status: TaskStatus::Running,
+            restarts: 0,
+            last_failure: None,
+        });
+        f(health)
+    }
+
+    fn set_status(&self, name: &'static str, status: TaskStatus) {
+        self.update(name, |health| health.status = status)
+    }
+
+    pub(crate) fn health(&self) -> ApiHealth {
+        ApiHealth {
+            uptime: self.started_at.elapsed().as_secs(),
+            tasks: self
+                .tasks
+                .lock()
+                .expect("task supervisor lock got poisoned")
+                .values()
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Whether all the tasks are either running or have been stopped as part of the shutdown.
+    pub(crate) fn is_healthy(&self) -> bool {
+        self.tasks
+            .lock()
+            .expect("task supervisor lock got poisoned")
+            .values()
+            .all(|task| matches!(task.status, TaskStatus::Running | TaskStatus::Stopped))
+    }
+
+    /// Spawns the task created by `task`, creating it anew whenever the previous one crashes.
+    /// Each instance is given its own shutdown listener which doesn't bring the whole process down
+    /// when it's dropped, as the crashed tasks are handled by the supervisor instead.
+    pub(crate) fn spawn<F, Fut>(&self, name: &'static str, shutdown: &TaskManager, mut task: F)
+    where
+        F: FnMut(TaskClient) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), TaskFailure>> + Send + 'static,
+    {
+        let supervisor = self.clone();
+        let mut shutdown_listener = shutdown.subscribe();
+        supervisor.set_status(name, TaskStatus::Running);
+
+        tokio::spawn(async move {
+            let mut backoff = supervisor.initial_backoff;
+            loop {
+                let mut task_shutdown_listener = shutdown_listener.clone();
+                task_shutdown_listener.mark_as_success();
+
+                let started_at = Instant::now();
+                let result = tokio::spawn(task(task_shutdown_listener)).await;
+                if shutdown_listener.is_shutdown_poll() {
+                    break;
+                }
+
+                let failure = match result {
+                    Ok(Ok(())) => "the task has exited unexpectedly".to_string(),
+                    Ok(Err(TaskFailure::Recoverable(err))) => err.to_string(),
+                    Ok(Err(TaskFailure::Unrecoverable(err))) => {
+                        error!("{name} has failed in an unrecoverable way: {err}");
+                        supervisor.update(name, |health| {
+                            health.status = TaskStatus::Failed;
+                            health.last_failure = Some(err.to_string());
+                        });
+                        shutdown_listener.send_we_stopped(err.into());
+                        return;
+                    }
+                    Err(err) => format!("the task has crashed: {err}"),
+                };
+
+                if started_at.elapsed() >= BACKOFF_RESET_THRESHOLD {
+                    backoff = supervisor.initial_backoff;
+                }
+                warn!(
+                    "{name} has stopped: {failure}. Restarting it in {}s",
+                    backoff.as_secs_f32()
+                );
+                supervisor.update(name, |health| {
+                    health.status = TaskStatus::Restarting;
+                    health.restarts += 1;
+                    health.last_failure = Some(failure);
+                });
+
+                tokio::select! {
+                    _ = sleep(backoff) => (),
+                    _ = shutdown_listener.recv() => break,
+                }
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                supervisor.set_status(name, TaskStatus::Running);
+            }
+            supervisor.set_status(name, TaskStatus::Stopped);
+        });
+    }
+}
+
+/// Provides the status of all the background tasks of this API. Responds with 503
+/// if any of them is not currently running.
+#[get("/health")]
+pub(crate) fn health(supervisor: &State<TaskSupervisor>) -> (Status, Json<ApiHealth>) {
+    let status = if supervisor.is_healthy() {
+        Status::Ok
+    } else {
+        Status::ServiceUnavailable
+    };
+    (status, Json(supervisor.health()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn test_supervisor() -> TaskSupervisor {
+        TaskSupervisor {
+            initial_backoff: Duration::from_millis(10),
+            ..TaskSupervisor::new()
+        }
+    }
+
+    fn task_health(supervisor: &TaskSupervisor, name: &str) -> TaskHealth {
+        supervisor
+            .health()
+            .tasks
+            .into_iter()
+            .find(|task| task.name == name)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn crashed_tasks_get_restarted() {
+        let shutdown = TaskManager::new(1);
+        let supervisor = test_supervisor();
+        let attempts = Arc::new(AtomicU32::new(0));
+
+        let task_attempts = Arc::clone(&attempts);
+        supervisor.spawn("crashing", &shutdown, move |mut shutdown| {
+            let attempt = task_attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt < 2 {
+                    panic!("crash number {attempt}");
+                }
+                shutdown.recv().await;
+                Ok(())
+            }
+        });
+
+        while attempts.load(Ordering::SeqCst) < 3 {
+            sleep(Duration::from_millis(5)).await;
+        }
+        sleep(Duration::from_millis(5)).await;
+
+        let health = task_health(&supervisor, "crashing");
+        assert_eq!(health.status, TaskStatus::Running);
+        assert_eq!(health.restarts, 2);
+        assert!(health.last_failure.unwrap().contains("crashed"));
+        assert!(supervisor.is_healthy());
+
+        shutdown.signal_shutdown().unwrap();
+        while task_health(&supervisor, "crashing").status != TaskStatus::Stopped {
+            sleep(Duration::from_millis(5)).await;
+        }
+        assert!(supervisor.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn unrecoverable_failures_are_escalated() {
+        let mut shutdown = TaskManager::new(1);
+        let supervisor = test_supervisor();
+        supervisor.spawn("misconfigured", &shutdown, |_| async {
+            Err(TaskFailure::Unrecoverable(anyhow::anyhow!(
+                "invalid config"
+            )))
+        });
+
+        let err = shutdown.wait_for_error().await.unwrap();
+        assert_eq!(err.to_string(), "invalid config");
+
+        let health = task_health(&supervisor, "misconfigured");
+        assert_eq!(health.status, TaskStatus::Failed);
+        assert_eq!(health.restarts, 0);
+        assert!(!supervisor.is_healthy());
+    }
+}
@@ -0,0 +1,85 @@
+#!/usr/bin/env python3
+# Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+# SPDX-License-Identifier: Apache-2.0
+
+"""
+Collects the results of the criterion benchmarks into a single JSON document, so that they could be
+stored as CI artifacts and compared between runs.
+
+usage:
+    collect_benchmark_results.py [--criterion-dir target/criterion] [--output results.json]
+                                 [--compare baseline.json] [--threshold 0.1]
+"""
+
+import argparse
+import json
+import os
+import sys
+
+
+def collect(criterion_dir):
+    results = {}
+    for root, _, files in os.walk(criterion_dir):
+        # criterion stores the latest results of each benchmark in '<benchmark>/new'
+        if os.path.basename(root) != "new" or "benchmark.json" not in files:
+            continue
+        with open(os.path.join(root, "benchmark.json")) as f:
+            benchmark = json.load(f)
+        with open(os.path.join(root, "estimates.json")) as f:
+            estimates = json.load(f)
+
+        results[benchmark["full_id"]] = {
+            "mean_ns": estimates["mean"]["point_estimate"],
+            "median_ns": estimates["median"]["point_estimate"],
+            "std_dev_ns": estimates["std_dev"]["point_estimate"],
+            "throughput": benchmark.get("throughput"),
+        }
+    return dict(sorted(results.items()))
+
+
+def compare(results, baseline, threshold):
+    regressions = []
+    for name, result in results.items():
+        if name not in baseline:
+            continue
+        old = baseline[name]["median_ns"]
+        new = result["median_ns"]
+        change = (new - old) / old
+        marker = ""
+        if change > threshold:
+            marker = " <-- REGRESSION"
+            regressions.append(name)
+        print(f"{name}: {old:.0f}ns -> {new:.0f}ns ({change:+.2%}){marker}", file=sys.stderr)
+    return regressions
+
+
+def main():
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--criterion-dir", default="target/criterion")
+    parser.add_argument("--output", help="file to write the results to (stdout by default)")
+    parser.add_argument("--compare", help="previously collected results to compare against")
+    parser.add_argument("--threshold", type=float, default=0.1,
+                        help="relative slowdown of the median considered a regression")
+    args = parser.parse_args()
+
+    results = collect(args.criterion_dir)
+    if not results:
+        sys.exit(f"no benchmark results found in {args.criterion_dir}")
+
+    serialized = json.dumps(results, indent=2)
+    if args.output:
+        with open(args.output, "w") as f:
+            f.write(serialized + "\n")
+    else:
+        print(serialized)
+
+    if args.compare:
+        with open(args.compare) as f:
+            baseline = json.load(f)
+        regressions = compare(results, baseline, args.threshold)
+        if regressions:
+            sys.exit(f"{len(regressions)} benchmark(s) regressed by more than {args.threshold:.0%}")
+
+
+if __name__ == "__main__":
+    main()
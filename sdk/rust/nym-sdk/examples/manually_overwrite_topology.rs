@@ -31,6 +31,7 @@ async fn main() {
                 .unwrap(),
             layer: Layer::One,
             version: "1.1.0".to_string(),
+            family: None,
         }],
     );
     mixnodes.insert(
@@ -48,6 +49,7 @@ async fn main() {
                 .unwrap(),
             layer: Layer::Two,
             version: "1.1.0".to_string(),
+            family: None,
         }],
     );
     mixnodes.insert(
@@ -65,6 +67,7 @@ async fn main() {
                 .unwrap(),
             layer: Layer::Three,
             version: "1.1.0".to_string(),
+            family: None,
         }],
     );
 
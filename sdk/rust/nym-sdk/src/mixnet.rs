@@ -45,11 +45,15 @@ pub use native_client::MixnetClient;
 pub use native_client::MixnetClientSender;
 pub use nym_client_core::{
     client::{
+        bandwidth_forecast::BandwidthForecast,
+        delivery::{DeadLetter, DeliveryEvent, DeliveryEventReceiver, DeliveryFailure, MessageId},
         inbound_messages::InputMessage,
         replies::reply_storage::{fs_backend::Backend as ReplyStorage, Empty as EmptyReplyStorage},
+        topology_control::anomaly::{TopologyAnomaly, TopologyAnomalyStatus},
     },
     config::GatewayEndpointConfig,
 };
+pub use nym_gateway_client::embedded::EmbeddedConnection;
 pub use nym_network_defaults::NymNetworkDetails;
 pub use nym_socks5_client_core::config::Socks5;
 pub use nym_sphinx::{
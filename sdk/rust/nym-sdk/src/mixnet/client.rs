@@ -17,6 +17,7 @@ use nym_client_core::{
 use nym_credential_storage::ephemeral_storage::EphemeralStorage;
 use nym_credential_storage::initialise_ephemeral_storage;
 use nym_crypto::asymmetric::identity;
+use nym_gateway_client::embedded::EmbeddedConnection;
 use nym_network_defaults::NymNetworkDetails;
 
 use nym_socks5_client_core::config::Socks5;
@@ -44,6 +45,7 @@ pub struct MixnetClientBuilder {
     gateway_config: Option<GatewayEndpointConfig>,
     socks5_config: Option<Socks5>,
     custom_topology_provider: Option<Box<dyn TopologyProvider>>,
+    embedded_gateway_connection: Option<EmbeddedConnection>,
 }
 
 impl MixnetClientBuilder {
@@ -118,6 +120,14 @@ impl MixnetClientBuilder {
         self
     }
 
+    /// Connect to the gateway in-process rather than over the websocket. This is only
+    /// applicable to clients running within the gateway binary itself.
+    #[must_use]
+    pub fn embedded_gateway_connection(mut self, connection: EmbeddedConnection) -> Self {
+        self.embedded_gateway_connection = Some(connection);
+        self
+    }
+
     /// Construct a [`DisconnectedMixnetClient`] from the setup specified.
     pub async fn build<B>(self) -> Result<DisconnectedMixnetClient<B>>
     where
@@ -138,6 +148,8 @@ impl MixnetClientBuilder {
             client.set_keys(keys);
         }
 
+        client.embedded_gateway_connection = self.embedded_gateway_connection;
+
         // If we have a gateway config, we can move the client into a registered state. This will
         // fail if no gateway key is set.
         if let Some(gateway_config) = self.gateway_config {
@@ -181,6 +193,9 @@ where
 
     /// Alternative provider of network topology used for constructing sphinx packets.
     custom_topology_provider: Option<Box<dyn TopologyProvider>>,
+
+    /// In-process connection to the gateway, if the client is running within the gateway binary.
+    embedded_gateway_connection: Option<EmbeddedConnection>,
 }
 
 impl<B> DisconnectedMixnetClient<B>
@@ -262,6 +277,7 @@ where
             reply_storage_backend,
             bandwidth_controller,
             custom_topology_provider,
+            embedded_gateway_connection: None,
         })
     }
 
@@ -467,6 +483,10 @@ where
             base_builder = base_builder.with_topology_provider(topology_provider);
         }
 
+        if let Some(embedded_connection) = self.embedded_gateway_connection {
+            base_builder = base_builder.with_embedded_gateway_connection(embedded_connection);
+        }
+
         let started_client = base_builder.start_base().await?;
 
         Ok((started_client, nym_address))
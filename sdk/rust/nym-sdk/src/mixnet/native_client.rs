@@ -1,8 +1,11 @@
 use nym_client_core::client::{
+    bandwidth_forecast::BandwidthForecast,
     base_client::{ClientInput, ClientOutput, ClientState},
+    delivery::{DeadLetter, DeliveryEventReceiver, MessageId},
     inbound_messages::InputMessage,
     key_manager::KeyManager,
     received_buffer::ReconstructedMessagesReceiver,
+    topology_control::anomaly::{TopologyAnomaly, TopologyAnomalyStatus},
 };
 use nym_sphinx::{
     addressing::clients::{ClientIdentity, Recipient},
@@ -18,6 +21,7 @@ use nym_topology::NymTopology;
 
 use crate::mixnet::client::{IncludedSurbs, MixnetClientBuilder};
 use crate::Result;
+use std::time::Duration;
 
 /// Client connected to the Nym mixnet.
 pub struct MixnetClient {
@@ -33,7 +37,6 @@ pub struct MixnetClient {
 
     /// Output from the client from the users perspective. This is typically messages arriving from
     /// the mixnet.
-    #[allow(dead_code)]
     pub(crate) client_output: ClientOutput,
 
     /// The current state of the client that is exposed to the user. This includes things like
@@ -121,6 +124,41 @@ impl MixnetClient {
         self.client_state.topology_accessor.release_manual_control()
     }
 
+    /// Put the client into the low-power hibernation mode, in which no cover traffic is sent,
+    /// the network topology is refreshed less often and the gateway connection is only being
+    /// kept alive. Any real messages are still going to be sent out.
+    pub fn hibernate(&self) {
+        self.client_state.hibernation.hibernate();
+    }
+
+    /// Bring the client out of the hibernation mode and restore its full traffic shaping.
+    pub fn wake(&self) {
+        self.client_state.hibernation.wake();
+    }
+
+    /// Check whether the client is currently in the hibernation mode.
+    pub fn is_hibernating(&self) -> bool {
+        self.client_state.hibernation.is_hibernating()
+    }
+
+    /// Get the suspicious changes of the network topology that haven't been acknowledged yet,
+    /// alongside the information whether the traffic is paused because of them.
+    pub fn topology_anomalies(&self) -> TopologyAnomalyStatus {
+        self.client_state.topology_anomalies.status()
+    }
+
+    /// Acknowledge all of the detected topology anomalies and resume the traffic if it has been
+    /// paused because of them. Returns the acknowledged anomalies.
+    pub fn acknowledge_topology_anomalies(&self) -> Vec<TopologyAnomaly> {
+        self.client_state.topology_anomalies.acknowledge()
+    }
+
+    /// Get the latest estimate of how long the bandwidth remaining at the gateway is going to last
+    /// at the current consumption rate.
+    pub fn bandwidth_forecast(&self) -> BandwidthForecast {
+        self.client_state.bandwidth_forecast.forecast()
+    }
+
     /// Sends stringy data to the supplied Nym address
     ///
     /// # Example
@@ -160,14 +198,59 @@ impl MixnetClient {
     /// }
     /// ```
     pub async fn send_bytes(&self, address: Recipient, message: Vec<u8>, surbs: IncludedSurbs) {
+        let input_msg = Self::create_input_message(address, message, surbs);
+        self.send(input_msg).await
+    }
+
+    /// Sends bytes to the supplied Nym address, same as [`MixnetClient::send_bytes`], while also
+    /// requesting a notification about the outcome of the message with the provided `message_id`.
+    /// The notifications are available via [`MixnetClient::delivery_events`].
+    pub async fn send_bytes_tracked(
+        &self,
+        address: Recipient,
+        message: Vec<u8>,
+        surbs: IncludedSurbs,
+        message_id: MessageId,
+    ) {
+        let input_msg = Self::create_input_message(address, message, surbs);
+        self.send(input_msg.with_delivery_tracking(message_id))
+            .await
+    }
+
+    /// Sends bytes to the supplied Nym address, same as [`MixnetClient::send_bytes`], but the
+    /// message is dropped if it hasn't been delivered within the provided `ttl`. If `message_id`
+    /// is provided, the outcome is reported via [`MixnetClient::delivery_events`], with messages
+    /// that have not made it in time resulting in the `Expired` event.
+    pub async fn send_bytes_with_ttl(
+        &self,
+        address: Recipient,
+        message: Vec<u8>,
+        surbs: IncludedSurbs,
+        ttl: Duration,
+        message_id: Option<MessageId>,
+    ) {
+        let input_msg = Self::create_input_message(address, message, surbs).with_ttl(ttl);
+        match message_id {
+            Some(message_id) => {
+                self.send(input_msg.with_delivery_tracking(message_id))
+                    .await
+            }
+            None => self.send(input_msg).await,
+        }
+    }
+
+    fn create_input_message(
+        address: Recipient,
+        message: Vec<u8>,
+        surbs: IncludedSurbs,
+    ) -> InputMessage {
         let lane = TransmissionLane::General;
-        let input_msg = match surbs {
+        match surbs {
             IncludedSurbs::Amount(surbs) => {
                 InputMessage::new_anonymous(address, message, surbs, lane)
             }
             IncludedSurbs::ExposeSelfAddress => InputMessage::new_regular(address, message, lane),
-        };
-        self.send(input_msg).await
+        }
     }
 
     /// Sends a [`InputMessage`] to the mixnet. This is the most low-level sending function, for
@@ -194,6 +277,35 @@ impl MixnetClient {
         self.reconstructed_receiver.next().await
     }
 
+    /// Get a channel for the `Delivered`, `Failed` and `Expired` events of messages sent with delivery
+    /// tracking. Note that calling it again replaces the previously obtained channel.
+    pub fn delivery_events(&mut self) -> Result<DeliveryEventReceiver> {
+        Ok(self.client_output.register_delivery_receiver()?)
+    }
+
+    /// Get all tracked messages that could not have been delivered, either because they have
+    /// exhausted their retransmission budget or because they could not have been sent at all.
+    pub async fn dead_letters(&self) -> Result<Vec<DeadLetter>> {
+        Ok(self.client_output.dead_letters().await?)
+    }
+
+    /// Remove the message with the provided `message_id` from the dead letter queue, if present.
+    pub async fn take_dead_letter(&self, message_id: MessageId) -> Result<Option<DeadLetter>> {
+        Ok(self.client_output.take_dead_letter(message_id).await?)
+    }
+
+    /// Send the undelivered message with the provided `message_id` again, with its delivery
+    /// still being tracked. Returns `false` if there was no such message on the dead letter queue.
+    pub async fn retry_dead_letter(&self, message_id: MessageId) -> Result<bool> {
+        let Some(dead_letter) = self.take_dead_letter(message_id).await? else {
+            return Ok(false);
+        };
+
+        self.send(dead_letter.message.with_delivery_tracking(message_id))
+            .await;
+        Ok(true)
+    }
+
     /// Provide a callback to execute on incoming messages from the mixnet.
     pub async fn on_messages<F>(&mut self, fun: F)
     where
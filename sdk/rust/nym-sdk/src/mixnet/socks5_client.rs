@@ -1,4 +1,9 @@
-use nym_client_core::client::{base_client::ClientState, key_manager::KeyManager};
+use nym_client_core::client::{
+    bandwidth_forecast::BandwidthForecast,
+    base_client::ClientState,
+    key_manager::KeyManager,
+    topology_control::anomaly::{TopologyAnomaly, TopologyAnomalyStatus},
+};
 use nym_socks5_client_core::config::Socks5;
 use nym_sphinx::addressing::clients::{ClientIdentity, Recipient};
 use nym_task::{connections::LaneQueueLengths, TaskManager};
@@ -96,6 +101,41 @@ impl Socks5MixnetClient {
         self.client_state.topology_accessor.release_manual_control()
     }
 
+    /// Put the client into the low-power hibernation mode, in which no cover traffic is sent,
+    /// the network topology is refreshed less often and the gateway connection is only being
+    /// kept alive. Any real messages are still going to be sent out.
+    pub fn hibernate(&self) {
+        self.client_state.hibernation.hibernate();
+    }
+
+    /// Bring the client out of the hibernation mode and restore its full traffic shaping.
+    pub fn wake(&self) {
+        self.client_state.hibernation.wake();
+    }
+
+    /// Check whether the client is currently in the hibernation mode.
+    pub fn is_hibernating(&self) -> bool {
+        self.client_state.hibernation.is_hibernating()
+    }
+
+    /// Get the suspicious changes of the network topology that haven't been acknowledged yet,
+    /// alongside the information whether the traffic is paused because of them.
+    pub fn topology_anomalies(&self) -> TopologyAnomalyStatus {
+        self.client_state.topology_anomalies.status()
+    }
+
+    /// Acknowledge all of the detected topology anomalies and resume the traffic if it has been
+    /// paused because of them. Returns the acknowledged anomalies.
+    pub fn acknowledge_topology_anomalies(&self) -> Vec<TopologyAnomaly> {
+        self.client_state.topology_anomalies.acknowledge()
+    }
+
+    /// Get the latest estimate of how long the bandwidth remaining at the gateway is going to last
+    /// at the current consumption rate.
+    pub fn bandwidth_forecast(&self) -> BandwidthForecast {
+        self.client_state.bandwidth_forecast.forecast()
+    }
+
     /// Disconnect from the mixnet. Currently it is not supported to reconnect a disconnected
     /// client.
     pub async fn disconnect(&mut self) {
@@ -82,5 +82,37 @@ async fn main() -> anyhow::Result<()> {
     let response = wait_for_control_response(&mut client).await;
     println!("response to 'SupportedRequestVersions' request: {response:#?}");
 
+    let full_request_negotiation: Request = Request::new_control(
+        ProviderInterfaceVersion::new_current(),
+        ControlRequest::new_version_negotiation(),
+    );
+
+    println!("Sending 'NegotiateVersion' request...");
+    client
+        .send_bytes(
+            provider,
+            full_request_negotiation.into_bytes(),
+            IncludedSurbs::none(),
+        )
+        .await;
+    let response = wait_for_control_response(&mut client).await;
+    println!("response to 'NegotiateVersion' request: {response:#?}");
+
+    let full_request_descriptor: Request = Request::new_control(
+        ProviderInterfaceVersion::new_current(),
+        ControlRequest::ProviderDescriptor,
+    );
+
+    println!("Sending 'ProviderDescriptor' request...");
+    client
+        .send_bytes(
+            provider,
+            full_request_descriptor.into_bytes(),
+            IncludedSurbs::new(10),
+        )
+        .await;
+    let response = wait_for_control_response(&mut client).await;
+    println!("response to 'ProviderDescriptor' request: {response:#?}");
+
     Ok(())
 }
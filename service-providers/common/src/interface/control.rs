@@ -1,7 +1,9 @@
 // Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::interface::{Serializable, ServiceProviderMessagingError};
+use crate::interface::{
+    ExitPolicy, ProviderInterfaceVersion, Serializable, ServiceProviderMessagingError,
+};
 use nym_bin_common::build_information::BinaryBuildInformationOwned;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +12,18 @@ pub enum ControlRequest {
     Health,
     BinaryInfo,
     SupportedRequestVersions,
+
+    /// Informs the provider about the highest interface version the client supports so that
+    /// both parties could settle on the highest version they both understand.
+    // introduced in interface version 4
+    NegotiateVersion {
+        highest_supported: u8,
+    },
+
+    /// Requests the provider to describe itself, including its exit policy, so that the client
+    /// could reject disallowed destinations locally.
+    // introduced in interface version 4
+    ProviderDescriptor,
 }
 
 #[repr(u8)]
@@ -22,6 +36,12 @@ enum ControlRequestTag {
 
     /// Value tag representing [`SupportedRequestVersions`] variant of the [`ControlRequest`]
     RequestVersions = 0x02,
+
+    /// Value tag representing [`NegotiateVersion`] variant of the [`ControlRequest`]
+    NegotiateVersion = 0x03,
+
+    /// Value tag representing [`ProviderDescriptor`] variant of the [`ControlRequest`]
+    ProviderDescriptor = 0x04,
 }
 
 impl TryFrom<u8> for ControlRequestTag {
@@ -32,6 +52,8 @@ impl TryFrom<u8> for ControlRequestTag {
             _ if value == (Self::Health as u8) => Ok(Self::Health),
             _ if value == (Self::BinaryInfo as u8) => Ok(Self::BinaryInfo),
             _ if value == (Self::RequestVersions as u8) => Ok(Self::RequestVersions),
+            _ if value == (Self::NegotiateVersion as u8) => Ok(Self::NegotiateVersion),
+            _ if value == (Self::ProviderDescriptor as u8) => Ok(Self::ProviderDescriptor),
             received => Err(ServiceProviderMessagingError::InvalidControlRequestTag { received }),
         }
     }
@@ -41,8 +63,12 @@ impl Serializable for ControlRequest {
     type Error = ServiceProviderMessagingError;
 
     fn into_bytes(self) -> Vec<u8> {
-        // current variants do not require sending any data apart from the tag
-        vec![self.tag() as u8]
+        let tag = self.tag() as u8;
+        match self {
+            ControlRequest::NegotiateVersion { highest_supported } => vec![tag, highest_supported],
+            // remaining variants do not require sending any data apart from the tag
+            _ => vec![tag],
+        }
     }
 
     fn try_from_bytes(b: &[u8]) -> Result<Self, ServiceProviderMessagingError> {
@@ -55,6 +81,13 @@ impl Serializable for ControlRequest {
             ControlRequestTag::Health => Ok(ControlRequest::Health),
             ControlRequestTag::BinaryInfo => Ok(ControlRequest::BinaryInfo),
             ControlRequestTag::RequestVersions => Ok(ControlRequest::SupportedRequestVersions),
+            ControlRequestTag::NegotiateVersion => match b.get(1) {
+                Some(&highest_supported) => {
+                    Ok(ControlRequest::NegotiateVersion { highest_supported })
+                }
+                None => Err(ServiceProviderMessagingError::IncompleteVersionNegotiationRequest),
+            },
+            ControlRequestTag::ProviderDescriptor => Ok(ControlRequest::ProviderDescriptor),
         }
     }
 }
@@ -65,6 +98,17 @@ impl ControlRequest {
             ControlRequest::Health => ControlRequestTag::Health,
             ControlRequest::BinaryInfo => ControlRequestTag::BinaryInfo,
             ControlRequest::SupportedRequestVersions => ControlRequestTag::RequestVersions,
+            ControlRequest::NegotiateVersion { .. } => ControlRequestTag::NegotiateVersion,
+            ControlRequest::ProviderDescriptor => ControlRequestTag::ProviderDescriptor,
+        }
+    }
+
+    pub fn new_version_negotiation() -> Self {
+        ControlRequest::NegotiateVersion {
+            // the current version is always versioned
+            highest_supported: ProviderInterfaceVersion::new_current()
+                .as_u8()
+                .unwrap_or_default(),
         }
     }
 }
@@ -81,9 +125,27 @@ pub struct SupportedVersions {
     pub provider_version: String,
 }
 
+/// Self-description of a service provider.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProviderDescriptor {
+    pub supported_versions: SupportedVersions,
+
+    /// Destinations the provider is willing to relay traffic to, if applicable.
+    pub exit_policy: Option<ExitPolicy>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
-    message: String,
+    pub message: String,
+}
+
+/// Unsolicited notification sent by a service provider once the bandwidth it's willing to
+/// relay on behalf of the client is about to run out.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BandwidthWarning {
+    /// Number of bytes the provider is still willing to relay, if known.
+    pub remaining_bytes: Option<u64>,
+    pub message: String,
 }
 
 #[derive(Debug)]
@@ -91,6 +153,16 @@ pub enum ControlResponse {
     Health,
     BinaryInfo(Box<BinaryInformation>),
     SupportedRequestVersions(SupportedVersions),
+
+    /// The interface version both parties should use from now on.
+    // introduced in interface version 4
+    NegotiatedVersion(u8),
+
+    // introduced in interface version 4
+    BandwidthWarning(BandwidthWarning),
+
+    // introduced in interface version 4
+    ProviderDescriptor(Box<ProviderDescriptor>),
     Error(ErrorResponse),
 }
 
@@ -105,6 +177,15 @@ enum ControlResponseTag {
     /// Value tag representing [`SupportedRequestVersions`] variant of the [`ControlResponse`]
     SupportedRequestVersions = 0x02,
 
+    /// Value tag representing [`NegotiatedVersion`] variant of the [`ControlResponse`]
+    NegotiatedVersion = 0x03,
+
+    /// Value tag representing [`BandwidthWarning`] variant of the [`ControlResponse`]
+    BandwidthWarning = 0x04,
+
+    /// Value tag representing [`ProviderDescriptor`] variant of the [`ControlResponse`]
+    ProviderDescriptor = 0x05,
+
     /// Value tag representing [`Error`] variant of the [`ControlResponse`]
     Error = 0xFF,
 }
@@ -119,6 +200,9 @@ impl TryFrom<u8> for ControlResponseTag {
             _ if value == (Self::SupportedRequestVersions as u8) => {
                 Ok(Self::SupportedRequestVersions)
             }
+            _ if value == (Self::NegotiatedVersion as u8) => Ok(Self::NegotiatedVersion),
+            _ if value == (Self::BandwidthWarning as u8) => Ok(Self::BandwidthWarning),
+            _ if value == (Self::ProviderDescriptor as u8) => Ok(Self::ProviderDescriptor),
             _ if value == (Self::Error as u8) => Ok(Self::Error),
             received => Err(ServiceProviderMessagingError::InvalidControlResponseTag { received }),
         }
@@ -152,9 +236,27 @@ impl Serializable for ControlResponse {
                 Ok(supported_versions) => Ok(ControlResponse::SupportedRequestVersions(
                     supported_versions,
                 )),
-                Err(source) => {
-                    Err(ServiceProviderMessagingError::MalformedErrorControlResponse { source })
-                }
+                Err(source) => Err(
+                    ServiceProviderMessagingError::MalformedSupportedVersionsResponse { source },
+                ),
+            },
+            ControlResponseTag::NegotiatedVersion => match b.get(1) {
+                Some(&version) => Ok(ControlResponse::NegotiatedVersion(version)),
+                None => Err(ServiceProviderMessagingError::IncompleteVersionNegotiationResponse),
+            },
+            ControlResponseTag::BandwidthWarning => match serde_json::from_slice(&b[1..]) {
+                Ok(warning) => Ok(ControlResponse::BandwidthWarning(warning)),
+                Err(source) => Err(
+                    ServiceProviderMessagingError::MalformedBandwidthWarningControlResponse {
+                        source,
+                    },
+                ),
+            },
+            ControlResponseTag::ProviderDescriptor => match serde_json::from_slice(&b[1..]) {
+                Ok(descriptor) => Ok(ControlResponse::ProviderDescriptor(descriptor)),
+                Err(source) => Err(
+                    ServiceProviderMessagingError::MalformedProviderDescriptorResponse { source },
+                ),
             },
             ControlResponseTag::Error => match serde_json::from_slice(&b[1..]) {
                 Ok(error_response) => Ok(ControlResponse::Error(error_response)),
@@ -174,10 +276,29 @@ impl ControlResponse {
             ControlResponse::SupportedRequestVersions(_) => {
                 ControlResponseTag::SupportedRequestVersions
             }
+            ControlResponse::NegotiatedVersion(_) => ControlResponseTag::NegotiatedVersion,
+            ControlResponse::BandwidthWarning(_) => ControlResponseTag::BandwidthWarning,
+            ControlResponse::ProviderDescriptor(_) => ControlResponseTag::ProviderDescriptor,
             ControlResponse::Error(_) => ControlResponseTag::Error,
         }
     }
 
+    pub fn new_error<S: Into<String>>(message: S) -> Self {
+        ControlResponse::Error(ErrorResponse {
+            message: message.into(),
+        })
+    }
+
+    pub fn new_bandwidth_warning<S: Into<String>>(
+        remaining_bytes: Option<u64>,
+        message: S,
+    ) -> Self {
+        ControlResponse::BandwidthWarning(BandwidthWarning {
+            remaining_bytes,
+            message: message.into(),
+        })
+    }
+
     fn serialize_inner(self) -> Vec<u8> {
         match self {
             ControlResponse::Health => Vec::new(),
@@ -189,7 +310,7 @@ impl ControlResponse {
                 // fail, or if `T` contains a map with non-string keys.
                 // ```
                 //
-                // And since `BinaryInformation` does not contain any maps and its serialization
+                // And since `BinaryInformation` only contains maps with string keys and its serialization
                 // is fully derived with serde macros, the below cannot possibly fail,
                 // so the unwrap is fine
                 // (unless the serde's macro is bugged but at this point we're already out of luck)
@@ -199,6 +320,89 @@ impl ControlResponse {
             ControlResponse::SupportedRequestVersions(supported_versions) => {
                 serde_json::to_vec(&supported_versions).unwrap()
             }
+            ControlResponse::NegotiatedVersion(version) => vec![version],
+            ControlResponse::BandwidthWarning(warning) => serde_json::to_vec(&warning).unwrap(),
+            ControlResponse::ProviderDescriptor(descriptor) => {
+                serde_json::to_vec(&descriptor).unwrap()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_negotiation_serde() {
+        let request = ControlRequest::NegotiateVersion {
+            highest_supported: 42,
+        };
+        match ControlRequest::try_from_bytes(&request.into_bytes()).unwrap() {
+            ControlRequest::NegotiateVersion { highest_supported } => {
+                assert_eq!(highest_supported, 42)
+            }
+            _ => panic!("unexpected request"),
+        }
+        assert!(matches!(
+            ControlRequest::try_from_bytes(&[ControlRequestTag::NegotiateVersion as u8]),
+            Err(ServiceProviderMessagingError::IncompleteVersionNegotiationRequest)
+        ));
+
+        let response = ControlResponse::NegotiatedVersion(4);
+        match ControlResponse::try_from_bytes(&response.into_bytes()).unwrap() {
+            ControlResponse::NegotiatedVersion(version) => assert_eq!(version, 4),
+            _ => panic!("unexpected response"),
+        }
+    }
+
+    #[test]
+    fn bandwidth_warning_serde() {
+        let response = ControlResponse::new_bandwidth_warning(Some(1024), "running low");
+        match ControlResponse::try_from_bytes(&response.into_bytes()).unwrap() {
+            ControlResponse::BandwidthWarning(warning) => {
+                assert_eq!(warning.remaining_bytes, Some(1024));
+                assert_eq!(warning.message, "running low");
+            }
+            _ => panic!("unexpected response"),
+        }
+    }
+
+    #[test]
+    fn provider_descriptor_serde() {
+        let request = ControlRequest::ProviderDescriptor;
+        assert!(matches!(
+            ControlRequest::try_from_bytes(&request.into_bytes()).unwrap(),
+            ControlRequest::ProviderDescriptor
+        ));
+
+        let exit_policy = ExitPolicy {
+            open_proxy: false,
+            allowed_domains: vec!["nymtech.net".to_string()],
+            allowed_ip_networks: vec!["1.2.3.0/24".parse().unwrap()],
+        };
+        let response = ControlResponse::ProviderDescriptor(Box::new(ProviderDescriptor {
+            supported_versions: SupportedVersions {
+                interface_version: "4".to_string(),
+                provider_version: "3".to_string(),
+            },
+            exit_policy: Some(exit_policy.clone()),
+        }));
+        match ControlResponse::try_from_bytes(&response.into_bytes()).unwrap() {
+            ControlResponse::ProviderDescriptor(descriptor) => {
+                assert_eq!(descriptor.supported_versions.interface_version, "4");
+                assert_eq!(descriptor.exit_policy, Some(exit_policy));
+            }
+            _ => panic!("unexpected response"),
+        }
+    }
+
+    #[test]
+    fn error_serde() {
+        let response = ControlResponse::new_error("something went wrong");
+        match ControlResponse::try_from_bytes(&response.into_bytes()).unwrap() {
+            ControlResponse::Error(err) => assert_eq!(err.message, "something went wrong"),
+            _ => panic!("unexpected response"),
         }
     }
 }
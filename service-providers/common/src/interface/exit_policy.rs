@@ -0,0 +1,120 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use ipnetwork::IpNetwork;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+
+/// Set of destinations a service provider is willing to relay traffic to.
+///
+/// Note that the policy does not carry any port information as providers currently do not
+/// filter on it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExitPolicy {
+    /// Indicates whether the provider relays traffic to any destination, in which case
+    /// the remaining fields are meaningless.
+    pub open_proxy: bool,
+
+    /// Allowed root domains. Any of their subdomains are allowed as well.
+    pub allowed_domains: Vec<String>,
+
+    pub allowed_ip_networks: Vec<IpNetwork>,
+}
+
+impl ExitPolicy {
+    pub fn new_open_proxy() -> Self {
+        ExitPolicy {
+            open_proxy: true,
+            ..Default::default()
+        }
+    }
+
+    /// Checks whether the provided destination, in the `host:port` form, is allowed by this policy.
+    ///
+    /// The check is permissive with respect to the provider's own filtering, i.e. a destination
+    /// accepted here might still get rejected by the provider, but not the other way around.
+    pub fn allows(&self, destination: &str) -> bool {
+        if self.open_proxy {
+            return true;
+        }
+
+        // the same order of checks as performed by the provider itself so that
+        // we wouldn't strip what looks like a port from an ipv6 address
+        if let Ok(socket_addr) = destination.parse::<SocketAddr>() {
+            self.allows_ip_address(socket_addr.ip())
+        } else if let Ok(ip_addr) = destination.parse::<IpAddr>() {
+            self.allows_ip_address(ip_addr)
+        } else {
+            let host = match destination.rsplit_once(':') {
+                Some((host, _port)) => host,
+                None => destination,
+            };
+            self.allows_domain(host)
+        }
+    }
+
+    fn allows_ip_address(&self, address: IpAddr) -> bool {
+        self.allowed_ip_networks
+            .iter()
+            .any(|network| network.contains(address))
+    }
+
+    fn allows_domain(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        self.allowed_domains.iter().any(|domain| {
+            host == *domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .map(|subdomain| subdomain.ends_with('.'))
+                    .unwrap_or_default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> ExitPolicy {
+        ExitPolicy {
+            open_proxy: false,
+            allowed_domains: vec!["nymtech.net".to_string(), "example.co.uk".to_string()],
+            allowed_ip_networks: vec![
+                "1.2.3.0/24".parse().unwrap(),
+                "2001:db8::/32".parse().unwrap(),
+            ],
+        }
+    }
+
+    #[test]
+    fn open_proxy_allows_everything() {
+        let policy = ExitPolicy::new_open_proxy();
+        assert!(policy.allows("foomp.com:443"));
+        assert!(policy.allows("8.8.8.8:53"));
+    }
+
+    #[test]
+    fn domains_and_their_subdomains_are_allowed() {
+        let policy = policy();
+        assert!(policy.allows("nymtech.net:443"));
+        assert!(policy.allows("validator.nymtech.net:443"));
+        assert!(policy.allows("Foo.Example.co.uk:80"));
+        assert!(policy.allows("nymtech.net"));
+
+        assert!(!policy.allows("notnymtech.net:443"));
+        assert!(!policy.allows("nymtech.net.evil.com:443"));
+        assert!(!policy.allows("co.uk:80"));
+    }
+
+    #[test]
+    fn ip_addresses_are_checked_against_networks() {
+        let policy = policy();
+        assert!(policy.allows("1.2.3.4:443"));
+        assert!(policy.allows("1.2.3.4"));
+        assert!(policy.allows("[2001:db8::1]:443"));
+        assert!(policy.allows("2001:db8::1"));
+
+        assert!(!policy.allows("1.2.4.4:443"));
+        assert!(!policy.allows("[2001:db9::1]:443"));
+    }
+}
@@ -1,15 +1,20 @@
 // Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-pub use control::{BinaryInformation, ControlRequest, ControlResponse, SupportedVersions};
+pub use control::{
+    BandwidthWarning, BinaryInformation, ControlRequest, ControlResponse, ErrorResponse,
+    ProviderDescriptor, SupportedVersions,
+};
+pub use exit_policy::ExitPolicy;
 pub use request::{Request, RequestContent, ServiceProviderRequest};
 pub use response::{Response, ResponseContent, ServiceProviderResponse};
-pub use version::{ProviderInterfaceVersion, RequestVersion, Version};
+pub use version::{ProviderInterfaceVersion, RequestVersion, Version, INTERFACE_VERSION};
 
 use std::fmt::{Display, Formatter};
 use thiserror::Error;
 
 mod control;
+mod exit_policy;
 mod request;
 mod response;
 mod version;
@@ -65,6 +70,18 @@ pub enum ServiceProviderMessagingError {
 
     #[error("the received supported versions control response was malformed: {source}")]
     MalformedSupportedVersionsResponse { source: serde_json::Error },
+
+    #[error("the received bandwidth warning control response was malformed: {source}")]
+    MalformedBandwidthWarningControlResponse { source: serde_json::Error },
+
+    #[error("the received provider descriptor control response was malformed: {source}")]
+    MalformedProviderDescriptorResponse { source: serde_json::Error },
+
+    #[error("the version negotiation request did not specify the supported version")]
+    IncompleteVersionNegotiationRequest,
+
+    #[error("the version negotiation response did not specify the negotiated version")]
+    IncompleteVersionNegotiationResponse,
 }
 
 // can't use 'normal' trait (i.e. Serialize/Deserialize from serde) as `Socks5Message` uses custom serialization
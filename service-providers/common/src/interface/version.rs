@@ -10,7 +10,11 @@ pub const INITIAL_INTERFACE_VERSION: u8 = 3;
 
 /// Defines the current version of the communication interface between clients and service providers.
 /// It has to be incremented for any breaking change.
-pub const INTERFACE_VERSION: u8 = 3;
+pub const INTERFACE_VERSION: u8 = 4;
+
+/// Defines the first version of the interface that supports version negotiation as well as
+/// the unsolicited bandwidth warnings.
+pub const VERSION_NEGOTIATION_INTERFACE_VERSION: u8 = 4;
 
 /// Defines full version of particular request that includes version of common service provider interface
 /// and provider-specific protocol.
@@ -26,6 +30,18 @@ pub struct RequestVersion<T: ServiceProviderRequest = EmptyMessage> {
     pub provider_protocol: T::ProtocolVersion,
 }
 
+impl ProviderInterfaceVersion {
+    /// Returns the highest version supported by both parties given the highest version
+    /// supported by the remote.
+    pub fn negotiate(remote_highest: u8) -> ProviderInterfaceVersion {
+        ProviderInterfaceVersion::from(remote_highest).min(ProviderInterfaceVersion::new_current())
+    }
+
+    pub fn supports_version_negotiation(&self) -> bool {
+        *self >= ProviderInterfaceVersion::Versioned(VERSION_NEGOTIATION_INTERFACE_VERSION)
+    }
+}
+
 impl<T: ServiceProviderRequest> RequestVersion<T> {
     pub fn new(
         provider_interface: ProviderInterfaceVersion,
@@ -130,4 +146,20 @@ mod tests {
         assert!(ProviderInterfaceVersion::Versioned(1) < ProviderInterfaceVersion::Versioned(2));
         assert!(ProviderInterfaceVersion::Versioned(42) < ProviderInterfaceVersion::Versioned(100));
     }
+
+    #[test]
+    fn version_negotiation() {
+        assert_eq!(
+            ProviderInterfaceVersion::negotiate(1),
+            ProviderInterfaceVersion::Legacy
+        );
+        assert_eq!(
+            ProviderInterfaceVersion::negotiate(3),
+            ProviderInterfaceVersion::Versioned(3)
+        );
+        assert_eq!(
+            ProviderInterfaceVersion::negotiate(u8::MAX),
+            ProviderInterfaceVersion::new_current()
+        );
+    }
 }
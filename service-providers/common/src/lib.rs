@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::interface::{
-    BinaryInformation, ControlRequest, ControlResponse, EmptyMessage, ProviderInterfaceVersion,
-    Request, RequestContent, Response, ResponseContent, ServiceProviderRequest, SupportedVersions,
+    BinaryInformation, ControlRequest, ControlResponse, EmptyMessage, ExitPolicy,
+    ProviderDescriptor, ProviderInterfaceVersion, Request, RequestContent, Response,
+    ResponseContent, ServiceProviderRequest, SupportedVersions,
 };
 use async_trait::async_trait;
 use nym_sphinx_anonymous_replies::requests::AnonymousSenderTag;
@@ -85,9 +86,24 @@ where
                 ControlRequest::SupportedRequestVersions => {
                     let versions = self.handle_supported_request_versions_request().await?;
                     Some(ControlResponse::SupportedRequestVersions(versions))
-                } //
-                  // TODO: if we ever add new request for interface version 4 (or higher),
-                  // we need to include a check to make sure we return a `None` if passed `interface_version` was 3
+                }
+                // Version 4 requests:
+                ControlRequest::NegotiateVersion { highest_supported } => {
+                    if interface_version.supports_version_negotiation() {
+                        let negotiated = self.handle_version_negotiation(highest_supported).await?;
+                        negotiated.as_u8().map(ControlResponse::NegotiatedVersion)
+                    } else {
+                        None
+                    }
+                }
+                ControlRequest::ProviderDescriptor => {
+                    if interface_version.supports_version_negotiation() {
+                        let descriptor = self.handle_provider_descriptor_request().await?;
+                        Some(ControlResponse::ProviderDescriptor(Box::new(descriptor)))
+                    } else {
+                        None
+                    }
+                }
             };
             Ok(response)
         }
@@ -113,6 +129,29 @@ where
         })
     }
 
+    // note: the provider doesn't keep any per-client state, so it's up to the client
+    // to use the negotiated version in all subsequent requests
+    async fn handle_version_negotiation(
+        &self,
+        remote_highest: u8,
+    ) -> Result<ProviderInterfaceVersion, Self::ServiceProviderError> {
+        Ok(ProviderInterfaceVersion::negotiate(remote_highest))
+    }
+
+    async fn handle_provider_descriptor_request(
+        &self,
+    ) -> Result<ProviderDescriptor, Self::ServiceProviderError> {
+        Ok(ProviderDescriptor {
+            supported_versions: self.handle_supported_request_versions_request().await?,
+            exit_policy: self.exit_policy().await?,
+        })
+    }
+
+    // providers that don't relay traffic outside the mixnet have no exit policy to speak of
+    async fn exit_policy(&self) -> Result<Option<ExitPolicy>, Self::ServiceProviderError> {
+        Ok(None)
+    }
+
     async fn handle_provider_data_request(
         &mut self,
         sender: Option<AnonymousSenderTag>,
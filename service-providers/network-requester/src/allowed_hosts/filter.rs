@@ -5,6 +5,8 @@ use super::HostsStore;
 use crate::allowed_hosts::group::HostsGroup;
 use crate::allowed_hosts::standard_list::StandardList;
 use crate::allowed_hosts::stored_allowed_hosts::StoredAllowedHosts;
+use nym_service_providers_common::interface::ExitPolicy;
+use std::collections::HashSet;
 use std::net::{IpAddr, SocketAddr};
 
 #[derive(Debug)]
@@ -117,6 +119,35 @@ impl OutboundRequestFilter {
         self.check_standard_list(request_host).await
     }
 
+    /// Combines the `allowed_hosts` and the standard list into a policy that can be published
+    /// to the clients.
+    pub(crate) async fn exit_policy(&self) -> ExitPolicy {
+        let mut domains = HashSet::new();
+        let mut ip_nets = HashSet::new();
+
+        for group in [
+            &self.allowed_hosts.get().await.data,
+            &*self.standard_list.get().await,
+        ] {
+            domains.extend(
+                group
+                    .domains
+                    .iter()
+                    .map(|domain| domain.to_ascii_lowercase()),
+            );
+            ip_nets.extend(group.ip_nets.iter().copied());
+        }
+
+        let mut allowed_domains: Vec<_> = domains.into_iter().collect();
+        allowed_domains.sort();
+
+        ExitPolicy {
+            open_proxy: false,
+            allowed_domains,
+            allowed_ip_networks: ip_nets.into_iter().collect(),
+        }
+    }
+
     /// Returns `true` if a host's root domain is in the `allowed_hosts` list.
     ///
     /// If it's not in the list, return `false` and write it to the `unknown_hosts` storefile.
@@ -478,6 +509,23 @@ mod tests {
         }
     }
 
+    #[cfg(test)]
+    mod published_exit_policy {
+        use super::*;
+
+        #[tokio::test]
+        async fn contains_allowed_hosts() {
+            let filter = setup_with_allowed(&["NymTech.net", "1.2.3.4/24"]);
+            let policy = filter.exit_policy().await;
+
+            assert!(!policy.open_proxy);
+            assert_eq!(policy.allowed_domains, vec!["nymtech.net".to_string()]);
+            assert!(policy.allows("nymtech.net:443"));
+            assert!(policy.allows("1.2.3.42:443"));
+            assert!(!policy.allows("edwardsnowden.com:443"));
+        }
+    }
+
     #[cfg(test)]
     mod creating_a_new_host_store {
         use super::*;
@@ -2,16 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::cli::try_upgrade_v1_1_13_config;
-use crate::{
-    cli::{override_config, OverrideConfig},
-    config::Config,
-    error::NetworkRequesterError,
-};
+use crate::cli::{override_config, OverrideConfig};
 use clap::Args;
 use nym_bin_common::output_format::OutputFormat;
 use nym_config::NymConfig;
 use nym_credential_storage::persistent_storage::PersistentStorage;
 use nym_crypto::asymmetric::identity;
+use nym_network_requester::{config::Config, error::NetworkRequesterError};
 use nym_sphinx::addressing::clients::Recipient;
 use serde::Serialize;
 use std::fmt::Display;
@@ -3,22 +3,22 @@
 
 use clap::{CommandFactory, Parser, Subcommand};
 use log::info;
-use nym_bin_common::build_information::BinaryBuildInformation;
+use nym_bin_common::build_information::BuildInfo;
 use nym_bin_common::completions::{fig_generate, ArgShell};
 use nym_config::NymConfig;
 
-use crate::config::old_config_v1_1_13::OldConfigV1_1_13;
-use crate::{
+use nym_network_requester::config::old_config_v1_1_13::OldConfigV1_1_13;
+use nym_network_requester::{
     config::{BaseConfig, Config},
     error::NetworkRequesterError,
+    BUILD_INFORMATION,
 };
 
 mod init;
 mod run;
 
 lazy_static::lazy_static! {
-    pub static ref PRETTY_BUILD_INFORMATION: String =
-        BinaryBuildInformation::new(env!("CARGO_PKG_VERSION")).pretty_print();
+    pub static ref PRETTY_BUILD_INFORMATION: String = BUILD_INFORMATION.pretty_print();
 }
 
 // Helper for passing LONG_VERSION to clap
@@ -40,6 +40,9 @@ pub(crate) struct Cli {
 #[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 pub(crate) enum Commands {
+    /// Show build information of this binary
+    BuildInfo(BuildInfo),
+
     /// Initialize a network-requester. Do this first!
     Init(init::Init),
 
@@ -89,6 +92,7 @@ pub(crate) async fn execute(args: Cli) -> Result<(), NetworkRequesterError> {
     let bin_name = "nym-network-requester";
 
     match &args.command {
+        Commands::BuildInfo(m) => m.execute(&BUILD_INFORMATION),
         Commands::Init(m) => init::execute(m).await?,
         Commands::Run(m) => run::execute(m).await?,
         Commands::Completions(s) => s.generate(&mut Cli::command(), bin_name),
@@ -2,14 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::cli::try_upgrade_v1_1_13_config;
-use crate::{
-    cli::{override_config, OverrideConfig},
-    config::Config,
-    error::NetworkRequesterError,
-};
+use crate::cli::{override_config, OverrideConfig};
 use clap::Args;
 use nym_bin_common::version_checker;
 use nym_config::NymConfig;
+use nym_network_requester::{config::Config, error::NetworkRequesterError};
 use nym_sphinx::addressing::clients::Recipient;
 
 const ENABLE_STATISTICS: &str = "enable-statistics";
@@ -156,7 +153,7 @@ pub(crate) async fn execute(args: &Run) -> Result<(), NetworkRequesterError> {
         .unwrap_or(None);
 
     log::info!("Starting socks5 service provider");
-    let server = crate::core::NRServiceProviderBuilder::new(
+    let server = nym_network_requester::core::NRServiceProviderBuilder::new(
         config,
         args.open_proxy,
         args.enable_statistics,
@@ -3,6 +3,7 @@
 
 use crate::config::template::config_template;
 use nym_client_core::config::ClientCoreConfigTrait;
+use nym_config::paths::default_nym_directory;
 use nym_config::{NymConfig, OptionalSet};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -27,6 +28,9 @@ pub struct Config {
     #[serde(default)]
     pub network_requester: NetworkRequster,
 
+    #[serde(default)]
+    pub network_requester_quotas: Quotas,
+
     #[serde(default)]
     pub network_requester_debug: Debug,
 }
@@ -37,15 +41,13 @@ impl NymConfig for Config {
     }
 
     fn default_root_directory() -> PathBuf {
-        dirs::home_dir()
-            .expect("Failed to evaluate $HOME value")
-            .join(".nym")
-            .join("service-providers")
+        default_nym_directory("service-providers")
+            .expect("no data directory known for this OS")
             .join("network-requester")
     }
 
     fn try_default_root_directory() -> Option<PathBuf> {
-        dirs::home_dir().map(|path| path.join(".nym").join("clients"))
+        default_nym_directory("clients")
     }
 
     fn root_directory(&self) -> PathBuf {
@@ -91,6 +93,20 @@ impl Default for NetworkRequster {
     }
 }
 
+/// Usage limits imposed on each of the clients of the network requester. Zero disables the limit.
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Quotas {
+    /// Maximum number of new connections a single client can request within a minute.
+    pub max_requests_per_minute: u32,
+
+    /// Maximum number of connections a single client can have open at the same time.
+    pub max_concurrent_connections: u32,
+
+    /// Maximum number of bytes that can be sent and received on behalf of a single client within a day.
+    pub max_bytes_per_day: u64,
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct Debug {
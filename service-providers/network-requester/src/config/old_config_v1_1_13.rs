@@ -3,6 +3,7 @@
 
 use crate::config::Config;
 use nym_client_core::config::old_config_v1_1_13::OldConfigV1_1_13 as OldBaseConfigV1_1_13;
+use nym_config::paths::default_nym_directory;
 use nym_config::NymConfig;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -22,15 +23,13 @@ impl NymConfig for OldConfigV1_1_13 {
 
     // TODO: merge base dir with `HostStore`.
     fn default_root_directory() -> PathBuf {
-        dirs::home_dir()
-            .expect("Failed to evaluate $HOME value")
-            .join(".nym")
-            .join("service-providers")
+        default_nym_directory("service-providers")
+            .expect("no data directory known for this OS")
             .join("network-requester")
     }
 
     fn try_default_root_directory() -> Option<PathBuf> {
-        dirs::home_dir().map(|path| path.join(".nym").join("clients"))
+        default_nym_directory("clients")
     }
 
     fn root_directory(&self) -> PathBuf {
@@ -55,6 +54,7 @@ impl From<OldConfigV1_1_13> for Config {
         Config {
             base: value.base.into(),
             network_requester: Default::default(),
+            network_requester_quotas: Default::default(),
             network_requester_debug: Default::default(),
         }
     }
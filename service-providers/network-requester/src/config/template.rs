@@ -33,6 +33,11 @@ nym_api_urls = [{{#each client.nym_api_urls }}
     '{{this}}',
 {{/each}}]
 
+# Identity keys of the mixnodes and gateways that should never be used by this client.
+ignored_nodes = [{{#each client.ignored_nodes }}
+    '{{this}}',
+{{/each}}]
+
 # Path to file containing private identity key.
 private_identity_key_file = '{{ client.private_identity_key_file }}'
 
@@ -85,12 +90,42 @@ allowed_list_location = '{{ network_requester.allowed_list_location }}'
 # Location of the file containing our unknown.list
 unknown_list_location = '{{ network_requester.unknown_list_location }}'
 
+# Usage limits imposed on each of the clients. Setting any of them to 0 disables the limit.
+[network_requester_quotas]
+# Maximum number of new connections a single client can request within a minute.
+max_requests_per_minute = {{ network_requester_quotas.max_requests_per_minute }}
+
+# Maximum number of connections a single client can have open at the same time.
+max_concurrent_connections = {{ network_requester_quotas.max_concurrent_connections }}
+
+# Maximum number of bytes that can be sent and received on behalf of a single client within a day.
+max_bytes_per_day = {{ network_requester_quotas.max_bytes_per_day }}
+
 ##### logging configuration options #####
 
 [logging]
 
 # TODO
 
+##### proxy configuration options #####
+# Proxies through which the HTTP(S) requests of the particular classes of endpoints are routed.
+# The nym API can be reached through either HTTP(S) or SOCKS5 proxies (use the 'socks5h' scheme
+# for the '.onion' endpoints), while nyxd can only be reached through HTTP(S) ones.
+
+[proxy]
+{{#if proxy.directory }}
+# proxy used for retrieving the network topology and the list of available gateways
+directory = '{{ proxy.directory }}'
+{{/if}}
+{{#if proxy.nym_api }}
+# proxy used for all the other requests sent to the nym API
+nym_api = '{{ proxy.nym_api }}'
+{{/if}}
+{{#if proxy.nyxd }}
+# proxy used for communicating with the nyxd validators
+nyxd = '{{ proxy.nyxd }}'
+{{/if}}
+
 
 ##### debug configuration options #####
 # The following options should not be modified unless you know EXACTLY what you are doing
@@ -7,16 +7,17 @@ use crate::allowed_hosts::stored_allowed_hosts::{start_allowed_list_reloader, St
 use crate::allowed_hosts::{OutboundRequestFilter, StandardList};
 use crate::config::Config;
 use crate::error::NetworkRequesterError;
+use crate::quotas::{TrackedConnection, UsageQuotas};
 use crate::reply::MixnetMessage;
 use crate::statistics::ServiceStatisticsCollector;
 use crate::{reply, socks5};
 use async_trait::async_trait;
 use futures::channel::mpsc;
 use log::warn;
-use nym_bin_common::build_information::BinaryBuildInformation;
 use nym_network_defaults::NymNetworkDetails;
+use nym_sdk::mixnet::EmbeddedConnection;
 use nym_service_providers_common::interface::{
-    BinaryInformation, ProviderInterfaceVersion, Request, RequestVersion,
+    BinaryInformation, ExitPolicy, ProviderInterfaceVersion, Request, RequestVersion,
 };
 use nym_service_providers_common::ServiceProvider;
 use nym_socks5_proxy_helpers::connection_controller::{
@@ -24,8 +25,9 @@ use nym_socks5_proxy_helpers::connection_controller::{
 };
 use nym_socks5_proxy_helpers::proxy_runner::{MixProxyReader, MixProxySender};
 use nym_socks5_requests::{
-    ConnectRequest, ConnectionId, NetworkData, SendRequest, Socks5ProtocolVersion,
-    Socks5ProviderRequest, Socks5Request, Socks5RequestContent, Socks5Response,
+    ConnectRequest, ConnectionId, NetworkData, SendDatagramRequest, SendRequest,
+    Socks5ProtocolVersion, Socks5ProviderRequest, Socks5Request, Socks5RequestContent,
+    Socks5Response, UdpAssociateRequest,
 };
 use nym_sphinx::addressing::clients::Recipient;
 use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
@@ -33,9 +35,12 @@ use nym_statistics_common::collector::StatisticsSender;
 use nym_task::connections::LaneQueueLengths;
 use nym_task::{TaskClient, TaskManager};
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 // Since it's an atomic, it's safe to be kept static and shared across threads
 static ACTIVE_PROXIES: AtomicUsize = AtomicUsize::new(0);
+static ACTIVE_ASSOCIATIONS: AtomicUsize = AtomicUsize::new(0);
 
 pub(crate) fn new_legacy_request_version() -> RequestVersion<Socks5Request> {
     RequestVersion {
@@ -54,6 +59,7 @@ pub struct NRServiceProviderBuilder {
     stats_provider_addr: Option<Recipient>,
     standard_list: StandardList,
     allowed_hosts: StoredAllowedHosts,
+    embedded_gateway_connection: Option<EmbeddedConnection>,
 }
 
 struct NRServiceProvider {
@@ -65,6 +71,7 @@ struct NRServiceProvider {
     mix_input_sender: MixProxySender<MixnetMessage>,
     //shared_lane_queue_lengths: LaneQueueLengths,
     stats_collector: Option<ServiceStatisticsCollector>,
+    quotas: Arc<Mutex<UsageQuotas>>,
     shutdown: TaskManager,
 }
 
@@ -97,10 +104,18 @@ impl ServiceProvider<Socks5Request> for NRServiceProvider {
     ) -> Result<BinaryInformation, Self::ServiceProviderError> {
         Ok(BinaryInformation {
             binary_name: env!("CARGO_PKG_NAME").to_string(),
-            build_information: BinaryBuildInformation::new(env!("CARGO_PKG_VERSION")).to_owned(),
+            build_information: crate::BUILD_INFORMATION.to_owned(),
         })
     }
 
+    async fn exit_policy(&self) -> Result<Option<ExitPolicy>, Self::ServiceProviderError> {
+        if self.open_proxy {
+            Ok(Some(ExitPolicy::new_open_proxy()))
+        } else {
+            Ok(Some(self.outbound_request_filter.exit_policy().await))
+        }
+    }
+
     async fn handle_provider_data_request(
         &mut self,
         sender: Option<AnonymousSenderTag>,
@@ -143,8 +158,13 @@ impl ServiceProvider<Socks5Request> for NRServiceProvider {
                             .processed(remote_addr, req.data.len() as u32);
                     }
                 }
-                self.handle_proxy_send(req)
+                self.handle_proxy_send(req).await
             }
+            Socks5RequestContent::UdpAssociate(req) => {
+                self.handle_udp_associate(request_version, sender, req)
+                    .await
+            }
+            Socks5RequestContent::SendDatagram(req) => self.handle_send_datagram(req).await,
         }
 
         Ok(None)
@@ -174,13 +194,23 @@ impl NRServiceProviderBuilder {
             stats_provider_addr,
             standard_list,
             allowed_hosts,
+            embedded_gateway_connection: None,
         }
     }
 
+    /// Run the network requester within the gateway process, exchanging messages with it
+    /// through the provided in-process connection rather than the websocket.
+    #[must_use]
+    pub fn with_embedded_gateway_connection(mut self, connection: EmbeddedConnection) -> Self {
+        self.embedded_gateway_connection = Some(connection);
+        self
+    }
+
     /// Start all subsystems
     pub async fn run_service_provider(self) -> Result<(), NetworkRequesterError> {
         // Connect to the mixnet
-        let mixnet_client = create_mixnet_client(self.config.get_base()).await?;
+        let mixnet_client =
+            create_mixnet_client(self.config.get_base(), self.embedded_gateway_connection).await?;
 
         // channels responsible for managing messages that are to be sent to the mix network. The receiver is
         // going to be used by `mixnet_response_listener`
@@ -214,7 +244,13 @@ impl NRServiceProviderBuilder {
             None
         };
 
+        let quotas = Arc::new(Mutex::new(UsageQuotas::new(
+            self.config.network_requester_quotas,
+        )));
+
         let stats_collector_clone = stats_collector.clone();
+        let quotas_clone = Arc::clone(&quotas);
+        let controller_sender_clone = controller_sender.clone();
         let mixnet_client_sender = mixnet_client.sender();
         let self_address = *mixnet_client.nym_address();
 
@@ -224,6 +260,8 @@ impl NRServiceProviderBuilder {
                 mixnet_client_sender,
                 mix_input_receiver,
                 stats_collector_clone,
+                quotas_clone,
+                controller_sender_clone,
             )
             .await;
         });
@@ -249,6 +287,7 @@ impl NRServiceProviderBuilder {
             mix_input_sender,
             //shared_lane_queue_lengths: mixnet_client.shared_lane_queue_lengths(),
             stats_collector,
+            quotas,
             shutdown,
         };
 
@@ -292,6 +331,8 @@ impl NRServiceProvider {
         mut mixnet_client_sender: nym_sdk::mixnet::MixnetClientSender,
         mut mix_input_reader: MixProxyReader<MixnetMessage>,
         stats_collector: Option<ServiceStatisticsCollector>,
+        quotas: Arc<Mutex<UsageQuotas>>,
+        controller_sender: ControllerSender,
     ) {
         loop {
             tokio::select! {
@@ -312,7 +353,30 @@ impl NRServiceProvider {
                             }
                         }
 
-                        let response_message = msg.into_input_message();
+                        let usage = quotas.lock().expect("quotas lock got poisoned").record_bytes(
+                            msg.connection_id,
+                            msg.data_size(),
+                            Instant::now(),
+                        );
+                        // the response that went over the quota is replaced with the notification
+                        // about it and the connection gets closed
+                        let response_message = match usage {
+                            Ok(_) => msg.into_input_message(),
+                            Err((quota_exceeded, connection)) => {
+                                log::info!("{quota_exceeded}");
+                                Self::close_exhausted_connection(
+                                    &controller_sender,
+                                    quota_exceeded.connection_id,
+                                    &connection,
+                                );
+                                MixnetMessage::new_quota_exceeded(
+                                    connection.return_address,
+                                    connection.request_version,
+                                    quota_exceeded,
+                                )
+                                .into_input_message()
+                            }
+                        };
                         mixnet_client_sender.send_input_message(response_message).await;
                     } else {
                         log::error!("Exiting: channel closed!");
@@ -437,9 +501,17 @@ impl NRServiceProvider {
             return;
         }
 
+        if !self
+            .try_open_connection(conn_id, &return_address, &remote_version, false)
+            .await
+        {
+            return;
+        }
+
         let controller_sender_clone = self.controller_sender.clone();
         let mix_input_sender_clone = self.mix_input_sender.clone();
         let lane_queue_lengths_clone = self.mixnet_client.shared_lane_queue_lengths();
+        let quotas = Arc::clone(&self.quotas);
         let shutdown = self.shutdown.subscribe();
 
         // and start the proxy for this connection
@@ -454,11 +526,227 @@ impl NRServiceProvider {
                 lane_queue_lengths_clone,
                 shutdown,
             )
+            .await;
+            quotas
+                .lock()
+                .expect("quotas lock got poisoned")
+                .close_connection(conn_id);
+        });
+    }
+
+    /// Checks whether the client is allowed to open another connection (or UDP association)
+    /// and informs it if that's not the case.
+    async fn try_open_connection(
+        &self,
+        conn_id: ConnectionId,
+        return_address: &reply::MixnetAddress,
+        remote_version: &RequestVersion<Socks5Request>,
+        is_association: bool,
+    ) -> bool {
+        let tracked = TrackedConnection {
+            return_address: return_address.clone(),
+            request_version: remote_version.clone(),
+            is_association,
+        };
+        let opened = self
+            .quotas
+            .lock()
+            .expect("quotas lock got poisoned")
+            .open_connection(conn_id, tracked, Instant::now());
+
+        match opened {
+            Ok(_) => true,
+            Err(quota_exceeded) => {
+                log::info!("{quota_exceeded}");
+                let msg = MixnetMessage::new_quota_exceeded(
+                    return_address.clone(),
+                    remote_version.clone(),
+                    quota_exceeded,
+                );
+                self.mix_input_sender
+                    .send(msg)
+                    .await
+                    .expect("InputMessageReceiver has stopped receiving!");
+                false
+            }
+        }
+    }
+
+    /// Records the outbound data against the quota of the client.
+    /// Returns whether the data should still be forwarded to the remote.
+    async fn record_outbound_data(&self, conn_id: ConnectionId, amount: usize) -> bool {
+        let usage = self
+            .quotas
+            .lock()
+            .expect("quotas lock got poisoned")
+            .record_bytes(conn_id, amount, Instant::now());
+
+        match usage {
+            Ok(_) => true,
+            Err((quota_exceeded, connection)) => {
+                log::info!("{quota_exceeded}");
+                Self::close_exhausted_connection(&self.controller_sender, conn_id, &connection);
+                let msg = MixnetMessage::new_quota_exceeded(
+                    connection.return_address,
+                    connection.request_version,
+                    quota_exceeded,
+                );
+                self.mix_input_sender
+                    .send(msg)
+                    .await
+                    .expect("InputMessageReceiver has stopped receiving!");
+                false
+            }
+        }
+    }
+
+    fn close_exhausted_connection(
+        controller_sender: &ControllerSender,
+        conn_id: ConnectionId,
+        connection: &TrackedConnection,
+    ) {
+        // closing our side of the tcp connection makes the proxy finish. UDP associations have no
+        // notion of closing, so they are removed from the controller straight away instead
+        let command = if connection.is_association {
+            ControllerCommand::Remove {
+                connection_id: conn_id,
+            }
+        } else {
+            ControllerCommand::Send {
+                connection_id: conn_id,
+                data: Vec::new(),
+                is_closed: true,
+            }
+        };
+        controller_sender.unbounded_send(command).unwrap();
+    }
+
+    async fn handle_proxy_send(&mut self, req: SendRequest) {
+        if !self.record_outbound_data(req.conn_id, req.data.len()).await {
+            return;
+        }
+        self.controller_sender.unbounded_send(req.into()).unwrap()
+    }
+
+    async fn start_association(
+        remote_version: RequestVersion<Socks5Request>,
+        connection_id: ConnectionId,
+        return_address: reply::MixnetAddress,
+        controller_sender: ControllerSender,
+        mix_input_sender: MixProxySender<MixnetMessage>,
+        shutdown: TaskClient,
+    ) {
+        let association =
+            match socks5::udp::Association::new(connection_id, return_address.clone()).await {
+                Ok(association) => association,
+                Err(err) => {
+                    log::error!("failed to bind socket for UDP association: {err}");
+                    let msg = MixnetMessage::new_connection_error(
+                        return_address,
+                        remote_version,
+                        connection_id,
+                        format!("failed to create UDP association: {err}"),
+                    );
+                    mix_input_sender
+                        .send(msg)
+                        .await
+                        .expect("InputMessageReceiver has stopped receiving!");
+                    return;
+                }
+            };
+
+        let (datagram_sender, datagram_receiver) = mpsc::unbounded();
+        controller_sender
+            .unbounded_send(ControllerCommand::InsertAssociation {
+                connection_id,
+                datagram_sender,
+            })
+            .unwrap();
+
+        let old_count = ACTIVE_ASSOCIATIONS.fetch_add(1, Ordering::SeqCst);
+        log::info!(
+            "Starting UDP association {} (currently there are {} associations being handled)",
+            connection_id,
+            old_count + 1
+        );
+
+        association
+            .run(
+                remote_version,
+                datagram_receiver,
+                mix_input_sender,
+                shutdown,
+            )
+            .await;
+
+        controller_sender
+            .unbounded_send(ControllerCommand::Remove { connection_id })
+            .unwrap();
+
+        let old_count = ACTIVE_ASSOCIATIONS.fetch_sub(1, Ordering::SeqCst);
+        log::info!(
+            "UDP association {} is finished (currently there are {} associations being handled)",
+            connection_id,
+            old_count - 1
+        );
+    }
+
+    async fn handle_udp_associate(
+        &mut self,
+        remote_version: RequestVersion<Socks5Request>,
+        sender_tag: Option<AnonymousSenderTag>,
+        associate_req: UdpAssociateRequest,
+    ) {
+        let Some(return_address) =
+            reply::MixnetAddress::new(associate_req.return_address, sender_tag)
+        else {
+            log::warn!(
+                "attempted to start UDP association with no way of returning data back to the sender"
+            );
+            return;
+        };
+
+        let conn_id = associate_req.conn_id;
+        if !self
+            .try_open_connection(conn_id, &return_address, &remote_version, true)
             .await
+        {
+            return;
+        }
+
+        let controller_sender_clone = self.controller_sender.clone();
+        let mix_input_sender_clone = self.mix_input_sender.clone();
+        let quotas = Arc::clone(&self.quotas);
+        let shutdown = self.shutdown.subscribe();
+
+        tokio::spawn(async move {
+            Self::start_association(
+                remote_version,
+                conn_id,
+                return_address,
+                controller_sender_clone,
+                mix_input_sender_clone,
+                shutdown,
+            )
+            .await;
+            quotas
+                .lock()
+                .expect("quotas lock got poisoned")
+                .close_connection(conn_id);
         });
     }
 
-    fn handle_proxy_send(&mut self, req: SendRequest) {
+    async fn handle_send_datagram(&mut self, req: SendDatagramRequest) {
+        // datagrams are independent of each other, so each one has to pass the filter on its own.
+        // there's also nobody to report the failure to, so it's just silently dropped
+        if !self.open_proxy && !self.outbound_request_filter.check(&req.remote_addr).await {
+            log::info!("Datagram to {:?} failed filter check", req.remote_addr);
+            return;
+        }
+        if !self.record_outbound_data(req.conn_id, req.data.len()).await {
+            return;
+        }
+
         self.controller_sender.unbounded_send(req.into()).unwrap()
     }
 }
@@ -468,16 +756,22 @@ impl NRServiceProvider {
 // We could however consider moving it to a crate in common in the future.
 async fn create_mixnet_client<T>(
     config: &nym_client_core::config::Config<T>,
+    embedded_gateway_connection: Option<EmbeddedConnection>,
 ) -> Result<nym_sdk::mixnet::MixnetClient, NetworkRequesterError> {
     let debug_config = *config.get_debug_config();
 
     let storage_paths = nym_sdk::mixnet::StoragePaths::from(config);
 
-    let mixnet_client = nym_sdk::mixnet::MixnetClientBuilder::new()
+    let mut client_builder = nym_sdk::mixnet::MixnetClientBuilder::new()
         .network_details(NymNetworkDetails::new_from_env())
         .debug_config(debug_config)
         .enable_storage(storage_paths)
-        .registered_gateway(config.get_gateway_endpoint_config().clone())
+        .registered_gateway(config.get_gateway_endpoint_config().clone());
+    if let Some(connection) = embedded_gateway_connection {
+        client_builder = client_builder.embedded_gateway_connection(connection);
+    }
+
+    let mixnet_client = client_builder
         .build::<nym_sdk::mixnet::ReplyStorage>()
         .await
         .map_err(|err| NetworkRequesterError::FailedToSetupMixnetClient { source: err })?;
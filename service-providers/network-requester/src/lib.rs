@@ -0,0 +1,26 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nym_bin_common::build_information::{protocols, BinaryBuildInformation};
+
+mod allowed_hosts;
+pub mod config;
+pub mod core;
+pub mod error;
+mod quotas;
+mod reply;
+mod socks5;
+mod statistics;
+
+pub const BUILD_INFORMATION: BinaryBuildInformation =
+    BinaryBuildInformation::new(env!("CARGO_PKG_VERSION")).with_protocol_versions(&[
+        (
+            protocols::SPHINX_PACKET,
+            nym_sphinx::params::CURRENT_PACKET_VERSION_NUMBER,
+        ),
+        (
+            protocols::SERVICE_PROVIDER_INTERFACE,
+            nym_service_providers_common::interface::INTERFACE_VERSION,
+        ),
+        (protocols::SOCKS5, nym_socks5_requests::INTERFACE_VERSION),
+    ]);
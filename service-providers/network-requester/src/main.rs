@@ -3,18 +3,11 @@
 
 use clap::{crate_name, crate_version, Parser};
 use nym_bin_common::logging::{maybe_print_banner, setup_logging};
+use nym_config::paths::migrate_legacy_nym_directory;
 use nym_network_defaults::setup_env;
+use nym_network_requester::error::NetworkRequesterError;
 
-use error::NetworkRequesterError;
-
-mod allowed_hosts;
 mod cli;
-mod config;
-mod core;
-mod error;
-mod reply;
-mod socks5;
-mod statistics;
 
 #[tokio::main]
 async fn main() -> Result<(), NetworkRequesterError> {
@@ -23,6 +16,8 @@ async fn main() -> Result<(), NetworkRequesterError> {
 
     let args = cli::Cli::parse();
     setup_env(args.config_env_file.as_ref());
+    migrate_legacy_nym_directory("service-providers");
+    migrate_legacy_nym_directory("clients");
 
     cli::execute(args).await
 }
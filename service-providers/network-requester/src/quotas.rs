@@ -0,0 +1,314 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Usage quotas imposed on each of the clients of the network requester.
+//!
+//! Clients are identified by the return address of their requests, i.e. either their explicit
+//! nym address or the anonymous sender tag they're using for the reply surbs.
+//! Only the requests opening new connections (or UDP associations) count towards
+//! the request rate, while all the data sent and received on their behalf counts towards
+//! the daily bandwidth.
+
+use crate::config::Quotas;
+use crate::reply::MixnetAddress;
+use nym_service_providers_common::interface::RequestVersion;
+use nym_socks5_requests::{ConnectionId, QuotaExceeded, QuotaKind, Socks5Request};
+use nym_sphinx::addressing::clients::RecipientBytes;
+use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+const REQUESTS_WINDOW: Duration = Duration::from_secs(60);
+const BANDWIDTH_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+// how often the usage of clients that are no longer active gets removed
+const PRUNING_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ClientId {
+    Known(RecipientBytes),
+    Anonymous(AnonymousSenderTag),
+}
+
+impl From<&MixnetAddress> for ClientId {
+    fn from(address: &MixnetAddress) -> Self {
+        match address {
+            MixnetAddress::Known(recipient) => ClientId::Known(recipient.to_bytes()),
+            MixnetAddress::Anonymous(sender_tag) => ClientId::Anonymous(*sender_tag),
+        }
+    }
+}
+
+/// Counter that resets itself once the window it was started in has elapsed.
+#[derive(Debug)]
+struct WindowedCounter {
+    window: Duration,
+    started_at: Instant,
+    count: u64,
+}
+
+impl WindowedCounter {
+    fn new(window: Duration, now: Instant) -> Self {
+        WindowedCounter {
+            window,
+            started_at: now,
+            count: 0,
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started_at) >= self.window
+    }
+
+    fn current(&mut self, now: Instant) -> u64 {
+        if self.is_expired(now) {
+            self.started_at = now;
+            self.count = 0;
+        }
+        self.count
+    }
+
+    fn add(&mut self, amount: u64, now: Instant) -> u64 {
+        self.count = self.current(now).saturating_add(amount);
+        self.count
+    }
+}
+
+#[derive(Debug)]
+struct ClientUsage {
+    requests: WindowedCounter,
+    bytes: WindowedCounter,
+    connections: HashSet<ConnectionId>,
+}
+
+impl ClientUsage {
+    fn new(now: Instant) -> Self {
+        ClientUsage {
+            requests: WindowedCounter::new(REQUESTS_WINDOW, now),
+            bytes: WindowedCounter::new(BANDWIDTH_WINDOW, now),
+            connections: HashSet::new(),
+        }
+    }
+
+    fn is_stale(&self, now: Instant) -> bool {
+        self.connections.is_empty() && self.requests.is_expired(now) && self.bytes.is_expired(now)
+    }
+}
+
+/// Details of an open connection required for sending responses back to its client.
+#[derive(Debug, Clone)]
+pub(crate) struct TrackedConnection {
+    pub(crate) return_address: MixnetAddress,
+    pub(crate) request_version: RequestVersion<Socks5Request>,
+    pub(crate) is_association: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct UsageQuotas {
+    limits: Quotas,
+    clients: HashMap<ClientId, ClientUsage>,
+    connections: HashMap<ConnectionId, TrackedConnection>,
+    last_pruned: Instant,
+}
+
+impl UsageQuotas {
+    pub(crate) fn new(limits: Quotas) -> Self {
+        UsageQuotas {
+            limits,
+            clients: HashMap::new(),
+            connections: HashMap::new(),
+            last_pruned: Instant::now(),
+        }
+    }
+
+    fn prune(&mut self, now: Instant) {
+        if now.saturating_duration_since(self.last_pruned) < PRUNING_INTERVAL {
+            return;
+        }
+        self.clients.retain(|_, usage| !usage.is_stale(now));
+        self.last_pruned = now;
+    }
+
+    /// Attempts to open a new connection (or UDP association) on behalf of the client.
+    pub(crate) fn open_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        connection: TrackedConnection,
+        now: Instant,
+    ) -> Result<(), QuotaExceeded> {
+        self.prune(now);
+
+        let limits = self.limits;
+        let usage = self
+            .clients
+            .entry(ClientId::from(&connection.return_address))
+            .or_insert_with(|| ClientUsage::new(now));
+
+        if limits.max_requests_per_minute != 0
+            && usage.requests.add(1, now) > limits.max_requests_per_minute as u64
+        {
+            return Err(QuotaExceeded::new(
+                connection_id,
+                QuotaKind::RequestsPerMinute,
+                limits.max_requests_per_minute as u64,
+            ));
+        }
+        if limits.max_concurrent_connections != 0
+            && usage.connections.len() >= limits.max_concurrent_connections as usize
+        {
+            return Err(QuotaExceeded::new(
+                connection_id,
+                QuotaKind::ConcurrentConnections,
+                limits.max_concurrent_connections as u64,
+            ));
+        }
+        if limits.max_bytes_per_day != 0 && usage.bytes.current(now) >= limits.max_bytes_per_day {
+            return Err(QuotaExceeded::new(
+                connection_id,
+                QuotaKind::BytesPerDay,
+                limits.max_bytes_per_day,
+            ));
+        }
+
+        usage.connections.insert(connection_id);
+        self.connections.insert(connection_id, connection);
+        Ok(())
+    }
+
+    /// Records data sent or received on the connection. Once the client goes over its daily
+    /// bandwidth, the connection stops being tracked and its details are returned alongside
+    /// the error so that the client could be informed about it.
+    pub(crate) fn record_bytes(
+        &mut self,
+        connection_id: ConnectionId,
+        amount: usize,
+        now: Instant,
+    ) -> Result<(), (QuotaExceeded, TrackedConnection)> {
+        let Some(connection) = self.connections.get(&connection_id) else {
+            return Ok(());
+        };
+        let Some(usage) = self
+            .clients
+            .get_mut(&ClientId::from(&connection.return_address))
+        else {
+            return Ok(());
+        };
+
+        let used = usage.bytes.add(amount as u64, now);
+        if self.limits.max_bytes_per_day != 0 && used > self.limits.max_bytes_per_day {
+            usage.connections.remove(&connection_id);
+            let connection = self
+                .connections
+                .remove(&connection_id)
+                .expect("the connection has just been retrieved");
+            return Err((
+                QuotaExceeded::new(
+                    connection_id,
+                    QuotaKind::BytesPerDay,
+                    self.limits.max_bytes_per_day,
+                ),
+                connection,
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn close_connection(&mut self, connection_id: ConnectionId) {
+        let Some(connection) = self.connections.remove(&connection_id) else {
+            return;
+        };
+        if let Some(usage) = self
+            .clients
+            .get_mut(&ClientId::from(&connection.return_address))
+        {
+            usage.connections.remove(&connection_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::new_legacy_request_version;
+
+    fn connection(client: u8) -> TrackedConnection {
+        TrackedConnection {
+            return_address: MixnetAddress::Anonymous(AnonymousSenderTag::from_bytes([client; 16])),
+            request_version: new_legacy_request_version(),
+            is_association: false,
+        }
+    }
+
+    fn limits(
+        max_requests_per_minute: u32,
+        max_concurrent_connections: u32,
+        max_bytes_per_day: u64,
+    ) -> Quotas {
+        Quotas {
+            max_requests_per_minute,
+            max_concurrent_connections,
+            max_bytes_per_day,
+        }
+    }
+
+    #[test]
+    fn request_rate_is_limited_per_minute() {
+        let now = Instant::now();
+        let mut quotas = UsageQuotas::new(limits(2, 0, 0));
+
+        assert!(quotas.open_connection(1, connection(1), now).is_ok());
+        assert!(quotas.open_connection(2, connection(1), now).is_ok());
+        let err = quotas.open_connection(3, connection(1), now).unwrap_err();
+        assert_eq!(err, QuotaExceeded::new(3, QuotaKind::RequestsPerMinute, 2));
+
+        // other clients are not affected
+        assert!(quotas.open_connection(4, connection(2), now).is_ok());
+
+        // and the limit resets after a minute
+        let later = now + REQUESTS_WINDOW;
+        assert!(quotas.open_connection(5, connection(1), later).is_ok());
+    }
+
+    #[test]
+    fn concurrent_connections_are_limited() {
+        let now = Instant::now();
+        let mut quotas = UsageQuotas::new(limits(0, 1, 0));
+
+        assert!(quotas.open_connection(1, connection(1), now).is_ok());
+        let err = quotas.open_connection(2, connection(1), now).unwrap_err();
+        assert_eq!(err.quota, QuotaKind::ConcurrentConnections);
+
+        quotas.close_connection(1);
+        assert!(quotas.open_connection(3, connection(1), now).is_ok());
+    }
+
+    #[test]
+    fn bandwidth_is_limited_per_day() {
+        let now = Instant::now();
+        let mut quotas = UsageQuotas::new(limits(0, 0, 100));
+
+        assert!(quotas.open_connection(1, connection(1), now).is_ok());
+        assert!(quotas.record_bytes(1, 100, now).is_ok());
+        let (err, _) = quotas.record_bytes(1, 1, now).unwrap_err();
+        assert_eq!(err, QuotaExceeded::new(1, QuotaKind::BytesPerDay, 100));
+
+        // the client is only informed once, after which the connection is no longer tracked
+        assert!(quotas.record_bytes(1, 1, now).is_ok());
+
+        // no new connections can be opened until the bandwidth resets
+        assert!(quotas.open_connection(2, connection(1), now).is_err());
+        let later = now + BANDWIDTH_WINDOW;
+        assert!(quotas.open_connection(2, connection(1), later).is_ok());
+    }
+
+    #[test]
+    fn no_limits_are_enforced_by_default() {
+        let now = Instant::now();
+        let mut quotas = UsageQuotas::new(Quotas::default());
+
+        for id in 0..100 {
+            assert!(quotas.open_connection(id, connection(1), now).is_ok());
+            assert!(quotas.record_bytes(id, 1_000_000, now).is_ok());
+        }
+    }
+}
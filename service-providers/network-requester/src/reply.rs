@@ -6,8 +6,8 @@ use nym_service_providers_common::interface::{
     ControlRequest, ControlResponse, ProviderInterfaceVersion, RequestVersion,
 };
 use nym_socks5_requests::{
-    ConnectionId, NetworkData, Socks5ProviderRequest, Socks5ProviderResponse, Socks5Request,
-    Socks5RequestContent, Socks5Response, Socks5ResponseContent,
+    ConnectionId, NetworkData, QuotaExceeded, Socks5ProviderRequest, Socks5ProviderResponse,
+    Socks5Request, Socks5RequestContent, Socks5Response, Socks5ResponseContent,
 };
 use nym_sphinx::addressing::clients::Recipient;
 use nym_sphinx::anonymous_replies::requests::AnonymousSenderTag;
@@ -131,6 +131,22 @@ impl MixnetMessage {
         Self::new_provider_response(address, connection_id, msg)
     }
 
+    pub(crate) fn new_quota_exceeded(
+        address: MixnetAddress,
+        request_version: RequestVersion<Socks5Request>,
+        quota_exceeded: QuotaExceeded,
+    ) -> Self {
+        let connection_id = quota_exceeded.connection_id;
+        let res = Socks5Response::new(
+            request_version.provider_protocol,
+            Socks5ResponseContent::QuotaExceeded(quota_exceeded),
+        );
+        let msg =
+            Socks5ProviderResponse::new_provider_data(request_version.provider_interface, res);
+
+        Self::new_provider_response(address, connection_id, msg)
+    }
+
     // TODO: the naming is awful, but naming things is difficult...
     pub(crate) fn new_network_data_response_content(
         address: MixnetAddress,
@@ -143,6 +159,27 @@ impl MixnetMessage {
         Self::new_network_data_response(address, request_version, connection_id, response_content)
     }
 
+    pub(crate) fn new_datagram_response(
+        address: MixnetAddress,
+        request_version: RequestVersion<Socks5Request>,
+        connection_id: ConnectionId,
+        sequence: u64,
+        remote_addr: String,
+        data: Vec<u8>,
+    ) -> Self {
+        let res = Socks5Response::new_datagram(
+            request_version.provider_protocol,
+            connection_id,
+            sequence,
+            remote_addr,
+            data,
+        );
+        let msg =
+            Socks5ProviderResponse::new_provider_data(request_version.provider_interface, res);
+
+        Self::new_provider_response(address, connection_id, msg)
+    }
+
     pub(crate) fn data_size(&self) -> usize {
         self.data.len()
     }
@@ -0,0 +1,157 @@
+// Copyright 2023 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::reply;
+use crate::reply::MixnetMessage;
+use futures::StreamExt;
+use nym_service_providers_common::interface::RequestVersion;
+use nym_socks5_proxy_helpers::datagram::{DatagramMessage, DatagramReceiver};
+use nym_socks5_proxy_helpers::proxy_runner::MixProxySender;
+use nym_socks5_requests::{ConnectionId, Socks5Request};
+use nym_task::TaskClient;
+use std::collections::HashSet;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+// maximum size of an UDP payload
+const MAX_DATAGRAM_SIZE: usize = u16::MAX as usize;
+
+// there's no explicit teardown of an association, so if nothing has been sent nor received
+// for this long, we assume the client has gone away
+const ASSOCIATION_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// An outbound UDP association between the Socks5 service provider and arbitrary remotes,
+/// relaying datagrams on behalf of users and returning the received ones through the mixnet.
+#[derive(Debug)]
+pub(crate) struct Association {
+    id: ConnectionId,
+    socket: UdpSocket,
+    return_address: reply::MixnetAddress,
+
+    // only datagrams coming from remotes we have explicitly sent something to are relayed back
+    contacted_remotes: HashSet<SocketAddr>,
+    next_sequence: u64,
+}
+
+impl Association {
+    pub(crate) async fn new(
+        id: ConnectionId,
+        return_address: reply::MixnetAddress,
+    ) -> io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+
+        Ok(Association {
+            id,
+            socket,
+            return_address,
+            contacted_remotes: HashSet::new(),
+            next_sequence: 0,
+        })
+    }
+
+    async fn send_to_remote(&mut self, datagram: DatagramMessage) {
+        let remote = match tokio::net::lookup_host(&datagram.remote_addr).await {
+            Ok(mut addrs) => addrs.next(),
+            Err(err) => {
+                log::debug!("failed to resolve {}: {err}", datagram.remote_addr);
+                None
+            }
+        };
+        let Some(remote) = remote else {
+            log::debug!(
+                "dropping datagram to unresolvable {} on association {}",
+                datagram.remote_addr,
+                self.id
+            );
+            return;
+        };
+
+        if let Err(err) = self.socket.send_to(&datagram.data, remote).await {
+            log::debug!("failed to send datagram to {remote}: {err}");
+            return;
+        }
+        self.contacted_remotes.insert(remote);
+    }
+
+    async fn send_to_mixnet(
+        &mut self,
+        remote_version: &RequestVersion<Socks5Request>,
+        mix_sender: &MixProxySender<MixnetMessage>,
+        remote: SocketAddr,
+        data: Vec<u8>,
+    ) {
+        if !self.contacted_remotes.contains(&remote) {
+            log::trace!(
+                "dropping unsolicited datagram from {remote} on association {}",
+                self.id
+            );
+            return;
+        }
+
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+
+        let msg = MixnetMessage::new_datagram_response(
+            self.return_address.clone(),
+            remote_version.clone(),
+            self.id,
+            sequence,
+            remote.to_string(),
+            data,
+        );
+        if mix_sender.send(msg).await.is_err() {
+            log::error!("InputMessageReceiver has stopped receiving!")
+        }
+    }
+
+    pub(crate) async fn run(
+        mut self,
+        remote_version: RequestVersion<Socks5Request>,
+        mut datagram_receiver: DatagramReceiver,
+        mix_sender: MixProxySender<MixnetMessage>,
+        mut shutdown: TaskClient,
+    ) {
+        // the association going away should never bring down the whole service provider
+        shutdown.mark_as_success();
+
+        let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+
+        let idle_timeout = tokio::time::sleep(ASSOCIATION_IDLE_TIMEOUT);
+        tokio::pin!(idle_timeout);
+
+        while !shutdown.is_shutdown() {
+            tokio::select! {
+                biased;
+                _ = shutdown.recv() => {
+                    log::trace!("Association {}: Received shutdown", self.id);
+                }
+                _ = &mut idle_timeout => {
+                    log::debug!("Association {} has been idle for too long", self.id);
+                    break;
+                }
+                datagram = datagram_receiver.next() => {
+                    let Some(datagram) = datagram else {
+                        log::trace!("Association {}: Stopping since channel closed", self.id);
+                        break;
+                    };
+                    self.send_to_remote(datagram).await;
+                    idle_timeout.as_mut().reset(tokio::time::Instant::now() + ASSOCIATION_IDLE_TIMEOUT);
+                }
+                received = self.socket.recv_from(&mut buf) => {
+                    match received {
+                        Ok((n, remote)) => {
+                            let data = buf[..n].to_vec();
+                            self.send_to_mixnet(&remote_version, &mix_sender, remote, data).await;
+                            idle_timeout.as_mut().reset(tokio::time::Instant::now() + ASSOCIATION_IDLE_TIMEOUT);
+                        }
+                        Err(err) => {
+                            log::debug!("failed to receive datagram on association {}: {err}", self.id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -3,6 +3,7 @@
 
 use api::NetworkStatisticsAPI;
 use nym_bin_common::logging::setup_logging;
+use nym_config::paths::{default_nym_directory, migrate_legacy_nym_directory};
 use std::path::PathBuf;
 
 mod api;
@@ -11,6 +12,7 @@ mod storage;
 #[tokio::main]
 async fn main() {
     setup_logging();
+    migrate_legacy_nym_directory("service-providers");
 
     let base_dir = default_base_dir();
     let storage = storage::NetworkStatisticsStorage::init(&base_dir)
@@ -27,9 +29,7 @@ async fn main() {
 ///
 /// This is split out so we can easily inject our own base_dir for unit tests.
 fn default_base_dir() -> PathBuf {
-    dirs::home_dir()
-        .expect("no home directory known for this OS")
-        .join(".nym")
-        .join("service-providers")
+    default_nym_directory("service-providers")
+        .expect("no data directory known for this OS")
         .join("network-statistics")
 }
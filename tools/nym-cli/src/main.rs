@@ -3,6 +3,7 @@
 
 use clap::{CommandFactory, Parser, Subcommand};
 use log::{error, warn};
+use nym_bin_common::completions::ArgShell;
 use nym_bin_common::logging::setup_logging;
 use nym_cli_commands::context::{get_network_details, ClientArgs};
 use nym_validator_client::nyxd::AccountId;
@@ -70,6 +71,8 @@ pub(crate) enum Commands {
     VestingSchedule(nym_cli_commands::validator::vesting::VestingSchedule),
     /// Manage your mixnet infrastructure, delegate stake or query the directory
     Mixnet(nym_cli_commands::validator::mixnet::Mixnet),
+    /// Generate shell completions
+    Completions(ArgShell),
     /// Generates shell completion
     GenerateFig,
 }
@@ -113,6 +116,11 @@ async fn execute(cli: Cli) -> anyhow::Result<()> {
         Commands::Mixnet(mixnet) => {
             validator::mixnet::execute(args, mixnet, &network_details).await?
         }
+        Commands::Completions(shell) => {
+            let mut cmd = Cli::command();
+            let name = cmd.get_name().to_string();
+            shell.generate(&mut cmd, &name);
+        }
         Commands::GenerateFig => {
             let mut cmd = Cli::command();
             completion::print_fig(&mut cmd);